@@ -1,16 +1,18 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
-use massa_models::address::ExecutionAddressCycleInfo;
+use massa_hash::Hash;
+use massa_models::address::{ExecutionAddressCycleInfo, StakingRewards};
 use massa_models::endorsement::EndorsementId;
 use massa_models::operation::OperationId;
 use massa_models::slot::{IndexedSlot, Slot};
 use massa_models::{address::Address, amount::Amount, block_id::BlockId};
+use massa_time::MassaTime;
 use serde::{Deserialize, Serialize};
 
 use crate::slot::SlotAmount;
 
 /// All you ever dream to know about an address
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct AddressInfo {
     /// the address
     pub address: Address,
@@ -48,6 +50,36 @@ pub struct AddressInfo {
 
     /// cycle information
     pub cycle_infos: Vec<ExecutionAddressCycleInfo>,
+
+    /// hash of the candidate bytecode, `None` if the address has no bytecode
+    #[serde(default)]
+    pub bytecode_hash: Option<Hash>,
+    /// verification of the candidate bytecode registered on the node, `None` if it is not verified
+    #[serde(default)]
+    pub verified_contract: Option<VerifiedContract>,
+}
+
+/// Source of a smart contract whose compilation gives a known bytecode, registered by the node operator
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct VerifiedContract {
+    /// hash of the bytecode obtained by compiling the source
+    pub bytecode_hash: Hash,
+    /// name of the contract
+    pub name: String,
+    /// location of the source code, ex: a repository URL at a given commit
+    pub source_url: String,
+    /// version of the compiler used to obtain the bytecode
+    pub compiler_version: String,
+}
+
+impl std::fmt::Display for VerifiedContract {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} from {}, compiler {}",
+            self.bytecode_hash, self.name, self.source_url, self.compiler_version
+        )
+    }
 }
 
 impl std::fmt::Display for AddressInfo {
@@ -75,6 +107,16 @@ impl std::fmt::Display for AddressInfo {
                 )?;
             }
         }
+        if let Some(bytecode_hash) = self.bytecode_hash {
+            match &self.verified_contract {
+                Some(contract) => writeln!(
+                    f,
+                    "\tBytecode: {} (verified: {} from {}, compiler {})",
+                    bytecode_hash, contract.name, contract.source_url, contract.compiler_version
+                )?,
+                None => writeln!(f, "\tBytecode: {} (not verified)", bytecode_hash)?,
+            }
+        }
         writeln!(f, "\tCycle infos:")?;
         for cycle_info in &self.cycle_infos {
             writeln!(
@@ -155,3 +197,142 @@ impl std::fmt::Display for CompactAddressInfo {
         Ok(())
     }
 }
+
+/// Schedule of the upcoming deferred credits (roll sale reimbursements) of an address
+#[derive(Debug, Deserialize, Serialize)]
+pub struct AddressDeferredCredits {
+    /// the address
+    pub address: Address,
+    /// sum of the deferred credits in the final state
+    pub final_total: Amount,
+    /// sum of the deferred credits in the candidate state
+    pub candidate_total: Amount,
+    /// upcoming deferred credits, sorted by slot
+    pub schedule: Vec<DeferredCreditEntry>,
+    /// deferred credits already credited and still kept by the node, sorted by slot
+    pub executed: Vec<ExecutedCreditEntry>,
+}
+
+/// Coins credited to an address at a past slot
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ExecutedCreditEntry {
+    /// slot at which the coins were credited
+    pub slot: Slot,
+    /// cycle of the slot
+    pub cycle: u64,
+    /// timestamp of the slot
+    pub timestamp: MassaTime,
+    /// amount credited
+    pub amount: Amount,
+}
+
+/// Coins that become available to an address at a given slot
+#[derive(Debug, Deserialize, Serialize)]
+pub struct DeferredCreditEntry {
+    /// slot at which the coins are credited
+    pub slot: Slot,
+    /// cycle of the slot
+    pub cycle: u64,
+    /// timestamp of the slot
+    pub timestamp: MassaTime,
+    /// amount credited according to the final state
+    pub final_amount: Option<Amount>,
+    /// amount credited according to the candidate state
+    pub candidate_amount: Option<Amount>,
+}
+
+/// Staking rewards credited to an address by final executions
+#[derive(Debug, Deserialize, Serialize)]
+pub struct AddressStakingRewards {
+    /// the address
+    pub address: Address,
+    /// sum of the rewards over all the known cycles
+    pub total: Amount,
+    /// rewards credited during each known cycle, sorted by cycle
+    pub cycles: Vec<CycleStakingRewards>,
+}
+
+impl std::fmt::Display for AddressStakingRewards {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Address {}: total rewards={}", self.address, self.total)?;
+        for cycle_rewards in &self.cycles {
+            writeln!(f, "\t{}", cycle_rewards)?;
+        }
+        Ok(())
+    }
+}
+
+/// Storage rent of an address, projected from the latest candidate slot
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct AddressStorageRent {
+    /// the address
+    pub address: Address,
+    /// bytes of bytecode and datastore charged, the entries reserved to the ledger being free
+    pub storage_bytes: u64,
+    /// rent charged at the start of each cycle
+    pub rent_per_cycle: Amount,
+    /// number of cycles of rent the candidate balance pays for, `None` if no rent is charged
+    pub paid_cycles: Option<u64>,
+    /// first cycle whose rent the address could not pay, if it has not paid since
+    pub rent_due_since: Option<u64>,
+    /// cycle at whose start the storage is reclaimed if the rent is still unpaid
+    pub reclamation_cycle: Option<u64>,
+    /// timestamp of the start of the reclamation cycle
+    pub reclamation_timestamp: Option<MassaTime>,
+}
+
+impl std::fmt::Display for AddressStorageRent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Address {}: storage={} bytes, rent={} coins per cycle",
+            self.address, self.storage_bytes, self.rent_per_cycle
+        )?;
+        if let Some(paid_cycles) = self.paid_cycles {
+            writeln!(f, "	Candidate balance pays for {} cycles", paid_cycles)?;
+        }
+        if let (Some(due_since), Some(reclamation_cycle)) =
+            (self.rent_due_since, self.reclamation_cycle)
+        {
+            write!(
+                f,
+                "	Rent unpaid since cycle {}, storage reclaimed at cycle {}",
+                due_since, reclamation_cycle
+            )?;
+            if let Some(timestamp) = self.reclamation_timestamp {
+                write!(f, " ({})", timestamp.to_utc_string())?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Staking rewards credited to an address during a cycle
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CycleStakingRewards {
+    /// cycle number
+    pub cycle: u64,
+    /// timestamp of the first slot of the cycle
+    pub start_timestamp: MassaTime,
+    /// timestamp of the last slot of the cycle
+    pub end_timestamp: MassaTime,
+    /// rewards credited during the cycle
+    pub rewards: StakingRewards,
+}
+
+impl std::fmt::Display for CycleStakingRewards {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Cycle {}: blocks={} ({} coins), endorsements={} ({} coins), endorsed blocks={} coins, total={}",
+            self.cycle,
+            self.rewards.block_count,
+            self.rewards.block_rewards,
+            self.rewards.endorsement_count,
+            self.rewards.endorsement_rewards,
+            self.rewards.endorsed_rewards,
+            self.rewards.total()
+        )
+    }
+}
@@ -0,0 +1,92 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use massa_async_pool::AsyncMessage;
+use massa_models::{address::Address, amount::Amount, operation::OperationId, slot::Slot};
+use serde::{Deserialize, Serialize};
+
+/// Message of the final asynchronous pool with its place in the execution order
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AsyncMessageInfo {
+    /// position of the message in the pool, 0 being executed first
+    pub rank: usize,
+    /// fee per unit of gas offered by the sender, priority fee included, rounded down to the raw amount:
+    /// messages are executed by decreasing effective priority
+    pub effective_priority: Amount,
+    /// the message
+    pub message: AsyncMessage,
+}
+
+impl AsyncMessageInfo {
+    /// Info of `message`, at `rank` in the pool
+    pub fn new(rank: usize, message: AsyncMessage) -> Self {
+        AsyncMessageInfo {
+            rank,
+            effective_priority: Amount::from_raw(message.effective_priority().to_integer()),
+            message,
+        }
+    }
+}
+
+impl std::fmt::Display for AsyncMessageInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "#{} from {} to {}::{}",
+            self.rank, self.message.sender, self.message.destination, self.message.handler
+        )?;
+        writeln!(
+            f,
+            "\tEffective priority: {} per gas (fee {}, priority fee {}, max gas {})",
+            self.effective_priority,
+            self.message.fee,
+            self.message.priority_fee,
+            self.message.max_gas
+        )?;
+        writeln!(
+            f,
+            "\tValidity: from {} to {}",
+            self.message.validity_start, self.message.validity_end
+        )?;
+        Ok(())
+    }
+}
+
+/// Pending scheduled transfer, held by the asynchronous pool until its activation slot
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ScheduledTransferInfo {
+    /// id of the operation that scheduled the transfer, to cancel it
+    pub operation_id: OperationId,
+    /// sender of the transfer
+    pub sender: Address,
+    /// recipient of the transfer
+    pub recipient: Address,
+    /// amount held until the activation slot
+    pub amount: Amount,
+    /// slot from which the amount is credited to the recipient
+    pub activation_slot: Slot,
+}
+
+impl ScheduledTransferInfo {
+    /// Info of the scheduled transfer held by `message`, `None` if it does not hold one
+    pub fn from_message(message: &AsyncMessage) -> Option<Self> {
+        Some(ScheduledTransferInfo {
+            operation_id: message.scheduled_transfer_operation_id()?,
+            sender: message.sender,
+            recipient: message.destination,
+            amount: message.coins,
+            activation_slot: message.validity_start,
+        })
+    }
+}
+
+impl std::fmt::Display for ScheduledTransferInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Transfer of {} from {} to {} at slot {}",
+            self.amount, self.sender, self.recipient, self.activation_slot
+        )?;
+        writeln!(f, "\tScheduled by operation {}", self.operation_id)?;
+        Ok(())
+    }
+}
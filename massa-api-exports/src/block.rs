@@ -1,6 +1,10 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
-use massa_models::{address::Address, block::Block, block_id::BlockId, slot::Slot};
+use massa_execution_exports::FinalBlockOperations;
+use massa_models::{
+    address::Address, block::Block, block_header::SecuredHeader, block_id::BlockId, slot::Slot,
+};
+use massa_time::MassaTime;
 
 use serde::{Deserialize, Serialize};
 
@@ -13,6 +17,9 @@ pub struct BlockInfo {
     pub id: BlockId,
     /// optional block info content
     pub content: Option<BlockInfoContent>,
+    /// operations of the final block, if the node indexes them: available after the block left the node storage
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub final_operations: Option<FinalBlockOperations>,
 }
 
 /// Block content
@@ -26,8 +33,38 @@ pub struct BlockInfoContent {
     pub is_candidate: bool,
     /// true if discarded
     pub is_discarded: bool,
-    /// block
-    pub block: Block,
+    /// block, unless only the headers or the ids were requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block: Option<Block>,
+    /// header of the block, if only the headers were requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub header: Option<SecuredHeader>,
+}
+
+/// Parts of the blocks returned by the block and graph queries
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockFieldMask {
+    /// everything available
+    #[default]
+    Full,
+    /// the headers, without the lists of operations
+    HeadersOnly,
+    /// the ids and the statuses only
+    IdsOnly,
+}
+
+impl std::str::FromStr for BlockFieldMask {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "full" => Ok(BlockFieldMask::Full),
+            "headers_only" => Ok(BlockFieldMask::HeadersOnly),
+            "ids_only" => Ok(BlockFieldMask::IdsOnly),
+            _ => Err(format!("unknown block field mask {}", s)),
+        }
+    }
 }
 
 impl std::fmt::Display for BlockInfo {
@@ -42,16 +79,30 @@ impl std::fmt::Display for BlockInfo {
                 display_if_true(content.is_in_blockclique, " (blockclique)"),
                 display_if_true(content.is_discarded, " (discarded)"),
             )?;
-            writeln!(f, "Block: {}", content.block)?;
-        } else {
+            if let Some(block) = &content.block {
+                writeln!(f, "Block: {}", block)?;
+            } else if let Some(header) = &content.header {
+                writeln!(f, "Header: {}", header)?;
+            }
+        } else if self.final_operations.is_none() {
             writeln!(f, "Block {} not found", self.id)?;
         }
+        if let Some(final_operations) = &self.final_operations {
+            writeln!(
+                f,
+                "Final block {} at slot {}, operations:",
+                self.id, final_operations.slot
+            )?;
+            for (index, operation_id) in final_operations.operations.iter().enumerate() {
+                writeln!(f, "\t{}: {}", index, operation_id)?;
+            }
+        }
         Ok(())
     }
 }
 
 /// A block resume (without the block itself)
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct BlockSummary {
     /// id
     pub id: BlockId,
@@ -88,3 +139,56 @@ impl std::fmt::Display for BlockSummary {
         Ok(())
     }
 }
+
+/// Request of a page of the block graph
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct GraphPageRequest {
+    /// optional start time (included)
+    pub start: Option<MassaTime>,
+    /// optional end time (excluded)
+    pub end: Option<MassaTime>,
+    /// returned parts of the blocks: the headers are only returned with `full` and `headers_only`
+    #[serde(default)]
+    pub fields: BlockFieldMask,
+    /// maximal number of blocks in the page, capped by the node
+    pub limit: Option<usize>,
+    /// token of the previous page to continue from, `next_page_token` of its response
+    pub page_token: Option<String>,
+}
+
+/// A block of a page of the graph
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct GraphBlock {
+    /// id
+    pub id: BlockId,
+    /// summary of the block, unless only the ids were requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<BlockSummary>,
+    /// header of the block, if requested and the block is not stale
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub header: Option<SecuredHeader>,
+}
+
+/// A page of the block graph, sorted by slot then id
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct GraphPage {
+    /// blocks of the page
+    pub blocks: Vec<GraphBlock>,
+    /// token to pass in the next request to get the following page, none on the last page
+    pub next_page_token: Option<String>,
+}
+
+impl std::fmt::Display for GraphPage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for block in &self.blocks {
+            match &block.summary {
+                Some(summary) => write!(f, "{}", summary)?,
+                None => writeln!(f, "Block's ID: {}", block.id)?,
+            }
+        }
+        if let Some(token) = &self.next_page_token {
+            writeln!(f, "Next page token: {}", token)?;
+        }
+        Ok(())
+    }
+}
@@ -46,6 +46,18 @@ pub struct APIConfig {
     pub enable_http: bool,
     /// whether to enable WS.
     pub enable_ws: bool,
+    /// max number of cached responses of expensive read requests, 0 disables the cache
+    pub cache_max_entries: usize,
+    /// max number of rows returned by a query over the node-local indexes
+    pub max_query_results: usize,
+    /// delay during which the private operations are only included in the blocks produced by this node
+    pub private_relay_delay: MassaTime,
+    /// what to do with the private operations not included in a block after `private_relay_delay`
+    pub private_relay_expiry: PrivateRelayExpiry,
+    /// max number of bytes of a datastore value returned by a ranged read
+    pub max_datastore_chunk_size: u64,
+    /// file keeping the contracts verified by the node operator
+    pub verified_contracts_path: PathBuf,
     /// max datastore value length
     pub max_datastore_value_length: u64,
     /// max op datastore entry
@@ -66,4 +78,16 @@ pub struct APIConfig {
     pub t0: MassaTime,
     /// periods per cycle
     pub periods_per_cycle: u64,
+    /// endorsement count
+    pub endorsement_count: u32,
+}
+
+/// What to do with a private operation not included in a block before the end of its relay delay
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PrivateRelayExpiry {
+    /// propagate the operation to the network
+    Gossip,
+    /// remove the operation from the pool
+    Drop,
 }
@@ -1,6 +1,6 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
-use massa_models::address::Address;
+use massa_models::{address::Address, datastore::DatastoreChunk};
 use serde::{Deserialize, Serialize};
 
 /// Datastore entry query input structure
@@ -28,3 +28,25 @@ impl std::fmt::Display for DatastoreEntryOutput {
         Ok(())
     }
 }
+
+/// Ranged read of a datastore entry value
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct DatastoreEntryChunkInput {
+    /// associated address of the entry
+    pub address: Address,
+    /// datastore key
+    pub key: Vec<u8>,
+    /// position in the value of the first byte to read
+    pub offset: u64,
+    /// max number of bytes to read, capped by the node
+    pub length: u64,
+}
+
+/// Chunks of the final and candidate values of a datastore entry
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct DatastoreEntryChunkOutput {
+    /// chunk of the final value, `None` if the entry does not exist
+    pub final_chunk: Option<DatastoreChunk>,
+    /// chunk of the candidate value, `None` if the entry does not exist
+    pub candidate_chunk: Option<DatastoreChunk>,
+}
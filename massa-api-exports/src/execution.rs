@@ -1,7 +1,13 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use massa_execution_exports::{ReadOnlyExecutionLimits, ReadOnlyResourceUsage};
 use massa_final_state::StateChanges;
-use massa_models::{address::Address, output_event::SCOutputEvent, slot::Slot};
+use massa_models::{
+    address::Address,
+    amount::Amount,
+    output_event::{ExecutionErrorInfo, SCOutputEvent},
+    slot::Slot,
+};
 use serde::{Deserialize, Serialize};
 use std::{collections::VecDeque, fmt::Display};
 
@@ -27,6 +33,12 @@ pub struct ExecuteReadOnlyResponse {
     pub gas_cost: u64,
     /// state changes caused by the execution step
     pub state_changes: StateChanges,
+    /// Structured description of the failure, if the execution failed.
+    #[serde(default)]
+    pub error: Option<ExecutionErrorInfo>,
+    /// Resources used by the execution, if it succeeded.
+    #[serde(default)]
+    pub usage: Option<ReadOnlyResourceUsage>,
 }
 
 impl Display for ExecuteReadOnlyResponse {
@@ -41,7 +53,112 @@ impl Display for ExecuteReadOnlyResponse {
                 ReadOnlyResult::Ok(ret) => format!("success, returned value: {:?}", ret),
             }
         )?;
+        if let Some(info) = &self.error {
+            writeln!(f, "Error code: {}", info.code)?;
+            if let Some(reason) = &info.revert_reason {
+                writeln!(f, "Revert reason: {}", String::from_utf8_lossy(reason))?;
+            }
+        }
         writeln!(f, "Gas cost: {}", self.gas_cost)?;
+        if let Some(usage) = &self.usage {
+            writeln!(
+                f,
+                "Resources used: {} us of CPU time, {} bytes of memory",
+                usage.cpu_time_us, usage.memory_bytes
+            )?;
+        }
+        if !self.output_events.is_empty() {
+            writeln!(f, "Generated events:",)?;
+            for event in self.output_events.iter() {
+                writeln!(f, "{}", event)?; // id already displayed in event
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Balance of an address changed by a dry run
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DryRunBalanceChange {
+    /// address
+    pub address: Address,
+    /// candidate balance before the operation
+    pub before: Amount,
+    /// balance after the operation
+    pub after: Amount,
+}
+
+/// Roll count of an address changed by a dry run
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DryRunRollChange {
+    /// address
+    pub address: Address,
+    /// candidate roll count before the operation
+    pub before: u64,
+    /// roll count after the operation
+    pub after: u64,
+}
+
+/// The response to a dry run of an operation against the candidate state.
+/// Nothing is kept by the node: the operation is neither pooled nor propagated.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DryRunOperationResponse {
+    /// The slot at which the operation was executed, in the thread of its creator.
+    /// None if the operation could not be included in a block.
+    pub executed_at: Option<Slot>,
+    /// Whether the operation could be included in a block and executed successfully.
+    pub executed_successfully: bool,
+    /// Why the operation could not be included in a block or failed, if it did.
+    pub error: Option<String>,
+    /// Structured description of the failure, if the operation failed.
+    #[serde(default)]
+    pub error_info: Option<ExecutionErrorInfo>,
+    /// The gas cost of the operation
+    pub gas_cost: u64,
+    /// The balances changed by the operation, fees included.
+    pub balance_changes: Vec<DryRunBalanceChange>,
+    /// The roll counts changed by the operation.
+    pub roll_changes: Vec<DryRunRollChange>,
+    /// The output events generated by the operation.
+    pub output_events: VecDeque<SCOutputEvent>,
+}
+
+impl Display for DryRunOperationResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(slot) = self.executed_at {
+            writeln!(f, "Executed at slot: {}", slot)?;
+        }
+        match &self.error {
+            Some(error) => writeln!(f, "Result: the operation would fail: {}", error)?,
+            None => writeln!(f, "Result: success")?,
+        }
+        if let Some(info) = &self.error_info {
+            writeln!(f, "Error code: {}", info.code)?;
+            if let Some(reason) = &info.revert_reason {
+                writeln!(f, "Revert reason: {}", String::from_utf8_lossy(reason))?;
+            }
+        }
+        writeln!(f, "Gas cost: {}", self.gas_cost)?;
+        if !self.balance_changes.is_empty() {
+            writeln!(f, "Balance changes:")?;
+            for change in self.balance_changes.iter() {
+                writeln!(
+                    f,
+                    "\t{}: {} -> {}",
+                    change.address, change.before, change.after
+                )?;
+            }
+        }
+        if !self.roll_changes.is_empty() {
+            writeln!(f, "Roll changes:")?;
+            for change in self.roll_changes.iter() {
+                writeln!(
+                    f,
+                    "\t{}: {} -> {}",
+                    change.address, change.before, change.after
+                )?;
+            }
+        }
         if !self.output_events.is_empty() {
             writeln!(f, "Generated events:",)?;
             for event in self.output_events.iter() {
@@ -66,6 +183,45 @@ pub struct ReadOnlyBytecodeExecution {
     /// whether to start execution from final or active state. Default false
     #[serde(default)]
     pub is_final: bool,
+    /// interpreter limits, lower than the node's read-only limits. Default none
+    #[serde(default)]
+    pub limits: ReadOnlyLimits,
+    /// name of an execution fork to execute in instead of the final or active state, overrides `is_final`. Default none
+    #[serde(default)]
+    pub fork: Option<String>,
+}
+
+/// interpreter limits requested for a read-only execution.
+/// The node's own limit applies to every field left empty or set above it.
+#[derive(Debug, Deserialize, Clone, Copy, Default, Serialize)]
+pub struct ReadOnlyLimits {
+    /// maximal depth of the call stack, optional
+    pub max_call_depth: Option<u16>,
+    /// maximal number of emitted events, optional
+    pub max_event_count: Option<u64>,
+    /// maximal size of a written datastore value, optional
+    pub max_datastore_value_size: Option<u64>,
+    /// maximal CPU time spent by the execution in microseconds, optional
+    pub max_cpu_time_us: Option<u64>,
+    /// maximal bytes of datastore entries, bytecode, events and return value produced, optional
+    pub max_memory_bytes: Option<u64>,
+}
+
+impl From<ReadOnlyLimits> for ReadOnlyExecutionLimits {
+    fn from(limits: ReadOnlyLimits) -> Self {
+        let unlimited = ReadOnlyExecutionLimits::default();
+        ReadOnlyExecutionLimits {
+            max_call_depth: limits.max_call_depth.unwrap_or(unlimited.max_call_depth),
+            max_event_count: limits.max_event_count.unwrap_or(unlimited.max_event_count),
+            max_datastore_value_size: limits
+                .max_datastore_value_size
+                .unwrap_or(unlimited.max_datastore_value_size),
+            max_cpu_time_us: limits.max_cpu_time_us.unwrap_or(unlimited.max_cpu_time_us),
+            max_memory_bytes: limits
+                .max_memory_bytes
+                .unwrap_or(unlimited.max_memory_bytes),
+        }
+    }
 }
 
 /// read SC call request
@@ -84,4 +240,10 @@ pub struct ReadOnlyCall {
     /// whether to start execution from final or active state. Default false
     #[serde(default)]
     pub is_final: bool,
+    /// interpreter limits, lower than the node's read-only limits. Default none
+    #[serde(default)]
+    pub limits: ReadOnlyLimits,
+    /// name of an execution fork to execute in instead of the final or active state, overrides `is_final`. Default none
+    #[serde(default)]
+    pub fork: Option<String>,
 }
@@ -13,6 +13,8 @@ use serde::{Deserialize, Serialize};
 
 /// address related structures
 pub mod address;
+/// asynchronous pool
+pub mod async_pool;
 /// block-related structures
 pub mod block;
 /// node configuration
@@ -33,8 +35,14 @@ pub mod node;
 pub mod operation;
 /// page
 pub mod page;
+/// CPU and heap profiling
+pub mod profiling;
+/// queries over the node-local indexes
+pub mod query;
 /// rolls
 pub mod rolls;
+/// selection draws and forecasts
+pub mod selection;
 /// slots
 pub mod slot;
 
@@ -76,6 +84,13 @@ pub struct TimeInterval {
     pub end: Option<MassaTime>,
 }
 
+/// Filter of the stakers queries
+#[derive(Debug, Default, Deserialize, Clone, Copy, Serialize)]
+pub struct StakersFilter {
+    /// only return the stakers with at least this number of active rolls
+    pub min_rolls: Option<u64>,
+}
+
 /// SCRUD operations
 #[derive(strum::Display)]
 #[strum(serialize_all = "snake_case")]
@@ -1,13 +1,58 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 use massa_models::node::NodeId;
-use massa_models::stats::{ConsensusStats, ExecutionStats, NetworkStats};
+use massa_models::stats::{
+    ApiCacheStats, BlockProductionStats, BytecodeRejectStats, ConsensusStats, DiskStatus,
+    ExecutionStats, MemoryStatus, NetworkStats,
+};
 use massa_models::{config::CompactConfig, slot::Slot, version::Version};
+use massa_network_exports::{NodeMetadata, PeerDistribution, ProbeStats};
+use massa_signature::SignatureVerificationStats;
 use massa_time::MassaTime;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::net::IpAddr;
 
+/// Provenance of the node binary, embedded at compile time.
+/// Two builds with the same inputs have the same `build_hash`, which is not a hash of the binary itself.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct BuildInfo {
+    /// git commit of the sources
+    pub git_commit: String,
+    /// whether the sources had uncommitted changes
+    pub git_dirty: bool,
+    /// version of the compiler
+    pub rustc_version: String,
+    /// target triple
+    pub target: String,
+    /// cargo profile (debug or release)
+    pub profile: String,
+    /// enabled features of the node
+    pub features: Vec<String>,
+    /// hash of the inputs above and of `Cargo.lock`, not of the produced binary
+    pub build_hash: String,
+}
+
+impl std::fmt::Display for BuildInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Build info:")?;
+        writeln!(
+            f,
+            "	Commit: {}{}",
+            self.git_commit,
+            if self.git_dirty { " (dirty)" } else { "" }
+        )?;
+        writeln!(f, "	Compiler: {}", self.rustc_version)?;
+        writeln!(f, "	Target: {} ({})", self.target, self.profile)?;
+        if self.features.is_empty() {
+            writeln!(f, "	Features: none")?;
+        } else {
+            writeln!(f, "	Features: {}", self.features.join(", "))?;
+        }
+        writeln!(f, "	Build hash: {}", self.build_hash)
+    }
+}
+
 /// node status
 #[derive(Debug, Deserialize, Serialize)]
 pub struct NodeStatus {
@@ -17,8 +62,14 @@ pub struct NodeStatus {
     pub node_ip: Option<IpAddr>,
     /// node version
     pub version: Version,
+    /// provenance of the node binary
+    #[serde(default)]
+    pub build_info: BuildInfo,
     /// now
     pub current_time: MassaTime,
+    /// median offset of the clocks of the connected nodes relative to ours in milliseconds, if known
+    #[serde(default)]
+    pub network_clock_offset: Option<i64>,
     /// current cycle
     pub current_cycle: u64,
     /// current cycle starting timestamp
@@ -27,18 +78,48 @@ pub struct NodeStatus {
     pub next_cycle_time: MassaTime,
     /// connected nodes (node id, ip address, true if the connection is outgoing, false if incoming)
     pub connected_nodes: BTreeMap<NodeId, (IpAddr, bool)>,
+    /// verified metadata published by the connected nodes
+    #[serde(default)]
+    pub connected_nodes_metadata: BTreeMap<NodeId, NodeMetadata>,
+    /// health probes of the connected nodes that support them
+    #[serde(default)]
+    pub connected_nodes_probes: BTreeMap<NodeId, ProbeStats>,
+    /// latest final slots gossiped by the connected nodes that support it
+    #[serde(default)]
+    pub connected_nodes_final_slots: BTreeMap<NodeId, Slot>,
+    /// distribution of the connections across IP ranges and regions
+    #[serde(default)]
+    pub peer_distribution: PeerDistribution,
     /// latest slot, none if now is before genesis timestamp
     pub last_slot: Option<Slot>,
     /// next slot
     pub next_slot: Slot,
+    /// number of slots by which our final slot lags behind the median of the final slots of the connected nodes, if known
+    #[serde(default)]
+    pub sync_lag: Option<u64>,
     /// consensus stats
     pub consensus_stats: ConsensusStats,
     /// pool stats (operation count and endorsement count)
     pub pool_stats: (usize, usize),
+    /// operations refused by the bytecode analyzer of the pool
+    #[serde(default)]
+    pub pool_bytecode_rejects: BytecodeRejectStats,
     /// network stats
     pub network_stats: NetworkStats,
     /// execution stats
     pub execution_stats: ExecutionStats,
+    /// signature verification stats
+    pub signature_stats: SignatureVerificationStats,
+    /// disk usage and read-only mode
+    pub disk_status: DiskStatus,
+    /// resident memory and live capacities of the pool and protocol caches
+    #[serde(default)]
+    pub memory_status: MemoryStatus,
+    /// time spent in each stage of the production of the latest blocks produced by the node
+    #[serde(default)]
+    pub block_production: BlockProductionStats,
+    /// hit rate of the API response cache
+    pub api_cache_stats: ApiCacheStats,
     /// compact configuration
     pub config: CompactConfig,
 }
@@ -54,15 +135,22 @@ impl std::fmt::Display for NodeStatus {
         writeln!(f)?;
 
         writeln!(f, "Version: {}", self.version)?;
+        write!(f, "{}", self.build_info)?;
         writeln!(f, "Config:\n{}", self.config)?;
         writeln!(f)?;
 
         writeln!(f, "Current time: {}", self.current_time.to_utc_string())?;
+        if let Some(offset) = self.network_clock_offset {
+            writeln!(f, "Network clock offset: {} ms", offset)?;
+        }
         writeln!(f, "Current cycle: {}", self.current_cycle)?;
         if self.last_slot.is_some() {
             writeln!(f, "Last slot: {}", self.last_slot.unwrap())?;
         }
         writeln!(f, "Next slot: {}", self.next_slot)?;
+        if let Some(lag) = self.sync_lag {
+            writeln!(f, "Sync lag: {} slot(s) behind the network", lag)?;
+        }
         writeln!(f)?;
 
         writeln!(f, "{}", self.consensus_stats)?;
@@ -72,10 +160,25 @@ impl std::fmt::Display for NodeStatus {
         writeln!(f, "\tEndorsements count: {}", self.pool_stats.1)?;
         writeln!(f)?;
 
+        writeln!(f, "{}", self.pool_bytecode_rejects)?;
+
         writeln!(f, "{}", self.network_stats)?;
 
         writeln!(f, "{}", self.execution_stats)?;
 
+        writeln!(f, "{}", self.signature_stats)?;
+
+        writeln!(f, "{}", self.disk_status)?;
+
+        writeln!(f, "{}", self.memory_status)?;
+
+        writeln!(f, "{}", self.block_production)?;
+
+        writeln!(f, "{}", self.api_cache_stats)?;
+
+        write!(f, "{}", self.peer_distribution)?;
+        writeln!(f)?;
+
         writeln!(f, "Connected nodes:")?;
         for (node_id, (ip_addr, is_outgoing)) in &self.connected_nodes {
             writeln!(
@@ -84,7 +187,23 @@ impl std::fmt::Display for NodeStatus {
                 node_id,
                 ip_addr,
                 if *is_outgoing { "Out" } else { "In" }
-            )?
+            )?;
+            if let Some(metadata) = self.connected_nodes_metadata.get(node_id) {
+                writeln!(
+                    f,
+                    "\tVersion: {} / Region: {} / Contact: {} / Capabilities: {}",
+                    metadata.version,
+                    metadata.region.as_deref().unwrap_or("-"),
+                    metadata.operator_contact.as_deref().unwrap_or("-"),
+                    metadata.capabilities.join(", ")
+                )?;
+            }
+            if let Some(probes) = self.connected_nodes_probes.get(node_id) {
+                writeln!(f, "\t{}", probes)?;
+            }
+            if let Some(final_slot) = self.connected_nodes_final_slots.get(node_id) {
+                writeln!(f, "\tFinal slot: {}", final_slot)?;
+            }
         }
         Ok(())
     }
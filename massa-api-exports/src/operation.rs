@@ -1,5 +1,6 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use massa_execution_exports::OperationInclusion;
 use massa_models::{
     block_id::BlockId,
     operation::{OperationId, SecureShareOperation},
@@ -39,6 +40,10 @@ pub struct OperationInfo {
     pub operation: SecureShareOperation,
     /// true if the operation execution succeeded, false if failed, None means unknown
     pub op_exec_status: Option<bool>,
+    /// final block including the operation and index of the operation in the block,
+    /// if the node indexes the operations of the final blocks
+    #[serde(default)]
+    pub final_inclusion: Option<OperationInclusion>,
 }
 
 impl std::fmt::Display for OperationInfo {
@@ -60,11 +65,58 @@ impl std::fmt::Display for OperationInfo {
         for block_id in &self.in_blocks {
             writeln!(f, "\t- {}", block_id)?;
         }
+        if let Some(inclusion) = &self.final_inclusion {
+            writeln!(
+                f,
+                "Included in final block {} at slot {}, index {}",
+                inclusion.block_id, inclusion.slot, inclusion.index
+            )?;
+        }
         writeln!(f, "{}", self.operation)?;
         Ok(())
     }
 }
 
+/// Status of a watched operation, sent at each of its transitions
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum OperationStatus {
+    /// waiting in the pool to be included in a block
+    Pending,
+    /// included in the given candidate blocks, none of them being final yet
+    InCandidateBlocks {
+        /// the candidate blocks
+        block_ids: Vec<BlockId>,
+    },
+    /// executed in a final slot
+    Final {
+        /// true if the execution succeeded
+        executed_successfully: bool,
+    },
+    /// not included in a final block before its expiry period
+    Expired {
+        /// last period at which the operation could be included in a block
+        expire_period: u64,
+    },
+    /// removed before being included in a block
+    Dropped {
+        /// why the operation was dropped
+        reason: String,
+    },
+}
+
+impl OperationStatus {
+    /// true if the status will not change anymore
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            OperationStatus::Final { .. }
+                | OperationStatus::Expired { .. }
+                | OperationStatus::Dropped { .. }
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use jsonrpsee_core::__reexports::serde_json::{self, Value};
@@ -106,4 +158,17 @@ mod tests {
         let expected_op = serde_json::from_str(&op_json_str).unwrap();
         assert_eq!(given_op, expected_op);
     }
+
+    #[test]
+    #[serial]
+    fn test_operation_status_json() {
+        let status = super::OperationStatus::Final {
+            executed_successfully: true,
+        };
+        let status_json: Value = serde_json::to_value(&status).unwrap();
+        assert_eq!(status_json["status"], "final");
+        assert_eq!(status_json["executed_successfully"], true);
+        assert!(status.is_terminal());
+        assert!(!super::OperationStatus::Pending.is_terminal());
+    }
 }
@@ -36,6 +36,16 @@ impl<T: Serialize> PagedVec<T> {
     }
 }
 
+impl<T> PagedVec<T> {
+    /// Creates a Paged Vec from an already extracted page and the total number of elements
+    pub fn from_page(page: Vec<T>, total_count: usize) -> Self {
+        PagedVec {
+            res: page,
+            _total_count: total_count,
+        }
+    }
+}
+
 impl<T: Serialize> Serialize for PagedVec<T> {
     fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
         self.res.serialize::<S>(s)
@@ -43,7 +53,7 @@ impl<T: Serialize> Serialize for PagedVec<T> {
 }
 
 /// Represents the request inputs for a PagedVec
-#[derive(Deserialize, Serialize)]
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
 pub struct PageRequest {
     /// The limit of elements in a page
     pub limit: usize,
@@ -51,6 +61,13 @@ pub struct PageRequest {
     pub offset: usize,
 }
 
+impl PageRequest {
+    /// Index of the first element of the requested page
+    pub fn start(&self) -> usize {
+        self.offset.saturating_mul(self.limit)
+    }
+}
+
 /// Represents the request inputs for a PagedVecV2
 #[derive(Deserialize, Serialize)]
 pub struct PagedVecV2<T> {
@@ -0,0 +1,64 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use massa_time::MassaTime;
+use serde::{Deserialize, Serialize};
+
+/// Heap statistics of the node process, counted by its allocator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HeapStats {
+    /// bytes currently allocated
+    pub allocated: u64,
+    /// maximum number of bytes allocated at once since the start of the node
+    pub peak_allocated: u64,
+    /// number of allocations since the start of the node
+    pub allocations: u64,
+    /// number of deallocations since the start of the node
+    pub deallocations: u64,
+}
+
+impl std::fmt::Display for HeapStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Allocated: {} bytes", self.allocated)?;
+        writeln!(f, "Peak allocated: {} bytes", self.peak_allocated)?;
+        writeln!(
+            f,
+            "Allocations: {} ({} live)",
+            self.allocations,
+            self.allocations.saturating_sub(self.deallocations)
+        )
+    }
+}
+
+/// CPU profile sampled between the start and the stop of a profiling session
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CpuProfile {
+    /// start of the session
+    pub start: MassaTime,
+    /// end of the session
+    pub end: MassaTime,
+    /// sampling frequency in Hz
+    pub frequency: u32,
+    /// number of samples
+    pub sample_count: u64,
+    /// sampled stacks in the folded format (`thread;root;...;leaf count` per line),
+    /// the input of `flamegraph.pl` and `inferno-flamegraph`
+    pub folded_stacks: String,
+}
+
+impl std::fmt::Display for CpuProfile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "CPU profile from {} to {}",
+            self.start.to_utc_string(),
+            self.end.to_utc_string()
+        )?;
+        writeln!(
+            f,
+            "{} samples at {} Hz, {} distinct stacks",
+            self.sample_count,
+            self.frequency,
+            self.folded_stacks.lines().count()
+        )
+    }
+}
@@ -0,0 +1,402 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use crate::error::ApiError;
+use massa_models::{
+    address::Address, amount::Amount, block_id::BlockId, operation::OperationId,
+    output_event::SCOutputEvent, slot::Slot,
+};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+/// Node-local index that can be queried
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryIndex {
+    /// operations created by an address
+    OperationsByCreator,
+    /// events emitted by an address
+    EventsByEmitter,
+    /// blocks created by an address
+    BlocksByCreator,
+}
+
+/// Field of the rows that can be filtered and sorted on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryField {
+    /// slot period of blocks and events, expiration period of operations
+    Period,
+    /// thread of the slot of blocks and events, thread of the creator of operations
+    Thread,
+    /// fee of operations
+    Fee,
+    /// index of events in their slot
+    IndexInSlot,
+    /// memo of transactions, compared with the UTF-8 bytes of the filter value
+    Memo,
+}
+
+impl QueryField {
+    /// whether the rows of `index` have this field
+    fn applies_to(&self, index: QueryIndex) -> bool {
+        match self {
+            QueryField::Period | QueryField::Thread => true,
+            QueryField::Fee | QueryField::Memo => index == QueryIndex::OperationsByCreator,
+            QueryField::IndexInSlot => index == QueryIndex::EventsByEmitter,
+        }
+    }
+
+    /// parse a value of this field, given as a string in the query
+    fn parse_value(&self, value: &str) -> Result<QueryValue, ApiError> {
+        match self {
+            QueryField::Fee => Amount::from_str(value)
+                .map(QueryValue::Amount)
+                .map_err(|err| ApiError::BadRequest(format!("invalid fee {}: {}", value, err))),
+            QueryField::Memo => Ok(QueryValue::Bytes(value.as_bytes().to_vec())),
+            _ => u64::from_str(value).map(QueryValue::Number).map_err(|err| {
+                ApiError::BadRequest(format!("invalid {:?} {}: {}", self, value, err))
+            }),
+        }
+    }
+}
+
+/// Value of a field, compared with the values of the filters
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum QueryValue {
+    Number(u64),
+    Amount(Amount),
+    Bytes(Vec<u8>),
+}
+
+/// Comparison operator of a filter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryOperator {
+    /// equal
+    Eq,
+    /// not equal
+    Ne,
+    /// lower than
+    Lt,
+    /// lower than or equal
+    Le,
+    /// greater than
+    Gt,
+    /// greater than or equal
+    Ge,
+}
+
+impl QueryOperator {
+    fn matches(&self, ordering: Ordering) -> bool {
+        match self {
+            QueryOperator::Eq => ordering == Ordering::Equal,
+            QueryOperator::Ne => ordering != Ordering::Equal,
+            QueryOperator::Lt => ordering == Ordering::Less,
+            QueryOperator::Le => ordering != Ordering::Greater,
+            QueryOperator::Gt => ordering == Ordering::Greater,
+            QueryOperator::Ge => ordering != Ordering::Less,
+        }
+    }
+}
+
+/// Keep the rows whose `field` compares to `value` with `op`
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct QueryFilter {
+    /// filtered field
+    pub field: QueryField,
+    /// comparison operator
+    pub op: QueryOperator,
+    /// compared value: an integer, a decimal amount for the fee, or a text for the memo
+    pub value: String,
+}
+
+/// Sort the rows by `field`
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct QuerySort {
+    /// field to sort on
+    pub field: QueryField,
+    /// sort in descending order
+    #[serde(default)]
+    pub descending: bool,
+}
+
+/// Query over a node-local index: the rows of `address` in `index`,
+/// filtered by all the `filters`, sorted and truncated to `limit` rows.
+///
+/// Without `sort`, rows are sorted by slot. The indexes only cover the objects the node still keeps in memory.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct IndexQuery {
+    /// queried index
+    pub index: QueryIndex,
+    /// address whose rows are queried
+    pub address: Address,
+    /// filters, all must match
+    #[serde(default)]
+    pub filters: Vec<QueryFilter>,
+    /// optional sort
+    #[serde(default)]
+    pub sort: Option<QuerySort>,
+    /// optional max number of rows, capped by the node
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+impl IndexQuery {
+    /// Filter, sort and truncate the rows of the queried index.
+    /// At most `max_results` rows are returned.
+    pub fn apply(
+        &self,
+        mut rows: Vec<QueryRow>,
+        max_results: usize,
+    ) -> Result<Vec<QueryRow>, ApiError> {
+        let mut filters = Vec::with_capacity(self.filters.len());
+        for filter in &self.filters {
+            if !filter.field.applies_to(self.index) {
+                return Err(ApiError::BadRequest(format!(
+                    "field {:?} is not available in index {:?}",
+                    filter.field, self.index
+                )));
+            }
+            filters.push((
+                filter.field,
+                filter.op,
+                filter.field.parse_value(&filter.value)?,
+            ));
+        }
+        if let Some(sort) = &self.sort {
+            if !sort.field.applies_to(self.index) {
+                return Err(ApiError::BadRequest(format!(
+                    "field {:?} is not available in index {:?}",
+                    sort.field, self.index
+                )));
+            }
+        }
+
+        rows.retain(|row| {
+            filters.iter().all(|(field, op, value)| {
+                row.value(*field)
+                    .map_or(false, |row_value| op.matches(row_value.cmp(value)))
+            })
+        });
+        // sort by slot first so that the order is deterministic, the requested sort being stable
+        rows.sort_by_key(|row| (row.value(QueryField::Period), row.value(QueryField::Thread)));
+        if let Some(sort) = &self.sort {
+            rows.sort_by(|a, b| {
+                let ordering = a.value(sort.field).cmp(&b.value(sort.field));
+                if sort.descending {
+                    ordering.reverse()
+                } else {
+                    ordering
+                }
+            });
+        }
+        rows.truncate(std::cmp::min(
+            self.limit.unwrap_or(max_results),
+            max_results,
+        ));
+        Ok(rows)
+    }
+}
+
+/// Row of a node-local index
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryRow {
+    /// operation created by the queried address
+    Operation {
+        /// operation id
+        id: OperationId,
+        /// last period at which the operation can be included
+        expire_period: u64,
+        /// thread of the creator
+        thread: u8,
+        /// fee of the operation
+        fee: Amount,
+        /// memo of the operation, if it is a transaction with a memo
+        memo: Option<Vec<u8>>,
+    },
+    /// block created by the queried address
+    Block {
+        /// block id
+        id: BlockId,
+        /// slot of the block
+        slot: Slot,
+    },
+    /// event emitted by the queried address
+    Event(SCOutputEvent),
+}
+
+impl QueryRow {
+    fn value(&self, field: QueryField) -> Option<QueryValue> {
+        match (self, field) {
+            (QueryRow::Operation { expire_period, .. }, QueryField::Period) => {
+                Some(QueryValue::Number(*expire_period))
+            }
+            (QueryRow::Operation { thread, .. }, QueryField::Thread) => {
+                Some(QueryValue::Number(*thread as u64))
+            }
+            (QueryRow::Operation { fee, .. }, QueryField::Fee) => Some(QueryValue::Amount(*fee)),
+            (QueryRow::Operation { memo, .. }, QueryField::Memo) => {
+                memo.clone().map(QueryValue::Bytes)
+            }
+            (QueryRow::Block { slot, .. }, QueryField::Period) => {
+                Some(QueryValue::Number(slot.period))
+            }
+            (QueryRow::Block { slot, .. }, QueryField::Thread) => {
+                Some(QueryValue::Number(slot.thread as u64))
+            }
+            (QueryRow::Event(event), QueryField::Period) => {
+                Some(QueryValue::Number(event.context.slot.period))
+            }
+            (QueryRow::Event(event), QueryField::Thread) => {
+                Some(QueryValue::Number(event.context.slot.thread as u64))
+            }
+            (QueryRow::Event(event), QueryField::IndexInSlot) => {
+                Some(QueryValue::Number(event.context.index_in_slot))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for QueryRow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QueryRow::Operation {
+                id,
+                expire_period,
+                thread,
+                fee,
+                memo,
+            } => {
+                write!(
+                    f,
+                    "Operation {}: expire period {}, thread {}, fee {}",
+                    id, expire_period, thread, fee
+                )?;
+                if let Some(memo) = memo {
+                    write!(f, ", memo {}", String::from_utf8_lossy(memo))?;
+                }
+                Ok(())
+            }
+            QueryRow::Block { id, slot } => write!(f, "Block {} at slot {}", id, slot),
+            QueryRow::Event(event) => write!(f, "{}", event),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_signature::KeyPair;
+
+    fn block_rows() -> Vec<QueryRow> {
+        [(3, 1), (1, 0), (2, 5), (1, 4)]
+            .into_iter()
+            .map(|(period, thread)| QueryRow::Block {
+                id: BlockId(massa_hash::Hash::compute_from(&[period as u8, thread])),
+                slot: Slot::new(period, thread),
+            })
+            .collect()
+    }
+
+    fn row_slot(row: &QueryRow) -> Slot {
+        match row {
+            QueryRow::Block { slot, .. } => *slot,
+            _ => panic!("unexpected row"),
+        }
+    }
+
+    #[test]
+    fn test_index_query_filter_sort_limit() {
+        let address = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let mut query = IndexQuery {
+            index: QueryIndex::BlocksByCreator,
+            address,
+            filters: vec![QueryFilter {
+                field: QueryField::Period,
+                op: QueryOperator::Le,
+                value: "2".to_string(),
+            }],
+            sort: None,
+            limit: None,
+        };
+        let rows = query.apply(block_rows(), 10).unwrap();
+        assert_eq!(
+            rows.iter().map(row_slot).collect::<Vec<_>>(),
+            vec![Slot::new(1, 0), Slot::new(1, 4), Slot::new(2, 5)]
+        );
+
+        query.sort = Some(QuerySort {
+            field: QueryField::Thread,
+            descending: true,
+        });
+        query.limit = Some(2);
+        let rows = query.apply(block_rows(), 10).unwrap();
+        assert_eq!(
+            rows.iter().map(row_slot).collect::<Vec<_>>(),
+            vec![Slot::new(2, 5), Slot::new(1, 4)]
+        );
+        // the node limit caps the requested one
+        assert_eq!(query.apply(block_rows(), 1).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_index_query_invalid_filters() {
+        let address = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let mut query = IndexQuery {
+            index: QueryIndex::BlocksByCreator,
+            address,
+            filters: vec![QueryFilter {
+                field: QueryField::Fee,
+                op: QueryOperator::Gt,
+                value: "1".to_string(),
+            }],
+            sort: None,
+            limit: None,
+        };
+        assert!(query.apply(block_rows(), 10).is_err());
+        query.filters[0].field = QueryField::Period;
+        query.filters[0].value = "not a period".to_string();
+        assert!(query.apply(block_rows(), 10).is_err());
+    }
+
+    #[test]
+    fn test_index_query_memo_filter() {
+        let address = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let rows: Vec<QueryRow> = [Some("deposit-42"), None, Some("deposit-7")]
+            .into_iter()
+            .enumerate()
+            .map(|(index, memo)| QueryRow::Operation {
+                id: OperationId::from_bytes(
+                    massa_hash::Hash::compute_from(&[index as u8]).to_bytes(),
+                ),
+                expire_period: index as u64,
+                thread: 0,
+                fee: Amount::zero(),
+                memo: memo.map(|memo| memo.as_bytes().to_vec()),
+            })
+            .collect();
+        let query = IndexQuery {
+            index: QueryIndex::OperationsByCreator,
+            address,
+            filters: vec![QueryFilter {
+                field: QueryField::Memo,
+                op: QueryOperator::Eq,
+                value: "deposit-7".to_string(),
+            }],
+            sort: None,
+            limit: None,
+        };
+        let rows = query.apply(rows, 10).unwrap();
+        assert_eq!(rows.len(), 1);
+        assert!(matches!(
+            &rows[0],
+            QueryRow::Operation {
+                expire_period: 2,
+                ..
+            }
+        ));
+    }
+}
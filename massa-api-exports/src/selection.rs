@@ -0,0 +1,140 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use massa_models::{
+    address::Address,
+    slot::{IndexedSlot, Slot},
+};
+use serde::{Deserialize, Serialize};
+
+/// Upcoming selections of an address:
+/// concrete draws for the cycles already drawn, estimates for the following ones
+#[derive(Debug, Deserialize, Serialize)]
+pub struct AddressSelectionForecast {
+    /// the address
+    pub address: Address,
+    /// latest cycle for which draws are available
+    pub last_drawn_cycle: Option<u64>,
+    /// next block draws
+    pub next_block_draws: Vec<Slot>,
+    /// next endorsement draws
+    pub next_endorsement_draws: Vec<IndexedSlot>,
+    /// estimates for the cycles that are not drawn yet, sorted by cycle
+    pub estimates: Vec<CycleSelectionEstimate>,
+}
+
+impl std::fmt::Display for AddressSelectionForecast {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Address {}:", self.address)?;
+        match self.last_drawn_cycle {
+            Some(cycle) => writeln!(f, "\tDraws available up to cycle {}", cycle)?,
+            None => writeln!(f, "\tNo draws available yet")?,
+        }
+        writeln!(
+            f,
+            "\tNext block draws: {}",
+            self.next_block_draws
+                .iter()
+                .map(|slot| slot.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )?;
+        writeln!(
+            f,
+            "\tNext endorsement draws: {}",
+            self.next_endorsement_draws
+                .iter()
+                .map(|indexed_slot| indexed_slot.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )?;
+        if !self.estimates.is_empty() {
+            writeln!(f, "\tEstimates:")?;
+            for estimate in &self.estimates {
+                writeln!(f, "\t\t{}", estimate)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Estimated production of an address during a cycle whose draws are not computed yet,
+/// based on the latest known roll distribution
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CycleSelectionEstimate {
+    /// cycle number
+    pub cycle: u64,
+    /// rolls of the address in the distribution used for the estimate
+    pub roll_count: u64,
+    /// total rolls in the distribution used for the estimate
+    pub total_rolls: u64,
+    /// probability of being selected for a given draw
+    pub draw_probability: f64,
+    /// probability of being selected to produce at least one block during the cycle
+    pub block_probability: f64,
+    /// expected number of blocks to produce during the cycle
+    pub expected_blocks: f64,
+    /// expected number of endorsements to produce during the cycle
+    pub expected_endorsements: f64,
+}
+
+impl CycleSelectionEstimate {
+    /// Estimate the selections of an address holding `roll_count` rolls out of `total_rolls`
+    /// during a cycle of `slot_count` slots with `endorsement_count` endorsements per slot
+    pub fn new(
+        cycle: u64,
+        roll_count: u64,
+        total_rolls: u64,
+        slot_count: u64,
+        endorsement_count: u32,
+    ) -> Self {
+        let draw_probability = if total_rolls == 0 {
+            0.0
+        } else {
+            roll_count as f64 / total_rolls as f64
+        };
+        let slot_count = slot_count as f64;
+        CycleSelectionEstimate {
+            cycle,
+            roll_count,
+            total_rolls,
+            draw_probability,
+            block_probability: 1.0 - (1.0 - draw_probability).powf(slot_count),
+            expected_blocks: draw_probability * slot_count,
+            expected_endorsements: draw_probability * slot_count * endorsement_count as f64,
+        }
+    }
+}
+
+impl std::fmt::Display for CycleSelectionEstimate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Cycle {}: rolls={}/{}, expected blocks={:.2} (at least one: {:.2}%), expected endorsements={:.2}",
+            self.cycle,
+            self.roll_count,
+            self.total_rolls,
+            self.expected_blocks,
+            self.block_probability * 100.0,
+            self.expected_endorsements
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CycleSelectionEstimate;
+
+    #[test]
+    fn test_cycle_selection_estimate() {
+        let estimate = CycleSelectionEstimate::new(5, 1, 4, 128 * 32, 16);
+        assert_eq!(estimate.draw_probability, 0.25);
+        assert_eq!(estimate.expected_blocks, 1024.0);
+        assert_eq!(estimate.expected_endorsements, 16384.0);
+        assert!(estimate.block_probability > 0.99);
+
+        let no_rolls = CycleSelectionEstimate::new(5, 0, 0, 128 * 32, 16);
+        assert_eq!(no_rolls.draw_probability, 0.0);
+        assert_eq!(no_rolls.block_probability, 0.0);
+        assert_eq!(no_rolls.expected_endorsements, 0.0);
+    }
+}
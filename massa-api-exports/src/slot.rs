@@ -1,14 +1,174 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
-use massa_models::{amount::Amount, slot::Slot};
+use massa_async_pool::{AsyncMessage, Change};
+use massa_final_state::StateChanges;
+use massa_hash::Hash;
+use massa_ledger_exports::{SetOrKeep, SetUpdateOrDelete};
+use massa_models::{
+    address::Address, amount::Amount, block::FilledBlock, operation::OperationId,
+    output_event::SCOutputEvent, slot::Slot,
+};
+use massa_time::MassaTime;
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// slot / amount pair
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct SlotAmount {
     /// slot
     pub slot: Slot,
     /// amount
     pub amount: Amount,
 }
+
+/// Random beacon of a final slot
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SlotBeacon {
+    /// slot
+    pub slot: Slot,
+    /// hash of the draw seed of the cycle and of the seed bits of its slots up to `slot` included
+    pub beacon: Hash,
+}
+
+/// Slot with its timestamp
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SlotTiming {
+    /// slot
+    pub slot: Slot,
+    /// time at which a block is expected at that slot
+    pub timestamp: MassaTime,
+    /// cycle of the slot
+    pub cycle: u64,
+}
+
+/// First and last slots of a cycle with its time range
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CycleBounds {
+    /// cycle
+    pub cycle: u64,
+    /// first slot of the cycle
+    pub first_slot: Slot,
+    /// last slot of the cycle
+    pub last_slot: Slot,
+    /// timestamp of the first slot of the cycle
+    pub start_timestamp: MassaTime,
+    /// timestamp of the first slot of the next cycle
+    pub end_timestamp: MassaTime,
+}
+
+/// Slots of a time range with the bounds of the cycles they belong to
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SlotCalendar {
+    /// slots whose timestamp is in the time range, in chronological order
+    pub slots: Vec<SlotTiming>,
+    /// cycles of those slots
+    pub cycles: Vec<CycleBounds>,
+}
+
+impl std::fmt::Display for SlotCalendar {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for cycle in &self.cycles {
+            writeln!(
+                f,
+                "Cycle {}: slots {} to {}, from {} to {}",
+                cycle.cycle,
+                cycle.first_slot,
+                cycle.last_slot,
+                cycle.start_timestamp.to_utc_string(),
+                cycle.end_timestamp.to_utc_string()
+            )?;
+        }
+        for timing in &self.slots {
+            writeln!(
+                f,
+                "\t{} at {}",
+                timing.slot,
+                timing.timestamp.to_utc_string()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Everything an explorer needs to index a slot
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SlotDetail {
+    /// slot
+    pub slot: Slot,
+    /// true if the slot is final
+    pub is_final: bool,
+    /// block of the blockclique at that slot with its operations, None if the slot was missed
+    pub block: Option<FilledBlock>,
+    /// events produced by the execution of the slot
+    pub events: Vec<SCOutputEvent>,
+    /// summary of the state changes of the slot,
+    /// None if it was not executed yet or if it is too old to be in the history of the final state
+    pub state_changes: Option<StateChangesSummary>,
+}
+
+/// Summary of the state changes caused by the execution of a slot
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct StateChangesSummary {
+    /// new balances of the addresses whose balance changed (state changes record values, not deltas)
+    pub balances: BTreeMap<Address, Amount>,
+    /// addresses whose bytecode was set
+    pub bytecode_updates: Vec<Address>,
+    /// number of datastore entries written or deleted
+    pub datastore_writes_count: usize,
+    /// new roll counts of the addresses whose roll count changed
+    pub roll_counts: BTreeMap<Address, u64>,
+    /// asynchronous messages emitted
+    pub async_messages_emitted: Vec<AsyncMessage>,
+    /// number of asynchronous messages removed from the pool, because they were executed or expired
+    pub async_messages_removed_count: usize,
+    /// executed operations, with true if their execution succeeded
+    pub executed_operations: BTreeMap<OperationId, bool>,
+}
+
+impl From<&StateChanges> for StateChangesSummary {
+    fn from(changes: &StateChanges) -> Self {
+        let mut summary = StateChangesSummary::default();
+        for (address, change) in changes.ledger_changes.0.iter() {
+            match change {
+                SetUpdateOrDelete::Set(entry) => {
+                    summary.balances.insert(*address, entry.balance);
+                    summary.bytecode_updates.push(*address);
+                    summary.datastore_writes_count += entry.datastore.len();
+                }
+                SetUpdateOrDelete::Update(update) => {
+                    if let SetOrKeep::Set(balance) = &update.balance {
+                        summary.balances.insert(*address, *balance);
+                    }
+                    if let SetOrKeep::Set(_) = &update.bytecode {
+                        summary.bytecode_updates.push(*address);
+                    }
+                    summary.datastore_writes_count += update.datastore.len();
+                }
+                SetUpdateOrDelete::Delete => {
+                    summary.balances.insert(*address, Amount::zero());
+                }
+            }
+        }
+        summary.bytecode_updates.sort_unstable();
+        summary.roll_counts = changes
+            .pos_changes
+            .roll_changes
+            .iter()
+            .map(|(address, rolls)| (*address, *rolls))
+            .collect();
+        for change in changes.async_pool_changes.0.iter() {
+            match change {
+                Change::Add(_, message) => summary.async_messages_emitted.push(message.clone()),
+                Change::Delete(_) => summary.async_messages_removed_count += 1,
+                Change::Activate(_) => {}
+            }
+        }
+        summary.executed_operations = changes
+            .executed_ops_changes
+            .iter()
+            .map(|(op_id, (success, _))| (*op_id, *success))
+            .collect();
+        summary
+    }
+}
@@ -7,22 +7,35 @@ use crate::{ApiServer, ApiV2, StopHandle, API};
 use async_trait::async_trait;
 use jsonrpsee::core::error::SubscriptionClosed;
 use jsonrpsee::core::{Error as JsonRpseeError, RpcResult};
-use jsonrpsee::types::SubscriptionResult;
+use jsonrpsee::types::{ErrorObject, SubscriptionResult};
 use jsonrpsee::SubscriptionSink;
+use massa_api_exports::address::{
+    AddressDeferredCredits, DeferredCreditEntry, ExecutedCreditEntry,
+};
 use massa_api_exports::config::APIConfig;
 use massa_api_exports::error::ApiError;
+use massa_api_exports::node::BuildInfo;
+use massa_api_exports::operation::OperationStatus;
 use massa_api_exports::page::{PageRequest, PagedVec, PagedVecV2};
-use massa_api_exports::ApiRequest;
+use massa_api_exports::{ApiRequest, StakersFilter};
+use massa_channel::event_bus::RecvError;
+use massa_channel::{EventBus, EventSubscription, NodeEvent, Topic};
 use massa_consensus_exports::{ConsensusChannels, ConsensusController};
 use massa_execution_exports::ExecutionController;
 use massa_models::address::Address;
+use massa_models::amount::Amount;
+use massa_models::block::BlockGraphStatus;
 use massa_models::block_id::BlockId;
+use massa_models::execution::EventFilter;
+use massa_models::operation::OperationId;
 use massa_models::slot::Slot;
-use massa_models::timeslots::get_latest_block_slot_at_timestamp;
+use massa_models::timeslots::{get_block_slot_timestamp, get_latest_block_slot_at_timestamp};
 use massa_models::version::Version;
-use massa_pool_exports::PoolChannels;
+use massa_pool_exports::{PoolChannels, PoolController};
+use massa_storage::Storage;
 use massa_time::MassaTime;
 use serde::Serialize;
+use std::collections::BTreeMap;
 use tokio_stream::wrappers::BroadcastStream;
 
 impl API<ApiV2> {
@@ -32,16 +45,22 @@ impl API<ApiV2> {
         consensus_channels: ConsensusChannels,
         execution_controller: Box<dyn ExecutionController>,
         pool_channels: PoolChannels,
+        event_bus: EventBus,
+        storage: Storage,
         api_settings: APIConfig,
         version: Version,
+        build_info: BuildInfo,
     ) -> Self {
         API(ApiV2 {
             consensus_controller,
             consensus_channels,
             execution_controller,
             pool_channels,
+            event_bus,
+            storage,
             api_settings,
             version,
+            build_info,
         })
     }
 }
@@ -63,6 +82,7 @@ impl MassaApiServer for API<ApiV2> {
     async fn get_largest_stakers(
         &self,
         api_request: Option<ApiRequest>,
+        filter: Option<StakersFilter>,
     ) -> RpcResult<PagedVecV2<(Address, u64)>> {
         let execution_controller = self.0.execution_controller.clone();
         let cfg = self.0.api_settings.clone();
@@ -86,37 +106,105 @@ impl MassaApiServer for API<ApiV2> {
             Err(e) => return Err(ApiError::ModelsError(e).into()),
         };
 
-        let mut staker_vec = execution_controller
-            .get_cycle_active_rolls(curr_cycle)
-            .into_iter()
-            .collect::<Vec<(Address, u64)>>();
-
-        staker_vec
-            .sort_by(|&(_, roll_counts_a), &(_, roll_counts_b)| roll_counts_b.cmp(&roll_counts_a));
-
-        let paged_vec = if let Some(api_request) = api_request {
-            PagedVec::new(staker_vec, api_request.page_request)
-        } else {
-            PagedVec::new(
-                staker_vec,
-                Some(PageRequest {
-                    offset: 0,
-                    limit: 50,
-                }),
-            )
+        let page_request = match api_request {
+            Some(api_request) => api_request.page_request,
+            None => Some(PageRequest {
+                offset: 0,
+                limit: 50,
+            }),
         };
+        let (offset, limit) = page_request.map_or((0, usize::MAX), |page_request| {
+            (page_request.start(), page_request.limit)
+        });
+        let min_rolls = filter.and_then(|filter| filter.min_rolls).unwrap_or(0);
+        let (stakers, total_count) =
+            execution_controller.get_cycle_stakers(curr_cycle, min_rolls, offset, limit);
 
-        Ok(paged_vec.into())
+        Ok(PagedVec::from_page(stakers, total_count).into())
     }
 
     async fn get_next_block_best_parents(&self) -> RpcResult<Vec<(BlockId, u64)>> {
         Ok(self.0.consensus_controller.get_best_parents())
     }
 
+    async fn get_deferred_credits_schedule(
+        &self,
+        addresses: Vec<Address>,
+    ) -> RpcResult<Vec<AddressDeferredCredits>> {
+        let cfg = self.0.api_settings.clone();
+
+        if addresses.len() as u64 > cfg.max_arguments {
+            return Err(ApiError::BadRequest("too many arguments".into()).into());
+        }
+
+        let execution_infos = self.0.execution_controller.get_addresses_infos(&addresses);
+
+        let mut res = Vec::with_capacity(addresses.len());
+        for (address, info) in addresses.into_iter().zip(execution_infos.into_iter()) {
+            // merge final and candidate credits by slot
+            let mut credits: BTreeMap<Slot, (Option<Amount>, Option<Amount>)> = BTreeMap::new();
+            for (slot, amount) in info.final_future_deferred_credits {
+                credits.entry(slot).or_default().0 = Some(amount);
+            }
+            for (slot, amount) in info.future_deferred_credits {
+                credits.entry(slot).or_default().1 = Some(amount);
+            }
+
+            let mut final_total = Amount::default();
+            let mut candidate_total = Amount::default();
+            let mut schedule = Vec::with_capacity(credits.len());
+            for (slot, (final_amount, candidate_amount)) in credits {
+                let timestamp =
+                    get_block_slot_timestamp(cfg.thread_count, cfg.t0, cfg.genesis_timestamp, slot)
+                        .map_err(ApiError::ModelsError)?;
+                final_total = final_total.saturating_add(final_amount.unwrap_or_default());
+                candidate_total =
+                    candidate_total.saturating_add(candidate_amount.unwrap_or_default());
+                schedule.push(DeferredCreditEntry {
+                    slot,
+                    cycle: slot.get_cycle(cfg.periods_per_cycle),
+                    timestamp,
+                    final_amount,
+                    candidate_amount,
+                });
+            }
+
+            let mut executed = Vec::with_capacity(info.executed_deferred_credits.len());
+            for (slot, amount) in info.executed_deferred_credits {
+                executed.push(ExecutedCreditEntry {
+                    slot,
+                    cycle: slot.get_cycle(cfg.periods_per_cycle),
+                    timestamp: get_block_slot_timestamp(
+                        cfg.thread_count,
+                        cfg.t0,
+                        cfg.genesis_timestamp,
+                        slot,
+                    )
+                    .map_err(ApiError::ModelsError)?,
+                    amount,
+                });
+            }
+
+            res.push(AddressDeferredCredits {
+                address,
+                final_total,
+                candidate_total,
+                schedule,
+                executed,
+            });
+        }
+
+        Ok(res)
+    }
+
     async fn get_version(&self) -> RpcResult<Version> {
         Ok(self.0.version)
     }
 
+    async fn get_build_info(&self) -> RpcResult<BuildInfo> {
+        Ok(self.0.build_info.clone())
+    }
+
     fn subscribe_new_blocks(&self, sink: SubscriptionSink) -> SubscriptionResult {
         broadcast_via_ws(self.0.consensus_channels.block_sender.clone(), sink);
         Ok(())
@@ -132,10 +220,284 @@ impl MassaApiServer for API<ApiV2> {
         Ok(())
     }
 
-    fn subscribe_new_operations(&self, sink: SubscriptionSink) -> SubscriptionResult {
-        broadcast_via_ws(self.0.pool_channels.operation_sender.clone(), sink);
+    fn subscribe_chain_reorgs(&self, sink: SubscriptionSink) -> SubscriptionResult {
+        broadcast_via_ws(self.0.consensus_channels.reorg_sender.clone(), sink);
+        Ok(())
+    }
+
+    fn watch_operation(
+        &self,
+        mut sink: SubscriptionSink,
+        operation_id: OperationId,
+    ) -> SubscriptionResult {
+        let expire_period = self
+            .0
+            .storage
+            .read_operations()
+            .get(&operation_id)
+            .map(|operation| operation.content.expire_period);
+        let Some(expire_period) = expire_period else {
+            sink.reject(ErrorObject::owned(
+                -32004,
+                "operation not found",
+                None::<()>,
+            ))?;
+            return Ok(());
+        };
+        sink.accept()?;
+
+        let consensus_controller = self.0.consensus_controller.clone();
+        let execution_controller = self.0.execution_controller.clone();
+        let pool_controller = self.0.consensus_channels.pool_command_sender.clone();
+        let storage = self.0.storage.clone_without_refs();
+        let cfg = self.0.api_settings.clone();
+        tokio::spawn(async move {
+            // the status can only change once per slot
+            let mut interval = tokio::time::interval(
+                cfg.t0
+                    .checked_div_u64(cfg.thread_count as u64)
+                    .unwrap_or(cfg.t0)
+                    .to_duration(),
+            );
+            let mut previous_status = None;
+            loop {
+                interval.tick().await;
+                let status = get_operation_status(
+                    operation_id,
+                    expire_period,
+                    consensus_controller.as_ref(),
+                    execution_controller.as_ref(),
+                    pool_controller.as_ref(),
+                    &storage,
+                    &cfg,
+                );
+                if previous_status.as_ref() != Some(&status) {
+                    match sink.send(&status) {
+                        Ok(true) => {}
+                        // the subscriber left
+                        Ok(false) => return,
+                        Err(err) => {
+                            sink.close(SubscriptionClosed::Failed(ErrorObject::owned(
+                                -32001,
+                                err.to_string(),
+                                None::<()>,
+                            )));
+                            return;
+                        }
+                    }
+                    if status.is_terminal() {
+                        sink.close(SubscriptionClosed::Success);
+                        return;
+                    }
+                    previous_status = Some(status);
+                }
+            }
+        });
         Ok(())
     }
+
+    fn subscribe_final_events(
+        &self,
+        mut sink: SubscriptionSink,
+        filter: EventFilter,
+    ) -> SubscriptionResult {
+        sink.accept()?;
+
+        let execution_controller = self.0.execution_controller.clone();
+        let cfg = self.0.api_settings.clone();
+        tokio::spawn(async move {
+            // new events can only become final once per slot
+            let mut interval = tokio::time::interval(
+                cfg.t0
+                    .checked_div_u64(cfg.thread_count as u64)
+                    .unwrap_or(cfg.t0)
+                    .to_duration(),
+            );
+            let mut filter = EventFilter {
+                is_final: Some(true),
+                ..filter
+            };
+            // slot and index of the last sent event
+            let mut last_sent: Option<(Slot, u64)> = None;
+            loop {
+                if sink.is_closed() {
+                    return;
+                }
+                let events = execution_controller.get_filtered_sc_output_event(filter.clone());
+                let mut sent_any = false;
+                for event in events {
+                    let position = (event.context.slot, event.context.index_in_slot);
+                    if last_sent.map_or(false, |last_sent| position <= last_sent) {
+                        continue;
+                    }
+                    match sink.send(&event) {
+                        Ok(true) => {}
+                        // the subscriber left
+                        Ok(false) => return,
+                        Err(err) => {
+                            sink.close(SubscriptionClosed::Failed(ErrorObject::owned(
+                                -32001,
+                                err.to_string(),
+                                None::<()>,
+                            )));
+                            return;
+                        }
+                    }
+                    last_sent = Some(position);
+                    sent_any = true;
+                }
+                if let Some((slot, _)) = last_sent {
+                    filter.start = Some(slot);
+                }
+                // the queries are limited in size: fetch the next events at once while catching up
+                if !sent_any {
+                    interval.tick().await;
+                }
+            }
+        });
+        Ok(())
+    }
+
+    fn subscribe_new_operations(&self, sink: SubscriptionSink) -> SubscriptionResult {
+        let subscription = self.0.event_bus.subscribe(&[Topic::OperationPooled]);
+        events_via_ws(subscription, sink, |event| match event {
+            NodeEvent::OperationPooled { operation, .. } => Some(operation),
+            _ => None,
+        })
+    }
+
+    fn subscribe_node_events(
+        &self,
+        sink: SubscriptionSink,
+        topics: Vec<Topic>,
+    ) -> SubscriptionResult {
+        let topics = if topics.is_empty() {
+            Topic::ALL.to_vec()
+        } else {
+            topics
+        };
+        events_via_ws(self.0.event_bus.subscribe(&topics), sink, Some)
+    }
+}
+
+/// Current status of an operation, from the indexes of execution, consensus and pool
+fn get_operation_status(
+    operation_id: OperationId,
+    expire_period: u64,
+    consensus_controller: &dyn ConsensusController,
+    execution_controller: &dyn ExecutionController,
+    pool_controller: &dyn PoolController,
+    storage: &Storage,
+    cfg: &APIConfig,
+) -> OperationStatus {
+    let (_, final_op_exec_statuses) = execution_controller.get_op_exec_status();
+    if let Some(executed_successfully) = final_op_exec_statuses.get(&operation_id) {
+        return OperationStatus::Final {
+            executed_successfully: *executed_successfully,
+        };
+    }
+
+    // blocks containing the operation that may still become final
+    let block_ids: Vec<BlockId> = storage
+        .read_blocks()
+        .get_blocks_by_operation(&operation_id)
+        .map(|block_ids| block_ids.iter().copied().collect())
+        .unwrap_or_default();
+    let statuses = consensus_controller.get_block_statuses(&block_ids);
+    let mut block_ids: Vec<BlockId> = block_ids
+        .into_iter()
+        .zip(statuses)
+        .filter_map(|(block_id, status)| match status {
+            BlockGraphStatus::Discarded | BlockGraphStatus::NotFound => None,
+            _ => Some(block_id),
+        })
+        .collect();
+    if !block_ids.is_empty() {
+        block_ids.sort_unstable();
+        return OperationStatus::InCandidateBlocks { block_ids };
+    }
+
+    if pool_controller
+        .contains_operations(&[operation_id])
+        .first()
+        .copied()
+        .unwrap_or_default()
+    {
+        return OperationStatus::Pending;
+    }
+
+    let current_period = MassaTime::now()
+        .ok()
+        .and_then(|now| {
+            get_latest_block_slot_at_timestamp(cfg.thread_count, cfg.t0, cfg.genesis_timestamp, now)
+                .ok()
+                .flatten()
+        })
+        .map(|slot| slot.period)
+        .unwrap_or_default();
+    if current_period > expire_period {
+        OperationStatus::Expired { expire_period }
+    } else {
+        OperationStatus::Dropped {
+            reason: "removed from the pool before being included in a block".to_string(),
+        }
+    }
+}
+
+/// Sends via a WebSocket the events of the `subscription` to the event bus, as converted by `select`
+fn events_via_ws<T: Serialize + Send + 'static>(
+    mut subscription: EventSubscription,
+    mut sink: SubscriptionSink,
+    select: fn(NodeEvent) -> Option<T>,
+) -> SubscriptionResult {
+    sink.accept()?;
+    tokio::spawn(async move {
+        // the subscriber may leave while no event comes
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(1));
+        loop {
+            let event = tokio::select! {
+                event = subscription.recv() => event,
+                _ = interval.tick() => {
+                    if sink.is_closed() {
+                        return;
+                    }
+                    continue;
+                }
+            };
+            let item = match event {
+                Ok(event) => match select(event) {
+                    Some(item) => item,
+                    None => continue,
+                },
+                Err(RecvError::Lagged(missed)) => {
+                    sink.close(SubscriptionClosed::Failed(ErrorObject::owned(
+                        -32001,
+                        format!("the subscriber lagged behind and missed {} events", missed),
+                        None::<()>,
+                    )));
+                    return;
+                }
+                Err(RecvError::Closed) => {
+                    sink.close(SubscriptionClosed::Success);
+                    return;
+                }
+            };
+            match sink.send(&item) {
+                Ok(true) => {}
+                // the subscriber left
+                Ok(false) => return,
+                Err(err) => {
+                    sink.close(SubscriptionClosed::Failed(ErrorObject::owned(
+                        -32001,
+                        err.to_string(),
+                        None::<()>,
+                    )));
+                    return;
+                }
+            }
+        }
+    });
+    Ok(())
 }
 
 /// Brodcast the stream(sender) content via a WebSocket
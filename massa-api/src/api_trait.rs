@@ -2,30 +2,49 @@
 //! Json RPC API for a massa-node
 use jsonrpsee::core::RpcResult;
 use jsonrpsee::proc_macros::rpc;
+use massa_api_exports::address::AddressDeferredCredits;
+use massa_api_exports::node::BuildInfo;
 use massa_api_exports::page::PagedVecV2;
-use massa_api_exports::ApiRequest;
+use massa_api_exports::{ApiRequest, StakersFilter};
+use massa_channel::{NodeEvent, Topic};
 use massa_models::address::Address;
 use massa_models::block_id::BlockId;
+use massa_models::execution::EventFilter;
+use massa_models::operation::OperationId;
+use massa_models::output_event::SCOutputEvent;
 use massa_models::version::Version;
 
 /// Exposed API methods
 #[rpc(server)]
 pub trait MassaApi {
-    /// Get the active stakers and their active roll counts for the current cycle sorted by largest roll counts.
+    /// Get the active stakers and their active roll counts for the current cycle sorted by largest roll counts,
+    /// optionally filtered by a minimum roll count.
     #[method(name = "get_largest_stakers")]
     async fn get_largest_stakers(
         &self,
         page_request: Option<ApiRequest>,
+        filter: Option<StakersFilter>,
     ) -> RpcResult<PagedVecV2<(Address, u64)>>;
 
     /// Get the ids of best parents for the next block to be produced along with their period
     #[method(name = "get_next_block_best_parents")]
     async fn get_next_block_best_parents(&self) -> RpcResult<Vec<(BlockId, u64)>>;
 
+    /// Get the schedule of upcoming deferred credits (roll sale reimbursements) of the given addresses.
+    #[method(name = "get_deferred_credits_schedule")]
+    async fn get_deferred_credits_schedule(
+        &self,
+        addresses: Vec<Address>,
+    ) -> RpcResult<Vec<AddressDeferredCredits>>;
+
     /// Get Massa node version.
     #[method(name = "get_version")]
     async fn get_version(&self) -> RpcResult<Version>;
 
+    /// Get the provenance of the node binary: commit, compiler, target, profile, features and build hash.
+    #[method(name = "get_build_info")]
+    async fn get_build_info(&self) -> RpcResult<BuildInfo>;
+
     /// New produced block.
     #[subscription(
 		name = "subscribe_new_blocks" => "new_blocks",
@@ -50,6 +69,33 @@ pub trait MassaApi {
 	)]
     fn subscribe_new_filled_blocks(&self);
 
+    /// Announced blocks that became stale or left the blockclique, with their operations,
+    /// and the best parents before and after the change.
+    #[subscription(
+        name = "subscribe_chain_reorgs" => "chain_reorgs",
+        unsubscribe = "unsubscribe_chain_reorgs",
+        item = ChainReorg
+    )]
+    fn subscribe_chain_reorgs(&self);
+
+    /// Status transitions of an operation: pending, in candidate blocks, then final, expired or dropped.
+    /// The subscription ends after a final, expired or dropped status.
+    #[subscription(
+        name = "watch_operation" => "operation_status",
+        unsubscribe = "unwatch_operation",
+        item = OperationStatus
+    )]
+    fn watch_operation(&self, operation_id: OperationId);
+
+    /// Final execution events selected by the filter: those stored from its start slot, then the new ones
+    /// as their slots become final. A subscriber can resume after a downtime from the slot of the last event it received.
+    #[subscription(
+        name = "subscribe_final_events" => "final_events",
+        unsubscribe = "unsubscribe_final_events",
+        item = SCOutputEvent
+    )]
+    fn subscribe_final_events(&self, filter: EventFilter);
+
     /// New produced operations.
     #[subscription(
 		name = "subscribe_new_operations" => "new_operations",
@@ -57,4 +103,14 @@ pub trait MassaApi {
 		item = Operation
 	)]
     fn subscribe_new_operations(&self);
+
+    /// Events of the node on the given topics (final blocks, pooled operations, bans, bootstrap), on all of them if none is given.
+    /// The bootstrap event comes first if the node already finished its bootstrap.
+    /// The subscription fails if the subscriber lags behind the event bus of the node.
+    #[subscription(
+        name = "subscribe_node_events" => "node_events",
+        unsubscribe = "unsubscribe_node_events",
+        item = NodeEvent
+    )]
+    fn subscribe_node_events(&self, topics: Vec<Topic>);
 }
@@ -0,0 +1,178 @@
+//! Copyright (c) 2022 MASSA LABS <info@massa.net>
+//!
+//! Cache of the responses of the expensive read APIs.
+//!
+//! Explorers tend to send the same requests over and over, each one reaching consensus, execution
+//! and the selector. Their responses are kept until the final state moves to a new slot, so that
+//! repeated requests are answered without touching the other modules.
+//! Candidate values in the cached responses may thus lag behind by up to one finalization.
+
+use jsonrpsee::core::RpcResult;
+use massa_models::slot::Slot;
+use massa_models::stats::ApiCacheStats;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Cached responses, all computed while the final state was at `final_slot`
+#[derive(Default)]
+struct CacheContent {
+    final_slot: Option<Slot>,
+    responses: HashMap<(&'static str, String), Box<dyn Any + Send + Sync>>,
+}
+
+pub(crate) struct ApiCache {
+    /// max number of cached responses, 0 disables the cache
+    max_entries: usize,
+    content: Mutex<CacheContent>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl ApiCache {
+    pub(crate) fn new(max_entries: usize) -> Self {
+        ApiCache {
+            max_entries,
+            content: Default::default(),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the cached response of `method` for `params` if the final state did not move
+    /// since it was computed, otherwise computes it with `compute` and caches it.
+    ///
+    /// The lock is not held while computing: concurrent identical requests may both compute.
+    pub(crate) fn get_or_compute<P, T, F>(
+        &self,
+        final_slot: Slot,
+        method: &'static str,
+        params: &P,
+        compute: F,
+    ) -> RpcResult<T>
+    where
+        P: Serialize,
+        T: Clone + Send + Sync + 'static,
+        F: FnOnce() -> RpcResult<T>,
+    {
+        if self.max_entries == 0 {
+            return compute();
+        }
+        let key = (method, serde_json::to_string(params).unwrap_or_default());
+        {
+            let mut content = self.content.lock();
+            if content.final_slot != Some(final_slot) {
+                // a slot was finalized: every cached response may be outdated
+                content.responses.clear();
+                content.final_slot = Some(final_slot);
+            } else if let Some(response) = content
+                .responses
+                .get(&key)
+                .and_then(|response| response.downcast_ref::<T>())
+            {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                return Ok(response.clone());
+            }
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let response = compute()?;
+        let mut content = self.content.lock();
+        // a response computed before the latest finalization must not be cached
+        if content.final_slot == Some(final_slot) && content.responses.len() < self.max_entries {
+            content.responses.insert(key, Box::new(response.clone()));
+        }
+        Ok(response)
+    }
+
+    pub(crate) fn stats(&self) -> ApiCacheStats {
+        ApiCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.content.lock().responses.len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_api_exports::error::ApiError;
+    use std::cell::Cell;
+
+    /// Gets `method` for `params` from `cache`, counting the computations in `computed`
+    fn get(
+        cache: &ApiCache,
+        final_slot: Slot,
+        method: &'static str,
+        params: u64,
+        computed: &Cell<u64>,
+    ) -> u64 {
+        cache
+            .get_or_compute(final_slot, method, &params, || {
+                computed.set(computed.get() + 1);
+                Ok(params * 10)
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_cache_hit_and_miss() {
+        let cache = ApiCache::new(10);
+        let computed = Cell::new(0);
+        let slot = Slot::new(1, 0);
+        assert_eq!(get(&cache, slot, "method", 1, &computed), 10);
+        assert_eq!(get(&cache, slot, "method", 1, &computed), 10);
+        assert_eq!(computed.get(), 1);
+
+        // other parameters or another method are other responses
+        assert_eq!(get(&cache, slot, "method", 2, &computed), 20);
+        assert_eq!(get(&cache, slot, "other_method", 1, &computed), 10);
+        assert_eq!(computed.get(), 3);
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (1, 3, 3));
+    }
+
+    #[test]
+    fn test_cache_invalidated_on_new_final_slot() {
+        let cache = ApiCache::new(10);
+        let computed = Cell::new(0);
+        get(&cache, Slot::new(1, 0), "method", 1, &computed);
+        get(&cache, Slot::new(1, 1), "method", 1, &computed);
+        assert_eq!(computed.get(), 2);
+        assert_eq!(cache.stats().entries, 1);
+
+        // an error is not cached
+        let error = cache.get_or_compute(Slot::new(1, 1), "failing", &(), || {
+            Err::<u64, _>(ApiError::NotFound.into())
+        });
+        assert!(error.is_err());
+        assert_eq!(cache.stats().entries, 1);
+    }
+
+    #[test]
+    fn test_cache_max_entries() {
+        let cache = ApiCache::new(2);
+        let computed = Cell::new(0);
+        let slot = Slot::new(1, 0);
+        for params in 0..3 {
+            get(&cache, slot, "method", params, &computed);
+        }
+        assert_eq!(cache.stats().entries, 2);
+
+        // the response that did not fit is computed again, the cached ones are kept
+        get(&cache, slot, "method", 2, &computed);
+        get(&cache, slot, "method", 0, &computed);
+        assert_eq!(computed.get(), 4);
+
+        // a cache without entries computes every response
+        let cache = ApiCache::new(0);
+        let computed = Cell::new(0);
+        get(&cache, slot, "method", 1, &computed);
+        get(&cache, slot, "method", 1, &computed);
+        assert_eq!(computed.get(), 2);
+        assert_eq!(cache.stats().entries, 0);
+    }
+}
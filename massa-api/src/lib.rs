@@ -4,26 +4,41 @@
 #![warn(missing_docs)]
 #![warn(unused_crate_dependencies)]
 use api_trait::MassaApiServer;
+use cache::ApiCache;
 use hyper::Method;
 use jsonrpsee::core::{Error as JsonRpseeError, RpcResult};
 use jsonrpsee::proc_macros::rpc;
 use jsonrpsee::server::{AllowHosts, ServerBuilder, ServerHandle};
 use jsonrpsee::RpcModule;
 use massa_api_exports::{
-    address::AddressInfo,
-    block::{BlockInfo, BlockSummary},
+    address::{AddressInfo, AddressStakingRewards, AddressStorageRent, VerifiedContract},
+    async_pool::{AsyncMessageInfo, ScheduledTransferInfo},
+    block::{BlockFieldMask, BlockInfo, BlockSummary, GraphPage, GraphPageRequest},
     config::APIConfig,
-    datastore::{DatastoreEntryInput, DatastoreEntryOutput},
+    datastore::{
+        DatastoreEntryChunkInput, DatastoreEntryChunkOutput, DatastoreEntryInput,
+        DatastoreEntryOutput,
+    },
     endorsement::EndorsementInfo,
     error::ApiError::WrongAPI,
-    execution::{ExecuteReadOnlyResponse, ReadOnlyBytecodeExecution, ReadOnlyCall},
-    node::NodeStatus,
+    execution::{
+        DryRunOperationResponse, ExecuteReadOnlyResponse, ReadOnlyBytecodeExecution, ReadOnlyCall,
+    },
+    node::{BuildInfo, NodeStatus},
     operation::{OperationInfo, OperationInput},
     page::{PageRequest, PagedVec},
-    TimeInterval,
+    profiling::{CpuProfile, HeapStats},
+    query::{IndexQuery, QueryRow},
+    selection::AddressSelectionForecast,
+    slot::{SlotBeacon, SlotCalendar, SlotDetail},
+    StakersFilter, TimeInterval,
 };
+use massa_channel::{ChannelStats, EventBus};
 use massa_consensus_exports::{ConsensusChannels, ConsensusController};
 use massa_execution_exports::ExecutionController;
+use massa_final_state::{BackupManifest, FinalState};
+use massa_hash::Hash;
+use massa_logging::{LogLine, RecentLogs};
 use massa_models::clique::Clique;
 use massa_models::composite::PubkeySig;
 use massa_models::node::NodeId;
@@ -31,18 +46,27 @@ use massa_models::operation::OperationId;
 use massa_models::output_event::SCOutputEvent;
 use massa_models::prehash::PreHashSet;
 use massa_models::{
-    address::Address, block::Block, block_id::BlockId, endorsement::EndorsementId,
-    execution::EventFilter, slot::Slot, version::Version,
+    address::Address,
+    block::Block,
+    block_id::BlockId,
+    endorsement::EndorsementId,
+    execution::EventFilter,
+    slot::{SharedSlot, Slot},
+    stats::{BlockProductionStats, DiskStatus, MemoryStatus},
+    version::Version,
 };
-use massa_network_exports::{NetworkCommandSender, NetworkConfig};
+use massa_network_exports::{BanEntry, IpSubnet, NetworkCommandSender, NetworkConfig};
 use massa_pool_exports::{PoolChannels, PoolController};
-use massa_pos_exports::SelectorController;
-use massa_protocol_exports::ProtocolCommandSender;
-use massa_storage::Storage;
+use massa_pos_exports::{PoSHistoryRanges, SelectorController};
+use massa_protocol_exports::{PeerMessageStats, ProtocolCommandSender};
+use massa_storage::{Storage, StorageOwnersDump};
+use massa_time::MassaTime;
+use massa_versioning::MipStatus;
 use massa_wallet::Wallet;
 use parking_lot::RwLock;
 use serde_json::Value;
 use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::mpsc;
 use tower_http::cors::{Any, CorsLayer};
@@ -50,8 +74,13 @@ use tracing::{info, warn};
 
 mod api;
 mod api_trait;
+mod cache;
 mod private;
+mod private_relay;
+#[cfg(feature = "profiling")]
+mod profiling;
 mod public;
+mod verified_contracts;
 
 /// Public API component
 pub struct Public {
@@ -73,16 +102,32 @@ pub struct Public {
     pub network_settings: NetworkConfig,
     /// node version (TODO remove, can be retrieved via an endpoint)
     pub version: Version,
+    /// provenance of the node binary
+    pub build_info: BuildInfo,
     /// link to the network component
     pub network_command_sender: NetworkCommandSender,
     /// our node id
     pub node_id: NodeId,
+    /// disk status, updated by the disk monitor
+    pub disk_status: Arc<RwLock<DiskStatus>>,
+    /// resident memory and live cache capacities, updated by the memory monitor
+    pub memory_status: Arc<RwLock<MemoryStatus>>,
+    /// timings of the latest blocks produced by the node, updated by the block factory
+    pub production_stats: Arc<RwLock<BlockProductionStats>>,
+    /// final state
+    pub final_state: Arc<RwLock<FinalState>>,
+    /// slot of the final state, whose changes invalidate the cached responses
+    pub final_slot: SharedSlot,
+    /// cache of the expensive read responses
+    pub(crate) cache: ApiCache,
 }
 
 /// Private API content
 pub struct Private {
     /// link to the network component
     pub network_command_sender: NetworkCommandSender,
+    /// link to the protocol component
+    pub protocol_command_sender: ProtocolCommandSender,
     /// link to the execution component
     pub execution_controller: Box<dyn ExecutionController>,
     /// API settings
@@ -91,6 +136,12 @@ pub struct Private {
     pub stop_node_channel: mpsc::Sender<()>,
     /// User wallet
     pub node_wallet: Arc<RwLock<Wallet>>,
+    /// shared storage, for diagnostics
+    pub storage: Storage,
+    /// final state, for backups
+    pub final_state: Arc<RwLock<FinalState>>,
+    /// recent log lines of the node
+    pub recent_logs: RecentLogs,
 }
 
 /// API v2 content
@@ -103,10 +154,16 @@ pub struct ApiV2 {
     pub execution_controller: Box<dyn ExecutionController>,
     /// link(channels) to the pool component
     pub pool_channels: PoolChannels,
+    /// event bus of the node
+    pub event_bus: EventBus,
+    /// link to the storage component
+    pub storage: Storage,
     /// API settings
     pub api_settings: APIConfig,
     /// node version
     pub version: Version,
+    /// provenance of the node binary
+    pub build_info: BuildInfo,
 }
 
 /// The API wrapper
@@ -235,6 +292,30 @@ pub trait MassaRpc {
         arg: Vec<ReadOnlyCall>,
     ) -> RpcResult<Vec<ExecuteReadOnlyResponse>>;
 
+    /// Execute a signed operation against the candidate state, without pooling nor propagating it.
+    /// Returns the balance and roll changes, events and errors it would cause.
+    #[method(name = "dry_run_operation")]
+    async fn dry_run_operation(&self, arg: OperationInput) -> RpcResult<DryRunOperationResponse>;
+
+    /// Create a temporary copy of the candidate state named `name`, to simulate a sequence of operations with
+    /// `execute_operations_in_fork` and read-only executions in the fork. Returns the latest candidate slot copied.
+    /// An unused fork is discarded after a while.
+    #[method(name = "create_execution_fork")]
+    async fn create_execution_fork(&self, name: String) -> RpcResult<Slot>;
+
+    /// Execute signed operations one after the other in the execution fork `name`, keeping their changes in it.
+    /// The operations are neither pooled nor propagated.
+    #[method(name = "execute_operations_in_fork")]
+    async fn execute_operations_in_fork(
+        &self,
+        name: String,
+        ops: Vec<OperationInput>,
+    ) -> RpcResult<Vec<ExecuteReadOnlyResponse>>;
+
+    /// Discard the execution fork `name`.
+    #[method(name = "discard_execution_fork")]
+    async fn discard_execution_fork(&self, name: String) -> RpcResult<()>;
+
     /// Remove a vector of addresses used to stake.
     /// No confirmation to expect.
     #[method(name = "remove_staking_addresses")]
@@ -244,7 +325,21 @@ pub trait MassaRpc {
     #[method(name = "get_staking_addresses")]
     async fn get_staking_addresses(&self) -> RpcResult<PreHashSet<Address>>;
 
-    /// Bans given IP address(es).
+    /// Resume the block and endorsement production of staking addresses disabled before.
+    /// No confirmation to expect.
+    #[method(name = "enable_staking_addresses")]
+    async fn enable_staking_addresses(&self, arg: Vec<Address>) -> RpcResult<()>;
+
+    /// Pause the block and endorsement production of staking addresses, keeping their keys.
+    /// No confirmation to expect.
+    #[method(name = "disable_staking_addresses")]
+    async fn disable_staking_addresses(&self, arg: Vec<Address>) -> RpcResult<()>;
+
+    /// Return hash set of the staking addresses whose production is disabled.
+    #[method(name = "get_disabled_staking_addresses")]
+    async fn get_disabled_staking_addresses(&self) -> RpcResult<PreHashSet<Address>>;
+
+    /// Bans given IP address(es) until they are unbanned, across restarts.
     /// No confirmation to expect.
     #[method(name = "node_ban_by_ip")]
     async fn node_ban_by_ip(&self, arg: Vec<IpAddr>) -> RpcResult<()>;
@@ -258,6 +353,10 @@ pub trait MassaRpc {
     #[method(name = "node_peers_whitelist")]
     async fn node_peers_whitelist(&self) -> RpcResult<Vec<IpAddr>>;
 
+    /// Returns the message counters and the detected anomalies of each connected peer.
+    #[method(name = "node_peer_message_stats")]
+    async fn node_peer_message_stats(&self) -> RpcResult<Vec<PeerMessageStats>>;
+
     /// Add IP address(es) to node peers whitelist.
     /// No confirmation to expect.
     /// Note: If the ip was unknown it adds it to the known peers, otherwise it updates the peer type
@@ -309,6 +408,74 @@ pub trait MassaRpc {
     #[method(name = "node_unban_by_id")]
     async fn node_unban_by_id(&self, arg: Vec<NodeId>) -> RpcResult<()>;
 
+    /// Bans a range of IP addresses in CIDR notation, for `duration` milliseconds or permanently.
+    /// The ban is kept across restarts and the connections from the range are closed.
+    #[method(name = "node_ban_subnet")]
+    async fn node_ban_subnet(
+        &self,
+        subnet: IpSubnet,
+        duration: Option<MassaTime>,
+        reason: String,
+    ) -> RpcResult<()>;
+
+    /// Lifts the bans of given IP ranges.
+    #[method(name = "node_unban_subnet")]
+    async fn node_unban_subnet(&self, arg: Vec<IpSubnet>) -> RpcResult<()>;
+
+    /// Returns the bans of IP ranges and addresses.
+    #[method(name = "node_list_bans")]
+    async fn node_list_bans(&self) -> RpcResult<Vec<BanEntry>>;
+
+    /// Objects in storage along with the modules referencing them.
+    /// Requires storage diagnostics to be enabled in the node configuration.
+    #[method(name = "get_storage_diagnostics")]
+    async fn get_storage_diagnostics(&self) -> RpcResult<StorageOwnersDump>;
+
+    /// Capacity, overflow policy, load and lag of the channels between the workers of the node.
+    /// No confirmation to expect.
+    #[method(name = "get_channel_stats")]
+    async fn get_channel_stats(&self) -> RpcResult<Vec<ChannelStats>>;
+
+    /// Latest `count` log lines of `level` (error, warn, info, debug or trace) or of a more severe level, oldest first.
+    /// Only the lines at the logging level of the node are kept.
+    #[method(name = "get_recent_logs")]
+    async fn get_recent_logs(&self, level: String, count: usize) -> RpcResult<Vec<LogLine>>;
+
+    /// Start sampling the CPU stacks of the node at the given frequency in Hz.
+    /// Requires a node built with the `profiling` feature.
+    #[method(name = "node_start_profiling")]
+    async fn node_start_profiling(&self, frequency: u32) -> RpcResult<()>;
+
+    /// Stop the CPU profiling session and return the sampled stacks in the folded format of flamegraphs.
+    #[method(name = "node_stop_profiling")]
+    async fn node_stop_profiling(&self) -> RpcResult<CpuProfile>;
+
+    /// Heap statistics of the node process.
+    /// Requires a node built with the `profiling` feature.
+    #[method(name = "get_heap_stats")]
+    async fn get_heap_stats(&self) -> RpcResult<HeapStats>;
+
+    /// Write a consistent backup of the final state (ledger checkpoint, in-memory state and manifest)
+    /// in a new directory of the node machine, without stopping the node.
+    /// Restore it by starting the node with `--restore-backup <directory>`.
+    #[method(name = "node_create_backup")]
+    async fn node_create_backup(&self, arg: PathBuf) -> RpcResult<BackupManifest>;
+
+    /// Registers the source of contracts verified by the node operator, replacing the previous registration of the same bytecode.
+    /// Addresses whose bytecode has a registered hash are shown as verified by `get_addresses`.
+    /// No confirmation to expect.
+    #[method(name = "node_add_verified_contracts")]
+    async fn node_add_verified_contracts(&self, arg: Vec<VerifiedContract>) -> RpcResult<()>;
+
+    /// Removes the verified contracts of given bytecode hashes.
+    /// No confirmation to expect.
+    #[method(name = "node_remove_verified_contracts")]
+    async fn node_remove_verified_contracts(&self, arg: Vec<Hash>) -> RpcResult<()>;
+
+    /// Returns the contracts verified by the node operator.
+    #[method(name = "node_verified_contracts")]
+    async fn node_verified_contracts(&self) -> RpcResult<Vec<VerifiedContract>>;
+
     /// Summary of the current state: time, last final blocks (hash, thread, slot, timestamp), clique count, connected nodes count.
     #[method(name = "get_status")]
     async fn get_status(&self) -> RpcResult<NodeStatus>;
@@ -317,13 +484,31 @@ pub trait MassaRpc {
     #[method(name = "get_cliques")]
     async fn get_cliques(&self) -> RpcResult<Vec<Clique>>;
 
-    /// Returns the active stakers and their active roll counts for the current cycle.
+    /// Returns the active stakers and their active roll counts for the current cycle,
+    /// sorted by largest roll counts and optionally filtered by a minimum roll count.
     #[method(name = "get_stakers")]
     async fn get_stakers(
         &self,
         page_request: Option<PageRequest>,
+        filter: Option<StakersFilter>,
     ) -> RpcResult<PagedVec<(Address, u64)>>;
 
+    /// Returns the messages of the final asynchronous pool, in the order in which they are executed:
+    /// by decreasing fee per gas, priority fee included.
+    #[method(name = "get_async_pool_messages")]
+    async fn get_async_pool_messages(
+        &self,
+        page_request: Option<PageRequest>,
+    ) -> RpcResult<PagedVec<AsyncMessageInfo>>;
+
+    /// Returns the pending scheduled transfers sent by the given addresses, final or candidate,
+    /// sorted by activation slot. They can be cancelled until then with a `CancelScheduledTransaction` operation.
+    #[method(name = "get_scheduled_transfers")]
+    async fn get_scheduled_transfers(
+        &self,
+        arg: Vec<Address>,
+    ) -> RpcResult<Vec<ScheduledTransferInfo>>;
+
     /// Returns operation(s) information associated to a given list of operation(s) ID(s).
     #[method(name = "get_operations")]
     async fn get_operations(&self, arg: Vec<OperationId>) -> RpcResult<Vec<OperationInfo>>;
@@ -332,20 +517,51 @@ pub trait MassaRpc {
     #[method(name = "get_endorsements")]
     async fn get_endorsements(&self, arg: Vec<EndorsementId>) -> RpcResult<Vec<EndorsementInfo>>;
 
-    /// Returns block(s) information associated to a given list of block(s) ID(s)
+    /// Returns block(s) information associated to a given list of block(s) ID(s),
+    /// optionally restricted to their headers or their statuses.
     #[method(name = "get_blocks")]
-    async fn get_blocks(&self, arg: Vec<BlockId>) -> RpcResult<Vec<BlockInfo>>;
+    async fn get_blocks(
+        &self,
+        arg: Vec<BlockId>,
+        fields: Option<BlockFieldMask>,
+    ) -> RpcResult<Vec<BlockInfo>>;
 
     /// Get information on the block at a slot in the blockclique.
     /// If there is no block at this slot a `None` is returned.
     #[method(name = "get_blockclique_block_by_slot")]
     async fn get_blockclique_block_by_slot(&self, arg: Slot) -> RpcResult<Option<Block>>;
 
+    /// Get the blockclique block at a slot with its operations, the events produced by the slot
+    /// and a summary of its state changes, to index the chain with one call per slot.
+    #[method(name = "get_slot_detail")]
+    async fn get_slot_detail(&self, arg: Slot) -> RpcResult<SlotDetail>;
+
+    /// Get the random beacons of final slots.
+    /// They are derived from the PoS draw seeds and can be recomputed by anyone from the final state.
+    #[method(name = "get_slot_beacons")]
+    async fn get_slot_beacons(&self, arg: Vec<Slot>) -> RpcResult<Vec<SlotBeacon>>;
+
+    /// Get the ranges of cycles and deferred credits that the PoS final state can still be queried for,
+    /// which depend on the retention settings of the node.
+    #[method(name = "get_pos_history_ranges")]
+    async fn get_pos_history_ranges(&self) -> RpcResult<PoSHistoryRanges>;
+
+    /// Get the slots expected in a time range, from `<time_start>` (included, genesis by default)
+    /// to `<time_end>` (excluded, required), with their timestamps and the bounds of their cycles.
+    #[method(name = "get_slot_calendar")]
+    async fn get_slot_calendar(&self, arg: TimeInterval) -> RpcResult<SlotCalendar>;
+
     /// Get the block graph within the specified time interval.
     /// Optional parameters: from `<time_start>` (included) and to `<time_end>` (excluded) millisecond timestamp
     #[method(name = "get_graph_interval")]
     async fn get_graph_interval(&self, arg: TimeInterval) -> RpcResult<Vec<BlockSummary>>;
 
+    /// Get a page of the block graph within the specified time interval, sorted by slot then id,
+    /// with the ids, the summaries or the headers of the blocks.
+    /// The following page is requested with the `next_page_token` of the response.
+    #[method(name = "get_graph_page")]
+    async fn get_graph_page(&self, arg: GraphPageRequest) -> RpcResult<GraphPage>;
+
     /// Get multiple datastore entries.
     #[method(name = "get_datastore_entries")]
     async fn get_datastore_entries(
@@ -353,14 +569,50 @@ pub trait MassaRpc {
         arg: Vec<DatastoreEntryInput>,
     ) -> RpcResult<Vec<DatastoreEntryOutput>>;
 
+    /// Get chunks of the values of datastore entries, so that large values can be fetched incrementally.
+    /// Each chunk has at most the configured max datastore chunk size.
+    #[method(name = "get_datastore_entry_chunks")]
+    async fn get_datastore_entry_chunks(
+        &self,
+        arg: Vec<DatastoreEntryChunkInput>,
+    ) -> RpcResult<Vec<DatastoreEntryChunkOutput>>;
+
     /// Get addresses.
     #[method(name = "get_addresses")]
     async fn get_addresses(&self, arg: Vec<Address>) -> RpcResult<Vec<AddressInfo>>;
 
+    /// Get the upcoming selections of addresses: concrete draws for the cycles already drawn,
+    /// and production estimates based on the latest roll distribution for the following ones,
+    /// up to `cycle_count` cycles after the current one.
+    #[method(name = "get_selection_forecast")]
+    async fn get_selection_forecast(
+        &self,
+        arg: Vec<Address>,
+        cycle_count: u64,
+    ) -> RpcResult<Vec<AddressSelectionForecast>>;
+
+    /// Get the staking rewards credited to addresses, per cycle.
+    #[method(name = "get_staking_rewards")]
+    async fn get_staking_rewards(&self, arg: Vec<Address>)
+        -> RpcResult<Vec<AddressStakingRewards>>;
+
+    /// Get the storage rent of addresses: the bytes they are charged for, the rent per cycle,
+    /// and when their storage is reclaimed if they do not pay it.
+    #[method(name = "get_storage_rent")]
+    async fn get_storage_rent(&self, arg: Vec<Address>) -> RpcResult<Vec<AddressStorageRent>>;
+
     /// Adds operations to pool. Returns operations that were ok and sent to pool.
     #[method(name = "send_operations")]
     async fn send_operations(&self, arg: Vec<OperationInput>) -> RpcResult<Vec<OperationId>>;
 
+    /// Adds operations to pool without gossiping them: until the private relay delay is over,
+    /// only the blocks produced by this node can include them. Returns operations that were ok and sent to pool.
+    #[method(name = "send_private_operations")]
+    async fn send_private_operations(
+        &self,
+        arg: Vec<OperationInput>,
+    ) -> RpcResult<Vec<OperationId>>;
+
     /// Get events optionally filtered by:
     /// * start slot
     /// * end slot
@@ -371,6 +623,15 @@ pub trait MassaRpc {
     async fn get_filtered_sc_output_event(&self, arg: EventFilter)
         -> RpcResult<Vec<SCOutputEvent>>;
 
+    /// Query a node-local index: operations by creator, events by emitter or blocks by creator.
+    /// The rows of an address can be filtered on their fields, sorted and limited.
+    #[method(name = "query_index")]
+    async fn query_index(&self, arg: IndexQuery) -> RpcResult<Vec<QueryRow>>;
+
+    /// Get the status of the MIPs known by the node: their vote, state and activation time.
+    #[method(name = "get_mip_status")]
+    async fn get_mip_status(&self) -> RpcResult<Vec<MipStatus>>;
+
     /// Get OpenRPC specification.
     #[method(name = "rpc.discover")]
     async fn get_openrpc_spec(&self) -> RpcResult<Value>;
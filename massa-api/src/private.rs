@@ -1,24 +1,39 @@
 //! Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use crate::verified_contracts::{read_verified_contracts, write_verified_contracts};
 use crate::{MassaRpcServer, Private, RpcServer, StopHandle, Value, API};
 
 use async_trait::async_trait;
 use itertools::Itertools;
 use jsonrpsee::core::{Error as JsonRpseeError, RpcResult};
 use massa_api_exports::{
-    address::AddressInfo,
-    block::{BlockInfo, BlockSummary},
+    address::{AddressInfo, AddressStakingRewards, AddressStorageRent, VerifiedContract},
+    async_pool::{AsyncMessageInfo, ScheduledTransferInfo},
+    block::{BlockFieldMask, BlockInfo, BlockSummary, GraphPage, GraphPageRequest},
     config::APIConfig,
-    datastore::{DatastoreEntryInput, DatastoreEntryOutput},
+    datastore::{
+        DatastoreEntryChunkInput, DatastoreEntryChunkOutput, DatastoreEntryInput,
+        DatastoreEntryOutput,
+    },
     endorsement::EndorsementInfo,
     error::ApiError,
-    execution::{ExecuteReadOnlyResponse, ReadOnlyBytecodeExecution, ReadOnlyCall},
+    execution::{
+        DryRunOperationResponse, ExecuteReadOnlyResponse, ReadOnlyBytecodeExecution, ReadOnlyCall,
+    },
     node::NodeStatus,
     operation::{OperationInfo, OperationInput},
     page::{PageRequest, PagedVec},
-    ListType, ScrudOperation, TimeInterval,
+    profiling::{CpuProfile, HeapStats},
+    query::{IndexQuery, QueryRow},
+    selection::AddressSelectionForecast,
+    slot::{SlotBeacon, SlotCalendar, SlotDetail},
+    ListType, ScrudOperation, StakersFilter, TimeInterval,
 };
+use massa_channel::{channel_stats, ChannelStats};
 use massa_execution_exports::ExecutionController;
+use massa_final_state::{BackupManifest, FinalState};
+use massa_hash::Hash;
+use massa_logging::{LogLine, RecentLogs};
 use massa_models::clique::Clique;
 use massa_models::composite::PubkeySig;
 use massa_models::node::NodeId;
@@ -28,8 +43,13 @@ use massa_models::{
     address::Address, block::Block, block_id::BlockId, endorsement::EndorsementId,
     execution::EventFilter, operation::OperationId, slot::Slot,
 };
-use massa_network_exports::NetworkCommandSender;
+use massa_network_exports::{BanEntry, IpSubnet, NetworkCommandSender};
+use massa_pos_exports::PoSHistoryRanges;
+use massa_protocol_exports::{PeerMessageStats, ProtocolCommandSender};
 use massa_signature::KeyPair;
+use massa_storage::{Storage, StorageOwnersDump};
+use massa_time::MassaTime;
+use massa_versioning::MipStatus;
 use massa_wallet::Wallet;
 
 use parking_lot::RwLock;
@@ -40,23 +60,32 @@ use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::Arc;
 use tokio::sync::mpsc;
+use tracing::{info, Level};
 
 impl API<Private> {
     /// generate a new private API
     pub fn new(
         network_command_sender: NetworkCommandSender,
+        protocol_command_sender: ProtocolCommandSender,
         execution_controller: Box<dyn ExecutionController>,
         api_settings: APIConfig,
         node_wallet: Arc<RwLock<Wallet>>,
+        storage: Storage,
+        final_state: Arc<RwLock<FinalState>>,
+        recent_logs: RecentLogs,
     ) -> (Self, mpsc::Receiver<()>) {
         let (stop_node_channel, rx) = mpsc::channel(1);
         (
             API(Private {
                 network_command_sender,
+                protocol_command_sender,
                 execution_controller,
                 api_settings,
                 stop_node_channel,
                 node_wallet,
+                storage,
+                final_state,
+                recent_logs,
             }),
             rx,
         )
@@ -121,6 +150,26 @@ impl MassaRpcServer for API<Private> {
         crate::wrong_api::<_>()
     }
 
+    async fn dry_run_operation(&self, _: OperationInput) -> RpcResult<DryRunOperationResponse> {
+        crate::wrong_api::<_>()
+    }
+
+    async fn create_execution_fork(&self, _: String) -> RpcResult<Slot> {
+        crate::wrong_api::<_>()
+    }
+
+    async fn execute_operations_in_fork(
+        &self,
+        _: String,
+        _: Vec<OperationInput>,
+    ) -> RpcResult<Vec<ExecuteReadOnlyResponse>> {
+        crate::wrong_api::<_>()
+    }
+
+    async fn discard_execution_fork(&self, _: String) -> RpcResult<()> {
+        crate::wrong_api::<_>()
+    }
+
     async fn remove_staking_addresses(&self, addresses: Vec<Address>) -> RpcResult<()> {
         let node_wallet = self.0.node_wallet.clone();
         let mut w_wallet = node_wallet.write();
@@ -135,6 +184,28 @@ impl MassaRpcServer for API<Private> {
         Ok(w_wallet.get_wallet_address_list())
     }
 
+    async fn enable_staking_addresses(&self, addresses: Vec<Address>) -> RpcResult<()> {
+        let node_wallet = self.0.node_wallet.clone();
+        let mut w_wallet = node_wallet.write();
+        w_wallet
+            .set_staking_enabled(&addresses, true)
+            .map_err(|e| ApiError::WalletError(e).into())
+    }
+
+    async fn disable_staking_addresses(&self, addresses: Vec<Address>) -> RpcResult<()> {
+        let node_wallet = self.0.node_wallet.clone();
+        let mut w_wallet = node_wallet.write();
+        w_wallet
+            .set_staking_enabled(&addresses, false)
+            .map_err(|e| ApiError::WalletError(e).into())
+    }
+
+    async fn get_disabled_staking_addresses(&self) -> RpcResult<PreHashSet<Address>> {
+        let node_wallet = self.0.node_wallet.clone();
+        let w_wallet = node_wallet.read();
+        Ok(w_wallet.disabled_addresses.clone())
+    }
+
     async fn node_ban_by_ip(&self, ips: Vec<IpAddr>) -> RpcResult<()> {
         let network_command_sender = self.0.network_command_sender.clone();
         network_command_sender
@@ -159,6 +230,42 @@ impl MassaRpcServer for API<Private> {
             .map_err(|e| ApiError::NetworkError(e).into())
     }
 
+    async fn node_ban_subnet(
+        &self,
+        subnet: IpSubnet,
+        duration: Option<MassaTime>,
+        reason: String,
+    ) -> RpcResult<()> {
+        let created = MassaTime::now().map_err(ApiError::from)?;
+        let entry = BanEntry {
+            subnet,
+            reason,
+            created,
+            expiry: duration.map(|duration| created.saturating_add(duration)),
+        };
+        let network_command_sender = self.0.network_command_sender.clone();
+        network_command_sender
+            .node_ban_subnet(entry)
+            .await
+            .map_err(|e| ApiError::NetworkError(e).into())
+    }
+
+    async fn node_unban_subnet(&self, subnets: Vec<IpSubnet>) -> RpcResult<()> {
+        let network_command_sender = self.0.network_command_sender.clone();
+        network_command_sender
+            .node_unban_subnets(subnets)
+            .await
+            .map_err(|e| ApiError::NetworkError(e).into())
+    }
+
+    async fn node_list_bans(&self) -> RpcResult<Vec<BanEntry>> {
+        let network_command_sender = self.0.network_command_sender.clone();
+        network_command_sender
+            .get_bans()
+            .await
+            .map_err(|e| ApiError::NetworkError(e).into())
+    }
+
     async fn node_unban_by_ip(&self, ips: Vec<IpAddr>) -> RpcResult<()> {
         let network_command_sender = self.0.network_command_sender.clone();
         network_command_sender
@@ -167,6 +274,120 @@ impl MassaRpcServer for API<Private> {
             .map_err(|e| ApiError::NetworkError(e).into())
     }
 
+    async fn get_storage_diagnostics(&self) -> RpcResult<StorageOwnersDump> {
+        self.0.storage.get_owners_dump().ok_or_else(|| {
+            ApiError::MissingConfig(
+                "storage diagnostics are disabled, enable them with storage.diagnostics".into(),
+            )
+            .into()
+        })
+    }
+
+    async fn get_channel_stats(&self) -> RpcResult<Vec<ChannelStats>> {
+        Ok(channel_stats())
+    }
+
+    async fn get_recent_logs(&self, level: String, count: usize) -> RpcResult<Vec<LogLine>> {
+        let level = Level::from_str(&level)
+            .map_err(|_| ApiError::BadRequest(format!("unknown log level {}", level)))?;
+        if count > self.0.api_settings.max_arguments as usize {
+            return Err(ApiError::BadRequest("too many arguments".into()).into());
+        }
+        Ok(self.0.recent_logs.recent(level, count))
+    }
+
+    #[cfg(feature = "profiling")]
+    async fn node_start_profiling(&self, frequency: u32) -> RpcResult<()> {
+        if !(1..=1000).contains(&frequency) {
+            return Err(ApiError::BadRequest(
+                "the sampling frequency must be between 1 and 1000 Hz".into(),
+            )
+            .into());
+        }
+        crate::profiling::start_cpu_profiling(frequency)?;
+        info!("CPU profiling started at {} Hz", frequency);
+        Ok(())
+    }
+
+    #[cfg(not(feature = "profiling"))]
+    async fn node_start_profiling(&self, _: u32) -> RpcResult<()> {
+        Err(profiling_disabled())
+    }
+
+    #[cfg(feature = "profiling")]
+    async fn node_stop_profiling(&self) -> RpcResult<CpuProfile> {
+        let profile = tokio::task::spawn_blocking(crate::profiling::stop_cpu_profiling)
+            .await
+            .map_err(|err| ApiError::InternalServerError(err.to_string()))??;
+        info!(
+            "CPU profiling stopped after {} samples",
+            profile.sample_count
+        );
+        Ok(profile)
+    }
+
+    #[cfg(not(feature = "profiling"))]
+    async fn node_stop_profiling(&self) -> RpcResult<CpuProfile> {
+        Err(profiling_disabled())
+    }
+
+    #[cfg(feature = "profiling")]
+    async fn get_heap_stats(&self) -> RpcResult<HeapStats> {
+        Ok(crate::profiling::heap_stats())
+    }
+
+    #[cfg(not(feature = "profiling"))]
+    async fn get_heap_stats(&self) -> RpcResult<HeapStats> {
+        Err(profiling_disabled())
+    }
+
+    async fn node_create_backup(&self, arg: PathBuf) -> RpcResult<BackupManifest> {
+        // the read lock prevents slots from being finalized while the backup is written
+        let manifest = self
+            .0
+            .final_state
+            .read()
+            .create_backup(&arg)
+            .map_err(|err| ApiError::InternalServerError(err.to_string()))?;
+        info!(
+            "final state backup written in {} at slot {}",
+            arg.display(),
+            manifest.slot
+        );
+        Ok(manifest)
+    }
+
+    async fn node_add_verified_contracts(&self, contracts: Vec<VerifiedContract>) -> RpcResult<()> {
+        let path = &self.0.api_settings.verified_contracts_path;
+        let mut verified_contracts = read_verified_contracts(path)?;
+        for contract in contracts {
+            info!(
+                "contract {} verified for bytecode {}",
+                contract.name, contract.bytecode_hash
+            );
+            verified_contracts.insert(contract.bytecode_hash, contract);
+        }
+        write_verified_contracts(path, verified_contracts)
+    }
+
+    async fn node_remove_verified_contracts(&self, bytecode_hashes: Vec<Hash>) -> RpcResult<()> {
+        let path = &self.0.api_settings.verified_contracts_path;
+        let mut verified_contracts = read_verified_contracts(path)?;
+        for hash in &bytecode_hashes {
+            verified_contracts.remove(hash);
+        }
+        write_verified_contracts(path, verified_contracts)
+    }
+
+    async fn node_verified_contracts(&self) -> RpcResult<Vec<VerifiedContract>> {
+        let mut contracts: Vec<VerifiedContract> =
+            read_verified_contracts(&self.0.api_settings.verified_contracts_path)?
+                .into_values()
+                .collect();
+        contracts.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(contracts)
+    }
+
     async fn get_status(&self) -> RpcResult<NodeStatus> {
         crate::wrong_api::<NodeStatus>()
     }
@@ -175,10 +396,28 @@ impl MassaRpcServer for API<Private> {
         crate::wrong_api::<Vec<Clique>>()
     }
 
-    async fn get_stakers(&self, _: Option<PageRequest>) -> RpcResult<PagedVec<(Address, u64)>> {
+    async fn get_stakers(
+        &self,
+        _: Option<PageRequest>,
+        _: Option<StakersFilter>,
+    ) -> RpcResult<PagedVec<(Address, u64)>> {
         crate::wrong_api::<PagedVec<(Address, u64)>>()
     }
 
+    async fn get_async_pool_messages(
+        &self,
+        _: Option<PageRequest>,
+    ) -> RpcResult<PagedVec<AsyncMessageInfo>> {
+        crate::wrong_api::<PagedVec<AsyncMessageInfo>>()
+    }
+
+    async fn get_scheduled_transfers(
+        &self,
+        _: Vec<Address>,
+    ) -> RpcResult<Vec<ScheduledTransferInfo>> {
+        crate::wrong_api::<Vec<ScheduledTransferInfo>>()
+    }
+
     async fn get_operations(&self, _: Vec<OperationId>) -> RpcResult<Vec<OperationInfo>> {
         crate::wrong_api::<Vec<OperationInfo>>()
     }
@@ -187,7 +426,11 @@ impl MassaRpcServer for API<Private> {
         crate::wrong_api::<Vec<EndorsementInfo>>()
     }
 
-    async fn get_blocks(&self, _: Vec<BlockId>) -> RpcResult<Vec<BlockInfo>> {
+    async fn get_blocks(
+        &self,
+        _: Vec<BlockId>,
+        _: Option<BlockFieldMask>,
+    ) -> RpcResult<Vec<BlockInfo>> {
         crate::wrong_api::<Vec<BlockInfo>>()
     }
 
@@ -195,10 +438,30 @@ impl MassaRpcServer for API<Private> {
         crate::wrong_api::<Option<Block>>()
     }
 
+    async fn get_slot_detail(&self, _: Slot) -> RpcResult<SlotDetail> {
+        crate::wrong_api::<SlotDetail>()
+    }
+
+    async fn get_slot_beacons(&self, _: Vec<Slot>) -> RpcResult<Vec<SlotBeacon>> {
+        crate::wrong_api::<Vec<SlotBeacon>>()
+    }
+
+    async fn get_pos_history_ranges(&self) -> RpcResult<PoSHistoryRanges> {
+        crate::wrong_api::<PoSHistoryRanges>()
+    }
+
+    async fn get_slot_calendar(&self, _: TimeInterval) -> RpcResult<SlotCalendar> {
+        crate::wrong_api::<SlotCalendar>()
+    }
+
     async fn get_graph_interval(&self, _: TimeInterval) -> RpcResult<Vec<BlockSummary>> {
         crate::wrong_api::<Vec<BlockSummary>>()
     }
 
+    async fn get_graph_page(&self, _: GraphPageRequest) -> RpcResult<GraphPage> {
+        crate::wrong_api::<GraphPage>()
+    }
+
     async fn get_datastore_entries(
         &self,
         _: Vec<DatastoreEntryInput>,
@@ -206,18 +469,49 @@ impl MassaRpcServer for API<Private> {
         crate::wrong_api()
     }
 
+    async fn get_datastore_entry_chunks(
+        &self,
+        _: Vec<DatastoreEntryChunkInput>,
+    ) -> RpcResult<Vec<DatastoreEntryChunkOutput>> {
+        crate::wrong_api()
+    }
+
     async fn get_addresses(&self, _: Vec<Address>) -> RpcResult<Vec<AddressInfo>> {
         crate::wrong_api::<Vec<AddressInfo>>()
     }
 
+    async fn get_selection_forecast(
+        &self,
+        _: Vec<Address>,
+        _: u64,
+    ) -> RpcResult<Vec<AddressSelectionForecast>> {
+        crate::wrong_api::<Vec<AddressSelectionForecast>>()
+    }
+
+    async fn get_staking_rewards(&self, _: Vec<Address>) -> RpcResult<Vec<AddressStakingRewards>> {
+        crate::wrong_api::<Vec<AddressStakingRewards>>()
+    }
+
+    async fn get_storage_rent(&self, _: Vec<Address>) -> RpcResult<Vec<AddressStorageRent>> {
+        crate::wrong_api::<Vec<AddressStorageRent>>()
+    }
+
     async fn send_operations(&self, _: Vec<OperationInput>) -> RpcResult<Vec<OperationId>> {
         crate::wrong_api::<Vec<OperationId>>()
     }
 
+    async fn send_private_operations(&self, _: Vec<OperationInput>) -> RpcResult<Vec<OperationId>> {
+        crate::wrong_api::<Vec<OperationId>>()
+    }
+
     async fn get_filtered_sc_output_event(&self, _: EventFilter) -> RpcResult<Vec<SCOutputEvent>> {
         crate::wrong_api::<Vec<SCOutputEvent>>()
     }
 
+    async fn query_index(&self, _: IndexQuery) -> RpcResult<Vec<QueryRow>> {
+        crate::wrong_api::<Vec<QueryRow>>()
+    }
+
     async fn node_peers_whitelist(&self) -> RpcResult<Vec<IpAddr>> {
         let network_command_sender = self.0.network_command_sender.clone();
         match network_command_sender.get_peers().await {
@@ -226,6 +520,14 @@ impl MassaRpcServer for API<Private> {
         }
     }
 
+    async fn node_peer_message_stats(&self) -> RpcResult<Vec<PeerMessageStats>> {
+        self.0
+            .protocol_command_sender
+            .get_peer_message_stats()
+            .await
+            .map_err(|e| ApiError::ProtocolError(e).into())
+    }
+
     async fn node_add_to_peers_whitelist(&self, ips: Vec<IpAddr>) -> RpcResult<()> {
         let network_command_sender = self.0.network_command_sender.clone();
         network_command_sender
@@ -302,6 +604,10 @@ impl MassaRpcServer for API<Private> {
         )
     }
 
+    async fn get_mip_status(&self) -> RpcResult<Vec<MipStatus>> {
+        crate::wrong_api::<Vec<MipStatus>>()
+    }
+
     async fn get_openrpc_spec(&self) -> RpcResult<Value> {
         crate::wrong_api::<Value>()
     }
@@ -442,3 +748,12 @@ fn write_ips_to_jsonfile(
             })
         })
 }
+
+/// Error of the profiling methods of a node built without the `profiling` feature
+#[cfg(not(feature = "profiling"))]
+fn profiling_disabled() -> JsonRpseeError {
+    ApiError::MissingConfig(
+        "profiling is not available, build the node with the profiling feature".into(),
+    )
+    .into()
+}
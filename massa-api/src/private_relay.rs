@@ -0,0 +1,76 @@
+//! Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Release of the operations sent with `send_private_operations`.
+//!
+//! Private operations are added to the pool without being gossiped, so that only the blocks produced by this node
+//! can include them. Once the relay delay is over, the ones that were not included in a block
+//! are either propagated to the network or removed from the pool.
+
+use massa_api_exports::config::PrivateRelayExpiry;
+use massa_execution_exports::ExecutionController;
+use massa_models::{operation::OperationId, prehash::PreHashSet};
+use massa_pool_exports::PoolController;
+use massa_protocol_exports::ProtocolCommandSender;
+use massa_storage::Storage;
+use massa_time::MassaTime;
+use tracing::{info, warn};
+
+/// Waits for the end of the relay delay of the private operations referenced by `operations`,
+/// then gossips or drops the ones that were not included in a block.
+pub(crate) async fn release_private_operations(
+    mut operations: Storage,
+    delay: MassaTime,
+    expiry: PrivateRelayExpiry,
+    execution_controller: Box<dyn ExecutionController>,
+    mut pool_controller: Box<dyn PoolController>,
+    mut protocol_sender: ProtocolCommandSender,
+) {
+    tokio::time::sleep(delay.to_duration()).await;
+
+    // the included operations are already public: they are gossiped with their block
+    let included: PreHashSet<OperationId> = {
+        let (_, final_op_exec_statuses) = execution_controller.get_op_exec_status();
+        let blocks = operations.read_blocks();
+        operations
+            .get_op_refs()
+            .iter()
+            .filter(|op_id| {
+                final_op_exec_statuses.contains_key(op_id)
+                    || blocks
+                        .get_blocks_by_operation(op_id)
+                        .map_or(false, |block_ids| !block_ids.is_empty())
+            })
+            .copied()
+            .collect()
+    };
+    operations.drop_operation_refs(&included);
+    let pending = operations.get_op_refs().clone();
+    if pending.is_empty() {
+        return;
+    }
+
+    match expiry {
+        PrivateRelayExpiry::Gossip => {
+            info!(
+                "propagating {} private operations not included before the end of their relay delay",
+                pending.len()
+            );
+            match tokio::task::spawn_blocking(move || {
+                protocol_sender.propagate_operations(operations)
+            })
+            .await
+            {
+                Ok(Ok(())) => {}
+                Ok(Err(err)) => warn!("could not propagate the private operations: {}", err),
+                Err(err) => warn!("could not propagate the private operations: {}", err),
+            }
+        }
+        PrivateRelayExpiry::Drop => {
+            info!(
+                "dropping {} private operations not included before the end of their relay delay",
+                pending.len()
+            );
+            pool_controller.remove_operations(pending);
+        }
+    }
+}
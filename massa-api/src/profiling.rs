@@ -0,0 +1,172 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! CPU and heap profiling of the node, built with the `profiling` feature.
+//!
+//! The CPU profiler samples the stacks of all the threads of the process at a given frequency
+//! between a start and a stop requested through the private API, and returns them in the folded
+//! format of flamegraphs. The heap statistics are counted by the global allocator of the process,
+//! which wraps the system allocator.
+
+use massa_api_exports::error::ApiError;
+use massa_api_exports::profiling::{CpuProfile, HeapStats};
+use massa_time::MassaTime;
+use parking_lot::Mutex;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc;
+use std::thread::JoinHandle;
+
+/// running profiling session
+struct Session {
+    stop_tx: mpsc::Sender<()>,
+    handle: JoinHandle<Result<CpuProfile, String>>,
+}
+
+/// only one session at a time: the sampling timer is global to the process
+static SESSION: Mutex<Option<Session>> = parking_lot::const_mutex(None);
+
+/// Starts sampling the stacks of the node at `frequency` Hz
+pub(crate) fn start_cpu_profiling(frequency: u32) -> Result<(), ApiError> {
+    let mut session = SESSION.lock();
+    if session.is_some() {
+        return Err(ApiError::BadRequest(
+            "a profiling session is already running".into(),
+        ));
+    }
+    let (stop_tx, stop_rx) = mpsc::channel::<()>();
+    let (started_tx, started_rx) = mpsc::channel::<Result<(), String>>();
+    let handle = std::thread::Builder::new()
+        .name("cpu-profiler".into())
+        .spawn(move || {
+            let start = MassaTime::now().map_err(|err| err.to_string())?;
+            let guard = match pprof::ProfilerGuardBuilder::default()
+                .frequency(frequency as i32)
+                .blocklist(&["libc", "libgcc", "pthread", "vdso"])
+                .build()
+            {
+                Ok(guard) => {
+                    let _ = started_tx.send(Ok(()));
+                    guard
+                }
+                Err(err) => {
+                    let _ = started_tx.send(Err(err.to_string()));
+                    return Err(err.to_string());
+                }
+            };
+            // stop when asked or when the API is dropped
+            let _ = stop_rx.recv();
+            let report = guard.report().build().map_err(|err| err.to_string())?;
+            let mut folded_stacks = String::new();
+            let mut sample_count = 0;
+            for (frames, count) in report.data.iter() {
+                let mut line = frames.thread_name.clone();
+                for frame in frames.frames.iter().rev() {
+                    for symbol in frame.iter().rev() {
+                        let _ = write!(line, ";{}", symbol);
+                    }
+                }
+                let _ = writeln!(folded_stacks, "{} {}", line, count);
+                sample_count += (*count).max(0) as u64;
+            }
+            Ok(CpuProfile {
+                start,
+                end: MassaTime::now().map_err(|err| err.to_string())?,
+                frequency,
+                sample_count,
+                folded_stacks,
+            })
+        })
+        .map_err(|err| ApiError::InternalServerError(err.to_string()))?;
+    match started_rx.recv() {
+        Ok(Ok(())) => {
+            *session = Some(Session { stop_tx, handle });
+            Ok(())
+        }
+        Ok(Err(err)) => Err(ApiError::InternalServerError(format!(
+            "could not start the profiler: {}",
+            err
+        ))),
+        Err(_) => Err(ApiError::InternalServerError(
+            "the profiler thread stopped".into(),
+        )),
+    }
+}
+
+/// Stops the running session and returns its profile.
+/// Blocking: the symbols of the sampled stacks are resolved before returning.
+pub(crate) fn stop_cpu_profiling() -> Result<CpuProfile, ApiError> {
+    let Session { stop_tx, handle } = SESSION
+        .lock()
+        .take()
+        .ok_or_else(|| ApiError::BadRequest("no profiling session is running".into()))?;
+    let _ = stop_tx.send(());
+    handle
+        .join()
+        .map_err(|_| ApiError::InternalServerError("the profiler thread panicked".into()))?
+        .map_err(ApiError::InternalServerError)
+}
+
+static ALLOCATED: AtomicU64 = AtomicU64::new(0);
+static PEAK_ALLOCATED: AtomicU64 = AtomicU64::new(0);
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+static DEALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+
+/// System allocator counting the allocated bytes
+struct CountingAllocator;
+
+fn on_alloc(size: usize) {
+    let allocated = ALLOCATED.fetch_add(size as u64, Ordering::Relaxed) + size as u64;
+    PEAK_ALLOCATED.fetch_max(allocated, Ordering::Relaxed);
+    ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+}
+
+fn on_dealloc(size: usize) {
+    ALLOCATED.fetch_sub(size as u64, Ordering::Relaxed);
+    DEALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            on_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            on_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        on_dealloc(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            on_dealloc(layout.size());
+            on_alloc(new_size);
+        }
+        new_ptr
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Heap statistics counted by the global allocator
+pub(crate) fn heap_stats() -> HeapStats {
+    HeapStats {
+        allocated: ALLOCATED.load(Ordering::Relaxed),
+        peak_allocated: PEAK_ALLOCATED.load(Ordering::Relaxed),
+        allocations: ALLOCATIONS.load(Ordering::Relaxed),
+        deallocations: DEALLOCATIONS.load(Ordering::Relaxed),
+    }
+}
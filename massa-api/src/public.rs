@@ -1,45 +1,73 @@
 //! Copyright (c) 2022 MASSA LABS <info@massa.net>
 #![allow(clippy::too_many_arguments)]
 
+use crate::cache::ApiCache;
+use crate::private_relay::release_private_operations;
+use crate::verified_contracts::read_verified_contracts;
 use crate::{MassaRpcServer, Public, RpcServer, StopHandle, Value, API};
 use async_trait::async_trait;
 use jsonrpsee::core::{Error as JsonRpseeError, RpcResult};
 use massa_api_exports::{
-    address::AddressInfo,
-    block::{BlockInfo, BlockInfoContent, BlockSummary},
+    address::{
+        AddressInfo, AddressStakingRewards, AddressStorageRent, CycleStakingRewards,
+        VerifiedContract,
+    },
+    async_pool::{AsyncMessageInfo, ScheduledTransferInfo},
+    block::{
+        BlockFieldMask, BlockInfo, BlockInfoContent, BlockSummary, GraphBlock, GraphPage,
+        GraphPageRequest,
+    },
     config::APIConfig,
-    datastore::{DatastoreEntryInput, DatastoreEntryOutput},
+    datastore::{
+        DatastoreEntryChunkInput, DatastoreEntryChunkOutput, DatastoreEntryInput,
+        DatastoreEntryOutput,
+    },
     endorsement::EndorsementInfo,
     error::ApiError,
-    execution::{ExecuteReadOnlyResponse, ReadOnlyBytecodeExecution, ReadOnlyCall, ReadOnlyResult},
-    node::NodeStatus,
+    execution::{
+        DryRunBalanceChange, DryRunOperationResponse, DryRunRollChange, ExecuteReadOnlyResponse,
+        ReadOnlyBytecodeExecution, ReadOnlyCall, ReadOnlyResult,
+    },
+    node::{BuildInfo, NodeStatus},
     operation::{OperationInfo, OperationInput},
     page::{PageRequest, PagedVec},
-    slot::SlotAmount,
-    TimeInterval,
+    profiling::{CpuProfile, HeapStats},
+    query::{IndexQuery, QueryIndex, QueryRow},
+    selection::{AddressSelectionForecast, CycleSelectionEstimate},
+    slot::{
+        CycleBounds, SlotAmount, SlotBeacon, SlotCalendar, SlotDetail, SlotTiming,
+        StateChangesSummary,
+    },
+    StakersFilter, TimeInterval,
 };
-use massa_consensus_exports::block_status::DiscardReason;
+use massa_channel::ChannelStats;
+use massa_consensus_exports::block_graph_export::{BlockGraphQuery, ExportBlockSummary};
 use massa_consensus_exports::ConsensusController;
 use massa_execution_exports::{
-    ExecutionController, ExecutionStackElement, ReadOnlyExecutionRequest, ReadOnlyExecutionTarget,
+    ExecutionController, ExecutionError, ExecutionStackElement, ReadOnlyExecutionOutput,
+    ReadOnlyExecutionRequest, ReadOnlyExecutionTarget,
 };
-use massa_models::operation::OperationDeserializer;
+use massa_final_state::{BackupManifest, FinalState};
+use massa_logging::LogLine;
+use massa_models::operation::{OperationDeserializer, OperationType};
 use massa_models::secure_share::SecureShareDeserializer;
 use massa_models::{
-    block::{Block, BlockGraphStatus},
+    block::{Block, BlockGraphStatus, FilledBlock},
     endorsement::SecureShareEndorsement,
     error::ModelsError,
     operation::SecureShareOperation,
+    stats::{BlockProductionStats, DiskStatus, MemoryStatus},
     timeslots,
 };
-use massa_pos_exports::SelectorController;
-use massa_protocol_exports::ProtocolCommandSender;
+use massa_pos_exports::{PoSHistoryRanges, SelectorController};
+use massa_protocol_exports::{PeerMessageStats, ProtocolCommandSender};
 use massa_serialization::{DeserializeError, Deserializer};
 
 use itertools::{izip, Itertools};
 use massa_models::datastore::DatastoreDeserializer;
 use massa_models::{
     address::Address,
+    amount::Amount,
     block_id::BlockId,
     clique::Clique,
     composite::PubkeySig,
@@ -48,19 +76,26 @@ use massa_models::{
     execution::EventFilter,
     node::NodeId,
     operation::OperationId,
-    output_event::SCOutputEvent,
+    output_event::{ExecutionErrorInfo, SCOutputEvent},
     prehash::{PreHashMap, PreHashSet},
     slot::Slot,
-    timeslots::{get_latest_block_slot_at_timestamp, time_range_to_slot_range},
+    timeslots::{
+        get_block_slot_timestamp, get_cycle_slot_bounds, get_cycle_time_range,
+        get_latest_block_slot_at_timestamp, slots_in_time_range, time_range_to_slot_range,
+    },
     version::Version,
 };
-use massa_network_exports::{NetworkCommandSender, NetworkConfig};
+use massa_network_exports::{BanEntry, IpSubnet, NetworkCommandSender, NetworkConfig};
 use massa_pool_exports::PoolController;
-use massa_signature::KeyPair;
-use massa_storage::Storage;
+use massa_signature::{get_verification_stats, verify_signature_batch, KeyPair};
+use massa_storage::{Storage, StorageOwnersDump};
 use massa_time::MassaTime;
+use massa_versioning::MipStatus;
+use parking_lot::RwLock;
 use std::collections::BTreeMap;
 use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+use std::sync::Arc;
 
 impl API<Public> {
     /// generate a new public API
@@ -73,24 +108,322 @@ impl API<Public> {
         protocol_command_sender: ProtocolCommandSender,
         network_settings: NetworkConfig,
         version: Version,
+        build_info: BuildInfo,
         network_command_sender: NetworkCommandSender,
         node_id: NodeId,
         storage: Storage,
+        disk_status: Arc<RwLock<DiskStatus>>,
+        memory_status: Arc<RwLock<MemoryStatus>>,
+        production_stats: Arc<RwLock<BlockProductionStats>>,
+        final_state: Arc<RwLock<FinalState>>,
     ) -> Self {
+        let cache = ApiCache::new(api_settings.cache_max_entries);
+        let final_slot = final_state.read().shared_slot.clone();
         API(Public {
             consensus_controller,
             api_settings,
             pool_command_sender,
             network_settings,
             version,
+            build_info,
             network_command_sender,
             protocol_command_sender,
             node_id,
             execution_controller,
             selector_controller,
             storage,
+            disk_status,
+            memory_status,
+            production_stats,
+            final_state,
+            final_slot,
+            cache,
         })
     }
+
+    /// slot of the final state, the cached responses are dropped when it changes
+    fn final_slot(&self) -> Slot {
+        self.0.final_slot.get()
+    }
+
+    /// gets the summaries of the blocks of a part of the block graph from consensus
+    /// time filtering and pagination are done consensus-side to prevent communication overhead
+    fn query_graph(
+        &self,
+        start: Option<MassaTime>,
+        end: Option<MassaTime>,
+        after: Option<(Slot, BlockId)>,
+        limit: Option<usize>,
+        with_headers: bool,
+    ) -> RpcResult<Vec<ExportBlockSummary>> {
+        let api_settings = &self.0.api_settings;
+        let (start_slot, end_slot) = time_range_to_slot_range(
+            api_settings.thread_count,
+            api_settings.t0,
+            api_settings.genesis_timestamp,
+            start,
+            end,
+        )
+        .map_err(ApiError::ModelsError)?;
+        Ok(self
+            .0
+            .consensus_controller
+            .get_block_graph_summaries(BlockGraphQuery {
+                start_slot,
+                end_slot,
+                after,
+                limit,
+                with_headers,
+            })
+            .map_err(ApiError::ConsensusError)?)
+    }
+
+    /// gets an interval of the block graph from consensus, with time filtering
+    fn compute_graph_interval(&self, time: TimeInterval) -> RpcResult<Vec<BlockSummary>> {
+        Ok(self
+            .query_graph(time.start, time.end, None, None, false)?
+            .iter()
+            .map(block_summary)
+            .collect())
+    }
+
+    /// gets a page of the block graph from consensus, only cloning the headers if requested
+    fn compute_graph_page(&self, request: GraphPageRequest) -> RpcResult<GraphPage> {
+        let max_limit = self.0.api_settings.max_query_results;
+        let limit = request.limit.unwrap_or(max_limit).min(max_limit);
+        if limit == 0 {
+            return Err(ApiError::BadRequest("the page limit must be positive".into()).into());
+        }
+        let after = request
+            .page_token
+            .as_deref()
+            .map(parse_graph_page_token)
+            .transpose()?;
+        let with_headers = request.fields != BlockFieldMask::IdsOnly;
+        // one more block is requested to know whether there is a following page
+        let mut summaries = self.query_graph(
+            request.start,
+            request.end,
+            after,
+            Some(limit + 1),
+            with_headers,
+        )?;
+        let next_page_token = if summaries.len() > limit {
+            summaries.truncate(limit);
+            summaries
+                .last()
+                .map(|last| graph_page_token(last.slot, last.id))
+        } else {
+            None
+        };
+        let blocks = summaries
+            .into_iter()
+            .map(|summary| match request.fields {
+                BlockFieldMask::IdsOnly => GraphBlock {
+                    id: summary.id,
+                    summary: None,
+                    header: None,
+                },
+                _ => GraphBlock {
+                    id: summary.id,
+                    summary: Some(block_summary(&summary)),
+                    header: summary.header,
+                },
+            })
+            .collect();
+        Ok(GraphPage {
+            blocks,
+            next_page_token,
+        })
+    }
+
+    /// gathers the info of the addresses from storage, execution and selector
+    fn compute_addresses(&self, addresses: Vec<Address>) -> RpcResult<Vec<AddressInfo>> {
+        // get info from storage about which blocks the addresses have created
+        let created_blocks: Vec<PreHashSet<BlockId>> = {
+            let lck = self.0.storage.read_blocks();
+            addresses
+                .iter()
+                .map(|address| {
+                    lck.get_blocks_created_by(address)
+                        .cloned()
+                        .unwrap_or_default()
+                })
+                .collect()
+        };
+
+        // get info from storage about which operations the addresses have created
+        let created_operations: Vec<PreHashSet<OperationId>> = {
+            let lck = self.0.storage.read_operations();
+            addresses
+                .iter()
+                .map(|address| {
+                    lck.get_operations_created_by(address)
+                        .cloned()
+                        .unwrap_or_default()
+                })
+                .collect()
+        };
+
+        // get info from storage about which endorsements the addresses have created
+        let created_endorsements: Vec<PreHashSet<EndorsementId>> = {
+            let lck = self.0.storage.read_endorsements();
+            addresses
+                .iter()
+                .map(|address| {
+                    lck.get_endorsements_created_by(address)
+                        .cloned()
+                        .unwrap_or_default()
+                })
+                .collect()
+        };
+
+        // get execution info
+        let execution_infos = self.0.execution_controller.get_addresses_infos(&addresses);
+        let verified_contracts =
+            read_verified_contracts(&self.0.api_settings.verified_contracts_path)?;
+
+        // get future draws from selector
+        let selection_draws = {
+            let cur_slot = timeslots::get_current_latest_block_slot(
+                self.0.api_settings.thread_count,
+                self.0.api_settings.t0,
+                self.0.api_settings.genesis_timestamp,
+            )
+            .expect("could not get latest current slot")
+            .unwrap_or_else(|| Slot::new(0, 0));
+            let slot_end = Slot::new(
+                cur_slot
+                    .period
+                    .saturating_add(self.0.api_settings.draw_lookahead_period_count),
+                cur_slot.thread,
+            );
+            addresses
+                .iter()
+                .map(|addr| {
+                    self.0
+                        .selector_controller
+                        .get_address_selections(addr, cur_slot, slot_end)
+                        .unwrap_or_default()
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // compile results
+        let mut res = Vec::with_capacity(addresses.len());
+        let iterator = izip!(
+            addresses.into_iter(),
+            created_blocks.into_iter(),
+            created_operations.into_iter(),
+            created_endorsements.into_iter(),
+            execution_infos.into_iter(),
+            selection_draws.into_iter(),
+        );
+        for (
+            address,
+            created_blocks,
+            created_operations,
+            created_endorsements,
+            execution_infos,
+            (next_block_draws, next_endorsement_draws),
+        ) in iterator
+        {
+            res.push(AddressInfo {
+                // general address info
+                address,
+                thread: address.get_thread(self.0.api_settings.thread_count),
+
+                // final execution info
+                final_balance: execution_infos.final_balance,
+                final_roll_count: execution_infos.final_roll_count,
+                final_datastore_keys: execution_infos
+                    .final_datastore_keys
+                    .into_iter()
+                    .collect::<Vec<_>>(),
+
+                // candidate execution info
+                candidate_balance: execution_infos.candidate_balance,
+                candidate_roll_count: execution_infos.candidate_roll_count,
+                candidate_datastore_keys: execution_infos
+                    .candidate_datastore_keys
+                    .into_iter()
+                    .collect::<Vec<_>>(),
+
+                // deferred credits
+                deferred_credits: execution_infos
+                    .future_deferred_credits
+                    .into_iter()
+                    .map(|(slot, amount)| SlotAmount { slot, amount })
+                    .collect::<Vec<_>>(),
+
+                // selector info
+                next_block_draws,
+                next_endorsement_draws,
+
+                // created objects
+                created_blocks: created_blocks.into_iter().collect::<Vec<_>>(),
+                created_endorsements: created_endorsements.into_iter().collect::<Vec<_>>(),
+                created_operations: created_operations.into_iter().collect::<Vec<_>>(),
+
+                // cycle infos
+                cycle_infos: execution_infos.cycle_infos,
+
+                // bytecode verification
+                bytecode_hash: execution_infos.candidate_bytecode_hash,
+                verified_contract: execution_infos
+                    .candidate_bytecode_hash
+                    .and_then(|hash| verified_contracts.get(&hash).cloned()),
+            });
+        }
+
+        Ok(res)
+    }
+
+    /// Deserializes the operations sent by a user and verifies their signatures
+    fn verify_operations(&self, ops: Vec<OperationInput>) -> RpcResult<Vec<SecureShareOperation>> {
+        let api_cfg = &self.0.api_settings;
+        if ops.len() as u64 > api_cfg.max_arguments {
+            return Err(ApiError::BadRequest("too many arguments".into()).into());
+        }
+        let operation_deserializer = SecureShareDeserializer::new(OperationDeserializer::new(
+            api_cfg.max_datastore_value_length,
+            api_cfg.max_function_name_length,
+            api_cfg.max_parameter_size,
+            api_cfg.max_op_datastore_entry_count,
+            api_cfg.max_op_datastore_key_length,
+            api_cfg.max_op_datastore_value_length,
+        ));
+        let verified_ops = ops
+            .into_iter()
+            .map(|op_input| {
+                let mut op_serialized = Vec::new();
+                op_serialized.extend(op_input.signature.to_bytes());
+                op_serialized.extend(op_input.creator_public_key.to_bytes());
+                op_serialized.extend(op_input.serialized_content);
+                let (rest, op): (&[u8], SecureShareOperation) = operation_deserializer
+                    .deserialize::<DeserializeError>(&op_serialized)
+                    .map_err(|err| {
+                        ApiError::ModelsError(ModelsError::DeserializeError(err.to_string()))
+                    })?;
+                if rest.is_empty() {
+                    Ok(op)
+                } else {
+                    Err(ApiError::ModelsError(ModelsError::DeserializeError(
+                        "There is data left after operation deserialization".to_owned(),
+                    ))
+                    .into())
+                }
+            })
+            .collect::<RpcResult<Vec<SecureShareOperation>>>()?;
+        let signatures = verified_ops
+            .iter()
+            .map(|op| op.get_signatures_to_verify())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(ApiError::ModelsError)?
+            .concat();
+        verify_signature_batch(&signatures).map_err(|e| ApiError::ModelsError(e.into()))?;
+        Ok(verified_ops)
+    }
 }
 
 #[async_trait]
@@ -134,6 +467,8 @@ impl MassaRpcServer for API<Public> {
             bytecode,
             operation_datastore,
             is_final,
+            limits,
+            fork,
         } in reqs
         {
             let address = address.unwrap_or_else(|| {
@@ -178,28 +513,13 @@ impl MassaRpcServer for API<Public> {
                     operation_datastore: op_datastore,
                 }],
                 is_final,
+                limits: limits.into(),
+                fork,
             };
 
             // run
             let result = self.0.execution_controller.execute_readonly_request(req);
-
-            // map result
-            let result = ExecuteReadOnlyResponse {
-                executed_at: result
-                    .as_ref()
-                    .map_or_else(|_| Slot::new(0, 0), |v| v.out.slot),
-                result: result.as_ref().map_or_else(
-                    |err| ReadOnlyResult::Error(format!("readonly call failed: {}", err)),
-                    |res| ReadOnlyResult::Ok(res.call_result.clone()),
-                ),
-                gas_cost: result.as_ref().map_or_else(|_| 0, |v| v.gas_cost),
-                output_events: result
-                    .as_ref()
-                    .map_or_else(|_| Default::default(), |v| v.out.events.clone().0),
-                state_changes: result.map_or_else(|_| Default::default(), |v| v.out.state_changes),
-            };
-
-            res.push(result);
+            res.push(read_only_response(result));
         }
 
         // return result
@@ -222,6 +542,8 @@ impl MassaRpcServer for API<Public> {
             parameter,
             caller_address,
             is_final,
+            limits,
+            fork,
         } in reqs
         {
             let caller_address = caller_address.unwrap_or_else(|| {
@@ -257,34 +579,168 @@ impl MassaRpcServer for API<Public> {
                     },
                 ],
                 is_final,
+                limits: limits.into(),
+                fork,
             };
 
             // run
             let result = self.0.execution_controller.execute_readonly_request(req);
-
-            // map result
-            let result = ExecuteReadOnlyResponse {
-                executed_at: result
-                    .as_ref()
-                    .map_or_else(|_| Slot::new(0, 0), |v| v.out.slot),
-                result: result.as_ref().map_or_else(
-                    |err| ReadOnlyResult::Error(format!("readonly call failed: {}", err)),
-                    |res| ReadOnlyResult::Ok(res.call_result.clone()),
-                ),
-                gas_cost: result.as_ref().map_or_else(|_| 0, |v| v.gas_cost),
-                output_events: result
-                    .as_ref()
-                    .map_or_else(|_| Default::default(), |v| v.out.events.clone().0),
-                state_changes: result.map_or_else(|_| Default::default(), |v| v.out.state_changes),
-            };
-
-            res.push(result);
+            res.push(read_only_response(result));
         }
 
         // return result
         Ok(res)
     }
 
+    async fn dry_run_operation(&self, op: OperationInput) -> RpcResult<DryRunOperationResponse> {
+        let operation = self
+            .verify_operations(vec![op])?
+            .pop()
+            .expect("one operation was verified");
+        let operation_id = operation.id;
+        let execution_controller = &self.0.execution_controller;
+
+        // execute the operation from the candidate state, nothing is kept
+        let req = ReadOnlyExecutionRequest {
+            max_gas: operation.get_gas_usage(),
+            call_stack: Vec::new(),
+            target: ReadOnlyExecutionTarget::Operation(Box::new(operation)),
+            is_final: false,
+            limits: Default::default(),
+            fork: None,
+        };
+        let output = match execution_controller.execute_readonly_request(req) {
+            Ok(output) => output,
+            Err(err) => {
+                return Ok(DryRunOperationResponse {
+                    executed_at: None,
+                    executed_successfully: false,
+                    error: Some(err.to_string()),
+                    error_info: Some(ExecutionErrorInfo {
+                        code: err.code(),
+                        message: err.to_string(),
+                        revert_reason: err.revert_reason().map(|reason| reason.to_vec()),
+                    }),
+                    gas_cost: 0,
+                    balance_changes: Vec::new(),
+                    roll_changes: Vec::new(),
+                    output_events: Default::default(),
+                })
+            }
+        };
+        let output_events = output.out.events.0;
+        let state_changes = output.out.state_changes;
+        let executed_successfully = state_changes
+            .executed_ops_changes
+            .get(&operation_id)
+            .map_or(false, |(success, _)| *success);
+        // the error of a failed execution is reported by its last error event
+        let error_event = output_events
+            .iter()
+            .rev()
+            .find(|event| event.context.is_error);
+        let error = (!executed_successfully).then(|| {
+            error_event.map_or_else(
+                || "the operation failed".to_string(),
+                |event| event.data.clone(),
+            )
+        });
+        let error_info = if executed_successfully {
+            None
+        } else {
+            error_event.and_then(|event| event.context.error.clone())
+        };
+
+        // compare the changed balances and roll counts to the candidate ones
+        let mut changed_addresses: Vec<Address> =
+            state_changes.ledger_changes.0.keys().copied().collect();
+        changed_addresses.sort_unstable();
+        let balance_changes = changed_addresses
+            .iter()
+            .zip(execution_controller.get_final_and_candidate_balance(&changed_addresses))
+            .filter_map(|(address, (_, candidate_balance))| {
+                let before = candidate_balance.unwrap_or_default();
+                let after = state_changes
+                    .ledger_changes
+                    .get_balance_or_else(address, || Some(before))
+                    .unwrap_or_default();
+                (before != after).then_some(DryRunBalanceChange {
+                    address: *address,
+                    before,
+                    after,
+                })
+            })
+            .collect();
+        let mut roll_addresses: Vec<Address> = state_changes
+            .pos_changes
+            .roll_changes
+            .keys()
+            .copied()
+            .collect();
+        roll_addresses.sort_unstable();
+        let roll_changes = roll_addresses
+            .iter()
+            .zip(execution_controller.get_addresses_infos(&roll_addresses))
+            .filter_map(|(address, info)| {
+                let after = state_changes.pos_changes.roll_changes[address];
+                (info.candidate_roll_count != after).then_some(DryRunRollChange {
+                    address: *address,
+                    before: info.candidate_roll_count,
+                    after,
+                })
+            })
+            .collect();
+
+        Ok(DryRunOperationResponse {
+            executed_at: Some(output.out.slot),
+            executed_successfully,
+            error,
+            error_info,
+            gas_cost: output.gas_cost,
+            balance_changes,
+            roll_changes,
+            output_events,
+        })
+    }
+
+    async fn create_execution_fork(&self, name: String) -> RpcResult<Slot> {
+        self.0
+            .execution_controller
+            .create_fork(name)
+            .map_err(|err| ApiError::BadRequest(err.to_string()).into())
+    }
+
+    async fn execute_operations_in_fork(
+        &self,
+        name: String,
+        ops: Vec<OperationInput>,
+    ) -> RpcResult<Vec<ExecuteReadOnlyResponse>> {
+        let operations = self.verify_operations(ops)?;
+
+        // the operations are applied to the fork one after the other
+        let mut res: Vec<ExecuteReadOnlyResponse> = Vec::with_capacity(operations.len());
+        for operation in operations {
+            let req = ReadOnlyExecutionRequest {
+                max_gas: operation.get_gas_usage(),
+                call_stack: Vec::new(),
+                target: ReadOnlyExecutionTarget::Operation(Box::new(operation)),
+                is_final: false,
+                limits: Default::default(),
+                fork: Some(name.clone()),
+            };
+            let result = self.0.execution_controller.execute_readonly_request(req);
+            res.push(read_only_response(result));
+        }
+        Ok(res)
+    }
+
+    async fn discard_execution_fork(&self, name: String) -> RpcResult<()> {
+        self.0
+            .execution_controller
+            .discard_fork(&name)
+            .map_err(|err| ApiError::BadRequest(err.to_string()).into())
+    }
+
     async fn remove_staking_addresses(&self, _: Vec<Address>) -> RpcResult<()> {
         crate::wrong_api::<()>()
     }
@@ -293,6 +749,18 @@ impl MassaRpcServer for API<Public> {
         crate::wrong_api::<PreHashSet<Address>>()
     }
 
+    async fn enable_staking_addresses(&self, _: Vec<Address>) -> RpcResult<()> {
+        crate::wrong_api::<()>()
+    }
+
+    async fn disable_staking_addresses(&self, _: Vec<Address>) -> RpcResult<()> {
+        crate::wrong_api::<()>()
+    }
+
+    async fn get_disabled_staking_addresses(&self) -> RpcResult<PreHashSet<Address>> {
+        crate::wrong_api::<PreHashSet<Address>>()
+    }
+
     async fn node_ban_by_ip(&self, _: Vec<IpAddr>) -> RpcResult<()> {
         crate::wrong_api::<()>()
     }
@@ -309,19 +777,76 @@ impl MassaRpcServer for API<Public> {
         crate::wrong_api::<()>()
     }
 
-    async fn get_status(&self) -> RpcResult<NodeStatus> {
-        let execution_controller = self.0.execution_controller.clone();
-        let consensus_controller = self.0.consensus_controller.clone();
-        let network_command_sender = self.0.network_command_sender.clone();
-        let network_config = self.0.network_settings.clone();
-        let version = self.0.version;
-        let api_settings = self.0.api_settings.clone();
-        let pool_command_sender = self.0.pool_command_sender.clone();
-        let node_id = self.0.node_id;
-        let config = CompactConfig::default();
-        let now = match MassaTime::now() {
-            Ok(now) => now,
-            Err(e) => return Err(ApiError::TimeError(e).into()),
+    async fn node_ban_subnet(&self, _: IpSubnet, _: Option<MassaTime>, _: String) -> RpcResult<()> {
+        crate::wrong_api::<()>()
+    }
+
+    async fn node_unban_subnet(&self, _: Vec<IpSubnet>) -> RpcResult<()> {
+        crate::wrong_api::<()>()
+    }
+
+    async fn node_list_bans(&self) -> RpcResult<Vec<BanEntry>> {
+        crate::wrong_api::<Vec<BanEntry>>()
+    }
+
+    async fn get_storage_diagnostics(&self) -> RpcResult<StorageOwnersDump> {
+        crate::wrong_api::<StorageOwnersDump>()
+    }
+
+    async fn get_channel_stats(&self) -> RpcResult<Vec<ChannelStats>> {
+        crate::wrong_api::<Vec<ChannelStats>>()
+    }
+
+    async fn get_recent_logs(&self, _: String, _: usize) -> RpcResult<Vec<LogLine>> {
+        crate::wrong_api::<Vec<LogLine>>()
+    }
+
+    async fn node_start_profiling(&self, _: u32) -> RpcResult<()> {
+        crate::wrong_api::<()>()
+    }
+
+    async fn node_stop_profiling(&self) -> RpcResult<CpuProfile> {
+        crate::wrong_api::<CpuProfile>()
+    }
+
+    async fn get_heap_stats(&self) -> RpcResult<HeapStats> {
+        crate::wrong_api::<HeapStats>()
+    }
+
+    async fn node_create_backup(&self, _: PathBuf) -> RpcResult<BackupManifest> {
+        crate::wrong_api::<BackupManifest>()
+    }
+
+    async fn node_add_verified_contracts(&self, _: Vec<VerifiedContract>) -> RpcResult<()> {
+        crate::wrong_api::<()>()
+    }
+
+    async fn node_remove_verified_contracts(&self, _: Vec<Hash>) -> RpcResult<()> {
+        crate::wrong_api::<()>()
+    }
+
+    async fn node_verified_contracts(&self) -> RpcResult<Vec<VerifiedContract>> {
+        crate::wrong_api::<Vec<VerifiedContract>>()
+    }
+
+    async fn get_status(&self) -> RpcResult<NodeStatus> {
+        let execution_controller = self.0.execution_controller.clone();
+        let consensus_controller = self.0.consensus_controller.clone();
+        let network_command_sender = self.0.network_command_sender.clone();
+        let network_config = self.0.network_settings.clone();
+        let version = self.0.version;
+        let build_info = self.0.build_info.clone();
+        let api_settings = self.0.api_settings.clone();
+        let pool_command_sender = self.0.pool_command_sender.clone();
+        let node_id = self.0.node_id;
+        let disk_status = self.0.disk_status.read().clone();
+        let memory_status = self.0.memory_status.read().clone();
+        let block_production = self.0.production_stats.read().clone();
+        let api_cache_stats = self.0.cache.stats();
+        let config = CompactConfig::default();
+        let now = match MassaTime::now() {
+            Ok(now) => now,
+            Err(e) => return Err(ApiError::TimeError(e).into()),
         };
 
         let last_slot_result = get_latest_block_slot_at_timestamp(
@@ -361,6 +886,7 @@ impl MassaRpcServer for API<Public> {
             pool_command_sender.get_operation_count(),
             pool_command_sender.get_endorsement_count(),
         );
+        let pool_bytecode_rejects = pool_command_sender.get_bytecode_reject_stats();
 
         let next_slot_result = last_slot
             .unwrap_or_else(|| Slot::new(0, 0))
@@ -381,6 +907,20 @@ impl MassaRpcServer for API<Public> {
             })
             .collect::<BTreeMap<_, _>>();
 
+        let connected_nodes_metadata = peers
+            .node_metadata
+            .into_iter()
+            .map(|(node_id, signed)| (node_id, signed.metadata))
+            .collect::<BTreeMap<_, _>>();
+
+        let connected_nodes_probes = peers.node_probes.into_iter().collect::<BTreeMap<_, _>>();
+        let network_clock_offset = peers.clock_offset;
+        let connected_nodes_final_slots = peers
+            .node_final_slots
+            .into_iter()
+            .collect::<BTreeMap<_, _>>();
+        let sync_lag = peers.sync_lag;
+
         let current_cycle = last_slot
             .unwrap_or_else(|| Slot::new(0, 0))
             .get_cycle(api_settings.periods_per_cycle);
@@ -416,16 +956,29 @@ impl MassaRpcServer for API<Public> {
             node_id,
             node_ip: network_config.routable_ip,
             version,
+            build_info,
             current_time: now,
+            network_clock_offset,
             current_cycle_time,
             next_cycle_time,
             connected_nodes,
+            connected_nodes_metadata,
+            connected_nodes_probes,
+            connected_nodes_final_slots,
+            peer_distribution: peers.distribution,
             last_slot,
             next_slot,
+            sync_lag,
             execution_stats,
             consensus_stats,
             network_stats,
             pool_stats,
+            pool_bytecode_rejects,
+            signature_stats: get_verification_stats(),
+            disk_status,
+            memory_status,
+            block_production,
+            api_cache_stats,
             config,
             current_cycle,
         })
@@ -433,12 +986,17 @@ impl MassaRpcServer for API<Public> {
 
     async fn get_cliques(&self) -> RpcResult<Vec<Clique>> {
         let consensus_controller = self.0.consensus_controller.clone();
-        Ok(consensus_controller.get_cliques())
+        self.0
+            .cache
+            .get_or_compute(self.final_slot(), "get_cliques", &(), || {
+                Ok(consensus_controller.get_cliques())
+            })
     }
 
     async fn get_stakers(
         &self,
         page_request: Option<PageRequest>,
+        filter: Option<StakersFilter>,
     ) -> RpcResult<PagedVec<(Address, u64)>> {
         let execution_controller = self.0.execution_controller.clone();
         let cfg = self.0.api_settings.clone();
@@ -462,17 +1020,59 @@ impl MassaRpcServer for API<Public> {
             Err(e) => return Err(ApiError::ModelsError(e).into()),
         };
 
-        let mut staker_vec = execution_controller
-            .get_cycle_active_rolls(curr_cycle)
-            .into_iter()
-            .collect::<Vec<(Address, u64)>>();
+        // without page request, all the stakers are returned
+        let (offset, limit) = page_request.map_or((0, usize::MAX), |page_request| {
+            (page_request.start(), page_request.limit)
+        });
+        let min_rolls = filter.and_then(|filter| filter.min_rolls).unwrap_or(0);
+        let (stakers, total_count) = self.0.cache.get_or_compute(
+            self.final_slot(),
+            "get_stakers",
+            &(curr_cycle, min_rolls, offset, limit),
+            || Ok(execution_controller.get_cycle_stakers(curr_cycle, min_rolls, offset, limit)),
+        )?;
+
+        Ok(PagedVec::from_page(stakers, total_count))
+    }
 
-        staker_vec
-            .sort_by(|&(_, roll_counts_a), &(_, roll_counts_b)| roll_counts_b.cmp(&roll_counts_a));
+    async fn get_async_pool_messages(
+        &self,
+        page_request: Option<PageRequest>,
+    ) -> RpcResult<PagedVec<AsyncMessageInfo>> {
+        let execution_controller = self.0.execution_controller.clone();
 
-        let paged_vec = PagedVec::new(staker_vec, page_request);
+        // without page request, all the messages are returned
+        let (offset, limit) = page_request.map_or((0, usize::MAX), |page_request| {
+            (page_request.start(), page_request.limit)
+        });
+        let (messages, total_count) = self.0.cache.get_or_compute(
+            self.final_slot(),
+            "get_async_pool_messages",
+            &(offset, limit),
+            || Ok(execution_controller.get_async_pool_messages(offset, limit)),
+        )?;
+
+        let messages = messages
+            .into_iter()
+            .enumerate()
+            .map(|(index, message)| AsyncMessageInfo::new(offset + index, message))
+            .collect();
+        Ok(PagedVec::from_page(messages, total_count))
+    }
 
-        Ok(paged_vec)
+    async fn get_scheduled_transfers(
+        &self,
+        addresses: Vec<Address>,
+    ) -> RpcResult<Vec<ScheduledTransferInfo>> {
+        if addresses.len() as u64 > self.0.api_settings.max_arguments {
+            return Err(ApiError::BadRequest("too many arguments".into()).into());
+        }
+        let execution_controller = self.0.execution_controller.clone();
+        Ok(addresses
+            .iter()
+            .flat_map(|address| execution_controller.get_scheduled_transfers(address))
+            .filter_map(|message| ScheduledTransferInfo::from_message(&message))
+            .collect())
     }
 
     async fn get_operations(&self, ops: Vec<OperationId>) -> RpcResult<Vec<OperationInfo>> {
@@ -531,6 +1131,9 @@ impl MassaRpcServer for API<Public> {
             .into_iter()
             .unzip();
 
+        // the final blocks including the operations, from the operation index
+        let final_inclusions = self.0.execution_controller.get_operation_inclusions(&ops);
+
         // gather all values into a vector of OperationInfo instances
         let mut res: Vec<OperationInfo> = Vec::with_capacity(ops.len());
         let zipped_iterator = izip!(
@@ -539,9 +1142,16 @@ impl MassaRpcServer for API<Public> {
             in_pool.into_iter(),
             is_operation_final.into_iter(),
             statuses.into_iter(),
+            final_inclusions.into_iter(),
         );
-        for (id, (operation, in_blocks), in_pool, is_operation_final, op_exec_status) in
-            zipped_iterator
+        for (
+            id,
+            (operation, in_blocks),
+            in_pool,
+            is_operation_final,
+            op_exec_status,
+            final_inclusion,
+        ) in zipped_iterator
         {
             res.push(OperationInfo {
                 id,
@@ -553,6 +1163,7 @@ impl MassaRpcServer for API<Public> {
                 operation,
                 in_blocks: in_blocks.into_iter().collect(),
                 op_exec_status,
+                final_inclusion,
             });
         }
 
@@ -641,16 +1252,35 @@ impl MassaRpcServer for API<Public> {
 
     /// gets a block(s). Returns nothing if not found
     /// only active blocks are returned
-    async fn get_blocks(&self, ids: Vec<BlockId>) -> RpcResult<Vec<BlockInfo>> {
+    async fn get_blocks(
+        &self,
+        ids: Vec<BlockId>,
+        fields: Option<BlockFieldMask>,
+    ) -> RpcResult<Vec<BlockInfo>> {
         let consensus_controller = self.0.consensus_controller.clone();
         let storage = self.0.storage.clone_without_refs();
+        let fields = fields.unwrap_or_default();
+        // the final blocks that left the storage are still known by the operation index
+        let indexed_operations = self.0.execution_controller.get_final_block_operations(&ids);
         let blocks = ids
             .into_iter()
-            .filter_map(|id| {
-                let content = if let Some(wrapped_block) = storage.read_blocks().get(&id) {
-                    wrapped_block.content.clone()
-                } else {
-                    return None;
+            .zip(indexed_operations)
+            .filter_map(|(id, final_operations)| {
+                let (block, header) = match storage.read_blocks().get(&id) {
+                    Some(wrapped_block) => match fields {
+                        BlockFieldMask::Full => (Some(wrapped_block.content.clone()), None),
+                        BlockFieldMask::HeadersOnly => {
+                            (None, Some(wrapped_block.content.header.clone()))
+                        }
+                        BlockFieldMask::IdsOnly => (None, None),
+                    },
+                    None => {
+                        return final_operations.map(|final_operations| BlockInfo {
+                            id,
+                            content: None,
+                            final_operations: Some(final_operations),
+                        })
+                    }
                 };
 
                 if let Some(graph_status) = consensus_controller
@@ -671,8 +1301,10 @@ impl MassaRpcServer for API<Public> {
                             is_in_blockclique,
                             is_candidate,
                             is_discarded,
-                            block: content,
+                            block,
+                            header,
                         }),
+                        final_operations,
                     });
                 }
 
@@ -701,62 +1333,154 @@ impl MassaRpcServer for API<Public> {
         Ok(res)
     }
 
-    /// gets an interval of the block graph from consensus, with time filtering
-    /// time filtering is done consensus-side to prevent communication overhead
-    async fn get_graph_interval(&self, time: TimeInterval) -> RpcResult<Vec<BlockSummary>> {
-        let consensus_controller = self.0.consensus_controller.clone();
-        let api_settings = self.0.api_settings.clone();
+    async fn get_slot_detail(&self, slot: Slot) -> RpcResult<SlotDetail> {
+        let block = self
+            .0
+            .consensus_controller
+            .get_blockclique_block_at_slot(slot)
+            .and_then(|block_id| {
+                let storage = self.0.storage.clone_without_refs();
+                let read_blocks = storage.read_blocks();
+                let read_ops = storage.read_operations();
+                read_blocks.get(&block_id).map(|block| FilledBlock {
+                    header: block.content.header.clone(),
+                    operations: block
+                        .content
+                        .operations
+                        .iter()
+                        .map(|op_id| (*op_id, read_ops.get(op_id).cloned()))
+                        .collect(),
+                })
+            });
 
-        // filter blocks from graph_export
-        let time_range_to_slot_range_result = time_range_to_slot_range(
-            api_settings.thread_count,
-            api_settings.t0,
-            api_settings.genesis_timestamp,
-            time.start,
-            time.end,
-        );
+        let end = slot
+            .get_next_slot(self.0.api_settings.thread_count)
+            .map_err(ApiError::ModelsError)?;
+        let events = self
+            .0
+            .execution_controller
+            .get_filtered_sc_output_event(EventFilter {
+                start: Some(slot),
+                end: Some(end),
+                emitter_address: None,
+                original_caller_address: None,
+                original_operation_id: None,
+                is_final: None,
+                is_error: None,
+            });
 
-        let (start_slot, end_slot) = match time_range_to_slot_range_result {
-            Ok(time_range_to_slot_range) => time_range_to_slot_range,
-            Err(e) => return Err(ApiError::ModelsError(e).into()),
-        };
+        let state_changes = self
+            .0
+            .execution_controller
+            .get_slot_state_changes(slot)
+            .map(|changes| StateChangesSummary::from(&changes));
+
+        Ok(SlotDetail {
+            slot,
+            is_final: slot <= self.final_slot(),
+            block,
+            events,
+            state_changes,
+        })
+    }
 
-        let graph = match consensus_controller.get_block_graph_status(start_slot, end_slot) {
-            Ok(graph) => graph,
-            Err(e) => return Err(ApiError::ConsensusError(e).into()),
-        };
+    async fn get_slot_beacons(&self, slots: Vec<Slot>) -> RpcResult<Vec<SlotBeacon>> {
+        if slots.len() as u64 > self.0.api_settings.max_arguments {
+            return Err(ApiError::BadRequest("too many arguments".into()).into());
+        }
 
-        let mut res = Vec::with_capacity(graph.active_blocks.len());
-        let blockclique = graph
-            .max_cliques
-            .iter()
-            .find(|clique| clique.is_blockclique)
-            .ok_or_else(|| ApiError::InconsistencyError("missing blockclique".to_string()))?;
-        for (id, exported_block) in graph.active_blocks.into_iter() {
-            res.push(BlockSummary {
-                id,
-                is_final: exported_block.is_final,
-                is_stale: false,
-                is_in_blockclique: blockclique.block_ids.contains(&id),
-                slot: exported_block.header.content.slot,
-                creator: exported_block.header.content_creator_address,
-                parents: exported_block.header.content.parents,
-            });
+        let final_state = self.0.final_state.read();
+        let mut res = Vec::with_capacity(slots.len());
+        for slot in slots {
+            let beacon = final_state.pos_state.get_slot_beacon(slot).map_err(|err| {
+                ApiError::BadRequest(format!("no beacon for slot {}: {}", slot, err))
+            })?;
+            res.push(SlotBeacon { slot, beacon });
         }
-        for (id, (reason, (slot, creator, parents))) in graph.discarded_blocks.into_iter() {
-            if reason == DiscardReason::Stale {
-                res.push(BlockSummary {
-                    id,
-                    is_final: false,
-                    is_stale: true,
-                    is_in_blockclique: false,
-                    slot,
-                    creator,
-                    parents,
+        Ok(res)
+    }
+
+    async fn get_pos_history_ranges(&self) -> RpcResult<PoSHistoryRanges> {
+        Ok(self.0.final_state.read().pos_state.get_history_ranges())
+    }
+
+    async fn get_slot_calendar(&self, time: TimeInterval) -> RpcResult<SlotCalendar> {
+        let cfg = &self.0.api_settings;
+        let end_time = time
+            .end
+            .ok_or_else(|| ApiError::BadRequest("missing end of the time range".into()))?;
+        let start_time = time.start.unwrap_or(cfg.genesis_timestamp);
+
+        let slots = slots_in_time_range(
+            cfg.thread_count,
+            cfg.t0,
+            cfg.genesis_timestamp,
+            start_time,
+            end_time,
+        )
+        .map_err(ApiError::ModelsError)?;
+        if slots.size_hint().0 as u64 > cfg.max_arguments {
+            return Err(ApiError::BadRequest(format!(
+                "too many slots in the time range: at most {}",
+                cfg.max_arguments
+            ))
+            .into());
+        }
+
+        let mut calendar = SlotCalendar {
+            slots: Vec::with_capacity(slots.size_hint().0),
+            cycles: Vec::new(),
+        };
+        for slot in slots {
+            let cycle = slot.get_cycle(cfg.periods_per_cycle);
+            if calendar.cycles.last().map(|bounds| bounds.cycle) != Some(cycle) {
+                let (first_slot, last_slot) =
+                    get_cycle_slot_bounds(cycle, cfg.periods_per_cycle, cfg.thread_count)
+                        .map_err(ApiError::ModelsError)?;
+                let (start_timestamp, end_timestamp) = get_cycle_time_range(
+                    cycle,
+                    cfg.periods_per_cycle,
+                    cfg.thread_count,
+                    cfg.t0,
+                    cfg.genesis_timestamp,
+                )
+                .map_err(ApiError::ModelsError)?;
+                calendar.cycles.push(CycleBounds {
+                    cycle,
+                    first_slot,
+                    last_slot,
+                    start_timestamp,
+                    end_timestamp,
                 });
             }
+            let timestamp =
+                get_block_slot_timestamp(cfg.thread_count, cfg.t0, cfg.genesis_timestamp, slot)
+                    .map_err(ApiError::ModelsError)?;
+            calendar.slots.push(SlotTiming {
+                slot,
+                timestamp,
+                cycle,
+            });
         }
-        Ok(res)
+        Ok(calendar)
+    }
+
+    /// gets an interval of the block graph from consensus, with time filtering
+    /// time filtering is done consensus-side to prevent communication overhead
+    async fn get_graph_interval(&self, time: TimeInterval) -> RpcResult<Vec<BlockSummary>> {
+        self.0
+            .cache
+            .get_or_compute(self.final_slot(), "get_graph_interval", &time, || {
+                self.compute_graph_interval(time)
+            })
+    }
+
+    async fn get_graph_page(&self, request: GraphPageRequest) -> RpcResult<GraphPage> {
+        self.0
+            .cache
+            .get_or_compute(self.final_slot(), "get_graph_page", &request, || {
+                self.compute_graph_page(request.clone())
+            })
     }
 
     async fn get_datastore_entries(
@@ -779,133 +1503,211 @@ impl MassaRpcServer for API<Public> {
             .collect())
     }
 
+    async fn get_datastore_entry_chunks(
+        &self,
+        entries: Vec<DatastoreEntryChunkInput>,
+    ) -> RpcResult<Vec<DatastoreEntryChunkOutput>> {
+        let api_cfg = &self.0.api_settings;
+        if entries.len() as u64 > api_cfg.max_arguments {
+            return Err(ApiError::BadRequest("too many arguments".into()).into());
+        }
+        Ok(self
+            .0
+            .execution_controller
+            .get_final_and_active_data_entry_chunk(
+                entries
+                    .into_iter()
+                    .map(|input| {
+                        let length = input.length.min(api_cfg.max_datastore_chunk_size);
+                        (input.address, input.key, input.offset, length)
+                    })
+                    .collect(),
+            )
+            .into_iter()
+            .map(|(final_chunk, candidate_chunk)| DatastoreEntryChunkOutput {
+                final_chunk,
+                candidate_chunk,
+            })
+            .collect())
+    }
+
     async fn get_addresses(&self, addresses: Vec<Address>) -> RpcResult<Vec<AddressInfo>> {
-        // get info from storage about which blocks the addresses have created
-        let created_blocks: Vec<PreHashSet<BlockId>> = {
-            let lck = self.0.storage.read_blocks();
-            addresses
-                .iter()
-                .map(|address| {
-                    lck.get_blocks_created_by(address)
-                        .cloned()
-                        .unwrap_or_default()
-                })
-                .collect()
-        };
+        self.0
+            .cache
+            .get_or_compute(self.final_slot(), "get_addresses", &addresses, || {
+                self.compute_addresses(addresses.clone())
+            })
+    }
 
-        // get info from storage about which operations the addresses have created
-        let created_operations: Vec<PreHashSet<OperationId>> = {
-            let lck = self.0.storage.read_operations();
-            addresses
-                .iter()
-                .map(|address| {
-                    lck.get_operations_created_by(address)
-                        .cloned()
-                        .unwrap_or_default()
-                })
-                .collect()
-        };
+    async fn get_selection_forecast(
+        &self,
+        addresses: Vec<Address>,
+        cycle_count: u64,
+    ) -> RpcResult<Vec<AddressSelectionForecast>> {
+        let api_cfg = &self.0.api_settings;
+        if addresses.len() as u64 > api_cfg.max_arguments || cycle_count > api_cfg.max_arguments {
+            return Err(ApiError::BadRequest("too many arguments".into()).into());
+        }
 
-        // get info from storage about which endorsements the addresses have created
-        let created_endorsements: Vec<PreHashSet<EndorsementId>> = {
-            let lck = self.0.storage.read_endorsements();
-            addresses
-                .iter()
-                .map(|address| {
-                    lck.get_endorsements_created_by(address)
-                        .cloned()
-                        .unwrap_or_default()
-                })
-                .collect()
+        let cur_slot = timeslots::get_current_latest_block_slot(
+            api_cfg.thread_count,
+            api_cfg.t0,
+            api_cfg.genesis_timestamp,
+        )
+        .map_err(ApiError::ModelsError)?
+        .unwrap_or_else(|| Slot::new(0, 0));
+        let cur_cycle = cur_slot.get_cycle(api_cfg.periods_per_cycle);
+        let last_cycle = cur_cycle.saturating_add(cycle_count);
+
+        // concrete draws are available up to the last drawn cycle
+        let last_drawn_cycle = self
+            .0
+            .selector_controller
+            .get_last_drawn_cycle()
+            .map_err(|err| ApiError::InternalServerError(err.to_string()))?;
+        let draws_end_cycle = match last_drawn_cycle {
+            Some(cycle) => cycle.min(last_cycle).saturating_add(1),
+            None => cur_cycle,
         };
+        let draws_end = Slot::new_first_of_cycle(draws_end_cycle, api_cfg.periods_per_cycle)
+            .map_err(ApiError::ModelsError)?;
 
-        // get execution info
-        let execution_infos = self.0.execution_controller.get_addresses_infos(&addresses);
-
-        // get future draws from selector
-        let selection_draws = {
-            let cur_slot = timeslots::get_current_latest_block_slot(
-                self.0.api_settings.thread_count,
-                self.0.api_settings.t0,
-                self.0.api_settings.genesis_timestamp,
-            )
-            .expect("could not get latest current slot")
-            .unwrap_or_else(|| Slot::new(0, 0));
-            let slot_end = Slot::new(
-                cur_slot
-                    .period
-                    .saturating_add(self.0.api_settings.draw_lookahead_period_count),
-                cur_slot.thread,
-            );
-            addresses
-                .iter()
-                .map(|addr| {
-                    self.0
-                        .selector_controller
-                        .get_address_selections(addr, cur_slot, slot_end)
-                        .unwrap_or_default()
-                })
-                .collect::<Vec<_>>()
-        };
+        // the following cycles are estimated from the latest known roll distribution:
+        // the rolls of the current cycle, which will be used for the draws of cycle + 3
+        let mut rolls = self
+            .0
+            .execution_controller
+            .get_cycle_active_rolls(cur_cycle.saturating_add(3));
+        if rolls.is_empty() {
+            rolls = self
+                .0
+                .execution_controller
+                .get_cycle_active_rolls(cur_cycle);
+        }
+        let total_rolls = rolls.values().fold(0u64, |acc, r| acc.saturating_add(*r));
+        let slot_count = api_cfg
+            .periods_per_cycle
+            .saturating_mul(api_cfg.thread_count as u64);
 
-        // compile results
         let mut res = Vec::with_capacity(addresses.len());
-        let iterator = izip!(
-            addresses.into_iter(),
-            created_blocks.into_iter(),
-            created_operations.into_iter(),
-            created_endorsements.into_iter(),
-            execution_infos.into_iter(),
-            selection_draws.into_iter(),
-        );
-        for (
-            address,
-            created_blocks,
-            created_operations,
-            created_endorsements,
-            execution_infos,
-            (next_block_draws, next_endorsement_draws),
-        ) in iterator
-        {
-            res.push(AddressInfo {
-                // general address info
+        for address in addresses {
+            let (next_block_draws, next_endorsement_draws) = self
+                .0
+                .selector_controller
+                .get_address_selections(&address, cur_slot, draws_end)
+                .unwrap_or_default();
+            let roll_count = rolls.get(&address).copied().unwrap_or_default();
+            let estimates = (draws_end_cycle.max(cur_cycle)..=last_cycle)
+                .map(|cycle| {
+                    CycleSelectionEstimate::new(
+                        cycle,
+                        roll_count,
+                        total_rolls,
+                        slot_count,
+                        api_cfg.endorsement_count,
+                    )
+                })
+                .collect();
+            res.push(AddressSelectionForecast {
                 address,
-                thread: address.get_thread(self.0.api_settings.thread_count),
+                last_drawn_cycle,
+                next_block_draws,
+                next_endorsement_draws,
+                estimates,
+            });
+        }
 
-                // final execution info
-                final_balance: execution_infos.final_balance,
-                final_roll_count: execution_infos.final_roll_count,
-                final_datastore_keys: execution_infos
-                    .final_datastore_keys
-                    .into_iter()
-                    .collect::<Vec<_>>(),
+        Ok(res)
+    }
 
-                // candidate execution info
-                candidate_balance: execution_infos.candidate_balance,
-                candidate_roll_count: execution_infos.candidate_roll_count,
-                candidate_datastore_keys: execution_infos
-                    .candidate_datastore_keys
-                    .into_iter()
-                    .collect::<Vec<_>>(),
+    async fn get_staking_rewards(
+        &self,
+        addresses: Vec<Address>,
+    ) -> RpcResult<Vec<AddressStakingRewards>> {
+        let api_cfg = &self.0.api_settings;
+        if addresses.len() as u64 > api_cfg.max_arguments {
+            return Err(ApiError::BadRequest("too many arguments".into()).into());
+        }
 
-                // deferred credits
-                deferred_credits: execution_infos
-                    .future_deferred_credits
-                    .into_iter()
-                    .map(|(slot, amount)| SlotAmount { slot, amount })
-                    .collect::<Vec<_>>(),
+        let execution_infos = self.0.execution_controller.get_addresses_infos(&addresses);
 
-                // selector info
-                next_block_draws,
-                next_endorsement_draws,
+        let mut res = Vec::with_capacity(addresses.len());
+        for (address, execution_info) in addresses.into_iter().zip(execution_infos) {
+            let mut total = Amount::default();
+            let mut cycles = Vec::with_capacity(execution_info.final_staking_rewards.len());
+            for (cycle, rewards) in execution_info.final_staking_rewards {
+                let first_slot = Slot::new_first_of_cycle(cycle, api_cfg.periods_per_cycle)
+                    .map_err(ApiError::ModelsError)?;
+                let last_slot =
+                    Slot::new_last_of_cycle(cycle, api_cfg.periods_per_cycle, api_cfg.thread_count)
+                        .map_err(ApiError::ModelsError)?;
+                total = total.saturating_add(rewards.total());
+                cycles.push(CycleStakingRewards {
+                    cycle,
+                    start_timestamp: timeslots::get_block_slot_timestamp(
+                        api_cfg.thread_count,
+                        api_cfg.t0,
+                        api_cfg.genesis_timestamp,
+                        first_slot,
+                    )
+                    .map_err(ApiError::ModelsError)?,
+                    end_timestamp: timeslots::get_block_slot_timestamp(
+                        api_cfg.thread_count,
+                        api_cfg.t0,
+                        api_cfg.genesis_timestamp,
+                        last_slot,
+                    )
+                    .map_err(ApiError::ModelsError)?,
+                    rewards,
+                });
+            }
+            res.push(AddressStakingRewards {
+                address,
+                total,
+                cycles,
+            });
+        }
 
-                // created objects
-                created_blocks: created_blocks.into_iter().collect::<Vec<_>>(),
-                created_endorsements: created_endorsements.into_iter().collect::<Vec<_>>(),
-                created_operations: created_operations.into_iter().collect::<Vec<_>>(),
+        Ok(res)
+    }
 
-                // cycle infos
-                cycle_infos: execution_infos.cycle_infos,
+    async fn get_storage_rent(
+        &self,
+        addresses: Vec<Address>,
+    ) -> RpcResult<Vec<AddressStorageRent>> {
+        let api_cfg = &self.0.api_settings;
+        if addresses.len() as u64 > api_cfg.max_arguments {
+            return Err(ApiError::BadRequest("too many arguments".into()).into());
+        }
+
+        let rent_infos = self.0.execution_controller.get_storage_rent(&addresses);
+
+        let mut res = Vec::with_capacity(addresses.len());
+        for (address, rent_info) in addresses.into_iter().zip(rent_infos) {
+            let reclamation_timestamp = match rent_info.reclamation_cycle {
+                Some(cycle) => {
+                    let first_slot = Slot::new_first_of_cycle(cycle, api_cfg.periods_per_cycle)
+                        .map_err(ApiError::ModelsError)?;
+                    Some(
+                        timeslots::get_block_slot_timestamp(
+                            api_cfg.thread_count,
+                            api_cfg.t0,
+                            api_cfg.genesis_timestamp,
+                            first_slot,
+                        )
+                        .map_err(ApiError::ModelsError)?,
+                    )
+                }
+                None => None,
+            };
+            res.push(AddressStorageRent {
+                address,
+                storage_bytes: rent_info.storage_bytes,
+                rent_per_cycle: rent_info.rent_per_cycle,
+                paid_cycles: rent_info.paid_cycles,
+                rent_due_since: rent_info.rent_due_since,
+                reclamation_cycle: rent_info.reclamation_cycle,
+                reclamation_timestamp,
             });
         }
 
@@ -915,52 +1717,8 @@ impl MassaRpcServer for API<Public> {
     async fn send_operations(&self, ops: Vec<OperationInput>) -> RpcResult<Vec<OperationId>> {
         let mut cmd_sender = self.0.pool_command_sender.clone();
         let mut protocol_sender = self.0.protocol_command_sender.clone();
-        let api_cfg = self.0.api_settings.clone();
         let mut to_send = self.0.storage.clone_without_refs();
-
-        if ops.len() as u64 > api_cfg.max_arguments {
-            return Err(ApiError::BadRequest("too many arguments".into()).into());
-        }
-        let operation_deserializer = SecureShareDeserializer::new(OperationDeserializer::new(
-            api_cfg.max_datastore_value_length,
-            api_cfg.max_function_name_length,
-            api_cfg.max_parameter_size,
-            api_cfg.max_op_datastore_entry_count,
-            api_cfg.max_op_datastore_key_length,
-            api_cfg.max_op_datastore_value_length,
-        ));
-        let verified_ops = ops
-            .into_iter()
-            .map(|op_input| {
-                let mut op_serialized = Vec::new();
-                op_serialized.extend(op_input.signature.to_bytes());
-                op_serialized.extend(op_input.creator_public_key.to_bytes());
-                op_serialized.extend(op_input.serialized_content);
-                let (rest, op): (&[u8], SecureShareOperation) = operation_deserializer
-                    .deserialize::<DeserializeError>(&op_serialized)
-                    .map_err(|err| {
-                        ApiError::ModelsError(ModelsError::DeserializeError(err.to_string()))
-                    })?;
-                if rest.is_empty() {
-                    Ok(op)
-                } else {
-                    Err(ApiError::ModelsError(ModelsError::DeserializeError(
-                        "There is data left after operation deserialization".to_owned(),
-                    ))
-                    .into())
-                }
-            })
-            .map(|op| match op {
-                Ok(operation) => {
-                    let _verify_signature = match operation.verify_signature() {
-                        Ok(()) => (),
-                        Err(e) => return Err(ApiError::ModelsError(e).into()),
-                    };
-                    Ok(operation)
-                }
-                Err(e) => Err(e),
-            })
-            .collect::<RpcResult<Vec<SecureShareOperation>>>()?;
+        let verified_ops = self.verify_operations(ops)?;
         to_send.store_operations(verified_ops.clone());
         let ids: Vec<OperationId> = verified_ops.iter().map(|op| op.id).collect();
         cmd_sender.add_operations(to_send.clone());
@@ -974,6 +1732,28 @@ impl MassaRpcServer for API<Public> {
         Ok(ids)
     }
 
+    async fn send_private_operations(
+        &self,
+        ops: Vec<OperationInput>,
+    ) -> RpcResult<Vec<OperationId>> {
+        let mut cmd_sender = self.0.pool_command_sender.clone();
+        let mut to_send = self.0.storage.clone_without_refs();
+        let verified_ops = self.verify_operations(ops)?;
+        let ids: Vec<OperationId> = verified_ops.iter().map(|op| op.id).collect();
+        to_send.store_operations(verified_ops);
+        cmd_sender.add_private_operations(to_send.clone());
+
+        tokio::spawn(release_private_operations(
+            to_send,
+            self.0.api_settings.private_relay_delay,
+            self.0.api_settings.private_relay_expiry,
+            self.0.execution_controller.clone(),
+            cmd_sender,
+            self.0.protocol_command_sender.clone(),
+        ));
+        Ok(ids)
+    }
+
     /// Get events optionally filtered by:
     /// * start slot
     /// * end slot
@@ -993,10 +1773,64 @@ impl MassaRpcServer for API<Public> {
         Ok(events)
     }
 
+    async fn query_index(&self, query: IndexQuery) -> RpcResult<Vec<QueryRow>> {
+        let address = query.address;
+        let rows: Vec<QueryRow> = match query.index {
+            QueryIndex::OperationsByCreator => {
+                let thread = address.get_thread(self.0.api_settings.thread_count);
+                let read_operations = self.0.storage.read_operations();
+                read_operations
+                    .get_operations_created_by(&address)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|id| read_operations.get(id))
+                    .map(|operation| QueryRow::Operation {
+                        id: operation.id,
+                        expire_period: operation.content.expire_period,
+                        thread,
+                        fee: operation.content.fee,
+                        memo: match &operation.content.op {
+                            OperationType::Transaction { memo, .. } => memo.clone(),
+                            _ => None,
+                        },
+                    })
+                    .collect()
+            }
+            QueryIndex::BlocksByCreator => {
+                let read_blocks = self.0.storage.read_blocks();
+                read_blocks
+                    .get_blocks_created_by(&address)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|id| read_blocks.get(id))
+                    .map(|block| QueryRow::Block {
+                        id: block.id,
+                        slot: block.content.header.content.slot,
+                    })
+                    .collect()
+            }
+            QueryIndex::EventsByEmitter => self
+                .0
+                .execution_controller
+                .get_filtered_sc_output_event(EventFilter {
+                    emitter_address: Some(address),
+                    ..Default::default()
+                })
+                .into_iter()
+                .map(QueryRow::Event)
+                .collect(),
+        };
+        Ok(query.apply(rows, self.0.api_settings.max_query_results)?)
+    }
+
     async fn node_peers_whitelist(&self) -> RpcResult<Vec<IpAddr>> {
         crate::wrong_api::<Vec<IpAddr>>()
     }
 
+    async fn node_peer_message_stats(&self) -> RpcResult<Vec<PeerMessageStats>> {
+        crate::wrong_api::<Vec<PeerMessageStats>>()
+    }
+
     async fn node_add_to_peers_whitelist(&self, _: Vec<IpAddr>) -> RpcResult<()> {
         crate::wrong_api::<()>()
     }
@@ -1033,6 +1867,10 @@ impl MassaRpcServer for API<Public> {
         crate::wrong_api::<()>()
     }
 
+    async fn get_mip_status(&self) -> RpcResult<Vec<MipStatus>> {
+        Ok(self.0.final_state.read().mip_store.get_status())
+    }
+
     async fn get_openrpc_spec(&self) -> RpcResult<Value> {
         let openrpc_spec_path = self.0.api_settings.openrpc_spec_path.clone();
         let openrpc: RpcResult<Value> = std::fs::read_to_string(openrpc_spec_path)
@@ -1056,3 +1894,61 @@ impl MassaRpcServer for API<Public> {
         openrpc
     }
 }
+
+/// Maps the result of a read-only execution to the response sent to the client
+fn read_only_response(
+    result: Result<ReadOnlyExecutionOutput, ExecutionError>,
+) -> ExecuteReadOnlyResponse {
+    ExecuteReadOnlyResponse {
+        executed_at: result
+            .as_ref()
+            .map_or_else(|_| Slot::new(0, 0), |v| v.out.slot),
+        result: result.as_ref().map_or_else(
+            |err| ReadOnlyResult::Error(format!("readonly call failed: {}", err)),
+            |res| ReadOnlyResult::Ok(res.call_result.clone()),
+        ),
+        gas_cost: result.as_ref().map_or_else(|_| 0, |v| v.gas_cost),
+        error: result.as_ref().err().map(|err| ExecutionErrorInfo {
+            code: err.code(),
+            message: err.to_string(),
+            revert_reason: err.revert_reason().map(|reason| reason.to_vec()),
+        }),
+        usage: result.as_ref().ok().map(|v| v.usage),
+        output_events: result
+            .as_ref()
+            .map_or_else(|_| Default::default(), |v| v.out.events.clone().0),
+        state_changes: result.map_or_else(|_| Default::default(), |v| v.out.state_changes),
+    }
+}
+
+/// Summary of a block of the graph as returned by the graph queries
+fn block_summary(summary: &ExportBlockSummary) -> BlockSummary {
+    BlockSummary {
+        id: summary.id,
+        is_final: summary.is_final,
+        is_stale: summary.is_stale,
+        is_in_blockclique: summary.is_in_blockclique,
+        slot: summary.slot,
+        creator: summary.creator,
+        parents: summary.parents.clone(),
+    }
+}
+
+/// Token of the graph page following the block `id` at `slot`
+fn graph_page_token(slot: Slot, id: BlockId) -> String {
+    format!("{}-{}-{}", slot.period, slot.thread, id)
+}
+
+/// Block after which the graph page of `token` starts
+fn parse_graph_page_token(token: &str) -> Result<(Slot, BlockId), ApiError> {
+    let invalid = || ApiError::BadRequest(format!("invalid page token {}", token));
+    let mut parts = token.splitn(3, '-');
+    let (Some(period), Some(thread), Some(id)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(invalid());
+    };
+    let slot = Slot::new(
+        period.parse().map_err(|_| invalid())?,
+        thread.parse().map_err(|_| invalid())?,
+    );
+    Ok((slot, id.parse().map_err(|_| invalid())?))
+}
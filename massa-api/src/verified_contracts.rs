@@ -0,0 +1,58 @@
+//! Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Contracts verified by the node operator.
+//!
+//! The operator registers through the private API the source of the contracts whose compilation they checked,
+//! keyed by the hash of the resulting bytecode. The registry is kept in the JSON file at `verified_contracts_path`,
+//! and the public API returns the registered verification of the bytecode of each address in `get_addresses`.
+//! As the responses of `get_addresses` are cached until the next finalization, a registration shows up there within a slot.
+
+use jsonrpsee::core::RpcResult;
+use massa_api_exports::{address::VerifiedContract, error::ApiError};
+use massa_hash::Hash;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Reads the verified contracts registered in `path`, indexed by bytecode hash. A missing file means no contract.
+pub(crate) fn read_verified_contracts(path: &Path) -> RpcResult<HashMap<Hash, VerifiedContract>> {
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+    let json = std::fs::read_to_string(path).map_err(|e| {
+        ApiError::InternalServerError(format!("failed to read the verified contracts file: {}", e))
+    })?;
+    let contracts: Vec<VerifiedContract> = serde_json::from_str(&json).map_err(|e| {
+        ApiError::InternalServerError(format!(
+            "failed to parse the verified contracts file: {}",
+            e
+        ))
+    })?;
+    Ok(contracts
+        .into_iter()
+        .map(|contract| (contract.bytecode_hash, contract))
+        .collect())
+}
+
+/// Writes the verified `contracts` in `path`, sorted by name
+pub(crate) fn write_verified_contracts(
+    path: &Path,
+    contracts: HashMap<Hash, VerifiedContract>,
+) -> RpcResult<()> {
+    let mut contracts: Vec<VerifiedContract> = contracts.into_values().collect();
+    contracts.sort_by_cached_key(|contract| {
+        (
+            contract.name.clone(),
+            contract.bytecode_hash.to_bs58_check(),
+        )
+    });
+    let json = serde_json::to_string_pretty(&contracts).map_err(|e| {
+        ApiError::InternalServerError(format!("failed to serialize the verified contracts: {}", e))
+    })?;
+    std::fs::write(path, json).map_err(|e| {
+        ApiError::InternalServerError(format!(
+            "failed to write the verified contracts file: {}",
+            e
+        ))
+        .into()
+    })
+}
@@ -24,6 +24,8 @@
 //!     "validity_start": {"period": 123456, "thread": 12},  // the message can be handled starting from the validity_start slot (included)
 //!     "validity_end": {"period": 123457, "thread": 16},  // the message can be handled until the validity_end slot (excluded)
 //!     "max_gas": 12334,  // max gas available when the handler is called
+//!     "fee": "0.5",  // fee burned on emission
+//!     "priority_fee": "0.1",  // additional fee paid to the producer of the block executing the message
 //!     "coins": "1111.11",  // amount of coins to transfer to the destination address when calling its handler
 //!     "data": { ... any object ... }  // data payload of the message, passed as the sole parameter of the destination handler when called
 //! }
@@ -39,16 +41,20 @@
 //!
 //! Note that `fee + coins` coins are burned when sending the message.
 //!
+//! A message can also offer a `priority_fee`, at most the configured `max_async_priority_fee`, to be executed before the others.
+//! It is consumed from the sender on emission with `fee + coins`, paid to the producer of the block at the slot where
+//! the message is executed (burned if that slot is a miss), and reimbursed with `coins` if the message is deleted without being executed.
+//!
 //! ## How is the `AsyncPool` handled
 //! ```md
-//! * In the AsyncPool, Messages are kept sorted by `priority = AsyncMessageId(rev(Ratio(msg.fee, max(msg.max_gas,1))), rev(msg.slot), rev(msg.emission_index))`
+//! * In the AsyncPool, Messages are kept sorted by `priority = AsyncMessageId(rev(Ratio(msg.fee + msg.priority_fee, max(msg.max_gas,1))), rev(msg.slot), rev(msg.emission_index))`
 //!
 //! * when an AsyncMessage is added to the AsyncPool:
 //!   * if the AsyncPool length has exceeded config.max_async_pool_length:
-//!     * remove the lowest-priority message and reimburse "coins" to the message sender
+//!     * remove the lowest-priority message and reimburse "coins" and "priority_fee" to the message sender
 //!
 //! * At every slot S :
-//!   * expired messages are deleted, and "coins" are credited back to the message sender
+//!   * expired messages are deleted, and "coins" and "priority_fee" are credited back to the message sender
 //!   * messages that are valid at slot S (in terms of validity_start, validity end) are popped in highest-to-lowest priority order until they accumulate max_async_gas_per_slot. For each selected message M in decreasing priority order:
 //!     * make sure that M.target_address exists and has a method called M.target_handler with the right signature, otherwise fail the execution
 //!     * credit target_address with M.coins
@@ -101,6 +107,7 @@ pub use config::AsyncPoolConfig;
 pub use message::{
     AsyncMessage, AsyncMessageDeserializer, AsyncMessageId, AsyncMessageIdDeserializer,
     AsyncMessageIdSerializer, AsyncMessageSerializer, AsyncMessageTrigger,
+    SCHEDULED_TRANSFER_HANDLER,
 };
 pub use pool::{AsyncPool, AsyncPoolDeserializer, AsyncPoolSerializer};
 
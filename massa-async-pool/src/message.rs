@@ -9,6 +9,7 @@ use massa_models::slot::{SlotDeserializer, SlotSerializer};
 use massa_models::{
     address::Address,
     amount::Amount,
+    operation::OperationId,
     serialization::{VecU8Deserializer, VecU8Serializer},
     slot::Slot,
 };
@@ -24,9 +25,18 @@ use num::rational::Ratio;
 use serde::{Deserialize, Serialize};
 use std::ops::Bound::{Excluded, Included};
 
+/// Handler of the messages holding a scheduled transfer: their coins are credited to the destination
+/// from their validity start, without executing any bytecode, and their data is the id of the scheduling operation.
+/// It starts with a zero byte and cannot be used by smart contracts.
+pub const SCHEDULED_TRANSFER_HANDLER: &str = "\0scheduled_transfer";
+
+/// Tag written in place of the trigger option to announce a priority fee, followed by the priority fee and the trigger option.
+/// It is not a valid option byte, so that messages without priority fee keep their serialization and their hash.
+const PRIORITY_FEE_TAG: u8 = b'p';
+
 /// Unique identifier of a message.
 /// Also has the property of ordering by priority (highest first) following the triplet:
-/// `(rev(Ratio(msg.fee, max(msg.max_gas,1))), emission_slot, emission_index)`
+/// `(rev(Ratio(msg.fee + msg.priority_fee, max(msg.max_gas,1))), emission_slot, emission_index)`
 pub type AsyncMessageId = (std::cmp::Reverse<Ratio<u64>>, Slot, u64);
 
 #[derive(Clone)]
@@ -272,6 +282,12 @@ pub struct AsyncMessage {
     /// Fee paid by the sender when the message is processed.
     pub fee: Amount,
 
+    /// Additional fee offered by the sender to be executed before the other messages.
+    /// It is spent by the sender address when the message is sent and paid to the producer of the block
+    /// executing the message. In case of discard, it is reimbursed to the sender.
+    #[serde(default)]
+    pub priority_fee: Amount,
+
     /// Coins sent from the sender to the target address of the message.
     /// Those coins are spent by the sender address when the message is sent,
     /// and credited to the destination address when receiving the message.
@@ -325,6 +341,7 @@ impl AsyncMessage {
             handler,
             max_gas,
             fee,
+            priority_fee: Amount::zero(),
             coins,
             validity_start,
             validity_end,
@@ -341,11 +358,38 @@ impl AsyncMessage {
         message
     }
 
+    /// Return the message with a priority fee of `priority_fee`, its hash recomputed
+    pub fn with_priority_fee(mut self, priority_fee: Amount) -> Self {
+        self.priority_fee = priority_fee;
+        self.compute_hash();
+        self
+    }
+
+    /// Fee per unit of gas paid by the sender, fee and priority fee included, in raw amount.
+    /// Messages are executed and kept on pool overflow by decreasing effective priority.
+    pub fn effective_priority(&self) -> Ratio<u64> {
+        let denom = if self.max_gas > 0 { self.max_gas } else { 1 };
+        Ratio::new(self.fee.saturating_add(self.priority_fee).to_raw(), denom)
+    }
+
+    /// Whether the message holds a scheduled transfer, see [`SCHEDULED_TRANSFER_HANDLER`]
+    pub fn is_scheduled_transfer(&self) -> bool {
+        self.handler == SCHEDULED_TRANSFER_HANDLER
+    }
+
+    /// Id of the operation that scheduled the transfer held by the message, `None` if it is not a scheduled transfer
+    pub fn scheduled_transfer_operation_id(&self) -> Option<OperationId> {
+        if !self.is_scheduled_transfer() {
+            return None;
+        }
+        let bytes = self.data.as_slice().try_into().ok()?;
+        Some(OperationId::from_bytes(bytes))
+    }
+
     /// Compute the ID of the message for use when choosing which operations to keep in priority (highest score) on pool overflow.
     pub fn compute_id(&self) -> AsyncMessageId {
-        let denom = if self.max_gas > 0 { self.max_gas } else { 1 };
         (
-            std::cmp::Reverse(Ratio::new(self.fee.to_raw(), denom)),
+            std::cmp::Reverse(self.effective_priority()),
             self.emission_slot,
             self.emission_index,
         )
@@ -447,6 +491,11 @@ impl Serializer<AsyncMessage> for AsyncMessageSerializer {
         self.slot_serializer
             .serialize(&value.validity_end, buffer)?;
         self.vec_u8_serializer.serialize(&value.data, buffer)?;
+        if !value.priority_fee.is_zero() {
+            buffer.push(PRIORITY_FEE_TAG);
+            self.amount_serializer
+                .serialize(&value.priority_fee, buffer)?;
+        }
         self.trigger_serializer.serialize(&value.trigger, buffer)?;
         Ok(())
     }
@@ -580,6 +629,24 @@ impl Deserializer<AsyncMessage> for AsyncMessageDeserializer {
                 context("Failed data deserialization", |input| {
                     self.data_deserializer.deserialize(input)
                 }),
+                context(
+                    "Failed priority_fee deserialization",
+                    |input: &'a [u8]| match input.first() {
+                        Some(&PRIORITY_FEE_TAG) => {
+                            let (rest, priority_fee) =
+                                self.amount_deserializer.deserialize(&input[1..])?;
+                            // a tagged zero priority fee would not serialize back to the same bytes
+                            if priority_fee.is_zero() {
+                                return Err(nom::Err::Error(ParseError::from_error_kind(
+                                    input,
+                                    nom::error::ErrorKind::Fail,
+                                )));
+                            }
+                            Ok((rest, priority_fee))
+                        }
+                        _ => Ok((input, Amount::zero())),
+                    },
+                ),
                 context("Failed filter deserialization", |input| {
                     self.trigger_deserializer.deserialize(input)
                 }),
@@ -598,9 +665,10 @@ impl Deserializer<AsyncMessage> for AsyncMessageDeserializer {
                 validity_start,
                 validity_end,
                 data,
+                priority_fee,
                 filter,
             )| {
-                AsyncMessage::new_with_hash(
+                let mut message = AsyncMessage {
                     emission_slot,
                     emission_index,
                     sender,
@@ -608,12 +676,18 @@ impl Deserializer<AsyncMessage> for AsyncMessageDeserializer {
                     handler,
                     max_gas,
                     fee,
+                    priority_fee,
                     coins,
                     validity_start,
                     validity_end,
                     data,
-                    filter,
-                )
+                    can_be_executed: filter.is_none(),
+                    trigger: filter,
+                    // placeholder hash to serialize the message, replaced below
+                    hash: Hash::from_bytes(&[0; 32]),
+                };
+                message.compute_hash();
+                message
             },
         )
         .parse(buffer)
@@ -622,18 +696,22 @@ impl Deserializer<AsyncMessage> for AsyncMessageDeserializer {
 
 #[cfg(test)]
 mod tests {
-    use massa_serialization::{DeserializeError, Deserializer, Serializer};
+    use massa_hash::Hash;
+    use massa_serialization::{
+        DeserializeError, Deserializer, OptionSerializer, Serializer, U64VarIntSerializer,
+    };
 
     use crate::{AsyncMessage, AsyncMessageDeserializer, AsyncMessageSerializer};
     use massa_models::{
-        address::Address,
-        amount::Amount,
+        address::{Address, AddressSerializer},
+        amount::{Amount, AmountSerializer},
         config::{MAX_ASYNC_MESSAGE_DATA, MAX_DATASTORE_KEY_LENGTH, THREAD_COUNT},
-        slot::Slot,
+        serialization::VecU8Serializer,
+        slot::{Slot, SlotSerializer},
     };
     use std::str::FromStr;
 
-    use super::AsyncMessageTrigger;
+    use super::{AsyncMessageTrigger, AsyncMessageTriggerSerializer};
 
     #[test]
     fn bad_serialization_version() {
@@ -670,4 +748,109 @@ mod tests {
             .deserialize::<DeserializeError>(&serialized)
             .unwrap_err();
     }
+
+    fn test_message() -> AsyncMessage {
+        AsyncMessage::new_with_hash(
+            Slot::new(1, 2),
+            0,
+            Address::from_str("AU12dG5xP1RDEB5ocdHkymNVvvSJmUL9BgHwCksDowqmGWxfpm93x").unwrap(),
+            Address::from_str("AU12htxRWiEm8jDJpJptr6cwEhWNcCSFWstN1MLSa96DDkVM9Y42G").unwrap(),
+            String::from("test"),
+            10000000,
+            Amount::from_str("1").unwrap(),
+            Amount::from_str("2").unwrap(),
+            Slot::new(2, 0),
+            Slot::new(3, 0),
+            vec![1, 2, 3, 4],
+            Some(AsyncMessageTrigger {
+                address: Address::from_str("AU12htxRWiEm8jDJpJptr6cwEhWNcCSFWstN1MLSa96DDkVM9Y42G")
+                    .unwrap(),
+                datastore_key: Some(vec![5, 6]),
+            }),
+        )
+    }
+
+    #[test]
+    fn legacy_serialization_without_priority_fee() {
+        let message = test_message();
+        // serialization of the messages before the priority fee
+        let mut legacy = Vec::new();
+        SlotSerializer::new()
+            .serialize(&message.emission_slot, &mut legacy)
+            .unwrap();
+        U64VarIntSerializer::new()
+            .serialize(&message.emission_index, &mut legacy)
+            .unwrap();
+        AddressSerializer::new()
+            .serialize(&message.sender, &mut legacy)
+            .unwrap();
+        AddressSerializer::new()
+            .serialize(&message.destination, &mut legacy)
+            .unwrap();
+        legacy.push(message.handler.len() as u8);
+        legacy.extend(message.handler.as_bytes());
+        U64VarIntSerializer::new()
+            .serialize(&message.max_gas, &mut legacy)
+            .unwrap();
+        AmountSerializer::new()
+            .serialize(&message.fee, &mut legacy)
+            .unwrap();
+        AmountSerializer::new()
+            .serialize(&message.coins, &mut legacy)
+            .unwrap();
+        SlotSerializer::new()
+            .serialize(&message.validity_start, &mut legacy)
+            .unwrap();
+        SlotSerializer::new()
+            .serialize(&message.validity_end, &mut legacy)
+            .unwrap();
+        VecU8Serializer::new()
+            .serialize(&message.data, &mut legacy)
+            .unwrap();
+        OptionSerializer::new(AsyncMessageTriggerSerializer::new())
+            .serialize(&message.trigger, &mut legacy)
+            .unwrap();
+
+        let mut serialized = Vec::new();
+        AsyncMessageSerializer::new()
+            .serialize(&message, &mut serialized)
+            .unwrap();
+        assert_eq!(serialized, legacy);
+        assert_eq!(message.hash, Hash::compute_from(&legacy));
+
+        let (rest, deserialized) = AsyncMessageDeserializer::new(
+            THREAD_COUNT,
+            MAX_ASYNC_MESSAGE_DATA,
+            MAX_DATASTORE_KEY_LENGTH as u32,
+        )
+        .deserialize::<DeserializeError>(&legacy)
+        .unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(deserialized, message);
+        assert!(deserialized.priority_fee.is_zero());
+    }
+
+    #[test]
+    fn serialization_with_priority_fee() {
+        let message = test_message();
+        let with_priority_fee = message
+            .clone()
+            .with_priority_fee(Amount::from_str("0.5").unwrap());
+        assert_ne!(with_priority_fee.hash, message.hash);
+
+        let mut serialized = Vec::new();
+        AsyncMessageSerializer::new()
+            .serialize(&with_priority_fee, &mut serialized)
+            .unwrap();
+        let (rest, deserialized) = AsyncMessageDeserializer::new(
+            THREAD_COUNT,
+            MAX_ASYNC_MESSAGE_DATA,
+            MAX_DATASTORE_KEY_LENGTH as u32,
+        )
+        .deserialize::<DeserializeError>(&serialized)
+        .unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(deserialized, with_priority_fee);
+        assert_eq!(deserialized.hash, with_priority_fee.hash);
+    }
 }
@@ -11,7 +11,7 @@ use crate::{
 };
 use massa_hash::{Hash, HASH_SIZE_BYTES};
 use massa_ledger_exports::LedgerChanges;
-use massa_models::{slot::Slot, streaming_step::StreamingStep};
+use massa_models::{address::Address, slot::Slot, streaming_step::StreamingStep};
 use massa_serialization::{
     Deserializer, SerializeError, Serializer, U64VarIntDeserializer, U64VarIntSerializer,
 };
@@ -182,6 +182,30 @@ impl AsyncPool {
             .collect()
     }
 
+    /// Get the messages of the pool by decreasing priority, skipping the first `offset` ones
+    ///
+    /// # returns
+    /// At most `limit` messages and the number of messages in the pool
+    pub fn get_messages(&self, offset: usize, limit: usize) -> (Vec<AsyncMessage>, usize) {
+        let messages = self
+            .messages
+            .values()
+            .skip(offset)
+            .take(limit)
+            .cloned()
+            .collect();
+        (messages, self.messages.len())
+    }
+
+    /// Get the pending scheduled transfers of `sender`, by decreasing priority
+    pub fn get_scheduled_transfers(&self, sender: &Address) -> Vec<(AsyncMessageId, AsyncMessage)> {
+        self.messages
+            .iter()
+            .filter(|(_id, message)| message.is_scheduled_transfer() && message.sender == *sender)
+            .map(|(id, message)| (*id, message.clone()))
+            .collect()
+    }
+
     /// Get a part of the async pool.
     /// Used for bootstrap.
     ///
@@ -387,3 +411,112 @@ fn test_take_batch() {
     pool.take_batch_to_execute(Slot::new(2, 0), 19);
     assert_eq!(pool.messages.len(), 4);
 }
+
+#[test]
+fn test_take_batch_priority_fee() {
+    use massa_hash::Hash;
+    use massa_models::{
+        address::{Address, UserAddress},
+        amount::Amount,
+        slot::Slot,
+    };
+    use std::str::FromStr;
+
+    let config = AsyncPoolConfig {
+        thread_count: 2,
+        max_length: 10,
+        max_async_message_data: 1_000_000,
+        bootstrap_part_size: 100,
+    };
+    let mut pool = AsyncPool::new(config);
+    let address = Address::User(UserAddress(Hash::compute_from(b"abc")));
+    for (i, priority_fee) in ["0", "0.5", "0.2"].into_iter().enumerate() {
+        let message = AsyncMessage::new_with_hash(
+            Slot::new(0, 0),
+            i as u64,
+            address,
+            address,
+            "function".to_string(),
+            10,
+            Amount::from_str("0.1").unwrap(),
+            Amount::from_str("0.3").unwrap(),
+            Slot::new(1, 0),
+            Slot::new(3, 0),
+            Vec::new(),
+            None,
+        )
+        .with_priority_fee(Amount::from_str(priority_fee).unwrap());
+        pool.messages.insert(message.compute_id(), message);
+    }
+    let (messages, count) = pool.get_messages(0, 10);
+    assert_eq!(count, 3);
+    let indexes: Vec<u64> = messages.iter().map(|msg| msg.emission_index).collect();
+    assert_eq!(indexes, vec![1, 2, 0]);
+    // only the message with the highest priority fee fits in the available gas
+    let batch = pool.take_batch_to_execute(Slot::new(2, 0), 15);
+    assert_eq!(batch.len(), 1);
+    assert_eq!(batch[0].1.emission_index, 1);
+}
+
+#[test]
+fn test_get_scheduled_transfers() {
+    use crate::SCHEDULED_TRANSFER_HANDLER;
+    use massa_hash::Hash;
+    use massa_models::{
+        address::{Address, UserAddress},
+        amount::Amount,
+        operation::OperationId,
+        slot::Slot,
+    };
+    use std::str::FromStr;
+
+    let config = AsyncPoolConfig {
+        thread_count: 2,
+        max_length: 10,
+        max_async_message_data: 1_000_000,
+        bootstrap_part_size: 100,
+    };
+    let mut pool = AsyncPool::new(config);
+    let sender = Address::User(UserAddress(Hash::compute_from(b"abc")));
+    let other = Address::User(UserAddress(Hash::compute_from(b"def")));
+    let operation_id = OperationId::from_bytes(Hash::compute_from(b"op").to_bytes());
+    for (i, (from, handler)) in [
+        (sender, SCHEDULED_TRANSFER_HANDLER),
+        (sender, "function"),
+        (other, SCHEDULED_TRANSFER_HANDLER),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let message = AsyncMessage::new_with_hash(
+            Slot::new(0, 0),
+            i as u64,
+            from,
+            other,
+            handler.to_string(),
+            0,
+            Amount::zero(),
+            Amount::from_str("0.3").unwrap(),
+            Slot::new(1, 0),
+            Slot::new(2, 0),
+            operation_id.to_bytes().to_vec(),
+            None,
+        );
+        pool.messages.insert(message.compute_id(), message);
+    }
+    let transfers = pool.get_scheduled_transfers(&sender);
+    assert_eq!(transfers.len(), 1);
+    assert_eq!(transfers[0].1.emission_index, 0);
+    assert_eq!(
+        transfers[0].1.scheduled_transfer_operation_id(),
+        Some(operation_id)
+    );
+    assert_eq!(
+        pool.messages
+            .values()
+            .find(|message| message.emission_index == 1)
+            .unwrap()
+            .scheduled_transfer_operation_id(),
+        None
+    );
+}
@@ -1,9 +1,28 @@
 use humantime::format_duration;
-use std::{collections::HashSet, net::SocketAddr, sync::Arc, time::Duration};
+use std::{
+    collections::{BTreeMap, HashSet},
+    net::SocketAddr,
+    sync::Arc,
+    time::Duration,
+};
 
-use massa_final_state::FinalState;
+use massa_async_pool::{AsyncMessage, AsyncMessageId};
+use massa_consensus_exports::bootstrapable_graph::BootstrapableGraph;
+use massa_final_state::{FinalState, FinalStateHashes, StateChanges};
+use massa_ledger_exports::Key as LedgerKey;
 use massa_logging::massa_trace;
-use massa_models::{node::NodeId, streaming_step::StreamingStep, version::Version};
+use massa_models::{
+    block_id::BlockId,
+    checkpoint::TrustedCheckpoint,
+    config::{MAX_LIGHT_BOOTSTRAP_LEDGER_KEYS, NODE_CAPABILITIES},
+    node::NodeId,
+    operation::OperationId,
+    prehash::PreHashSet,
+    slot::Slot,
+    streaming_step::StreamingStep,
+    version::{Capabilities, Version},
+};
+use massa_pos_exports::{CycleInfo, DeferredCredits};
 use massa_signature::PublicKey;
 use massa_time::MassaTime;
 use parking_lot::RwLock;
@@ -11,20 +30,341 @@ use rand::{
     prelude::{SliceRandom, StdRng},
     SeedableRng,
 };
-use tokio::time::sleep;
+use tokio::{sync::mpsc, time::sleep};
 use tracing::{debug, info, warn};
 
 use crate::{
     client_binder::BootstrapClientBinder,
+    directory::fetch_directories,
     error::BootstrapError,
     messages::{BootstrapClientMessage, BootstrapServerMessage},
     settings::IpType,
-    BootstrapConfig, Establisher, GlobalBootstrapState,
+    BootstrapConfig, Establisher, GlobalBootstrapState, LightBootstrapState,
 };
 
+/// Part of the final state and consensus received from the server, waiting to be applied
+struct ReceivedBootstrapPart {
+    slot: Slot,
+    ledger_part: Vec<u8>,
+    async_pool_part: BTreeMap<AsyncMessageId, AsyncMessage>,
+    pos_cycle_part: Option<CycleInfo>,
+    pos_credits_part: DeferredCredits,
+    exec_ops_part: BTreeMap<Slot, PreHashSet<OperationId>>,
+    final_state_changes: Vec<(Slot, StateChanges)>,
+    consensus_part: BootstrapableGraph,
+    consensus_outdated_ids: PreHashSet<BlockId>,
+}
+
+/// How the stream of bootstrap parts ended
+enum BootstrapStreamEnd {
+    /// the server sent the whole state, attached to `slot` and with the given hashes
+    Finished {
+        slot: Slot,
+        final_state_hashes: FinalStateHashes,
+    },
+    /// the server could not resume from our last slot
+    SlotTooOld,
+    /// the applier stopped on an error
+    ApplierStopped,
+}
+
+/// Applies a received part to the final state and the consensus graph.
+///
+/// A part covers its own key range of each final state structure, so the ledger part is written to disk
+/// in a worker thread while the other structures are updated. The state changes that happened since the previous part
+/// are then applied in order.
+///
+/// Returns the message resuming the bootstrap after this part.
+fn apply_bootstrap_part(
+    final_state: &RwLock<FinalState>,
+    graph: &mut Option<BootstrapableGraph>,
+    part: ReceivedBootstrapPart,
+) -> Result<BootstrapClientMessage, BootstrapError> {
+    let ReceivedBootstrapPart {
+        slot,
+        ledger_part,
+        async_pool_part,
+        pos_cycle_part,
+        pos_credits_part,
+        exec_ops_part,
+        final_state_changes,
+        consensus_part,
+        consensus_outdated_ids,
+    } = part;
+
+    // Set final state
+    let mut write_final_state = final_state.write();
+    let final_state = &mut *write_final_state;
+    let (last_ledger_step, last_pool_step, last_cycle_step, last_credits_step, last_ops_step) =
+        std::thread::scope(|scope| {
+            let ledger = &final_state.ledger;
+            let ledger_worker = scope.spawn(move || ledger.set_ledger_part(ledger_part));
+            let last_pool_step = final_state.async_pool.set_pool_part(async_pool_part);
+            let last_cycle_step = final_state.pos_state.set_cycle_history_part(pos_cycle_part);
+            let last_credits_step = final_state
+                .pos_state
+                .set_deferred_credits_part(pos_credits_part);
+            let last_ops_step = final_state
+                .executed_ops
+                .set_executed_ops_part(exec_ops_part);
+            (
+                ledger_worker
+                    .join()
+                    .expect("the bootstrap ledger part worker panicked"),
+                last_pool_step,
+                last_cycle_step,
+                last_credits_step,
+                last_ops_step,
+            )
+        });
+    let last_ledger_step = last_ledger_step?;
+    for (changes_slot, changes) in final_state_changes.iter() {
+        final_state
+            .ledger
+            .apply_changes(changes.ledger_changes.clone(), *changes_slot);
+        final_state
+            .async_pool
+            .apply_changes_unchecked(&changes.async_pool_changes);
+        if !changes.pos_changes.is_empty() {
+            final_state.pos_state.apply_changes(
+                changes.pos_changes.clone(),
+                *changes_slot,
+                false,
+            )?;
+        }
+        if !changes.executed_ops_changes.is_empty() {
+            final_state
+                .executed_ops
+                .apply_changes(changes.executed_ops_changes.clone(), *changes_slot);
+        }
+    }
+    final_state.slot = slot;
+    final_state.shared_slot.set(slot);
+    drop(write_final_state);
+
+    // Set consensus blocks
+    if let Some(graph) = graph.as_mut() {
+        // Extend the final blocks with the received part
+        graph.final_blocks.extend(consensus_part.final_blocks);
+        // Remove every outdated block
+        graph
+            .final_blocks
+            .retain(|block_export| !consensus_outdated_ids.contains(&block_export.block.id));
+    } else {
+        *graph = Some(consensus_part);
+    }
+    let last_consensus_step = StreamingStep::Ongoing(
+        // Note that this unwrap call is safe because of the above conditional statement
+        graph
+            .as_ref()
+            .unwrap()
+            .final_blocks
+            .iter()
+            .map(|b_export| b_export.block.id)
+            .collect(),
+    );
+
+    // Message resuming the bootstrap in case of disconnection
+    let next_bootstrap_message = BootstrapClientMessage::AskBootstrapPart {
+        last_slot: Some(slot),
+        last_ledger_step,
+        last_pool_step,
+        last_cycle_step,
+        last_credits_step,
+        last_ops_step,
+        last_consensus_step,
+    };
+
+    // Logs for an easier diagnostic if needed
+    debug!(
+        "client final state bootstrap cursors: {:?}",
+        next_bootstrap_message
+    );
+    debug!(
+        "client final state slot changes length: {}",
+        final_state_changes.len()
+    );
+    Ok(next_bootstrap_message)
+}
+
+/// Applies the parts received in `parts`, in order, until the queue is closed or an application fails.
+/// Runs in a blocking thread so that the next parts are downloaded meanwhile.
+///
+/// Returns the consensus graph, the message resuming the bootstrap after the last applied part, if any,
+/// and the application error, if any.
+fn apply_bootstrap_parts(
+    final_state: Arc<RwLock<FinalState>>,
+    mut graph: Option<BootstrapableGraph>,
+    mut parts: mpsc::Receiver<ReceivedBootstrapPart>,
+) -> (
+    Option<BootstrapableGraph>,
+    Option<BootstrapClientMessage>,
+    Result<(), BootstrapError>,
+) {
+    let mut next_bootstrap_message = None;
+    while let Some(part) = parts.blocking_recv() {
+        match apply_bootstrap_part(&final_state, &mut graph, part) {
+            Ok(message) => next_bootstrap_message = Some(message),
+            Err(err) => return (graph, next_bootstrap_message, Err(err)),
+        }
+    }
+    (graph, next_bootstrap_message, Ok(()))
+}
+
+/// Cross-checks the bootstrapped final state against the consensus graph and the hashes sent by the server
+/// before the node starts participating: the final state must be attached to the streamed `slot`,
+/// none of the bootstrapped final blocks can be after that slot,
+/// and the recomputed hashes of the final state components must match `expected_hashes`.
+///
+/// On success, the final state hash is set to the checked one.
+pub(crate) fn check_bootstrapped_state(
+    final_state: &RwLock<FinalState>,
+    graph: Option<&BootstrapableGraph>,
+    slot: Slot,
+    expected_hashes: &FinalStateHashes,
+) -> Result<(), BootstrapError> {
+    let mut final_state = final_state.write();
+    if final_state.slot != slot {
+        return Err(BootstrapError::StateMismatch(format!(
+            "the final state is attached to slot {} but the server streamed the state at slot {}",
+            final_state.slot, slot
+        )));
+    }
+    let final_blocks = graph
+        .map(|graph| graph.final_blocks.as_slice())
+        .unwrap_or_default();
+    if let Some(b_export) = final_blocks
+        .iter()
+        .find(|b_export| b_export.block.content.header.content.slot > slot)
+    {
+        return Err(BootstrapError::StateMismatch(format!(
+            "the final block {} at slot {} is after the slot {} of the final state",
+            b_export.block.id, b_export.block.content.header.content.slot, slot
+        )));
+    }
+    let computed_hashes = final_state.get_state_hashes();
+    if computed_hashes != *expected_hashes {
+        let mismatches = computed_hashes.mismatches(expected_hashes);
+        return Err(BootstrapError::StateMismatch(format!(
+            "the final state hash at slot {} is {} but the server announced {}, differing components: {}",
+            slot,
+            computed_hashes.final_state,
+            expected_hashes.final_state,
+            if mismatches.is_empty() {
+                "none, the combined hash differs".to_string()
+            } else {
+                mismatches.join(", ")
+            }
+        )));
+    }
+    final_state.final_state_hash = computed_hashes.final_state;
+    info!(
+        "final state hash at slot {} checked against the server: {}",
+        slot, computed_hashes.final_state
+    );
+    Ok(())
+}
+
+/// Checks that the bootstrapped history passes through the `checkpoint` pinned by the operator.
+///
+/// A final state attached to the slot of the checkpoint must have its final state hash.
+/// A later final state must either still hold the final block at the slot of the checkpoint in the consensus graph,
+/// or the final state hash snapshot of its cycle when the checkpoint is the last slot of a cycle:
+/// a checkpoint too old to be checked against either of them is rejected.
+/// An earlier final state is accepted, consensus rejecting the blocks at the slot of the checkpoint
+/// that contradict it during the sync.
+pub(crate) fn check_trusted_checkpoint(
+    cfg: &BootstrapConfig,
+    checkpoint: &TrustedCheckpoint,
+    final_state: &FinalState,
+    graph: Option<&BootstrapableGraph>,
+) -> Result<(), BootstrapError> {
+    if final_state.slot < checkpoint.slot {
+        info!(
+            "the bootstrapped final state at slot {} is before the trusted checkpoint at slot {}, it will be checked during the sync",
+            final_state.slot, checkpoint.slot
+        );
+        return Ok(());
+    }
+    if final_state.slot == checkpoint.slot {
+        if final_state.final_state_hash != checkpoint.final_state_hash {
+            return Err(BootstrapError::CheckpointMismatch(format!(
+                "the final state hash at slot {} is {} but the checkpoint is {}",
+                checkpoint.slot, final_state.final_state_hash, checkpoint.final_state_hash
+            )));
+        }
+        info!("trusted checkpoint at slot {} checked", checkpoint.slot);
+        return Ok(());
+    }
+
+    let mut checked = false;
+    if let Some(b_export) = graph
+        .map(|graph| graph.final_blocks.as_slice())
+        .unwrap_or_default()
+        .iter()
+        .find(|b_export| b_export.block.content.header.content.slot == checkpoint.slot)
+    {
+        if checkpoint.conflicts_with(&checkpoint.slot, &b_export.block.id) {
+            return Err(BootstrapError::CheckpointMismatch(format!(
+                "the final block at slot {} is {} but the checkpoint is {}",
+                checkpoint.slot,
+                b_export.block.id,
+                checkpoint
+                    .block_id
+                    .map_or_else(|| "a miss".to_string(), |id| id.to_string())
+            )));
+        }
+        checked = true;
+    }
+    if checkpoint
+        .slot
+        .is_last_of_cycle(cfg.periods_per_cycle, cfg.thread_count)
+    {
+        let cycle = checkpoint.slot.get_cycle(cfg.periods_per_cycle);
+        if let Some(snapshot) = final_state
+            .pos_state
+            .get_cycle_info(cycle)
+            .filter(|cycle_info| cycle_info.complete)
+            .and_then(|cycle_info| cycle_info.final_state_hash_snapshot)
+        {
+            if snapshot != checkpoint.final_state_hash {
+                return Err(BootstrapError::CheckpointMismatch(format!(
+                    "the final state hash at the end of cycle {} is {} but the checkpoint is {}",
+                    cycle, snapshot, checkpoint.final_state_hash
+                )));
+            }
+            checked = true;
+        }
+    }
+    if !checked {
+        return Err(BootstrapError::CheckpointMismatch(format!(
+            "the checkpoint at slot {} is too old to be checked against the state bootstrapped at slot {}",
+            checkpoint.slot, final_state.slot
+        )));
+    }
+    info!("trusted checkpoint at slot {} checked", checkpoint.slot);
+    Ok(())
+}
+
+/// Message asking the whole state to the server, when bootstrapping from scratch
+fn ask_whole_state_message() -> BootstrapClientMessage {
+    BootstrapClientMessage::AskBootstrapPart {
+        last_slot: None,
+        last_ledger_step: StreamingStep::Started,
+        last_pool_step: StreamingStep::Started,
+        last_cycle_step: StreamingStep::Started,
+        last_credits_step: StreamingStep::Started,
+        last_ops_step: StreamingStep::Started,
+        last_consensus_step: StreamingStep::Started,
+    }
+}
+
 /// This function will send the starting point to receive a stream of the ledger and will receive and process each part until receive a `BootstrapServerMessage::FinalStateFinished` message from the server.
 /// `next_bootstrap_message` passed as parameter must be `BootstrapClientMessage::AskFinalStatePart` enum variant.
-/// `next_bootstrap_message` will be updated after receiving each part so that in case of connection lost we can restart from the last message we processed.
+/// `next_bootstrap_message` will be updated after applying each part so that in case of connection lost we can restart from the last message we processed.
+///
+/// The download and the application of the parts are pipelined: the received parts wait in a queue of
+/// `apply_queue_length` parts, applied in a blocking thread while the next ones are downloaded.
 async fn stream_final_state_and_consensus(
     cfg: &BootstrapConfig,
     client: &mut BootstrapClientBinder,
@@ -46,142 +386,138 @@ async fn stream_final_state_and_consensus(
             Ok(Err(e)) => Err(e),
             Ok(Ok(_)) => Ok(()),
         }?;
-        loop {
-            let msg = match tokio::time::timeout(cfg.read_timeout.into(), client.next()).await {
-                Err(_) => {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::TimedOut,
-                        "final state bootstrap read timed out",
-                    )
-                    .into());
-                }
-                Ok(Err(e)) => return Err(e),
-                Ok(Ok(msg)) => msg,
-            };
-            match msg {
-                BootstrapServerMessage::BootstrapPart {
-                    slot,
-                    ledger_part,
-                    async_pool_part,
-                    pos_cycle_part,
-                    pos_credits_part,
-                    exec_ops_part,
-                    final_state_changes,
-                    consensus_part,
-                    consensus_outdated_ids,
-                } => {
-                    // Set final state
-                    let mut write_final_state = global_bootstrap_state.final_state.write();
-                    let last_ledger_step = write_final_state.ledger.set_ledger_part(ledger_part)?;
-                    let last_pool_step =
-                        write_final_state.async_pool.set_pool_part(async_pool_part);
-                    let last_cycle_step = write_final_state
-                        .pos_state
-                        .set_cycle_history_part(pos_cycle_part);
-                    let last_credits_step = write_final_state
-                        .pos_state
-                        .set_deferred_credits_part(pos_credits_part);
-                    let last_ops_step = write_final_state
-                        .executed_ops
-                        .set_executed_ops_part(exec_ops_part);
-                    for (changes_slot, changes) in final_state_changes.iter() {
-                        write_final_state
-                            .ledger
-                            .apply_changes(changes.ledger_changes.clone(), *changes_slot);
-                        write_final_state
-                            .async_pool
-                            .apply_changes_unchecked(&changes.async_pool_changes);
-                        if !changes.pos_changes.is_empty() {
-                            write_final_state.pos_state.apply_changes(
-                                changes.pos_changes.clone(),
-                                *changes_slot,
-                                false,
-                            )?;
-                        }
-                        if !changes.executed_ops_changes.is_empty() {
-                            write_final_state
-                                .executed_ops
-                                .apply_changes(changes.executed_ops_changes.clone(), *changes_slot);
+
+        let (part_sender, part_receiver) = mpsc::channel(cfg.apply_queue_length.max(1));
+        let final_state = global_bootstrap_state.final_state.clone();
+        let graph = global_bootstrap_state.graph.take();
+        let applier = tokio::task::spawn_blocking(move || {
+            apply_bootstrap_parts(final_state, graph, part_receiver)
+        });
+
+        let stream_end: Result<BootstrapStreamEnd, BootstrapError> = async {
+            loop {
+                let msg = match tokio::time::timeout(cfg.read_timeout.into(), client.next()).await {
+                    Err(_) => {
+                        return Err(std::io::Error::new(
+                            std::io::ErrorKind::TimedOut,
+                            "final state bootstrap read timed out",
+                        )
+                        .into());
+                    }
+                    Ok(Err(e)) => return Err(e),
+                    Ok(Ok(msg)) => msg,
+                };
+                match msg {
+                    BootstrapServerMessage::BootstrapPart {
+                        slot,
+                        ledger_part,
+                        async_pool_part,
+                        pos_cycle_part,
+                        pos_credits_part,
+                        exec_ops_part,
+                        final_state_changes,
+                        consensus_part,
+                        consensus_outdated_ids,
+                    } => {
+                        let part = ReceivedBootstrapPart {
+                            slot,
+                            ledger_part,
+                            async_pool_part,
+                            pos_cycle_part,
+                            pos_credits_part,
+                            exec_ops_part,
+                            final_state_changes,
+                            consensus_part,
+                            consensus_outdated_ids,
+                        };
+                        // waits while the queue is full, the applier being slower than the link
+                        if part_sender.send(part).await.is_err() {
+                            return Ok(BootstrapStreamEnd::ApplierStopped);
                         }
                     }
-                    write_final_state.slot = slot;
-
-                    // Set consensus blocks
-                    if let Some(graph) = global_bootstrap_state.graph.as_mut() {
-                        // Extend the final blocks with the received part
-                        graph.final_blocks.extend(consensus_part.final_blocks);
-                        // Remove every outdated block
-                        graph.final_blocks.retain(|block_export| {
-                            !consensus_outdated_ids.contains(&block_export.block.id)
-                        });
-                    } else {
-                        global_bootstrap_state.graph = Some(consensus_part);
+                    BootstrapServerMessage::BootstrapFinished {
+                        slot,
+                        final_state_hashes,
+                    } => {
+                        return Ok(BootstrapStreamEnd::Finished {
+                            slot,
+                            final_state_hashes,
+                        })
                     }
-                    let last_consensus_step = StreamingStep::Ongoing(
-                        // Note that this unwrap call is safe because of the above conditional statement
-                        global_bootstrap_state
-                            .graph
-                            .as_ref()
-                            .unwrap()
-                            .final_blocks
-                            .iter()
-                            .map(|b_export| b_export.block.id)
-                            .collect(),
-                    );
+                    BootstrapServerMessage::SlotTooOld => {
+                        return Ok(BootstrapStreamEnd::SlotTooOld)
+                    }
+                    BootstrapServerMessage::BootstrapError { error } => {
+                        return Err(
+                            std::io::Error::new(std::io::ErrorKind::InvalidData, error).into()
+                        )
+                    }
+                    _ => {
+                        return Err(std::io::Error::new(
+                            std::io::ErrorKind::InvalidData,
+                            "unexpected message",
+                        )
+                        .into())
+                    }
+                }
+            }
+        }
+        .await;
 
-                    // Set new message in case of disconnection
-                    *next_bootstrap_message = BootstrapClientMessage::AskBootstrapPart {
-                        last_slot: Some(slot),
-                        last_ledger_step,
-                        last_pool_step,
-                        last_cycle_step,
-                        last_credits_step,
-                        last_ops_step,
-                        last_consensus_step,
-                    };
+        // let the applier finish the queued parts before resuming or completing
+        drop(part_sender);
+        let (graph, applied_message, apply_result) = applier.await.map_err(|err| {
+            BootstrapError::GeneralError(format!("bootstrap part applier failed: {}", err))
+        })?;
+        global_bootstrap_state.graph = graph;
+        if let Some(applied_message) = applied_message {
+            *next_bootstrap_message = applied_message;
+        }
+        apply_result?;
 
-                    // Logs for an easier diagnostic if needed
-                    debug!(
-                        "client final state bootstrap cursors: {:?}",
-                        next_bootstrap_message
-                    );
-                    debug!(
-                        "client final state slot changes length: {}",
-                        final_state_changes.len()
+        match stream_end? {
+            BootstrapStreamEnd::Finished {
+                slot,
+                final_state_hashes,
+            } => {
+                // aborts the bootstrap before the node participates with the received state
+                check_bootstrapped_state(
+                    &global_bootstrap_state.final_state,
+                    global_bootstrap_state.graph.as_ref(),
+                    slot,
+                    &final_state_hashes,
+                )?;
+                if let Some(checkpoint) = &cfg.trusted_checkpoint {
+                    let checked = check_trusted_checkpoint(
+                        cfg,
+                        checkpoint,
+                        &global_bootstrap_state.final_state.read(),
+                        global_bootstrap_state.graph.as_ref(),
                     );
+                    if let Err(err) = checked {
+                        // the next server streams its whole state instead of resuming the rejected history
+                        *next_bootstrap_message = ask_whole_state_message();
+                        global_bootstrap_state.graph = None;
+                        global_bootstrap_state.final_state.write().reset();
+                        return Err(err);
+                    }
                 }
-                BootstrapServerMessage::BootstrapFinished => {
-                    info!("State bootstrap complete");
-                    // Set next bootstrap message
-                    *next_bootstrap_message = BootstrapClientMessage::AskBootstrapPeers;
-                    return Ok(());
-                }
-                BootstrapServerMessage::SlotTooOld => {
-                    info!("Slot is too old retry bootstrap from scratch");
-                    *next_bootstrap_message = BootstrapClientMessage::AskBootstrapPart {
-                        last_slot: None,
-                        last_ledger_step: StreamingStep::Started,
-                        last_pool_step: StreamingStep::Started,
-                        last_cycle_step: StreamingStep::Started,
-                        last_credits_step: StreamingStep::Started,
-                        last_ops_step: StreamingStep::Started,
-                        last_consensus_step: StreamingStep::Started,
-                    };
-                    let mut write_final_state = global_bootstrap_state.final_state.write();
-                    write_final_state.reset();
-                    return Err(BootstrapError::GeneralError(String::from("Slot too old")));
-                }
-                BootstrapServerMessage::BootstrapError { error } => {
-                    return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, error).into())
-                }
-                _ => {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::InvalidData,
-                        "unexpected message",
-                    )
-                    .into())
-                }
+                info!("State bootstrap complete");
+                // Set next bootstrap message
+                *next_bootstrap_message = BootstrapClientMessage::AskMipStore;
+                Ok(())
+            }
+            BootstrapStreamEnd::SlotTooOld => {
+                info!("Slot is too old retry bootstrap from scratch");
+                *next_bootstrap_message = ask_whole_state_message();
+                let mut write_final_state = global_bootstrap_state.final_state.write();
+                write_final_state.reset();
+                Err(BootstrapError::GeneralError(String::from("Slot too old")))
             }
+            // the applier returned its error above
+            BootstrapStreamEnd::ApplierStopped => Err(BootstrapError::GeneralError(String::from(
+                "bootstrap part applier stopped",
+            ))),
         }
     } else {
         Err(BootstrapError::GeneralError(format!(
@@ -191,17 +527,15 @@ async fn stream_final_state_and_consensus(
     }
 }
 
-/// Gets the state from a bootstrap server (internal private function)
+/// Checks the server version and synchronizes clocks with it, before asking for any data
 /// needs to be CANCELLABLE
-async fn bootstrap_from_server(
+///
+/// Returns the optional features supported by both the server and us.
+async fn handshake_with_server(
     cfg: &BootstrapConfig,
     client: &mut BootstrapClientBinder,
-    next_bootstrap_message: &mut BootstrapClientMessage,
-    global_bootstrap_state: &mut GlobalBootstrapState,
     our_version: Version,
-) -> Result<(), BootstrapError> {
-    massa_trace!("bootstrap.lib.bootstrap_from_server", {});
-
+) -> Result<Capabilities, BootstrapError> {
     // read error (if sent by the server)
     // client.next() is not cancel-safe but we drop the whole client object if cancelled => it's OK
     match tokio::time::timeout(cfg.read_error_timeout.into(), client.next()).await {
@@ -243,32 +577,34 @@ async fn bootstrap_from_server(
 
     // First, clock and version.
     // client.next() is not cancel-safe but we drop the whole client object if cancelled => it's OK
-    let server_time = match tokio::time::timeout(cfg.read_timeout.into(), client.next()).await {
-        Err(_) => {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::TimedOut,
-                "bootstrap clock sync read timed out",
-            )
-            .into())
-        }
-        Ok(Err(e)) => return Err(e),
-        Ok(Ok(BootstrapServerMessage::BootstrapTime {
-            server_time,
-            version,
-        })) => {
-            if !our_version.is_compatible(&version) {
-                return Err(BootstrapError::IncompatibleVersionError(format!(
-                    "remote is running incompatible version: {} (local node version: {})",
-                    version, our_version
-                )));
+    let (server_time, capabilities) =
+        match tokio::time::timeout(cfg.read_timeout.into(), client.next()).await {
+            Err(_) => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    "bootstrap clock sync read timed out",
+                )
+                .into())
             }
-            server_time
-        }
-        Ok(Ok(BootstrapServerMessage::BootstrapError { error })) => {
-            return Err(BootstrapError::ReceivedError(error))
-        }
-        Ok(Ok(msg)) => return Err(BootstrapError::UnexpectedServerMessage(msg)),
-    };
+            Ok(Err(e)) => return Err(e),
+            Ok(Ok(BootstrapServerMessage::BootstrapTime {
+                server_time,
+                version,
+                capabilities,
+            })) => {
+                if !our_version.is_bootstrap_compatible(&version) {
+                    return Err(BootstrapError::IncompatibleVersionError(format!(
+                        "remote is running incompatible version: {} (local node version: {})",
+                        version, our_version
+                    )));
+                }
+                (server_time, NODE_CAPABILITIES.intersection(capabilities))
+            }
+            Ok(Ok(BootstrapServerMessage::BootstrapError { error })) => {
+                return Err(BootstrapError::ReceivedError(error))
+            }
+            Ok(Ok(msg)) => return Err(BootstrapError::UnexpectedServerMessage(msg)),
+        };
 
     // get the time of reception
     let recv_time = MassaTime::now()?;
@@ -296,6 +632,20 @@ async fn bootstrap_from_server(
         );
         return Err(BootstrapError::ClockError(message));
     }
+    Ok(capabilities)
+}
+
+/// Gets the state from a bootstrap server (internal private function)
+/// needs to be CANCELLABLE
+async fn bootstrap_from_server(
+    cfg: &BootstrapConfig,
+    client: &mut BootstrapClientBinder,
+    next_bootstrap_message: &mut BootstrapClientMessage,
+    global_bootstrap_state: &mut GlobalBootstrapState,
+    our_version: Version,
+) -> Result<(), BootstrapError> {
+    massa_trace!("bootstrap.lib.bootstrap_from_server", {});
+    let capabilities = handshake_with_server(cfg, client, our_version).await?;
 
     let write_timeout: std::time::Duration = cfg.write_timeout.into();
     // Loop to ask data to the server depending on the last message we sent
@@ -310,6 +660,32 @@ async fn bootstrap_from_server(
                 )
                 .await?;
             }
+            BootstrapClientMessage::AskMipStore => {
+                // servers without the capability keep the MIPs of the client in their initial state
+                if capabilities.contains(Capabilities::MIP_STORE) {
+                    let snapshot = match send_client_message(
+                        next_bootstrap_message,
+                        client,
+                        write_timeout,
+                        cfg.read_timeout.into(),
+                        "ask mip store timed out",
+                    )
+                    .await?
+                    {
+                        BootstrapServerMessage::MipStore { snapshot } => snapshot,
+                        BootstrapServerMessage::BootstrapError { error } => {
+                            return Err(BootstrapError::ReceivedError(error))
+                        }
+                        other => return Err(BootstrapError::UnexpectedServerMessage(other)),
+                    };
+                    global_bootstrap_state
+                        .final_state
+                        .read()
+                        .mip_store
+                        .restore(snapshot);
+                }
+                *next_bootstrap_message = BootstrapClientMessage::AskBootstrapPeers;
+            }
             BootstrapClientMessage::AskBootstrapPeers => {
                 let peers = match send_client_message(
                     next_bootstrap_message,
@@ -345,12 +721,93 @@ async fn bootstrap_from_server(
             BootstrapClientMessage::BootstrapError { error: _ } => {
                 panic!("The next message to send shouldn't be BootstrapError");
             }
+            BootstrapClientMessage::AskLightState { .. } => {
+                panic!("The next message to send shouldn't be AskLightState");
+            }
         };
     }
     info!("Successful bootstrap");
     Ok(())
 }
 
+/// Gets a summary of the final state from a bootstrap server (internal private function)
+/// needs to be CANCELLABLE
+async fn light_bootstrap_from_server(
+    cfg: &BootstrapConfig,
+    client: &mut BootstrapClientBinder,
+    ledger_keys: &[LedgerKey],
+    our_version: Version,
+) -> Result<LightBootstrapState, BootstrapError> {
+    massa_trace!("bootstrap.lib.light_bootstrap_from_server", {});
+    let capabilities = handshake_with_server(cfg, client, our_version).await?;
+    if !capabilities.contains(Capabilities::LIGHT_BOOTSTRAP) {
+        return Err(BootstrapError::GeneralError(
+            "the server does not support light bootstrap".into(),
+        ));
+    }
+
+    let write_timeout: std::time::Duration = cfg.write_timeout.into();
+    let light_state = match send_client_message(
+        &BootstrapClientMessage::AskLightState {
+            ledger_keys: ledger_keys.to_vec(),
+        },
+        client,
+        write_timeout,
+        cfg.read_timeout.into(),
+        "ask light state timed out",
+    )
+    .await?
+    {
+        BootstrapServerMessage::LightState {
+            slot,
+            final_state_hash,
+            final_headers,
+            pos_cycle,
+            ledger_values,
+        } => LightBootstrapState {
+            slot,
+            final_state_hash,
+            final_headers,
+            pos_cycle,
+            ledger_values,
+        },
+        BootstrapServerMessage::BootstrapError { error } => {
+            return Err(BootstrapError::ReceivedError(error))
+        }
+        other => return Err(BootstrapError::UnexpectedServerMessage(other)),
+    };
+    if !light_state
+        .ledger_values
+        .iter()
+        .map(|(key, _)| key)
+        .eq(ledger_keys.iter())
+    {
+        return Err(BootstrapError::GeneralError(
+            "light state ledger values do not match the requested keys".into(),
+        ));
+    }
+    for header in &light_state.final_headers {
+        header.verify_signature()?;
+    }
+
+    match tokio::time::timeout(
+        write_timeout,
+        client.send(&BootstrapClientMessage::BootstrapSuccess),
+    )
+    .await
+    {
+        Err(_) => Err(std::io::Error::new(
+            std::io::ErrorKind::TimedOut,
+            "send bootstrap success timed out",
+        )
+        .into()),
+        Ok(Err(e)) => Err(e),
+        Ok(Ok(_)) => Ok(()),
+    }?;
+    info!("Successful light bootstrap at slot {}", light_state.slot);
+    Ok(light_state)
+}
+
 async fn send_client_message(
     message_to_send: &BootstrapClientMessage,
     client: &mut BootstrapClientBinder,
@@ -414,8 +871,43 @@ fn filter_bootstrap_list(
     filtered_bootstrap_list
 }
 
+/// Bootstrap servers of the static list and of the directories to try, in a random order
+async fn get_bootstrap_list(
+    bootstrap_config: &BootstrapConfig,
+) -> Result<Vec<(SocketAddr, NodeId)>, BootstrapError> {
+    // the servers of the directories are merged with the static list
+    let mut bootstrap_list = bootstrap_config.bootstrap_list.clone();
+    bootstrap_list.extend(
+        fetch_directories(
+            &bootstrap_config.bootstrap_directories,
+            bootstrap_config.directory_timeout,
+        )
+        .await,
+    );
+
+    // we filter the bootstrap list to keep only the ip addresses we are compatible with
+    let mut filtered_bootstrap_list =
+        filter_bootstrap_list(bootstrap_list, bootstrap_config.bootstrap_protocol);
+    if filtered_bootstrap_list.is_empty() {
+        return Err(BootstrapError::GeneralError(
+            "no bootstrap nodes found in list".into(),
+        ));
+    }
+
+    // we shuffle the list
+    filtered_bootstrap_list.shuffle(&mut StdRng::from_entropy());
+
+    // we remove the duplicated node ids (if a bootstrap server appears both with its IPv4 and IPv6 address)
+    let mut unique_node_ids: HashSet<NodeId> = HashSet::new();
+    filtered_bootstrap_list.retain(|e| unique_node_ids.insert(e.1));
+    Ok(filtered_bootstrap_list)
+}
+
 /// Gets the state from a bootstrap server
 /// needs to be CANCELLABLE
+///
+/// If `restored_from_backup` is set, `final_state` was restored from a backup
+/// and only the changes that happened since its slot are bootstrapped, when the servers still have them.
 pub async fn get_state(
     bootstrap_config: &BootstrapConfig,
     final_state: Arc<RwLock<FinalState>>,
@@ -423,59 +915,49 @@ pub async fn get_state(
     version: Version,
     genesis_timestamp: MassaTime,
     end_timestamp: Option<MassaTime>,
+    restored_from_backup: bool,
 ) -> Result<GlobalBootstrapState, BootstrapError> {
     massa_trace!("bootstrap.lib.get_state", {});
     let now = MassaTime::now()?;
+    let restored_slot = restored_from_backup.then(|| final_state.read().slot);
     // if we are before genesis, do not bootstrap
-    if now < genesis_timestamp {
+    if now < genesis_timestamp && restored_slot.is_none() {
         massa_trace!("bootstrap.lib.get_state.init_from_scratch", {});
         // init final state
         {
             let mut final_state_guard = final_state.write();
-            // load ledger from initial ledger file
-            final_state_guard
-                .ledger
-                .load_initial_ledger()
-                .map_err(|err| {
-                    BootstrapError::GeneralError(format!("could not load initial ledger: {}", err))
-                })?;
-            // create the initial cycle of PoS cycle_history
-            final_state_guard.pos_state.create_initial_cycle();
+            // load the genesis ledger and create the initial cycle of PoS cycle_history
+            final_state_guard.init_genesis_state().map_err(|err| {
+                BootstrapError::GeneralError(format!("could not init genesis state: {}", err))
+            })?;
         }
         return Ok(GlobalBootstrapState::new(final_state));
     }
 
-    // we filter the bootstrap list to keep only the ip addresses we are compatible with
-    let mut filtered_bootstrap_list = filter_bootstrap_list(
-        bootstrap_config.bootstrap_list.clone(),
-        bootstrap_config.bootstrap_protocol,
-    );
-
     // we are after genesis => bootstrap
     massa_trace!("bootstrap.lib.get_state.init_from_others", {});
-    if filtered_bootstrap_list.is_empty() {
-        return Err(BootstrapError::GeneralError(
-            "no bootstrap nodes found in list".into(),
-        ));
-    }
+    let filtered_bootstrap_list = get_bootstrap_list(bootstrap_config).await?;
 
-    // we shuffle the list
-    filtered_bootstrap_list.shuffle(&mut StdRng::from_entropy());
-
-    // we remove the duplicated node ids (if a bootstrap server appears both with its IPv4 and IPv6 address)
-    let mut unique_node_ids: HashSet<NodeId> = HashSet::new();
-    filtered_bootstrap_list.retain(|e| unique_node_ids.insert(e.1));
-
-    let mut next_bootstrap_message: BootstrapClientMessage =
-        BootstrapClientMessage::AskBootstrapPart {
-            last_slot: None,
-            last_ledger_step: StreamingStep::Started,
-            last_pool_step: StreamingStep::Started,
-            last_cycle_step: StreamingStep::Started,
-            last_credits_step: StreamingStep::Started,
-            last_ops_step: StreamingStep::Started,
-            last_consensus_step: StreamingStep::Started,
-        };
+    let mut next_bootstrap_message: BootstrapClientMessage = match restored_slot {
+        // only ask for the changes that happened since the restored slot,
+        // the server answers `SlotTooOld` if the backup is older than its changes history
+        Some(slot) => {
+            info!(
+                "Catching up with the network from the state restored at slot {}",
+                slot
+            );
+            BootstrapClientMessage::AskBootstrapPart {
+                last_slot: Some(slot),
+                last_ledger_step: StreamingStep::Finished(None),
+                last_pool_step: StreamingStep::Finished(None),
+                last_cycle_step: StreamingStep::Finished(None),
+                last_credits_step: StreamingStep::Finished(None),
+                last_ops_step: StreamingStep::Finished(None),
+                last_consensus_step: StreamingStep::Started,
+            }
+        }
+        None => ask_whole_state_message(),
+    };
     let mut global_bootstrap_state = GlobalBootstrapState::new(final_state.clone());
 
     loop {
@@ -499,6 +981,11 @@ pub async fn get_state(
                     .await  // cancellable
                     {
                         Err(BootstrapError::ReceivedError(error)) => warn!("Error received from bootstrap server: {}", error),
+                        Err(e @ BootstrapError::StateMismatch(_)) => {
+                            let _ = tokio::time::timeout(bootstrap_config.write_error_timeout.into(), client.send(&BootstrapClientMessage::BootstrapError { error: e.to_string() })).await;
+                            // the node must not participate with a state that does not match the network
+                            return Err(e);
+                        }
                         Err(e) => {
                             warn!("Error while bootstrapping: {}", e);
                             // We allow unused result because we don't care if an error is thrown when sending the error message to the server we will close the socket anyway.
@@ -519,3 +1006,67 @@ pub async fn get_state(
         }
     }
 }
+
+/// Gets a summary of the final state from a bootstrap server, for light clients that do not keep the ledger
+/// (wallet backends, bridges): the headers of the latest final blocks, the latest Proof of Stake cycle
+/// and the values of the requested ledger keys.
+/// needs to be CANCELLABLE
+///
+/// Like any bootstrap message, the summary is signed by the bootstrap server:
+/// the ledger values are as trusted as the servers of the bootstrap list,
+/// they are not proven against the final state hash.
+pub async fn get_light_state(
+    bootstrap_config: &BootstrapConfig,
+    mut establisher: Establisher,
+    version: Version,
+    ledger_keys: Vec<LedgerKey>,
+) -> Result<LightBootstrapState, BootstrapError> {
+    massa_trace!("bootstrap.lib.get_light_state", {});
+    if ledger_keys.len() > MAX_LIGHT_BOOTSTRAP_LEDGER_KEYS as usize {
+        return Err(BootstrapError::GeneralError(format!(
+            "at most {} ledger keys can be requested by a light bootstrap",
+            MAX_LIGHT_BOOTSTRAP_LEDGER_KEYS
+        )));
+    }
+    let filtered_bootstrap_list = get_bootstrap_list(bootstrap_config).await?;
+
+    loop {
+        for (addr, node_id) in filtered_bootstrap_list.iter() {
+            info!("Start light bootstrapping from {}", addr);
+            match connect_to_server(
+                &mut establisher,
+                bootstrap_config,
+                addr,
+                &node_id.get_public_key(),
+            )
+            .await
+            {
+                Ok(mut client) => {
+                    match light_bootstrap_from_server(bootstrap_config, &mut client, &ledger_keys, version)
+                    .await  // cancellable
+                    {
+                        Err(BootstrapError::ReceivedError(error)) => warn!("Error received from bootstrap server: {}", error),
+                        Err(e) => {
+                            warn!("Error while light bootstrapping: {}", e);
+                            // We allow unused result because we don't care if an error is thrown when sending the error message to the server we will close the socket anyway.
+                            let _ = tokio::time::timeout(bootstrap_config.write_error_timeout.into(), client.send(&BootstrapClientMessage::BootstrapError { error: e.to_string() })).await;
+                        }
+                        Ok(light_state) => {
+                            return Ok(light_state)
+                        }
+                    }
+                }
+                Err(e) => {
+                    warn!("Error while connecting to bootstrap server: {}", e);
+                }
+            };
+
+            info!(
+                "Light bootstrap from server {} failed. Trying another server in {}.",
+                addr,
+                format_duration(bootstrap_config.retry_delay.to_duration()).to_string()
+            );
+            sleep(bootstrap_config.retry_delay.into()).await;
+        }
+    }
+}
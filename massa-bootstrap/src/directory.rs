@@ -0,0 +1,141 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Directories of community bootstrap servers.
+//!
+//! The servers of the static bootstrap list of a release come and go: a directory lets a publisher maintain
+//! an up-to-date list that the nodes fetch before bootstrapping and merge with their static list.
+//! A directory is a JSON document served over plain `http://`, holding the servers and the signature by the publisher
+//! of the hash of their compact JSON serialization. The transport is not trusted: a list that is not signed by the
+//! publisher key configured for its directory is ignored, and the bootstrap servers of a list still authenticate
+//! with their node id.
+
+use crate::error::BootstrapError;
+use crate::settings::BootstrapDirectory;
+use massa_hash::Hash;
+use massa_models::node::NodeId;
+use massa_signature::{KeyPair, Signature};
+use massa_time::MassaTime;
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tracing::{info, warn};
+
+/// Maximum size of the response of a directory
+const MAX_DIRECTORY_RESPONSE_SIZE: u64 = 1_000_000;
+
+/// List of bootstrap servers signed by the publisher of a directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedServerList {
+    /// address and node id of the servers
+    pub servers: Vec<(SocketAddr, NodeId)>,
+    /// signature of the hash of the compact JSON serialization of `servers`
+    pub signature: Signature,
+}
+
+impl SignedServerList {
+    /// Signs the list of `servers` with the `keypair` of the publisher
+    pub fn new(
+        servers: Vec<(SocketAddr, NodeId)>,
+        keypair: &KeyPair,
+    ) -> Result<Self, BootstrapError> {
+        let signature = keypair.sign(&Self::compute_hash(&servers)?)?;
+        Ok(SignedServerList { servers, signature })
+    }
+
+    /// Checks that the list was signed by the publisher of `directory`
+    pub fn verify(&self, directory: &BootstrapDirectory) -> Result<(), BootstrapError> {
+        directory
+            .publisher_key
+            .verify_signature(&Self::compute_hash(&self.servers)?, &self.signature)?;
+        Ok(())
+    }
+
+    fn compute_hash(servers: &[(SocketAddr, NodeId)]) -> Result<Hash, BootstrapError> {
+        let bytes = serde_json::to_vec(servers).map_err(|err| {
+            BootstrapError::DirectoryError(format!("could not serialize the servers: {}", err))
+        })?;
+        Ok(Hash::compute_from(&bytes))
+    }
+}
+
+/// Fetches the servers of every directory, the directories that cannot be fetched or checked being skipped
+pub(crate) async fn fetch_directories(
+    directories: &[BootstrapDirectory],
+    timeout: MassaTime,
+) -> Vec<(SocketAddr, NodeId)> {
+    let mut servers = Vec::new();
+    for directory in directories {
+        match tokio::time::timeout(timeout.to_duration(), fetch_directory(directory)).await {
+            Ok(Ok(list)) => {
+                info!(
+                    "{} bootstrap servers fetched from the directory {}",
+                    list.len(),
+                    directory.url
+                );
+                servers.extend(list);
+            }
+            Ok(Err(err)) => warn!(
+                "could not fetch the bootstrap directory {}: {}",
+                directory.url, err
+            ),
+            Err(_) => warn!(
+                "could not fetch the bootstrap directory {}: timed out",
+                directory.url
+            ),
+        }
+    }
+    servers
+}
+
+/// Fetches the servers of a `http://` directory and checks their signature
+pub(crate) async fn fetch_directory(
+    directory: &BootstrapDirectory,
+) -> Result<Vec<(SocketAddr, NodeId)>, BootstrapError> {
+    let location = directory.url.strip_prefix("http://").ok_or_else(|| {
+        BootstrapError::DirectoryError("only http:// directories are supported".into())
+    })?;
+    let (host, path) = match location.find('/') {
+        Some(idx) => (&location[..idx], &location[idx..]),
+        None => (location, "/"),
+    };
+    let mut stream = if host.contains(':') {
+        TcpStream::connect(host).await
+    } else {
+        TcpStream::connect((host, 80)).await
+    }?;
+    // HTTP/1.0 responses are not chunked and end with the connection
+    stream
+        .write_all(
+            format!(
+                "GET {} HTTP/1.0\r\nHost: {}\r\nAccept: application/json\r\nConnection: close\r\n\r\n",
+                path, host
+            )
+            .as_bytes(),
+        )
+        .await?;
+    let mut response = Vec::new();
+    stream
+        .take(MAX_DIRECTORY_RESPONSE_SIZE)
+        .read_to_end(&mut response)
+        .await?;
+
+    let body_start = response
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .ok_or_else(|| BootstrapError::DirectoryError("invalid HTTP response".into()))?;
+    let head = String::from_utf8_lossy(&response[..body_start]);
+    match head.split_whitespace().nth(1) {
+        Some(code) if code.starts_with('2') => {}
+        _ => {
+            return Err(BootstrapError::DirectoryError(format!(
+                "unexpected response: {}",
+                head.lines().next().unwrap_or_default()
+            )))
+        }
+    }
+    let list: SignedServerList = serde_json::from_slice(&response[body_start + 4..])
+        .map_err(|err| BootstrapError::DirectoryError(format!("invalid list: {}", err)))?;
+    list.verify(directory)?;
+    Ok(list.servers)
+}
@@ -58,4 +58,12 @@ pub enum BootstrapError {
     BlackListed(String),
     /// IP {0} is not in the whitelist
     WhiteListed(String),
+    /// trusted sync error: {0}
+    TrustedSyncError(String),
+    /// bootstrapped state mismatch: {0}
+    StateMismatch(String),
+    /// bootstrapped state not matching the trusted checkpoint: {0}
+    CheckpointMismatch(String),
+    /// bootstrap directory error: {0}
+    DirectoryError(String),
 }
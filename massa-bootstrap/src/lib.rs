@@ -7,6 +7,9 @@
 //! On server side, the server will query consensus for the graph and the ledger,
 //! execution for execution related data and network for the peer list.
 //!
+//! Light clients (wallet backends, bridges) can instead use [`get_light_state`]
+//! to only get the latest final block headers, the PoS roll state and a few ledger values.
+//!
 #![feature(async_closure)]
 #![warn(missing_docs)]
 #![warn(unused_crate_dependencies)]
@@ -16,12 +19,17 @@
 pub use establisher::types::Establisher;
 use massa_consensus_exports::bootstrapable_graph::BootstrapableGraph;
 use massa_final_state::FinalState;
+use massa_hash::Hash;
+use massa_ledger_exports::Key as LedgerKey;
+use massa_models::{block_header::SecuredHeader, slot::Slot};
 use massa_network_exports::BootstrapPeers;
+use massa_pos_exports::CycleInfo;
 use parking_lot::RwLock;
 use std::sync::Arc;
 
 mod client;
 mod client_binder;
+mod directory;
 mod error;
 mod establisher;
 mod messages;
@@ -29,15 +37,20 @@ mod server;
 mod server_binder;
 mod settings;
 mod tools;
-pub use client::get_state;
+mod trusted_sync;
+pub use client::{get_light_state, get_state};
+pub use directory::SignedServerList;
 pub use establisher::types;
 pub use messages::{
     BootstrapClientMessage, BootstrapClientMessageDeserializer, BootstrapClientMessageSerializer,
     BootstrapServerMessage, BootstrapServerMessageDeserializer, BootstrapServerMessageSerializer,
 };
 pub use server::{start_bootstrap_server, BootstrapManager};
-pub use settings::IpType;
 pub use settings::{BootstrapConfig, BootstrapServerMessageDeserializerArgs};
+pub use settings::{BootstrapDirectory, IpType};
+pub use trusted_sync::{
+    fetch_trusted_state, start_trusted_sync_server, TrustedSyncConfig, TrustedSyncServer,
+};
 
 #[cfg(test)]
 pub mod tests;
@@ -63,3 +76,18 @@ impl GlobalBootstrapState {
         }
     }
 }
+
+/// Summary of the final state received by a light bootstrap
+#[derive(Debug, Clone)]
+pub struct LightBootstrapState {
+    /// slot at the output of which the final state is attached
+    pub slot: Slot,
+    /// hash of the final state at `slot`
+    pub final_state_hash: Hash,
+    /// header of the latest final block of each thread, with checked signatures
+    pub final_headers: Vec<SecuredHeader>,
+    /// latest Proof of Stake cycle, holding the current roll counts
+    pub pos_cycle: Option<CycleInfo>,
+    /// values of the requested ledger keys in their ledger database format, `None` for the missing ones
+    pub ledger_values: Vec<(LedgerKey, Option<Vec<u8>>)>,
+}
@@ -9,11 +9,17 @@ use massa_consensus_exports::bootstrapable_graph::{
     BootstrapableGraph, BootstrapableGraphDeserializer, BootstrapableGraphSerializer,
 };
 use massa_executed_ops::{ExecutedOpsDeserializer, ExecutedOpsSerializer};
-use massa_final_state::{StateChanges, StateChangesDeserializer, StateChangesSerializer};
+use massa_final_state::{
+    FinalStateHashes, StateChanges, StateChangesDeserializer, StateChangesSerializer,
+};
+use massa_hash::{Hash, HashDeserializer, HashSerializer};
 use massa_ledger_exports::{Key as LedgerKey, KeyDeserializer, KeySerializer};
+use massa_models::block_header::{BlockHeader, BlockHeaderDeserializer, SecuredHeader};
 use massa_models::block_id::{BlockId, BlockIdDeserializer, BlockIdSerializer};
+use massa_models::config::MAX_LIGHT_BOOTSTRAP_LEDGER_KEYS;
 use massa_models::operation::OperationId;
 use massa_models::prehash::PreHashSet;
+use massa_models::secure_share::{SecureShareDeserializer, SecureShareSerializer};
 use massa_models::serialization::{
     PreHashSetDeserializer, PreHashSetSerializer, VecU8Deserializer, VecU8Serializer,
 };
@@ -21,7 +27,10 @@ use massa_models::slot::{Slot, SlotDeserializer, SlotSerializer};
 use massa_models::streaming_step::{
     StreamingStep, StreamingStepDeserializer, StreamingStepSerializer,
 };
-use massa_models::version::{Version, VersionDeserializer, VersionSerializer};
+use massa_models::version::{
+    Capabilities, CapabilitiesDeserializer, CapabilitiesSerializer, Version, VersionDeserializer,
+    VersionSerializer,
+};
 use massa_network_exports::{BootstrapPeers, BootstrapPeersDeserializer, BootstrapPeersSerializer};
 use massa_pos_exports::{
     CycleInfo, CycleInfoDeserializer, CycleInfoSerializer, DeferredCredits,
@@ -32,8 +41,11 @@ use massa_serialization::{
     U32VarIntDeserializer, U32VarIntSerializer, U64VarIntDeserializer, U64VarIntSerializer,
 };
 use massa_time::{MassaTime, MassaTimeDeserializer, MassaTimeSerializer};
+use massa_versioning::{
+    MipStoreSnapshot, MipStoreSnapshotDeserializer, MipStoreSnapshotSerializer,
+};
 use nom::error::context;
-use nom::multi::{length_count, length_data};
+use nom::multi::{count, length_count, length_data};
 use nom::sequence::tuple;
 use nom::Parser;
 use nom::{
@@ -55,6 +67,9 @@ pub enum BootstrapServerMessage {
         server_time: MassaTime,
         /// The version of the bootstrap server.
         version: Version,
+        /// Optional features supported by the bootstrap server.
+        /// Servers that do not send them are considered to support none.
+        capabilities: Capabilities,
     },
     /// Bootstrap peers
     BootstrapPeers {
@@ -82,8 +97,31 @@ pub enum BootstrapServerMessage {
         /// Outdated block ids in the current consensus graph bootstrap
         consensus_outdated_ids: PreHashSet<BlockId>,
     },
+    /// Summary of the final state for light clients
+    LightState {
+        /// Slot the final state is attached to
+        slot: Slot,
+        /// Hash of the final state at `slot`
+        final_state_hash: Hash,
+        /// Header of the latest final block of each thread
+        final_headers: Vec<SecuredHeader>,
+        /// Latest Proof of Stake cycle, holding the current roll counts
+        pos_cycle: Option<CycleInfo>,
+        /// Values of the requested ledger keys, `None` for the missing ones
+        ledger_values: Vec<(LedgerKey, Option<Vec<u8>>)>,
+    },
+    /// State of the MIPs, see `massa_versioning`
+    MipStore {
+        /// Snapshot of the versioning state of the server
+        snapshot: MipStoreSnapshot,
+    },
     /// Message sent when the final state and consensus bootstrap are finished
-    BootstrapFinished,
+    BootstrapFinished {
+        /// Slot of the streamed final state
+        slot: Slot,
+        /// Hashes of the final state at `slot`, checked by the client against the state it received
+        final_state_hashes: FinalStateHashes,
+    },
     /// Slot sent to get state changes is too old
     SlotTooOld,
     /// Bootstrap error
@@ -102,6 +140,8 @@ enum MessageServerTypeId {
     FinalStateFinished = 3u32,
     SlotTooOld = 4u32,
     BootstrapError = 5u32,
+    LightState = 6u32,
+    MipStore = 7u32,
 }
 
 /// Serializer for `BootstrapServerMessage`
@@ -110,6 +150,7 @@ pub struct BootstrapServerMessageSerializer {
     u64_serializer: U64VarIntSerializer,
     time_serializer: MassaTimeSerializer,
     version_serializer: VersionSerializer,
+    capabilities_serializer: CapabilitiesSerializer,
     peers_serializer: BootstrapPeersSerializer,
     state_changes_serializer: StateChangesSerializer,
     bootstrapable_graph_serializer: BootstrapableGraphSerializer,
@@ -120,6 +161,11 @@ pub struct BootstrapServerMessageSerializer {
     opt_pos_cycle_serializer: OptionSerializer<CycleInfo, CycleInfoSerializer>,
     pos_credits_serializer: DeferredCreditsSerializer,
     exec_ops_serializer: ExecutedOpsSerializer,
+    hash_serializer: HashSerializer,
+    header_serializer: SecureShareSerializer,
+    ledger_key_serializer: KeySerializer,
+    opt_ledger_value_serializer: OptionSerializer<Vec<u8>, VecU8Serializer>,
+    mip_store_serializer: MipStoreSnapshotSerializer,
 }
 
 impl Default for BootstrapServerMessageSerializer {
@@ -136,6 +182,7 @@ impl BootstrapServerMessageSerializer {
             u64_serializer: U64VarIntSerializer::new(),
             time_serializer: MassaTimeSerializer::new(),
             version_serializer: VersionSerializer::new(),
+            capabilities_serializer: CapabilitiesSerializer::new(),
             peers_serializer: BootstrapPeersSerializer::new(),
             state_changes_serializer: StateChangesSerializer::new(),
             bootstrapable_graph_serializer: BootstrapableGraphSerializer::new(),
@@ -146,6 +193,11 @@ impl BootstrapServerMessageSerializer {
             opt_pos_cycle_serializer: OptionSerializer::new(CycleInfoSerializer::new()),
             pos_credits_serializer: DeferredCreditsSerializer::new(),
             exec_ops_serializer: ExecutedOpsSerializer::new(),
+            hash_serializer: HashSerializer::new(),
+            header_serializer: SecureShareSerializer::new(),
+            ledger_key_serializer: KeySerializer::new(true),
+            opt_ledger_value_serializer: OptionSerializer::new(VecU8Serializer::new()),
+            mip_store_serializer: MipStoreSnapshotSerializer::new(),
         }
     }
 }
@@ -156,13 +208,14 @@ impl Serializer<BootstrapServerMessage> for BootstrapServerMessageSerializer {
     /// use massa_bootstrap::{BootstrapServerMessage, BootstrapServerMessageSerializer};
     /// use massa_serialization::Serializer;
     /// use massa_time::MassaTime;
-    /// use massa_models::version::Version;
+    /// use massa_models::version::{Capabilities, Version};
     /// use std::str::FromStr;
     ///
     /// let message_serializer = BootstrapServerMessageSerializer::new();
     /// let bootstrap_server_message = BootstrapServerMessage::BootstrapTime {
     ///    server_time: MassaTime::from(0),
     ///    version: Version::from_str("TEST.1.10").unwrap(),
+    ///    capabilities: Capabilities::LIGHT_BOOTSTRAP,
     /// };
     /// let mut message_serialized = Vec::new();
     /// message_serializer.serialize(&bootstrap_server_message, &mut message_serialized).unwrap();
@@ -176,11 +229,14 @@ impl Serializer<BootstrapServerMessage> for BootstrapServerMessageSerializer {
             BootstrapServerMessage::BootstrapTime {
                 server_time,
                 version,
+                capabilities,
             } => {
                 self.u32_serializer
                     .serialize(&u32::from(MessageServerTypeId::BootstrapTime), buffer)?;
                 self.time_serializer.serialize(server_time, buffer)?;
                 self.version_serializer.serialize(version, buffer)?;
+                self.capabilities_serializer
+                    .serialize(capabilities, buffer)?;
             }
             BootstrapServerMessage::BootstrapPeers { peers } => {
                 self.u32_serializer
@@ -232,9 +288,60 @@ impl Serializer<BootstrapServerMessage> for BootstrapServerMessageSerializer {
                 self.block_id_set_serializer
                     .serialize(consensus_outdated_ids, buffer)?;
             }
-            BootstrapServerMessage::BootstrapFinished => {
+            BootstrapServerMessage::LightState {
+                slot,
+                final_state_hash,
+                final_headers,
+                pos_cycle,
+                ledger_values,
+            } => {
+                self.u32_serializer
+                    .serialize(&u32::from(MessageServerTypeId::LightState), buffer)?;
+                self.slot_serializer.serialize(slot, buffer)?;
+                self.hash_serializer.serialize(final_state_hash, buffer)?;
+                self.u32_serializer.serialize(
+                    &final_headers.len().try_into().map_err(|_| {
+                        SerializeError::GeneralError("Fail to convert usize to u32".to_string())
+                    })?,
+                    buffer,
+                )?;
+                for header in final_headers {
+                    self.header_serializer.serialize(header, buffer)?;
+                }
+                self.opt_pos_cycle_serializer.serialize(pos_cycle, buffer)?;
+                self.u32_serializer.serialize(
+                    &ledger_values.len().try_into().map_err(|_| {
+                        SerializeError::GeneralError("Fail to convert usize to u32".to_string())
+                    })?,
+                    buffer,
+                )?;
+                for (key, value) in ledger_values {
+                    self.ledger_key_serializer.serialize(key, buffer)?;
+                    self.opt_ledger_value_serializer.serialize(value, buffer)?;
+                }
+            }
+            BootstrapServerMessage::MipStore { snapshot } => {
+                self.u32_serializer
+                    .serialize(&u32::from(MessageServerTypeId::MipStore), buffer)?;
+                self.mip_store_serializer.serialize(snapshot, buffer)?;
+            }
+            BootstrapServerMessage::BootstrapFinished {
+                slot,
+                final_state_hashes,
+            } => {
                 self.u32_serializer
                     .serialize(&u32::from(MessageServerTypeId::FinalStateFinished), buffer)?;
+                self.slot_serializer.serialize(slot, buffer)?;
+                for hash in [
+                    &final_state_hashes.ledger,
+                    &final_state_hashes.async_pool,
+                    &final_state_hashes.deferred_credits,
+                    &final_state_hashes.cycle_history,
+                    &final_state_hashes.executed_ops,
+                    &final_state_hashes.final_state,
+                ] {
+                    self.hash_serializer.serialize(hash, buffer)?;
+                }
             }
             BootstrapServerMessage::SlotTooOld => {
                 self.u32_serializer
@@ -261,6 +368,7 @@ pub struct BootstrapServerMessageDeserializer {
     message_id_deserializer: U32VarIntDeserializer,
     time_deserializer: MassaTimeDeserializer,
     version_deserializer: VersionDeserializer,
+    capabilities_deserializer: CapabilitiesDeserializer,
     peers_deserializer: BootstrapPeersDeserializer,
     length_state_changes: U64VarIntDeserializer,
     state_changes_deserializer: StateChangesDeserializer,
@@ -273,6 +381,13 @@ pub struct BootstrapServerMessageDeserializer {
     opt_pos_cycle_deserializer: OptionDeserializer<CycleInfo, CycleInfoDeserializer>,
     pos_credits_deserializer: DeferredCreditsDeserializer,
     exec_ops_deserializer: ExecutedOpsDeserializer,
+    hash_deserializer: HashDeserializer,
+    length_headers: U32VarIntDeserializer,
+    header_deserializer: SecureShareDeserializer<BlockHeader, BlockHeaderDeserializer>,
+    length_ledger_values: U32VarIntDeserializer,
+    ledger_key_deserializer: KeyDeserializer,
+    opt_ledger_value_deserializer: OptionDeserializer<Vec<u8>, VecU8Deserializer>,
+    mip_store_deserializer: MipStoreSnapshotDeserializer,
 }
 
 impl BootstrapServerMessageDeserializer {
@@ -286,6 +401,7 @@ impl BootstrapServerMessageDeserializer {
                 Included(MassaTime::from_millis(u64::MAX)),
             )),
             version_deserializer: VersionDeserializer::new(),
+            capabilities_deserializer: CapabilitiesDeserializer::new(),
             peers_deserializer: BootstrapPeersDeserializer::new(args.max_advertise_length),
             state_changes_deserializer: StateChangesDeserializer::new(
                 args.thread_count,
@@ -344,6 +460,25 @@ impl BootstrapServerMessageDeserializer {
                 args.max_executed_ops_length,
                 args.max_operations_per_block as u64,
             ),
+            hash_deserializer: HashDeserializer::new(),
+            length_headers: U32VarIntDeserializer::new(
+                Included(0),
+                Included(args.thread_count as u32),
+            ),
+            header_deserializer: SecureShareDeserializer::new(BlockHeaderDeserializer::new(
+                args.thread_count,
+                args.endorsement_count,
+            )),
+            length_ledger_values: U32VarIntDeserializer::new(
+                Included(0),
+                Included(MAX_LIGHT_BOOTSTRAP_LEDGER_KEYS),
+            ),
+            ledger_key_deserializer: KeyDeserializer::new(args.max_datastore_key_length, true),
+            opt_ledger_value_deserializer: OptionDeserializer::new(VecU8Deserializer::new(
+                Included(0),
+                Included(args.max_bootstrap_final_state_parts_size),
+            )),
+            mip_store_deserializer: MipStoreSnapshotDeserializer::new(args.thread_count),
         }
     }
 }
@@ -355,7 +490,7 @@ impl Deserializer<BootstrapServerMessage> for BootstrapServerMessageDeserializer
     /// use massa_bootstrap::BootstrapServerMessageDeserializerArgs;
     /// use massa_serialization::{Serializer, Deserializer, DeserializeError};
     /// use massa_time::MassaTime;
-    /// use massa_models::version::Version;
+    /// use massa_models::version::{Capabilities, Version};
     /// use std::str::FromStr;
     ///
     /// let message_serializer = BootstrapServerMessageSerializer::new();
@@ -373,6 +508,7 @@ impl Deserializer<BootstrapServerMessage> for BootstrapServerMessageDeserializer
     /// let bootstrap_server_message = BootstrapServerMessage::BootstrapTime {
     ///    server_time: MassaTime::from(0),
     ///    version: Version::from_str("TEST.1.10").unwrap(),
+    ///    capabilities: Capabilities::LIGHT_BOOTSTRAP,
     /// };
     /// let mut message_serialized = Vec::new();
     /// message_serializer.serialize(&bootstrap_server_message, &mut message_serialized).unwrap();
@@ -381,9 +517,11 @@ impl Deserializer<BootstrapServerMessage> for BootstrapServerMessageDeserializer
     ///     BootstrapServerMessage::BootstrapTime {
     ///        server_time,
     ///        version,
+    ///        capabilities,
     ///    } => {
     ///     assert_eq!(server_time, MassaTime::from(0));
     ///     assert_eq!(version, Version::from_str("TEST.1.10").unwrap());
+    ///     assert_eq!(capabilities, Capabilities::LIGHT_BOOTSTRAP);
     ///   }
     ///   _ => panic!("Unexpected message"),
     /// }
@@ -414,11 +552,23 @@ impl Deserializer<BootstrapServerMessage> for BootstrapServerMessageDeserializer
                     context("Failed version deserialization", |input| {
                         self.version_deserializer.deserialize(input)
                     }),
+                    context(
+                        "Failed capabilities deserialization",
+                        |input: &'a [u8]| {
+                            // older servers end their bootstrap time message after the version
+                            if input.is_empty() {
+                                Ok((input, Capabilities::empty()))
+                            } else {
+                                self.capabilities_deserializer.deserialize(input)
+                            }
+                        },
+                    ),
                 ))
                 .map(
-                    |(server_time, version)| BootstrapServerMessage::BootstrapTime {
+                    |(server_time, version, capabilities)| BootstrapServerMessage::BootstrapTime {
                         server_time,
                         version,
+                        capabilities,
                     },
                 )
                 .parse(input),
@@ -491,9 +641,78 @@ impl Deserializer<BootstrapServerMessage> for BootstrapServerMessageDeserializer
                     },
                 )
                 .parse(input),
-                MessageServerTypeId::FinalStateFinished => {
-                    Ok((input, BootstrapServerMessage::BootstrapFinished))
+                MessageServerTypeId::LightState => tuple((
+                    context("Failed slot deserialization", |input| {
+                        self.slot_deserializer.deserialize(input)
+                    }),
+                    context("Failed final_state_hash deserialization", |input| {
+                        self.hash_deserializer.deserialize(input)
+                    }),
+                    context(
+                        "Failed final_headers deserialization",
+                        length_count(
+                            context("Failed length deserialization", |input| {
+                                self.length_headers.deserialize(input)
+                            }),
+                            |input| self.header_deserializer.deserialize(input),
+                        ),
+                    ),
+                    context("Failed pos_cycle deserialization", |input| {
+                        self.opt_pos_cycle_deserializer.deserialize(input)
+                    }),
+                    context(
+                        "Failed ledger_values deserialization",
+                        length_count(
+                            context("Failed length deserialization", |input| {
+                                self.length_ledger_values.deserialize(input)
+                            }),
+                            tuple((
+                                |input| self.ledger_key_deserializer.deserialize(input),
+                                |input| self.opt_ledger_value_deserializer.deserialize(input),
+                            )),
+                        ),
+                    ),
+                ))
+                .map(
+                    |(slot, final_state_hash, final_headers, pos_cycle, ledger_values)| {
+                        BootstrapServerMessage::LightState {
+                            slot,
+                            final_state_hash,
+                            final_headers,
+                            pos_cycle,
+                            ledger_values,
+                        }
+                    },
+                )
+                .parse(input),
+                MessageServerTypeId::MipStore => {
+                    context("Failed mip_store deserialization", |input| {
+                        self.mip_store_deserializer.deserialize(input)
+                    })
+                    .map(|snapshot| BootstrapServerMessage::MipStore { snapshot })
+                    .parse(input)
                 }
+                MessageServerTypeId::FinalStateFinished => tuple((
+                    context("Failed slot deserialization", |input| {
+                        self.slot_deserializer.deserialize(input)
+                    }),
+                    context(
+                        "Failed final_state_hashes deserialization",
+                        count(|input| self.hash_deserializer.deserialize(input), 6),
+                    ),
+                ))
+                .map(|(slot, hashes)| BootstrapServerMessage::BootstrapFinished {
+                    slot,
+                    final_state_hashes: FinalStateHashes {
+                        ledger: hashes[0],
+                        async_pool: hashes[1],
+                        deferred_credits: hashes[2],
+                        cycle_history: hashes[3],
+                        executed_ops: hashes[4],
+                        final_state: hashes[5],
+                    },
+                })
+                .parse(input),
                 MessageServerTypeId::SlotTooOld => Ok((input, BootstrapServerMessage::SlotTooOld)),
                 MessageServerTypeId::BootstrapError => context(
                     "Failed BootstrapError deserialization",
@@ -534,6 +753,13 @@ pub enum BootstrapClientMessage {
         /// Last received consensus block slot
         last_consensus_step: StreamingStep<PreHashSet<BlockId>>,
     },
+    /// Ask for a summary of the final state, for light clients
+    AskLightState {
+        /// Ledger keys whose values are requested
+        ledger_keys: Vec<LedgerKey>,
+    },
+    /// Ask for the state of the MIPs
+    AskMipStore,
     /// Bootstrap error
     BootstrapError {
         /// Error message
@@ -550,6 +776,8 @@ enum MessageClientTypeId {
     AskFinalStatePart = 1u32,
     BootstrapError = 2u32,
     BootstrapSuccess = 3u32,
+    AskLightState = 4u32,
+    AskMipStore = 5u32,
 }
 
 /// Serializer for `BootstrapClientMessage`
@@ -564,6 +792,7 @@ pub struct BootstrapClientMessageSerializer {
         PreHashSet<BlockId>,
         PreHashSetSerializer<BlockId, BlockIdSerializer>,
     >,
+    ledger_key_serializer: KeySerializer,
 }
 
 impl BootstrapClientMessageSerializer {
@@ -579,6 +808,7 @@ impl BootstrapClientMessageSerializer {
             block_ids_step_serializer: StreamingStepSerializer::new(PreHashSetSerializer::new(
                 BlockIdSerializer::new(),
             )),
+            ledger_key_serializer: KeySerializer::new(true),
         }
     }
 }
@@ -654,6 +884,23 @@ impl Serializer<BootstrapClientMessage> for BootstrapClientMessageSerializer {
                 self.u32_serializer
                     .serialize(&u32::from(MessageClientTypeId::BootstrapSuccess), buffer)?;
             }
+            BootstrapClientMessage::AskMipStore => {
+                self.u32_serializer
+                    .serialize(&u32::from(MessageClientTypeId::AskMipStore), buffer)?;
+            }
+            BootstrapClientMessage::AskLightState { ledger_keys } => {
+                self.u32_serializer
+                    .serialize(&u32::from(MessageClientTypeId::AskLightState), buffer)?;
+                self.u32_serializer.serialize(
+                    &ledger_keys.len().try_into().map_err(|_| {
+                        SerializeError::GeneralError("Fail to convert usize to u32".to_string())
+                    })?,
+                    buffer,
+                )?;
+                for key in ledger_keys {
+                    self.ledger_key_serializer.serialize(key, buffer)?;
+                }
+            }
         }
         Ok(())
     }
@@ -672,6 +919,8 @@ pub struct BootstrapClientMessageDeserializer {
         PreHashSet<BlockId>,
         PreHashSetDeserializer<BlockId, BlockIdDeserializer>,
     >,
+    length_ledger_keys_deserializer: U32VarIntDeserializer,
+    ledger_key_deserializer: KeyDeserializer,
 }
 
 impl BootstrapClientMessageDeserializer {
@@ -710,6 +959,11 @@ impl BootstrapClientMessageDeserializer {
                     Included(max_consensus_block_ids),
                 ),
             ),
+            length_ledger_keys_deserializer: U32VarIntDeserializer::new(
+                Included(0),
+                Included(MAX_LIGHT_BOOTSTRAP_LEDGER_KEYS),
+            ),
+            ledger_key_deserializer: KeyDeserializer::new(max_datastore_key_length, true),
         }
     }
 }
@@ -831,6 +1085,20 @@ impl Deserializer<BootstrapClientMessage> for BootstrapClientMessageDeserializer
                 MessageClientTypeId::BootstrapSuccess => {
                     Ok((input, BootstrapClientMessage::BootstrapSuccess))
                 }
+                MessageClientTypeId::AskMipStore => {
+                    Ok((input, BootstrapClientMessage::AskMipStore))
+                }
+                MessageClientTypeId::AskLightState => context(
+                    "Failed ledger_keys deserialization",
+                    length_count(
+                        context("Failed length deserialization", |input| {
+                            self.length_ledger_keys_deserializer.deserialize(input)
+                        }),
+                        |input| self.ledger_key_deserializer.deserialize(input),
+                    ),
+                )
+                .map(|ledger_keys| BootstrapClientMessage::AskLightState { ledger_keys })
+                .parse(input),
             }
         })
         .parse(buffer)
@@ -33,13 +33,14 @@ use humantime::format_duration;
 use massa_async_pool::AsyncMessageId;
 use massa_consensus_exports::{bootstrapable_graph::BootstrapableGraph, ConsensusController};
 use massa_final_state::{FinalState, FinalStateError};
-use massa_ledger_exports::Key as LedgerKey;
+use massa_ledger_exports::{Key as LedgerKey, KeyType};
 use massa_logging::massa_trace;
 use massa_models::{
-    block_id::BlockId, prehash::PreHashSet, slot::Slot, streaming_step::StreamingStep,
-    version::Version,
+    amount::AmountSerializer, block_id::BlockId, config::NODE_CAPABILITIES, prehash::PreHashSet,
+    slot::Slot, streaming_step::StreamingStep, version::Version,
 };
 use massa_network_exports::NetworkCommandSender;
+use massa_serialization::Serializer;
 use massa_signature::KeyPair;
 use massa_time::MassaTime;
 use parking_lot::RwLock;
@@ -85,15 +86,21 @@ impl BootstrapManager {
         if self.update_stopper_tx.send(()).is_err() {
             warn!("bootstrap ip-list-updater already dropped");
         }
-        // TODO?: handle join errors.
-
         // when the runtime is dropped at the end of this stop, the listener is auto-aborted
 
-        // unwrap() effectively passes up a panic from the thread being handled
-        self.update_handle.join().unwrap()?;
-
-        // unwrap() effectively passes up a panic from the thread being handled
-        self.main_handle.join().unwrap()
+        // a panic of the threads being joined is returned as an error
+        self.update_handle.join().map_err(|err| {
+            Box::new(BootstrapError::GeneralError(format!(
+                "ip-list-updater panicked: {:?}",
+                err
+            )))
+        })??;
+        self.main_handle.join().map_err(|err| {
+            Box::new(BootstrapError::GeneralError(format!(
+                "bootstrap server panicked: {:?}",
+                err
+            )))
+        })?
     }
 }
 
@@ -568,6 +575,7 @@ pub async fn stream_bootstrap_information(
         }
 
         let current_slot;
+        let current_state_hashes;
         let ledger_part;
         let async_pool_part;
         let pos_cycle_part;
@@ -637,6 +645,14 @@ pub async fn stream_bootstrap_information(
             last_ops_step = new_ops_step;
             last_slot = Some(final_state_read.slot);
             current_slot = final_state_read.slot;
+            // the hashes are only sent at the end of the bootstrap, once the whole state is streamed
+            current_state_hashes = (last_ledger_step.finished()
+                && last_pool_step.finished()
+                && last_cycle_step.finished()
+                && last_credits_step.finished()
+                && last_ops_step.finished()
+                && final_state_changes.is_empty())
+            .then(|| final_state_read.get_state_hashes());
         }
 
         if slot_too_old {
@@ -701,12 +717,19 @@ pub async fn stream_bootstrap_information(
         }
 
         // If the consensus streaming is finished (also meaning that consensus slot == final state slot) exit
-        if final_state_global_step.finished()
+        if let Some(final_state_hashes) = current_state_hashes
+            && final_state_global_step.finished()
             && final_state_changes_step.finished()
             && last_consensus_step.finished()
         {
             match server
-                .send_msg(write_timeout, BootstrapServerMessage::BootstrapFinished)
+                .send_msg(
+                    write_timeout,
+                    BootstrapServerMessage::BootstrapFinished {
+                        slot: current_slot,
+                        final_state_hashes,
+                    },
+                )
                 .await
             {
                 Err(_) => Err(std::io::Error::new(
@@ -750,6 +773,55 @@ pub async fn stream_bootstrap_information(
     Ok(())
 }
 
+/// Summarizes the final state for a light client: the headers of the latest final blocks,
+/// the latest PoS cycle and the values of the requested ledger keys
+fn build_light_state(
+    final_state: &RwLock<FinalState>,
+    consensus_controller: &dyn ConsensusController,
+    ledger_keys: Vec<LedgerKey>,
+) -> Result<BootstrapServerMessage, BootstrapError> {
+    // note that consensus finality can be slightly ahead of the final state slot
+    let graph = consensus_controller.get_block_graph_status(None, None)?;
+    let final_headers = graph
+        .latest_final_blocks_periods
+        .iter()
+        .filter_map(|(block_id, _)| graph.active_blocks.get(block_id))
+        .map(|block| block.header.clone())
+        .collect();
+
+    let final_state = final_state.read();
+    let amount_serializer = AmountSerializer::new();
+    let mut ledger_values = Vec::with_capacity(ledger_keys.len());
+    for key in ledger_keys {
+        // values are sent in their ledger database format
+        let value = match &key.key_type {
+            KeyType::BALANCE => match final_state.ledger.get_balance(&key.address) {
+                Some(balance) => {
+                    let mut bytes = Vec::new();
+                    amount_serializer.serialize(&balance, &mut bytes)?;
+                    Some(bytes)
+                }
+                None => None,
+            },
+            KeyType::BYTECODE => final_state
+                .ledger
+                .get_bytecode(&key.address)
+                .map(|bytecode| bytecode.0),
+            KeyType::DATASTORE(datastore_key) => final_state
+                .ledger
+                .get_data_entry(&key.address, datastore_key),
+        };
+        ledger_values.push((key, value));
+    }
+    Ok(BootstrapServerMessage::LightState {
+        slot: final_state.slot,
+        final_state_hash: final_state.final_state_hash,
+        final_headers,
+        pos_cycle: final_state.pos_state.cycle_history.back().cloned(),
+        ledger_values,
+    })
+}
+
 #[allow(clippy::too_many_arguments)]
 async fn manage_bootstrap(
     bootstrap_config: &BootstrapConfig,
@@ -799,6 +871,7 @@ async fn manage_bootstrap(
             BootstrapServerMessage::BootstrapTime {
                 server_time,
                 version,
+                capabilities: NODE_CAPABILITIES,
             },
         )
         .await
@@ -860,6 +933,37 @@ async fn manage_bootstrap(
                     )
                     .await?;
                 }
+                BootstrapClientMessage::AskLightState { ledger_keys } => {
+                    let light_state = build_light_state(
+                        &final_state,
+                        consensus_controller.as_ref(),
+                        ledger_keys,
+                    )?;
+                    match server.send_msg(write_timeout, light_state).await {
+                        Err(_) => Err(std::io::Error::new(
+                            std::io::ErrorKind::TimedOut,
+                            "bootstrap light state send timed out",
+                        )
+                        .into()),
+                        Ok(Err(e)) => Err(e),
+                        Ok(Ok(_)) => Ok(()),
+                    }?;
+                }
+                BootstrapClientMessage::AskMipStore => {
+                    let snapshot = final_state.read().mip_store.snapshot();
+                    match server
+                        .send_msg(write_timeout, BootstrapServerMessage::MipStore { snapshot })
+                        .await
+                    {
+                        Err(_) => Err(std::io::Error::new(
+                            std::io::ErrorKind::TimedOut,
+                            "bootstrap mip store send timed out",
+                        )
+                        .into()),
+                        Ok(Err(e)) => Err(e),
+                        Ok(Ok(_)) => Ok(()),
+                    }?;
+                }
                 BootstrapClientMessage::BootstrapSuccess => break Ok(()),
                 BootstrapClientMessage::BootstrapError { error } => {
                     break Err(BootstrapError::ReceivedError(error));
@@ -1,7 +1,9 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 use massa_models::block::BlockDeserializerArgs;
+use massa_models::checkpoint::TrustedCheckpoint;
 use massa_models::node::NodeId;
+use massa_signature::PublicKey;
 use massa_time::MassaTime;
 use serde::Deserialize;
 use std::{net::SocketAddr, path::PathBuf};
@@ -19,11 +21,24 @@ pub enum IpType {
     IPv6,
 }
 
+/// Directory publishing a signed list of bootstrap servers
+#[derive(Debug, Deserialize, Clone)]
+pub struct BootstrapDirectory {
+    /// `http://` URL of the list
+    pub url: String,
+    /// public key of the publisher signing the list
+    pub publisher_key: PublicKey,
+}
+
 /// Bootstrap configuration.
 #[derive(Debug, Deserialize, Clone)]
 pub struct BootstrapConfig {
     /// Ip address of our bootstrap nodes and their public key.
     pub bootstrap_list: Vec<(SocketAddr, NodeId)>,
+    /// Directories whose servers are added to the bootstrap list
+    pub bootstrap_directories: Vec<BootstrapDirectory>,
+    /// Timeout of the fetch of each directory
+    pub directory_timeout: MassaTime,
     /// IP version filter for bootstrap list, targeting IpType::IPv4, IpType::IPv6 or IpType::Both. Defaults to IpType::Both.
     pub bootstrap_protocol: IpType,
     /// Path to the bootstrap whitelist file. This whitelist define IPs that can bootstrap on your node.
@@ -61,6 +76,10 @@ pub struct BootstrapConfig {
     pub ip_list_max_size: usize,
     /// Read-Write limitation for a connection in bytes per seconds
     pub max_bytes_read_write: f64,
+    /// Max number of received parts of the final state waiting to be applied by the client
+    pub apply_queue_length: usize,
+    /// Point of the final history the bootstrapped state must agree with, if any
+    pub trusted_checkpoint: Option<TrustedCheckpoint>,
     /// max bootstrap message size in bytes
     pub max_bootstrap_message_size: u32,
     /// thread count
@@ -3,9 +3,13 @@ use crate::settings::{BootstrapClientConfig, BootstrapSrvBindCfg};
 use crate::types::Duplex;
 use crate::BootstrapConfig;
 use crate::{
-    client_binder::BootstrapClientBinder, server_binder::BootstrapServerBinder,
-    tests::tools::get_bootstrap_config, BootstrapPeers,
+    client_binder::BootstrapClientBinder,
+    server_binder::BootstrapServerBinder,
+    tests::tools::{get_boot_state, get_bootstrap_config, get_random_address},
+    BootstrapPeers,
 };
+use massa_hash::Hash;
+use massa_ledger_exports::{Key as LedgerKey, KeyType};
 use massa_models::config::{
     BOOTSTRAP_RANDOMNESS_SIZE_BYTES, CONSENSUS_BOOTSTRAP_PART_SIZE, ENDORSEMENT_COUNT,
     MAX_ADVERTISE_LENGTH, MAX_ASYNC_MESSAGE_DATA, MAX_ASYNC_POOL_LENGTH,
@@ -16,6 +20,7 @@ use massa_models::config::{
     MAX_OPERATIONS_PER_BLOCK, MAX_PRODUCTION_STATS_LENGTH, MAX_ROLLS_COUNT_LENGTH, THREAD_COUNT,
 };
 use massa_models::node::NodeId;
+use massa_models::slot::Slot;
 use massa_models::version::Version;
 use massa_signature::{KeyPair, PublicKey};
 use massa_time::MassaTime;
@@ -341,3 +346,101 @@ async fn test_binders_try_double_send_client_works() {
     server_thread.await.unwrap();
     client_thread.await.unwrap();
 }
+
+/// The client asks for a light state and the server answers it
+#[tokio::test]
+#[serial]
+async fn test_binders_light_state() {
+    let (bootstrap_config, server_keypair): &(BootstrapConfig, KeyPair) = &BOOTSTRAP_CONFIG_KEYPAIR;
+    let (client, server) = duplex(1000000);
+    let mut server = BootstrapServerBinder::new(
+        server,
+        server_keypair.clone(),
+        BootstrapSrvBindCfg {
+            max_bytes_read_write: f64::INFINITY,
+            max_bootstrap_message_size: MAX_BOOTSTRAP_MESSAGE_SIZE,
+            thread_count: THREAD_COUNT,
+            max_datastore_key_length: MAX_DATASTORE_KEY_LENGTH,
+            randomness_size_bytes: BOOTSTRAP_RANDOMNESS_SIZE_BYTES,
+            consensus_bootstrap_part_size: CONSENSUS_BOOTSTRAP_PART_SIZE,
+            write_error_timeout: MassaTime::from_millis(1000),
+        },
+    );
+    let mut client = BootstrapClientBinder::test_default(
+        client,
+        bootstrap_config.bootstrap_list[0].1.get_public_key(),
+    );
+    let address = get_random_address();
+    let ledger_keys = vec![
+        LedgerKey::new(&address, KeyType::BALANCE),
+        LedgerKey::new(&address, KeyType::DATASTORE(b"key".to_vec())),
+    ];
+    let header = get_boot_state().final_blocks[0]
+        .block
+        .content
+        .header
+        .clone();
+    let header_id = header.id;
+
+    let server_keys = ledger_keys.clone();
+    let server_thread = tokio::spawn(async move {
+        let version: Version = Version::from_str("TEST.1.10").unwrap();
+        server.handshake(version).await.unwrap();
+        let ledger_keys = match server.next().await.unwrap() {
+            BootstrapClientMessage::AskLightState { ledger_keys } => ledger_keys,
+            _ => panic!("Bad message receive: Expected a light state request"),
+        };
+        assert_eq!(ledger_keys, server_keys);
+        server
+            .send(BootstrapServerMessage::LightState {
+                slot: Slot::new(1, 0),
+                final_state_hash: Hash::compute_from(b"final state"),
+                final_headers: vec![header],
+                pos_cycle: None,
+                ledger_values: vec![
+                    (ledger_keys[0].clone(), Some(vec![42])),
+                    (ledger_keys[1].clone(), None),
+                ],
+            })
+            .await
+            .unwrap();
+    });
+
+    let client_thread = tokio::spawn(async move {
+        let version: Version = Version::from_str("TEST.1.10").unwrap();
+        client.handshake(version).await.unwrap();
+        client
+            .send(&BootstrapClientMessage::AskLightState {
+                ledger_keys: ledger_keys.clone(),
+            })
+            .await
+            .unwrap();
+        match client.next().await.unwrap() {
+            BootstrapServerMessage::LightState {
+                slot,
+                final_state_hash,
+                final_headers,
+                pos_cycle,
+                ledger_values,
+            } => {
+                assert_eq!(slot, Slot::new(1, 0));
+                assert_eq!(final_state_hash, Hash::compute_from(b"final state"));
+                assert_eq!(final_headers.len(), 1);
+                assert_eq!(final_headers[0].id, header_id);
+                final_headers[0].verify_signature().unwrap();
+                assert!(pos_cycle.is_none());
+                assert_eq!(
+                    ledger_values,
+                    vec![
+                        (ledger_keys[0].clone(), Some(vec![42])),
+                        (ledger_keys[1].clone(), None),
+                    ]
+                );
+            }
+            _ => panic!("Bad message receive: Expected a light state"),
+        }
+    });
+
+    server_thread.await.unwrap();
+    client_thread.await.unwrap();
+}
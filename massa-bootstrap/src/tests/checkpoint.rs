@@ -0,0 +1,120 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use super::tools::{get_boot_state, get_bootstrap_config, get_dummy_block_id};
+use super::trusted_sync::create_final_state;
+use crate::client::check_trusted_checkpoint;
+use crate::error::BootstrapError;
+use massa_final_state::FinalState;
+use massa_hash::Hash;
+use massa_models::checkpoint::TrustedCheckpoint;
+use massa_models::config::{PERIODS_PER_CYCLE, THREAD_COUNT};
+use massa_models::node::NodeId;
+use massa_models::slot::Slot;
+use massa_signature::KeyPair;
+use tempfile::TempDir;
+
+fn check(
+    checkpoint: &TrustedCheckpoint,
+    final_state: &FinalState,
+    with_graph: bool,
+) -> Result<(), BootstrapError> {
+    let cfg = get_bootstrap_config(NodeId::new(KeyPair::generate().get_public_key()));
+    let graph = with_graph.then(get_boot_state);
+    check_trusted_checkpoint(&cfg, checkpoint, final_state, graph.as_ref())
+}
+
+fn is_mismatch(result: Result<(), BootstrapError>) -> bool {
+    matches!(result, Err(BootstrapError::CheckpointMismatch(_)))
+}
+
+#[test]
+fn test_checkpoint_after_final_state() {
+    let temp_dir = TempDir::new().unwrap();
+    let final_state = create_final_state(&temp_dir.path().join("ledger"));
+    let checkpoint = TrustedCheckpoint {
+        slot: Slot::new(5, 0),
+        final_state_hash: Hash::compute_from(b"state"),
+        block_id: None,
+    };
+    // checked by consensus during the sync
+    assert!(check(&checkpoint, &final_state, false).is_ok());
+}
+
+#[test]
+fn test_checkpoint_at_final_state() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut final_state = create_final_state(&temp_dir.path().join("ledger"));
+    final_state.final_state_hash = Hash::compute_from(b"state");
+    let mut checkpoint = TrustedCheckpoint {
+        slot: final_state.slot,
+        final_state_hash: Hash::compute_from(b"other state"),
+        block_id: None,
+    };
+    assert!(is_mismatch(check(&checkpoint, &final_state, false)));
+    checkpoint.final_state_hash = final_state.final_state_hash;
+    assert!(check(&checkpoint, &final_state, false).is_ok());
+}
+
+#[test]
+fn test_checkpoint_in_graph() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut final_state = create_final_state(&temp_dir.path().join("ledger"));
+    final_state.slot = Slot::new(2, 0);
+    // the final block of the graph is at slot (1, 0)
+    let block_id = get_boot_state().final_blocks[0].block.id;
+    let mut checkpoint = TrustedCheckpoint {
+        slot: Slot::new(1, 0),
+        final_state_hash: Hash::compute_from(b"state"),
+        block_id: Some(get_dummy_block_id("other")),
+    };
+    assert!(is_mismatch(check(&checkpoint, &final_state, true)));
+    // a miss at the slot of the block conflicts too
+    checkpoint.block_id = None;
+    assert!(is_mismatch(check(&checkpoint, &final_state, true)));
+    checkpoint.block_id = Some(block_id);
+    assert!(check(&checkpoint, &final_state, true).is_ok());
+}
+
+#[test]
+fn test_checkpoint_at_end_of_cycle() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut final_state = create_final_state(&temp_dir.path().join("ledger"));
+    final_state.pos_state.create_initial_cycle();
+    let snapshot = Hash::compute_from(b"end of cycle 0");
+    let cycle_info = final_state.pos_state.cycle_history.back_mut().unwrap();
+    cycle_info.complete = true;
+    cycle_info.final_state_hash_snapshot = Some(snapshot);
+    final_state.slot = Slot::new(PERIODS_PER_CYCLE + 1, 0);
+    let mut checkpoint = TrustedCheckpoint {
+        slot: Slot::new_last_of_cycle(0, PERIODS_PER_CYCLE, THREAD_COUNT).unwrap(),
+        final_state_hash: Hash::compute_from(b"other state"),
+        block_id: None,
+    };
+    assert!(is_mismatch(check(&checkpoint, &final_state, false)));
+    checkpoint.final_state_hash = snapshot;
+    assert!(check(&checkpoint, &final_state, false).is_ok());
+
+    // the snapshot of an incomplete cycle is not used
+    final_state
+        .pos_state
+        .cycle_history
+        .back_mut()
+        .unwrap()
+        .complete = false;
+    assert!(is_mismatch(check(&checkpoint, &final_state, false)));
+}
+
+#[test]
+fn test_checkpoint_too_old() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut final_state = create_final_state(&temp_dir.path().join("ledger"));
+    final_state.slot = Slot::new(2, 0);
+    // neither in the graph nor at the end of a cycle
+    let checkpoint = TrustedCheckpoint {
+        slot: Slot::new(1, 1),
+        final_state_hash: Hash::compute_from(b"state"),
+        block_id: None,
+    };
+    assert!(is_mismatch(check(&checkpoint, &final_state, true)));
+    assert!(is_mismatch(check(&checkpoint, &final_state, false)));
+}
@@ -0,0 +1,60 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use crate::directory::{fetch_directories, fetch_directory};
+use crate::{BootstrapDirectory, SignedServerList};
+use massa_models::node::NodeId;
+use massa_signature::KeyPair;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// Serves `body` to the next `count` requests, returns the URL of the directory
+async fn serve_directory(body: String, count: usize) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        for _ in 0..count {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = [0u8; 1024];
+            let _ = stream.read(&mut request).await.unwrap();
+            let response = format!(
+                "HTTP/1.0 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+        }
+    });
+    format!("http://{}/bootstrap.json", addr)
+}
+
+#[tokio::test]
+async fn test_fetch_directory() {
+    let publisher = KeyPair::generate();
+    let servers = vec![
+        (
+            "1.2.3.4:31245".parse().unwrap(),
+            NodeId::new(KeyPair::generate().get_public_key()),
+        ),
+        (
+            "[2001:db8::1]:31245".parse().unwrap(),
+            NodeId::new(KeyPair::generate().get_public_key()),
+        ),
+    ];
+    let list = SignedServerList::new(servers.clone(), &publisher).unwrap();
+    let url = serve_directory(serde_json::to_string(&list).unwrap(), 3).await;
+
+    let directory = BootstrapDirectory {
+        url: url.clone(),
+        publisher_key: publisher.get_public_key(),
+    };
+    assert_eq!(fetch_directory(&directory).await.unwrap(), servers);
+
+    // a list not signed by the configured publisher is ignored
+    let impostor = BootstrapDirectory {
+        url,
+        publisher_key: KeyPair::generate().get_public_key(),
+    };
+    assert!(fetch_directory(&impostor).await.is_err());
+    let fetched = fetch_directories(&[impostor, directory], 1000.into()).await;
+    assert_eq!(fetched, servers);
+}
@@ -1,6 +1,10 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 mod binders;
+mod checkpoint;
+mod directory;
 pub mod mock_establisher;
 mod scenarios;
+mod state_check;
 pub mod tools;
+mod trusted_sync;
@@ -16,6 +16,7 @@ use crate::{
     tests::tools::{assert_eq_bootstrap_graph, get_bootstrap_config},
 };
 use massa_async_pool::AsyncPoolConfig;
+use massa_channel::{channel, ChannelConfig};
 use massa_consensus_exports::{
     bootstrapable_graph::BootstrapableGraph,
     test_exports::{MockConsensusController, MockConsensusControllerMessage},
@@ -44,11 +45,11 @@ use massa_pos_exports::{
 use massa_pos_worker::start_selector_worker;
 use massa_signature::KeyPair;
 use massa_time::MassaTime;
+use massa_versioning::MipStoreConfig;
 use parking_lot::RwLock;
 use serial_test::serial;
 use std::{path::PathBuf, str::FromStr, sync::Arc, time::Duration};
 use tempfile::TempDir;
-use tokio::sync::mpsc;
 
 lazy_static::lazy_static! {
     pub static ref BOOTSTRAP_CONFIG_KEYPAIR: (BootstrapConfig, KeyPair) = {
@@ -68,7 +69,8 @@ async fn test_bootstrap_server() {
 
     let (consensus_controller, mut consensus_event_receiver) =
         MockConsensusController::new_with_receiver();
-    let (network_cmd_tx, mut network_cmd_rx) = mpsc::channel::<NetworkCommand>(5);
+    let (network_cmd_tx, mut network_cmd_rx) =
+        channel::<NetworkCommand>("network_command", ChannelConfig::new(5));
 
     // setup final state local config
     let temp_dir = TempDir::new().unwrap();
@@ -91,6 +93,8 @@ async fn test_bootstrap_server() {
             periods_per_cycle,
             thread_count,
             cycle_history_length: POS_SAVED_CYCLES,
+            cycle_info_retention: 0,
+            deferred_credits_retention: 0,
             credits_bootstrap_part_size: 100,
         },
         executed_ops_config: ExecutedOpsConfig {
@@ -100,8 +104,16 @@ async fn test_bootstrap_server() {
         final_history_length: 100,
         initial_seed_string: "".into(),
         initial_rolls_path: "".into(),
+        genesis_path: None,
         thread_count,
         periods_per_cycle,
+        journal_path: None,
+        journal_checkpoint_interval: 256,
+        mip_store_config: MipStoreConfig {
+            mips: Vec::new(),
+            block_count_considered: 100,
+            activation_threshold_percent: 75,
+        },
     };
 
     // setup selector local config
@@ -171,6 +183,7 @@ async fn test_bootstrap_server() {
             Version::from_str("TEST.1.10").unwrap(),
             MassaTime::now().unwrap().saturating_sub(1000.into()),
             None,
+            false,
         )
         .await
         .unwrap()
@@ -282,6 +295,20 @@ async fn test_bootstrap_server() {
                 async_pool_changes: get_random_async_pool_changes(10),
                 executed_ops_changes: get_random_executed_ops_changes(10),
             };
+            // apply the changes like a finalization, so that the streamed hashes match the state
+            final_write
+                .pos_state
+                .apply_changes(changes.pos_changes.clone(), next, false)
+                .unwrap();
+            final_write
+                .ledger
+                .apply_changes(changes.ledger_changes.clone(), next);
+            final_write
+                .async_pool
+                .apply_changes_unchecked(&changes.async_pool_changes);
+            final_write
+                .executed_ops
+                .apply_changes(changes.executed_ops_changes.clone(), next);
             final_write
                 .changes_history
                 .push_back((next, changes.clone()));
@@ -301,26 +328,27 @@ async fn test_bootstrap_server() {
     // wait for bridge
     bridge.await.expect("bridge join failed");
 
-    // apply the changes to the server state before matching with the client
+    // check that the client caught up with the changes made on the server while streaming
     {
-        let mut final_state_server_write = final_state_server.write();
-        let list_changes_read = list_changes.read().clone();
-        // note: skip the first change to match the update loop behaviour
-        for (slot, change) in list_changes_read.iter().skip(1) {
-            final_state_server_write
-                .pos_state
-                .apply_changes(change.pos_changes.clone(), *slot, false)
-                .unwrap();
-            final_state_server_write
-                .ledger
-                .apply_changes(change.ledger_changes.clone(), *slot);
-            final_state_server_write
-                .async_pool
-                .apply_changes_unchecked(&change.async_pool_changes);
-            final_state_server_write
-                .executed_ops
-                .apply_changes(change.executed_ops_changes.clone(), *slot);
-        }
+        let list_changes_read = list_changes.read();
+        let (last_slot, _) = list_changes_read
+            .last()
+            .expect("no changes were made during the bootstrap");
+        assert_eq!(
+            final_state_client.read().slot,
+            *last_slot,
+            "the client final state is not attached to the last changed slot"
+        );
+        let final_state_server_read = final_state_server.read();
+        assert!(
+            list_changes_read
+                .iter()
+                .all(|(slot, _)| final_state_server_read
+                    .changes_history
+                    .iter()
+                    .any(|(history_slot, _)| history_slot == slot)),
+            "the server history is missing some of the streamed changes"
+        );
     }
 
     // check final states
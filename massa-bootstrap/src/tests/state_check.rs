@@ -0,0 +1,112 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use super::tools::get_boot_state;
+use super::trusted_sync::create_final_state;
+use crate::client::check_bootstrapped_state;
+use crate::error::BootstrapError;
+use massa_final_state::{FinalState, FinalStateHashes};
+use massa_hash::Hash;
+use massa_models::config::THREAD_COUNT;
+use massa_models::slot::Slot;
+use parking_lot::RwLock;
+use tempfile::TempDir;
+
+fn check(
+    final_state: &RwLock<FinalState>,
+    with_graph: bool,
+    slot: Slot,
+    expected_hashes: &FinalStateHashes,
+) -> Result<(), String> {
+    let graph = with_graph.then(get_boot_state);
+    match check_bootstrapped_state(final_state, graph.as_ref(), slot, expected_hashes) {
+        Ok(()) => Ok(()),
+        Err(BootstrapError::StateMismatch(msg)) => Err(msg),
+        Err(err) => panic!("unexpected error: {}", err),
+    }
+}
+
+#[test]
+fn test_bootstrapped_state_match() {
+    let temp_dir = TempDir::new().unwrap();
+    let final_state = RwLock::new(create_final_state(&temp_dir.path().join("ledger")));
+    let (slot, hashes) = {
+        let final_state_read = final_state.read();
+        (final_state_read.slot, final_state_read.get_state_hashes())
+    };
+    assert!(check(&final_state, false, slot, &hashes).is_ok());
+    // the checked hash becomes the final state hash
+    assert_eq!(final_state.read().final_state_hash, hashes.final_state);
+}
+
+#[test]
+fn test_bootstrapped_state_hash_mismatch() {
+    let temp_dir = TempDir::new().unwrap();
+    let final_state = RwLock::new(create_final_state(&temp_dir.path().join("ledger")));
+    let (slot, hashes) = {
+        let final_state_read = final_state.read();
+        (final_state_read.slot, final_state_read.get_state_hashes())
+    };
+
+    // the diagnostic names the differing components
+    let expected_hashes = FinalStateHashes {
+        ledger: Hash::compute_from(b"other ledger"),
+        executed_ops: Hash::compute_from(b"other executed ops"),
+        final_state: Hash::compute_from(b"other state"),
+        ..hashes
+    };
+    let msg = check(&final_state, false, slot, &expected_hashes).unwrap_err();
+    assert!(
+        msg.ends_with("differing components: ledger, executed operations"),
+        "{}",
+        msg
+    );
+
+    // only the combined hash differs
+    let expected_hashes = FinalStateHashes {
+        final_state: Hash::compute_from(b"other state"),
+        ..hashes
+    };
+    let msg = check(&final_state, false, slot, &expected_hashes).unwrap_err();
+    assert!(
+        msg.ends_with("differing components: none, the combined hash differs"),
+        "{}",
+        msg
+    );
+}
+
+#[test]
+fn test_bootstrapped_state_slot_mismatch() {
+    let temp_dir = TempDir::new().unwrap();
+    let final_state = RwLock::new(create_final_state(&temp_dir.path().join("ledger")));
+    let (slot, hashes) = {
+        let final_state_read = final_state.read();
+        (final_state_read.slot, final_state_read.get_state_hashes())
+    };
+    let streamed_slot = slot.get_next_slot(THREAD_COUNT).unwrap();
+    let msg = check(&final_state, false, streamed_slot, &hashes).unwrap_err();
+    assert!(
+        msg.starts_with(&format!("the final state is attached to slot {}", slot)),
+        "{}",
+        msg
+    );
+}
+
+#[test]
+fn test_bootstrapped_final_block_after_slot() {
+    let temp_dir = TempDir::new().unwrap();
+    let final_state = RwLock::new(create_final_state(&temp_dir.path().join("ledger")));
+    let (slot, hashes) = {
+        let final_state_read = final_state.read();
+        (final_state_read.slot, final_state_read.get_state_hashes())
+    };
+    // the final block of the bootstrap graph is at slot (1, 0), after the initial final state
+    assert!(slot < Slot::new(1, 0));
+    let msg = check(&final_state, true, slot, &hashes).unwrap_err();
+    assert!(
+        msg.contains(&format!("is after the slot {} of the final state", slot)),
+        "{}",
+        msg
+    );
+    // the final state hash is left untouched
+    assert_ne!(final_state.read().final_state_hash, hashes.final_state);
+}
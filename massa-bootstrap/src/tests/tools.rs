@@ -5,6 +5,7 @@ use crate::settings::{BootstrapConfig, IpType};
 use bitvec::vec::BitVec;
 use massa_async_pool::test_exports::{create_async_pool, get_random_message};
 use massa_async_pool::{AsyncPoolChanges, Change};
+use massa_channel::MassaReceiver;
 use massa_consensus_exports::{
     bootstrapable_graph::{
         BootstrapableGraph, BootstrapableGraphDeserializer, BootstrapableGraphSerializer,
@@ -62,7 +63,7 @@ use std::{
 };
 use tokio::io::AsyncReadExt;
 use tokio::io::AsyncWriteExt;
-use tokio::{sync::mpsc::Receiver, time::sleep};
+use tokio::time::sleep;
 
 pub const BASE_BOOTSTRAP_IP: IpAddr = IpAddr::V4(Ipv4Addr::new(169, 202, 0, 10));
 
@@ -293,6 +294,8 @@ pub fn get_bootstrap_config(bootstrap_public_key: NodeId) -> BootstrapConfig {
     BootstrapConfig {
         listen_addr: Some("0.0.0.0:31244".parse().unwrap()),
         bootstrap_protocol: IpType::Both,
+        bootstrap_directories: Vec::new(),
+        directory_timeout: 1000.into(),
         bootstrap_timeout: 120000.into(),
         connect_timeout: 200.into(),
         retry_delay: 200.into(),
@@ -314,6 +317,8 @@ pub fn get_bootstrap_config(bootstrap_public_key: NodeId) -> BootstrapConfig {
         ip_list_max_size: 10,
         per_ip_min_interval: 10000.into(),
         max_bytes_read_write: std::f64::INFINITY,
+        apply_queue_length: 4,
+        trusted_checkpoint: None,
         max_bootstrap_message_size: MAX_BOOTSTRAP_MESSAGE_SIZE,
         max_datastore_key_length: MAX_DATASTORE_KEY_LENGTH,
         randomness_size_bytes: BOOTSTRAP_RANDOMNESS_SIZE_BYTES,
@@ -348,7 +353,7 @@ pub fn get_bootstrap_config(bootstrap_public_key: NodeId) -> BootstrapConfig {
 }
 
 pub async fn wait_network_command<F, T>(
-    network_command_receiver: &mut Receiver<NetworkCommand>,
+    network_command_receiver: &mut MassaReceiver<NetworkCommand>,
     timeout: MassaTime,
     filter_map: F,
 ) -> Option<T>
@@ -397,6 +402,7 @@ pub fn get_boot_state() -> BootstrapableGraph {
                     slot: Slot::new(1, 0),
                     parents: vec![get_dummy_block_id("p1"); THREAD_COUNT as usize],
                     operation_merkle_root: Hash::compute_from("op_hash".as_bytes()),
+                    announced_version: 0,
                     endorsements: vec![
                         Endorsement::new_verifiable(
                             Endorsement {
@@ -419,6 +425,7 @@ pub fn get_boot_state() -> BootstrapableGraph {
                         )
                         .unwrap(),
                     ],
+                    aggregated_endorsements: None,
                 },
                 BlockHeaderSerializer::new(),
                 &keypair,
@@ -0,0 +1,82 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use crate::{fetch_trusted_state, start_trusted_sync_server, TrustedSyncConfig};
+use massa_final_state::{read_manifest, FinalState, FinalStateConfig};
+use massa_ledger_exports::LedgerConfig;
+use massa_ledger_worker::FinalLedger;
+use massa_models::config::THREAD_COUNT;
+use massa_pos_exports::test_exports::MockSelectorController;
+use parking_lot::RwLock;
+use serial_test::serial;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+use tempfile::TempDir;
+
+pub(crate) fn create_final_state(ledger_path: &Path) -> FinalState {
+    let config = FinalStateConfig {
+        ledger_config: LedgerConfig {
+            disk_ledger_path: ledger_path.to_path_buf(),
+            ..Default::default()
+        },
+        thread_count: THREAD_COUNT,
+        initial_rolls_path: "../massa-node/base_config/initial_rolls.json".into(),
+        genesis_path: None,
+        ..Default::default()
+    };
+    let ledger = FinalLedger::new(config.ledger_config.clone());
+    let (selector_controller, _) = MockSelectorController::new_with_receiver();
+    FinalState::new(config, Box::new(ledger), selector_controller).unwrap()
+}
+
+fn trusted_sync_config(secret: &str, work_dir: &Path) -> TrustedSyncConfig {
+    TrustedSyncConfig {
+        secret: secret.into(),
+        work_dir: work_dir.to_path_buf(),
+        timeout: 5000.into(),
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn test_trusted_sync() {
+    let temp_dir = TempDir::new().unwrap();
+    let bind: SocketAddr = "127.0.0.1:31246".parse().unwrap();
+    let mut final_state = create_final_state(&temp_dir.path().join("ledger"));
+    final_state.pos_state.create_initial_cycle();
+    final_state.compute_state_hash_at_slot(final_state.slot);
+    let final_state_hash = final_state.final_state_hash;
+    let ledger_hash = final_state.ledger.get_ledger_hash();
+    let _server = start_trusted_sync_server(
+        trusted_sync_config("secret", &temp_dir.path().join("server")),
+        bind,
+        Arc::new(RwLock::new(final_state)),
+    )
+    .await
+    .unwrap();
+
+    // a node that does not know the secret gets nothing
+    let client_dir = temp_dir.path().join("client");
+    assert!(
+        fetch_trusted_state(&trusted_sync_config("wrong", &client_dir), bind)
+            .await
+            .is_err()
+    );
+    assert!(!client_dir.join("incoming").exists());
+
+    // a node that knows it receives a complete backup of the final state
+    let backup_path = fetch_trusted_state(&trusted_sync_config("secret", &client_dir), bind)
+        .await
+        .unwrap();
+    let manifest = read_manifest(&backup_path).unwrap();
+    assert_eq!(manifest.final_state_hash, final_state_hash);
+    assert_eq!(manifest.ledger_hash, ledger_hash);
+    assert!(backup_path
+        .join("ledger")
+        .read_dir()
+        .unwrap()
+        .next()
+        .is_some());
+    // the server does not keep the sent backup
+    assert!(!temp_dir.path().join("server").join("outgoing").exists());
+}
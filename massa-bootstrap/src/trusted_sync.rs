@@ -0,0 +1,318 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Final state synchronization between the nodes of a same operator.
+//!
+//! A node serving trusted sync gives a copy of its final state to the nodes that know a shared secret.
+//! It writes a backup of its final state (see `FinalState::create_backup`), which mostly consists of
+//! hard links to the files of its ledger database, and streams the files of the backup as they are.
+//! This is much faster than a regular bootstrap, which serializes and checks every part of the state.
+//! The receiving node restores the backup as if it was a local one, and then catches up with the
+//! network through a regular bootstrap from the slot of the backup.
+//!
+//! The secret authenticates both sides, but the stream is not encrypted:
+//! trusted sync is meant for the private network of the operator.
+
+use crate::error::BootstrapError;
+use massa_final_state::FinalState;
+use massa_hash::Hash;
+use massa_time::MassaTime;
+use parking_lot::RwLock;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use tokio::fs::File;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+const NONCE_SIZE: usize = 32;
+const CHUNK_SIZE: usize = 1 << 16;
+const MAX_PATH_LENGTH: u32 = 1024;
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// Trusted sync configuration
+#[derive(Debug, Clone)]
+pub struct TrustedSyncConfig {
+    /// secret shared by the nodes of the operator
+    pub secret: String,
+    /// directory in which the state is written before being sent or after being received
+    pub work_dir: PathBuf,
+    /// timeout of each network operation
+    pub timeout: MassaTime,
+}
+
+/// Trusted sync server, stops when dropped
+pub struct TrustedSyncServer {
+    handle: JoinHandle<()>,
+}
+
+impl Drop for TrustedSyncServer {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+/// Serves the final state to the trusted nodes connecting to `bind`, one at a time
+pub async fn start_trusted_sync_server(
+    config: TrustedSyncConfig,
+    bind: SocketAddr,
+    final_state: Arc<RwLock<FinalState>>,
+) -> Result<TrustedSyncServer, BootstrapError> {
+    if config.secret.is_empty() {
+        return Err(BootstrapError::TrustedSyncError(
+            "the trusted sync secret is empty".into(),
+        ));
+    }
+    let listener = TcpListener::bind(bind).await?;
+    info!("trusted sync server listening on {}", bind);
+    let handle = tokio::spawn(async move {
+        loop {
+            let (stream, remote_addr) = match listener.accept().await {
+                Ok(connection) => connection,
+                Err(err) => {
+                    warn!("trusted sync server could not accept a connection: {}", err);
+                    continue;
+                }
+            };
+            match serve_state(&config, stream, final_state.clone()).await {
+                Ok(()) => info!("final state sent to trusted node {}", remote_addr),
+                Err(err) => warn!(
+                    "could not send the final state to trusted node {}: {}",
+                    remote_addr, err
+                ),
+            }
+        }
+    });
+    Ok(TrustedSyncServer { handle })
+}
+
+/// Fetches the final state of the trusted node at `addr`.
+///
+/// # Returns
+/// The directory of the received backup, to be restored with `FinalState::restore_backup`
+pub async fn fetch_trusted_state(
+    config: &TrustedSyncConfig,
+    addr: SocketAddr,
+) -> Result<PathBuf, BootstrapError> {
+    if config.secret.is_empty() {
+        return Err(BootstrapError::TrustedSyncError(
+            "the trusted sync secret is empty".into(),
+        ));
+    }
+    let path = config.work_dir.join("incoming");
+    if path.exists() {
+        tokio::fs::remove_dir_all(&path).await?;
+    }
+    tokio::fs::create_dir_all(&path).await?;
+    info!("fetching the final state of trusted node {}", addr);
+    let result = async {
+        let mut stream = with_timeout(config.timeout, TcpStream::connect(addr)).await??;
+        let server_nonce: [u8; NONCE_SIZE] =
+            with_timeout(config.timeout, read_array(&mut stream)).await??;
+        let client_nonce: [u8; NONCE_SIZE] = rand::random();
+        let client_proof = proof(&config.secret, "client", &server_nonce, &client_nonce);
+        with_timeout(config.timeout, async {
+            stream.write_all(&client_nonce).await?;
+            stream.write_all(client_proof.to_bytes()).await
+        })
+        .await??;
+        let server_proof: [u8; 32] = with_timeout(config.timeout, read_array(&mut stream))
+            .await?
+            .map_err(|_| {
+                BootstrapError::TrustedSyncError("the trusted node refused the secret".into())
+            })?;
+        if server_proof != *proof(&config.secret, "server", &server_nonce, &client_nonce).to_bytes()
+        {
+            return Err(BootstrapError::TrustedSyncError(
+                "the trusted node does not know the secret".into(),
+            ));
+        }
+        receive_files(config.timeout, &mut stream, &path).await
+    }
+    .await;
+    if let Err(err) = result {
+        let _ = tokio::fs::remove_dir_all(&path).await;
+        return Err(err);
+    }
+    Ok(path)
+}
+
+/// Authenticates a trusted node and sends it a backup of the final state
+async fn serve_state(
+    config: &TrustedSyncConfig,
+    mut stream: TcpStream,
+    final_state: Arc<RwLock<FinalState>>,
+) -> Result<(), BootstrapError> {
+    let server_nonce: [u8; NONCE_SIZE] = rand::random();
+    with_timeout(config.timeout, stream.write_all(&server_nonce)).await??;
+    let (client_nonce, client_proof): ([u8; NONCE_SIZE], [u8; 32]) =
+        with_timeout(config.timeout, async {
+            Ok::<_, std::io::Error>((
+                read_array(&mut stream).await?,
+                read_array(&mut stream).await?,
+            ))
+        })
+        .await??;
+    if client_proof != *proof(&config.secret, "client", &server_nonce, &client_nonce).to_bytes() {
+        return Err(BootstrapError::TrustedSyncError(
+            "the node does not know the secret".into(),
+        ));
+    }
+    let server_proof = proof(&config.secret, "server", &server_nonce, &client_nonce);
+    with_timeout(config.timeout, stream.write_all(server_proof.to_bytes())).await??;
+
+    let path = config.work_dir.join("outgoing");
+    if path.exists() {
+        tokio::fs::remove_dir_all(&path).await?;
+    }
+    tokio::fs::create_dir_all(&config.work_dir).await?;
+    let backup_path = path.clone();
+    let manifest = tokio::task::spawn_blocking(move || {
+        // the read lock prevents slots from being finalized while the backup is written
+        final_state.read().create_backup(&backup_path)
+    })
+    .await??;
+    info!(
+        "sending the final state at slot {} to a trusted node",
+        manifest.slot
+    );
+    let result = send_files(config.timeout, &mut stream, &path).await;
+    tokio::fs::remove_dir_all(&path).await?;
+    result
+}
+
+/// Proves the knowledge of the secret for a given connection
+fn proof(
+    secret: &str,
+    side: &str,
+    server_nonce: &[u8; NONCE_SIZE],
+    client_nonce: &[u8; NONCE_SIZE],
+) -> Hash {
+    let mut bytes = Vec::new();
+    bytes.extend(secret.as_bytes());
+    bytes.extend(side.as_bytes());
+    bytes.extend(server_nonce);
+    bytes.extend(client_nonce);
+    Hash::compute_from(&bytes)
+}
+
+/// Files of a backup directory, relative to it, with the manifest last
+fn list_files(root: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut dirs = vec![root.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else if let Ok(relative) = path.strip_prefix(root) {
+                files.push(relative.to_path_buf());
+            }
+        }
+    }
+    // the receiver considers the backup incomplete until it gets the manifest
+    files.sort_by_key(|file| file == Path::new(MANIFEST_FILE));
+    Ok(files)
+}
+
+/// Streams the files of a directory: for each file, its relative path and its content,
+/// each one prefixed by its length. An empty path ends the stream.
+async fn send_files(
+    timeout: MassaTime,
+    stream: &mut TcpStream,
+    root: &Path,
+) -> Result<(), BootstrapError> {
+    let mut buffer = vec![0u8; CHUNK_SIZE];
+    for relative in list_files(root)? {
+        let name = relative.to_string_lossy().into_owned();
+        let mut file = File::open(root.join(&relative)).await?;
+        let mut remaining = file.metadata().await?.len();
+        with_timeout(timeout, async {
+            stream.write_u32(name.len() as u32).await?;
+            stream.write_all(name.as_bytes()).await?;
+            stream.write_u64(remaining).await
+        })
+        .await??;
+        while remaining > 0 {
+            let chunk_size = remaining.min(CHUNK_SIZE as u64) as usize;
+            file.read_exact(&mut buffer[..chunk_size]).await?;
+            with_timeout(timeout, stream.write_all(&buffer[..chunk_size])).await??;
+            remaining -= chunk_size as u64;
+        }
+    }
+    with_timeout(timeout, async {
+        stream.write_u32(0).await?;
+        stream.flush().await
+    })
+    .await??;
+    Ok(())
+}
+
+/// Receives the files streamed by `send_files` in `root`
+async fn receive_files(
+    timeout: MassaTime,
+    stream: &mut TcpStream,
+    root: &Path,
+) -> Result<(), BootstrapError> {
+    let mut buffer = vec![0u8; CHUNK_SIZE];
+    loop {
+        let name_length = with_timeout(timeout, stream.read_u32()).await??;
+        if name_length == 0 {
+            return Ok(());
+        }
+        if name_length > MAX_PATH_LENGTH {
+            return Err(BootstrapError::TrustedSyncError(format!(
+                "file path of {} bytes received",
+                name_length
+            )));
+        }
+        let mut name = vec![0u8; name_length as usize];
+        with_timeout(timeout, stream.read_exact(&mut name)).await??;
+        let relative =
+            PathBuf::from(String::from_utf8(name).map_err(|_| {
+                BootstrapError::TrustedSyncError("invalid file path received".into())
+            })?);
+        // never write outside of the destination directory
+        if !relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+        {
+            return Err(BootstrapError::TrustedSyncError(format!(
+                "invalid file path received: {}",
+                relative.display()
+            )));
+        }
+        let path = root.join(&relative);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let mut file = File::create(&path).await?;
+        let mut remaining = with_timeout(timeout, stream.read_u64()).await??;
+        while remaining > 0 {
+            let chunk_size = remaining.min(CHUNK_SIZE as u64) as usize;
+            with_timeout(timeout, stream.read_exact(&mut buffer[..chunk_size])).await??;
+            file.write_all(&buffer[..chunk_size]).await?;
+            remaining -= chunk_size as u64;
+        }
+        file.sync_all().await?;
+    }
+}
+
+async fn read_array<const N: usize>(stream: &mut TcpStream) -> std::io::Result<[u8; N]> {
+    let mut bytes = [0u8; N];
+    stream.read_exact(&mut bytes).await?;
+    Ok(bytes)
+}
+
+async fn with_timeout<T>(
+    timeout: MassaTime,
+    future: impl Future<Output = T>,
+) -> Result<T, BootstrapError> {
+    tokio::time::timeout(timeout.to_duration(), future)
+        .await
+        .map_err(|_| {
+            std::io::Error::new(std::io::ErrorKind::TimedOut, "trusted sync timed out").into()
+        })
+}
@@ -0,0 +1,343 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use crate::config::{ChannelConfig, OverflowPolicy, Priority};
+use crate::metrics::ChannelMetrics;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+pub use tokio::sync::mpsc::error::{SendError, SendTimeoutError, TryRecvError, TrySendError};
+
+/// message with its sending time, to measure the lag of the channel
+type Envelope<T> = (Instant, T);
+
+/// Creates a channel whose messages all have the normal priority
+///
+/// # Arguments
+/// * `name`: name of the channel in the metrics
+/// * `config`: capacity and overflow policy of the channel
+pub fn channel<T>(name: &str, config: ChannelConfig) -> (MassaSender<T>, MassaReceiver<T>) {
+    let metrics = ChannelMetrics::register(name, config);
+    let (normal_tx, normal_rx) = mpsc::channel(config.capacity.max(1));
+    (
+        MassaSender {
+            high: None,
+            normal: normal_tx,
+            classifier: |_| Priority::Normal,
+            overflow_policy: config.overflow_policy,
+            metrics: metrics.clone(),
+        },
+        MassaReceiver {
+            high: None,
+            normal: normal_rx,
+            metrics,
+        },
+    )
+}
+
+/// Creates a channel with a high and a normal priority queue, each of `config.capacity` messages
+///
+/// # Arguments
+/// * `name`: name of the channel in the metrics
+/// * `config`: capacity and overflow policy of the channel
+/// * `classifier`: priority of a message
+pub fn channel_with_priority<T>(
+    name: &str,
+    config: ChannelConfig,
+    classifier: fn(&T) -> Priority,
+) -> (MassaSender<T>, MassaReceiver<T>) {
+    let metrics = ChannelMetrics::register(name, config);
+    let (high_tx, high_rx) = mpsc::channel(config.capacity.max(1));
+    let (normal_tx, normal_rx) = mpsc::channel(config.capacity.max(1));
+    (
+        MassaSender {
+            high: Some(high_tx),
+            normal: normal_tx,
+            classifier,
+            overflow_policy: config.overflow_policy,
+            metrics: metrics.clone(),
+        },
+        MassaReceiver {
+            high: Some(high_rx),
+            normal: normal_rx,
+            metrics,
+        },
+    )
+}
+
+/// Sending half of a channel
+pub struct MassaSender<T> {
+    high: Option<mpsc::Sender<Envelope<T>>>,
+    normal: mpsc::Sender<Envelope<T>>,
+    classifier: fn(&T) -> Priority,
+    overflow_policy: OverflowPolicy,
+    metrics: Arc<ChannelMetrics>,
+}
+
+impl<T> Clone for MassaSender<T> {
+    fn clone(&self) -> Self {
+        MassaSender {
+            high: self.high.clone(),
+            normal: self.normal.clone(),
+            classifier: self.classifier,
+            overflow_policy: self.overflow_policy,
+            metrics: self.metrics.clone(),
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for MassaSender<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MassaSender")
+            .field("metrics", &self.metrics)
+            .finish()
+    }
+}
+
+impl<T> MassaSender<T> {
+    /// queue of a message, depending on its priority
+    fn queue(&self, value: &T) -> &mpsc::Sender<Envelope<T>> {
+        match &self.high {
+            Some(high) if (self.classifier)(value) == Priority::High => high,
+            _ => &self.normal,
+        }
+    }
+
+    /// Sends without waiting, dropping the message if the channel is full
+    fn send_or_drop(&self, value: T) -> Result<(), SendError<T>> {
+        match self.queue(&value).try_send((Instant::now(), value)) {
+            Ok(()) => {
+                self.metrics.on_sent();
+                Ok(())
+            }
+            Err(TrySendError::Full(_)) => {
+                self.metrics.on_dropped();
+                Ok(())
+            }
+            Err(TrySendError::Closed((_, value))) => Err(SendError(value)),
+        }
+    }
+
+    /// Sends a message, applying the overflow policy of the channel if it is full.
+    /// Fails only if the receiver was dropped.
+    pub async fn send(&self, value: T) -> Result<(), SendError<T>> {
+        match self.overflow_policy {
+            OverflowPolicy::Block => {
+                self.queue(&value)
+                    .send((Instant::now(), value))
+                    .await
+                    .map_err(|SendError((_, value))| SendError(value))?;
+                self.metrics.on_sent();
+                Ok(())
+            }
+            OverflowPolicy::DropNewest => self.send_or_drop(value),
+        }
+    }
+
+    /// Sends a message, waiting at most `timeout` for a free slot if the overflow policy is `Block`
+    pub async fn send_timeout(
+        &self,
+        value: T,
+        timeout: Duration,
+    ) -> Result<(), SendTimeoutError<T>> {
+        match self.overflow_policy {
+            OverflowPolicy::Block => {
+                self.queue(&value)
+                    .send_timeout((Instant::now(), value), timeout)
+                    .await
+                    .map_err(|err| match err {
+                        SendTimeoutError::Timeout((_, value)) => {
+                            self.metrics.on_dropped();
+                            SendTimeoutError::Timeout(value)
+                        }
+                        SendTimeoutError::Closed((_, value)) => SendTimeoutError::Closed(value),
+                    })?;
+                self.metrics.on_sent();
+                Ok(())
+            }
+            OverflowPolicy::DropNewest => self
+                .send_or_drop(value)
+                .map_err(|SendError(value)| SendTimeoutError::Closed(value)),
+        }
+    }
+
+    /// Sends a message from a synchronous context, applying the overflow policy of the channel if it is full.
+    /// Panics if called from an asynchronous context with the `Block` policy, like `tokio::sync::mpsc::Sender::blocking_send`.
+    pub fn blocking_send(&self, value: T) -> Result<(), SendError<T>> {
+        match self.overflow_policy {
+            OverflowPolicy::Block => {
+                self.queue(&value)
+                    .blocking_send((Instant::now(), value))
+                    .map_err(|SendError((_, value))| SendError(value))?;
+                self.metrics.on_sent();
+                Ok(())
+            }
+            OverflowPolicy::DropNewest => self.send_or_drop(value),
+        }
+    }
+
+    /// Sends a message without waiting.
+    /// If the channel is full, the message is dropped with the `DropNewest` policy and returned as an error otherwise.
+    pub fn try_send(&self, value: T) -> Result<(), TrySendError<T>> {
+        match self.overflow_policy {
+            OverflowPolicy::Block => {
+                self.queue(&value)
+                    .try_send((Instant::now(), value))
+                    .map_err(|err| match err {
+                        TrySendError::Full((_, value)) => TrySendError::Full(value),
+                        TrySendError::Closed((_, value)) => TrySendError::Closed(value),
+                    })?;
+                self.metrics.on_sent();
+                Ok(())
+            }
+            OverflowPolicy::DropNewest => self
+                .send_or_drop(value)
+                .map_err(|SendError(value)| TrySendError::Closed(value)),
+        }
+    }
+
+    /// Number of free slots in the normal priority queue
+    pub fn capacity(&self) -> usize {
+        self.normal.capacity()
+    }
+
+    /// Whether the receiver was dropped
+    pub fn is_closed(&self) -> bool {
+        self.normal.is_closed()
+    }
+}
+
+/// Receiving half of a channel
+pub struct MassaReceiver<T> {
+    high: Option<mpsc::Receiver<Envelope<T>>>,
+    normal: mpsc::Receiver<Envelope<T>>,
+    metrics: Arc<ChannelMetrics>,
+}
+
+impl<T> std::fmt::Debug for MassaReceiver<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MassaReceiver")
+            .field("metrics", &self.metrics)
+            .finish()
+    }
+}
+
+impl<T> MassaReceiver<T> {
+    fn open(&self, (sent_at, value): Envelope<T>) -> T {
+        self.metrics.on_received(sent_at.elapsed());
+        value
+    }
+
+    /// Receives the next message, the high priority ones first.
+    /// Returns `None` once all the senders were dropped and the channel is empty.
+    /// Cancel safe, it can be used in `tokio::select!`.
+    pub async fn recv(&mut self) -> Option<T> {
+        let envelope = match &mut self.high {
+            Some(high) => tokio::select! {
+                biased;
+                Some(envelope) = high.recv() => Some(envelope),
+                envelope = self.normal.recv() => envelope,
+            },
+            None => self.normal.recv().await,
+        };
+        envelope.map(|envelope| self.open(envelope))
+    }
+
+    /// Receives the next message without waiting, the high priority ones first
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        if let Some(high) = &mut self.high {
+            if let Ok(envelope) = high.try_recv() {
+                return Ok(self.open(envelope));
+            }
+        }
+        let envelope = self.normal.try_recv()?;
+        Ok(self.open(envelope))
+    }
+
+    /// Closes the channel: the senders fail while the waiting messages can still be received
+    pub fn close(&mut self) {
+        if let Some(high) = &mut self.high {
+            high.close();
+        }
+        self.normal.close();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::channel_stats;
+
+    fn priority(value: &u32) -> Priority {
+        if *value >= 100 {
+            Priority::High
+        } else {
+            Priority::Normal
+        }
+    }
+
+    #[tokio::test]
+    async fn test_priority() {
+        let (tx, mut rx) = channel_with_priority("test_priority", ChannelConfig::new(10), priority);
+        for value in [1, 100, 2, 101] {
+            tx.send(value).await.unwrap();
+        }
+        let mut received = Vec::new();
+        while let Ok(value) = rx.try_recv() {
+            received.push(value);
+        }
+        assert_eq!(received, vec![100, 101, 1, 2]);
+
+        tx.send(3).await.unwrap();
+        tx.send(102).await.unwrap();
+        assert_eq!(rx.recv().await, Some(102));
+        assert_eq!(rx.recv().await, Some(3));
+        drop(tx);
+        assert_eq!(rx.recv().await, None);
+    }
+
+    #[tokio::test]
+    async fn test_drop_newest() {
+        let config = ChannelConfig {
+            capacity: 2,
+            overflow_policy: OverflowPolicy::DropNewest,
+        };
+        let (tx, mut rx) = channel("test_drop_newest", config);
+        for value in 0..5u32 {
+            tx.send(value).await.unwrap();
+        }
+        assert_eq!(rx.recv().await, Some(0));
+        assert_eq!(rx.recv().await, Some(1));
+        assert!(matches!(rx.try_recv(), Err(TryRecvError::Empty)));
+
+        let stats = channel_stats()
+            .into_iter()
+            .find(|stats| stats.name == "test_drop_newest")
+            .unwrap();
+        assert_eq!(stats.sent, 2);
+        assert_eq!(stats.received, 2);
+        assert_eq!(stats.dropped, 3);
+        assert_eq!(stats.queued, 0);
+
+        // the metrics of closed channels are forgotten
+        drop(tx);
+        drop(rx);
+        assert!(channel_stats()
+            .iter()
+            .all(|stats| stats.name != "test_drop_newest"));
+    }
+
+    #[tokio::test]
+    async fn test_block() {
+        let (tx, mut rx) = channel::<u32>("test_block", ChannelConfig::new(1));
+        tx.send(0).await.unwrap();
+        assert!(matches!(tx.try_send(1), Err(TrySendError::Full(1))));
+        assert!(matches!(
+            tx.send_timeout(1, Duration::from_millis(10)).await,
+            Err(SendTimeoutError::Timeout(1))
+        ));
+        assert_eq!(rx.recv().await, Some(0));
+        rx.close();
+        assert!(tx.send(2).await.is_err());
+    }
+}
@@ -0,0 +1,41 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use serde::{Deserialize, Serialize};
+
+/// What a channel does with a message sent while it is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverflowPolicy {
+    /// wait for a free slot
+    Block,
+    /// drop the message
+    DropNewest,
+}
+
+/// Configuration of a channel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChannelConfig {
+    /// maximum number of messages waiting in each queue of the channel
+    pub capacity: usize,
+    /// what to do with a message sent while the channel is full
+    pub overflow_policy: OverflowPolicy,
+}
+
+impl ChannelConfig {
+    /// Configuration of a channel of `capacity` messages whose senders wait when it is full
+    pub fn new(capacity: usize) -> Self {
+        ChannelConfig {
+            capacity,
+            overflow_policy: OverflowPolicy::Block,
+        }
+    }
+}
+
+/// Priority of a message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Priority {
+    /// received before the normal messages
+    High,
+    /// received when no high priority message is waiting
+    Normal,
+}
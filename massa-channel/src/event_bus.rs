@@ -0,0 +1,321 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Typed bus of the events of the node.
+//!
+//! The workers publish on a shared `EventBus` what happened in the node (a block became final, an operation entered
+//! the pool, a peer was banned, the bootstrap finished), and the subsystems reporting it outside the node (websockets,
+//! webhooks, metrics) subscribe to the topics they need instead of each being wired to every worker.
+//!
+//! The bus is a broadcast channel: a subscriber lagging behind by more than the capacity of the bus misses the oldest events.
+//! The events of the sticky topics happen once in the life of the node, the last one is replayed to the new subscriptions.
+
+use massa_models::block_id::BlockId;
+use massa_models::node::NodeId;
+use massa_models::operation::{Operation, OperationId};
+use massa_models::slot::Slot;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::net::IpAddr;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+pub use tokio::sync::broadcast::error::RecvError;
+
+/// Topic of the events published on the bus
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Topic {
+    /// a block became final
+    BlockFinalized,
+    /// an operation was added to the pool
+    OperationPooled,
+    /// a peer was banned
+    PeerBanned,
+    /// the node finished its bootstrap
+    BootstrapFinished,
+}
+
+impl Topic {
+    /// All the topics
+    pub const ALL: [Topic; 4] = [
+        Topic::BlockFinalized,
+        Topic::OperationPooled,
+        Topic::PeerBanned,
+        Topic::BootstrapFinished,
+    ];
+
+    fn index(&self) -> usize {
+        *self as usize
+    }
+
+    /// Whether the last event of the topic is replayed to the new subscriptions
+    pub fn is_sticky(&self) -> bool {
+        matches!(self, Topic::BootstrapFinished)
+    }
+}
+
+impl std::fmt::Display for Topic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Topic::BlockFinalized => write!(f, "block_finalized"),
+            Topic::OperationPooled => write!(f, "operation_pooled"),
+            Topic::PeerBanned => write!(f, "peer_banned"),
+            Topic::BootstrapFinished => write!(f, "bootstrap_finished"),
+        }
+    }
+}
+
+impl FromStr for Topic {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Topic::ALL
+            .into_iter()
+            .find(|topic| topic.to_string() == s)
+            .ok_or_else(|| format!("unknown event topic {}", s))
+    }
+}
+
+/// Event published on the bus
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "topic", rename_all = "snake_case")]
+pub enum NodeEvent {
+    /// a block became final
+    BlockFinalized {
+        /// id of the block
+        block_id: BlockId,
+        /// slot of the block
+        slot: Slot,
+    },
+    /// an operation was added to the pool
+    OperationPooled {
+        /// id of the operation
+        operation_id: OperationId,
+        /// content of the operation
+        operation: Operation,
+    },
+    /// a peer was banned
+    PeerBanned {
+        /// id of the banned node, if banned by id
+        node_id: Option<NodeId>,
+        /// IP of the banned peer, if known
+        ip: Option<IpAddr>,
+        /// reason of the ban
+        reason: String,
+    },
+    /// the node finished its bootstrap
+    BootstrapFinished {
+        /// final slot of the state the node starts from
+        slot: Slot,
+        /// whether the state was received from a bootstrap server
+        from_server: bool,
+    },
+}
+
+impl NodeEvent {
+    /// Topic of the event
+    pub fn topic(&self) -> Topic {
+        match self {
+            NodeEvent::BlockFinalized { .. } => Topic::BlockFinalized,
+            NodeEvent::OperationPooled { .. } => Topic::OperationPooled,
+            NodeEvent::PeerBanned { .. } => Topic::PeerBanned,
+            NodeEvent::BootstrapFinished { .. } => Topic::BootstrapFinished,
+        }
+    }
+}
+
+/// State shared by the handles of a bus
+#[derive(Debug)]
+struct BusShared {
+    /// number of open subscriptions to each topic
+    subscribers: [AtomicUsize; Topic::ALL.len()],
+    /// last event of each sticky topic
+    sticky: Mutex<[Option<NodeEvent>; Topic::ALL.len()]>,
+}
+
+/// Handle to publish on and subscribe to the event bus of the node
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<NodeEvent>,
+    shared: Arc<BusShared>,
+}
+
+impl EventBus {
+    /// Creates a bus keeping at most `capacity` events for its slowest subscriber
+    pub fn new(capacity: usize) -> Self {
+        EventBus {
+            sender: broadcast::channel(capacity.max(1)).0,
+            shared: Arc::new(BusShared {
+                subscribers: Default::default(),
+                sticky: Default::default(),
+            }),
+        }
+    }
+
+    /// Publishes `event` to the subscribers of its topic
+    pub fn publish(&self, event: NodeEvent) {
+        let topic = event.topic();
+        if topic.is_sticky() {
+            self.shared.sticky.lock()[topic.index()] = Some(event.clone());
+        }
+        if self.has_subscribers(topic) {
+            // the send only fails when there is no subscription
+            let _ = self.sender.send(event);
+        }
+    }
+
+    /// Publishes the event built by `event` if `topic` has subscribers, to avoid building the events nobody reads
+    pub fn publish_with(&self, topic: Topic, event: impl FnOnce() -> NodeEvent) {
+        if topic.is_sticky() || self.has_subscribers(topic) {
+            self.publish(event());
+        }
+    }
+
+    /// Whether a subscription to `topic` is open
+    pub fn has_subscribers(&self, topic: Topic) -> bool {
+        self.shared.subscribers[topic.index()].load(Ordering::Relaxed) > 0
+    }
+
+    /// Subscribes to the events of `topics`, starting with the last event of the sticky ones
+    pub fn subscribe(&self, topics: &[Topic]) -> EventSubscription {
+        let mut topics = topics.to_vec();
+        topics.sort_unstable_by_key(Topic::index);
+        topics.dedup();
+        let receiver = self.sender.subscribe();
+        let sticky = self.shared.sticky.lock();
+        let replay = topics
+            .iter()
+            .filter_map(|topic| sticky[topic.index()].clone())
+            .collect();
+        for topic in &topics {
+            self.shared.subscribers[topic.index()].fetch_add(1, Ordering::Relaxed);
+        }
+        EventSubscription {
+            receiver,
+            topics,
+            replay,
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+/// Subscription to some topics of the event bus, closed when dropped
+#[derive(Debug)]
+pub struct EventSubscription {
+    receiver: broadcast::Receiver<NodeEvent>,
+    topics: Vec<Topic>,
+    /// events of the sticky topics published before the subscription
+    replay: VecDeque<NodeEvent>,
+    shared: Arc<BusShared>,
+}
+
+impl EventSubscription {
+    /// Topics of the subscription
+    pub fn topics(&self) -> &[Topic] {
+        &self.topics
+    }
+
+    /// Receives the next event of the subscribed topics.
+    /// Fails with `RecvError::Lagged` and the number of missed events (of any topic) if the subscription lagged behind,
+    /// the next call then receiving the oldest event still on the bus, and with `RecvError::Closed` once the bus is dropped.
+    /// Cancel safe, it can be used in `tokio::select!`.
+    pub async fn recv(&mut self) -> Result<NodeEvent, RecvError> {
+        if let Some(event) = self.replay.pop_front() {
+            return Ok(event);
+        }
+        loop {
+            let event = self.receiver.recv().await?;
+            if self.topics.contains(&event.topic()) {
+                return Ok(event);
+            }
+        }
+    }
+}
+
+impl Drop for EventSubscription {
+    fn drop(&mut self) {
+        for topic in &self.topics {
+            self.shared.subscribers[topic.index()].fetch_sub(1, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_hash::Hash;
+
+    fn finalized(period: u64) -> NodeEvent {
+        NodeEvent::BlockFinalized {
+            block_id: BlockId(Hash::compute_from(&period.to_be_bytes())),
+            slot: Slot::new(period, 0),
+        }
+    }
+
+    fn banned(reason: &str) -> NodeEvent {
+        NodeEvent::PeerBanned {
+            node_id: None,
+            ip: None,
+            reason: reason.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_topics() {
+        let bus = EventBus::new(10);
+        // nobody subscribed yet: not received by the later subscriptions
+        bus.publish(finalized(1));
+        let mut blocks = bus.subscribe(&[Topic::BlockFinalized]);
+        let mut all = bus.subscribe(&Topic::ALL);
+        assert!(bus.has_subscribers(Topic::PeerBanned));
+        bus.publish(banned("test"));
+        bus.publish(finalized(2));
+
+        assert!(matches!(
+            blocks.recv().await,
+            Ok(NodeEvent::BlockFinalized { slot, .. }) if slot.period == 2
+        ));
+        assert_eq!(all.recv().await.unwrap().topic(), Topic::PeerBanned);
+        assert_eq!(all.recv().await.unwrap().topic(), Topic::BlockFinalized);
+
+        drop(all);
+        assert!(!bus.has_subscribers(Topic::PeerBanned));
+        let mut built = false;
+        bus.publish_with(Topic::PeerBanned, || {
+            built = true;
+            banned("nobody listens")
+        });
+        assert!(!built);
+    }
+
+    #[tokio::test]
+    async fn test_sticky_and_lag() {
+        let bus = EventBus::new(2);
+        bus.publish(NodeEvent::BootstrapFinished {
+            slot: Slot::new(3, 1),
+            from_server: true,
+        });
+        let mut subscription = bus.subscribe(&[Topic::BlockFinalized, Topic::BootstrapFinished]);
+        for period in 0..4 {
+            bus.publish(finalized(period));
+        }
+        // the sticky event published before the subscription comes first
+        assert_eq!(
+            subscription.recv().await.unwrap().topic(),
+            Topic::BootstrapFinished
+        );
+        assert!(matches!(
+            subscription.recv().await,
+            Err(RecvError::Lagged(2))
+        ));
+        assert!(matches!(
+            subscription.recv().await,
+            Ok(NodeEvent::BlockFinalized { slot, .. }) if slot.period == 2
+        ));
+        assert_eq!("peer_banned".parse::<Topic>(), Ok(Topic::PeerBanned));
+        assert!("unknown".parse::<Topic>().is_err());
+    }
+}
@@ -0,0 +1,30 @@
+//! Copyright (c) 2022 MASSA LABS <info@massa.net>
+//!
+//! # General description
+//!
+//! Channels shared by the workers to send commands and events to each other.
+//!
+//! A channel is created with `channel` or `channel_with_priority` from a `ChannelConfig`, which sets
+//! its capacity and what happens when it is full (`OverflowPolicy`):
+//! * `Block`: the sender waits for a free slot, slowing down the producer (back-pressure)
+//! * `DropNewest`: the message is dropped and counted, the producer never waits
+//!
+//! A channel created with a priority classifier has two queues: the messages classified as `Priority::High`
+//! are always received before the `Priority::Normal` ones, so that e.g. blocks are not delayed by a flood of operations.
+//!
+//! Every channel records metrics (sent, received and dropped messages, lag between the sending and
+//! the reception of the messages) which are listed by `channel_stats` and exposed by the private API.
+//!
+//! The `EventBus` broadcasts the typed events of the node to any number of subscribers, see the `event_bus` module.
+#![warn(missing_docs)]
+#![warn(unused_crate_dependencies)]
+
+mod channel;
+mod config;
+pub mod event_bus;
+mod metrics;
+
+pub use channel::*;
+pub use config::*;
+pub use event_bus::{EventBus, EventSubscription, NodeEvent, Topic};
+pub use metrics::{channel_stats, ChannelStats};
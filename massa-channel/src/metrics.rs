@@ -0,0 +1,129 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use crate::config::{ChannelConfig, OverflowPolicy};
+use lazy_static::lazy_static;
+use massa_time::MassaTime;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+lazy_static! {
+    /// metrics of the channels of the node
+    static ref CHANNELS: Mutex<Vec<Arc<ChannelMetrics>>> = Mutex::new(Vec::new());
+}
+
+/// Metrics of a channel, shared by its senders and its receiver
+#[derive(Debug)]
+pub(crate) struct ChannelMetrics {
+    name: String,
+    config: ChannelConfig,
+    sent: AtomicU64,
+    received: AtomicU64,
+    dropped: AtomicU64,
+    /// lag of the last received message, in microseconds
+    last_lag: AtomicU64,
+    /// maximum lag of the received messages, in microseconds
+    max_lag: AtomicU64,
+}
+
+impl ChannelMetrics {
+    /// Creates the metrics of a new channel and registers them
+    pub(crate) fn register(name: &str, config: ChannelConfig) -> Arc<ChannelMetrics> {
+        let metrics = Arc::new(ChannelMetrics {
+            name: name.to_string(),
+            config,
+            sent: AtomicU64::new(0),
+            received: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+            last_lag: AtomicU64::new(0),
+            max_lag: AtomicU64::new(0),
+        });
+        CHANNELS.lock().push(metrics.clone());
+        metrics
+    }
+
+    pub(crate) fn on_sent(&self) {
+        self.sent.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn on_dropped(&self) {
+        self.dropped.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn on_received(&self, lag: Duration) {
+        let lag = u64::try_from(lag.as_micros()).unwrap_or(u64::MAX);
+        self.received.fetch_add(1, Ordering::Relaxed);
+        self.last_lag.store(lag, Ordering::Relaxed);
+        self.max_lag.fetch_max(lag, Ordering::Relaxed);
+    }
+
+    fn stats(&self) -> ChannelStats {
+        let sent = self.sent.load(Ordering::Relaxed);
+        let received = self.received.load(Ordering::Relaxed);
+        ChannelStats {
+            name: self.name.clone(),
+            capacity: self.config.capacity,
+            overflow_policy: self.config.overflow_policy,
+            queued: sent.saturating_sub(received),
+            sent,
+            received,
+            dropped: self.dropped.load(Ordering::Relaxed),
+            last_lag: MassaTime::from_millis(self.last_lag.load(Ordering::Relaxed) / 1000),
+            max_lag: MassaTime::from_millis(self.max_lag.load(Ordering::Relaxed) / 1000),
+        }
+    }
+}
+
+/// Metrics of a channel, reported by the API
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChannelStats {
+    /// name of the channel
+    pub name: String,
+    /// maximum number of messages waiting in each queue of the channel
+    pub capacity: usize,
+    /// what the channel does with the messages sent while it is full
+    pub overflow_policy: OverflowPolicy,
+    /// number of messages waiting to be received
+    pub queued: u64,
+    /// number of sent messages
+    pub sent: u64,
+    /// number of received messages
+    pub received: u64,
+    /// number of messages dropped because the channel was full
+    pub dropped: u64,
+    /// time the last received message waited in the channel
+    pub last_lag: MassaTime,
+    /// maximum time a received message waited in the channel
+    pub max_lag: MassaTime,
+}
+
+impl std::fmt::Display for ChannelStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Channel {} (capacity {}, {:?} when full)",
+            self.name, self.capacity, self.overflow_policy
+        )?;
+        writeln!(
+            f,
+            "\tMessages: {} sent, {} received, {} queued, {} dropped",
+            self.sent, self.received, self.queued, self.dropped
+        )?;
+        writeln!(
+            f,
+            "\tLag: {} ms for the last message, {} ms at most",
+            self.last_lag.to_millis(),
+            self.max_lag.to_millis()
+        )
+    }
+}
+
+/// Metrics of the open channels of the node.
+/// The channels whose senders and receiver were all dropped are forgotten.
+pub fn channel_stats() -> Vec<ChannelStats> {
+    let mut channels = CHANNELS.lock();
+    channels.retain(|metrics| Arc::strong_count(metrics) > 1);
+    channels.iter().map(|metrics| metrics.stats()).collect()
+}
@@ -0,0 +1,181 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Local address book of the client.
+//!
+//! Commands accept a name from the address book anywhere an address is expected.
+//! Names missing from the address book are resolved through the on-chain naming contract,
+//! if one is configured, and the outputs display the names of the known addresses.
+
+use crate::settings::SETTINGS;
+use anyhow::{anyhow, bail, Result};
+use massa_api_exports::execution::{ReadOnlyCall, ReadOnlyResult};
+use massa_models::address::Address;
+use massa_sdk::Client;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+lazy_static::lazy_static! {
+    pub(crate) static ref ADDRESS_BOOK: RwLock<AddressBook> = RwLock::new(
+        AddressBook::load(&SETTINGS.address_book.path).expect("could not load the address book")
+    );
+}
+
+/// Names given to addresses, persisted in a JSON file
+#[derive(Debug, Default)]
+pub(crate) struct AddressBook {
+    path: PathBuf,
+    entries: BTreeMap<String, Address>,
+}
+
+impl AddressBook {
+    /// Loads the address book stored at `path`, which is empty if the file does not exist yet
+    pub(crate) fn load(path: &Path) -> Result<Self> {
+        let entries = if path.is_file() {
+            let content = std::fs::read_to_string(path)?;
+            serde_json::from_str(&content)
+                .map_err(|e| anyhow!("invalid address book {}: {}", path.display(), e))?
+        } else {
+            BTreeMap::new()
+        };
+        Ok(AddressBook {
+            path: path.to_path_buf(),
+            entries,
+        })
+    }
+
+    fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, serde_json::to_string_pretty(&self.entries)?)?;
+        Ok(())
+    }
+
+    /// Names `address`, replacing the previous address of `name` if any
+    pub(crate) fn add(&mut self, name: &str, address: Address) -> Result<()> {
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            bail!("a name must be non-empty and must not contain spaces");
+        }
+        if name.parse::<Address>().is_ok() {
+            bail!("a name must not be an address");
+        }
+        self.entries.insert(name.to_string(), address);
+        self.save()
+    }
+
+    /// Forgets the given names, returns the addresses they were given to
+    pub(crate) fn remove(&mut self, names: &[String]) -> Result<Vec<Address>> {
+        let removed = names
+            .iter()
+            .filter_map(|name| self.entries.remove(name))
+            .collect();
+        self.save()?;
+        Ok(removed)
+    }
+
+    /// Names and their addresses
+    pub(crate) fn entries(&self) -> &BTreeMap<String, Address> {
+        &self.entries
+    }
+
+    /// Appends its name to every known address in `text`
+    pub(crate) fn label(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        for (name, address) in &self.entries {
+            let address = address.to_string();
+            text = text.replace(&address, &format!("{} ({})", address, name));
+        }
+        text
+    }
+}
+
+/// Displays `value` with the names of the known addresses
+pub(crate) fn with_labels(value: &dyn Display) -> String {
+    let text = value.to_string();
+    match ADDRESS_BOOK.read() {
+        Ok(address_book) => address_book.label(&text),
+        Err(_) => text,
+    }
+}
+
+/// Parses an address, or resolves a name through the address book,
+/// then through the naming contract if one is configured
+pub(crate) async fn resolve_address(client: &Client, input: &str) -> Result<Address> {
+    if let Ok(address) = input.parse::<Address>() {
+        return Ok(address);
+    }
+    let known = ADDRESS_BOOK
+        .read()
+        .map_err(|_| anyhow!("address book unavailable"))?
+        .entries
+        .get(input)
+        .copied();
+    if let Some(address) = known {
+        return Ok(address);
+    }
+    let Some(naming_contract) = SETTINGS.address_book.naming_contract else {
+        bail!("\"{}\" is neither an address nor a known name", input);
+    };
+    let response = client
+        .public
+        .execute_read_only_call(ReadOnlyCall {
+            max_gas: SETTINGS.address_book.naming_max_gas,
+            target_address: naming_contract,
+            target_function: "resolve".to_string(),
+            parameter: input.as_bytes().to_vec(),
+            caller_address: None,
+            is_final: true,
+            limits: Default::default(),
+            fork: None,
+        })
+        .await
+        .map_err(|e| anyhow!("could not resolve \"{}\": {}", input, e))?;
+    match response.result {
+        ReadOnlyResult::Ok(output) => String::from_utf8(output)
+            .ok()
+            .and_then(|address| address.parse::<Address>().ok())
+            .ok_or_else(|| anyhow!("the naming contract does not know \"{}\"", input)),
+        ReadOnlyResult::Error(e) => bail!("could not resolve \"{}\": {}", input, e),
+    }
+}
+
+/// Resolves a list of addresses or names, see `resolve_address`
+pub(crate) async fn resolve_addresses(client: &Client, inputs: &[String]) -> Result<Vec<Address>> {
+    let mut addresses = Vec::with_capacity(inputs.len());
+    for input in inputs {
+        addresses.push(resolve_address(client, input).await?);
+    }
+    Ok(addresses)
+}
+
+#[cfg(test)]
+#[test]
+fn test_address_book() {
+    use massa_signature::KeyPair;
+
+    let path = std::env::temp_dir().join(format!("massa_address_book_{}.json", std::process::id()));
+    let address = Address::from_public_key(&KeyPair::generate().get_public_key());
+    let mut address_book = AddressBook::load(&path).unwrap();
+    assert!(address_book.entries().is_empty());
+    assert!(address_book.add("my address", address).is_err());
+    assert!(address_book.add(&address.to_string(), address).is_err());
+    address_book.add("alice", address).unwrap();
+
+    // persisted
+    let mut address_book = AddressBook::load(&path).unwrap();
+    assert_eq!(address_book.entries().get("alice"), Some(&address));
+    assert_eq!(
+        address_book.label(&format!("Address {}:", address)),
+        format!("Address {} (alice):", address)
+    );
+    assert_eq!(
+        address_book
+            .remove(&["alice".to_string(), "bob".to_string()])
+            .unwrap(),
+        vec![address]
+    );
+    assert!(AddressBook::load(&path).unwrap().entries().is_empty());
+    std::fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,219 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+//! Massa test fixture generator
+//!
+//! Captures from a live node the blocks around a slot, their operations and the ledger entries
+//! of the addresses they involve, and writes them as a Rust module that consensus and execution
+//! tests can include to reproduce a bug report.
+//!
+//! Only built with the `fixture-tool` feature:
+//! `cargo run -p massa-client --features fixture-tool --bin massa-fixture -- --period 1000 --thread 3`
+#![warn(missing_docs)]
+use anyhow::{anyhow, bail, Result};
+use massa_api_exports::{datastore::DatastoreEntryInput, operation::OperationInfo, TimeInterval};
+use massa_models::{
+    address::Address, operation::OperationId, prehash::PreHashSet, slot::Slot,
+    timeslots::get_block_slot_timestamp,
+};
+use massa_sdk::{Client, ClientConfig, HttpConfig};
+use massa_time::MassaTime;
+use render::{Fixture, FixtureBlock, FixtureLedgerEntry};
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+mod render;
+
+#[derive(StructOpt)]
+struct Args {
+    /// Address of the node
+    #[structopt(long, default_value = "127.0.0.1")]
+    ip: IpAddr,
+    /// Port of the public API of the node
+    #[structopt(long, default_value = "33035")]
+    public_port: u16,
+    /// Port of the private API of the node
+    #[structopt(long, default_value = "33034")]
+    private_port: u16,
+    /// Period of the slot around which the fixture is captured
+    #[structopt(long)]
+    period: u64,
+    /// Thread of the slot around which the fixture is captured
+    #[structopt(long)]
+    thread: u8,
+    /// Number of periods captured before and after the slot
+    #[structopt(long, default_value = "2")]
+    radius: u64,
+    /// Maximum number of datastore entries captured for each address
+    #[structopt(long, default_value = "100")]
+    max_datastore_entries: usize,
+    /// Path of the generated Rust module
+    #[structopt(short = "o", long, parse(from_os_str), default_value = "fixture.rs")]
+    output: PathBuf,
+}
+
+fn http_config() -> HttpConfig {
+    HttpConfig {
+        client_config: ClientConfig {
+            max_request_body_size: 52428800,
+            request_timeout: MassaTime::from_millis(60000),
+            max_concurrent_requests: 100,
+            certificate_store: "Native".to_string(),
+            id_kind: "Number".to_string(),
+            max_log_length: 4096,
+            headers: Vec::new(),
+        },
+        enabled: true,
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::from_args();
+    let client = Client::new(args.ip, args.public_port, args.private_port, &http_config()).await;
+    let fixture = capture(&client, &args).await?;
+    tokio::fs::write(&args.output, render::render(&fixture)).await?;
+    println!(
+        "Captured {} blocks, {} operations and {} ledger entries around slot {} into {}",
+        fixture.blocks.len(),
+        fixture.operations.len(),
+        fixture.ledger.len(),
+        fixture.slot,
+        args.output.display()
+    );
+    Ok(())
+}
+
+/// Fetches the blocks of the periods around the requested slot, their operations and the final
+/// state of the addresses they involve
+async fn capture(client: &Client, args: &Args) -> Result<Fixture> {
+    let status = client
+        .public
+        .get_status()
+        .await
+        .map_err(|e| anyhow!("check if your node is running: {}", e))?;
+    let cfg = status.config;
+    if args.thread >= cfg.thread_count {
+        bail!(
+            "thread {} does not exist, the network has {} threads",
+            args.thread,
+            cfg.thread_count
+        );
+    }
+    let slot = Slot::new(args.period, args.thread);
+    let timestamp = |period| {
+        get_block_slot_timestamp(
+            cfg.thread_count,
+            cfg.t0,
+            cfg.genesis_timestamp,
+            Slot::new(period, 0),
+        )
+    };
+    let time_interval = TimeInterval {
+        start: Some(timestamp(args.period.saturating_sub(args.radius))?),
+        end: Some(timestamp(args.period.saturating_add(args.radius + 1))?),
+    };
+
+    let summaries = client
+        .public
+        .get_graph_interval(time_interval)
+        .await
+        .map_err(|e| anyhow!("could not get the block graph: {}", e))?;
+    if summaries.is_empty() {
+        bail!("the node does not know any block around slot {}", slot);
+    }
+    let block_infos = client
+        .public
+        .get_blocks(summaries.iter().map(|summary| summary.id).collect(), None)
+        .await
+        .map_err(|e| anyhow!("could not get the blocks: {}", e))?;
+    let mut blocks: Vec<FixtureBlock> = block_infos
+        .into_iter()
+        .filter_map(|info| info.content)
+        .filter_map(|content| {
+            Some(FixtureBlock {
+                block: content.block?,
+                is_final: content.is_final,
+            })
+        })
+        .collect();
+    blocks.sort_by_key(|fixture_block| fixture_block.block.header.content.slot);
+
+    let operation_ids: Vec<OperationId> = blocks
+        .iter()
+        .flat_map(|fixture_block| fixture_block.block.operations.iter().copied())
+        .collect::<PreHashSet<OperationId>>()
+        .into_iter()
+        .collect();
+    let operations = if operation_ids.is_empty() {
+        Vec::new()
+    } else {
+        client
+            .public
+            .get_operations(operation_ids)
+            .await
+            .map_err(|e| anyhow!("could not get the operations: {}", e))?
+            .into_iter()
+            .map(|info: OperationInfo| info.operation)
+            .collect()
+    };
+
+    let mut addresses: PreHashSet<Address> = blocks
+        .iter()
+        .map(|fixture_block| fixture_block.block.header.content_creator_address)
+        .collect();
+    for operation in &operations {
+        addresses.extend(operation.get_ledger_involved_addresses());
+    }
+    let address_infos = client
+        .public
+        .get_addresses(addresses.into_iter().collect())
+        .await
+        .map_err(|e| anyhow!("could not get the addresses: {}", e))?;
+    let mut ledger = BTreeMap::new();
+    for info in address_infos {
+        let keys: Vec<Vec<u8>> = info
+            .final_datastore_keys
+            .into_iter()
+            .take(args.max_datastore_entries)
+            .collect();
+        let values = if keys.is_empty() {
+            Vec::new()
+        } else {
+            client
+                .public
+                .get_datastore_entries(
+                    keys.iter()
+                        .map(|key| DatastoreEntryInput {
+                            address: info.address,
+                            key: key.clone(),
+                        })
+                        .collect(),
+                )
+                .await
+                .map_err(|e| anyhow!("could not get the datastore of {}: {}", info.address, e))?
+        };
+        let datastore = keys
+            .into_iter()
+            .zip(values)
+            .filter_map(|(key, entry)| entry.final_value.map(|value| (key, value)))
+            .collect();
+        ledger.insert(
+            info.address,
+            FixtureLedgerEntry {
+                balance: info.final_balance,
+                roll_count: info.final_roll_count,
+                datastore,
+            },
+        );
+    }
+
+    Ok(Fixture {
+        source: format!("{}:{}", args.ip, args.public_port),
+        node_version: status.version.to_string(),
+        slot,
+        blocks,
+        operations,
+        ledger,
+    })
+}
@@ -0,0 +1,227 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Rendering of a captured fixture as a Rust module.
+//!
+//! Blocks and operations are embedded as JSON, which keeps their signatures and lets the tests
+//! deserialize them with `serde_json`. The generated module depends on `massa_models`,
+//! `massa_ledger_exports` and `serde_json`.
+
+use massa_models::{
+    address::Address, amount::Amount, block::Block, datastore::Datastore,
+    operation::SecureShareOperation, slot::Slot,
+};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// Block captured with its finality
+pub struct FixtureBlock {
+    /// the block
+    pub block: Block,
+    /// true if the block was final at capture time
+    pub is_final: bool,
+}
+
+/// Final state of an address at capture time
+pub struct FixtureLedgerEntry {
+    /// final balance
+    pub balance: Amount,
+    /// final roll count
+    pub roll_count: u64,
+    /// captured final datastore entries
+    pub datastore: Datastore,
+}
+
+/// Data captured from a node around a slot
+pub struct Fixture {
+    /// public API the fixture was captured from
+    pub source: String,
+    /// version of the node
+    pub node_version: String,
+    /// slot around which the fixture was captured
+    pub slot: Slot,
+    /// blocks around the slot, sorted by slot
+    pub blocks: Vec<FixtureBlock>,
+    /// operations of the blocks
+    pub operations: Vec<SecureShareOperation>,
+    /// final state of the addresses involved in the blocks
+    pub ledger: BTreeMap<Address, FixtureLedgerEntry>,
+}
+
+/// Raw string literal containing `value`, with enough `#` to not be closed by it
+fn raw_string(value: &str) -> String {
+    let mut hashes = "#".to_string();
+    while value.contains(&format!("\"{}", hashes)) {
+        hashes.push('#');
+    }
+    format!("r{}\"{}\"{}", hashes, value, hashes)
+}
+
+fn bytes(value: &[u8]) -> String {
+    format!("vec!{:?}", value)
+}
+
+/// Renders the fixture as a Rust module
+pub fn render(fixture: &Fixture) -> String {
+    let mut out = String::new();
+    // writing into a `String` cannot fail
+    let _ = write!(
+        out,
+        "\
+// Generated by massa-fixture from {source} (node version {version}), do not edit.
+
+//! Fixture captured around slot {slot}.
+//!
+//! The ledger entries are the final state of the involved addresses at capture time, not at the
+//! slot of the fixture. Bytecodes are not exposed by the API and are left empty.
+#![allow(dead_code)]
+
+use massa_ledger_exports::LedgerEntry;
+use massa_models::{{
+    address::Address, amount::Amount, block::Block, operation::SecureShareOperation, slot::Slot,
+}};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// Slot around which the fixture was captured
+pub fn slot() -> Slot {{
+    Slot::new({period}, {thread})
+}}
+",
+        source = fixture.source,
+        version = fixture.node_version,
+        slot = fixture.slot,
+        period = fixture.slot.period,
+        thread = fixture.slot.thread,
+    );
+
+    out.push_str(
+        "
+/// Blocks around the slot sorted by slot, with their finality at capture time
+pub fn blocks() -> Vec<(Block, bool)> {
+    [
+",
+    );
+    for fixture_block in &fixture.blocks {
+        let json = serde_json::to_string(&fixture_block.block).expect("block serialization");
+        let _ = writeln!(
+            out,
+            "        ({}, {}),",
+            raw_string(&json),
+            fixture_block.is_final
+        );
+    }
+    out.push_str(
+        "    ]
+    .iter()
+    .map(|(json, is_final)| (serde_json::from_str(json).expect(\"invalid fixture block\"), *is_final))
+    .collect()
+}
+
+/// Operations of the blocks
+pub fn operations() -> Vec<SecureShareOperation> {
+    [
+",
+    );
+    for operation in &fixture.operations {
+        let json = serde_json::to_string(operation).expect("operation serialization");
+        let _ = writeln!(out, "        {},", raw_string(&json));
+    }
+    out.push_str(
+        "    ]
+    .iter()
+    .map(|json| serde_json::from_str(json).expect(\"invalid fixture operation\"))
+    .collect()
+}
+
+/// Final ledger entries of the addresses involved in the blocks
+pub fn ledger() -> BTreeMap<Address, LedgerEntry> {
+    let mut ledger = BTreeMap::new();
+",
+    );
+    for (address, entry) in &fixture.ledger {
+        let _ = writeln!(
+            out,
+            "    ledger.insert(
+        Address::from_str(\"{}\").unwrap(),
+        LedgerEntry {{
+            balance: Amount::from_str(\"{}\").unwrap(),
+            datastore: BTreeMap::from([",
+            address, entry.balance
+        );
+        for (key, value) in &entry.datastore {
+            let _ = writeln!(out, "                ({}, {}),", bytes(key), bytes(value));
+        }
+        out.push_str(
+            "            ]),
+            ..Default::default()
+        },
+    );
+",
+        );
+    }
+    out.push_str(
+        "    ledger
+}
+
+/// Final roll counts of the addresses involved in the blocks
+pub fn rolls() -> BTreeMap<Address, u64> {
+    let mut rolls = BTreeMap::new();
+",
+    );
+    for (address, entry) in &fixture.ledger {
+        if entry.roll_count > 0 {
+            let _ = writeln!(
+                out,
+                "    rolls.insert(Address::from_str(\"{}\").unwrap(), {});",
+                address, entry.roll_count
+            );
+        }
+    }
+    out.push_str(
+        "    rolls
+}
+",
+    );
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_raw_string() {
+        assert_eq!(raw_string("{\"a\":1}"), "r#\"{\"a\":1}\"#");
+        assert_eq!(raw_string("\"#"), "r##\"\"#\"##");
+    }
+
+    #[test]
+    fn test_render_ledger() {
+        let address =
+            Address::from_str("AU12dG5xP1RDEB5ocdHkymNVvvSJmUL9BgHwCksDowqmGWxfpm93x").unwrap();
+        let fixture = Fixture {
+            source: "127.0.0.1:33035".to_string(),
+            node_version: "TEST.20.0".to_string(),
+            slot: Slot::new(10, 3),
+            blocks: Vec::new(),
+            operations: Vec::new(),
+            ledger: BTreeMap::from([(
+                address,
+                FixtureLedgerEntry {
+                    balance: Amount::from_str("12.5").unwrap(),
+                    roll_count: 2,
+                    datastore: BTreeMap::from([(vec![1, 2], vec![3])]),
+                },
+            )]),
+        };
+        let rendered = render(&fixture);
+        assert!(rendered.contains("Slot::new(10, 3)"));
+        assert!(rendered.contains("balance: Amount::from_str(\"12.5\").unwrap()"));
+        assert!(rendered.contains("(vec![1, 2], vec![3]),"));
+        assert!(rendered.contains(&format!(
+            "rolls.insert(Address::from_str(\"{}\").unwrap(), 2);",
+            address
+        )));
+    }
+}
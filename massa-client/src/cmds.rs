@@ -1,28 +1,37 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use crate::address_book::{resolve_address, resolve_addresses, ADDRESS_BOOK};
+use crate::deployment::{deploy, load_initial_datastore, Deployment};
+use crate::operation_builder::{build_operation, select_expire_period, BuiltOperation};
 use crate::repl::Output;
 use anyhow::{anyhow, bail, Result};
 use console::style;
 use massa_api_exports::{
-    address::{AddressInfo, CompactAddressInfo},
+    address::{AddressInfo, AddressStakingRewards, CompactAddressInfo, VerifiedContract},
+    block::BlockFieldMask,
     datastore::DatastoreEntryInput,
     execution::{ReadOnlyBytecodeExecution, ReadOnlyCall},
     operation::OperationInput,
 };
+use massa_hash::Hash;
+use massa_models::args::{encode_arg_values, ArgValue};
+use massa_models::config::{
+    MAX_MULTI_TRANSACTION_OUTPUTS, MAX_TRANSACTION_MEMO_LENGTH, MULTI_TRANSACTION_FEE_PER_OUTPUT,
+};
 use massa_models::node::NodeId;
 use massa_models::prehash::PreHashMap;
-use massa_models::timeslots::get_current_latest_block_slot;
 use massa_models::{
     address::Address,
     amount::Amount,
     block_id::BlockId,
     endorsement::EndorsementId,
     execution::EventFilter,
-    operation::{Operation, OperationId, OperationType},
+    operation::{Operation, OperationId, OperationType, TransactionOutput},
     slot::Slot,
 };
+use massa_network_exports::IpSubnet;
 use massa_sdk::Client;
-use massa_signature::KeyPair;
+use massa_signature::{BlsKeyPair, KeyPair};
 use massa_time::MassaTime;
 use massa_wallet::Wallet;
 use serde::Serialize;
@@ -84,6 +93,27 @@ pub enum Command {
     )]
     node_ban_by_id,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Subnet [Duration] [Reason]", pwd_not_needed = "true"),
+        message = "ban a range of IP addresses in CIDR notation (ex: 192.0.2.0/24) for a duration in milliseconds or as 2h30m, permanently without duration"
+    )]
+    node_ban_subnet,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Subnet1 Subnet2 ...", pwd_not_needed = "true"),
+        message = "lift the bans of given ranges of IP addresses"
+    )]
+    node_unban_subnet,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(pwd_not_needed = "true"),
+        message = "show the bans of IP ranges and addresses"
+    )]
+    node_list_bans,
+
     #[strum(
         ascii_case_insensitive,
         props(pwd_not_needed = "true"),
@@ -91,6 +121,79 @@ pub enum Command {
     )]
     node_stop,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(pwd_not_needed = "true"),
+        message = "show the objects in the node storage and the modules referencing them (requires storage diagnostics)"
+    )]
+    node_get_storage_diagnostics,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(pwd_not_needed = "true"),
+        message = "show the capacity, load and lag of the channels between the node workers"
+    )]
+    node_get_channel_stats,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Level Count", pwd_not_needed = "true"),
+        message = "show the latest log lines of a level (error, warn, info, debug or trace) or of a more severe level"
+    )]
+    node_get_recent_logs,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Frequency", pwd_not_needed = "true"),
+        message = "start sampling the CPU stacks of the node at Frequency Hz, 100 if omitted (requires a node built with the profiling feature)"
+    )]
+    node_start_profiling,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "OutputFile", pwd_not_needed = "true"),
+        message = "stop the CPU profiling of the node and write the sampled stacks in OutputFile, in the folded format of flamegraph.pl and inferno-flamegraph"
+    )]
+    node_stop_profiling,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(pwd_not_needed = "true"),
+        message = "show the heap statistics of the node (requires a node built with the profiling feature)"
+    )]
+    node_get_heap_stats,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "BackupDirectory", pwd_not_needed = "true"),
+        message = "write a backup of the final state in a new directory of the node machine, restore it by starting the node with --restore-backup"
+    )]
+    node_create_backup,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(
+            args = "BytecodeHash Name SourceUrl CompilerVersion",
+            pwd_not_needed = "true"
+        ),
+        message = "register the source of a contract verified by the node operator, shown as verified by get_addresses"
+    )]
+    node_add_verified_contract,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "BytecodeHash1 BytecodeHash2 ...", pwd_not_needed = "true"),
+        message = "remove the verified contracts of given bytecode hashes"
+    )]
+    node_remove_verified_contracts,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(pwd_not_needed = "true"),
+        message = "show the contracts verified by the node operator"
+    )]
+    node_list_verified_contracts,
+
     #[strum(
         ascii_case_insensitive,
         props(pwd_not_needed = "true"),
@@ -112,6 +215,27 @@ pub enum Command {
     )]
     node_stop_staking,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Address1 Address2 ...", pwd_not_needed = "true"),
+        message = "resumes block and endorsement production with the given staking addresses"
+    )]
+    node_enable_staking,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Address1 Address2 ...", pwd_not_needed = "true"),
+        message = "pauses block and endorsement production with the given staking addresses, keeping their keys on the node"
+    )]
+    node_disable_staking,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(pwd_not_needed = "true"),
+        message = "show the staking addresses whose production is paused"
+    )]
+    node_get_disabled_staking_addresses,
+
     #[strum(
         ascii_case_insensitive,
         props(args = "Address discord_id"),
@@ -147,6 +271,13 @@ pub enum Command {
     )]
     get_status,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(pwd_not_needed = "true"),
+        message = "show the status of the MIPs known by the node (vote, state, activation time)"
+    )]
+    get_mip_status,
+
     #[strum(
         ascii_case_insensitive,
         props(args = "Address1 Address2 ...", pwd_not_needed = "true"),
@@ -154,6 +285,34 @@ pub enum Command {
     )]
     get_addresses,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "CycleCount Address1 Address2 ...", pwd_not_needed = "true"),
+        message = "get the upcoming draws of a list of addresses, and production estimates for the next CycleCount cycles"
+    )]
+    get_selection_forecast,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Address1 Address2 ...", pwd_not_needed = "true"),
+        message = "get the staking rewards credited to a list of addresses, per cycle"
+    )]
+    get_staking_rewards,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "CsvPath (Address1 Address2 ...)", pwd_not_needed = "true"),
+        message = "export the staking rewards of a list of addresses (node staking addresses by default) to a CSV file"
+    )]
+    export_staking_rewards,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Address1 Address2 ...", pwd_not_needed = "true"),
+        message = "get the storage rent of a list of addresses, and when their storage is reclaimed if they do not pay it"
+    )]
+    get_storage_rent,
+
     #[strum(
         ascii_case_insensitive,
         props(args = "Address Key", pwd_not_needed = "true"),
@@ -163,8 +322,11 @@ pub enum Command {
 
     #[strum(
         ascii_case_insensitive,
-        props(args = "BlockId", pwd_not_needed = "true"),
-        message = "show info about a block (content, finality ...)"
+        props(
+            args = "[full|headers_only|ids_only] BlockId1 BlockId2 ...",
+            pwd_not_needed = "true"
+        ),
+        message = "show info about a block (content, finality ...), optionally only its header or its status"
     )]
     get_blocks,
 
@@ -242,25 +404,90 @@ pub enum Command {
 
     #[strum(
         ascii_case_insensitive,
-        props(args = "Address RollCount Fee"),
-        message = "buy rolls with wallet address"
+        props(pwd_not_needed = "true"),
+        message = "show the names of the address book, which can be used instead of addresses in all the commands"
+    )]
+    address_book_list,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Name Address", pwd_not_needed = "true"),
+        message = "give a name to an address in the address book"
+    )]
+    address_book_add,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Name1 Name2 ...", pwd_not_needed = "true"),
+        message = "remove names from the address book"
+    )]
+    address_book_remove,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Address1 Address2 ... RollCount Fee"),
+        message = "buy rolls with wallet address(es), the operations of several addresses being sent in one batch"
     )]
     buy_rolls,
 
     #[strum(
         ascii_case_insensitive,
-        props(args = "Address RollCount Fee"),
-        message = "sell rolls with wallet address"
+        props(args = "Address1 Address2 ... RollCount Fee"),
+        message = "sell rolls with wallet address(es), the operations of several addresses being sent in one batch"
     )]
     sell_rolls,
 
     #[strum(
         ascii_case_insensitive,
-        props(args = "SenderAddress ReceiverAddress Amount Fee"),
-        message = "send coins from a wallet address"
+        props(args = "SenderAddress ReceiverAddress Amount Fee [Memo]"),
+        message = "send coins from a wallet address, with an optional memo (ex: to tag a deposit) charged by size"
     )]
     send_transaction,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "SenderAddress Fee ReceiverAddress1=Amount1 ReceiverAddress2=Amount2 ..."),
+        message = "send coins from a wallet address to several recipients in one operation, all paid or none. The fee must cover the minimal fee per recipient"
+    )]
+    send_multi_transaction,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "SenderAddress ReceiverAddress Amount Fee ActivationPeriod,ActivationThread"),
+        message = "schedule a transfer of coins from a wallet address, credited to the recipient at the activation slot. The amount is held until then and the transfer can be cancelled"
+    )]
+    send_scheduled_transaction,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "SenderAddress OperationId Fee"),
+        message = "cancel a pending scheduled transfer of a wallet address, given the id of the operation that scheduled it, reimbursing its amount"
+    )]
+    cancel_scheduled_transaction,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "SenderAddress Fee"),
+        message = "register the BLS key derived from the key of a wallet address, with which its node signs the endorsements aggregated in the block headers. It can sign from the second cycle after the registration and cannot be changed"
+    )]
+    register_bls_key,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Address1 Address2 ...", pwd_not_needed = "true"),
+        message = "get the pending scheduled transfers sent by a list of addresses"
+    )]
+    get_scheduled_transfers,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(
+            args = "transaction SenderAddress ReceiverAddress Amount Fee | buy_rolls Address RollCount Fee | sell_rolls Address RollCount Fee"
+        ),
+        message = "build and sign an operation without sending it, after checking the balance. Its expiry period is chosen from the current period and `validity_periods` of the client configuration"
+    )]
+    build_operation,
+
     #[strum(
         ascii_case_insensitive,
         props(args = "SenderAddress PathToBytecode MaxGas Fee"),
@@ -268,10 +495,19 @@ pub enum Command {
     )]
     execute_smart_contract,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(
+            args = "SenderAddress PathToDeployer PathToContract ConstructorArgs MaxGas Coins Fee PathToDatastore"
+        ),
+        message = "deploy a smart contract through a deployer bytecode, with constructor arguments (written as the parameter of call_smart_contract), coins and initial datastore entries (optional JSON file). The operation is dry run first and pays the gas it used plus a margin, up to MaxGas"
+    )]
+    deploy_smart_contract,
+
     #[strum(
         ascii_case_insensitive,
         props(args = "SenderAddress TargetAddress FunctionName Parameter MaxGas Coins Fee"),
-        message = "create and send an operation to call a function of a smart contract"
+        message = "create and send an operation to call a function of a smart contract. The parameter is sent as a string, or encoded in the contract `Args` format if written `args:[{\"string\": \"hello\"}, {\"u64\": 42}]`"
     )]
     call_smart_contract,
 
@@ -291,7 +527,7 @@ pub enum Command {
             args = "TargetAddress TargetFunction Parameter MaxGas SenderAddress IsFinal",
             pwd_not_needed = "true"
         ),
-        message = "call a smart contract function, sender address is optional, is_final is optional. Nothing is really executed on chain"
+        message = "call a smart contract function, sender address is optional, is_final is optional, the parameter is written as for call_smart_contract. Nothing is really executed on chain"
     )]
     read_only_call,
 
@@ -534,6 +770,52 @@ impl Command {
                 Ok(Box::new(()))
             }
 
+            Command::node_ban_subnet => {
+                let Some(subnet) = parameters.first() else {
+                    bail!("wrong number of parameters");
+                };
+                let subnet = subnet
+                    .parse::<IpSubnet>()
+                    .map_err(|e| anyhow!("failed to parse \"{}\" due to: {}", subnet, e))?;
+                // the duration is optional, the reason is the rest of the line
+                let (duration, reason) =
+                    match parameters.get(1).map(|arg| MassaTime::parse_duration(arg)) {
+                        Some(Ok(duration)) => (Some(duration), &parameters[2..]),
+                        _ => (None, &parameters[1..]),
+                    };
+                match client
+                    .private
+                    .node_ban_subnet(subnet, duration, reason.join(" "))
+                    .await
+                {
+                    Ok(()) => {
+                        if !json {
+                            println!("Request of banning successfully sent!")
+                        }
+                    }
+                    Err(e) => rpc_error!(e),
+                }
+                Ok(Box::new(()))
+            }
+
+            Command::node_unban_subnet => {
+                let subnets = parse_vec::<IpSubnet>(parameters)?;
+                match client.private.node_unban_subnet(subnets).await {
+                    Ok(()) => {
+                        if !json {
+                            println!("Request of unbanning successfully sent!")
+                        }
+                    }
+                    Err(e) => rpc_error!(e),
+                }
+                Ok(Box::new(()))
+            }
+
+            Command::node_list_bans => match client.private.node_list_bans().await {
+                Ok(bans) => Ok(Box::new(bans)),
+                Err(e) => rpc_error!(e),
+            },
+
             Command::node_stop => {
                 match client.private.stop_node().await {
                     Ok(()) => {
@@ -546,6 +828,137 @@ impl Command {
                 Ok(Box::new(()))
             }
 
+            Command::node_get_storage_diagnostics => {
+                match client.private.get_storage_diagnostics().await {
+                    Ok(dump) => Ok(Box::new(dump)),
+                    Err(e) => rpc_error!(e),
+                }
+            }
+
+            Command::node_get_channel_stats => match client.private.get_channel_stats().await {
+                Ok(stats) => Ok(Box::new(stats)),
+                Err(e) => rpc_error!(e),
+            },
+
+            Command::node_start_profiling => {
+                if parameters.len() > 1 {
+                    bail!("wrong number of parameters");
+                }
+                let frequency = match parameters.first() {
+                    Some(frequency) => frequency.parse::<u32>()?,
+                    None => 100,
+                };
+                match client.private.node_start_profiling(frequency).await {
+                    Ok(()) => {
+                        if !json {
+                            println!("CPU profiling started at {} Hz", frequency);
+                        }
+                        Ok(Box::new(()))
+                    }
+                    Err(e) => rpc_error!(e),
+                }
+            }
+
+            Command::node_stop_profiling => {
+                if parameters.len() != 1 {
+                    bail!("wrong number of parameters");
+                }
+                let path = parameters[0].parse::<PathBuf>()?;
+                match client.private.node_stop_profiling().await {
+                    Ok(profile) => {
+                        tokio::fs::write(&path, &profile.folded_stacks).await?;
+                        if !json {
+                            println!("Folded stacks written in {}", path.display());
+                        }
+                        Ok(Box::new(profile))
+                    }
+                    Err(e) => rpc_error!(e),
+                }
+            }
+
+            Command::node_get_heap_stats => match client.private.get_heap_stats().await {
+                Ok(stats) => Ok(Box::new(stats)),
+                Err(e) => rpc_error!(e),
+            },
+
+            Command::node_get_recent_logs => {
+                if parameters.len() != 2 {
+                    bail!("wrong number of parameters");
+                }
+                let count = parameters[1].parse::<usize>()?;
+                match client
+                    .private
+                    .get_recent_logs(parameters[0].clone(), count)
+                    .await
+                {
+                    Ok(lines) => Ok(Box::new(lines)),
+                    Err(e) => rpc_error!(e),
+                }
+            }
+
+            Command::node_create_backup => {
+                if parameters.len() != 1 {
+                    bail!("wrong number of parameters");
+                }
+                match client
+                    .private
+                    .node_create_backup(PathBuf::from(&parameters[0]))
+                    .await
+                {
+                    Ok(manifest) => Ok(Box::new(manifest)),
+                    Err(e) => rpc_error!(e),
+                }
+            }
+
+            Command::node_add_verified_contract => {
+                if parameters.len() != 4 {
+                    bail!("wrong number of parameters");
+                }
+                let contract = VerifiedContract {
+                    bytecode_hash: parameters[0].parse()?,
+                    name: parameters[1].clone(),
+                    source_url: parameters[2].clone(),
+                    compiler_version: parameters[3].clone(),
+                };
+                match client
+                    .private
+                    .node_add_verified_contracts(vec![contract])
+                    .await
+                {
+                    Ok(()) => {
+                        if !json {
+                            println!("Verified contract successfully registered!")
+                        }
+                    }
+                    Err(e) => rpc_error!(e),
+                }
+                Ok(Box::new(()))
+            }
+
+            Command::node_remove_verified_contracts => {
+                let bytecode_hashes = parse_vec::<Hash>(parameters)?;
+                match client
+                    .private
+                    .node_remove_verified_contracts(bytecode_hashes)
+                    .await
+                {
+                    Ok(()) => {
+                        if !json {
+                            println!("Verified contracts successfully removed!")
+                        }
+                    }
+                    Err(e) => rpc_error!(e),
+                }
+                Ok(Box::new(()))
+            }
+
+            Command::node_list_verified_contracts => {
+                match client.private.node_verified_contracts().await {
+                    Ok(contracts) => Ok(Box::new(contracts)),
+                    Err(e) => rpc_error!(e),
+                }
+            }
+
             Command::node_get_staking_addresses => {
                 match client.private.get_staking_addresses().await {
                     Ok(staking_addresses) => Ok(Box::new(staking_addresses)),
@@ -560,7 +973,7 @@ impl Command {
                     bail!("wrong number of parameters");
                 }
                 // parse
-                let addr = parameters[0].parse::<Address>()?;
+                let addr = resolve_address(client, &parameters[0]).await?;
                 let msg = parameters[1].as_bytes().to_vec();
                 // get address signature
                 if let Some(addr_sig) = wallet.sign_message(&addr, msg.clone()) {
@@ -591,19 +1004,80 @@ impl Command {
                 Err(e) => rpc_error!(e),
             },
 
+            Command::get_mip_status => match client.public.get_mip_status().await {
+                Ok(mip_status) => Ok(Box::new(mip_status)),
+                Err(e) => rpc_error!(e),
+            },
+
             Command::get_addresses => {
-                let addresses = parse_vec::<Address>(parameters)?;
+                let addresses = resolve_addresses(client, parameters).await?;
                 match client.public.get_addresses(addresses).await {
                     Ok(addresses_info) => Ok(Box::new(addresses_info)),
                     Err(e) => rpc_error!(e),
                 }
             }
 
+            Command::get_selection_forecast => {
+                if parameters.len() < 2 {
+                    bail!("wrong number of parameters");
+                }
+                let cycle_count = parameters[0].parse::<u64>()?;
+                let addresses = resolve_addresses(client, &parameters[1..]).await?;
+                match client
+                    .public
+                    .get_selection_forecast(addresses, cycle_count)
+                    .await
+                {
+                    Ok(forecasts) => Ok(Box::new(forecasts)),
+                    Err(e) => rpc_error!(e),
+                }
+            }
+
+            Command::get_staking_rewards => {
+                let addresses = resolve_addresses(client, parameters).await?;
+                match client.public.get_staking_rewards(addresses).await {
+                    Ok(staking_rewards) => Ok(Box::new(staking_rewards)),
+                    Err(e) => rpc_error!(e),
+                }
+            }
+
+            Command::get_storage_rent => {
+                let addresses = resolve_addresses(client, parameters).await?;
+                match client.public.get_storage_rent(addresses).await {
+                    Ok(storage_rents) => Ok(Box::new(storage_rents)),
+                    Err(e) => rpc_error!(e),
+                }
+            }
+
+            Command::export_staking_rewards => {
+                if parameters.is_empty() {
+                    bail!("wrong number of parameters");
+                }
+                let path = parameters[0].parse::<PathBuf>()?;
+                let mut addresses = resolve_addresses(client, &parameters[1..]).await?;
+                if addresses.is_empty() {
+                    match client.private.get_staking_addresses().await {
+                        Ok(staking_addresses) => addresses.extend(staking_addresses),
+                        Err(e) => rpc_error!(e),
+                    }
+                }
+                match client.public.get_staking_rewards(addresses).await {
+                    Ok(staking_rewards) => {
+                        tokio::fs::write(&path, staking_rewards_to_csv(&staking_rewards)).await?;
+                        if !json {
+                            println!("Staking rewards exported to {}", path.display());
+                        }
+                        Ok(Box::new(()))
+                    }
+                    Err(e) => rpc_error!(e),
+                }
+            }
+
             Command::get_datastore_entry => {
                 if parameters.len() != 2 {
                     bail!("invalid number of parameters");
                 }
-                let address = parameters[0].parse::<Address>()?;
+                let address = resolve_address(client, &parameters[0]).await?;
                 let key = parameters[1].as_bytes().to_vec();
                 match client
                     .public
@@ -616,11 +1090,17 @@ impl Command {
             }
 
             Command::get_blocks => {
+                // the field mask is optional
+                let (fields, parameters) =
+                    match parameters.first().map(|arg| arg.parse::<BlockFieldMask>()) {
+                        Some(Ok(fields)) => (Some(fields), &parameters[1..]),
+                        _ => (None, parameters),
+                    };
                 if parameters.is_empty() {
                     bail!("wrong param numbers, expecting at least one block id")
                 }
                 let block_ids = parse_vec::<BlockId>(parameters)?;
-                match client.public.get_blocks(block_ids).await {
+                match client.public.get_blocks(block_ids, fields).await {
                     Ok(blocks_info) => Ok(Box::new(blocks_info)),
                     Err(e) => rpc_error!(e),
                 }
@@ -661,11 +1141,18 @@ impl Command {
                         bail!("invalid parameter: {}, type \"help get_filtered_sc_output_event\" to get the list of valid parameters", v);
                     }
                 }
+                let mut addresses = Vec::with_capacity(2);
+                for key in &p_list[2..4] {
+                    addresses.push(match p.get(key) {
+                        Some(address) => Some(resolve_address(client, address).await?),
+                        None => None,
+                    });
+                }
                 let filter = EventFilter {
                     start: parse_key_value(&p, p_list[0])?,
                     end: parse_key_value(&p, p_list[1])?,
-                    emitter_address: parse_key_value(&p, p_list[2])?,
-                    original_caller_address: parse_key_value(&p, p_list[3])?,
+                    emitter_address: addresses[0],
+                    original_caller_address: addresses[1],
                     original_operation_id: parse_key_value(&p, p_list[4])?,
                     is_final: parse_key_value(&p, p_list[5])?,
                     is_error: parse_key_value(&p, p_list[6])?,
@@ -704,7 +1191,7 @@ impl Command {
             Command::wallet_get_public_key => {
                 let wallet = wallet_opt.as_mut().unwrap();
 
-                let addresses = parse_vec::<Address>(parameters)?;
+                let addresses = resolve_addresses(client, parameters).await?;
 
                 let hashset: HashSet<_> = addresses.into_iter().collect();
 
@@ -729,7 +1216,7 @@ impl Command {
                     client_warning!("do not share your secret key");
                 }
 
-                let addresses = parse_vec::<Address>(parameters)?;
+                let addresses = resolve_addresses(client, parameters).await?;
 
                 let hashset: HashSet<_> = addresses.into_iter().collect();
 
@@ -750,7 +1237,7 @@ impl Command {
             Command::node_start_staking => {
                 let wallet = wallet_opt.as_mut().unwrap();
 
-                let addresses = parse_vec::<Address>(parameters)?;
+                let addresses = resolve_addresses(client, parameters).await?;
                 let secret: Vec<Option<&KeyPair>> = addresses
                     .iter()
                     .map(|addr| wallet.get_full_wallet().get(addr))
@@ -773,7 +1260,7 @@ impl Command {
             }
 
             Command::node_stop_staking => {
-                let addresses = parse_vec::<Address>(parameters)?;
+                let addresses = resolve_addresses(client, parameters).await?;
                 match client.private.remove_staking_addresses(addresses).await {
                     Ok(()) => {
                         if !json {
@@ -785,6 +1272,39 @@ impl Command {
                 Ok(Box::new(()))
             }
 
+            Command::node_enable_staking => {
+                let addresses = resolve_addresses(client, parameters).await?;
+                match client.private.enable_staking_addresses(addresses).await {
+                    Ok(()) => {
+                        if !json {
+                            println!("Staking successfully enabled!")
+                        }
+                    }
+                    Err(e) => rpc_error!(e),
+                }
+                Ok(Box::new(()))
+            }
+
+            Command::node_disable_staking => {
+                let addresses = resolve_addresses(client, parameters).await?;
+                match client.private.disable_staking_addresses(addresses).await {
+                    Ok(()) => {
+                        if !json {
+                            println!("Staking successfully disabled!")
+                        }
+                    }
+                    Err(e) => rpc_error!(e),
+                }
+                Ok(Box::new(()))
+            }
+
+            Command::node_get_disabled_staking_addresses => {
+                match client.private.get_disabled_staking_addresses().await {
+                    Ok(staking_addresses) => Ok(Box::new(staking_addresses)),
+                    Err(e) => rpc_error!(e),
+                }
+            }
+
             Command::wallet_generate_secret_key => {
                 let wallet = wallet_opt.as_mut().unwrap();
 
@@ -821,7 +1341,7 @@ impl Command {
                 let wallet = wallet_opt.as_mut().unwrap();
 
                 let mut res = "".to_string();
-                let addresses = parse_vec::<Address>(parameters)?;
+                let addresses = resolve_addresses(client, parameters).await?;
                 match wallet.remove_addresses(&addresses) {
                     Ok(_) => {
                         let _ = writeln!(res, "Addresses removed from the wallet");
@@ -836,15 +1356,56 @@ impl Command {
                 Ok(Box::new(()))
             }
 
+            Command::address_book_list => {
+                let address_book = ADDRESS_BOOK
+                    .read()
+                    .map_err(|_| anyhow!("address book unavailable"))?;
+                Ok(Box::new(address_book.entries().clone()))
+            }
+
+            Command::address_book_add => {
+                if parameters.len() != 2 {
+                    bail!("wrong number of parameters");
+                }
+                let address = resolve_address(client, &parameters[1]).await?;
+                ADDRESS_BOOK
+                    .write()
+                    .map_err(|_| anyhow!("address book unavailable"))?
+                    .add(&parameters[0], address)?;
+                if !json {
+                    println!("Address {} is now named {}", address, parameters[0]);
+                }
+                Ok(Box::new(()))
+            }
+
+            Command::address_book_remove => {
+                let removed = ADDRESS_BOOK
+                    .write()
+                    .map_err(|_| anyhow!("address book unavailable"))?
+                    .remove(parameters)?;
+                if !json {
+                    println!("{} name(s) removed from the address book", removed.len());
+                }
+                Ok(Box::new(()))
+            }
+
             Command::buy_rolls => {
                 let wallet = wallet_opt.as_mut().unwrap();
 
-                if parameters.len() != 3 {
+                if parameters.len() < 3 {
                     bail!("wrong number of parameters");
                 }
-                let addr = parameters[0].parse::<Address>()?;
-                let roll_count = parameters[1].parse::<u64>()?;
-                let fee = parameters[2].parse::<Amount>()?;
+                let (address_params, count_and_fee) = parameters.split_at(parameters.len() - 2);
+                let roll_count = count_and_fee[0].parse::<u64>()?;
+                let fee = count_and_fee[1].parse::<Amount>()?;
+                if address_params.len() > 1 {
+                    let addresses = resolve_addresses(client, address_params).await?;
+                    return send_roll_operations(
+                        client, wallet, addresses, true, roll_count, fee, json,
+                    )
+                    .await;
+                }
+                let addr = resolve_address(client, &address_params[0]).await?;
 
                 if !json {
                     let roll_price = match client.public.get_status().await {
@@ -895,12 +1456,20 @@ impl Command {
             Command::sell_rolls => {
                 let wallet = wallet_opt.as_mut().unwrap();
 
-                if parameters.len() != 3 {
+                if parameters.len() < 3 {
                     bail!("wrong number of parameters");
                 }
-                let addr = parameters[0].parse::<Address>()?;
-                let roll_count = parameters[1].parse::<u64>()?;
-                let fee = parameters[2].parse::<Amount>()?;
+                let (address_params, count_and_fee) = parameters.split_at(parameters.len() - 2);
+                let roll_count = count_and_fee[0].parse::<u64>()?;
+                let fee = count_and_fee[1].parse::<Amount>()?;
+                if address_params.len() > 1 {
+                    let addresses = resolve_addresses(client, address_params).await?;
+                    return send_roll_operations(
+                        client, wallet, addresses, false, roll_count, fee, json,
+                    )
+                    .await;
+                }
+                let addr = resolve_address(client, &address_params[0]).await?;
 
                 if !json {
                     if let Ok(addresses_info) = client.public.get_addresses(vec![addr]).await {
@@ -928,16 +1497,156 @@ impl Command {
                 .await
             }
 
+            Command::send_multi_transaction => {
+                let wallet = wallet_opt.as_mut().unwrap();
+
+                if parameters.len() < 3 {
+                    bail!("wrong number of parameters");
+                }
+                let addr = resolve_address(client, &parameters[0]).await?;
+                let fee = parameters[1].parse::<Amount>()?;
+                let mut outputs = Vec::with_capacity(parameters.len() - 2);
+                for output in &parameters[2..] {
+                    let (recipient, amount) = output.rsplit_once('=').ok_or_else(|| {
+                        anyhow!("{} is not of the form ReceiverAddress=Amount", output)
+                    })?;
+                    outputs.push(TransactionOutput {
+                        recipient_address: resolve_address(client, recipient).await?,
+                        amount: amount.parse::<Amount>()?,
+                    });
+                }
+                if outputs.len() as u64 > MAX_MULTI_TRANSACTION_OUTPUTS {
+                    bail!(
+                        "{} recipients were given, at most {} are allowed",
+                        outputs.len(),
+                        MAX_MULTI_TRANSACTION_OUTPUTS
+                    );
+                }
+                let min_fee =
+                    MULTI_TRANSACTION_FEE_PER_OUTPUT.saturating_mul_u64(outputs.len() as u64);
+                if fee < min_fee {
+                    bail!(
+                        "the fee must be at least {} for {} recipients",
+                        min_fee,
+                        outputs.len()
+                    );
+                }
+
+                send_operation(
+                    client,
+                    wallet,
+                    OperationType::MultiTransaction { outputs },
+                    fee,
+                    addr,
+                    json,
+                )
+                .await
+            }
+
+            Command::send_scheduled_transaction => {
+                let wallet = wallet_opt.as_mut().unwrap();
+
+                if parameters.len() != 5 {
+                    bail!("wrong number of parameters");
+                }
+                let addr = resolve_address(client, &parameters[0]).await?;
+                let recipient_address = resolve_address(client, &parameters[1]).await?;
+                let amount = parameters[2].parse::<Amount>()?;
+                let fee = parameters[3].parse::<Amount>()?;
+                let activation_slot = parameters[4].parse::<Slot>()?;
+
+                send_operation(
+                    client,
+                    wallet,
+                    OperationType::ScheduledTransaction {
+                        recipient_address,
+                        amount,
+                        activation_slot,
+                    },
+                    fee,
+                    addr,
+                    json,
+                )
+                .await
+            }
+
+            Command::cancel_scheduled_transaction => {
+                let wallet = wallet_opt.as_mut().unwrap();
+
+                if parameters.len() != 3 {
+                    bail!("wrong number of parameters");
+                }
+                let addr = resolve_address(client, &parameters[0]).await?;
+                let operation_id = parameters[1].parse::<OperationId>()?;
+                let fee = parameters[2].parse::<Amount>()?;
+
+                send_operation(
+                    client,
+                    wallet,
+                    OperationType::CancelScheduledTransaction { operation_id },
+                    fee,
+                    addr,
+                    json,
+                )
+                .await
+            }
+
+            Command::register_bls_key => {
+                let wallet = wallet_opt.as_mut().unwrap();
+
+                if parameters.len() != 2 {
+                    bail!("wrong number of parameters");
+                }
+                let addr = resolve_address(client, &parameters[0]).await?;
+                let fee = parameters[1].parse::<Amount>()?;
+                // the node derives the same key from the staking key of the address
+                let bls_keypair = match wallet.find_associated_keypair(&addr) {
+                    Some(keypair) => BlsKeyPair::derive_from(keypair),
+                    None => bail!("address {} is not in the wallet", addr),
+                };
+
+                send_operation(
+                    client,
+                    wallet,
+                    OperationType::RegisterBlsKey {
+                        public_key: bls_keypair.get_public_key(),
+                        proof_of_possession: bls_keypair.prove_possession(),
+                    },
+                    fee,
+                    addr,
+                    json,
+                )
+                .await
+            }
+
+            Command::get_scheduled_transfers => {
+                let addresses = resolve_addresses(client, parameters).await?;
+                match client.public.get_scheduled_transfers(addresses).await {
+                    Ok(transfers) => Ok(Box::new(transfers)),
+                    Err(e) => rpc_error!(e),
+                }
+            }
+
             Command::send_transaction => {
                 let wallet = wallet_opt.as_mut().unwrap();
 
-                if parameters.len() != 4 {
+                if parameters.len() != 4 && parameters.len() != 5 {
                     bail!("wrong number of parameters");
                 }
-                let addr = parameters[0].parse::<Address>()?;
-                let recipient_address = parameters[1].parse::<Address>()?;
+                let addr = resolve_address(client, &parameters[0]).await?;
+                let recipient_address = resolve_address(client, &parameters[1]).await?;
                 let amount = parameters[2].parse::<Amount>()?;
                 let fee = parameters[3].parse::<Amount>()?;
+                let memo = parameters.get(4).map(|memo| memo.as_bytes().to_vec());
+                if let Some(memo) = &memo {
+                    if memo.len() as u64 > MAX_TRANSACTION_MEMO_LENGTH {
+                        bail!(
+                            "the memo is {} bytes long, at most {} bytes are allowed",
+                            memo.len(),
+                            MAX_TRANSACTION_MEMO_LENGTH
+                        );
+                    }
+                }
 
                 if !json {
                     if let Ok(addresses_info) = client.public.get_addresses(vec![addr]).await {
@@ -960,6 +1669,7 @@ impl Command {
                     OperationType::Transaction {
                         recipient_address,
                         amount,
+                        memo,
                     },
                     fee,
                     addr,
@@ -967,6 +1677,46 @@ impl Command {
                 )
                 .await
             }
+            Command::build_operation => {
+                let wallet = wallet_opt.as_mut().unwrap();
+
+                let (op, addr, fee) =
+                    match (parameters.get(0).map(String::as_str), parameters.len()) {
+                        (Some("transaction"), 5) => (
+                            OperationType::Transaction {
+                                recipient_address: resolve_address(client, &parameters[2]).await?,
+                                amount: parameters[3].parse::<Amount>()?,
+                                memo: None,
+                            },
+                            resolve_address(client, &parameters[1]).await?,
+                            parameters[4].parse::<Amount>()?,
+                        ),
+                        (Some("buy_rolls"), 4) => (
+                            OperationType::RollBuy {
+                                roll_count: parameters[2].parse::<u64>()?,
+                            },
+                            resolve_address(client, &parameters[1]).await?,
+                            parameters[3].parse::<Amount>()?,
+                        ),
+                        (Some("sell_rolls"), 4) => (
+                            OperationType::RollSell {
+                                roll_count: parameters[2].parse::<u64>()?,
+                            },
+                            resolve_address(client, &parameters[1]).await?,
+                            parameters[3].parse::<Amount>()?,
+                        ),
+                        (Some("transaction" | "buy_rolls" | "sell_rolls"), _) => {
+                            bail!("wrong number of parameters")
+                        }
+                        _ => {
+                            bail!("the operation kind must be transaction, buy_rolls or sell_rolls")
+                        }
+                    };
+
+                let operation = build_operation(client, wallet, op, fee, addr).await?;
+                Ok(Box::new(BuiltOperation::new(&operation)?))
+            }
+
             Command::when_episode_ends => {
                 let end = match client.public.get_status().await {
                     Ok(node_status) => node_status.config.end_timestamp,
@@ -999,7 +1749,7 @@ impl Command {
                 if parameters.len() != 4 {
                     bail!("wrong number of parameters");
                 }
-                let addr = parameters[0].parse::<Address>()?;
+                let addr = resolve_address(client, &parameters[0]).await?;
                 let path = parameters[1].parse::<PathBuf>()?;
                 let max_gas = parameters[2].parse::<u64>()?;
                 let fee = parameters[3].parse::<Amount>()?;
@@ -1043,16 +1793,50 @@ impl Command {
                 )
                 .await
             }
+            Command::deploy_smart_contract => {
+                let wallet = wallet_opt.as_mut().unwrap();
+
+                if parameters.len() != 7 && parameters.len() != 8 {
+                    bail!("wrong number of parameters");
+                }
+                let addr = resolve_address(client, &parameters[0]).await?;
+                let deployer = get_file_as_byte_vec(&parameters[1].parse::<PathBuf>()?).await?;
+                let contract = get_file_as_byte_vec(&parameters[2].parse::<PathBuf>()?).await?;
+                let constructor_args = parse_call_parameter(&parameters[3])?;
+                let max_gas = parameters[4].parse::<u64>()?;
+                let coins = parameters[5].parse::<Amount>()?;
+                let fee = parameters[6].parse::<Amount>()?;
+                let initial_datastore = match parameters.get(7) {
+                    Some(path) => load_initial_datastore(&path.parse::<PathBuf>()?).await?,
+                    None => Default::default(),
+                };
+                let summary = deploy(
+                    client,
+                    wallet,
+                    Deployment {
+                        deployer,
+                        contract,
+                        constructor_args,
+                        initial_datastore,
+                        coins,
+                        max_gas,
+                        fee,
+                    },
+                    addr,
+                )
+                .await?;
+                Ok(Box::new(summary))
+            }
             Command::call_smart_contract => {
                 let wallet = wallet_opt.as_mut().unwrap();
 
                 if parameters.len() != 7 {
                     bail!("wrong number of parameters");
                 }
-                let addr = parameters[0].parse::<Address>()?;
-                let target_addr = parameters[1].parse::<Address>()?;
+                let addr = resolve_address(client, &parameters[0]).await?;
+                let target_addr = resolve_address(client, &parameters[1]).await?;
                 let target_func = parameters[2].clone();
-                let param = parameters[3].clone().into_bytes();
+                let param = parse_call_parameter(&parameters[3])?;
                 let max_gas = parameters[4].parse::<u64>()?;
                 let coins = parameters[5].parse::<Amount>()?;
                 let fee = parameters[6].parse::<Amount>()?;
@@ -1104,7 +1888,7 @@ impl Command {
                 if parameters.len() != 2 {
                     bail!("wrong number of parameters");
                 }
-                let addr = parameters[0].parse::<Address>()?;
+                let addr = resolve_address(client, &parameters[0]).await?;
                 let msg = parameters[1].clone();
                 if let Some(signed) = wallet.sign_message(&addr, msg.into_bytes()) {
                     Ok(Box::new(signed))
@@ -1120,7 +1904,7 @@ impl Command {
                 let path = parameters[0].parse::<PathBuf>()?;
                 let max_gas = parameters[1].parse::<u64>()?;
                 let address = if let Some(adr) = parameters.get(2) {
-                    Some(adr.parse::<Address>()?)
+                    Some(resolve_address(client, adr).await?)
                 } else {
                     None
                 };
@@ -1138,6 +1922,8 @@ impl Command {
                         address,
                         operation_datastore: None, // TODO - #3072
                         is_final,
+                        limits: Default::default(),
+                        fork: None,
                     })
                     .await
                 {
@@ -1150,12 +1936,12 @@ impl Command {
                     bail!("wrong number of parameters");
                 }
 
-                let target_address = parameters[0].parse::<Address>()?;
+                let target_address = resolve_address(client, &parameters[0]).await?;
                 let target_function = parameters[1].parse::<String>()?;
-                let parameter = parameters[2].parse::<String>()?.into_bytes();
+                let parameter = parse_call_parameter(&parameters[2])?;
                 let max_gas = parameters[3].parse::<u64>()?;
                 let caller_address = if let Some(addr) = parameters.get(4) {
-                    Some(addr.parse::<Address>()?)
+                    Some(resolve_address(client, addr).await?)
                 } else {
                     None
                 };
@@ -1173,6 +1959,8 @@ impl Command {
                         parameter,
                         max_gas,
                         is_final,
+                        limits: Default::default(),
+                        fork: None,
                     })
                     .await
                 {
@@ -1377,17 +2165,14 @@ async fn send_operation(
     }
     .config;
 
-    let slot = get_current_latest_block_slot(cfg.thread_count, cfg.t0, cfg.genesis_timestamp)?
-        .unwrap_or_else(|| Slot::new(0, 0));
-    let mut expire_period = slot.period + cfg.operation_validity_periods;
-    if slot.thread >= addr.get_thread(cfg.thread_count) {
-        expire_period += 1;
-    };
+    let expire_period = select_expire_period(&cfg, addr)?;
 
     let op = wallet.create_operation(
         Operation {
             fee,
             expire_period,
+            sequence: None,
+            sponsor: None,
             op,
         },
         addr,
@@ -1412,6 +2197,153 @@ async fn send_operation(
     }
 }
 
+/// Roll operation of an address in a batch, with the expected roll count once it is executed
+#[derive(Debug, Serialize)]
+pub struct RollBatchEntry {
+    /// address buying or selling rolls
+    pub address: Address,
+    /// id of the sent operation
+    pub operation_id: OperationId,
+    /// candidate roll count before the operation
+    pub candidate_roll_count: u64,
+    /// expected candidate roll count after the operation
+    pub expected_roll_count: u64,
+}
+
+/// Summary of a batch of roll operations
+#[derive(Debug, Serialize)]
+pub struct RollBatchSummary(pub Vec<RollBatchEntry>);
+
+impl Display for RollBatchSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let address_width = self
+            .0
+            .iter()
+            .map(|entry| entry.address.to_string().len())
+            .max()
+            .unwrap_or_default()
+            .max("Address".len());
+        let id_width = self
+            .0
+            .iter()
+            .map(|entry| entry.operation_id.to_string().len())
+            .max()
+            .unwrap_or_default()
+            .max("Operation ID".len());
+        writeln!(
+            f,
+            "{:<address_width$}  {:<id_width$}  {:>6}  {:>8}",
+            "Address", "Operation ID", "Rolls", "Expected"
+        )?;
+        for entry in &self.0 {
+            writeln!(
+                f,
+                "{:<address_width$}  {:<id_width$}  {:>6}  {:>8}",
+                entry.address.to_string(),
+                entry.operation_id.to_string(),
+                entry.candidate_roll_count,
+                entry.expected_roll_count
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Builds one roll buy (or sell) operation per address, with the same roll count and fee, and sends them in one batch
+async fn send_roll_operations(
+    client: &Client,
+    wallet: &Wallet,
+    addresses: Vec<Address>,
+    buy: bool,
+    roll_count: u64,
+    fee: Amount,
+    json: bool,
+) -> Result<Box<dyn Output>> {
+    let mut unique_addresses = HashSet::with_capacity(addresses.len());
+    if let Some(duplicate) = addresses
+        .iter()
+        .find(|addr| !unique_addresses.insert(**addr))
+    {
+        bail!("address {} appears several times in the batch", duplicate);
+    }
+    let cfg = match client.public.get_status().await {
+        Ok(node_status) => node_status,
+        Err(e) => rpc_error!(e),
+    }
+    .config;
+    let addresses_info = match client.public.get_addresses(addresses.clone()).await {
+        Ok(addresses_info) => addresses_info,
+        Err(e) => rpc_error!(e),
+    };
+    let spent = if buy {
+        cfg.roll_price
+            .checked_mul_u64(roll_count)
+            .and_then(|price| price.checked_add(fee))
+            .ok_or_else(|| anyhow!("the total amount hit the limit overflow"))?
+    } else {
+        fee
+    };
+    if !json && buy {
+        if let Ok(staked_keys) = client.private.get_staking_addresses().await {
+            for addr in addresses.iter().filter(|addr| !staked_keys.contains(addr)) {
+                client_warning!(format!("{} is not registered for staking. Don't forget to run 'node_start_staking <address>'", addr));
+            }
+        }
+    }
+
+    let mut operations = Vec::with_capacity(addresses.len());
+    let mut entries = Vec::with_capacity(addresses.len());
+    for addr in addresses {
+        let info = addresses_info
+            .iter()
+            .find(|info| info.address == addr)
+            .ok_or_else(|| anyhow!("address {} not found", addr))?;
+        if !json
+            && (info.candidate_balance < spent || (!buy && roll_count > info.candidate_roll_count))
+        {
+            client_warning!(format!(
+                "the operation of {} may be rejected due to insufficient balance or roll count",
+                addr
+            ));
+        }
+        let op = if buy {
+            OperationType::RollBuy { roll_count }
+        } else {
+            OperationType::RollSell { roll_count }
+        };
+        let operation = wallet.create_operation(
+            Operation {
+                fee,
+                expire_period: select_expire_period(&cfg, addr)?,
+                sequence: None,
+                sponsor: None,
+                op,
+            },
+            addr,
+        )?;
+        entries.push(RollBatchEntry {
+            address: addr,
+            operation_id: operation.id,
+            candidate_roll_count: info.candidate_roll_count,
+            expected_roll_count: if buy {
+                info.candidate_roll_count.saturating_add(roll_count)
+            } else {
+                info.candidate_roll_count.saturating_sub(roll_count)
+            },
+        });
+        operations.push(OperationInput {
+            creator_public_key: operation.content_creator_pub_key,
+            serialized_content: operation.serialized_data,
+            signature: operation.signature,
+        });
+    }
+
+    if let Err(e) = client.public.send_operations(operations).await {
+        rpc_error!(e)
+    }
+    Ok(Box::new(RollBatchSummary(entries)))
+}
+
 /// TODO: ugly utilities functions
 /// takes a slice of string and makes it into a `Vec<T>`
 pub fn parse_vec<T: std::str::FromStr>(args: &[String]) -> anyhow::Result<Vec<T>, anyhow::Error>
@@ -1426,7 +2358,45 @@ where
         .collect()
 }
 
+/// formats staking rewards as CSV, one line per address and cycle
+fn staking_rewards_to_csv(staking_rewards: &[AddressStakingRewards]) -> String {
+    let mut csv = String::from("address,cycle,start_time,end_time,block_count,block_rewards,endorsement_count,endorsement_rewards,endorsed_rewards,total\n");
+    for address_rewards in staking_rewards {
+        for cycle_rewards in &address_rewards.cycles {
+            let rewards = &cycle_rewards.rewards;
+            let _ = writeln!(
+                csv,
+                "{},{},{},{},{},{},{},{},{},{}",
+                address_rewards.address,
+                cycle_rewards.cycle,
+                cycle_rewards.start_timestamp.to_utc_string(),
+                cycle_rewards.end_timestamp.to_utc_string(),
+                rewards.block_count,
+                rewards.block_rewards,
+                rewards.endorsement_count,
+                rewards.endorsement_rewards,
+                rewards.endorsed_rewards,
+                rewards.total()
+            );
+        }
+    }
+    csv
+}
+
 /// reads a file
+/// Parses the parameter of a smart contract call: a string, or typed values to encode in the `Args` format
+/// if prefixed by `args:`
+fn parse_call_parameter(parameter: &str) -> Result<Vec<u8>> {
+    match parameter.strip_prefix("args:") {
+        Some(values) => {
+            let values: Vec<ArgValue> = serde_json::from_str(values)
+                .map_err(|e| anyhow!("invalid typed arguments {}: {}", values, e))?;
+            Ok(encode_arg_values(&values))
+        }
+        None => Ok(parameter.as_bytes().to_vec()),
+    }
+}
+
 async fn get_file_as_byte_vec(filename: &std::path::Path) -> Result<Vec<u8>> {
     Ok(tokio::fs::read(filename).await?)
 }
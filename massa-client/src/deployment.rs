@@ -0,0 +1,296 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Construction of the operations deploying a smart contract.
+//!
+//! A deployment is an `ExecuteSC` operation running a deployer bytecode, which creates the contract
+//! from the operation datastore. The datastore follows the layout of the standard deployers:
+//! * `[0]`: number of deployed contracts (u64, little endian), always 1
+//! * `1u64`: bytecode of the contract
+//! * `1u64 ++ [0]`: arguments of the constructor of the contract
+//! * `1u64 ++ [1]`: coins sent to the contract (raw amount, u64, little endian)
+//! * `1u64 ++ [2] ++ key`: initial datastore entry `key` of the contract
+//!
+//! The files of initial datastore entries are JSON objects whose keys and values are UTF-8 strings.
+//!
+//! The operation is first dry run to check that it succeeds and to size its max gas.
+
+use crate::operation_builder::select_expire_period;
+use anyhow::{anyhow, bail, Result};
+use massa_api_exports::operation::OperationInput;
+use massa_models::{
+    address::Address,
+    amount::Amount,
+    config::{
+        LEDGER_COST_PER_BYTE, LEDGER_ENTRY_BASE_SIZE, LEDGER_ENTRY_DATASTORE_BASE_SIZE,
+        MAX_OPERATION_DATASTORE_ENTRY_COUNT, MAX_OPERATION_DATASTORE_KEY_LENGTH,
+        MAX_OPERATION_DATASTORE_VALUE_LENGTH,
+    },
+    datastore::Datastore,
+    operation::{Operation, OperationId, OperationType, SecureShareOperation},
+};
+use massa_sdk::Client;
+use massa_wallet::Wallet;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::path::Path;
+
+/// index of the deployed contract in the deployer datastore
+const CONTRACT_INDEX: u64 = 1;
+
+/// Paid gas on top of the gas used by the dry run of the deployment, in percent
+const GAS_MARGIN_PERCENT: u64 = 20;
+
+fn contract_key(suffix: &[u8]) -> Vec<u8> {
+    let mut key = CONTRACT_INDEX.to_le_bytes().to_vec();
+    key.extend_from_slice(suffix);
+    key
+}
+
+/// Builds the operation datastore read by the deployer
+pub(crate) fn deployment_datastore(
+    contract: Vec<u8>,
+    constructor_args: Vec<u8>,
+    coins: Amount,
+    initial_datastore: &Datastore,
+) -> Result<Datastore> {
+    let mut datastore = Datastore::new();
+    datastore.insert(vec![0], 1u64.to_le_bytes().to_vec());
+    datastore.insert(contract_key(&[]), contract);
+    datastore.insert(contract_key(&[0]), constructor_args);
+    datastore.insert(contract_key(&[1]), coins.to_raw().to_le_bytes().to_vec());
+    for (key, value) in initial_datastore {
+        let mut entry_key = vec![2];
+        entry_key.extend_from_slice(key);
+        datastore.insert(contract_key(&entry_key), value.clone());
+    }
+
+    // the node rejects the operations exceeding the datastore limits
+    if datastore.len() as u64 > MAX_OPERATION_DATASTORE_ENTRY_COUNT {
+        bail!(
+            "the deployment needs {} datastore entries but operations are limited to {}",
+            datastore.len(),
+            MAX_OPERATION_DATASTORE_ENTRY_COUNT
+        );
+    }
+    for (key, value) in datastore.iter() {
+        if key.len() > MAX_OPERATION_DATASTORE_KEY_LENGTH as usize {
+            bail!(
+                "datastore key of {} bytes above the {} bytes limit of operations",
+                key.len(),
+                MAX_OPERATION_DATASTORE_KEY_LENGTH
+            );
+        }
+        if value.len() as u64 > MAX_OPERATION_DATASTORE_VALUE_LENGTH {
+            bail!(
+                "datastore value of {} bytes above the {} bytes limit of operations",
+                value.len(),
+                MAX_OPERATION_DATASTORE_VALUE_LENGTH
+            );
+        }
+    }
+    Ok(datastore)
+}
+
+/// Storage cost of the ledger entry of the deployed contract, paid by the deployer
+pub(crate) fn storage_cost(contract_size: usize, initial_datastore: &Datastore) -> Result<Amount> {
+    let size = initial_datastore.iter().fold(
+        LEDGER_ENTRY_BASE_SIZE + contract_size,
+        |size, (key, value)| size + LEDGER_ENTRY_DATASTORE_BASE_SIZE + key.len() + value.len(),
+    );
+    LEDGER_COST_PER_BYTE
+        .checked_mul_u64(size as u64)
+        .ok_or_else(|| anyhow!("the storage cost of the contract overflows"))
+}
+
+/// Loads the initial datastore entries of a contract from a JSON file
+pub(crate) async fn load_initial_datastore(path: &Path) -> Result<Datastore> {
+    let data = tokio::fs::read_to_string(path).await?;
+    let entries: BTreeMap<String, String> = serde_json::from_str(&data)
+        .map_err(|e| anyhow!("invalid datastore file {}: {}", path.display(), e))?;
+    Ok(entries
+        .into_iter()
+        .map(|(key, value)| (key.into_bytes(), value.into_bytes()))
+        .collect())
+}
+
+/// Contract to deploy
+pub(crate) struct Deployment {
+    /// bytecode of the deployer
+    pub deployer: Vec<u8>,
+    /// bytecode of the contract
+    pub contract: Vec<u8>,
+    /// arguments of the constructor of the contract
+    pub constructor_args: Vec<u8>,
+    /// initial datastore entries of the contract
+    pub initial_datastore: Datastore,
+    /// coins sent to the contract
+    pub coins: Amount,
+    /// max gas of the deployment, the operation paying the gas used by its dry run plus a margin
+    pub max_gas: u64,
+    /// fee of the operation
+    pub fee: Amount,
+}
+
+fn operation_input(operation: &SecureShareOperation) -> OperationInput {
+    OperationInput {
+        creator_public_key: operation.content_creator_pub_key,
+        serialized_content: operation.serialized_data.clone(),
+        signature: operation.signature,
+    }
+}
+
+/// Checks the balance of `addr`, dry runs the deployment then signs and sends its operation
+pub(crate) async fn deploy(
+    client: &Client,
+    wallet: &Wallet,
+    deployment: Deployment,
+    addr: Address,
+) -> Result<DeploymentSummary> {
+    let cfg = client
+        .public
+        .get_status()
+        .await
+        .map_err(|e| anyhow!("check if your node is running: {}", e))?
+        .config;
+    let storage_cost = storage_cost(deployment.contract.len(), &deployment.initial_datastore)?;
+    let spent = deployment
+        .fee
+        .checked_add(deployment.coins)
+        .and_then(|amount| amount.checked_add(storage_cost))
+        .ok_or_else(|| anyhow!("the total amount of the deployment overflows"))?;
+    let info = client
+        .public
+        .get_addresses(vec![addr])
+        .await
+        .map_err(|e| anyhow!("check if your node is running: {}", e))?
+        .pop()
+        .ok_or_else(|| anyhow!("address {} not found", addr))?;
+    if info.candidate_balance < spent {
+        bail!(
+            "insufficient balance: the deployment spends {} (fee, coins and storage) but address {} has {}",
+            spent,
+            addr,
+            info.candidate_balance
+        );
+    }
+
+    let datastore = deployment_datastore(
+        deployment.contract,
+        deployment.constructor_args,
+        deployment.coins,
+        &deployment.initial_datastore,
+    )?;
+    let expire_period = select_expire_period(&cfg, addr)?;
+    let create_operation = |max_gas: u64| {
+        wallet.create_operation(
+            Operation {
+                fee: deployment.fee,
+                expire_period,
+                sequence: None,
+                sponsor: None,
+                op: OperationType::ExecuteSC {
+                    data: deployment.deployer.clone(),
+                    max_gas,
+                    datastore: datastore.clone(),
+                },
+            },
+            addr,
+        )
+    };
+
+    // dry run with the max gas, then pay the used gas plus a margin
+    let dry_run = client
+        .public
+        .dry_run_operation(operation_input(&create_operation(deployment.max_gas)?))
+        .await
+        .map_err(|e| anyhow!("could not dry run the deployment: {}", e))?;
+    if !dry_run.executed_successfully {
+        bail!(
+            "the deployment would fail: {}",
+            dry_run.error.unwrap_or_default()
+        );
+    }
+    let max_gas = dry_run
+        .gas_cost
+        .saturating_mul(100 + GAS_MARGIN_PERCENT)
+        .saturating_div(100)
+        .clamp(1, deployment.max_gas);
+
+    let operation = create_operation(max_gas)?;
+    let operation_id = client
+        .public
+        .send_operations(vec![operation_input(&operation)])
+        .await
+        .map_err(|e| anyhow!("could not send the deployment: {}", e))?
+        .pop()
+        .ok_or_else(|| anyhow!("the node did not return the id of the deployment"))?;
+    Ok(DeploymentSummary {
+        operation_id,
+        sender: addr,
+        max_gas,
+        fee: deployment.fee,
+        coins: deployment.coins,
+        storage_cost,
+    })
+}
+
+/// Summary of a sent deployment
+#[derive(Debug, Serialize)]
+pub struct DeploymentSummary {
+    /// id of the sent operation
+    pub operation_id: OperationId,
+    /// sender of the operation
+    pub sender: Address,
+    /// max gas of the operation
+    pub max_gas: u64,
+    /// fee of the operation
+    pub fee: Amount,
+    /// coins sent to the contract
+    pub coins: Amount,
+    /// storage cost of the contract, paid by the sender
+    pub storage_cost: Amount,
+}
+
+impl Display for DeploymentSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Sent deployment operation: {}", self.operation_id)?;
+        writeln!(f, "Sender: {}", self.sender)?;
+        writeln!(f, "Max gas: {}", self.max_gas)?;
+        writeln!(f, "Fee: {}", self.fee)?;
+        writeln!(f, "Coins sent to the contract: {}", self.coins)?;
+        writeln!(f, "Storage cost: {}", self.storage_cost)
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_deployment_datastore() {
+    let initial_datastore: Datastore = [(b"owner".to_vec(), b"alice".to_vec())].into();
+    let coins = Amount::from_raw(42);
+    let datastore =
+        deployment_datastore(vec![1, 2, 3], vec![4], coins, &initial_datastore).unwrap();
+    assert_eq!(datastore.get(&vec![0]), Some(&1u64.to_le_bytes().to_vec()));
+    assert_eq!(datastore.get(&contract_key(&[])), Some(&vec![1, 2, 3]));
+    assert_eq!(datastore.get(&contract_key(&[0])), Some(&vec![4]));
+    assert_eq!(
+        datastore.get(&contract_key(&[1])),
+        Some(&42u64.to_le_bytes().to_vec())
+    );
+    assert_eq!(
+        datastore.get(&contract_key(b"\x02owner")),
+        Some(&b"alice".to_vec())
+    );
+
+    assert_eq!(
+        storage_cost(3, &initial_datastore).unwrap(),
+        LEDGER_COST_PER_BYTE
+            .checked_mul_u64(
+                (LEDGER_ENTRY_BASE_SIZE + 3 + LEDGER_ENTRY_DATASTORE_BASE_SIZE + 10) as u64
+            )
+            .unwrap()
+    );
+
+    let too_large: Datastore = [(vec![0; 255], Vec::new())].into();
+    assert!(deployment_datastore(Vec::new(), Vec::new(), coins, &too_large).is_err());
+}
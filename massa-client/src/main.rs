@@ -2,8 +2,8 @@
 //! Massa stateless CLI
 #![warn(missing_docs)]
 #![warn(unused_crate_dependencies)]
-use crate::settings::SETTINGS;
-use anyhow::Result;
+use crate::settings::{Settings, SETTINGS};
+use anyhow::{anyhow, bail, Result};
 use atty::Stream;
 use cmds::Command;
 use console::style;
@@ -17,7 +17,10 @@ use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use structopt::StructOpt;
 
+mod address_book;
 mod cmds;
+mod deployment;
+mod operation_builder;
 mod repl;
 mod settings;
 
@@ -35,6 +38,12 @@ struct Args {
     /// Address to listen on
     #[structopt(long)]
     ip: Option<IpAddr>,
+    /// Name of the node profile to use instead of the default node
+    #[structopt(long)]
+    node: Option<String>,
+    /// Run the command on all the node profiles (only for commands that do not need the wallet)
+    #[structopt(long)]
+    broadcast_to_all: bool,
     /// Command that client would execute (non-interactive mode)
     #[structopt(name = "COMMAND", default_value = "help")]
     command: Command,
@@ -94,7 +103,15 @@ fn main(args: Args) -> anyhow::Result<()> {
     tokio_rt.block_on(run(args))
 }
 
-async fn run(args: Args) -> Result<()> {
+/// HTTP configuration of the client, authenticating with `auth_token` if any
+fn http_config(auth_token: Option<&str>) -> HttpConfig {
+    let mut headers = SETTINGS.client.headers.clone();
+    if let Some(auth_token) = auth_token {
+        headers.push((
+            "Authorization".to_string(),
+            format!("Bearer {}", auth_token),
+        ));
+    }
     let client_config = ClientConfig {
         max_request_body_size: SETTINGS.client.max_request_body_size,
         request_timeout: SETTINGS.client.request_timeout,
@@ -102,29 +119,45 @@ async fn run(args: Args) -> Result<()> {
         certificate_store: SETTINGS.client.certificate_store.clone(),
         id_kind: SETTINGS.client.id_kind.clone(),
         max_log_length: SETTINGS.client.max_log_length,
-        headers: SETTINGS.client.headers.clone(),
+        headers,
     };
 
-    let http_config = HttpConfig {
+    HttpConfig {
         client_config,
         enabled: SETTINGS.client.http.enabled,
-    };
+    }
+}
 
+async fn run(args: Args) -> Result<()> {
     // TODO: move settings loading in another crate ... see #1277
     let settings = SETTINGS.clone();
 
+    let profile = match &args.node {
+        Some(name) => Some(
+            settings
+                .nodes
+                .get(name)
+                .ok_or_else(|| anyhow!("unknown node profile \"{}\"", name))?,
+        ),
+        None => None,
+    };
     let address = match args.ip {
         Some(ip) => ip,
-        None => settings.default_node.ip,
+        None => profile.map_or(settings.default_node.ip, |profile| profile.ip),
     };
     let public_port = match args.public_port {
         Some(public_port) => public_port,
-        None => settings.default_node.public_port,
+        None => profile.map_or(settings.default_node.public_port, |profile| {
+            profile.public_port
+        }),
     };
     let private_port = match args.private_port {
         Some(private_port) => private_port,
-        None => settings.default_node.private_port,
+        None => profile.map_or(settings.default_node.private_port, |profile| {
+            profile.private_port
+        }),
     };
+    let http_config = http_config(profile.and_then(|profile| profile.auth_token.as_deref()));
 
     // Setup panic handlers,
     // and when a panic occurs,
@@ -136,6 +169,10 @@ async fn run(args: Args) -> Result<()> {
         std::process::exit(1);
     }));
 
+    if args.broadcast_to_all && args.command != Command::help {
+        return broadcast_to_all(&args, &settings).await;
+    }
+
     let client = Client::new(address, public_port, private_port, &http_config).await;
     if atty::is(Stream::Stdout) && args.command == Command::help && !args.json {
         // Interactive mode
@@ -187,3 +224,52 @@ async fn run(args: Args) -> Result<()> {
     }
     Ok(())
 }
+
+/// Runs the command on each node profile, one after the other
+async fn broadcast_to_all(args: &Args, settings: &Settings) -> Result<()> {
+    if args.command.is_pwd_needed() {
+        bail!(
+            "{} needs the wallet and cannot be broadcast to all nodes",
+            args.command
+        );
+    }
+    if settings.nodes.is_empty() {
+        bail!("no node profile in the configuration");
+    }
+    let mut json_outputs = serde_json::Map::new();
+    for (name, profile) in &settings.nodes {
+        let client = Client::new(
+            profile.ip,
+            profile.public_port,
+            profile.private_port,
+            &http_config(profile.auth_token.as_deref()),
+        )
+        .await;
+        let result = args
+            .command
+            .run(&client, &mut None, &args.parameters, args.json)
+            .await;
+        if args.json {
+            let output = match result {
+                Ok(output) => erased_serde::serialize(&*output, serde_json::value::Serializer)?,
+                Err(e) => serde_json::to_value(JsonError {
+                    error: format!("{:?}", e),
+                })?,
+            };
+            json_outputs.insert(name.clone(), output);
+        } else {
+            println!(
+                "{}",
+                style(format!("=== {} ({}) ===", name, profile.ip)).green()
+            );
+            match result {
+                Ok(output) => output.pretty_print(),
+                Err(e) => println!("{}", style(format!("Error: {}", e)).red()),
+            }
+        }
+    }
+    if args.json {
+        println!("{}", serde_json::Value::Object(json_outputs));
+    }
+    Ok(())
+}
@@ -0,0 +1,152 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Construction of signed operations.
+//!
+//! The expiry period of an operation is chosen from the current period and the `validity_periods`
+//! of the `[operations]` configuration, and the balance of the sender is checked before signing,
+//! so that the built operations are not rejected for these reasons.
+
+use crate::settings::SETTINGS;
+use anyhow::{anyhow, bail, Result};
+use massa_models::config::CompactConfig;
+use massa_models::secure_share::SecureShareSerializer;
+use massa_models::timeslots::get_current_latest_block_slot;
+use massa_models::{
+    address::Address,
+    amount::Amount,
+    operation::{Operation, OperationId, OperationType, SecureShareOperation},
+    slot::Slot,
+};
+use massa_sdk::Client;
+use massa_serialization::Serializer;
+use massa_wallet::Wallet;
+use serde::Serialize;
+use std::fmt::Display;
+
+/// A signed operation, ready to be sent
+#[derive(Debug, Serialize)]
+pub struct BuiltOperation {
+    /// operation id
+    pub id: OperationId,
+    /// last period at which the operation can be included in a block
+    pub expire_period: u64,
+    /// signed operation, serialized in hexadecimal
+    pub serialized: String,
+}
+
+impl BuiltOperation {
+    pub(crate) fn new(operation: &SecureShareOperation) -> Result<Self> {
+        let mut buffer = Vec::new();
+        SecureShareSerializer::new().serialize(operation, &mut buffer)?;
+        Ok(BuiltOperation {
+            id: operation.id,
+            expire_period: operation.content.expire_period,
+            serialized: buffer.iter().map(|byte| format!("{:02x}", byte)).collect(),
+        })
+    }
+}
+
+impl Display for BuiltOperation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Operation ID: {}", self.id)?;
+        writeln!(f, "Expire period: {}", self.expire_period)?;
+        writeln!(f, "Signed operation: {}", self.serialized)
+    }
+}
+
+/// Chooses the expiry period of an operation created now by `addr`
+pub(crate) fn select_expire_period(cfg: &CompactConfig, addr: Address) -> Result<u64> {
+    let slot = get_current_latest_block_slot(cfg.thread_count, cfg.t0, cfg.genesis_timestamp)?
+        .unwrap_or_else(|| Slot::new(0, 0));
+    // the node rejects operations expiring after its own validity window
+    let validity_periods = SETTINGS
+        .operations
+        .validity_periods
+        .clamp(1, cfg.operation_validity_periods.max(1));
+    let mut expire_period = slot.period + validity_periods;
+    // the slot of the sender thread is already over in the current period
+    if slot.thread >= addr.get_thread(cfg.thread_count) {
+        expire_period += 1;
+    };
+    Ok(expire_period)
+}
+
+/// Fails if the candidate balance or roll count of `addr` does not cover `op` and `fee`
+async fn check_balance(
+    client: &Client,
+    cfg: &CompactConfig,
+    op: &OperationType,
+    fee: Amount,
+    addr: Address,
+) -> Result<()> {
+    let info = client
+        .public
+        .get_addresses(vec![addr])
+        .await
+        .map_err(|e| anyhow!("check if your node is running: {}", e))?
+        .pop()
+        .ok_or_else(|| anyhow!("address {} not found", addr))?;
+    let spent = match op {
+        OperationType::Transaction { amount, .. } => fee.checked_add(*amount),
+        OperationType::RollBuy { roll_count } => cfg
+            .roll_price
+            .checked_mul_u64(*roll_count)
+            .and_then(|price| price.checked_add(fee)),
+        OperationType::RollSell { roll_count } => {
+            if *roll_count > info.candidate_roll_count {
+                bail!(
+                    "address {} only has {} rolls",
+                    addr,
+                    info.candidate_roll_count
+                );
+            }
+            Some(fee)
+        }
+        OperationType::CallSC { coins, .. } => fee.checked_add(*coins),
+        OperationType::ExecuteSC { .. } => Some(fee),
+        OperationType::MultiTransaction { outputs } => outputs
+            .iter()
+            .try_fold(fee, |total, output| total.checked_add(output.amount)),
+        OperationType::ScheduledTransaction { amount, .. } => fee.checked_add(*amount),
+        OperationType::CancelScheduledTransaction { .. } => Some(fee),
+        OperationType::RegisterBlsKey { .. } => Some(fee),
+    }
+    .ok_or_else(|| anyhow!("the total amount of the operation overflows"))?;
+    if info.candidate_balance < spent {
+        bail!(
+            "insufficient balance: the operation spends {} but address {} has {}",
+            spent,
+            addr,
+            info.candidate_balance
+        );
+    }
+    Ok(())
+}
+
+/// Checks the balance of `addr`, then creates and signs an operation with an automatically chosen expiry period
+pub(crate) async fn build_operation(
+    client: &Client,
+    wallet: &Wallet,
+    op: OperationType,
+    fee: Amount,
+    addr: Address,
+) -> Result<SecureShareOperation> {
+    let cfg = client
+        .public
+        .get_status()
+        .await
+        .map_err(|e| anyhow!("check if your node is running: {}", e))?
+        .config;
+    check_balance(client, &cfg, &op, fee, addr).await?;
+    let expire_period = select_expire_period(&cfg, addr)?;
+    Ok(wallet.create_operation(
+        Operation {
+            fee,
+            expire_period,
+            sequence: None,
+            sponsor: None,
+            op,
+        },
+        addr,
+    )?)
+}
@@ -1,28 +1,45 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use crate::address_book::with_labels;
 use crate::ask_password;
-use crate::cmds::{Command, ExtendedWallet};
+use crate::cmds::{Command, ExtendedWallet, RollBatchSummary};
+use crate::deployment::DeploymentSummary;
+use crate::operation_builder::BuiltOperation;
 use crate::settings::SETTINGS;
 use anyhow::Result;
 use console::style;
 use erased_serde::{Serialize, Serializer};
 use massa_api_exports::{
-    address::AddressInfo, block::BlockInfo, datastore::DatastoreEntryOutput,
-    endorsement::EndorsementInfo, execution::ExecuteReadOnlyResponse, node::NodeStatus,
+    address::{AddressInfo, AddressStakingRewards, AddressStorageRent, VerifiedContract},
+    async_pool::ScheduledTransferInfo,
+    block::BlockInfo,
+    datastore::DatastoreEntryOutput,
+    endorsement::EndorsementInfo,
+    execution::ExecuteReadOnlyResponse,
+    node::NodeStatus,
     operation::OperationInfo,
+    profiling::{CpuProfile, HeapStats},
+    selection::AddressSelectionForecast,
 };
+use massa_channel::ChannelStats;
+use massa_final_state::BackupManifest;
+use massa_logging::LogLine;
 use massa_models::composite::PubkeySig;
 use massa_models::output_event::SCOutputEvent;
 use massa_models::prehash::PreHashSet;
 use massa_models::{address::Address, operation::OperationId};
+use massa_network_exports::BanEntry;
 use massa_sdk::Client;
 use massa_signature::{KeyPair, PublicKey};
+use massa_storage::StorageOwnersDump;
+use massa_versioning::MipStatus;
 use massa_wallet::Wallet;
 use rustyline::completion::{Completer, FilenameCompleter, Pair};
 use rustyline::error::ReadlineError;
 use rustyline::validate::MatchingBracketValidator;
 use rustyline::{CompletionType, Config, Editor};
 use rustyline_derive::{Completer, Helper, Highlighter, Hinter, Validator};
+use std::collections::BTreeMap;
 use std::env;
 use std::net::IpAddr;
 use std::path::Path;
@@ -239,13 +256,13 @@ impl dyn Output {
 
 impl Output for Wallet {
     fn pretty_print(&self) {
-        println!("{}", self);
+        println!("{}", with_labels(self));
     }
 }
 
 impl Output for ExtendedWallet {
     fn pretty_print(&self) {
-        println!("{}", self);
+        println!("{}", with_labels(self));
     }
 }
 
@@ -301,18 +318,52 @@ impl Output for NodeStatus {
     }
 }
 
-impl Output for BlockInfo {
+impl Output for StorageOwnersDump {
+    fn pretty_print(&self) {
+        println!("{}", with_labels(self));
+    }
+}
+
+impl Output for BackupManifest {
+    fn pretty_print(&self) {
+        println!("{}", self);
+    }
+}
+
+impl Output for DeploymentSummary {
     fn pretty_print(&self) {
         println!("{}", self);
     }
 }
 
+impl Output for BuiltOperation {
+    fn pretty_print(&self) {
+        println!("{}", self);
+    }
+}
+
+impl Output for RollBatchSummary {
+    fn pretty_print(&self) {
+        // address labels would break the alignment of the table
+        println!("{}", self);
+    }
+}
+
+impl Output for BlockInfo {
+    fn pretty_print(&self) {
+        println!("{}", with_labels(self));
+    }
+}
+
 impl Output for PreHashSet<Address> {
     fn pretty_print(&self) {
         println!(
             "{}",
-            self.iter()
-                .fold("".to_string(), |acc, a| format!("{}{}\n", acc, a))
+            with_labels(
+                &self
+                    .iter()
+                    .fold("".to_string(), |acc, a| format!("{}{}\n", acc, a))
+            )
         )
     }
 }
@@ -320,7 +371,39 @@ impl Output for PreHashSet<Address> {
 impl Output for Vec<AddressInfo> {
     fn pretty_print(&self) {
         for address_info in self {
-            println!("{}", address_info);
+            println!("{}", with_labels(address_info));
+        }
+    }
+}
+
+impl Output for Vec<AddressSelectionForecast> {
+    fn pretty_print(&self) {
+        for forecast in self {
+            println!("{}", with_labels(forecast));
+        }
+    }
+}
+
+impl Output for Vec<AddressStakingRewards> {
+    fn pretty_print(&self) {
+        for address_rewards in self {
+            println!("{}", with_labels(address_rewards));
+        }
+    }
+}
+
+impl Output for Vec<AddressStorageRent> {
+    fn pretty_print(&self) {
+        for storage_rent in self {
+            println!("{}", with_labels(storage_rent));
+        }
+    }
+}
+
+impl Output for Vec<ScheduledTransferInfo> {
+    fn pretty_print(&self) {
+        for transfer in self {
+            println!("{}", with_labels(transfer));
         }
     }
 }
@@ -336,7 +419,7 @@ impl Output for Vec<DatastoreEntryOutput> {
 impl Output for Vec<EndorsementInfo> {
     fn pretty_print(&self) {
         for endorsement_info in self {
-            println!("{}", endorsement_info);
+            println!("{}", with_labels(endorsement_info));
         }
     }
 }
@@ -352,7 +435,7 @@ impl Output for Vec<IpAddr> {
 impl Output for Vec<OperationInfo> {
     fn pretty_print(&self) {
         for operation_info in self {
-            println!("{}", operation_info);
+            println!("{}", with_labels(operation_info));
         }
     }
 }
@@ -360,7 +443,7 @@ impl Output for Vec<OperationInfo> {
 impl Output for Vec<BlockInfo> {
     fn pretty_print(&self) {
         for block_info in self {
-            println!("{}", block_info);
+            println!("{}", with_labels(block_info));
         }
     }
 }
@@ -376,7 +459,15 @@ impl Output for Vec<OperationId> {
 impl Output for Vec<Address> {
     fn pretty_print(&self) {
         for addr in self {
-            println!("{}", addr);
+            println!("{}", with_labels(addr));
+        }
+    }
+}
+
+impl Output for BTreeMap<String, Address> {
+    fn pretty_print(&self) {
+        for (name, address) in self {
+            println!("{}: {}", name, address);
         }
     }
 }
@@ -384,7 +475,71 @@ impl Output for Vec<Address> {
 impl Output for Vec<SCOutputEvent> {
     fn pretty_print(&self) {
         for addr in self {
-            println!("{}", addr);
+            println!("{}", with_labels(addr));
+        }
+    }
+}
+
+impl Output for Vec<ChannelStats> {
+    fn pretty_print(&self) {
+        for stats in self {
+            println!("{}", stats);
+        }
+    }
+}
+
+impl Output for CpuProfile {
+    fn pretty_print(&self) {
+        println!("{}", self);
+    }
+}
+
+impl Output for HeapStats {
+    fn pretty_print(&self) {
+        println!("{}", self);
+    }
+}
+
+impl Output for Vec<LogLine> {
+    fn pretty_print(&self) {
+        if self.is_empty() {
+            println!("No log line kept by the node at this level");
+        }
+        for line in self {
+            println!("{}", line);
+        }
+    }
+}
+
+impl Output for Vec<BanEntry> {
+    fn pretty_print(&self) {
+        if self.is_empty() {
+            println!("No IP range or address banned");
+        }
+        for ban in self {
+            println!("{}", ban);
+        }
+    }
+}
+
+impl Output for Vec<VerifiedContract> {
+    fn pretty_print(&self) {
+        if self.is_empty() {
+            println!("No verified contract");
+        }
+        for contract in self {
+            println!("{}", contract);
+        }
+    }
+}
+
+impl Output for Vec<MipStatus> {
+    fn pretty_print(&self) {
+        if self.is_empty() {
+            println!("No MIP known by the node");
+        }
+        for mip_status in self {
+            println!("{}", mip_status);
         }
     }
 }
@@ -397,6 +552,6 @@ impl Output for PubkeySig {
 
 impl Output for ExecuteReadOnlyResponse {
     fn pretty_print(&self) {
-        println!("{}", self);
+        println!("{}", with_labels(self));
     }
 }
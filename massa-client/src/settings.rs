@@ -1,10 +1,11 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 //! Build here the default client settings from the configuration file toml
+use massa_models::address::Address;
 use massa_models::config::build_massa_settings;
 use massa_time::MassaTime;
 use serde::Deserialize;
-use std::{net::IpAddr, path::PathBuf};
+use std::{collections::BTreeMap, net::IpAddr, path::PathBuf};
 
 lazy_static::lazy_static! {
     pub static ref SETTINGS: Settings = build_massa_settings("massa-client", "MASSA_CLIENT");
@@ -13,10 +14,14 @@ lazy_static::lazy_static! {
 #[derive(Debug, Deserialize, Clone)]
 pub struct Settings {
     pub default_node: DefaultNode,
+    #[serde(default)]
+    pub nodes: BTreeMap<String, NodeProfile>,
     pub history: usize,
     pub history_file_path: PathBuf,
     pub timeout: MassaTime,
     pub client: ClientSettings,
+    pub address_book: AddressBookSettings,
+    pub operations: OperationsSettings,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -26,6 +31,16 @@ pub struct DefaultNode {
     pub public_port: u16,
 }
 
+/// Named node, selected with `--node <name>`
+#[derive(Debug, Deserialize, Clone)]
+pub struct NodeProfile {
+    pub ip: IpAddr,
+    pub private_port: u16,
+    pub public_port: u16,
+    /// sent as a bearer token in the `Authorization` header, for nodes behind an authenticating proxy
+    pub auth_token: Option<String>,
+}
+
 /// Client settings
 /// the client settings.
 #[derive(Debug, Deserialize, Clone)]
@@ -48,6 +63,21 @@ pub struct HttpSettings {
     pub enabled: bool,
 }
 
+/// Address book settings
+#[derive(Debug, Deserialize, Clone)]
+pub struct AddressBookSettings {
+    pub path: PathBuf,
+    pub naming_contract: Option<Address>,
+    pub naming_max_gas: u64,
+}
+
+/// Settings of the operations created by the client
+#[derive(Debug, Deserialize, Clone)]
+pub struct OperationsSettings {
+    /// number of periods during which an operation can be included, capped by the node's own limit
+    pub validity_periods: u64,
+}
+
 #[cfg(test)]
 #[test]
 fn test_load_client_config() {
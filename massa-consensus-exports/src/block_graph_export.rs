@@ -1,5 +1,6 @@
 use massa_models::{
     address::Address,
+    block_header::SecuredHeader,
     block_id::BlockId,
     clique::Clique,
     prehash::{PreHashMap, PreHashSet},
@@ -27,3 +28,39 @@ pub struct BlockGraphExport {
     /// List of maximal cliques of compatible blocks.
     pub max_cliques: Vec<Clique>,
 }
+
+/// Query of a part of the graph as block summaries, sorted by slot then id
+#[derive(Debug, Clone, Default)]
+pub struct BlockGraphQuery {
+    /// only the blocks from this slot (included), if any
+    pub start_slot: Option<Slot>,
+    /// only the blocks before this slot (excluded), if any
+    pub end_slot: Option<Slot>,
+    /// only the blocks after this `(slot, id)` (excluded), to resume a previous query
+    pub after: Option<(Slot, BlockId)>,
+    /// maximal number of returned blocks, if any
+    pub limit: Option<usize>,
+    /// whether the headers of the active blocks are exported
+    pub with_headers: bool,
+}
+
+/// Light export of an active or stale block, without the operations nor the graph structures
+#[derive(Debug, Clone)]
+pub struct ExportBlockSummary {
+    /// id of the block
+    pub id: BlockId,
+    /// slot of the block
+    pub slot: Slot,
+    /// creator of the block
+    pub creator: Address,
+    /// parents of the block
+    pub parents: Vec<BlockId>,
+    /// true if final
+    pub is_final: bool,
+    /// true if discarded as stale
+    pub is_stale: bool,
+    /// true if in the blockclique
+    pub is_in_blockclique: bool,
+    /// header of the block, if requested and the block is active
+    pub header: Option<SecuredHeader>,
+}
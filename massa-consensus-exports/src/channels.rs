@@ -1,11 +1,14 @@
+use massa_channel::EventBus;
 use massa_execution_exports::ExecutionController;
 use massa_models::block::{Block, FilledBlock};
 use massa_models::block_header::BlockHeader;
 use massa_pool_exports::PoolController;
 use massa_pos_exports::SelectorController;
 use massa_protocol_exports::ProtocolCommandSender;
+use massa_versioning::MipStore;
 
 use crate::events::ConsensusEvent;
+use crate::reorg::ChainReorg;
 
 /// Contains links to other modules of the node to be able to interact with them.
 #[derive(Clone)]
@@ -26,4 +29,10 @@ pub struct ConsensusChannels {
     pub block_header_sender: tokio::sync::broadcast::Sender<BlockHeader>,
     /// Channel use by Websocket (if they are enable) to broadcast a new block integrated
     pub filled_block_sender: tokio::sync::broadcast::Sender<FilledBlock>,
+    /// Channel used for Websocket broadcast (if enabled) of the blocks becoming stale or leaving the blockclique
+    pub reorg_sender: tokio::sync::broadcast::Sender<ChainReorg>,
+    /// Versioning state, to check the network version announced by the block headers
+    pub mip_store: MipStore,
+    /// Event bus of the node, on which the blocks becoming final are published
+    pub event_bus: EventBus,
 }
@@ -1,10 +1,10 @@
-use crate::block_graph_export::BlockGraphExport;
+use crate::block_graph_export::{BlockGraphExport, BlockGraphQuery, ExportBlockSummary};
 use crate::{bootstrapable_graph::BootstrapableGraph, error::ConsensusError};
 use massa_models::prehash::PreHashSet;
 use massa_models::streaming_step::StreamingStep;
 use massa_models::{
     block::BlockGraphStatus, block_header::BlockHeader, block_id::BlockId, clique::Clique,
-    secure_share::SecureShare, slot::Slot, stats::ConsensusStats,
+    node::NodeId, secure_share::SecureShare, slot::Slot, stats::ConsensusStats,
 };
 use massa_storage::Storage;
 
@@ -24,6 +24,19 @@ pub trait ConsensusController: Send + Sync {
         end_slot: Option<Slot>,
     ) -> Result<BlockGraphExport, ConsensusError>;
 
+    /// Get the active and stale blocks of a part of the graph as light summaries,
+    /// without exporting the graph structures
+    ///
+    /// # Arguments
+    /// * `query`: the slot range, the resume point, the maximal count and whether the headers are exported
+    ///
+    /// # Returns
+    /// The summaries of the blocks, sorted by slot then id
+    fn get_block_graph_summaries(
+        &self,
+        query: BlockGraphQuery,
+    ) -> Result<Vec<ExportBlockSummary>, ConsensusError>;
+
     /// Get statuses of a list of blocks
     ///
     /// # Arguments
@@ -108,7 +121,13 @@ pub trait ConsensusController: Send + Sync {
     /// # Arguments
     /// * `block_id`: the id of the block to register
     /// * `header`: the header of the block to register
-    fn register_block_header(&self, block_id: BlockId, header: SecureShare<BlockHeader, BlockId>);
+    /// * `source_node_id`: the node that sent the header
+    fn register_block_header(
+        &self,
+        block_id: BlockId,
+        header: SecureShare<BlockHeader, BlockId>,
+        source_node_id: NodeId,
+    );
 
     /// Mark a block as invalid in the graph
     ///
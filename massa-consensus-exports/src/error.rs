@@ -41,6 +41,8 @@ pub enum ConsensusError {
     TransactionError(String),
     /// Protocol error {0}
     ProtocolError(#[from] ProtocolError),
+    /// invalid block header {0}
+    InvalidHeader(String),
 }
 
 /// Internal error
@@ -177,6 +177,7 @@ impl Deserializer<ExportActiveBlock> for ExportActiveBlockDeserializer {
     ///         slot: Slot::new(1, 1),
     ///         parents,
     ///         operation_merkle_root: Hash::compute_from("mno".as_bytes()),
+    ///         announced_version: 0,
     ///         endorsements: vec![
     ///             Endorsement::new_verifiable(
     ///                 Endorsement {
@@ -199,6 +200,7 @@ impl Deserializer<ExportActiveBlock> for ExportActiveBlockDeserializer {
     ///             )
     ///             .unwrap(),
     ///         ],
+    ///         aggregated_endorsements: None,
     ///     },
     ///     BlockHeaderSerializer::new(),
     ///     &keypair,
@@ -11,6 +11,7 @@ pub mod bootstrapable_graph;
 pub mod error;
 pub mod events;
 pub mod export_active_block;
+pub mod reorg;
 
 pub use channels::ConsensusChannels;
 pub use controller_trait::{ConsensusController, ConsensusManager};
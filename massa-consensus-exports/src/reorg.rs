@@ -0,0 +1,32 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Notifications of the changes of the blockclique that are not simple extensions of it.
+
+use massa_models::block_id::BlockId;
+use massa_models::operation::OperationId;
+use massa_models::slot::Slot;
+use serde::{Deserialize, Serialize};
+
+/// Block affected by a reorganization of the blockclique
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReorgBlock {
+    /// block id
+    pub block_id: BlockId,
+    /// slot of the block
+    pub slot: Slot,
+    /// operations of the block, which are not included in the blockclique through it anymore
+    pub operations: Vec<OperationId>,
+}
+
+/// Broadcast when announced blocks become stale or leave the blockclique
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainReorg {
+    /// blocks that became stale: they will never be final
+    pub stale_blocks: Vec<ReorgBlock>,
+    /// blocks that left the blockclique after a clique switch: they may come back to it later
+    pub blocks_left_blockclique: Vec<ReorgBlock>,
+    /// best parents `(block id, period)` per thread before the reorganization
+    pub previous_best_parents: Vec<(BlockId, u64)>,
+    /// best parents `(block id, period)` per thread after the reorganization
+    pub best_parents: Vec<(BlockId, u64)>,
+}
@@ -1,6 +1,8 @@
+use massa_models::{address::Address, checkpoint::TrustedCheckpoint};
 use massa_signature::KeyPair;
 use massa_time::MassaTime;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ConsensusConfig {
@@ -20,6 +22,8 @@ pub struct ConsensusConfig {
     pub max_future_processing_blocks: usize,
     /// Maximum number of blocks allowed in `DependencyWaitingBlocks`.
     pub max_dependency_blocks: usize,
+    /// Maximum number of headers waiting for missing parents kept per source node
+    pub max_orphans_per_node: usize,
     /// max event send wait
     pub max_send_wait: MassaTime,
     /// old blocks are pruned every `block_db_prune_interval`
@@ -54,4 +58,10 @@ pub struct ConsensusConfig {
     pub broadcast_blocks_capacity: usize,
     /// filled blocks sender(channel) capacity
     pub broadcast_filled_blocks_capacity: usize,
+    /// chain reorganizations sender(channel) capacity
+    pub broadcast_reorgs_capacity: usize,
+    /// point of the final history the blocks must agree with, if any
+    pub trusted_checkpoint: Option<TrustedCheckpoint>,
+    /// staking addresses allowed to produce blocks and endorsements in a private network, `None` on a public network
+    pub permissioned_validators: Option<BTreeSet<Address>>,
 }
@@ -20,6 +20,7 @@ impl Default for ConsensusConfig {
             future_block_processing_max_periods: 100,
             max_future_processing_blocks: 100,
             max_dependency_blocks: 2048,
+            max_orphans_per_node: 256,
             max_send_wait: MassaTime::from_millis(100),
             block_db_prune_interval: MassaTime::from_millis(5000),
             max_item_return_count: 100,
@@ -37,6 +38,9 @@ impl Default for ConsensusConfig {
             broadcast_blocks_headers_capacity: 128,
             broadcast_blocks_capacity: 128,
             broadcast_filled_blocks_capacity: 128,
+            broadcast_reorgs_capacity: 128,
+            trusted_checkpoint: None,
+            permissioned_validators: None,
         }
     }
 }
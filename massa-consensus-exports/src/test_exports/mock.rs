@@ -7,15 +7,17 @@ use std::sync::{
 
 use massa_models::{
     block::BlockGraphStatus, block_header::BlockHeader, block_id::BlockId, clique::Clique,
-    prehash::PreHashSet, secure_share::SecureShare, slot::Slot, stats::ConsensusStats,
-    streaming_step::StreamingStep,
+    node::NodeId, prehash::PreHashSet, secure_share::SecureShare, slot::Slot,
+    stats::ConsensusStats, streaming_step::StreamingStep,
 };
 use massa_storage::Storage;
 use massa_time::MassaTime;
 
 use crate::{
-    block_graph_export::BlockGraphExport, bootstrapable_graph::BootstrapableGraph,
-    error::ConsensusError, ConsensusController,
+    block_graph_export::{BlockGraphExport, BlockGraphQuery, ExportBlockSummary},
+    bootstrapable_graph::BootstrapableGraph,
+    error::ConsensusError,
+    ConsensusController,
 };
 
 /// Test tool to mock graph controller responses
@@ -36,6 +38,10 @@ pub enum MockConsensusControllerMessage {
         end_slot: Option<Slot>,
         response_tx: mpsc::Sender<Result<BlockGraphExport, ConsensusError>>,
     },
+    GetBlockGraphSummaries {
+        query: BlockGraphQuery,
+        response_tx: mpsc::Sender<Result<Vec<ExportBlockSummary>, ConsensusError>>,
+    },
     GetCliques {
         response_tx: mpsc::Sender<Vec<Clique>>,
     },
@@ -139,6 +145,19 @@ impl ConsensusController for MockConsensusController {
         response_rx.recv().unwrap()
     }
 
+    fn get_block_graph_summaries(
+        &self,
+        query: BlockGraphQuery,
+    ) -> Result<Vec<ExportBlockSummary>, ConsensusError> {
+        let (response_tx, response_rx) = mpsc::channel();
+        self.0
+            .lock()
+            .unwrap()
+            .send(MockConsensusControllerMessage::GetBlockGraphSummaries { query, response_tx })
+            .unwrap();
+        response_rx.recv().unwrap()
+    }
+
     fn get_block_statuses(&self, ids: &[BlockId]) -> Vec<BlockGraphStatus> {
         let (response_tx, response_rx) = mpsc::channel();
         self.0
@@ -253,7 +272,12 @@ impl ConsensusController for MockConsensusController {
             .unwrap();
     }
 
-    fn register_block_header(&self, block_id: BlockId, header: SecureShare<BlockHeader, BlockId>) {
+    fn register_block_header(
+        &self,
+        block_id: BlockId,
+        header: SecureShare<BlockHeader, BlockId>,
+        _source_node_id: NodeId,
+    ) {
         self.0
             .lock()
             .unwrap()
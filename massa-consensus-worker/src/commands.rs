@@ -1,11 +1,12 @@
 use massa_models::{
-    block_header::BlockHeader, block_id::BlockId, secure_share::SecureShare, slot::Slot,
+    block_header::BlockHeader, block_id::BlockId, node::NodeId, secure_share::SecureShare,
+    slot::Slot,
 };
 use massa_storage::Storage;
 
 #[allow(clippy::large_enum_variant)]
 pub enum ConsensusCommand {
     RegisterBlock(BlockId, Slot, Storage, bool),
-    RegisterBlockHeader(BlockId, SecureShare<BlockHeader, BlockId>),
+    RegisterBlockHeader(BlockId, SecureShare<BlockHeader, BlockId>, NodeId),
     MarkInvalidBlock(BlockId, SecureShare<BlockHeader, BlockId>),
 }
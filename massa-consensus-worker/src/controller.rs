@@ -1,13 +1,17 @@
 use massa_consensus_exports::{
-    block_graph_export::BlockGraphExport, block_status::BlockStatus,
-    bootstrapable_graph::BootstrapableGraph, error::ConsensusError,
-    export_active_block::ExportActiveBlock, ConsensusChannels, ConsensusController,
+    block_graph_export::{BlockGraphExport, BlockGraphQuery, ExportBlockSummary},
+    block_status::BlockStatus,
+    bootstrapable_graph::BootstrapableGraph,
+    error::ConsensusError,
+    export_active_block::ExportActiveBlock,
+    ConsensusChannels, ConsensusController,
 };
 use massa_models::{
     block::{BlockGraphStatus, FilledBlock},
-    block_header::BlockHeader,
+    block_header::{BlockHeader, SecuredHeader},
     block_id::BlockId,
     clique::Clique,
+    node::NodeId,
     operation::{Operation, OperationId},
     prehash::PreHashSet,
     secure_share::SecureShare,
@@ -32,6 +36,7 @@ use crate::{commands::ConsensusCommand, state::ConsensusState};
 #[derive(Clone)]
 pub struct ConsensusControllerImpl {
     command_sender: SyncSender<ConsensusCommand>,
+    header_sender: SyncSender<(BlockId, SecuredHeader, NodeId)>,
     channels: ConsensusChannels,
     shared_state: Arc<RwLock<ConsensusState>>,
     bootstrap_part_size: u64,
@@ -41,6 +46,7 @@ pub struct ConsensusControllerImpl {
 impl ConsensusControllerImpl {
     pub fn new(
         command_sender: SyncSender<ConsensusCommand>,
+        header_sender: SyncSender<(BlockId, SecuredHeader, NodeId)>,
         channels: ConsensusChannels,
         shared_state: Arc<RwLock<ConsensusState>>,
         bootstrap_part_size: u64,
@@ -48,6 +54,7 @@ impl ConsensusControllerImpl {
     ) -> Self {
         Self {
             command_sender,
+            header_sender,
             channels,
             shared_state,
             bootstrap_part_size,
@@ -75,6 +82,22 @@ impl ConsensusController for ConsensusControllerImpl {
             .extract_block_graph_part(start_slot, end_slot)
     }
 
+    /// Get the active and stale blocks of a part of the graph as light summaries
+    ///
+    /// # Arguments:
+    /// * `query`: the slot range, the resume point, the maximal count and whether the headers are exported
+    ///
+    /// # Returns:
+    /// The summaries of the blocks, sorted by slot then id
+    fn get_block_graph_summaries(
+        &self,
+        query: BlockGraphQuery,
+    ) -> Result<Vec<ExportBlockSummary>, ConsensusError> {
+        self.shared_state
+            .read()
+            .extract_block_graph_summaries(&query)
+    }
+
     /// Get statuses of blocks present in the graph
     ///
     /// # Arguments:
@@ -274,7 +297,12 @@ impl ConsensusController for ConsensusControllerImpl {
         }
     }
 
-    fn register_block_header(&self, block_id: BlockId, header: SecureShare<BlockHeader, BlockId>) {
+    fn register_block_header(
+        &self,
+        block_id: BlockId,
+        header: SecureShare<BlockHeader, BlockId>,
+        source_node_id: NodeId,
+    ) {
         if self.broadcast_enabled {
             let _ = self
                 .channels
@@ -282,10 +310,10 @@ impl ConsensusController for ConsensusControllerImpl {
                 .send(header.clone().content);
         }
         if let Err(err) = self
-            .command_sender
-            .try_send(ConsensusCommand::RegisterBlockHeader(block_id, header))
+            .header_sender
+            .try_send((block_id, header, source_node_id))
         {
-            warn!("error trying to register a block header: {}", err);
+            warn!("error trying to pre-validate a block header: {}", err);
         }
     }
 
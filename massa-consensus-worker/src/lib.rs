@@ -17,6 +17,8 @@
 //! The consensus worker is launched and initializes a shared state that contains caches, counters and other info.
 //! The consensus worker wakes up at each slot or when it receives a command.
 //! If a command is received, the worker executes it and goes back to sleep.
+//! Incoming block headers first go through a pre-validation thread that drops the ones with a bad signature, slot or parents
+//! before they reach the consensus worker, so that they don't need to take the consensus locks.
 //!  * When an incoming block header is fed to the module, it registers it and asks the Protocol module for the dependencies of the block (including the full block itself) if needed.
 //!  * When an incoming full block is fed to the module, it registers it and asks the Protocol module for its dependencies if needed.
 //!    * If the dependencies are already available, the module checks if it can validate the block and add it to a clique.
@@ -31,6 +33,7 @@
 mod commands;
 mod controller;
 mod manager;
+mod pre_validation;
 mod state;
 mod worker;
 
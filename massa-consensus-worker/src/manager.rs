@@ -1,22 +1,33 @@
 use massa_consensus_exports::ConsensusManager;
 use std::{sync::mpsc::SyncSender, thread::JoinHandle};
-use tracing::log::info;
+use tracing::log::{info, warn};
+
+use massa_models::{block_header::SecuredHeader, block_id::BlockId, node::NodeId};
 
 use crate::commands::ConsensusCommand;
 
 pub struct ConsensusManagerImpl {
+    pub pre_validation_thread:
+        Option<(SyncSender<(BlockId, SecuredHeader, NodeId)>, JoinHandle<()>)>,
     pub consensus_thread: Option<(SyncSender<ConsensusCommand>, JoinHandle<()>)>,
 }
 
 impl ConsensusManager for ConsensusManagerImpl {
     fn stop(&mut self) {
         info!("stopping consensus worker...");
+        // join the pre-validation thread first as it holds a command sender
+        if let Some((tx, join_handle)) = self.pre_validation_thread.take() {
+            drop(tx);
+            if let Err(err) = join_handle.join() {
+                warn!("consensus pre-validation thread panicked: {:?}", err);
+            }
+        }
         // join the consensus thread
         if let Some((tx, join_handle)) = self.consensus_thread.take() {
             drop(tx);
-            join_handle
-                .join()
-                .expect("consensus thread panicked on try to join");
+            if let Err(err) = join_handle.join() {
+                warn!("consensus thread panicked: {:?}", err);
+            }
         }
         info!("consensus worker stopped");
     }
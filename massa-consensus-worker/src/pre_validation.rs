@@ -0,0 +1,151 @@
+//! Pre-validation stage of the incoming block headers.
+//!
+//! Headers received from the network go through this stage, running in its own thread, before being
+//! forwarded to the consensus worker. It performs the cheap checks that do not need a write access to the graph
+//! (signature, slot, parents) so that garbage is dropped before it gets a chance to take the consensus locks.
+
+use massa_consensus_exports::{
+    block_status::{BlockStatus, DiscardReason},
+    error::ConsensusError,
+    ConsensusConfig,
+};
+use massa_models::{
+    block_header::SecuredHeader, block_id::BlockId, node::NodeId,
+    timeslots::get_latest_block_slot_at_timestamp,
+};
+use massa_time::MassaTime;
+use parking_lot::RwLock;
+use std::sync::{
+    mpsc::{Receiver, SyncSender},
+    Arc,
+};
+use tracing::log::{debug, warn};
+
+use crate::{commands::ConsensusCommand, state::ConsensusState};
+
+/// Worker running the pre-validation of the block headers
+pub struct PreValidationWorker {
+    /// Channel to receive the headers to pre-validate from the controller
+    header_receiver: Receiver<(BlockId, SecuredHeader, NodeId)>,
+    /// Channel to forward the valid headers to the consensus worker
+    command_sender: SyncSender<ConsensusCommand>,
+    /// Configuration of the consensus
+    config: ConsensusConfig,
+    /// State shared with the consensus worker, only read when it is not locked
+    shared_state: Arc<RwLock<ConsensusState>>,
+    /// Number of headers dropped since the start of the worker
+    dropped_headers: u64,
+}
+
+impl PreValidationWorker {
+    pub fn new(
+        header_receiver: Receiver<(BlockId, SecuredHeader, NodeId)>,
+        command_sender: SyncSender<ConsensusCommand>,
+        config: ConsensusConfig,
+        shared_state: Arc<RwLock<ConsensusState>>,
+    ) -> Self {
+        Self {
+            header_receiver,
+            command_sender,
+            config,
+            shared_state,
+            dropped_headers: 0,
+        }
+    }
+
+    /// Pre-validate the incoming headers until the channel is disconnected
+    pub fn run(&mut self) {
+        while let Ok((block_id, header, source_node_id)) = self.header_receiver.recv() {
+            if let Err(err) = self.pre_validate(&header) {
+                self.dropped_headers = self.dropped_headers.saturating_add(1);
+                debug!(
+                    "dropping block header {} during pre-validation ({} dropped so far): {}",
+                    block_id, self.dropped_headers, err
+                );
+                continue;
+            }
+            if let Err(err) = self
+                .command_sender
+                .try_send(ConsensusCommand::RegisterBlockHeader(
+                    block_id,
+                    header,
+                    source_node_id,
+                ))
+            {
+                warn!("error trying to register a block header: {}", err);
+            }
+        }
+    }
+
+    /// Check a header without taking the consensus locks.
+    ///
+    /// The checks that need the graph are only performed if the shared state is not currently locked
+    /// by the consensus worker: they are done again, in any case, when the header is registered.
+    fn pre_validate(&self, header: &SecuredHeader) -> Result<(), ConsensusError> {
+        let slot = header.content.slot;
+
+        // check slot validity
+        if slot.thread >= self.config.thread_count {
+            return Err(ConsensusError::InvalidHeader(format!(
+                "thread {} out of range",
+                slot.thread
+            )));
+        }
+        if slot.period == 0 {
+            return Err(ConsensusError::InvalidHeader(
+                "genesis slot is not allowed".to_string(),
+            ));
+        }
+        let current_slot = get_latest_block_slot_at_timestamp(
+            self.config.thread_count,
+            self.config.t0,
+            self.config.genesis_timestamp,
+            MassaTime::now()?,
+        )?;
+        let max_period = current_slot
+            .map_or(0, |s| s.period)
+            .saturating_add(self.config.future_block_processing_max_periods);
+        if slot.period > max_period {
+            return Err(ConsensusError::InvalidHeader(format!(
+                "slot {} is too far in the future",
+                slot
+            )));
+        }
+
+        // check parents count
+        if header.content.parents.len() != self.config.thread_count as usize {
+            return Err(ConsensusError::InvalidHeader(format!(
+                "expected {} parents, got {}",
+                self.config.thread_count,
+                header.content.parents.len()
+            )));
+        }
+
+        // check signature
+        header.verify_signature()?;
+
+        // check the slot and the parents against the graph, without waiting for the consensus worker
+        if let Some(read_shared_state) = self.shared_state.try_read() {
+            if slot.period <= read_shared_state.latest_final_blocks_periods[slot.thread as usize].1
+            {
+                return Err(ConsensusError::InvalidHeader(format!(
+                    "slot {} is older than the latest final block of its thread",
+                    slot
+                )));
+            }
+            for parent in header.content.parents.iter() {
+                if let Some(BlockStatus::Discarded {
+                    reason: DiscardReason::Invalid(_),
+                    ..
+                }) = read_shared_state.block_statuses.get(parent)
+                {
+                    return Err(ConsensusError::InvalidHeader(format!(
+                        "parent {} is invalid",
+                        parent
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -3,6 +3,7 @@ use std::collections::VecDeque;
 use massa_consensus_exports::{
     block_status::{BlockStatus, DiscardReason},
     error::ConsensusError,
+    reorg::ReorgBlock,
 };
 use massa_logging::massa_trace;
 use massa_models::{block_id::BlockId, clique::Clique, prehash::PreHashSet, slot::Slot};
@@ -99,7 +100,7 @@ impl ConsensusState {
     ) -> Result<(), ConsensusError> {
         if let Some(BlockStatus::Active {
             a_block: active_block,
-            storage: _storage,
+            storage,
         }) = self.block_statuses.remove(block_id)
         {
             self.active_index.remove(block_id);
@@ -149,6 +150,20 @@ impl ConsensusState {
                 "hash": block_id
             });
 
+            // keep its operations for the chain reorganization notification
+            if self.config.broadcast_enabled {
+                let operations = storage
+                    .read_blocks()
+                    .get(block_id)
+                    .map(|block| block.content.operations.clone())
+                    .unwrap_or_default();
+                self.new_stale_reorg_blocks.push(ReorgBlock {
+                    block_id: *block_id,
+                    slot: active_block.slot,
+                    operations,
+                });
+            }
+
             // mark as stale
             self.new_stale_blocks
                 .insert(*block_id, (active_block.creator_address, active_block.slot));
@@ -4,9 +4,10 @@ use std::{
 };
 
 use massa_consensus_exports::{
-    block_graph_export::BlockGraphExport,
-    block_status::{BlockStatus, ExportCompiledBlock, HeaderOrBlock},
+    block_graph_export::{BlockGraphExport, BlockGraphQuery, ExportBlockSummary},
+    block_status::{BlockStatus, DiscardReason, ExportCompiledBlock, HeaderOrBlock},
     error::ConsensusError,
+    reorg::ReorgBlock,
     ConsensusChannels, ConsensusConfig,
 };
 use massa_models::{
@@ -23,7 +24,10 @@ use massa_storage::Storage;
 use massa_time::MassaTime;
 use tracing::debug;
 
+use self::orphans::OrphanPool;
+
 mod graph;
+mod orphans;
 mod process;
 mod process_commands;
 mod prune;
@@ -90,9 +94,15 @@ pub struct ConsensusState {
     pub wishlist: PreHashMap<BlockId, Option<SecuredHeader>>,
     /// previous blockclique notified to Execution
     pub prev_blockclique: PreHashMap<BlockId, Slot>,
+    /// Newly stale blocks, with their operations, to be notified as a chain reorganization (if broadcast is enabled)
+    pub new_stale_reorg_blocks: Vec<ReorgBlock>,
+    /// best parents at the previous notification of the blockclique changes
+    pub prev_best_parents: Vec<(BlockId, u64)>,
     /// Blocks indexed by slot (used for multi-stake limiting). Blocks
     /// should be saved in this map when we receive the header or the full block directly.
     pub nonfinal_active_blocks_per_slot: HashMap<Slot, PreHashSet<BlockId>>,
+    /// Headers waiting for missing parents, with the node that sent them
+    pub orphans: OrphanPool,
 }
 
 impl ConsensusState {
@@ -448,6 +458,86 @@ impl ConsensusState {
         Ok(export)
     }
 
+    /// Extracts the active and stale blocks matching `query` as summaries sorted by slot then id.
+    /// The headers are only cloned for the blocks of the returned page.
+    pub fn extract_block_graph_summaries(
+        &self,
+        query: &BlockGraphQuery,
+    ) -> Result<Vec<ExportBlockSummary>, ConsensusError> {
+        let blockclique = self
+            .max_cliques
+            .iter()
+            .find(|clique| clique.is_blockclique)
+            .ok_or_else(|| ConsensusError::ContainerInconsistency("missing blockclique".into()))?;
+        let selected = |slot: &Slot, id: &BlockId| {
+            query.start_slot.map_or(true, |start| *slot >= start)
+                && query.end_slot.map_or(true, |end| *slot < end)
+                && query.after.map_or(true, |after| (*slot, *id) > after)
+        };
+
+        let mut summaries = Vec::new();
+        for (id, status) in self.block_statuses.iter() {
+            match status {
+                BlockStatus::Active { a_block, .. } if selected(&a_block.slot, id) => {
+                    summaries.push(ExportBlockSummary {
+                        id: *id,
+                        slot: a_block.slot,
+                        creator: a_block.creator_address,
+                        parents: a_block.parents.iter().map(|(parent, _)| *parent).collect(),
+                        is_final: a_block.is_final,
+                        is_stale: false,
+                        is_in_blockclique: blockclique.block_ids.contains(id),
+                        header: None,
+                    });
+                }
+                BlockStatus::Discarded {
+                    slot,
+                    creator,
+                    parents,
+                    reason: DiscardReason::Stale,
+                    ..
+                } if selected(slot, id) => {
+                    summaries.push(ExportBlockSummary {
+                        id: *id,
+                        slot: *slot,
+                        creator: *creator,
+                        parents: parents.clone(),
+                        is_final: false,
+                        is_stale: true,
+                        is_in_blockclique: false,
+                        header: None,
+                    });
+                }
+                _ => continue,
+            }
+        }
+        summaries.sort_unstable_by_key(|summary| (summary.slot, summary.id));
+        if let Some(limit) = query.limit {
+            summaries.truncate(limit);
+        }
+
+        if query.with_headers {
+            for summary in summaries.iter_mut() {
+                if let Some(BlockStatus::Active { storage, .. }) =
+                    self.block_statuses.get(&summary.id)
+                {
+                    let header = storage
+                        .read_blocks()
+                        .get(&summary.id)
+                        .map(|block| block.content.header.clone())
+                        .ok_or_else(|| {
+                            ConsensusError::MissingBlock(format!(
+                                "missing block in extract_block_graph_summaries: {}",
+                                summary.id
+                            ))
+                        })?;
+                    summary.header = Some(header);
+                }
+            }
+        }
+        Ok(summaries)
+    }
+
     /// Gets all stored final blocks, not only the still-useful ones
     /// This is used when initializing Execution from Consensus.
     /// Since the Execution bootstrap snapshot is older than the Consensus snapshot,
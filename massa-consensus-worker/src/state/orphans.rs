@@ -0,0 +1,160 @@
+use std::collections::{HashMap, VecDeque};
+
+use massa_consensus_exports::{block_status::BlockStatus, error::ConsensusError};
+use massa_models::{block_id::BlockId, node::NodeId, prehash::PreHashMap};
+use massa_time::MassaTime;
+use tracing::debug;
+
+use super::ConsensusState;
+
+/// Pool of the headers that arrived before their parents.
+///
+/// The headers themselves stay in the graph with a `WaitingForDependencies` status, so that they are
+/// reprocessed automatically when their missing parents become active. The pool only keeps track of the node
+/// that sent each of them, to enforce a quota per node, and of the timestamps used for the orphan rate stats.
+#[derive(Clone, Default)]
+pub struct OrphanPool {
+    /// node that sent each orphan
+    sources: PreHashMap<BlockId, NodeId>,
+    /// orphans sent by each node, oldest first
+    by_node: HashMap<NodeId, VecDeque<BlockId>>,
+    /// times at which headers became orphans
+    pub added_stats: VecDeque<MassaTime>,
+    /// times at which orphans got all their parents and were reprocessed
+    pub reprocessed_stats: VecDeque<MassaTime>,
+    /// times at which orphans were dropped, because of the quota of their node or because they were pruned
+    pub dropped_stats: VecDeque<MassaTime>,
+}
+
+impl OrphanPool {
+    /// Number of orphans in the pool
+    pub fn count(&self) -> usize {
+        self.sources.len()
+    }
+
+    /// Whether the block is in the pool
+    pub fn contains(&self, block_id: &BlockId) -> bool {
+        self.sources.contains_key(block_id)
+    }
+
+    /// Add an orphan sent by `source`.
+    ///
+    /// # Returns:
+    /// The oldest orphans of `source` that exceed `max_orphans_per_node` and must be dropped from the graph
+    pub fn insert(
+        &mut self,
+        block_id: BlockId,
+        source: NodeId,
+        max_orphans_per_node: usize,
+        now: MassaTime,
+    ) -> Vec<BlockId> {
+        if self.sources.contains_key(&block_id) {
+            return Vec::new();
+        }
+        self.sources.insert(block_id, source);
+        self.added_stats.push_back(now);
+        let node_orphans = self.by_node.entry(source).or_default();
+        node_orphans.push_back(block_id);
+        let mut evicted = Vec::new();
+        while node_orphans.len() > max_orphans_per_node {
+            if let Some(evicted_id) = node_orphans.pop_front() {
+                self.sources.remove(&evicted_id);
+                self.dropped_stats.push_back(now);
+                evicted.push(evicted_id);
+            }
+        }
+        if node_orphans.is_empty() {
+            self.by_node.remove(&source);
+        }
+        evicted
+    }
+
+    /// Remove an orphan whose missing parents all arrived
+    pub fn resolve(&mut self, block_id: &BlockId, now: MassaTime) {
+        if self.remove(block_id) {
+            self.reprocessed_stats.push_back(now);
+        }
+    }
+
+    /// Remove the orphans that are no longer waiting for their dependencies (pruned or discarded)
+    pub fn retain_waiting<F: Fn(&BlockId) -> bool>(&mut self, is_waiting: F, now: MassaTime) {
+        let gone: Vec<BlockId> = self
+            .sources
+            .keys()
+            .filter(|block_id| !is_waiting(block_id))
+            .copied()
+            .collect();
+        for block_id in gone {
+            if self.remove(&block_id) {
+                self.dropped_stats.push_back(now);
+            }
+        }
+    }
+
+    /// Remove the stats older than `start_time`
+    pub fn prune_stats(&mut self, start_time: MassaTime) {
+        for stats in [
+            &mut self.added_stats,
+            &mut self.reprocessed_stats,
+            &mut self.dropped_stats,
+        ] {
+            while let Some(t) = stats.front() {
+                if t < &start_time {
+                    stats.pop_front();
+                } else {
+                    break;
+                }
+            }
+        }
+    }
+
+    fn remove(&mut self, block_id: &BlockId) -> bool {
+        let source = match self.sources.remove(block_id) {
+            Some(source) => source,
+            None => return false,
+        };
+        if let Some(node_orphans) = self.by_node.get_mut(&source) {
+            node_orphans.retain(|id| id != block_id);
+            if node_orphans.is_empty() {
+                self.by_node.remove(&source);
+            }
+        }
+        true
+    }
+}
+
+impl ConsensusState {
+    /// Add the header to the orphan pool if it is waiting for missing parents,
+    /// dropping the oldest orphans of `source` above its quota.
+    pub fn note_orphan(&mut self, block_id: BlockId, source: NodeId) -> Result<(), ConsensusError> {
+        let is_orphan = match self.block_statuses.get(&block_id) {
+            Some(BlockStatus::WaitingForDependencies {
+                unsatisfied_dependencies,
+                ..
+            }) => unsatisfied_dependencies.iter().any(|dep| dep != &block_id),
+            _ => false,
+        };
+        if !is_orphan {
+            return Ok(());
+        }
+        let evicted = self.orphans.insert(
+            block_id,
+            source,
+            self.config.max_orphans_per_node,
+            MassaTime::now()?,
+        );
+        for evicted_id in evicted {
+            debug!(
+                "dropping orphan block {} sent by {}: too many orphans from this node",
+                evicted_id, source
+            );
+            if let Some(BlockStatus::WaitingForDependencies { .. }) =
+                self.block_statuses.get(&evicted_id)
+            {
+                self.block_statuses.remove(&evicted_id);
+                self.waiting_for_dependencies_index.remove(&evicted_id);
+            }
+        }
+        Ok(())
+    }
+}
@@ -3,9 +3,11 @@ use std::{
     mem,
 };
 
+use massa_channel::{NodeEvent, Topic};
 use massa_consensus_exports::{
     block_status::{BlockStatus, DiscardReason, HeaderOrBlock},
     error::ConsensusError,
+    reorg::{ChainReorg, ReorgBlock},
 };
 use massa_logging::massa_trace;
 use massa_models::{
@@ -274,6 +276,7 @@ impl ConsensusState {
                 "block_id": block_id
             });
             self.to_propagate.insert(block_id, storage.clone());
+            let now = MassaTime::now()?;
             for itm_block_id in self.waiting_for_dependencies_index.iter() {
                 if let Some(BlockStatus::WaitingForDependencies {
                     header_or_block,
@@ -284,6 +287,14 @@ impl ConsensusState {
                     if unsatisfied_dependencies.remove(&block_id) {
                         // a dependency was satisfied: retry
                         reprocess.insert((header_or_block.get_slot(), *itm_block_id));
+                        // an orphan with all its parents is no longer an orphan
+                        if self.orphans.contains(itm_block_id)
+                            && unsatisfied_dependencies
+                                .iter()
+                                .all(|dep| dep == itm_block_id)
+                        {
+                            self.orphans.resolve(itm_block_id, now);
+                        }
                     }
                 }
             }
@@ -588,7 +599,8 @@ impl ConsensusState {
         }
         // Overwrite previous blockclique.
         // Should still be done even if unchanged because elements were removed from it above.
-        self.prev_blockclique = new_blockclique.clone();
+        let left_blockclique = mem::replace(&mut self.prev_blockclique, new_blockclique.clone());
+        self.notify_reorg(left_blockclique);
 
         if finalized_blocks.is_empty() && !blockclique_changed {
             // There are no changes (neither block finalizations not blockclique changes) to send to execution.
@@ -609,6 +621,49 @@ impl ConsensusState {
             );
     }
 
+    /// Broadcast the blocks that became stale or left the blockclique, if any and if broadcast is enabled.
+    ///
+    /// # Arguments:
+    /// * `left_blockclique`: blocks of the previous blockclique that are not in the new one
+    fn notify_reorg(&mut self, left_blockclique: PreHashMap<BlockId, Slot>) {
+        if !self.config.broadcast_enabled {
+            return;
+        }
+        let stale_blocks = mem::take(&mut self.new_stale_reorg_blocks);
+        // final and stale blocks also leave the blockclique: keep the ones that are still candidates
+        let mut blocks_left_blockclique: Vec<ReorgBlock> = left_blockclique
+            .into_iter()
+            .filter_map(
+                |(block_id, slot)| match self.block_statuses.get(&block_id) {
+                    Some(BlockStatus::Active { a_block, storage }) if !a_block.is_final => {
+                        Some(ReorgBlock {
+                            block_id,
+                            slot,
+                            operations: storage
+                                .read_blocks()
+                                .get(&block_id)
+                                .map(|block| block.content.operations.clone())
+                                .unwrap_or_default(),
+                        })
+                    }
+                    _ => None,
+                },
+            )
+            .collect();
+        blocks_left_blockclique.sort_unstable_by_key(|block| block.slot);
+        let previous_best_parents =
+            mem::replace(&mut self.prev_best_parents, self.best_parents.clone());
+        if stale_blocks.is_empty() && blocks_left_blockclique.is_empty() {
+            return;
+        }
+        let _reorg_receivers_count = self.channels.reorg_sender.send(ChainReorg {
+            stale_blocks,
+            blocks_left_blockclique,
+            previous_best_parents,
+            best_parents: self.best_parents.clone(),
+        });
+    }
+
     /// call me if the block database changed
     /// Processing of final blocks, pruning.
     ///
@@ -660,6 +715,12 @@ impl ConsensusState {
                 {
                     // add to final blocks to notify execution
                     final_block_slots.insert(a_block.slot, b_id);
+                    self.channels
+                        .event_bus
+                        .publish_with(Topic::BlockFinalized, || NodeEvent::BlockFinalized {
+                            block_id: b_id,
+                            slot: a_block.slot,
+                        });
 
                     // add to stats
                     let block_is_from_protocol = self
@@ -5,7 +5,7 @@ use massa_consensus_exports::{
     error::ConsensusError,
 };
 use massa_logging::massa_trace;
-use massa_models::{block_header::SecuredHeader, block_id::BlockId, slot::Slot};
+use massa_models::{block_header::SecuredHeader, block_id::BlockId, node::NodeId, slot::Slot};
 use massa_storage::Storage;
 use massa_time::MassaTime;
 use tracing::debug;
@@ -18,6 +18,7 @@ impl ConsensusState {
     /// # Arguments:
     /// * `block_id`: the block id
     /// * `header`: the header to register
+    /// * `source_node_id`: the node that sent the header
     /// * `current_slot`: the slot when this function is called
     ///
     /// # Returns:
@@ -26,6 +27,7 @@ impl ConsensusState {
         &mut self,
         block_id: BlockId,
         header: SecuredHeader,
+        source_node_id: NodeId,
         current_slot: Option<Slot>,
     ) -> Result<(), ConsensusError> {
         // ignore genesis blocks
@@ -65,6 +67,9 @@ impl ConsensusState {
         // process
         self.rec_process(to_ack, current_slot)?;
 
+        // keep track of the header if it is waiting for its parents
+        self.note_orphan(block_id, source_node_id)?;
+
         Ok(())
     }
 
@@ -9,6 +9,7 @@ use massa_models::{
     prehash::{PreHashMap, PreHashSet},
     slot::Slot,
 };
+use massa_time::MassaTime;
 use tracing::debug;
 
 use super::ConsensusState;
@@ -348,8 +349,13 @@ impl ConsensusState {
         // Step 2: prune slot waiting blocks
         self.prune_slot_waiting();
 
-        // Step 3: prune dependency waiting blocks
+        // Step 3: prune dependency waiting blocks and the orphans that went with them
         self.prune_waiting_for_dependencies()?;
+        let waiting_for_dependencies_index = &self.waiting_for_dependencies_index;
+        self.orphans.retain_waiting(
+            |block_id| waiting_for_dependencies_index.contains(block_id),
+            MassaTime::now()?,
+        );
 
         // Step 4: prune discarded
         self.prune_discarded()?;
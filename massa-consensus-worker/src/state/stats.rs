@@ -1,8 +1,15 @@
 use super::ConsensusState;
-use massa_consensus_exports::error::ConsensusError;
-use massa_models::stats::ConsensusStats;
+use massa_consensus_exports::{
+    block_status::{BlockStatus, DiscardReason, HeaderOrBlock},
+    error::ConsensusError,
+};
+use massa_models::{
+    active_block::ActiveBlock,
+    block_id::BlockId,
+    stats::{ConsensusMemoryStats, ConsensusStats},
+};
 use massa_time::MassaTime;
-use std::cmp::max;
+use std::{cmp::max, collections::VecDeque, mem::size_of};
 
 #[cfg(not(feature = "sandbox"))]
 use tracing::log::warn;
@@ -29,15 +36,93 @@ impl ConsensusState {
             .filter(|t| **t >= timespan_start && **t < timespan_end)
             .count() as u64;
         let clique_count = self.get_clique_count() as u64;
+        let count_in_timespan = |stats: &VecDeque<MassaTime>| {
+            stats
+                .iter()
+                .filter(|t| **t >= timespan_start && **t < timespan_end)
+                .count() as u64
+        };
         Ok(ConsensusStats {
             final_block_count,
             stale_block_count,
             clique_count,
+            orphan_count: self.orphans.count() as u64,
+            orphan_added_count: count_in_timespan(&self.orphans.added_stats),
+            orphan_reprocessed_count: count_in_timespan(&self.orphans.reprocessed_stats),
+            orphan_dropped_count: count_in_timespan(&self.orphans.dropped_stats),
+            memory: self.get_memory_stats(),
             start_timespan: timespan_start,
             end_timespan: timespan_end,
         })
     }
 
+    /// Estimates the memory used by each structure of the graph.
+    /// Each block status is counted with its entries in the indexes, and the blocks and headers at their serialized size.
+    pub fn get_memory_stats(&self) -> ConsensusMemoryStats {
+        let id_size = size_of::<BlockId>();
+        // entry of the status map and of the index of the status
+        let entry_size = id_size * 2 + size_of::<BlockStatus>();
+        let blocks = self.storage.read_blocks();
+        let block_size = |block_id: &BlockId| {
+            blocks
+                .get(block_id)
+                .map_or(0, |block| block.serialized_data.len())
+        };
+        let header_or_block_size = |header_or_block: &HeaderOrBlock| match header_or_block {
+            HeaderOrBlock::Header(header) => header.serialized_data.len(),
+            HeaderOrBlock::Block { id, .. } => block_size(id),
+        };
+
+        let mut stats = ConsensusMemoryStats::default();
+        for (block_id, status) in &self.block_statuses {
+            match status {
+                BlockStatus::Active { a_block, .. } => stats.active_blocks.add(
+                    entry_size
+                        + size_of::<ActiveBlock>()
+                        + a_block.parents.capacity() * size_of::<(BlockId, u64)>()
+                        + a_block
+                            .children
+                            .iter()
+                            .map(|children| children.capacity() * (id_size + size_of::<u64>()))
+                            .sum::<usize>()
+                        + a_block.descendants.capacity() * id_size
+                        + block_size(block_id),
+                ),
+                BlockStatus::Discarded {
+                    parents, reason, ..
+                } => stats.discarded_index.add(
+                    entry_size
+                        + parents.capacity() * id_size
+                        + match reason {
+                            DiscardReason::Invalid(message) => message.capacity(),
+                            _ => 0,
+                        },
+                ),
+                BlockStatus::Incoming(header_or_block) => stats
+                    .incoming
+                    .add(entry_size + header_or_block_size(header_or_block)),
+                BlockStatus::WaitingForDependencies {
+                    header_or_block,
+                    unsatisfied_dependencies,
+                    ..
+                } => stats.waiting_for_dependencies.add(
+                    entry_size
+                        + header_or_block_size(header_or_block)
+                        + unsatisfied_dependencies.capacity() * id_size,
+                ),
+                BlockStatus::WaitingForSlot(header_or_block) => stats
+                    .waiting_for_slot
+                    .add(entry_size + header_or_block_size(header_or_block)),
+            }
+        }
+        for incompatibilities in self.gi_head.values() {
+            stats
+                .incompatibility_graph
+                .add(id_size + incompatibilities.capacity() * id_size);
+        }
+        stats
+    }
+
     /// Must be called each tick to update stats. Will detect if a desynchronization happened
     pub fn stats_tick(&mut self) -> Result<(), ConsensusError> {
         // check if there are any final blocks is coming from protocol
@@ -92,6 +177,7 @@ impl ConsensusState {
                 break;
             }
         }
+        self.orphans.prune_stats(start_time);
         Ok(())
     }
 }
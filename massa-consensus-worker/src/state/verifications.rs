@@ -6,10 +6,14 @@ use massa_consensus_exports::{
 };
 use massa_logging::massa_trace;
 use massa_models::{
-    block::SecureShareBlock, block_header::SecuredHeader, block_id::BlockId, prehash::PreHashSet,
-    slot::Slot,
+    address::Address, aggregate_signature::AggregateSignature, block::SecureShareBlock,
+    block_header::SecuredHeader, block_id::BlockId, config::AGGREGATED_ENDORSEMENTS_VERSION,
+    endorsement_signature::EndorsementSignature, prehash::PreHashSet, slot::Slot,
+    timeslots::get_block_slot_timestamp,
 };
+use massa_signature::BlsPublicKey;
 use massa_storage::Storage;
+use massa_versioning::MipComponent;
 
 /// Possible output of a header check
 #[derive(Debug)]
@@ -248,6 +252,7 @@ impl ConsensusState {
     /// - Slot above 0.
     /// - Valid thread.
     /// - Check that the block is older than the latest final one in thread.
+    /// - Check that the block does not contradict the trusted checkpoint.
     /// - Check that the block slot is not too much into the future,
     ///   as determined by the configuration `future_block_processing_max_periods`.
     /// - Check if it was the creator's turn to create this block.
@@ -283,6 +288,18 @@ impl ConsensusState {
             return Ok(HeaderCheckOutcome::Discard(DiscardReason::Stale));
         }
 
+        // check that the block is not part of another history than the trusted checkpoint
+        if let Some(checkpoint) = &self.config.trusted_checkpoint {
+            if checkpoint.conflicts_with(&header.content.slot, block_id) {
+                return Ok(HeaderCheckOutcome::Discard(DiscardReason::Invalid(
+                    format!(
+                        "Block at slot {} contradicts the trusted checkpoint",
+                        header.content.slot
+                    ),
+                )));
+            }
+        }
+
         // check if block slot is too much in the future
         if let Some(cur_slot) = current_slot {
             if header.content.slot.period
@@ -304,6 +321,15 @@ impl ConsensusState {
             Ok(draw) => draw,
             Err(_) => return Ok(HeaderCheckOutcome::WaitForSlot), // TODO properly handle PoS errors
         };
+        // the draws only select permissioned validators, but do not trust them for it
+        if !self.is_permissioned(&creator_addr) {
+            return Ok(HeaderCheckOutcome::Discard(DiscardReason::Invalid(
+                format!(
+                    "Block creator {} is not a permissioned validator",
+                    creator_addr
+                ),
+            )));
+        }
         if creator_addr != slot_draw_address {
             // it was not the creator's turn to create a block for this slot
             return Ok(HeaderCheckOutcome::Discard(DiscardReason::Invalid(
@@ -311,6 +337,26 @@ impl ConsensusState {
             )));
         }
 
+        // check that the block producer is ready for the consensus version active at the block slot
+        let slot_timestamp = get_block_slot_timestamp(
+            self.config.thread_count,
+            self.config.t0,
+            self.config.genesis_timestamp,
+            header.content.slot,
+        )?;
+        let active_version = self
+            .channels
+            .mip_store
+            .component_version_at(MipComponent::Consensus, slot_timestamp);
+        if header.content.announced_version < active_version {
+            return Ok(HeaderCheckOutcome::Discard(DiscardReason::Invalid(
+                format!(
+                    "Announced version {} is older than the active consensus version {} at slot {}",
+                    header.content.announced_version, active_version, header.content.slot
+                ),
+            )));
+        }
+
         // check if block is in the future: queue it
         // note: do it after testing signature + draw to prevent queue flooding/DoS
         // note: Some(x) > None
@@ -457,7 +503,8 @@ impl ConsensusState {
         })?;
 
         // check endorsements
-        match self.check_endorsements(header)? {
+        let aggregating = active_version >= AGGREGATED_ENDORSEMENTS_VERSION;
+        match self.check_endorsements(header, aggregating)? {
             EndorsementsCheckOutcome::Proceed => {}
             EndorsementsCheckOutcome::Discard(reason) => {
                 return Ok(HeaderCheckOutcome::Discard(reason))
@@ -570,10 +617,29 @@ impl ConsensusState {
     /// check endorsements:
     /// * endorser was selected for that (slot, index)
     /// * endorsed slot is `parent_in_own_thread` slot
+    /// * the endorsements are aggregated if and only if `aggregating`, the aggregated version of the consensus being active
     pub fn check_endorsements(
         &self,
         header: &SecuredHeader,
+        aggregating: bool,
     ) -> Result<EndorsementsCheckOutcome, ConsensusError> {
+        if aggregating && !header.content.endorsements.is_empty() {
+            return Ok(EndorsementsCheckOutcome::Discard(DiscardReason::Invalid(
+                format!(
+                    "non-aggregated endorsements in header in slot: {}",
+                    header.content.slot
+                ),
+            )));
+        }
+        if !aggregating && header.content.aggregated_endorsements.is_some() {
+            return Ok(EndorsementsCheckOutcome::Discard(DiscardReason::Invalid(
+                format!(
+                    "aggregated endorsements in header in slot: {} before their activation",
+                    header.content.slot
+                ),
+            )));
+        }
+
         // check endorsements
         let endorsement_draws = match self
             .channels
@@ -583,7 +649,19 @@ impl ConsensusState {
             Ok(sel) => sel.endorsements,
             Err(_) => return Ok(EndorsementsCheckOutcome::WaitForSlot),
         };
+        if let Some(aggregate) = &header.content.aggregated_endorsements {
+            return Ok(self.check_aggregated_endorsements(header, aggregate, &endorsement_draws));
+        }
         for endorsement in header.content.endorsements.iter() {
+            if !self.is_permissioned(&endorsement.content_creator_address) {
+                return Ok(EndorsementsCheckOutcome::Discard(DiscardReason::Invalid(
+                    format!(
+                        "endorser {} is not a permissioned validator",
+                        endorsement.content_creator_address
+                    ),
+                )));
+            }
+
             // check that the draw is correct
             if endorsement.content_creator_address
                 != endorsement_draws[endorsement.content.index as usize]
@@ -605,4 +683,74 @@ impl ConsensusState {
 
         Ok(EndorsementsCheckOutcome::Proceed)
     }
+
+    /// Whether `address` can produce blocks and endorsements, always true on a public network
+    fn is_permissioned(&self, address: &Address) -> bool {
+        self.config
+            .permissioned_validators
+            .as_ref()
+            .map_or(true, |validators| validators.contains(address))
+    }
+
+    /// check aggregated endorsements:
+    /// * there is one signer bit per endorser drawn for the slot
+    /// * the signers registered a BLS key usable at the slot
+    /// * the aggregate is the signature by the signers of the parent in the thread of the block
+    fn check_aggregated_endorsements(
+        &self,
+        header: &SecuredHeader,
+        aggregate: &AggregateSignature,
+        endorsement_draws: &[Address],
+    ) -> EndorsementsCheckOutcome {
+        let slot = header.content.slot;
+        if aggregate.signers.len() != endorsement_draws.len() {
+            return EndorsementsCheckOutcome::Discard(DiscardReason::Invalid(format!(
+                "{} signer bits for the {} endorsers of slot {}",
+                aggregate.signers.len(),
+                endorsement_draws.len(),
+                slot
+            )));
+        }
+        // only the keys of the signers are read
+        let signers: Vec<Address> = aggregate
+            .signers
+            .iter_ones()
+            .map(|index| endorsement_draws[index])
+            .collect();
+        let registered_keys = self
+            .channels
+            .execution_controller
+            .get_registered_bls_keys(&signers);
+        let mut public_keys: Vec<BlsPublicKey> = Vec::with_capacity(signers.len());
+        for (signer, registered_key) in signers.iter().zip(registered_keys) {
+            if !self.is_permissioned(signer) {
+                return EndorsementsCheckOutcome::Discard(DiscardReason::Invalid(format!(
+                    "endorser {} is not a permissioned validator",
+                    signer
+                )));
+            }
+            match registered_key {
+                Some(key) if key.is_usable_at(&slot, self.config.periods_per_cycle) => {
+                    public_keys.push(key.public_key)
+                }
+                _ => {
+                    return EndorsementsCheckOutcome::Discard(DiscardReason::Invalid(format!(
+                        "endorser {} of slot {} has no usable BLS key",
+                        signer, slot
+                    )))
+                }
+            }
+        }
+        let hash = EndorsementSignature::compute_signed_hash(
+            &slot,
+            &header.content.parents[slot.thread as usize],
+        );
+        if let Err(err) = aggregate.signature.verify_aggregate(&hash, &public_keys) {
+            return EndorsementsCheckOutcome::Discard(DiscardReason::Invalid(format!(
+                "invalid aggregated endorsements in header in slot {}: {}",
+                slot, err
+            )));
+        }
+        EndorsementsCheckOutcome::Proceed
+    }
 }
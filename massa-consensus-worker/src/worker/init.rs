@@ -45,7 +45,9 @@ pub fn create_genesis_block(
             slot: Slot::new(0, thread_number),
             parents: Vec::new(),
             operation_merkle_root: Hash::compute_from(&Vec::new()),
+            announced_version: 0,
             endorsements: Vec::new(),
+            aggregated_endorsements: None,
         },
         BlockHeaderSerializer::new(),
         keypair,
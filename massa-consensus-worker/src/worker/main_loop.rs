@@ -29,8 +29,13 @@ impl ConsensusWorker {
     fn manage_command(&mut self, command: ConsensusCommand) -> Result<(), ConsensusError> {
         let mut write_shared_state = self.shared_state.write();
         match command {
-            ConsensusCommand::RegisterBlockHeader(block_id, header) => {
-                write_shared_state.register_block_header(block_id, header, self.previous_slot)?;
+            ConsensusCommand::RegisterBlockHeader(block_id, header, source_node_id) => {
+                write_shared_state.register_block_header(
+                    block_id,
+                    header,
+                    source_node_id,
+                    self.previous_slot,
+                )?;
                 write_shared_state.block_db_changed()
             }
             ConsensusCommand::RegisterBlock(block_id, slot, block_storage, created) => {
@@ -17,6 +17,7 @@ use std::time::Instant;
 use crate::commands::ConsensusCommand;
 use crate::controller::ConsensusControllerImpl;
 use crate::manager::ConsensusManagerImpl;
+use crate::pre_validation::PreValidationWorker;
 use crate::state::ConsensusState;
 
 /// The consensus worker structure that contains all information and tools for the consensus worker thread.
@@ -96,7 +97,10 @@ pub fn start_consensus_worker(
             config.stats_timespan,
         ),
         prev_blockclique: Default::default(),
+        new_stale_reorg_blocks: Default::default(),
+        prev_best_parents: Default::default(),
         nonfinal_active_blocks_per_slot: Default::default(),
+        orphans: Default::default(),
     }));
 
     let shared_state_cloned = shared_state.clone();
@@ -108,12 +112,22 @@ pub fn start_consensus_worker(
         .spawn(move || consensus_worker.run())
         .expect("Can't spawn consensus thread.");
 
+    let (header_tx, header_rx) = mpsc::sync_channel(CHANNEL_SIZE);
+    let mut pre_validation_worker =
+        PreValidationWorker::new(header_rx, tx.clone(), config.clone(), shared_state.clone());
+    let pre_validation_thread = thread::Builder::new()
+        .name("consensus pre-validation".into())
+        .spawn(move || pre_validation_worker.run())
+        .expect("Can't spawn consensus pre-validation thread.");
+
     let manager = ConsensusManagerImpl {
+        pre_validation_thread: Some((header_tx.clone(), pre_validation_thread)),
         consensus_thread: Some((tx.clone(), consensus_thread)),
     };
 
     let controller = ConsensusControllerImpl::new(
         tx,
+        header_tx,
         channels,
         shared_state,
         bootstrap_part_size,
@@ -4,10 +4,17 @@
 
 use crate::types::ReadOnlyExecutionRequest;
 use crate::ExecutionError;
-use crate::{ExecutionAddressInfo, ReadOnlyExecutionOutput};
+use crate::{
+    ExecutionAddressInfo, FinalBlockOperations, OperationInclusion, ReadOnlyExecutionOutput,
+    StorageRentInfo,
+};
+use massa_async_pool::AsyncMessage;
+use massa_final_state::StateChanges;
 use massa_models::address::Address;
 use massa_models::amount::Amount;
 use massa_models::block_id::BlockId;
+use massa_models::datastore::{DatastoreChunk, BLS_PUBLIC_KEY_KEY};
+use massa_models::endorsement_signature::RegisteredBlsKey;
 use massa_models::execution::EventFilter;
 use massa_models::operation::OperationId;
 use massa_models::output_event::SCOutputEvent;
@@ -71,12 +78,64 @@ pub trait ExecutionController: Send + Sync {
         input: Vec<(Address, Vec<u8>)>,
     ) -> Vec<(Option<Vec<u8>>, Option<Vec<u8>>)>;
 
+    /// Get at most `length` bytes from `offset` of the final and active values of datastore entries,
+    /// without copying the rest of the values
+    ///
+    /// # Arguments
+    /// * `input`: list of `(address, key, offset, length)`
+    ///
+    /// # Return value
+    /// * `(final_chunk, active_chunk)`
+    #[allow(clippy::type_complexity)]
+    fn get_final_and_active_data_entry_chunk(
+        &self,
+        input: Vec<(Address, Vec<u8>, u64, u64)>,
+    ) -> Vec<(Option<DatastoreChunk>, Option<DatastoreChunk>)>;
+
+    /// Get the BLS keys registered by a list of addresses, `None` for the addresses without one.
+    /// Only the final registrations are returned, so that all the nodes check the endorsement signatures
+    /// against the same keys.
+    fn get_registered_bls_keys(&self, addresses: &[Address]) -> Vec<Option<RegisteredBlsKey>> {
+        self.get_final_and_active_data_entry(
+            addresses
+                .iter()
+                .map(|addr| (*addr, BLS_PUBLIC_KEY_KEY.to_vec()))
+                .collect(),
+        )
+        .into_iter()
+        .map(|(final_value, _)| final_value.and_then(|value| RegisteredBlsKey::from_bytes(&value)))
+        .collect()
+    }
+
     /// Returns for a given cycle the stakers taken into account
     /// by the selector. That correspond to the `roll_counts` in `cycle - 3`.
     ///
     /// By default it returns an empty map.
     fn get_cycle_active_rolls(&self, cycle: u64) -> BTreeMap<Address, u64>;
 
+    /// Returns a page of the stakers taken into account by the selector for a given `cycle`,
+    /// with at least `min_rolls` active rolls, sorted by decreasing roll count,
+    /// along with the total number of matching stakers.
+    fn get_cycle_stakers(
+        &self,
+        cycle: u64,
+        min_rolls: u64,
+        offset: usize,
+        limit: usize,
+    ) -> (Vec<(Address, u64)>, usize);
+
+    /// Returns a page of the messages of the final asynchronous pool,
+    /// sorted by decreasing priority (the first one is executed first),
+    /// along with the total number of messages in the pool.
+    fn get_async_pool_messages(&self, offset: usize, limit: usize) -> (Vec<AsyncMessage>, usize);
+
+    /// Returns the pending scheduled transfers of an address, final or candidate, sorted by activation slot
+    fn get_scheduled_transfers(&self, address: &Address) -> Vec<AsyncMessage>;
+
+    /// Get the state changes caused by the execution of `slot`,
+    /// if it is a candidate slot or a final slot still in the final state history
+    fn get_slot_state_changes(&self, slot: Slot) -> Option<StateChanges>;
+
     /// Execute read-only SC function call without causing modifications to the consensus state
     ///
     /// # arguments
@@ -90,6 +149,15 @@ pub trait ExecutionController: Send + Sync {
         req: ReadOnlyExecutionRequest,
     ) -> Result<ReadOnlyExecutionOutput, ExecutionError>;
 
+    /// Create a temporary copy of the active state named `name`, in which read-only requests can apply operations
+    ///
+    /// # returns
+    /// The latest active slot copied in the fork
+    fn create_fork(&self, name: String) -> Result<Slot, ExecutionError>;
+
+    /// Discard the execution fork named `name`
+    fn discard_fork(&self, name: &str) -> Result<(), ExecutionError>;
+
     /// List which operations inside the provided list were not executed
     fn unexecuted_ops_among(
         &self,
@@ -100,6 +168,17 @@ pub trait ExecutionController: Send + Sync {
     /// Gets information about a batch of addresses
     fn get_addresses_infos(&self, addresses: &[Address]) -> Vec<ExecutionAddressInfo>;
 
+    /// Get the storage rent of addresses, projected from the latest candidate slot
+    fn get_storage_rent(&self, addresses: &[Address]) -> Vec<StorageRentInfo>;
+
+    /// Get the final block including each operation and the index of the operation in the block,
+    /// from the on-disk operation index. `None` if the index is disabled or the operation is not indexed.
+    fn get_operation_inclusions(&self, ids: &[OperationId]) -> Vec<Option<OperationInclusion>>;
+
+    /// Get the operations of final blocks from the on-disk operation index.
+    /// `None` if the index is disabled or the block is not indexed.
+    fn get_final_block_operations(&self, ids: &[BlockId]) -> Vec<Option<FinalBlockOperations>>;
+
     /// Get execution statistics
     fn get_stats(&self) -> ExecutionStats;
 
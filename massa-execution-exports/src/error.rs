@@ -3,6 +3,7 @@
 //! this file defines all possible execution error categories
 
 use displaydoc::Display;
+use massa_models::{operation::OperationId, output_event::ExecutionErrorCode};
 use thiserror::Error;
 
 /// Errors of the execution component.
@@ -15,6 +16,12 @@ pub enum ExecutionError {
     /// Runtime error: {0}
     RuntimeError(String),
 
+    /// Reverted by the smart contract: {0}
+    RevertedError(String, Vec<u8>),
+
+    /// runtime error when executing operation {0}: {1}
+    OperationError(OperationId, Box<ExecutionError>),
+
     /// `MassaHashError`: {0}
     MassaHashError(#[from] massa_hash::MassaHashError),
 
@@ -50,4 +57,129 @@ pub enum ExecutionError {
 
     /// We reach the vesting constraint : {0}
     VestingError(String),
+
+    /// State sink error: {0}
+    StateSinkError(String),
+
+    /// Event store error: {0}
+    EventStoreError(String),
+
+    /// Operation index error: {0}
+    OperationIndexError(String),
+
+    /// Resource limit exceeded: {0}
+    ResourceLimitExceeded(String),
+
+    /// Execution fork error: {0}
+    ForkError(String),
+}
+
+impl ExecutionError {
+    /// Category of the error, reported to the clients
+    pub fn code(&self) -> ExecutionErrorCode {
+        match self {
+            ExecutionError::RuntimeError(_) => ExecutionErrorCode::Runtime,
+            ExecutionError::RevertedError(..) => ExecutionErrorCode::Reverted,
+            ExecutionError::OperationError(_, err) => err.code(),
+            ExecutionError::RollBuyError(_) => ExecutionErrorCode::RollBuy,
+            ExecutionError::RollSellError(_) => ExecutionErrorCode::RollSell,
+            ExecutionError::TransactionError(_) => ExecutionErrorCode::Transaction,
+            ExecutionError::VestingError(_) => ExecutionErrorCode::Vesting,
+            ExecutionError::InvalidSlotRange => ExecutionErrorCode::InvalidSlotRange,
+            ExecutionError::BlockGasError(_) | ExecutionError::NotEnoughGas(_) => {
+                ExecutionErrorCode::NotEnoughGas
+            }
+            ExecutionError::TooMuchGas(_) => ExecutionErrorCode::TooMuchGas,
+            ExecutionError::IncludeOperationError(_) => ExecutionErrorCode::IncludeOperation,
+            ExecutionError::ResourceLimitExceeded(_) => ExecutionErrorCode::ResourceLimit,
+            _ => ExecutionErrorCode::Internal,
+        }
+    }
+
+    /// Category of the error of a smart contract execution that set `revert_reason` before failing:
+    /// the runtime error of the abort is reported as a revert
+    pub fn code_with_revert_reason(&self, revert_reason: Option<&[u8]>) -> ExecutionErrorCode {
+        match (self.code(), revert_reason) {
+            (ExecutionErrorCode::Runtime, Some(_)) => ExecutionErrorCode::Reverted,
+            (code, _) => code,
+        }
+    }
+
+    /// Revert reason set by the smart contract, if it reverted
+    pub fn revert_reason(&self) -> Option<&[u8]> {
+        match self {
+            ExecutionError::RevertedError(_, reason) => Some(reason),
+            ExecutionError::OperationError(_, err) => err.revert_reason(),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_hash::Hash;
+
+    fn operation_error(err: ExecutionError) -> ExecutionError {
+        ExecutionError::OperationError(OperationId::new(Hash::compute_from(b"op")), Box::new(err))
+    }
+
+    #[test]
+    fn test_error_code() {
+        let runtime = ExecutionError::RuntimeError("abort".to_string());
+        let reverted = ExecutionError::RevertedError("abort".to_string(), b"reason".to_vec());
+        assert_eq!(runtime.code(), ExecutionErrorCode::Runtime);
+        assert_eq!(reverted.code(), ExecutionErrorCode::Reverted);
+        assert_eq!(
+            ExecutionError::BlockGasError("gas".to_string()).code(),
+            ExecutionErrorCode::NotEnoughGas
+        );
+        assert_eq!(
+            ExecutionError::ChannelError("closed".to_string()).code(),
+            ExecutionErrorCode::Internal
+        );
+        // the operation errors report the code of the underlying error
+        assert_eq!(
+            operation_error(reverted).code(),
+            ExecutionErrorCode::Reverted
+        );
+        assert_eq!(
+            operation_error(ExecutionError::RollBuyError("rolls".to_string())).code(),
+            ExecutionErrorCode::RollBuy
+        );
+    }
+
+    #[test]
+    fn test_error_code_with_revert_reason() {
+        let runtime = operation_error(ExecutionError::RuntimeError("abort".to_string()));
+        assert_eq!(
+            runtime.code_with_revert_reason(Some(b"reason")),
+            ExecutionErrorCode::Reverted
+        );
+        // without a revert reason event, the abort stays a runtime error
+        assert_eq!(
+            runtime.code_with_revert_reason(None),
+            ExecutionErrorCode::Runtime
+        );
+        // the other failures are not turned into reverts
+        assert_eq!(
+            ExecutionError::TransactionError("balance".to_string())
+                .code_with_revert_reason(Some(b"reason")),
+            ExecutionErrorCode::Transaction
+        );
+    }
+
+    #[test]
+    fn test_revert_reason() {
+        let reverted = ExecutionError::RevertedError("abort".to_string(), b"reason".to_vec());
+        assert_eq!(reverted.revert_reason(), Some(&b"reason"[..]));
+        assert_eq!(
+            operation_error(reverted).revert_reason(),
+            Some(&b"reason"[..])
+        );
+        assert_eq!(
+            operation_error(ExecutionError::RuntimeError("abort".to_string())).revert_reason(),
+            None
+        );
+    }
 }
@@ -34,6 +34,9 @@
 //! ## `event_store.rs`
 //! Defines an indexed, finite-size storage system for execution events.
 //!
+//! ## `state_sink.rs`
+//! Defines the interface used to stream the finalized state changes to an external system.
+//!
 //! ## `types.rs`
 //! Defines useful shared structures.
 //!
@@ -48,16 +51,22 @@ mod controller_traits;
 mod error;
 mod event_store;
 mod settings;
+mod state_sink;
 mod types;
 
 pub use controller_traits::{ExecutionController, ExecutionManager};
 pub use error::ExecutionError;
 pub use event_store::EventStore;
 pub use massa_sc_runtime::GasCosts;
-pub use settings::{ExecutionConfig, StorageCostsConstants};
+pub use settings::{
+    EventStoreConfig, ExecutionConfig, OperationIndexConfig, StorageCostsConstants,
+    StorageRentConstants,
+};
+pub use state_sink::{FinalizedStateChanges, LedgerEntryChange, StateSink, StateSinkConfig};
 pub use types::{
-    ExecutionAddressInfo, ExecutionOutput, ExecutionStackElement, ReadOnlyCallRequest,
-    ReadOnlyExecutionOutput, ReadOnlyExecutionRequest, ReadOnlyExecutionTarget,
+    ExecutionAddressInfo, ExecutionOutput, ExecutionStackElement, FinalBlockOperations,
+    OperationInclusion, ReadOnlyCallRequest, ReadOnlyExecutionLimits, ReadOnlyExecutionOutput,
+    ReadOnlyExecutionRequest, ReadOnlyExecutionTarget, ReadOnlyResourceUsage, StorageRentInfo,
 };
 
 #[cfg(any(feature = "testing", feature = "gas_calibration"))]
@@ -2,10 +2,12 @@
 
 //! This module provides the structures used to provide configuration parameters to the Execution system
 
+use crate::{ReadOnlyExecutionLimits, StateSinkConfig};
 use massa_models::amount::Amount;
 use massa_sc_runtime::GasCosts;
 use massa_time::MassaTime;
 use num::rational::Ratio;
+use serde::Deserialize;
 use std::path::PathBuf;
 
 /// Storage cost constants
@@ -19,6 +21,41 @@ pub struct StorageCostsConstants {
     pub ledger_entry_datastore_base_cost: Amount,
 }
 
+/// Storage rent constants
+#[derive(Debug, Clone, Copy)]
+pub struct StorageRentConstants {
+    /// rent of one byte of bytecode or datastore for one cycle, zero if no rent is charged
+    pub rent_per_byte: Amount,
+    /// number of cycles an address can stay unable to pay its rent before its storage is reclaimed
+    pub grace_cycles: u64,
+}
+
+/// On-disk store of the final execution events
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct EventStoreConfig {
+    /// whether the final events are stored on disk, otherwise only the latest `max_final_events` are kept in RAM
+    pub enabled: bool,
+    /// path of the database directory
+    pub path: PathBuf,
+    /// number of periods whose events are kept, 0 for no limit
+    pub max_periods: u64,
+    /// maximum total size of the stored events in bytes, 0 for no limit
+    pub max_bytes: u64,
+    /// maximum number of events returned by a query, the oldest first
+    pub max_query_events: usize,
+}
+
+/// On-disk index of the operations of the final blocks
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct OperationIndexConfig {
+    /// whether the operations of the final blocks are indexed on disk
+    pub enabled: bool,
+    /// path of the database directory
+    pub path: PathBuf,
+    /// number of periods whose blocks are kept in the index, 0 for no limit
+    pub max_periods: u64,
+}
+
 /// Execution module configuration
 #[derive(Debug, Clone)]
 pub struct ExecutionConfig {
@@ -26,8 +63,20 @@ pub struct ExecutionConfig {
     pub readonly_queue_length: usize,
     /// maximum number of SC output events kept in cache
     pub max_final_events: usize,
+    /// maximum number of cycles of final staking rewards kept in cache
+    pub max_staking_rewards_cycles: usize,
+    /// maximum number of addresses whose final staking rewards are kept in cache per cycle
+    pub max_staking_rewards_addresses: usize,
     /// maximum available gas for asynchronous messages execution
     pub max_async_gas: u64,
+    /// maximum priority fee an asynchronous message can offer
+    pub max_async_priority_fee: Amount,
+    /// cost of one byte of the memo of a transaction
+    pub transaction_memo_cost_per_byte: Amount,
+    /// maximum number of pending scheduled transfers of an address
+    pub max_scheduled_transfers_per_address: u64,
+    /// maximum number of periods between the execution of a scheduled transfer and its activation slot
+    pub max_scheduled_transfer_delay: u64,
     /// maximum gas per block
     pub max_gas_per_block: u64,
     /// number of threads
@@ -62,10 +111,26 @@ pub struct ExecutionConfig {
     pub max_module_cache_size: u32,
     /// Storage cost constants
     pub storage_costs_constants: StorageCostsConstants,
+    /// Storage rent constants
+    pub storage_rent_constants: StorageRentConstants,
     /// Max gas for read only executions
     pub max_read_only_gas: u64,
+    /// Interpreter limits of read only executions
+    pub read_only_limits: ReadOnlyExecutionLimits,
+    /// maximum number of execution forks kept at once
+    pub max_execution_forks: usize,
+    /// duration after which an unused execution fork is discarded
+    pub execution_fork_lifetime: MassaTime,
     /// Gas costs
     pub gas_costs: GasCosts,
     /// path of the initial vesting file
     pub initial_vesting_path: PathBuf,
+    /// where the finalized state changes are streamed
+    pub state_sink: StateSinkConfig,
+    /// maximum number of final slots waiting to be written to the state sink
+    pub state_sink_queue_length: usize,
+    /// on-disk store of the final events
+    pub event_store: EventStoreConfig,
+    /// on-disk index of the operations of the final blocks
+    pub operation_index: OperationIndexConfig,
 }
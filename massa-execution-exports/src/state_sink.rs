@@ -0,0 +1,80 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! This module defines the interface used to stream the finalized state changes to an external system,
+//! so that indexers do not need to re-derive the state from the API.
+
+use crate::ExecutionError;
+use massa_models::{
+    address::Address, amount::Amount, block_id::BlockId, output_event::SCOutputEvent, slot::Slot,
+};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Changes of a ledger entry caused by a final slot
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LedgerEntryChange {
+    /// address of the entry
+    pub address: Address,
+    /// new balance, `None` if unchanged
+    pub balance: Option<Amount>,
+    /// whether the entry was created or entirely replaced, in which case its previous datastore is cleared
+    pub replaced: bool,
+    /// whether the entry was deleted
+    pub deleted: bool,
+    /// datastore writes: new value of each key, `None` if the key was deleted
+    pub datastore: Vec<(Vec<u8>, Option<Vec<u8>>)>,
+}
+
+/// State changes of a final slot, as streamed to the state sink
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FinalizedStateChanges {
+    /// final slot
+    pub slot: Slot,
+    /// block at that slot, `None` if it was missed
+    pub block_id: Option<BlockId>,
+    /// changes of the ledger entries
+    pub ledger_changes: Vec<LedgerEntryChange>,
+    /// events emitted at that slot
+    pub events: Vec<SCOutputEvent>,
+}
+
+/// Destination of the finalized state changes.
+///
+/// Slots are written in increasing order, from a dedicated thread.
+pub trait StateSink: Send {
+    /// Write the changes of a final slot
+    fn write(&mut self, changes: &FinalizedStateChanges) -> Result<(), ExecutionError>;
+
+    /// Flush the pending writes, called before the node stops
+    fn flush(&mut self) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+}
+
+/// Where the finalized state changes are streamed
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StateSinkConfig {
+    /// the changes are not streamed
+    #[default]
+    None,
+    /// one JSON object per line appended to a file
+    File {
+        /// path of the file
+        path: PathBuf,
+    },
+    /// one row per slot in a PostgreSQL table, requires the `state_sink_postgres` feature
+    Postgres {
+        /// connection string, ex: "host=localhost user=massa dbname=indexer"
+        url: String,
+        /// name of the table, created if it does not exist
+        table: String,
+    },
+    /// one message per slot in a Kafka topic, requires the `state_sink_kafka` feature
+    Kafka {
+        /// comma-separated list of brokers
+        brokers: String,
+        /// name of the topic
+        topic: String,
+    },
+}
@@ -2,7 +2,9 @@
 
 //! This file defines testing tools related to the configuration
 
-use crate::{ExecutionConfig, StorageCostsConstants};
+use crate::{
+    ExecutionConfig, ReadOnlyExecutionLimits, StorageCostsConstants, StorageRentConstants,
+};
 use massa_models::config::*;
 use massa_sc_runtime::GasCosts;
 use massa_time::MassaTime;
@@ -24,7 +26,13 @@ impl Default for ExecutionConfig {
         Self {
             readonly_queue_length: 100,
             max_final_events: 1000,
+            max_staking_rewards_cycles: 10,
+            max_staking_rewards_addresses: 1000,
             max_async_gas: MAX_ASYNC_GAS,
+            max_async_priority_fee: MAX_ASYNC_PRIORITY_FEE,
+            transaction_memo_cost_per_byte: TRANSACTION_MEMO_COST_PER_BYTE,
+            max_scheduled_transfers_per_address: MAX_SCHEDULED_TRANSFERS_PER_ADDRESS,
+            max_scheduled_transfer_delay: MAX_SCHEDULED_TRANSFER_DELAY,
             thread_count: THREAD_COUNT,
             roll_price: ROLL_PRICE,
             cursor_delay: MassaTime::from_millis(0),
@@ -42,7 +50,20 @@ impl Default for ExecutionConfig {
             max_bytecode_size: MAX_BYTECODE_LENGTH,
             max_datastore_value_size: MAX_DATASTORE_VALUE_LENGTH,
             storage_costs_constants,
+            storage_rent_constants: StorageRentConstants {
+                rent_per_byte: STORAGE_RENT_PER_BYTE,
+                grace_cycles: STORAGE_RENT_GRACE_CYCLES,
+            },
             max_read_only_gas: 100_000_000,
+            read_only_limits: ReadOnlyExecutionLimits {
+                max_call_depth: 64,
+                max_event_count: 1000,
+                max_datastore_value_size: MAX_DATASTORE_VALUE_LENGTH,
+                max_cpu_time_us: 10_000_000,
+                max_memory_bytes: 100_000_000,
+            },
+            max_execution_forks: 10,
+            execution_fork_lifetime: MassaTime::from_millis(60000),
             gas_costs: GasCosts::new(
                 concat!(
                     env!("CARGO_MANIFEST_DIR"),
@@ -58,6 +79,10 @@ impl Default for ExecutionConfig {
             .unwrap(),
             max_module_cache_size: 1000,
             initial_vesting_path: PathBuf::default(),
+            state_sink: Default::default(),
+            state_sink_queue_length: 100,
+            event_store: Default::default(),
+            operation_index: Default::default(),
         }
     }
 }
@@ -3,14 +3,17 @@
 //! This file defines utilities to mock the crate for testing purposes
 
 use crate::{
-    ExecutionAddressInfo, ExecutionController, ExecutionError, ReadOnlyExecutionOutput,
-    ReadOnlyExecutionRequest,
+    ExecutionAddressInfo, ExecutionController, ExecutionError, FinalBlockOperations,
+    OperationInclusion, ReadOnlyExecutionOutput, ReadOnlyExecutionRequest, StorageRentInfo,
 };
+use massa_async_pool::AsyncMessage;
+use massa_final_state::StateChanges;
 use massa_ledger_exports::LedgerEntry;
 use massa_models::{
     address::Address,
     amount::Amount,
     block_id::BlockId,
+    datastore::DatastoreChunk,
     execution::EventFilter,
     operation::OperationId,
     output_event::SCOutputEvent,
@@ -83,6 +86,14 @@ pub enum MockExecutionControllerMessage {
         /// response channel
         response_tx: mpsc::Sender<Vec<(Option<Amount>, Option<Amount>)>>,
     },
+    /// Get final and active datastore entries
+    GetFinalAndActiveDataEntry {
+        /// addresses and keys to get
+        input: Vec<(Address, Vec<u8>)>,
+        /// response channel
+        #[allow(clippy::type_complexity)]
+        response_tx: mpsc::Sender<Vec<(Option<Vec<u8>>, Option<Vec<u8>>)>>,
+    },
 }
 
 /// A mocked execution controller that will intercept calls on its methods
@@ -172,19 +183,72 @@ impl ExecutionController for MockExecutionController {
 
     fn get_final_and_active_data_entry(
         &self,
-        _: Vec<(Address, Vec<u8>)>,
+        input: Vec<(Address, Vec<u8>)>,
     ) -> Vec<(Option<Vec<u8>>, Option<Vec<u8>>)> {
-        Vec::default()
+        let input_count = input.len();
+        let (response_tx, response_rx) = mpsc::channel();
+        if let Err(err) = self
+            .0
+            .lock()
+            .send(MockExecutionControllerMessage::GetFinalAndActiveDataEntry { input, response_tx })
+        {
+            println!("mock error {err}");
+        }
+        // the tests that do not answer see absent entries
+        response_rx
+            .recv_timeout(Duration::from_millis(100))
+            .unwrap_or_else(|_| vec![(None, None); input_count])
+    }
+
+    fn get_final_and_active_data_entry_chunk(
+        &self,
+        input: Vec<(Address, Vec<u8>, u64, u64)>,
+    ) -> Vec<(Option<DatastoreChunk>, Option<DatastoreChunk>)> {
+        vec![(None, None); input.len()]
     }
 
     fn get_addresses_infos(&self, _addresses: &[Address]) -> Vec<ExecutionAddressInfo> {
         Vec::default()
     }
 
+    fn get_storage_rent(&self, _addresses: &[Address]) -> Vec<StorageRentInfo> {
+        Vec::default()
+    }
+
+    fn get_operation_inclusions(&self, ids: &[OperationId]) -> Vec<Option<OperationInclusion>> {
+        vec![None; ids.len()]
+    }
+
+    fn get_final_block_operations(&self, ids: &[BlockId]) -> Vec<Option<FinalBlockOperations>> {
+        vec![None; ids.len()]
+    }
+
     fn get_cycle_active_rolls(&self, _cycle: u64) -> BTreeMap<Address, u64> {
         BTreeMap::default()
     }
 
+    fn get_cycle_stakers(
+        &self,
+        _cycle: u64,
+        _min_rolls: u64,
+        _offset: usize,
+        _limit: usize,
+    ) -> (Vec<(Address, u64)>, usize) {
+        (Vec::new(), 0)
+    }
+
+    fn get_async_pool_messages(&self, _offset: usize, _limit: usize) -> (Vec<AsyncMessage>, usize) {
+        (Vec::new(), 0)
+    }
+
+    fn get_scheduled_transfers(&self, _address: &Address) -> Vec<AsyncMessage> {
+        Vec::new()
+    }
+
+    fn get_slot_state_changes(&self, _slot: Slot) -> Option<StateChanges> {
+        None
+    }
+
     fn execute_readonly_request(
         &self,
         req: ReadOnlyExecutionRequest,
@@ -197,6 +261,14 @@ impl ExecutionController for MockExecutionController {
         response_rx.recv().unwrap()
     }
 
+    fn create_fork(&self, _name: String) -> Result<Slot, ExecutionError> {
+        Ok(Slot::new(0, 0))
+    }
+
+    fn discard_fork(&self, _name: &str) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+
     fn unexecuted_ops_among(
         &self,
         ops: &PreHashSet<OperationId>,
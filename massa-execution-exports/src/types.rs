@@ -4,11 +4,14 @@
 
 use crate::event_store::EventStore;
 use massa_final_state::StateChanges;
+use massa_hash::Hash;
 use massa_models::datastore::Datastore;
+use massa_models::prehash::PreHashMap;
 use massa_models::{
-    address::Address, address::ExecutionAddressCycleInfo, amount::Amount, block_id::BlockId,
-    slot::Slot,
+    address::Address, address::ExecutionAddressCycleInfo, address::StakingRewards, amount::Amount,
+    block_id::BlockId, operation::OperationId, operation::SecureShareOperation, slot::Slot,
 };
+use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, BTreeSet};
 
 /// Execution info about an address
@@ -28,12 +31,55 @@ pub struct ExecutionAddressInfo {
     pub candidate_roll_count: u64,
     /// candidate datastore keys of the address
     pub candidate_datastore_keys: BTreeSet<Vec<u8>>,
+    /// hash of the candidate bytecode of the address, `None` if it has no bytecode
+    pub candidate_bytecode_hash: Option<Hash>,
 
     /// future deferred credits
     pub future_deferred_credits: BTreeMap<Slot, Amount>,
+    /// future deferred credits, as seen by the latest final slot
+    pub final_future_deferred_credits: BTreeMap<Slot, Amount>,
+    /// deferred credits already credited, kept by the final state for a limited number of periods
+    pub executed_deferred_credits: BTreeMap<Slot, Amount>,
 
     /// cycle information
     pub cycle_infos: Vec<ExecutionAddressCycleInfo>,
+    /// final staking rewards, indexed by cycle
+    pub final_staking_rewards: BTreeMap<u64, StakingRewards>,
+}
+
+/// Storage rent of an address, projected from the latest candidate slot
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StorageRentInfo {
+    /// bytes of bytecode and datastore the address is charged for
+    pub storage_bytes: u64,
+    /// rent charged at the start of each cycle
+    pub rent_per_cycle: Amount,
+    /// first cycle whose rent the address could not pay, if it has not paid since
+    pub rent_due_since: Option<u64>,
+    /// cycle at whose start the storage is reclaimed if the rent is still unpaid
+    pub reclamation_cycle: Option<u64>,
+    /// number of cycles of rent the candidate balance pays for, `None` if no rent is charged
+    pub paid_cycles: Option<u64>,
+}
+
+/// Inclusion of an operation in a final block, read from the on-disk operation index
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OperationInclusion {
+    /// final block including the operation
+    pub block_id: BlockId,
+    /// slot of the block
+    pub slot: Slot,
+    /// index of the operation in the operations of the block
+    pub index: u32,
+}
+
+/// Operations of a final block, read from the on-disk operation index
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FinalBlockOperations {
+    /// slot of the block
+    pub slot: Slot,
+    /// operations of the block, in their order in the block
+    pub operations: Vec<OperationId>,
 }
 
 /// structure describing the output of a single execution
@@ -47,6 +93,8 @@ pub struct ExecutionOutput {
     pub state_changes: StateChanges,
     /// events emitted by the execution step
     pub events: EventStore,
+    /// staking rewards credited by the execution step
+    pub staking_rewards: PreHashMap<Address, StakingRewards>,
 }
 
 /// structure describing the output of a read only execution
@@ -58,6 +106,17 @@ pub struct ReadOnlyExecutionOutput {
     pub gas_cost: u64,
     /// Returned value from the module call
     pub call_result: Vec<u8>,
+    /// Resources used by the execution
+    pub usage: ReadOnlyResourceUsage,
+}
+
+/// Resources used by a read-only execution
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReadOnlyResourceUsage {
+    /// CPU time spent by the execution thread, in microseconds
+    pub cpu_time_us: u64,
+    /// bytes of datastore entries, bytecode, events and return value produced by the execution
+    pub memory_bytes: u64,
 }
 
 /// structure describing different types of read-only execution request
@@ -73,6 +132,55 @@ pub struct ReadOnlyExecutionRequest {
     ///
     /// Whether to start execution from final or active state
     pub is_final: bool,
+    /// Interpreter limits requested for this execution.
+    /// They are combined with the node's own read-only limits, the lowest value being kept.
+    pub limits: ReadOnlyExecutionLimits,
+    /// Execution fork to start from instead of the final or active state.
+    /// The operations executed in a fork are applied to it, the bytecode executions and function calls only read it.
+    pub fork: Option<String>,
+}
+
+/// Interpreter limits applied to read-only executions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadOnlyExecutionLimits {
+    /// maximal depth of the call stack
+    pub max_call_depth: u16,
+    /// maximal number of events emitted by the execution
+    pub max_event_count: u64,
+    /// maximal size of a datastore value written by the execution
+    pub max_datastore_value_size: u64,
+    /// maximal CPU time spent by the execution, in microseconds
+    pub max_cpu_time_us: u64,
+    /// maximal memory produced by the execution, in bytes, see `ReadOnlyResourceUsage::memory_bytes`
+    pub max_memory_bytes: u64,
+}
+
+impl Default for ReadOnlyExecutionLimits {
+    /// No limit other than the node's own read-only limits
+    fn default() -> Self {
+        ReadOnlyExecutionLimits {
+            max_call_depth: u16::MAX,
+            max_event_count: u64::MAX,
+            max_datastore_value_size: u64::MAX,
+            max_cpu_time_us: u64::MAX,
+            max_memory_bytes: u64::MAX,
+        }
+    }
+}
+
+impl ReadOnlyExecutionLimits {
+    /// Returns the limits that fulfill both `self` and `other`
+    pub fn restrict(&self, other: &ReadOnlyExecutionLimits) -> ReadOnlyExecutionLimits {
+        ReadOnlyExecutionLimits {
+            max_call_depth: self.max_call_depth.min(other.max_call_depth),
+            max_event_count: self.max_event_count.min(other.max_event_count),
+            max_datastore_value_size: self
+                .max_datastore_value_size
+                .min(other.max_datastore_value_size),
+            max_cpu_time_us: self.max_cpu_time_us.min(other.max_cpu_time_us),
+            max_memory_bytes: self.max_memory_bytes.min(other.max_memory_bytes),
+        }
+    }
 }
 
 /// structure describing different possible targets of a read-only execution request
@@ -90,6 +198,9 @@ pub enum ReadOnlyExecutionTarget {
         /// Parameter to pass to the target function
         parameter: Vec<u8>,
     },
+
+    /// Execute an operation in the next slot of the thread of its creator, without settling the slot
+    Operation(Box<SecureShareOperation>),
 }
 
 /// structure describing a read-only call
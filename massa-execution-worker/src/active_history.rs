@@ -105,18 +105,31 @@ impl ActiveHistory {
         addr: &Address,
         key: &[u8],
     ) -> HistorySearchResult<Vec<u8>> {
+        match self.fetch_active_history_data_entry_ref(addr, key) {
+            HistorySearchResult::Present(value) => HistorySearchResult::Present(value.to_vec()),
+            HistorySearchResult::Absent => HistorySearchResult::Absent,
+            HistorySearchResult::NoInfo => HistorySearchResult::NoInfo,
+        }
+    }
+
+    /// Same as `fetch_active_history_data_entry` without copying the value.
+    pub fn fetch_active_history_data_entry_ref(
+        &self,
+        addr: &Address,
+        key: &[u8],
+    ) -> HistorySearchResult<&[u8]> {
         for output in self.0.iter().rev() {
             match output.state_changes.ledger_changes.0.get(addr) {
                 Some(SetUpdateOrDelete::Set(LedgerEntry { datastore, .. })) => {
                     match datastore.get(key) {
-                        Some(value) => return HistorySearchResult::Present(value.to_vec()),
+                        Some(value) => return HistorySearchResult::Present(value),
                         None => return HistorySearchResult::Absent,
                     }
                 }
                 Some(SetUpdateOrDelete::Update(LedgerEntryUpdate { datastore, .. })) => {
                     match datastore.get(key) {
                         Some(SetOrDelete::Set(value)) => {
-                            return HistorySearchResult::Present(value.to_vec())
+                            return HistorySearchResult::Present(value)
                         }
                         Some(SetOrDelete::Delete) => return HistorySearchResult::Absent,
                         None => (),
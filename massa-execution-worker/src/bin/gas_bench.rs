@@ -0,0 +1,405 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Benchmark of the execution costs, to justify the updates of the gas tables with reproducible numbers.
+//!
+//! Runs against the in-memory sample ledger of the tests:
+//! * each ABI of the corpus below, called directly on the interface of the execution context,
+//! * each contract of the given directories (by default the benchmark and test contracts of this crate),
+//!
+//! and reports the mean time of each of them with its current gas cost and the resulting time per gas unit.
+//! The ABIs whose time per gas unit is far from the median one are flagged as over or under priced.
+//!
+//! ```text
+//! cargo run --release --features gas_calibration --bin gas_bench -- [--iterations N] [--runs N] [--output report.json] [CONTRACT_DIR...]
+//! ```
+//!
+//! The contracts needing a setup (deployed targets, messages, operation datastore) fail and are reported as such.
+//! The ABI times do not include the transition from the VM, which the gas costs of the table also pay for:
+//! they are lower bounds to compare between ABIs and between versions of the node, on the same machine.
+
+use anyhow::{bail, Context, Result};
+use massa_execution_worker::InterfaceImpl;
+use massa_hash::Hash;
+use massa_models::address::Address;
+use massa_sc_runtime::{GasCosts, Interface, RuntimeModule};
+use massa_signature::KeyPair;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// Gas given to each contract run
+const CONTRACT_MAX_GAS: u64 = 2_000_000_000;
+
+/// Ratio to the median time per gas unit beyond which an ABI is flagged
+const OUTLIER_RATIO: f64 = 3.0;
+
+/// Call of an ABI measured by the benchmark
+struct AbiCase {
+    /// name of the ABI in the gas table
+    abi: &'static str,
+    /// ABI whose mean time is deducted, when each call needs a preparing call
+    baseline: Option<&'static str>,
+    /// one call of the ABI
+    call: Box<dyn Fn(&InterfaceImpl) -> Result<()>>,
+}
+
+impl AbiCase {
+    fn new(abi: &'static str, call: impl Fn(&InterfaceImpl) -> Result<()> + 'static) -> Self {
+        AbiCase {
+            abi,
+            baseline: None,
+            call: Box::new(call),
+        }
+    }
+
+    fn with_baseline(mut self, baseline: &'static str) -> Self {
+        self.baseline = Some(baseline);
+        self
+    }
+}
+
+/// Corpus of ABI calls, with arguments of fixed sizes
+fn abi_corpus(sender: Address) -> Vec<AbiCase> {
+    let keypair = KeyPair::generate();
+    let public_key = keypair.get_public_key().to_string();
+    let data = vec![7u8; 256];
+    let signature = keypair
+        .sign(&Hash::compute_from(&data))
+        .expect("could not sign")
+        .to_bs58_check();
+    let sender = sender.to_string();
+    let recipient = Address::from_public_key(&KeyPair::generate().get_public_key()).to_string();
+    let key = b"bench_key".to_vec();
+    let value = vec![1u8; 64];
+
+    let mut corpus = vec![
+        AbiCase::new("assembly_script_get_balance", |i| i.get_balance().map(drop)),
+        {
+            let sender = sender.clone();
+            AbiCase::new("assembly_script_get_balance_for", move |i| {
+                i.get_balance_for(&sender).map(drop)
+            })
+        },
+        {
+            let (key, value) = (key.clone(), value.clone());
+            AbiCase::new("assembly_script_set_data", move |i| {
+                i.raw_set_data(&key, &value)
+            })
+        },
+        {
+            let key = key.clone();
+            AbiCase::new("assembly_script_get_data", move |i| {
+                i.raw_get_data(&key).map(drop)
+            })
+        },
+        {
+            let (sender, key) = (sender.clone(), key.clone());
+            AbiCase::new("assembly_script_get_data_for", move |i| {
+                i.raw_get_data_for(&sender, &key).map(drop)
+            })
+        },
+        {
+            let key = key.clone();
+            AbiCase::new("assembly_script_has_data", move |i| {
+                i.has_data(&key).map(drop)
+            })
+        },
+        {
+            let (sender, key) = (sender.clone(), key.clone());
+            AbiCase::new("assembly_script_has_data_for", move |i| {
+                i.has_data_for(&sender, &key).map(drop)
+            })
+        },
+        {
+            let (key, value) = (key.clone(), value.clone());
+            AbiCase::new("assembly_script_delete_data", move |i| {
+                i.raw_set_data(&key, &value)?;
+                i.raw_delete_data(&key)
+            })
+            .with_baseline("assembly_script_set_data")
+        },
+        AbiCase::new("assembly_script_get_keys", |i| i.get_keys().map(drop)),
+        AbiCase::new("assembly_script_caller_has_write_access", |i| {
+            i.caller_has_write_access().map(drop)
+        }),
+        AbiCase::new("assembly_script_get_bytecode", |i| {
+            i.raw_get_bytecode().map(drop)
+        }),
+        AbiCase::new("assembly_script_get_owned_addresses", |i| {
+            i.get_owned_addresses().map(drop)
+        }),
+        AbiCase::new("assembly_script_get_call_stack", |i| {
+            i.get_call_stack().map(drop)
+        }),
+        AbiCase::new("assembly_script_get_call_coins", |i| {
+            i.get_call_coins().map(drop)
+        }),
+        AbiCase::new("assembly_script_generate_event", |i| {
+            i.generate_event("benchmark event".to_string())
+        }),
+        AbiCase::new("assembly_script_get_time", |i| i.get_time().map(drop)),
+        AbiCase::new("assembly_script_unsafe_random", |i| {
+            i.unsafe_random().map(drop)
+        }),
+        AbiCase::new("assembly_script_get_current_period", |i| {
+            i.get_current_period().map(drop)
+        }),
+        AbiCase::new("assembly_script_get_current_thread", |i| {
+            i.get_current_thread().map(drop)
+        }),
+        {
+            let data = data.clone();
+            AbiCase::new("assembly_script_hash", move |i| i.hash(&data).map(drop))
+        },
+        {
+            let data = data.clone();
+            AbiCase::new("assembly_script_hash_sha256", move |i| {
+                i.hash_sha256(&data).map(drop)
+            })
+        },
+        {
+            let public_key = public_key.clone();
+            AbiCase::new("assembly_script_address_from_public_key", move |i| {
+                i.address_from_public_key(&public_key).map(drop)
+            })
+        },
+        AbiCase::new("assembly_script_signature_verify", move |i| {
+            if !i.signature_verify(&data, &signature, &public_key)? {
+                bail!("invalid benchmark signature");
+            }
+            Ok(())
+        }),
+        {
+            let recipient = recipient.clone();
+            AbiCase::new("assembly_script_transfer_coins", move |i| {
+                i.transfer_coins(&recipient, 1)
+            })
+        },
+    ];
+    corpus.push(AbiCase::new(
+        "assembly_script_transfer_coins_for",
+        move |i| i.transfer_coins_for(&sender, &recipient, 1),
+    ));
+    corpus
+}
+
+/// Mean time of `call` in nanoseconds, over `iterations` calls after a warm-up
+fn measure(iterations: u64, mut call: impl FnMut() -> Result<()>) -> Result<f64> {
+    for _ in 0..(iterations / 10).max(1) {
+        call()?;
+    }
+    let start = Instant::now();
+    for _ in 0..iterations {
+        call()?;
+    }
+    Ok(start.elapsed().as_nanos() as f64 / iterations as f64)
+}
+
+/// Contracts of `dirs`, sorted by path
+fn read_contracts(dirs: &[PathBuf]) -> Result<Vec<(PathBuf, Vec<u8>)>> {
+    let mut contracts = Vec::new();
+    for dir in dirs {
+        for entry in
+            std::fs::read_dir(dir).with_context(|| format!("could not read {}", dir.display()))?
+        {
+            let path = entry?.path();
+            if path
+                .extension()
+                .map_or(false, |extension| extension == "wasm")
+            {
+                let bytecode = std::fs::read(&path)?;
+                contracts.push((path, bytecode));
+            }
+        }
+    }
+    contracts.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+    Ok(contracts)
+}
+
+/// Gas used and mean time in nanoseconds of a run of `bytecode`, over `runs` runs
+fn run_contract(
+    interface: &InterfaceImpl,
+    bytecode: &[u8],
+    gas_costs: &GasCosts,
+    runs: u64,
+) -> Result<(u64, f64)> {
+    let mut gas = 0;
+    let mut total_ns = 0;
+    for _ in 0..runs.max(1) {
+        let start = Instant::now();
+        let module = RuntimeModule::new(bytecode, CONTRACT_MAX_GAS, gas_costs.clone())?;
+        let response =
+            massa_sc_runtime::run_main(interface, module, CONTRACT_MAX_GAS, gas_costs.clone())?;
+        total_ns += start.elapsed().as_nanos();
+        gas = CONTRACT_MAX_GAS.saturating_sub(response.remaining_gas);
+    }
+    Ok((gas, total_ns as f64 / runs.max(1) as f64))
+}
+
+struct Args {
+    iterations: u64,
+    runs: u64,
+    output: Option<PathBuf>,
+    contract_dirs: Vec<PathBuf>,
+}
+
+fn parse_args() -> Result<Args> {
+    let mut args = Args {
+        iterations: 10_000,
+        runs: 20,
+        output: None,
+        contract_dirs: Vec::new(),
+    };
+    let mut raw_args = std::env::args().skip(1);
+    while let Some(arg) = raw_args.next() {
+        let mut value = |name: &str| {
+            raw_args
+                .next()
+                .with_context(|| format!("missing value of {}", name))
+        };
+        match arg.as_str() {
+            "--iterations" => args.iterations = value("--iterations")?.parse()?,
+            "--runs" => args.runs = value("--runs")?.parse()?,
+            "--output" => args.output = Some(value("--output")?.into()),
+            flag if flag.starts_with("--") => bail!("unknown argument {}", flag),
+            dir => args.contract_dirs.push(dir.into()),
+        }
+    }
+    if args.iterations == 0 {
+        bail!("the number of iterations must be positive");
+    }
+    if args.contract_dirs.is_empty() {
+        let crate_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+        args.contract_dirs = vec![
+            crate_dir.join("benches/wasm"),
+            crate_dir.join("src/tests/wasm"),
+        ];
+    }
+    Ok(args)
+}
+
+fn main() -> Result<()> {
+    let args = parse_args()?;
+    let base_config =
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("../massa-node/base_config/gas_costs");
+    let abi_costs_path = base_config.join("abi_gas_costs.json");
+    let abi_costs: BTreeMap<String, u64> =
+        serde_json::from_str(&std::fs::read_to_string(&abi_costs_path)?)
+            .with_context(|| format!("could not parse {}", abi_costs_path.display()))?;
+    let gas_costs = GasCosts::new(abi_costs_path, base_config.join("wasm_gas_costs.json"))?;
+    let sender = Address::from_public_key(&KeyPair::generate().get_public_key());
+    let interface = InterfaceImpl::new_default(sender, None);
+
+    // ABIs
+    let mut means: BTreeMap<&str, f64> = BTreeMap::new();
+    let mut abi_rows = Vec::new();
+    for case in abi_corpus(sender) {
+        let mean_ns = measure(args.iterations, || (case.call)(&interface))
+            .with_context(|| format!("could not call {}", case.abi))?;
+        means.insert(case.abi, mean_ns);
+        let mean_ns = match case.baseline.and_then(|baseline| means.get(baseline)) {
+            Some(baseline_ns) => (mean_ns - baseline_ns).max(0.0),
+            None => mean_ns,
+        };
+        let gas = abi_costs.get(case.abi).copied();
+        abi_rows.push((case.abi, mean_ns, gas));
+    }
+    let mut ns_per_gas: Vec<f64> = abi_rows
+        .iter()
+        .filter_map(|(_, mean_ns, gas)| gas.filter(|gas| *gas > 0).map(|gas| mean_ns / gas as f64))
+        .collect();
+    ns_per_gas.sort_unstable_by(f64::total_cmp);
+    let median_ns_per_gas = ns_per_gas
+        .get(ns_per_gas.len() / 2)
+        .copied()
+        .unwrap_or_default();
+
+    println!("ABI calls ({} iterations each)", args.iterations);
+    println!(
+        "{:<45} {:>12} {:>8} {:>10} {:>10}",
+        "abi", "mean (ns)", "gas", "ns/gas", "vs median"
+    );
+    let mut abis_json = Vec::new();
+    for (abi, mean_ns, gas) in &abi_rows {
+        let ratio = gas
+            .filter(|gas| *gas > 0 && median_ns_per_gas > 0.0)
+            .map(|gas| mean_ns / gas as f64 / median_ns_per_gas);
+        let flag = match ratio {
+            Some(ratio) if ratio > OUTLIER_RATIO => "underpriced",
+            Some(ratio) if ratio < 1.0 / OUTLIER_RATIO => "overpriced",
+            _ => "",
+        };
+        println!(
+            "{:<45} {:>12.1} {:>8} {:>10} {:>10} {}",
+            abi,
+            mean_ns,
+            gas.map_or("-".to_string(), |gas| gas.to_string()),
+            gas.filter(|gas| *gas > 0)
+                .map_or("-".to_string(), |gas| format!(
+                    "{:.3}",
+                    mean_ns / gas as f64
+                )),
+            ratio.map_or("-".to_string(), |ratio| format!("{:.2}", ratio)),
+            flag
+        );
+        abis_json.push(json!({
+            "abi": abi,
+            "mean_ns": mean_ns,
+            "gas": gas,
+            "ratio_to_median": ratio,
+            "flag": flag,
+        }));
+    }
+    println!("median time per gas unit: {:.3} ns", median_ns_per_gas);
+
+    // contracts
+    println!("\nContracts ({} runs each)", args.runs);
+    println!(
+        "{:<45} {:>12} {:>12} {:>10}",
+        "contract", "mean (ns)", "gas", "ns/gas"
+    );
+    let mut contracts_json = Vec::new();
+    for (path, bytecode) in read_contracts(&args.contract_dirs)? {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        match run_contract(&interface, &bytecode, &gas_costs, args.runs) {
+            Ok((gas, mean_ns)) => {
+                let ns_per_gas = (gas > 0).then(|| mean_ns / gas as f64);
+                println!(
+                    "{:<45} {:>12.1} {:>12} {:>10}",
+                    name,
+                    mean_ns,
+                    gas,
+                    ns_per_gas.map_or("-".to_string(), |ns_per_gas| format!("{:.3}", ns_per_gas))
+                );
+                contracts_json.push(json!({
+                    "contract": name,
+                    "mean_ns": mean_ns,
+                    "gas": gas,
+                    "ns_per_gas": ns_per_gas,
+                }));
+            }
+            Err(err) => {
+                println!("{:<45} failed: {}", name, err);
+                contracts_json.push(json!({ "contract": name, "error": err.to_string() }));
+            }
+        }
+    }
+
+    if let Some(output) = args.output {
+        let report = json!({
+            "iterations": args.iterations,
+            "runs": args.runs,
+            "median_ns_per_gas": median_ns_per_gas,
+            "abis": abis_json,
+            "contracts": contracts_json,
+        });
+        std::fs::write(&output, serde_json::to_string_pretty(&report)?)
+            .with_context(|| format!("could not write {}", output.display()))?;
+        println!("\nreport written in {}", output.display());
+    }
+    Ok(())
+}
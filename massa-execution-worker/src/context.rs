@@ -8,6 +8,7 @@
 //! and does not write anything persistent to the consensus state.
 
 use crate::module_cache::ModuleCache;
+use crate::resource_meter::ResourceMeter;
 use crate::speculative_async_pool::SpeculativeAsyncPool;
 use crate::speculative_executed_ops::SpeculativeExecutedOps;
 use crate::speculative_ledger::SpeculativeLedger;
@@ -16,6 +17,7 @@ use massa_async_pool::{AsyncMessage, AsyncMessageId};
 use massa_executed_ops::ExecutedOpsChanges;
 use massa_execution_exports::{
     EventStore, ExecutionConfig, ExecutionError, ExecutionOutput, ExecutionStackElement,
+    ReadOnlyExecutionLimits,
 };
 use massa_final_state::{FinalState, StateChanges};
 use massa_ledger_exports::LedgerChanges;
@@ -27,11 +29,17 @@ use massa_models::{
     address::Address,
     amount::Amount,
     block_id::BlockId,
-    operation::OperationId,
-    output_event::{EventExecutionContext, SCOutputEvent},
+    config::STORAGE_RENT_VERSION,
+    datastore::is_reserved_datastore_key,
+    endorsement_signature::RegisteredBlsKey,
+    operation::{next_operation_sequence, OperationId, OPERATION_SEQUENCE_KEY},
+    output_event::{
+        latest_revert_reason, EventExecutionContext, ExecutionErrorInfo, SCOutputEvent,
+    },
     slot::Slot,
 };
 use massa_pos_exports::PoSChanges;
+use massa_signature::BlsPublicKey;
 use parking_lot::RwLock;
 use rand::SeedableRng;
 use rand_xoshiro::Xoshiro256PlusPlus;
@@ -109,6 +117,10 @@ pub struct ExecutionContext {
     /// slot at which the execution happens
     pub slot: Slot,
 
+    /// version of the execution component at the slot, see `massa_versioning`.
+    /// Behaviors introduced by a MIP must only be enabled when it is at least the version of the MIP.
+    pub execution_version: u32,
+
     /// counter of newly created addresses so far during this execution
     pub created_addr_index: u64,
 
@@ -127,6 +139,12 @@ pub struct ExecutionContext {
     /// True if it's a read-only context
     pub read_only: bool,
 
+    /// interpreter limits of the execution, only set in read-only contexts
+    pub read_only_limits: Option<ReadOnlyExecutionLimits>,
+
+    /// resources used by the execution, only measured in read-only contexts
+    pub read_only_meter: Option<ResourceMeter>,
+
     /// generated events during this execution, with multiple indexes
     pub events: EventStore,
 
@@ -184,12 +202,15 @@ impl ExecutionContext {
             speculative_executed_ops: SpeculativeExecutedOps::new(final_state, active_history),
             max_gas: Default::default(),
             slot: Slot::new(0, 0),
+            execution_version: Default::default(),
             created_addr_index: Default::default(),
             created_event_index: Default::default(),
             created_message_index: Default::default(),
             opt_block_id: Default::default(),
             stack: Default::default(),
             read_only: Default::default(),
+            read_only_limits: Default::default(),
+            read_only_meter: None,
             events: Default::default(),
             unsafe_rng: Xoshiro256PlusPlus::from_seed([0u8; 32]),
             creator_address: Default::default(),
@@ -240,16 +261,26 @@ impl ExecutionContext {
 
         // For events, set snapshot delta to error events.
         // Start iterating from snapshot events length because we are dealing with a VecDeque.
+        // The latest revert reason set by the failed execution is kept.
         for event in self.events.0.range_mut(snapshot.events.0.len()..) {
             event.context.is_error = true;
         }
+        let revert_reason = latest_revert_reason(self.events.0.range(snapshot.events.0.len()..))
+            .map(<[u8]>::to_vec);
+        let code = error.code_with_revert_reason(revert_reason.as_deref());
 
         // Emit the error event.
         // Note that the context event counter is properly handled by event_emit (see doc).
-        self.event_emit(self.event_create(
+        let mut event = self.event_create(
             serde_json::json!({ "massa_execution_error": format!("{}", error) }).to_string(),
             true,
-        ));
+        );
+        event.context.error = Some(ExecutionErrorInfo {
+            code,
+            message: error.to_string(),
+            revert_reason,
+        });
+        self.event_emit(event);
     }
 
     /// Create a new `ExecutionContext` for read-only execution
@@ -258,6 +289,7 @@ impl ExecutionContext {
     /// # arguments
     /// * `slot`: slot at which the execution will happen
     /// * `req`: parameters of the read only execution
+    /// * `limits`: interpreter limits of the read only execution
     /// * `final_state`: thread-safe access to the final state. Note that this will be used only for reading, never for writing
     ///
     /// # returns
@@ -268,6 +300,7 @@ impl ExecutionContext {
         slot: Slot,
         max_gas: u64,
         call_stack: Vec<ExecutionStackElement>,
+        limits: ReadOnlyExecutionLimits,
         final_state: Arc<RwLock<FinalState>>,
         active_history: Arc<RwLock<ActiveHistory>>,
         module_cache: Arc<RwLock<ModuleCache>>,
@@ -294,6 +327,8 @@ impl ExecutionContext {
             slot,
             stack: call_stack,
             read_only: true,
+            read_only_limits: Some(limits),
+            read_only_meter: Some(ResourceMeter::start()),
             unsafe_rng,
             ..ExecutionContext::new(
                 config,
@@ -443,6 +478,8 @@ impl ExecutionContext {
         // hash the seed to get a unique address
         let address = Address::SC(SCAddress(massa_hash::Hash::compute_from(&data)));
 
+        self.charge_read_only_memory(bytecode.0.len())?;
+
         // add this address with its bytecode to the speculative ledger
         self.speculative_ledger.create_new_sc_address(
             self.get_current_address()?,
@@ -517,6 +554,10 @@ impl ExecutionContext {
                 address
             )));
         }
+        check_datastore_key_not_reserved(&key)?;
+
+        self.check_read_only_datastore_value_size(data.len())?;
+        self.charge_read_only_memory(key.len() + data.len())?;
 
         // set data entry
         self.speculative_ledger
@@ -544,6 +585,7 @@ impl ExecutionContext {
                 address
             )));
         }
+        check_datastore_key_not_reserved(&key)?;
 
         // get current data entry
         let mut res_data = self
@@ -557,13 +599,162 @@ impl ExecutionContext {
             })?;
 
         // append data
+        self.charge_read_only_memory(data.len())?;
         res_data.extend(data);
+        self.check_read_only_datastore_value_size(res_data.len())?;
 
         // set data entry
         self.speculative_ledger
             .set_data_entry(&self.get_current_address()?, address, key, res_data)
     }
 
+    /// Fails if a datastore value of size `value_size` exceeds the read-only limits of the context
+    fn check_read_only_datastore_value_size(
+        &self,
+        value_size: usize,
+    ) -> Result<(), ExecutionError> {
+        if let Some(limits) = &self.read_only_limits {
+            if value_size as u64 > limits.max_datastore_value_size {
+                return Err(ExecutionError::RuntimeError(format!(
+                    "datastore value size {} exceeds the read-only limit of {} bytes",
+                    value_size, limits.max_datastore_value_size
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Counts `bytes` produced by a read-only execution and fails if its resources exceed the read-only limits
+    pub fn charge_read_only_memory(&mut self, bytes: usize) -> Result<(), ExecutionError> {
+        if let Some(meter) = &mut self.read_only_meter {
+            meter.add_memory(bytes);
+        }
+        self.check_read_only_resources()
+    }
+
+    /// Fails if the resources used by a read-only execution exceed the read-only limits of the context
+    pub fn check_read_only_resources(&self) -> Result<(), ExecutionError> {
+        match (&self.read_only_meter, &self.read_only_limits) {
+            (Some(meter), Some(limits)) => meter.check(limits),
+            _ => Ok(()),
+        }
+    }
+
+    /// Next sequence expected from the operations of an address, as recorded in the speculative ledger
+    pub fn get_next_operation_sequence(&self, address: &Address) -> u64 {
+        next_operation_sequence(
+            self.speculative_ledger
+                .get_data_entry(address, OPERATION_SEQUENCE_KEY)
+                .as_deref(),
+        )
+    }
+
+    /// Records that an operation of an address used `sequence`, the next expected one being `sequence + 1`.
+    /// Fails if the address does not exist.
+    pub fn record_operation_sequence(
+        &mut self,
+        address: &Address,
+        sequence: u64,
+    ) -> Result<(), ExecutionError> {
+        let next_sequence = sequence.checked_add(1).ok_or_else(|| {
+            ExecutionError::RuntimeError("operation sequence overflow".to_string())
+        })?;
+        self.speculative_ledger
+            .set_operation_sequence(address, next_sequence)
+    }
+
+    /// Whether an address registered a BLS key, in the speculative ledger
+    pub fn has_registered_bls_key(&self, address: &Address) -> bool {
+        self.speculative_ledger
+            .get_registered_bls_key(address)
+            .is_some()
+    }
+
+    /// Registers the BLS key of an address at the cycle of the current slot, whose proof of possession was checked.
+    /// Fails if the address does not exist or already registered a key.
+    pub fn register_bls_key(
+        &mut self,
+        address: &Address,
+        public_key: BlsPublicKey,
+    ) -> Result<(), ExecutionError> {
+        let key = RegisteredBlsKey {
+            public_key,
+            cycle: self.slot.get_cycle(self.config.periods_per_cycle),
+        };
+        self.speculative_ledger
+            .set_registered_bls_key(address, &key)
+    }
+
+    /// Charges the storage rent of every address at the first slot of a cycle, burning the rent from its balance.
+    /// Nothing is charged before the execution reaches [`STORAGE_RENT_VERSION`].
+    ///
+    /// An address that cannot pay is recorded as due since the current cycle, and the record is cleared once it pays again
+    /// (the unpaid cycles are not collected).
+    /// When the address stayed unable to pay for `grace_cycles` cycles, its bytecode and datastore are reclaimed
+    /// and an event announces it.
+    pub fn charge_storage_rent(&mut self) {
+        let rent_constants = self.config.storage_rent_constants;
+        if rent_constants.rent_per_byte.is_zero()
+            || self.execution_version < STORAGE_RENT_VERSION
+            || self.slot.thread != 0
+            || self.slot.period == 0
+            || self.slot.period % self.config.periods_per_cycle != 0
+        {
+            return;
+        }
+        let cycle = self.slot.get_cycle(self.config.periods_per_cycle);
+        for address in self.speculative_ledger.get_addresses_with_storage() {
+            let due_since = self.speculative_ledger.get_storage_rent_due(&address);
+            let storage_size = self.speculative_ledger.get_storage_size(&address);
+            if storage_size == 0 {
+                // the address freed its storage by itself
+                if due_since.is_some() {
+                    self.speculative_ledger.set_storage_rent_due(&address, None);
+                }
+                continue;
+            }
+            let rent = rent_constants
+                .rent_per_byte
+                .saturating_mul_u64(storage_size);
+            match self
+                .speculative_ledger
+                .transfer_coins(Some(address), None, rent)
+            {
+                Ok(()) => {
+                    if due_since.is_some() {
+                        self.speculative_ledger.set_storage_rent_due(&address, None);
+                    }
+                }
+                Err(_) => {
+                    let first_unpaid_cycle = due_since.unwrap_or(cycle);
+                    if cycle.saturating_sub(first_unpaid_cycle) >= rent_constants.grace_cycles {
+                        self.reclaim_storage(&address, first_unpaid_cycle);
+                    } else if due_since.is_none() {
+                        self.speculative_ledger
+                            .set_storage_rent_due(&address, Some(cycle));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Reclaims the storage of an address whose rent is unpaid since `due_since`
+    fn reclaim_storage(&mut self, address: &Address, due_since: u64) {
+        debug!(
+            "reclaiming the storage of {}: rent unpaid since cycle {}",
+            address, due_since
+        );
+        self.speculative_ledger.reclaim_storage(address);
+        let event = self.event_create(
+            format!(
+                "storage of {} reclaimed: rent unpaid since cycle {}",
+                address, due_since
+            ),
+            false,
+        );
+        self.event_emit(event);
+    }
+
     /// Deletes a datastore entry for an address.
     /// Fails if the address or the entry does not exist or if write access rights are missing.
     ///
@@ -582,6 +773,7 @@ impl ExecutionContext {
                 address
             )));
         }
+        check_datastore_key_not_reserved(&key)?;
 
         // delete entry
         self.speculative_ledger
@@ -671,6 +863,20 @@ impl ExecutionContext {
         self.speculative_async_pool.push_new_message(msg);
     }
 
+    /// Gets the pending scheduled transfers of `sender`
+    pub fn get_scheduled_transfers(&self, sender: &Address) -> Vec<(AsyncMessageId, AsyncMessage)> {
+        self.speculative_async_pool.get_scheduled_transfers(sender)
+    }
+
+    /// Removes an asynchronous message from the speculative pool before its execution, without reimbursement.
+    /// The removal is not reverted by `reset_to_snapshot`, so it must come last in an execution.
+    ///
+    /// # Arguments
+    /// * `msg_id`: id of the message to remove
+    pub fn remove_async_message(&mut self, msg_id: &AsyncMessageId) {
+        self.speculative_async_pool.remove_message(msg_id);
+    }
+
     /// Cancels an asynchronous message, reimbursing `msg.coins` to the sender
     ///x
     /// # Arguments
@@ -684,6 +890,21 @@ impl ExecutionContext {
         }
     }
 
+    /// Discards an asynchronous message that was not executed,
+    /// reimbursing `msg.coins` and `msg.priority_fee` to the sender
+    ///
+    /// # Arguments
+    /// * `msg`: the asynchronous message to discard
+    pub fn discard_async_message(&mut self, msg: &AsyncMessage) {
+        self.cancel_async_message(msg);
+        if let Err(e) = self.transfer_coins(None, Some(msg.sender), msg.priority_fee, false) {
+            debug!(
+                "async message discard: priority fee reimbursement of {} failed: {}",
+                msg.sender, e
+            );
+        }
+    }
+
     /// Add `roll_count` rolls to the buyer address.
     /// Validity checks must be performed _outside_ of this function.
     ///
@@ -777,7 +998,7 @@ impl ExecutionContext {
             .speculative_async_pool
             .settle_slot(&slot, &ledger_changes);
         for (_msg_id, msg) in deleted_messages {
-            self.cancel_async_message(&msg);
+            self.discard_async_message(&msg);
         }
 
         // if the current slot is last in cycle check the production stats and act accordingly
@@ -806,6 +1027,24 @@ impl ExecutionContext {
             block_id: std::mem::take(&mut self.opt_block_id),
             state_changes,
             events: std::mem::take(&mut self.events),
+            staking_rewards: Default::default(),
+        }
+    }
+
+    /// Gathers the changes and events caused so far in the context, without settling the slot.
+    /// Used to report the effects of a single operation.
+    pub fn take_output(&mut self) -> ExecutionOutput {
+        ExecutionOutput {
+            slot: self.slot,
+            block_id: std::mem::take(&mut self.opt_block_id),
+            state_changes: StateChanges {
+                ledger_changes: self.speculative_ledger.take(),
+                async_pool_changes: self.speculative_async_pool.take(),
+                pos_changes: self.speculative_roll_state.take(),
+                executed_ops_changes: self.speculative_executed_ops.take(),
+            },
+            events: std::mem::take(&mut self.events),
+            staking_rewards: Default::default(),
         }
     }
 
@@ -836,6 +1075,8 @@ impl ExecutionContext {
                                                             address)));
         }
 
+        self.charge_read_only_memory(bytecode.0.len())?;
+
         // set data entry
         self.speculative_ledger
             .set_bytecode(&self.get_current_address()?, address, bytecode)
@@ -857,6 +1098,7 @@ impl ExecutionContext {
             origin_operation_id: self.origin_operation_id,
             is_final: false,
             is_error,
+            error: None,
         };
 
         // Return the event
@@ -872,6 +1114,11 @@ impl ExecutionContext {
         // Increment the event counter fot this slot
         self.created_event_index += 1;
 
+        // the limits are checked by the next execution step
+        if let Some(meter) = &mut self.read_only_meter {
+            meter.add_memory(event.data.len());
+        }
+
         // Add the event to the context store
         self.events.push(event);
     }
@@ -933,3 +1180,14 @@ impl ExecutionContext {
         })
     }
 }
+
+/// Fails if `key` is reserved to the ledger, ex: [`OPERATION_SEQUENCE_KEY`], and cannot be written by smart contracts
+fn check_datastore_key_not_reserved(key: &[u8]) -> Result<(), ExecutionError> {
+    if is_reserved_datastore_key(key) {
+        return Err(ExecutionError::RuntimeError(format!(
+            "the datastore key {:?} is reserved to the ledger",
+            key
+        )));
+    }
+    Ok(())
+}
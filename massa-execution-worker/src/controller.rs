@@ -5,10 +5,15 @@
 
 use crate::execution::ExecutionState;
 use crate::request_queue::{RequestQueue, RequestWithResponseSender};
+use massa_async_pool::AsyncMessage;
 use massa_execution_exports::{
     ExecutionAddressInfo, ExecutionConfig, ExecutionController, ExecutionError, ExecutionManager,
-    ReadOnlyExecutionOutput, ReadOnlyExecutionRequest,
+    FinalBlockOperations, OperationInclusion, ReadOnlyExecutionOutput, ReadOnlyExecutionRequest,
+    StorageRentInfo,
 };
+use massa_final_state::StateChanges;
+use massa_hash::Hash;
+use massa_models::datastore::DatastoreChunk;
 use massa_models::execution::EventFilter;
 use massa_models::output_event::SCOutputEvent;
 use massa_models::prehash::{PreHashMap, PreHashSet};
@@ -20,7 +25,7 @@ use parking_lot::{Condvar, Mutex, RwLock};
 use std::collections::{BTreeMap, HashMap};
 use std::fmt::Display;
 use std::sync::Arc;
-use tracing::info;
+use tracing::{info, warn};
 
 /// structure used to communicate with execution thread
 pub(crate) struct ExecutionInputData {
@@ -169,11 +174,55 @@ impl ExecutionController for ExecutionControllerImpl {
         result
     }
 
+    /// Get chunks of the final and active values of datastore entries
+    fn get_final_and_active_data_entry_chunk(
+        &self,
+        input: Vec<(Address, Vec<u8>, u64, u64)>,
+    ) -> Vec<(Option<DatastoreChunk>, Option<DatastoreChunk>)> {
+        let lock = self.execution_state.read();
+        input
+            .into_iter()
+            .map(|(addr, key, offset, length)| {
+                lock.get_final_and_active_data_entry_chunk(&addr, &key, offset, length)
+            })
+            .collect()
+    }
+
     /// Return the active rolls distribution for the given `cycle`
     fn get_cycle_active_rolls(&self, cycle: u64) -> BTreeMap<Address, u64> {
         self.execution_state.read().get_cycle_active_rolls(cycle)
     }
 
+    /// Return a page of the stakers of the given `cycle`, and the total number of matching stakers
+    fn get_cycle_stakers(
+        &self,
+        cycle: u64,
+        min_rolls: u64,
+        offset: usize,
+        limit: usize,
+    ) -> (Vec<(Address, u64)>, usize) {
+        self.execution_state
+            .read()
+            .get_cycle_stakers(cycle, min_rolls, offset, limit)
+    }
+
+    /// Get a page of the messages of the final asynchronous pool by decreasing priority
+    fn get_async_pool_messages(&self, offset: usize, limit: usize) -> (Vec<AsyncMessage>, usize) {
+        self.execution_state
+            .read()
+            .get_async_pool_messages(offset, limit)
+    }
+
+    /// Get the pending scheduled transfers of an address, final or candidate
+    fn get_scheduled_transfers(&self, address: &Address) -> Vec<AsyncMessage> {
+        self.execution_state.read().get_scheduled_transfers(address)
+    }
+
+    /// Get the state changes of a candidate slot or of a final slot still in history
+    fn get_slot_state_changes(&self, slot: Slot) -> Option<StateChanges> {
+        self.execution_state.read().get_slot_state_changes(&slot)
+    }
+
     /// Executes a read-only request
     /// Read-only requests do not modify consensus state
     fn execute_readonly_request(
@@ -215,6 +264,16 @@ impl ExecutionController for ExecutionControllerImpl {
         }
     }
 
+    /// Creates a fork of the active state named `name`
+    fn create_fork(&self, name: String) -> Result<Slot, ExecutionError> {
+        self.execution_state.write().create_fork(name)
+    }
+
+    /// Discards the fork named `name`
+    fn discard_fork(&self, name: &str) -> Result<(), ExecutionError> {
+        self.execution_state.write().discard_fork(name)
+    }
+
     /// List which operations inside the provided list were not executed
     fn unexecuted_ops_among(
         &self,
@@ -237,6 +296,10 @@ impl ExecutionController for ExecutionControllerImpl {
                 exec_state.get_final_and_candidate_balance(addr);
             let (final_roll_count, candidate_roll_count) =
                 exec_state.get_final_and_candidate_rolls(addr);
+            let candidate_bytecode_hash = exec_state
+                .get_candidate_bytecode(addr)
+                .filter(|bytecode| !bytecode.0.is_empty())
+                .map(|bytecode| Hash::compute_from(&bytecode.0));
             res.push(ExecutionAddressInfo {
                 final_datastore_keys,
                 candidate_datastore_keys,
@@ -244,13 +307,52 @@ impl ExecutionController for ExecutionControllerImpl {
                 candidate_balance: candidate_balance.unwrap_or_default(),
                 final_roll_count,
                 candidate_roll_count,
+                candidate_bytecode_hash,
                 future_deferred_credits: exec_state.get_address_future_deferred_credits(addr),
+                final_future_deferred_credits: exec_state
+                    .get_address_final_future_deferred_credits(addr),
+                executed_deferred_credits: exec_state.get_address_executed_deferred_credits(addr),
                 cycle_infos: exec_state.get_address_cycle_infos(addr),
+                final_staking_rewards: exec_state.get_address_final_staking_rewards(addr),
             });
         }
         res
     }
 
+    /// Get the storage rent of addresses, projected from the latest candidate slot
+    fn get_storage_rent(&self, addresses: &[Address]) -> Vec<StorageRentInfo> {
+        let exec_state = self.execution_state.read();
+        addresses
+            .iter()
+            .map(|addr| exec_state.get_storage_rent(addr))
+            .collect()
+    }
+
+    /// Get the final block including each operation and the index of the operation in the block,
+    /// from the on-disk operation index
+    fn get_operation_inclusions(&self, ids: &[OperationId]) -> Vec<Option<OperationInclusion>> {
+        let exec_state = self.execution_state.read();
+        match &exec_state.operation_index_db {
+            Some(operation_index_db) => ids
+                .iter()
+                .map(|id| operation_index_db.get_operation_inclusion(id))
+                .collect(),
+            None => vec![None; ids.len()],
+        }
+    }
+
+    /// Get the operations of final blocks from the on-disk operation index
+    fn get_final_block_operations(&self, ids: &[BlockId]) -> Vec<Option<FinalBlockOperations>> {
+        let exec_state = self.execution_state.read();
+        match &exec_state.operation_index_db {
+            Some(operation_index_db) => ids
+                .iter()
+                .map(|id| operation_index_db.get_block_operations(id))
+                .collect(),
+            None => vec![None; ids.len()],
+        }
+    }
+
     /// Get execution statistics
     fn get_stats(&self) -> ExecutionStats {
         self.execution_state.read().get_stats()
@@ -277,6 +379,10 @@ pub struct ExecutionManagerImpl {
     pub(crate) input_data: Arc<(Condvar, Mutex<ExecutionInputData>)>,
     /// handle used to join the worker thread
     pub(crate) thread_handle: Option<std::thread::JoinHandle<()>>,
+    /// execution state, used to close the state sink queue
+    pub(crate) execution_state: Arc<RwLock<ExecutionState>>,
+    /// handle used to join the state sink thread
+    pub(crate) state_sink_handle: Option<std::thread::JoinHandle<()>>,
 }
 
 impl ExecutionManager for ExecutionManagerImpl {
@@ -291,8 +397,19 @@ impl ExecutionManager for ExecutionManagerImpl {
         }
         // join the execution thread
         if let Some(join_handle) = self.thread_handle.take() {
-            join_handle.join().expect("VM controller thread panicked");
+            if let Err(err) = join_handle.join() {
+                warn!("VM controller thread panicked: {:?}", err);
+            }
+        }
+        // close the state sink queue and wait for the pending changes to be written
+        self.execution_state.write().state_sink = None;
+        if let Some(join_handle) = self.state_sink_handle.take() {
+            if let Err(err) = join_handle.join() {
+                warn!("state sink thread panicked: {:?}", err);
+            }
         }
+        // close the event store
+        self.execution_state.write().event_db = None;
         info!("execution controller stopped");
     }
 }
@@ -0,0 +1,144 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! On-disk store of the final execution events.
+//!
+//! The in-memory store only keeps the latest `max_final_events`, so an indexer that stops for a while loses the
+//! events emitted in the meantime. When enabled, the final events are also written in a RocksDB database, keyed
+//! by slot and index in the slot, and kept according to the retention configured by periods and by bytes.
+//! The events of a slot are written at once when it becomes final.
+
+use massa_execution_exports::{EventStore, EventStoreConfig, ExecutionError};
+use massa_models::execution::EventFilter;
+use massa_models::output_event::SCOutputEvent;
+use massa_models::slot::Slot;
+use rocksdb::{Direction, IteratorMode, Options, WriteBatch, DB};
+use tracing::warn;
+
+/// Size of a key: slot then index of the event in the slot
+const KEY_SIZE: usize = 9 + 8;
+
+/// Key of an event, in the order of emission
+fn event_key(slot: &Slot, index_in_slot: u64) -> Vec<u8> {
+    let mut key = Vec::with_capacity(KEY_SIZE);
+    key.extend(slot.to_bytes_key());
+    key.extend(index_in_slot.to_be_bytes());
+    key
+}
+
+/// Period of the event at `key`
+fn key_period(key: &[u8]) -> u64 {
+    u64::from_be_bytes(key[..8].try_into().expect("invalid event key"))
+}
+
+/// Final execution events stored on disk
+pub(crate) struct EventDB {
+    db: DB,
+    config: EventStoreConfig,
+    /// total size of the keys and values in the database
+    total_bytes: u64,
+}
+
+impl EventDB {
+    /// Opens the database at `config.path`, creating it if needed
+    pub fn open(config: EventStoreConfig) -> Result<Self, ExecutionError> {
+        let mut db_opts = Options::default();
+        db_opts.create_if_missing(true);
+        let db = DB::open(&db_opts, &config.path).map_err(|err| {
+            ExecutionError::EventStoreError(format!(
+                "could not open {}: {}",
+                config.path.display(),
+                err
+            ))
+        })?;
+        let total_bytes = db
+            .iterator(IteratorMode::Start)
+            .flatten()
+            .map(|(key, value)| (key.len() + value.len()) as u64)
+            .sum();
+        Ok(EventDB {
+            db,
+            config,
+            total_bytes,
+        })
+    }
+
+    /// Stores the events of the final slot `slot` and drops the events out of the retention
+    pub fn append(&mut self, slot: &Slot, events: &EventStore) {
+        let mut batch = WriteBatch::default();
+        for event in events.0.iter() {
+            let key = event_key(&event.context.slot, event.context.index_in_slot);
+            let value = match serde_json::to_vec(event) {
+                Ok(value) => value,
+                Err(err) => {
+                    warn!("could not serialize an event of slot {}: {}", slot, err);
+                    continue;
+                }
+            };
+            // the events of a slot executed again after a restart replace the stored ones
+            if let Ok(Some(previous)) = self.db.get(&key) {
+                self.total_bytes = self
+                    .total_bytes
+                    .saturating_sub((key.len() + previous.len()) as u64);
+            }
+            self.total_bytes += (key.len() + value.len()) as u64;
+            batch.put(key, value);
+        }
+        if let Err(err) = self.db.write(batch) {
+            warn!("could not store the events of slot {}: {}", slot, err);
+        }
+        self.prune(slot);
+    }
+
+    /// Drops the oldest events until the stored ones fit in the retention, `latest_slot` being the latest final slot
+    fn prune(&mut self, latest_slot: &Slot) {
+        let min_period = match self.config.max_periods {
+            0 => 0,
+            max_periods => (latest_slot.period + 1).saturating_sub(max_periods),
+        };
+        let max_bytes = match self.config.max_bytes {
+            0 => u64::MAX,
+            max_bytes => max_bytes,
+        };
+        let mut batch = WriteBatch::default();
+        let mut total_bytes = self.total_bytes;
+        for (key, value) in self.db.iterator(IteratorMode::Start).flatten() {
+            if key_period(&key) >= min_period && total_bytes <= max_bytes {
+                break;
+            }
+            total_bytes = total_bytes.saturating_sub((key.len() + value.len()) as u64);
+            batch.delete(key);
+        }
+        if batch.is_empty() {
+            return;
+        }
+        match self.db.write(batch) {
+            Ok(()) => self.total_bytes = total_bytes,
+            Err(err) => warn!("could not drop the outdated events: {}", err),
+        }
+    }
+
+    /// Stored events selected by `filter`, the oldest first, at most `max_query_events`
+    pub fn get_filtered_sc_output_events(&self, filter: &EventFilter) -> Vec<SCOutputEvent> {
+        let start_key = filter.start.map(|slot| event_key(&slot, 0));
+        let mode = match &start_key {
+            Some(key) => IteratorMode::From(key, Direction::Forward),
+            None => IteratorMode::Start,
+        };
+        let end_key = filter.end.map(|slot| event_key(&slot, 0));
+        let mut events = Vec::new();
+        for (key, value) in self.db.iterator(mode).flatten() {
+            if events.len() >= self.config.max_query_events {
+                break;
+            }
+            if let Some(end_key) = &end_key && key.as_ref() >= end_key.as_slice() {
+                break;
+            }
+            match serde_json::from_slice::<SCOutputEvent>(&value) {
+                Ok(event) if filter.matches(&event) => events.push(event),
+                Ok(_) => {}
+                Err(err) => warn!("could not deserialize a stored event: {}", err),
+            }
+        }
+        events
+    }
+}
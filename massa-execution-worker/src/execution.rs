@@ -10,20 +10,28 @@
 
 use crate::active_history::{ActiveHistory, HistorySearchResult};
 use crate::context::{ExecutionContext, ExecutionContextSnapshot};
+use crate::event_db::EventDB;
+use crate::execution_fork::ExecutionFork;
 use crate::interface_impl::InterfaceImpl;
 use crate::module_cache::ModuleCache;
+use crate::operation_index_db::OperationIndexDB;
+use crate::speculative_ledger::SpeculativeLedger;
+use crate::staking_rewards::StakingRewardsTracker;
+use crate::state_sink::{finalized_state_changes, StateSinkSender};
 use crate::stats::ExecutionStatsCounter;
-use massa_async_pool::AsyncMessage;
+use massa_async_pool::{AsyncMessage, AsyncMessageId, Change, SCHEDULED_TRANSFER_HANDLER};
 use massa_execution_exports::{
     EventStore, ExecutionConfig, ExecutionError, ExecutionOutput, ExecutionStackElement,
     ReadOnlyExecutionOutput, ReadOnlyExecutionRequest, ReadOnlyExecutionTarget,
+    ReadOnlyResourceUsage, StorageRentInfo,
 };
-use massa_final_state::FinalState;
+use massa_final_state::{FinalState, StateChanges};
 use massa_ledger_exports::{SetOrDelete, SetUpdateOrDelete};
-use massa_models::address::ExecutionAddressCycleInfo;
+use massa_models::address::{ExecutionAddressCycleInfo, StakingRewards};
 use massa_models::bytecode::Bytecode;
+use massa_models::datastore::DatastoreChunk;
 use massa_models::execution::EventFilter;
-use massa_models::output_event::SCOutputEvent;
+use massa_models::output_event::{latest_revert_reason, SCOutputEvent};
 use massa_models::prehash::{PreHashMap, PreHashSet};
 use massa_models::stats::ExecutionStats;
 use massa_models::vesting_range::VestingRange;
@@ -32,11 +40,12 @@ use massa_models::{
     block_id::BlockId,
     operation::{OperationId, OperationType, SecureShareOperation},
 };
-use massa_models::{amount::Amount, slot::Slot};
+use massa_models::{amount::Amount, slot::Slot, timeslots::get_block_slot_timestamp};
 use massa_pos_exports::SelectorController;
 use massa_sc_runtime::{Interface, Response, RuntimeModule};
 use massa_storage::Storage;
 use massa_time::MassaTime;
+use massa_versioning::MipComponent;
 use parking_lot::{Mutex, RwLock};
 use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::sync::Arc;
@@ -66,6 +75,8 @@ pub(crate) struct ExecutionState {
     pub final_cursor: Slot,
     // store containing execution events that became final
     final_events: EventStore,
+    // staking rewards credited by final executions
+    final_staking_rewards: StakingRewardsTracker,
     // final state with atomic R/W access
     final_state: Arc<RwLock<FinalState>>,
     // execution context (see documentation in context.rs)
@@ -78,6 +89,14 @@ pub(crate) struct ExecutionState {
     module_cache: Arc<RwLock<ModuleCache>>,
     // Map of vesting addresses
     vesting_registry: Arc<PreHashMap<Address, Vec<VestingRange>>>,
+    /// queue of the state sink receiving the finalized state changes, if one is configured
+    pub(crate) state_sink: Option<StateSinkSender>,
+    /// on-disk store of the final events, if enabled
+    pub(crate) event_db: Option<EventDB>,
+    /// on-disk index of the operations of the final blocks, if enabled
+    pub(crate) operation_index_db: Option<OperationIndexDB>,
+    /// execution forks by name
+    forks: HashMap<String, ExecutionFork>,
 }
 
 impl ExecutionState {
@@ -111,6 +130,17 @@ impl ExecutionState {
             config.max_module_cache_size,
         )));
 
+        // Open the on-disk store of the final events
+        let event_db = config.event_store.enabled.then(|| {
+            EventDB::open(config.event_store.clone()).unwrap_or_else(|err| panic!("{}", err))
+        });
+
+        // Open the on-disk index of the operations of the final blocks
+        let operation_index_db = config.operation_index.enabled.then(|| {
+            OperationIndexDB::open(config.operation_index.clone())
+                .unwrap_or_else(|err| panic!("{}", err))
+        });
+
         // Create an empty placeholder execution context, with shared atomic access
         let execution_context = Arc::new(Mutex::new(ExecutionContext::new(
             config.clone(),
@@ -135,6 +165,11 @@ impl ExecutionState {
             active_history,
             // empty final event store: it is not recovered through bootstrap
             final_events: Default::default(),
+            // empty staking rewards: they are not recovered through bootstrap
+            final_staking_rewards: StakingRewardsTracker::new(
+                config.max_staking_rewards_cycles,
+                config.max_staking_rewards_addresses,
+            ),
             // no active slots executed yet: set active_cursor to the last final block
             active_cursor: last_final_slot,
             final_cursor: last_final_slot,
@@ -142,6 +177,10 @@ impl ExecutionState {
             module_cache,
             config,
             vesting_registry,
+            state_sink: None,
+            event_db,
+            operation_index_db,
+            forks: HashMap::new(),
         }
     }
 
@@ -168,6 +207,21 @@ impl ExecutionState {
             );
         }
 
+        // record the credited staking rewards
+        self.final_staking_rewards.register(
+            exec_out.slot.get_cycle(self.config.periods_per_cycle),
+            &exec_out.staking_rewards,
+        );
+
+        // build the changes streamed to the state sink before they are consumed by the final state
+        let mut sink_changes = self.state_sink.as_ref().map(|_| {
+            finalized_state_changes(
+                exec_out.slot,
+                exec_out.block_id,
+                &exec_out.state_changes.ledger_changes,
+            )
+        });
+
         // apply state changes to the final ledger
         self.final_state
             .write()
@@ -184,6 +238,15 @@ impl ExecutionState {
 
         // append generated events to the final event store
         exec_out.events.finalize();
+        if let (Some(state_sink), Some(mut changes)) =
+            (self.state_sink.as_mut(), sink_changes.take())
+        {
+            changes.events = exec_out.events.0.iter().cloned().collect();
+            state_sink.send(changes);
+        }
+        if let Some(event_db) = self.event_db.as_mut() {
+            event_db.append(&exec_out.slot, &exec_out.events);
+        }
         self.final_events.extend(exec_out.events);
         self.final_events.prune(self.config.max_final_events);
     }
@@ -233,17 +296,43 @@ impl ExecutionState {
             ));
         }
 
-        // debit the fee from the operation sender
+        // ignore the operation if its sequence is not the next one expected from the sender
+        if let Some(sequence) = operation.content.sequence {
+            let next_sequence = context.get_next_operation_sequence(&sender_addr);
+            if sequence != next_sequence {
+                return Err(ExecutionError::IncludeOperationError(format!(
+                    "operation sequence {} does not match the next sequence {} of {}",
+                    sequence, next_sequence, sender_addr
+                )));
+            }
+        }
+
+        // debit the fee from the operation sender, or from its sponsor if any
         // fail execution if there are not enough coins
-        if let Err(err) =
-            context.transfer_coins(Some(sender_addr), None, operation.content.fee, false)
-        {
+        if let Err(err) = context.transfer_coins(
+            Some(operation.get_fee_payer()),
+            None,
+            operation.content.fee,
+            false,
+        ) {
             return Err(ExecutionError::IncludeOperationError(format!(
                 "could not spend fees: {}",
                 err
             )));
         }
 
+        // consume the sequence of the operation: it is kept even if the execution fails, like the fees
+        if let Some(sequence) = operation.content.sequence {
+            context
+                .record_operation_sequence(&sender_addr, sequence)
+                .map_err(|err| {
+                    ExecutionError::IncludeOperationError(format!(
+                        "could not record the operation sequence: {}",
+                        err
+                    ))
+                })?;
+        }
+
         // from here, fees transferred. Op will be executed just after in the context of a snapshot.
 
         // save a snapshot of the context to revert any further changes on error
@@ -305,6 +394,15 @@ impl ExecutionState {
             ));
         }
 
+        // check that the fee covers the size of the operation, ex: the recipients of a multi-recipient transaction
+        let min_fee = operation.get_min_fee();
+        if operation.content.fee < min_fee {
+            return Err(ExecutionError::IncludeOperationError(format!(
+                "fee {} is lower than the minimal fee {} of the operation",
+                operation.content.fee, min_fee
+            )));
+        }
+
         // get operation ID
         let operation_id = operation.id;
 
@@ -336,6 +434,20 @@ impl ExecutionState {
             OperationType::Transaction { .. } => {
                 self.execute_transaction_op(&operation.content.op, sender_addr)
             }
+            OperationType::MultiTransaction { .. } => {
+                self.execute_multi_transaction_op(&operation.content.op, sender_addr)
+            }
+            OperationType::ScheduledTransaction { .. } => self.execute_scheduled_transaction_op(
+                &operation.content.op,
+                sender_addr,
+                operation_id,
+            ),
+            OperationType::CancelScheduledTransaction { .. } => {
+                self.execute_cancel_scheduled_transaction_op(&operation.content.op, sender_addr)
+            }
+            OperationType::RegisterBlsKey { .. } => {
+                self.execute_register_bls_key_op(&operation.content.op, sender_addr)
+            }
         };
 
         {
@@ -351,10 +463,7 @@ impl ExecutionState {
                 ),
                 Err(err) => {
                     // an error occurred: emit error event and reset context to snapshot
-                    let err = ExecutionError::RuntimeError(format!(
-                        "runtime error when executing operation {}: {}",
-                        operation_id, &err
-                    ));
+                    let err = ExecutionError::OperationError(operation_id, Box::new(err));
                     debug!("{}", &err);
                     context.reset_to_snapshot(context_snapshot, err);
 
@@ -491,11 +600,12 @@ impl ExecutionState {
         sender_addr: Address,
     ) -> Result<(), ExecutionError> {
         // process transaction operations only
-        let (recipient_address, amount) = match operation {
+        let (recipient_address, amount, memo) = match operation {
             OperationType::Transaction {
                 recipient_address,
                 amount,
-            } => (recipient_address, amount),
+                memo,
+            } => (recipient_address, amount, memo),
             _ => panic!("unexpected operation type"),
         };
 
@@ -511,6 +621,22 @@ impl ExecutionState {
             operation_datastore: None,
         }];
 
+        // burn the cost of the memo, charged by size
+        if let Some(memo) = memo {
+            let memo_cost = self
+                .config
+                .transaction_memo_cost_per_byte
+                .saturating_mul_u64(memo.len() as u64);
+            if let Err(err) = context.transfer_coins(Some(sender_addr), None, memo_cost, false) {
+                return Err(ExecutionError::TransactionError(format!(
+                    "could not pay the {} coins of the memo of {} bytes: {}",
+                    memo_cost,
+                    memo.len(),
+                    err
+                )));
+            }
+        }
+
         // send `roll_price` * `roll_count` coins from the sender to the recipient
         if let Err(err) =
             context.transfer_coins(Some(sender_addr), Some(*recipient_address), *amount, false)
@@ -524,6 +650,257 @@ impl ExecutionState {
         Ok(())
     }
 
+    /// Execute an operation of type `MultiTransaction`: all the recipients are paid or none
+    /// Will panic if called with another operation type
+    ///
+    /// # Arguments
+    /// * `operation`: the `WrappedOperation` to process, must be a `MultiTransaction`
+    /// * `sender_addr`: address of the sender
+    pub fn execute_multi_transaction_op(
+        &self,
+        operation: &OperationType,
+        sender_addr: Address,
+    ) -> Result<(), ExecutionError> {
+        // process multi-recipient transaction operations only
+        let outputs = match operation {
+            OperationType::MultiTransaction { outputs } => outputs,
+            _ => panic!("unexpected operation type"),
+        };
+
+        // acquire write access to the context
+        let mut context = context_guard!(self);
+
+        // Set call stack
+        // This needs to be defined before anything can fail, so that the emitted event contains the right stack
+        context.stack = vec![ExecutionStackElement {
+            address: sender_addr,
+            coins: outputs.iter().fold(Amount::zero(), |total, output| {
+                total.saturating_add(output.amount)
+            }),
+            owned_addresses: vec![sender_addr],
+            operation_datastore: None,
+        }];
+
+        // send the amount of each output from the sender to its recipient.
+        // On failure, the caller resets the context to its snapshot: no recipient is paid.
+        for output in outputs {
+            if let Err(err) = context.transfer_coins(
+                Some(sender_addr),
+                Some(output.recipient_address),
+                output.amount,
+                false,
+            ) {
+                return Err(ExecutionError::TransactionError(format!(
+                    "transfer of {} coins from {} to {} failed: {}",
+                    output.amount, sender_addr, output.recipient_address, err
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Execute an operation of type `ScheduledTransaction`: the amount is spent from the sender
+    /// and held by an asynchronous message credited to the recipient at the activation slot
+    /// Will panic if called with another operation type
+    ///
+    /// # Arguments
+    /// * `operation`: the `WrappedOperation` to process, must be a `ScheduledTransaction`
+    /// * `sender_addr`: address of the sender
+    /// * `operation_id`: ID of the operation, identifying the scheduled transfer
+    pub fn execute_scheduled_transaction_op(
+        &self,
+        operation: &OperationType,
+        sender_addr: Address,
+        operation_id: OperationId,
+    ) -> Result<(), ExecutionError> {
+        // process scheduled transaction operations only
+        let (recipient_address, amount, activation_slot) = match operation {
+            OperationType::ScheduledTransaction {
+                recipient_address,
+                amount,
+                activation_slot,
+            } => (recipient_address, amount, activation_slot),
+            _ => panic!("unexpected operation type"),
+        };
+
+        // acquire write access to the context
+        let mut context = context_guard!(self);
+
+        // Set call stack
+        // This needs to be defined before anything can fail, so that the emitted event contains the right stack
+        context.stack = vec![ExecutionStackElement {
+            address: sender_addr,
+            coins: *amount,
+            owned_addresses: vec![sender_addr],
+            operation_datastore: None,
+        }];
+
+        // check the activation slot
+        let slot = context.slot;
+        if activation_slot.thread >= self.config.thread_count {
+            return Err(ExecutionError::TransactionError(format!(
+                "activation slot {} has an invalid thread",
+                activation_slot
+            )));
+        }
+        if *activation_slot <= slot
+            || activation_slot.period
+                > slot
+                    .period
+                    .saturating_add(self.config.max_scheduled_transfer_delay)
+        {
+            return Err(ExecutionError::TransactionError(format!(
+                "activation slot {} must be after slot {} and at most {} periods after it",
+                activation_slot, slot, self.config.max_scheduled_transfer_delay
+            )));
+        }
+
+        // check the number of pending scheduled transfers of the sender
+        let pending_count = context.get_scheduled_transfers(&sender_addr).len() as u64;
+        if pending_count >= self.config.max_scheduled_transfers_per_address {
+            return Err(ExecutionError::TransactionError(format!(
+                "address {} already has {} pending scheduled transfers",
+                sender_addr, pending_count
+            )));
+        }
+
+        // spend the amount from the sender, it is held by the message until the activation slot
+        if let Err(err) = context.transfer_coins(Some(sender_addr), None, *amount, false) {
+            return Err(ExecutionError::TransactionError(format!(
+                "could not spend the {} scheduled coins of {}: {}",
+                amount, sender_addr, err
+            )));
+        }
+
+        // the message is valid during the period following the activation slot,
+        // it is executed at the first slot of that period since it does not use any gas
+        let emission_index = context.created_message_index;
+        context.push_new_message(AsyncMessage::new_with_hash(
+            slot,
+            emission_index,
+            sender_addr,
+            *recipient_address,
+            SCHEDULED_TRANSFER_HANDLER.to_string(),
+            0,
+            Amount::zero(),
+            *amount,
+            *activation_slot,
+            Slot::new(activation_slot.period + 1, activation_slot.thread),
+            operation_id.to_bytes().to_vec(),
+            None,
+        ));
+        context.created_message_index += 1;
+
+        Ok(())
+    }
+
+    /// Execute an operation of type `CancelScheduledTransaction`: the pending scheduled transfer
+    /// is removed from the asynchronous pool and its amount is reimbursed to the sender
+    /// Will panic if called with another operation type
+    ///
+    /// # Arguments
+    /// * `operation`: the `WrappedOperation` to process, must be a `CancelScheduledTransaction`
+    /// * `sender_addr`: address of the sender, which must have scheduled the transfer
+    pub fn execute_cancel_scheduled_transaction_op(
+        &self,
+        operation: &OperationType,
+        sender_addr: Address,
+    ) -> Result<(), ExecutionError> {
+        // process scheduled transaction cancellations only
+        let operation_id = match operation {
+            OperationType::CancelScheduledTransaction { operation_id } => operation_id,
+            _ => panic!("unexpected operation type"),
+        };
+
+        // acquire write access to the context
+        let mut context = context_guard!(self);
+
+        // Set call stack
+        // This needs to be defined before anything can fail, so that the emitted event contains the right stack
+        context.stack = vec![ExecutionStackElement {
+            address: sender_addr,
+            coins: Amount::zero(),
+            owned_addresses: vec![sender_addr],
+            operation_datastore: None,
+        }];
+
+        // find the pending transfer among the ones of the sender
+        let Some((msg_id, msg)) = context
+            .get_scheduled_transfers(&sender_addr)
+            .into_iter()
+            .find(|(_id, msg)| msg.scheduled_transfer_operation_id() == Some(*operation_id))
+        else {
+            return Err(ExecutionError::TransactionError(format!(
+                "no pending transfer of {} scheduled by operation {}",
+                sender_addr, operation_id
+            )));
+        };
+
+        // reimburse the held amount, then remove the message: its removal cannot be reverted
+        if let Err(err) = context.transfer_coins(None, Some(sender_addr), msg.coins, false) {
+            return Err(ExecutionError::TransactionError(format!(
+                "could not reimburse the {} scheduled coins to {}: {}",
+                msg.coins, sender_addr, err
+            )));
+        }
+        context.remove_async_message(&msg_id);
+
+        Ok(())
+    }
+
+    /// Execute an operation of type `RegisterBlsKey`: records the BLS key of the sender, with which it signs
+    /// the endorsements aggregated in the block headers.
+    /// Will panic if called with another operation type
+    ///
+    /// # Arguments
+    /// * `operation`: the `WrappedOperation` to process, must be a `RegisterBlsKey`
+    /// * `sender_addr`: address of the sender, which must not have registered a key yet
+    pub fn execute_register_bls_key_op(
+        &self,
+        operation: &OperationType,
+        sender_addr: Address,
+    ) -> Result<(), ExecutionError> {
+        // process BLS key registrations only
+        let (public_key, proof_of_possession) = match operation {
+            OperationType::RegisterBlsKey {
+                public_key,
+                proof_of_possession,
+            } => (public_key, proof_of_possession),
+            _ => panic!("unexpected operation type"),
+        };
+
+        // acquire write access to the context
+        let mut context = context_guard!(self);
+
+        // Set call stack
+        // This needs to be defined before anything can fail, so that the emitted event contains the right stack
+        context.stack = vec![ExecutionStackElement {
+            address: sender_addr,
+            coins: Amount::zero(),
+            owned_addresses: vec![sender_addr],
+            operation_datastore: None,
+        }];
+
+        // the key cannot be changed: the headers it signed are checked against it
+        if context.has_registered_bls_key(&sender_addr) {
+            return Err(ExecutionError::TransactionError(format!(
+                "{} already registered a BLS key",
+                sender_addr
+            )));
+        }
+
+        // aggregating the signatures of a key is only safe once its possession is proven
+        if let Err(err) = public_key.verify_possession(proof_of_possession) {
+            return Err(ExecutionError::TransactionError(format!(
+                "invalid proof of possession of the BLS key {}: {}",
+                public_key, err
+            )));
+        }
+
+        context.register_bls_key(&sender_addr, *public_key)
+    }
+
     /// Execute an operation of type `ExecuteSC`
     /// Will panic if called with another operation type
     ///
@@ -719,6 +1096,23 @@ impl ExecutionState {
                 },
             ];
 
+            // a scheduled transfer only credits its coins to the target address
+            if message.is_scheduled_transfer() {
+                if let Err(err) =
+                    context.transfer_coins(None, Some(message.destination), message.coins, false)
+                {
+                    // coin crediting failed: reset context to snapshot and reimburse sender
+                    let err = ExecutionError::RuntimeError(format!(
+                        "could not credit the coins of a scheduled transfer: {}",
+                        err
+                    ));
+                    context.reset_to_snapshot(context_snapshot, err.clone());
+                    context.cancel_async_message(&message);
+                    return Err(err);
+                }
+                return Ok(());
+            }
+
             // If there is no target bytecode or if message data is invalid,
             // reimburse sender with coins and quit
             let bytecode = match bytecode {
@@ -814,14 +1208,23 @@ impl ExecutionState {
             self.module_cache.clone(),
             self.vesting_registry.clone(),
         );
+        execution_context.execution_version = self.execution_version_at(slot);
 
         // Get asynchronous messages to execute
         let messages = execution_context.take_async_batch(self.config.max_async_gas);
         debug!("executing {} messages at slot {}", messages.len(), slot);
 
+        // Priority fees of the executed messages, paid to the block producer (burned if the slot is a miss)
+        let async_priority_fees = messages.iter().fold(Amount::zero(), |total, (_, message)| {
+            total.saturating_add(message.priority_fee)
+        });
+
         // Apply the created execution context for slot execution
         *context_guard!(self) = execution_context;
 
+        // Charge the storage rent if the slot starts a cycle
+        context_guard!(self).charge_storage_rent();
+
         // Try executing asynchronous messages.
         // Effects are cancelled on failure and the sender is reimbursed.
         for (opt_bytecode, message) in messages {
@@ -830,6 +1233,9 @@ impl ExecutionState {
             }
         }
 
+        // Staking rewards credited at this slot
+        let mut staking_rewards: PreHashMap<Address, StakingRewards> = Default::default();
+
         // Check if there is a block at this slot
         if let Some((block_id, block_store)) = exec_target {
             // Retrieve the block from storage
@@ -857,15 +1263,28 @@ impl ExecutionState {
             debug!("executing {} operations at slot {}", operations.len(), slot);
 
             // gather all available endorsement creators and target blocks
+            let header = &stored_block.content.header.content;
             let (endorsement_creators, endorsement_targets): &(Vec<Address>, Vec<BlockId>) =
-                &stored_block
-                    .content
-                    .header
-                    .content
-                    .endorsements
-                    .iter()
-                    .map(|endo| (endo.content_creator_address, endo.content.endorsed_block))
-                    .unzip();
+                &match &header.aggregated_endorsements {
+                    // the signers of an aggregate are the endorsers drawn at their index,
+                    // all endorsing the parent in the thread of the block
+                    Some(aggregate) => {
+                        let selection = selector
+                            .get_selection(*slot)
+                            .expect("couldn't get the endorsers of an executed block");
+                        let endorsed_block = header.parents[slot.thread as usize];
+                        aggregate
+                            .signers
+                            .iter_ones()
+                            .map(|index| (selection.endorsements[index], endorsed_block))
+                            .unzip()
+                    }
+                    None => header
+                        .endorsements
+                        .iter()
+                        .map(|endo| (endo.content_creator_address, endo.content.endorsed_block))
+                        .unzip(),
+                };
 
             // deduce endorsement target block creators
             let endorsement_target_creators = {
@@ -885,7 +1304,7 @@ impl ExecutionState {
             let mut remaining_block_gas = self.config.max_gas_per_block;
 
             // Set block credits
-            let mut block_credits = self.config.block_reward;
+            let mut block_credits = self.config.block_reward.saturating_add(async_priority_fees);
 
             // Try executing the operations of this block in the order in which they appear in the block.
             // Errors are logged but do not interrupt the execution of the slot.
@@ -930,6 +1349,11 @@ impl ExecutionState {
                 ) {
                     Ok(_) => {
                         remaining_credit = remaining_credit.saturating_sub(block_credit_part);
+                        let rewards = staking_rewards.entry(*endorsement_creator).or_default();
+                        rewards.endorsement_count += 1;
+                        rewards.endorsement_rewards = rewards
+                            .endorsement_rewards
+                            .saturating_add(block_credit_part);
                     }
                     Err(err) => {
                         debug!(
@@ -948,6 +1372,11 @@ impl ExecutionState {
                 ) {
                     Ok(_) => {
                         remaining_credit = remaining_credit.saturating_sub(block_credit_part);
+                        let rewards = staking_rewards
+                            .entry(endorsement_target_creator)
+                            .or_default();
+                        rewards.endorsed_rewards =
+                            rewards.endorsed_rewards.saturating_add(block_credit_part);
                     }
                     Err(err) => {
                         debug!(
@@ -959,13 +1388,18 @@ impl ExecutionState {
             }
 
             // Credit block creator with remaining_credit
-            if let Err(err) =
-                context.transfer_coins(None, Some(block_creator_addr), remaining_credit, false)
-            {
-                debug!(
-                    "failed to credit {} coins to block creator {} on block execution: {}",
-                    remaining_credit, block_creator_addr, err
-                )
+            let rewards = staking_rewards.entry(block_creator_addr).or_default();
+            rewards.block_count += 1;
+            match context.transfer_coins(None, Some(block_creator_addr), remaining_credit, false) {
+                Ok(_) => {
+                    rewards.block_rewards = rewards.block_rewards.saturating_add(remaining_credit);
+                }
+                Err(err) => {
+                    debug!(
+                        "failed to credit {} coins to block creator {} on block execution: {}",
+                        remaining_credit, block_creator_addr, err
+                    )
+                }
             }
         } else {
             // the slot is a miss, check who was supposed to be the creator and update production stats
@@ -976,7 +1410,9 @@ impl ExecutionState {
         }
 
         // Finish slot and return the execution output
-        context_guard!(self).settle_slot()
+        let mut exec_out = context_guard!(self).settle_slot();
+        exec_out.staking_rewards = staking_rewards;
+        exec_out
     }
 
     /// Execute a candidate slot
@@ -1041,6 +1477,35 @@ impl ExecutionState {
             return;
         }
 
+        // take the version announced by the final block into account
+        if let Some((block_id, block_store)) = exec_target {
+            let announced_version = block_store
+                .read_blocks()
+                .get(block_id)
+                .map(|block| block.content.header.content.announced_version);
+            match (announced_version, self.slot_timestamp(slot)) {
+                (Some(announced_version), Some(timestamp)) => self
+                    .final_state
+                    .read()
+                    .mip_store
+                    .update_with_block_header(*slot, announced_version, timestamp),
+                _ => warn!(
+                    "could not take the announced version of final block {} into account",
+                    block_id
+                ),
+            }
+        }
+
+        // index the operations of the final block
+        if let (Some(operation_index_db), Some((block_id, block_store))) =
+            (self.operation_index_db.as_mut(), exec_target)
+        {
+            match block_store.read_blocks().get(block_id) {
+                Some(block) => operation_index_db.append(slot, block_id, &block.content.operations),
+                None => warn!("could not index the operations of final block {}", block_id),
+            }
+        }
+
         // check if the final slot execution result is already cached at the front of the speculative execution history
         let first_exec_output = self.active_history.write().0.pop_front();
         if let Some(exec_out) = first_exec_output {
@@ -1080,6 +1545,27 @@ impl ExecutionState {
         debug!("execute_final_slot: execution finished & result applied");
     }
 
+    /// Timestamp of `slot`, `None` on overflow
+    fn slot_timestamp(&self, slot: &Slot) -> Option<MassaTime> {
+        get_block_slot_timestamp(
+            self.config.thread_count,
+            self.config.t0,
+            self.config.genesis_timestamp,
+            *slot,
+        )
+        .ok()
+    }
+
+    /// Version of the execution component active at `slot`
+    fn execution_version_at(&self, slot: &Slot) -> u32 {
+        self.slot_timestamp(slot).map_or(0, |timestamp| {
+            self.final_state
+                .read()
+                .mip_store
+                .component_version_at(MipComponent::Execution, timestamp)
+        })
+    }
+
     /// Runs a read-only execution request.
     /// The executed bytecode appears to be able to read and write the consensus state,
     /// but all accumulated changes are simply returned as an `ExecutionOutput` object,
@@ -1091,7 +1577,7 @@ impl ExecutionState {
     /// # Returns
     ///  `ExecutionOutput` describing the output of the execution, or an error
     pub(crate) fn execute_readonly_request(
-        &self,
+        &mut self,
         req: ReadOnlyExecutionRequest,
     ) -> Result<ReadOnlyExecutionOutput, ExecutionError> {
         // TODO ensure that speculative things are reset after every execution ends (incl. on error and readonly)
@@ -1105,28 +1591,55 @@ impl ExecutionState {
             )));
         }
 
-        // set the execution slot to be the one after the latest executed active or final slot
-        let slot = if req.is_final {
-            self.final_cursor
-                .get_next_slot(self.config.thread_count)
-                .expect("slot overflow in readonly execution from final slot")
+        // set the execution slot to be the one after the latest executed slot of the fork, or active or final slot
+        let (mut slot, active_history) = if let Some(name) = &req.fork {
+            let fork = self.get_fork_mut(name)?;
+            (
+                fork.cursor
+                    .get_next_slot(self.config.thread_count)
+                    .expect("slot overflow in readonly execution from fork slot"),
+                fork.history.clone(),
+            )
+        } else if req.is_final {
+            (
+                self.final_cursor
+                    .get_next_slot(self.config.thread_count)
+                    .expect("slot overflow in readonly execution from final slot"),
+                self.active_history.clone(),
+            )
         } else {
-            self.active_cursor
-                .get_next_slot(self.config.thread_count)
-                .expect("slot overflow in readonly execution from active slot")
+            (
+                self.active_cursor
+                    .get_next_slot(self.config.thread_count)
+                    .expect("slot overflow in readonly execution from active slot"),
+                self.active_history.clone(),
+            )
         };
+        // an operation can only be included in a block of the thread of its creator
+        if let ReadOnlyExecutionTarget::Operation(operation) = &req.target {
+            let op_thread = operation
+                .content_creator_address
+                .get_thread(self.config.thread_count);
+            while slot.thread != op_thread {
+                slot = slot
+                    .get_next_slot(self.config.thread_count)
+                    .expect("slot overflow in readonly operation execution");
+            }
+        }
 
         // create a readonly execution context
-        let execution_context = ExecutionContext::readonly(
+        let mut execution_context = ExecutionContext::readonly(
             self.config.clone(),
             slot,
             req.max_gas,
             req.call_stack,
+            self.config.read_only_limits.restrict(&req.limits),
             self.final_state.clone(),
-            self.active_history.clone(),
+            active_history,
             self.module_cache.clone(),
             self.vesting_registry.clone(),
         );
+        execution_context.execution_version = self.execution_version_at(&slot);
 
         // run the interpreter according to the target type
         let exec_response = match req.target {
@@ -1150,7 +1663,7 @@ impl ExecutionState {
                     self.config.gas_costs.clone(),
                 )
                 .map_err(|err| {
-                    ExecutionError::RuntimeError(format!(
+                    self.readonly_runtime_error(format!(
                         "module execution error in execute_readonly_request BytecodeExecution: {}",
                         err,
                     ))
@@ -1185,7 +1698,7 @@ impl ExecutionState {
                     self.config.gas_costs.clone(),
                 )
                 .map_err(|err| {
-                    ExecutionError::RuntimeError(format!(
+                    self.readonly_runtime_error(format!(
                         "module execution error in execute_readonly_request BytecodeExecution: {}",
                         err,
                     ))
@@ -1195,17 +1708,128 @@ impl ExecutionState {
                     .save_module(&bytecode, module, response.init_cost);
                 response
             }
+            ReadOnlyExecutionTarget::Operation(operation) => {
+                // set the execution context for execution
+                *context_guard!(self) = execution_context;
+
+                // execute the operation as if it was the only one of a block at that slot
+                let mut remaining_gas = req.max_gas;
+                self.execute_operation(
+                    &operation,
+                    slot,
+                    &mut remaining_gas,
+                    &mut Amount::default(),
+                )?;
+
+                // only report the effects of the operation: the rest of the slot is not settled
+                let usage = self.readonly_usage(0)?;
+                let execution_output = context_guard!(self).take_output();
+
+                // the operations executed in a fork are applied to it
+                if let Some(name) = &req.fork {
+                    let thread_count = self.config.thread_count;
+                    self.get_fork_mut(name)?
+                        .apply(execution_output.clone(), thread_count);
+                }
+                return Ok(ReadOnlyExecutionOutput {
+                    out: execution_output,
+                    gas_cost: req.max_gas.saturating_sub(remaining_gas),
+                    call_result: Vec::new(),
+                    usage,
+                });
+            }
         };
 
         // return the execution output
+        let usage = self.readonly_usage(exec_response.ret.len())?;
         let execution_output = context_guard!(self).settle_slot();
         Ok(ReadOnlyExecutionOutput {
             out: execution_output,
             gas_cost: req.max_gas.saturating_sub(exec_response.remaining_gas),
             call_result: exec_response.ret,
+            usage,
         })
     }
 
+    /// Creates a fork of the active state named `name`, discarding the expired forks first
+    ///
+    /// # Returns
+    /// The latest active slot copied in the fork
+    pub fn create_fork(&mut self, name: String) -> Result<Slot, ExecutionError> {
+        let lifetime = self.config.execution_fork_lifetime.to_duration();
+        self.forks.retain(|_, fork| !fork.is_expired(lifetime));
+        if self.forks.contains_key(&name) {
+            return Err(ExecutionError::ForkError(format!(
+                "fork {} already exists",
+                name
+            )));
+        }
+        if self.forks.len() >= self.config.max_execution_forks {
+            return Err(ExecutionError::ForkError(format!(
+                "too many forks, the maximum is {}",
+                self.config.max_execution_forks
+            )));
+        }
+        let fork = ExecutionFork::new(&self.active_history.read(), self.active_cursor);
+        self.forks.insert(name, fork);
+        Ok(self.active_cursor)
+    }
+
+    /// Discards the fork named `name`
+    pub fn discard_fork(&mut self, name: &str) -> Result<(), ExecutionError> {
+        match self.forks.remove(name) {
+            Some(_) => Ok(()),
+            None => Err(ExecutionError::ForkError(format!("unknown fork {}", name))),
+        }
+    }
+
+    /// Gets the fork named `name` to execute a read-only request in it, if it has not expired
+    fn get_fork_mut(&mut self, name: &str) -> Result<&mut ExecutionFork, ExecutionError> {
+        let lifetime = self.config.execution_fork_lifetime.to_duration();
+        if self
+            .forks
+            .get(name)
+            .map_or(false, |fork| fork.is_expired(lifetime))
+        {
+            self.forks.remove(name);
+        }
+        let fork = self
+            .forks
+            .get_mut(name)
+            .ok_or_else(|| ExecutionError::ForkError(format!("unknown fork {}", name)))?;
+        fork.touch();
+        Ok(fork)
+    }
+
+    /// Resources used by the current read-only execution, including the `returned_bytes` of its result.
+    /// Fails if they exceed the read-only limits.
+    fn readonly_usage(
+        &self,
+        returned_bytes: usize,
+    ) -> Result<ReadOnlyResourceUsage, ExecutionError> {
+        let mut context = context_guard!(self);
+        context.charge_read_only_memory(returned_bytes)?;
+        Ok(context
+            .read_only_meter
+            .as_ref()
+            .map(|meter| meter.usage())
+            .unwrap_or_default())
+    }
+
+    /// Builds the error of a failed read-only execution,
+    /// reporting the revert reason set by the smart contract if any
+    fn readonly_runtime_error(&self, message: String) -> ExecutionError {
+        let context = context_guard!(self);
+        // the interface aborts the execution when it exceeds its resource limits
+        if let Err(err) = context.check_read_only_resources() {
+            return err;
+        }
+        match latest_revert_reason(context.events.0.iter()) {
+            Some(reason) => ExecutionError::RevertedError(message, reason.to_vec()),
+            None => ExecutionError::RuntimeError(message),
+        }
+    }
+
     /// Gets a balance both at the latest final and candidate executed slots
     pub fn get_final_and_candidate_balance(
         &self,
@@ -1223,6 +1847,44 @@ impl ExecutionState {
         )
     }
 
+    /// Gets the bytecode of an address at the latest candidate executed slot
+    pub fn get_candidate_bytecode(&self, address: &Address) -> Option<Bytecode> {
+        match self.active_history.read().fetch_bytecode(address) {
+            HistorySearchResult::Present(bytecode) => Some(bytecode),
+            HistorySearchResult::NoInfo => self.final_state.read().ledger.get_bytecode(address),
+            HistorySearchResult::Absent => None,
+        }
+    }
+
+    /// Gets the storage rent of an address, projected from the latest candidate executed slot
+    pub fn get_storage_rent(&self, address: &Address) -> StorageRentInfo {
+        let ledger = SpeculativeLedger::new(
+            self.final_state.clone(),
+            self.active_history.clone(),
+            self.config.max_datastore_key_length,
+            self.config.max_bytecode_size,
+            self.config.max_datastore_value_size,
+            self.config.storage_costs_constants,
+        );
+        let storage_bytes = ledger.get_storage_size(address);
+        let rent_per_cycle = self
+            .config
+            .storage_rent_constants
+            .rent_per_byte
+            .saturating_mul_u64(storage_bytes);
+        let rent_due_since = ledger.get_storage_rent_due(address);
+        StorageRentInfo {
+            storage_bytes,
+            rent_per_cycle,
+            rent_due_since,
+            reclamation_cycle: rent_due_since
+                .map(|cycle| cycle.saturating_add(self.config.storage_rent_constants.grace_cycles)),
+            paid_cycles: (!rent_per_cycle.is_zero()).then(|| {
+                ledger.get_balance(address).unwrap_or_default().to_raw() / rent_per_cycle.to_raw()
+            }),
+        }
+    }
+
     /// Gets roll counts both at the latest final and active executed slots
     pub fn get_final_and_candidate_rolls(&self, address: &Address) -> (u64, u64) {
         let final_rolls = self.final_state.read().pos_state.get_rolls_for(address);
@@ -1255,6 +1917,30 @@ impl ExecutionState {
         )
     }
 
+    /// Gets a chunk of a data entry value both at the latest final and active executed slots
+    pub fn get_final_and_active_data_entry_chunk(
+        &self,
+        address: &Address,
+        key: &[u8],
+        offset: u64,
+        length: u64,
+    ) -> (Option<DatastoreChunk>, Option<DatastoreChunk>) {
+        let final_chunk = self
+            .final_state
+            .read()
+            .ledger
+            .get_data_entry_chunk(address, key, offset, length);
+        let active_history = self.active_history.read();
+        let active_chunk = match active_history.fetch_active_history_data_entry_ref(address, key) {
+            HistorySearchResult::Present(value) => {
+                Some(DatastoreChunk::from_value(value, offset, length))
+            }
+            HistorySearchResult::NoInfo => final_chunk.clone(),
+            HistorySearchResult::Absent => None,
+        };
+        (final_chunk, active_chunk)
+    }
+
     /// Get every final and active datastore key of the given address
     pub fn get_final_and_candidate_datastore_keys(
         &self,
@@ -1321,6 +2007,63 @@ impl ExecutionState {
         }
     }
 
+    /// Returns a page of the stakers taken into account by the selector for a given cycle,
+    /// see `PoSFinalState::get_cycle_stakers`.
+    pub fn get_cycle_stakers(
+        &self,
+        cycle: u64,
+        min_rolls: u64,
+        offset: usize,
+        limit: usize,
+    ) -> (Vec<(Address, u64)>, usize) {
+        self.final_state
+            .read()
+            .pos_state
+            .get_cycle_stakers(cycle, min_rolls, offset, limit)
+    }
+
+    /// Returns a page of the messages of the final asynchronous pool by decreasing priority,
+    /// see `AsyncPool::get_messages`.
+    pub fn get_async_pool_messages(
+        &self,
+        offset: usize,
+        limit: usize,
+    ) -> (Vec<AsyncMessage>, usize) {
+        self.final_state
+            .read()
+            .async_pool
+            .get_messages(offset, limit)
+    }
+
+    /// Returns the pending scheduled transfers of `address`, final or candidate, by activation slot
+    pub fn get_scheduled_transfers(&self, address: &Address) -> Vec<AsyncMessage> {
+        let mut transfers: BTreeMap<AsyncMessageId, AsyncMessage> = self
+            .final_state
+            .read()
+            .async_pool
+            .get_scheduled_transfers(address)
+            .into_iter()
+            .collect();
+        for history_item in self.active_history.read().0.iter() {
+            for change in history_item.state_changes.async_pool_changes.0.iter() {
+                match change {
+                    Change::Add(msg_id, msg)
+                        if msg.is_scheduled_transfer() && msg.sender == *address =>
+                    {
+                        transfers.insert(*msg_id, msg.clone());
+                    }
+                    Change::Delete(msg_id) => {
+                        transfers.remove(msg_id);
+                    }
+                    _ => {}
+                }
+            }
+        }
+        let mut transfers: Vec<AsyncMessage> = transfers.into_values().collect();
+        transfers.sort_by_key(|msg| (msg.validity_start, msg.emission_slot, msg.emission_index));
+        transfers
+    }
+
     /// Gets execution events optionally filtered by:
     /// * start slot
     /// * end slot
@@ -1330,11 +2073,7 @@ impl ExecutionState {
     /// * event state (final, candidate or both)
     pub fn get_filtered_sc_output_event(&self, filter: EventFilter) -> Vec<SCOutputEvent> {
         match filter.is_final {
-            Some(true) => self
-                .final_events
-                .get_filtered_sc_output_events(&filter)
-                .into_iter()
-                .collect(),
+            Some(true) => self.get_filtered_final_events(&filter),
             Some(false) => self
                 .active_history
                 .read()
@@ -1343,8 +2082,7 @@ impl ExecutionState {
                 .flat_map(|item| item.events.get_filtered_sc_output_events(&filter))
                 .collect(),
             None => self
-                .final_events
-                .get_filtered_sc_output_events(&filter)
+                .get_filtered_final_events(&filter)
                 .into_iter()
                 .chain(
                     self.active_history
@@ -1357,6 +2095,38 @@ impl ExecutionState {
         }
     }
 
+    /// Gets the final events selected by `filter`, from the on-disk store if enabled
+    fn get_filtered_final_events(&self, filter: &EventFilter) -> Vec<SCOutputEvent> {
+        match &self.event_db {
+            Some(event_db) => event_db.get_filtered_sc_output_events(filter),
+            None => self
+                .final_events
+                .get_filtered_sc_output_events(filter)
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    /// Get the state changes caused by the execution of `slot`,
+    /// looked up in the active history, then in the history of the final state
+    pub fn get_slot_state_changes(&self, slot: &Slot) -> Option<StateChanges> {
+        if let Some(output) = self
+            .active_history
+            .read()
+            .0
+            .iter()
+            .find(|output| &output.slot == slot)
+        {
+            return Some(output.state_changes.clone());
+        }
+        self.final_state
+            .read()
+            .changes_history
+            .iter()
+            .find(|(history_slot, _)| history_slot == slot)
+            .map(|(_, changes)| changes.clone())
+    }
+
     /// List which operations inside the provided list were not executed
     pub fn unexecuted_ops_among(
         &self,
@@ -1407,6 +2177,40 @@ impl ExecutionState {
         context_guard!(self).get_address_future_deferred_credits(address, self.config.thread_count)
     }
 
+    /// Get future deferred credits of an address, as seen by the latest final slot
+    pub fn get_address_final_future_deferred_credits(
+        &self,
+        address: &Address,
+    ) -> BTreeMap<Slot, Amount> {
+        let min_slot = self
+            .final_cursor
+            .get_next_slot(self.config.thread_count)
+            .expect("unexpected slot overflow in get_address_final_future_deferred_credits");
+        self.final_state
+            .read()
+            .pos_state
+            .get_address_deferred_credits(address, min_slot)
+    }
+
+    /// Get the deferred credits of an address that were already credited and are still kept by the final state
+    pub fn get_address_executed_deferred_credits(
+        &self,
+        address: &Address,
+    ) -> BTreeMap<Slot, Amount> {
+        self.final_state
+            .read()
+            .pos_state
+            .get_address_executed_credits(address)
+    }
+
+    /// Get the staking rewards credited to an address by final executions, indexed by cycle
+    pub fn get_address_final_staking_rewards(
+        &self,
+        address: &Address,
+    ) -> BTreeMap<u64, StakingRewards> {
+        self.final_staking_rewards.get_address_rewards(address)
+    }
+
     /// Get the execution statuses of both speculative and final executions
     ///
     /// # Return
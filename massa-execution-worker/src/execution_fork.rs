@@ -0,0 +1,71 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Execution forks for what-if simulations.
+//!
+//! A fork is a named copy of the active history taken at its creation, on top of which read-only requests
+//! execute instead of the active state. The operations executed in a fork are appended to its history,
+//! so that a sequence of operations can be simulated step by step and queried between the steps.
+//! A fork only copies the active slots: it keeps reading the final state, which is unaffected by the fork,
+//! and it does not follow the candidate slots executed after its creation.
+
+use crate::active_history::ActiveHistory;
+use massa_execution_exports::ExecutionOutput;
+use massa_models::slot::Slot;
+use parking_lot::RwLock;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Temporary copy of the active state
+pub(crate) struct ExecutionFork {
+    /// active history at the creation of the fork, followed by the outputs of the operations applied to it
+    pub history: Arc<RwLock<ActiveHistory>>,
+    /// latest slot executed in the fork
+    pub cursor: Slot,
+    /// last time the fork was used
+    last_use: Instant,
+}
+
+impl ExecutionFork {
+    /// Forks `history`, whose latest executed slot is `cursor`
+    pub fn new(history: &ActiveHistory, cursor: Slot) -> Self {
+        ExecutionFork {
+            history: Arc::new(RwLock::new(ActiveHistory(history.0.clone()))),
+            cursor,
+            last_use: Instant::now(),
+        }
+    }
+
+    /// Whether the fork has not been used for `lifetime`
+    pub fn is_expired(&self, lifetime: Duration) -> bool {
+        self.last_use.elapsed() > lifetime
+    }
+
+    /// Marks the fork as used
+    pub fn touch(&mut self) {
+        self.last_use = Instant::now();
+    }
+
+    /// Appends the output of an operation executed in the fork, at a slot after its cursor.
+    /// The skipped slots are recorded as empty to keep the history consecutive.
+    pub fn apply(&mut self, output: ExecutionOutput, thread_count: u8) {
+        let mut history = self.history.write();
+        let mut slot = self
+            .cursor
+            .get_next_slot(thread_count)
+            .expect("slot overflow in execution fork");
+        while slot < output.slot {
+            history.0.push_back(ExecutionOutput {
+                slot,
+                block_id: None,
+                state_changes: Default::default(),
+                events: Default::default(),
+                staking_rewards: Default::default(),
+            });
+            slot = slot
+                .get_next_slot(thread_count)
+                .expect("slot overflow in execution fork");
+        }
+        self.cursor = output.slot;
+        history.0.push_back(output);
+    }
+}
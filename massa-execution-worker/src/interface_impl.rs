@@ -6,8 +6,9 @@
 //! See the definition of Interface in the massa-sc-runtime crate for functional details.
 
 use crate::context::ExecutionContext;
+use crate::native_crypto;
 use anyhow::{anyhow, bail, Result};
-use massa_async_pool::{AsyncMessage, AsyncMessageTrigger};
+use massa_async_pool::{AsyncMessage, AsyncMessageTrigger, SCHEDULED_TRANSFER_HANDLER};
 use massa_execution_exports::ExecutionConfig;
 use massa_execution_exports::ExecutionStackElement;
 use massa_models::bytecode::Bytecode;
@@ -103,6 +104,182 @@ impl InterfaceImpl {
         let context = Arc::new(Mutex::new(execution_context));
         InterfaceImpl::new(config, context)
     }
+
+    /// Adds an asynchronous message offering a priority fee to the context speculative asynchronous pool.
+    /// The priority fee is spent by the sender on emission, in addition to the fee,
+    /// and raises the priority of the message by being counted in its fee per gas.
+    ///
+    /// # Arguments
+    /// Same as `send_message`, with
+    /// * `raw_priority_fee`: Priority fee to pay, at most `max_async_priority_fee`
+    #[allow(clippy::too_many_arguments)]
+    pub fn send_message_with_priority_fee(
+        &self,
+        target_address: &str,
+        target_handler: &str,
+        validity_start: (u64, u8),
+        validity_end: (u64, u8),
+        max_gas: u64,
+        raw_fee: u64,
+        raw_priority_fee: u64,
+        raw_coins: u64,
+        data: &[u8],
+        filter: Option<(&str, Option<&[u8]>)>,
+    ) -> Result<()> {
+        if validity_start.1 >= self.config.thread_count {
+            bail!("validity start thread exceeds the configuration thread count")
+        }
+        if validity_end.1 >= self.config.thread_count {
+            bail!("validity end thread exceeds the configuration thread count")
+        }
+        if target_handler == SCHEDULED_TRANSFER_HANDLER {
+            bail!("the handler is reserved to scheduled transfers")
+        }
+        let mut execution_context = context_guard!(self);
+        let emission_slot = execution_context.slot;
+        let emission_index = execution_context.created_message_index;
+        let sender = execution_context.get_current_address()?;
+        let coins = Amount::from_raw(raw_coins);
+        let fee = Amount::from_raw(raw_fee);
+        let priority_fee = Amount::from_raw(raw_priority_fee);
+        if priority_fee > self.config.max_async_priority_fee {
+            bail!(
+                "priority fee {} exceeds the maximum of {}",
+                priority_fee,
+                self.config.max_async_priority_fee
+            )
+        }
+        execution_context.transfer_coins(Some(sender), None, coins, true)?;
+        execution_context.transfer_coins(Some(sender), None, fee, true)?;
+        execution_context.transfer_coins(Some(sender), None, priority_fee, true)?;
+        execution_context.push_new_message(
+            AsyncMessage::new_with_hash(
+                emission_slot,
+                emission_index,
+                sender,
+                Address::from_str(target_address)?,
+                target_handler.to_string(),
+                max_gas,
+                fee,
+                coins,
+                Slot::new(validity_start.0, validity_start.1),
+                Slot::new(validity_end.0, validity_end.1),
+                data.to_vec(),
+                filter
+                    .map(|(addr, key)| {
+                        let datastore_key = key.map(|k| k.to_vec());
+                        if let Some(ref k) = datastore_key {
+                            if k.len() > MAX_DATASTORE_KEY_LENGTH as usize {
+                                bail!("datastore key is too long")
+                            }
+                        }
+                        Ok::<AsyncMessageTrigger, _>(AsyncMessageTrigger {
+                            address: Address::from_str(addr)?,
+                            datastore_key,
+                        })
+                    })
+                    .transpose()?,
+            )
+            .with_priority_fee(priority_fee),
+        );
+        execution_context.created_message_index += 1;
+        Ok(())
+    }
+
+    /// Hashes givens byte array with keccak256, the hash function of the EVM
+    ///
+    /// # Arguments
+    /// * bytes: byte array to hash
+    ///
+    /// # Returns
+    /// The vector of bytes representation of the resulting hash
+    pub fn hash_keccak256(&self, bytes: &[u8]) -> Result<[u8; 32]> {
+        Ok(native_crypto::hash_keccak256(bytes))
+    }
+
+    /// Hashes givens byte array with sha3-256
+    ///
+    /// # Arguments
+    /// * bytes: byte array to hash
+    ///
+    /// # Returns
+    /// The vector of bytes representation of the resulting hash
+    pub fn hash_sha3_256(&self, bytes: &[u8]) -> Result<[u8; 32]> {
+        Ok(native_crypto::hash_sha3_256(bytes))
+    }
+
+    /// Hashes givens byte array with blake3
+    ///
+    /// # Arguments
+    /// * bytes: byte array to hash
+    ///
+    /// # Returns
+    /// The vector of bytes representation of the resulting hash
+    pub fn hash_blake3(&self, bytes: &[u8]) -> Result<[u8; 32]> {
+        Ok(native_crypto::hash_blake3(bytes))
+    }
+
+    /// Verifies an ECDSA signature on the secp256k1 curve (Bitcoin, EVM)
+    ///
+    /// # Arguments
+    /// * `message_hash`: 32 bytes hash of the signed message, ex: its keccak256 hash for EVM signatures
+    /// * signature: 64 bytes `r || s` signature, an additional EVM recovery byte being ignored
+    /// * `public_key`: SEC1 encoded public key, compressed or not
+    ///
+    /// # Returns
+    /// true if the signature verification succeeded, false otherwise.
+    /// Signatures with a high `s` are rejected, as in the EVM.
+    pub fn verify_secp256k1_signature(
+        &self,
+        message_hash: &[u8],
+        signature: &[u8],
+        public_key: &[u8],
+    ) -> Result<bool> {
+        let Ok(message_hash) = <&[u8; 32]>::try_from(message_hash) else {
+            bail!("the message hash must be 32 bytes long")
+        };
+        let signature = match signature
+            .get(..64)
+            .filter(|_| signature.len() <= 65)
+            .and_then(|bytes| <&[u8; 64]>::try_from(bytes).ok())
+        {
+            Some(sig) => sig,
+            None => return Ok(false),
+        };
+        Ok(native_crypto::verify_secp256k1_signature(
+            message_hash,
+            signature,
+            public_key,
+        ))
+    }
+
+    /// Verifies an ECDSA signature on the secp256r1 curve (P-256, passkeys and secure enclaves)
+    ///
+    /// # Arguments
+    /// * `message_hash`: 32 bytes hash of the signed message, usually its sha256 hash
+    /// * signature: 64 bytes `r || s` signature
+    /// * `public_key`: SEC1 encoded public key, compressed or not
+    ///
+    /// # Returns
+    /// true if the signature verification succeeded, false otherwise
+    pub fn verify_secp256r1_signature(
+        &self,
+        message_hash: &[u8],
+        signature: &[u8],
+        public_key: &[u8],
+    ) -> Result<bool> {
+        let Ok(message_hash) = <&[u8; 32]>::try_from(message_hash) else {
+            bail!("the message hash must be 32 bytes long")
+        };
+        let Ok(signature) = <&[u8; 64]>::try_from(signature) else {
+            return Ok(false);
+        };
+        Ok(native_crypto::verify_secp256r1_signature(
+            message_hash,
+            signature,
+            public_key,
+        ))
+    }
 }
 
 impl InterfaceClone for InterfaceImpl {
@@ -145,6 +322,17 @@ impl Interface for InterfaceImpl {
         // write-lock context
         let mut context = context_guard!(self);
 
+        // check the call depth of read-only executions
+        if let Some(limits) = &context.read_only_limits {
+            if context.stack.len() >= limits.max_call_depth as usize {
+                bail!(
+                    "call depth exceeds the read-only limit of {}",
+                    limits.max_call_depth
+                );
+            }
+        }
+        context.check_read_only_resources()?;
+
         // get target bytecode
         let bytecode = match context.get_bytecode(&to_address) {
             Some(bytecode) => bytecode,
@@ -633,6 +821,15 @@ impl Interface for InterfaceImpl {
     /// data: the string data that is the payload of the event
     fn generate_event(&self, data: String) -> Result<()> {
         let mut context = context_guard!(self);
+        if let Some(limits) = &context.read_only_limits {
+            if context.created_event_index >= limits.max_event_count {
+                bail!(
+                    "event count exceeds the read-only limit of {}",
+                    limits.max_event_count
+                );
+            }
+        }
+        context.check_read_only_resources()?;
         let event = context.event_create(data, false);
         context.event_emit(event);
         Ok(())
@@ -694,49 +891,18 @@ impl Interface for InterfaceImpl {
         data: &[u8],
         filter: Option<(&str, Option<&[u8]>)>,
     ) -> Result<()> {
-        if validity_start.1 >= self.config.thread_count {
-            bail!("validity start thread exceeds the configuration thread count")
-        }
-        if validity_end.1 >= self.config.thread_count {
-            bail!("validity end thread exceeds the configuration thread count")
-        }
-        let mut execution_context = context_guard!(self);
-        let emission_slot = execution_context.slot;
-        let emission_index = execution_context.created_message_index;
-        let sender = execution_context.get_current_address()?;
-        let coins = Amount::from_raw(raw_coins);
-        let fee = Amount::from_raw(raw_fee);
-        execution_context.transfer_coins(Some(sender), None, coins, true)?;
-        execution_context.transfer_coins(Some(sender), None, fee, true)?;
-        execution_context.push_new_message(AsyncMessage::new_with_hash(
-            emission_slot,
-            emission_index,
-            sender,
-            Address::from_str(target_address)?,
-            target_handler.to_string(),
+        self.send_message_with_priority_fee(
+            target_address,
+            target_handler,
+            validity_start,
+            validity_end,
             max_gas,
-            fee,
-            coins,
-            Slot::new(validity_start.0, validity_start.1),
-            Slot::new(validity_end.0, validity_end.1),
-            data.to_vec(),
-            filter
-                .map(|(addr, key)| {
-                    let datastore_key = key.map(|k| k.to_vec());
-                    if let Some(ref k) = datastore_key {
-                        if k.len() > MAX_DATASTORE_KEY_LENGTH as usize {
-                            bail!("datastore key is too long")
-                        }
-                    }
-                    Ok::<AsyncMessageTrigger, _>(AsyncMessageTrigger {
-                        address: Address::from_str(addr)?,
-                        datastore_key,
-                    })
-                })
-                .transpose()?,
-        ));
-        execution_context.created_message_index += 1;
-        Ok(())
+            raw_fee,
+            0,
+            raw_coins,
+            data,
+            filter,
+        )
     }
 
     /// Returns the period of the current execution slot
@@ -75,6 +75,15 @@
 //!
 //! ## `stats.rs`
 //! Defines a structure that gathers execution statistics.
+//!
+//! ## `event_db.rs`
+//! On-disk store of the final execution events, kept according to a retention by periods and by bytes.
+//!
+//! ## `resource_meter.rs`
+//! Measures the CPU time and memory used by read-only executions.
+//!
+//! ## `execution_fork.rs`
+//! Named copies of the active state in which read-only requests simulate sequences of operations.
 
 #![warn(missing_docs)]
 #![warn(unused_crate_dependencies)]
@@ -86,15 +95,22 @@
 mod active_history;
 mod context;
 mod controller;
+mod event_db;
 mod execution;
+mod execution_fork;
 mod interface_impl;
 mod module_cache;
+mod native_crypto;
+mod operation_index_db;
 mod request_queue;
+mod resource_meter;
 mod slot_sequencer;
 mod speculative_async_pool;
 mod speculative_executed_ops;
 mod speculative_ledger;
 mod speculative_roll_state;
+mod staking_rewards;
+mod state_sink;
 mod stats;
 mod worker;
 
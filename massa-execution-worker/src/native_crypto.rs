@@ -0,0 +1,253 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Hash functions and signature verifications of other ecosystems, offered to the smart contracts
+//! so that they can check proofs and signatures coming from them (bridges, EVM wallets, passkeys).
+//!
+//! The ECDSA verifications only handle public data, so they do not need to run in constant time.
+
+use num::{BigUint, One, Zero};
+
+/// Number of bytes absorbed by each Keccak-f\[1600\] permutation for a 256 bits output
+const KECCAK_256_RATE: usize = 136;
+
+/// Keccak sponge with a 256 bits output, `domain` being the first padding byte
+fn keccak_256_sponge(bytes: &[u8], domain: u8) -> [u8; 32] {
+    let mut padded = bytes.to_vec();
+    padded.push(domain);
+    padded.resize(
+        padded.len() + (KECCAK_256_RATE - padded.len() % KECCAK_256_RATE) % KECCAK_256_RATE,
+        0,
+    );
+    if let Some(last) = padded.last_mut() {
+        *last |= 0x80;
+    }
+    let mut state = [0u64; 25];
+    for block in padded.chunks(KECCAK_256_RATE) {
+        for (lane, word) in state.iter_mut().zip(block.chunks(8)) {
+            let mut word_bytes = [0u8; 8];
+            word_bytes.copy_from_slice(word);
+            *lane ^= u64::from_le_bytes(word_bytes);
+        }
+        keccak::f1600(&mut state);
+    }
+    let mut hash = [0u8; 32];
+    for (hash_bytes, lane) in hash.chunks_mut(8).zip(state.iter()) {
+        hash_bytes.copy_from_slice(&lane.to_le_bytes());
+    }
+    hash
+}
+
+/// Keccak256 hash, the hash function of the EVM (padded before the standardization of SHA-3)
+pub(crate) fn hash_keccak256(bytes: &[u8]) -> [u8; 32] {
+    keccak_256_sponge(bytes, 0x01)
+}
+
+/// SHA3-256 hash
+pub(crate) fn hash_sha3_256(bytes: &[u8]) -> [u8; 32] {
+    keccak_256_sponge(bytes, 0x06)
+}
+
+/// BLAKE3 hash
+pub(crate) fn hash_blake3(bytes: &[u8]) -> [u8; 32] {
+    ::blake3::hash(bytes).into()
+}
+
+/// Point in Jacobian coordinates `(X, Y, Z)`, standing for the affine point `(X / Z², Y / Z³)`,
+/// `None` being the point at infinity
+type JacobianPoint = Option<(BigUint, BigUint, BigUint)>;
+
+/// Short Weierstrass curve `y² = x³ + ax + b` over the prime field of order `p`,
+/// whose generator `g` has the prime order `n`
+struct Curve {
+    p: BigUint,
+    a: BigUint,
+    b: BigUint,
+    n: BigUint,
+    g: (BigUint, BigUint),
+}
+
+fn hex_constant(hex: &[u8]) -> BigUint {
+    BigUint::parse_bytes(hex, 16).expect("invalid curve constant")
+}
+
+impl Curve {
+    /// secp256k1, the curve of Bitcoin and of the EVM
+    fn secp256k1() -> Self {
+        Curve {
+            p: hex_constant(b"FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFC2F"),
+            a: BigUint::zero(),
+            b: BigUint::from(7u8),
+            n: hex_constant(b"FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364141"),
+            g: (
+                hex_constant(b"79BE667EF9DCBBAC55A06295CE870B07029BFCDB2DCE28D959F2815B16F81798"),
+                hex_constant(b"483ADA7726A3C4655DA4FBFC0E1108A8FD17B448A68554199C47D08FFB10D4B8"),
+            ),
+        }
+    }
+
+    /// secp256r1, also known as P-256
+    fn secp256r1() -> Self {
+        Curve {
+            p: hex_constant(b"FFFFFFFF00000001000000000000000000000000FFFFFFFFFFFFFFFFFFFFFFFF"),
+            a: hex_constant(b"FFFFFFFF00000001000000000000000000000000FFFFFFFFFFFFFFFFFFFFFFFC"),
+            b: hex_constant(b"5AC635D8AA3A93E7B3EBBD55769886BC651D06B0CC53B0F63BCE3C3E27D2604B"),
+            n: hex_constant(b"FFFFFFFF00000000FFFFFFFFFFFFFFFFBCE6FAADA7179E84F3B9CAC2FC632551"),
+            g: (
+                hex_constant(b"6B17D1F2E12C4247F8BCE6E563A440F277037D812DEB33A0F4A13945D898C296"),
+                hex_constant(b"4FE342E2FE1A7F9B8EE7EB4A7C0F9E162BCE33576B315ECECBB6406837BF51F5"),
+            ),
+        }
+    }
+
+    /// `x - y` in the field, both being reduced
+    fn sub(&self, x: &BigUint, y: &BigUint) -> BigUint {
+        (x + &self.p - y) % &self.p
+    }
+
+    /// `x³ + ax + b` in the field
+    fn rhs(&self, x: &BigUint) -> BigUint {
+        (x * x % &self.p * x + &self.a * x + &self.b) % &self.p
+    }
+
+    fn double(&self, point: &JacobianPoint) -> JacobianPoint {
+        let (x, y, z) = point.as_ref()?;
+        if y.is_zero() {
+            return None;
+        }
+        let p = &self.p;
+        let y2 = y * y % p;
+        let s = BigUint::from(4u8) * x * &y2 % p;
+        let z2 = z * z % p;
+        let m = (BigUint::from(3u8) * x * x + &self.a * &z2 * &z2) % p;
+        let x3 = self.sub(&(&m * &m % p), &(BigUint::from(2u8) * &s % p));
+        let y3 = self.sub(
+            &(&m * self.sub(&s, &x3) % p),
+            &(BigUint::from(8u8) * &y2 * &y2 % p),
+        );
+        let z3 = BigUint::from(2u8) * y * z % p;
+        Some((x3, y3, z3))
+    }
+
+    fn add(&self, first: &JacobianPoint, second: &JacobianPoint) -> JacobianPoint {
+        let (x1, y1, z1) = match first {
+            Some(point) => point,
+            None => return second.clone(),
+        };
+        let (x2, y2, z2) = match second {
+            Some(point) => point,
+            None => return first.clone(),
+        };
+        let p = &self.p;
+        let z1_2 = z1 * z1 % p;
+        let z2_2 = z2 * z2 % p;
+        let u1 = x1 * &z2_2 % p;
+        let u2 = x2 * &z1_2 % p;
+        let s1 = y1 * &z2_2 * z2 % p;
+        let s2 = y2 * &z1_2 * z1 % p;
+        if u1 == u2 {
+            return if s1 == s2 { self.double(first) } else { None };
+        }
+        let h = self.sub(&u2, &u1);
+        let r = self.sub(&s2, &s1);
+        let h2 = &h * &h % p;
+        let h3 = &h2 * &h % p;
+        let u1_h2 = &u1 * &h2 % p;
+        let x3 = self.sub(
+            &self.sub(&(&r * &r % p), &h3),
+            &(BigUint::from(2u8) * &u1_h2 % p),
+        );
+        let y3 = self.sub(&(&r * self.sub(&u1_h2, &x3) % p), &(&s1 * &h3 % p));
+        let z3 = h * z1 * z2 % p;
+        Some((x3, y3, z3))
+    }
+
+    /// Decodes a SEC1 public key, compressed or not, `None` if it is not a point of the curve
+    fn decode_public_key(&self, bytes: &[u8]) -> Option<(BigUint, BigUint)> {
+        let p = &self.p;
+        let (x, y) = match (bytes.len(), *bytes.first()?) {
+            (65, 4) => (
+                BigUint::from_bytes_be(&bytes[1..33]),
+                BigUint::from_bytes_be(&bytes[33..]),
+            ),
+            (33, prefix @ (2 | 3)) => {
+                let x = BigUint::from_bytes_be(&bytes[1..]);
+                if &x >= p {
+                    return None;
+                }
+                // both primes are 3 modulo 4: a square root of `rhs` is `rhs^((p + 1) / 4)`
+                let mut y = self.rhs(&x).modpow(&((p + BigUint::one()) >> 2), p);
+                if y.bit(0) != (prefix == 3) {
+                    y = self.sub(&BigUint::zero(), &y);
+                }
+                (x, y)
+            }
+            _ => return None,
+        };
+        // the cofactors are 1: every point of the curve is in the group of the generator
+        (&x < p && &y < p && &y * &y % p == self.rhs(&x)).then_some((x, y))
+    }
+
+    /// Verifies the ECDSA signature `r || s` of `message_hash`, rejecting a high `s` if `low_s` is set
+    fn verify(
+        &self,
+        message_hash: &[u8; 32],
+        signature: &[u8; 64],
+        public_key: &[u8],
+        low_s: bool,
+    ) -> bool {
+        let n = &self.n;
+        let r = BigUint::from_bytes_be(&signature[..32]);
+        let s = BigUint::from_bytes_be(&signature[32..]);
+        if r.is_zero() || s.is_zero() || &r >= n || &s >= n || (low_s && s > (n >> 1)) {
+            return false;
+        }
+        let Some((qx, qy)) = self.decode_public_key(public_key) else {
+            return false;
+        };
+        let e = BigUint::from_bytes_be(message_hash) % n;
+        let s_inv = s.modpow(&(n - BigUint::from(2u8)), n);
+        let u1 = &e * &s_inv % n;
+        let u2 = &r * &s_inv % n;
+
+        // u1 * g + u2 * q, sharing the doublings
+        let g: JacobianPoint = Some((self.g.0.clone(), self.g.1.clone(), BigUint::one()));
+        let q: JacobianPoint = Some((qx, qy, BigUint::one()));
+        let g_q = self.add(&g, &q);
+        let mut point: JacobianPoint = None;
+        for bit in (0..n.bits()).rev() {
+            point = self.double(&point);
+            let term = match (u1.bit(bit), u2.bit(bit)) {
+                (true, true) => &g_q,
+                (true, false) => &g,
+                (false, true) => &q,
+                (false, false) => continue,
+            };
+            point = self.add(&point, term);
+        }
+        let Some((x, _, z)) = point else {
+            return false;
+        };
+        let p = &self.p;
+        let z_inv = z.modpow(&(p - BigUint::from(2u8)), p);
+        let x = x * &z_inv % p * &z_inv % p;
+        x % n == r
+    }
+}
+
+/// Verifies an ECDSA signature on secp256k1, a high `s` being rejected as in the EVM
+pub(crate) fn verify_secp256k1_signature(
+    message_hash: &[u8; 32],
+    signature: &[u8; 64],
+    public_key: &[u8],
+) -> bool {
+    Curve::secp256k1().verify(message_hash, signature, public_key, true)
+}
+
+/// Verifies an ECDSA signature on secp256r1, whose signers do not normalize `s`
+pub(crate) fn verify_secp256r1_signature(
+    message_hash: &[u8; 32],
+    signature: &[u8; 64],
+    public_key: &[u8],
+) -> bool {
+    Curve::secp256r1().verify(message_hash, signature, public_key, false)
+}
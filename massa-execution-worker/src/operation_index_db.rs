@@ -0,0 +1,176 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! On-disk index of the operations of the final blocks.
+//!
+//! The blocks and operations leave the storage once they are no longer needed by consensus, so telling which block
+//! included an operation would otherwise need the node to have kept them. When enabled, each final block is indexed
+//! when its slot is executed as final, in a RocksDB database holding three kinds of entries:
+//! * by slot: the id of the block and its operation ids, in their order in the block, used for pruning
+//! * by block: the slot of the block
+//! * by operation: the slot and id of the block including it and its index in the block
+//!
+//! The blocks older than the retention configured in periods are dropped with their operations.
+
+use massa_execution_exports::{
+    ExecutionError, FinalBlockOperations, OperationInclusion, OperationIndexConfig,
+};
+use massa_hash::HASH_SIZE_BYTES;
+use massa_models::block_id::BlockId;
+use massa_models::operation::{OperationId, OPERATION_ID_SIZE_BYTES};
+use massa_models::slot::{Slot, SLOT_KEY_SIZE};
+use rocksdb::{Direction, IteratorMode, Options, WriteBatch, DB};
+use tracing::warn;
+
+/// Prefix of the entries by slot
+const SLOT_PREFIX: u8 = b's';
+/// Prefix of the entries by block
+const BLOCK_PREFIX: u8 = b'b';
+/// Prefix of the entries by operation
+const OPERATION_PREFIX: u8 = b'o';
+
+/// Key of an entry: its prefix then `id`
+fn prefixed_key(prefix: u8, id: &[u8]) -> Vec<u8> {
+    let mut key = Vec::with_capacity(1 + id.len());
+    key.push(prefix);
+    key.extend(id);
+    key
+}
+
+/// Slot read at the start of `bytes`
+fn read_slot(bytes: &[u8]) -> Option<Slot> {
+    Some(Slot::from_bytes_key(
+        bytes.get(..SLOT_KEY_SIZE)?.try_into().ok()?,
+    ))
+}
+
+/// Block id read at the start of `bytes`
+fn read_block_id(bytes: &[u8]) -> Option<BlockId> {
+    Some(BlockId::from_bytes(
+        bytes.get(..HASH_SIZE_BYTES)?.try_into().ok()?,
+    ))
+}
+
+/// Operation ids concatenated in `bytes`
+fn read_operation_ids(bytes: &[u8]) -> Vec<OperationId> {
+    bytes
+        .chunks_exact(OPERATION_ID_SIZE_BYTES)
+        .map(|chunk| OperationId::from_bytes(chunk.try_into().expect("invalid operation id")))
+        .collect()
+}
+
+/// Index of the operations of the final blocks stored on disk
+pub(crate) struct OperationIndexDB {
+    db: DB,
+    config: OperationIndexConfig,
+}
+
+impl OperationIndexDB {
+    /// Opens the database at `config.path`, creating it if needed
+    pub fn open(config: OperationIndexConfig) -> Result<Self, ExecutionError> {
+        let mut db_opts = Options::default();
+        db_opts.create_if_missing(true);
+        let db = DB::open(&db_opts, &config.path).map_err(|err| {
+            ExecutionError::OperationIndexError(format!(
+                "could not open {}: {}",
+                config.path.display(),
+                err
+            ))
+        })?;
+        Ok(OperationIndexDB { db, config })
+    }
+
+    /// Indexes the `operations` of the final block `block_id` at `slot` and drops the blocks out of the retention
+    pub fn append(&mut self, slot: &Slot, block_id: &BlockId, operations: &[OperationId]) {
+        let slot_key = slot.to_bytes_key();
+        let mut batch = WriteBatch::default();
+        let mut slot_value = block_id.to_bytes().to_vec();
+        for (index, operation_id) in operations.iter().enumerate() {
+            slot_value.extend(operation_id.to_bytes());
+            let mut operation_value = slot_key.to_vec();
+            operation_value.extend(block_id.to_bytes());
+            operation_value.extend((index as u32).to_be_bytes());
+            batch.put(
+                prefixed_key(OPERATION_PREFIX, operation_id.to_bytes()),
+                operation_value,
+            );
+        }
+        batch.put(prefixed_key(SLOT_PREFIX, &slot_key), slot_value);
+        batch.put(prefixed_key(BLOCK_PREFIX, block_id.to_bytes()), slot_key);
+        if let Err(err) = self.db.write(batch) {
+            warn!(
+                "could not index the operations of block {}: {}",
+                block_id, err
+            );
+        }
+        self.prune(slot);
+    }
+
+    /// Drops the blocks older than the retention, `latest_slot` being the latest final slot
+    fn prune(&mut self, latest_slot: &Slot) {
+        let min_period = match self.config.max_periods {
+            0 => return,
+            max_periods => (latest_slot.period + 1).saturating_sub(max_periods),
+        };
+        let start_key = [SLOT_PREFIX];
+        let mut batch = WriteBatch::default();
+        for (key, value) in self
+            .db
+            .iterator(IteratorMode::From(&start_key, Direction::Forward))
+            .flatten()
+        {
+            if key.first() != Some(&SLOT_PREFIX) {
+                break;
+            }
+            match read_slot(&key[1..]) {
+                Some(slot) if slot.period < min_period => {}
+                _ => break,
+            }
+            if let Some(block_id) = read_block_id(&value) {
+                batch.delete(prefixed_key(BLOCK_PREFIX, block_id.to_bytes()));
+                for operation_id in read_operation_ids(&value[HASH_SIZE_BYTES..]) {
+                    batch.delete(prefixed_key(OPERATION_PREFIX, operation_id.to_bytes()));
+                }
+            }
+            batch.delete(key);
+        }
+        if batch.is_empty() {
+            return;
+        }
+        if let Err(err) = self.db.write(batch) {
+            warn!(
+                "could not drop the outdated blocks of the operation index: {}",
+                err
+            );
+        }
+    }
+
+    /// Final block including `operation_id` and index of the operation in the block, if indexed
+    pub fn get_operation_inclusion(
+        &self,
+        operation_id: &OperationId,
+    ) -> Option<OperationInclusion> {
+        let value = self
+            .db
+            .get(prefixed_key(OPERATION_PREFIX, operation_id.to_bytes()))
+            .ok()??;
+        let index_bytes = value.get(SLOT_KEY_SIZE + HASH_SIZE_BYTES..)?;
+        Some(OperationInclusion {
+            slot: read_slot(&value)?,
+            block_id: read_block_id(&value[SLOT_KEY_SIZE..])?,
+            index: u32::from_be_bytes(index_bytes.try_into().ok()?),
+        })
+    }
+
+    /// Operations of the final block `block_id`, if indexed
+    pub fn get_block_operations(&self, block_id: &BlockId) -> Option<FinalBlockOperations> {
+        let slot_key = self
+            .db
+            .get(prefixed_key(BLOCK_PREFIX, block_id.to_bytes()))
+            .ok()??;
+        let value = self.db.get(prefixed_key(SLOT_PREFIX, &slot_key)).ok()??;
+        Some(FinalBlockOperations {
+            slot: read_slot(&slot_key)?,
+            operations: read_operation_ids(value.get(HASH_SIZE_BYTES..)?),
+        })
+    }
+}
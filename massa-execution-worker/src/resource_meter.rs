@@ -0,0 +1,89 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Accounting of the resources used by read-only executions.
+//!
+//! Gas bounds the computation of an execution but not the time a node actually spends on it nor the data it
+//! generates, which an expensive read-only call can use to slow down the node for free.
+//! The meter measures the CPU time of the execution thread and the bytes the execution produces
+//! (datastore entries, bytecode, events and return value), and fails the execution once they exceed its limits.
+
+use massa_execution_exports::{ExecutionError, ReadOnlyExecutionLimits, ReadOnlyResourceUsage};
+use std::time::{Duration, Instant};
+
+/// CPU time consumed by the calling thread, `None` where it cannot be measured
+#[cfg(unix)]
+fn thread_cpu_time() -> Option<Duration> {
+    let mut time = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    // SAFETY: `time` is a valid `timespec` for the duration of the call
+    let res = unsafe { libc::clock_gettime(libc::CLOCK_THREAD_CPUTIME_ID, &mut time) };
+    if res != 0 {
+        return None;
+    }
+    Some(Duration::new(time.tv_sec as u64, time.tv_nsec as u32))
+}
+
+/// CPU time consumed by the calling thread, `None` where it cannot be measured
+#[cfg(not(unix))]
+fn thread_cpu_time() -> Option<Duration> {
+    None
+}
+
+/// Resources used by a read-only execution, measured from its creation
+#[derive(Debug, Clone)]
+pub(crate) struct ResourceMeter {
+    /// CPU time of the execution thread at the start of the execution
+    cpu_start: Option<Duration>,
+    /// start of the execution, used when the CPU time cannot be measured
+    wall_start: Instant,
+    /// bytes produced by the execution so far
+    memory_bytes: u64,
+}
+
+impl ResourceMeter {
+    /// Starts measuring on the current thread: the execution must run on it
+    pub fn start() -> Self {
+        ResourceMeter {
+            cpu_start: thread_cpu_time(),
+            wall_start: Instant::now(),
+            memory_bytes: 0,
+        }
+    }
+
+    /// Counts `bytes` produced by the execution
+    pub fn add_memory(&mut self, bytes: usize) {
+        self.memory_bytes = self.memory_bytes.saturating_add(bytes as u64);
+    }
+
+    /// Resources used so far
+    pub fn usage(&self) -> ReadOnlyResourceUsage {
+        let cpu_time = match (self.cpu_start, thread_cpu_time()) {
+            (Some(start), Some(now)) => now.saturating_sub(start),
+            _ => self.wall_start.elapsed(),
+        };
+        ReadOnlyResourceUsage {
+            cpu_time_us: cpu_time.as_micros().try_into().unwrap_or(u64::MAX),
+            memory_bytes: self.memory_bytes,
+        }
+    }
+
+    /// Fails if the resources used so far exceed `limits`
+    pub fn check(&self, limits: &ReadOnlyExecutionLimits) -> Result<(), ExecutionError> {
+        let usage = self.usage();
+        if usage.cpu_time_us > limits.max_cpu_time_us {
+            return Err(ExecutionError::ResourceLimitExceeded(format!(
+                "CPU time of {} us exceeds the read-only limit of {} us",
+                usage.cpu_time_us, limits.max_cpu_time_us
+            )));
+        }
+        if usage.memory_bytes > limits.max_memory_bytes {
+            return Err(ExecutionError::ResourceLimitExceeded(format!(
+                "produced data of {} bytes exceeds the read-only limit of {} bytes",
+                usage.memory_bytes, limits.max_memory_bytes
+            )));
+        }
+        Ok(())
+    }
+}
@@ -7,7 +7,7 @@ use crate::active_history::ActiveHistory;
 use massa_async_pool::{AsyncMessage, AsyncMessageId, AsyncPool, AsyncPoolChanges};
 use massa_final_state::FinalState;
 use massa_ledger_exports::LedgerChanges;
-use massa_models::slot::Slot;
+use massa_models::{address::Address, slot::Slot};
 use parking_lot::RwLock;
 use std::sync::Arc;
 
@@ -69,6 +69,32 @@ impl SpeculativeAsyncPool {
         self.emitted.push((msg.compute_id(), msg));
     }
 
+    /// Gets the pending scheduled transfers of `sender`, in the pool or emitted since the snapshot
+    pub fn get_scheduled_transfers(&self, sender: &Address) -> Vec<(AsyncMessageId, AsyncMessage)> {
+        let mut transfers = self.async_pool.get_scheduled_transfers(sender);
+        transfers.extend(
+            self.emitted
+                .iter()
+                .filter(|(_id, msg)| msg.is_scheduled_transfer() && msg.sender == *sender)
+                .cloned(),
+        );
+        transfers
+    }
+
+    /// Removes a message before its execution, ex: a cancelled scheduled transfer.
+    /// Unlike the removal of an emitted message, the removal of a message of the pool
+    /// is not reverted by `reset_to_snapshot`: nothing must fail after it.
+    pub fn remove_message(&mut self, msg_id: &AsyncMessageId) {
+        if let Some(index) = self.emitted.iter().position(|(id, _msg)| id == msg_id) {
+            self.emitted.remove(index);
+            return;
+        }
+        let mut deletion = AsyncPoolChanges::default();
+        deletion.push_delete(*msg_id);
+        self.async_pool.apply_changes_unchecked(&deletion);
+        self.settled_changes.push_delete(*msg_id);
+    }
+
     /// Takes a batch of asynchronous messages to execute,
     /// removing them from the speculative asynchronous pool and settling their deletion from it in the changes accumulator.
     ///
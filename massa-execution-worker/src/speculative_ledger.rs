@@ -11,7 +11,13 @@ use massa_execution_exports::StorageCostsConstants;
 use massa_final_state::FinalState;
 use massa_ledger_exports::{Applicable, LedgerChanges, SetOrDelete, SetUpdateOrDelete};
 use massa_models::bytecode::Bytecode;
-use massa_models::{address::Address, amount::Amount};
+use massa_models::{
+    address::Address,
+    amount::Amount,
+    datastore::{is_reserved_datastore_key, BLS_PUBLIC_KEY_KEY, STORAGE_RENT_DUE_KEY},
+    endorsement_signature::RegisteredBlsKey,
+    operation::OPERATION_SEQUENCE_KEY,
+};
 use parking_lot::RwLock;
 use std::collections::BTreeSet;
 use std::sync::Arc;
@@ -575,6 +581,135 @@ impl SpeculativeLedger {
         Ok(())
     }
 
+    /// Records the next sequence expected from the operations of an address.
+    /// Like the balance, the record is part of the address and has no storage cost.
+    /// Fails if the address doesn't exist.
+    ///
+    /// # Arguments
+    /// * `addr`: address of the sender of the operations
+    /// * `next_sequence`: next expected sequence
+    pub fn set_operation_sequence(
+        &mut self,
+        addr: &Address,
+        next_sequence: u64,
+    ) -> Result<(), ExecutionError> {
+        if !self.entry_exists(addr) {
+            return Err(ExecutionError::RuntimeError(format!(
+                "could not record the operation sequence of address {}: address does not exist",
+                addr
+            )));
+        }
+        self.added_changes.set_data_entry(
+            *addr,
+            OPERATION_SEQUENCE_KEY.to_vec(),
+            next_sequence.to_be_bytes().to_vec(),
+        );
+        Ok(())
+    }
+
+    /// Gets the BLS key registered by an address, if any
+    pub fn get_registered_bls_key(&self, addr: &Address) -> Option<RegisteredBlsKey> {
+        self.get_data_entry(addr, BLS_PUBLIC_KEY_KEY)
+            .and_then(|value| RegisteredBlsKey::from_bytes(&value))
+    }
+
+    /// Records the BLS key registered by an address. Like the operation sequence, the record has no storage cost.
+    /// Fails if the address does not exist or already registered a key.
+    pub fn set_registered_bls_key(
+        &mut self,
+        addr: &Address,
+        key: &RegisteredBlsKey,
+    ) -> Result<(), ExecutionError> {
+        if !self.entry_exists(addr) {
+            return Err(ExecutionError::RuntimeError(format!(
+                "could not register the BLS key of address {}: address does not exist",
+                addr
+            )));
+        }
+        if self.get_data_entry(addr, BLS_PUBLIC_KEY_KEY).is_some() {
+            return Err(ExecutionError::RuntimeError(format!(
+                "address {} already registered a BLS key",
+                addr
+            )));
+        }
+        self.added_changes
+            .set_data_entry(*addr, BLS_PUBLIC_KEY_KEY.to_vec(), key.to_bytes());
+        Ok(())
+    }
+
+    /// Gets every address that may be charged storage rent: the final addresses with storage
+    /// and the addresses changed since finality, in a deterministic order.
+    pub fn get_addresses_with_storage(&self) -> BTreeSet<Address> {
+        let mut addresses = self.final_state.read().ledger.get_addresses_with_storage();
+        let active_history = self.active_history.read();
+        let changes_iterator = active_history
+            .0
+            .iter()
+            .map(|item| &item.state_changes.ledger_changes)
+            .chain(std::iter::once(&self.added_changes));
+        for ledger_changes in changes_iterator {
+            addresses.extend(ledger_changes.0.keys().copied());
+        }
+        addresses
+    }
+
+    /// Gets the number of bytes an address is charged storage rent for:
+    /// the length of its bytecode, and of the keys and values of its datastore entries not reserved to the ledger.
+    pub fn get_storage_size(&self, addr: &Address) -> u64 {
+        let bytecode_size = self
+            .get_bytecode(addr)
+            .map(|bytecode| bytecode.0.len() as u64)
+            .unwrap_or_default();
+        self.get_keys(addr)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|key| !is_reserved_datastore_key(key))
+            .fold(bytecode_size, |size, key| {
+                let value_size = self
+                    .get_data_entry(addr, &key)
+                    .map(|value| value.len() as u64)
+                    .unwrap_or_default();
+                size.saturating_add(key.len() as u64)
+                    .saturating_add(value_size)
+            })
+    }
+
+    /// Gets the first cycle whose storage rent an address could not pay, if it has not paid it since
+    pub fn get_storage_rent_due(&self, addr: &Address) -> Option<u64> {
+        self.get_data_entry(addr, STORAGE_RENT_DUE_KEY)
+            .and_then(|value| value.try_into().ok())
+            .map(u64::from_be_bytes)
+    }
+
+    /// Records the first cycle whose storage rent an address could not pay, or clears the record with `None`.
+    /// Like the operation sequence, the record is part of the address and has no storage cost.
+    pub fn set_storage_rent_due(&mut self, addr: &Address, due_since: Option<u64>) {
+        match due_since {
+            Some(cycle) => self.added_changes.set_data_entry(
+                *addr,
+                STORAGE_RENT_DUE_KEY.to_vec(),
+                cycle.to_be_bytes().to_vec(),
+            ),
+            None => self
+                .added_changes
+                .delete_data_entry(*addr, STORAGE_RENT_DUE_KEY.to_vec()),
+        }
+    }
+
+    /// Reclaims the storage of an address that did not pay its rent:
+    /// its bytecode and its datastore entries not reserved to the ledger are deleted without refunding their storage costs,
+    /// and its record of unpaid rent is cleared.
+    pub fn reclaim_storage(&mut self, addr: &Address) {
+        for key in self.get_keys(addr).unwrap_or_default() {
+            if !is_reserved_datastore_key(&key) || key == STORAGE_RENT_DUE_KEY {
+                self.added_changes.delete_data_entry(*addr, key);
+            }
+        }
+        if let Some(bytecode) = self.get_bytecode(addr) && !bytecode.0.is_empty() {
+            self.added_changes.set_bytecode(*addr, Bytecode::default());
+        }
+    }
+
     /// Deletes a datastore entry for a given address.
     /// Fails if the entry or address does not exist.
     ///
@@ -0,0 +1,77 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! This module keeps track of the staking rewards credited by final slot executions.
+//! Rewards are aggregated per cycle and per address, and only the most recent cycles are kept.
+//! Every block producer and endorser is tracked, so the number of addresses kept per cycle is bounded as well:
+//! once a cycle reaches the bound, the rewards of the addresses it does not track yet are dropped.
+
+use massa_models::address::{Address, StakingRewards};
+use massa_models::prehash::PreHashMap;
+use std::collections::{BTreeMap, VecDeque};
+
+/// Staking rewards tracker
+pub struct StakingRewardsTracker {
+    /// maximum number of cycles kept
+    max_cycles: usize,
+    /// maximum number of addresses kept per cycle
+    max_addresses: usize,
+    /// rewards credited during each cycle, oldest at the front
+    cycles: VecDeque<(u64, PreHashMap<Address, StakingRewards>)>,
+}
+
+impl StakingRewardsTracker {
+    /// create a new `StakingRewardsTracker`
+    pub fn new(max_cycles: usize, max_addresses: usize) -> Self {
+        StakingRewardsTracker {
+            max_cycles,
+            max_addresses,
+            cycles: Default::default(),
+        }
+    }
+
+    /// register the rewards credited by a final slot execution happening during `cycle`
+    pub fn register(&mut self, cycle: u64, rewards: &PreHashMap<Address, StakingRewards>) {
+        if self.max_cycles == 0 || self.max_addresses == 0 || rewards.is_empty() {
+            return;
+        }
+        match self.cycles.back() {
+            Some((last_cycle, _)) if *last_cycle > cycle => {
+                // final slots are executed in order: this is a bug, but not worth stopping the node
+                debug_assert!(
+                    false,
+                    "attempting to register staking rewards of an older cycle"
+                );
+                return;
+            }
+            Some((last_cycle, _)) if *last_cycle == cycle => {}
+            _ => {
+                self.cycles.push_back((cycle, Default::default()));
+                while self.cycles.len() > self.max_cycles {
+                    self.cycles.pop_front();
+                }
+            }
+        }
+        let Some((_, cycle_rewards)) = self.cycles.back_mut() else {
+            return;
+        };
+        for (addr, addr_rewards) in rewards {
+            if let Some(tracked_rewards) = cycle_rewards.get_mut(addr) {
+                tracked_rewards.extend(addr_rewards);
+            } else if cycle_rewards.len() < self.max_addresses {
+                cycle_rewards.insert(*addr, *addr_rewards);
+            }
+        }
+    }
+
+    /// get the rewards credited to an address, indexed by cycle
+    pub fn get_address_rewards(&self, address: &Address) -> BTreeMap<u64, StakingRewards> {
+        self.cycles
+            .iter()
+            .filter_map(|(cycle, cycle_rewards)| {
+                cycle_rewards
+                    .get(address)
+                    .map(|addr_rewards| (*cycle, *addr_rewards))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,316 @@
+//! Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! This module streams the finalized state changes to the state sink configured by the operator.
+//! The sink runs in its own thread so that a slow external system does not delay the execution:
+//! when its queue is full, the changes of the final slot are dropped with a warning.
+
+use massa_execution_exports::{
+    ExecutionConfig, ExecutionError, FinalizedStateChanges, LedgerEntryChange, StateSink,
+    StateSinkConfig,
+};
+use massa_ledger_exports::{LedgerChanges, SetOrDelete, SetOrKeep, SetUpdateOrDelete};
+use massa_models::{block_id::BlockId, slot::Slot};
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender, TrySendError};
+use std::thread::{self, JoinHandle};
+use tracing::{info, warn};
+
+/// Builds the ledger changes streamed to the state sink for a final slot, the events are added once finalized
+pub(crate) fn finalized_state_changes(
+    slot: Slot,
+    block_id: Option<BlockId>,
+    ledger_changes: &LedgerChanges,
+) -> FinalizedStateChanges {
+    let mut entry_changes: Vec<LedgerEntryChange> = ledger_changes
+        .0
+        .iter()
+        .map(|(address, change)| match change {
+            SetUpdateOrDelete::Set(entry) => LedgerEntryChange {
+                address: *address,
+                balance: Some(entry.balance),
+                replaced: true,
+                deleted: false,
+                datastore: entry
+                    .datastore
+                    .iter()
+                    .map(|(key, value)| (key.clone(), Some(value.clone())))
+                    .collect(),
+            },
+            SetUpdateOrDelete::Update(update) => LedgerEntryChange {
+                address: *address,
+                balance: match update.balance {
+                    SetOrKeep::Set(balance) => Some(balance),
+                    SetOrKeep::Keep => None,
+                },
+                replaced: false,
+                deleted: false,
+                datastore: update
+                    .datastore
+                    .iter()
+                    .map(|(key, value)| {
+                        let value = match value {
+                            SetOrDelete::Set(value) => Some(value.clone()),
+                            SetOrDelete::Delete => None,
+                        };
+                        (key.clone(), value)
+                    })
+                    .collect(),
+            },
+            SetUpdateOrDelete::Delete => LedgerEntryChange {
+                address: *address,
+                balance: None,
+                replaced: false,
+                deleted: true,
+                datastore: Vec::new(),
+            },
+        })
+        .collect();
+    entry_changes.sort_unstable_by_key(|change| change.address);
+    FinalizedStateChanges {
+        slot,
+        block_id,
+        ledger_changes: entry_changes,
+        events: Vec::new(),
+    }
+}
+
+/// Sending half of the state sink queue, held by the execution state
+pub(crate) struct StateSinkSender {
+    sender: SyncSender<FinalizedStateChanges>,
+    /// number of final slots whose changes were dropped because the queue was full
+    dropped_slots: u64,
+}
+
+impl StateSinkSender {
+    /// Queue the changes of a final slot, without blocking
+    pub fn send(&mut self, changes: FinalizedStateChanges) {
+        match self.sender.try_send(changes) {
+            Ok(()) => {}
+            Err(TrySendError::Full(changes)) => {
+                self.dropped_slots = self.dropped_slots.saturating_add(1);
+                warn!(
+                    "state sink is too slow, dropping the changes of final slot {} ({} slots dropped so far)",
+                    changes.slot, self.dropped_slots
+                );
+            }
+            Err(TrySendError::Disconnected(changes)) => {
+                warn!(
+                    "state sink stopped, dropping the changes of final slot {}",
+                    changes.slot
+                );
+            }
+        }
+    }
+}
+
+/// Starts the thread writing to the state sink configured in `config`, if any
+pub(crate) fn start_state_sink(
+    config: &ExecutionConfig,
+) -> Result<Option<(StateSinkSender, JoinHandle<()>)>, ExecutionError> {
+    let sink = match create_state_sink(&config.state_sink)? {
+        Some(sink) => sink,
+        None => return Ok(None),
+    };
+    let (sender, receiver) = sync_channel(config.state_sink_queue_length);
+    let thread_handle = thread::Builder::new()
+        .name("execution state sink".into())
+        .spawn(move || run_state_sink(sink, receiver))
+        .map_err(|err| ExecutionError::StateSinkError(err.to_string()))?;
+    Ok(Some((
+        StateSinkSender {
+            sender,
+            dropped_slots: 0,
+        },
+        thread_handle,
+    )))
+}
+
+/// Writes the queued changes until the sending half is dropped
+fn run_state_sink(mut sink: Box<dyn StateSink>, receiver: Receiver<FinalizedStateChanges>) {
+    while let Ok(changes) = receiver.recv() {
+        if let Err(err) = sink.write(&changes) {
+            warn!(
+                "could not write the changes of final slot {} to the state sink: {}",
+                changes.slot, err
+            );
+        }
+    }
+    if let Err(err) = sink.flush() {
+        warn!("could not flush the state sink: {}", err);
+    }
+    info!("state sink stopped");
+}
+
+fn create_state_sink(
+    config: &StateSinkConfig,
+) -> Result<Option<Box<dyn StateSink>>, ExecutionError> {
+    match config {
+        StateSinkConfig::None => Ok(None),
+        StateSinkConfig::File { path } => {
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .map_err(|err| {
+                    ExecutionError::StateSinkError(format!(
+                        "could not open {}: {}",
+                        path.display(),
+                        err
+                    ))
+                })?;
+            Ok(Some(Box::new(FileStateSink {
+                writer: BufWriter::new(file),
+            })))
+        }
+        #[cfg(feature = "state_sink_postgres")]
+        StateSinkConfig::Postgres { url, table } => Ok(Some(Box::new(
+            postgres_sink::PostgresStateSink::new(url, table)?,
+        ))),
+        #[cfg(not(feature = "state_sink_postgres"))]
+        StateSinkConfig::Postgres { .. } => Err(ExecutionError::StateSinkError(
+            "the node was built without the state_sink_postgres feature".into(),
+        )),
+        #[cfg(feature = "state_sink_kafka")]
+        StateSinkConfig::Kafka { brokers, topic } => Ok(Some(Box::new(
+            kafka_sink::KafkaStateSink::new(brokers, topic)?,
+        ))),
+        #[cfg(not(feature = "state_sink_kafka"))]
+        StateSinkConfig::Kafka { .. } => Err(ExecutionError::StateSinkError(
+            "the node was built without the state_sink_kafka feature".into(),
+        )),
+    }
+}
+
+/// Appends one JSON object per final slot to a file
+struct FileStateSink {
+    writer: BufWriter<File>,
+}
+
+impl StateSink for FileStateSink {
+    fn write(&mut self, changes: &FinalizedStateChanges) -> Result<(), ExecutionError> {
+        serde_json::to_writer(&mut self.writer, changes)
+            .map_err(|err| ExecutionError::StateSinkError(err.to_string()))?;
+        self.writer
+            .write_all(b"\n")
+            .map_err(|err| ExecutionError::StateSinkError(err.to_string()))
+    }
+
+    fn flush(&mut self) -> Result<(), ExecutionError> {
+        self.writer
+            .flush()
+            .map_err(|err| ExecutionError::StateSinkError(err.to_string()))
+    }
+}
+
+#[cfg(feature = "state_sink_postgres")]
+mod postgres_sink {
+    use massa_execution_exports::{ExecutionError, FinalizedStateChanges, StateSink};
+    use postgres::{Client, NoTls};
+
+    /// Inserts one row per final slot in a PostgreSQL table
+    pub(super) struct PostgresStateSink {
+        url: String,
+        client: Client,
+        insert_query: String,
+    }
+
+    impl PostgresStateSink {
+        pub(super) fn new(url: &str, table: &str) -> Result<Self, ExecutionError> {
+            let mut client = Client::connect(url, NoTls)
+                .map_err(|err| ExecutionError::StateSinkError(err.to_string()))?;
+            client
+                .batch_execute(&format!(
+                    "CREATE TABLE IF NOT EXISTS {} (
+                        period BIGINT NOT NULL,
+                        thread SMALLINT NOT NULL,
+                        block_id TEXT,
+                        changes JSONB NOT NULL,
+                        PRIMARY KEY (period, thread)
+                    )",
+                    table
+                ))
+                .map_err(|err| ExecutionError::StateSinkError(err.to_string()))?;
+            Ok(PostgresStateSink {
+                url: url.to_string(),
+                client,
+                insert_query: format!(
+                    "INSERT INTO {} (period, thread, block_id, changes) VALUES ($1, $2, $3, $4)
+                    ON CONFLICT (period, thread) DO NOTHING",
+                    table
+                ),
+            })
+        }
+    }
+
+    impl StateSink for PostgresStateSink {
+        fn write(&mut self, changes: &FinalizedStateChanges) -> Result<(), ExecutionError> {
+            // reconnect if the connection was lost since the previous slot
+            if self.client.is_closed() {
+                self.client = Client::connect(&self.url, NoTls)
+                    .map_err(|err| ExecutionError::StateSinkError(err.to_string()))?;
+            }
+            let period = i64::try_from(changes.slot.period)
+                .map_err(|err| ExecutionError::StateSinkError(err.to_string()))?;
+            let thread = i16::from(changes.slot.thread);
+            let block_id = changes.block_id.map(|id| id.to_string());
+            let json = serde_json::to_value(changes)
+                .map_err(|err| ExecutionError::StateSinkError(err.to_string()))?;
+            self.client
+                .execute(
+                    self.insert_query.as_str(),
+                    &[&period, &thread, &block_id, &json],
+                )
+                .map_err(|err| ExecutionError::StateSinkError(err.to_string()))?;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(feature = "state_sink_kafka")]
+mod kafka_sink {
+    use massa_execution_exports::{ExecutionError, FinalizedStateChanges, StateSink};
+    use rdkafka::{
+        producer::{BaseProducer, BaseRecord, Producer},
+        ClientConfig,
+    };
+    use std::time::Duration;
+
+    /// Produces one message per final slot to a Kafka topic, keyed by slot
+    pub(super) struct KafkaStateSink {
+        producer: BaseProducer,
+        topic: String,
+    }
+
+    impl KafkaStateSink {
+        pub(super) fn new(brokers: &str, topic: &str) -> Result<Self, ExecutionError> {
+            let producer = ClientConfig::new()
+                .set("bootstrap.servers", brokers)
+                .create()
+                .map_err(|err| ExecutionError::StateSinkError(err.to_string()))?;
+            Ok(KafkaStateSink {
+                producer,
+                topic: topic.to_string(),
+            })
+        }
+    }
+
+    impl StateSink for KafkaStateSink {
+        fn write(&mut self, changes: &FinalizedStateChanges) -> Result<(), ExecutionError> {
+            let key = changes.slot.to_string();
+            let payload = serde_json::to_vec(changes)
+                .map_err(|err| ExecutionError::StateSinkError(err.to_string()))?;
+            self.producer
+                .send(BaseRecord::to(&self.topic).key(&key).payload(&payload))
+                .map_err(|(err, _)| ExecutionError::StateSinkError(err.to_string()))?;
+            // serve the delivery callbacks
+            self.producer.poll(Duration::ZERO);
+            Ok(())
+        }
+
+        fn flush(&mut self) -> Result<(), ExecutionError> {
+            let _ = self.producer.flush(Duration::from_secs(10));
+            Ok(())
+        }
+    }
+}
@@ -19,4 +19,139 @@ mod tests {
             &hex!("3fc9b689459d738f8c88a3a48aa9e33542016b7a4052e001aaa536fca74813cb")[..];
         assert_eq!(actual_hash, expected_hash);
     }
+
+    fn default_interface() -> InterfaceImpl {
+        InterfaceImpl::new_default(
+            Address::from_str("AU12cMW9zRKFDS43Z2W88VCmdQFxmHjAo54XvuVV34UzJeXRLXW9M").unwrap(),
+            None,
+        )
+    }
+
+    #[test]
+    fn test_hash_keccak256() {
+        let interface = default_interface();
+        assert_eq!(
+            interface.hash_keccak256(b"").unwrap(),
+            hex!("c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470")
+        );
+        assert_eq!(
+            interface.hash_keccak256(b"abc").unwrap(),
+            hex!("4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45")
+        );
+    }
+
+    #[test]
+    fn test_hash_sha3_256() {
+        let interface = default_interface();
+        assert_eq!(
+            interface.hash_sha3_256(b"").unwrap(),
+            hex!("a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a")
+        );
+        assert_eq!(
+            interface.hash_sha3_256(b"abc").unwrap(),
+            hex!("3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532")
+        );
+    }
+
+    #[test]
+    fn test_hash_blake3() {
+        let interface = default_interface();
+        assert_eq!(
+            interface.hash_blake3(b"").unwrap(),
+            hex!("af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262")
+        );
+        assert_eq!(
+            interface.hash_blake3(b"abc").unwrap(),
+            hex!("6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85")
+        );
+    }
+
+    /// sha256 hash of "massa", signed with the private key 3 on both curves
+    const MESSAGE_HASH: [u8; 32] =
+        hex!("67f9390708de76a98e5ab5b5445a8683f26b3f27761d743b637bfb5f342e251d");
+
+    #[test]
+    fn test_verify_secp256k1_signature() {
+        let interface = default_interface();
+        let signature = hex!("4a8a05647ea1b80860f8df9c59cf0fdb8c57530a385ddeca2c001eef0968f1f762b3c31b037fa5b01410061d34f3acf7fa278a2b9e91589d05726213facbabe2");
+        let public_key = hex!("04f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9388f7b0f632de8140fe337e62a37f3566500a99934c2231b6cb9fd7584b8e672");
+        let compressed_public_key =
+            hex!("02f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9");
+        let verify = |hash: &[u8], sig: &[u8], pubk: &[u8]| {
+            interface
+                .verify_secp256k1_signature(hash, sig, pubk)
+                .unwrap()
+        };
+
+        assert!(verify(&MESSAGE_HASH, &signature, &public_key));
+        assert!(verify(&MESSAGE_HASH, &signature, &compressed_public_key));
+        // the recovery byte of the EVM signatures is ignored
+        assert!(verify(
+            &MESSAGE_HASH,
+            &[&signature[..], &[27]].concat(),
+            &public_key
+        ));
+
+        let mut other_hash = MESSAGE_HASH;
+        other_hash[0] ^= 1;
+        assert!(!verify(&other_hash, &signature, &public_key));
+        let other_public_key =
+            hex!("022f8bde4d1a07209355b4a7250a5c5128e88b84bddc619ab7cba8d569b240efe4");
+        assert!(!verify(&MESSAGE_HASH, &signature, &other_public_key));
+        // same signature with `n - s`: valid for the curve but rejected as in the EVM
+        let high_s_signature = hex!("4a8a05647ea1b80860f8df9c59cf0fdb8c57530a385ddeca2c001eef0968f1f79d4c3ce4fc805a4febeff9e2cb0c5306c08752bb10b7479eba5ffc78d56a955f");
+        assert!(!verify(&MESSAGE_HASH, &high_s_signature, &public_key));
+        let mut off_curve_public_key = public_key;
+        off_curve_public_key[64] ^= 1;
+        assert!(!verify(&MESSAGE_HASH, &signature, &off_curve_public_key));
+        assert!(!verify(&MESSAGE_HASH, &signature[..63], &public_key));
+        assert!(!verify(
+            &MESSAGE_HASH,
+            &[&signature[..], &[27, 0]].concat(),
+            &public_key
+        ));
+        assert!(!verify(&MESSAGE_HASH, &signature, &public_key[..64]));
+        assert!(interface
+            .verify_secp256k1_signature(&MESSAGE_HASH[..31], &signature, &public_key)
+            .is_err());
+    }
+
+    #[test]
+    fn test_verify_secp256r1_signature() {
+        let interface = default_interface();
+        let signature = hex!("c51fdd46b9dd654ea37869aa8d36a66c26cb0fe12d3b86dc50d25f27b6c79504fe58fbfc4dc34994f2cf761d776dc3f2d65b2f5037c52e354c7b44d1c6aad622");
+        let public_key = hex!("045ecbe4d1a6330a44c8f7ef951d4bf165e6c6b721efada985fb41661bc6e7fd6c8734640c4998ff7e374b06ce1a64a2ecd82ab036384fb83d9a79b127a27d5032");
+        let compressed_public_key =
+            hex!("025ecbe4d1a6330a44c8f7ef951d4bf165e6c6b721efada985fb41661bc6e7fd6c");
+        let verify = |hash: &[u8], sig: &[u8], pubk: &[u8]| {
+            interface
+                .verify_secp256r1_signature(hash, sig, pubk)
+                .unwrap()
+        };
+
+        assert!(verify(&MESSAGE_HASH, &signature, &public_key));
+        assert!(verify(&MESSAGE_HASH, &signature, &compressed_public_key));
+        // `s` is not normalized on this curve: both `s` and `n - s` are valid
+        let low_s_signature = hex!("c51fdd46b9dd654ea37869aa8d36a66c26cb0fe12d3b86dc50d25f27b6c7950401a70402b23cb66c0d3089e288923c0ce68bcb5d6f52704fa73e85f135b84f2f");
+        assert!(verify(&MESSAGE_HASH, &low_s_signature, &public_key));
+
+        let mut other_hash = MESSAGE_HASH;
+        other_hash[0] ^= 1;
+        assert!(!verify(&other_hash, &signature, &public_key));
+        let other_public_key =
+            hex!("0251590b7a515140d2d784c85608668fdfef8c82fd1f5be52421554a0dc3d033ed");
+        assert!(!verify(&MESSAGE_HASH, &signature, &other_public_key));
+        let mut off_curve_public_key = public_key;
+        off_curve_public_key[64] ^= 1;
+        assert!(!verify(&MESSAGE_HASH, &signature, &off_curve_public_key));
+        assert!(!verify(&MESSAGE_HASH, &signature[..63], &public_key));
+        assert!(!verify(
+            &MESSAGE_HASH,
+            &[&signature[..], &[27]].concat(),
+            &public_key
+        ));
+        assert!(interface
+            .verify_secp256r1_signature(&MESSAGE_HASH[..31], &signature, &public_key)
+            .is_err());
+    }
 }
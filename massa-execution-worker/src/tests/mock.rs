@@ -119,8 +119,12 @@ pub fn get_sample_state() -> Result<(Arc<RwLock<FinalState>>, NamedTempFile, Tem
         final_history_length: 128,
         thread_count: THREAD_COUNT,
         initial_rolls_path: rolls_file.path().to_path_buf(),
+        genesis_path: None,
         initial_seed_string: "".to_string(),
         periods_per_cycle: 10,
+        journal_path: None,
+        journal_checkpoint_interval: 256,
+        mip_store_config: default_config.mip_store_config,
     };
     let (_, selector_controller) = start_selector_worker(SelectorConfig::default())
         .expect("could not start selector controller");
@@ -151,7 +155,9 @@ pub fn create_block(
             slot,
             parents: vec![],
             operation_merkle_root,
+            announced_version: 0,
             endorsements: vec![],
+            aggregated_endorsements: None,
         },
         BlockHeaderSerializer::new(),
         &creator_keypair,
@@ -6,22 +6,36 @@ mod tests {
         create_block, get_initials_vesting, get_random_address_full, get_sample_state,
     };
     use massa_execution_exports::{
-        ExecutionConfig, ExecutionController, ExecutionError, ReadOnlyExecutionRequest,
-        ReadOnlyExecutionTarget,
+        EventStoreConfig, ExecutionConfig, ExecutionController, ExecutionError,
+        FinalBlockOperations, OperationInclusion, OperationIndexConfig, ReadOnlyExecutionLimits,
+        ReadOnlyExecutionRequest, ReadOnlyExecutionTarget, StateSinkConfig, StorageRentConstants,
     };
-    use massa_models::config::{LEDGER_ENTRY_BASE_SIZE, LEDGER_ENTRY_DATASTORE_BASE_SIZE};
+    use massa_hash::Hash;
+    use massa_ledger_exports::{LedgerChanges, LedgerEntry, SetUpdateOrDelete};
+    use massa_models::config::{
+        LEDGER_ENTRY_BASE_SIZE, LEDGER_ENTRY_DATASTORE_BASE_SIZE, MULTI_TRANSACTION_FEE_PER_OUTPUT,
+        STORAGE_RENT_VERSION,
+    };
+    use massa_models::output_event::ExecutionErrorCode;
     use massa_models::prehash::PreHashMap;
     use massa_models::{address::Address, amount::Amount, slot::Slot};
     use massa_models::{
         block_id::BlockId,
-        datastore::Datastore,
+        datastore::{Datastore, BLS_PUBLIC_KEY_KEY},
+        endorsement_signature::RegisteredBlsKey,
         execution::EventFilter,
-        operation::{Operation, OperationSerializer, OperationType, SecureShareOperation},
+        operation::{
+            next_operation_sequence, Operation, OperationSerializer, OperationType,
+            SecureShareOperation, TransactionOutput, OPERATION_SEQUENCE_KEY,
+        },
         secure_share::SecureShareContent,
     };
-    use massa_signature::KeyPair;
+    use massa_signature::{BlsKeyPair, KeyPair};
     use massa_storage::Storage;
     use massa_time::MassaTime;
+    use massa_versioning::{
+        MipComponent, MipInfo, MipState, MipStore, MipStoreConfig, MipStoreSnapshot,
+    };
     use num::rational::Ratio;
     use serial_test::serial;
     use std::{
@@ -100,10 +114,16 @@ mod tests {
                     include_bytes!("./wasm/event_test.wasm").to_vec(),
                 ),
                 is_final: true,
+                limits: Default::default(),
+                fork: None,
             })
             .expect("readonly execution failed");
         assert_eq!(res.out.slot, Slot::new(1, 0));
         assert!(res.gas_cost > 0);
+        assert!(
+            res.usage.memory_bytes > 0,
+            "the emitted event was not accounted"
+        );
         assert_eq!(res.out.events.take().len(), 1, "wrong number of events");
 
         let res = controller
@@ -114,10 +134,47 @@ mod tests {
                     include_bytes!("./wasm/event_test.wasm").to_vec(),
                 ),
                 is_final: false,
+                limits: Default::default(),
+                fork: None,
             })
             .expect("readonly execution failed");
         assert!(res.out.slot.period > 8);
 
+        // the event emitted by the bytecode exceeds the requested limit
+        let res = controller.execute_readonly_request(ReadOnlyExecutionRequest {
+            max_gas: 1_000_000,
+            call_stack: vec![],
+            target: ReadOnlyExecutionTarget::BytecodeExecution(
+                include_bytes!("./wasm/event_test.wasm").to_vec(),
+            ),
+            is_final: true,
+            limits: ReadOnlyExecutionLimits {
+                max_event_count: 0,
+                ..Default::default()
+            },
+            fork: None,
+        });
+        assert!(res.is_err(), "the event limit was not enforced");
+
+        // the event emitted by the bytecode exceeds the requested memory limit
+        let res = controller.execute_readonly_request(ReadOnlyExecutionRequest {
+            max_gas: 1_000_000,
+            call_stack: vec![],
+            target: ReadOnlyExecutionTarget::BytecodeExecution(
+                include_bytes!("./wasm/event_test.wasm").to_vec(),
+            ),
+            is_final: true,
+            limits: ReadOnlyExecutionLimits {
+                max_memory_bytes: 0,
+                ..Default::default()
+            },
+            fork: None,
+        });
+        assert_eq!(
+            res.expect_err("the memory limit was not enforced").code(),
+            ExecutionErrorCode::ResourceLimit
+        );
+
         manager.stop();
     }
 
@@ -966,9 +1023,12 @@ mod tests {
             Operation {
                 fee: Amount::zero(),
                 expire_period: 10,
+                sequence: None,
+                sponsor: None,
                 op: OperationType::Transaction {
                     recipient_address,
                     amount: Amount::from_str("100").unwrap(),
+                    memo: None,
                 },
             },
             OperationSerializer::new(),
@@ -1013,10 +1073,11 @@ mod tests {
         manager.stop();
     }
 
+    /// Sends transactions with a sequence: only the next sequence of the sender is executed, once
     #[test]
     #[serial]
-    fn vesting_transfer_coins() {
-        let vesting = get_initials_vesting(true);
+    fn send_transactions_with_sequence() {
+        let vesting = get_initials_vesting(false);
         // setup the period duration
         let exec_cfg = ExecutionConfig {
             t0: 100.into(),
@@ -1040,28 +1101,39 @@ mod tests {
         // generate the sender_keypair and recipient_address
         let sender_keypair =
             KeyPair::from_str("S1JJeHiZv1C1zZN5GLFcbz6EXYiccmUPLkYuDFA3kayjxP39kFQ").unwrap();
-        let sender_addr = Address::from_public_key(&sender_keypair.get_public_key());
+        let sender_address = Address::from_public_key(&sender_keypair.get_public_key());
         let (recipient_address, _keypair) = get_random_address_full();
-        // create the operation
-        let operation = Operation::new_verifiable(
-            Operation {
-                fee: Amount::zero(),
-                expire_period: 10,
-                op: OperationType::Transaction {
-                    recipient_address,
-                    amount: Amount::from_str("250000").unwrap(),
+        let transaction = |sequence: u64, amount: &str| {
+            Operation::new_verifiable(
+                Operation {
+                    fee: Amount::zero(),
+                    expire_period: 10,
+                    sequence: Some(sequence),
+                    sponsor: None,
+                    op: OperationType::Transaction {
+                        recipient_address,
+                        amount: Amount::from_str(amount).unwrap(),
+                        memo: None,
+                    },
                 },
-            },
-            OperationSerializer::new(),
-            &sender_keypair,
-        )
-        .unwrap();
-        // create the block containing the transaction operation
-        storage.store_operations(vec![operation.clone()]);
-        let block = create_block(KeyPair::generate(), vec![operation], Slot::new(1, 0)).unwrap();
+                OperationSerializer::new(),
+                &sender_keypair,
+            )
+            .unwrap()
+        };
+        // the second operation replays the sequence 0, the third one leaves a gap
+        let operations = vec![
+            transaction(0, "100"),
+            transaction(0, "50"),
+            transaction(2, "20"),
+            transaction(1, "10"),
+        ];
+        // create the block containing the transaction operations
+        storage.store_operations(operations.clone());
+        let block = create_block(KeyPair::generate(), operations, Slot::new(1, 0)).unwrap();
         // store the block in storage
         storage.store_block(block.clone());
-        // set our block as a final block so the transaction is processed
+        // set our block as a final block so the transactions are processed
         let mut finalized_blocks: HashMap<Slot, BlockId> = Default::default();
         finalized_blocks.insert(block.content.header.content.slot, block.id);
         let mut block_storage: PreHashMap<BlockId, Storage> = Default::default();
@@ -1071,47 +1143,47 @@ mod tests {
             Default::default(),
             block_storage.clone(),
         );
-        std::thread::sleep(Duration::from_millis(100));
-
-        // retrieve the event emitted by the execution error
-        let events = controller.get_filtered_sc_output_event(EventFilter::default());
-        assert!(events[0].data.contains("massa_execution_error"));
-        assert!(events[0]
-            .data
-            .contains("We reach the vesting constraint : vesting_min_balance=100000 with value min_balance=60000"));
-
-        // check recipient balance
-        assert!(sample_state
-            .read()
-            .ledger
-            .get_balance(&recipient_address)
-            .is_none());
-        // Check sender balance
+        std::thread::sleep(Duration::from_millis(10));
+        // only the sequences 0 and 1 were executed
         assert_eq!(
             sample_state
                 .read()
                 .ledger
-                .get_balance(&sender_addr)
+                .get_balance(&recipient_address)
                 .unwrap(),
-            Amount::from_str("300000").unwrap()
+            Amount::from_str("110").unwrap().saturating_sub(
+                exec_cfg
+                    .storage_costs_constants
+                    .ledger_cost_per_byte
+                    .saturating_mul_u64(LEDGER_ENTRY_BASE_SIZE as u64)
+            )
+        );
+        assert_eq!(
+            next_operation_sequence(
+                sample_state
+                    .read()
+                    .ledger
+                    .get_data_entry(&sender_address, OPERATION_SEQUENCE_KEY)
+                    .as_deref()
+            ),
+            2
         );
-
         // stop the execution controller
         manager.stop();
     }
 
+    /// Sends sponsored transactions: the sponsor pays the fee, the sender the amount
     #[test]
     #[serial]
-    fn vesting_max_rolls() {
+    fn send_sponsored_transactions() {
+        let vesting = get_initials_vesting(false);
         // setup the period duration
-        let vesting = get_initials_vesting(true);
         let exec_cfg = ExecutionConfig {
             t0: 100.into(),
             cursor_delay: 0.into(),
             initial_vesting_path: vesting.path().to_path_buf(),
             ..ExecutionConfig::default()
         };
-
         // get a sample final state
         let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
 
@@ -1125,28 +1197,41 @@ mod tests {
         );
         // initialize the execution system with genesis blocks
         init_execution_worker(&exec_cfg, &storage, controller.clone());
-        // generate the keypair and its corresponding address
-        let keypair =
+        // generate the sender and sponsor keypairs and the recipient address
+        let sender_keypair =
             KeyPair::from_str("S1JJeHiZv1C1zZN5GLFcbz6EXYiccmUPLkYuDFA3kayjxP39kFQ").unwrap();
-        let address = Address::from_public_key(&keypair.get_public_key());
-        // create the operation
-        // try to buy 60 rolls so (100+60) and the max rolls specified for this address in vesting is 150
-        let operation = Operation::new_verifiable(
-            Operation {
-                fee: Amount::zero(),
+        let sender_address = Address::from_public_key(&sender_keypair.get_public_key());
+        let sponsor_keypair =
+            KeyPair::from_str("S1kEBGgxHFBdsNC4HtRHhsZsB5irAtYHEmuAKATkfiomYmj58tm").unwrap();
+        let sponsor_address = Address::from_public_key(&sponsor_keypair.get_public_key());
+        let (recipient_address, _keypair) = get_random_address_full();
+        let transaction = |sponsor_keypair: &KeyPair, amount: &str| {
+            let content = Operation {
+                fee: Amount::from_str("10").unwrap(),
                 expire_period: 10,
-                op: OperationType::RollBuy { roll_count: 60 },
-            },
-            OperationSerializer::new(),
-            &keypair,
-        )
-        .unwrap();
-        // create the block containing the roll buy operation
-        storage.store_operations(vec![operation.clone()]);
-        let block = create_block(KeyPair::generate(), vec![operation], Slot::new(1, 0)).unwrap();
+                sequence: None,
+                sponsor: None,
+                op: OperationType::Transaction {
+                    recipient_address,
+                    amount: Amount::from_str(amount).unwrap(),
+                    memo: None,
+                },
+            }
+            .with_sponsor(&sender_keypair.get_public_key(), sponsor_keypair)
+            .unwrap();
+            Operation::new_verifiable(content, OperationSerializer::new(), &sender_keypair).unwrap()
+        };
+        // the second operation is sponsored by an address without coins to pay its fee
+        let operations = vec![
+            transaction(&sponsor_keypair, "100"),
+            transaction(&KeyPair::generate(), "50"),
+        ];
+        // create the block containing the transaction operations
+        storage.store_operations(operations.clone());
+        let block = create_block(KeyPair::generate(), operations, Slot::new(1, 0)).unwrap();
         // store the block in storage
         storage.store_block(block.clone());
-        // set our block as a final block so the purchase is processed
+        // set our block as a final block so the transactions are processed
         let mut finalized_blocks: HashMap<Slot, BlockId> = Default::default();
         finalized_blocks.insert(block.content.header.content.slot, block.id);
         let mut block_storage: PreHashMap<BlockId, Storage> = Default::default();
@@ -1156,29 +1241,45 @@ mod tests {
             Default::default(),
             block_storage.clone(),
         );
-        std::thread::sleep(Duration::from_millis(100));
-
-        // retrieve the event emitted by the execution error
-        let events = controller.get_filtered_sc_output_event(EventFilter::default());
-        assert!(events[0].data.contains("massa_execution_error"));
-        assert!(events[0].data.contains(
-            "We reach the vesting constraint : trying to get to a total of 160 rolls but only 150 are allowed at that time by the vesting scheme"
-        ));
-
-        // check roll count of the buyer address and its balance, same as start because operation was rejected
-        let sample_read = sample_state.read();
-        assert_eq!(sample_read.pos_state.get_rolls_for(&address), 100);
+        std::thread::sleep(Duration::from_millis(10));
+        // only the first transaction was executed, its fee paid by the sponsor
         assert_eq!(
-            sample_read.ledger.get_balance(&address).unwrap(),
-            Amount::from_str("300_000").unwrap()
+            sample_state
+                .read()
+                .ledger
+                .get_balance(&recipient_address)
+                .unwrap(),
+            Amount::from_str("100").unwrap().saturating_sub(
+                exec_cfg
+                    .storage_costs_constants
+                    .ledger_cost_per_byte
+                    .saturating_mul_u64(LEDGER_ENTRY_BASE_SIZE as u64)
+            )
+        );
+        assert_eq!(
+            sample_state
+                .read()
+                .ledger
+                .get_balance(&sender_address)
+                .unwrap(),
+            Amount::from_str("299_900").unwrap()
+        );
+        assert_eq!(
+            sample_state
+                .read()
+                .ledger
+                .get_balance(&sponsor_address)
+                .unwrap(),
+            Amount::from_str("299_990").unwrap()
         );
         // stop the execution controller
         manager.stop();
     }
 
+    /// Sends a transaction with a memo: the sender pays for its size
     #[test]
     #[serial]
-    pub fn roll_buy() {
+    fn send_transaction_with_memo() {
         let vesting = get_initials_vesting(false);
         // setup the period duration
         let exec_cfg = ExecutionConfig {
@@ -1200,27 +1301,40 @@ mod tests {
         );
         // initialize the execution system with genesis blocks
         init_execution_worker(&exec_cfg, &storage, controller.clone());
-        // generate the keypair and its corresponding address
-        let keypair =
+        // generate the sender_keypair and recipient_address
+        let sender_keypair =
             KeyPair::from_str("S1JJeHiZv1C1zZN5GLFcbz6EXYiccmUPLkYuDFA3kayjxP39kFQ").unwrap();
-        let address = Address::from_public_key(&keypair.get_public_key());
+        let sender_address = Address::from_public_key(&sender_keypair.get_public_key());
+        let sender_balance = sample_state
+            .read()
+            .ledger
+            .get_balance(&sender_address)
+            .unwrap();
+        let (recipient_address, _keypair) = get_random_address_full();
+        let memo = b"deposit-42".to_vec();
         // create the operation
         let operation = Operation::new_verifiable(
             Operation {
                 fee: Amount::zero(),
                 expire_period: 10,
-                op: OperationType::RollBuy { roll_count: 10 },
+                sequence: None,
+                sponsor: None,
+                op: OperationType::Transaction {
+                    recipient_address,
+                    amount: Amount::from_str("100").unwrap(),
+                    memo: Some(memo.clone()),
+                },
             },
             OperationSerializer::new(),
-            &keypair,
+            &sender_keypair,
         )
         .unwrap();
-        // create the block containing the roll buy operation
+        // create the block containing the transaction operation
         storage.store_operations(vec![operation.clone()]);
         let block = create_block(KeyPair::generate(), vec![operation], Slot::new(1, 0)).unwrap();
         // store the block in storage
         storage.store_block(block.clone());
-        // set our block as a final block so the purchase is processed
+        // set our block as a final block so the transaction is processed
         let mut finalized_blocks: HashMap<Slot, BlockId> = Default::default();
         finalized_blocks.insert(block.content.header.content.slot, block.id);
         let mut block_storage: PreHashMap<BlockId, Storage> = Default::default();
@@ -1230,39 +1344,38 @@ mod tests {
             Default::default(),
             block_storage.clone(),
         );
-        std::thread::sleep(Duration::from_millis(100));
-        // check roll count of the buyer address and its balance
-        let sample_read = sample_state.read();
-        assert_eq!(sample_read.pos_state.get_rolls_for(&address), 110);
+        std::thread::sleep(Duration::from_millis(10));
+        // the sender paid the amount and the memo
         assert_eq!(
-            sample_read.ledger.get_balance(&address).unwrap(),
-            Amount::from_str("299_000").unwrap()
+            sample_state
+                .read()
+                .ledger
+                .get_balance(&sender_address)
+                .unwrap(),
+            sender_balance
+                .saturating_sub(Amount::from_str("100").unwrap())
+                .saturating_sub(
+                    exec_cfg
+                        .transaction_memo_cost_per_byte
+                        .saturating_mul_u64(memo.len() as u64)
+                )
         );
         // stop the execution controller
         manager.stop();
     }
 
+    /// Sends multi-recipient transactions: all the recipients are paid, or none if the sender cannot pay them all
     #[test]
     #[serial]
-    pub fn roll_sell() {
+    fn send_multi_transaction() {
         let vesting = get_initials_vesting(false);
-        // Try to sell 10 rolls (operation 1) then 1 rolls (operation 2)
-        // Check for resulting roll count + resulting deferred credits
-
         // setup the period duration
-        let mut exec_cfg = ExecutionConfig {
+        let exec_cfg = ExecutionConfig {
             t0: 100.into(),
-            periods_per_cycle: 2,
-            thread_count: 2,
             cursor_delay: 0.into(),
             initial_vesting_path: vesting.path().to_path_buf(),
-            ..Default::default()
+            ..ExecutionConfig::default()
         };
-        // turn off roll selling on missed block opportunities
-        // otherwise balance will be credited with those sold roll (and we need to check the balance for
-        // if the deferred credits are reimbursed
-        exec_cfg.max_miss_ratio = Ratio::new(1, 1);
-
         // get a sample final state
         let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
 
@@ -1276,55 +1389,48 @@ mod tests {
         );
         // initialize the execution system with genesis blocks
         init_execution_worker(&exec_cfg, &storage, controller.clone());
-        // generate the keypair and its corresponding address
-        let keypair =
+        // generate the sender_keypair and recipient addresses
+        let sender_keypair =
             KeyPair::from_str("S1JJeHiZv1C1zZN5GLFcbz6EXYiccmUPLkYuDFA3kayjxP39kFQ").unwrap();
-        let address = Address::from_public_key(&keypair.get_public_key());
-
-        // get initial balance
-        let balance_initial = sample_state.read().ledger.get_balance(&address).unwrap();
-
-        // get initial roll count
-        let roll_count_initial = sample_state.read().pos_state.get_rolls_for(&address);
-        let roll_sell_1 = 10;
-        let roll_sell_2 = 1;
-
-        // create operation 1
-        let operation1 = Operation::new_verifiable(
-            Operation {
-                fee: Amount::zero(),
-                expire_period: 10,
-                op: OperationType::RollSell {
-                    roll_count: roll_sell_1,
-                },
-            },
-            OperationSerializer::new(),
-            &keypair,
-        )
-        .unwrap();
-        let operation2 = Operation::new_verifiable(
-            Operation {
-                fee: Amount::zero(),
-                expire_period: 10,
-                op: OperationType::RollSell {
-                    roll_count: roll_sell_2,
+        let recipient_addresses: Vec<Address> =
+            (0..3).map(|_| get_random_address_full().0).collect();
+        let multi_transaction = |amounts: &[&str], fee: Amount| {
+            Operation::new_verifiable(
+                Operation {
+                    fee,
+                    expire_period: 10,
+                    sequence: None,
+                    sponsor: None,
+                    op: OperationType::MultiTransaction {
+                        outputs: recipient_addresses
+                            .iter()
+                            .zip(amounts)
+                            .map(|(recipient_address, amount)| TransactionOutput {
+                                recipient_address: *recipient_address,
+                                amount: Amount::from_str(amount).unwrap(),
+                            })
+                            .collect(),
+                    },
                 },
-            },
-            OperationSerializer::new(),
-            &keypair,
-        )
-        .unwrap();
-        // create the block containing the roll buy operation
-        storage.store_operations(vec![operation1.clone(), operation2.clone()]);
-        let block = create_block(
-            KeyPair::generate(),
-            vec![operation1, operation2],
-            Slot::new(1, 0),
-        )
-        .unwrap();
+                OperationSerializer::new(),
+                &sender_keypair,
+            )
+            .unwrap()
+        };
+        let fee = MULTI_TRANSACTION_FEE_PER_OUTPUT.saturating_mul_u64(3);
+        let operations = vec![
+            multi_transaction(&["100", "200", "300"], fee),
+            // the sender cannot pay the last recipient
+            multi_transaction(&["1", "1", "100000000000"], fee),
+            // the fee does not cover the recipients
+            multi_transaction(&["1", "1", "1"], Amount::zero()),
+        ];
+        // create the block containing the operations
+        storage.store_operations(operations.clone());
+        let block = create_block(KeyPair::generate(), operations, Slot::new(1, 0)).unwrap();
         // store the block in storage
         storage.store_block(block.clone());
-        // set the block as final so the sell and credits are processed
+        // set our block as a final block so the operations are processed
         let mut finalized_blocks: HashMap<Slot, BlockId> = Default::default();
         finalized_blocks.insert(block.content.header.content.slot, block.id);
         let mut block_storage: PreHashMap<BlockId, Storage> = Default::default();
@@ -1334,36 +1440,870 @@ mod tests {
             Default::default(),
             block_storage.clone(),
         );
-        std::thread::sleep(Duration::from_millis(1000));
-        // check roll count deferred credits and candidate balance of the seller address
-        let sample_read = sample_state.read();
-        let mut credits = PreHashMap::default();
-        let roll_remaining = roll_count_initial - roll_sell_1 - roll_sell_2;
-        let roll_sold = roll_sell_1 + roll_sell_2;
-        credits.insert(
-            address,
-            exec_cfg.roll_price.checked_mul_u64(roll_sold).unwrap(),
-        );
-
-        assert_eq!(
-            sample_read.pos_state.get_rolls_for(&address),
-            roll_remaining
-        );
-        assert_eq!(
-            sample_read
-                .pos_state
-                .get_deferred_credits_at(&Slot::new(7, 1)),
-            credits
-        );
+        std::thread::sleep(Duration::from_millis(10));
+        // only the first operation paid the recipients
+        for (recipient_address, amount) in recipient_addresses.iter().zip(["100", "200", "300"]) {
+            assert_eq!(
+                sample_state
+                    .read()
+                    .ledger
+                    .get_balance(recipient_address)
+                    .unwrap(),
+                // Storage cost applied
+                Amount::from_str(amount).unwrap().saturating_sub(
+                    exec_cfg
+                        .storage_costs_constants
+                        .ledger_cost_per_byte
+                        .saturating_mul_u64(LEDGER_ENTRY_BASE_SIZE as u64)
+                )
+            );
+        }
+        // stop the execution controller
+        manager.stop();
+    }
 
-        // Check that deferred credit are reimbursed
-        let credits = PreHashMap::default();
-        assert_eq!(
-            sample_read
-                .pos_state
-                .get_deferred_credits_at(&Slot::new(8, 1)),
-            credits
-        );
+    /// Schedules transfers, cancels one of them and checks that the other one is credited at its activation slot
+    #[test]
+    #[serial]
+    fn send_scheduled_transaction() {
+        let vesting = get_initials_vesting(false);
+        // setup the period duration, long enough for the activation slot not to be executed as a candidate first
+        let exec_cfg = ExecutionConfig {
+            t0: 1000.into(),
+            cursor_delay: 0.into(),
+            initial_vesting_path: vesting.path().to_path_buf(),
+            ..ExecutionConfig::default()
+        };
+        // get a sample final state
+        let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+
+        // init the storage
+        let mut storage = Storage::create_root();
+        // start the execution worker
+        let (mut manager, controller) = start_execution_worker(
+            exec_cfg.clone(),
+            sample_state.clone(),
+            sample_state.read().pos_state.selector.clone(),
+        );
+        // initialize the execution system with genesis blocks
+        init_execution_worker(&exec_cfg, &storage, controller.clone());
+        // generate the sender_keypair and recipient addresses
+        let sender_keypair =
+            KeyPair::from_str("S1JJeHiZv1C1zZN5GLFcbz6EXYiccmUPLkYuDFA3kayjxP39kFQ").unwrap();
+        let sender_address = Address::from_public_key(&sender_keypair.get_public_key());
+        let (recipient_address, cancelled_recipient_address) =
+            (get_random_address_full().0, get_random_address_full().0);
+        let operation = |op: OperationType| {
+            Operation::new_verifiable(
+                Operation {
+                    fee: Amount::zero(),
+                    expire_period: 10,
+                    sequence: None,
+                    sponsor: None,
+                    op,
+                },
+                OperationSerializer::new(),
+                &sender_keypair,
+            )
+            .unwrap()
+        };
+        let scheduled = operation(OperationType::ScheduledTransaction {
+            recipient_address,
+            amount: Amount::from_str("100").unwrap(),
+            activation_slot: Slot::new(1, 1),
+        });
+        let cancelled = operation(OperationType::ScheduledTransaction {
+            recipient_address: cancelled_recipient_address,
+            amount: Amount::from_str("50").unwrap(),
+            activation_slot: Slot::new(1, 1),
+        });
+        // the activation slot is not in the future
+        let past = operation(OperationType::ScheduledTransaction {
+            recipient_address: cancelled_recipient_address,
+            amount: Amount::from_str("1").unwrap(),
+            activation_slot: Slot::new(1, 0),
+        });
+        let cancellation = operation(OperationType::CancelScheduledTransaction {
+            operation_id: cancelled.id,
+        });
+
+        // finalize a block scheduling the transfers and cancelling one of them
+        let operations = vec![scheduled.clone(), cancelled, past, cancellation];
+        storage.store_operations(operations.clone());
+        let block = create_block(KeyPair::generate(), operations, Slot::new(1, 0)).unwrap();
+        storage.store_block(block.clone());
+        let mut finalized_blocks: HashMap<Slot, BlockId> = Default::default();
+        finalized_blocks.insert(block.content.header.content.slot, block.id);
+        let mut block_storage: PreHashMap<BlockId, Storage> = Default::default();
+        block_storage.insert(block.id, storage.clone());
+        controller.update_blockclique_status(finalized_blocks, Default::default(), block_storage);
+        std::thread::sleep(Duration::from_millis(10));
+        // only the first transfer is pending
+        let pending = controller.get_scheduled_transfers(&sender_address);
+        assert_eq!(pending.len(), 1);
+        assert_eq!(
+            pending[0].scheduled_transfer_operation_id(),
+            Some(scheduled.id)
+        );
+        assert_eq!(pending[0].destination, recipient_address);
+        assert!(sample_state
+            .read()
+            .ledger
+            .get_balance(&recipient_address)
+            .is_none());
+
+        // finalize an empty block at the activation slot
+        let mut storage = Storage::create_root();
+        let block = create_block(KeyPair::generate(), vec![], Slot::new(1, 1)).unwrap();
+        storage.store_block(block.clone());
+        let mut finalized_blocks: HashMap<Slot, BlockId> = Default::default();
+        finalized_blocks.insert(block.content.header.content.slot, block.id);
+        let mut block_storage: PreHashMap<BlockId, Storage> = Default::default();
+        block_storage.insert(block.id, storage.clone());
+        controller.update_blockclique_status(finalized_blocks, Default::default(), block_storage);
+        std::thread::sleep(Duration::from_millis(10));
+        // the transfer was credited at its activation slot
+        assert!(controller
+            .get_scheduled_transfers(&sender_address)
+            .is_empty());
+        assert_eq!(
+            sample_state
+                .read()
+                .ledger
+                .get_balance(&recipient_address)
+                .unwrap(),
+            // Storage cost applied
+            Amount::from_str("100").unwrap().saturating_sub(
+                exec_cfg
+                    .storage_costs_constants
+                    .ledger_cost_per_byte
+                    .saturating_mul_u64(LEDGER_ENTRY_BASE_SIZE as u64)
+            )
+        );
+        assert!(sample_state
+            .read()
+            .ledger
+            .get_balance(&cancelled_recipient_address)
+            .is_none());
+        // stop the execution controller
+        manager.stop();
+    }
+
+    /// Registers a BLS key, checks that it is recorded at the cycle of the registration and cannot be replaced
+    #[test]
+    #[serial]
+    fn register_bls_key() {
+        let vesting = get_initials_vesting(false);
+        // setup the period duration
+        let exec_cfg = ExecutionConfig {
+            t0: 100.into(),
+            cursor_delay: 0.into(),
+            initial_vesting_path: vesting.path().to_path_buf(),
+            ..ExecutionConfig::default()
+        };
+        // get a sample final state
+        let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+
+        // init the storage
+        let mut storage = Storage::create_root();
+        // start the execution worker
+        let (mut manager, controller) = start_execution_worker(
+            exec_cfg.clone(),
+            sample_state.clone(),
+            sample_state.read().pos_state.selector.clone(),
+        );
+        // initialize the execution system with genesis blocks
+        init_execution_worker(&exec_cfg, &storage, controller.clone());
+        // generate the sender_keypair and its BLS keys
+        let sender_keypair =
+            KeyPair::from_str("S1JJeHiZv1C1zZN5GLFcbz6EXYiccmUPLkYuDFA3kayjxP39kFQ").unwrap();
+        let sender_address = Address::from_public_key(&sender_keypair.get_public_key());
+        let bls_keypair = BlsKeyPair::derive_from(&sender_keypair);
+        let other_bls_keypair = BlsKeyPair::generate();
+        let operation = |public_key, proof_of_possession| {
+            Operation::new_verifiable(
+                Operation {
+                    fee: Amount::zero(),
+                    expire_period: 10,
+                    sequence: None,
+                    sponsor: None,
+                    op: OperationType::RegisterBlsKey {
+                        public_key,
+                        proof_of_possession,
+                    },
+                },
+                OperationSerializer::new(),
+                &sender_keypair,
+            )
+            .unwrap()
+        };
+        // the proof of possession is not the one of the key
+        let unproven = operation(
+            bls_keypair.get_public_key(),
+            other_bls_keypair.prove_possession(),
+        );
+        let registration = operation(bls_keypair.get_public_key(), bls_keypair.prove_possession());
+        // the registered key cannot be replaced
+        let replacement = operation(
+            other_bls_keypair.get_public_key(),
+            other_bls_keypair.prove_possession(),
+        );
+
+        // finalize a block registering the key
+        let operations = vec![unproven, registration, replacement];
+        storage.store_operations(operations.clone());
+        let block = create_block(KeyPair::generate(), operations, Slot::new(1, 0)).unwrap();
+        storage.store_block(block.clone());
+        let mut finalized_blocks: HashMap<Slot, BlockId> = Default::default();
+        finalized_blocks.insert(block.content.header.content.slot, block.id);
+        let mut block_storage: PreHashMap<BlockId, Storage> = Default::default();
+        block_storage.insert(block.id, storage.clone());
+        controller.update_blockclique_status(finalized_blocks, Default::default(), block_storage);
+        std::thread::sleep(Duration::from_millis(10));
+        // only the proven key was registered
+        let registered = sample_state
+            .read()
+            .ledger
+            .get_data_entry(&sender_address, BLS_PUBLIC_KEY_KEY)
+            .and_then(|bytes| RegisteredBlsKey::from_bytes(&bytes));
+        assert_eq!(
+            registered,
+            Some(RegisteredBlsKey {
+                public_key: bls_keypair.get_public_key(),
+                cycle: 0,
+            })
+        );
+        // stop the execution controller
+        manager.stop();
+    }
+
+    #[test]
+    #[serial]
+    fn dry_run_transaction() {
+        let vesting = get_initials_vesting(false);
+        // setup the period duration
+        let exec_cfg = ExecutionConfig {
+            t0: 100.into(),
+            cursor_delay: 0.into(),
+            initial_vesting_path: vesting.path().to_path_buf(),
+            ..ExecutionConfig::default()
+        };
+        // get a sample final state
+        let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+
+        // init the storage
+        let storage = Storage::create_root();
+        // start the execution worker
+        let (mut manager, controller) = start_execution_worker(
+            exec_cfg.clone(),
+            sample_state.clone(),
+            sample_state.read().pos_state.selector.clone(),
+        );
+        // initialize the execution system with genesis blocks
+        init_execution_worker(&exec_cfg, &storage, controller.clone());
+        // generate the sender_keypair and recipient_address
+        let sender_keypair =
+            KeyPair::from_str("S1JJeHiZv1C1zZN5GLFcbz6EXYiccmUPLkYuDFA3kayjxP39kFQ").unwrap();
+        let sender_address = Address::from_public_key(&sender_keypair.get_public_key());
+        let (recipient_address, _keypair) = get_random_address_full();
+        let operation = Operation::new_verifiable(
+            Operation {
+                fee: Amount::zero(),
+                expire_period: 10,
+                sequence: None,
+                sponsor: None,
+                op: OperationType::Transaction {
+                    recipient_address,
+                    amount: Amount::from_str("100").unwrap(),
+                    memo: None,
+                },
+            },
+            OperationSerializer::new(),
+            &sender_keypair,
+        )
+        .unwrap();
+
+        // dry run the transaction from the final state
+        let res = controller
+            .execute_readonly_request(ReadOnlyExecutionRequest {
+                max_gas: operation.get_gas_usage(),
+                call_stack: vec![],
+                target: ReadOnlyExecutionTarget::Operation(Box::new(operation.clone())),
+                is_final: true,
+                limits: Default::default(),
+                fork: None,
+            })
+            .expect("dry run failed");
+        assert_eq!(
+            res.out.slot.thread,
+            sender_address.get_thread(exec_cfg.thread_count)
+        );
+        assert_eq!(
+            res.out
+                .state_changes
+                .executed_ops_changes
+                .get(&operation.id)
+                .map(|(success, _)| *success),
+            Some(true)
+        );
+        assert!(res
+            .out
+            .state_changes
+            .ledger_changes
+            .0
+            .contains_key(&recipient_address));
+        // the state is left untouched
+        std::thread::sleep(Duration::from_millis(10));
+        assert!(sample_state
+            .read()
+            .ledger
+            .get_balance(&recipient_address)
+            .is_none());
+        assert!(
+            controller.get_final_and_candidate_balance(&[recipient_address])[0]
+                .1
+                .is_none()
+        );
+        // stop the execution controller
+        manager.stop();
+    }
+
+    #[test]
+    #[serial]
+    fn simulate_transactions_in_fork() {
+        let vesting = get_initials_vesting(false);
+        // setup the period duration
+        let exec_cfg = ExecutionConfig {
+            t0: 100.into(),
+            cursor_delay: 0.into(),
+            initial_vesting_path: vesting.path().to_path_buf(),
+            ..ExecutionConfig::default()
+        };
+        // get a sample final state
+        let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+
+        // init the storage
+        let storage = Storage::create_root();
+        // start the execution worker
+        let (mut manager, controller) = start_execution_worker(
+            exec_cfg.clone(),
+            sample_state.clone(),
+            sample_state.read().pos_state.selector.clone(),
+        );
+        // initialize the execution system with genesis blocks
+        init_execution_worker(&exec_cfg, &storage, controller.clone());
+        // generate the sender_keypair and recipient_address
+        let sender_keypair =
+            KeyPair::from_str("S1JJeHiZv1C1zZN5GLFcbz6EXYiccmUPLkYuDFA3kayjxP39kFQ").unwrap();
+        let (recipient_address, _keypair) = get_random_address_full();
+        let transfer = |amount: &str| {
+            Operation::new_verifiable(
+                Operation {
+                    fee: Amount::zero(),
+                    expire_period: 10,
+                    sequence: None,
+                    sponsor: None,
+                    op: OperationType::Transaction {
+                        recipient_address,
+                        amount: Amount::from_str(amount).unwrap(),
+                        memo: None,
+                    },
+                },
+                OperationSerializer::new(),
+                &sender_keypair,
+            )
+            .unwrap()
+        };
+        let execute_in_fork = |operation: &SecureShareOperation| {
+            controller.execute_readonly_request(ReadOnlyExecutionRequest {
+                max_gas: operation.get_gas_usage(),
+                call_stack: vec![],
+                target: ReadOnlyExecutionTarget::Operation(Box::new(operation.clone())),
+                is_final: false,
+                limits: Default::default(),
+                fork: Some("simulation".to_string()),
+            })
+        };
+
+        // unknown forks are refused
+        let first_transfer = transfer("100");
+        execute_in_fork(&first_transfer).expect_err("executed in an unknown fork");
+
+        // the operations executed in a fork are applied to it
+        controller.create_fork("simulation".to_string()).unwrap();
+        controller
+            .create_fork("simulation".to_string())
+            .expect_err("created a fork twice");
+        let first_res = execute_in_fork(&first_transfer).expect("execution in fork failed");
+        let second_res = execute_in_fork(&transfer("50")).expect("execution in fork failed");
+        assert!(second_res.out.slot > first_res.out.slot);
+        assert_eq!(
+            second_res
+                .out
+                .state_changes
+                .ledger_changes
+                .get_balance_or_else(&recipient_address, || None),
+            Some(Amount::from_str("150").unwrap())
+        );
+        execute_in_fork(&first_transfer).expect_err("replayed an operation in the fork");
+
+        // the candidate state is left untouched
+        assert!(
+            controller.get_final_and_candidate_balance(&[recipient_address])[0]
+                .1
+                .is_none()
+        );
+
+        // a discarded fork cannot be used anymore
+        controller.discard_fork("simulation").unwrap();
+        execute_in_fork(&first_transfer).expect_err("executed in a discarded fork");
+        // stop the execution controller
+        manager.stop();
+    }
+
+    #[test]
+    #[serial]
+    fn stream_final_changes_to_file_state_sink() {
+        let vesting = get_initials_vesting(false);
+        let sink_file = tempfile::NamedTempFile::new().unwrap();
+        // setup the period duration and the state sink
+        let exec_cfg = ExecutionConfig {
+            t0: 100.into(),
+            cursor_delay: 0.into(),
+            initial_vesting_path: vesting.path().to_path_buf(),
+            state_sink: StateSinkConfig::File {
+                path: sink_file.path().to_path_buf(),
+            },
+            ..ExecutionConfig::default()
+        };
+        // get a sample final state
+        let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+
+        // init the storage
+        let mut storage = Storage::create_root();
+        // start the execution worker
+        let (mut manager, controller) = start_execution_worker(
+            exec_cfg.clone(),
+            sample_state.clone(),
+            sample_state.read().pos_state.selector.clone(),
+        );
+        // initialize the execution system with genesis blocks
+        init_execution_worker(&exec_cfg, &storage, controller.clone());
+        // create a final block with a transaction to a new address
+        let sender_keypair =
+            KeyPair::from_str("S1JJeHiZv1C1zZN5GLFcbz6EXYiccmUPLkYuDFA3kayjxP39kFQ").unwrap();
+        let (recipient_address, _keypair) = get_random_address_full();
+        let operation = Operation::new_verifiable(
+            Operation {
+                fee: Amount::zero(),
+                expire_period: 10,
+                sequence: None,
+                sponsor: None,
+                op: OperationType::Transaction {
+                    recipient_address,
+                    amount: Amount::from_str("100").unwrap(),
+                    memo: None,
+                },
+            },
+            OperationSerializer::new(),
+            &sender_keypair,
+        )
+        .unwrap();
+        storage.store_operations(vec![operation.clone()]);
+        let block = create_block(KeyPair::generate(), vec![operation], Slot::new(1, 0)).unwrap();
+        storage.store_block(block.clone());
+        let mut finalized_blocks: HashMap<Slot, BlockId> = Default::default();
+        finalized_blocks.insert(block.content.header.content.slot, block.id);
+        let mut block_storage: PreHashMap<BlockId, Storage> = Default::default();
+        block_storage.insert(block.id, storage.clone());
+        controller.update_blockclique_status(finalized_blocks, Default::default(), block_storage);
+        std::thread::sleep(Duration::from_millis(10));
+        // stopping the execution controller flushes the state sink
+        manager.stop();
+
+        // check that the changes of the block slot were streamed, with the new entry of the recipient
+        let content = std::fs::read_to_string(sink_file.path()).unwrap();
+        let changes: serde_json::Value = content
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .find(|changes| changes["slot"] == serde_json::json!({"period": 1, "thread": 0}))
+            .expect("changes of the final block slot were not streamed");
+        assert_eq!(changes["block_id"], serde_json::json!(block.id.to_string()));
+        let recipient_change = changes["ledger_changes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|change| change["address"] == serde_json::json!(recipient_address.to_string()))
+            .expect("the recipient entry change was not streamed");
+        assert_eq!(recipient_change["replaced"], serde_json::json!(true));
+    }
+
+    #[test]
+    #[serial]
+    fn vesting_transfer_coins() {
+        let vesting = get_initials_vesting(true);
+        // setup the period duration
+        let exec_cfg = ExecutionConfig {
+            t0: 100.into(),
+            cursor_delay: 0.into(),
+            initial_vesting_path: vesting.path().to_path_buf(),
+            ..ExecutionConfig::default()
+        };
+        // get a sample final state
+        let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+
+        // init the storage
+        let mut storage = Storage::create_root();
+        // start the execution worker
+        let (mut manager, controller) = start_execution_worker(
+            exec_cfg.clone(),
+            sample_state.clone(),
+            sample_state.read().pos_state.selector.clone(),
+        );
+        // initialize the execution system with genesis blocks
+        init_execution_worker(&exec_cfg, &storage, controller.clone());
+        // generate the sender_keypair and recipient_address
+        let sender_keypair =
+            KeyPair::from_str("S1JJeHiZv1C1zZN5GLFcbz6EXYiccmUPLkYuDFA3kayjxP39kFQ").unwrap();
+        let sender_addr = Address::from_public_key(&sender_keypair.get_public_key());
+        let (recipient_address, _keypair) = get_random_address_full();
+        // create the operation
+        let operation = Operation::new_verifiable(
+            Operation {
+                fee: Amount::zero(),
+                expire_period: 10,
+                sequence: None,
+                sponsor: None,
+                op: OperationType::Transaction {
+                    recipient_address,
+                    amount: Amount::from_str("250000").unwrap(),
+                    memo: None,
+                },
+            },
+            OperationSerializer::new(),
+            &sender_keypair,
+        )
+        .unwrap();
+        // create the block containing the transaction operation
+        storage.store_operations(vec![operation.clone()]);
+        let block = create_block(KeyPair::generate(), vec![operation], Slot::new(1, 0)).unwrap();
+        // store the block in storage
+        storage.store_block(block.clone());
+        // set our block as a final block so the transaction is processed
+        let mut finalized_blocks: HashMap<Slot, BlockId> = Default::default();
+        finalized_blocks.insert(block.content.header.content.slot, block.id);
+        let mut block_storage: PreHashMap<BlockId, Storage> = Default::default();
+        block_storage.insert(block.id, storage.clone());
+        controller.update_blockclique_status(
+            finalized_blocks,
+            Default::default(),
+            block_storage.clone(),
+        );
+        std::thread::sleep(Duration::from_millis(100));
+
+        // retrieve the event emitted by the execution error
+        let events = controller.get_filtered_sc_output_event(EventFilter::default());
+        assert!(events[0].data.contains("massa_execution_error"));
+        assert!(events[0]
+            .data
+            .contains("We reach the vesting constraint : vesting_min_balance=100000 with value min_balance=60000"));
+        assert_eq!(
+            events[0].context.error.as_ref().map(|error| error.code),
+            Some(ExecutionErrorCode::Vesting)
+        );
+
+        // check recipient balance
+        assert!(sample_state
+            .read()
+            .ledger
+            .get_balance(&recipient_address)
+            .is_none());
+        // Check sender balance
+        assert_eq!(
+            sample_state
+                .read()
+                .ledger
+                .get_balance(&sender_addr)
+                .unwrap(),
+            Amount::from_str("300000").unwrap()
+        );
+
+        // stop the execution controller
+        manager.stop();
+    }
+
+    #[test]
+    #[serial]
+    fn vesting_max_rolls() {
+        // setup the period duration
+        let vesting = get_initials_vesting(true);
+        let exec_cfg = ExecutionConfig {
+            t0: 100.into(),
+            cursor_delay: 0.into(),
+            initial_vesting_path: vesting.path().to_path_buf(),
+            ..ExecutionConfig::default()
+        };
+
+        // get a sample final state
+        let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+
+        // init the storage
+        let mut storage = Storage::create_root();
+        // start the execution worker
+        let (mut manager, controller) = start_execution_worker(
+            exec_cfg.clone(),
+            sample_state.clone(),
+            sample_state.read().pos_state.selector.clone(),
+        );
+        // initialize the execution system with genesis blocks
+        init_execution_worker(&exec_cfg, &storage, controller.clone());
+        // generate the keypair and its corresponding address
+        let keypair =
+            KeyPair::from_str("S1JJeHiZv1C1zZN5GLFcbz6EXYiccmUPLkYuDFA3kayjxP39kFQ").unwrap();
+        let address = Address::from_public_key(&keypair.get_public_key());
+        // create the operation
+        // try to buy 60 rolls so (100+60) and the max rolls specified for this address in vesting is 150
+        let operation = Operation::new_verifiable(
+            Operation {
+                fee: Amount::zero(),
+                expire_period: 10,
+                sequence: None,
+                sponsor: None,
+                op: OperationType::RollBuy { roll_count: 60 },
+            },
+            OperationSerializer::new(),
+            &keypair,
+        )
+        .unwrap();
+        // create the block containing the roll buy operation
+        storage.store_operations(vec![operation.clone()]);
+        let block = create_block(KeyPair::generate(), vec![operation], Slot::new(1, 0)).unwrap();
+        // store the block in storage
+        storage.store_block(block.clone());
+        // set our block as a final block so the purchase is processed
+        let mut finalized_blocks: HashMap<Slot, BlockId> = Default::default();
+        finalized_blocks.insert(block.content.header.content.slot, block.id);
+        let mut block_storage: PreHashMap<BlockId, Storage> = Default::default();
+        block_storage.insert(block.id, storage.clone());
+        controller.update_blockclique_status(
+            finalized_blocks,
+            Default::default(),
+            block_storage.clone(),
+        );
+        std::thread::sleep(Duration::from_millis(100));
+
+        // retrieve the event emitted by the execution error
+        let events = controller.get_filtered_sc_output_event(EventFilter::default());
+        assert!(events[0].data.contains("massa_execution_error"));
+        assert!(events[0].data.contains(
+            "We reach the vesting constraint : trying to get to a total of 160 rolls but only 150 are allowed at that time by the vesting scheme"
+        ));
+
+        // check roll count of the buyer address and its balance, same as start because operation was rejected
+        let sample_read = sample_state.read();
+        assert_eq!(sample_read.pos_state.get_rolls_for(&address), 100);
+        assert_eq!(
+            sample_read.ledger.get_balance(&address).unwrap(),
+            Amount::from_str("300_000").unwrap()
+        );
+        // stop the execution controller
+        manager.stop();
+    }
+
+    #[test]
+    #[serial]
+    pub fn roll_buy() {
+        let vesting = get_initials_vesting(false);
+        // setup the period duration
+        let exec_cfg = ExecutionConfig {
+            t0: 100.into(),
+            cursor_delay: 0.into(),
+            initial_vesting_path: vesting.path().to_path_buf(),
+            ..ExecutionConfig::default()
+        };
+        // get a sample final state
+        let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+
+        // init the storage
+        let mut storage = Storage::create_root();
+        // start the execution worker
+        let (mut manager, controller) = start_execution_worker(
+            exec_cfg.clone(),
+            sample_state.clone(),
+            sample_state.read().pos_state.selector.clone(),
+        );
+        // initialize the execution system with genesis blocks
+        init_execution_worker(&exec_cfg, &storage, controller.clone());
+        // generate the keypair and its corresponding address
+        let keypair =
+            KeyPair::from_str("S1JJeHiZv1C1zZN5GLFcbz6EXYiccmUPLkYuDFA3kayjxP39kFQ").unwrap();
+        let address = Address::from_public_key(&keypair.get_public_key());
+        // create the operation
+        let operation = Operation::new_verifiable(
+            Operation {
+                fee: Amount::zero(),
+                expire_period: 10,
+                sequence: None,
+                sponsor: None,
+                op: OperationType::RollBuy { roll_count: 10 },
+            },
+            OperationSerializer::new(),
+            &keypair,
+        )
+        .unwrap();
+        // create the block containing the roll buy operation
+        storage.store_operations(vec![operation.clone()]);
+        let block = create_block(KeyPair::generate(), vec![operation], Slot::new(1, 0)).unwrap();
+        // store the block in storage
+        storage.store_block(block.clone());
+        // set our block as a final block so the purchase is processed
+        let mut finalized_blocks: HashMap<Slot, BlockId> = Default::default();
+        finalized_blocks.insert(block.content.header.content.slot, block.id);
+        let mut block_storage: PreHashMap<BlockId, Storage> = Default::default();
+        block_storage.insert(block.id, storage.clone());
+        controller.update_blockclique_status(
+            finalized_blocks,
+            Default::default(),
+            block_storage.clone(),
+        );
+        std::thread::sleep(Duration::from_millis(100));
+        // check roll count of the buyer address and its balance
+        let sample_read = sample_state.read();
+        assert_eq!(sample_read.pos_state.get_rolls_for(&address), 110);
+        assert_eq!(
+            sample_read.ledger.get_balance(&address).unwrap(),
+            Amount::from_str("299_000").unwrap()
+        );
+        // stop the execution controller
+        manager.stop();
+    }
+
+    #[test]
+    #[serial]
+    pub fn roll_sell() {
+        let vesting = get_initials_vesting(false);
+        // Try to sell 10 rolls (operation 1) then 1 rolls (operation 2)
+        // Check for resulting roll count + resulting deferred credits
+
+        // setup the period duration
+        let mut exec_cfg = ExecutionConfig {
+            t0: 100.into(),
+            periods_per_cycle: 2,
+            thread_count: 2,
+            cursor_delay: 0.into(),
+            initial_vesting_path: vesting.path().to_path_buf(),
+            ..Default::default()
+        };
+        // turn off roll selling on missed block opportunities
+        // otherwise balance will be credited with those sold roll (and we need to check the balance for
+        // if the deferred credits are reimbursed
+        exec_cfg.max_miss_ratio = Ratio::new(1, 1);
+
+        // get a sample final state
+        let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+
+        // init the storage
+        let mut storage = Storage::create_root();
+        // start the execution worker
+        let (mut manager, controller) = start_execution_worker(
+            exec_cfg.clone(),
+            sample_state.clone(),
+            sample_state.read().pos_state.selector.clone(),
+        );
+        // initialize the execution system with genesis blocks
+        init_execution_worker(&exec_cfg, &storage, controller.clone());
+        // generate the keypair and its corresponding address
+        let keypair =
+            KeyPair::from_str("S1JJeHiZv1C1zZN5GLFcbz6EXYiccmUPLkYuDFA3kayjxP39kFQ").unwrap();
+        let address = Address::from_public_key(&keypair.get_public_key());
+
+        // get initial balance
+        let balance_initial = sample_state.read().ledger.get_balance(&address).unwrap();
+
+        // get initial roll count
+        let roll_count_initial = sample_state.read().pos_state.get_rolls_for(&address);
+        let roll_sell_1 = 10;
+        let roll_sell_2 = 1;
+
+        // create operation 1
+        let operation1 = Operation::new_verifiable(
+            Operation {
+                fee: Amount::zero(),
+                expire_period: 10,
+                sequence: None,
+                sponsor: None,
+                op: OperationType::RollSell {
+                    roll_count: roll_sell_1,
+                },
+            },
+            OperationSerializer::new(),
+            &keypair,
+        )
+        .unwrap();
+        let operation2 = Operation::new_verifiable(
+            Operation {
+                fee: Amount::zero(),
+                expire_period: 10,
+                sequence: None,
+                sponsor: None,
+                op: OperationType::RollSell {
+                    roll_count: roll_sell_2,
+                },
+            },
+            OperationSerializer::new(),
+            &keypair,
+        )
+        .unwrap();
+        // create the block containing the roll buy operation
+        storage.store_operations(vec![operation1.clone(), operation2.clone()]);
+        let block = create_block(
+            KeyPair::generate(),
+            vec![operation1, operation2],
+            Slot::new(1, 0),
+        )
+        .unwrap();
+        // store the block in storage
+        storage.store_block(block.clone());
+        // set the block as final so the sell and credits are processed
+        let mut finalized_blocks: HashMap<Slot, BlockId> = Default::default();
+        finalized_blocks.insert(block.content.header.content.slot, block.id);
+        let mut block_storage: PreHashMap<BlockId, Storage> = Default::default();
+        block_storage.insert(block.id, storage.clone());
+        controller.update_blockclique_status(
+            finalized_blocks,
+            Default::default(),
+            block_storage.clone(),
+        );
+        std::thread::sleep(Duration::from_millis(1000));
+        // check roll count deferred credits and candidate balance of the seller address
+        let sample_read = sample_state.read();
+        let mut credits = PreHashMap::default();
+        let roll_remaining = roll_count_initial - roll_sell_1 - roll_sell_2;
+        let roll_sold = roll_sell_1 + roll_sell_2;
+        credits.insert(
+            address,
+            exec_cfg.roll_price.checked_mul_u64(roll_sold).unwrap(),
+        );
+
+        assert_eq!(
+            sample_read.pos_state.get_rolls_for(&address),
+            roll_remaining
+        );
+        assert_eq!(
+            sample_read
+                .pos_state
+                .get_deferred_credits_at(&Slot::new(7, 1)),
+            credits
+        );
+
+        // Check that deferred credit are reimbursed
+        let credits = PreHashMap::default();
+        assert_eq!(
+            sample_read
+                .pos_state
+                .get_deferred_credits_at(&Slot::new(8, 1)),
+            credits
+        );
 
         // Now check balance
         let balances = controller.get_final_and_candidate_balance(&[address]);
@@ -1446,6 +2386,295 @@ mod tests {
             .data
             .contains("runtime error when executing operation"));
         assert!(events[1].data.contains("address parsing error"));
+        assert!(events[0].context.error.is_none());
+        let error = events[1]
+            .context
+            .error
+            .as_ref()
+            .expect("error event without error info");
+        assert_eq!(error.code, ExecutionErrorCode::Runtime);
+        assert!(error.revert_reason.is_none());
+        // stop the execution controller
+        manager.stop();
+    }
+
+    #[test]
+    #[serial]
+    fn store_final_events_on_disk() {
+        let vesting = get_initials_vesting(false);
+        let event_dir = tempfile::TempDir::new().unwrap();
+        // keep no final event in RAM: they can only be read from the event store
+        let exec_cfg = ExecutionConfig {
+            t0: 100.into(),
+            max_async_gas: 100_000,
+            cursor_delay: 0.into(),
+            initial_vesting_path: vesting.path().to_path_buf(),
+            max_final_events: 0,
+            event_store: EventStoreConfig {
+                enabled: true,
+                path: event_dir.path().to_path_buf(),
+                max_periods: 0,
+                max_bytes: 0,
+                max_query_events: 100,
+            },
+            ..ExecutionConfig::default()
+        };
+        // get a sample final state
+        let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+
+        // init the storage
+        let mut storage = Storage::create_root();
+        // start the execution worker
+        let (mut manager, controller) = start_execution_worker(
+            exec_cfg.clone(),
+            sample_state.clone(),
+            sample_state.read().pos_state.selector.clone(),
+        );
+        // initialize the execution system with genesis blocks
+        init_execution_worker(&exec_cfg, &storage, controller.clone());
+        // keypair associated to thread 0
+        let keypair =
+            KeyPair::from_str("S1JJeHiZv1C1zZN5GLFcbz6EXYiccmUPLkYuDFA3kayjxP39kFQ").unwrap();
+        // the execution emits an event then fails
+        let bytecode = include_bytes!("./wasm/execution_error.wasm");
+        let operation =
+            create_execute_sc_operation(&keypair, bytecode, BTreeMap::default()).unwrap();
+        storage.store_operations(vec![operation.clone()]);
+        let block = create_block(KeyPair::generate(), vec![operation], Slot::new(1, 0)).unwrap();
+        storage.store_block(block.clone());
+        // set our block as a final block
+        let mut finalized_blocks: HashMap<Slot, BlockId> = Default::default();
+        finalized_blocks.insert(block.content.header.content.slot, block.id);
+        let mut block_storage: PreHashMap<BlockId, Storage> = Default::default();
+        block_storage.insert(block.id, storage.clone());
+        controller.update_blockclique_status(finalized_blocks, Default::default(), block_storage);
+        std::thread::sleep(Duration::from_millis(10));
+
+        let filter = EventFilter {
+            start: Some(Slot::new(1, 0)),
+            end: Some(Slot::new(1, 1)),
+            is_final: Some(true),
+            ..Default::default()
+        };
+        let events = controller.get_filtered_sc_output_event(filter.clone());
+        assert_eq!(events.len(), 2, "the final events were not stored");
+        assert_eq!(events[0].data, "event generated before the sc failure");
+        assert!(events.iter().all(|event| event.context.is_final));
+        manager.stop();
+
+        // the events are kept across restarts
+        let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+        let (mut manager, controller) = start_execution_worker(
+            exec_cfg,
+            sample_state.clone(),
+            sample_state.read().pos_state.selector.clone(),
+        );
+        let events = controller.get_filtered_sc_output_event(filter);
+        assert_eq!(events.len(), 2, "the stored events were lost at restart");
+        manager.stop();
+    }
+
+    #[test]
+    #[serial]
+    fn index_final_block_operations() {
+        let vesting = get_initials_vesting(false);
+        let index_dir = tempfile::TempDir::new().unwrap();
+        let exec_cfg = ExecutionConfig {
+            t0: 100.into(),
+            cursor_delay: 0.into(),
+            initial_vesting_path: vesting.path().to_path_buf(),
+            operation_index: OperationIndexConfig {
+                enabled: true,
+                path: index_dir.path().to_path_buf(),
+                max_periods: 0,
+            },
+            ..ExecutionConfig::default()
+        };
+        // get a sample final state
+        let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+
+        // init the storage
+        let mut storage = Storage::create_root();
+        // start the execution worker
+        let (mut manager, controller) = start_execution_worker(
+            exec_cfg.clone(),
+            sample_state.clone(),
+            sample_state.read().pos_state.selector.clone(),
+        );
+        // initialize the execution system with genesis blocks
+        init_execution_worker(&exec_cfg, &storage, controller.clone());
+        // keypair associated to thread 0
+        let keypair =
+            KeyPair::from_str("S1JJeHiZv1C1zZN5GLFcbz6EXYiccmUPLkYuDFA3kayjxP39kFQ").unwrap();
+        let bytecode = include_bytes!("./wasm/execution_error.wasm");
+        let operation =
+            create_execute_sc_operation(&keypair, bytecode, BTreeMap::default()).unwrap();
+        storage.store_operations(vec![operation.clone()]);
+        let block = create_block(
+            KeyPair::generate(),
+            vec![operation.clone()],
+            Slot::new(1, 0),
+        )
+        .unwrap();
+        storage.store_block(block.clone());
+        // set our block as a final block
+        let mut finalized_blocks: HashMap<Slot, BlockId> = Default::default();
+        finalized_blocks.insert(block.content.header.content.slot, block.id);
+        let mut block_storage: PreHashMap<BlockId, Storage> = Default::default();
+        block_storage.insert(block.id, storage.clone());
+        controller.update_blockclique_status(finalized_blocks, Default::default(), block_storage);
+        std::thread::sleep(Duration::from_millis(10));
+
+        let expected_inclusion = OperationInclusion {
+            block_id: block.id,
+            slot: Slot::new(1, 0),
+            index: 0,
+        };
+        let expected_operations = FinalBlockOperations {
+            slot: Slot::new(1, 0),
+            operations: vec![operation.id],
+        };
+        assert_eq!(
+            controller.get_operation_inclusions(&[operation.id]),
+            vec![Some(expected_inclusion)]
+        );
+        assert_eq!(
+            controller
+                .get_final_block_operations(&[block.id, BlockId(Hash::compute_from(b"none"))]),
+            vec![Some(expected_operations.clone()), None]
+        );
+        manager.stop();
+
+        // the index is kept across restarts
+        let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+        let (mut manager, controller) = start_execution_worker(
+            exec_cfg,
+            sample_state.clone(),
+            sample_state.read().pos_state.selector.clone(),
+        );
+        assert_eq!(
+            controller.get_operation_inclusions(&[operation.id]),
+            vec![Some(expected_inclusion)]
+        );
+        assert_eq!(
+            controller.get_final_block_operations(&[block.id]),
+            vec![Some(expected_operations)]
+        );
+        manager.stop();
+    }
+
+    #[test]
+    #[serial]
+    fn storage_rent_reclamation() {
+        let vesting = get_initials_vesting(false);
+        // charge one coin per byte and cycle, and reclaim the storage after one unpaid cycle
+        let exec_cfg = ExecutionConfig {
+            t0: 100.into(),
+            periods_per_cycle: 2,
+            thread_count: 2,
+            cursor_delay: 0.into(),
+            initial_vesting_path: vesting.path().to_path_buf(),
+            storage_rent_constants: StorageRentConstants {
+                rent_per_byte: Amount::from_str("1").unwrap(),
+                grace_cycles: 1,
+            },
+            ..ExecutionConfig::default()
+        };
+        // get a sample final state with two addresses storing data:
+        // one can pay for its 3 bytes, the other cannot pay for its 10 bytes
+        let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+        let (paying, _) = get_random_address_full();
+        let (abandoned, _) = get_random_address_full();
+        let mut changes = LedgerChanges::default();
+        for (address, balance, value) in
+            [(paying, "1000", vec![2, 3]), (abandoned, "4", vec![0; 9])]
+        {
+            changes.0.insert(
+                address,
+                SetUpdateOrDelete::Set(LedgerEntry {
+                    balance: Amount::from_str(balance).unwrap(),
+                    datastore: BTreeMap::from([(vec![1], value)]),
+                    ..Default::default()
+                }),
+            );
+        }
+        let final_slot = sample_state.read().slot;
+        sample_state
+            .write()
+            .ledger
+            .apply_changes(changes, final_slot);
+
+        // the rent is only charged once the MIP introducing it is active
+        let mip_name = "storage rent".to_string();
+        let mip_store = MipStore::new(MipStoreConfig {
+            mips: vec![MipInfo {
+                name: mip_name.clone(),
+                version: STORAGE_RENT_VERSION,
+                components: vec![MipComponent::Execution],
+                start: MassaTime::from_millis(0),
+                timeout: MassaTime::from_millis(0),
+                activation_delay: MassaTime::from_millis(0),
+            }],
+            block_count_considered: 1,
+            activation_threshold_percent: 100,
+        });
+        mip_store.restore(MipStoreSnapshot {
+            states: BTreeMap::from([(
+                mip_name,
+                MipState::Active {
+                    activation: MassaTime::from_millis(0),
+                },
+            )]),
+            ..Default::default()
+        });
+        sample_state.write().mip_store = mip_store;
+
+        // start the execution worker
+        let storage = Storage::create_root();
+        let (mut manager, controller) = start_execution_worker(
+            exec_cfg.clone(),
+            sample_state.clone(),
+            sample_state.read().pos_state.selector.clone(),
+        );
+        // initialize the execution system with genesis blocks
+        init_execution_worker(&exec_cfg, &storage, controller.clone());
+        // let the candidate execution go through several cycles
+        std::thread::sleep(Duration::from_millis(1000));
+
+        let rents = controller.get_storage_rent(&[paying, abandoned]);
+        assert_eq!(rents[0].storage_bytes, 3);
+        assert_eq!(rents[0].rent_per_cycle, Amount::from_str("3").unwrap());
+        assert_eq!(rents[0].rent_due_since, None);
+        let balances = controller.get_final_and_candidate_balance(&[paying, abandoned]);
+        let paid = Amount::from_str("1000")
+            .unwrap()
+            .saturating_sub(balances[0].1.unwrap());
+        assert!(!paid.is_zero(), "no rent was charged");
+        assert_eq!(
+            paid.to_raw() % rents[0].rent_per_cycle.to_raw(),
+            0,
+            "the rent is charged by whole cycles"
+        );
+
+        // the storage of the address that could not pay is reclaimed at the start of cycle 2, without taking its coins
+        assert_eq!(rents[1].storage_bytes, 0);
+        assert_eq!(rents[1].rent_due_since, None);
+        assert_eq!(balances[1].1, Some(Amount::from_str("4").unwrap()));
+        let events = controller.get_filtered_sc_output_event(EventFilter::default());
+        let reclamations: Vec<_> = events
+            .iter()
+            .filter(|event| event.data.starts_with("storage of"))
+            .collect();
+        assert_eq!(reclamations.len(), 1);
+        assert_eq!(
+            reclamations[0].data,
+            format!(
+                "storage of {} reclaimed: rent unpaid since cycle 1",
+                abandoned
+            )
+        );
+        assert_eq!(reclamations[0].context.slot, Slot::new(4, 0));
+
         // stop the execution controller
         manager.stop();
     }
@@ -1779,6 +3008,8 @@ mod tests {
             Operation {
                 fee: Amount::from_mantissa_scale(10, 0),
                 expire_period: 10,
+                sequence: None,
+                sponsor: None,
                 op,
             },
             OperationSerializer::new(),
@@ -1809,6 +3040,8 @@ mod tests {
             Operation {
                 fee,
                 expire_period: 10,
+                sequence: None,
+                sponsor: None,
                 op,
             },
             OperationSerializer::new(),
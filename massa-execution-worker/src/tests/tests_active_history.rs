@@ -52,6 +52,7 @@ mod tests {
                 executed_ops_changes: Default::default(),
             },
             events: Default::default(),
+            staking_rewards: Default::default(),
         };
 
         let active_history = ActiveHistory {
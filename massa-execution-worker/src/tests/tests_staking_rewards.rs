@@ -0,0 +1,70 @@
+#[cfg(test)]
+mod tests {
+    use crate::staking_rewards::StakingRewardsTracker;
+    use massa_hash::Hash;
+    use massa_models::address::{Address, StakingRewards, UserAddress};
+    use massa_models::amount::Amount;
+    use massa_models::prehash::PreHashMap;
+
+    fn block_rewards(address: Address, raw_amount: u64) -> PreHashMap<Address, StakingRewards> {
+        let mut rewards = PreHashMap::default();
+        rewards.insert(
+            address,
+            StakingRewards {
+                block_count: 1,
+                block_rewards: Amount::from_raw(raw_amount),
+                ..Default::default()
+            },
+        );
+        rewards
+    }
+
+    #[test]
+    fn test_staking_rewards_tracker() {
+        let addr1 = Address::User(UserAddress(Hash::compute_from("AU1".as_bytes())));
+        let addr2 = Address::User(UserAddress(Hash::compute_from("AU2".as_bytes())));
+        let mut tracker = StakingRewardsTracker::new(2, 10);
+
+        // rewards of the same cycle are accumulated
+        tracker.register(1, &block_rewards(addr1, 100));
+        tracker.register(1, &block_rewards(addr1, 50));
+        tracker.register(2, &block_rewards(addr2, 10));
+        let addr1_rewards = tracker.get_address_rewards(&addr1);
+        assert_eq!(addr1_rewards.len(), 1);
+        assert_eq!(addr1_rewards[&1].block_count, 2);
+        assert_eq!(addr1_rewards[&1].total(), Amount::from_raw(150));
+
+        // the oldest cycle is pruned
+        tracker.register(3, &block_rewards(addr2, 20));
+        assert!(tracker.get_address_rewards(&addr1).is_empty());
+        let addr2_rewards = tracker.get_address_rewards(&addr2);
+        assert_eq!(
+            addr2_rewards.keys().copied().collect::<Vec<_>>(),
+            vec![2, 3]
+        );
+    }
+
+    #[test]
+    fn test_staking_rewards_tracker_address_bound() {
+        let addr1 = Address::User(UserAddress(Hash::compute_from("AU1".as_bytes())));
+        let addr2 = Address::User(UserAddress(Hash::compute_from("AU2".as_bytes())));
+        let mut tracker = StakingRewardsTracker::new(2, 1);
+
+        // a full cycle keeps accumulating the rewards of its tracked addresses only
+        tracker.register(1, &block_rewards(addr1, 100));
+        tracker.register(1, &block_rewards(addr2, 10));
+        tracker.register(1, &block_rewards(addr1, 50));
+        assert_eq!(
+            tracker.get_address_rewards(&addr1)[&1].total(),
+            Amount::from_raw(150)
+        );
+        assert!(tracker.get_address_rewards(&addr2).is_empty());
+
+        // the bound applies per cycle
+        tracker.register(2, &block_rewards(addr2, 20));
+        assert_eq!(
+            tracker.get_address_rewards(&addr2)[&2].total(),
+            Amount::from_raw(20)
+        );
+    }
+}
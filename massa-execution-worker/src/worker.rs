@@ -9,6 +9,7 @@ use crate::controller::{ExecutionControllerImpl, ExecutionInputData, ExecutionMa
 use crate::execution::ExecutionState;
 use crate::request_queue::RequestQueue;
 use crate::slot_sequencer::SlotSequencer;
+use crate::state_sink::start_state_sink;
 use massa_execution_exports::{
     ExecutionConfig, ExecutionController, ExecutionError, ExecutionManager,
     ReadOnlyExecutionOutput, ReadOnlyExecutionRequest,
@@ -244,6 +245,16 @@ pub fn start_execution_worker(
         final_state,
     )));
 
+    // start the state sink receiving the finalized state changes
+    let state_sink_handle = match start_state_sink(&config) {
+        Ok(Some((state_sink, handle))) => {
+            execution_state.write().state_sink = Some(state_sink);
+            Some(handle)
+        }
+        Ok(None) => None,
+        Err(err) => panic!("failed to start the execution state sink: {}", err),
+    };
+
     // define the input data interface
     let input_data = Arc::new((
         Condvar::new(),
@@ -259,15 +270,19 @@ pub fn start_execution_worker(
     // launch the execution thread
     let input_data_clone = input_data.clone();
     let thread_builder = thread::Builder::new().name("execution".into());
+    let execution_state_clone = execution_state.clone();
     let thread_handle = thread_builder
         .spawn(move || {
-            ExecutionThread::new(config, input_data_clone, execution_state, selector).main_loop();
+            ExecutionThread::new(config, input_data_clone, execution_state_clone, selector)
+                .main_loop();
         })
         .expect("failed to spawn thread : execution");
     // create a manager
     let manager = ExecutionManagerImpl {
         input_data,
         thread_handle: Some(thread_handle),
+        execution_state,
+        state_sink_handle,
     };
 
     // return the execution manager and controller pair
@@ -3,6 +3,7 @@
 //! This file defines the factory settings
 
 use massa_time::MassaTime;
+use std::path::PathBuf;
 
 /// Structure defining the settings of the factory
 #[derive(Debug, Clone)]
@@ -27,4 +28,31 @@ pub struct FactoryConfig {
 
     /// maximum number of operation ids in block
     pub max_operations_per_block: u32,
+
+    /// delay after a slot before checking that the block of a local staking address made it to the blockclique
+    pub production_check_delay: MassaTime,
+
+    /// production delay after the start of a slot above which a miss is attributed to late production
+    pub late_production_threshold: MassaTime,
+
+    /// number of produced blocks whose stage timings are kept for the API
+    pub production_timings_history: usize,
+
+    /// optional `http://` webhook receiving a JSON alert for each missed block production
+    pub miss_alert_webhook: Option<String>,
+
+    /// optional JSON file listing the addresses whose operations are never included in the blocks produced locally
+    pub operation_denylist_path: Option<PathBuf>,
+
+    /// optional file recording the blocks and endorsements signed by the node, refusing to sign twice for a same slot
+    pub production_guard_path: Option<PathBuf>,
+
+    /// whether the blocks are produced on the network clock, estimated from the clock offsets of the connected nodes
+    pub compensate_clock_offset: bool,
+
+    /// maximal shift of the production timing towards the network clock
+    pub max_clock_offset_compensation: MassaTime,
+
+    /// lag in slots of the final slot of the node behind the one of the network above which no block is produced, 0 to disable
+    pub max_sync_lag: u64,
 }
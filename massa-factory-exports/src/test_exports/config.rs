@@ -14,6 +14,15 @@ impl Default for FactoryConfig {
             max_block_size: MAX_BLOCK_SIZE as u64,
             max_block_gas: MAX_GAS_PER_BLOCK,
             max_operations_per_block: MAX_OPERATIONS_PER_BLOCK,
+            production_check_delay: MassaTime::from_millis(60000),
+            late_production_threshold: MassaTime::from_millis(1000),
+            production_timings_history: 10,
+            miss_alert_webhook: None,
+            operation_denylist_path: None,
+            production_guard_path: None,
+            compensate_clock_offset: false,
+            max_clock_offset_compensation: MassaTime::from_millis(1000),
+            max_sync_lag: 0,
         }
     }
 }
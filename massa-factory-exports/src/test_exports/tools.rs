@@ -14,7 +14,9 @@ pub fn create_empty_block(keypair: &KeyPair, slot: &Slot) -> SecureShareBlock {
             slot: *slot,
             parents: Vec::new(),
             operation_merkle_root: Hash::compute_from(&Vec::new()),
+            announced_version: 0,
             endorsements: Vec::new(),
+            aggregated_endorsements: None,
         },
         BlockHeaderSerializer::new(),
         keypair,
@@ -1,9 +1,15 @@
 use massa_consensus_exports::ConsensusController;
 use massa_models::block::Block;
+use massa_models::slot::SyncLag;
+use massa_models::stats::{BlockProductionStats, DiskStatus};
 use massa_pool_exports::PoolController;
 use massa_pos_exports::SelectorController;
 use massa_protocol_exports::ProtocolCommandSender;
 use massa_storage::Storage;
+use massa_time::ClockOffset;
+use massa_versioning::MipStore;
+use parking_lot::RwLock;
+use std::sync::Arc;
 
 /// History of block production from latest to oldest
 /// todo: redesign type (maybe add slots, draws...)
@@ -22,4 +28,14 @@ pub struct FactoryChannels {
     pub protocol: ProtocolCommandSender,
     /// storage instance
     pub storage: Storage,
+    /// disk status of the node, production is paused while it is in read-only mode
+    pub disk_status: Arc<RwLock<DiskStatus>>,
+    /// timings of the latest blocks produced by the node, updated by the block factory
+    pub production_stats: Arc<RwLock<BlockProductionStats>>,
+    /// versioning state, to get the network version announced by the produced blocks
+    pub mip_store: MipStore,
+    /// estimate of the network clock offset, updated by the network
+    pub clock_offset: ClockOffset,
+    /// estimate of the lag of the final slot of the node behind the network, updated by the network
+    pub sync_lag: SyncLag,
 }
@@ -1,20 +1,28 @@
 //! Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use crate::denylist::OperationDenylist;
+use crate::production_guard::ProductionGuard;
+use crate::production_monitor::{ProducedBlock, ProducedBlocks};
 use massa_factory_exports::{FactoryChannels, FactoryConfig};
 use massa_hash::Hash;
 use massa_models::{
+    aggregate_signature::AggregateSignature,
     block::{Block, BlockSerializer},
     block_header::{BlockHeader, BlockHeaderSerializer, SecuredHeader},
     block_id::BlockId,
+    config::{AGGREGATED_ENDORSEMENTS_VERSION, ENDORSEMENT_COUNT},
     endorsement::SecureShareEndorsement,
+    format_version::FormatVersionSchedule,
     prehash::PreHashSet,
     secure_share::SecureShareContent,
     slot::Slot,
+    stats::BlockProductionTimings,
     timeslots::{get_block_slot_timestamp, get_closest_slot_to_timestamp},
 };
 use massa_time::MassaTime;
+use massa_versioning::MipComponent;
 use massa_wallet::Wallet;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use std::{
     sync::{mpsc, Arc},
     thread,
@@ -22,11 +30,35 @@ use std::{
 };
 use tracing::{debug, info, warn};
 
+/// Measures the successive stages of a block production
+struct StageTimer {
+    last: Instant,
+}
+
+impl StageTimer {
+    fn start() -> Self {
+        StageTimer {
+            last: Instant::now(),
+        }
+    }
+
+    /// Time elapsed since the end of the previous stage, in microseconds
+    fn lap(&mut self) -> u64 {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last);
+        self.last = now;
+        u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX)
+    }
+}
+
 /// Structure gathering all elements needed by the factory thread
 pub(crate) struct BlockFactoryWorker {
     cfg: FactoryConfig,
     wallet: Arc<RwLock<Wallet>>,
     channels: FactoryChannels,
+    produced_blocks: ProducedBlocks,
+    production_guard: Arc<Mutex<ProductionGuard>>,
+    denylist: OperationDenylist,
     factory_receiver: mpsc::Receiver<()>,
 }
 
@@ -37,15 +69,21 @@ impl BlockFactoryWorker {
         cfg: FactoryConfig,
         wallet: Arc<RwLock<Wallet>>,
         channels: FactoryChannels,
+        produced_blocks: ProducedBlocks,
+        production_guard: Arc<Mutex<ProductionGuard>>,
         factory_receiver: mpsc::Receiver<()>,
     ) -> thread::JoinHandle<()> {
         thread::Builder::new()
             .name("block-factory".into())
             .spawn(|| {
+                let denylist = OperationDenylist::new(cfg.operation_denylist_path.clone());
                 let mut this = Self {
                     cfg,
                     wallet,
                     channels,
+                    produced_blocks,
+                    production_guard,
+                    denylist,
                     factory_receiver,
                 };
                 this.run();
@@ -57,8 +95,19 @@ impl BlockFactoryWorker {
     /// Slots can be skipped if we waited too much in-between.
     /// Extra safety against double-production caused by clock adjustments (this is the role of the `previous_slot` parameter).
     fn get_next_slot(&self, previous_slot: Option<Slot>) -> (Slot, Instant) {
-        // get current absolute time
-        let now = MassaTime::now().expect("could not get current time");
+        // shift towards the network clock, in milliseconds
+        let clock_shift = if self.cfg.compensate_clock_offset {
+            self.channels
+                .clock_offset
+                .get_bounded(self.cfg.max_clock_offset_compensation)
+        } else {
+            0
+        };
+
+        // get current absolute time on the network clock
+        let now = MassaTime::now()
+            .expect("could not get current time")
+            .saturating_shift(clock_shift);
 
         // if it's the first computed slot, add a time shift to prevent double-production on node restart with clock skew
         let base_time = if previous_slot.is_none() {
@@ -97,6 +146,7 @@ impl BlockFactoryWorker {
             next_slot,
         )
         .expect("could not get block slot timestamp")
+        .saturating_shift(-clock_shift)
         .estimate_instant()
         .expect("could not estimate block slot instant");
 
@@ -120,6 +170,30 @@ impl BlockFactoryWorker {
 
     /// Process a slot: produce a block at that slot if one of the managed keys is drawn.
     fn process_slot(&mut self, slot: Slot) {
+        let mut timer = StageTimer::start();
+        let slot_timestamp = get_block_slot_timestamp(
+            self.cfg.thread_count,
+            self.cfg.t0,
+            self.cfg.genesis_timestamp,
+            slot,
+        )
+        .expect("could not get block slot timestamp");
+        let delay_since_slot_start = || {
+            MassaTime::now()
+                .expect("could not get current time")
+                .saturating_sub(slot_timestamp)
+        };
+        let wakeup_delay = delay_since_slot_start();
+
+        // do not produce while the node is in read-only mode
+        if self.channels.disk_status.read().read_only {
+            debug!(
+                "block factory skipping slot {}: the node is in read-only mode",
+                slot
+            );
+            return;
+        }
+
         // get block producer address for that slot
         let block_producer_addr = match self.channels.selector.get_producer(slot) {
             Ok(addr) => addr,
@@ -137,10 +211,10 @@ impl BlockFactoryWorker {
             slot, block_producer_addr
         );
 
-        // check if the block producer address is handled by the wallet
+        // check if the block producer address is handled by the wallet and staking
         let block_producer_keypair_ref = self.wallet.read();
         let block_producer_keypair = if let Some(kp) =
-            block_producer_keypair_ref.find_associated_keypair(&block_producer_addr)
+            block_producer_keypair_ref.find_staking_keypair(&block_producer_addr)
         {
             // the selected block producer is managed locally => continue to attempt block production
             kp
@@ -148,6 +222,19 @@ impl BlockFactoryWorker {
             // the selected block producer is not managed locally => quit
             return;
         };
+
+        // do not produce on a stale state while the node lags behind the network
+        if let Some(lag) = self.channels.sync_lag.get() {
+            if self.cfg.max_sync_lag > 0 && lag > self.cfg.max_sync_lag {
+                warn!(
+                    "block factory delaying the production: the final slot of the node lags {} slots behind the network, skipping slot {}",
+                    lag, slot
+                );
+                return;
+            }
+        }
+        let draw_check_us = timer.lap();
+
         // get best parents and their periods
         let parents: Vec<(BlockId, u64)> = self.channels.consensus.get_best_parents(); // Vec<(parent_id, parent_period)>
                                                                                        // generate the local storage object
@@ -169,32 +256,71 @@ impl BlockFactoryWorker {
 
         // get the parent in the same thread, with its period
         // will not panic because the thread is validated before the call
-        let (same_thread_parent_id, _) = parents[slot.thread as usize];
+        let (same_thread_parent_id, same_thread_parent_period) = parents[slot.thread as usize];
 
-        // gather endorsements
-        let (endorsements_ids, endo_storage) = self
-            .channels
-            .pool
-            .get_block_endorsements(&same_thread_parent_id, &slot);
+        // if the best parents are already at or after this slot, the local clock is probably late
+        let network_ahead = same_thread_parent_period >= slot.period
+            || parents.iter().any(|(_, period)| *period > slot.period);
+
+        let parents_us = timer.lap();
 
-        //TODO: Do we want ot populate only with endorsement id in the future ?
-        let endorsements: Vec<SecureShareEndorsement> = {
-            let endo_read = endo_storage.read_endorsements();
-            endorsements_ids
+        // gather endorsements, aggregated from the consensus version carrying them as one BLS signature
+        let aggregating = self
+            .channels
+            .mip_store
+            .component_version_at(MipComponent::Consensus, slot_timestamp)
+            >= AGGREGATED_ENDORSEMENTS_VERSION;
+        let (endorsements, aggregated_endorsements) = if aggregating {
+            let signatures: Vec<_> = self
+                .channels
+                .pool
+                .get_block_endorsement_signatures(&same_thread_parent_id, &slot)
                 .into_iter()
-                .flatten()
-                .map(|endo_id| {
-                    endo_read
-                        .get(&endo_id)
-                        .expect("could not retrieve endorsement")
-                        .clone()
-                })
-                .collect()
+                .map(|signature| (signature.index as usize, signature.signature))
+                .collect();
+            let aggregate = if signatures.is_empty() {
+                None
+            } else {
+                // the pool only returns checked signatures at distinct indexes
+                Some(
+                    AggregateSignature::aggregate(ENDORSEMENT_COUNT as usize, &signatures)
+                        .expect("could not aggregate the endorsement signatures"),
+                )
+            };
+            (Vec::new(), aggregate)
+        } else {
+            let (endorsements_ids, endo_storage) = self
+                .channels
+                .pool
+                .get_block_endorsements(&same_thread_parent_id, &slot);
+
+            //TODO: Do we want ot populate only with endorsement id in the future ?
+            let endorsements: Vec<SecureShareEndorsement> = {
+                let endo_read = endo_storage.read_endorsements();
+                endorsements_ids
+                    .into_iter()
+                    .flatten()
+                    .map(|endo_id| {
+                        endo_read
+                            .get(&endo_id)
+                            .expect("could not retrieve endorsement")
+                            .clone()
+                    })
+                    .collect()
+            };
+            block_storage.extend(endo_storage);
+            (endorsements, None)
         };
-        block_storage.extend(endo_storage);
+        let endorsements_us = timer.lap();
 
         // gather operations and compute global operations hash
-        let (op_ids, op_storage) = self.channels.pool.get_block_operations(&slot);
+        let (op_ids, mut op_storage) = self.channels.pool.get_block_operations(&slot);
+
+        // leave out the operations involving denylisted addresses
+        self.denylist.refresh();
+        let op_ids = self
+            .denylist
+            .filter_operations(slot, op_ids, &mut op_storage);
 
         if op_ids.len() > self.cfg.max_operations_per_block as usize {
             warn!("Too many operations returned");
@@ -208,6 +334,23 @@ impl BlockFactoryWorker {
                 .flat_map(|op_id| *op_id.to_bytes())
                 .collect::<Vec<u8>>(),
         );
+        let operations_us = timer.lap();
+
+        // never sign a second block for this slot, even produced by another process sharing the guard
+        if !self
+            .production_guard
+            .lock()
+            .record(slot, block_producer_addr, None)
+        {
+            return;
+        }
+
+        // the headers of the original wire format cannot announce a network version
+        let announced_version = if FormatVersionSchedule::block_header().version_at(&slot) > 0 {
+            self.channels.mip_store.version_to_announce()
+        } else {
+            0
+        };
 
         // create header
         let header: SecuredHeader = BlockHeader::new_verifiable::<BlockHeaderSerializer, BlockId>(
@@ -215,7 +358,9 @@ impl BlockFactoryWorker {
                 slot,
                 parents: parents.into_iter().map(|(id, _period)| id).collect(),
                 operation_merkle_root: global_operations_hash,
+                announced_version,
                 endorsements,
+                aggregated_endorsements,
             },
             BlockHeaderSerializer::new(), // TODO reuse self.block_header_serializer
             block_producer_keypair,
@@ -235,8 +380,8 @@ impl BlockFactoryWorker {
         )
         .expect("error while producing block");
         let block_id = block.id;
-        // store block in storage
-        block_storage.store_block(block);
+        let signing_us = timer.lap();
+        let production_delay = delay_since_slot_start();
 
         // log block creation
         info!(
@@ -244,10 +389,45 @@ impl BlockFactoryWorker {
             block_id, slot, block_producer_addr
         );
 
-        // send full block to consensus
+        // store block in storage and send it to consensus, which starts its propagation
+        block_storage.store_block(block);
         self.channels
             .consensus
             .register_block(block_id, slot, block_storage, true);
+        let propagation_start_us = timer.lap();
+
+        let timings = BlockProductionTimings {
+            slot,
+            block_id,
+            wakeup_delay,
+            draw_check_us,
+            parents_us,
+            endorsements_us,
+            operations_us,
+            signing_us,
+            propagation_start_us,
+            total_delay: delay_since_slot_start(),
+        };
+        if timings.total_delay > self.cfg.late_production_threshold {
+            warn!("late block production: {}", timings);
+        } else {
+            debug!("block production: {}", timings);
+        }
+        self.channels
+            .production_stats
+            .write()
+            .record(timings, self.cfg.production_timings_history);
+
+        // record the production for the production monitor
+        self.produced_blocks.write().insert(
+            slot,
+            ProducedBlock {
+                block_id,
+                production_delay,
+                network_ahead,
+                timings,
+            },
+        );
     }
 
     /// main run loop of the block creator thread
@@ -0,0 +1,147 @@
+//! Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Operator-configured address denylist, consulted when selecting the operations of the blocks produced locally.
+//!
+//! Operations created by, or sending coins or calls to, a denied address are left out of the local blocks.
+//! This is a local production policy only: blocks of other producers containing such operations stay valid.
+//! The denylist file is a JSON array of addresses, reloaded when it is modified.
+
+use massa_models::{
+    address::Address,
+    operation::{OperationId, SecureShareOperation},
+    prehash::PreHashSet,
+    slot::Slot,
+};
+use massa_storage::Storage;
+use std::{
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+use tracing::{info, warn};
+
+/// Denylist of the block factory
+pub(crate) struct OperationDenylist {
+    /// path of the denylist file, `None` if the denylist is disabled
+    path: Option<PathBuf>,
+    /// modification time of the file when it was last loaded
+    modified: Option<SystemTime>,
+    /// denied addresses
+    addresses: PreHashSet<Address>,
+}
+
+impl OperationDenylist {
+    /// Creates the denylist, loading the file at `path` if any
+    pub fn new(path: Option<PathBuf>) -> Self {
+        let mut denylist = OperationDenylist {
+            path,
+            modified: None,
+            addresses: PreHashSet::default(),
+        };
+        denylist.refresh();
+        denylist
+    }
+
+    /// Creates a denylist from a set of addresses, without backing file
+    #[cfg(test)]
+    pub fn from_addresses(addresses: PreHashSet<Address>) -> Self {
+        OperationDenylist {
+            path: None,
+            modified: None,
+            addresses,
+        }
+    }
+
+    /// Reloads the denylist file if it was modified since it was last loaded.
+    /// On error, the previously loaded addresses are kept.
+    pub fn refresh(&mut self) {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return,
+        };
+        let modified = match std::fs::metadata(path).and_then(|metadata| metadata.modified()) {
+            Ok(modified) => modified,
+            Err(err) => {
+                warn!(
+                    "could not read the block production denylist {}: {}",
+                    path.display(),
+                    err
+                );
+                return;
+            }
+        };
+        if self.modified == Some(modified) {
+            return;
+        }
+        match load_addresses(path) {
+            Ok(addresses) => {
+                info!(
+                    "block production denylist loaded from {}: {} addresses",
+                    path.display(),
+                    addresses.len()
+                );
+                self.addresses = addresses;
+                self.modified = Some(modified);
+            }
+            Err(err) => warn!(
+                "could not load the block production denylist {}, keeping the previous one: {}",
+                path.display(),
+                err
+            ),
+        }
+    }
+
+    /// Returns the denied address involved in `operation`, if any
+    pub fn denied_address(&self, operation: &SecureShareOperation) -> Option<Address> {
+        if self.addresses.is_empty() {
+            return None;
+        }
+        operation
+            .get_ledger_involved_addresses()
+            .into_iter()
+            .find(|address| self.addresses.contains(address))
+    }
+
+    /// Removes the operations involving a denied address from the operations selected for the block at `slot`,
+    /// logging each skipped operation.
+    pub fn filter_operations(
+        &self,
+        slot: Slot,
+        op_ids: Vec<OperationId>,
+        op_storage: &mut Storage,
+    ) -> Vec<OperationId> {
+        if self.addresses.is_empty() {
+            return op_ids;
+        }
+        let mut skipped = PreHashSet::default();
+        let kept = {
+            let ops = op_storage.read_operations();
+            op_ids
+                .into_iter()
+                .filter(|op_id| {
+                    let denied = ops
+                        .get(op_id)
+                        .and_then(|operation| self.denied_address(operation));
+                    match denied {
+                        Some(address) => {
+                            info!(
+                                "block factory skipping operation {} at slot {}: address {} is denylisted",
+                                op_id, slot, address
+                            );
+                            skipped.insert(*op_id);
+                            false
+                        }
+                        None => true,
+                    }
+                })
+                .collect()
+        };
+        op_storage.drop_operation_refs(&skipped);
+        kept
+    }
+}
+
+fn load_addresses(path: &Path) -> Result<PreHashSet<Address>, String> {
+    let data = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+    let addresses: Vec<Address> = serde_json::from_str(&data).map_err(|err| err.to_string())?;
+    Ok(addresses.into_iter().collect())
+}
@@ -1,17 +1,22 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use crate::production_guard::ProductionGuard;
 use massa_factory_exports::{FactoryChannels, FactoryConfig};
 use massa_models::{
+    address::Address,
     block_id::BlockId,
+    config::AGGREGATED_ENDORSEMENTS_VERSION,
     endorsement::{Endorsement, EndorsementSerializer, SecureShareEndorsement},
+    endorsement_signature::EndorsementSignature,
     secure_share::SecureShareContent,
     slot::Slot,
     timeslots::{get_block_slot_timestamp, get_closest_slot_to_timestamp},
 };
-use massa_signature::KeyPair;
+use massa_signature::{BlsKeyPair, KeyPair};
 use massa_time::MassaTime;
+use massa_versioning::MipComponent;
 use massa_wallet::Wallet;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use std::{
     sync::{mpsc, Arc},
     thread,
@@ -24,6 +29,7 @@ pub(crate) struct EndorsementFactoryWorker {
     cfg: FactoryConfig,
     wallet: Arc<RwLock<Wallet>>,
     channels: FactoryChannels,
+    production_guard: Arc<Mutex<ProductionGuard>>,
     factory_receiver: mpsc::Receiver<()>,
     half_t0: MassaTime,
     endorsement_serializer: EndorsementSerializer,
@@ -36,6 +42,7 @@ impl EndorsementFactoryWorker {
         cfg: FactoryConfig,
         wallet: Arc<RwLock<Wallet>>,
         channels: FactoryChannels,
+        production_guard: Arc<Mutex<ProductionGuard>>,
         factory_receiver: mpsc::Receiver<()>,
     ) -> thread::JoinHandle<()> {
         thread::Builder::new()
@@ -49,6 +56,7 @@ impl EndorsementFactoryWorker {
                     cfg,
                     wallet,
                     channels,
+                    production_guard,
                     factory_receiver,
                     endorsement_serializer: EndorsementSerializer::new(),
                 };
@@ -125,6 +133,15 @@ impl EndorsementFactoryWorker {
 
     /// Process a slot: produce an endorsement at that slot if one of the managed keys is drawn.
     fn process_slot(&mut self, slot: Slot) {
+        // do not produce while the node is in read-only mode
+        if self.channels.disk_status.read().read_only {
+            debug!(
+                "endorsement factory skipping slot {}: the node is in read-only mode",
+                slot
+            );
+            return;
+        }
+
         // get endorsement producer addresses for that slot
         let producer_addrs = match self.channels.selector.get_selection(slot) {
             Ok(sel) => sel.endorsements,
@@ -142,15 +159,15 @@ impl EndorsementFactoryWorker {
         {
             let wallet = self.wallet.read();
             for (index, producer_addr) in producer_addrs.into_iter().enumerate() {
-                // check if the block producer address is handled by the wallet
-                let producer_keypair =
-                    if let Some(kp) = wallet.find_associated_keypair(&producer_addr) {
-                        // the selected block producer is managed locally => continue to attempt endorsement production
-                        kp.clone()
-                    } else {
-                        // the selected block producer is not managed locally => continue
-                        continue;
-                    };
+                // check if the block producer address is handled by the wallet and staking
+                let producer_keypair = if let Some(kp) = wallet.find_staking_keypair(&producer_addr)
+                {
+                    // the selected block producer is managed locally => continue to attempt endorsement production
+                    kp.clone()
+                } else {
+                    // the selected block producer is not managed locally => continue
+                    continue;
+                };
                 producers_indices.push((producer_keypair, index));
             }
         }
@@ -166,10 +183,34 @@ impl EndorsementFactoryWorker {
             .consensus
             .get_latest_blockclique_block_at_slot(slot);
 
+        // from the consensus version aggregating the endorsements on, sign with the BLS key derived from the staking key
+        let slot_timestamp = get_block_slot_timestamp(
+            self.cfg.thread_count,
+            self.cfg.t0,
+            self.cfg.genesis_timestamp,
+            slot,
+        )
+        .expect("could not get block slot timestamp");
+        if self
+            .channels
+            .mip_store
+            .component_version_at(MipComponent::Consensus, slot_timestamp)
+            >= AGGREGATED_ENDORSEMENTS_VERSION
+        {
+            self.produce_endorsement_signatures(slot, endorsed_block, producers_indices);
+            return;
+        }
+
         // produce endorsements
         let mut endorsements: Vec<SecureShareEndorsement> =
             Vec::with_capacity(producers_indices.len());
+        let mut production_guard = self.production_guard.lock();
         for (keypair, index) in producers_indices {
+            // never sign a second endorsement for this slot and index
+            let address = Address::from_public_key(&keypair.get_public_key());
+            if !production_guard.record(slot, address, Some(index as u32)) {
+                continue;
+            }
             let endorsement = Endorsement::new_verifiable(
                 Endorsement {
                     slot,
@@ -189,6 +230,10 @@ impl EndorsementFactoryWorker {
 
             endorsements.push(endorsement);
         }
+        drop(production_guard);
+        if endorsements.is_empty() {
+            return;
+        }
 
         // store endorsements
         let mut endo_storage = self.channels.storage.clone_without_refs();
@@ -202,6 +247,52 @@ impl EndorsementFactoryWorker {
         }
     }
 
+    /// Produces the BLS signatures of the endorsers managed locally, to be aggregated by the block producer.
+    /// The endorsers that did not register their key yet produce signatures that the pool does not keep.
+    fn produce_endorsement_signatures(
+        &mut self,
+        slot: Slot,
+        endorsed_block: BlockId,
+        producers_indices: Vec<(KeyPair, usize)>,
+    ) {
+        let mut signatures: Vec<EndorsementSignature> = Vec::with_capacity(producers_indices.len());
+        let mut production_guard = self.production_guard.lock();
+        for (keypair, index) in producers_indices {
+            // never sign a second endorsement for this slot and index
+            let address = Address::from_public_key(&keypair.get_public_key());
+            if !production_guard.record(slot, address, Some(index as u32)) {
+                continue;
+            }
+            signatures.push(EndorsementSignature::new(
+                slot,
+                index as u32,
+                endorsed_block,
+                &BlsKeyPair::derive_from(&keypair),
+            ));
+            debug!(
+                "endorsement signature created at slot {} by address {}",
+                slot, address
+            );
+        }
+        drop(production_guard);
+
+        // send the signatures to pool, which checks them, then propagate the ones it kept
+        let signatures = self.channels.pool.add_endorsement_signatures(signatures);
+        if signatures.is_empty() {
+            return;
+        }
+        if let Err(err) = self
+            .channels
+            .protocol
+            .propagate_endorsement_signatures(signatures)
+        {
+            warn!(
+                "could not propagate endorsement signatures to protocol: {}",
+                err
+            );
+        }
+    }
+
     /// main run loop of the endorsement creator thread
     fn run(&mut self) {
         let mut prev_slot = None;
@@ -3,8 +3,11 @@
 #![feature(deadline_api)]
 
 mod block_factory;
+mod denylist;
 mod endorsement_factory;
 mod manager;
+mod production_guard;
+mod production_monitor;
 mod run;
 
 pub use run::start_factory;
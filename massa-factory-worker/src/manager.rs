@@ -16,6 +16,9 @@ pub struct FactoryManagerImpl {
 
     /// endorsement worker message sender and join handle
     pub(crate) endorsement_worker: Option<(mpsc::Sender<()>, JoinHandle<()>)>,
+
+    /// production monitor worker message sender and join handle
+    pub(crate) monitor_worker: Option<(mpsc::Sender<()>, JoinHandle<()>)>,
 }
 
 impl FactoryManager for FactoryManagerImpl {
@@ -34,6 +37,12 @@ impl FactoryManager for FactoryManagerImpl {
                 warn!("endorsement factory worker panicked: {:?}", err);
             }
         }
+        if let Some((chan_tx, join_handle)) = self.monitor_worker.take() {
+            std::mem::drop(chan_tx);
+            if let Err(err) = join_handle.join() {
+                warn!("production monitor worker panicked: {:?}", err);
+            }
+        }
         info!("factory stopped");
     }
 }
@@ -0,0 +1,192 @@
+//! Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Local protection against double production.
+//!
+//! Before signing a block or an endorsement, the factory records its slot, address and endorsement index
+//! in the guard file, one JSON line per production, and refuses to sign if they are already recorded:
+//! a staking key never signs two conflicting blocks or endorsements, even after a restart.
+//! The file is locked while it is checked and appended to, and the records appended by other processes are read
+//! before each check, so that nodes sharing the file (ex: the same key mistakenly loaded on two nodes) exclude each other.
+
+use fs2::FileExt;
+use massa_models::{address::Address, slot::Slot};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    fs::{File, OpenOptions},
+    io::{Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+use tracing::warn;
+
+/// Number of periods before the latest recorded production whose records are kept when the guard is opened.
+/// Older slots cannot be produced anymore.
+const RETENTION_PERIODS: u64 = 10_000;
+
+/// Production recorded in the guard file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+struct ProductionRecord {
+    slot: Slot,
+    address: Address,
+    /// index of the endorsement, `None` for a block
+    endorsement_index: Option<u32>,
+}
+
+/// Productions recorded in a guard file
+struct GuardFile {
+    file: File,
+    /// length of the file already read
+    read_offset: u64,
+    /// the file ends with a partial line, left by a crash during an append
+    torn: bool,
+    records: HashSet<ProductionRecord>,
+}
+
+/// Guard refusing the double productions of the local staking keys, disabled without a guard file
+pub(crate) struct ProductionGuard(Option<GuardFile>);
+
+impl ProductionGuard {
+    /// Opens the guard file at `path`, creating it if needed, and drops its outdated records.
+    /// Without `path`, the guard accepts every production.
+    pub fn open(path: Option<&Path>) -> std::io::Result<Self> {
+        let Some(path) = path else {
+            return Ok(ProductionGuard(None));
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(path)?;
+        let mut guard = GuardFile {
+            file,
+            read_offset: 0,
+            torn: false,
+            records: HashSet::new(),
+        };
+        guard.file.lock_exclusive()?;
+        let result = guard.read_new_records().and_then(|()| guard.prune());
+        guard.file.unlock()?;
+        result?;
+        Ok(ProductionGuard(Some(guard)))
+    }
+
+    /// Records the production of `address` at `slot`, with `endorsement_index` for an endorsement.
+    /// Returns `false` if it was already recorded, by this node or by another one sharing the file, or if it could
+    /// not be recorded: the production must not be signed in that case.
+    pub fn record(&mut self, slot: Slot, address: Address, endorsement_index: Option<u32>) -> bool {
+        let Some(guard) = self.0.as_mut() else {
+            return true;
+        };
+        let record = ProductionRecord {
+            slot,
+            address,
+            endorsement_index,
+        };
+        if let Err(err) = guard.file.lock_exclusive() {
+            warn!("could not lock the production guard file: {}", err);
+            return false;
+        }
+        let result = guard.record(record);
+        let _ = guard.file.unlock();
+        match result {
+            Ok(true) => true,
+            Ok(false) => {
+                warn!(
+                    "refusing to sign a second {} for address {} at slot {}: double production",
+                    if endorsement_index.is_some() {
+                        "endorsement"
+                    } else {
+                        "block"
+                    },
+                    address,
+                    slot
+                );
+                false
+            }
+            Err(err) => {
+                warn!("could not record the production in the guard file: {}", err);
+                false
+            }
+        }
+    }
+}
+
+impl GuardFile {
+    /// Checks and appends `record`, the file being locked
+    fn record(&mut self, record: ProductionRecord) -> std::io::Result<bool> {
+        self.read_new_records()?;
+        if self.records.contains(&record) {
+            return Ok(false);
+        }
+        let mut line = serde_json::to_string(&record)?;
+        line.push('\n');
+        if self.torn {
+            line.insert(0, '\n');
+        }
+        self.file.write_all(line.as_bytes())?;
+        self.file.sync_data()?;
+        self.read_offset += line.len() as u64;
+        self.torn = false;
+        self.records.insert(record);
+        Ok(true)
+    }
+
+    /// Reads the records appended since the last read, the file being locked
+    fn read_new_records(&mut self) -> std::io::Result<()> {
+        let len = self.file.metadata()?.len();
+        if len < self.read_offset {
+            // pruned by another process
+            self.read_offset = 0;
+            self.records.clear();
+        }
+        self.file.seek(SeekFrom::Start(self.read_offset))?;
+        let mut content = Vec::new();
+        self.file.read_to_end(&mut content)?;
+        self.read_offset += content.len() as u64;
+        if content.is_empty() {
+            return Ok(());
+        }
+        self.torn = content.last() != Some(&b'\n');
+        for line in content.split(|byte| *byte == b'\n') {
+            if line.is_empty() {
+                continue;
+            }
+            match serde_json::from_slice::<ProductionRecord>(line) {
+                Ok(record) => {
+                    self.records.insert(record);
+                }
+                // partial line left by a crash during an append
+                Err(_) => continue,
+            }
+        }
+        Ok(())
+    }
+
+    /// Rewrites the file without the outdated records, the file being locked
+    fn prune(&mut self) -> std::io::Result<()> {
+        let Some(latest_period) = self.records.iter().map(|record| record.slot.period).max() else {
+            return Ok(());
+        };
+        let min_period = latest_period.saturating_sub(RETENTION_PERIODS);
+        let count = self.records.len();
+        self.records
+            .retain(|record| record.slot.period >= min_period);
+        if self.records.len() == count && !self.torn {
+            return Ok(());
+        }
+        let mut content = String::new();
+        for record in &self.records {
+            content.push_str(&serde_json::to_string(record)?);
+            content.push('\n');
+        }
+        self.file.set_len(0)?;
+        self.file.write_all(content.as_bytes())?;
+        self.file.sync_data()?;
+        self.read_offset = content.len() as u64;
+        self.torn = false;
+        Ok(())
+    }
+}
@@ -0,0 +1,303 @@
+//! Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Compares the slots for which a local staking address was selected to produce a block
+//! with the blocks that actually made it to the blockclique, and raises alerts on misses.
+
+use massa_factory_exports::{FactoryChannels, FactoryConfig};
+use massa_models::{
+    address::Address, block_id::BlockId, slot::Slot, stats::BlockProductionTimings,
+    timeslots::get_latest_block_slot_at_timestamp,
+};
+use massa_time::MassaTime;
+use massa_wallet::Wallet;
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    io::{Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    sync::{mpsc, Arc},
+    thread,
+    time::Duration,
+};
+use tracing::{debug, warn};
+
+/// Timeout applied to each step of a webhook call
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Information recorded by the block factory about a block it produced
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ProducedBlock {
+    /// id of the produced block
+    pub block_id: BlockId,
+    /// delay between the start of the slot and the creation of the block
+    pub production_delay: MassaTime,
+    /// true if the best parents were already at or after the slot when the block was produced
+    pub network_ahead: bool,
+    /// time spent in each stage of the production
+    pub timings: BlockProductionTimings,
+}
+
+/// Blocks produced locally and not checked yet, shared between the block factory and the monitor
+pub(crate) type ProducedBlocks = Arc<RwLock<BTreeMap<Slot, ProducedBlock>>>;
+
+/// Probable cause of a missed block production
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum MissCause {
+    /// no block was produced locally for the slot
+    NotProduced,
+    /// the block was produced too long after the start of the slot
+    LateProduction,
+    /// the rest of the network was already past the slot: the local clock is probably late
+    ClockSkew,
+    /// the block was produced on time but did not make it to the blockclique
+    Propagation,
+}
+
+impl std::fmt::Display for MissCause {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MissCause::NotProduced => write!(f, "no block was produced"),
+            MissCause::LateProduction => write!(f, "late block production"),
+            MissCause::ClockSkew => write!(f, "probable clock skew"),
+            MissCause::Propagation => write!(f, "probable propagation issue"),
+        }
+    }
+}
+
+impl MissCause {
+    /// Classify a miss from what the block factory recorded at that slot (if anything)
+    pub(crate) fn classify(
+        produced: Option<&ProducedBlock>,
+        late_production_threshold: MassaTime,
+    ) -> Self {
+        match produced {
+            None => MissCause::NotProduced,
+            Some(block) if block.network_ahead => MissCause::ClockSkew,
+            Some(block) if block.production_delay > late_production_threshold => {
+                MissCause::LateProduction
+            }
+            Some(_) => MissCause::Propagation,
+        }
+    }
+}
+
+/// Alert raised when a local staking address missed a block production
+#[derive(Debug, Clone, Serialize)]
+struct MissAlert {
+    /// slot of the missed block
+    slot: Slot,
+    /// address that was selected to produce the block
+    address: Address,
+    /// block produced locally for that slot, if any
+    block_id: Option<BlockId>,
+    /// probable cause of the miss
+    cause: MissCause,
+    /// time spent in each stage of the production of the local block, if any
+    timings: Option<BlockProductionTimings>,
+}
+
+/// Structure gathering all elements needed by the production monitor thread
+pub(crate) struct ProductionMonitorWorker {
+    cfg: FactoryConfig,
+    wallet: Arc<RwLock<Wallet>>,
+    channels: FactoryChannels,
+    produced_blocks: ProducedBlocks,
+    monitor_receiver: mpsc::Receiver<()>,
+}
+
+impl ProductionMonitorWorker {
+    /// Creates the `ProductionMonitorWorker` structure to gather all data and references
+    /// needed by the production monitor thread.
+    pub(crate) fn spawn(
+        cfg: FactoryConfig,
+        wallet: Arc<RwLock<Wallet>>,
+        channels: FactoryChannels,
+        produced_blocks: ProducedBlocks,
+        monitor_receiver: mpsc::Receiver<()>,
+    ) -> thread::JoinHandle<()> {
+        thread::Builder::new()
+            .name("production-monitor".into())
+            .spawn(|| {
+                let mut this = Self {
+                    cfg,
+                    wallet,
+                    channels,
+                    produced_blocks,
+                    monitor_receiver,
+                };
+                this.run();
+            })
+            .expect("failed to spawn thread : production-monitor")
+    }
+
+    /// Get the latest slot that is old enough to be checked
+    fn get_latest_checkable_slot(&self) -> Option<Slot> {
+        let now = MassaTime::now().expect("could not get current time");
+        get_latest_block_slot_at_timestamp(
+            self.cfg.thread_count,
+            self.cfg.t0,
+            self.cfg.genesis_timestamp,
+            now.saturating_sub(self.cfg.production_check_delay),
+        )
+        .expect("could not get latest block slot at timestamp")
+    }
+
+    /// Check a slot: raise an alert if a local staking address was selected at that slot
+    /// and no block made it to the blockclique.
+    fn check_slot(&mut self, slot: Slot) {
+        let produced = self.produced_blocks.write().remove(&slot);
+
+        // get block producer address for that slot
+        let producer_addr = match self.channels.selector.get_producer(slot) {
+            Ok(addr) => addr,
+            Err(err) => {
+                debug!(
+                    "production monitor could not get selector draws for slot {}: {}",
+                    slot, err
+                );
+                return;
+            }
+        };
+
+        // only monitor the staking addresses handled by the wallet
+        if self
+            .wallet
+            .read()
+            .find_staking_keypair(&producer_addr)
+            .is_none()
+        {
+            return;
+        }
+
+        if let Some(block_id) = self.channels.consensus.get_blockclique_block_at_slot(slot) {
+            debug!(
+                "production monitor: block {} produced at slot {} by address {} is in the blockclique",
+                block_id, slot, producer_addr
+            );
+            return;
+        }
+
+        let alert = MissAlert {
+            slot,
+            address: producer_addr,
+            block_id: produced.as_ref().map(|block| block.block_id),
+            cause: MissCause::classify(produced.as_ref(), self.cfg.late_production_threshold),
+            timings: produced.as_ref().map(|block| block.timings),
+        };
+        warn!(
+            "address {} missed its block production at slot {}: {}",
+            alert.address, alert.slot, alert.cause
+        );
+        if let Some(timings) = &alert.timings {
+            warn!("missed {}", timings);
+        }
+        if let Some(url) = &self.cfg.miss_alert_webhook {
+            if let Err(err) = send_webhook(url, &alert) {
+                warn!(
+                    "could not send block production miss alert to webhook: {}",
+                    err
+                );
+            }
+        }
+    }
+
+    /// main run loop of the production monitor thread
+    fn run(&mut self) {
+        // only the slots coming after the start of the monitor are checked
+        let mut last_checked = self.get_latest_checkable_slot();
+        let slot_duration = self
+            .cfg
+            .t0
+            .checked_div_u64(self.cfg.thread_count as u64)
+            .expect("could not compute slot duration")
+            .to_duration();
+        loop {
+            match self.monitor_receiver.recv_timeout(slot_duration) {
+                // timeout => check the slots that became checkable
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
+                // message received or channel disconnected (sender dropped) => quit main loop
+                _ => break,
+            }
+
+            let target = match self.get_latest_checkable_slot() {
+                Some(slot) => slot,
+                None => continue,
+            };
+            if self.wallet.read().keys.is_empty() {
+                last_checked = Some(target);
+                self.produced_blocks.write().clear();
+                continue;
+            }
+            let mut slot = match last_checked {
+                Some(s) => s
+                    .get_next_slot(self.cfg.thread_count)
+                    .expect("could not compute next slot"),
+                None => target,
+            };
+            while slot <= target {
+                self.check_slot(slot);
+                slot = slot
+                    .get_next_slot(self.cfg.thread_count)
+                    .expect("could not compute next slot");
+            }
+            last_checked = Some(target);
+
+            // forget about blocks produced at slots that will not be checked
+            self.produced_blocks
+                .write()
+                .retain(|produced_slot, _| produced_slot > &target);
+        }
+    }
+}
+
+/// Post an alert as JSON to a `http://` webhook
+fn send_webhook(url: &str, alert: &MissAlert) -> Result<(), String> {
+    let location = url
+        .strip_prefix("http://")
+        .ok_or_else(|| format!("unsupported webhook url {}: only http:// is supported", url))?;
+    let (host, path) = match location.find('/') {
+        Some(idx) => (&location[..idx], &location[idx..]),
+        None => (location, "/"),
+    };
+    let socket_addr = if host.contains(':') {
+        host.to_socket_addrs()
+    } else {
+        (host, 80).to_socket_addrs()
+    }
+    .map_err(|err| format!("could not resolve {}: {}", host, err))?
+    .next()
+    .ok_or_else(|| format!("could not resolve {}", host))?;
+
+    let body = serde_json::to_string(alert).map_err(|err| err.to_string())?;
+    let mut stream = TcpStream::connect_timeout(&socket_addr, WEBHOOK_TIMEOUT)
+        .map_err(|err| format!("could not connect to {}: {}", host, err))?;
+    stream
+        .set_read_timeout(Some(WEBHOOK_TIMEOUT))
+        .and_then(|_| stream.set_write_timeout(Some(WEBHOOK_TIMEOUT)))
+        .map_err(|err| err.to_string())?;
+    write!(
+        stream,
+        "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        path,
+        host,
+        body.len(),
+        body
+    )
+    .map_err(|err| format!("could not send request to {}: {}", host, err))?;
+
+    // only the status line of the response is relevant
+    let mut response = [0u8; 32];
+    let read = stream
+        .read(&mut response)
+        .map_err(|err| format!("could not read response from {}: {}", host, err))?;
+    let status_line = String::from_utf8_lossy(&response[..read]);
+    match status_line.split_whitespace().nth(1) {
+        Some(code) if code.starts_with('2') => Ok(()),
+        _ => Err(format!(
+            "unexpected response from {}: {}",
+            host, status_line
+        )),
+    }
+}
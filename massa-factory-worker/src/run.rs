@@ -1,11 +1,14 @@
 //! Copyright (c) 2022 MASSA LABS <info@massa.net>
 
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use std::sync::{mpsc, Arc};
 
 use crate::{
-    block_factory::BlockFactoryWorker, endorsement_factory::EndorsementFactoryWorker,
+    block_factory::BlockFactoryWorker,
+    endorsement_factory::EndorsementFactoryWorker,
     manager::FactoryManagerImpl,
+    production_guard::ProductionGuard,
+    production_monitor::{ProducedBlocks, ProductionMonitorWorker},
 };
 use massa_factory_exports::{FactoryChannels, FactoryConfig, FactoryManager};
 use massa_wallet::Wallet;
@@ -30,22 +33,51 @@ pub fn start_factory(
     // create endorsement factory channel
     let (endorsement_worker_tx, endorsement_worker_rx) = mpsc::channel::<()>();
 
+    // create production monitor channel
+    let (monitor_worker_tx, monitor_worker_rx) = mpsc::channel::<()>();
+
+    // blocks produced locally, checked by the production monitor
+    let produced_blocks = ProducedBlocks::default();
+
+    // productions of the local staking keys, shared by the block and endorsement factories
+    let production_guard = Arc::new(Mutex::new(
+        ProductionGuard::open(cfg.production_guard_path.as_deref())
+            .expect("could not open the production guard file"),
+    ));
+
     // start block factory worker
     let block_worker_handle = BlockFactoryWorker::spawn(
         cfg.clone(),
         wallet.clone(),
         channels.clone(),
+        produced_blocks.clone(),
+        production_guard.clone(),
         block_worker_rx,
     );
 
+    // start production monitor worker
+    let monitor_worker_handle = ProductionMonitorWorker::spawn(
+        cfg.clone(),
+        wallet.clone(),
+        channels.clone(),
+        produced_blocks,
+        monitor_worker_rx,
+    );
+
     // start endorsement factory worker
-    let endorsement_worker_handle =
-        EndorsementFactoryWorker::spawn(cfg, wallet, channels, endorsement_worker_rx);
+    let endorsement_worker_handle = EndorsementFactoryWorker::spawn(
+        cfg,
+        wallet,
+        channels,
+        production_guard,
+        endorsement_worker_rx,
+    );
 
     // create factory manager
     let manager = FactoryManagerImpl {
         block_worker: Some((block_worker_tx, block_worker_handle)),
         endorsement_worker: Some((endorsement_worker_tx, endorsement_worker_handle)),
+        monitor_worker: Some((monitor_worker_tx, monitor_worker_handle)),
     };
 
     Box::new(manager)
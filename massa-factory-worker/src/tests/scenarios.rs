@@ -27,6 +27,8 @@ fn basic_creation_with_operation() {
     let content = Operation {
         fee: Amount::from_str("0.01").unwrap(),
         expire_period: 2,
+        sequence: None,
+        sponsor: None,
         op: OperationType::RollBuy { roll_count: 1 },
     };
     let operation =
@@ -50,6 +52,8 @@ fn basic_creation_with_multiple_operations() {
     let content = Operation {
         fee: Amount::from_str("0.01").unwrap(),
         expire_period: 2,
+        sequence: None,
+        sponsor: None,
         op: OperationType::RollBuy { roll_count: 1 },
     };
     let operation =
@@ -63,3 +67,140 @@ fn basic_creation_with_multiple_operations() {
     }
     assert_eq!(block.content.operations.len(), 2);
 }
+
+/// Classifies the probable causes of missed block productions.
+#[test]
+fn miss_cause_classification() {
+    use crate::production_monitor::{MissCause, ProducedBlock};
+    use massa_hash::Hash;
+    use massa_models::{block_id::BlockId, slot::Slot, stats::BlockProductionTimings};
+    use massa_time::MassaTime;
+
+    let threshold = MassaTime::from_millis(1000);
+    let block_id = BlockId(Hash::compute_from("block".as_bytes()));
+    let produced = ProducedBlock {
+        block_id,
+        production_delay: MassaTime::from_millis(200),
+        network_ahead: false,
+        timings: BlockProductionTimings {
+            slot: Slot::new(1, 0),
+            block_id,
+            wakeup_delay: MassaTime::from_millis(1),
+            draw_check_us: 10,
+            parents_us: 10,
+            endorsements_us: 10,
+            operations_us: 10,
+            signing_us: 10,
+            propagation_start_us: 10,
+            total_delay: MassaTime::from_millis(200),
+        },
+    };
+    assert_eq!(MissCause::classify(None, threshold), MissCause::NotProduced);
+    assert_eq!(
+        MissCause::classify(Some(&produced), threshold),
+        MissCause::Propagation
+    );
+    let late = ProducedBlock {
+        production_delay: MassaTime::from_millis(3000),
+        ..produced
+    };
+    assert_eq!(
+        MissCause::classify(Some(&late), threshold),
+        MissCause::LateProduction
+    );
+    let skewed = ProducedBlock {
+        network_ahead: true,
+        ..late
+    };
+    assert_eq!(
+        MissCause::classify(Some(&skewed), threshold),
+        MissCause::ClockSkew
+    );
+}
+
+/// Leaves out the operations involving a denylisted address.
+#[test]
+fn denylist_operation_filtering() {
+    use crate::denylist::OperationDenylist;
+    use massa_models::{address::Address, prehash::PreHashSet};
+
+    let creator = KeyPair::generate();
+    let denied = Address::from_public_key(&KeyPair::generate().get_public_key());
+    let allowed = Address::from_public_key(&KeyPair::generate().get_public_key());
+    let transfer = |recipient_address| {
+        let content = Operation {
+            fee: Amount::from_str("0.01").unwrap(),
+            expire_period: 2,
+            sequence: None,
+            sponsor: None,
+            op: OperationType::Transaction {
+                recipient_address,
+                amount: Amount::from_str("1").unwrap(),
+                memo: None,
+            },
+        };
+        Operation::new_verifiable(content, OperationSerializer::new(), &creator).unwrap()
+    };
+
+    let denylist = OperationDenylist::from_addresses(PreHashSet::from_iter([denied]));
+    assert_eq!(denylist.denied_address(&transfer(denied)), Some(denied));
+    assert_eq!(denylist.denied_address(&transfer(allowed)), None);
+
+    // the creator is denied as well
+    let denylist =
+        OperationDenylist::from_addresses(PreHashSet::from_iter([Address::from_public_key(
+            &creator.get_public_key(),
+        )]));
+    assert!(denylist.denied_address(&transfer(allowed)).is_some());
+}
+
+/// Refuses the double productions recorded in the guard file, by the same process or by another one.
+#[test]
+fn production_guard_double_production() {
+    use crate::production_guard::ProductionGuard;
+    use massa_models::{address::Address, slot::Slot};
+    use std::io::Write;
+
+    let path = std::env::temp_dir().join(format!(
+        "massa_production_guard_{}.jsonl",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&path);
+    let address = Address::from_public_key(&KeyPair::generate().get_public_key());
+    let slot = Slot::new(10, 3);
+
+    let mut guard = ProductionGuard::open(Some(&path)).unwrap();
+    assert!(guard.record(slot, address, None));
+    assert!(!guard.record(slot, address, None));
+    assert!(guard.record(slot, address, Some(0)));
+    assert!(!guard.record(slot, address, Some(0)));
+    assert!(guard.record(slot, address, Some(1)));
+
+    // another process sharing the file
+    let mut other_guard = ProductionGuard::open(Some(&path)).unwrap();
+    assert!(!other_guard.record(slot, address, None));
+    assert!(other_guard.record(Slot::new(11, 3), address, None));
+    assert!(!guard.record(Slot::new(11, 3), address, None));
+
+    // after a restart interrupted during an append
+    drop(guard);
+    drop(other_guard);
+    std::fs::OpenOptions::new()
+        .append(true)
+        .open(&path)
+        .unwrap()
+        .write_all(b"{\"slot\":")
+        .unwrap();
+    let mut guard = ProductionGuard::open(Some(&path)).unwrap();
+    assert!(!guard.record(slot, address, Some(1)));
+    assert!(guard.record(Slot::new(12, 3), address, None));
+    drop(guard);
+    let mut guard = ProductionGuard::open(Some(&path)).unwrap();
+    assert!(!guard.record(Slot::new(12, 3), address, None));
+
+    // disabled without a guard file
+    let mut guard = ProductionGuard::open(None).unwrap();
+    assert!(guard.record(slot, address, None));
+    assert!(guard.record(slot, address, None));
+    let _ = std::fs::remove_file(&path);
+}
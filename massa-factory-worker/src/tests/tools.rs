@@ -12,9 +12,14 @@ use massa_factory_exports::{
     test_exports::create_empty_block, FactoryChannels, FactoryConfig, FactoryManager,
 };
 use massa_models::{
-    address::Address, block_id::BlockId, config::ENDORSEMENT_COUNT,
-    endorsement::SecureShareEndorsement, operation::SecureShareOperation, prehash::PreHashMap,
-    slot::Slot, test_exports::get_next_slot_instant,
+    address::Address,
+    block_id::BlockId,
+    config::ENDORSEMENT_COUNT,
+    endorsement::SecureShareEndorsement,
+    operation::SecureShareOperation,
+    prehash::PreHashMap,
+    slot::{Slot, SyncLag},
+    test_exports::get_next_slot_instant,
 };
 use massa_pool_exports::test_exports::{
     MockPoolController, MockPoolControllerMessage, PoolEventReceiver,
@@ -26,7 +31,8 @@ use massa_pos_exports::{
 use massa_protocol_exports::test_exports::MockProtocolController;
 use massa_signature::KeyPair;
 use massa_storage::Storage;
-use massa_time::MassaTime;
+use massa_time::{ClockOffset, MassaTime};
+use massa_versioning::{MipStore, MipStoreConfig};
 
 use crate::start_factory;
 use massa_wallet::test_exports::create_test_wallet;
@@ -87,6 +93,15 @@ impl TestFactory {
                 pool: pool_controller.clone(),
                 protocol: protocol_command_sender,
                 storage: storage.clone_without_refs(),
+                disk_status: Default::default(),
+                production_stats: Default::default(),
+                mip_store: MipStore::new(MipStoreConfig {
+                    mips: Vec::new(),
+                    block_count_considered: 100,
+                    activation_threshold_percent: 75,
+                }),
+                clock_offset: ClockOffset::new(),
+                sync_lag: SyncLag::new(),
             },
         );
 
@@ -0,0 +1,246 @@
+//! Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Online backups of the final state.
+//!
+//! A backup is a directory containing:
+//! * `ledger/`: a checkpoint of the disk ledger database
+//! * `final_state.bin`: the async pool, the PoS cycle history and deferred credits, and the executed operations
+//! * `versioning.json`: the state of the MIPs, optional when restoring for the backups of older nodes
+//! * `manifest.json`: the slot of the backup and the hashes checked when restoring it
+//!
+//! The backup is taken while holding a read lock on the final state, so that no slot gets finalized
+//! while it is written and all its parts are attached to the same slot.
+
+use crate::{error::FinalStateError, final_state::FinalState};
+use massa_async_pool::{AsyncPoolDeserializer, AsyncPoolSerializer};
+use massa_executed_ops::{ExecutedOpsDeserializer, ExecutedOpsSerializer};
+use massa_hash::Hash;
+use massa_models::{slot::Slot, streaming_step::StreamingStep};
+use massa_pos_exports::{
+    CycleInfoDeserializer, CycleInfoSerializer, DeferredCreditsDeserializer,
+    DeferredCreditsSerializer,
+};
+use massa_serialization::{
+    DeserializeError, Deserializer, Serializer, U64VarIntDeserializer, U64VarIntSerializer,
+};
+use massa_time::MassaTime;
+use massa_versioning::MipStoreSnapshot;
+use nom::multi::length_count;
+use nom::sequence::tuple;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::ops::Bound::Included;
+use std::path::Path;
+
+const LEDGER_DIR: &str = "ledger";
+const FINAL_STATE_FILE: &str = "final_state.bin";
+const MANIFEST_FILE: &str = "manifest.json";
+const VERSIONING_FILE: &str = "versioning.json";
+
+/// Description of a final state backup
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupManifest {
+    /// slot at the output of which the backed up state is attached
+    pub slot: Slot,
+    /// hash of the backed up final state
+    pub final_state_hash: Hash,
+    /// hash of the backed up ledger
+    pub ledger_hash: Hash,
+    /// creation time of the backup
+    pub created_at: MassaTime,
+}
+
+impl std::fmt::Display for BackupManifest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Backup at slot {}", self.slot)?;
+        writeln!(f, "\tFinal state hash: {}", self.final_state_hash)?;
+        writeln!(f, "\tLedger hash: {}", self.ledger_hash)?;
+        writeln!(f, "\tCreated at: {}", self.created_at.to_utc_string())
+    }
+}
+
+fn backup_error(context: &str, err: impl std::fmt::Display) -> FinalStateError {
+    FinalStateError::BackupError(format!("{}: {}", context, err))
+}
+
+impl FinalState {
+    /// Writes a backup of the final state in `path`, which must not exist yet.
+    ///
+    /// The caller must hold the final state lock for the whole call,
+    /// which is the case when it is called through `final_state.read()`.
+    pub fn create_backup(&self, path: &Path) -> Result<BackupManifest, FinalStateError> {
+        if path.exists() {
+            return Err(FinalStateError::BackupError(format!(
+                "{} already exists",
+                path.display()
+            )));
+        }
+        std::fs::create_dir_all(path)
+            .map_err(|err| backup_error("could not create the backup directory", err))?;
+
+        // the ledger checkpoint is taken first as it is the largest part
+        self.ledger
+            .create_checkpoint(&path.join(LEDGER_DIR))
+            .map_err(|err| FinalStateError::LedgerError(err.to_string()))?;
+
+        // in-memory parts
+        let mut messages = BTreeMap::new();
+        let mut pool_step = StreamingStep::Started;
+        loop {
+            let (part, new_step) = self.async_pool.get_pool_part(pool_step);
+            if new_step.finished() {
+                break;
+            }
+            messages.extend(part);
+            pool_step = new_step;
+        }
+        let mut buffer = Vec::new();
+        AsyncPoolSerializer::new()
+            .serialize(&messages, &mut buffer)
+            .and_then(|_| {
+                U64VarIntSerializer::new()
+                    .serialize(&(self.pos_state.cycle_history.len() as u64), &mut buffer)
+            })
+            .and_then(|_| {
+                let cycle_serializer = CycleInfoSerializer::new();
+                self.pos_state
+                    .cycle_history
+                    .iter()
+                    .try_for_each(|cycle_info| cycle_serializer.serialize(cycle_info, &mut buffer))
+            })
+            .and_then(|_| {
+                DeferredCreditsSerializer::new()
+                    .serialize(&self.pos_state.deferred_credits, &mut buffer)
+            })
+            .and_then(|_| {
+                ExecutedOpsSerializer::new().serialize(&self.executed_ops.sorted_ops, &mut buffer)
+            })
+            .map_err(|err| backup_error("could not serialize the final state", err))?;
+        std::fs::write(path.join(FINAL_STATE_FILE), buffer)
+            .map_err(|err| backup_error("could not write the final state", err))?;
+        let versioning_file = std::fs::File::create(path.join(VERSIONING_FILE))
+            .map_err(|err| backup_error("could not create the versioning state", err))?;
+        serde_json::to_writer_pretty(versioning_file, &self.mip_store.snapshot())
+            .map_err(|err| backup_error("could not write the versioning state", err))?;
+
+        // the manifest is written last: a backup without manifest is incomplete
+        let manifest = BackupManifest {
+            slot: self.slot,
+            final_state_hash: self.compute_state_hash(),
+            ledger_hash: self.ledger.get_ledger_hash(),
+            created_at: MassaTime::now().map_err(|err| backup_error("invalid time", err))?,
+        };
+        let manifest_file = std::fs::File::create(path.join(MANIFEST_FILE))
+            .map_err(|err| backup_error("could not create the manifest", err))?;
+        serde_json::to_writer_pretty(manifest_file, &manifest)
+            .map_err(|err| backup_error("could not write the manifest", err))?;
+        Ok(manifest)
+    }
+
+    /// Loads the in-memory parts of a backup written by `create_backup`,
+    /// and checks the resulting state against the manifest of the backup.
+    ///
+    /// The ledger must have been restored beforehand with `restore_ledger_backup`,
+    /// before the creation of the final ledger.
+    pub fn restore_backup(&mut self, path: &Path) -> Result<BackupManifest, FinalStateError> {
+        let manifest = read_manifest(path)?;
+        let ledger_hash = self.ledger.get_ledger_hash();
+        if ledger_hash != manifest.ledger_hash {
+            return Err(FinalStateError::BackupError(format!(
+                "restored ledger hash {} does not match the backup hash {}",
+                ledger_hash, manifest.ledger_hash
+            )));
+        }
+
+        let data = std::fs::read(path.join(FINAL_STATE_FILE))
+            .map_err(|err| backup_error("could not read the final state", err))?;
+        // the backup was written by a node with the same configuration,
+        // and its content is checked against the manifest hash below
+        let thread_count = self.config.thread_count;
+        let pool_deserializer = AsyncPoolDeserializer::new(
+            thread_count,
+            self.config.async_pool_config.max_length,
+            self.config.async_pool_config.max_async_message_data,
+            self.config.ledger_config.max_key_length as u32,
+        );
+        let cycle_count_deserializer = U64VarIntDeserializer::new(
+            Included(0),
+            Included(self.config.pos_config.cycle_history_length as u64),
+        );
+        let cycle_deserializer = CycleInfoDeserializer::new(u64::MAX, u64::MAX);
+        let credits_deserializer = DeferredCreditsDeserializer::new(thread_count, u64::MAX);
+        let ops_deserializer = ExecutedOpsDeserializer::new(thread_count, u64::MAX, u64::MAX);
+        let (_, (messages, cycles, credits, executed_ops)) = tuple((
+            |input| pool_deserializer.deserialize::<DeserializeError>(input),
+            length_count(
+                |input| cycle_count_deserializer.deserialize(input),
+                |input| cycle_deserializer.deserialize(input),
+            ),
+            |input| credits_deserializer.deserialize(input),
+            |input| ops_deserializer.deserialize(input),
+        ))(data.as_slice())
+        .map_err(|err| backup_error("invalid final state backup", err))?;
+
+        self.async_pool.reset();
+        self.async_pool.set_pool_part(messages);
+        self.pos_state.reset();
+        for cycle_info in cycles {
+            self.pos_state.set_cycle_history_part(Some(cycle_info));
+        }
+        self.pos_state.set_deferred_credits_part(credits);
+        self.executed_ops.reset();
+        self.executed_ops.set_executed_ops_part(executed_ops);
+        let versioning_path = path.join(VERSIONING_FILE);
+        let mip_store_snapshot = if versioning_path.exists() {
+            let versioning_file = std::fs::File::open(versioning_path)
+                .map_err(|err| backup_error("could not open the versioning state", err))?;
+            serde_json::from_reader(versioning_file)
+                .map_err(|err| backup_error("invalid versioning state", err))?
+        } else {
+            MipStoreSnapshot::default()
+        };
+        self.mip_store.restore(mip_store_snapshot);
+        self.changes_history.clear();
+        self.slot = manifest.slot;
+        self.shared_slot.set(manifest.slot);
+
+        self.final_state_hash = self.compute_state_hash();
+        if self.final_state_hash != manifest.final_state_hash {
+            return Err(FinalStateError::BackupError(format!(
+                "restored final state hash {} does not match the backup hash {}",
+                self.final_state_hash, manifest.final_state_hash
+            )));
+        }
+        Ok(manifest)
+    }
+}
+
+/// Reads the manifest of a backup
+pub fn read_manifest(path: &Path) -> Result<BackupManifest, FinalStateError> {
+    let manifest_file = std::fs::File::open(path.join(MANIFEST_FILE))
+        .map_err(|err| backup_error("could not open the backup manifest", err))?;
+    serde_json::from_reader(manifest_file)
+        .map_err(|err| backup_error("invalid backup manifest", err))
+}
+
+/// Replaces the disk ledger at `ledger_path` by the ledger checkpoint of the backup at `path`.
+/// Must be called before the final ledger is created.
+pub fn restore_ledger_backup(path: &Path, ledger_path: &Path) -> Result<(), FinalStateError> {
+    // fail before deleting anything if the backup is incomplete
+    read_manifest(path)?;
+    if ledger_path.exists() {
+        std::fs::remove_dir_all(ledger_path)
+            .map_err(|err| backup_error("could not delete the current ledger", err))?;
+    }
+    std::fs::create_dir_all(ledger_path)
+        .map_err(|err| backup_error("could not create the ledger directory", err))?;
+    let entries = std::fs::read_dir(path.join(LEDGER_DIR))
+        .map_err(|err| backup_error("could not read the ledger backup", err))?;
+    for entry in entries {
+        let entry = entry.map_err(|err| backup_error("could not read the ledger backup", err))?;
+        // the files are copied so that the backup can be restored again
+        std::fs::copy(entry.path(), ledger_path.join(entry.file_name()))
+            .map_err(|err| backup_error("could not copy the ledger backup", err))?;
+    }
+    Ok(())
+}
@@ -6,6 +6,7 @@ use massa_async_pool::AsyncPoolConfig;
 use massa_executed_ops::ExecutedOpsConfig;
 use massa_ledger_exports::LedgerConfig;
 use massa_pos_exports::PoSConfig;
+use massa_versioning::MipStoreConfig;
 use std::path::PathBuf;
 
 /// Ledger configuration
@@ -29,4 +30,12 @@ pub struct FinalStateConfig {
     pub initial_seed_string: String,
     /// initial rolls file path
     pub initial_rolls_path: PathBuf,
+    /// genesis artifact built with `GenesisBuilder`, replacing the initial ledger and rolls files if set
+    pub genesis_path: Option<PathBuf>,
+    /// directory of the write-ahead journal of the final state changes, `None` to disable it
+    pub journal_path: Option<PathBuf>,
+    /// number of final slots between two checkpoints of the journal
+    pub journal_checkpoint_interval: u64,
+    /// versioning configuration
+    pub mip_store_config: MipStoreConfig,
 }
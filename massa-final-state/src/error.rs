@@ -15,4 +15,10 @@ pub enum FinalStateError {
     LedgerError(String),
     /// PoS error: {0}
     PosError(String),
+    /// backup error: {0}
+    BackupError(String),
+    /// journal error: {0}
+    JournalError(String),
+    /// genesis error: {0}
+    GenesisError(String),
 }
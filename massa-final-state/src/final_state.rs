@@ -5,13 +5,20 @@
 //! the output of a given final slot (the latest executed final slot),
 //! and need to be bootstrapped by nodes joining the network.
 
-use crate::{config::FinalStateConfig, error::FinalStateError, state_changes::StateChanges};
+use crate::{
+    config::FinalStateConfig, error::FinalStateError, genesis::Genesis, journal::FinalStateJournal,
+    state_changes::StateChanges,
+};
 use massa_async_pool::{AsyncMessageId, AsyncPool, AsyncPoolChanges, Change};
 use massa_executed_ops::ExecutedOps;
 use massa_hash::{Hash, HASH_SIZE_BYTES};
-use massa_ledger_exports::{Key as LedgerKey, LedgerChanges, LedgerController};
-use massa_models::{slot::Slot, streaming_step::StreamingStep};
-use massa_pos_exports::{DeferredCredits, PoSFinalState, SelectorController};
+use massa_ledger_exports::{Key as LedgerKey, LedgerChanges, LedgerController, SetUpdateOrDelete};
+use massa_models::{
+    slot::{SharedSlot, Slot},
+    streaming_step::StreamingStep,
+};
+use massa_pos_exports::{DeferredCredits, JobScheduler, PoSFinalState, SelectorController};
+use massa_versioning::{MipStore, MipStoreSnapshot};
 use std::collections::VecDeque;
 use tracing::info;
 
@@ -21,6 +28,8 @@ pub struct FinalState {
     pub(crate) config: FinalStateConfig,
     /// slot at the output of which the state is attached
     pub slot: Slot,
+    /// `slot`, shared so that it can be read without locking the state
+    pub shared_slot: SharedSlot,
     /// final ledger associating addresses to their balance, executable bytecode and data
     pub ledger: Box<dyn LedgerController>,
     /// asynchronous pool containing messages sorted by priority and their data
@@ -29,11 +38,56 @@ pub struct FinalState {
     pub pos_state: PoSFinalState,
     /// executed operations
     pub executed_ops: ExecutedOps,
+    /// state of the MIPs, updated with the final blocks
+    pub mip_store: MipStore,
     /// history of recent final state changes, useful for streaming bootstrap
     /// `front = oldest`, `back = newest`
     pub changes_history: VecDeque<(Slot, StateChanges)>,
     /// hash of the final state, it is computed on finality
     pub final_state_hash: Hash,
+    /// write-ahead journal of the final state changes, if enabled
+    pub(crate) journal: Option<FinalStateJournal>,
+    /// genesis loaded from `config.genesis_path`, if set
+    pub(crate) genesis: Option<Genesis>,
+}
+
+/// Hashes of the components of a final state, compared at the end of a bootstrap
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FinalStateHashes {
+    /// hash of the ledger
+    pub ledger: Hash,
+    /// hash of the asynchronous pool
+    pub async_pool: Hash,
+    /// hash of the deferred credits
+    pub deferred_credits: Hash,
+    /// hash of the concatenated global hashes of the cycles included in the final state hash
+    pub cycle_history: Hash,
+    /// hash of the executed operations
+    pub executed_ops: Hash,
+    /// hash of the final state
+    pub final_state: Hash,
+}
+
+impl FinalStateHashes {
+    /// Names of the components whose hash differs from `other`
+    pub fn mismatches(&self, other: &FinalStateHashes) -> Vec<&'static str> {
+        [
+            ("ledger", self.ledger == other.ledger),
+            ("async pool", self.async_pool == other.async_pool),
+            (
+                "deferred credits",
+                self.deferred_credits == other.deferred_credits,
+            ),
+            ("cycle history", self.cycle_history == other.cycle_history),
+            (
+                "executed operations",
+                self.executed_ops == other.executed_ops,
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(name, matches)| (!matches).then_some(name))
+        .collect()
+    }
 }
 
 const FINAL_STATE_HASH_INITIAL_BYTES: &[u8; 32] = &[0; HASH_SIZE_BYTES];
@@ -48,15 +102,32 @@ impl FinalState {
         ledger: Box<dyn LedgerController>,
         selector: Box<dyn SelectorController>,
     ) -> Result<Self, FinalStateError> {
-        // create the pos state
-        let pos_state = PoSFinalState::new(
-            config.pos_config.clone(),
-            &config.initial_seed_string,
-            &config.initial_rolls_path,
-            selector,
-            ledger.get_ledger_hash(),
-        )
-        .map_err(|err| FinalStateError::PosError(format!("PoS final state init error: {}", err)))?;
+        // load the genesis artifact, if any
+        let genesis = match &config.genesis_path {
+            Some(path) => Some(Genesis::load(path, &config)?),
+            None => None,
+        };
+
+        // create the pos state, with the initial rolls of the genesis artifact if any
+        let pos_state = match &genesis {
+            Some(genesis) => PoSFinalState::with_initial_rolls(
+                config.pos_config.clone(),
+                &config.initial_seed_string,
+                genesis.rolls.clone(),
+                selector,
+                ledger.get_ledger_hash(),
+            ),
+            None => PoSFinalState::new(
+                config.pos_config.clone(),
+                &config.initial_seed_string,
+                &config.initial_rolls_path,
+                selector,
+                ledger.get_ledger_hash(),
+            )
+            .map_err(|err| {
+                FinalStateError::PosError(format!("PoS final state init error: {}", err))
+            })?,
+        };
 
         // attach at the output of the latest initial final slot, that is the last genesis slot
         let slot = Slot::new(0, config.thread_count.saturating_sub(1));
@@ -67,55 +138,156 @@ impl FinalState {
         // create a default executed ops
         let executed_ops = ExecutedOps::new(config.executed_ops_config.clone());
 
+        // create the versioning state
+        let mip_store = MipStore::new(config.mip_store_config.clone());
+
+        // open the journal of the final state changes
+        let journal = match &config.journal_path {
+            Some(path) => Some(FinalStateJournal::open(
+                path,
+                config.journal_checkpoint_interval,
+            )?),
+            None => None,
+        };
+
         // create the final state
         Ok(FinalState {
             slot,
+            shared_slot: SharedSlot::new(slot),
             ledger,
             async_pool,
             pos_state,
             config,
             executed_ops,
+            mip_store,
             changes_history: Default::default(), // no changes in history
             final_state_hash: Hash::from_bytes(FINAL_STATE_HASH_INITIAL_BYTES),
+            journal,
+            genesis,
         })
     }
 
+    /// Initializes the state at genesis, when the node does not bootstrap:
+    /// from the genesis artifact if configured, otherwise from the initial ledger file
+    pub fn init_genesis_state(&mut self) -> Result<(), FinalStateError> {
+        match &self.genesis {
+            Some(genesis) => {
+                let ledger_changes = LedgerChanges(
+                    genesis
+                        .ledger
+                        .iter()
+                        .map(|(address, entry)| (*address, SetUpdateOrDelete::Set(entry.clone())))
+                        .collect(),
+                );
+                self.ledger.apply_changes(ledger_changes, self.slot);
+                let mut deferred_credits = DeferredCredits::default();
+                for credit in &genesis.deferred_credits {
+                    deferred_credits.insert(credit.address, credit.slot, credit.amount);
+                }
+                self.pos_state
+                    .deferred_credits
+                    .final_nested_extend(deferred_credits);
+                self.async_pool.apply_changes_unchecked(&AsyncPoolChanges(
+                    genesis
+                        .async_messages
+                        .iter()
+                        .map(|message| Change::Add(message.compute_id(), message.clone()))
+                        .collect(),
+                ));
+            }
+            None => self.ledger.load_initial_ledger().map_err(|err| {
+                FinalStateError::LedgerError(format!("could not load initial ledger: {}", err))
+            })?,
+        }
+        // create the initial cycle of PoS cycle_history
+        self.pos_state.create_initial_cycle();
+        Ok(())
+    }
+
+    /// Defers the periodic checkpoints of the journal to the quiet windows of `scheduler`
+    pub fn set_job_scheduler(&mut self, scheduler: JobScheduler) {
+        if let Some(journal) = self.journal.as_mut() {
+            journal.set_scheduler(scheduler);
+        }
+    }
+
     /// Reset the final state to the initial state.
     ///
     /// USED ONLY FOR BOOTSTRAP
     pub fn reset(&mut self) {
         self.slot = Slot::new(0, self.config.thread_count.saturating_sub(1));
+        self.shared_slot.set(self.slot);
         self.ledger.reset();
         self.async_pool.reset();
         self.pos_state.reset();
         self.executed_ops.reset();
+        self.mip_store.restore(MipStoreSnapshot::default());
         self.changes_history.clear();
+        // the journaled changes do not apply to the new state
+        if let Some(journal) = self.journal.as_mut() {
+            journal.reset();
+        }
         // reset the final state hash
         self.final_state_hash = Hash::from_bytes(FINAL_STATE_HASH_INITIAL_BYTES);
     }
 
-    /// Compute the current state hash.
-    ///
-    /// Used when finalizing a slot.
-    /// Slot information is only used for logging.
-    pub fn compute_state_hash_at_slot(&mut self, slot: Slot) {
-        // 1. init hash concatenation with the ledger hash
+    /// Compute the hashes of the components of the current state, along with the state hash
+    pub fn get_state_hashes(&self) -> FinalStateHashes {
         let ledger_hash = self.ledger.get_ledger_hash();
+        let cycle_hashes = self.get_cycle_history_hashes();
+        FinalStateHashes {
+            ledger: ledger_hash,
+            async_pool: self.async_pool.hash,
+            deferred_credits: self.pos_state.deferred_credits.hash,
+            cycle_history: Hash::compute_from(&cycle_hashes),
+            executed_ops: self.executed_ops.hash,
+            final_state: self.combine_state_hash(ledger_hash, &cycle_hashes),
+        }
+    }
+
+    /// Concatenated global hashes of the cycles included in the state hash,
+    /// skipping the bootstrap safety cycle if there is one
+    fn get_cycle_history_hashes(&self) -> Vec<u8> {
+        let n = (self.pos_state.cycle_history.len() == self.config.pos_config.cycle_history_length)
+            as usize;
+        let mut cycle_hashes: Vec<u8> = Vec::new();
+        for cycle_info in self.pos_state.cycle_history.iter().skip(n) {
+            cycle_hashes.extend(cycle_info.cycle_global_hash.to_bytes());
+        }
+        cycle_hashes
+    }
+
+    /// Compute the hash of the current state
+    pub(crate) fn compute_state_hash(&self) -> Hash {
+        self.combine_state_hash(
+            self.ledger.get_ledger_hash(),
+            &self.get_cycle_history_hashes(),
+        )
+    }
+
+    /// Combine the hashes of the components of the current state into the state hash,
+    /// `ledger_hash` and `cycle_hashes` being computed once by the callers
+    fn combine_state_hash(&self, ledger_hash: Hash, cycle_hashes: &[u8]) -> Hash {
+        // 1. init hash concatenation with the ledger hash
         let mut hash_concat: Vec<u8> = ledger_hash.to_bytes().to_vec();
         // 2. async_pool hash
         hash_concat.extend(self.async_pool.hash.to_bytes());
         // 3. pos deferred_credit hash
         hash_concat.extend(self.pos_state.deferred_credits.hash.to_bytes());
-        // 4. pos cycle history hashes, skip the bootstrap safety cycle if there is one
-        let n = (self.pos_state.cycle_history.len() == self.config.pos_config.cycle_history_length)
-            as usize;
-        for cycle_info in self.pos_state.cycle_history.iter().skip(n) {
-            hash_concat.extend(cycle_info.cycle_global_hash.to_bytes());
-        }
+        // 4. pos cycle history hashes
+        hash_concat.extend(cycle_hashes);
         // 5. executed operations hash
         hash_concat.extend(self.executed_ops.hash.to_bytes());
-        // 6. compute and save final state hash
-        self.final_state_hash = Hash::compute_from(&hash_concat);
+        Hash::compute_from(&hash_concat)
+    }
+
+    /// Compute the current state hash.
+    ///
+    /// Used when finalizing a slot.
+    /// Slot information is only used for logging.
+    pub fn compute_state_hash_at_slot(&mut self, slot: Slot) {
+        // compute and save final state hash
+        self.final_state_hash = self.compute_state_hash();
         info!(
             "final_state hash at slot {}: {}",
             slot, self.final_state_hash
@@ -143,8 +315,15 @@ impl FinalState {
             panic!("attempting to apply execution state changes at slot {} while the current slot is {}", slot, self.slot);
         }
 
+        // journal the changes before applying them, so that they can be replayed after a crash
+        if let Some(mut journal) = self.journal.take() {
+            journal.write_ahead(self, slot, &changes);
+            self.journal = Some(journal);
+        }
+
         // update current slot
         self.slot = slot;
+        self.shared_slot.set(slot);
 
         // apply the state changes
         // unwrap is justified because every error in PoS `apply_changes` is critical
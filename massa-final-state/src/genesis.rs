@@ -0,0 +1,229 @@
+//! Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! This file defines the genesis of a network built programmatically.
+//!
+//! `GenesisBuilder` gathers the initial ledger entries, rolls, deferred credits and asynchronous messages
+//! of a network, and validates them into a `Genesis` committed by a hash of its content.
+//! A genesis is saved as a JSON artifact holding its content and its hash, checked again when it is loaded:
+//! set as `genesis_path` in the final state configuration, it replaces the initial ledger and rolls files.
+
+use crate::{config::FinalStateConfig, error::FinalStateError};
+use massa_async_pool::AsyncMessage;
+use massa_hash::Hash;
+use massa_ledger_exports::{LedgerEntry, LedgerEntrySerializer};
+use massa_models::{address::Address, amount::Amount, slot::Slot};
+use massa_serialization::Serializer;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Credit of coins to an address at a slot after genesis
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GenesisCredit {
+    /// slot of the credit
+    pub slot: Slot,
+    /// credited address
+    pub address: Address,
+    /// credited amount
+    pub amount: Amount,
+}
+
+/// Validated genesis of a network
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Genesis {
+    /// initial ledger entries
+    pub ledger: BTreeMap<Address, LedgerEntry>,
+    /// initial rolls
+    pub rolls: BTreeMap<Address, u64>,
+    /// deferred credits, sorted by slot then address
+    pub deferred_credits: Vec<GenesisCredit>,
+    /// initial asynchronous messages
+    pub async_messages: Vec<AsyncMessage>,
+    /// hash committing to the content above
+    pub hash: Hash,
+}
+
+impl Genesis {
+    /// Hash of the content of the genesis
+    fn compute_hash(&self) -> Result<Hash, FinalStateError> {
+        let entry_serializer = LedgerEntrySerializer::new();
+        let mut hash_data = Vec::new();
+        for (address, entry) in &self.ledger {
+            hash_data.extend(address.prefixed_bytes());
+            entry_serializer
+                .serialize(entry, &mut hash_data)
+                .map_err(|err| FinalStateError::GenesisError(err.to_string()))?;
+        }
+        for (address, rolls) in &self.rolls {
+            hash_data.extend(address.prefixed_bytes());
+            hash_data.extend(rolls.to_be_bytes());
+        }
+        for credit in &self.deferred_credits {
+            hash_data.extend(credit.slot.to_bytes_key());
+            hash_data.extend(credit.address.prefixed_bytes());
+            hash_data.extend(credit.amount.to_raw().to_be_bytes());
+        }
+        for message in &self.async_messages {
+            hash_data.extend(message.hash.to_bytes());
+        }
+        Ok(Hash::compute_from(&hash_data))
+    }
+
+    /// Saves the genesis artifact in `path`
+    pub fn save(&self, path: &Path) -> Result<(), FinalStateError> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|err| FinalStateError::GenesisError(err.to_string()))?;
+        std::fs::write(path, json).map_err(|err| {
+            FinalStateError::GenesisError(format!("could not write {}: {}", path.display(), err))
+        })
+    }
+
+    /// Loads the genesis artifact saved in `path`, checking its hash and its content against `config`
+    pub fn load(path: &Path, config: &FinalStateConfig) -> Result<Self, FinalStateError> {
+        let json = std::fs::read_to_string(path).map_err(|err| {
+            FinalStateError::GenesisError(format!("could not read {}: {}", path.display(), err))
+        })?;
+        let genesis: Genesis = serde_json::from_str(&json).map_err(|err| {
+            FinalStateError::GenesisError(format!("could not parse {}: {}", path.display(), err))
+        })?;
+        let builder = GenesisBuilder {
+            ledger: genesis.ledger.clone().into_iter().collect(),
+            rolls: genesis.rolls.clone().into_iter().collect(),
+            deferred_credits: genesis.deferred_credits.clone(),
+            async_messages: genesis.async_messages.clone(),
+        };
+        let checked = builder.build(config)?;
+        if checked != genesis {
+            return Err(FinalStateError::GenesisError(format!(
+                "the content of {} does not match its hash {}",
+                path.display(),
+                genesis.hash
+            )));
+        }
+        Ok(checked)
+    }
+}
+
+/// Builder of a `Genesis`
+#[derive(Debug, Clone, Default)]
+pub struct GenesisBuilder {
+    ledger: Vec<(Address, LedgerEntry)>,
+    rolls: Vec<(Address, u64)>,
+    deferred_credits: Vec<GenesisCredit>,
+    async_messages: Vec<AsyncMessage>,
+}
+
+impl GenesisBuilder {
+    /// Creates an empty `GenesisBuilder`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the initial ledger entry of `address`
+    pub fn ledger_entry(mut self, address: Address, entry: LedgerEntry) -> Self {
+        self.ledger.push((address, entry));
+        self
+    }
+
+    /// Gives `rolls` initial rolls to `address`
+    pub fn rolls(mut self, address: Address, rolls: u64) -> Self {
+        self.rolls.push((address, rolls));
+        self
+    }
+
+    /// Credits `amount` to `address` at `slot`
+    pub fn deferred_credit(mut self, slot: Slot, address: Address, amount: Amount) -> Self {
+        self.deferred_credits.push(GenesisCredit {
+            slot,
+            address,
+            amount,
+        });
+        self
+    }
+
+    /// Adds an initial asynchronous message
+    pub fn async_message(mut self, message: AsyncMessage) -> Self {
+        self.async_messages.push(message);
+        self
+    }
+
+    /// Validates the genesis against `config` and computes its hash
+    pub fn build(self, config: &FinalStateConfig) -> Result<Genesis, FinalStateError> {
+        let invalid = |reason: String| Err(FinalStateError::GenesisError(reason));
+        let genesis_slot = Slot::new(0, config.thread_count.saturating_sub(1));
+        let mut ledger = BTreeMap::new();
+        for (address, entry) in self.ledger {
+            for (key, value) in &entry.datastore {
+                if key.len() > config.ledger_config.max_key_length as usize {
+                    return invalid(format!("datastore key of {} is too long", address));
+                }
+                if value.len() as u64 > config.ledger_config.max_datastore_value_length {
+                    return invalid(format!("datastore value of {} is too long", address));
+                }
+            }
+            if ledger.insert(address, entry).is_some() {
+                return invalid(format!("several ledger entries for {}", address));
+            }
+        }
+        let mut rolls = BTreeMap::new();
+        for (address, count) in self.rolls {
+            if count == 0 {
+                return invalid(format!("no rolls given to {}", address));
+            }
+            if !ledger.contains_key(&address) {
+                return invalid(format!("{} has rolls but no ledger entry", address));
+            }
+            if rolls.insert(address, count).is_some() {
+                return invalid(format!("several roll counts for {}", address));
+            }
+        }
+        let mut deferred_credits = self.deferred_credits;
+        deferred_credits.sort_unstable_by_key(|credit| (credit.slot, credit.address));
+        for (index, credit) in deferred_credits.iter().enumerate() {
+            if credit.slot.thread >= config.thread_count || credit.slot <= genesis_slot {
+                return invalid(format!("invalid slot {} of a deferred credit", credit.slot));
+            }
+            if index > 0
+                && deferred_credits[index - 1].slot == credit.slot
+                && deferred_credits[index - 1].address == credit.address
+            {
+                return invalid(format!(
+                    "several deferred credits for {} at slot {}",
+                    credit.address, credit.slot
+                ));
+            }
+        }
+        if self.async_messages.len() as u64 > config.async_pool_config.max_length {
+            return invalid("too many asynchronous messages".to_string());
+        }
+        let mut async_messages = self.async_messages;
+        for message in async_messages.iter_mut() {
+            if message.emission_slot != genesis_slot
+                || message.validity_end <= message.validity_start
+                || message.validity_end.thread >= config.thread_count
+                || message.validity_start.thread >= config.thread_count
+            {
+                return invalid(format!(
+                    "invalid slots of the asynchronous message {} of {}",
+                    message.emission_index, message.sender
+                ));
+            }
+            if message.data.len() as u64 > config.async_pool_config.max_async_message_data {
+                return invalid(format!(
+                    "data of the asynchronous message {} of {} is too long",
+                    message.emission_index, message.sender
+                ));
+            }
+            message.compute_hash();
+        }
+        let mut genesis = Genesis {
+            ledger,
+            rolls,
+            deferred_credits,
+            async_messages,
+            hash: Hash::compute_from(&[]),
+        };
+        genesis.hash = genesis.compute_hash()?;
+        Ok(genesis)
+    }
+}
@@ -0,0 +1,336 @@
+//! Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Write-ahead journal of the final state changes.
+//!
+//! A journal is a directory containing:
+//! * `checkpoint/`: a backup of the final state (see `backup.rs`)
+//! * `changes.journal`: the state changes of the slots finalized since the checkpoint
+//!
+//! The changes of a slot are appended and synced to the journal before being applied to the final state.
+//! If the node crashes while changes are applied, the disk ledger may be left half-updated but the journal is complete:
+//! on restart, the checkpoint is restored and the journaled changes are replayed on top of it.
+//! A record torn by the crash fails its hash check and is ignored, as well as all the records after it.
+//!
+//! A checkpoint is taken at the first slot finalized after the journal is opened or reset,
+//! because the state may have been modified by the bootstrap in between, then periodically.
+//! With a job scheduler, the periodic checkpoints are deferred while the node is about to produce,
+//! for at most another checkpoint interval.
+
+use crate::{
+    backup::read_manifest,
+    error::FinalStateError,
+    final_state::FinalState,
+    restore_ledger_backup,
+    state_changes::{StateChanges, StateChangesDeserializer, StateChangesSerializer},
+};
+use massa_hash::{Hash, HASH_SIZE_BYTES};
+use massa_models::slot::{Slot, SlotDeserializer, SlotSerializer};
+use massa_pos_exports::JobScheduler;
+use massa_serialization::{DeserializeError, Deserializer, Serializer};
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write};
+use std::ops::Bound::{Excluded, Included};
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+const CHECKPOINT_DIR: &str = "checkpoint";
+const PENDING_CHECKPOINT_DIR: &str = "checkpoint.new";
+const JOURNAL_FILE: &str = "changes.journal";
+
+/// length prefix and hash of each record
+const RECORD_HEADER_SIZE: usize = 4 + HASH_SIZE_BYTES;
+
+fn journal_error(context: &str, err: impl std::fmt::Display) -> FinalStateError {
+    FinalStateError::JournalError(format!("{}: {}", context, err))
+}
+
+/// Promotes a checkpoint whose replacement was interrupted by a crash.
+/// Returns the path of the checkpoint if there is a complete one.
+fn complete_checkpoint(journal_path: &Path) -> Result<Option<PathBuf>, FinalStateError> {
+    let checkpoint_path = journal_path.join(CHECKPOINT_DIR);
+    let pending_path = journal_path.join(PENDING_CHECKPOINT_DIR);
+    if pending_path.exists() {
+        // the manifest is the last file written in a backup
+        if !checkpoint_path.exists() && read_manifest(&pending_path).is_ok() {
+            std::fs::rename(&pending_path, &checkpoint_path)
+                .map_err(|err| journal_error("could not promote the pending checkpoint", err))?;
+        } else {
+            std::fs::remove_dir_all(&pending_path)
+                .map_err(|err| journal_error("could not delete the pending checkpoint", err))?;
+        }
+    }
+    Ok(
+        (checkpoint_path.exists() && read_manifest(&checkpoint_path).is_ok())
+            .then_some(checkpoint_path),
+    )
+}
+
+/// Replaces the disk ledger at `ledger_path` by the ledger of the checkpoint of the journal at `journal_path`.
+/// Must be called before the final ledger is created, then followed by `FinalState::recover_from_journal`.
+///
+/// Returns `false` if there is no checkpoint to recover from, in which case the disk ledger is left untouched.
+pub fn restore_journal_ledger(
+    journal_path: &Path,
+    ledger_path: &Path,
+) -> Result<bool, FinalStateError> {
+    match complete_checkpoint(journal_path)? {
+        Some(checkpoint_path) => {
+            restore_ledger_backup(&checkpoint_path, ledger_path)?;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Journal of the final state changes
+pub(crate) struct FinalStateJournal {
+    /// directory of the journal
+    path: PathBuf,
+    /// journal file, opened in append mode
+    file: File,
+    /// number of final slots between two checkpoints
+    checkpoint_interval: u64,
+    /// number of slots journaled since the last checkpoint, `None` if a checkpoint is needed
+    slots_since_checkpoint: Option<u64>,
+    /// scheduler deferring the periodic checkpoints to the quiet windows of the node, if any
+    scheduler: Option<JobScheduler>,
+}
+
+impl FinalStateJournal {
+    /// Opens the journal at `path`, creating it if needed
+    pub fn open(path: &Path, checkpoint_interval: u64) -> Result<Self, FinalStateError> {
+        std::fs::create_dir_all(path)
+            .map_err(|err| journal_error("could not create the journal directory", err))?;
+        let file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(path.join(JOURNAL_FILE))
+            .map_err(|err| journal_error("could not open the journal", err))?;
+        Ok(FinalStateJournal {
+            path: path.to_path_buf(),
+            file,
+            checkpoint_interval,
+            slots_since_checkpoint: None,
+            scheduler: None,
+        })
+    }
+
+    /// Defers the periodic checkpoints to the quiet windows of `scheduler`
+    pub fn set_scheduler(&mut self, scheduler: JobScheduler) {
+        self.scheduler = Some(scheduler);
+    }
+
+    /// Journals the changes of `slot`, taking a checkpoint of `final_state` first if needed.
+    /// Must be called before applying the changes, `final_state` being attached to the previous slot.
+    pub fn write_ahead(&mut self, final_state: &FinalState, slot: Slot, changes: &StateChanges) {
+        let needs_checkpoint = match self.slots_since_checkpoint {
+            None => true,
+            Some(count) if count >= self.checkpoint_interval.saturating_mul(2) => true,
+            Some(count) => {
+                count >= self.checkpoint_interval
+                    && self.scheduler.as_ref().map_or(true, JobScheduler::is_quiet)
+            }
+        };
+        if needs_checkpoint {
+            match self.checkpoint(final_state) {
+                Ok(()) => self.slots_since_checkpoint = Some(0),
+                Err(err) => {
+                    // the previous checkpoint and its journal, if any, are still consistent
+                    warn!("could not checkpoint the final state journal: {}", err);
+                }
+            }
+        }
+        match self.append(slot, changes) {
+            Ok(()) => {
+                self.slots_since_checkpoint = self.slots_since_checkpoint.map(|count| count + 1);
+            }
+            Err(err) => {
+                // replaying stops at the missing slot, take a new checkpoint at the next one
+                warn!(
+                    "could not journal the final state changes of slot {}: {}",
+                    slot, err
+                );
+                self.slots_since_checkpoint = None;
+            }
+        }
+    }
+
+    /// Replaces the checkpoint by a backup of `final_state` and empties the journal
+    fn checkpoint(&mut self, final_state: &FinalState) -> Result<(), FinalStateError> {
+        let checkpoint_path = self.path.join(CHECKPOINT_DIR);
+        let pending_path = self.path.join(PENDING_CHECKPOINT_DIR);
+        if pending_path.exists() {
+            std::fs::remove_dir_all(&pending_path)
+                .map_err(|err| journal_error("could not delete the pending checkpoint", err))?;
+        }
+        final_state.create_backup(&pending_path)?;
+        if checkpoint_path.exists() {
+            std::fs::remove_dir_all(&checkpoint_path)
+                .map_err(|err| journal_error("could not delete the previous checkpoint", err))?;
+        }
+        std::fs::rename(&pending_path, &checkpoint_path)
+            .map_err(|err| journal_error("could not promote the pending checkpoint", err))?;
+        // the records left after a crash here are older than the checkpoint and skipped when replaying
+        self.file
+            .set_len(0)
+            .and_then(|_| self.file.sync_data())
+            .map_err(|err| journal_error("could not truncate the journal", err))
+    }
+
+    /// Appends a record and waits for it to reach the disk
+    fn append(&mut self, slot: Slot, changes: &StateChanges) -> Result<(), FinalStateError> {
+        let mut payload = Vec::new();
+        SlotSerializer::new()
+            .serialize(&slot, &mut payload)
+            .and_then(|_| StateChangesSerializer::new().serialize(changes, &mut payload))
+            .map_err(|err| journal_error("could not serialize the state changes", err))?;
+        let length: u32 = payload
+            .len()
+            .try_into()
+            .map_err(|err| journal_error("state changes too large", err))?;
+        let mut record = Vec::with_capacity(RECORD_HEADER_SIZE + payload.len());
+        record.extend(length.to_le_bytes());
+        record.extend(Hash::compute_from(&payload).to_bytes());
+        record.extend(payload);
+        self.file
+            .write_all(&record)
+            .and_then(|_| self.file.sync_data())
+            .map_err(|err| journal_error("could not write the journal", err))
+    }
+
+    /// Reads the journaled changes, up to the first torn or corrupted record which is truncated
+    fn read_records(
+        &mut self,
+        thread_count: u8,
+        max_key_length: u8,
+    ) -> Result<Vec<(Slot, StateChanges)>, FinalStateError> {
+        let mut data = Vec::new();
+        File::open(self.path.join(JOURNAL_FILE))
+            .and_then(|mut file| file.read_to_end(&mut data))
+            .map_err(|err| journal_error("could not read the journal", err))?;
+        // the journal was written by this node, each record being checked against its hash
+        let slot_deserializer = SlotDeserializer::new(
+            (Included(0), Included(u64::MAX)),
+            (Included(0), Excluded(thread_count)),
+        );
+        let changes_deserializer = StateChangesDeserializer::new(
+            thread_count,
+            u64::MAX,
+            u64::MAX,
+            u64::MAX,
+            max_key_length,
+            u64::MAX,
+            u64::MAX,
+            u64::MAX,
+            u64::MAX,
+            u64::MAX,
+            u64::MAX,
+        );
+        let mut records = Vec::new();
+        let mut cursor = 0;
+        while data.len() - cursor >= RECORD_HEADER_SIZE {
+            let length =
+                u32::from_le_bytes(data[cursor..cursor + 4].try_into().expect("4 bytes")) as usize;
+            let payload_start = cursor + RECORD_HEADER_SIZE;
+            if data.len() - payload_start < length {
+                break;
+            }
+            let payload = &data[payload_start..payload_start + length];
+            if Hash::compute_from(payload).to_bytes()[..] != data[cursor + 4..payload_start] {
+                break;
+            }
+            let record = slot_deserializer
+                .deserialize::<DeserializeError>(payload)
+                .and_then(|(rest, slot)| {
+                    changes_deserializer
+                        .deserialize::<DeserializeError>(rest)
+                        .map(|(rest, changes)| (rest, (slot, changes)))
+                });
+            match record {
+                Ok((rest, record)) if rest.is_empty() => records.push(record),
+                _ => break,
+            }
+            cursor = payload_start + length;
+        }
+        if cursor < data.len() {
+            warn!(
+                "ignoring {} bytes of torn or corrupted records at the end of the final state journal",
+                data.len() - cursor
+            );
+            self.file
+                .set_len(cursor as u64)
+                .map_err(|err| journal_error("could not truncate the journal", err))?;
+        }
+        Ok(records)
+    }
+
+    /// Deletes the checkpoint and the journaled changes
+    pub fn reset(&mut self) {
+        let checkpoint_path = self.path.join(CHECKPOINT_DIR);
+        if checkpoint_path.exists() {
+            if let Err(err) = std::fs::remove_dir_all(&checkpoint_path) {
+                warn!(
+                    "could not delete the final state journal checkpoint: {}",
+                    err
+                );
+            }
+        }
+        if let Err(err) = self.file.set_len(0) {
+            warn!("could not truncate the final state journal: {}", err);
+        }
+        self.slots_since_checkpoint = None;
+    }
+}
+
+impl FinalState {
+    /// Recovers the final state from its journal: loads the checkpoint and replays the journaled changes.
+    /// Returns the slot of the recovered state.
+    ///
+    /// The ledger must have been restored beforehand with `restore_journal_ledger`,
+    /// before the creation of the final ledger.
+    pub fn recover_from_journal(&mut self) -> Result<Slot, FinalStateError> {
+        let mut journal = self.journal.take().ok_or_else(|| {
+            FinalStateError::JournalError("the final state journal is disabled".to_string())
+        })?;
+        let result = self.replay_journal(&mut journal);
+        self.journal = Some(journal);
+        result
+    }
+
+    fn replay_journal(&mut self, journal: &mut FinalStateJournal) -> Result<Slot, FinalStateError> {
+        let checkpoint_path = complete_checkpoint(&journal.path)?.ok_or_else(|| {
+            FinalStateError::JournalError("no checkpoint to recover from".to_string())
+        })?;
+        let manifest = self.restore_backup(&checkpoint_path)?;
+        let records = journal.read_records(
+            self.config.thread_count,
+            self.config.ledger_config.max_key_length,
+        )?;
+        let mut replayed_count = 0;
+        for (slot, changes) in records {
+            // records older than the checkpoint are left by a crash during the checkpoint
+            if slot <= self.slot {
+                continue;
+            }
+            let next_slot = self
+                .slot
+                .get_next_slot(self.config.thread_count)
+                .map_err(|err| journal_error("invalid journaled slot", err))?;
+            if slot != next_slot {
+                warn!(
+                    "final state journal interrupted at slot {}, the next journaled slot being {}",
+                    self.slot, slot
+                );
+                break;
+            }
+            self.finalize(slot, changes);
+            replayed_count += 1;
+        }
+        info!(
+            "final state recovered from the checkpoint at slot {} and {} journaled slots",
+            manifest.slot, replayed_count
+        );
+        Ok(self.slot)
+    }
+}
@@ -17,6 +17,15 @@
 //! It can be manipulated using `StateChanges` (see `state_changes.rs`).
 //! The `FinalState` is bootstrapped using tooling available in bootstrap.rs
 //!
+//! ## `backup.rs`
+//! Creates consistent backups of the final state while the node is running, and restores them.
+//!
+//! ## `genesis.rs`
+//! Builds and validates the genesis of a network, saved as a hash-committed artifact.
+//!
+//! ## `journal.rs`
+//! Journals the final state changes before they are applied, so that the state can be recovered after a crash.
+//!
 //! ## `state_changes.rs`
 //! Represents a list of changes the final state.
 //! It can be modified, combined or applied to the final ledger.
@@ -39,14 +48,20 @@
 #![feature(async_closure)]
 #![feature(map_try_insert)]
 
+mod backup;
 mod config;
 mod error;
 mod final_state;
+mod genesis;
+mod journal;
 mod state_changes;
 
+pub use backup::{read_manifest, restore_ledger_backup, BackupManifest};
 pub use config::FinalStateConfig;
 pub use error::FinalStateError;
-pub use final_state::FinalState;
+pub use final_state::{FinalState, FinalStateHashes};
+pub use genesis::{Genesis, GenesisBuilder, GenesisCredit};
+pub use journal::restore_journal_ledger;
 pub use state_changes::{StateChanges, StateChangesDeserializer, StateChangesSerializer};
 
 #[cfg(test)]
@@ -8,8 +8,9 @@ use massa_async_pool::AsyncPool;
 use massa_executed_ops::ExecutedOps;
 use massa_hash::{Hash, HASH_SIZE_BYTES};
 use massa_ledger_exports::LedgerController;
-use massa_models::slot::Slot;
+use massa_models::slot::{SharedSlot, Slot};
 use massa_pos_exports::PoSFinalState;
+use massa_versioning::MipStore;
 
 use crate::{FinalState, FinalStateConfig, StateChanges};
 
@@ -24,14 +25,18 @@ pub fn create_final_state(
     executed_ops: ExecutedOps,
 ) -> FinalState {
     FinalState {
-        config,
         slot,
+        shared_slot: SharedSlot::new(slot),
         ledger,
         async_pool,
         changes_history,
         pos_state,
         executed_ops,
+        mip_store: MipStore::new(config.mip_store_config.clone()),
+        config,
         final_state_hash: Hash::from_bytes(&[0; HASH_SIZE_BYTES]),
+        journal: None,
+        genesis: None,
     }
 }
 
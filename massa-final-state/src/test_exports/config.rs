@@ -15,22 +15,27 @@ use massa_models::{
         DEFERRED_CREDITS_BOOTSTRAP_PART_SIZE, EXECUTED_OPS_BOOTSTRAP_PART_SIZE, PERIODS_PER_CYCLE,
         POS_SAVED_CYCLES, THREAD_COUNT,
     },
-    slot::Slot,
+    slot::{SharedSlot, Slot},
 };
 use massa_pos_exports::{PoSConfig, PoSFinalState};
+use massa_versioning::{MipStore, MipStoreConfig};
 
 impl FinalState {
     /// Create a final stat
     pub fn create_final_state(pos_state: PoSFinalState, config: FinalStateConfig) -> Self {
         FinalState {
             slot: Slot::new(0, 0),
+            shared_slot: SharedSlot::new(Slot::new(0, 0)),
             ledger: Box::new(FinalLedger::new(config.ledger_config.clone())),
             async_pool: AsyncPool::new(config.async_pool_config.clone()),
             pos_state,
             executed_ops: ExecutedOps::new(config.executed_ops_config.clone()),
+            mip_store: MipStore::new(config.mip_store_config.clone()),
             changes_history: Default::default(),
             config,
             final_state_hash: Hash::from_bytes(&[0; HASH_SIZE_BYTES]),
+            journal: None,
+            genesis: None,
         }
     }
 }
@@ -49,13 +54,23 @@ impl Default for FinalStateConfig {
                 periods_per_cycle: PERIODS_PER_CYCLE,
                 thread_count: THREAD_COUNT,
                 cycle_history_length: POS_SAVED_CYCLES,
+                cycle_info_retention: 0,
+                deferred_credits_retention: 0,
                 credits_bootstrap_part_size: DEFERRED_CREDITS_BOOTSTRAP_PART_SIZE,
             },
             final_history_length: 10,
             thread_count: 2,
             periods_per_cycle: 100,
             initial_rolls_path: PathBuf::new(),
+            genesis_path: None,
             initial_seed_string: "".to_string(),
+            journal_path: None,
+            journal_checkpoint_interval: 256,
+            mip_store_config: MipStoreConfig {
+                mips: Vec::new(),
+                block_count_considered: 100,
+                activation_threshold_percent: 75,
+            },
         }
     }
 }
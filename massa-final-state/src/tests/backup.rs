@@ -0,0 +1,66 @@
+//! Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use super::create_final_state;
+use crate::{restore_ledger_backup, test_exports::assert_eq_final_state};
+use massa_executed_ops::ExecutedOpsChanges;
+use massa_hash::Hash;
+use massa_ledger_exports::{LedgerChanges, LedgerEntry, SetUpdateOrDelete};
+use massa_models::{address::Address, amount::Amount, operation::OperationId, slot::Slot};
+use massa_signature::KeyPair;
+use std::str::FromStr;
+use tempfile::TempDir;
+
+#[test]
+fn test_backup_and_restore() {
+    let temp_dir = TempDir::new().unwrap();
+    let slot = Slot::new(3, 0);
+
+    // fill a final state
+    let mut final_state = create_final_state(&temp_dir.path().join("ledger"));
+    let mut ledger_changes = LedgerChanges::default();
+    let address = Address::from_public_key(&KeyPair::generate().get_public_key());
+    ledger_changes.0.insert(
+        address,
+        SetUpdateOrDelete::Set(LedgerEntry {
+            balance: Amount::from_str("42").unwrap(),
+            ..Default::default()
+        }),
+    );
+    final_state.ledger.apply_changes(ledger_changes, slot);
+    final_state.pos_state.create_initial_cycle();
+    let mut executed_ops = ExecutedOpsChanges::default();
+    executed_ops.insert(
+        OperationId::new(Hash::compute_from(b"executed operation")),
+        (true, slot),
+    );
+    final_state.executed_ops.apply_changes(executed_ops, slot);
+    final_state.slot = slot;
+    final_state.compute_state_hash_at_slot(slot);
+
+    // back it up
+    let backup_path = temp_dir.path().join("backup");
+    let manifest = final_state.create_backup(&backup_path).unwrap();
+    assert_eq!(manifest.slot, slot);
+    assert_eq!(manifest.final_state_hash, final_state.final_state_hash);
+    assert_eq!(manifest.ledger_hash, final_state.ledger.get_ledger_hash());
+    // an existing backup is never overwritten
+    assert!(final_state.create_backup(&backup_path).is_err());
+
+    // restore it in a new final state
+    let restored_ledger_path = temp_dir.path().join("restored_ledger");
+    restore_ledger_backup(&backup_path, &restored_ledger_path).unwrap();
+    let mut restored_state = create_final_state(&restored_ledger_path);
+    assert_eq!(
+        restored_state.restore_backup(&backup_path).unwrap(),
+        manifest
+    );
+    assert_eq!(
+        restored_state.final_state_hash,
+        final_state.final_state_hash
+    );
+    assert_eq_final_state(&final_state, &restored_state);
+
+    // a backup does not apply to another ledger
+    let mut other_state = create_final_state(&temp_dir.path().join("other_ledger"));
+    assert!(other_state.restore_backup(&backup_path).is_err());
+}
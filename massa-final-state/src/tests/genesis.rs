@@ -0,0 +1,111 @@
+//! Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use super::create_final_state_config;
+use crate::{FinalState, FinalStateConfig, FinalStateError, Genesis, GenesisBuilder};
+use massa_async_pool::AsyncMessage;
+use massa_ledger_exports::LedgerEntry;
+use massa_ledger_worker::FinalLedger;
+use massa_models::{address::Address, amount::Amount, config::THREAD_COUNT, slot::Slot};
+use massa_pos_exports::test_exports::MockSelectorController;
+use massa_signature::KeyPair;
+use std::str::FromStr;
+use tempfile::TempDir;
+
+fn open_final_state(config: FinalStateConfig) -> Result<FinalState, FinalStateError> {
+    let ledger = FinalLedger::new(config.ledger_config.clone());
+    let (selector_controller, _) = MockSelectorController::new_with_receiver();
+    FinalState::new(config, Box::new(ledger), selector_controller)
+}
+
+#[test]
+fn test_genesis_builder() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = create_final_state_config(&temp_dir.path().join("ledger"));
+    let staker = Address::from_public_key(&KeyPair::generate().get_public_key());
+    let user = Address::from_public_key(&KeyPair::generate().get_public_key());
+    let genesis_slot = Slot::new(0, THREAD_COUNT - 1);
+    let message = AsyncMessage::new_with_hash(
+        genesis_slot,
+        0,
+        staker,
+        user,
+        "receive".to_string(),
+        100_000,
+        Amount::zero(),
+        Amount::zero(),
+        Slot::new(1, 0),
+        Slot::new(10, 0),
+        Vec::new(),
+        None,
+    );
+    let genesis = GenesisBuilder::new()
+        .ledger_entry(
+            staker,
+            LedgerEntry {
+                balance: Amount::from_str("1000").unwrap(),
+                ..Default::default()
+            },
+        )
+        .ledger_entry(
+            user,
+            LedgerEntry {
+                balance: Amount::from_str("10").unwrap(),
+                ..Default::default()
+            },
+        )
+        .rolls(staker, 5)
+        .deferred_credit(Slot::new(5, 1), user, Amount::from_str("50").unwrap())
+        .async_message(message.clone())
+        .build(&config)
+        .unwrap();
+
+    // the artifact is checked when loaded
+    let genesis_path = temp_dir.path().join("genesis.json");
+    genesis.save(&genesis_path).unwrap();
+    assert_eq!(Genesis::load(&genesis_path, &config).unwrap(), genesis);
+    let tampered = std::fs::read_to_string(&genesis_path)
+        .unwrap()
+        .replace("\"1000\"", "\"2000\"");
+    std::fs::write(temp_dir.path().join("tampered.json"), tampered).unwrap();
+    Genesis::load(&temp_dir.path().join("tampered.json"), &config).unwrap_err();
+
+    // it replaces the initial ledger and rolls files
+    let mut final_state = open_final_state(FinalStateConfig {
+        genesis_path: Some(genesis_path),
+        ..config.clone()
+    })
+    .unwrap();
+    assert_eq!(
+        final_state.pos_state.initial_rolls,
+        [(staker, 5)].into_iter().collect()
+    );
+    final_state.init_genesis_state().unwrap();
+    assert_eq!(
+        final_state.ledger.get_balance(&user),
+        Some(Amount::from_str("10").unwrap())
+    );
+    assert_eq!(
+        final_state
+            .pos_state
+            .deferred_credits
+            .get_address_deferred_credit_for_slot(&user, &Slot::new(5, 1)),
+        Some(Amount::from_str("50").unwrap())
+    );
+    assert_eq!(final_state.async_pool.get_messages(0, 10).0, vec![message]);
+    assert_eq!(final_state.pos_state.cycle_history.len(), 1);
+
+    // invalid content
+    GenesisBuilder::new()
+        .rolls(user, 1)
+        .build(&config)
+        .unwrap_err();
+    GenesisBuilder::new()
+        .deferred_credit(genesis_slot, user, Amount::from_str("1").unwrap())
+        .build(&config)
+        .unwrap_err();
+    GenesisBuilder::new()
+        .ledger_entry(user, LedgerEntry::default())
+        .ledger_entry(user, LedgerEntry::default())
+        .build(&config)
+        .unwrap_err();
+}
@@ -0,0 +1,70 @@
+//! Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use super::create_journaled_final_state;
+use crate::{restore_journal_ledger, test_exports::assert_eq_final_state, StateChanges};
+use massa_ledger_exports::{LedgerChanges, LedgerEntry, SetUpdateOrDelete};
+use massa_models::{address::Address, amount::Amount, config::THREAD_COUNT, slot::Slot};
+use massa_signature::KeyPair;
+use std::io::Write;
+use std::str::FromStr;
+use tempfile::TempDir;
+
+fn balance_changes(address: Address, balance: &str) -> StateChanges {
+    let mut ledger_changes = LedgerChanges::default();
+    ledger_changes.0.insert(
+        address,
+        SetUpdateOrDelete::Set(LedgerEntry {
+            balance: Amount::from_str(balance).unwrap(),
+            ..Default::default()
+        }),
+    );
+    StateChanges {
+        ledger_changes,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_recover_from_journal() {
+    let temp_dir = TempDir::new().unwrap();
+    let journal_path = temp_dir.path().join("journal");
+    let address = Address::from_public_key(&KeyPair::generate().get_public_key());
+
+    // finalize a few slots in a journaled final state
+    let mut final_state =
+        create_journaled_final_state(&temp_dir.path().join("ledger"), Some(&journal_path));
+    final_state.pos_state.create_initial_cycle();
+    let mut slot = final_state.slot;
+    for balance in ["1", "2", "3"] {
+        slot = slot.get_next_slot(THREAD_COUNT).unwrap();
+        final_state.finalize(slot, balance_changes(address, balance));
+    }
+
+    // a record torn by a crash is ignored
+    std::fs::OpenOptions::new()
+        .append(true)
+        .open(journal_path.join("changes.journal"))
+        .unwrap()
+        .write_all(&[42; 20])
+        .unwrap();
+
+    // recover it in a new final state
+    let recovered_ledger_path = temp_dir.path().join("recovered_ledger");
+    assert!(restore_journal_ledger(&journal_path, &recovered_ledger_path).unwrap());
+    let mut recovered_state =
+        create_journaled_final_state(&recovered_ledger_path, Some(&journal_path));
+    assert_eq!(recovered_state.recover_from_journal().unwrap(), slot);
+    assert_eq!(
+        recovered_state.ledger.get_balance(&address),
+        Some(Amount::from_str("3").unwrap())
+    );
+    assert_eq!(
+        recovered_state.final_state_hash,
+        final_state.final_state_hash
+    );
+    assert_eq_final_state(&final_state, &recovered_state);
+
+    // a reset journal has nothing to recover from
+    recovered_state.reset();
+    assert!(!restore_journal_ledger(&journal_path, &temp_dir.path().join("other_ledger")).unwrap());
+}
@@ -1 +1,45 @@
 //! Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use crate::{FinalState, FinalStateConfig};
+use massa_ledger_exports::LedgerConfig;
+use massa_ledger_worker::FinalLedger;
+use massa_models::config::THREAD_COUNT;
+use massa_pos_exports::test_exports::MockSelectorController;
+use std::path::Path;
+
+mod backup;
+mod genesis;
+mod journal;
+mod retention;
+mod stakers;
+
+pub(crate) fn create_final_state(ledger_path: &Path) -> FinalState {
+    create_journaled_final_state(ledger_path, None)
+}
+
+pub(crate) fn create_journaled_final_state(
+    ledger_path: &Path,
+    journal_path: Option<&Path>,
+) -> FinalState {
+    let config = FinalStateConfig {
+        journal_path: journal_path.map(Path::to_path_buf),
+        ..create_final_state_config(ledger_path)
+    };
+    let ledger = FinalLedger::new(config.ledger_config.clone());
+    let (selector_controller, _) = MockSelectorController::new_with_receiver();
+    FinalState::new(config, Box::new(ledger), selector_controller).unwrap()
+}
+
+pub(crate) fn create_final_state_config(ledger_path: &Path) -> FinalStateConfig {
+    FinalStateConfig {
+        ledger_config: LedgerConfig {
+            disk_ledger_path: ledger_path.to_path_buf(),
+            ..Default::default()
+        },
+        thread_count: THREAD_COUNT,
+        initial_rolls_path: "../massa-node/base_config/initial_rolls.json".into(),
+        genesis_path: None,
+        journal_path: None,
+        ..Default::default()
+    }
+}
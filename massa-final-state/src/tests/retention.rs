@@ -0,0 +1,83 @@
+//! Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use super::create_final_state;
+use massa_models::{address::Address, amount::Amount, config::THREAD_COUNT, slot::Slot};
+use massa_pos_exports::PoSChanges;
+use massa_signature::KeyPair;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use tempfile::TempDir;
+
+#[test]
+fn test_executed_credits_retention() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut final_state = create_final_state(&temp_dir.path().join("ledger"));
+    let pos_state = &mut final_state.pos_state;
+    pos_state.config.deferred_credits_retention = 3;
+    pos_state.create_initial_cycle();
+    let address = Address::from_public_key(&KeyPair::generate().get_public_key());
+    let amount = Amount::from_str("10").unwrap();
+
+    // a credit is scheduled, then executed
+    let mut changes = PoSChanges::default();
+    changes
+        .deferred_credits
+        .insert(address, Slot::new(2, 0), amount);
+    pos_state
+        .apply_changes(changes, Slot::new(1, 0), false)
+        .unwrap();
+    let mut changes = PoSChanges::default();
+    changes
+        .deferred_credits
+        .insert(address, Slot::new(2, 0), Amount::default());
+    pos_state
+        .apply_changes(changes, Slot::new(2, 0), false)
+        .unwrap();
+    assert!(pos_state.deferred_credits.credits.is_empty());
+    assert_eq!(
+        pos_state.get_address_executed_credits(&address),
+        BTreeMap::from([(Slot::new(2, 0), amount)])
+    );
+    assert_eq!(
+        pos_state.get_history_ranges().executed_credits,
+        Some((Slot::new(2, 0), Slot::new(2, 0)))
+    );
+
+    // the executed credit is kept for `deferred_credits_retention` periods
+    pos_state
+        .apply_changes(PoSChanges::default(), Slot::new(5, 0), false)
+        .unwrap();
+    assert_eq!(pos_state.get_address_executed_credits(&address).len(), 1);
+    pos_state
+        .apply_changes(PoSChanges::default(), Slot::new(6, 0), false)
+        .unwrap();
+    assert!(pos_state.get_address_executed_credits(&address).is_empty());
+    assert_eq!(pos_state.get_history_ranges().executed_credits, None);
+}
+
+#[test]
+fn test_cycle_info_retention() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut final_state = create_final_state(&temp_dir.path().join("ledger"));
+    let pos_state = &mut final_state.pos_state;
+    pos_state.config.periods_per_cycle = 2;
+    pos_state.config.cycle_info_retention = 2;
+    pos_state.create_initial_cycle();
+
+    // finalize the slots of cycles 0 to 9, genesis excluded
+    let mut slot = Slot::new(1, 0);
+    while slot.get_cycle(2) < 10 {
+        let mut changes = PoSChanges::default();
+        changes.seed_bits.push(true);
+        pos_state.apply_changes(changes, slot, false).unwrap();
+        slot = slot.get_next_slot(THREAD_COUNT).unwrap();
+    }
+
+    let ranges = pos_state.get_history_ranges();
+    let history_length = pos_state.config.cycle_history_length as u64;
+    assert_eq!(ranges.cycle_history, Some((10 - history_length, 9)));
+    assert_eq!(ranges.cycles, Some((8 - history_length, 9)));
+    assert!(pos_state.get_cycle_info(8 - history_length).is_some());
+    assert!(pos_state.get_cycle_info(7 - history_length).is_none());
+    assert_eq!(pos_state.archived_cycles.len(), 2);
+}
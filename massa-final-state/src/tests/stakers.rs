@@ -0,0 +1,41 @@
+//! Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use super::create_final_state;
+use massa_models::address::Address;
+use massa_signature::KeyPair;
+use tempfile::TempDir;
+
+#[test]
+fn test_cycle_stakers_pagination_and_filter() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut final_state = create_final_state(&temp_dir.path().join("ledger"));
+    let addresses: Vec<Address> = (0..5)
+        .map(|_| Address::from_public_key(&KeyPair::generate().get_public_key()))
+        .collect();
+    // the first cycles use the initial rolls
+    final_state.pos_state.initial_rolls = addresses
+        .iter()
+        .zip([3, 10, 1, 7, 10])
+        .map(|(addr, rolls)| (*addr, rolls))
+        .collect();
+
+    let (all, total_count) = final_state.pos_state.get_cycle_stakers(0, 0, 0, usize::MAX);
+    assert_eq!(total_count, 5);
+    let rolls: Vec<u64> = all.iter().map(|(_, rolls)| *rolls).collect();
+    assert_eq!(rolls, vec![10, 10, 7, 3, 1]);
+
+    // pages are cut from the sorted list, the total count ignores the pagination
+    let (page, total_count) = final_state.pos_state.get_cycle_stakers(0, 0, 2, 2);
+    assert_eq!(total_count, 5);
+    assert_eq!(page, all[2..4].to_vec());
+
+    // the total count takes the roll filter into account
+    let (filtered, total_count) = final_state.pos_state.get_cycle_stakers(2, 5, 0, 10);
+    assert_eq!(total_count, 3);
+    assert_eq!(filtered, all[..3].to_vec());
+
+    // unknown lookback cycle
+    let (none, total_count) = final_state.pos_state.get_cycle_stakers(100, 0, 0, 10);
+    assert!(none.is_empty());
+    assert_eq!(total_count, 0);
+}
@@ -1,10 +1,11 @@
 use massa_hash::Hash;
 use massa_models::{
-    address::Address, amount::Amount, bytecode::Bytecode, error::ModelsError, slot::Slot,
-    streaming_step::StreamingStep,
+    address::Address, amount::Amount, bytecode::Bytecode, datastore::DatastoreChunk,
+    error::ModelsError, slot::Slot, streaming_step::StreamingStep,
 };
 use std::collections::BTreeSet;
 use std::fmt::Debug;
+use std::path::Path;
 
 use crate::{Key, LedgerChanges, LedgerError};
 
@@ -43,12 +44,30 @@ pub trait LedgerController: Send + Sync + Debug {
     /// A copy of the datastore value, or `None` if the ledger entry or datastore entry was not found
     fn get_data_entry(&self, addr: &Address, key: &[u8]) -> Option<Vec<u8>>;
 
+    /// Gets at most `length` bytes from `offset` of the value of a datastore entry for a given address,
+    /// without copying the rest of the value.
+    ///
+    /// # Returns
+    /// The chunk of the datastore value, or `None` if the ledger entry or datastore entry was not found
+    fn get_data_entry_chunk(
+        &self,
+        addr: &Address,
+        key: &[u8],
+        offset: u64,
+        length: u64,
+    ) -> Option<DatastoreChunk>;
+
     /// Get every key of the datastore for a given address.
     ///
     /// # Returns
     /// A `BTreeSet` of the datastore keys
     fn get_datastore_keys(&self, addr: &Address) -> Option<BTreeSet<Vec<u8>>>;
 
+    /// Get every address with a bytecode, a datastore entry that is not reserved to the ledger,
+    /// or a record of unpaid storage rent.
+    /// Used to charge the storage rent at the start of each cycle.
+    fn get_addresses_with_storage(&self) -> BTreeSet<Address>;
+
     /// Get the current disk ledger hash
     fn get_ledger_hash(&self) -> Hash;
 
@@ -70,6 +89,16 @@ pub trait LedgerController: Send + Sync + Debug {
     /// USED FOR BOOTSTRAP ONLY
     fn reset(&mut self);
 
+    /// Create a consistent copy of the disk ledger in `path`, which must not exist yet
+    ///
+    /// Used for backups
+    fn create_checkpoint(&self, path: &Path) -> Result<(), LedgerError>;
+
+    /// Compact the disk ledger, reclaiming the space of the overwritten and deleted entries
+    ///
+    /// Heavy on the disk: run as a background job
+    fn compact(&self);
+
     /// Get every address and their corresponding balance.
     ///
     /// IMPORTANT: This should only be used for debug and test purposes.
@@ -11,6 +11,7 @@ use massa_models::{
     address::Address,
     amount::{Amount, AmountDeserializer},
     bytecode::{Bytecode, BytecodeDeserializer},
+    datastore::DatastoreChunk,
     error::ModelsError,
     slot::Slot,
     streaming_step::StreamingStep,
@@ -19,6 +20,7 @@ use massa_serialization::{DeserializeError, Deserializer};
 use nom::AsBytes;
 use std::collections::{BTreeSet, HashMap};
 use std::ops::Bound::Included;
+use std::path::Path;
 
 /// Represents a final ledger associating addresses to their balances, bytecode and data.
 /// The final ledger is part of the final state which is attached to a final slot, can be bootstrapped and allows others to bootstrap.
@@ -143,6 +145,25 @@ impl LedgerController for FinalLedger {
             .get_sub_entry(addr, LedgerSubEntry::Datastore(key.to_owned()))
     }
 
+    /// Gets at most `length` bytes from `offset` of the value of a datastore entry for a given address.
+    ///
+    /// # Returns
+    /// The chunk of the datastore value, or `None` if the ledger entry or datastore entry was not found
+    fn get_data_entry_chunk(
+        &self,
+        addr: &Address,
+        key: &[u8],
+        offset: u64,
+        length: u64,
+    ) -> Option<DatastoreChunk> {
+        self.sorted_ledger.get_sub_entry_chunk(
+            addr,
+            LedgerSubEntry::Datastore(key.to_owned()),
+            offset,
+            length,
+        )
+    }
+
     /// Get every key of the datastore for a given address.
     ///
     /// # Returns
@@ -151,6 +172,12 @@ impl LedgerController for FinalLedger {
         self.sorted_ledger.get_datastore_keys(addr)
     }
 
+    /// Get every address with a bytecode, a datastore entry that is not reserved to the ledger,
+    /// or a record of unpaid storage rent.
+    fn get_addresses_with_storage(&self) -> BTreeSet<Address> {
+        self.sorted_ledger.get_addresses_with_storage()
+    }
+
     /// Get the current disk ledger hash
     fn get_ledger_hash(&self) -> Hash {
         self.sorted_ledger.get_ledger_hash()
@@ -186,6 +213,22 @@ impl LedgerController for FinalLedger {
         self.sorted_ledger.reset();
     }
 
+    /// Create a checkpoint of the disk ledger.
+    ///
+    /// Used for backups.
+    fn create_checkpoint(&self, path: &Path) -> Result<(), LedgerError> {
+        self.sorted_ledger
+            .create_checkpoint(path)
+            .map_err(|err| LedgerError::FileError(err.to_string()))
+    }
+
+    /// Compact the disk ledger.
+    ///
+    /// Run as a background job.
+    fn compact(&self) {
+        self.sorted_ledger.compact()
+    }
+
     /// Get every address and their corresponding balance.
     ///
     /// IMPORTANT: This should only be used for debug and test purposes.
@@ -7,7 +7,8 @@ use massa_ledger_exports::*;
 use massa_models::{
     address::Address,
     amount::AmountSerializer,
-    bytecode::BytecodeSerializer,
+    bytecode::{Bytecode, BytecodeSerializer},
+    datastore::{is_reserved_datastore_key, DatastoreChunk, STORAGE_RENT_DUE_KEY},
     error::ModelsError,
     serialization::{VecU8Deserializer, VecU8Serializer},
     slot::{Slot, SlotSerializer},
@@ -16,12 +17,13 @@ use massa_models::{
 use massa_serialization::{DeserializeError, Deserializer, Serializer, U64VarIntSerializer};
 use nom::multi::many0;
 use nom::sequence::tuple;
+use rayon::prelude::*;
 use rocksdb::{
-    ColumnFamily, ColumnFamilyDescriptor, Direction, IteratorMode, Options, ReadOptions,
+    checkpoint::Checkpoint, ColumnFamilyDescriptor, Direction, IteratorMode, Options, ReadOptions,
     WriteBatch, DB,
 };
 use std::ops::Bound;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::{collections::BTreeMap, fmt::Debug};
 use std::{
@@ -34,6 +36,8 @@ use massa_models::amount::{Amount, AmountDeserializer};
 
 const LEDGER_CF: &str = "ledger";
 const METADATA_CF: &str = "metadata";
+const HASHES_CF: &str = "hashes";
+const STORAGE_CF: &str = "storage";
 const OPEN_ERROR: &str = "critical: rocksdb open operation failed";
 const CRUD_ERROR: &str = "critical: rocksdb crud operation failed";
 const CF_ERROR: &str = "critical: rocksdb column family operation failed";
@@ -43,6 +47,7 @@ const KEY_SER_ERROR: &str = "critical: key serialization failed";
 const KEY_LEN_SER_ERROR: &str = "critical: key length serialization failed";
 const SLOT_KEY: &[u8; 1] = b"s";
 const LEDGER_HASH_KEY: &[u8; 1] = b"h";
+const STORAGE_INDEXED_KEY: &[u8; 1] = b"i";
 const LEDGER_HASH_INITIAL_BYTES: &[u8; 32] = &[0; HASH_SIZE_BYTES];
 
 /// Ledger sub entry enum
@@ -78,7 +83,8 @@ pub(crate) struct LedgerDB {
     amount_serializer: AmountSerializer,
     bytecode_serializer: BytecodeSerializer,
     slot_serializer: SlotSerializer,
-    len_serializer: U64VarIntSerializer,
+    /// serialization of an empty bytecode, which is not storage
+    empty_bytecode: Vec<u8>,
     ledger_part_size_message_bytes: u64,
     #[cfg(feature = "testing")]
     amount_deserializer: AmountDeserializer,
@@ -96,8 +102,8 @@ pub struct LedgerBatch {
     write_batch: WriteBatch,
     // Ledger hash state in the current batch
     ledger_hash: Hash,
-    // Added entry hashes in the current batch
-    aeh_list: BTreeMap<Vec<u8>, Hash>,
+    // Last value written to each serialized key in the current batch, `None` if deleted
+    entry_writes: BTreeMap<Vec<u8>, Option<Vec<u8>>>,
 }
 
 impl LedgerBatch {
@@ -105,7 +111,7 @@ impl LedgerBatch {
         Self {
             write_batch: WriteBatch::default(),
             ledger_hash,
-            aeh_list: BTreeMap::new(),
+            entry_writes: BTreeMap::new(),
         }
     }
 }
@@ -131,11 +137,20 @@ impl LedgerDB {
             vec![
                 ColumnFamilyDescriptor::new(LEDGER_CF, Options::default()),
                 ColumnFamilyDescriptor::new(METADATA_CF, Options::default()),
+                ColumnFamilyDescriptor::new(HASHES_CF, Options::default()),
+                ColumnFamilyDescriptor::new(STORAGE_CF, Options::default()),
             ],
         )
         .expect(OPEN_ERROR);
 
-        LedgerDB {
+        let bytecode_serializer = BytecodeSerializer::new();
+        let mut empty_bytecode = Vec::new();
+        // Bytecode serialization never fails
+        bytecode_serializer
+            .serialize(&Bytecode::default(), &mut empty_bytecode)
+            .unwrap();
+
+        let ledger_db = LedgerDB {
             db,
             thread_count,
             key_serializer: KeySerializer::new(true),
@@ -143,16 +158,18 @@ impl LedgerDB {
             key_deserializer: KeyDeserializer::new(max_datastore_key_length, true),
             key_deserializer_db: KeyDeserializer::new(max_datastore_key_length, false),
             amount_serializer: AmountSerializer::new(),
-            bytecode_serializer: BytecodeSerializer::new(),
+            bytecode_serializer,
             slot_serializer: SlotSerializer::new(),
-            len_serializer: U64VarIntSerializer::new(),
+            empty_bytecode,
             ledger_part_size_message_bytes,
             #[cfg(feature = "testing")]
             amount_deserializer: AmountDeserializer::new(
                 Bound::Included(Amount::MIN),
                 Bound::Included(Amount::MAX),
             ),
-        }
+        };
+        ledger_db.index_storage();
+        ledger_db
     }
 
     /// Loads the initial disk ledger
@@ -244,6 +261,30 @@ impl LedgerDB {
         self.db.get_cf(handle, serialized_key).expect(CRUD_ERROR)
     }
 
+    /// Get at most `length` bytes from `offset` of the given sub-entry of a given address,
+    /// without copying the rest of its value.
+    ///
+    /// # Returns
+    /// An Option of the chunk of the sub-entry value
+    pub fn get_sub_entry_chunk(
+        &self,
+        addr: &Address,
+        ty: LedgerSubEntry,
+        offset: u64,
+        length: u64,
+    ) -> Option<DatastoreChunk> {
+        let handle = self.db.cf_handle(LEDGER_CF).expect(CF_ERROR);
+        let key = ty.derive_key(addr);
+        let mut serialized_key = Vec::new();
+        self.key_serializer_db
+            .serialize(&key, &mut serialized_key)
+            .expect(KEY_SER_ERROR);
+        self.db
+            .get_pinned_cf(handle, serialized_key)
+            .expect(CRUD_ERROR)
+            .map(|value| DatastoreChunk::from_value(&value, offset, length))
+    }
+
     /// Get every key of the datastore for a given address.
     ///
     /// # Returns
@@ -281,6 +322,26 @@ impl LedgerDB {
         Some(iter.collect())
     }
 
+    /// Get every address with a non-empty bytecode, a datastore entry that is not reserved to the ledger,
+    /// or a record of unpaid storage rent.
+    ///
+    /// # Returns
+    /// A `BTreeSet` of the addresses, read from the index of the storage keys maintained on every write
+    pub fn get_addresses_with_storage(&self) -> BTreeSet<Address> {
+        let handle = self.db.cf_handle(STORAGE_CF).expect(CF_ERROR);
+        self.db
+            .iterator_cf(handle, IteratorMode::Start)
+            .flatten()
+            .map(|(serialized_key, _)| {
+                let (_rest, key) = self
+                    .key_deserializer_db
+                    .deserialize::<DeserializeError>(&serialized_key)
+                    .expect(KEY_DESER_ERROR);
+                key.address
+            })
+            .collect()
+    }
+
     /// Get a part of the disk Ledger.
     /// Mainly used in the bootstrap process.
     ///
@@ -347,7 +408,6 @@ impl LedgerDB {
     /// # Returns
     /// The last key of the inserted entry (this is an optimization to easily keep a reference to the last key)
     pub fn set_ledger_part<'a>(&self, data: &'a [u8]) -> Result<StreamingStep<Key>, ModelsError> {
-        let handle = self.db.cf_handle(LEDGER_CF).expect(CF_ERROR);
         let vec_u8_deserializer =
             VecU8Deserializer::new(Bound::Included(0), Bound::Excluded(u64::MAX));
         let mut last_key: Rc<Option<Key>> = Rc::new(None);
@@ -362,7 +422,7 @@ impl LedgerDB {
             *Rc::get_mut(&mut last_key).ok_or_else(|| {
                 nom::Err::Error(nom::error::Error::new(input, nom::error::ErrorKind::Fail))
             })? = Some(key.clone());
-            self.put_entry_value(handle, &mut batch, &key, &value);
+            self.put_key_value(&mut batch, &key, &value);
             Ok((rest, ()))
         })(data)
         .map_err(|_| ModelsError::SerializeError("Error in deserialization".to_string()))?;
@@ -389,6 +449,12 @@ impl LedgerDB {
         self.db
             .drop_cf(METADATA_CF)
             .expect("Error dropping metadata cf");
+        self.db
+            .drop_cf(HASHES_CF)
+            .expect("Error dropping hashes cf");
+        self.db
+            .drop_cf(STORAGE_CF)
+            .expect("Error dropping storage cf");
         let mut db_opts = Options::default();
         db_opts.set_error_if_exists(true);
         self.db
@@ -397,13 +463,84 @@ impl LedgerDB {
         self.db
             .create_cf(METADATA_CF, &db_opts)
             .expect("Error creating metadata cf");
+        self.db
+            .create_cf(HASHES_CF, &db_opts)
+            .expect("Error creating hashes cf");
+        self.db
+            .create_cf(STORAGE_CF, &db_opts)
+            .expect("Error creating storage cf");
+        self.index_storage();
+    }
+
+    /// Create a checkpoint of the database in `path`, which must not exist yet.
+    /// The files of the checkpoint are hard links to the database files when they are on the same filesystem.
+    pub fn create_checkpoint(&self, path: &Path) -> Result<(), rocksdb::Error> {
+        Checkpoint::new(&self.db)?.create_checkpoint(path)
+    }
+
+    /// Compact the entries and hashes of the database, which can take a while on a large ledger.
+    pub fn compact(&self) {
+        for cf in [LEDGER_CF, HASHES_CF] {
+            let handle = self.db.cf_handle(cf).expect(CF_ERROR);
+            self.db
+                .compact_range_cf(handle, None::<&[u8]>, None::<&[u8]>);
+        }
     }
 }
 
 // Private helpers
 impl LedgerDB {
-    /// Apply the given operation batch to the disk ledger
+    /// Apply the given operation batch to the disk ledger.
+    ///
+    /// The hash of every entry is stored next to it so that overwriting or deleting it does not require
+    /// reading and hashing its previous value. The written entries are hashed in parallel.
+    /// The keys holding storage are indexed so that the addresses with storage are listed without a scan of the ledger.
     fn write_batch(&self, mut batch: LedgerBatch) {
+        let ledger_handle = self.db.cf_handle(LEDGER_CF).expect(CF_ERROR);
+        let hashes_handle = self.db.cf_handle(HASHES_CF).expect(CF_ERROR);
+        let storage_handle = self.db.cf_handle(STORAGE_CF).expect(CF_ERROR);
+        let entry_hashes: Vec<(Option<Hash>, Option<Hash>)> = batch
+            .entry_writes
+            .par_iter()
+            .map(|(serialized_key, value)| {
+                (
+                    self.get_entry_hash(serialized_key),
+                    value
+                        .as_ref()
+                        .map(|value| compute_entry_hash(serialized_key, value)),
+                )
+            })
+            .collect();
+        for ((serialized_key, value), (prev_hash, new_hash)) in
+            batch.entry_writes.into_iter().zip(entry_hashes)
+        {
+            if let Some(prev_hash) = prev_hash {
+                batch.ledger_hash ^= prev_hash;
+            }
+            if self.is_storage(&serialized_key, value.as_deref()) {
+                batch
+                    .write_batch
+                    .put_cf(storage_handle, &serialized_key, b"");
+            } else {
+                batch.write_batch.delete_cf(storage_handle, &serialized_key);
+            }
+            match (value, new_hash) {
+                (Some(value), Some(new_hash)) => {
+                    batch.ledger_hash ^= new_hash;
+                    batch
+                        .write_batch
+                        .put_cf(hashes_handle, &serialized_key, new_hash.to_bytes());
+                    batch
+                        .write_batch
+                        .put_cf(ledger_handle, serialized_key, value);
+                }
+                _ => {
+                    batch.write_batch.delete_cf(hashes_handle, &serialized_key);
+                    batch.write_batch.delete_cf(ledger_handle, serialized_key);
+                }
+            }
+        }
+
         let handle = self.db.cf_handle(METADATA_CF).expect(CF_ERROR);
         batch
             .write_batch
@@ -433,26 +570,81 @@ impl LedgerDB {
         batch.ledger_hash ^= Hash::compute_from(&slot_bytes);
     }
 
-    /// Internal function to put a key & value and perform the ledger hash XORs
-    fn put_entry_value(
-        &self,
-        handle: &ColumnFamily,
-        batch: &mut LedgerBatch,
-        key: &Key,
-        value: &[u8],
-    ) {
+    /// Whether the entry stored at `serialized_key` holds storage, `value` being `None` if it is deleted
+    fn is_storage(&self, serialized_key: &[u8], value: Option<&[u8]>) -> bool {
+        let Some(value) = value else {
+            return false;
+        };
+        let (_rest, key) = self
+            .key_deserializer_db
+            .deserialize::<DeserializeError>(serialized_key)
+            .expect(KEY_DESER_ERROR);
+        match &key.key_type {
+            KeyType::BALANCE => false,
+            KeyType::BYTECODE => value != self.empty_bytecode.as_slice(),
+            KeyType::DATASTORE(datastore_key) => {
+                !is_reserved_datastore_key(datastore_key)
+                    || datastore_key.as_slice() == STORAGE_RENT_DUE_KEY
+            }
+        }
+    }
+
+    /// Index the keys holding storage of a ledger written before they were indexed
+    fn index_storage(&self) {
+        let metadata_handle = self.db.cf_handle(METADATA_CF).expect(CF_ERROR);
+        if self
+            .db
+            .get_pinned_cf(metadata_handle, STORAGE_INDEXED_KEY)
+            .expect(CRUD_ERROR)
+            .is_some()
+        {
+            return;
+        }
+        let ledger_handle = self.db.cf_handle(LEDGER_CF).expect(CF_ERROR);
+        let storage_handle = self.db.cf_handle(STORAGE_CF).expect(CF_ERROR);
+        let mut write_batch = WriteBatch::default();
+        for (serialized_key, value) in self
+            .db
+            .iterator_cf(ledger_handle, IteratorMode::Start)
+            .flatten()
+        {
+            if self.is_storage(&serialized_key, Some(&value[..])) {
+                write_batch.put_cf(storage_handle, serialized_key, b"");
+            }
+        }
+        write_batch.put_cf(metadata_handle, STORAGE_INDEXED_KEY, b"");
+        self.db.write(write_batch).expect(CRUD_ERROR);
+    }
+
+    /// Get the hash of the entry stored at `serialized_key`, if any
+    fn get_entry_hash(&self, serialized_key: &[u8]) -> Option<Hash> {
+        let hashes_handle = self.db.cf_handle(HASHES_CF).expect(CF_ERROR);
+        if let Some(hash_bytes) = self
+            .db
+            .get_pinned_cf(hashes_handle, serialized_key)
+            .expect(CRUD_ERROR)
+        {
+            return Some(Hash::from_bytes(
+                hash_bytes[..].try_into().expect(LEDGER_HASH_ERROR),
+            ));
+        }
+        // entries written before the hashes were stored
+        let ledger_handle = self.db.cf_handle(LEDGER_CF).expect(CF_ERROR);
+        self.db
+            .get_pinned_cf(ledger_handle, serialized_key)
+            .expect(CRUD_ERROR)
+            .map(|value| compute_entry_hash(serialized_key, &value))
+    }
+
+    /// Internal function to put a key & value, the ledger hash being updated when the batch is written
+    fn put_key_value(&self, batch: &mut LedgerBatch, key: &Key, value: &[u8]) {
         let mut serialized_key = Vec::new();
         self.key_serializer_db
             .serialize(key, &mut serialized_key)
             .expect(KEY_SER_ERROR);
-        let mut len_bytes = Vec::new();
-        self.len_serializer
-            .serialize(&(serialized_key.len() as u64), &mut len_bytes)
-            .expect(KEY_LEN_SER_ERROR);
-        let hash = Hash::compute_from(&[&len_bytes, &serialized_key, value].concat());
-        batch.ledger_hash ^= hash;
-        batch.aeh_list.insert(serialized_key.clone(), hash);
-        batch.write_batch.put_cf(handle, serialized_key, value);
+        batch
+            .entry_writes
+            .insert(serialized_key, Some(value.to_vec()));
     }
 
     /// Add every sub-entry individually for a given entry.
@@ -462,7 +654,6 @@ impl LedgerDB {
     /// * `ledger_entry`: complete entry to be added
     /// * `batch`: the given operation batch to update
     fn put_entry(&mut self, addr: &Address, ledger_entry: LedgerEntry, batch: &mut LedgerBatch) {
-        let handle = self.db.cf_handle(LEDGER_CF).expect(CF_ERROR);
         // Amount serialization never fails
         let mut bytes_balance = Vec::new();
         self.amount_serializer
@@ -475,65 +666,17 @@ impl LedgerDB {
             .unwrap();
 
         // balance
-        self.put_entry_value(
-            handle,
-            batch,
-            &Key::new(addr, KeyType::BALANCE),
-            &bytes_balance,
-        );
+        self.put_key_value(batch, &Key::new(addr, KeyType::BALANCE), &bytes_balance);
 
         // bytecode
-        self.put_entry_value(
-            handle,
-            batch,
-            &Key::new(addr, KeyType::BYTECODE),
-            &bytes_bytecode,
-        );
+        self.put_key_value(batch, &Key::new(addr, KeyType::BYTECODE), &bytes_bytecode);
 
         // datastore
         for (hash, entry) in ledger_entry.datastore {
-            self.put_entry_value(
-                handle,
-                batch,
-                &Key::new(addr, KeyType::DATASTORE(hash)),
-                &entry,
-            );
+            self.put_key_value(batch, &Key::new(addr, KeyType::DATASTORE(hash)), &entry);
         }
     }
 
-    /// Internal function to update a key & value and perform the ledger hash XORs
-    fn update_key_value(
-        &self,
-        handle: &ColumnFamily,
-        batch: &mut LedgerBatch,
-        key: &Key,
-        value: &[u8],
-    ) {
-        let mut serialized_key = Vec::new();
-        self.key_serializer_db
-            .serialize(key, &mut serialized_key)
-            .expect(KEY_SER_ERROR);
-
-        let mut len_bytes = Vec::new();
-        self.len_serializer
-            .serialize(&(serialized_key.len() as u64), &mut len_bytes)
-            .expect(KEY_LEN_SER_ERROR);
-        if let Some(added_hash) = batch.aeh_list.get(&serialized_key) {
-            batch.ledger_hash ^= *added_hash;
-        } else if let Some(prev_bytes) = self
-            .db
-            .get_pinned_cf(handle, &serialized_key)
-            .expect(CRUD_ERROR)
-        {
-            batch.ledger_hash ^=
-                Hash::compute_from(&[&len_bytes, &serialized_key, &prev_bytes[..]].concat());
-        }
-        let hash = Hash::compute_from(&[&len_bytes, &serialized_key, value].concat());
-        batch.ledger_hash ^= hash;
-        batch.aeh_list.insert(serialized_key.clone(), hash);
-        batch.write_batch.put_cf(handle, serialized_key, value);
-    }
-
     /// Update the ledger entry of a given address.
     ///
     /// # Arguments
@@ -545,8 +688,6 @@ impl LedgerDB {
         entry_update: LedgerEntryUpdate,
         batch: &mut LedgerBatch,
     ) {
-        let handle = self.db.cf_handle(LEDGER_CF).expect(CF_ERROR);
-
         // balance
         if let SetOrKeep::Set(balance) = entry_update.balance {
             let mut bytes = Vec::new();
@@ -556,7 +697,7 @@ impl LedgerDB {
                 .unwrap();
 
             let balance_key = Key::new(addr, KeyType::BALANCE);
-            self.update_key_value(handle, batch, &balance_key, &bytes);
+            self.put_key_value(batch, &balance_key, &bytes);
         }
 
         // bytecode
@@ -567,42 +708,26 @@ impl LedgerDB {
                 .unwrap();
 
             let bytecode_key = Key::new(addr, KeyType::BYTECODE);
-            self.update_key_value(handle, batch, &bytecode_key, &bytes);
+            self.put_key_value(batch, &bytecode_key, &bytes);
         }
 
         // datastore
         for (hash, update) in entry_update.datastore {
             let datastore_key = Key::new(addr, KeyType::DATASTORE(hash));
             match update {
-                SetOrDelete::Set(entry) => {
-                    self.update_key_value(handle, batch, &datastore_key, &entry)
-                }
-                SetOrDelete::Delete => self.delete_key(handle, batch, &datastore_key),
+                SetOrDelete::Set(entry) => self.put_key_value(batch, &datastore_key, &entry),
+                SetOrDelete::Delete => self.delete_key(batch, &datastore_key),
             }
         }
     }
 
-    /// Internal function to delete a key and perform the ledger hash XOR
-    fn delete_key(&self, handle: &ColumnFamily, batch: &mut LedgerBatch, key: &Key) {
+    /// Internal function to delete a key, the ledger hash being updated when the batch is written
+    fn delete_key(&self, batch: &mut LedgerBatch, key: &Key) {
         let mut serialized_key = Vec::new();
         self.key_serializer_db
             .serialize(key, &mut serialized_key)
             .expect(KEY_SER_ERROR);
-        if let Some(added_hash) = batch.aeh_list.get(&serialized_key) {
-            batch.ledger_hash ^= *added_hash;
-        } else if let Some(prev_bytes) = self
-            .db
-            .get_pinned_cf(handle, &serialized_key)
-            .expect(CRUD_ERROR)
-        {
-            let mut len_bytes = Vec::new();
-            self.len_serializer
-                .serialize(&(serialized_key.len() as u64), &mut len_bytes)
-                .expect(KEY_LEN_SER_ERROR);
-            batch.ledger_hash ^=
-                Hash::compute_from(&[&len_bytes, &serialized_key, &prev_bytes[..]].concat());
-        }
-        batch.write_batch.delete_cf(handle, serialized_key);
+        batch.entry_writes.insert(serialized_key, None);
     }
 
     /// Delete every sub-entry associated to the given address.
@@ -613,10 +738,10 @@ impl LedgerDB {
         let handle = self.db.cf_handle(LEDGER_CF).expect(CF_ERROR);
 
         // balance
-        self.delete_key(handle, batch, &Key::new(addr, KeyType::BALANCE));
+        self.delete_key(batch, &Key::new(addr, KeyType::BALANCE));
 
         // bytecode
-        self.delete_key(handle, batch, &Key::new(addr, KeyType::BYTECODE));
+        self.delete_key(batch, &Key::new(addr, KeyType::BYTECODE));
 
         // datastore
         let mut opt = ReadOptions::default();
@@ -635,7 +760,7 @@ impl LedgerDB {
                 .key_deserializer_db
                 .deserialize::<DeserializeError>(&key)
                 .expect(KEY_DESER_ERROR);
-            self.delete_key(handle, batch, &deserialized_key);
+            self.delete_key(batch, &deserialized_key);
         }
     }
 }
@@ -716,6 +841,15 @@ impl LedgerDB {
     }
 }
 
+/// Hash of a ledger entry, given its key in the database format
+fn compute_entry_hash(serialized_key: &[u8], value: &[u8]) -> Hash {
+    let mut len_bytes = Vec::new();
+    U64VarIntSerializer::new()
+        .serialize(&(serialized_key.len() as u64), &mut len_bytes)
+        .expect(KEY_LEN_SER_ERROR);
+    Hash::compute_from(&[&len_bytes, serialized_key, value].concat())
+}
+
 /// For a given start prefix (inclusive), returns the correct end prefix (non-inclusive).
 /// This assumes the key bytes are ordered in lexicographical order.
 /// Since key length is not limited, for some case we return `None` because there is
@@ -737,10 +871,13 @@ fn end_prefix(prefix: &[u8]) -> Option<Vec<u8>> {
 mod tests {
     use super::*;
     use massa_hash::Hash;
-    use massa_ledger_exports::{LedgerEntry, LedgerEntryUpdate, SetOrKeep};
+    use massa_ledger_exports::{
+        LedgerChanges, LedgerEntry, LedgerEntryUpdate, SetOrDelete, SetOrKeep, SetUpdateOrDelete,
+    };
     use massa_models::{
         address::Address,
         amount::{Amount, AmountDeserializer},
+        operation::OPERATION_SEQUENCE_KEY,
         streaming_step::StreamingStep,
     };
     use massa_serialization::{DeserializeError, Deserializer};
@@ -821,6 +958,183 @@ mod tests {
         assert!(db.get_entire_datastore(&addr).is_empty());
     }
 
+    /// Test the ranged reads of datastore values
+    #[test]
+    fn test_ledger_sub_entry_chunk() {
+        let addr = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let value: Vec<u8> = (0..100).collect();
+        let entry = LedgerEntry {
+            datastore: BTreeMap::from([(b"blob".to_vec(), value.clone())]),
+            ..Default::default()
+        };
+        let temp_dir = TempDir::new().unwrap();
+        let mut db = LedgerDB::new(temp_dir.path().to_path_buf(), 32, 255, 1_000_000);
+        let mut batch = LedgerBatch::new(Hash::from_bytes(LEDGER_HASH_INITIAL_BYTES));
+        db.put_entry(&addr, entry, &mut batch);
+        db.write_batch(batch);
+
+        let blob = || LedgerSubEntry::Datastore(b"blob".to_vec());
+        let chunk = db.get_sub_entry_chunk(&addr, blob(), 90, 20).unwrap();
+        assert_eq!(chunk.offset, 90);
+        assert_eq!(chunk.bytes, value[90..].to_vec());
+        assert_eq!(chunk.value_length, 100);
+        let chunk = db.get_sub_entry_chunk(&addr, blob(), 10, 5).unwrap();
+        assert_eq!(chunk.bytes, value[10..15].to_vec());
+        assert!(db
+            .get_sub_entry_chunk(&addr, LedgerSubEntry::Datastore(b"none".to_vec()), 0, 5)
+            .is_none());
+    }
+
+    /// Test the listing of the addresses charged storage rent
+    #[test]
+    fn test_ledger_addresses_with_storage() {
+        let with_datastore = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let with_bytecode = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let with_rent_due = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let without_storage = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let temp_dir = TempDir::new().unwrap();
+        let mut db = LedgerDB::new(temp_dir.path().to_path_buf(), 32, 255, 1_000_000);
+        let mut batch = LedgerBatch::new(Hash::from_bytes(LEDGER_HASH_INITIAL_BYTES));
+        let entry = |datastore_key: Option<&[u8]>, bytecode: &[u8]| LedgerEntry {
+            balance: Amount::from_str("1").unwrap(),
+            bytecode: Bytecode(bytecode.to_vec()),
+            datastore: datastore_key
+                .map(|key| (key.to_vec(), vec![0; 8]))
+                .into_iter()
+                .collect(),
+        };
+        db.put_entry(
+            &with_datastore,
+            entry(Some(b"key".as_slice()), b""),
+            &mut batch,
+        );
+        db.put_entry(&with_bytecode, entry(None, b"bytecode"), &mut batch);
+        db.put_entry(
+            &with_rent_due,
+            entry(Some(STORAGE_RENT_DUE_KEY), b""),
+            &mut batch,
+        );
+        db.put_entry(
+            &without_storage,
+            entry(Some(OPERATION_SEQUENCE_KEY), b""),
+            &mut batch,
+        );
+        db.write_batch(batch);
+
+        assert_eq!(
+            db.get_addresses_with_storage(),
+            BTreeSet::from([with_datastore, with_bytecode, with_rent_due])
+        );
+
+        // the index follows the updates and deletions
+        let mut changes = LedgerChanges::default();
+        changes.0.insert(
+            with_datastore,
+            SetUpdateOrDelete::Update(LedgerEntryUpdate {
+                datastore: BTreeMap::from([(b"key".to_vec(), SetOrDelete::Delete)]),
+                ..Default::default()
+            }),
+        );
+        changes.0.insert(
+            with_bytecode,
+            SetUpdateOrDelete::Update(LedgerEntryUpdate {
+                bytecode: SetOrKeep::Set(Bytecode::default()),
+                ..Default::default()
+            }),
+        );
+        changes.0.insert(with_rent_due, SetUpdateOrDelete::Delete);
+        changes.0.insert(
+            without_storage,
+            SetUpdateOrDelete::Update(LedgerEntryUpdate {
+                bytecode: SetOrKeep::Set(Bytecode(b"bytecode".to_vec())),
+                ..Default::default()
+            }),
+        );
+        db.apply_changes(changes, Slot::new(1, 0));
+        assert_eq!(
+            db.get_addresses_with_storage(),
+            BTreeSet::from([without_storage])
+        );
+
+        // a ledger written before the index is indexed when it is opened
+        let storage_handle = db.db.cf_handle(STORAGE_CF).unwrap();
+        let metadata_handle = db.db.cf_handle(METADATA_CF).unwrap();
+        let mut write_batch = WriteBatch::default();
+        for (serialized_key, _) in db
+            .db
+            .iterator_cf(storage_handle, IteratorMode::Start)
+            .flatten()
+        {
+            write_batch.delete_cf(storage_handle, serialized_key);
+        }
+        write_batch.delete_cf(metadata_handle, STORAGE_INDEXED_KEY);
+        db.db.write(write_batch).unwrap();
+        assert!(db.get_addresses_with_storage().is_empty());
+        drop(db);
+        let db = LedgerDB::new(temp_dir.path().to_path_buf(), 32, 255, 1_000_000);
+        assert_eq!(
+            db.get_addresses_with_storage(),
+            BTreeSet::from([without_storage])
+        );
+    }
+
+    #[test]
+    fn test_ledger_hash_maintained_on_write() {
+        let addr = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let (mut db, _) = init_test_ledger(addr);
+
+        // overwrite, add and delete datastore entries
+        let mut datastore = BTreeMap::new();
+        datastore.insert(b"1".to_vec(), SetOrDelete::Set(b"d".to_vec()));
+        datastore.insert(b"2".to_vec(), SetOrDelete::Delete);
+        datastore.insert(b"4".to_vec(), SetOrDelete::Set(b"e".to_vec()));
+        let mut changes = LedgerChanges::default();
+        changes.0.insert(
+            addr,
+            SetUpdateOrDelete::Update(LedgerEntryUpdate {
+                balance: SetOrKeep::Set(Amount::from_str("12").unwrap()),
+                bytecode: SetOrKeep::Keep,
+                datastore,
+            }),
+        );
+        db.apply_changes(changes, Slot::new(1, 0));
+
+        // the maintained hash matches the hash computed from scratch
+        let ledger_handle = db.db.cf_handle(LEDGER_CF).unwrap();
+        let metadata_handle = db.db.cf_handle(METADATA_CF).unwrap();
+        let mut expected_hash = Hash::from_bytes(LEDGER_HASH_INITIAL_BYTES);
+        for (key, value) in db
+            .db
+            .iterator_cf(ledger_handle, IteratorMode::Start)
+            .flatten()
+        {
+            expected_hash ^= compute_entry_hash(&key, &value);
+        }
+        let slot_bytes = db.db.get_cf(metadata_handle, SLOT_KEY).unwrap().unwrap();
+        expected_hash ^= Hash::compute_from(&slot_bytes);
+        assert_eq!(db.get_ledger_hash(), expected_hash);
+        assert_eq!(
+            db.get_entire_datastore(&addr).keys().collect::<Vec<_>>(),
+            vec![&b"1".to_vec(), &b"3".to_vec(), &b"4".to_vec()]
+        );
+    }
+
+    #[test]
+    fn test_ledger_checkpoint() {
+        let addr = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let (db, data) = init_test_ledger(addr);
+
+        let checkpoint_dir = TempDir::new().unwrap();
+        let checkpoint_path = checkpoint_dir.path().join("ledger");
+        db.create_checkpoint(&checkpoint_path).unwrap();
+        // the target directory must not exist
+        assert!(db.create_checkpoint(&checkpoint_path).is_err());
+
+        let restored = LedgerDB::new(checkpoint_path, 32, 255, 1_000_000);
+        assert_eq!(restored.get_ledger_hash(), db.get_ledger_hash());
+        assert_eq!(restored.get_entire_datastore(&addr), data);
+    }
+
     #[test]
     fn test_ledger_parts() {
         let pub_a = KeyPair::generate().get_public_key();
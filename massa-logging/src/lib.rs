@@ -2,6 +2,10 @@
 //! Log utilities
 
 #![warn(missing_docs)]
+
+mod recent_logs;
+pub use recent_logs::{LogLine, RecentLogs};
+
 #[macro_export]
 /// tracing with some context
 macro_rules! massa_trace {
@@ -0,0 +1,189 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Bounded in-memory buffer of the recent log lines.
+//!
+//! The lines are kept per level so that a flood of frequent lines does not evict the rare, more
+//! severe ones. `RecentLogs` is a tracing layer: filter it like the other layers of the node.
+
+use massa_time::MassaTime;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt::Write;
+use std::sync::Arc;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+/// Log line kept in memory
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogLine {
+    /// time of the log
+    pub time: MassaTime,
+    /// level of the log
+    pub level: String,
+    /// module that logged the line
+    pub target: String,
+    /// message and fields of the log
+    pub message: String,
+}
+
+impl std::fmt::Display for LogLine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {}: {}",
+            self.time.to_utc_string(),
+            self.level,
+            self.target,
+            self.message
+        )
+    }
+}
+
+/// Buffers of the lines of each level with their sequence number, ERROR first
+struct Buffers {
+    levels: [VecDeque<(u64, LogLine)>; 5],
+    next_seq: u64,
+}
+
+/// Recent log lines of each level, cheap to clone
+#[derive(Clone)]
+pub struct RecentLogs {
+    buffers: Arc<Mutex<Buffers>>,
+    capacity_per_level: usize,
+}
+
+/// Index of the buffer of a level, the most severe first
+fn level_index(level: &Level) -> usize {
+    match *level {
+        Level::ERROR => 0,
+        Level::WARN => 1,
+        Level::INFO => 2,
+        Level::DEBUG => 3,
+        Level::TRACE => 4,
+    }
+}
+
+impl RecentLogs {
+    /// Creates a buffer keeping the latest `capacity_per_level` lines of each level
+    pub fn new(capacity_per_level: usize) -> Self {
+        RecentLogs {
+            buffers: Arc::new(Mutex::new(Buffers {
+                levels: Default::default(),
+                next_seq: 0,
+            })),
+            capacity_per_level,
+        }
+    }
+
+    /// Adds a line to the buffer of its level, evicting the oldest one if the buffer is full
+    pub fn push(&self, level: &Level, line: LogLine) {
+        if self.capacity_per_level == 0 {
+            return;
+        }
+        let mut buffers = self.buffers.lock();
+        let seq = buffers.next_seq;
+        buffers.next_seq += 1;
+        let buffer = &mut buffers.levels[level_index(level)];
+        if buffer.len() >= self.capacity_per_level {
+            buffer.pop_front();
+        }
+        buffer.push_back((seq, line));
+    }
+
+    /// Latest `count` lines of `level` or of a more severe level, oldest first
+    pub fn recent(&self, level: Level, count: usize) -> Vec<LogLine> {
+        let buffers = self.buffers.lock();
+        let mut lines: Vec<&(u64, LogLine)> = buffers.levels[..=level_index(&level)]
+            .iter()
+            .flat_map(|buffer| buffer.iter().rev().take(count))
+            .collect();
+        lines.sort_unstable_by_key(|(seq, _)| *seq);
+        let skipped = lines.len().saturating_sub(count);
+        lines
+            .into_iter()
+            .skip(skipped)
+            .map(|(_, line)| line.clone())
+            .collect()
+    }
+}
+
+/// Formats the fields of an event on a single line
+struct LineVisitor(String);
+
+impl Visit for LineVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if !self.0.is_empty() {
+            self.0.push(' ');
+        }
+        let _ = if field.name() == "message" {
+            write!(self.0, "{:?}", value)
+        } else {
+            write!(self.0, "{}={:?}", field.name(), value)
+        };
+    }
+}
+
+impl<S: Subscriber> Layer<S> for RecentLogs {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        if self.capacity_per_level == 0 {
+            return;
+        }
+        let mut visitor = LineVisitor(String::new());
+        event.record(&mut visitor);
+        let metadata = event.metadata();
+        self.push(
+            metadata.level(),
+            LogLine {
+                time: MassaTime::now().unwrap_or_else(|_| MassaTime::from_millis(0)),
+                level: metadata.level().to_string(),
+                target: metadata.target().to_string(),
+                message: visitor.0,
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::prelude::*;
+
+    #[test]
+    fn test_recent_logs() {
+        let logs = RecentLogs::new(2);
+        let subscriber = tracing_subscriber::registry().with(logs.clone());
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::error!("first error");
+            for i in 0..5 {
+                tracing::info!(index = i, "info");
+            }
+            tracing::warn!("warning");
+        });
+
+        // the error is not evicted by the info lines
+        let messages: Vec<String> = logs
+            .recent(Level::INFO, 10)
+            .into_iter()
+            .map(|line| format!("{} {}", line.level, line.message))
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "ERROR first error",
+                "INFO info index=3",
+                "INFO info index=4",
+                "WARN warning"
+            ]
+        );
+
+        let messages: Vec<String> = logs
+            .recent(Level::WARN, 1)
+            .into_iter()
+            .map(|line| line.message)
+            .collect();
+        assert_eq!(messages, vec!["warning"]);
+        assert!(logs.recent(Level::TRACE, 0).is_empty());
+    }
+}
@@ -1,5 +1,6 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use crate::amount::Amount;
 use crate::error::ModelsError;
 use crate::prehash::PreHashed;
 use massa_hash::{Hash, HashDeserializer};
@@ -54,6 +55,58 @@ const ADDRESS_PREFIX: char = 'A';
 const USER_PREFIX: u64 = 0;
 const SC_PREFIX: u64 = 1;
 const ADDRESS_VERSION: u64 = 0;
+// version of the encoding binding the address to a chain
+const ADDRESS_CHAIN_VERSION: u64 = 1;
+const CHAIN_SEPARATOR: char = ':';
+
+/// Identifier of the network an address is meant for, written in the chain-bound address format
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct ChainId(pub u64);
+
+impl ChainId {
+    /// Main network
+    pub const MAINNET: ChainId = ChainId(77658377);
+    /// Test network
+    pub const TESTNET: ChainId = ChainId(77658366);
+}
+
+impl std::fmt::Display for ChainId {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            ChainId::MAINNET => write!(f, "mainnet"),
+            ChainId::TESTNET => write!(f, "testnet"),
+            ChainId(id) => write!(f, "chain{}", id),
+        }
+    }
+}
+
+impl FromStr for ChainId {
+    type Err = ModelsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "mainnet" => Ok(ChainId::MAINNET),
+            "testnet" => Ok(ChainId::TESTNET),
+            _ => s
+                .strip_prefix("chain")
+                .and_then(|id| id.parse().ok())
+                .map(ChainId)
+                .ok_or_else(|| ModelsError::AddressParseError(format!("unknown chain {}", s))),
+        }
+    }
+}
+
+/// Human-readable encoding of an address
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AddressFormat {
+    /// `A{U|S}` + base58check(version 0 + hash), valid on every network: the format of `Display`
+    #[default]
+    Legacy,
+    /// `chain:A{U|S}` + base58check(version 1 + chain id + hash), ex: `mainnet:AU...`.
+    /// The checksum covers the chain id, so that an address of another network is refused when parsing.
+    Chain(ChainId),
+}
 
 impl std::fmt::Display for Address {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
@@ -168,15 +221,69 @@ impl FromStr for Address {
     /// assert_eq!(address, res_addr);
     /// ```
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let err = Err(ModelsError::AddressParseError(s.to_string()));
+        Address::from_str_with_chain(s).map(|(address, _chain_id)| address)
+    }
+}
+
+impl PreHashed for Address {}
+
+impl Address {
+    /// Encodes the address in the given format
+    ///
+    /// ```
+    /// # use massa_signature::KeyPair;
+    /// # use std::str::FromStr;
+    /// # use massa_models::address::{Address, AddressFormat, ChainId};
+    /// # let address = Address::from_public_key(&KeyPair::generate().get_public_key());
+    /// let encoded = address.to_string_with_format(AddressFormat::Chain(ChainId::MAINNET));
+    /// assert!(encoded.starts_with("mainnet:AU"));
+    /// assert_eq!(Address::from_str(&encoded).unwrap(), address);
+    /// assert_eq!(address.to_string_with_format(AddressFormat::Legacy), address.to_string());
+    /// ```
+    pub fn to_string_with_format(&self, format: AddressFormat) -> String {
+        let chain_id = match format {
+            AddressFormat::Legacy => return self.to_string(),
+            AddressFormat::Chain(chain_id) => chain_id,
+        };
+        let u64_serializer = U64VarIntSerializer::new();
+        let mut bytes: Vec<u8> = Vec::new();
+        u64_serializer
+            .serialize(&ADDRESS_CHAIN_VERSION, &mut bytes)
+            .expect("impl always returns Ok(())");
+        u64_serializer
+            .serialize(&chain_id.0, &mut bytes)
+            .expect("impl always returns Ok(())");
+        bytes.extend(self.hash_bytes());
+        format!(
+            "{}{}{}{}{}",
+            chain_id,
+            CHAIN_SEPARATOR,
+            ADDRESS_PREFIX,
+            match self {
+                Address::User(_) => 'U',
+                Address::SC(_) => 'S',
+            },
+            bs58::encode(bytes).with_check().into_string()
+        )
+    }
+
+    /// Parses an address in any format, along with the chain it is bound to, `None` for the legacy format
+    pub fn from_str_with_chain(s: &str) -> Result<(Address, Option<ChainId>), ModelsError> {
+        let err = || ModelsError::AddressParseError(s.to_string());
+
+        // Handle the chain prefix ("{chain}:"), if any
+        let (chain_prefix, encoded) = match s.split_once(CHAIN_SEPARATOR) {
+            Some((chain, encoded)) => (Some(ChainId::from_str(chain)?), encoded),
+            None => (None, s),
+        };
 
         // Handle the prefix ("A{U|S}")
-        let mut chars = s.chars();
+        let mut chars = encoded.chars();
         let Some(ADDRESS_PREFIX) = chars.next() else {
-            return err;
+            return Err(err());
         };
         let Some(pref) = chars.next() else {
-            return err;
+            return Err(err());
         };
 
         // Turn the version + hash encoded string into a byte-vec
@@ -184,32 +291,55 @@ impl FromStr for Address {
         let decoded_bs58_check = bs58::decode(data)
             .with_check(None)
             .into_vec()
-            .map_err(|_| ModelsError::AddressParseError(s.to_string()))?;
+            .map_err(|_| err())?;
 
-        // extract the version
+        // extract the version, and the chain id of the chain-bound format
         let u64_deserializer = U64VarIntDeserializer::new(Included(0), Included(u64::MAX));
-        let (rest, _version) = u64_deserializer
+        let (rest, version) = u64_deserializer
             .deserialize::<DeserializeError>(&decoded_bs58_check[..])
-            .map_err(|_| ModelsError::AddressParseError(s.to_string()))?;
+            .map_err(|_| err())?;
+        let (rest, chain_id) = match version {
+            ADDRESS_VERSION => (rest, None),
+            ADDRESS_CHAIN_VERSION => {
+                let (rest, chain_id) = u64_deserializer
+                    .deserialize::<DeserializeError>(rest)
+                    .map_err(|_| err())?;
+                (rest, Some(ChainId(chain_id)))
+            }
+            _ => return Err(err()),
+        };
+        if chain_id != chain_prefix {
+            return Err(ModelsError::AddressParseError(format!(
+                "{}: the chain prefix does not match the encoded chain",
+                s
+            )));
+        }
 
         // ...and package it up
-        let res = UserAddress(Hash::from_bytes(
-            rest.try_into()
-                .map_err(|_| ModelsError::AddressParseError(s.to_string()))?,
-        ));
+        let res = UserAddress(Hash::from_bytes(rest.try_into().map_err(|_| err())?));
 
         let res = match pref {
             'U' => Address::User(res),
             'S' => Address::SC(res.into()),
-            _ => return err,
+            _ => return Err(err()),
         };
-        Ok(res)
+        Ok((res, chain_id))
     }
-}
 
-impl PreHashed for Address {}
+    /// Parses an address meant for `chain_id`: refuses the addresses bound to another chain.
+    /// Legacy addresses, valid on every network, are accepted.
+    pub fn from_str_for_chain(s: &str, chain_id: ChainId) -> Result<Address, ModelsError> {
+        match Address::from_str_with_chain(s)? {
+            (_, Some(address_chain_id)) if address_chain_id != chain_id => {
+                Err(ModelsError::AddressParseError(format!(
+                    "{} is an address of {}, not of {}",
+                    s, address_chain_id, chain_id
+                )))
+            }
+            (address, _) => Ok(address),
+        }
+    }
 
-impl Address {
     /// Gets the associated thread. Depends on the `thread_count`
     pub fn get_thread(&self, thread_count: u8) -> u8 {
         (self.hash_bytes()[0])
@@ -395,6 +525,43 @@ pub struct ExecutionAddressCycleInfo {
     pub active_rolls: Option<u64>,
 }
 
+/// Staking rewards credited to an address
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StakingRewards {
+    /// number of blocks produced by the address
+    pub block_count: u64,
+    /// coins credited for producing blocks, operation fees included
+    pub block_rewards: Amount,
+    /// number of endorsements produced by the address and included in blocks
+    pub endorsement_count: u64,
+    /// coins credited for producing endorsements
+    pub endorsement_rewards: Amount,
+    /// coins credited because blocks produced by the address were endorsed
+    pub endorsed_rewards: Amount,
+}
+
+impl StakingRewards {
+    /// Total amount of coins credited
+    pub fn total(&self) -> Amount {
+        self.block_rewards
+            .saturating_add(self.endorsement_rewards)
+            .saturating_add(self.endorsed_rewards)
+    }
+
+    /// Accumulate other rewards into these ones
+    pub fn extend(&mut self, other: &StakingRewards) {
+        self.block_count = self.block_count.saturating_add(other.block_count);
+        self.block_rewards = self.block_rewards.saturating_add(other.block_rewards);
+        self.endorsement_count = self
+            .endorsement_count
+            .saturating_add(other.endorsement_count);
+        self.endorsement_rewards = self
+            .endorsement_rewards
+            .saturating_add(other.endorsement_rewards);
+        self.endorsed_rewards = self.endorsed_rewards.saturating_add(other.endorsed_rewards);
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -409,4 +576,40 @@ mod test {
         let b = Address::from_str(&a).unwrap();
         assert_eq!(address, b);
     }
+
+    #[test]
+    fn test_address_chain_format() {
+        use massa_signature::KeyPair;
+
+        let keypair = KeyPair::generate();
+        let address = Address::from_public_key(&keypair.get_public_key());
+        let legacy = address.to_string();
+        let mainnet = address.to_string_with_format(AddressFormat::Chain(ChainId::MAINNET));
+        let testnet = address.to_string_with_format(AddressFormat::Chain(ChainId::TESTNET));
+        assert_ne!(mainnet, testnet);
+
+        assert_eq!(
+            Address::from_str_with_chain(&mainnet).unwrap(),
+            (address, Some(ChainId::MAINNET))
+        );
+        assert_eq!(
+            Address::from_str_with_chain(&legacy).unwrap(),
+            (address, None)
+        );
+        assert_eq!(
+            Address::from_str_for_chain(&mainnet, ChainId::MAINNET).unwrap(),
+            address
+        );
+        assert_eq!(
+            Address::from_str_for_chain(&legacy, ChainId::TESTNET).unwrap(),
+            address
+        );
+        assert!(Address::from_str_for_chain(&mainnet, ChainId::TESTNET).is_err());
+
+        // the chain prefix is covered by the checksum
+        let forged = mainnet.replacen("mainnet", "testnet", 1);
+        assert!(Address::from_str(&forged).is_err());
+        let stripped = mainnet.trim_start_matches("mainnet:");
+        assert!(Address::from_str(stripped).is_err());
+    }
 }
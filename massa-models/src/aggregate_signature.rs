@@ -0,0 +1,180 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use crate::error::ModelsError;
+use crate::serialization::{BitVecDeserializer, BitVecSerializer};
+use bitvec::vec::BitVec;
+use massa_hash::Hash;
+use massa_serialization::{Deserializer, SerializeError, Serializer};
+use massa_signature::{BlsPublicKey, BlsSignature, BlsSignatureDeserializer};
+use nom::error::{context, ContextError, ErrorKind, ParseError};
+use nom::sequence::tuple;
+use nom::{IResult, Parser};
+use serde::{Deserialize, Serialize};
+
+/// BLS signature aggregating the signatures of a same hash by members of a known committee
+/// (ex: the endorsers drawn for a slot), the committee being known by the verifiers
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AggregateSignature {
+    /// aggregation of the signatures of the signers
+    pub signature: BlsSignature,
+    /// one bit per member of the committee, set for the signers
+    pub signers: BitVec<u8>,
+}
+
+impl AggregateSignature {
+    /// Aggregates the `signatures` of members of a committee of `committee_size` members,
+    /// each given with the position of its signer in the committee
+    pub fn aggregate(
+        committee_size: usize,
+        signatures: &[(usize, BlsSignature)],
+    ) -> Result<Self, ModelsError> {
+        let mut signers = BitVec::repeat(false, committee_size);
+        for (position, _) in signatures {
+            match signers.get_mut(*position) {
+                Some(mut signer) if !*signer => signer.set(true),
+                Some(_) => {
+                    return Err(ModelsError::InvalidAggregateSignature(format!(
+                        "committee member {} signed twice",
+                        position
+                    )))
+                }
+                None => {
+                    return Err(ModelsError::InvalidAggregateSignature(format!(
+                        "committee member {} out of a committee of {}",
+                        position, committee_size
+                    )))
+                }
+            }
+        }
+        let signatures: Vec<BlsSignature> = signatures.iter().map(|(_, sig)| *sig).collect();
+        Ok(AggregateSignature {
+            signature: BlsSignature::aggregate(&signatures)?,
+            signers,
+        })
+    }
+
+    /// Number of signers
+    pub fn signer_count(&self) -> usize {
+        self.signers.count_ones()
+    }
+
+    /// Checks that the signers of the `committee` signed `hash`.
+    /// The proofs of possession of the keys of the committee must have been checked beforehand.
+    ///
+    /// # Example
+    /// ```
+    /// # use massa_hash::Hash;
+    /// # use massa_models::aggregate_signature::AggregateSignature;
+    /// # use massa_signature::BlsKeyPair;
+    /// let keypairs: Vec<BlsKeyPair> = (0..4).map(|_| BlsKeyPair::generate()).collect();
+    /// let committee: Vec<_> = keypairs.iter().map(BlsKeyPair::get_public_key).collect();
+    /// let hash = Hash::compute_from("endorsed block".as_bytes());
+    /// let signatures = vec![(0, keypairs[0].sign(&hash)), (3, keypairs[3].sign(&hash))];
+    /// let aggregate = AggregateSignature::aggregate(committee.len(), &signatures).unwrap();
+    /// assert_eq!(aggregate.signer_count(), 2);
+    /// assert!(aggregate.verify(&hash, &committee).is_ok());
+    /// assert!(aggregate.verify(&Hash::compute_from("other".as_bytes()), &committee).is_err());
+    /// ```
+    pub fn verify(&self, hash: &Hash, committee: &[BlsPublicKey]) -> Result<(), ModelsError> {
+        if self.signers.len() != committee.len() {
+            return Err(ModelsError::InvalidAggregateSignature(format!(
+                "{} signer bits for a committee of {}",
+                self.signers.len(),
+                committee.len()
+            )));
+        }
+        let public_keys: Vec<BlsPublicKey> = self
+            .signers
+            .iter_ones()
+            .map(|position| committee[position])
+            .collect();
+        self.signature.verify_aggregate(hash, &public_keys)?;
+        Ok(())
+    }
+}
+
+/// Serializer for `AggregateSignature`
+#[derive(Default)]
+pub struct AggregateSignatureSerializer {
+    bitvec_serializer: BitVecSerializer,
+}
+
+impl AggregateSignatureSerializer {
+    /// Creates a new `AggregateSignatureSerializer`
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Serializer<AggregateSignature> for AggregateSignatureSerializer {
+    fn serialize(
+        &self,
+        value: &AggregateSignature,
+        buffer: &mut Vec<u8>,
+    ) -> Result<(), SerializeError> {
+        buffer.extend(value.signature.to_bytes());
+        self.bitvec_serializer.serialize(&value.signers, buffer)
+    }
+}
+
+/// Deserializer for `AggregateSignature`
+pub struct AggregateSignatureDeserializer {
+    signature_deserializer: BlsSignatureDeserializer,
+    bitvec_deserializer: BitVecDeserializer,
+    max_committee_size: usize,
+}
+
+impl AggregateSignatureDeserializer {
+    /// Creates a new `AggregateSignatureDeserializer` for committees of at most `max_committee_size` members
+    pub fn new(max_committee_size: usize) -> Self {
+        AggregateSignatureDeserializer {
+            signature_deserializer: BlsSignatureDeserializer::new(),
+            bitvec_deserializer: BitVecDeserializer::new(),
+            max_committee_size,
+        }
+    }
+}
+
+impl Deserializer<AggregateSignature> for AggregateSignatureDeserializer {
+    /// ## Example:
+    /// ```rust
+    /// use massa_hash::Hash;
+    /// use massa_models::aggregate_signature::{AggregateSignature, AggregateSignatureDeserializer, AggregateSignatureSerializer};
+    /// use massa_serialization::{DeserializeError, Deserializer, Serializer};
+    /// use massa_signature::BlsKeyPair;
+    ///
+    /// let hash = Hash::compute_from("endorsed block".as_bytes());
+    /// let aggregate = AggregateSignature::aggregate(16, &[(5, BlsKeyPair::generate().sign(&hash))]).unwrap();
+    /// let mut buffer = Vec::new();
+    /// AggregateSignatureSerializer::new().serialize(&aggregate, &mut buffer).unwrap();
+    /// let (rest, deserialized) = AggregateSignatureDeserializer::new(16).deserialize::<DeserializeError>(&buffer).unwrap();
+    /// assert!(rest.is_empty());
+    /// assert_eq!(deserialized, aggregate);
+    /// assert!(AggregateSignatureDeserializer::new(8).deserialize::<DeserializeError>(&buffer).is_err());
+    /// ```
+    fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], AggregateSignature, E> {
+        context(
+            "Failed aggregate signature deserialization",
+            tuple((
+                context("Failed signature deserialization", |input| {
+                    self.signature_deserializer.deserialize(input)
+                }),
+                context("Failed signers deserialization", |input| {
+                    let (rest, signers) = self.bitvec_deserializer.deserialize(input)?;
+                    if signers.len() > self.max_committee_size {
+                        return Err(nom::Err::Error(ParseError::from_error_kind(
+                            input,
+                            ErrorKind::TooLarge,
+                        )));
+                    }
+                    Ok((rest, signers))
+                }),
+            )),
+        )
+        .map(|(signature, signers)| AggregateSignature { signature, signers })
+        .parse(buffer)
+    }
+}
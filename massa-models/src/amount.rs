@@ -153,6 +153,94 @@ impl Amount {
     pub fn checked_div_u64(self, factor: u64) -> Option<Self> {
         self.0.checked_div(factor).map(Amount)
     }
+
+    /// safely multiply self by the ratio `numerator / denominator`, rounding down,
+    /// returning None if the denominator is zero or on overflow.
+    /// The intermediate product does not overflow, unlike chaining `checked_mul_u64` and `checked_div_u64`.
+    /// ```
+    /// # use massa_models::amount::Amount;
+    /// # use std::str::FromStr;
+    /// let amount_1 : Amount = Amount::from_str("10000000000").unwrap();
+    /// let res : Amount = amount_1.checked_mul_div(2, 3).unwrap();
+    /// assert_eq!(res, Amount::from_str("6666666666.666666666").unwrap());
+    /// assert!(amount_1.checked_mul_div(2, 0).is_none());
+    /// assert!(amount_1.checked_mul_div(2, 1).is_none());
+    /// ```
+    pub fn checked_mul_div(self, numerator: u64, denominator: u64) -> Option<Self> {
+        if denominator == 0 {
+            return None;
+        }
+        let res = (self.0 as u128) * (numerator as u128) / (denominator as u128);
+        u64::try_from(res).ok().map(Amount)
+    }
+
+    /// safely compute `percent`% of self, rounding down, returning None on overflow
+    /// ```
+    /// # use massa_models::amount::Amount;
+    /// # use std::str::FromStr;
+    /// let amount_1 : Amount = Amount::from_str("42").unwrap();
+    /// let res : Amount = amount_1.checked_percentage(15).unwrap();
+    /// assert_eq!(res, Amount::from_str("6.3").unwrap());
+    /// ```
+    pub fn checked_percentage(self, percent: u64) -> Option<Self> {
+        self.checked_mul_div(percent, 100)
+    }
+
+    /// constructs an `Amount` from a raw value that may not fit in the `u64` representation,
+    /// saturating to `Amount::MAX`
+    /// ```
+    /// # use massa_models::amount::Amount;
+    /// assert_eq!(Amount::saturating_from_raw(42), Amount::from_raw(42));
+    /// assert_eq!(Amount::saturating_from_raw(u128::MAX), Amount::MAX);
+    /// ```
+    pub fn saturating_from_raw(raw: u128) -> Self {
+        Amount(u64::try_from(raw).unwrap_or(u64::MAX))
+    }
+
+    /// constructs an `Amount` of `coins` whole coins, saturating to `Amount::MAX`
+    /// ```
+    /// # use massa_models::amount::Amount;
+    /// # use std::str::FromStr;
+    /// assert_eq!(Amount::saturating_from_coins(42), Amount::from_str("42").unwrap());
+    /// assert_eq!(Amount::saturating_from_coins(u64::MAX), Amount::MAX);
+    /// ```
+    pub const fn saturating_from_coins(coins: u64) -> Self {
+        Amount(coins.saturating_mul(AMOUNT_DECIMAL_FACTOR))
+    }
+
+    /// number of whole coins in the amount, the fractional part being dropped
+    /// ```
+    /// # use massa_models::amount::Amount;
+    /// # use std::str::FromStr;
+    /// assert_eq!(Amount::from_str("42.99").unwrap().to_coins(), 42);
+    /// ```
+    pub const fn to_coins(&self) -> u64 {
+        self.0 / AMOUNT_DECIMAL_FACTOR
+    }
+}
+
+/// suffix of an amount expressed in coins, ex: "12.5 MAS"
+pub const AMOUNT_COIN_SUFFIX: &str = "MAS";
+
+/// suffix of an amount expressed in raw units (`1 / AMOUNT_DECIMAL_FACTOR` coin), ex: "12500 nMAS"
+pub const AMOUNT_RAW_SUFFIX: &str = "nMAS";
+
+/// Removes the underscores used to separate digit groups, ex: "1_000_000",
+/// failing if one of them is not between two digits
+fn remove_digit_separators(str_amount: &str) -> Result<String, ModelsError> {
+    let bytes = str_amount.as_bytes();
+    for (index, byte) in bytes.iter().enumerate() {
+        if *byte == b'_'
+            && (index == 0
+                || !bytes[index - 1].is_ascii_digit()
+                || !bytes.get(index + 1).map_or(false, u8::is_ascii_digit))
+        {
+            return Err(ModelsError::AmountParseError(
+                "underscores must separate digits".to_string(),
+            ));
+        }
+    }
+    Ok(str_amount.replace('_', ""))
 }
 
 /// display an Amount in decimal string form (like "10.33")
@@ -185,10 +273,18 @@ impl fmt::Debug for Amount {
 /// note that this will fail if the string format is invalid
 /// or if the conversion would cause an overflow, underflow or precision loss
 ///
+/// Digits can be grouped with underscores, and the amount can end with a unit suffix:
+/// `MAS` for coins (the default) or `nMAS` for raw units, optionally separated by a space.
+///
 /// ```
 /// # use massa_models::amount::Amount;
 /// # use std::str::FromStr;
 /// assert!(Amount::from_str("11.1").is_ok());
+/// assert_eq!(Amount::from_str("1_000.5 MAS").unwrap(), Amount::from_str("1000.5").unwrap());
+/// assert_eq!(Amount::from_str("1_500nMAS").unwrap(), Amount::from_raw(1500));
+/// assert!(Amount::from_str("1.5 nMAS").is_err());
+/// assert!(Amount::from_str("1__000").is_err());
+/// assert!(Amount::from_str("_1").is_err());
 /// assert!(Amount::from_str("11.1111111111111111111111").is_err());
 /// assert!(Amount::from_str("1111111111111111111111").is_err());
 /// assert!(Amount::from_str("-11.1").is_err());
@@ -198,7 +294,19 @@ impl FromStr for Amount {
     type Err = ModelsError;
 
     fn from_str(str_amount: &str) -> Result<Self, Self::Err> {
-        let res = Decimal::from_str(str_amount)
+        if let Some(raw_amount) = str_amount.strip_suffix(AMOUNT_RAW_SUFFIX) {
+            return remove_digit_separators(raw_amount.trim_end())?
+                .parse::<u64>()
+                .map(Amount)
+                .map_err(|err| {
+                    ModelsError::AmountParseError(format!("invalid raw amount: {}", err))
+                });
+        }
+        let str_amount = match str_amount.strip_suffix(AMOUNT_COIN_SUFFIX) {
+            Some(coin_amount) => coin_amount.trim_end(),
+            None => str_amount,
+        };
+        let res = Decimal::from_str(&remove_digit_separators(str_amount)?)
             .map_err(|err| ModelsError::AmountParseError(err.to_string()))?
             .checked_mul(AMOUNT_DECIMAL_FACTOR.into())
             .ok_or_else(|| ModelsError::AmountParseError("amount is too large".to_string()))?;
@@ -308,12 +416,25 @@ impl Deserializer<Amount> for AmountDeserializer {
     }
 }
 
+/// Accepts an amount in decimal string form (see `Amount::from_str`) or a number of coins
+///
+/// ```
+/// # use massa_models::amount::Amount;
+/// # use std::str::FromStr;
+/// let amount: Amount = serde_json::from_str("\"1_000.5 MAS\"").unwrap();
+/// assert_eq!(amount, Amount::from_str("1000.5").unwrap());
+/// let amount: Amount = serde_json::from_str("12").unwrap();
+/// assert_eq!(amount, Amount::from_str("12").unwrap());
+/// let amount: Amount = serde_json::from_str("0.1").unwrap();
+/// assert_eq!(amount, Amount::from_str("0.1").unwrap());
+/// assert!(serde_json::from_str::<Amount>("-1").is_err());
+/// ```
 impl<'de> serde::Deserialize<'de> for Amount {
     fn deserialize<D>(deserializer: D) -> Result<Amount, D::Error>
     where
         D: serde::de::Deserializer<'de>,
     {
-        deserializer.deserialize_str(AmountVisitor)
+        deserializer.deserialize_any(AmountVisitor)
     }
 }
 
@@ -329,10 +450,38 @@ impl<'de> serde::de::Visitor<'de> for AmountVisitor {
         Amount::from_str(value).map_err(|_| E::invalid_value(Unexpected::Str(value), &self))
     }
 
+    fn visit_u64<E>(self, value: u64) -> Result<Amount, E>
+    where
+        E: serde::de::Error,
+    {
+        value
+            .checked_mul(AMOUNT_DECIMAL_FACTOR)
+            .map(Amount)
+            .ok_or_else(|| E::invalid_value(Unexpected::Unsigned(value), &self))
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<Amount, E>
+    where
+        E: serde::de::Error,
+    {
+        u64::try_from(value)
+            .map_err(|_| E::invalid_value(Unexpected::Signed(value), &self))
+            .and_then(|value| self.visit_u64(value))
+    }
+
+    fn visit_f64<E>(self, value: f64) -> Result<Amount, E>
+    where
+        E: serde::de::Error,
+    {
+        // the shortest representation of the float, ex: "0.1", is the amount that was written
+        Amount::from_str(&value.to_string())
+            .map_err(|_| E::invalid_value(Unexpected::Float(value), &self))
+    }
+
     fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
         write!(
             formatter,
-            "an Amount type representing a fixed-point currency amount"
+            "an Amount type representing a fixed-point currency amount, as a string or a number of coins"
         )
     }
 }
@@ -345,3 +494,95 @@ impl serde::Serialize for Amount {
         serializer.serialize_str(&self.to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn amount(str_amount: &str) -> Amount {
+        Amount::from_str(str_amount).unwrap()
+    }
+
+    #[test]
+    fn test_checked_mul_div() {
+        assert_eq!(
+            amount("10").checked_mul_div(1, 3),
+            Some(amount("3.333333333"))
+        );
+        assert_eq!(amount("10").checked_mul_div(0, 3), Some(Amount::zero()));
+        assert_eq!(Amount::MAX.checked_mul_div(7, 7), Some(Amount::MAX));
+        // the product overflows a u64 but not the result
+        assert_eq!(
+            Amount::MAX.checked_mul_div(u64::MAX, u64::MAX),
+            Some(Amount::MAX)
+        );
+        assert_eq!(Amount::MAX.checked_mul_div(2, 1), None);
+        assert_eq!(amount("10").checked_mul_div(1, 0), None);
+    }
+
+    #[test]
+    fn test_checked_percentage() {
+        assert_eq!(amount("200").checked_percentage(0), Some(Amount::zero()));
+        assert_eq!(amount("200").checked_percentage(100), Some(amount("200")));
+        assert_eq!(amount("200").checked_percentage(250), Some(amount("500")));
+        // rounded down to the raw unit
+        assert_eq!(
+            Amount::from_raw(199).checked_percentage(1),
+            Some(Amount::from_raw(1))
+        );
+        assert_eq!(Amount::MAX.checked_percentage(101), None);
+    }
+
+    #[test]
+    fn test_saturating_from() {
+        assert_eq!(Amount::saturating_from_raw(0), Amount::zero());
+        assert_eq!(Amount::saturating_from_raw(u64::MAX as u128), Amount::MAX);
+        assert_eq!(
+            Amount::saturating_from_raw(u64::MAX as u128 + 1),
+            Amount::MAX
+        );
+        assert_eq!(Amount::saturating_from_coins(0), Amount::zero());
+        assert_eq!(
+            Amount::saturating_from_coins(18_446_744_073),
+            amount("18446744073")
+        );
+        assert_eq!(Amount::saturating_from_coins(18_446_744_074), Amount::MAX);
+    }
+
+    #[test]
+    fn test_amount_suffixes() {
+        assert_eq!(amount("12.5MAS"), amount("12.5"));
+        assert_eq!(amount("12.5 MAS"), amount("12.5"));
+        assert_eq!(amount("12500 nMAS"), Amount::from_raw(12500));
+        assert_eq!(amount("12500nMAS"), Amount::from_raw(12500));
+        assert_eq!(amount("18446744073709551615 nMAS"), Amount::MAX);
+        // raw units are whole numbers that fit in the representation
+        assert!(Amount::from_str("12.5 nMAS").is_err());
+        assert!(Amount::from_str("18446744073709551616 nMAS").is_err());
+        assert!(Amount::from_str("18446744074 MAS").is_err());
+        assert!(Amount::from_str("-1 nMAS").is_err());
+        // a suffix alone, a lower case suffix or a prefix is not an amount
+        assert!(Amount::from_str("MAS").is_err());
+        assert!(Amount::from_str("nMAS").is_err());
+        assert!(Amount::from_str("12 mas").is_err());
+        assert!(Amount::from_str("MAS 12").is_err());
+        assert!(Amount::from_str("12 MAS MAS").is_err());
+    }
+
+    #[test]
+    fn test_amount_digit_separators() {
+        assert_eq!(amount("1_000_000"), amount("1000000"));
+        assert_eq!(amount("1_000.000_5"), amount("1000.0005"));
+        assert_eq!(amount("1_000 MAS"), amount("1000"));
+        assert_eq!(amount("1_000 nMAS"), Amount::from_raw(1000));
+        for invalid in [
+            "_1", "1_", "1__000", "1_.5", "1._5", "1_ MAS", "1_ nMAS", "_",
+        ] {
+            assert!(
+                Amount::from_str(invalid).is_err(),
+                "{} is accepted",
+                invalid
+            );
+        }
+    }
+}
@@ -0,0 +1,352 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Encoding of the arguments of smart contract functions, in the `Args` format of the contract SDKs.
+//!
+//! Values are concatenated without separator:
+//! * integers and floats are little endian, booleans are one byte
+//! * strings are prefixed by their length in bytes (u32, little endian)
+//! * arrays are prefixed by the length in bytes of their encoded elements (u32, little endian),
+//!   so that byte arrays `Vec<u8>` are length prefixed bytes
+//! * addresses are encoded as their string representation, amounts as their raw u64 value
+//!
+//! Structs are encoded as their fields in declaration order, see `impl_args_serializable!`.
+
+use crate::{address::Address, amount::Amount, error::ModelsError};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// A value that can be encoded in the `Args` format
+pub trait ArgsSerializable: Sized {
+    /// Appends the encoded value to `buffer`
+    fn serialize_args(&self, buffer: &mut Vec<u8>);
+
+    /// Decodes a value from the beginning of `buffer`, returning the rest of the buffer
+    fn deserialize_args(buffer: &[u8]) -> Result<(&[u8], Self), ModelsError>;
+}
+
+fn take(buffer: &[u8], length: usize) -> Result<(&[u8], &[u8]), ModelsError> {
+    if buffer.len() < length {
+        return Err(ModelsError::DeserializeError(format!(
+            "args: expected {} bytes but only {} are left",
+            length,
+            buffer.len()
+        )));
+    }
+    let (value, rest) = buffer.split_at(length);
+    Ok((rest, value))
+}
+
+fn take_length_prefixed(buffer: &[u8]) -> Result<(&[u8], &[u8]), ModelsError> {
+    let (rest, length) = u32::deserialize_args(buffer)?;
+    take(rest, length as usize)
+}
+
+fn serialize_length_prefixed(value: &[u8], buffer: &mut Vec<u8>) {
+    let length: u32 = value
+        .len()
+        .try_into()
+        .expect("args value longer than u32::MAX bytes");
+    length.serialize_args(buffer);
+    buffer.extend_from_slice(value);
+}
+
+macro_rules! impl_args_number {
+    ($($t:ty),*) => {
+        $(
+            impl ArgsSerializable for $t {
+                fn serialize_args(&self, buffer: &mut Vec<u8>) {
+                    buffer.extend_from_slice(&self.to_le_bytes());
+                }
+
+                fn deserialize_args(buffer: &[u8]) -> Result<(&[u8], Self), ModelsError> {
+                    let (rest, bytes) = take(buffer, std::mem::size_of::<$t>())?;
+                    Ok((
+                        rest,
+                        <$t>::from_le_bytes(bytes.try_into().expect("size checked above")),
+                    ))
+                }
+            }
+        )*
+    };
+}
+
+impl_args_number!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64);
+
+impl ArgsSerializable for bool {
+    fn serialize_args(&self, buffer: &mut Vec<u8>) {
+        buffer.push(u8::from(*self));
+    }
+
+    fn deserialize_args(buffer: &[u8]) -> Result<(&[u8], Self), ModelsError> {
+        let (rest, value) = u8::deserialize_args(buffer)?;
+        match value {
+            0 => Ok((rest, false)),
+            1 => Ok((rest, true)),
+            _ => Err(ModelsError::DeserializeError(format!(
+                "args: invalid boolean {}",
+                value
+            ))),
+        }
+    }
+}
+
+impl ArgsSerializable for String {
+    fn serialize_args(&self, buffer: &mut Vec<u8>) {
+        serialize_length_prefixed(self.as_bytes(), buffer);
+    }
+
+    fn deserialize_args(buffer: &[u8]) -> Result<(&[u8], Self), ModelsError> {
+        let (rest, bytes) = take_length_prefixed(buffer)?;
+        let value = String::from_utf8(bytes.to_vec())
+            .map_err(|err| ModelsError::DeserializeError(format!("args: {}", err)))?;
+        Ok((rest, value))
+    }
+}
+
+impl<T: ArgsSerializable> ArgsSerializable for Vec<T> {
+    fn serialize_args(&self, buffer: &mut Vec<u8>) {
+        let mut elements = Vec::new();
+        for element in self {
+            element.serialize_args(&mut elements);
+        }
+        serialize_length_prefixed(&elements, buffer);
+    }
+
+    fn deserialize_args(buffer: &[u8]) -> Result<(&[u8], Self), ModelsError> {
+        let (rest, mut elements) = take_length_prefixed(buffer)?;
+        let mut values = Vec::new();
+        while !elements.is_empty() {
+            let (elements_rest, value) = T::deserialize_args(elements)?;
+            elements = elements_rest;
+            values.push(value);
+        }
+        Ok((rest, values))
+    }
+}
+
+impl ArgsSerializable for Address {
+    fn serialize_args(&self, buffer: &mut Vec<u8>) {
+        self.to_string().serialize_args(buffer);
+    }
+
+    fn deserialize_args(buffer: &[u8]) -> Result<(&[u8], Self), ModelsError> {
+        let (rest, value) = String::deserialize_args(buffer)?;
+        Ok((rest, Address::from_str(&value)?))
+    }
+}
+
+impl ArgsSerializable for Amount {
+    fn serialize_args(&self, buffer: &mut Vec<u8>) {
+        self.to_raw().serialize_args(buffer);
+    }
+
+    fn deserialize_args(buffer: &[u8]) -> Result<(&[u8], Self), ModelsError> {
+        let (rest, value) = u64::deserialize_args(buffer)?;
+        Ok((rest, Amount::from_raw(value)))
+    }
+}
+
+/// Implements `ArgsSerializable` for a struct, encoding its fields in the given order.
+///
+/// ```
+/// # use massa_models::{address::Address, amount::Amount, impl_args_serializable};
+/// struct Transfer {
+///     to: Address,
+///     amount: Amount,
+/// }
+/// impl_args_serializable!(Transfer { to, amount });
+/// ```
+#[macro_export]
+macro_rules! impl_args_serializable {
+    ($name:ident { $($field:ident),* $(,)? }) => {
+        impl $crate::args::ArgsSerializable for $name {
+            fn serialize_args(&self, buffer: &mut Vec<u8>) {
+                $($crate::args::ArgsSerializable::serialize_args(&self.$field, buffer);)*
+            }
+
+            fn deserialize_args(
+                buffer: &[u8],
+            ) -> Result<(&[u8], Self), $crate::error::ModelsError> {
+                $(
+                    let (buffer, $field) = $crate::args::ArgsSerializable::deserialize_args(buffer)?;
+                )*
+                Ok((buffer, $name { $($field),* }))
+            }
+        }
+    };
+}
+
+/// Builder of the encoded arguments of a function call
+#[derive(Debug, Clone, Default)]
+pub struct Args {
+    buffer: Vec<u8>,
+}
+
+impl Args {
+    /// Creates empty arguments
+    pub fn new() -> Self {
+        Args::default()
+    }
+
+    /// Appends a value to the arguments
+    pub fn add<T: ArgsSerializable>(mut self, value: &T) -> Self {
+        value.serialize_args(&mut self.buffer);
+        self
+    }
+
+    /// Returns the encoded arguments
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.buffer
+    }
+}
+
+/// Reader of the values of encoded arguments, in order
+#[derive(Debug, Clone)]
+pub struct ArgsReader<'a> {
+    buffer: &'a [u8],
+}
+
+impl<'a> ArgsReader<'a> {
+    /// Reads the arguments encoded in `buffer`
+    pub fn new(buffer: &'a [u8]) -> Self {
+        ArgsReader { buffer }
+    }
+
+    /// Decodes the next value
+    pub fn read<T: ArgsSerializable>(&mut self) -> Result<T, ModelsError> {
+        let (rest, value) = T::deserialize_args(self.buffer)?;
+        self.buffer = rest;
+        Ok(value)
+    }
+
+    /// Whether all the values were read
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+}
+
+/// Untyped argument value, used by the tools getting arguments as JSON such as the client:
+/// `[{"string": "hello"}, {"u64": 42}, {"address": "AU..."}]`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArgValue {
+    /// boolean
+    Bool(bool),
+    /// unsigned 8 bits integer
+    U8(u8),
+    /// unsigned 32 bits integer
+    U32(u32),
+    /// unsigned 64 bits integer
+    U64(u64),
+    /// signed 32 bits integer
+    I32(i32),
+    /// signed 64 bits integer
+    I64(i64),
+    /// 32 bits float
+    F32(f32),
+    /// 64 bits float
+    F64(f64),
+    /// string
+    String(String),
+    /// byte array
+    Bytes(Vec<u8>),
+    /// address
+    Address(Address),
+    /// amount, in coins
+    Amount(Amount),
+}
+
+impl ArgValue {
+    /// Appends the encoded value to `buffer`
+    pub fn serialize_args(&self, buffer: &mut Vec<u8>) {
+        match self {
+            ArgValue::Bool(value) => value.serialize_args(buffer),
+            ArgValue::U8(value) => value.serialize_args(buffer),
+            ArgValue::U32(value) => value.serialize_args(buffer),
+            ArgValue::U64(value) => value.serialize_args(buffer),
+            ArgValue::I32(value) => value.serialize_args(buffer),
+            ArgValue::I64(value) => value.serialize_args(buffer),
+            ArgValue::F32(value) => value.serialize_args(buffer),
+            ArgValue::F64(value) => value.serialize_args(buffer),
+            ArgValue::String(value) => value.serialize_args(buffer),
+            ArgValue::Bytes(value) => value.serialize_args(buffer),
+            ArgValue::Address(value) => value.serialize_args(buffer),
+            ArgValue::Amount(value) => value.serialize_args(buffer),
+        }
+    }
+}
+
+/// Encodes a list of untyped values
+pub fn encode_arg_values(values: &[ArgValue]) -> Vec<u8> {
+    let mut buffer = Vec::new();
+    for value in values {
+        value.serialize_args(&mut buffer);
+    }
+    buffer
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_signature::KeyPair;
+
+    struct Transfer {
+        to: Address,
+        amount: Amount,
+        memo: String,
+    }
+    impl_args_serializable!(Transfer { to, amount, memo });
+
+    #[test]
+    fn test_args_roundtrip() {
+        let address = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let bytes = Args::new()
+            .add(&42u64)
+            .add(&"hello".to_string())
+            .add(&vec![1u8, 2, 3])
+            .add(&vec![7u32, 8])
+            .add(&true)
+            .add(&address)
+            .add(&-1.5f64)
+            .into_bytes();
+        assert_eq!(bytes[..8], 42u64.to_le_bytes());
+        assert_eq!(bytes[8..17], [5, 0, 0, 0, b'h', b'e', b'l', b'l', b'o']);
+        assert_eq!(bytes[17..24], [3, 0, 0, 0, 1, 2, 3]);
+        assert_eq!(bytes[24..36], [8, 0, 0, 0, 7, 0, 0, 0, 8, 0, 0, 0]);
+
+        let mut reader = ArgsReader::new(&bytes);
+        assert_eq!(reader.read::<u64>().unwrap(), 42);
+        assert_eq!(reader.read::<String>().unwrap(), "hello");
+        assert_eq!(reader.read::<Vec<u8>>().unwrap(), vec![1, 2, 3]);
+        assert_eq!(reader.read::<Vec<u32>>().unwrap(), vec![7, 8]);
+        assert!(reader.read::<bool>().unwrap());
+        assert_eq!(reader.read::<Address>().unwrap(), address);
+        assert_eq!(reader.read::<f64>().unwrap(), -1.5);
+        assert!(reader.is_empty());
+        assert!(reader.read::<u8>().is_err());
+
+        // untyped values are encoded as the typed ones
+        let values: Vec<ArgValue> =
+            serde_json::from_str(r#"[{"u64": 42}, {"string": "hello"}, {"bytes": [1, 2, 3]}]"#)
+                .unwrap();
+        assert_eq!(encode_arg_values(&values), bytes[..24]);
+    }
+
+    #[test]
+    fn test_args_struct() {
+        let transfer = Transfer {
+            to: Address::from_public_key(&KeyPair::generate().get_public_key()),
+            amount: Amount::from_raw(1_000),
+            memo: "rent".to_string(),
+        };
+        let bytes = Args::new().add(&transfer).into_bytes();
+        let decoded = ArgsReader::new(&bytes).read::<Transfer>().unwrap();
+        assert_eq!(decoded.to, transfer.to);
+        assert_eq!(decoded.amount, transfer.amount);
+        assert_eq!(decoded.memo, transfer.memo);
+        // truncated
+        assert!(ArgsReader::new(&bytes[..bytes.len() - 1])
+            .read::<Transfer>()
+            .is_err());
+    }
+}
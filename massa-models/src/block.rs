@@ -66,6 +66,10 @@ pub struct FilledBlock {
 pub type SecureShareBlock = SecureShare<Block, BlockId>;
 
 impl SecureShareContent for Block {
+    fn signature_period(&self) -> Option<u64> {
+        self.header.content.signature_period()
+    }
+
     fn new_verifiable<SC: Serializer<Self>, U: Id>(
         content: Self,
         content_serializer: SC,
@@ -161,6 +165,7 @@ impl Serializer<Block> for BlockSerializer {
     ///         slot: Slot::new(1, 1),
     ///         parents,
     ///         operation_merkle_root: Hash::compute_from("mno".as_bytes()),
+    ///         announced_version: 0,
     ///         endorsements: vec![
     ///             Endorsement::new_verifiable(
     ///                 Endorsement {
@@ -183,6 +188,7 @@ impl Serializer<Block> for BlockSerializer {
     ///             )
     ///             .unwrap(),
     ///         ],
+    ///         aggregated_endorsements: None,
     ///     },
     ///     BlockHeaderSerializer::new(),
     ///     &keypair,
@@ -256,6 +262,7 @@ impl Deserializer<Block> for BlockDeserializer {
     ///         slot: Slot::new(1, 1),
     ///         parents: parents.clone(),
     ///         operation_merkle_root: Hash::compute_from("mno".as_bytes()),
+    ///         announced_version: 0,
     ///         endorsements: vec![
     ///             Endorsement::new_verifiable(
     ///                 Endorsement {
@@ -278,6 +285,7 @@ impl Deserializer<Block> for BlockDeserializer {
     ///             )
     ///             .unwrap(),
     ///         ],
+    ///         aggregated_endorsements: None,
     ///     },
     ///     BlockHeaderSerializer::new(),
     ///     &keypair,
@@ -401,14 +409,15 @@ pub enum BlockGraphStatus {
 mod test {
     use super::*;
     use crate::{
-        block_header::BlockHeaderSerializer,
+        block_header::{BlockHeaderDeserializer, BlockHeaderSerializer, SecuredHeader},
         config::{ENDORSEMENT_COUNT, MAX_OPERATIONS_PER_BLOCK, THREAD_COUNT},
         endorsement::Endorsement,
         endorsement::EndorsementSerializer,
-        slot::Slot,
+        format_version::FormatVersionSchedule,
+        slot::{Slot, SlotSerializer},
     };
     use massa_hash::Hash;
-    use massa_serialization::DeserializeError;
+    use massa_serialization::{DeserializeError, U32VarIntSerializer};
     use massa_signature::KeyPair;
     use serial_test::serial;
     use std::str::FromStr;
@@ -460,7 +469,9 @@ mod test {
                 slot: Slot::new(1, 0),
                 parents,
                 operation_merkle_root: Hash::compute_from("mno".as_bytes()),
+                announced_version: 0,
                 endorsements: vec![endo1, endo2],
+                aggregated_endorsements: None,
             },
             BlockHeaderSerializer::new(),
             &keypair,
@@ -523,6 +534,157 @@ mod test {
         res_block.content.header.assert_invariants().unwrap();
     }
 
+    #[test]
+    fn test_header_format_versions() {
+        let schedule = FormatVersionSchedule::new(&[(Slot::new(2, 0), 1)], 1).unwrap();
+        let serializer = BlockHeaderSerializer::with_format_schedule(schedule.clone());
+        let deserializer = BlockHeaderDeserializer::with_format_schedule(
+            THREAD_COUNT,
+            ENDORSEMENT_COUNT,
+            schedule,
+        );
+        let header_at = |slot| BlockHeader {
+            slot,
+            parents: (0..THREAD_COUNT)
+                .map(|i| BlockId(Hash::compute_from(&[i])))
+                .collect(),
+            operation_merkle_root: Hash::compute_from("mno".as_bytes()),
+            announced_version: 0,
+            endorsements: vec![],
+            aggregated_endorsements: None,
+        };
+
+        // the headers before and after the activation are both parseable
+        for slot in [Slot::new(1, 5), Slot::new(2, 0)] {
+            let mut buffer = Vec::new();
+            serializer.serialize(&header_at(slot), &mut buffer).unwrap();
+            let (rest, header) = deserializer
+                .deserialize::<DeserializeError>(&buffer)
+                .unwrap();
+            assert!(rest.is_empty());
+            assert_eq!(header.slot, slot);
+        }
+
+        // the version 0 headers are written without version, the later ones with it
+        let mut legacy = Vec::new();
+        BlockHeaderSerializer::with_format_schedule(FormatVersionSchedule::default())
+            .serialize(&header_at(Slot::new(2, 0)), &mut legacy)
+            .unwrap();
+        let mut versioned = Vec::new();
+        serializer
+            .serialize(&header_at(Slot::new(2, 0)), &mut versioned)
+            .unwrap();
+        assert!(versioned.len() > legacy.len());
+
+        // a header written in version 0 after the activation is rejected
+        assert!(deserializer
+            .deserialize::<DeserializeError>(&legacy)
+            .is_err());
+
+        // an unterminated varint in place of the marker is an error, not a version 0 header
+        let root = Hash::compute_from("mno".as_bytes()).to_bytes().to_vec();
+        let root_end = legacy
+            .windows(root.len())
+            .position(|window| window == root.as_slice())
+            .unwrap()
+            + root.len();
+        let mut truncated = legacy[..root_end].to_vec();
+        truncated.push(0xFF);
+        assert!(BlockHeaderDeserializer::with_format_schedule(
+            THREAD_COUNT,
+            ENDORSEMENT_COUNT,
+            FormatVersionSchedule::default(),
+        )
+        .deserialize::<DeserializeError>(&truncated)
+        .is_err());
+    }
+
+    #[test]
+    fn test_legacy_header_id() {
+        let keypair = KeyPair::generate();
+        let content = BlockHeader {
+            slot: Slot::new(3, 1),
+            parents: (0..THREAD_COUNT)
+                .map(|i| BlockId(Hash::compute_from(&[i])))
+                .collect(),
+            operation_merkle_root: Hash::compute_from("mno".as_bytes()),
+            announced_version: 0,
+            endorsements: vec![],
+            aggregated_endorsements: None,
+        };
+
+        // original layout: slot, parents, merkle root, endorsement count and endorsements
+        let mut legacy = Vec::new();
+        SlotSerializer::new()
+            .serialize(&content.slot, &mut legacy)
+            .unwrap();
+        legacy.push(1);
+        for parent in content.parents.iter() {
+            legacy.extend(parent.0.to_bytes());
+        }
+        legacy.extend(content.operation_merkle_root.to_bytes());
+        U32VarIntSerializer::new()
+            .serialize(&0, &mut legacy)
+            .unwrap();
+
+        // a header of format version 0 is written in the original layout, so its id is unchanged
+        let schedule = FormatVersionSchedule::new(&[(Slot::new(5, 0), 1)], 1).unwrap();
+        let header: SecuredHeader = BlockHeader::new_verifiable(
+            content.clone(),
+            BlockHeaderSerializer::with_format_schedule(schedule.clone()),
+            &keypair,
+        )
+        .unwrap();
+        assert_eq!(header.serialized_data, legacy);
+        let mut hash_data = keypair.get_public_key().to_bytes().to_vec();
+        hash_data.extend(&legacy);
+        assert_eq!(header.id, BlockId(Hash::compute_from(&hash_data)));
+
+        // and it still deserializes with the same id once later versions are scheduled
+        let mut buffer = Vec::new();
+        SecureShareSerializer::new()
+            .serialize(&header, &mut buffer)
+            .unwrap();
+        let (rest, deserialized): (&[u8], SecuredHeader) =
+            SecureShareDeserializer::new(BlockHeaderDeserializer::with_format_schedule(
+                THREAD_COUNT,
+                ENDORSEMENT_COUNT,
+                schedule.clone(),
+            ))
+            .deserialize::<DeserializeError>(&buffer)
+            .unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(deserialized.id, header.id);
+        assert_eq!(deserialized.content.announced_version, 0);
+
+        // it cannot announce a network version, the headers of the later versions can
+        let announcing = BlockHeader {
+            announced_version: 3,
+            ..content.clone()
+        };
+        assert!(
+            BlockHeaderSerializer::with_format_schedule(schedule.clone())
+                .serialize(&announcing, &mut Vec::new())
+                .is_err()
+        );
+        let announcing = BlockHeader {
+            slot: Slot::new(5, 0),
+            ..announcing
+        };
+        let mut buffer = Vec::new();
+        BlockHeaderSerializer::with_format_schedule(schedule.clone())
+            .serialize(&announcing, &mut buffer)
+            .unwrap();
+        let (_, deserialized) = BlockHeaderDeserializer::with_format_schedule(
+            THREAD_COUNT,
+            ENDORSEMENT_COUNT,
+            schedule,
+        )
+        .deserialize::<DeserializeError>(&buffer)
+        .unwrap();
+        assert_eq!(deserialized.announced_version, 3);
+    }
+
     #[test]
     #[serial]
     fn test_genesis_block_serialization() {
@@ -535,7 +697,9 @@ mod test {
                 slot: Slot::new(0, 1),
                 parents,
                 operation_merkle_root: Hash::compute_from("mno".as_bytes()),
+                announced_version: 0,
                 endorsements: vec![],
+                aggregated_endorsements: None,
             },
             BlockHeaderSerializer::new(),
             &keypair,
@@ -614,12 +778,14 @@ mod test {
                 slot: Slot::new(0, 1),
                 parents,
                 operation_merkle_root: Hash::compute_from("mno".as_bytes()),
+                announced_version: 0,
                 endorsements: vec![Endorsement::new_verifiable(
                     endorsement,
                     EndorsementSerializer::new(),
                     &keypair,
                 )
                 .unwrap()],
+                aggregated_endorsements: None,
             },
             BlockHeaderSerializer::new(),
             &keypair,
@@ -671,7 +837,9 @@ mod test {
                 slot: Slot::new(0, 1),
                 parents,
                 operation_merkle_root: Hash::compute_from("mno".as_bytes()),
+                announced_version: 0,
                 endorsements: vec![],
+                aggregated_endorsements: None,
             },
             BlockHeaderSerializer::new(),
             &keypair,
@@ -718,7 +886,9 @@ mod test {
                 slot: Slot::new(1, 1),
                 parents: vec![],
                 operation_merkle_root: Hash::compute_from("mno".as_bytes()),
+                announced_version: 0,
                 endorsements: vec![],
+                aggregated_endorsements: None,
             },
             BlockHeaderSerializer::new(),
             &keypair,
@@ -768,7 +938,9 @@ mod test {
                 slot: Slot::new(1, 1),
                 parents,
                 operation_merkle_root: Hash::compute_from("mno".as_bytes()),
+                announced_version: 0,
                 endorsements: vec![],
+                aggregated_endorsements: None,
             },
             BlockHeaderSerializer::new(),
             &keypair,
@@ -840,7 +1012,9 @@ mod test {
                 slot: Slot::new(1, 0),
                 parents,
                 operation_merkle_root: Hash::compute_from("mno".as_bytes()),
+                announced_version: 0,
                 endorsements,
+                aggregated_endorsements: None,
             },
             BlockHeaderSerializer::new(),
             &keypair,
@@ -889,7 +1063,9 @@ mod test {
                 slot: Slot::new(1, 1),
                 parents,
                 operation_merkle_root: Hash::compute_from("mno".as_bytes()),
+                announced_version: 0,
                 endorsements: vec![],
+                aggregated_endorsements: None,
             },
             BlockHeaderSerializer::new(),
             &keypair,
@@ -953,7 +1129,9 @@ mod test {
                 slot: Slot::new(1, 1),
                 parents,
                 operation_merkle_root: Hash::compute_from("mno".as_bytes()),
+                announced_version: 0,
                 endorsements,
+                aggregated_endorsements: None,
             },
             BlockHeaderSerializer::new(),
             &keypair,
@@ -1022,7 +1200,9 @@ mod test {
                 slot: Slot::new(1, 0),
                 parents,
                 operation_merkle_root: Hash::compute_from("mno".as_bytes()),
+                announced_version: 0,
                 endorsements: vec![endo1],
+                aggregated_endorsements: None,
             },
             BlockHeaderSerializer::new(),
             &keypair,
@@ -1103,7 +1283,9 @@ mod test {
                 slot: Slot::new(1, 0),
                 parents,
                 operation_merkle_root: Hash::compute_from("mno".as_bytes()),
+                announced_version: 0,
                 endorsements: vec![endo1, endo2],
+                aggregated_endorsements: None,
             },
             BlockHeaderSerializer::new(),
             &keypair,
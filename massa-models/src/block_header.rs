@@ -1,9 +1,13 @@
+use crate::aggregate_signature::{
+    AggregateSignature, AggregateSignatureDeserializer, AggregateSignatureSerializer,
+};
 use crate::block_id::BlockId;
 use crate::config::THREAD_COUNT;
 use crate::endorsement::{
     Endorsement, EndorsementDeserializerLW, EndorsementId, EndorsementSerializer,
     EndorsementSerializerLW, SecureShareEndorsement,
 };
+use crate::format_version::FormatVersionSchedule;
 use crate::secure_share::{
     SecureShare, SecureShareContent, SecureShareDeserializer, SecureShareSerializer,
 };
@@ -15,7 +19,7 @@ use massa_serialization::{
 use nom::branch::alt;
 use nom::bytes::complete::tag;
 use nom::error::{context, ContextError, ParseError};
-use nom::multi::{count, length_count};
+use nom::multi::count;
 use nom::sequence::{preceded, tuple};
 use nom::{IResult, Parser};
 use serde::{Deserialize, Serialize};
@@ -32,10 +36,25 @@ pub struct BlockHeader {
     pub parents: Vec<BlockId>,
     /// all operations hash
     pub operation_merkle_root: Hash,
-    /// endorsements
+    /// latest network version the block producer is ready for, see `massa_versioning`.
+    /// Always 0 in the headers of wire-format version 0, which cannot carry it.
+    pub announced_version: u32,
+    /// endorsements, empty in the headers aggregating them
     pub endorsements: Vec<SecureShareEndorsement>,
+    /// aggregated BLS signatures of the endorsers of the parent in the thread of the block,
+    /// see `massa_models::endorsement_signature`
+    #[serde(default)]
+    pub aggregated_endorsements: Option<AggregateSignature>,
 }
 
+/// Endorsement count announcing aggregated endorsements in a serialized header,
+/// the count of the endorsements carried one by one being at most `ENDORSEMENT_COUNT`
+const AGGREGATED_ENDORSEMENTS_MARKER: u32 = u32::MAX;
+
+/// Value announcing an explicit wire-format version after the operation merkle root of a serialized header,
+/// where the headers of version 0 carry their endorsement count
+const FORMAT_VERSION_MARKER: u32 = u32::MAX - 1;
+
 // TODO: gh-issue #3398
 #[cfg(any(test, feature = "testing"))]
 impl BlockHeader {
@@ -44,7 +63,7 @@ impl BlockHeader {
             if !self.parents.is_empty() {
                 return Err("Invariant broken: genesis block with parent(s)".into());
             }
-            if !self.endorsements.is_empty() {
+            if !self.endorsements.is_empty() || self.aggregated_endorsements.is_some() {
                 return Err("Invariant broken: genesis block with endorsement(s)".into());
             }
         } else {
@@ -56,6 +75,9 @@ impl BlockHeader {
             if self.endorsements.len() > crate::config::ENDORSEMENT_COUNT as usize {
                 return Err("Invariant broken: endorsement count too high".into());
             }
+            if !self.endorsements.is_empty() && self.aggregated_endorsements.is_some() {
+                return Err("Invariant broken: both endorsements and aggregated ones".into());
+            }
 
             let parent_id = self.parents[self.slot.thread as usize];
             for endo in self.endorsements.iter() {
@@ -83,9 +105,14 @@ impl BlockHeader {
 pub type SecuredHeader = SecureShare<BlockHeader, BlockId>;
 
 impl SecuredHeader {
-    /// gets the header fitness
+    /// gets the header fitness: one plus the number of endorsements, aggregated or not
     pub fn get_fitness(&self) -> u64 {
-        (self.content.endorsements.len() as u64) + 1
+        let aggregated = self
+            .content
+            .aggregated_endorsements
+            .as_ref()
+            .map_or(0, AggregateSignature::signer_count);
+        (self.content.endorsements.len() + aggregated) as u64 + 1
     }
     // TODO: gh-issue #3398
     #[allow(dead_code)]
@@ -97,24 +124,39 @@ impl SecuredHeader {
     }
 }
 
-impl SecureShareContent for BlockHeader {}
+impl SecureShareContent for BlockHeader {
+    fn signature_period(&self) -> Option<u64> {
+        Some(self.slot.period)
+    }
+}
 
-/// Serializer for `BlockHeader`
+/// Serializer for `BlockHeader`, writing each header in the wire-format version active at its slot.
+/// The headers of version 0 carry no version, the later ones carry `FORMAT_VERSION_MARKER` and their version
+/// after the operation merkle root, followed by the announced network version.
 pub struct BlockHeaderSerializer {
+    format_schedule: FormatVersionSchedule,
     slot_serializer: SlotSerializer,
     endorsement_serializer: SecureShareSerializer,
     endorsement_content_serializer: EndorsementSerializerLW,
+    aggregate_serializer: AggregateSignatureSerializer,
     u32_serializer: U32VarIntSerializer,
 }
 
 impl BlockHeaderSerializer {
-    /// Creates a new `BlockHeaderSerializer`
+    /// Creates a new `BlockHeaderSerializer` following the format schedule of the network
     pub fn new() -> Self {
+        Self::with_format_schedule(FormatVersionSchedule::block_header())
+    }
+
+    /// Creates a new `BlockHeaderSerializer` following `format_schedule`
+    pub fn with_format_schedule(format_schedule: FormatVersionSchedule) -> Self {
         Self {
+            format_schedule,
             slot_serializer: SlotSerializer::new(),
             endorsement_serializer: SecureShareSerializer::new(),
             u32_serializer: U32VarIntSerializer::new(),
             endorsement_content_serializer: EndorsementSerializerLW::new(),
+            aggregate_serializer: AggregateSignatureSerializer::new(),
         }
     }
 }
@@ -144,6 +186,7 @@ impl Serializer<BlockHeader> for BlockHeaderSerializer {
     ///   slot: Slot::new(1, 1),
     ///   parents,
     ///   operation_merkle_root: Hash::compute_from("mno".as_bytes()),
+    ///   announced_version: 0,
     ///   endorsements: vec![
     ///     Endorsement::new_verifiable(
     ///        Endorsement {
@@ -166,6 +209,7 @@ impl Serializer<BlockHeader> for BlockHeaderSerializer {
     ///     )
     ///     .unwrap(),
     ///    ],
+    ///   aggregated_endorsements: None,
     /// };
     /// let mut buffer = vec![];
     /// BlockHeaderSerializer::new().serialize(&header, &mut buffer).unwrap();
@@ -185,6 +229,33 @@ impl Serializer<BlockHeader> for BlockHeaderSerializer {
         // operations merkle root
         buffer.extend(value.operation_merkle_root.to_bytes());
 
+        // explicit wire-format version, from version 1 on
+        let format_version = self.format_schedule.version_at(&value.slot);
+        if format_version > 0 {
+            self.u32_serializer
+                .serialize(&FORMAT_VERSION_MARKER, buffer)?;
+            self.u32_serializer.serialize(&format_version, buffer)?;
+            // announced network version
+            self.u32_serializer
+                .serialize(&value.announced_version, buffer)?;
+        } else if value.announced_version != 0 {
+            return Err(SerializeError::GeneralError(
+                "the headers of format version 0 cannot announce a network version".into(),
+            ));
+        }
+
+        // aggregated endorsements replace the endorsements
+        if let Some(aggregate) = &value.aggregated_endorsements {
+            if !value.endorsements.is_empty() {
+                return Err(SerializeError::GeneralError(
+                    "a header cannot carry endorsements along with aggregated ones".into(),
+                ));
+            }
+            self.u32_serializer
+                .serialize(&AGGREGATED_ENDORSEMENTS_MARKER, buffer)?;
+            return self.aggregate_serializer.serialize(aggregate, buffer);
+        }
+
         self.u32_serializer.serialize(
             &value.endorsements.len().try_into().map_err(|err| {
                 SerializeError::GeneralError(format!("too many endorsements: {}", err))
@@ -202,30 +273,58 @@ impl Serializer<BlockHeader> for BlockHeaderSerializer {
     }
 }
 
-/// Deserializer for `BlockHeader`
+/// Deserializer for `BlockHeader`, accepting each header only in the wire-format version active at its slot
 pub struct BlockHeaderDeserializer {
+    format_schedule: FormatVersionSchedule,
+    format_version_deserializer: U32VarIntDeserializer,
     slot_deserializer: SlotDeserializer,
     endorsement_serializer: EndorsementSerializer,
     length_endorsements_deserializer: U32VarIntDeserializer,
+    aggregate_deserializer: AggregateSignatureDeserializer,
     hash_deserializer: HashDeserializer,
+    announced_version_deserializer: U32VarIntDeserializer,
     thread_count: u8,
     endorsement_count: u32,
 }
 
 impl BlockHeaderDeserializer {
-    /// Creates a new `BlockHeaderDeserializerLW`
-    pub const fn new(thread_count: u8, endorsement_count: u32) -> Self {
+    /// Creates a new `BlockHeaderDeserializerLW` following the format schedule of the network
+    pub fn new(thread_count: u8, endorsement_count: u32) -> Self {
+        Self::with_format_schedule(
+            thread_count,
+            endorsement_count,
+            FormatVersionSchedule::block_header(),
+        )
+    }
+
+    /// Creates a new `BlockHeaderDeserializerLW` following `format_schedule`
+    pub fn with_format_schedule(
+        thread_count: u8,
+        endorsement_count: u32,
+        format_schedule: FormatVersionSchedule,
+    ) -> Self {
         Self {
+            format_schedule,
+            format_version_deserializer: U32VarIntDeserializer::new(
+                Included(0),
+                Included(u32::MAX),
+            ),
             slot_deserializer: SlotDeserializer::new(
                 (Included(0), Included(u64::MAX)),
                 (Included(0), Excluded(thread_count)),
             ),
             endorsement_serializer: EndorsementSerializer::new(),
+            // the count can also be the marker of aggregated endorsements
             length_endorsements_deserializer: U32VarIntDeserializer::new(
                 Included(0),
-                Included(endorsement_count),
+                Included(u32::MAX),
             ),
+            aggregate_deserializer: AggregateSignatureDeserializer::new(endorsement_count as usize),
             hash_deserializer: HashDeserializer::new(),
+            announced_version_deserializer: U32VarIntDeserializer::new(
+                Included(0),
+                Included(u32::MAX),
+            ),
             thread_count,
             endorsement_count,
         }
@@ -251,6 +350,7 @@ impl Deserializer<BlockHeader> for BlockHeaderDeserializer {
     ///   slot: Slot::new(1, 1),
     ///   parents: parents.clone(),
     ///   operation_merkle_root: Hash::compute_from("mno".as_bytes()),
+    ///   announced_version: 0,
     ///   endorsements: vec![
     ///     Endorsement::new_verifiable(
     ///        Endorsement {
@@ -273,6 +373,7 @@ impl Deserializer<BlockHeader> for BlockHeaderDeserializer {
     ///     )
     ///     .unwrap(),
     ///    ],
+    ///   aggregated_endorsements: None,
     /// };
     /// let mut buffer = vec![];
     /// BlockHeaderSerializer::new().serialize(&header, &mut buffer).unwrap();
@@ -286,64 +387,98 @@ impl Deserializer<BlockHeader> for BlockHeaderDeserializer {
         &self,
         buffer: &'a [u8],
     ) -> IResult<&'a [u8], BlockHeader, E> {
-        let (rest, (slot, parents, operation_merkle_root)): (&[u8], (Slot, Vec<BlockId>, Hash)) =
-            context("Failed BlockHeader deserialization", |input| {
-                let (rest, (slot, parents)) = tuple((
-                    context("Failed slot deserialization", |input| {
-                        self.slot_deserializer.deserialize(input)
-                    }),
-                    context(
-                        "Failed parents deserialization",
-                        alt((
-                            preceded(tag(&[0]), |input| Ok((input, Vec::new()))),
-                            preceded(
-                                tag(&[1]),
-                                count(
-                                    context("Failed block_id deserialization", |input| {
-                                        self.hash_deserializer
-                                            .deserialize(input)
-                                            .map(|(rest, hash)| (rest, BlockId(hash)))
-                                    }),
-                                    self.thread_count as usize,
-                                ),
+        #[allow(clippy::type_complexity)]
+        let (rest, (slot, parents, operation_merkle_root, announced_version)): (
+            &[u8],
+            (Slot, Vec<BlockId>, Hash, u32),
+        ) = context("Failed BlockHeader deserialization", |input| {
+            let (rest, (slot, parents)) = tuple((
+                context("Failed slot deserialization", |input| {
+                    self.slot_deserializer.deserialize(input)
+                }),
+                context(
+                    "Failed parents deserialization",
+                    alt((
+                        preceded(tag(&[0]), |input| Ok((input, Vec::new()))),
+                        preceded(
+                            tag(&[1]),
+                            count(
+                                context("Failed block_id deserialization", |input| {
+                                    self.hash_deserializer
+                                        .deserialize(input)
+                                        .map(|(rest, hash)| (rest, BlockId(hash)))
+                                }),
+                                self.thread_count as usize,
                             ),
-                        )),
-                    ),
-                ))
-                .parse(input)?;
-
-                // validate the parent/slot invariats before moving on to other fields
-                if slot.period == 0 && !parents.is_empty() {
-                    return Err(nom::Err::Failure(ContextError::add_context(
-                        rest,
-                        "Genesis block cannot contain parents",
-                        ParseError::from_error_kind(rest, nom::error::ErrorKind::Fail),
-                    )));
-                } else if slot.period != 0 && parents.len() != THREAD_COUNT as usize {
-                    return Err(nom::Err::Failure(ContextError::add_context(
-                        rest,
-                        const_format::formatcp!(
-                            "Non-genesis block must have {} parents",
-                            THREAD_COUNT
                         ),
-                        ParseError::from_error_kind(rest, nom::error::ErrorKind::Fail),
-                    )));
-                }
+                    )),
+                ),
+            ))
+            .parse(input)?;
+
+            // validate the parent/slot invariats before moving on to other fields
+            if slot.period == 0 && !parents.is_empty() {
+                return Err(nom::Err::Failure(ContextError::add_context(
+                    rest,
+                    "Genesis block cannot contain parents",
+                    ParseError::from_error_kind(rest, nom::error::ErrorKind::Fail),
+                )));
+            } else if slot.period != 0 && parents.len() != THREAD_COUNT as usize {
+                return Err(nom::Err::Failure(ContextError::add_context(
+                    rest,
+                    const_format::formatcp!("Non-genesis block must have {} parents", THREAD_COUNT),
+                    ParseError::from_error_kind(rest, nom::error::ErrorKind::Fail),
+                )));
+            }
 
-                let (rest, merkle) = context("Failed operation_merkle_root", |input| {
-                    self.hash_deserializer.deserialize(input)
+            let (rest, merkle) = context("Failed operation_merkle_root", |input| {
+                self.hash_deserializer.deserialize(input)
+            })
+            .parse(rest)?;
+
+            // explicit wire-format version, version 0 carrying none:
+            // any other value is the first field of version 0, parsed again below
+            let (after_marker, marker) =
+                context("Failed format_version_marker deserialization", |input| {
+                    self.format_version_deserializer.deserialize(input)
                 })
                 .parse(rest)?;
-                Ok((rest, (slot, parents, merkle)))
-            })
-            .parse(buffer)?;
+            let (rest, format_version) = if marker == FORMAT_VERSION_MARKER {
+                context("Failed format_version deserialization", |input| {
+                    self.format_version_deserializer.deserialize(input)
+                })
+                .parse(after_marker)?
+            } else {
+                (rest, 0)
+            };
+            if format_version != self.format_schedule.version_at(&slot) {
+                return Err(nom::Err::Failure(ContextError::add_context(
+                    rest,
+                    "Header format version not active at its slot",
+                    ParseError::from_error_kind(rest, nom::error::ErrorKind::Verify),
+                )));
+            }
+
+            let (rest, announced_version) = if format_version > 0 {
+                context("Failed announced_version", |input| {
+                    self.announced_version_deserializer.deserialize(input)
+                })
+                .parse(rest)?
+            } else {
+                (rest, 0)
+            };
+            Ok((rest, (slot, parents, merkle, announced_version)))
+        })
+        .parse(buffer)?;
 
         if parents.is_empty() {
             let res = BlockHeader {
                 slot,
                 parents,
                 operation_merkle_root,
+                announced_version,
                 endorsements: Vec::new(),
+                aggregated_endorsements: None,
             };
 
             // TODO: gh-issue #3398
@@ -355,6 +490,33 @@ impl Deserializer<BlockHeader> for BlockHeaderDeserializer {
                 res,
             ));
         }
+        let (rest, endorsement_count) = context("Failed length deserialization", |input| {
+            self.length_endorsements_deserializer.deserialize(input)
+        })
+        .parse(rest)?;
+        if endorsement_count == AGGREGATED_ENDORSEMENTS_MARKER {
+            let (rest, aggregate) =
+                context("Failed aggregated endorsements deserialization", |input| {
+                    self.aggregate_deserializer.deserialize(input)
+                })
+                .parse(rest)?;
+            let header = BlockHeader {
+                slot,
+                parents,
+                operation_merkle_root,
+                announced_version,
+                endorsements: Vec::new(),
+                aggregated_endorsements: Some(aggregate),
+            };
+            return Ok((rest, header));
+        }
+        if endorsement_count > self.endorsement_count {
+            return Err(nom::Err::Error(ParseError::from_error_kind(
+                rest,
+                nom::error::ErrorKind::TooLarge,
+            )));
+        }
+
         // Now deser the endorsements (which were light-weight serialized)
         let endorsement_deserializer =
             SecureShareDeserializer::new(EndorsementDeserializerLW::new(
@@ -366,10 +528,7 @@ impl Deserializer<BlockHeader> for BlockHeaderDeserializer {
         let parent_id = parents[slot.thread as usize];
         let (rest, endorsements): (&[u8], Vec<SecureShare<Endorsement, EndorsementId>>) = context(
             "Failed endorsements deserialization",
-            length_count::<&[u8], SecureShare<Endorsement, EndorsementId>, u32, E, _, _>(
-                context("Failed length deserialization", |input| {
-                    self.length_endorsements_deserializer.deserialize(input)
-                }),
+            count(
                 context("Failed endorsement deserialization", |input| {
                     let (rest, endo) = endorsement_deserializer
                         .deserialize_with(&self.endorsement_serializer, input)?;
@@ -384,6 +543,7 @@ impl Deserializer<BlockHeader> for BlockHeaderDeserializer {
 
                     Ok((rest, endo))
                 }),
+                endorsement_count as usize,
             ),
         )
         .parse(rest)?;
@@ -404,7 +564,9 @@ impl Deserializer<BlockHeader> for BlockHeaderDeserializer {
             slot,
             parents,
             operation_merkle_root,
+            announced_version,
             endorsements,
+            aggregated_endorsements: None,
         };
 
         // TODO: gh-issue #3398
@@ -423,6 +585,7 @@ impl std::fmt::Display for BlockHeader {
             self.slot.period, self.slot.thread,
         )?;
         writeln!(f, "\tMerkle root: {}", self.operation_merkle_root,)?;
+        writeln!(f, "\tAnnounced version: {}", self.announced_version)?;
         writeln!(f, "\tParents: ")?;
         for id in self.parents.iter() {
             let str_id = id.to_string();
@@ -445,7 +608,14 @@ impl std::fmt::Display for BlockHeader {
             writeln!(f, "\t\tEndorsed block: {}", ed.content.endorsed_block)?;
             writeln!(f, "\t\tSignature: {}", ed.signature)?;
         }
-        if self.endorsements.is_empty() {
+        if let Some(aggregate) = &self.aggregated_endorsements {
+            writeln!(
+                f,
+                "\tAggregated endorsements: {} signers, signature {}",
+                aggregate.signer_count(),
+                aggregate.signature
+            )?;
+        } else if self.endorsements.is_empty() {
             writeln!(f, "\tNo endorsements found")?;
         }
         Ok(())
@@ -0,0 +1,45 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use crate::block_id::BlockId;
+use crate::slot::Slot;
+use massa_hash::Hash;
+use serde::{Deserialize, Serialize};
+
+/// Point of the final history pinned by the operator of the node (weak subjectivity checkpoint).
+///
+/// A node bootstrapping from scratch trusts the state sent by its bootstrap servers.
+/// With a checkpoint, the bootstrapped state and the blocks synchronized afterwards must agree with it,
+/// so that malicious servers cannot make a new node follow a fake history forked long ago.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrustedCheckpoint {
+    /// final slot of the checkpoint
+    pub slot: Slot,
+    /// final state hash at the end of `slot`
+    pub final_state_hash: Hash,
+    /// id of the final block at `slot`, none if the slot was missed
+    pub block_id: Option<BlockId>,
+}
+
+impl TrustedCheckpoint {
+    /// Whether the block `block_id` at `slot` belongs to another history than the checkpoint
+    ///
+    /// ```
+    /// # use massa_hash::Hash;
+    /// # use massa_models::block_id::BlockId;
+    /// # use massa_models::checkpoint::TrustedCheckpoint;
+    /// # use massa_models::slot::Slot;
+    /// let block_id = BlockId(Hash::compute_from(b"block"));
+    /// let checkpoint = TrustedCheckpoint {
+    ///     slot: Slot::new(10, 3),
+    ///     final_state_hash: Hash::compute_from(b"state"),
+    ///     block_id: Some(block_id),
+    /// };
+    /// let other_id = BlockId(Hash::compute_from(b"other"));
+    /// assert!(!checkpoint.conflicts_with(&Slot::new(10, 3), &block_id));
+    /// assert!(checkpoint.conflicts_with(&Slot::new(10, 3), &other_id));
+    /// assert!(!checkpoint.conflicts_with(&Slot::new(11, 3), &other_id));
+    /// ```
+    pub fn conflicts_with(&self, slot: &Slot, block_id: &BlockId) -> bool {
+        *slot == self.slot && self.block_id.as_ref() != Some(block_id)
+    }
+}
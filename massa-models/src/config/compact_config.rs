@@ -27,6 +27,10 @@ pub struct CompactConfig {
     pub roll_price: Amount,
     /// Max total size of a block
     pub max_block_size: u32,
+    /// Identifier of the network, covered by the signatures from `chain_id_activation_period`
+    pub chain_id: u64,
+    /// Period from which the signatures cover the chain id
+    pub chain_id_activation_period: u64,
 }
 
 impl Default for CompactConfig {
@@ -42,6 +46,8 @@ impl Default for CompactConfig {
             block_reward: BLOCK_REWARD,
             roll_price: ROLL_PRICE,
             max_block_size: MAX_BLOCK_SIZE,
+            chain_id: CHAIN_ID.0,
+            chain_id_activation_period: CHAIN_ID_ACTIVATION_PERIOD,
         }
     }
 }
@@ -69,6 +75,11 @@ impl Display for CompactConfig {
         writeln!(f, "    Periods per cycle: {}", self.periods_per_cycle)?;
         writeln!(f, "    Roll price: {}", self.roll_price)?;
         writeln!(f, "    Max block size (in bytes): {}", self.max_block_size)?;
+        writeln!(
+            f,
+            "    Chain id: {} (signed from period {})",
+            self.chain_id, self.chain_id_activation_period
+        )?;
         Ok(())
     }
 }
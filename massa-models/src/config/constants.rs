@@ -15,7 +15,12 @@
 //! (`default_testing.rs`) But as for the current file you shouldn't modify it.
 use std::str::FromStr;
 
-use crate::{address::ADDRESS_SIZE_BYTES, amount::Amount, version::Version};
+use crate::{
+    address::{ChainId, ADDRESS_SIZE_BYTES},
+    amount::Amount,
+    slot::Slot,
+    version::{Capabilities, Version},
+};
 use massa_signature::KeyPair;
 use massa_time::MassaTime;
 use num::rational::Ratio;
@@ -30,6 +35,10 @@ pub const MAX_ASK_BLOCKS_PER_MESSAGE: u32 = 128;
 pub const MAX_OPERATIONS_PER_MESSAGE: u32 = 1024;
 /// Length of the handshake random signature
 pub const HANDSHAKE_RANDOMNESS_SIZE_BYTES: usize = 32;
+/// Maximum length of the text fields of the metadata a node advertises to its peers
+pub const MAX_NODE_METADATA_STRING_LENGTH: u16 = 256;
+/// Maximum number of capabilities a node can advertise in its metadata
+pub const MAX_NODE_METADATA_CAPABILITIES: u32 = 32;
 
 /// Consensus static parameters (defined by protocol used)
 /// Changing one of the following values is considered as a breaking change
@@ -38,8 +47,9 @@ pub const CHANNEL_SIZE: usize = 1024;
 
 lazy_static::lazy_static! {
     /// Time in milliseconds when the blockclique started.
+    /// In sandbox, the `GENESIS_TIMESTAMP` variable can hold milliseconds or an RFC3339 timestamp.
     pub static ref GENESIS_TIMESTAMP: MassaTime = if cfg!(feature = "sandbox") {
-        std::env::var("GENESIS_TIMESTAMP").map(|timestamp| timestamp.parse::<u64>().unwrap().into()).unwrap_or_else(|_|
+        std::env::var("GENESIS_TIMESTAMP").map(|timestamp| timestamp.parse::<MassaTime>().unwrap()).unwrap_or_else(|_|
             MassaTime::now()
                 .unwrap()
                 .saturating_add(MassaTime::from_millis(1000 * 10))
@@ -64,13 +74,43 @@ lazy_static::lazy_static! {
         if cfg!(feature = "sandbox") {
             "SAND.20.0"
         } else {
-            "TEST.20.2"
+            "TEST.20.3"
         }
         .parse()
         .unwrap()
     };
 }
 
+/// Minor version of `VERSION` from which the bootstrap messages carry the hashes of the streamed final state:
+/// the nodes of older minor versions cannot bootstrap from this node, nor this node from them
+pub const BOOTSTRAP_MESSAGES_MINOR_VERSION: u32 = 3;
+
+/// Optional features supported by this node, advertised during handshakes
+pub const NODE_CAPABILITIES: Capabilities = Capabilities::LIGHT_BOOTSTRAP
+    .union(Capabilities::BATCHED_ENDORSEMENTS)
+    .union(Capabilities::ERASURE_CODED_BLOCKS)
+    .union(Capabilities::MIP_STORE)
+    .union(Capabilities::PING)
+    .union(Capabilities::HANDSHAKE_TRANSCRIPT)
+    .union(Capabilities::CLOCK_SYNC)
+    .union(Capabilities::FINAL_SLOT)
+    .union(Capabilities::ENDORSEMENT_SIGNATURES);
+
+/// Identifier of the network, mixed into the signatures of operations, endorsements and block headers
+/// so that they cannot be replayed on another network
+pub const CHAIN_ID: ChainId = if cfg!(feature = "sandbox") {
+    ChainId(77)
+} else {
+    ChainId::TESTNET
+};
+/// Period from which the signatures are bound to `CHAIN_ID`: the expiry period of operations,
+/// the slot period of endorsements and block headers
+pub const CHAIN_ID_ACTIVATION_PERIOD: u64 = if cfg!(feature = "sandbox") {
+    0
+} else {
+    200_000
+};
+
 /// Price of a roll in the network
 pub const ROLL_PRICE: Amount = Amount::from_mantissa_scale(100, 0);
 /// Block reward is given for each block creation
@@ -89,12 +129,19 @@ pub const INITIAL_DRAW_SEED: &str = "massa_genesis_seed";
 pub const THREAD_COUNT: u8 = 32;
 /// Number of endorsement
 pub const ENDORSEMENT_COUNT: u32 = 16;
+/// Version of the consensus from which the endorsements of the block headers are aggregated,
+/// see `massa_models::endorsement_signature`
+pub const AGGREGATED_ENDORSEMENTS_VERSION: u32 = 1;
 /// Threshold for fitness.
 pub const DELTA_F0: u64 = 64 * (ENDORSEMENT_COUNT as u64 + 1);
 /// Maximum number of operations per block
 pub const MAX_OPERATIONS_PER_BLOCK: u32 = 5000;
 /// Maximum block size in bytes
 pub const MAX_BLOCK_SIZE: u32 = 1_000_000;
+/// Number of erasure-coded chunks needed to rebuild a block body
+pub const BLOCK_ERASURE_DATA_CHUNKS: u16 = 8;
+/// Number of erasure-coded chunks a block body is split into
+pub const BLOCK_ERASURE_TOTAL_CHUNKS: u16 = 12;
 /// Maximum capacity of the asynchronous messages pool
 pub const MAX_ASYNC_POOL_LENGTH: u64 = 10_000;
 /// Maximum data size in async message
@@ -184,6 +231,8 @@ pub const IP_LIST_MAX_SIZE: usize = 10000;
 pub const BOOTSTRAP_RANDOMNESS_SIZE_BYTES: usize = 32;
 /// Max size of the printed error
 pub const MAX_BOOTSTRAP_ERROR_LENGTH: u64 = 10000;
+/// Max number of ledger keys requested by a light bootstrap
+pub const MAX_LIGHT_BOOTSTRAP_LEDGER_KEYS: u32 = 1000;
 
 /// Protocol controller channel size
 pub const PROTOCOL_CONTROLLER_CHANNEL_SIZE: usize = 1024;
@@ -192,6 +241,21 @@ pub const PROTOCOL_EVENT_CHANNEL_SIZE: usize = 1024;
 /// Pool controller channel size
 pub const POOL_CONTROLLER_CHANNEL_SIZE: usize = 1024;
 
+// ***********************
+// Wire-format versions
+//
+
+/// Activation slots of the block header wire-format versions, version 0 being active from genesis.
+/// Versions must increase with their activation slot and be at most `BLOCK_HEADER_FORMAT_VERSION`.
+pub const BLOCK_HEADER_FORMAT_ACTIVATIONS: &[(Slot, u32)] = &[];
+/// Latest block header wire-format version known by the node, see `massa_models::format_version`
+pub const BLOCK_HEADER_FORMAT_VERSION: u32 = 1;
+/// Activation slots of the operation wire-format versions, version 0 being active from genesis.
+/// Versions must increase with their activation slot and be at most `OPERATION_FORMAT_VERSION`.
+pub const OPERATION_FORMAT_ACTIVATIONS: &[(Slot, u32)] = &[];
+/// Latest operation wire-format version known by the node, see `massa_models::format_version`
+pub const OPERATION_FORMAT_VERSION: u32 = 0;
+
 // ***********************
 // Constants used for execution module (injected from ConsensusConfig)
 //
@@ -200,6 +264,26 @@ pub const POOL_CONTROLLER_CHANNEL_SIZE: usize = 1024;
 pub const MAX_GAS_PER_BLOCK: u64 = u32::MAX as u64;
 /// Maximum of GAS allowed for asynchronous messages execution on one slot
 pub const MAX_ASYNC_GAS: u64 = 1_000_000_000;
+/// Maximum priority fee an asynchronous message can offer to be executed before the others
+pub const MAX_ASYNC_PRIORITY_FEE: Amount = Amount::from_mantissa_scale(1_000, 0);
+/// Maximum length of the memo of a transaction, in bytes
+pub const MAX_TRANSACTION_MEMO_LENGTH: u64 = 256;
+/// Cost of one byte of the memo of a transaction, burnt at execution
+pub const TRANSACTION_MEMO_COST_PER_BYTE: Amount = Amount::from_mantissa_scale(1, 4);
+/// Maximum number of recipients of a multi-recipient transaction
+pub const MAX_MULTI_TRANSACTION_OUTPUTS: u64 = 256;
+/// Minimal fee of a multi-recipient transaction, per recipient
+pub const MULTI_TRANSACTION_FEE_PER_OUTPUT: Amount = Amount::from_mantissa_scale(1, 3);
+/// Maximum number of pending scheduled transfers of an address
+pub const MAX_SCHEDULED_TRANSFERS_PER_ADDRESS: u64 = 16;
+/// Maximum delay between the execution of a scheduled transfer and its activation slot, in periods
+pub const MAX_SCHEDULED_TRANSFER_DELAY: u64 = 100_000;
+/// Version of the execution from which the storage rent is charged, see `massa_versioning`
+pub const STORAGE_RENT_VERSION: u32 = 1;
+/// Rent of one byte of bytecode or datastore for one cycle, charged at the start of each cycle. Zero disables the rent
+pub const STORAGE_RENT_PER_BYTE: Amount = Amount::zero();
+/// Number of cycles an address can stay unable to pay its storage rent before its bytecode and datastore are reclaimed
+pub const STORAGE_RENT_GRACE_CYCLES: u64 = 4;
 
 //
 // Constants used in network
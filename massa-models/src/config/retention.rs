@@ -0,0 +1,281 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Retention rules of the objects kept in RAM by the node.
+//!
+//! Blocks, operations, endorsements, execution events and PoS history are held by several modules
+//! (consensus, pool, execution, final state), each one pruning them when they exceed its limits.
+//! The [`PruningCoordinator`] owns all these limits so that they are set in a single place:
+//! when a memory budget is configured, the limits are scaled down together until the
+//! estimated memory use of the retained objects fits in the budget.
+//!
+//! The budget is an estimate applied once at startup. The [`CapacityScale`] shared with the pool and protocol
+//! lowers their capacities at runtime, when the memory monitor of the node measures that the memory actually used
+//! approaches its cap.
+
+use crate::config::constants::{MAX_BLOCK_SIZE, MAX_OPERATIONS_PER_BLOCK};
+use massa_time::MassaTime;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+/// Estimated memory use of a full block, in bytes
+const ESTIMATED_BLOCK_SIZE: u64 = MAX_BLOCK_SIZE as u64;
+/// Estimated memory use of a discarded block entry, in bytes
+const ESTIMATED_DISCARDED_BLOCK_SIZE: u64 = 1_024;
+/// Estimated memory use of an operation, in bytes
+const ESTIMATED_OPERATION_SIZE: u64 = (MAX_BLOCK_SIZE / MAX_OPERATIONS_PER_BLOCK) as u64;
+/// Estimated memory use of an endorsement, in bytes
+const ESTIMATED_ENDORSEMENT_SIZE: u64 = 200;
+/// Estimated memory use of an execution event, in bytes
+const ESTIMATED_EVENT_SIZE: u64 = 1_024;
+/// Estimated memory use of the roll counts and production stats of a cycle, in bytes
+const ESTIMATED_CYCLE_INFO_SIZE: u64 = 1_000_000;
+/// Estimated memory use of the executed deferred credits of a slot, in bytes
+const ESTIMATED_SLOT_CREDITS_SIZE: u64 = 1_024;
+
+/// Limits on the number of objects kept in RAM by each module
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetentionRules {
+    /// max number of previously discarded blocks kept by consensus
+    pub max_discarded_blocks: usize,
+    /// max number of blocks in the future kept by consensus
+    pub max_future_processing_blocks: usize,
+    /// max number of blocks waiting for dependencies kept by consensus
+    pub max_dependency_blocks: usize,
+    /// number of final periods that consensus keeps at all times
+    pub force_keep_final_periods: u64,
+    /// interval at which consensus prunes its blocks
+    pub block_db_prune_interval: MassaTime,
+    /// max number of operations kept by the pool, per thread
+    pub max_operations_per_thread: usize,
+    /// max number of endorsements kept by the pool, per thread
+    pub max_endorsements_per_thread: usize,
+    /// max number of final events kept by execution
+    pub max_final_events: usize,
+    /// number of complete PoS cycles kept by the final state after they are no longer needed by consensus
+    pub pos_cycle_info_retention: usize,
+    /// number of periods during which the final state keeps the executed deferred credits
+    pub pos_deferred_credits_retention: u64,
+    /// optional memory budget of all the retained objects, in bytes
+    #[serde(default)]
+    pub memory_budget: Option<u64>,
+}
+
+impl RetentionRules {
+    /// Memory use of the objects that are always kept, whatever the budget
+    fn fixed_memory_usage(&self, thread_count: u8) -> u64 {
+        self.force_keep_final_periods
+            .saturating_mul(thread_count as u64)
+            .saturating_mul(ESTIMATED_BLOCK_SIZE)
+    }
+
+    /// Memory use of the objects whose limits can be scaled down
+    fn scalable_memory_usage(&self, thread_count: u8) -> u64 {
+        let discarded_blocks =
+            (self.max_discarded_blocks as u64).saturating_mul(ESTIMATED_DISCARDED_BLOCK_SIZE);
+        let waiting_blocks = (self.max_future_processing_blocks as u64)
+            .saturating_add(self.max_dependency_blocks as u64)
+            .saturating_mul(ESTIMATED_BLOCK_SIZE);
+        let operations = (self.max_operations_per_thread as u64)
+            .saturating_mul(ESTIMATED_OPERATION_SIZE)
+            .saturating_mul(thread_count as u64);
+        let endorsements = (self.max_endorsements_per_thread as u64)
+            .saturating_mul(ESTIMATED_ENDORSEMENT_SIZE)
+            .saturating_mul(thread_count as u64);
+        let events = (self.max_final_events as u64).saturating_mul(ESTIMATED_EVENT_SIZE);
+        let cycle_infos =
+            (self.pos_cycle_info_retention as u64).saturating_mul(ESTIMATED_CYCLE_INFO_SIZE);
+        let credits = self
+            .pos_deferred_credits_retention
+            .saturating_mul(ESTIMATED_SLOT_CREDITS_SIZE)
+            .saturating_mul(thread_count as u64);
+        discarded_blocks
+            .saturating_add(waiting_blocks)
+            .saturating_add(operations)
+            .saturating_add(endorsements)
+            .saturating_add(events)
+            .saturating_add(cycle_infos)
+            .saturating_add(credits)
+    }
+
+    /// Estimated memory use, in bytes, of the objects retained when all the limits are reached
+    pub fn estimated_memory_usage(&self, thread_count: u8) -> u64 {
+        self.fixed_memory_usage(thread_count)
+            .saturating_add(self.scalable_memory_usage(thread_count))
+    }
+}
+
+/// Owns the retention rules applied by the modules
+#[derive(Debug, Clone)]
+pub struct PruningCoordinator {
+    /// rules as configured
+    configured: RetentionRules,
+    /// rules applied by the modules, scaled down to fit in the memory budget
+    effective: RetentionRules,
+    thread_count: u8,
+}
+
+impl PruningCoordinator {
+    /// Computes the rules applied by the modules from the configured ones.
+    ///
+    /// If the estimated memory use exceeds `memory_budget`, all the limits except
+    /// `force_keep_final_periods` are scaled down by the same factor, keeping at least one object of each kind.
+    pub fn new(configured: RetentionRules, thread_count: u8) -> Self {
+        let mut effective = configured;
+        if let Some(budget) = configured.memory_budget {
+            let scalable = configured.scalable_memory_usage(thread_count);
+            let available = budget.saturating_sub(configured.fixed_memory_usage(thread_count));
+            if scalable > available {
+                let scale = |limit: usize| -> usize {
+                    if limit == 0 {
+                        return 0;
+                    }
+                    let scaled = (limit as u128 * available as u128 / scalable as u128) as usize;
+                    scaled.max(1)
+                };
+                effective.max_discarded_blocks = scale(configured.max_discarded_blocks);
+                effective.max_future_processing_blocks =
+                    scale(configured.max_future_processing_blocks);
+                effective.max_dependency_blocks = scale(configured.max_dependency_blocks);
+                effective.max_operations_per_thread = scale(configured.max_operations_per_thread);
+                effective.max_endorsements_per_thread =
+                    scale(configured.max_endorsements_per_thread);
+                effective.max_final_events = scale(configured.max_final_events);
+                effective.pos_cycle_info_retention = scale(configured.pos_cycle_info_retention);
+                effective.pos_deferred_credits_retention =
+                    scale(configured.pos_deferred_credits_retention as usize) as u64;
+            }
+        }
+        PruningCoordinator {
+            configured,
+            effective,
+            thread_count,
+        }
+    }
+
+    /// Rules to apply in the modules
+    pub fn rules(&self) -> &RetentionRules {
+        &self.effective
+    }
+
+    /// Whether the configured limits were scaled down to fit in the memory budget
+    pub fn is_constrained(&self) -> bool {
+        self.effective != self.configured
+    }
+
+    /// Estimated memory use, in bytes, of the objects retained under the applied rules
+    pub fn estimated_memory_usage(&self) -> u64 {
+        self.effective.estimated_memory_usage(self.thread_count)
+    }
+}
+
+/// Scale of the configured capacities, in thousandths
+pub const FULL_CAPACITY_PERMILLE: u32 = 1000;
+
+/// Scale applied at runtime to the capacities of the pool and protocol caches, shared between the memory monitor
+/// lowering it under memory pressure and the modules reading it when they prune
+#[derive(Debug, Clone)]
+pub struct CapacityScale(Arc<AtomicU32>);
+
+impl Default for CapacityScale {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CapacityScale {
+    /// Creates a shared scale, initially applying the configured capacities
+    pub fn new() -> Self {
+        CapacityScale(Arc::new(AtomicU32::new(FULL_CAPACITY_PERMILLE)))
+    }
+
+    /// Current scale, in thousandths of the configured capacities
+    pub fn get_permille(&self) -> u32 {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Sets the scale, in thousandths of the configured capacities, at most `FULL_CAPACITY_PERMILLE`
+    pub fn set_permille(&self, permille: u32) {
+        self.0
+            .store(permille.min(FULL_CAPACITY_PERMILLE), Ordering::Relaxed);
+    }
+
+    /// Configured `capacity` under the current scale, keeping at least one object if the capacity is not zero
+    pub fn apply(&self, capacity: usize) -> usize {
+        if capacity == 0 {
+            return 0;
+        }
+        let scaled =
+            capacity as u128 * self.get_permille() as u128 / FULL_CAPACITY_PERMILLE as u128;
+        (scaled as usize).max(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(memory_budget: Option<u64>) -> RetentionRules {
+        RetentionRules {
+            max_discarded_blocks: 100,
+            max_future_processing_blocks: 400,
+            max_dependency_blocks: 2048,
+            force_keep_final_periods: 10,
+            block_db_prune_interval: 5000.into(),
+            max_operations_per_thread: 25000,
+            max_endorsements_per_thread: 25000,
+            max_final_events: 10000,
+            pos_cycle_info_retention: 5,
+            pos_deferred_credits_retention: 1000,
+            memory_budget,
+        }
+    }
+
+    #[test]
+    fn test_pruning_coordinator_without_budget() {
+        let coordinator = PruningCoordinator::new(rules(None), 32);
+        assert!(!coordinator.is_constrained());
+        assert_eq!(coordinator.rules(), &rules(None));
+        assert_eq!(
+            coordinator.estimated_memory_usage(),
+            rules(None).estimated_memory_usage(32)
+        );
+    }
+
+    #[test]
+    fn test_pruning_coordinator_scales_to_budget() {
+        let budget = 1_000_000_000;
+        let coordinator = PruningCoordinator::new(rules(Some(budget)), 32);
+        assert!(coordinator.is_constrained());
+        assert!(coordinator.estimated_memory_usage() <= budget);
+        let effective = coordinator.rules();
+        assert_eq!(effective.force_keep_final_periods, 10);
+        assert!(effective.max_dependency_blocks < 2048);
+        assert!(effective.max_operations_per_thread < 25000);
+
+        // a budget below the fixed part keeps one object of each kind
+        let coordinator = PruningCoordinator::new(rules(Some(1)), 32);
+        let effective = coordinator.rules();
+        assert_eq!(effective.max_discarded_blocks, 1);
+        assert_eq!(effective.max_final_events, 1);
+        assert_eq!(effective.pos_cycle_info_retention, 1);
+
+        // a large enough budget leaves the rules untouched
+        let coordinator = PruningCoordinator::new(rules(Some(u64::MAX)), 32);
+        assert!(!coordinator.is_constrained());
+    }
+
+    #[test]
+    fn test_capacity_scale() {
+        let scale = CapacityScale::new();
+        let shared = scale.clone();
+        assert_eq!(scale.apply(25000), 25000);
+        shared.set_permille(250);
+        assert_eq!(scale.apply(25000), 6250);
+        assert_eq!(scale.apply(0), 0);
+        shared.set_permille(0);
+        assert_eq!(scale.apply(25000), 1);
+        shared.set_permille(2000);
+        assert_eq!(scale.get_permille(), FULL_CAPACITY_PERMILLE);
+    }
+}
@@ -1,5 +1,6 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use crate::operation::OPERATION_SEQUENCE_KEY;
 use crate::serialization::{VecU8Deserializer, VecU8Serializer};
 use massa_serialization::{
     Deserializer, SerializeError, Serializer, U64VarIntDeserializer, U64VarIntSerializer,
@@ -8,6 +9,7 @@ use nom::error::{context, ContextError, ParseError};
 use nom::multi::length_count;
 use nom::sequence::tuple;
 use nom::{IResult, Parser};
+use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::ops::Bound::Included;
 
@@ -18,6 +20,44 @@ use std::ops::Bound::Included;
 /// What is stored can be arbitrary bytes but can often be smart contract bytecode (aka WASM binary)
 pub type Datastore = BTreeMap<Vec<u8>, Vec<u8>>;
 
+/// Datastore key under which the ledger records the first cycle whose storage rent an address could not pay.
+/// It starts with a zero byte and cannot be written by smart contracts.
+pub const STORAGE_RENT_DUE_KEY: &[u8] = b"\0storage_rent_due";
+
+/// Datastore key under which the ledger records the BLS key registered by a staker with a `RegisterBlsKey` operation,
+/// see `RegisteredBlsKey`. It cannot be written by smart contracts and the key cannot be changed once registered.
+pub const BLS_PUBLIC_KEY_KEY: &[u8] = b"\0bls_public_key";
+
+/// Whether `key` is reserved to the ledger and cannot be written by smart contracts.
+/// The reserved entries are not charged storage rent.
+pub fn is_reserved_datastore_key(key: &[u8]) -> bool {
+    key == OPERATION_SEQUENCE_KEY || key == STORAGE_RENT_DUE_KEY || key == BLS_PUBLIC_KEY_KEY
+}
+
+/// Part of a datastore value, read without transferring the whole value
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DatastoreChunk {
+    /// position of the first byte of the chunk in the value
+    pub offset: u64,
+    /// bytes of the value from `offset`, empty if `offset` is past the end of the value
+    pub bytes: Vec<u8>,
+    /// length of the whole value
+    pub value_length: u64,
+}
+
+impl DatastoreChunk {
+    /// Chunk of at most `length` bytes of `value` from `offset`
+    pub fn from_value(value: &[u8], offset: u64, length: u64) -> Self {
+        let start = offset.min(value.len() as u64) as usize;
+        let end = offset.saturating_add(length).min(value.len() as u64) as usize;
+        DatastoreChunk {
+            offset,
+            bytes: value[start..end].to_vec(),
+            value_length: value.len() as u64,
+        }
+    }
+}
+
 /// Serializer for `Datastore`
 #[derive(Default)]
 pub struct DatastoreSerializer {
@@ -207,4 +247,15 @@ mod tests {
             .deserialize::<DeserializeError>(&buffer)
             .unwrap();
     }
+
+    #[test]
+    fn test_chunk_from_value() {
+        let value: Vec<u8> = (0..10).collect();
+        let chunk = DatastoreChunk::from_value(&value, 4, 3);
+        assert_eq!(chunk.bytes, vec![4, 5, 6]);
+        assert_eq!(chunk.value_length, 10);
+        assert_eq!(DatastoreChunk::from_value(&value, 8, 5).bytes, vec![8, 9]);
+        assert!(DatastoreChunk::from_value(&value, 12, 5).bytes.is_empty());
+        assert_eq!(DatastoreChunk::from_value(&value, 0, u64::MAX).bytes, value);
+    }
 }
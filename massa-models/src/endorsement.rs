@@ -166,7 +166,11 @@ impl SecureShareEndorsement {
 /// Wrapped endorsement
 pub type SecureShareEndorsement = SecureShare<Endorsement, EndorsementId>;
 
-impl SecureShareContent for Endorsement {}
+impl SecureShareContent for Endorsement {
+    fn signature_period(&self) -> Option<u64> {
+        Some(self.slot.period)
+    }
+}
 
 /// Serializer for `Endorsement`
 #[derive(Clone)]
@@ -0,0 +1,240 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! BLS signatures of the endorsers, aggregated in the block headers.
+//!
+//! From the version [`AGGREGATED_ENDORSEMENTS_VERSION`] of the consensus on, the headers no longer carry
+//! the endorsements of their parent one by one. The endorsers drawn for the slot of a block sign its parent with
+//! the BLS key they registered with a `RegisterBlsKey` operation, and the producer of the block aggregates their
+//! signatures into a single one, along with the bitmap of the indexes that signed.
+//! As the index of an endorser is given by its position in the bitmap, all the endorsers of a slot sign the same
+//! hash and the aggregate is checked with a single verification.
+//!
+//! [`AGGREGATED_ENDORSEMENTS_VERSION`]: crate::config::AGGREGATED_ENDORSEMENTS_VERSION
+
+use crate::block_id::BlockId;
+use crate::config::CHAIN_ID;
+use crate::error::ModelsError;
+use crate::slot::{Slot, SlotDeserializer, SlotSerializer};
+use massa_hash::{Hash, HashDeserializer};
+use massa_serialization::{
+    Deserializer, SerializeError, Serializer, U32VarIntDeserializer, U32VarIntSerializer,
+};
+use massa_signature::{
+    BlsKeyPair, BlsPublicKey, BlsSignature, BlsSignatureDeserializer, BLS_PUBLIC_KEY_SIZE_BYTES,
+};
+use nom::error::{context, ContextError, ParseError};
+use nom::sequence::tuple;
+use nom::{IResult, Parser};
+use serde::{Deserialize, Serialize};
+use std::convert::TryInto;
+use std::ops::Bound::{Excluded, Included};
+
+/// Number of cycles after the one of its registration from which a BLS key can sign endorsements,
+/// so that the registration is final on all the nodes checking the headers
+pub const BLS_KEY_ACTIVATION_CYCLES: u64 = 2;
+
+/// BLS signature of an endorser, gossiped to the producer of the block in which it is aggregated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EndorsementSignature {
+    /// slot of the block in which the signature can be aggregated
+    pub slot: Slot,
+    /// index of the endorser among the endorsers drawn for the slot
+    pub index: u32,
+    /// endorsed block, parent in thread `self.slot.thread` of the block in which the signature is aggregated
+    pub endorsed_block: BlockId,
+    /// signature of `compute_signed_hash(slot, endorsed_block)`
+    pub signature: BlsSignature,
+}
+
+impl EndorsementSignature {
+    /// Endorses `endorsed_block` at `slot` with the BLS `keypair` of the endorser drawn at `index`
+    pub fn new(slot: Slot, index: u32, endorsed_block: BlockId, keypair: &BlsKeyPair) -> Self {
+        EndorsementSignature {
+            slot,
+            index,
+            endorsed_block,
+            signature: keypair.sign(&Self::compute_signed_hash(&slot, &endorsed_block)),
+        }
+    }
+
+    /// Hash signed by all the endorsers of `endorsed_block` at `slot`, bound to `CHAIN_ID`
+    pub fn compute_signed_hash(slot: &Slot, endorsed_block: &BlockId) -> Hash {
+        let mut bytes = CHAIN_ID.0.to_be_bytes().to_vec();
+        bytes.extend(slot.to_bytes_key());
+        bytes.extend(endorsed_block.0.to_bytes());
+        Hash::compute_from(&bytes)
+    }
+
+    /// Checks the signature against the `public_key` of the endorser
+    ///
+    /// # Example
+    /// ```
+    /// # use massa_hash::Hash;
+    /// # use massa_models::{block_id::BlockId, endorsement_signature::EndorsementSignature, slot::Slot};
+    /// # use massa_signature::BlsKeyPair;
+    /// let keypair = BlsKeyPair::generate();
+    /// let block = BlockId(Hash::compute_from("parent".as_bytes()));
+    /// let signature = EndorsementSignature::new(Slot::new(10, 3), 5, block, &keypair);
+    /// assert!(signature.verify(&keypair.get_public_key()).is_ok());
+    /// assert!(signature.verify(&BlsKeyPair::generate().get_public_key()).is_err());
+    /// ```
+    pub fn verify(&self, public_key: &BlsPublicKey) -> Result<(), ModelsError> {
+        public_key.verify_signature(
+            &Self::compute_signed_hash(&self.slot, &self.endorsed_block),
+            &self.signature,
+        )?;
+        Ok(())
+    }
+}
+
+/// Serializer for `EndorsementSignature`
+#[derive(Default)]
+pub struct EndorsementSignatureSerializer {
+    slot_serializer: SlotSerializer,
+    u32_serializer: U32VarIntSerializer,
+}
+
+impl EndorsementSignatureSerializer {
+    /// Creates a new `EndorsementSignatureSerializer`
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Serializer<EndorsementSignature> for EndorsementSignatureSerializer {
+    fn serialize(
+        &self,
+        value: &EndorsementSignature,
+        buffer: &mut Vec<u8>,
+    ) -> Result<(), SerializeError> {
+        self.slot_serializer.serialize(&value.slot, buffer)?;
+        self.u32_serializer.serialize(&value.index, buffer)?;
+        buffer.extend(value.endorsed_block.0.to_bytes());
+        buffer.extend(value.signature.to_bytes());
+        Ok(())
+    }
+}
+
+/// Deserializer for `EndorsementSignature`
+pub struct EndorsementSignatureDeserializer {
+    slot_deserializer: SlotDeserializer,
+    index_deserializer: U32VarIntDeserializer,
+    hash_deserializer: HashDeserializer,
+    signature_deserializer: BlsSignatureDeserializer,
+}
+
+impl EndorsementSignatureDeserializer {
+    /// Creates a new `EndorsementSignatureDeserializer`
+    pub fn new(thread_count: u8, endorsement_count: u32) -> Self {
+        EndorsementSignatureDeserializer {
+            slot_deserializer: SlotDeserializer::new(
+                (Included(0), Included(u64::MAX)),
+                (Included(0), Excluded(thread_count)),
+            ),
+            index_deserializer: U32VarIntDeserializer::new(
+                Included(0),
+                Excluded(endorsement_count),
+            ),
+            hash_deserializer: HashDeserializer::new(),
+            signature_deserializer: BlsSignatureDeserializer::new(),
+        }
+    }
+}
+
+impl Deserializer<EndorsementSignature> for EndorsementSignatureDeserializer {
+    /// ## Example:
+    /// ```rust
+    /// use massa_hash::Hash;
+    /// use massa_models::{block_id::BlockId, slot::Slot};
+    /// use massa_models::endorsement_signature::{EndorsementSignature, EndorsementSignatureDeserializer, EndorsementSignatureSerializer};
+    /// use massa_serialization::{DeserializeError, Deserializer, Serializer};
+    /// use massa_signature::BlsKeyPair;
+    ///
+    /// let block = BlockId(Hash::compute_from("parent".as_bytes()));
+    /// let signature = EndorsementSignature::new(Slot::new(10, 3), 5, block, &BlsKeyPair::generate());
+    /// let mut buffer = Vec::new();
+    /// EndorsementSignatureSerializer::new().serialize(&signature, &mut buffer).unwrap();
+    /// let (rest, deserialized) = EndorsementSignatureDeserializer::new(32, 16).deserialize::<DeserializeError>(&buffer).unwrap();
+    /// assert!(rest.is_empty());
+    /// assert_eq!(deserialized, signature);
+    /// assert!(EndorsementSignatureDeserializer::new(32, 5).deserialize::<DeserializeError>(&buffer).is_err());
+    /// ```
+    fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], EndorsementSignature, E> {
+        context(
+            "Failed endorsement signature deserialization",
+            tuple((
+                context("Failed slot deserialization", |input| {
+                    self.slot_deserializer.deserialize(input)
+                }),
+                context("Failed index deserialization", |input| {
+                    self.index_deserializer.deserialize(input)
+                }),
+                context("Failed endorsed_block deserialization", |input| {
+                    self.hash_deserializer.deserialize(input)
+                }),
+                context("Failed signature deserialization", |input| {
+                    self.signature_deserializer.deserialize(input)
+                }),
+            )),
+        )
+        .map(
+            |(slot, index, endorsed_block, signature)| EndorsementSignature {
+                slot,
+                index,
+                endorsed_block: BlockId(endorsed_block),
+                signature,
+            },
+        )
+        .parse(buffer)
+    }
+}
+
+/// BLS key registered by a staker, recorded by the ledger in its datastore under
+/// [`BLS_PUBLIC_KEY_KEY`](crate::datastore::BLS_PUBLIC_KEY_KEY).
+/// Its proof of possession was checked at the registration, so its signatures can be aggregated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegisteredBlsKey {
+    /// BLS public key of the staker
+    pub public_key: BlsPublicKey,
+    /// cycle of the registration
+    pub cycle: u64,
+}
+
+impl RegisteredBlsKey {
+    /// Value of the datastore entry: the public key followed by the cycle
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.public_key.to_bytes().to_vec();
+        bytes.extend(self.cycle.to_be_bytes());
+        bytes
+    }
+
+    /// Parses the value of the datastore entry, `None` if it is invalid
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != BLS_PUBLIC_KEY_SIZE_BYTES + 8 {
+            return None;
+        }
+        let (public_key, cycle) = bytes.split_at(BLS_PUBLIC_KEY_SIZE_BYTES);
+        Some(RegisteredBlsKey {
+            public_key: BlsPublicKey::from_bytes(public_key.try_into().ok()?).ok()?,
+            cycle: u64::from_be_bytes(cycle.try_into().ok()?),
+        })
+    }
+
+    /// Whether the key can sign the endorsements of `slot`
+    ///
+    /// # Example
+    /// ```
+    /// # use massa_models::{endorsement_signature::RegisteredBlsKey, slot::Slot};
+    /// # use massa_signature::BlsKeyPair;
+    /// let key = RegisteredBlsKey { public_key: BlsKeyPair::generate().get_public_key(), cycle: 4 };
+    /// assert_eq!(RegisteredBlsKey::from_bytes(&key.to_bytes()), Some(key));
+    /// assert!(!key.is_usable_at(&Slot::new(5 * 128 + 127, 31), 128));
+    /// assert!(key.is_usable_at(&Slot::new(6 * 128, 0), 128));
+    /// ```
+    pub fn is_usable_at(&self, slot: &Slot, periods_per_cycle: u64) -> bool {
+        slot.get_cycle(periods_per_cycle) >= self.cycle.saturating_add(BLS_KEY_ACTIVATION_CYCLES)
+    }
+}
@@ -0,0 +1,256 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Reed-Solomon erasure coding over GF(256).
+//!
+//! Data is split into `data_chunks` pieces and expanded into `total_chunks` chunks.
+//! Any `data_chunks` distinct chunks are enough to rebuild the data:
+//! chunk `i` holds the evaluation at `x = i` of the polynomials whose coefficients are the pieces,
+//! and every square sub-matrix of the resulting Vandermonde matrix is invertible.
+
+use crate::error::ModelsError;
+
+/// Maximum number of chunks: the evaluation points must be distinct elements of GF(256)
+pub const MAX_ERASURE_CHUNKS: usize = 256;
+
+const fn build_gf_exp() -> [u8; 512] {
+    let mut exp = [0u8; 512];
+    let mut x: u16 = 1;
+    let mut i = 0;
+    while i < 255 {
+        exp[i] = x as u8;
+        x <<= 1;
+        if x & 0x100 != 0 {
+            x ^= 0x11d;
+        }
+        i += 1;
+    }
+    while i < 512 {
+        exp[i] = exp[i - 255];
+        i += 1;
+    }
+    exp
+}
+
+const fn build_gf_log() -> [u8; 256] {
+    let exp = build_gf_exp();
+    let mut log = [0u8; 256];
+    let mut i = 0;
+    while i < 255 {
+        log[exp[i] as usize] = i as u8;
+        i += 1;
+    }
+    log
+}
+
+const GF_EXP: [u8; 512] = build_gf_exp();
+const GF_LOG: [u8; 256] = build_gf_log();
+
+fn gf_mul(a: u8, b: u8) -> u8 {
+    if a == 0 || b == 0 {
+        return 0;
+    }
+    GF_EXP[GF_LOG[a as usize] as usize + GF_LOG[b as usize] as usize]
+}
+
+fn gf_inv(a: u8) -> u8 {
+    // a is never 0: only called on the pivots of invertible matrices
+    GF_EXP[255 - GF_LOG[a as usize] as usize]
+}
+
+/// Encoder and decoder for a given `(data_chunks, total_chunks)` pair
+#[derive(Debug, Clone, Copy)]
+pub struct ErasureCoder {
+    data_chunks: usize,
+    total_chunks: usize,
+}
+
+impl ErasureCoder {
+    /// Creates a new `ErasureCoder`.
+    ///
+    /// # Arguments
+    /// * `data_chunks`: number of chunks needed to rebuild the data
+    /// * `total_chunks`: number of chunks produced, at most `MAX_ERASURE_CHUNKS`
+    pub fn new(data_chunks: usize, total_chunks: usize) -> Result<Self, ModelsError> {
+        if data_chunks == 0 || data_chunks > total_chunks || total_chunks > MAX_ERASURE_CHUNKS {
+            return Err(ModelsError::ErasureCodingError(format!(
+                "invalid parameters: {} data chunks out of {}",
+                data_chunks, total_chunks
+            )));
+        }
+        Ok(ErasureCoder {
+            data_chunks,
+            total_chunks,
+        })
+    }
+
+    /// Number of chunks needed to rebuild the data
+    pub fn data_chunks(&self) -> usize {
+        self.data_chunks
+    }
+
+    /// Number of chunks produced by `encode`
+    pub fn total_chunks(&self) -> usize {
+        self.total_chunks
+    }
+
+    /// row of the encoding matrix for the chunk `index`: the powers of `index`
+    fn encoding_row(&self, index: usize) -> Vec<u8> {
+        let mut row = Vec::with_capacity(self.data_chunks);
+        let mut power = 1u8;
+        for _ in 0..self.data_chunks {
+            row.push(power);
+            power = gf_mul(power, index as u8);
+        }
+        row
+    }
+
+    /// Split `data` into `total_chunks` chunks of equal size.
+    ///
+    /// ```
+    /// use massa_models::erasure::ErasureCoder;
+    ///
+    /// let coder = ErasureCoder::new(3, 5).unwrap();
+    /// let data = b"erasure coded block body".to_vec();
+    /// let chunks = coder.encode(&data);
+    /// assert_eq!(chunks.len(), 5);
+    /// let received = vec![(4, chunks[4].as_slice()), (0, chunks[0].as_slice()), (2, chunks[2].as_slice())];
+    /// assert_eq!(coder.decode(&received, data.len()).unwrap(), data);
+    /// ```
+    pub fn encode(&self, data: &[u8]) -> Vec<Vec<u8>> {
+        let chunk_size = std::cmp::max(1, (data.len() + self.data_chunks - 1) / self.data_chunks);
+        let pieces: Vec<&[u8]> = (0..self.data_chunks)
+            .map(|piece| {
+                let start = std::cmp::min(piece * chunk_size, data.len());
+                let end = std::cmp::min(start + chunk_size, data.len());
+                &data[start..end]
+            })
+            .collect();
+        (0..self.total_chunks)
+            .map(|index| {
+                let row = self.encoding_row(index);
+                let mut chunk = vec![0u8; chunk_size];
+                for (coef, piece) in row.iter().zip(pieces.iter()) {
+                    for (out, byte) in chunk.iter_mut().zip(piece.iter()) {
+                        *out ^= gf_mul(*coef, *byte);
+                    }
+                }
+                chunk
+            })
+            .collect()
+    }
+
+    /// Rebuild `data_len` bytes of data from at least `data_chunks` distinct chunks given with their index
+    pub fn decode(
+        &self,
+        chunks: &[(usize, &[u8])],
+        data_len: usize,
+    ) -> Result<Vec<u8>, ModelsError> {
+        // keep the first chunk of each index
+        let mut selected: Vec<(usize, &[u8])> = Vec::with_capacity(self.data_chunks);
+        for (index, chunk) in chunks {
+            if *index >= self.total_chunks {
+                return Err(ModelsError::ErasureCodingError(format!(
+                    "chunk index {} out of range",
+                    index
+                )));
+            }
+            if selected.iter().all(|(i, _)| i != index) {
+                selected.push((*index, chunk));
+            }
+            if selected.len() == self.data_chunks {
+                break;
+            }
+        }
+        if selected.len() < self.data_chunks {
+            return Err(ModelsError::ErasureCodingError(format!(
+                "{} distinct chunks received, {} needed",
+                selected.len(),
+                self.data_chunks
+            )));
+        }
+        let chunk_size = selected[0].1.len();
+        if selected.iter().any(|(_, chunk)| chunk.len() != chunk_size)
+            || data_len > chunk_size * self.data_chunks
+        {
+            return Err(ModelsError::ErasureCodingError(
+                "inconsistent chunk sizes".to_string(),
+            ));
+        }
+
+        // invert the encoding sub-matrix of the selected chunks with a Gauss-Jordan elimination
+        let size = self.data_chunks;
+        let mut matrix: Vec<Vec<u8>> = selected
+            .iter()
+            .map(|(index, _)| self.encoding_row(*index))
+            .collect();
+        let mut inverse: Vec<Vec<u8>> = (0..size)
+            .map(|row| (0..size).map(|col| u8::from(row == col)).collect())
+            .collect();
+        for col in 0..size {
+            let pivot = (col..size)
+                .find(|row| matrix[*row][col] != 0)
+                .ok_or_else(|| ModelsError::ErasureCodingError("singular matrix".to_string()))?;
+            matrix.swap(col, pivot);
+            inverse.swap(col, pivot);
+            let factor = gf_inv(matrix[col][col]);
+            for value in matrix[col].iter_mut().chain(inverse[col].iter_mut()) {
+                *value = gf_mul(*value, factor);
+            }
+            for row in 0..size {
+                let coef = matrix[row][col];
+                if row == col || coef == 0 {
+                    continue;
+                }
+                for k in 0..size {
+                    matrix[row][k] ^= gf_mul(coef, matrix[col][k]);
+                    inverse[row][k] ^= gf_mul(coef, inverse[col][k]);
+                }
+            }
+        }
+
+        // each piece is a combination of the selected chunks
+        let mut data = Vec::with_capacity(chunk_size * size);
+        for coefs in inverse.iter() {
+            let mut piece = vec![0u8; chunk_size];
+            for (coef, (_, chunk)) in coefs.iter().zip(selected.iter()) {
+                for (out, byte) in piece.iter_mut().zip(chunk.iter()) {
+                    *out ^= gf_mul(*coef, *byte);
+                }
+            }
+            data.extend(piece);
+        }
+        data.truncate(data_len);
+        Ok(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_erasure_decode_from_any_subset() {
+        let coder = ErasureCoder::new(4, 7).unwrap();
+        let data: Vec<u8> = (0..1001u32).map(|i| (i * 7 % 251) as u8).collect();
+        let chunks = coder.encode(&data);
+        for skipped in [[0, 1, 2], [4, 5, 6], [1, 3, 5]] {
+            let received: Vec<(usize, &[u8])> = chunks
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| !skipped.contains(index))
+                .map(|(index, chunk)| (index, chunk.as_slice()))
+                .collect();
+            assert_eq!(coder.decode(&received, data.len()).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn test_erasure_not_enough_chunks() {
+        let coder = ErasureCoder::new(3, 5).unwrap();
+        let data = vec![42u8; 100];
+        let chunks = coder.encode(&data);
+        let received = vec![(1, chunks[1].as_slice()), (1, chunks[1].as_slice())];
+        assert!(coder.decode(&received, data.len()).is_err());
+        assert!(ErasureCoder::new(6, 5).is_err());
+    }
+}
@@ -62,6 +62,12 @@ pub enum ModelsError {
     OutdatedBootstrapCursor,
     /// Error raised {0}
     ErrorRaised(String),
+    /// invalid wire-format version schedule: {0}
+    InvalidFormatSchedule(String),
+    /// erasure coding error: {0}
+    ErasureCodingError(String),
+    /// invalid aggregate signature: {0}
+    InvalidAggregateSignature(String),
 }
 
 impl From<nom::Err<nom::error::Error<&[u8]>>> for ModelsError {
@@ -0,0 +1,317 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Wire-format versions of blocks and operations.
+//!
+//! A [`FormatVersionSchedule`] tells which version of the wire format is active at each slot.
+//!
+//! Block headers: version 0 is the original format, which carries no version. The later versions are written
+//! explicitly in the header, behind a marker that the original format cannot contain, so that a new
+//! format can be activated at a configured slot while the headers produced before (for example in
+//! archives or in the bootstrap history) remain parseable with the same ids.
+//! See `BlockHeaderSerializer` for the fields of each version.
+//!
+//! Operations: serialized data is prefixed by its version by a [`ScheduledSerializer`], the version being
+//! the one active at the first slot of the expire period of the operation, and the deserializers keep every
+//! registered version, so the operations produced before an activation remain parseable.
+
+use crate::config::{
+    BLOCK_HEADER_FORMAT_ACTIVATIONS, BLOCK_HEADER_FORMAT_VERSION, OPERATION_FORMAT_ACTIVATIONS,
+    OPERATION_FORMAT_VERSION,
+};
+use crate::error::ModelsError;
+use crate::operation::{Operation, OperationDeserializer, OperationSerializer};
+use crate::slot::Slot;
+use massa_serialization::{
+    Deserializer, SerializeError, Serializer, VersionedDeserializer, VersionedSerializer,
+};
+use nom::error::{context, ContextError, ParseError};
+use nom::IResult;
+use std::collections::BTreeMap;
+
+/// Wire-format versions and their activation slots
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatVersionSchedule {
+    /// version active from each slot on, always contains version 0 at the minimal slot
+    activations: BTreeMap<Slot, u32>,
+}
+
+impl Default for FormatVersionSchedule {
+    fn default() -> Self {
+        FormatVersionSchedule {
+            activations: BTreeMap::from([(Slot::min(), 0)]),
+        }
+    }
+}
+
+impl FormatVersionSchedule {
+    /// Creates a schedule from `(activation slot, version)` pairs.
+    /// Version 0 is active from genesis, versions must increase with their activation slot
+    /// and be at most `latest_version`, the latest version known by the node.
+    pub fn new(activations: &[(Slot, u32)], latest_version: u32) -> Result<Self, ModelsError> {
+        let mut schedule = FormatVersionSchedule::default();
+        for (slot, version) in activations {
+            let (last_slot, last_version) = schedule
+                .activations
+                .iter()
+                .next_back()
+                .map(|(slot, version)| (*slot, *version))
+                .unwrap_or((Slot::min(), 0));
+            if *version > latest_version {
+                return Err(ModelsError::InvalidFormatSchedule(format!(
+                    "version {} activated at slot {} is unknown, the latest one being {}",
+                    version, slot, latest_version
+                )));
+            }
+            if *slot <= last_slot || *version <= last_version {
+                return Err(ModelsError::InvalidFormatSchedule(format!(
+                    "version {} activated at slot {} must come after version {} activated at slot {}",
+                    version, slot, last_version, last_slot
+                )));
+            }
+            schedule.activations.insert(*slot, *version);
+        }
+        Ok(schedule)
+    }
+
+    /// Schedule of the block header formats of the network, from `BLOCK_HEADER_FORMAT_ACTIVATIONS`
+    pub fn block_header() -> Self {
+        FormatVersionSchedule::new(BLOCK_HEADER_FORMAT_ACTIVATIONS, BLOCK_HEADER_FORMAT_VERSION)
+            .expect("invalid BLOCK_HEADER_FORMAT_ACTIVATIONS")
+    }
+
+    /// Schedule of the operation formats of the network, from `OPERATION_FORMAT_ACTIVATIONS`
+    pub fn operation() -> Self {
+        FormatVersionSchedule::new(OPERATION_FORMAT_ACTIVATIONS, OPERATION_FORMAT_VERSION)
+            .expect("invalid OPERATION_FORMAT_ACTIVATIONS")
+    }
+
+    /// Version active at a given slot
+    pub fn version_at(&self, slot: &Slot) -> u32 {
+        self.activations
+            .range(..=slot)
+            .next_back()
+            .map(|(_, version)| *version)
+            .unwrap_or(0)
+    }
+
+    /// All the versions of the schedule, in increasing order
+    pub fn versions(&self) -> impl Iterator<Item = u32> + '_ {
+        self.activations.values().copied()
+    }
+}
+
+/// Serializer writing data with the wire-format version active at the slot of the data
+pub struct ScheduledSerializer<T, ST>
+where
+    ST: Serializer<T>,
+{
+    schedule: FormatVersionSchedule,
+    versions: VersionedSerializer<T, ST>,
+    slot_of: fn(&T) -> Slot,
+}
+
+impl<T, ST> ScheduledSerializer<T, ST>
+where
+    ST: Serializer<T>,
+{
+    /// Creates a serializer using the format of `schedule` active at `slot_of(data)`.
+    /// `versions` must contain the serializer of every version of the schedule.
+    pub fn new(
+        schedule: FormatVersionSchedule,
+        versions: VersionedSerializer<T, ST>,
+        slot_of: fn(&T) -> Slot,
+    ) -> Self {
+        ScheduledSerializer {
+            schedule,
+            versions,
+            slot_of,
+        }
+    }
+}
+
+impl ScheduledSerializer<Operation, OperationSerializer> {
+    /// Creates an operation serializer, the version being chosen from the first slot
+    /// of the expire period of the operation
+    pub fn operation(schedule: FormatVersionSchedule) -> Self {
+        let mut versions = VersionedSerializer::new();
+        for version in schedule.versions() {
+            versions.register(version, OperationSerializer::new());
+        }
+        ScheduledSerializer::new(schedule, versions, |op| Slot::new(op.expire_period, 0))
+    }
+}
+
+impl<T, ST> Serializer<T> for ScheduledSerializer<T, ST>
+where
+    ST: Serializer<T>,
+{
+    fn serialize(&self, value: &T, buffer: &mut Vec<u8>) -> Result<(), SerializeError> {
+        let version = self.schedule.version_at(&(self.slot_of)(value));
+        self.versions.serialize_with_version(version, value, buffer)
+    }
+}
+
+/// Deserializer accepting data only in the wire-format version active at the slot of the data
+pub struct ScheduledDeserializer<T, DT>
+where
+    DT: Deserializer<T>,
+{
+    schedule: FormatVersionSchedule,
+    versions: VersionedDeserializer<T, DT>,
+    slot_of: fn(&T) -> Slot,
+}
+
+impl<T, DT> ScheduledDeserializer<T, DT>
+where
+    DT: Deserializer<T>,
+{
+    /// Creates a deserializer checking the version against the one of `schedule` active at `slot_of(data)`.
+    /// `versions` must contain the deserializer of every version of the schedule.
+    pub fn new(
+        schedule: FormatVersionSchedule,
+        versions: VersionedDeserializer<T, DT>,
+        slot_of: fn(&T) -> Slot,
+    ) -> Self {
+        ScheduledDeserializer {
+            schedule,
+            versions,
+            slot_of,
+        }
+    }
+}
+
+impl ScheduledDeserializer<Operation, OperationDeserializer> {
+    /// Creates an operation deserializer
+    #[allow(clippy::too_many_arguments)]
+    pub fn operation(
+        schedule: FormatVersionSchedule,
+        max_datastore_value_length: u64,
+        max_function_name_length: u16,
+        max_parameters_size: u32,
+        max_op_datastore_entry_count: u64,
+        max_op_datastore_key_length: u8,
+        max_op_datastore_value_length: u64,
+    ) -> Self {
+        let mut versions = VersionedDeserializer::new();
+        for version in schedule.versions() {
+            versions.register(
+                version,
+                OperationDeserializer::new(
+                    max_datastore_value_length,
+                    max_function_name_length,
+                    max_parameters_size,
+                    max_op_datastore_entry_count,
+                    max_op_datastore_key_length,
+                    max_op_datastore_value_length,
+                ),
+            );
+        }
+        ScheduledDeserializer::new(schedule, versions, |op| Slot::new(op.expire_period, 0))
+    }
+}
+
+impl<T, DT> Deserializer<T> for ScheduledDeserializer<T, DT>
+where
+    DT: Deserializer<T>,
+{
+    fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], T, E> {
+        context("Failed scheduled deserialization", |input: &'a [u8]| {
+            let (rest, (version, data)) = self.versions.deserialize(input)?;
+            if version != self.schedule.version_at(&(self.slot_of)(&data)) {
+                return Err(nom::Err::Error(ParseError::from_error_kind(
+                    input,
+                    nom::error::ErrorKind::Verify,
+                )));
+            }
+            Ok((rest, data))
+        })(buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::Address;
+    use crate::amount::Amount;
+    use crate::operation::OperationType;
+    use massa_serialization::{DeserializeError, U32VarIntSerializer};
+    use massa_signature::KeyPair;
+    use std::str::FromStr;
+
+    fn create_operation(expire_period: u64) -> Operation {
+        Operation {
+            fee: Amount::from_str("1").unwrap(),
+            expire_period,
+            sequence: None,
+            sponsor: None,
+            op: OperationType::Transaction {
+                recipient_address: Address::from_public_key(&KeyPair::generate().get_public_key()),
+                amount: Amount::from_str("10").unwrap(),
+                memo: None,
+            },
+        }
+    }
+
+    #[test]
+    fn test_format_version_schedule() {
+        let schedule =
+            FormatVersionSchedule::new(&[(Slot::new(10, 2), 1), (Slot::new(20, 0), 3)], 3).unwrap();
+        assert_eq!(schedule.version_at(&Slot::new(0, 0)), 0);
+        assert_eq!(schedule.version_at(&Slot::new(10, 1)), 0);
+        assert_eq!(schedule.version_at(&Slot::new(10, 2)), 1);
+        assert_eq!(schedule.version_at(&Slot::new(19, 31)), 1);
+        assert_eq!(schedule.version_at(&Slot::new(100, 0)), 3);
+        assert_eq!(schedule.versions().collect::<Vec<_>>(), vec![0, 1, 3]);
+
+        assert!(FormatVersionSchedule::new(&[(Slot::new(10, 0), 4)], 3).is_err());
+        assert!(
+            FormatVersionSchedule::new(&[(Slot::new(10, 0), 2), (Slot::new(5, 0), 3)], 3).is_err()
+        );
+        assert!(
+            FormatVersionSchedule::new(&[(Slot::new(10, 0), 2), (Slot::new(15, 0), 1)], 3).is_err()
+        );
+        assert!(FormatVersionSchedule::new(&[(Slot::new(10, 0), 0)], 3).is_err());
+    }
+
+    #[test]
+    fn test_network_activations() {
+        // the schedules of the network must be valid, as the (de)serializers build them
+        FormatVersionSchedule::block_header();
+        FormatVersionSchedule::operation();
+    }
+
+    #[test]
+    fn test_scheduled_operation_serialization() {
+        let schedule = FormatVersionSchedule::new(&[(Slot::new(10, 0), 1)], 1).unwrap();
+        let serializer = ScheduledSerializer::operation(schedule.clone());
+        let deserializer =
+            ScheduledDeserializer::operation(schedule, 10_000, 10_000, 10_000, 100, 255, 10_000);
+
+        // operations expiring before and after the activation are both parseable
+        for expire_period in [5, 15] {
+            let operation = create_operation(expire_period);
+            let mut buffer = Vec::new();
+            serializer.serialize(&operation, &mut buffer).unwrap();
+            let (rest, deserialized) = deserializer
+                .deserialize::<DeserializeError>(&buffer)
+                .unwrap();
+            assert!(rest.is_empty());
+            assert_eq!(deserialized.expire_period, expire_period);
+        }
+
+        // an operation written with the old format after the activation is rejected
+        let mut buffer = Vec::new();
+        U32VarIntSerializer::new()
+            .serialize(&0, &mut buffer)
+            .unwrap();
+        OperationSerializer::new()
+            .serialize(&create_operation(15), &mut buffer)
+            .unwrap();
+        assert!(deserializer
+            .deserialize::<DeserializeError>(&buffer)
+            .is_err());
+    }
+}
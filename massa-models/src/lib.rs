@@ -13,8 +13,12 @@ extern crate lazy_static;
 pub mod active_block;
 /// address related structures
 pub mod address;
+/// BLS signatures aggregated over a committee
+pub mod aggregate_signature;
 /// amount related structures
 pub mod amount;
+/// smart contract call arguments encoding
+pub mod args;
 /// block structure
 pub mod block;
 /// block-related structure: block_header
@@ -23,6 +27,8 @@ pub mod block_header;
 pub mod block_id;
 /// bytecode structures
 pub mod bytecode;
+/// trusted checkpoint of the final history
+pub mod checkpoint;
 /// clique
 pub mod clique;
 /// various structures
@@ -33,10 +39,16 @@ pub mod config;
 pub mod datastore;
 /// endorsements
 pub mod endorsement;
+/// BLS signatures of the endorsers, aggregated in the block headers
+pub mod endorsement_signature;
+/// erasure coding of block bodies
+pub mod erasure;
 /// models error
 pub mod error;
 /// execution related structures
 pub mod execution;
+/// wire-format versions of serialized structures
+pub mod format_version;
 /// ledger related structures
 pub mod ledger;
 /// node related structure
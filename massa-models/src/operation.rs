@@ -1,16 +1,22 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 use crate::address::AddressSerializer;
+use crate::config::{
+    MAX_MULTI_TRANSACTION_OUTPUTS, MAX_TRANSACTION_MEMO_LENGTH, MULTI_TRANSACTION_FEE_PER_OUTPUT,
+    THREAD_COUNT,
+};
 use crate::datastore::{Datastore, DatastoreDeserializer, DatastoreSerializer};
 use crate::prehash::{PreHashSet, PreHashed};
 use crate::secure_share::{
-    Id, SecureShare, SecureShareContent, SecureShareDeserializer, SecureShareSerializer,
+    compute_signed_hash, Id, SecureShare, SecureShareContent, SecureShareDeserializer,
+    SecureShareSerializer,
 };
 use crate::{
     address::{Address, AddressDeserializer},
     amount::{Amount, AmountDeserializer, AmountSerializer},
     error::ModelsError,
     serialization::{StringDeserializer, StringSerializer, VecU8Deserializer, VecU8Serializer},
+    slot::{Slot, SlotDeserializer, SlotSerializer},
 };
 use massa_hash::{Hash, HashDeserializer};
 use massa_serialization::{
@@ -18,6 +24,10 @@ use massa_serialization::{
     U16VarIntSerializer, U32VarIntDeserializer, U32VarIntSerializer, U64VarIntDeserializer,
     U64VarIntSerializer,
 };
+use massa_signature::{
+    BlsPublicKey, BlsPublicKeyDeserializer, BlsSignature, BlsSignatureDeserializer, KeyPair,
+    PublicKey, PublicKeyDeserializer, Signature, SignatureDeserializer,
+};
 use nom::error::context;
 use nom::multi::length_count;
 use nom::sequence::tuple;
@@ -32,7 +42,7 @@ use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, DeserializeFromStr, SerializeDisplay};
 use std::convert::TryInto;
 use std::fmt::Formatter;
-use std::{ops::Bound::Included, ops::RangeInclusive, str::FromStr};
+use std::{ops::Bound::Excluded, ops::Bound::Included, ops::RangeInclusive, str::FromStr};
 
 /// Size in bytes of the serialized operation ID
 pub const OPERATION_ID_SIZE_BYTES: usize = massa_hash::HASH_SIZE_BYTES;
@@ -232,6 +242,27 @@ impl Deserializer<OperationId> for OperationIdDeserializer {
     }
 }
 
+/// Datastore key under which the ledger records the next sequence expected from the operations of an address.
+/// It starts with a zero byte and cannot be written by smart contracts.
+pub const OPERATION_SEQUENCE_KEY: &[u8] = b"\0operation_sequence";
+
+/// Next sequence expected from the operations of an address, given the value recorded under
+/// [`OPERATION_SEQUENCE_KEY`] in its datastore. Without any record, the first expected sequence is 0.
+pub fn next_operation_sequence(recorded: Option<&[u8]>) -> u64 {
+    recorded
+        .and_then(|value| value.try_into().ok())
+        .map(u64::from_be_bytes)
+        .unwrap_or(0)
+}
+
+/// Tag written in place of the operation type to announce a sequence, followed by the sequence and the operation type.
+/// It is not a valid operation type, so that operations without sequence keep their serialization.
+const OPERATION_SEQUENCE_TAG: u32 = 255;
+
+/// Tag written in place of the operation type to announce a sponsor, followed by its public key, its signature
+/// and the operation type. It comes after the sequence, if any.
+const OPERATION_SPONSOR_TAG: u32 = 254;
+
 #[derive(IntoPrimitive, Debug, Eq, PartialEq, TryFromPrimitive)]
 #[repr(u32)]
 enum OperationTypeId {
@@ -240,6 +271,12 @@ enum OperationTypeId {
     RollSell = 2,
     ExecuteSC = 3,
     CallSC = 4,
+    /// transaction with a memo, kept apart so that the transactions without memo keep their serialization
+    TransactionWithMemo = 5,
+    MultiTransaction = 6,
+    ScheduledTransaction = 7,
+    CancelScheduledTransaction = 8,
+    RegisterBlsKey = 9,
 }
 
 /// the operation as sent in the network
@@ -251,14 +288,82 @@ pub struct Operation {
     pub fee: Amount,
     /// after `expire_period` slot the operation won't be included in a block
     pub expire_period: u64,
+    /// optional sequence of the operation among those of its sender.
+    /// If set, the operation is only executed if it is the next sequence recorded in the ledger for the sender,
+    /// which is then incremented: the operation can never be executed twice, even after its expiration.
+    #[serde(default)]
+    pub sequence: Option<u64>,
+    /// optional sponsor paying the fee of the operation in place of its creator, who still authorizes the operation.
+    /// See [`Operation::with_sponsor`].
+    #[serde(default)]
+    pub sponsor: Option<OperationSponsor>,
     /// the type specific operation part
     pub op: OperationType,
 }
 
+/// Sponsor of an operation: the address of `public_key` pays the fee of the operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OperationSponsor {
+    /// public key of the sponsor
+    pub public_key: PublicKey,
+    /// signature by the sponsor of [`Operation::sponsored_hash`]
+    pub signature: Signature,
+}
+
+impl Operation {
+    /// Hash signed by the sponsor of the operation. It covers the operation without its sponsor and both public keys,
+    /// so that a sponsorship cannot be reused for another operation or another creator.
+    pub fn sponsored_hash(
+        &self,
+        creator_public_key: &PublicKey,
+        sponsor_public_key: &PublicKey,
+    ) -> Result<Hash, ModelsError> {
+        let mut hash_data = Vec::new();
+        hash_data.extend(creator_public_key.to_bytes());
+        hash_data.extend(sponsor_public_key.to_bytes());
+        let unsponsored = Operation {
+            sponsor: None,
+            ..self.clone()
+        };
+        OperationSerializer::new().serialize(&unsponsored, &mut hash_data)?;
+        Ok(compute_signed_hash(
+            &Hash::compute_from(&hash_data),
+            self.signature_period(),
+        ))
+    }
+
+    /// Sponsors the operation of `creator_public_key` with `sponsor_keypair`, which will pay its fee.
+    /// The creator signs the operation once sponsored.
+    pub fn with_sponsor(
+        mut self,
+        creator_public_key: &PublicKey,
+        sponsor_keypair: &KeyPair,
+    ) -> Result<Self, ModelsError> {
+        let public_key = sponsor_keypair.get_public_key();
+        let signature =
+            sponsor_keypair.sign(&self.sponsored_hash(creator_public_key, &public_key)?)?;
+        self.sponsor = Some(OperationSponsor {
+            public_key,
+            signature,
+        });
+        Ok(self)
+    }
+}
+
 impl std::fmt::Display for Operation {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "Fee: {}", self.fee)?;
         writeln!(f, "Expire period: {}", self.expire_period)?;
+        if let Some(sequence) = self.sequence {
+            writeln!(f, "Sequence: {}", sequence)?;
+        }
+        if let Some(sponsor) = &self.sponsor {
+            writeln!(
+                f,
+                "Sponsor: {}",
+                Address::from_public_key(&sponsor.public_key)
+            )?;
+        }
         writeln!(f, "Operation type: {}", self.op)?;
         Ok(())
     }
@@ -267,10 +372,15 @@ impl std::fmt::Display for Operation {
 /// signed operation
 pub type SecureShareOperation = SecureShare<Operation, OperationId>;
 
-impl SecureShareContent for Operation {}
+impl SecureShareContent for Operation {
+    fn signature_period(&self) -> Option<u64> {
+        Some(self.expire_period)
+    }
+}
 
 /// Serializer for `Operation`
 pub struct OperationSerializer {
+    u32_serializer: U32VarIntSerializer,
     u64_serializer: U64VarIntSerializer,
     amount_serializer: AmountSerializer,
     op_type_serializer: OperationTypeSerializer,
@@ -280,6 +390,7 @@ impl OperationSerializer {
     /// Creates a new `OperationSerializer`
     pub fn new() -> Self {
         Self {
+            u32_serializer: U32VarIntSerializer::new(),
             u64_serializer: U64VarIntSerializer::new(),
             amount_serializer: AmountSerializer::new(),
             op_type_serializer: OperationTypeSerializer::new(),
@@ -305,11 +416,14 @@ impl Serializer<Operation> for OperationSerializer {
     /// let op = OperationType::Transaction {
     ///    recipient_address: Address::from_public_key(&keypair.get_public_key()),
     ///    amount: Amount::from_str("300").unwrap(),
+    ///    memo: None,
     /// };
     /// let operation = Operation {
     ///   fee: Amount::from_str("20").unwrap(),
     ///   op,
     ///   expire_period: 50,
+    ///   sequence: None,
+    ///   sponsor: None,
     /// };
     /// let mut buffer = Vec::new();
     /// OperationSerializer::new().serialize(&operation, &mut buffer).unwrap();
@@ -318,6 +432,17 @@ impl Serializer<Operation> for OperationSerializer {
         self.amount_serializer.serialize(&value.fee, buffer)?;
         self.u64_serializer
             .serialize(&value.expire_period, buffer)?;
+        if let Some(sequence) = value.sequence {
+            self.u32_serializer
+                .serialize(&OPERATION_SEQUENCE_TAG, buffer)?;
+            self.u64_serializer.serialize(&sequence, buffer)?;
+        }
+        if let Some(sponsor) = &value.sponsor {
+            self.u32_serializer
+                .serialize(&OPERATION_SPONSOR_TAG, buffer)?;
+            buffer.extend(sponsor.public_key.to_bytes());
+            buffer.extend(sponsor.signature.to_bytes());
+        }
         self.op_type_serializer.serialize(&value.op, buffer)?;
         Ok(())
     }
@@ -326,6 +451,10 @@ impl Serializer<Operation> for OperationSerializer {
 /// Serializer for `Operation`
 pub struct OperationDeserializer {
     expire_period_deserializer: U64VarIntDeserializer,
+    sequence_tag_deserializer: U32VarIntDeserializer,
+    sequence_deserializer: U64VarIntDeserializer,
+    public_key_deserializer: PublicKeyDeserializer,
+    signature_deserializer: SignatureDeserializer,
     amount_deserializer: AmountDeserializer,
     op_type_deserializer: OperationTypeDeserializer,
 }
@@ -342,6 +471,10 @@ impl OperationDeserializer {
     ) -> Self {
         Self {
             expire_period_deserializer: U64VarIntDeserializer::new(Included(0), Included(u64::MAX)),
+            sequence_tag_deserializer: U32VarIntDeserializer::new(Included(0), Included(u32::MAX)),
+            sequence_deserializer: U64VarIntDeserializer::new(Included(0), Included(u64::MAX)),
+            public_key_deserializer: PublicKeyDeserializer::new(),
+            signature_deserializer: SignatureDeserializer::new(),
             amount_deserializer: AmountDeserializer::new(
                 Included(Amount::MIN),
                 Included(Amount::MAX),
@@ -370,11 +503,14 @@ impl Deserializer<Operation> for OperationDeserializer {
     /// let op = OperationType::Transaction {
     ///    recipient_address: Address::from_public_key(&keypair.get_public_key()),
     ///    amount: Amount::from_str("300").unwrap(),
+    ///    memo: None,
     /// };
     /// let operation = Operation {
     ///   fee: Amount::from_str("20").unwrap(),
     ///   op,
     ///   expire_period: 50,
+    ///   sequence: None,
+    ///   sponsor: None,
     /// };
     /// let mut buffer = Vec::new();
     /// OperationSerializer::new().serialize(&operation, &mut buffer).unwrap();
@@ -386,6 +522,7 @@ impl Deserializer<Operation> for OperationDeserializer {
     ///   OperationType::Transaction {
     ///     recipient_address,
     ///     amount,
+    ///     ..
     ///   } => {
     ///     assert_eq!(recipient_address, Address::from_public_key(&keypair.get_public_key()));
     ///     assert_eq!(amount, Amount::from_str("300").unwrap());
@@ -406,15 +543,42 @@ impl Deserializer<Operation> for OperationDeserializer {
                 context("Failed expire_period deserialization", |input| {
                     self.expire_period_deserializer.deserialize(input)
                 }),
+                context("Failed sequence deserialization", |input| {
+                    match self.sequence_tag_deserializer.deserialize::<E>(input) {
+                        Ok((rest, OPERATION_SEQUENCE_TAG)) => self
+                            .sequence_deserializer
+                            .deserialize(rest)
+                            .map(|(rest, sequence)| (rest, Some(sequence))),
+                        _ => Ok((input, None)),
+                    }
+                }),
+                context("Failed sponsor deserialization", |input| {
+                    match self.sequence_tag_deserializer.deserialize::<E>(input) {
+                        Ok((rest, OPERATION_SPONSOR_TAG)) => tuple((
+                            |input| self.public_key_deserializer.deserialize(input),
+                            |input| self.signature_deserializer.deserialize(input),
+                        ))
+                        .map(|(public_key, signature)| {
+                            Some(OperationSponsor {
+                                public_key,
+                                signature,
+                            })
+                        })
+                        .parse(rest),
+                        _ => Ok((input, None)),
+                    }
+                }),
                 context("Failed op deserialization", |input| {
                     let (rest, op) = self.op_type_deserializer.deserialize(input)?;
                     Ok((rest, op))
                 }),
             )),
         )
-        .map(|(fee, expire_period, op)| Operation {
+        .map(|(fee, expire_period, sequence, sponsor, op)| Operation {
             fee,
             expire_period,
+            sequence,
+            sponsor,
             op,
         })
         .parse(buffer)
@@ -431,6 +595,10 @@ pub enum OperationType {
         recipient_address: Address,
         /// amount
         amount: Amount,
+        /// optional memo of at most `MAX_TRANSACTION_MEMO_LENGTH` bytes, ex: to tag a deposit.
+        /// It is signed with the operation and charged by size at execution.
+        #[serde(default)]
+        memo: Option<Vec<u8>>,
     },
     /// the sender buys `roll_count` rolls. Roll price is defined in configuration
     RollBuy {
@@ -465,6 +633,45 @@ pub enum OperationType {
         /// Extra coins that are spent from the caller's balance and transferred to the target
         coins: Amount,
     },
+    /// transfer coins from sender to several recipients, at most `MAX_MULTI_TRANSACTION_OUTPUTS`.
+    /// The fee must be at least `MULTI_TRANSACTION_FEE_PER_OUTPUT` per recipient.
+    MultiTransaction {
+        /// recipients and their amounts, all paid or none
+        outputs: Vec<TransactionOutput>,
+    },
+    /// transfer coins from sender to recipient at `activation_slot`.
+    /// The amount is spent at execution and held by the asynchronous pool until the activation slot,
+    /// the transfer can be cancelled until then with `CancelScheduledTransaction`.
+    ScheduledTransaction {
+        /// recipient address
+        recipient_address: Address,
+        /// amount
+        amount: Amount,
+        /// slot at which the amount is credited to the recipient, after the execution of the operation
+        activation_slot: Slot,
+    },
+    /// cancel a pending scheduled transfer of the sender, reimbursing its amount
+    CancelScheduledTransaction {
+        /// id of the `ScheduledTransaction` operation
+        operation_id: OperationId,
+    },
+    /// register the BLS key with which the sender signs its endorsements, aggregated in the block headers.
+    /// The key cannot be changed once registered, see `massa_models::endorsement_signature`.
+    RegisterBlsKey {
+        /// BLS public key
+        public_key: BlsPublicKey,
+        /// proof of possession of the secret key, preventing rogue key attacks on the aggregated signatures
+        proof_of_possession: BlsSignature,
+    },
+}
+
+/// Recipient of a multi-recipient transaction
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TransactionOutput {
+    /// recipient address
+    pub recipient_address: Address,
+    /// amount
+    pub amount: Amount,
 }
 
 impl std::fmt::Display for OperationType {
@@ -473,10 +680,14 @@ impl std::fmt::Display for OperationType {
             OperationType::Transaction {
                 recipient_address,
                 amount,
+                memo,
             } => {
                 writeln!(f, "Transaction:")?;
                 writeln!(f, "\t- Recipient:{}", recipient_address)?;
                 writeln!(f, "\t  Amount:{}", amount)?;
+                if let Some(memo) = memo {
+                    writeln!(f, "\t  Memo:{}", String::from_utf8_lossy(memo))?;
+                }
             }
             OperationType::RollBuy { roll_count } => {
                 writeln!(f, "Buy rolls:")?;
@@ -508,6 +719,31 @@ impl std::fmt::Display for OperationType {
                 writeln!(f, "\t- max_gas:{}", max_gas)?;
                 writeln!(f, "\t- coins:{}", coins)?;
             }
+            OperationType::MultiTransaction { outputs } => {
+                writeln!(f, "Multi-recipient transaction:")?;
+                for output in outputs {
+                    writeln!(f, "\t- Recipient:{}", output.recipient_address)?;
+                    writeln!(f, "\t  Amount:{}", output.amount)?;
+                }
+            }
+            OperationType::ScheduledTransaction {
+                recipient_address,
+                amount,
+                activation_slot,
+            } => {
+                writeln!(f, "Scheduled transaction:")?;
+                writeln!(f, "\t- Recipient:{}", recipient_address)?;
+                writeln!(f, "\t  Amount:{}", amount)?;
+                writeln!(f, "\t  Activation slot:{}", activation_slot)?;
+            }
+            OperationType::CancelScheduledTransaction { operation_id } => {
+                writeln!(f, "Scheduled transaction cancellation:")?;
+                writeln!(f, "\t- Operation:{}", operation_id)?;
+            }
+            OperationType::RegisterBlsKey { public_key, .. } => {
+                writeln!(f, "BLS key registration:")?;
+                writeln!(f, "\t- Public key:{}", public_key)?;
+            }
         }
         Ok(())
     }
@@ -522,6 +758,8 @@ pub struct OperationTypeSerializer {
     address_serializer: AddressSerializer,
     function_name_serializer: StringSerializer<U16VarIntSerializer, u16>,
     datastore_serializer: DatastoreSerializer,
+    slot_serializer: SlotSerializer,
+    operation_id_serializer: OperationIdSerializer,
 }
 
 impl OperationTypeSerializer {
@@ -535,6 +773,8 @@ impl OperationTypeSerializer {
             address_serializer: AddressSerializer::new(),
             function_name_serializer: StringSerializer::new(U16VarIntSerializer::new()),
             datastore_serializer: DatastoreSerializer::new(),
+            slot_serializer: SlotSerializer::new(),
+            operation_id_serializer: OperationIdSerializer::new(),
         }
     }
 }
@@ -568,12 +808,26 @@ impl Serializer<OperationType> for OperationTypeSerializer {
             OperationType::Transaction {
                 recipient_address,
                 amount,
+                memo,
             } => {
-                self.u32_serializer
-                    .serialize(&u32::from(OperationTypeId::Transaction), buffer)?;
+                let id = match memo {
+                    Some(_) => OperationTypeId::TransactionWithMemo,
+                    None => OperationTypeId::Transaction,
+                };
+                self.u32_serializer.serialize(&u32::from(id), buffer)?;
                 self.address_serializer
                     .serialize(recipient_address, buffer)?;
                 self.amount_serializer.serialize(amount, buffer)?;
+                if let Some(memo) = memo {
+                    if memo.len() as u64 > MAX_TRANSACTION_MEMO_LENGTH {
+                        return Err(SerializeError::GeneralError(format!(
+                            "transaction memo of {} bytes exceeds the maximum of {} bytes",
+                            memo.len(),
+                            MAX_TRANSACTION_MEMO_LENGTH
+                        )));
+                    }
+                    self.vec_u8_serializer.serialize(memo, buffer)?;
+                }
             }
             OperationType::RollBuy { roll_count } => {
                 self.u32_serializer
@@ -612,6 +866,53 @@ impl Serializer<OperationType> for OperationTypeSerializer {
                     .serialize(target_func, buffer)?;
                 self.vec_u8_serializer.serialize(param, buffer)?;
             }
+            OperationType::MultiTransaction { outputs } => {
+                if outputs.is_empty() || outputs.len() as u64 > MAX_MULTI_TRANSACTION_OUTPUTS {
+                    return Err(SerializeError::GeneralError(format!(
+                        "a multi-recipient transaction has {} recipients, it must have between 1 and {}",
+                        outputs.len(),
+                        MAX_MULTI_TRANSACTION_OUTPUTS
+                    )));
+                }
+                self.u32_serializer
+                    .serialize(&u32::from(OperationTypeId::MultiTransaction), buffer)?;
+                self.u64_serializer
+                    .serialize(&(outputs.len() as u64), buffer)?;
+                for output in outputs {
+                    self.address_serializer
+                        .serialize(&output.recipient_address, buffer)?;
+                    self.amount_serializer.serialize(&output.amount, buffer)?;
+                }
+            }
+            OperationType::ScheduledTransaction {
+                recipient_address,
+                amount,
+                activation_slot,
+            } => {
+                self.u32_serializer
+                    .serialize(&u32::from(OperationTypeId::ScheduledTransaction), buffer)?;
+                self.address_serializer
+                    .serialize(recipient_address, buffer)?;
+                self.amount_serializer.serialize(amount, buffer)?;
+                self.slot_serializer.serialize(activation_slot, buffer)?;
+            }
+            OperationType::CancelScheduledTransaction { operation_id } => {
+                self.u32_serializer.serialize(
+                    &u32::from(OperationTypeId::CancelScheduledTransaction),
+                    buffer,
+                )?;
+                self.operation_id_serializer
+                    .serialize(operation_id, buffer)?;
+            }
+            OperationType::RegisterBlsKey {
+                public_key,
+                proof_of_possession,
+            } => {
+                self.u32_serializer
+                    .serialize(&u32::from(OperationTypeId::RegisterBlsKey), buffer)?;
+                buffer.extend(public_key.to_bytes());
+                buffer.extend(proof_of_possession.to_bytes());
+            }
         }
         Ok(())
     }
@@ -628,6 +929,12 @@ pub struct OperationTypeDeserializer {
     function_name_deserializer: StringDeserializer<U16VarIntDeserializer, u16>,
     parameter_deserializer: VecU8Deserializer,
     datastore_deserializer: DatastoreDeserializer,
+    memo_deserializer: VecU8Deserializer,
+    outputs_count_deserializer: U64VarIntDeserializer,
+    slot_deserializer: SlotDeserializer,
+    operation_id_deserializer: OperationIdDeserializer,
+    bls_public_key_deserializer: BlsPublicKeyDeserializer,
+    bls_signature_deserializer: BlsSignatureDeserializer,
 }
 
 impl OperationTypeDeserializer {
@@ -666,6 +973,21 @@ impl OperationTypeDeserializer {
                 max_op_datastore_key_length,
                 max_op_datastore_value_length,
             ),
+            memo_deserializer: VecU8Deserializer::new(
+                Included(0),
+                Included(MAX_TRANSACTION_MEMO_LENGTH),
+            ),
+            outputs_count_deserializer: U64VarIntDeserializer::new(
+                Included(1),
+                Included(MAX_MULTI_TRANSACTION_OUTPUTS),
+            ),
+            slot_deserializer: SlotDeserializer::new(
+                (Included(0), Included(u64::MAX)),
+                (Included(0), Excluded(THREAD_COUNT)),
+            ),
+            operation_id_deserializer: OperationIdDeserializer::new(),
+            bls_public_key_deserializer: BlsPublicKeyDeserializer::new(),
+            bls_signature_deserializer: BlsSignatureDeserializer::new(),
         }
     }
 }
@@ -729,8 +1051,31 @@ impl Deserializer<OperationType> for OperationTypeDeserializer {
                 .map(|(recipient_address, amount)| OperationType::Transaction {
                     recipient_address,
                     amount,
+                    memo: None,
                 })
                 .parse(input),
+                OperationTypeId::TransactionWithMemo => context(
+                    "Failed Transaction deserialization",
+                    tuple((
+                        context("Failed recipient_address deserialization", |input| {
+                            self.address_deserializer.deserialize(input)
+                        }),
+                        context("Failed amount deserialization", |input| {
+                            self.amount_deserializer.deserialize(input)
+                        }),
+                        context("Failed memo deserialization", |input| {
+                            self.memo_deserializer.deserialize(input)
+                        }),
+                    )),
+                )
+                .map(
+                    |(recipient_address, amount, memo)| OperationType::Transaction {
+                        recipient_address,
+                        amount,
+                        memo: Some(memo),
+                    },
+                )
+                .parse(input),
                 OperationTypeId::RollBuy => context("Failed RollBuy deserialization", |input| {
                     self.rolls_number_deserializer.deserialize(input)
                 })
@@ -791,6 +1136,78 @@ impl Deserializer<OperationType> for OperationTypeDeserializer {
                     },
                 )
                 .parse(input),
+                OperationTypeId::MultiTransaction => context(
+                    "Failed MultiTransaction deserialization",
+                    length_count(
+                        context("Failed outputs count deserialization", |input| {
+                            self.outputs_count_deserializer.deserialize(input)
+                        }),
+                        tuple((
+                            context("Failed recipient_address deserialization", |input| {
+                                self.address_deserializer.deserialize(input)
+                            }),
+                            context("Failed amount deserialization", |input| {
+                                self.amount_deserializer.deserialize(input)
+                            }),
+                        )),
+                    ),
+                )
+                .map(|outputs| OperationType::MultiTransaction {
+                    outputs: outputs
+                        .into_iter()
+                        .map(|(recipient_address, amount)| TransactionOutput {
+                            recipient_address,
+                            amount,
+                        })
+                        .collect(),
+                })
+                .parse(input),
+                OperationTypeId::ScheduledTransaction => context(
+                    "Failed ScheduledTransaction deserialization",
+                    tuple((
+                        context("Failed recipient_address deserialization", |input| {
+                            self.address_deserializer.deserialize(input)
+                        }),
+                        context("Failed amount deserialization", |input| {
+                            self.amount_deserializer.deserialize(input)
+                        }),
+                        context("Failed activation_slot deserialization", |input| {
+                            self.slot_deserializer.deserialize(input)
+                        }),
+                    )),
+                )
+                .map(|(recipient_address, amount, activation_slot)| {
+                    OperationType::ScheduledTransaction {
+                        recipient_address,
+                        amount,
+                        activation_slot,
+                    }
+                })
+                .parse(input),
+                OperationTypeId::CancelScheduledTransaction => context(
+                    "Failed CancelScheduledTransaction deserialization",
+                    |input| self.operation_id_deserializer.deserialize(input),
+                )
+                .map(|operation_id| OperationType::CancelScheduledTransaction { operation_id })
+                .parse(input),
+                OperationTypeId::RegisterBlsKey => context(
+                    "Failed RegisterBlsKey deserialization",
+                    tuple((
+                        context("Failed public_key deserialization", |input| {
+                            self.bls_public_key_deserializer.deserialize(input)
+                        }),
+                        context("Failed proof_of_possession deserialization", |input| {
+                            self.bls_signature_deserializer.deserialize(input)
+                        }),
+                    )),
+                )
+                .map(
+                    |(public_key, proof_of_possession)| OperationType::RegisterBlsKey {
+                        public_key,
+                        proof_of_possession,
+                    },
+                )
+                .parse(input),
             }
         })
         .parse(buffer)
@@ -808,6 +1225,35 @@ impl SecureShareOperation {
         start..=self.content.expire_period
     }
 
+    /// Address paying the fee of the operation: its sponsor if any, otherwise its creator
+    pub fn get_fee_payer(&self) -> Address {
+        match &self.content.sponsor {
+            Some(sponsor) => Address::from_public_key(&sponsor.public_key),
+            None => self.content_creator_address,
+        }
+    }
+
+    /// Signatures making the operation valid, as `(signed hash, signature, public key)`:
+    /// the one of its creator, then the one of its sponsor if any
+    pub fn get_signatures_to_verify(
+        &self,
+    ) -> Result<Vec<(Hash, Signature, PublicKey)>, ModelsError> {
+        let mut signatures = vec![(
+            self.signed_hash(),
+            self.signature,
+            self.content_creator_pub_key,
+        )];
+        if let Some(sponsor) = &self.content.sponsor {
+            signatures.push((
+                self.content
+                    .sponsored_hash(&self.content_creator_pub_key, &sponsor.public_key)?,
+                sponsor.signature,
+                sponsor.public_key,
+            ));
+        }
+        Ok(signatures)
+    }
+
     /// Get the max amount of gas used by the operation (`max_gas`)
     pub fn get_gas_usage(&self) -> u64 {
         match &self.content.op {
@@ -816,6 +1262,20 @@ impl SecureShareOperation {
             OperationType::RollBuy { .. } => 0,
             OperationType::RollSell { .. } => 0,
             OperationType::Transaction { .. } => 0,
+            OperationType::MultiTransaction { .. } => 0,
+            OperationType::ScheduledTransaction { .. } => 0,
+            OperationType::CancelScheduledTransaction { .. } => 0,
+            OperationType::RegisterBlsKey { .. } => 0,
+        }
+    }
+
+    /// Get the minimal fee of the operation: the operation is not executed with a lower fee
+    pub fn get_min_fee(&self) -> Amount {
+        match &self.content.op {
+            OperationType::MultiTransaction { outputs } => {
+                MULTI_TRANSACTION_FEE_PER_OUTPUT.saturating_mul_u64(outputs.len() as u64)
+            }
+            _ => Amount::zero(),
         }
     }
 
@@ -824,6 +1284,7 @@ impl SecureShareOperation {
         let mut res = PreHashSet::<Address>::default();
         let emitter_address = Address::from_public_key(&self.content_creator_pub_key);
         res.insert(emitter_address);
+        res.insert(self.get_fee_payer());
         match &self.content.op {
             OperationType::Transaction {
                 recipient_address, ..
@@ -836,11 +1297,18 @@ impl SecureShareOperation {
             OperationType::CallSC { target_addr, .. } => {
                 res.insert(*target_addr);
             }
+            OperationType::MultiTransaction { outputs } => {
+                res.extend(outputs.iter().map(|output| output.recipient_address));
+            }
+            OperationType::ScheduledTransaction { .. } => {}
+            OperationType::CancelScheduledTransaction { .. } => {}
+            OperationType::RegisterBlsKey { .. } => {}
         }
         res
     }
 
-    /// Gets the maximal amount of coins that may be spent by this operation (incl. fee)
+    /// Gets the maximal amount of coins that may be spent by the creator of this operation
+    /// (incl. fee, unless it is paid by a sponsor)
     pub fn get_max_spending(&self, roll_price: Amount) -> Amount {
         // compute the max amount of coins spent outside of the fees
         let max_non_fee_seq_spending = match &self.content.op {
@@ -849,9 +1317,20 @@ impl SecureShareOperation {
             OperationType::RollSell { .. } => Amount::zero(),
             OperationType::ExecuteSC { .. } => Amount::zero(),
             OperationType::CallSC { coins, .. } => *coins,
+            OperationType::MultiTransaction { outputs } => {
+                outputs.iter().fold(Amount::zero(), |total, output| {
+                    total.saturating_add(output.amount)
+                })
+            }
+            OperationType::ScheduledTransaction { amount, .. } => *amount,
+            OperationType::CancelScheduledTransaction { .. } => Amount::zero(),
+            OperationType::RegisterBlsKey { .. } => Amount::zero(),
         };
 
         // add all fees and return
+        if self.content.sponsor.is_some() {
+            return max_non_fee_seq_spending;
+        }
         max_non_fee_seq_spending.saturating_add(self.content.fee)
     }
 
@@ -868,6 +1347,10 @@ impl SecureShareOperation {
             }
             OperationType::ExecuteSC { .. } => {}
             OperationType::CallSC { .. } => {}
+            OperationType::MultiTransaction { .. } => {}
+            OperationType::ScheduledTransaction { .. } => {}
+            OperationType::CancelScheduledTransaction { .. } => {}
+            OperationType::RegisterBlsKey { .. } => {}
         }
         Ok(res)
     }
@@ -1163,11 +1646,14 @@ impl Serializer<Vec<SecureShareOperation>> for OperationsSerializer {
     /// let op = OperationType::Transaction {
     ///    recipient_address: Address::from_public_key(&keypair.get_public_key()),
     ///    amount: Amount::from_str("300").unwrap(),
+    ///    memo: None,
     /// };
     /// let content = Operation {
     ///   fee: Amount::from_str("20").unwrap(),
     ///   op,
     ///   expire_period: 50,
+    ///   sequence: None,
+    ///   sponsor: None,
     /// };
     /// let op_secured = Operation::new_verifiable(content, OperationSerializer::new(), &keypair).unwrap();
     /// let operations = vec![op_secured.clone(), op_secured.clone()];
@@ -1236,11 +1722,14 @@ impl Deserializer<Vec<SecureShareOperation>> for OperationsDeserializer {
     /// let op = OperationType::Transaction {
     ///    recipient_address: Address::from_public_key(&keypair.get_public_key()),
     ///    amount: Amount::from_str("300").unwrap(),
+    ///    memo: None,
     /// };
     /// let content = Operation {
     ///   fee: Amount::from_str("20").unwrap(),
     ///   op,
     ///   expire_period: 50,
+    ///   sequence: None,
+    ///   sponsor: None,
     /// };
     /// let op_secured = Operation::new_verifiable(content, OperationSerializer::new(), &keypair).unwrap();
     /// let operations = vec![op_secured.clone(), op_secured.clone()];
@@ -1283,7 +1772,7 @@ mod tests {
 
     use super::*;
     use massa_serialization::DeserializeError;
-    use massa_signature::KeyPair;
+    use massa_signature::{BlsKeyPair, KeyPair};
     use serial_test::serial;
     use std::collections::BTreeMap;
 
@@ -1296,6 +1785,7 @@ mod tests {
         let op = OperationType::Transaction {
             recipient_address: Address::from_public_key(&recv_keypair.get_public_key()),
             amount: Amount::default(),
+            memo: None,
         };
         let mut ser_type = Vec::new();
         OperationTypeSerializer::new()
@@ -1318,6 +1808,8 @@ mod tests {
             fee: Amount::from_str("20").unwrap(),
             op,
             expire_period: 50,
+            sequence: None,
+            sponsor: None,
         };
 
         let mut ser_content = Vec::new();
@@ -1360,6 +1852,384 @@ mod tests {
         assert_eq!(op.get_validity_range(10), 40..=50);
     }
 
+    #[test]
+    #[serial]
+    fn test_operation_sequence() {
+        let sender_keypair = KeyPair::generate();
+        let recv_keypair = KeyPair::generate();
+        let op = OperationType::Transaction {
+            recipient_address: Address::from_public_key(&recv_keypair.get_public_key()),
+            amount: Amount::default(),
+            memo: None,
+        };
+        let without_sequence = Operation {
+            fee: Amount::from_str("20").unwrap(),
+            op,
+            expire_period: 50,
+            sequence: None,
+            sponsor: None,
+        };
+        let with_sequence = Operation {
+            sequence: Some(300),
+            ..without_sequence.clone()
+        };
+        let deserializer = OperationDeserializer::new(
+            MAX_DATASTORE_VALUE_LENGTH,
+            MAX_FUNCTION_NAME_LENGTH,
+            MAX_PARAMETERS_SIZE,
+            MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+            MAX_OPERATION_DATASTORE_KEY_LENGTH,
+            MAX_OPERATION_DATASTORE_VALUE_LENGTH,
+        );
+        let mut ser_without = Vec::new();
+        OperationSerializer::new()
+            .serialize(&without_sequence, &mut ser_without)
+            .unwrap();
+        let mut ser_with = Vec::new();
+        OperationSerializer::new()
+            .serialize(&with_sequence, &mut ser_with)
+            .unwrap();
+        assert!(ser_with.len() > ser_without.len());
+        for (ser, content) in [(ser_without, &without_sequence), (ser_with, &with_sequence)] {
+            let (rest, res_content) = deserializer.deserialize::<DeserializeError>(&ser).unwrap();
+            assert!(rest.is_empty());
+            assert_eq!(&res_content, content);
+        }
+
+        // the sequence is signed along with the rest of the operation
+        let op_without = Operation::new_verifiable(
+            without_sequence,
+            OperationSerializer::new(),
+            &sender_keypair,
+        )
+        .unwrap();
+        let op_with =
+            Operation::new_verifiable(with_sequence, OperationSerializer::new(), &sender_keypair)
+                .unwrap();
+        assert_ne!(op_without.id, op_with.id);
+
+        assert_eq!(next_operation_sequence(None), 0);
+        assert_eq!(next_operation_sequence(Some(&7u64.to_be_bytes())), 7);
+    }
+
+    #[test]
+    #[serial]
+    fn test_operation_sponsor() {
+        use massa_signature::verify_signature_batch;
+
+        let sender_keypair = KeyPair::generate();
+        let sponsor_keypair = KeyPair::generate();
+        let recv_keypair = KeyPair::generate();
+        let content = Operation {
+            fee: Amount::from_str("20").unwrap(),
+            op: OperationType::Transaction {
+                recipient_address: Address::from_public_key(&recv_keypair.get_public_key()),
+                amount: Amount::from_str("300").unwrap(),
+                memo: None,
+            },
+            expire_period: 50,
+            sequence: Some(3),
+            sponsor: None,
+        };
+        let sponsored = content
+            .clone()
+            .with_sponsor(&sender_keypair.get_public_key(), &sponsor_keypair)
+            .unwrap();
+
+        let mut ser_sponsored = Vec::new();
+        OperationSerializer::new()
+            .serialize(&sponsored, &mut ser_sponsored)
+            .unwrap();
+        let (rest, res_sponsored) = OperationDeserializer::new(
+            MAX_DATASTORE_VALUE_LENGTH,
+            MAX_FUNCTION_NAME_LENGTH,
+            MAX_PARAMETERS_SIZE,
+            MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+            MAX_OPERATION_DATASTORE_KEY_LENGTH,
+            MAX_OPERATION_DATASTORE_VALUE_LENGTH,
+        )
+        .deserialize::<DeserializeError>(&ser_sponsored)
+        .unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(res_sponsored, sponsored);
+
+        // the sponsor pays the fee, the creator the rest
+        let op = Operation::new_verifiable(
+            sponsored.clone(),
+            OperationSerializer::new(),
+            &sender_keypair,
+        )
+        .unwrap();
+        assert_eq!(
+            op.get_fee_payer(),
+            Address::from_public_key(&sponsor_keypair.get_public_key())
+        );
+        assert_eq!(
+            op.get_max_spending(Amount::zero()),
+            Amount::from_str("300").unwrap()
+        );
+        assert!(op
+            .get_ledger_involved_addresses()
+            .contains(&op.get_fee_payer()));
+        let signatures = op.get_signatures_to_verify().unwrap();
+        assert_eq!(signatures.len(), 2);
+        verify_signature_batch(&signatures).unwrap();
+
+        // the sponsorship is bound to the creator
+        let other_creator_op =
+            Operation::new_verifiable(sponsored, OperationSerializer::new(), &recv_keypair)
+                .unwrap();
+        verify_signature_batch(&other_creator_op.get_signatures_to_verify().unwrap()).unwrap_err();
+
+        // and to the rest of the operation
+        let mut tampered = op.content.clone();
+        tampered.fee = Amount::from_str("10").unwrap();
+        let tampered_op =
+            Operation::new_verifiable(tampered, OperationSerializer::new(), &sender_keypair)
+                .unwrap();
+        verify_signature_batch(&tampered_op.get_signatures_to_verify().unwrap()).unwrap_err();
+
+        let unsponsored_op =
+            Operation::new_verifiable(content, OperationSerializer::new(), &sender_keypair)
+                .unwrap();
+        assert_eq!(
+            unsponsored_op.get_fee_payer(),
+            unsponsored_op.content_creator_address
+        );
+        assert_eq!(unsponsored_op.get_signatures_to_verify().unwrap().len(), 1);
+    }
+
+    #[test]
+    #[serial]
+    fn test_transaction_memo() {
+        let recv_keypair = KeyPair::generate();
+        let transaction = |memo: Option<Vec<u8>>| OperationType::Transaction {
+            recipient_address: Address::from_public_key(&recv_keypair.get_public_key()),
+            amount: Amount::from_str("10").unwrap(),
+            memo,
+        };
+        let deserializer = OperationTypeDeserializer::new(
+            MAX_DATASTORE_VALUE_LENGTH,
+            MAX_FUNCTION_NAME_LENGTH,
+            MAX_PARAMETERS_SIZE,
+            MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+            MAX_OPERATION_DATASTORE_KEY_LENGTH,
+            MAX_OPERATION_DATASTORE_VALUE_LENGTH,
+        );
+
+        let mut ser_without = Vec::new();
+        OperationTypeSerializer::new()
+            .serialize(&transaction(None), &mut ser_without)
+            .unwrap();
+        assert_eq!(
+            ser_without[0],
+            u32::from(OperationTypeId::Transaction) as u8
+        );
+
+        let with_memo = transaction(Some(b"deposit-42".to_vec()));
+        let mut ser_with = Vec::new();
+        OperationTypeSerializer::new()
+            .serialize(&with_memo, &mut ser_with)
+            .unwrap();
+        let (rest, res_type) = deserializer
+            .deserialize::<DeserializeError>(&ser_with)
+            .unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(res_type, with_memo);
+
+        // the memo is bounded
+        let too_long = transaction(Some(vec![0; MAX_TRANSACTION_MEMO_LENGTH as usize + 1]));
+        assert!(OperationTypeSerializer::new()
+            .serialize(&too_long, &mut Vec::new())
+            .is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn test_multi_transaction() {
+        let sender_keypair = KeyPair::generate();
+        let outputs: Vec<TransactionOutput> = (1..=3)
+            .map(|amount| TransactionOutput {
+                recipient_address: Address::from_public_key(&KeyPair::generate().get_public_key()),
+                amount: Amount::from_mantissa_scale(amount, 0),
+            })
+            .collect();
+        let op = OperationType::MultiTransaction {
+            outputs: outputs.clone(),
+        };
+        let mut ser_type = Vec::new();
+        OperationTypeSerializer::new()
+            .serialize(&op, &mut ser_type)
+            .unwrap();
+        let (rest, res_type) = OperationTypeDeserializer::new(
+            MAX_DATASTORE_VALUE_LENGTH,
+            MAX_FUNCTION_NAME_LENGTH,
+            MAX_PARAMETERS_SIZE,
+            MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+            MAX_OPERATION_DATASTORE_KEY_LENGTH,
+            MAX_OPERATION_DATASTORE_VALUE_LENGTH,
+        )
+        .deserialize::<DeserializeError>(&ser_type)
+        .unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(res_type, op);
+
+        let content = Operation {
+            fee: Amount::from_str("1").unwrap(),
+            expire_period: 50,
+            sequence: None,
+            sponsor: None,
+            op,
+        };
+        let op = Operation::new_verifiable(content, OperationSerializer::new(), &sender_keypair)
+            .unwrap();
+        assert_eq!(
+            op.get_max_spending(Amount::zero()),
+            Amount::from_str("7").unwrap()
+        );
+        assert_eq!(
+            op.get_min_fee(),
+            MULTI_TRANSACTION_FEE_PER_OUTPUT.saturating_mul_u64(3)
+        );
+        let involved = op.get_ledger_involved_addresses();
+        assert_eq!(involved.len(), 4);
+        assert!(outputs
+            .iter()
+            .all(|output| involved.contains(&output.recipient_address)));
+
+        // at least one recipient
+        assert!(OperationTypeSerializer::new()
+            .serialize(
+                &OperationType::MultiTransaction {
+                    outputs: Vec::new()
+                },
+                &mut Vec::new()
+            )
+            .is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn test_scheduled_transaction() {
+        let sender_keypair = KeyPair::generate();
+        let scheduled = OperationType::ScheduledTransaction {
+            recipient_address: Address::from_public_key(&KeyPair::generate().get_public_key()),
+            amount: Amount::from_str("12").unwrap(),
+            activation_slot: Slot::new(42, 3),
+        };
+        let content = Operation {
+            fee: Amount::from_str("1").unwrap(),
+            expire_period: 50,
+            sequence: None,
+            sponsor: None,
+            op: scheduled,
+        };
+        let scheduled =
+            Operation::new_verifiable(content, OperationSerializer::new(), &sender_keypair)
+                .unwrap();
+        assert_eq!(
+            scheduled.get_max_spending(Amount::zero()),
+            Amount::from_str("13").unwrap()
+        );
+        // the recipient is only credited at the activation slot
+        assert_eq!(scheduled.get_ledger_involved_addresses().len(), 1);
+        let cancellation = OperationType::CancelScheduledTransaction {
+            operation_id: scheduled.id,
+        };
+
+        for op in [scheduled.content.op, cancellation] {
+            let mut ser_type = Vec::new();
+            OperationTypeSerializer::new()
+                .serialize(&op, &mut ser_type)
+                .unwrap();
+            let (rest, res_type) = OperationTypeDeserializer::new(
+                MAX_DATASTORE_VALUE_LENGTH,
+                MAX_FUNCTION_NAME_LENGTH,
+                MAX_PARAMETERS_SIZE,
+                MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+                MAX_OPERATION_DATASTORE_KEY_LENGTH,
+                MAX_OPERATION_DATASTORE_VALUE_LENGTH,
+            )
+            .deserialize::<DeserializeError>(&ser_type)
+            .unwrap();
+            assert!(rest.is_empty());
+            assert_eq!(res_type, op);
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_register_bls_key() {
+        let bls_keypair = BlsKeyPair::derive_from(&KeyPair::generate());
+        let op = OperationType::RegisterBlsKey {
+            public_key: bls_keypair.get_public_key(),
+            proof_of_possession: bls_keypair.prove_possession(),
+        };
+        let mut ser_type = Vec::new();
+        OperationTypeSerializer::new()
+            .serialize(&op, &mut ser_type)
+            .unwrap();
+        let (rest, res_type) = OperationTypeDeserializer::new(
+            MAX_DATASTORE_VALUE_LENGTH,
+            MAX_FUNCTION_NAME_LENGTH,
+            MAX_PARAMETERS_SIZE,
+            MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+            MAX_OPERATION_DATASTORE_KEY_LENGTH,
+            MAX_OPERATION_DATASTORE_VALUE_LENGTH,
+        )
+        .deserialize::<DeserializeError>(&ser_type)
+        .unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(res_type, op);
+
+        // a truncated proof is refused
+        ser_type.pop();
+        assert!(OperationTypeDeserializer::new(
+            MAX_DATASTORE_VALUE_LENGTH,
+            MAX_FUNCTION_NAME_LENGTH,
+            MAX_PARAMETERS_SIZE,
+            MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+            MAX_OPERATION_DATASTORE_KEY_LENGTH,
+            MAX_OPERATION_DATASTORE_VALUE_LENGTH,
+        )
+        .deserialize::<DeserializeError>(&ser_type)
+        .is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn test_signature_bound_to_chain_id() {
+        use crate::config::CHAIN_ID_ACTIVATION_PERIOD;
+
+        let sender_keypair = KeyPair::generate();
+        let new_operation = |expire_period| {
+            let content = Operation {
+                fee: Amount::from_str("20").unwrap(),
+                op: OperationType::RollBuy { roll_count: 1 },
+                expire_period,
+                sequence: None,
+                sponsor: None,
+            };
+            Operation::new_verifiable(content, OperationSerializer::new(), &sender_keypair).unwrap()
+        };
+
+        // before the activation, the signature covers the operation id
+        if CHAIN_ID_ACTIVATION_PERIOD > 0 {
+            let op: SecureShareOperation = new_operation(CHAIN_ID_ACTIVATION_PERIOD - 1);
+            op.verify_signature().unwrap();
+            assert_eq!(op.signed_hash(), *op.id.get_hash());
+        }
+
+        // from the activation, it covers the chain id too
+        let op: SecureShareOperation = new_operation(CHAIN_ID_ACTIVATION_PERIOD);
+        op.verify_signature().unwrap();
+        assert_ne!(op.signed_hash(), *op.id.get_hash());
+        assert!(op
+            .content_creator_pub_key
+            .verify_signature(op.id.get_hash(), &op.signature)
+            .is_err());
+    }
+
     #[test]
     #[serial]
     fn test_executesc() {
@@ -1393,6 +2263,8 @@ mod tests {
             fee: Amount::from_str("20").unwrap(),
             op,
             expire_period: 50,
+            sequence: None,
+            sponsor: None,
         };
 
         let mut ser_content = Vec::new();
@@ -1469,6 +2341,8 @@ mod tests {
             fee: Amount::from_str("20").unwrap(),
             op,
             expire_period: 50,
+            sequence: None,
+            sponsor: None,
         };
 
         let mut ser_content = Vec::new();
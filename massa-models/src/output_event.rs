@@ -37,6 +37,9 @@ pub struct EventExecutionContext {
     pub is_final: bool,
     /// if the sc that emitted this event failed
     pub is_error: bool,
+    /// structured description of the failure, set on the error event emitted when an execution fails
+    #[serde(default)]
+    pub error: Option<ExecutionErrorInfo>,
 }
 
 impl Display for EventExecutionContext {
@@ -58,6 +61,12 @@ impl Display for EventExecutionContext {
         if let Some(id) = self.origin_operation_id {
             writeln!(f, "Origin operation id: {}", id)?;
         }
+        if let Some(error) = &self.error {
+            writeln!(f, "Error code: {}", error.code)?;
+            if let Some(reason) = &error.revert_reason {
+                writeln!(f, "Revert reason: {}", String::from_utf8_lossy(reason))?;
+            }
+        }
         writeln!(
             f,
             "Call stack: {}",
@@ -69,3 +78,148 @@ impl Display for EventExecutionContext {
         )
     }
 }
+
+/// Prefix of the events through which a smart contract sets its revert reason before aborting:
+/// the bytes following the prefix in the latest such event are reported with the failure.
+///
+/// This convention stands in for a dedicated revert ABI, which is left for a release of the
+/// smart contract runtime: events not starting with the prefix never set a revert reason.
+pub const REVERT_REASON_EVENT_PREFIX: &str = "massa_revert_reason:";
+
+impl SCOutputEvent {
+    /// Revert reason set by this event, if its data starts with `REVERT_REASON_EVENT_PREFIX`
+    pub fn revert_reason(&self) -> Option<&[u8]> {
+        self.data
+            .strip_prefix(REVERT_REASON_EVENT_PREFIX)
+            .map(str::as_bytes)
+    }
+}
+
+/// Latest revert reason set by `events`, ordered from the oldest to the most recent
+pub fn latest_revert_reason<'a>(
+    events: impl DoubleEndedIterator<Item = &'a SCOutputEvent>,
+) -> Option<&'a [u8]> {
+    events.rev().find_map(SCOutputEvent::revert_reason)
+}
+
+/// Category of an execution failure
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecutionErrorCode {
+    /// the smart contract execution failed
+    Runtime,
+    /// the smart contract aborted after setting a revert reason
+    Reverted,
+    /// the roll buy could not be executed
+    RollBuy,
+    /// the roll sell could not be executed
+    RollSell,
+    /// the transaction could not be executed
+    Transaction,
+    /// the vesting constraints of the address were not met
+    Vesting,
+    /// the operation is not valid at the execution slot
+    InvalidSlotRange,
+    /// not enough gas left in the block
+    NotEnoughGas,
+    /// the requested gas is above the allowed maximum
+    TooMuchGas,
+    /// the operation could not be included in the block
+    IncludeOperation,
+    /// the read-only execution exceeded its resource limits
+    ResourceLimit,
+    /// any other failure
+    Internal,
+}
+
+impl Display for ExecutionErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let code = match self {
+            ExecutionErrorCode::Runtime => "runtime",
+            ExecutionErrorCode::Reverted => "reverted",
+            ExecutionErrorCode::RollBuy => "roll_buy",
+            ExecutionErrorCode::RollSell => "roll_sell",
+            ExecutionErrorCode::Transaction => "transaction",
+            ExecutionErrorCode::Vesting => "vesting",
+            ExecutionErrorCode::InvalidSlotRange => "invalid_slot_range",
+            ExecutionErrorCode::NotEnoughGas => "not_enough_gas",
+            ExecutionErrorCode::TooMuchGas => "too_much_gas",
+            ExecutionErrorCode::IncludeOperation => "include_operation",
+            ExecutionErrorCode::ResourceLimit => "resource_limit",
+            ExecutionErrorCode::Internal => "internal",
+        };
+        write!(f, "{}", code)
+    }
+}
+
+/// Structured description of an execution failure
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExecutionErrorInfo {
+    /// category of the failure
+    pub code: ExecutionErrorCode,
+    /// human readable description
+    pub message: String,
+    /// revert reason set by the failed smart contract, if any
+    pub revert_reason: Option<Vec<u8>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(data: &str) -> SCOutputEvent {
+        SCOutputEvent {
+            context: EventExecutionContext {
+                slot: Slot::new(1, 0),
+                block: None,
+                read_only: false,
+                index_in_slot: 0,
+                call_stack: VecDeque::new(),
+                origin_operation_id: None,
+                is_final: false,
+                is_error: false,
+                error: None,
+            },
+            data: data.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_revert_reason() {
+        assert_eq!(
+            event("massa_revert_reason:insufficient allowance").revert_reason(),
+            Some(&b"insufficient allowance"[..])
+        );
+        assert_eq!(
+            event("massa_revert_reason:").revert_reason(),
+            Some(&b""[..])
+        );
+        // only a leading prefix sets a revert reason
+        assert_eq!(event("insufficient allowance").revert_reason(), None);
+        assert_eq!(
+            event(" massa_revert_reason:insufficient allowance").revert_reason(),
+            None
+        );
+        assert_eq!(
+            event("{\"massa_revert_reason\": \"insufficient allowance\"}").revert_reason(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_latest_revert_reason() {
+        let events = [
+            event("massa_revert_reason:first"),
+            event("massa_revert_reason:second"),
+            event("unrelated event"),
+        ];
+        // the most recent reason wins, whatever the events emitted after it
+        assert_eq!(latest_revert_reason(events.iter()), Some(&b"second"[..]));
+        assert_eq!(
+            latest_revert_reason(events[..1].iter()),
+            Some(&b"first"[..])
+        );
+        assert_eq!(latest_revert_reason(events[2..].iter()), None);
+        assert_eq!(latest_revert_reason(events[..0].iter()), None);
+    }
+}
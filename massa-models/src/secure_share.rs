@@ -1,6 +1,10 @@
 use std::fmt::Display;
 
-use crate::{address::Address, error::ModelsError};
+use crate::{
+    address::Address,
+    config::{CHAIN_ID, CHAIN_ID_ACTIVATION_PERIOD},
+    error::ModelsError,
+};
 use massa_hash::Hash;
 use massa_serialization::{Deserializer, SerializeError, Serializer};
 use massa_signature::{
@@ -51,11 +55,30 @@ pub trait Id {
     fn get_hash(&self) -> &Hash;
 }
 
+/// Hash signed by the creator of a content whose id hash is `id_hash`, signed at `signature_period`.
+/// From `CHAIN_ID_ACTIVATION_PERIOD`, it is bound to `CHAIN_ID` so that the signature is refused on other networks.
+pub fn compute_signed_hash(id_hash: &Hash, signature_period: Option<u64>) -> Hash {
+    match signature_period {
+        Some(period) if period >= CHAIN_ID_ACTIVATION_PERIOD => {
+            let mut signed_data = CHAIN_ID.0.to_be_bytes().to_vec();
+            signed_data.extend(id_hash.to_bytes());
+            Hash::compute_from(&signed_data)
+        }
+        _ => *id_hash,
+    }
+}
+
 /// Trait that define a structure that can be signed for secure sharing.
 pub trait SecureShareContent
 where
     Self: Sized + Display,
 {
+    /// Period deciding whether the signature is bound to the chain id, see `compute_signed_hash`.
+    /// `None` if the signature is never bound to it.
+    fn signature_period(&self) -> Option<u64> {
+        None
+    }
+
     /// Using the provided key-pair, applies a cryptographic signature, and packages
     /// the data required to share and verify the data in a trust-free network of peers.
     fn new_verifiable<Ser: Serializer<Self>, ID: Id>(
@@ -72,7 +95,7 @@ where
         let hash = Hash::compute_from(&hash_data);
         let creator_address = Address::from_public_key(&public_key);
         Ok(SecureShare {
-            signature: keypair.sign(&hash)?,
+            signature: keypair.sign(&compute_signed_hash(&hash, content.signature_period()))?,
             content_creator_pub_key: public_key,
             content_creator_address: creator_address,
             content,
@@ -175,7 +198,12 @@ where
     pub fn verify_signature(&self) -> Result<(), ModelsError> {
         Ok(self
             .content_creator_pub_key
-            .verify_signature(self.id.get_hash(), &self.signature)?)
+            .verify_signature(&self.signed_hash(), &self.signature)?)
+    }
+
+    /// hash covered by the signature, bound to the chain id from its activation
+    pub fn signed_hash(&self) -> Hash {
+        compute_signed_hash(self.id.get_hash(), self.content.signature_period())
     }
 
     /// get full serialized size
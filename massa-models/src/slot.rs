@@ -9,6 +9,8 @@ use nom::error::{context, ContextError, ParseError};
 use serde::{Deserialize, Serialize};
 use std::ops::{Bound, RangeBounds};
 use std::str::FromStr;
+use std::sync::atomic::{self, AtomicU64};
+use std::sync::Arc;
 use std::{cmp::Ordering, convert::TryInto};
 
 /// a point in time where a block is expected
@@ -331,7 +333,7 @@ impl Slot {
 }
 
 /// When an address is drawn to create an endorsement it is selected for a specific index
-#[derive(Debug, Deserialize, Serialize, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Deserialize, Serialize, Hash, PartialEq, Eq)]
 pub struct IndexedSlot {
     /// slot
     pub slot: Slot,
@@ -344,3 +346,102 @@ impl std::fmt::Display for IndexedSlot {
         writeln!(f, "Slot: {}, Index: {}", self.slot, self.index)
     }
 }
+
+/// Value of the shared atomic when the lag is unknown
+const UNKNOWN_SYNC_LAG: u64 = u64::MAX;
+
+/// Estimate of the number of slots by which the latest final slot of the node lags behind the one of the network,
+/// from the final slots gossiped by the connected nodes.
+///
+/// The clones share the same estimate, so that it can be updated by the network and read by the modules
+/// that should not act on a stale state.
+#[derive(Debug, Clone)]
+pub struct SyncLag(Arc<AtomicU64>);
+
+impl Default for SyncLag {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SyncLag {
+    /// Creates a shared estimate, initially unknown
+    pub fn new() -> Self {
+        SyncLag(Arc::new(AtomicU64::new(UNKNOWN_SYNC_LAG)))
+    }
+
+    /// Current estimate in slots, `None` if unknown
+    pub fn get(&self) -> Option<u64> {
+        match self.0.load(atomic::Ordering::Relaxed) {
+            UNKNOWN_SYNC_LAG => None,
+            lag => Some(lag),
+        }
+    }
+
+    /// Sets the estimate to the number of slots from the `local` final slot to the median of the final slots
+    /// of the connected nodes, 0 if the node is ahead, unknown if there is no slot to compare.
+    /// The lower median is used, so that a minority of nodes announcing final slots in the future cannot stop the node.
+    /// ```
+    /// # use massa_models::slot::*;
+    /// let sync_lag = SyncLag::new();
+    /// let network = vec![Slot::new(12, 1), Slot::new(11, 0), Slot::new(900, 0)];
+    /// sync_lag.set_median(Some(Slot::new(10, 0)), network.clone(), 2);
+    /// assert_eq!(sync_lag.get(), Some(5));
+    /// sync_lag.set_median(Some(Slot::new(13, 0)), network, 2);
+    /// assert_eq!(sync_lag.get(), Some(0));
+    /// sync_lag.set_median(Some(Slot::new(13, 0)), vec![], 2);
+    /// assert_eq!(sync_lag.get(), None);
+    /// ```
+    pub fn set_median(
+        &self,
+        local: Option<Slot>,
+        network: impl IntoIterator<Item = Slot>,
+        thread_count: u8,
+    ) {
+        let mut network: Vec<Slot> = network.into_iter().collect();
+        network.sort_unstable();
+        let lag = match (local, network.len()) {
+            (None, _) | (_, 0) => UNKNOWN_SYNC_LAG,
+            (Some(local), len) => network[(len - 1) / 2]
+                .slots_since(&local, thread_count)
+                .unwrap_or(0)
+                .min(UNKNOWN_SYNC_LAG - 1),
+        };
+        self.0.store(lag, atomic::Ordering::Relaxed);
+    }
+}
+
+/// Slot shared between threads, which can be read without locking the structure it belongs to.
+///
+/// The clones share the same slot. Periods are stored on 56 bits.
+#[derive(Debug, Clone)]
+pub struct SharedSlot(Arc<AtomicU64>);
+
+impl SharedSlot {
+    /// Creates a shared slot, initially `slot`
+    /// ```
+    /// # use massa_models::slot::*;
+    /// let shared_slot = SharedSlot::new(Slot::new(10, 3));
+    /// let clone = shared_slot.clone();
+    /// shared_slot.set(Slot::new(11, 0));
+    /// assert_eq!(clone.get(), Slot::new(11, 0));
+    /// ```
+    pub fn new(slot: Slot) -> Self {
+        SharedSlot(Arc::new(AtomicU64::new(Self::pack(slot))))
+    }
+
+    /// Current slot
+    pub fn get(&self) -> Slot {
+        let packed = self.0.load(atomic::Ordering::Relaxed);
+        Slot::new(packed >> 8, packed as u8)
+    }
+
+    /// Sets the slot
+    pub fn set(&self, slot: Slot) {
+        self.0.store(Self::pack(slot), atomic::Ordering::Relaxed);
+    }
+
+    fn pack(slot: Slot) -> u64 {
+        (slot.period << 8) | u64::from(slot.thread)
+    }
+}
@@ -1,9 +1,12 @@
 //! Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use crate::block_id::BlockId;
 use crate::slot::Slot;
 use massa_time::MassaTime;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
 use std::fmt::Formatter;
+use std::path::PathBuf;
 
 /// execution statistics
 #[derive(Serialize, Deserialize, Debug)]
@@ -61,6 +64,9 @@ pub struct NetworkStats {
     pub banned_peer_count: u64,
     /// active node count
     pub active_node_count: u64,
+    /// failed handshakes since the start of the node, by reason
+    #[serde(default)]
+    pub handshake_failures: BTreeMap<String, u64>,
 }
 
 impl std::fmt::Display for NetworkStats {
@@ -71,6 +77,12 @@ impl std::fmt::Display for NetworkStats {
         writeln!(f, "\tKnown peers: {}", self.known_peer_count)?;
         writeln!(f, "\tBanned peers: {}", self.banned_peer_count)?;
         writeln!(f, "\tActive nodes: {}", self.active_node_count)?;
+        if !self.handshake_failures.is_empty() {
+            writeln!(f, "\tFailed handshakes:")?;
+            for (reason, count) in &self.handshake_failures {
+                writeln!(f, "\t\t{}: {}", reason, count)?;
+            }
+        }
         Ok(())
     }
 }
@@ -88,6 +100,17 @@ pub struct ConsensusStats {
     pub stale_block_count: u64,
     ///  number of actives cliques
     pub clique_count: u64,
+    /// number of headers currently waiting for missing parents
+    pub orphan_count: u64,
+    /// number of headers that arrived before their parents
+    pub orphan_added_count: u64,
+    /// number of orphans reprocessed after their parents arrived
+    pub orphan_reprocessed_count: u64,
+    /// number of orphans dropped because of the per-node quota or pruning
+    pub orphan_dropped_count: u64,
+    /// estimated memory use of the structures of consensus
+    #[serde(default)]
+    pub memory: ConsensusMemoryStats,
 }
 
 impl std::fmt::Display for ConsensusStats {
@@ -106,6 +129,100 @@ impl std::fmt::Display for ConsensusStats {
         writeln!(f, "\tFinal block count: {}", self.final_block_count)?;
         writeln!(f, "\tStale block count: {}", self.stale_block_count)?;
         writeln!(f, "\tClique count: {}", self.clique_count)?;
+        writeln!(
+            f,
+            "\tOrphan blocks: {} waiting, {} received, {} reprocessed, {} dropped",
+            self.orphan_count,
+            self.orphan_added_count,
+            self.orphan_reprocessed_count,
+            self.orphan_dropped_count
+        )?;
+        write!(f, "{}", self.memory)?;
+        Ok(())
+    }
+}
+
+/// number of entries and estimated memory use of a structure
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StructureSize {
+    /// number of entries
+    pub count: u64,
+    /// estimated memory use of the entries, in bytes
+    pub bytes: u64,
+}
+
+impl StructureSize {
+    /// Accounts for an entry using `bytes`
+    pub fn add(&mut self, bytes: usize) {
+        self.count += 1;
+        self.bytes = self.bytes.saturating_add(bytes as u64);
+    }
+}
+
+impl std::fmt::Display for StructureSize {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} entries, {} KB", self.count, self.bytes / 1_000)
+    }
+}
+
+/// estimated memory use of the structures of consensus, the blocks and headers being counted at their serialized size
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConsensusMemoryStats {
+    /// active blocks of the graph, with their bodies
+    pub active_blocks: StructureSize,
+    /// discarded blocks kept to avoid reprocessing them
+    pub discarded_index: StructureSize,
+    /// blocks and headers received but not processed yet
+    pub incoming: StructureSize,
+    /// blocks and headers waiting for missing dependencies
+    pub waiting_for_dependencies: StructureSize,
+    /// blocks and headers waiting for their slot
+    pub waiting_for_slot: StructureSize,
+    /// incompatibilities between the active blocks
+    pub incompatibility_graph: StructureSize,
+}
+
+impl ConsensusMemoryStats {
+    /// Estimated memory use of all the structures, in bytes
+    pub fn total_bytes(&self) -> u64 {
+        [
+            self.active_blocks,
+            self.discarded_index,
+            self.incoming,
+            self.waiting_for_dependencies,
+            self.waiting_for_slot,
+            self.incompatibility_graph,
+        ]
+        .iter()
+        .fold(0, |total, size| total.saturating_add(size.bytes))
+    }
+}
+
+impl std::fmt::Display for ConsensusMemoryStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "\tEstimated memory use: {} MB",
+            self.total_bytes() / 1_000_000
+        )?;
+        writeln!(f, "\t\tActive blocks: {}", self.active_blocks)?;
+        writeln!(f, "\t\tDiscarded blocks: {}", self.discarded_index)?;
+        writeln!(f, "\t\tIncoming blocks: {}", self.incoming)?;
+        writeln!(
+            f,
+            "\t\tBlocks waiting for dependencies: {}",
+            self.waiting_for_dependencies
+        )?;
+        writeln!(
+            f,
+            "\t\tBlocks waiting for their slot: {}",
+            self.waiting_for_slot
+        )?;
+        writeln!(
+            f,
+            "\t\tIncompatibility graph: {}",
+            self.incompatibility_graph
+        )?;
         Ok(())
     }
 }
@@ -127,3 +244,217 @@ impl std::fmt::Display for PoolStats {
         Ok(())
     }
 }
+
+/// operations refused by the bytecode analyzer of the pool, by reason
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BytecodeRejectStats {
+    /// bytecode too large
+    pub too_large: u64,
+    /// bytecode not a valid WebAssembly module
+    pub invalid_module: u64,
+    /// bytecode importing a function that is not allowed
+    pub banned_import: u64,
+    /// maximal gas too low for the bytecode
+    pub insufficient_gas: u64,
+}
+
+impl std::fmt::Display for BytecodeRejectStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Bytecode analysis rejects:")?;
+        writeln!(f, "	Too large: {}", self.too_large)?;
+        writeln!(f, "	Invalid module: {}", self.invalid_module)?;
+        writeln!(f, "	Banned import: {}", self.banned_import)?;
+        writeln!(f, "	Insufficient gas: {}", self.insufficient_gas)?;
+        Ok(())
+    }
+}
+
+/// disk usage of a directory monitored by the node
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiskUsage {
+    /// monitored directory
+    pub path: PathBuf,
+    /// size of the directory content, in bytes
+    pub used_bytes: u64,
+    /// free space left on the filesystem of the directory, in bytes
+    pub available_bytes: u64,
+    /// size of the filesystem of the directory, in bytes
+    pub total_bytes: u64,
+}
+
+/// disk status of the node, produced by the disk monitor
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiskStatus {
+    /// usage of each monitored directory
+    pub usage: Vec<DiskUsage>,
+    /// true if free disk space is too low: block and endorsement production is paused
+    pub read_only: bool,
+}
+
+impl std::fmt::Display for DiskStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Disk status:")?;
+        if self.read_only {
+            writeln!(
+                f,
+                "\tRead-only mode: production paused because of low disk space"
+            )?;
+        }
+        for usage in &self.usage {
+            writeln!(
+                f,
+                "\t{}: {} MB used, {} MB free out of {} MB",
+                usage.path.display(),
+                usage.used_bytes / 1_000_000,
+                usage.available_bytes / 1_000_000,
+                usage.total_bytes / 1_000_000
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// memory status of the node, produced by the memory monitor
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryStatus {
+    /// resident memory of the node process, in bytes, if measured
+    pub rss_bytes: Option<u64>,
+    /// resident memory above which the capacities are shrunk, in bytes, if configured
+    pub rss_cap: Option<u64>,
+    /// live capacities of the pool and protocol caches, in thousandths of the configured ones
+    pub capacity_permille: u32,
+    /// live capacities of the pool and protocol caches, by name
+    pub live_limits: BTreeMap<String, usize>,
+}
+
+impl std::fmt::Display for MemoryStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Memory status:")?;
+        match (self.rss_bytes, self.rss_cap) {
+            (Some(rss), Some(cap)) => writeln!(
+                f,
+                "\tResident memory: {} MB out of a {} MB cap",
+                rss / 1_000_000,
+                cap / 1_000_000
+            )?,
+            (Some(rss), None) => writeln!(f, "\tResident memory: {} MB", rss / 1_000_000)?,
+            _ => writeln!(f, "\tResident memory not monitored")?,
+        }
+        if self.capacity_permille < crate::config::FULL_CAPACITY_PERMILLE {
+            writeln!(
+                f,
+                "\tMemory pressure: capacities shrunk to {:.1}%",
+                self.capacity_permille as f64 / 10.0
+            )?;
+        }
+        for (name, limit) in &self.live_limits {
+            writeln!(f, "\t{}: {}", name, limit)?;
+        }
+        Ok(())
+    }
+}
+
+/// time spent in each stage of the production of a block, in microseconds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockProductionTimings {
+    /// slot of the block
+    pub slot: Slot,
+    /// id of the block
+    pub block_id: BlockId,
+    /// delay between the start of the slot and the wake-up of the block factory
+    pub wakeup_delay: MassaTime,
+    /// check that the producer drawn for the slot is a local staking address
+    pub draw_check_us: u64,
+    /// selection and claim of the best parents
+    pub parents_us: u64,
+    /// collection of the endorsements from the pool
+    pub endorsements_us: u64,
+    /// selection of the operations from the pool and denylist filtering
+    pub operations_us: u64,
+    /// double-production check, then signature of the header and of the block
+    pub signing_us: u64,
+    /// storage of the block and its registration in consensus, which starts its propagation
+    pub propagation_start_us: u64,
+    /// delay between the start of the slot and the start of the propagation
+    pub total_delay: MassaTime,
+}
+
+impl std::fmt::Display for BlockProductionTimings {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "block {} at slot {} sent {} ms after the slot start (wake-up {} ms, draw check {} µs, parents {} µs, endorsements {} µs, operations {} µs, signing {} µs, propagation start {} µs)",
+            self.block_id,
+            self.slot,
+            self.total_delay.to_millis(),
+            self.wakeup_delay.to_millis(),
+            self.draw_check_us,
+            self.parents_us,
+            self.endorsements_us,
+            self.operations_us,
+            self.signing_us,
+            self.propagation_start_us
+        )
+    }
+}
+
+/// timings of the latest blocks produced by the node, recorded by the block factory
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockProductionStats {
+    /// timings of the latest produced blocks, from oldest to newest
+    pub latest: VecDeque<BlockProductionTimings>,
+}
+
+impl BlockProductionStats {
+    /// Records the `timings` of a produced block, keeping at most `max_len` blocks
+    pub fn record(&mut self, timings: BlockProductionTimings, max_len: usize) {
+        self.latest.push_back(timings);
+        while self.latest.len() > max_len {
+            self.latest.pop_front();
+        }
+    }
+}
+
+impl std::fmt::Display for BlockProductionStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Block production timings:")?;
+        if self.latest.is_empty() {
+            writeln!(f, "	No block produced yet")?;
+        }
+        for timings in &self.latest {
+            writeln!(f, "	{}", timings)?;
+        }
+        Ok(())
+    }
+}
+
+/// statistics of the cache of the expensive read API responses
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiCacheStats {
+    /// requests answered from the cache
+    pub hits: u64,
+    /// requests answered by computing the response
+    pub misses: u64,
+    /// number of responses currently cached
+    pub entries: usize,
+}
+
+impl std::fmt::Display for ApiCacheStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "API cache stats:")?;
+        let requests = self.hits + self.misses;
+        if requests > 0 {
+            writeln!(
+                f,
+                "\tHit rate: {:.1}% ({} hits, {} misses)",
+                self.hits as f64 * 100.0 / requests as f64,
+                self.hits,
+                self.misses
+            )?;
+        } else {
+            writeln!(f, "\tNo cached request yet")?;
+        }
+        writeln!(f, "\tCached responses: {}", self.entries)?;
+        Ok(())
+    }
+}
@@ -157,6 +157,127 @@ pub fn time_range_to_slot_range(
     Ok((start_slot, end_slot))
 }
 
+/// Iterator over the slots of a slot range [start, end), in chronological order
+#[derive(Debug, Clone)]
+pub struct SlotRangeIter {
+    /// next slot to yield, None once the range is exhausted
+    next: Option<Slot>,
+    /// end of the range (excluded)
+    end: Slot,
+    /// number of threads
+    thread_count: u8,
+}
+
+impl Iterator for SlotRangeIter {
+    type Item = Slot;
+
+    fn next(&mut self) -> Option<Slot> {
+        let slot = self.next.filter(|slot| *slot < self.end)?;
+        self.next = slot.get_next_slot(self.thread_count).ok();
+        Some(slot)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let count = self
+            .next
+            .and_then(|slot| slot_count_in_range(slot, self.end, self.thread_count).ok())
+            .unwrap_or(0) as usize;
+        (count, Some(count))
+    }
+}
+
+/// Iterates over the slots of the slot range [start, end)
+///
+/// # Arguments
+/// * `start`: starting slot (included)
+/// * `end`: ending slot (excluded)
+/// * `thread_count`: number of threads
+pub fn slots_in_range(start: Slot, end: Slot, thread_count: u8) -> SlotRangeIter {
+    SlotRangeIter {
+        next: Some(start),
+        end,
+        thread_count,
+    }
+}
+
+/// Iterates over the slots whose timestamp is in the time range [start_time, end_time).
+/// Times before genesis are brought back to the genesis, where the first slot happens.
+///
+/// # Arguments
+/// * `thread_count`: number of threads.
+/// * `t0`: time in milliseconds between two periods in the same thread.
+/// * `genesis_timestamp`: when the blockclique first started, in milliseconds
+/// * `start_time`: start time (included)
+/// * `end_time`: end time (excluded)
+pub fn slots_in_time_range(
+    thread_count: u8,
+    t0: MassaTime,
+    genesis_timestamp: MassaTime,
+    start_time: MassaTime,
+    end_time: MassaTime,
+) -> Result<SlotRangeIter, ModelsError> {
+    let (start, end) = time_range_to_slot_range(
+        thread_count,
+        t0,
+        genesis_timestamp,
+        Some(start_time),
+        Some(end_time),
+    )?;
+    let start = start.ok_or(ModelsError::TimeOverflowError)?;
+    let end = end.ok_or(ModelsError::TimeOverflowError)?;
+    Ok(slots_in_range(start, end, thread_count))
+}
+
+/// Gets the first and last slots of a cycle
+///
+/// # Arguments
+/// * `cycle`: the considered cycle
+/// * `periods_per_cycle`: number of periods in a cycle
+/// * `thread_count`: number of threads
+pub fn get_cycle_slot_bounds(
+    cycle: u64,
+    periods_per_cycle: u64,
+    thread_count: u8,
+) -> Result<(Slot, Slot), ModelsError> {
+    Ok((
+        Slot::new_first_of_cycle(cycle, periods_per_cycle)?,
+        Slot::new_last_of_cycle(cycle, periods_per_cycle, thread_count)?,
+    ))
+}
+
+/// Gets the time range [start, end) of a cycle: from the timestamp of its first slot
+/// to the timestamp of the first slot of the next cycle
+///
+/// # Arguments
+/// * `cycle`: the considered cycle
+/// * `periods_per_cycle`: number of periods in a cycle
+/// * `thread_count`: number of threads.
+/// * `t0`: time in milliseconds between two periods in the same thread.
+/// * `genesis_timestamp`: when the blockclique first started, in milliseconds
+pub fn get_cycle_time_range(
+    cycle: u64,
+    periods_per_cycle: u64,
+    thread_count: u8,
+    t0: MassaTime,
+    genesis_timestamp: MassaTime,
+) -> Result<(MassaTime, MassaTime), ModelsError> {
+    let next_cycle = cycle.checked_add(1).ok_or(ModelsError::TimeOverflowError)?;
+    Ok((
+        get_block_slot_timestamp(
+            thread_count,
+            t0,
+            genesis_timestamp,
+            Slot::new_first_of_cycle(cycle, periods_per_cycle)?,
+        )?,
+        get_block_slot_timestamp(
+            thread_count,
+            t0,
+            genesis_timestamp,
+            Slot::new_first_of_cycle(next_cycle, periods_per_cycle)?,
+        )?,
+    ))
+}
+
 /// TODO DOC
 pub fn get_closest_slot_to_timestamp(
     thread_count: u8,
@@ -296,4 +417,61 @@ mod tests {
             get_closest_slot_to_timestamp(thread_count, t0, genesis_timestamp, 150.into());
         assert_eq!(out_slot, Slot::new(1, 2));
     }
+
+    #[test]
+    #[serial]
+    fn test_slots_in_time_range() {
+        let thread_count = 3u8;
+        let t0: MassaTime = 30.into();
+        let genesis_timestamp: MassaTime = 100.into();
+        /* slots:   (0, 0)  (0, 1)  (0, 2)  (1, 0)  (1, 1)  (1, 2)  (2, 0)  (2, 1)  (2, 2)
+            time:    100      110     120    130      140    150     160     170     180
+        */
+
+        // time [115, 145) => slots (0,2), (1,0), (1,1)
+        let slots =
+            slots_in_time_range(thread_count, t0, genesis_timestamp, 115.into(), 145.into())
+                .unwrap();
+        assert_eq!(slots.size_hint(), (3, Some(3)));
+        assert_eq!(
+            slots.collect::<Vec<_>>(),
+            vec![Slot::new(0, 2), Slot::new(1, 0), Slot::new(1, 1)]
+        );
+
+        // time [10, 115) => slots from genesis
+        let slots: Vec<Slot> =
+            slots_in_time_range(thread_count, t0, genesis_timestamp, 10.into(), 115.into())
+                .unwrap()
+                .collect();
+        assert_eq!(slots, vec![Slot::new(0, 0), Slot::new(0, 1)]);
+
+        // timestamps of the slots, back and forth
+        for slot in slots_in_range(Slot::new(0, 0), Slot::new(3, 0), thread_count) {
+            let timestamp =
+                get_block_slot_timestamp(thread_count, t0, genesis_timestamp, slot).unwrap();
+            assert_eq!(
+                get_latest_block_slot_at_timestamp(thread_count, t0, genesis_timestamp, timestamp)
+                    .unwrap(),
+                Some(slot)
+            );
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_cycle_bounds() {
+        let thread_count = 3u8;
+        let t0: MassaTime = 30.into();
+        let genesis_timestamp: MassaTime = 100.into();
+        let periods_per_cycle = 4;
+
+        let (first, last) = get_cycle_slot_bounds(1, periods_per_cycle, thread_count).unwrap();
+        assert_eq!(first, Slot::new(4, 0));
+        assert_eq!(last, Slot::new(7, 2));
+        let (start, end) =
+            get_cycle_time_range(1, periods_per_cycle, thread_count, t0, genesis_timestamp)
+                .unwrap();
+        assert_eq!(start, 220.into());
+        assert_eq!(end, 340.into());
+    }
 }
@@ -1,7 +1,11 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use crate::config::BOOTSTRAP_MESSAGES_MINOR_VERSION;
 use crate::error::ModelsError;
-use massa_serialization::{Deserializer, Serializer, U32VarIntDeserializer, U32VarIntSerializer};
+use massa_serialization::{
+    Deserializer, Serializer, U32VarIntDeserializer, U32VarIntSerializer, U64VarIntDeserializer,
+    U64VarIntSerializer,
+};
 use nom::bytes::complete::take;
 use nom::error::context;
 use nom::sequence::tuple;
@@ -191,6 +195,23 @@ impl Version {
     pub fn is_compatible(&self, other: &Version) -> bool {
         self.instance == other.instance && self.major == other.major && other.minor >= 2
     }
+
+    /// true if the versions are compatible and both speak the bootstrap messages introduced
+    /// in the minor version `BOOTSTRAP_MESSAGES_MINOR_VERSION`
+    ///
+    /// ```rust
+    /// # use massa_models::*;
+    /// # use std::str::FromStr;
+    /// let v: version::Version = version::Version::from_str("TEST.20.3").unwrap();
+    /// assert!(v.is_bootstrap_compatible(&version::Version::from_str("TEST.20.3").unwrap()));
+    /// assert!(!v.is_bootstrap_compatible(&version::Version::from_str("TEST.20.2").unwrap()));
+    /// assert!(!version::Version::from_str("TEST.20.2").unwrap().is_bootstrap_compatible(&v));
+    /// ```
+    pub fn is_bootstrap_compatible(&self, other: &Version) -> bool {
+        self.is_compatible(other)
+            && self.minor >= BOOTSTRAP_MESSAGES_MINOR_VERSION
+            && other.minor >= BOOTSTRAP_MESSAGES_MINOR_VERSION
+    }
 }
 
 impl fmt::Display for Version {
@@ -240,3 +261,185 @@ impl FromStr for Version {
         })
     }
 }
+
+/// Optional features supported by a node, exchanged along with the `Version` during handshakes.
+///
+/// Two nodes with compatible versions only use the features present in both of their sets,
+/// so that new features can be rolled out without a breaking version change.
+/// Unknown flags are kept as is: they belong to features of more recent versions.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, Default, serde::Serialize, serde::Deserialize,
+)]
+#[serde(transparent)]
+pub struct Capabilities(u64);
+
+impl Capabilities {
+    /// Compression of the messages
+    pub const COMPRESSION: Capabilities = Capabilities(1 << 0);
+    /// Light bootstrap of a summary of the final state
+    pub const LIGHT_BOOTSTRAP: Capabilities = Capabilities(1 << 1);
+    /// Several endorsements sent in a single message
+    pub const BATCHED_ENDORSEMENTS: Capabilities = Capabilities(1 << 2);
+    /// Block bodies served as erasure-coded chunks
+    pub const ERASURE_CODED_BLOCKS: Capabilities = Capabilities(1 << 3);
+    /// Bootstrap of the state of the MIPs
+    pub const MIP_STORE: Capabilities = Capabilities(1 << 4);
+    /// Ping messages probing the health of the connections
+    pub const PING: Capabilities = Capabilities(1 << 5);
+    /// Handshake signatures covering the random bytes and the public keys of both nodes
+    pub const HANDSHAKE_TRANSCRIPT: Capabilities = Capabilities(1 << 6);
+    /// Pongs carrying the time of the answering node, to estimate the clock offsets
+    pub const CLOCK_SYNC: Capabilities = Capabilities(1 << 7);
+    /// Periodic gossip of the latest final slot, to detect the nodes lagging behind the network
+    pub const FINAL_SLOT: Capabilities = Capabilities(1 << 8);
+    /// Gossip of the BLS signatures of the endorsers, aggregated in the block headers
+    pub const ENDORSEMENT_SIGNATURES: Capabilities = Capabilities(1 << 9);
+
+    /// No capability
+    pub const fn empty() -> Self {
+        Capabilities(0)
+    }
+
+    /// Capabilities from their raw bit representation
+    pub const fn from_bits(bits: u64) -> Self {
+        Capabilities(bits)
+    }
+
+    /// Raw bit representation
+    pub const fn bits(&self) -> u64 {
+        self.0
+    }
+
+    /// Capabilities present in `self` or in `other`
+    pub const fn union(&self, other: Capabilities) -> Self {
+        Capabilities(self.0 | other.0)
+    }
+
+    /// Capabilities present in both `self` and `other`: the ones two nodes can use together
+    pub const fn intersection(&self, other: Capabilities) -> Self {
+        Capabilities(self.0 & other.0)
+    }
+
+    /// true if all the capabilities of `other` are in `self`
+    pub const fn contains(&self, other: Capabilities) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl fmt::Display for Capabilities {
+    /// ```rust
+    /// # use massa_models::version::Capabilities;
+    /// let capabilities = Capabilities::LIGHT_BOOTSTRAP.union(Capabilities::from_bits(1 << 10));
+    /// assert_eq!(capabilities.to_string(), "light_bootstrap, unknown(0x400)");
+    /// assert_eq!(Capabilities::empty().to_string(), "none");
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let known = [
+            (Capabilities::COMPRESSION, "compression"),
+            (Capabilities::LIGHT_BOOTSTRAP, "light_bootstrap"),
+            (Capabilities::BATCHED_ENDORSEMENTS, "batched_endorsements"),
+            (Capabilities::ERASURE_CODED_BLOCKS, "erasure_coded_blocks"),
+            (Capabilities::MIP_STORE, "mip_store"),
+            (Capabilities::PING, "ping"),
+            (Capabilities::HANDSHAKE_TRANSCRIPT, "handshake_transcript"),
+            (Capabilities::CLOCK_SYNC, "clock_sync"),
+            (Capabilities::FINAL_SLOT, "final_slot"),
+            (
+                Capabilities::ENDORSEMENT_SIGNATURES,
+                "endorsement_signatures",
+            ),
+        ];
+        let mut names: Vec<String> = known
+            .iter()
+            .filter(|(flag, _)| self.contains(*flag))
+            .map(|(_, name)| name.to_string())
+            .collect();
+        let unknown = known
+            .iter()
+            .fold(self.0, |bits, (flag, _)| bits & !flag.bits());
+        if unknown != 0 {
+            names.push(format!("unknown({:#x})", unknown));
+        }
+        if names.is_empty() {
+            write!(f, "none")
+        } else {
+            write!(f, "{}", names.join(", "))
+        }
+    }
+}
+
+/// Serializer for `Capabilities`
+pub struct CapabilitiesSerializer {
+    u64_serializer: U64VarIntSerializer,
+}
+
+impl CapabilitiesSerializer {
+    /// Creates a `CapabilitiesSerializer`
+    pub const fn new() -> Self {
+        Self {
+            u64_serializer: U64VarIntSerializer::new(),
+        }
+    }
+}
+
+impl Default for CapabilitiesSerializer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Serializer<Capabilities> for CapabilitiesSerializer {
+    fn serialize(
+        &self,
+        value: &Capabilities,
+        buffer: &mut Vec<u8>,
+    ) -> Result<(), massa_serialization::SerializeError> {
+        self.u64_serializer.serialize(&value.0, buffer)
+    }
+}
+
+/// Deserializer for `Capabilities`
+pub struct CapabilitiesDeserializer {
+    u64_deserializer: U64VarIntDeserializer,
+}
+
+impl CapabilitiesDeserializer {
+    /// Creates a `CapabilitiesDeserializer`
+    pub const fn new() -> Self {
+        Self {
+            u64_deserializer: U64VarIntDeserializer::new(Included(0), Included(u64::MAX)),
+        }
+    }
+}
+
+impl Default for CapabilitiesDeserializer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deserializer<Capabilities> for CapabilitiesDeserializer {
+    /// ```
+    /// use massa_serialization::{Serializer, Deserializer, DeserializeError};
+    /// use massa_models::version::{Capabilities, CapabilitiesSerializer, CapabilitiesDeserializer};
+    ///
+    /// let capabilities = Capabilities::LIGHT_BOOTSTRAP.union(Capabilities::BATCHED_ENDORSEMENTS);
+    /// let mut serialized = Vec::new();
+    /// CapabilitiesSerializer::new().serialize(&capabilities, &mut serialized).unwrap();
+    /// let (rest, deserialized) = CapabilitiesDeserializer::new()
+    ///     .deserialize::<DeserializeError>(&serialized)
+    ///     .unwrap();
+    /// assert!(rest.is_empty());
+    /// assert_eq!(capabilities, deserialized);
+    /// ```
+    fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], Capabilities, E> {
+        context("Failed Capabilities deserialization", |input| {
+            self.u64_deserializer.deserialize(input)
+        })
+        .map(Capabilities)
+        .parse(buffer)
+    }
+}
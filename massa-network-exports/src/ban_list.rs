@@ -0,0 +1,133 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use crate::NetworkError;
+use massa_time::MassaTime;
+use serde::{Deserialize, Serialize};
+use serde_with::{DeserializeFromStr, SerializeDisplay};
+use std::fmt::Display;
+use std::net::IpAddr;
+use std::str::FromStr;
+
+/// Range of IP addresses in CIDR notation, ex: `192.0.2.0/24`. A single address is a full-length range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SerializeDisplay, DeserializeFromStr)]
+pub struct IpSubnet {
+    /// first address of the range, in canonical form
+    addr: IpAddr,
+    /// number of leading bits shared by the addresses of the range
+    prefix_len: u8,
+}
+
+/// Keeps the `prefix_len` leading bits of a `bits`-long value
+fn mask(value: u128, bits: u8, prefix_len: u8) -> u128 {
+    let host_bits = (bits - prefix_len) as u32;
+    value & u128::MAX.checked_shl(host_bits).unwrap_or(0)
+}
+
+impl IpSubnet {
+    /// Range of the addresses sharing the `prefix_len` leading bits of `addr`
+    pub fn new(addr: IpAddr, prefix_len: u8) -> Result<Self, NetworkError> {
+        let addr = addr.to_canonical();
+        let addr = match addr {
+            IpAddr::V4(v4) if prefix_len <= 32 => {
+                IpAddr::V4((mask(u32::from(v4) as u128, 32, prefix_len) as u32).into())
+            }
+            IpAddr::V6(v6) if prefix_len <= 128 => {
+                IpAddr::V6(mask(u128::from(v6), 128, prefix_len).into())
+            }
+            _ => {
+                return Err(NetworkError::InvalidSubnet(format!(
+                    "prefix length {} is too long for {}",
+                    prefix_len, addr
+                )))
+            }
+        };
+        Ok(IpSubnet { addr, prefix_len })
+    }
+
+    /// Range made of `addr` only
+    pub fn host(addr: IpAddr) -> Self {
+        let addr = addr.to_canonical();
+        IpSubnet {
+            addr,
+            prefix_len: if addr.is_ipv4() { 32 } else { 128 },
+        }
+    }
+
+    /// Whether the range is made of a single address
+    pub fn is_host(&self) -> bool {
+        *self == IpSubnet::host(self.addr)
+    }
+
+    /// Whether `ip` is in the range
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        match (self.addr, ip.to_canonical()) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                mask(u32::from(ip) as u128, 32, self.prefix_len) == u32::from(net) as u128
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                mask(u128::from(ip), 128, self.prefix_len) == u128::from(net)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Display for IpSubnet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix_len)
+    }
+}
+
+impl FromStr for IpSubnet {
+    type Err = NetworkError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || NetworkError::InvalidSubnet(s.to_string());
+        match s.split_once('/') {
+            Some((addr, prefix_len)) => IpSubnet::new(
+                addr.parse().map_err(|_| invalid())?,
+                prefix_len.parse().map_err(|_| invalid())?,
+            ),
+            None => Ok(IpSubnet::host(s.parse().map_err(|_| invalid())?)),
+        }
+    }
+}
+
+/// Ban of a range of IP addresses, kept across restarts
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BanEntry {
+    /// banned addresses
+    pub subnet: IpSubnet,
+    /// reason given by the operator
+    pub reason: String,
+    /// time of the ban
+    pub created: MassaTime,
+    /// end of the ban, `None` if it is permanent
+    pub expiry: Option<MassaTime>,
+}
+
+impl BanEntry {
+    /// Whether the ban is over at `now`
+    pub fn is_expired(&self, now: MassaTime) -> bool {
+        self.expiry.map_or(false, |expiry| expiry <= now)
+    }
+}
+
+impl Display for BanEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} banned since {}",
+            self.subnet,
+            self.created.to_utc_string()
+        )?;
+        match self.expiry {
+            Some(expiry) => write!(f, " until {}", expiry.to_utc_string())?,
+            None => write!(f, " permanently")?,
+        }
+        if !self.reason.is_empty() {
+            write!(f, ": {}", self.reason)?;
+        }
+        Ok(())
+    }
+}
@@ -69,16 +69,23 @@
 //! Look at `massa-protocol-worker/src/node-info.rs` to look further how we
 //! remember which node know what.
 
-use crate::{BootstrapPeers, ConnectionClosureReason, Peers};
+use crate::{
+    BanEntry, BootstrapPeers, ConnectionClosureReason, IpSubnet, Peers, SignedNodeMetadata,
+};
+use massa_channel::Priority;
 use massa_models::{
     block_header::SecuredHeader,
     block_id::BlockId,
     composite::PubkeySig,
     endorsement::SecureShareEndorsement,
+    endorsement_signature::EndorsementSignature,
     node::NodeId,
     operation::{OperationId, OperationPrefixIds, SecureShareOperation},
+    slot::Slot,
     stats::NetworkStats,
+    version::Capabilities,
 };
+use massa_time::MassaTime;
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, net::IpAddr};
 use tokio::sync::oneshot;
@@ -107,6 +114,16 @@ pub enum NodeCommand {
     SendEndorsements(Vec<SecureShareEndorsement>),
     /// Ask peer list
     AskPeerList,
+    /// Send our signed metadata to node.
+    SendNodeMetadata(SignedNodeMetadata),
+    /// Probe the connection: the node answers with a pong carrying the same nonce.
+    SendPing(u64),
+    /// Answer the ping carrying the nonce.
+    SendPong(u64),
+    /// Send our latest final slot.
+    SendFinalSlot(Slot),
+    /// Send BLS signatures of endorsers.
+    SendEndorsementSignatures(Vec<EndorsementSignature>),
 }
 
 /// Event types that node worker can emit
@@ -133,6 +150,15 @@ pub enum NodeEventType {
     ReceivedAskForOperations(OperationPrefixIds),
     /// Receive a set of endorsement
     ReceivedEndorsements(Vec<SecureShareEndorsement>),
+    /// Node we are connected to sent its signed metadata
+    ReceivedNodeMetadata(SignedNodeMetadata),
+    /// Result of a probe of the connection: round-trip time, `None` if the node did not answer,
+    /// and offset of the clock of the node relative to ours in milliseconds, if the node sent its time
+    ProbeResult(Option<MassaTime>, Option<i64>),
+    /// Node we are connected to sent its latest final slot
+    ReceivedFinalSlot(Slot),
+    /// Node we are connected to sent BLS signatures of endorsers
+    ReceivedEndorsementSignatures(Vec<EndorsementSignature>),
 }
 
 /// Events node worker can emit.
@@ -150,6 +176,8 @@ pub enum AskForBlocksInfo {
     Info,
     /// The actual operations are required.
     Operations(Vec<OperationId>),
+    /// Erasure-coded chunks of the operations are required (chunk indices).
+    Chunks(Vec<u16>),
 }
 
 /// Commands that the worker can execute
@@ -187,6 +215,12 @@ pub enum NetworkCommand {
     NodeUnbanByIds(Vec<NodeId>),
     /// Unban a list of peer by their ip address
     NodeUnbanByIps(Vec<IpAddr>),
+    /// Ban a range of IP addresses
+    NodeBanSubnet(BanEntry),
+    /// Lift the bans of ranges of IP addresses
+    NodeUnbanSubnets(Vec<IpSubnet>),
+    /// Get the bans of IP ranges
+    GetBans(oneshot::Sender<Vec<BanEntry>>),
     /// Send endorsements to a node
     SendEndorsements {
         /// to node id
@@ -194,6 +228,13 @@ pub enum NetworkCommand {
         /// endorsements
         endorsements: Vec<SecureShareEndorsement>,
     },
+    /// Send BLS signatures of endorsers to a node supporting them
+    SendEndorsementSignatures {
+        /// to node id
+        node: NodeId,
+        /// signatures
+        signatures: Vec<EndorsementSignature>,
+    },
     /// sign message with our node keypair (associated to node id)
     /// != staking key
     NodeSignMessage {
@@ -234,6 +275,22 @@ pub enum NetworkCommand {
     RemoveFromWhitelist(Vec<IpAddr>),
 }
 
+impl NetworkCommand {
+    /// Priority of the command in the channel of the network worker:
+    /// the block exchanges and the bans go before the operations and endorsements
+    pub fn priority(&self) -> Priority {
+        match self {
+            NetworkCommand::AskForBlocks { .. }
+            | NetworkCommand::SendBlockInfo { .. }
+            | NetworkCommand::SendBlockHeader { .. }
+            | NetworkCommand::NodeBanByIds(_)
+            | NetworkCommand::NodeBanByIps(_)
+            | NetworkCommand::NodeBanSubnet(_) => Priority::High,
+            _ => Priority::Normal,
+        }
+    }
+}
+
 /// A node replied with info about a block.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[allow(clippy::large_enum_variant)]
@@ -244,10 +301,23 @@ pub enum BlockInfoReply {
     Info(Vec<OperationId>),
     /// The actual operations required.
     Operations(Vec<SecureShareOperation>),
+    /// Erasure-coded chunks of the serialized operations of the block
+    Chunks(Vec<BlockChunk>),
     /// Block not found
     NotFound,
 }
 
+/// Erasure-coded chunk of the serialized operations of a block
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockChunk {
+    /// index of the chunk
+    pub index: u16,
+    /// size of the serialized operations the chunk was computed from
+    pub total_size: u32,
+    /// content of the chunk
+    pub data: Vec<u8>,
+}
+
 /// network event
 #[allow(clippy::large_enum_variant)]
 #[derive(Debug)]
@@ -256,6 +326,13 @@ pub enum NetworkEvent {
     NewConnection(NodeId),
     /// connection to node was closed
     ConnectionClosed(NodeId),
+    /// optional features supported by both our node and a newly connected node
+    NodeCapabilities {
+        /// node id
+        node: NodeId,
+        /// capabilities negotiated during the handshake
+        capabilities: Capabilities,
+    },
     /// Info about a block was received
     ReceivedBlockInfo {
         /// from node id
@@ -305,6 +382,27 @@ pub enum NetworkEvent {
         /// Endorsements
         endorsements: Vec<SecureShareEndorsement>,
     },
+    /// received BLS signatures of endorsers from node
+    ReceivedEndorsementSignatures {
+        /// node id
+        node: NodeId,
+        /// signatures
+        signatures: Vec<EndorsementSignature>,
+    },
+}
+
+impl NetworkEvent {
+    /// Priority of the event in the channel of the protocol worker:
+    /// the block exchanges go before the operations and endorsements.
+    /// The connection events keep the normal priority so that they stay ordered with each other.
+    pub fn priority(&self) -> Priority {
+        match self {
+            NetworkEvent::ReceivedBlockInfo { .. }
+            | NetworkEvent::ReceivedBlockHeader { .. }
+            | NetworkEvent::AskedForBlocks { .. } => Priority::High,
+            _ => Priority::Normal,
+        }
+    }
 }
 
 /// Network management command
@@ -25,6 +25,8 @@ pub enum NetworkError {
     PeerConnectionError(#[from] NetworkConnectionErrorType),
     /// The ip:`{0}` address is not valid
     InvalidIpError(IpAddr),
+    /// invalid IP subnet: {0}
+    InvalidSubnet(String),
     /// Active connection missing:`{0}`
     ActiveConnectionMissing(ConnectionId),
     /// IO error : {0}
@@ -76,6 +78,25 @@ pub enum HandshakeErrorType {
     IncompatibleVersion,
     /// Outgoing connection returned a bootstrapable peer list: {0:?}
     PeerListReceived(Vec<IpAddr>),
+    /// The random bytes of the node were already used in another handshake
+    HandshakeReplay,
+}
+
+impl HandshakeErrorType {
+    /// Short name of the error, used to count the failed handshakes by reason
+    pub fn reason(&self) -> &'static str {
+        match self {
+            HandshakeErrorType::HandshakeIdAlreadyExist(_) => "id_already_exist",
+            HandshakeErrorType::HandshakeTimeout => "timeout",
+            HandshakeErrorType::HandshakeInterruption(_) => "interruption",
+            HandshakeErrorType::HandshakeWrongMessage => "wrong_message",
+            HandshakeErrorType::HandshakeKey => "own_key",
+            HandshakeErrorType::HandshakeInvalidSignature => "invalid_signature",
+            HandshakeErrorType::IncompatibleVersion => "incompatible_version",
+            HandshakeErrorType::PeerListReceived(_) => "peer_list_received",
+            HandshakeErrorType::HandshakeReplay => "replay",
+        }
+    }
 }
 
 /// return handshake error
@@ -107,6 +128,8 @@ pub enum NetworkConnectionErrorType {
     TooManyConnectionFailure(IpAddr),
     /// Max connected peers reached: {0}
     MaxPeersConnectionReached(IpAddr),
+    /// Max connected peers reached in the IP range of: {0}
+    MaxPeersPerSubnetReached(IpAddr),
     /// Attempt too connect from you own IP
     SelfConnection,
     /// A banned peer is trying to connect: {0}
@@ -5,25 +5,32 @@
 #![feature(ip)]
 
 pub use commands::{
-    AskForBlocksInfo, BlockInfoReply, NetworkCommand, NetworkEvent, NetworkManagementCommand,
-    NodeCommand, NodeEvent, NodeEventType,
+    AskForBlocksInfo, BlockChunk, BlockInfoReply, NetworkCommand, NetworkEvent,
+    NetworkManagementCommand, NodeCommand, NodeEvent, NodeEventType,
 };
 
+pub use ban_list::{BanEntry, IpSubnet};
 pub use common::{ConnectionClosureReason, ConnectionId};
 pub use error::{HandshakeErrorType, NetworkConnectionErrorType, NetworkError};
 pub use establisher::{Establisher, Listener, ReadHalf, WriteHalf};
 pub use network_controller::{NetworkCommandSender, NetworkEventReceiver, NetworkManager};
+pub use node_metadata::{
+    NodeMetadata, NodeMetadataDeserializer, NodeMetadataSerializer, SignedNodeMetadata,
+    SignedNodeMetadataDeserializer, SignedNodeMetadataSerializer,
+};
 pub use peers::{
     BootstrapPeers, BootstrapPeersDeserializer, BootstrapPeersSerializer, ConnectionCount, Peer,
-    PeerInfo, PeerType, Peers,
+    PeerDistribution, PeerInfo, PeerType, Peers, ProbeStats,
 };
 pub use settings::NetworkConfig;
 
+mod ban_list;
 mod commands;
 mod common;
 mod error;
 mod establisher;
 mod network_controller;
+mod node_metadata;
 mod peers;
 
 /// network settings
@@ -3,13 +3,15 @@
 use crate::{
     commands::{AskForBlocksInfo, NetworkManagementCommand},
     error::NetworkError,
-    BlockInfoReply, BootstrapPeers, NetworkCommand, NetworkEvent, Peers,
+    BanEntry, BlockInfoReply, BootstrapPeers, IpSubnet, NetworkCommand, NetworkEvent, Peers,
 };
+use massa_channel::{MassaReceiver, MassaSender, TrySendError};
 use massa_models::{
     block_header::SecuredHeader,
     block_id::BlockId,
     composite::PubkeySig,
     endorsement::SecureShareEndorsement,
+    endorsement_signature::EndorsementSignature,
     node::NodeId,
     operation::{OperationPrefixIds, SecureShareOperation},
     stats::NetworkStats,
@@ -19,17 +21,14 @@ use std::{
     net::IpAddr,
 };
 use tokio::{
-    sync::{
-        mpsc::{self, error::TrySendError},
-        oneshot,
-    },
+    sync::{mpsc, oneshot},
     task::JoinHandle,
 };
 use tracing::{info, warn};
 
 /// Network command sender
 #[derive(Clone)]
-pub struct NetworkCommandSender(pub mpsc::Sender<NetworkCommand>);
+pub struct NetworkCommandSender(pub MassaSender<NetworkCommand>);
 
 impl NetworkCommandSender {
     /// ban node(s) by id(s)
@@ -88,6 +87,38 @@ impl NetworkCommandSender {
         Ok(())
     }
 
+    /// ban a range of IP addresses
+    pub async fn node_ban_subnet(&self, entry: BanEntry) -> Result<(), NetworkError> {
+        self.0
+            .send(NetworkCommand::NodeBanSubnet(entry))
+            .await
+            .map_err(|_| NetworkError::ChannelError("could not send BanSubnet command".into()))?;
+        Ok(())
+    }
+
+    /// lift the bans of ranges of IP addresses
+    pub async fn node_unban_subnets(&self, subnets: Vec<IpSubnet>) -> Result<(), NetworkError> {
+        self.0
+            .send(NetworkCommand::NodeUnbanSubnets(subnets))
+            .await
+            .map_err(|_| {
+                NetworkError::ChannelError("could not send UnbanSubnets command".into())
+            })?;
+        Ok(())
+    }
+
+    /// get the bans of IP ranges
+    pub async fn get_bans(&self) -> Result<Vec<BanEntry>, NetworkError> {
+        let (response_tx, response_rx) = oneshot::channel();
+        self.0
+            .send(NetworkCommand::GetBans(response_tx))
+            .await
+            .map_err(|_| NetworkError::ChannelError("could not send GetBans command".into()))?;
+        response_rx.await.map_err(|_| {
+            NetworkError::ChannelError("could not get the bans from the network worker".into())
+        })
+    }
+
     /// Send info about the contents of a block.
     pub async fn send_block_info(
         &self,
@@ -253,6 +284,23 @@ impl NetworkCommandSender {
         Ok(())
     }
 
+    /// send BLS signatures of endorsers to node id
+    pub async fn send_endorsement_signatures(
+        &self,
+        node: NodeId,
+        signatures: Vec<EndorsementSignature>,
+    ) -> Result<(), NetworkError> {
+        self.0
+            .send(NetworkCommand::SendEndorsementSignatures { node, signatures })
+            .await
+            .map_err(|_| {
+                NetworkError::ChannelError(
+                    "could not send send_endorsement_signatures command".into(),
+                )
+            })?;
+        Ok(())
+    }
+
     /// Sign a message using the node's keypair
     pub async fn node_sign_message(&self, msg: Vec<u8>) -> Result<PubkeySig, NetworkError> {
         let (response_tx, response_rx) = oneshot::channel();
@@ -269,7 +317,7 @@ impl NetworkCommandSender {
 }
 
 /// network event receiver
-pub struct NetworkEventReceiver(pub mpsc::Receiver<NetworkEvent>);
+pub struct NetworkEventReceiver(pub MassaReceiver<NetworkEvent>);
 
 impl NetworkEventReceiver {
     /// wait network event
@@ -312,4 +360,14 @@ impl NetworkManager {
         info!("network manager stopped");
         Ok(())
     }
+
+    /// Stop network when the event receiver was lost with a crashed protocol worker.
+    /// The network worker stops sending events once the receiver is dropped.
+    pub async fn stop_without_events(self) -> Result<(), NetworkError> {
+        info!("stopping network manager without its event receiver...");
+        drop(self.manager_tx);
+        let _ = self.join_handle.await?;
+        info!("network manager stopped");
+        Ok(())
+    }
 }
@@ -0,0 +1,317 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Metadata a node can advertise about itself to its peers.
+//!
+//! The record is signed with the keypair of the node, so that it can be relayed to network maps
+//! and bootstrap server directories while still being attributable to the `NodeId` that issued it.
+
+use crate::NetworkError;
+use massa_hash::Hash;
+use massa_models::{
+    node::NodeId,
+    serialization::{StringDeserializer, StringSerializer},
+    version::{Version, VersionDeserializer, VersionSerializer},
+};
+use massa_serialization::{
+    Deserializer, OptionDeserializer, OptionSerializer, SerializeError, Serializer,
+    U16VarIntDeserializer, U16VarIntSerializer, U32VarIntDeserializer, U32VarIntSerializer,
+};
+use massa_signature::{
+    KeyPair, PublicKey, PublicKeyDeserializer, Signature, SignatureDeserializer,
+};
+use massa_time::{MassaTime, MassaTimeDeserializer, MassaTimeSerializer};
+use nom::{
+    error::{context, ContextError, ParseError},
+    multi::length_count,
+    sequence::tuple,
+    IResult, Parser,
+};
+use serde::{Deserialize, Serialize};
+use std::ops::Bound::Included;
+
+/// Information a node advertises about itself
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeMetadata {
+    /// contact of the node operator
+    pub operator_contact: Option<String>,
+    /// region where the node is hosted
+    pub region: Option<String>,
+    /// version of the node
+    pub version: Version,
+    /// services offered by the node (ex: "bootstrap", "public_api")
+    pub capabilities: Vec<String>,
+    /// time at which the record was signed
+    pub timestamp: MassaTime,
+}
+
+/// Node metadata signed by the node it describes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedNodeMetadata {
+    /// the advertised metadata
+    pub metadata: NodeMetadata,
+    /// public key of the node
+    pub public_key: PublicKey,
+    /// signature of the hash of the serialized metadata
+    pub signature: Signature,
+}
+
+impl SignedNodeMetadata {
+    /// Signs `metadata` with the keypair of the node
+    pub fn new(metadata: NodeMetadata, keypair: &KeyPair) -> Result<Self, NetworkError> {
+        let hash = Self::compute_hash(&metadata)?;
+        Ok(SignedNodeMetadata {
+            metadata,
+            public_key: keypair.get_public_key(),
+            signature: keypair.sign(&hash)?,
+        })
+    }
+
+    /// Id of the node that signed the record
+    pub fn node_id(&self) -> NodeId {
+        NodeId::new(self.public_key)
+    }
+
+    /// Checks that the record was signed by its public key
+    pub fn verify(&self) -> Result<(), NetworkError> {
+        let hash = Self::compute_hash(&self.metadata)?;
+        self.public_key.verify_signature(&hash, &self.signature)?;
+        Ok(())
+    }
+
+    fn compute_hash(metadata: &NodeMetadata) -> Result<Hash, NetworkError> {
+        let mut buffer = Vec::new();
+        NodeMetadataSerializer::new().serialize(metadata, &mut buffer)?;
+        Ok(Hash::compute_from(&buffer))
+    }
+}
+
+/// Serializer for `NodeMetadata`
+pub struct NodeMetadataSerializer {
+    option_string_serializer: OptionSerializer<String, StringSerializer<U16VarIntSerializer, u16>>,
+    string_serializer: StringSerializer<U16VarIntSerializer, u16>,
+    version_serializer: VersionSerializer,
+    u32_serializer: U32VarIntSerializer,
+    time_serializer: MassaTimeSerializer,
+}
+
+impl NodeMetadataSerializer {
+    /// Creates a new `NodeMetadataSerializer`
+    pub fn new() -> Self {
+        Self {
+            option_string_serializer: OptionSerializer::new(StringSerializer::new(
+                U16VarIntSerializer::new(),
+            )),
+            string_serializer: StringSerializer::new(U16VarIntSerializer::new()),
+            version_serializer: VersionSerializer::new(),
+            u32_serializer: U32VarIntSerializer::new(),
+            time_serializer: MassaTimeSerializer::new(),
+        }
+    }
+}
+
+impl Default for NodeMetadataSerializer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Serializer<NodeMetadata> for NodeMetadataSerializer {
+    fn serialize(&self, value: &NodeMetadata, buffer: &mut Vec<u8>) -> Result<(), SerializeError> {
+        self.option_string_serializer
+            .serialize(&value.operator_contact, buffer)?;
+        self.option_string_serializer
+            .serialize(&value.region, buffer)?;
+        self.version_serializer.serialize(&value.version, buffer)?;
+        let capabilities_count: u32 = value.capabilities.len().try_into().map_err(|err| {
+            SerializeError::NumberTooBig(format!("too many capabilities: {}", err))
+        })?;
+        self.u32_serializer.serialize(&capabilities_count, buffer)?;
+        for capability in &value.capabilities {
+            self.string_serializer.serialize(capability, buffer)?;
+        }
+        self.time_serializer.serialize(&value.timestamp, buffer)?;
+        Ok(())
+    }
+}
+
+/// Deserializer for `NodeMetadata`
+pub struct NodeMetadataDeserializer {
+    option_string_deserializer:
+        OptionDeserializer<String, StringDeserializer<U16VarIntDeserializer, u16>>,
+    string_deserializer: StringDeserializer<U16VarIntDeserializer, u16>,
+    version_deserializer: VersionDeserializer,
+    capabilities_count_deserializer: U32VarIntDeserializer,
+    time_deserializer: MassaTimeDeserializer,
+}
+
+impl NodeMetadataDeserializer {
+    /// Creates a new `NodeMetadataDeserializer`
+    ///
+    /// # Arguments
+    /// * `max_string_length`: maximum length of the contact, region and capability strings
+    /// * `max_capabilities`: maximum number of capabilities
+    pub fn new(max_string_length: u16, max_capabilities: u32) -> Self {
+        Self {
+            option_string_deserializer: OptionDeserializer::new(StringDeserializer::new(
+                U16VarIntDeserializer::new(Included(0), Included(max_string_length)),
+            )),
+            string_deserializer: StringDeserializer::new(U16VarIntDeserializer::new(
+                Included(0),
+                Included(max_string_length),
+            )),
+            version_deserializer: VersionDeserializer::new(),
+            capabilities_count_deserializer: U32VarIntDeserializer::new(
+                Included(0),
+                Included(max_capabilities),
+            ),
+            time_deserializer: MassaTimeDeserializer::new((
+                Included(MassaTime::from_millis(0)),
+                Included(MassaTime::from_millis(u64::MAX)),
+            )),
+        }
+    }
+}
+
+impl Deserializer<NodeMetadata> for NodeMetadataDeserializer {
+    /// ```
+    /// use massa_network_exports::{NodeMetadata, NodeMetadataDeserializer, NodeMetadataSerializer};
+    /// use massa_serialization::{Deserializer, DeserializeError, Serializer};
+    /// use massa_models::version::Version;
+    /// use massa_time::MassaTime;
+    /// use std::str::FromStr;
+    ///
+    /// let metadata = NodeMetadata {
+    ///     operator_contact: Some("ops@example.com".to_string()),
+    ///     region: None,
+    ///     version: Version::from_str("TEST.1.10").unwrap(),
+    ///     capabilities: vec!["bootstrap".to_string()],
+    ///     timestamp: MassaTime::from_millis(1000),
+    /// };
+    /// let mut buffer = Vec::new();
+    /// NodeMetadataSerializer::new().serialize(&metadata, &mut buffer).unwrap();
+    /// let (rest, deserialized) = NodeMetadataDeserializer::new(256, 32)
+    ///     .deserialize::<DeserializeError>(&buffer)
+    ///     .unwrap();
+    /// assert!(rest.is_empty());
+    /// assert_eq!(metadata, deserialized);
+    /// ```
+    fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], NodeMetadata, E> {
+        context(
+            "Failed NodeMetadata deserialization",
+            tuple((
+                context("Failed operator_contact deserialization", |input| {
+                    self.option_string_deserializer.deserialize(input)
+                }),
+                context("Failed region deserialization", |input| {
+                    self.option_string_deserializer.deserialize(input)
+                }),
+                context("Failed version deserialization", |input| {
+                    self.version_deserializer.deserialize(input)
+                }),
+                context(
+                    "Failed capabilities deserialization",
+                    length_count(
+                        context("Failed length deserialization", |input| {
+                            self.capabilities_count_deserializer.deserialize(input)
+                        }),
+                        context("Failed capability deserialization", |input| {
+                            self.string_deserializer.deserialize(input)
+                        }),
+                    ),
+                ),
+                context("Failed timestamp deserialization", |input| {
+                    self.time_deserializer.deserialize(input)
+                }),
+            )),
+        )
+        .map(
+            |(operator_contact, region, version, capabilities, timestamp)| NodeMetadata {
+                operator_contact,
+                region,
+                version,
+                capabilities,
+                timestamp,
+            },
+        )
+        .parse(buffer)
+    }
+}
+
+/// Serializer for `SignedNodeMetadata`
+#[derive(Default)]
+pub struct SignedNodeMetadataSerializer {
+    metadata_serializer: NodeMetadataSerializer,
+}
+
+impl SignedNodeMetadataSerializer {
+    /// Creates a new `SignedNodeMetadataSerializer`
+    pub fn new() -> Self {
+        Self {
+            metadata_serializer: NodeMetadataSerializer::new(),
+        }
+    }
+}
+
+impl Serializer<SignedNodeMetadata> for SignedNodeMetadataSerializer {
+    fn serialize(
+        &self,
+        value: &SignedNodeMetadata,
+        buffer: &mut Vec<u8>,
+    ) -> Result<(), SerializeError> {
+        self.metadata_serializer
+            .serialize(&value.metadata, buffer)?;
+        buffer.extend(value.public_key.to_bytes());
+        buffer.extend(value.signature.to_bytes());
+        Ok(())
+    }
+}
+
+/// Deserializer for `SignedNodeMetadata`
+pub struct SignedNodeMetadataDeserializer {
+    metadata_deserializer: NodeMetadataDeserializer,
+    public_key_deserializer: PublicKeyDeserializer,
+    signature_deserializer: SignatureDeserializer,
+}
+
+impl SignedNodeMetadataDeserializer {
+    /// Creates a new `SignedNodeMetadataDeserializer`, see `NodeMetadataDeserializer::new` for the arguments
+    pub fn new(max_string_length: u16, max_capabilities: u32) -> Self {
+        Self {
+            metadata_deserializer: NodeMetadataDeserializer::new(
+                max_string_length,
+                max_capabilities,
+            ),
+            public_key_deserializer: PublicKeyDeserializer::new(),
+            signature_deserializer: SignatureDeserializer::new(),
+        }
+    }
+}
+
+impl Deserializer<SignedNodeMetadata> for SignedNodeMetadataDeserializer {
+    fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], SignedNodeMetadata, E> {
+        context(
+            "Failed SignedNodeMetadata deserialization",
+            tuple((
+                |input| self.metadata_deserializer.deserialize(input),
+                context("Failed public_key deserialization", |input| {
+                    self.public_key_deserializer.deserialize(input)
+                }),
+                context("Failed signature deserialization", |input| {
+                    self.signature_deserializer.deserialize(input)
+                }),
+            )),
+        )
+        .map(|(metadata, public_key, signature)| SignedNodeMetadata {
+            metadata,
+            public_key,
+            signature,
+        })
+        .parse(buffer)
+    }
+}
@@ -1,8 +1,11 @@
 use crate::settings::PeerTypeConnectionConfig;
+use crate::{IpSubnet, SignedNodeMetadata};
 use displaydoc::Display;
 use enum_map::Enum;
 use massa_models::node::NodeId;
 use massa_models::serialization::{IpAddrDeserializer, IpAddrSerializer};
+use massa_models::slot::Slot;
+use massa_models::version::Capabilities;
 use massa_serialization::{
     Deserializer, SerializeError, Serializer, U32VarIntDeserializer, U32VarIntSerializer,
 };
@@ -12,7 +15,10 @@ use nom::multi::length_count;
 use nom::{IResult, Parser};
 use serde::{Deserialize, Serialize};
 use std::ops::Bound::Included;
-use std::{collections::HashMap, net::IpAddr};
+use std::{
+    collections::{BTreeMap, HashMap},
+    net::IpAddr,
+};
 /// Associate a peer info with nodes
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Peer {
@@ -29,6 +35,121 @@ pub struct Peers {
     pub our_node_id: NodeId,
     /// peers
     pub peers: HashMap<IpAddr, Peer>,
+    /// verified metadata advertised by the connected nodes that published one
+    pub node_metadata: HashMap<NodeId, SignedNodeMetadata>,
+    /// optional features supported by both us and each connected node
+    pub node_capabilities: HashMap<NodeId, Capabilities>,
+    /// distribution of the active connections across IP ranges and regions
+    pub distribution: PeerDistribution,
+    /// health probes of the connected nodes that support them
+    pub node_probes: HashMap<NodeId, ProbeStats>,
+    /// median offset of the clocks of the connected nodes relative to ours in milliseconds, if known
+    pub clock_offset: Option<i64>,
+    /// latest final slots gossiped by the connected nodes that support it
+    pub node_final_slots: HashMap<NodeId, Slot>,
+    /// number of slots by which our final slot lags behind the median of the ones of the connected nodes, if known
+    pub sync_lag: Option<u64>,
+}
+
+/// Health probes of the connection to a node
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProbeStats {
+    /// round-trip time of the latest answered probe
+    pub last_rtt: Option<MassaTime>,
+    /// moving average of the round-trip times
+    pub average_rtt: Option<MassaTime>,
+    /// number of probes sent
+    pub sent: u64,
+    /// number of probes not answered before the next one
+    pub lost: u64,
+    /// number of probes not answered since the latest answered one
+    pub consecutive_losses: u32,
+    /// the node stopped answering: a replacement connection is opened
+    pub degraded: bool,
+    /// moving average of the offset of the clock of the node relative to ours, in milliseconds
+    #[serde(default)]
+    pub clock_offset: Option<i64>,
+}
+
+impl ProbeStats {
+    /// Records the result of a probe: its round-trip time, `None` if it was lost, and the offset of the clock of the node if measured.
+    /// The node is degraded after `max_losses` consecutive losses, and healthy again after an answer.
+    pub fn record(&mut self, rtt: Option<MassaTime>, clock_offset: Option<i64>, max_losses: u32) {
+        self.sent += 1;
+        match rtt {
+            Some(rtt) => {
+                self.last_rtt = Some(rtt);
+                // weight of 1/8 for the new sample, as for the TCP smoothed round-trip time
+                self.average_rtt = Some(match self.average_rtt {
+                    Some(average) => {
+                        MassaTime::from_millis((average.to_millis() * 7 + rtt.to_millis()) / 8)
+                    }
+                    None => rtt,
+                });
+                if let Some(offset) = clock_offset {
+                    self.clock_offset = Some(match self.clock_offset {
+                        Some(average) => (average * 7 + offset) / 8,
+                        None => offset,
+                    });
+                }
+                self.consecutive_losses = 0;
+                self.degraded = false;
+            }
+            None => {
+                self.lost += 1;
+                self.consecutive_losses += 1;
+                self.degraded = self.consecutive_losses >= max_losses;
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for ProbeStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let rtt = |rtt: Option<MassaTime>| {
+            rtt.map_or("-".to_string(), |rtt| format!("{} ms", rtt.to_millis()))
+        };
+        write!(
+            f,
+            "RTT: {} (average {}) / {} probe(s) lost out of {}{}{}",
+            rtt(self.last_rtt),
+            rtt(self.average_rtt),
+            self.lost,
+            self.sent,
+            self.clock_offset.map_or(String::new(), |offset| format!(
+                " / clock offset {} ms",
+                offset
+            )),
+            if self.degraded { " / degraded" } else { "" }
+        )
+    }
+}
+
+/// Distribution of the active connections, in and out, across IP ranges and regions
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeerDistribution {
+    /// connection count of each IP range, the most connected first
+    pub subnets: Vec<(IpSubnet, usize)>,
+    /// connection count of each region advertised in the metadata of the peers
+    pub regions: BTreeMap<String, usize>,
+    /// connections to peers whose region is unknown
+    pub unknown_region: usize,
+}
+
+impl std::fmt::Display for PeerDistribution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Peer distribution:")?;
+        for (subnet, count) in &self.subnets {
+            writeln!(f, "\t{}: {} connection(s)", subnet, count)?;
+        }
+        for (region, count) in &self.regions {
+            writeln!(f, "\tRegion {}: {} connection(s)", region, count)?;
+        }
+        if self.unknown_region > 0 {
+            writeln!(f, "\tUnknown region: {} connection(s)", self.unknown_region)?;
+        }
+        Ok(())
+    }
 }
 
 /// Peers that are transmitted during bootstrap
@@ -281,6 +402,9 @@ pub struct ConnectionCount {
     pub active_out_connections: usize,
     /// Number of currently live (TCP connection active, handshake successful) incoming connections
     pub active_in_connections: usize,
+    /// Number of live outgoing connections whose node stopped answering the probes.
+    /// They are not counted in the target, so that replacements are opened before they are closed.
+    pub degraded_out_connections: usize,
 }
 
 impl ConnectionCount {
@@ -290,9 +414,15 @@ impl ConnectionCount {
         std::cmp::min(
             cfg.target_out_connections
                 .saturating_sub(self.active_out_connection_attempts)
-                .saturating_sub(self.active_out_connections),
+                .saturating_sub(self.get_healthy_out_connections()),
             cfg.max_out_attempts
                 .saturating_sub(self.active_out_connection_attempts),
         )
     }
+
+    /// Number of live outgoing connections that are not degraded
+    pub fn get_healthy_out_connections(&self) -> usize {
+        self.active_out_connections
+            .saturating_sub(self.degraded_out_connections)
+    }
 }
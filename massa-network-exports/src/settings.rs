@@ -1,6 +1,7 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 use enum_map::EnumMap;
+use massa_channel::ChannelConfig;
 use massa_time::MassaTime;
 use serde::Deserialize;
 use std::net::{IpAddr, SocketAddr};
@@ -14,11 +15,18 @@ pub struct NetworkConfig {
     pub bind: SocketAddr,
     /// Our own IP if it is routable, else None.
     pub routable_ip: Option<IpAddr>,
+    /// Accept peers with non-global IPs (ex: loopback), for local networks such as the devnet
+    pub allow_local_peers: bool,
     /// Protocol port
     pub protocol_port: u16,
     /// Time interval spent waiting for a response from a peer.
     /// In milliseconds
     pub connect_timeout: MassaTime,
+    /// Each phase of a handshake (initiation, reply) must complete within `handshake_phase_timeout` milliseconds,
+    /// and the whole handshake within `connect_timeout`.
+    pub handshake_phase_timeout: MassaTime,
+    /// Maximum number of inbound handshakes running at the same time. Beyond, the new inbound connections are dropped.
+    pub max_in_handshakes: usize,
     /// `Network_worker` will try to connect to available peers every `wakeup_interval`.
     /// In milliseconds
     pub wakeup_interval: MassaTime,
@@ -26,10 +34,14 @@ pub struct NetworkConfig {
     pub initial_peers_file: std::path::PathBuf,
     /// Path to the file containing known peers.
     pub peers_file: std::path::PathBuf,
+    /// Path to the file containing the bans of IP ranges set by the operator.
+    pub ban_list_file: std::path::PathBuf,
     /// Path to the file containing our keypair
     pub keypair_file: std::path::PathBuf,
     /// Configuration for `PeerType` connections
     pub peer_types_config: EnumMap<PeerType, PeerTypeConnectionConfig>,
+    /// Diversity of the standard peers we are connected to
+    pub peer_diversity: PeerDiversityConfig,
     /// Limit on the number of in connections per ip.
     pub max_in_connections_per_ip: usize,
     /// Limit on the number of idle peers we remember.
@@ -42,6 +54,14 @@ pub struct NetworkConfig {
     pub message_timeout: MassaTime,
     /// Every `ask_peer_list_interval` in milliseconds we ask every one for its advertisable peers list.
     pub ask_peer_list_interval: MassaTime,
+    /// Every `probe_interval` in milliseconds we ping the nodes supporting it, 0 to disable the probes.
+    /// A ping is lost if it is not answered before the next one.
+    pub probe_interval: MassaTime,
+    /// Number of consecutive lost pings after which a node is degraded and replaced.
+    /// Its connection is closed after twice as many.
+    pub probe_max_losses: u32,
+    /// Every `final_slot_gossip_interval` in milliseconds we send our latest final slot to the nodes supporting it, 0 to disable the gossip.
+    pub final_slot_gossip_interval: MassaTime,
     /// Max wait time for sending a Node event.
     pub max_send_wait_node_event: MassaTime,
     /// Max wait time for sending a Network event.
@@ -58,6 +78,14 @@ pub struct NetworkConfig {
     pub max_bytes_read: f64,
     /// Write limitation for a connection in bytes per seconds
     pub max_bytes_write: f64,
+    /// Send our signed metadata to the nodes we connect to
+    pub publish_node_metadata: bool,
+    /// Contact of the node operator advertised in our metadata
+    pub operator_contact: Option<String>,
+    /// Region where the node is hosted advertised in our metadata
+    pub region: Option<String>,
+    /// Services offered by the node advertised in our metadata
+    pub node_capabilities: Vec<String>,
     /// Max number ids in ask blocks message
     pub max_ask_blocks: u32,
     /// Max operations per block
@@ -84,16 +112,23 @@ pub struct NetworkConfig {
     pub max_function_name_length: u16,
     /// Maximum size of parameters in call SC
     pub max_parameters_size: u32,
-    /// Controller channel size
-    pub controller_channel_size: usize,
-    /// Event channel size
-    pub event_channel_size: usize,
+    /// Channel of the commands sent to the network worker
+    pub command_channel: ChannelConfig,
+    /// Channel of the events sent by the network worker
+    pub event_channel: ChannelConfig,
     /// Node command channel size
     pub node_command_channel_size: usize,
     /// Node event channel size
     pub node_event_channel_size: usize,
 }
 
+impl NetworkConfig {
+    /// Whether `ip` can be the IP of a peer: only global IPs unless `allow_local_peers` is set
+    pub fn is_peer_ip(&self, ip: &IpAddr) -> bool {
+        self.allow_local_peers || ip.is_global()
+    }
+}
+
 /// Connection configuration for a peer type
 /// Limit the current connections for a given peer type as a whole
 #[derive(Debug, Deserialize, Clone, Default)]
@@ -106,12 +141,29 @@ pub struct PeerTypeConnectionConfig {
     pub max_out_attempts: usize,
 }
 
+/// Diversity of the connections to standard peers, against eclipse attacks:
+/// an attacker controlling many addresses usually gets them in a few ranges.
+/// Bootstrap and whitelisted peers, chosen by the operator, are not limited.
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct PeerDiversityConfig {
+    /// max number of connections (in, out and attempts) to the peers of a same IP range, 0 for no limit
+    pub max_peers_per_subnet: usize,
+    /// length of the prefix of the IPv4 ranges, 16 for ranges like `192.0.0.0/16`
+    pub ipv4_subnet_prefix_len: u8,
+    /// length of the prefix of the IPv6 ranges
+    pub ipv6_subnet_prefix_len: u8,
+    /// connect first to the peers hosted in regions we are not connected to yet,
+    /// as advertised in the metadata of the peers we already met
+    pub prefer_distinct_regions: bool,
+}
+
 /// setting tests
 #[cfg(feature = "testing")]
 pub mod tests {
     use crate::NetworkConfig;
     use crate::{test_exports::tools::get_temp_keypair_file, PeerType};
     use enum_map::enum_map;
+    use massa_channel::ChannelConfig;
     use massa_models::config::{
         ENDORSEMENT_COUNT, MAX_ADVERTISE_LENGTH, MAX_ASK_BLOCKS_PER_MESSAGE,
         MAX_DATASTORE_VALUE_LENGTH, MAX_ENDORSEMENTS_PER_MESSAGE, MAX_FUNCTION_NAME_LENGTH,
@@ -123,7 +175,7 @@ pub mod tests {
     use massa_time::MassaTime;
     use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 
-    use super::PeerTypeConnectionConfig;
+    use super::{PeerDiversityConfig, PeerTypeConnectionConfig};
 
     impl Default for NetworkConfig {
         fn default() -> Self {
@@ -147,16 +199,23 @@ pub mod tests {
             NetworkConfig {
                 bind: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8080),
                 routable_ip: Some(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1))),
+                allow_local_peers: false,
                 protocol_port: 0,
                 connect_timeout: MassaTime::from_millis(180_000),
+                handshake_phase_timeout: MassaTime::from_millis(180_000),
+                max_in_handshakes: 100,
                 wakeup_interval: MassaTime::from_millis(10_000),
                 peers_file: std::path::PathBuf::new(),
+                ban_list_file: std::path::PathBuf::new(),
                 max_in_connections_per_ip: 2,
                 max_idle_peers: 3,
                 max_banned_peers: 3,
                 peers_file_dump_interval: MassaTime::from_millis(10_000),
                 message_timeout: MassaTime::from_millis(5000u64),
                 ask_peer_list_interval: MassaTime::from_millis(50000u64),
+                probe_interval: MassaTime::from_millis(0),
+                probe_max_losses: 3,
+                final_slot_gossip_interval: MassaTime::from_millis(0),
                 keypair_file: std::path::PathBuf::new(),
                 max_send_wait_node_event: MassaTime::from_millis(100),
                 max_send_wait_network_event: MassaTime::from_millis(100),
@@ -165,9 +224,19 @@ pub mod tests {
                 peer_list_send_timeout: MassaTime::from_millis(500),
                 max_in_connection_overflow: 2,
                 peer_types_config,
+                peer_diversity: PeerDiversityConfig {
+                    max_peers_per_subnet: 0,
+                    ipv4_subnet_prefix_len: 16,
+                    ipv6_subnet_prefix_len: 32,
+                    prefer_distinct_regions: false,
+                },
                 max_operations_per_message: MAX_OPERATIONS_PER_MESSAGE,
                 max_bytes_read: std::f64::INFINITY,
                 max_bytes_write: std::f64::INFINITY,
+                publish_node_metadata: false,
+                operator_contact: None,
+                region: None,
+                node_capabilities: Vec::new(),
                 max_ask_blocks: MAX_ASK_BLOCKS_PER_MESSAGE,
                 endorsement_count: ENDORSEMENT_COUNT,
                 max_endorsements_per_message: MAX_ENDORSEMENTS_PER_MESSAGE,
@@ -181,8 +250,8 @@ pub mod tests {
                 max_op_datastore_value_length: MAX_OPERATION_DATASTORE_VALUE_LENGTH,
                 max_function_name_length: MAX_FUNCTION_NAME_LENGTH,
                 max_parameters_size: MAX_PARAMETERS_SIZE,
-                controller_channel_size: NETWORK_CONTROLLER_CHANNEL_SIZE,
-                event_channel_size: NETWORK_EVENT_CHANNEL_SIZE,
+                command_channel: ChannelConfig::new(NETWORK_CONTROLLER_CHANNEL_SIZE),
+                event_channel: ChannelConfig::new(NETWORK_EVENT_CHANNEL_SIZE),
                 node_command_channel_size: NETWORK_NODE_COMMAND_CHANNEL_SIZE,
                 node_event_channel_size: NETWORK_NODE_EVENT_CHANNEL_SIZE,
             }
@@ -214,9 +283,13 @@ pub mod tests {
             Self {
                 bind,
                 routable_ip,
+                allow_local_peers: false,
                 protocol_port: port,
                 connect_timeout: MassaTime::from_millis(3000),
+                handshake_phase_timeout: MassaTime::from_millis(3000),
+                max_in_handshakes: 100,
                 peers_file: peers_file.to_path_buf(),
+                ban_list_file: std::path::PathBuf::new(),
                 wakeup_interval: MassaTime::from_millis(3000),
                 max_in_connections_per_ip: 100,
                 max_idle_peers: 100,
@@ -224,6 +297,9 @@ pub mod tests {
                 peers_file_dump_interval: MassaTime::from_millis(30000),
                 message_timeout: MassaTime::from_millis(5000u64),
                 ask_peer_list_interval: MassaTime::from_millis(50000u64),
+                probe_interval: MassaTime::from_millis(0),
+                probe_max_losses: 3,
+                final_slot_gossip_interval: MassaTime::from_millis(0),
                 keypair_file: get_temp_keypair_file().path().to_path_buf(),
                 max_send_wait_node_event: MassaTime::from_millis(100),
                 max_send_wait_network_event: MassaTime::from_millis(100),
@@ -232,9 +308,19 @@ pub mod tests {
                 peer_list_send_timeout: MassaTime::from_millis(50),
                 max_in_connection_overflow: 10,
                 peer_types_config,
+                peer_diversity: PeerDiversityConfig {
+                    max_peers_per_subnet: 0,
+                    ipv4_subnet_prefix_len: 16,
+                    ipv6_subnet_prefix_len: 32,
+                    prefer_distinct_regions: false,
+                },
                 max_operations_per_message: MAX_OPERATIONS_PER_MESSAGE,
                 max_bytes_read: std::f64::INFINITY,
                 max_bytes_write: std::f64::INFINITY,
+                publish_node_metadata: false,
+                operator_contact: None,
+                region: None,
+                node_capabilities: Vec::new(),
                 max_ask_blocks: 10,
                 endorsement_count: 8,
                 max_endorsements_per_message: MAX_ENDORSEMENTS_PER_MESSAGE,
@@ -248,8 +334,8 @@ pub mod tests {
                 max_op_datastore_value_length: MAX_OPERATION_DATASTORE_VALUE_LENGTH,
                 max_function_name_length: MAX_FUNCTION_NAME_LENGTH,
                 max_parameters_size: MAX_PARAMETERS_SIZE,
-                controller_channel_size: NETWORK_CONTROLLER_CHANNEL_SIZE,
-                event_channel_size: NETWORK_EVENT_CHANNEL_SIZE,
+                command_channel: ChannelConfig::new(NETWORK_CONTROLLER_CHANNEL_SIZE),
+                event_channel: ChannelConfig::new(NETWORK_EVENT_CHANNEL_SIZE),
                 node_command_channel_size: NETWORK_NODE_COMMAND_CHANNEL_SIZE,
                 node_event_channel_size: NETWORK_NODE_EVENT_CHANNEL_SIZE,
             }
@@ -0,0 +1,113 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Bans of IP ranges set by the node operator.
+//!
+//! Unlike the bans of misbehaving peers, forgotten after `ban_timeout`, they last until they expire or are lifted,
+//! and are saved in the ban list file at each change to be kept across restarts.
+
+use massa_network_exports::{BanEntry, IpSubnet};
+use massa_time::MassaTime;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// Bans of IP ranges, saved in a file
+#[derive(Debug, Default)]
+pub(crate) struct BanList {
+    /// file in which the bans are saved
+    path: PathBuf,
+    /// bans by range
+    entries: HashMap<IpSubnet, BanEntry>,
+}
+
+impl BanList {
+    /// Loads the bans saved in `path`, if any. The expired bans are dropped.
+    pub fn load(path: &Path) -> Self {
+        let mut ban_list = BanList {
+            path: path.to_path_buf(),
+            entries: HashMap::new(),
+        };
+        if !path.is_file() {
+            return ban_list;
+        }
+        match std::fs::read(path)
+            .map_err(|err| err.to_string())
+            .and_then(|bytes| {
+                serde_json::from_slice::<Vec<BanEntry>>(&bytes).map_err(|err| err.to_string())
+            }) {
+            Ok(entries) => {
+                ban_list.entries = entries
+                    .into_iter()
+                    .map(|entry| (entry.subnet, entry))
+                    .collect();
+                ban_list.remove_expired();
+            }
+            Err(err) => warn!("could not load the ban list {}: {}", path.display(), err),
+        }
+        ban_list
+    }
+
+    /// Adds or replaces the ban of `entry.subnet`
+    pub fn ban(&mut self, entry: BanEntry) {
+        self.entries.insert(entry.subnet, entry);
+        self.save();
+    }
+
+    /// Lifts the bans of `subnets`, returns whether one of them was banned
+    pub fn unban(&mut self, subnets: &[IpSubnet]) -> bool {
+        let count = self.entries.len();
+        for subnet in subnets {
+            self.entries.remove(subnet);
+        }
+        let changed = self.entries.len() != count;
+        if changed {
+            self.save();
+        }
+        changed
+    }
+
+    /// Whether `ip` is in a banned range
+    pub fn is_banned(&self, ip: &IpAddr) -> bool {
+        let now = MassaTime::now().unwrap_or_else(|_| MassaTime::from_millis(0));
+        self.entries
+            .values()
+            .any(|entry| !entry.is_expired(now) && entry.subnet.contains(ip))
+    }
+
+    /// Current bans, sorted by time of ban
+    pub fn entries(&mut self) -> Vec<BanEntry> {
+        self.remove_expired();
+        let mut entries: Vec<BanEntry> = self.entries.values().cloned().collect();
+        entries.sort_unstable_by_key(|entry| (entry.created, entry.subnet.to_string()));
+        entries
+    }
+
+    /// Drops the expired bans
+    fn remove_expired(&mut self) {
+        let now = MassaTime::now().unwrap_or_else(|_| MassaTime::from_millis(0));
+        let count = self.entries.len();
+        self.entries.retain(|_, entry| !entry.is_expired(now));
+        if self.entries.len() != count {
+            self.save();
+        }
+    }
+
+    /// Writes the bans in the ban list file
+    fn save(&self) {
+        if self.path.as_os_str().is_empty() {
+            return;
+        }
+        let entries: Vec<&BanEntry> = self.entries.values().collect();
+        let result = serde_json::to_string_pretty(&entries)
+            .map_err(|err| err.to_string())
+            .and_then(|json| std::fs::write(&self.path, json).map_err(|err| err.to_string()));
+        if let Err(err) = result {
+            warn!(
+                "could not save the ban list {}: {}",
+                self.path.display(),
+                err
+            );
+        }
+    }
+}
@@ -0,0 +1,206 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Fault injection on peer connections, for tests only.
+//!
+//! When the `chaos` feature is enabled, every message sent to or received from a peer goes through
+//! the process-wide [`ChaosController`], which can drop, delay, duplicate or reorder it
+//! according to the rules set for that peer. Without rules, messages go through untouched.
+//!
+//! Decisions are drawn from a seeded random generator (see [`ChaosController::reseed`]).
+
+use crate::messages::Message;
+use massa_models::node::NodeId;
+use massa_time::MassaTime;
+use rand::{prelude::StdRng, Rng, SeedableRng};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tracing::debug;
+
+lazy_static::lazy_static! {
+    static ref CHAOS_CONTROLLER: ChaosController = ChaosController::new(0);
+}
+
+/// Get the process-wide chaos controller
+pub fn chaos_controller() -> &'static ChaosController {
+    &CHAOS_CONTROLLER
+}
+
+/// Direction of the messages a rule applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChaosDirection {
+    /// messages sent to the peer
+    Outgoing,
+    /// messages received from the peer
+    Incoming,
+}
+
+/// Faults applied to the messages exchanged with a peer
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FaultRules {
+    /// probability for a message to be dropped
+    pub drop_rate: f64,
+    /// fixed delay applied to each message
+    pub delay: MassaTime,
+    /// maximum random delay added to `delay`
+    pub delay_jitter: MassaTime,
+    /// probability for a message to be sent twice
+    pub duplicate_rate: f64,
+    /// probability for a message to be held back and released after the next one
+    pub reorder_rate: f64,
+}
+
+/// Number of faults applied to the messages exchanged with a peer
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChaosStats {
+    /// dropped messages
+    pub dropped: u64,
+    /// delayed messages
+    pub delayed: u64,
+    /// duplicated messages
+    pub duplicated: u64,
+    /// reordered messages
+    pub reordered: u64,
+}
+
+/// What to do with a given message
+#[derive(Debug, Default)]
+struct FaultDecision {
+    drop: bool,
+    delay: Option<Duration>,
+    duplicate: bool,
+    reorder: bool,
+}
+
+struct ChaosState {
+    rng: StdRng,
+    rules: HashMap<(NodeId, ChaosDirection), FaultRules>,
+    stats: HashMap<NodeId, ChaosStats>,
+}
+
+/// Controls the faults injected on peer connections
+pub struct ChaosController(Mutex<ChaosState>);
+
+impl ChaosController {
+    fn new(seed: u64) -> Self {
+        ChaosController(Mutex::new(ChaosState {
+            rng: StdRng::seed_from_u64(seed),
+            rules: Default::default(),
+            stats: Default::default(),
+        }))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, ChaosState> {
+        // a test panicking while holding the lock must not break the following ones
+        self.0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Reset the random generator used to draw faults
+    pub fn reseed(&self, seed: u64) {
+        self.lock().rng = StdRng::seed_from_u64(seed);
+    }
+
+    /// Set the faults applied to the messages exchanged with a peer in a given direction
+    pub fn set_rules(&self, peer: NodeId, direction: ChaosDirection, rules: FaultRules) {
+        self.lock().rules.insert((peer, direction), rules);
+    }
+
+    /// Remove the faults applied to the messages exchanged with a peer
+    pub fn clear_rules(&self, peer: &NodeId) {
+        self.lock()
+            .rules
+            .retain(|(rule_peer, _), _| rule_peer != peer);
+    }
+
+    /// Remove all the rules and reset the statistics
+    pub fn clear_all(&self) {
+        let mut state = self.lock();
+        state.rules.clear();
+        state.stats.clear();
+    }
+
+    /// Get the faults applied so far to the messages exchanged with a peer
+    pub fn get_stats(&self, peer: &NodeId) -> ChaosStats {
+        self.lock().stats.get(peer).copied().unwrap_or_default()
+    }
+
+    /// Draw the faults to apply to a message, `None` if there is no rule for the peer
+    fn decide(&self, peer: NodeId, direction: ChaosDirection) -> Option<FaultDecision> {
+        let mut state = self.lock();
+        let rules = *state.rules.get(&(peer, direction))?;
+        let mut decision = FaultDecision::default();
+        if state.rng.gen_bool(rules.drop_rate.clamp(0.0, 1.0)) {
+            decision.drop = true;
+        } else {
+            let delay =
+                rules.delay.to_millis() + state.rng.gen_range(0..=rules.delay_jitter.to_millis());
+            if delay > 0 {
+                decision.delay = Some(Duration::from_millis(delay));
+            }
+            decision.duplicate = state.rng.gen_bool(rules.duplicate_rate.clamp(0.0, 1.0));
+            decision.reorder = state.rng.gen_bool(rules.reorder_rate.clamp(0.0, 1.0));
+        }
+        let stats = state.stats.entry(peer).or_default();
+        stats.dropped += decision.drop as u64;
+        stats.delayed += decision.delay.is_some() as u64;
+        stats.duplicated += decision.duplicate as u64;
+        stats.reordered += decision.reorder as u64;
+        Some(decision)
+    }
+}
+
+/// Applies the chaos rules to the messages of one direction of a connection.
+/// Holds the message kept back for reordering.
+pub(crate) struct ChaosFilter {
+    peer: NodeId,
+    direction: ChaosDirection,
+    held: Option<Message>,
+}
+
+impl ChaosFilter {
+    pub(crate) fn new(peer: NodeId, direction: ChaosDirection) -> Self {
+        ChaosFilter {
+            peer,
+            direction,
+            held: None,
+        }
+    }
+
+    /// Returns the messages to actually process, in order.
+    /// Delays are applied by waiting before returning.
+    pub(crate) async fn apply(&mut self, messages: Vec<Message>) -> Vec<Message> {
+        let mut output = Vec::with_capacity(messages.len());
+        for msg in messages {
+            let decision = match chaos_controller().decide(self.peer, self.direction) {
+                Some(decision) => decision,
+                None => {
+                    output.push(msg);
+                    output.extend(self.held.take());
+                    continue;
+                }
+            };
+            if decision.drop {
+                debug!(
+                    "chaos: dropping {:?} message of {}",
+                    self.direction, self.peer
+                );
+                continue;
+            }
+            if let Some(delay) = decision.delay {
+                tokio::time::sleep(delay).await;
+            }
+            if decision.duplicate {
+                output.push(msg.clone());
+            }
+            if decision.reorder && self.held.is_none() {
+                self.held = Some(msg);
+                continue;
+            }
+            output.push(msg);
+            output.extend(self.held.take());
+        }
+        output
+    }
+}
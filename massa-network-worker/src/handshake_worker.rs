@@ -1,6 +1,11 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 //! Here are happening handshakes.
+//!
+//! Each node sends random bytes and signs the ones of the other node. Between nodes supporting
+//! `Capabilities::HANDSHAKE_TRANSCRIPT`, the signature also commits to the random bytes and public keys
+//! of both nodes, so that it cannot be relayed to another handshake. The random bytes we issued
+//! and the ones we received recently are remembered: a node sending them again is replaying a handshake.
 
 use crate::messages::MessageDeserializer;
 
@@ -18,7 +23,7 @@ use massa_models::{
         MAX_OPERATIONS_PER_BLOCK, MAX_OPERATION_DATASTORE_ENTRY_COUNT,
         MAX_OPERATION_DATASTORE_KEY_LENGTH, MAX_OPERATION_DATASTORE_VALUE_LENGTH, THREAD_COUNT,
     },
-    version::Version,
+    version::{Capabilities, Version},
 };
 use massa_models::{
     config::{MAX_ASK_BLOCKS_PER_MESSAGE, MAX_OPERATIONS_PER_MESSAGE},
@@ -29,13 +34,90 @@ use massa_network_exports::{
     WriteHalf,
 };
 use massa_signature::KeyPair;
+use massa_signature::PublicKey;
 use massa_time::MassaTime;
 use rand::{rngs::StdRng, RngCore, SeedableRng};
+use std::collections::{HashSet, VecDeque};
+use std::sync::{Arc, Mutex, PoisonError};
 use tokio::{task::JoinHandle, time::timeout};
 use tracing::debug;
 
-/// Type alias for more readability
-pub type HandshakeReturnType = Result<(NodeId, ReadBinder, WriteBinder), NetworkError>;
+/// Type alias for more readability.
+/// The capabilities are the ones supported by both nodes.
+pub type HandshakeReturnType =
+    Result<(NodeId, ReadBinder, WriteBinder, Capabilities), NetworkError>;
+
+/// Number of random bytes received in the latest handshakes kept to detect the replays
+const REMEMBERED_NONCE_COUNT: usize = 10_000;
+
+/// Random bytes of the handshakes, shared by all the handshake workers
+#[derive(Clone, Default)]
+pub struct HandshakeNonces(Arc<Mutex<NonceRegistry>>);
+
+#[derive(Default)]
+struct NonceRegistry {
+    /// random bytes we sent in the running handshakes
+    issued: HashSet<[u8; 32]>,
+    /// random bytes received in the latest handshakes, oldest first
+    received: VecDeque<[u8; 32]>,
+    /// same as `received`, for lookups
+    received_set: HashSet<[u8; 32]>,
+}
+
+impl HandshakeNonces {
+    /// Generates and remembers our random bytes for a new handshake
+    fn issue(&self) -> [u8; 32] {
+        let mut registry = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut rng = StdRng::from_entropy();
+        loop {
+            let mut random_bytes = [0u8; 32];
+            rng.fill_bytes(&mut random_bytes);
+            if registry.issued.insert(random_bytes) {
+                return random_bytes;
+            }
+        }
+    }
+
+    /// Forgets our random bytes once the handshake is over
+    fn release(&self, random_bytes: &[u8; 32]) {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .issued
+            .remove(random_bytes);
+    }
+
+    /// Remembers the random bytes received from a node.
+    /// Returns `false` if we issued them or already received them.
+    fn accept(&self, random_bytes: [u8; 32]) -> bool {
+        let mut registry = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        if registry.issued.contains(&random_bytes) || !registry.received_set.insert(random_bytes) {
+            return false;
+        }
+        registry.received.push_back(random_bytes);
+        if registry.received.len() > REMEMBERED_NONCE_COUNT {
+            if let Some(oldest) = registry.received.pop_front() {
+                registry.received_set.remove(&oldest);
+            }
+        }
+        true
+    }
+}
+
+/// Hash signed by `signer` for `peer` between nodes supporting `Capabilities::HANDSHAKE_TRANSCRIPT`
+fn transcript_hash(
+    peer_random_bytes: &[u8; 32],
+    signer_random_bytes: &[u8; 32],
+    peer_public_key: &PublicKey,
+    signer_public_key: &PublicKey,
+) -> Hash {
+    let mut transcript = Vec::new();
+    transcript.extend_from_slice(peer_random_bytes);
+    transcript.extend_from_slice(signer_random_bytes);
+    transcript.extend_from_slice(peer_public_key.to_bytes());
+    transcript.extend_from_slice(signer_public_key.to_bytes());
+    Hash::compute_from(&transcript)
+}
 
 /// Manages handshakes.
 pub struct HandshakeWorker {
@@ -49,7 +131,12 @@ pub struct HandshakeWorker {
     keypair: KeyPair,
     /// After `timeout_duration` milliseconds, the handshake attempt is dropped.
     timeout_duration: MassaTime,
+    /// After `phase_timeout` milliseconds without completing a phase, the handshake attempt is dropped.
+    phase_timeout: MassaTime,
     version: Version,
+    capabilities: Capabilities,
+    /// Random bytes of the handshakes
+    nonces: HandshakeNonces,
 }
 
 impl HandshakeWorker {
@@ -67,8 +154,11 @@ impl HandshakeWorker {
     /// * `self_node_id`: our node id.
     /// * `keypair`: our keypair.
     /// * `timeout_duration`: after `timeout_duration` milliseconds, the handshake attempt is dropped.
+    /// * `phase_timeout`: after `phase_timeout` milliseconds without completing a phase, the handshake attempt is dropped.
+    /// * `nonces`: random bytes of the handshakes, shared by all the handshake workers
     /// * `connection_id`: Node we are trying to connect for debugging
     /// * `version`: Node version used in handshake initialization (check peers compatibility)
+    /// * `capabilities`: optional features we support, intersected with the ones of the peer
     #[allow(clippy::too_many_arguments)]
     pub fn spawn(
         socket_reader: ReadHalf,
@@ -76,7 +166,10 @@ impl HandshakeWorker {
         self_node_id: NodeId,
         keypair: KeyPair,
         timeout_duration: MassaTime,
+        phase_timeout: MassaTime,
+        nonces: HandshakeNonces,
         version: Version,
+        capabilities: Capabilities,
         connection_id: ConnectionId,
         max_bytes_read: f64,
         max_bytes_write: f64,
@@ -115,7 +208,10 @@ impl HandshakeWorker {
                     self_node_id,
                     keypair,
                     timeout_duration,
+                    phase_timeout,
                     version,
+                    capabilities,
+                    nonces,
                 }
                 .run()
                 .await,
@@ -129,14 +225,35 @@ impl HandshakeWorker {
     /// Creates the binders to communicate with that node.
     async fn run(mut self) -> HandshakeReturnType {
         // generate random bytes
-        let mut self_random_bytes = [0u8; 32];
-        StdRng::from_entropy().fill_bytes(&mut self_random_bytes);
-        let self_random_hash = Hash::compute_from(&self_random_bytes);
+        let self_random_bytes = self.nonces.issue();
+        let result = match timeout(
+            self.timeout_duration.to_duration(),
+            self.handshake(self_random_bytes),
+        )
+        .await
+        {
+            Err(_) => Err(NetworkError::HandshakeError(
+                HandshakeErrorType::HandshakeTimeout,
+            )),
+            Ok(result) => result,
+        };
+        self.nonces.release(&self_random_bytes);
+        let (other_node_id, capabilities) = result?;
+        Ok((other_node_id, self.reader, self.writer, capabilities))
+    }
+
+    /// Exchanges the handshake messages, each phase within `phase_timeout`.
+    /// Returns the node id of the other node and the capabilities supported by both nodes.
+    async fn handshake(
+        &mut self,
+        self_random_bytes: [u8; 32],
+    ) -> Result<(NodeId, Capabilities), NetworkError> {
         // send handshake init future
         let msg = Message::HandshakeInitiation {
             public_key: self.self_node_id.get_public_key(),
             random_bytes: self_random_bytes,
             version: self.version,
+            capabilities: self.capabilities,
         };
         let send_init_fut = self.writer.send(&msg);
 
@@ -144,8 +261,8 @@ impl HandshakeWorker {
         let recv_init_fut = self.reader.next();
 
         // join send_init_fut and recv_init_fut with a timeout, and match result
-        let (other_node_id, other_random_bytes, other_version) = match timeout(
-            self.timeout_duration.to_duration(),
+        let (other_node_id, other_random_bytes, other_version, other_capabilities) = match timeout(
+            self.phase_timeout.to_duration(),
             try_join(send_init_fut, recv_init_fut),
         )
         .await
@@ -158,7 +275,8 @@ impl HandshakeWorker {
                     public_key: pk,
                     random_bytes: rb,
                     version,
-                } => (NodeId::new(pk), rb, version),
+                    capabilities,
+                } => (NodeId::new(pk), rb, version, capabilities),
                 Message::PeerList(list) => throw!(PeerListReceived, list),
                 _ => throw!(HandshakeWrongMessage),
             },
@@ -174,8 +292,37 @@ impl HandshakeWorker {
             throw!(IncompatibleVersion)
         }
 
-        // sign their random bytes
-        let other_random_hash = Hash::compute_from(&other_random_bytes);
+        // random bytes we issued (relay) or already received (replay)
+        if !self.nonces.accept(other_random_bytes) {
+            throw!(HandshakeReplay)
+        }
+
+        // sign their random bytes, with ours and both public keys if the other node supports it
+        let capabilities = self.capabilities.intersection(other_capabilities);
+        let transcript = capabilities.contains(Capabilities::HANDSHAKE_TRANSCRIPT);
+        let self_public_key = self.self_node_id.get_public_key();
+        let other_public_key = other_node_id.get_public_key();
+        let (other_random_hash, self_random_hash) = if transcript {
+            (
+                transcript_hash(
+                    &other_random_bytes,
+                    &self_random_bytes,
+                    &other_public_key,
+                    &self_public_key,
+                ),
+                transcript_hash(
+                    &self_random_bytes,
+                    &other_random_bytes,
+                    &self_public_key,
+                    &other_public_key,
+                ),
+            )
+        } else {
+            (
+                Hash::compute_from(&other_random_bytes),
+                Hash::compute_from(&self_random_bytes),
+            )
+        };
         let self_signature = self.keypair.sign(&other_random_hash)?;
 
         // send handshake reply future
@@ -189,7 +336,7 @@ impl HandshakeWorker {
 
         // join send_reply_fut and recv_reply_fut with a timeout, and match result
         let other_signature = match timeout(
-            self.timeout_duration.to_duration(),
+            self.phase_timeout.to_duration(),
             try_join(send_reply_fut, recv_reply_fut),
         )
         .await
@@ -204,13 +351,12 @@ impl HandshakeWorker {
         };
 
         // check their signature
-        other_node_id
-            .get_public_key()
+        other_public_key
             .verify_signature(&self_random_hash, &other_signature)
             .map_err(|_err| {
                 NetworkError::HandshakeError(HandshakeErrorType::HandshakeInvalidSignature)
             })?;
 
-        Ok((other_node_id, self.reader, self.writer))
+        Ok((other_node_id, capabilities))
     }
 }
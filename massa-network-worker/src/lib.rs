@@ -11,18 +11,24 @@ use crate::{
     network_worker::{NetworkWorker, NetworkWorkerChannels},
     peer_info_database::PeerInfoDatabase,
 };
+use massa_channel::{channel_with_priority, EventBus};
 use massa_logging::massa_trace;
-use massa_models::{node::NodeId, version::Version};
+use massa_models::{node::NodeId, slot::SyncLag, version::Version};
 use massa_network_exports::{
     BootstrapPeers, Establisher, NetworkCommand, NetworkCommandSender, NetworkConfig, NetworkError,
     NetworkEvent, NetworkEventReceiver, NetworkManagementCommand, NetworkManager,
 };
 use massa_signature::KeyPair;
+use massa_time::ClockOffset;
 use tokio::sync::mpsc;
 use tracing::{debug, error, info, warn};
 
 //pub use establisher::Establisher;
+mod ban_list;
 mod binders;
+/// fault injection on peer connections, for tests only
+#[cfg(feature = "chaos")]
+pub mod chaos;
 mod handshake_worker;
 mod messages;
 mod network_cmd_impl;
@@ -38,11 +44,18 @@ pub mod tests;
 ///
 /// # Arguments
 /// * `cfg`: network configuration
+/// * `clock_offset`: estimate of the network clock offset, updated with the probes of the connections
+/// * `sync_lag`: estimate of the lag of the final slot of the node behind the network, updated with the gossiped final slots
+/// * `event_bus`: event bus of the node, on which the bans are published and from which the final slot is followed
+#[allow(clippy::too_many_arguments)]
 pub async fn start_network_controller(
     network_settings: &NetworkConfig,
     mut establisher: Establisher,
     initial_peers: Option<BootstrapPeers>,
     version: Version,
+    clock_offset: ClockOffset,
+    sync_lag: SyncLag,
+    event_bus: EventBus,
 ) -> Result<
     (
         NetworkCommandSender,
@@ -57,7 +70,7 @@ pub async fn start_network_controller(
 
     // check that local IP is routable
     if let Some(self_ip) = network_settings.routable_ip {
-        if !self_ip.is_global() {
+        if !network_settings.is_peer_ip(&self_ip) {
             return Err(NetworkError::InvalidIpError(self_ip));
         }
     }
@@ -102,10 +115,16 @@ pub async fn start_network_controller(
     }
 
     // launch controller
-    let (command_tx, controller_command_rx) =
-        mpsc::channel::<NetworkCommand>(network_settings.controller_channel_size);
-    let (controller_event_tx, event_rx) =
-        mpsc::channel::<NetworkEvent>(network_settings.event_channel_size);
+    let (command_tx, controller_command_rx) = channel_with_priority(
+        "network_command",
+        network_settings.command_channel,
+        NetworkCommand::priority,
+    );
+    let (controller_event_tx, event_rx) = channel_with_priority(
+        "network_event",
+        network_settings.event_channel,
+        NetworkEvent::priority,
+    );
     let (manager_tx, controller_manager_rx) = mpsc::channel::<NetworkManagementCommand>(1);
     let cfg_copy = network_settings.clone();
     let keypair_cloned = keypair.clone();
@@ -120,8 +139,11 @@ pub async fn start_network_controller(
                 controller_command_rx,
                 controller_event_tx,
                 controller_manager_rx,
+                event_bus,
             },
             version,
+            clock_offset,
+            sync_lag,
         )
         .run_loop()
         .await;
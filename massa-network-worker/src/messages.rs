@@ -4,8 +4,14 @@ use massa_hash::HashDeserializer;
 use massa_models::{
     block_header::{BlockHeader, BlockHeaderDeserializer, SecuredHeader},
     block_id::BlockId,
-    config::HANDSHAKE_RANDOMNESS_SIZE_BYTES,
+    config::{
+        BLOCK_ERASURE_TOTAL_CHUNKS, HANDSHAKE_RANDOMNESS_SIZE_BYTES, MAX_BLOCK_SIZE,
+        MAX_NODE_METADATA_CAPABILITIES, MAX_NODE_METADATA_STRING_LENGTH,
+    },
     endorsement::{Endorsement, EndorsementDeserializer, SecureShareEndorsement},
+    endorsement_signature::{
+        EndorsementSignature, EndorsementSignatureDeserializer, EndorsementSignatureSerializer,
+    },
     operation::{
         OperationIdsDeserializer, OperationIdsSerializer, OperationPrefixIds,
         OperationPrefixIdsDeserializer, OperationPrefixIdsSerializer, OperationsDeserializer,
@@ -14,13 +20,22 @@ use massa_models::{
     secure_share::{SecureShareDeserializer, SecureShareSerializer},
     serialization::array_from_slice,
     serialization::{IpAddrDeserializer, IpAddrSerializer},
-    version::{Version, VersionDeserializer, VersionSerializer},
+    slot::{Slot, SlotDeserializer, SlotSerializer},
+    version::{
+        Capabilities, CapabilitiesDeserializer, CapabilitiesSerializer, Version,
+        VersionDeserializer, VersionSerializer,
+    },
+};
+use massa_network_exports::{
+    AskForBlocksInfo, BlockChunk, BlockInfoReply, SignedNodeMetadata,
+    SignedNodeMetadataDeserializer, SignedNodeMetadataSerializer,
 };
-use massa_network_exports::{AskForBlocksInfo, BlockInfoReply};
 use massa_serialization::{
-    Deserializer, SerializeError, Serializer, U32VarIntDeserializer, U32VarIntSerializer,
+    Deserializer, SerializeError, Serializer, U16VarIntDeserializer, U16VarIntSerializer,
+    U32VarIntDeserializer, U32VarIntSerializer, U64VarIntDeserializer, U64VarIntSerializer,
 };
 use massa_signature::{PublicKey, PublicKeyDeserializer, Signature, SignatureDeserializer};
+use massa_time::MassaTime;
 use nom::{
     bytes::complete::take,
     error::{context, ContextError, ParseError},
@@ -35,7 +50,7 @@ use std::ops::Bound::{Excluded, Included};
 
 /// All messages that can be sent or received.
 #[allow(clippy::large_enum_variant)]
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Message {
     /// Initiates handshake.
     HandshakeInitiation {
@@ -46,6 +61,9 @@ pub enum Message {
         /// let us know their public key.
         random_bytes: [u8; HANDSHAKE_RANDOMNESS_SIZE_BYTES],
         version: Version,
+        /// Optional features we support.
+        /// Nodes that do not send them are considered to support none.
+        capabilities: Capabilities,
     },
     /// Reply to a handshake initiation message.
     HandshakeReply {
@@ -73,6 +91,27 @@ pub enum Message {
     Operations(Vec<SecureShareOperation>),
     /// Endorsements
     Endorsements(Vec<SecureShareEndorsement>),
+    /// Metadata signed by the sending node
+    NodeMetadata(SignedNodeMetadata),
+    /// Probe of the connection, carrying a nonce.
+    /// Only sent to the nodes supporting `Capabilities::PING`.
+    Ping(u64),
+    /// Answer to a `Ping`, carrying its nonce
+    Pong(u64),
+    /// Answer to a `Ping` carrying its nonce and the time of the answering node when sending it.
+    /// Only sent to the nodes supporting `Capabilities::CLOCK_SYNC`.
+    TimedPong {
+        /// nonce of the ping
+        nonce: u64,
+        /// time of the answering node
+        time: MassaTime,
+    },
+    /// Latest final slot of the sending node, sent periodically.
+    /// Only sent to the nodes supporting `Capabilities::FINAL_SLOT`.
+    FinalSlot(Slot),
+    /// BLS signatures of endorsers, aggregated in the headers by the block producers.
+    /// Only sent to the nodes supporting `Capabilities::ENDORSEMENT_SIGNATURES`.
+    EndorsementSignatures(Vec<EndorsementSignature>),
 }
 
 #[derive(IntoPrimitive, Debug, Eq, PartialEq, TryFromPrimitive)]
@@ -89,6 +128,12 @@ pub(crate) enum MessageTypeId {
     AskForOperations,
     OperationsAnnouncement,
     ReplyForBlocks,
+    NodeMetadata,
+    Ping,
+    Pong,
+    TimedPong,
+    FinalSlot,
+    EndorsementSignatures,
 }
 
 #[derive(IntoPrimitive, Debug, Eq, PartialEq, TryFromPrimitive)]
@@ -98,17 +143,24 @@ pub(crate) enum BlockInfoType {
     Info,
     Operations,
     NotFound,
+    Chunks,
 }
 
 /// Basic serializer for `Message`.
 pub struct MessageSerializer {
     version_serializer: VersionSerializer,
+    capabilities_serializer: CapabilitiesSerializer,
     u32_serializer: U32VarIntSerializer,
+    u16_serializer: U16VarIntSerializer,
+    u64_serializer: U64VarIntSerializer,
     secure_serializer: SecureShareSerializer,
     operation_prefix_ids_serializer: OperationPrefixIdsSerializer,
     operations_ids_serializer: OperationIdsSerializer,
     operations_serializer: OperationsSerializer,
     ip_addr_serializer: IpAddrSerializer,
+    node_metadata_serializer: SignedNodeMetadataSerializer,
+    slot_serializer: SlotSerializer,
+    endorsement_signature_serializer: EndorsementSignatureSerializer,
 }
 
 impl MessageSerializer {
@@ -116,12 +168,18 @@ impl MessageSerializer {
     pub fn new() -> Self {
         MessageSerializer {
             version_serializer: VersionSerializer::new(),
+            capabilities_serializer: CapabilitiesSerializer::new(),
             u32_serializer: U32VarIntSerializer::new(),
+            u16_serializer: U16VarIntSerializer::new(),
+            u64_serializer: U64VarIntSerializer::new(),
             secure_serializer: SecureShareSerializer::new(),
             operation_prefix_ids_serializer: OperationPrefixIdsSerializer::new(),
             operations_ids_serializer: OperationIdsSerializer::new(),
             operations_serializer: OperationsSerializer::new(),
             ip_addr_serializer: IpAddrSerializer::new(),
+            node_metadata_serializer: SignedNodeMetadataSerializer::new(),
+            slot_serializer: SlotSerializer::new(),
+            endorsement_signature_serializer: EndorsementSignatureSerializer::new(),
         }
     }
 }
@@ -141,12 +199,15 @@ impl Serializer<Message> for MessageSerializer {
                 public_key,
                 random_bytes,
                 version,
+                capabilities,
             } => {
                 self.u32_serializer
                     .serialize(&(MessageTypeId::HandshakeInitiation as u32), buffer)?;
                 buffer.extend(public_key.to_bytes());
                 buffer.extend(random_bytes);
                 self.version_serializer.serialize(version, buffer)?;
+                self.capabilities_serializer
+                    .serialize(capabilities, buffer)?;
             }
             Message::HandshakeReply { signature } => {
                 self.u32_serializer
@@ -169,12 +230,20 @@ impl Serializer<Message> for MessageSerializer {
                         AskForBlocksInfo::Header => BlockInfoType::Header,
                         AskForBlocksInfo::Info => BlockInfoType::Info,
                         AskForBlocksInfo::Operations(_) => BlockInfoType::Operations,
+                        AskForBlocksInfo::Chunks(_) => BlockInfoType::Chunks,
                     };
                     self.u32_serializer
                         .serialize(&u32::from(info_type), buffer)?;
                     if let AskForBlocksInfo::Operations(ids) = info {
                         self.operations_ids_serializer.serialize(ids, buffer)?;
                     }
+                    if let AskForBlocksInfo::Chunks(indices) = info {
+                        self.u32_serializer
+                            .serialize(&(indices.len() as u32), buffer)?;
+                        for index in indices {
+                            self.u16_serializer.serialize(index, buffer)?;
+                        }
+                    }
                 }
             }
             Message::ReplyForBlocks(list) => {
@@ -189,6 +258,7 @@ impl Serializer<Message> for MessageSerializer {
                         BlockInfoReply::Info(_) => BlockInfoType::Info,
                         BlockInfoReply::Operations(_) => BlockInfoType::Operations,
                         BlockInfoReply::NotFound => BlockInfoType::NotFound,
+                        BlockInfoReply::Chunks(_) => BlockInfoType::Chunks,
                     };
                     self.u32_serializer
                         .serialize(&u32::from(info_type), buffer)?;
@@ -201,6 +271,17 @@ impl Serializer<Message> for MessageSerializer {
                     if let BlockInfoReply::Info(ids) = info {
                         self.operations_ids_serializer.serialize(ids, buffer)?;
                     }
+                    if let BlockInfoReply::Chunks(chunks) = info {
+                        self.u32_serializer
+                            .serialize(&(chunks.len() as u32), buffer)?;
+                        for chunk in chunks {
+                            self.u16_serializer.serialize(&chunk.index, buffer)?;
+                            self.u32_serializer.serialize(&chunk.total_size, buffer)?;
+                            self.u32_serializer
+                                .serialize(&(chunk.data.len() as u32), buffer)?;
+                            buffer.extend(&chunk.data);
+                        }
+                    }
                 }
             }
             Message::AskPeerList => {
@@ -242,6 +323,42 @@ impl Serializer<Message> for MessageSerializer {
                     self.secure_serializer.serialize(endorsement, buffer)?;
                 }
             }
+            Message::NodeMetadata(metadata) => {
+                self.u32_serializer
+                    .serialize(&(MessageTypeId::NodeMetadata as u32), buffer)?;
+                self.node_metadata_serializer.serialize(metadata, buffer)?;
+            }
+            Message::Ping(nonce) => {
+                self.u32_serializer
+                    .serialize(&(MessageTypeId::Ping as u32), buffer)?;
+                self.u64_serializer.serialize(nonce, buffer)?;
+            }
+            Message::Pong(nonce) => {
+                self.u32_serializer
+                    .serialize(&(MessageTypeId::Pong as u32), buffer)?;
+                self.u64_serializer.serialize(nonce, buffer)?;
+            }
+            Message::TimedPong { nonce, time } => {
+                self.u32_serializer
+                    .serialize(&(MessageTypeId::TimedPong as u32), buffer)?;
+                self.u64_serializer.serialize(nonce, buffer)?;
+                self.u64_serializer.serialize(&time.to_millis(), buffer)?;
+            }
+            Message::FinalSlot(slot) => {
+                self.u32_serializer
+                    .serialize(&(MessageTypeId::FinalSlot as u32), buffer)?;
+                self.slot_serializer.serialize(slot, buffer)?;
+            }
+            Message::EndorsementSignatures(signatures) => {
+                self.u32_serializer
+                    .serialize(&(MessageTypeId::EndorsementSignatures as u32), buffer)?;
+                self.u32_serializer
+                    .serialize(&(signatures.len() as u32), buffer)?;
+                for signature in signatures {
+                    self.endorsement_signature_serializer
+                        .serialize(signature, buffer)?;
+                }
+            }
         }
         Ok(())
     }
@@ -252,6 +369,7 @@ pub struct MessageDeserializer {
     public_key_deserializer: PublicKeyDeserializer,
     signature_deserializer: SignatureDeserializer,
     version_deserializer: VersionDeserializer,
+    capabilities_deserializer: CapabilitiesDeserializer,
     id_deserializer: U32VarIntDeserializer,
     ask_block_number_deserializer: U32VarIntDeserializer,
     peer_list_length_deserializer: U32VarIntDeserializer,
@@ -263,7 +381,14 @@ pub struct MessageDeserializer {
     endorsement_deserializer: SecureShareDeserializer<Endorsement, EndorsementDeserializer>,
     operation_prefix_ids_deserializer: OperationPrefixIdsDeserializer,
     infos_deserializer: OperationIdsDeserializer,
+    chunk_count_deserializer: U32VarIntDeserializer,
+    chunk_index_deserializer: U16VarIntDeserializer,
+    chunk_size_deserializer: U32VarIntDeserializer,
     ip_addr_deserializer: IpAddrDeserializer,
+    node_metadata_deserializer: SignedNodeMetadataDeserializer,
+    nonce_deserializer: U64VarIntDeserializer,
+    slot_deserializer: SlotDeserializer,
+    endorsement_signature_deserializer: EndorsementSignatureDeserializer,
 }
 
 impl MessageDeserializer {
@@ -288,6 +413,7 @@ impl MessageDeserializer {
             public_key_deserializer: PublicKeyDeserializer::new(),
             signature_deserializer: SignatureDeserializer::new(),
             version_deserializer: VersionDeserializer::new(),
+            capabilities_deserializer: CapabilitiesDeserializer::new(),
             id_deserializer: U32VarIntDeserializer::new(Included(0), Included(u32::MAX)),
             ask_block_number_deserializer: U32VarIntDeserializer::new(
                 Included(0),
@@ -332,11 +458,65 @@ impl MessageDeserializer {
                 max_operations_per_message,
             ),
             infos_deserializer: OperationIdsDeserializer::new(max_operations_per_block),
+            chunk_count_deserializer: U32VarIntDeserializer::new(
+                Included(0),
+                Included(BLOCK_ERASURE_TOTAL_CHUNKS as u32),
+            ),
+            chunk_index_deserializer: U16VarIntDeserializer::new(
+                Included(0),
+                Excluded(BLOCK_ERASURE_TOTAL_CHUNKS),
+            ),
+            chunk_size_deserializer: U32VarIntDeserializer::new(
+                Included(0),
+                Included(MAX_BLOCK_SIZE),
+            ),
             ip_addr_deserializer: IpAddrDeserializer::new(),
+            node_metadata_deserializer: SignedNodeMetadataDeserializer::new(
+                MAX_NODE_METADATA_STRING_LENGTH,
+                MAX_NODE_METADATA_CAPABILITIES,
+            ),
+            nonce_deserializer: U64VarIntDeserializer::new(Included(0), Included(u64::MAX)),
+            slot_deserializer: SlotDeserializer::new(
+                (Included(0), Included(u64::MAX)),
+                (Included(0), Excluded(thread_count)),
+            ),
+            endorsement_signature_deserializer: EndorsementSignatureDeserializer::new(
+                thread_count,
+                endorsement_count,
+            ),
         }
     }
 }
 
+impl MessageDeserializer {
+    fn deserialize_block_chunk<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], BlockChunk, E> {
+        context(
+            "Failed BlockChunk deserialization",
+            tuple((
+                context("Failed index deserialization", |input| {
+                    self.chunk_index_deserializer.deserialize(input)
+                }),
+                context("Failed total_size deserialization", |input| {
+                    self.chunk_size_deserializer.deserialize(input)
+                }),
+                context("Failed data deserialization", |input| {
+                    let (rest, length) = self.chunk_size_deserializer.deserialize(input)?;
+                    take(length)(rest)
+                }),
+            )),
+        )
+        .map(|(index, total_size, data): (u16, u32, &[u8])| BlockChunk {
+            index,
+            total_size,
+            data: data.to_vec(),
+        })
+        .parse(buffer)
+    }
+}
+
 impl Deserializer<Message> for MessageDeserializer {
     fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
         &self,
@@ -364,15 +544,29 @@ impl Deserializer<Message> for MessageDeserializer {
                         context("Failed version deserialization", |input| {
                             self.version_deserializer.deserialize(input)
                         }),
+                        context(
+                            "Failed capabilities deserialization",
+                            |input: &'a [u8]| {
+                                // older nodes end their handshake initiation after the version
+                                if input.is_empty() {
+                                    Ok((input, Capabilities::empty()))
+                                } else {
+                                    self.capabilities_deserializer.deserialize(input)
+                                }
+                            },
+                        ),
                     ))
-                    .map(|(public_key, random_bytes, version)| {
-                        // Unwrap safety: we checked above that we took enough bytes
-                        Message::HandshakeInitiation {
-                            public_key,
-                            random_bytes: array_from_slice(random_bytes).unwrap(),
-                            version,
-                        }
-                    }),
+                    .map(
+                        |(public_key, random_bytes, version, capabilities)| {
+                            // Unwrap safety: we checked above that we took enough bytes
+                            Message::HandshakeInitiation {
+                                public_key,
+                                random_bytes: array_from_slice(random_bytes).unwrap(),
+                                version,
+                                capabilities,
+                            }
+                        },
+                    ),
                 )
                 .parse(input),
                 MessageTypeId::HandshakeReply => {
@@ -423,6 +617,16 @@ impl Deserializer<Message> for MessageDeserializer {
                                             .map(|(rest, operation_ids)| {
                                                 (rest, AskForBlocksInfo::Operations(operation_ids))
                                             }),
+                                        BlockInfoType::Chunks => length_count(
+                                            |input| {
+                                                self.chunk_count_deserializer.deserialize(input)
+                                            },
+                                            |input| {
+                                                self.chunk_index_deserializer.deserialize(input)
+                                            },
+                                        )
+                                        .map(AskForBlocksInfo::Chunks)
+                                        .parse(rest),
                                         BlockInfoType::NotFound => {
                                             Err(nom::Err::Error(ParseError::from_error_kind(
                                                 buffer,
@@ -482,6 +686,14 @@ impl Deserializer<Message> for MessageDeserializer {
                                         BlockInfoType::NotFound => {
                                             Ok((rest, BlockInfoReply::NotFound))
                                         }
+                                        BlockInfoType::Chunks => length_count(
+                                            |input| {
+                                                self.chunk_count_deserializer.deserialize(input)
+                                            },
+                                            |input| self.deserialize_block_chunk(input),
+                                        )
+                                        .map(BlockInfoReply::Chunks)
+                                        .parse(rest),
                                     }
                                 },
                             )),
@@ -538,6 +750,53 @@ impl Deserializer<Message> for MessageDeserializer {
                 )
                 .map(Message::Endorsements)
                 .parse(input),
+                MessageTypeId::NodeMetadata => {
+                    context("Failed NodeMetadata deserialization", |input| {
+                        self.node_metadata_deserializer.deserialize(input)
+                    })
+                    .map(Message::NodeMetadata)
+                    .parse(input)
+                }
+                MessageTypeId::Ping => context("Failed Ping deserialization", |input| {
+                    self.nonce_deserializer.deserialize(input)
+                })
+                .map(Message::Ping)
+                .parse(input),
+                MessageTypeId::Pong => context("Failed Pong deserialization", |input| {
+                    self.nonce_deserializer.deserialize(input)
+                })
+                .map(Message::Pong)
+                .parse(input),
+                MessageTypeId::TimedPong => context(
+                    "Failed TimedPong deserialization",
+                    tuple((
+                        |input| self.nonce_deserializer.deserialize(input),
+                        |input| self.nonce_deserializer.deserialize(input),
+                    )),
+                )
+                .map(|(nonce, time)| Message::TimedPong {
+                    nonce,
+                    time: MassaTime::from_millis(time),
+                })
+                .parse(input),
+                MessageTypeId::FinalSlot => context("Failed FinalSlot deserialization", |input| {
+                    self.slot_deserializer.deserialize(input)
+                })
+                .map(Message::FinalSlot)
+                .parse(input),
+                MessageTypeId::EndorsementSignatures => context(
+                    "Failed EndorsementSignatures deserialization",
+                    length_count(
+                        context("Failed length deserialization", |input| {
+                            self.endorsements_length_deserializer.deserialize(input)
+                        }),
+                        context("Failed endorsement signature deserialization", |input| {
+                            self.endorsement_signature_deserializer.deserialize(input)
+                        }),
+                    ),
+                )
+                .map(Message::EndorsementSignatures)
+                .parse(input),
             }
         })
         .parse(buffer)
@@ -554,8 +813,12 @@ mod tests {
         MAX_OPERATION_DATASTORE_KEY_LENGTH, MAX_OPERATION_DATASTORE_VALUE_LENGTH,
         MAX_PARAMETERS_SIZE, THREAD_COUNT,
     };
+    use massa_models::node::NodeId;
+    use massa_network_exports::NodeMetadata;
+    use massa_protocol_exports::tests::fuzzing::ProtocolMessageGenerator;
     use massa_serialization::DeserializeError;
-    use massa_signature::KeyPair;
+    use massa_signature::{BlsKeyPair, KeyPair};
+    use massa_time::MassaTime;
     use rand::{prelude::StdRng, RngCore, SeedableRng};
     use serial_test::serial;
     use std::str::FromStr;
@@ -586,6 +849,7 @@ mod tests {
             public_key: keypair.get_public_key(),
             random_bytes,
             version: Version::from_str("TEST.1.10").unwrap(),
+            capabilities: Capabilities::LIGHT_BOOTSTRAP,
         };
         let mut ser = Vec::new();
         message_serializer.serialize(&msg, &mut ser).unwrap();
@@ -598,18 +862,347 @@ mod tests {
                     public_key: pk1,
                     random_bytes: rb1,
                     version: v1,
+                    capabilities: c1,
                 },
                 Message::HandshakeInitiation {
                     public_key,
                     random_bytes,
                     version,
+                    capabilities,
                 },
             ) => {
                 assert_eq!(pk1, public_key);
                 assert_eq!(rb1, random_bytes);
                 assert_eq!(v1, version);
+                assert_eq!(c1, capabilities);
+            }
+            _ => panic!("unexpected message"),
+        }
+
+        // a handshake initiation without capabilities, as sent by older nodes
+        let capabilities_len = {
+            let mut buffer = Vec::new();
+            CapabilitiesSerializer::new()
+                .serialize(&Capabilities::LIGHT_BOOTSTRAP, &mut buffer)
+                .unwrap();
+            buffer.len()
+        };
+        let (_, deser) = message_deserializer
+            .deserialize::<DeserializeError>(&ser[..ser.len() - capabilities_len])
+            .unwrap();
+        match deser {
+            Message::HandshakeInitiation { capabilities, .. } => {
+                assert_eq!(capabilities, Capabilities::empty())
+            }
+            _ => panic!("unexpected message"),
+        }
+    }
+
+    #[test]
+    fn test_node_metadata_ser_deser() {
+        let message_deserializer = MessageDeserializer::new(
+            THREAD_COUNT,
+            ENDORSEMENT_COUNT,
+            MAX_ADVERTISE_LENGTH,
+            MAX_ASK_BLOCKS_PER_MESSAGE,
+            MAX_OPERATIONS_PER_BLOCK,
+            MAX_OPERATIONS_PER_MESSAGE,
+            MAX_ENDORSEMENTS_PER_MESSAGE,
+            MAX_DATASTORE_VALUE_LENGTH,
+            MAX_FUNCTION_NAME_LENGTH,
+            MAX_PARAMETERS_SIZE,
+            MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+            MAX_OPERATION_DATASTORE_KEY_LENGTH,
+            MAX_OPERATION_DATASTORE_VALUE_LENGTH,
+        );
+        let keypair = KeyPair::generate();
+        let metadata = NodeMetadata {
+            operator_contact: Some("ops@example.com".to_string()),
+            region: Some("eu-west".to_string()),
+            version: Version::from_str("TEST.1.10").unwrap(),
+            capabilities: vec!["bootstrap".to_string(), "public_api".to_string()],
+            timestamp: MassaTime::from_millis(1000),
+        };
+        let signed = SignedNodeMetadata::new(metadata, &keypair).unwrap();
+        let mut ser = Vec::new();
+        MessageSerializer::new()
+            .serialize(&Message::NodeMetadata(signed.clone()), &mut ser)
+            .unwrap();
+        let (_, res) = message_deserializer
+            .deserialize::<DeserializeError>(&ser)
+            .unwrap();
+        let received = match res {
+            Message::NodeMetadata(received) => received,
+            _ => panic!("unexpected message"),
+        };
+        assert_eq!(received, signed);
+        received.verify().unwrap();
+        assert_eq!(received.node_id(), NodeId::new(keypair.get_public_key()));
+
+        // a relayed record must not be altered
+        let mut tampered = received;
+        tampered.metadata.region = Some("us-east".to_string());
+        assert!(tampered.verify().is_err());
+    }
+
+    #[test]
+    fn test_block_chunks_ser_deser() {
+        let message_deserializer = MessageDeserializer::new(
+            THREAD_COUNT,
+            ENDORSEMENT_COUNT,
+            MAX_ADVERTISE_LENGTH,
+            MAX_ASK_BLOCKS_PER_MESSAGE,
+            MAX_OPERATIONS_PER_BLOCK,
+            MAX_OPERATIONS_PER_MESSAGE,
+            MAX_ENDORSEMENTS_PER_MESSAGE,
+            MAX_DATASTORE_VALUE_LENGTH,
+            MAX_FUNCTION_NAME_LENGTH,
+            MAX_PARAMETERS_SIZE,
+            MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+            MAX_OPERATION_DATASTORE_KEY_LENGTH,
+            MAX_OPERATION_DATASTORE_VALUE_LENGTH,
+        );
+        let block_id = BlockId(massa_hash::Hash::compute_from(b"block"));
+        let chunks = vec![
+            BlockChunk {
+                index: 0,
+                total_size: 6,
+                data: vec![1, 2, 3],
+            },
+            BlockChunk {
+                index: 3,
+                total_size: 6,
+                data: vec![4, 5, 6],
+            },
+        ];
+
+        let mut ser = Vec::new();
+        MessageSerializer::new()
+            .serialize(
+                &Message::AskForBlocks(vec![(block_id, AskForBlocksInfo::Chunks(vec![0, 3]))]),
+                &mut ser,
+            )
+            .unwrap();
+        let (rest, res) = message_deserializer
+            .deserialize::<DeserializeError>(&ser)
+            .unwrap();
+        assert!(rest.is_empty());
+        match res {
+            Message::AskForBlocks(list) => match list.as_slice() {
+                [(id, AskForBlocksInfo::Chunks(indices))] => {
+                    assert_eq!(id, &block_id);
+                    assert_eq!(indices, &vec![0, 3]);
+                }
+                _ => panic!("unexpected ask"),
+            },
+            _ => panic!("unexpected message"),
+        }
+
+        let mut ser = Vec::new();
+        MessageSerializer::new()
+            .serialize(
+                &Message::ReplyForBlocks(vec![(block_id, BlockInfoReply::Chunks(chunks.clone()))]),
+                &mut ser,
+            )
+            .unwrap();
+        let (rest, res) = message_deserializer
+            .deserialize::<DeserializeError>(&ser)
+            .unwrap();
+        assert!(rest.is_empty());
+        match res {
+            Message::ReplyForBlocks(list) => match list.as_slice() {
+                [(id, BlockInfoReply::Chunks(received))] => {
+                    assert_eq!(id, &block_id);
+                    assert_eq!(received, &chunks);
+                }
+                _ => panic!("unexpected reply"),
+            },
+            _ => panic!("unexpected message"),
+        }
+    }
+
+    #[test]
+    fn test_timed_pong_ser_deser() {
+        let message_deserializer = MessageDeserializer::new(
+            THREAD_COUNT,
+            ENDORSEMENT_COUNT,
+            MAX_ADVERTISE_LENGTH,
+            MAX_ASK_BLOCKS_PER_MESSAGE,
+            MAX_OPERATIONS_PER_BLOCK,
+            MAX_OPERATIONS_PER_MESSAGE,
+            MAX_ENDORSEMENTS_PER_MESSAGE,
+            MAX_DATASTORE_VALUE_LENGTH,
+            MAX_FUNCTION_NAME_LENGTH,
+            MAX_PARAMETERS_SIZE,
+            MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+            MAX_OPERATION_DATASTORE_KEY_LENGTH,
+            MAX_OPERATION_DATASTORE_VALUE_LENGTH,
+        );
+        let msg = Message::TimedPong {
+            nonce: 7,
+            time: MassaTime::from_millis(1_680_000_000_000),
+        };
+        let mut ser = Vec::new();
+        MessageSerializer::new().serialize(&msg, &mut ser).unwrap();
+        let (rest, deser) = message_deserializer
+            .deserialize::<DeserializeError>(&ser)
+            .unwrap();
+        assert!(rest.is_empty());
+        match deser {
+            Message::TimedPong { nonce, time } => {
+                assert_eq!(nonce, 7);
+                assert_eq!(time, MassaTime::from_millis(1_680_000_000_000));
             }
             _ => panic!("unexpected message"),
         }
     }
+
+    #[test]
+    fn test_final_slot_ser_deser() {
+        let message_deserializer = MessageDeserializer::new(
+            THREAD_COUNT,
+            ENDORSEMENT_COUNT,
+            MAX_ADVERTISE_LENGTH,
+            MAX_ASK_BLOCKS_PER_MESSAGE,
+            MAX_OPERATIONS_PER_BLOCK,
+            MAX_OPERATIONS_PER_MESSAGE,
+            MAX_ENDORSEMENTS_PER_MESSAGE,
+            MAX_DATASTORE_VALUE_LENGTH,
+            MAX_FUNCTION_NAME_LENGTH,
+            MAX_PARAMETERS_SIZE,
+            MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+            MAX_OPERATION_DATASTORE_KEY_LENGTH,
+            MAX_OPERATION_DATASTORE_VALUE_LENGTH,
+        );
+        let slot = Slot::new(123_456, THREAD_COUNT - 1);
+        let mut ser = Vec::new();
+        MessageSerializer::new()
+            .serialize(&Message::FinalSlot(slot), &mut ser)
+            .unwrap();
+        let (rest, deser) = message_deserializer
+            .deserialize::<DeserializeError>(&ser)
+            .unwrap();
+        assert!(rest.is_empty());
+        match deser {
+            Message::FinalSlot(received) => assert_eq!(received, slot),
+            _ => panic!("unexpected message"),
+        }
+
+        // a thread out of range is refused
+        ser.clear();
+        MessageSerializer::new()
+            .serialize(&Message::FinalSlot(Slot::new(1, THREAD_COUNT)), &mut ser)
+            .unwrap();
+        assert!(message_deserializer
+            .deserialize::<DeserializeError>(&ser)
+            .is_err());
+    }
+
+    #[test]
+    fn test_endorsement_signatures_ser_deser() {
+        let message_deserializer = MessageDeserializer::new(
+            THREAD_COUNT,
+            ENDORSEMENT_COUNT,
+            MAX_ADVERTISE_LENGTH,
+            MAX_ASK_BLOCKS_PER_MESSAGE,
+            MAX_OPERATIONS_PER_BLOCK,
+            MAX_OPERATIONS_PER_MESSAGE,
+            MAX_ENDORSEMENTS_PER_MESSAGE,
+            MAX_DATASTORE_VALUE_LENGTH,
+            MAX_FUNCTION_NAME_LENGTH,
+            MAX_PARAMETERS_SIZE,
+            MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+            MAX_OPERATION_DATASTORE_KEY_LENGTH,
+            MAX_OPERATION_DATASTORE_VALUE_LENGTH,
+        );
+        let endorsed_block = BlockId(massa_hash::Hash::compute_from("parent".as_bytes()));
+        let signatures: Vec<EndorsementSignature> = (0..3)
+            .map(|index| {
+                EndorsementSignature::new(
+                    Slot::new(10, 1),
+                    index,
+                    endorsed_block,
+                    &BlsKeyPair::generate(),
+                )
+            })
+            .collect();
+        let mut ser = Vec::new();
+        MessageSerializer::new()
+            .serialize(
+                &Message::EndorsementSignatures(signatures.clone()),
+                &mut ser,
+            )
+            .unwrap();
+        let (rest, deser) = message_deserializer
+            .deserialize::<DeserializeError>(&ser)
+            .unwrap();
+        assert!(rest.is_empty());
+        match deser {
+            Message::EndorsementSignatures(received) => assert_eq!(received, signatures),
+            _ => panic!("unexpected message"),
+        }
+
+        // an index out of the endorsers of a slot is refused
+        let out_of_range = EndorsementSignature::new(
+            Slot::new(10, 1),
+            ENDORSEMENT_COUNT,
+            endorsed_block,
+            &BlsKeyPair::generate(),
+        );
+        ser.clear();
+        MessageSerializer::new()
+            .serialize(
+                &Message::EndorsementSignatures(vec![out_of_range]),
+                &mut ser,
+            )
+            .unwrap();
+        assert!(message_deserializer
+            .deserialize::<DeserializeError>(&ser)
+            .is_err());
+    }
+
+    #[test]
+    fn test_deser_generated_messages() {
+        let message_serializer = MessageSerializer::new();
+        let message_deserializer = MessageDeserializer::new(
+            THREAD_COUNT,
+            ENDORSEMENT_COUNT,
+            MAX_ADVERTISE_LENGTH,
+            MAX_ASK_BLOCKS_PER_MESSAGE,
+            MAX_OPERATIONS_PER_BLOCK,
+            MAX_OPERATIONS_PER_MESSAGE,
+            MAX_ENDORSEMENTS_PER_MESSAGE,
+            MAX_DATASTORE_VALUE_LENGTH,
+            MAX_FUNCTION_NAME_LENGTH,
+            MAX_PARAMETERS_SIZE,
+            MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+            MAX_OPERATION_DATASTORE_KEY_LENGTH,
+            MAX_OPERATION_DATASTORE_VALUE_LENGTH,
+        );
+        let mut generator = ProtocolMessageGenerator::new(42, THREAD_COUNT, ENDORSEMENT_COUNT, 1);
+        let mut accepted = 0;
+        for i in 0..500 {
+            let msg = match i % 5 {
+                0 => Message::BlockHeader(generator.generate_header()),
+                1 => Message::AskForBlocks(generator.generate_ask_for_blocks()),
+                2 => Message::ReplyForBlocks(generator.generate_block_info()),
+                3 => Message::Operations(generator.generate_operations()),
+                _ => Message::Endorsements(generator.generate_endorsements()),
+            };
+            let mut ser = Vec::new();
+            message_serializer.serialize(&msg, &mut ser).unwrap();
+            // generated messages may be rejected (out of range values)
+            // but must never make the deserializer panic
+            if message_deserializer
+                .deserialize::<DeserializeError>(&ser)
+                .is_ok()
+            {
+                accepted += 1;
+            }
+            let mutated = generator.mutate_bytes(&ser);
+            let _ = message_deserializer.deserialize::<DeserializeError>(&mutated);
+        }
+        // the generated corpus is not trivially rejected
+        assert!(accepted > 0);
+    }
 }
@@ -21,6 +21,7 @@
 //! ```
 use crate::network_worker::NetworkWorker;
 use futures::{stream::FuturesUnordered, StreamExt};
+use massa_channel::{NodeEvent, Topic};
 use massa_hash::Hash;
 use massa_logging::massa_trace;
 use massa_models::{
@@ -28,14 +29,16 @@ use massa_models::{
     block_id::BlockId,
     composite::PubkeySig,
     endorsement::SecureShareEndorsement,
+    endorsement_signature::EndorsementSignature,
     node::NodeId,
     operation::{OperationPrefixIds, SecureShareOperation},
     stats::NetworkStats,
 };
 use massa_network_exports::{
-    AskForBlocksInfo, BlockInfoReply, BootstrapPeers, ConnectionClosureReason, ConnectionId,
-    NetworkError, NodeCommand, Peer, Peers,
+    AskForBlocksInfo, BanEntry, BlockInfoReply, BootstrapPeers, ConnectionClosureReason,
+    ConnectionId, IpSubnet, NetworkError, NodeCommand, Peer, Peers,
 };
+use massa_time::MassaTime;
 use std::{
     collections::{HashMap, HashSet},
     net::IpAddr,
@@ -70,8 +73,23 @@ async fn ban_connection_ids(worker: &mut NetworkWorker, ids: HashSet<ConnectionI
 /// Ban the connections corresponding to `ips` from the `worker`
 /// See also `ban_connection_ids`
 async fn node_ban_by_ips(worker: &mut NetworkWorker, ips: Vec<IpAddr>) -> Result<(), NetworkError> {
+    let now = MassaTime::now()?;
     for ip in ips.iter() {
         worker.peer_info_db.peer_banned(ip)?;
+        // kept across restarts, until unbanned
+        worker.peer_info_db.ban_list.ban(BanEntry {
+            subnet: IpSubnet::host(*ip),
+            reason: "banned by IP".into(),
+            created: now,
+            expiry: None,
+        });
+        worker
+            .event_bus
+            .publish_with(Topic::PeerBanned, || NodeEvent::PeerBanned {
+                node_id: None,
+                ip: Some(*ip),
+                reason: "banned by IP".into(),
+            });
     }
     let connexion_ids = worker
         .active_connections
@@ -99,6 +117,20 @@ async fn node_ban_by_ids(worker: &mut NetworkWorker, ids: Vec<NodeId>) -> Result
         .filter(|res| res.is_ok())
         .flat_map(|res| res.unwrap())
         .collect::<HashSet<_>>();
+    for id in ids.iter() {
+        let ip = worker
+            .active_nodes
+            .get(id)
+            .and_then(|(conn_id, _)| worker.active_connections.get(conn_id))
+            .map(|(ip, _)| *ip);
+        worker
+            .event_bus
+            .publish_with(Topic::PeerBanned, || NodeEvent::PeerBanned {
+                node_id: Some(*id),
+                ip,
+                reason: "banned by node id".into(),
+            });
+    }
 
     ban_connection_ids(worker, connection_ids_to_ban).await;
     Ok(())
@@ -144,6 +176,13 @@ async fn get_peers(worker: &mut NetworkWorker, response_tx: oneshot::Sender<Peer
         .send(Peers {
             peers,
             our_node_id: worker.self_node_id,
+            node_metadata: worker.node_metadata.clone(),
+            node_capabilities: worker.node_capabilities.clone(),
+            distribution: worker.peer_info_db.get_peer_distribution(),
+            node_probes: worker.node_probes.clone(),
+            clock_offset: worker.clock_offset.get(),
+            node_final_slots: worker.node_final_slots.clone(),
+            sync_lag: worker.sync_lag.get(),
         })
         .is_err()
     {
@@ -271,6 +310,25 @@ pub async fn on_send_endorsements_cmd(
         .await;
 }
 
+pub async fn on_send_endorsement_signatures_cmd(
+    worker: &mut NetworkWorker,
+    node: NodeId,
+    signatures: Vec<EndorsementSignature>,
+) {
+    massa_trace!(
+        "network_worker.manage_network_command receive NetworkCommand::SendEndorsementSignatures",
+        { "node": node, "signatures": signatures }
+    );
+    worker
+        .event
+        .forward(
+            node,
+            worker.active_nodes.get(&node),
+            NodeCommand::SendEndorsementSignatures(signatures),
+        )
+        .await;
+}
+
 pub async fn on_node_sign_message_cmd(
     worker: &mut NetworkWorker,
     msg: Vec<u8>,
@@ -311,6 +369,53 @@ pub async fn on_node_unban_by_ips_cmd(
     worker.peer_info_db.unban(ips)
 }
 
+pub async fn on_node_ban_subnet_cmd(
+    worker: &mut NetworkWorker,
+    entry: BanEntry,
+) -> Result<(), NetworkError> {
+    massa_trace!(
+        "network_worker.manage_network_command receive NetworkCommand::NodeBanSubnet",
+        { "subnet": entry.subnet.to_string() }
+    );
+    let subnet = entry.subnet;
+    worker
+        .event_bus
+        .publish_with(Topic::PeerBanned, || NodeEvent::PeerBanned {
+            node_id: None,
+            ip: None,
+            reason: format!("subnet {} banned: {}", subnet, entry.reason),
+        });
+    worker.peer_info_db.ban_list.ban(entry);
+    let connection_ids = worker
+        .active_connections
+        .iter()
+        .filter(|(_, (ip, _))| subnet.contains(ip))
+        .map(|(conn_id, _)| *conn_id)
+        .collect::<HashSet<_>>();
+    ban_connection_ids(worker, connection_ids).await;
+    Ok(())
+}
+
+pub async fn on_node_unban_subnets_cmd(
+    worker: &mut NetworkWorker,
+    subnets: Vec<IpSubnet>,
+) -> Result<(), NetworkError> {
+    worker.peer_info_db.ban_list.unban(&subnets);
+    Ok(())
+}
+
+pub async fn on_get_bans_cmd(
+    worker: &mut NetworkWorker,
+    response_tx: oneshot::Sender<Vec<BanEntry>>,
+) {
+    if response_tx
+        .send(worker.peer_info_db.ban_list.entries())
+        .is_err()
+    {
+        warn!("network: could not send GetBans response upstream");
+    }
+}
+
 pub async fn on_whitelist_cmd(
     worker: &mut NetworkWorker,
     ips: Vec<IpAddr>,
@@ -340,6 +445,7 @@ pub async fn on_get_stats_cmd(
             .filter(|(_, p)| p.banned)
             .fold(0, |acc, _| acc + 1),
         active_node_count: worker.active_nodes.len() as u64,
+        handshake_failures: worker.handshake_failures.clone(),
     };
     if response_tx.send(res).is_err() {
         warn!("network: could not send NodeSignMessage response upstream");
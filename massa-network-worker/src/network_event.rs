@@ -1,12 +1,13 @@
+use massa_channel::{MassaSender, SendTimeoutError};
 use massa_models::node::NodeId;
 use massa_network_exports::{ConnectionId, NetworkError, NetworkEvent, NodeCommand, NodeEvent};
 use std::time::Duration;
-use tokio::sync::mpsc::{self, error::SendTimeoutError};
+use tokio::sync::mpsc;
 use tracing::debug;
 
 pub struct EventSender {
     /// Sender for network events
-    controller_event_tx: mpsc::Sender<NetworkEvent>,
+    controller_event_tx: MassaSender<NetworkEvent>,
     /// Channel for sending node events.
     node_event_tx: mpsc::Sender<NodeEvent>,
     /// Max time spend to wait
@@ -15,7 +16,7 @@ pub struct EventSender {
 
 impl EventSender {
     pub fn new(
-        controller_event_tx: mpsc::Sender<NetworkEvent>,
+        controller_event_tx: MassaSender<NetworkEvent>,
         node_event_tx: mpsc::Sender<NodeEvent>,
         max_send_wait: Duration,
     ) -> Self {
@@ -83,12 +84,17 @@ pub mod event_impl {
         block_header::SecuredHeader,
         block_id::BlockId,
         endorsement::SecureShareEndorsement,
+        endorsement_signature::EndorsementSignature,
         node::NodeId,
         operation::{OperationPrefixIds, SecureShareOperation},
         secure_share::Id,
+        slot::Slot,
+    };
+    use massa_network_exports::{
+        AskForBlocksInfo, BlockInfoReply, ConnectionClosureReason, NodeCommand, SignedNodeMetadata,
     };
-    use massa_network_exports::{AskForBlocksInfo, BlockInfoReply, NodeCommand};
     use massa_network_exports::{NetworkError, NetworkEvent};
+    use massa_time::MassaTime;
     use std::net::IpAddr;
     use tracing::{debug, info};
     macro_rules! evt_failed {
@@ -162,6 +168,94 @@ pub mod event_impl {
         Ok(())
     }
 
+    pub fn on_received_node_metadata(
+        worker: &mut NetworkWorker,
+        from: NodeId,
+        metadata: SignedNodeMetadata,
+    ) {
+        massa_trace!("node_metadata_received", { "node_id": from });
+        // the record must describe the node that sent it
+        if metadata.node_id() != from {
+            debug!("node_id={} sent metadata signed by another node", from);
+            return;
+        }
+        if let Err(err) = metadata.verify() {
+            debug!(
+                "node_id={} sent metadata with an invalid signature: {}",
+                from, err
+            );
+            return;
+        }
+        if let Some((conn_id, _)) = worker.active_nodes.get(&from) {
+            if let Some((ip, _)) = worker.active_connections.get(conn_id) {
+                worker
+                    .peer_info_db
+                    .set_peer_region(*ip, metadata.metadata.region.clone());
+            }
+            worker.node_metadata.insert(from, metadata);
+        }
+    }
+
+    /// Records the result of a probe of `from`. An outgoing connection whose node stopped answering is
+    /// marked degraded so that a replacement is opened, and is closed if the node still does not answer.
+    /// The measured offset of the clock of the node updates the network clock offset.
+    pub async fn on_probe_result(
+        worker: &mut NetworkWorker,
+        from: NodeId,
+        rtt: Option<MassaTime>,
+        clock_offset: Option<i64>,
+    ) {
+        let Some((conn_id, node_command_tx)) = worker.active_nodes.get(&from) else {
+            return;
+        };
+        let max_losses = worker.peer_info_db.network_settings.probe_max_losses;
+        let stats = worker.node_probes.entry(from).or_default();
+        let was_degraded = stats.degraded;
+        stats.record(rtt, clock_offset, max_losses);
+        let stats = *stats;
+        if clock_offset.is_some() {
+            worker.update_clock_offset();
+        }
+        if let Some((ip, true)) = worker.active_connections.get(conn_id) {
+            if stats.degraded != was_degraded {
+                if stats.degraded {
+                    info!(
+                        "node_id={} at {} stopped answering the probes, opening a replacement connection",
+                        from, ip
+                    );
+                } else {
+                    info!("node_id={} at {} answers the probes again", from, ip);
+                }
+                worker
+                    .peer_info_db
+                    .set_out_connection_degraded(ip, stats.degraded);
+            }
+        }
+        if stats.consecutive_losses >= max_losses.saturating_mul(2) {
+            debug!(
+                "node_id={} did not answer {} probes in a row, closing the connection",
+                from, stats.consecutive_losses
+            );
+            let node_command_tx = node_command_tx.clone();
+            if node_command_tx
+                .send(NodeCommand::Close(ConnectionClosureReason::Failed))
+                .await
+                .is_err()
+            {
+                debug!("could not close the connection to node_id={}", from);
+            }
+        }
+    }
+
+    /// Records the latest final slot gossiped by `from` and updates the lag of our final slot behind the network
+    pub fn on_received_final_slot(worker: &mut NetworkWorker, from: NodeId, slot: Slot) {
+        massa_trace!("node_final_slot_received", { "node_id": from, "slot": slot });
+        if worker.active_nodes.contains_key(&from) {
+            worker.node_final_slots.insert(from, slot);
+            worker.update_sync_lag();
+        }
+    }
+
     pub async fn on_asked_peer_list(
         worker: &mut NetworkWorker,
         from: NodeId,
@@ -278,4 +372,25 @@ pub mod event_impl {
             evt_failed!(err)
         }
     }
+
+    pub async fn on_received_endorsement_signatures(
+        worker: &mut NetworkWorker,
+        from: NodeId,
+        signatures: Vec<EndorsementSignature>,
+    ) {
+        massa_trace!(
+            "network_worker.on_node_event receive NetworkEvent::ReceivedEndorsementSignatures",
+            { "signatures": signatures }
+        );
+        if let Err(err) = worker
+            .event
+            .send(NetworkEvent::ReceivedEndorsementSignatures {
+                node: from,
+                signatures,
+            })
+            .await
+        {
+            evt_failed!(err)
+        }
+    }
 }
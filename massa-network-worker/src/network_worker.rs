@@ -6,21 +6,29 @@ use super::{
 };
 use crate::{
     binders::{ReadBinder, WriteBinder},
-    handshake_worker::HandshakeWorker,
+    handshake_worker::{HandshakeNonces, HandshakeWorker},
     messages::{Message, MessageDeserializer},
     network_event::EventSender,
 };
 use futures::{stream::FuturesUnordered, StreamExt};
+use massa_channel::{EventBus, MassaReceiver, MassaSender, Topic};
 use massa_logging::massa_trace;
-use massa_models::{node::NodeId, version::Version};
+use massa_models::{
+    config::NODE_CAPABILITIES,
+    node::NodeId,
+    slot::{Slot, SyncLag},
+    version::{Capabilities, Version},
+};
 use massa_network_exports::{
     ConnectionClosureReason, ConnectionId, Establisher, HandshakeErrorType, Listener,
     NetworkCommand, NetworkConfig, NetworkConnectionErrorType, NetworkError, NetworkEvent,
-    NetworkManagementCommand, NodeCommand, NodeEvent, NodeEventType, ReadHalf, WriteHalf,
+    NetworkManagementCommand, NodeCommand, NodeEvent, NodeEventType, NodeMetadata, ProbeStats,
+    ReadHalf, SignedNodeMetadata, WriteHalf,
 };
 use massa_signature::KeyPair;
+use massa_time::{ClockOffset, MassaTime};
 use std::{
-    collections::{hash_map, HashMap, HashSet},
+    collections::{hash_map, BTreeMap, HashMap, HashSet},
     net::{IpAddr, SocketAddr},
 };
 use tokio::sync::mpsc;
@@ -42,7 +50,7 @@ pub struct NetworkWorker {
     /// Database with peer information.
     pub(crate) peer_info_db: PeerInfoDatabase,
     /// Receiver for network commands
-    controller_command_rx: mpsc::Receiver<NetworkCommand>,
+    controller_command_rx: MassaReceiver<NetworkCommand>,
     /// Receiver for network management commands
     controller_manager_rx: mpsc::Receiver<NetworkManagementCommand>,
     /// Set of connection id of node with running handshake.
@@ -51,6 +59,10 @@ pub struct NetworkWorker {
     handshake_futures: FuturesUnordered<JoinHandle<(ConnectionId, HandshakeReturnType)>>,
     /// Running handshakes that send a list of peers.
     handshake_peer_list_futures: FuturesUnordered<JoinHandle<()>>,
+    /// Random bytes of the handshakes, to detect the replays.
+    handshake_nonces: HandshakeNonces,
+    /// Failed handshakes since the start, by reason.
+    pub(crate) handshake_failures: BTreeMap<String, u64>,
     /// Receiving channel for node events.
     node_event_rx: mpsc::Receiver<NodeEvent>,
     /// Ids of active nodes mapped to Connection id, node command sender and handle on the associated node worker.
@@ -62,14 +74,31 @@ pub struct NetworkWorker {
     pub(crate) active_connections: HashMap<ConnectionId, (IpAddr, bool)>,
     /// Node version
     version: Version,
+    /// Verified metadata advertised by the active nodes
+    pub(crate) node_metadata: HashMap<NodeId, SignedNodeMetadata>,
+    /// Optional features supported by both us and each active node
+    pub(crate) node_capabilities: HashMap<NodeId, Capabilities>,
+    /// Health probes of the active nodes supporting them
+    pub(crate) node_probes: HashMap<NodeId, ProbeStats>,
+    /// Estimate of the network clock offset, median of the offsets of the active nodes
+    pub(crate) clock_offset: ClockOffset,
+    /// Latest final slot of the node, gossiped to the active nodes supporting it
+    local_final_slot: Option<Slot>,
+    /// Latest final slots gossiped by the active nodes
+    pub(crate) node_final_slots: HashMap<NodeId, Slot>,
+    /// Estimate of the lag of our final slot behind the ones of the active nodes
+    pub(crate) sync_lag: SyncLag,
+    /// Event bus of the node, on which the bans are published and from which our final slot is followed
+    pub(crate) event_bus: EventBus,
     /// Event sender
     pub(crate) event: EventSender,
 }
 
 pub struct NetworkWorkerChannels {
-    pub controller_command_rx: mpsc::Receiver<NetworkCommand>,
-    pub controller_event_tx: mpsc::Sender<NetworkEvent>,
+    pub controller_command_rx: MassaReceiver<NetworkCommand>,
+    pub controller_event_tx: MassaSender<NetworkEvent>,
     pub controller_manager_rx: mpsc::Receiver<NetworkManagementCommand>,
+    pub event_bus: EventBus,
 }
 
 impl NetworkWorker {
@@ -83,6 +112,9 @@ impl NetworkWorker {
     /// * `controller_command_rx`: Channel receiving network commands.
     /// * `controller_event_tx`: Channel sending out network events.
     /// * `controller_manager_rx`: Channel receiving network management commands.
+    /// * `event_bus`: Event bus of the node.
+    /// * `clock_offset`: Estimate of the network clock offset, updated with the probes.
+    /// * `sync_lag`: Estimate of the lag of our final slot behind the network, updated with the gossiped final slots.
     #[allow(clippy::too_many_arguments)]
     pub fn new(
         cfg: NetworkConfig,
@@ -94,8 +126,11 @@ impl NetworkWorker {
             controller_command_rx,
             controller_event_tx,
             controller_manager_rx,
+            event_bus,
         }: NetworkWorkerChannels,
         version: Version,
+        clock_offset: ClockOffset,
+        sync_lag: SyncLag,
     ) -> NetworkWorker {
         let self_node_id = NodeId::new(keypair.get_public_key());
 
@@ -115,11 +150,62 @@ impl NetworkWorker {
             running_handshakes: HashSet::new(),
             handshake_futures: FuturesUnordered::new(),
             handshake_peer_list_futures: FuturesUnordered::new(),
+            handshake_nonces: HandshakeNonces::default(),
+            handshake_failures: BTreeMap::new(),
             node_event_rx,
             active_nodes: HashMap::new(),
             node_worker_handles: FuturesUnordered::new(),
             active_connections: HashMap::new(),
             version,
+            node_metadata: HashMap::new(),
+            node_capabilities: HashMap::new(),
+            node_probes: HashMap::new(),
+            clock_offset,
+            local_final_slot: None,
+            node_final_slots: HashMap::new(),
+            sync_lag,
+            event_bus,
+        }
+    }
+
+    /// Sets the network clock offset to the median of the offsets measured with the active nodes
+    pub(crate) fn update_clock_offset(&self) {
+        self.clock_offset.set_median(
+            self.node_probes
+                .values()
+                .filter_map(|stats| stats.clock_offset),
+        );
+    }
+
+    /// Sets the sync lag to the number of slots from our final slot to the median of the final slots of the active nodes
+    pub(crate) fn update_sync_lag(&self) {
+        self.sync_lag.set_median(
+            self.local_final_slot,
+            self.node_final_slots.values().copied(),
+            self.cfg.thread_count,
+        );
+    }
+
+    /// Sends our latest final slot to the active nodes supporting it
+    fn gossip_final_slot(&self) {
+        let Some(slot) = self.local_final_slot else {
+            return;
+        };
+        for (node_id, (_, node_command_tx)) in &self.active_nodes {
+            let supported = self
+                .node_capabilities
+                .get(node_id)
+                .map_or(false, |capabilities| {
+                    capabilities.contains(Capabilities::FINAL_SLOT)
+                });
+            // non-critical: a busy node gets it at the next gossip
+            if supported
+                && node_command_tx
+                    .try_send(NodeCommand::SendFinalSlot(slot))
+                    .is_err()
+            {
+                debug!("could not send our final slot to node_id={}", node_id);
+            }
         }
     }
 
@@ -133,6 +219,20 @@ impl NetworkWorker {
         let mut wakeup_interval = tokio::time::interval(self.cfg.wakeup_interval.to_duration());
         let mut need_connect_retry = true;
 
+        // follow our final slot and gossip it at a regular interval, 0 disabling the gossip
+        let mut final_slot_events = self
+            .event_bus
+            .subscribe(&[Topic::BlockFinalized, Topic::BootstrapFinished]);
+        let gossiping = self.cfg.final_slot_gossip_interval > MassaTime::from_millis(0);
+        let mut final_slot_interval = tokio::time::interval(
+            if gossiping {
+                self.cfg.final_slot_gossip_interval
+            } else {
+                self.cfg.wakeup_interval
+            }
+            .to_duration(),
+        );
+
         loop {
             if need_connect_retry {
                 // try to connect to candidate IPs
@@ -164,6 +264,7 @@ impl NetworkWorker {
                     * node events (HIGH FREQUENCY): we want to process incoming events in priority to know best about the network and empty buffers quickly
                     * incoming commands (HIGH FREQUENCY): we want to TRY to send data to the target, but don't wait if the buffers are full
                     * cleanup tick (less important, low freq)
+                    * final slot events and gossip (low freq, non-critical)
                     * node closed (no worries if processed a bit late)
                     * out connecting events (no problem if a bit late)
                     * listener event (HIGH FREQUENCY) non-critical
@@ -196,6 +297,23 @@ impl NetworkWorker {
                     need_connect_retry = true; // retry out connections
                 }
 
+                // our final slot moved forward
+                Ok(event) = final_slot_events.recv() => {
+                    if let massa_channel::NodeEvent::BlockFinalized { slot, .. }
+                    | massa_channel::NodeEvent::BootstrapFinished { slot, .. } = event
+                    {
+                        if self.local_final_slot.map_or(true, |final_slot| slot > final_slot) {
+                            self.local_final_slot = Some(slot);
+                        }
+                    }
+                }
+
+                // gossip our final slot
+                _ = final_slot_interval.tick(), if gossiping => {
+                    self.update_sync_lag();
+                    self.gossip_final_slot();
+                }
+
                 // wait for a handshake future to complete
                 Some(res) = self.handshake_futures.next() => {
                     let (conn_id, outcome) = res?;
@@ -232,6 +350,12 @@ impl NetworkWorker {
                     let _ = self
                         .event.send(NetworkEvent::ConnectionClosed(node_id))
                         .await;
+                    self.node_metadata.remove(&node_id);
+                    self.node_capabilities.remove(&node_id);
+                    self.node_probes.remove(&node_id);
+                    self.update_clock_offset();
+                    self.node_final_slots.remove(&node_id);
+                    self.update_sync_lag();
                     if let Some((connection_id, _)) = self
                         .active_nodes
                         .remove(&node_id) {
@@ -325,9 +449,15 @@ impl NetworkWorker {
         massa_trace!("network_worker.on_handshake_finished.", {
             "node": new_connection_id
         });
+        if let Err(err) = &outcome {
+            self.count_handshake_failure(match err {
+                NetworkError::HandshakeError(err) => err.reason(),
+                _ => "connection_error",
+            });
+        }
         match outcome {
             // a handshake finished, and succeeded
-            Ok((new_node_id, socket_reader, socket_writer)) => {
+            Ok((new_node_id, socket_reader, socket_writer, capabilities)) => {
                 massa_trace!("handshake_ok", {
                     "connection_id": new_connection_id,
                     "node_id": new_node_id
@@ -388,6 +518,7 @@ impl NetworkWorker {
                             let res = NodeWorker::new(
                                 cfg_copy,
                                 new_node_id,
+                                capabilities,
                                 socket_reader,
                                 socket_writer,
                                 node_worker_command_tx,
@@ -399,12 +530,34 @@ impl NetworkWorker {
                             (new_node_id, res)
                         });
                         entry.insert((new_connection_id, node_command_tx.clone()));
+                        self.node_capabilities.insert(new_node_id, capabilities);
                         self.node_worker_handles.push(node_fn_handle);
 
-                        let res = self
+                        let mut res = self
                             .event
                             .send(NetworkEvent::NewConnection(new_node_id))
                             .await;
+                        if res.is_ok() {
+                            res = self
+                                .event
+                                .send(NetworkEvent::NodeCapabilities {
+                                    node: new_node_id,
+                                    capabilities,
+                                })
+                                .await;
+                        }
+
+                        // advertise our metadata to the new node
+                        if res.is_ok() && self.cfg.publish_node_metadata {
+                            match self.sign_node_metadata() {
+                                Ok(metadata) => {
+                                    let _ = node_command_tx
+                                        .send(NodeCommand::SendNodeMetadata(metadata))
+                                        .await;
+                                }
+                                Err(err) => warn!("could not sign our node metadata: {}", err),
+                            }
+                        }
 
                         // If we failed to send the event to protocol, close the connection.
                         if res.is_err() {
@@ -446,6 +599,14 @@ impl NetworkWorker {
         Ok(())
     }
 
+    /// Counts a failed handshake in the network stats
+    fn count_handshake_failure(&mut self, reason: &str) {
+        *self
+            .handshake_failures
+            .entry(reason.to_string())
+            .or_default() += 1;
+    }
+
     async fn connection_closed(
         &mut self,
         id: ConnectionId,
@@ -528,11 +689,19 @@ impl NetworkWorker {
             NetworkCommand::SendEndorsements { node, endorsements } => {
                 on_send_endorsements_cmd(self, node, endorsements).await
             }
+            NetworkCommand::SendEndorsementSignatures { node, signatures } => {
+                on_send_endorsement_signatures_cmd(self, node, signatures).await
+            }
             NetworkCommand::NodeSignMessage { msg, response_tx } => {
                 on_node_sign_message_cmd(self, msg, response_tx).await?
             }
             NetworkCommand::NodeUnbanByIds(ids) => on_node_unban_by_ids_cmd(self, ids).await?,
             NetworkCommand::NodeUnbanByIps(ips) => on_node_unban_by_ips_cmd(self, ips).await?,
+            NetworkCommand::NodeBanSubnet(entry) => on_node_ban_subnet_cmd(self, entry).await?,
+            NetworkCommand::NodeUnbanSubnets(subnets) => {
+                on_node_unban_subnets_cmd(self, subnets).await?
+            }
+            NetworkCommand::GetBans(response_tx) => on_get_bans_cmd(self, response_tx).await,
             NetworkCommand::GetStats { response_tx } => on_get_stats_cmd(self, response_tx).await,
             NetworkCommand::Whitelist(ips) => on_whitelist_cmd(self, ips).await?,
             NetworkCommand::RemoveFromWhitelist(ips) => {
@@ -615,6 +784,21 @@ impl NetworkWorker {
     ) -> Result<(), NetworkError> {
         match res {
             Ok((reader, writer, remote_addr)) => {
+                // half-open handshakes must not exhaust the inbound connections
+                let running_in_handshakes = self
+                    .running_handshakes
+                    .iter()
+                    .filter(|id| matches!(self.active_connections.get(id), Some((_, false))))
+                    .count();
+                if running_in_handshakes >= self.cfg.max_in_handshakes {
+                    debug!(
+                        "inbound connection from addr={} dropped: too many running handshakes",
+                        remote_addr
+                    );
+                    massa_trace!("in_connection_refused", {"ip": remote_addr.ip()});
+                    self.count_handshake_failure("too_many_handshakes");
+                    return Ok(());
+                }
                 match self.peer_info_db.try_new_in_connection(&remote_addr.ip()) {
                     Ok(_) => {
                         let connection_id = *cur_connection_id;
@@ -632,7 +816,8 @@ impl NetworkWorker {
                         self.manage_successful_connection(connection_id, reader, writer)?;
                     }
                     Err(NetworkError::PeerConnectionError(
-                        NetworkConnectionErrorType::MaxPeersConnectionReached(_),
+                        NetworkConnectionErrorType::MaxPeersConnectionReached(_)
+                        | NetworkConnectionErrorType::MaxPeersPerSubnetReached(_),
                     )) => self.try_send_peer_list_in_handshake(reader, writer, remote_addr),
                     Err(_) => {
                         debug!("inbound connection from addr={} refused", remote_addr);
@@ -767,7 +952,10 @@ impl NetworkWorker {
             self.self_node_id,
             self.keypair.clone(),
             self.cfg.connect_timeout,
+            self.cfg.handshake_phase_timeout,
+            self.handshake_nonces.clone(),
             self.version,
+            NODE_CAPABILITIES,
             connection_id,
             self.cfg.max_bytes_read,
             self.cfg.max_bytes_write,
@@ -775,6 +963,18 @@ impl NetworkWorker {
         Ok(())
     }
 
+    /// Signs the metadata describing our node, as configured in `cfg`
+    fn sign_node_metadata(&self) -> Result<SignedNodeMetadata, NetworkError> {
+        let metadata = NodeMetadata {
+            operator_contact: self.cfg.operator_contact.clone(),
+            region: self.cfg.region.clone(),
+            version: self.version,
+            capabilities: self.cfg.node_capabilities.clone(),
+            timestamp: MassaTime::now()?,
+        };
+        SignedNodeMetadata::new(metadata, &self.keypair)
+    }
+
     /// Manages node events.
     /// Only used by the worker.
     ///
@@ -805,12 +1005,24 @@ impl NetworkWorker {
             NodeEvent(node, NodeEventType::ReceivedEndorsements(endorsements)) => {
                 event_impl::on_received_endorsements(self, node, endorsements).await
             }
+            NodeEvent(node, NodeEventType::ReceivedEndorsementSignatures(signatures)) => {
+                event_impl::on_received_endorsement_signatures(self, node, signatures).await
+            }
             NodeEvent(node, NodeEventType::ReceivedOperationAnnouncements(operation_ids)) => {
                 event_impl::on_received_operations_annoncement(self, node, operation_ids).await
             }
             NodeEvent(node, NodeEventType::ReceivedAskForOperations(operation_ids)) => {
                 event_impl::on_received_ask_for_operations(self, node, operation_ids).await
             }
+            NodeEvent(node, NodeEventType::ReceivedNodeMetadata(metadata)) => {
+                event_impl::on_received_node_metadata(self, node, metadata)
+            }
+            NodeEvent(node, NodeEventType::ProbeResult(rtt, clock_offset)) => {
+                event_impl::on_probe_result(self, node, rtt, clock_offset).await
+            }
+            NodeEvent(node, NodeEventType::ReceivedFinalSlot(slot)) => {
+                event_impl::on_received_final_slot(self, node, slot)
+            }
         }
         Ok(())
     }
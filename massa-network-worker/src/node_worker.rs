@@ -4,9 +4,11 @@ use super::{
     binders::{ReadBinder, WriteBinder},
     messages::Message,
 };
+#[cfg(feature = "chaos")]
+use crate::chaos::{ChaosDirection, ChaosFilter};
 use itertools::Itertools;
 use massa_logging::massa_trace;
-use massa_models::{node::NodeId, secure_share::Id};
+use massa_models::{node::NodeId, secure_share::Id, version::Capabilities};
 use massa_network_exports::{
     ConnectionClosureReason, NetworkConfig, NetworkError, NodeCommand, NodeEvent, NodeEventType,
 };
@@ -14,7 +16,7 @@ use massa_time::MassaTime;
 use tokio::{
     sync::mpsc,
     sync::mpsc::{error::SendTimeoutError, Sender},
-    time::timeout,
+    time::{timeout, Instant},
 };
 use tracing::{debug, trace, warn};
 
@@ -25,6 +27,8 @@ pub struct NodeWorker {
     cfg: NetworkConfig,
     /// Node id associated to that worker.
     node_id: NodeId,
+    /// Optional features supported by both us and the node.
+    capabilities: Capabilities,
     /// Reader for incoming data.
     socket_reader: ReadBinder,
     /// Optional writer to send data.
@@ -43,6 +47,7 @@ impl NodeWorker {
     /// # Arguments
     /// * `cfg`: Network configuration.
     /// * `node_id`: Node id associated to that worker.
+    /// * `capabilities`: Optional features supported by both us and the node.
     /// * `socket_reader`: Reader for incoming data.
     /// * `socket_writer`: Writer for sending data.
     /// * `node_command_rx`: Channel to receive node commands.
@@ -51,6 +56,7 @@ impl NodeWorker {
     pub fn new(
         cfg: NetworkConfig,
         node_id: NodeId,
+        capabilities: Capabilities,
         socket_reader: ReadBinder,
         socket_writer: WriteBinder,
         node_command_tx: mpsc::Sender<NodeCommand>,
@@ -60,6 +66,7 @@ impl NodeWorker {
         NodeWorker {
             cfg,
             node_id,
+            capabilities,
             socket_reader,
             socket_writer_opt: Some(socket_writer),
             node_command_tx,
@@ -76,6 +83,17 @@ impl NodeWorker {
             )
         })?;
 
+        // nodes that cannot handle endorsement batches receive them one by one
+        let max_endorsements_per_message = if self
+            .capabilities
+            .contains(Capabilities::BATCHED_ENDORSEMENTS)
+        {
+            self.cfg.max_endorsements_per_message
+        } else {
+            1
+        };
+        // the nodes supporting it receive our time in the pongs
+        let timed_pongs = self.capabilities.contains(Capabilities::CLOCK_SYNC);
         let node_writer_handle = tokio::spawn(async move {
             node_writer_handle(
                 &mut socket_writer,
@@ -84,17 +102,24 @@ impl NodeWorker {
                 self.node_id,
                 self.cfg.max_ask_blocks,
                 self.cfg.max_operations_per_message,
-                self.cfg.max_endorsements_per_message,
+                max_endorsements_per_message,
+                timed_pongs,
             )
             .await
         });
         tokio::pin!(node_writer_handle);
         let mut writer_joined = false;
 
+        // the reader answers the pings and forwards the nonces of the pongs, with the time of the node if given
+        let reader_command_tx = self.node_command_tx.clone();
+        let (pong_tx, mut pong_rx) = mpsc::channel::<(u64, Option<MassaTime>)>(1);
+        let mut probe_event_tx = self.node_event_tx.clone();
         let node_reader_handle = tokio::spawn(async move {
             node_reader_handle(
                 &mut self.socket_reader,
                 &mut self.node_event_tx,
+                &reader_command_tx,
+                &pong_tx,
                 self.node_id,
                 self.cfg.max_send_wait_node_event,
             )
@@ -105,6 +130,20 @@ impl NodeWorker {
 
         let mut ask_peer_list_interval =
             tokio::time::interval(self.cfg.ask_peer_list_interval.to_duration());
+        // only the nodes supporting it are probed
+        let probing = self.capabilities.contains(Capabilities::PING)
+            && self.cfg.probe_interval > MassaTime::from_millis(0);
+        let mut probe_interval = tokio::time::interval(
+            if probing {
+                self.cfg.probe_interval
+            } else {
+                self.cfg.ask_peer_list_interval
+            }
+            .to_duration(),
+        );
+        // nonce, send instant and send time of the probe waiting for its pong
+        let mut pending_probe: Option<(u64, Instant, Option<MassaTime>)> = None;
+        let mut next_probe_nonce: u64 = 0;
         let mut exit_reason = ConnectionClosureReason::Normal;
         let mut _exit_reason_reader = ConnectionClosureReason::Normal;
 
@@ -117,6 +156,7 @@ impl NodeWorker {
                     * incoming socket data (high frequency): forward incoming data in priority to avoid contention
                     * node commands (high frequency): try to send, fail on contention
                     * ask peers: low frequency, non-critical
+                    * probes: low frequency, non-critical
             */
             tokio::select! {
                 res = &mut node_writer_handle => {
@@ -157,6 +197,37 @@ impl NodeWorker {
 
                     trace!("after sending Message::AskPeerList from writer_command_tx in node_worker run_loop");
                 }
+                _ = probe_interval.tick(), if probing => {
+                    if pending_probe.take().is_some() {
+                        // not answered before the next one
+                        let event = NodeEvent(self.node_id, NodeEventType::ProbeResult(None, None));
+                        send_node_event(&mut probe_event_tx, event, self.cfg.max_send_wait_node_event).await;
+                    }
+                    let nonce = next_probe_nonce;
+                    next_probe_nonce = next_probe_nonce.wrapping_add(1);
+                    massa_trace!("node_worker.run_loop.select.timer send Message::Ping", {"node": self.node_id, "nonce": nonce});
+                    if let Err(e) = self.node_command_tx.send(NodeCommand::SendPing(nonce)).await {
+                        debug!("Node worker {}: unable to send ping: {}", self.node_id, e);
+                        break 'select_loop;
+                    }
+                    pending_probe = Some((nonce, Instant::now(), MassaTime::now().ok()));
+                }
+                Some((nonce, node_time)) = pong_rx.recv() => {
+                    if let Some((pending_nonce, sent, sent_time)) = pending_probe {
+                        if pending_nonce == nonce {
+                            pending_probe = None;
+                            let rtt = MassaTime::from_millis(sent.elapsed().as_millis() as u64);
+                            // the pong is assumed to be sent in the middle of the round trip
+                            let clock_offset = node_time.zip(sent_time).map(|(node_time, sent_time)| {
+                                node_time.to_millis() as i64
+                                    - sent_time.to_millis() as i64
+                                    - (rtt.to_millis() / 2) as i64
+                            });
+                            let event = NodeEvent(self.node_id, NodeEventType::ProbeResult(Some(rtt), clock_offset));
+                            send_node_event(&mut probe_event_tx, event, self.cfg.max_send_wait_node_event).await;
+                        }
+                    }
+                }
             }
         }
 
@@ -184,6 +255,7 @@ impl NodeWorker {
 }
 
 /// Handle incoming node command, convert to message(s) and write that to socket
+#[allow(clippy::too_many_arguments)]
 async fn node_writer_handle(
     socket_writer: &mut WriteBinder,
     node_command_rx: &mut mpsc::Receiver<NodeCommand>,
@@ -192,8 +264,11 @@ async fn node_writer_handle(
     max_ask_blocks: u32,
     max_operations_per_message: u32,
     max_endorsements_per_message: u32,
+    timed_pongs: bool,
 ) -> ConnectionClosureReason {
     let mut exit_reason = ConnectionClosureReason::Normal;
+    #[cfg(feature = "chaos")]
+    let mut chaos_filter = ChaosFilter::new(node_id, ChaosDirection::Outgoing);
 
     'writer_loop: loop {
         let messages_: Option<Vec<Message>> = match node_command_rx.recv().await {
@@ -270,6 +345,30 @@ async fn node_writer_handle(
                 Some(messages)
             }
             Some(NodeCommand::AskPeerList) => Some(vec![Message::AskPeerList]),
+            Some(NodeCommand::SendNodeMetadata(metadata)) => {
+                massa_trace!("node_worker.run_loop. send Message::NodeMetadata", {
+                    "node": node_id
+                });
+                Some(vec![Message::NodeMetadata(metadata)])
+            }
+            Some(NodeCommand::SendPing(nonce)) => Some(vec![Message::Ping(nonce)]),
+            Some(NodeCommand::SendPong(nonce)) => match MassaTime::now() {
+                // stamped as late as possible for a better estimate of the clock offset
+                Ok(time) if timed_pongs => Some(vec![Message::TimedPong { nonce, time }]),
+                _ => Some(vec![Message::Pong(nonce)]),
+            },
+            Some(NodeCommand::SendFinalSlot(slot)) => {
+                massa_trace!("node_worker.run_loop. send Message::FinalSlot", {"node": node_id, "slot": slot});
+                Some(vec![Message::FinalSlot(slot)])
+            }
+            Some(NodeCommand::SendEndorsementSignatures(signatures)) => {
+                massa_trace!("node_worker.run_loop. send Message::EndorsementSignatures", {"node": node_id, "signatures": signatures});
+                let messages = signatures
+                    .chunks(max_endorsements_per_message as usize)
+                    .map(|signatures| Message::EndorsementSignatures(signatures.to_vec()))
+                    .collect();
+                Some(messages)
+            }
             None => {
                 // Note: this should never happen,
                 // since it implies the network worker dropped its node command sender
@@ -284,6 +383,8 @@ async fn node_writer_handle(
         }
         // safe to unwrap here
         let messages = messages_.unwrap();
+        #[cfg(feature = "chaos")]
+        let messages = chaos_filter.apply(messages).await;
 
         for msg in messages.iter() {
             match timeout(write_timeout.to_duration(), socket_writer.send(msg)).await {
@@ -319,10 +420,14 @@ async fn node_writer_handle(
 async fn node_reader_handle(
     socket_reader: &mut ReadBinder,
     node_event_tx: &mut Sender<NodeEvent>,
+    node_command_tx: &Sender<NodeCommand>,
+    pong_tx: &Sender<(u64, Option<MassaTime>)>,
     node_id: NodeId,
     max_send_wait: MassaTime,
 ) -> ConnectionClosureReason {
     let mut exit_reason = ConnectionClosureReason::Normal;
+    #[cfg(feature = "chaos")]
+    let mut chaos_filter = ChaosFilter::new(node_id, ChaosDirection::Incoming);
 
     loop {
         match socket_reader.next().await {
@@ -330,75 +435,28 @@ async fn node_reader_handle(
                 massa_trace!("node_worker.run_loop. receive self.socket_reader.next()", {
                     "index": index
                 });
-                match msg {
-                    Message::BlockHeader(header) => {
-                        massa_trace!(
-                            "node_worker.run_loop. receive Message::BlockHeader",
-                            {"block_id": header.id.get_hash(), "header": header, "node": node_id}
-                        );
-                        let event = NodeEvent(node_id, NodeEventType::ReceivedBlockHeader(header));
-                        send_node_event(node_event_tx, event, max_send_wait).await
-                    }
-                    Message::AskForBlocks(list) => {
-                        massa_trace!("node_worker.run_loop. receive Message::AskForBlocks", {"hashlist": list, "node": node_id});
-                        let event = NodeEvent(node_id, NodeEventType::ReceivedAskForBlocks(list));
-                        send_node_event(node_event_tx, event, max_send_wait).await
-                    }
-                    Message::ReplyForBlocks(list) => {
-                        massa_trace!("node_worker.run_loop. receive Message::AskForBlocks", {"hashlist": list, "node": node_id});
-                        let event = NodeEvent(node_id, NodeEventType::ReceivedReplyForBlocks(list));
-                        send_node_event(node_event_tx, event, max_send_wait).await
-                    }
-                    Message::PeerList(pl) => {
-                        massa_trace!("node_worker.run_loop. receive Message::PeerList", {"peerlist": pl, "node": node_id});
-                        let event = NodeEvent(node_id, NodeEventType::ReceivedPeerList(pl));
-                        send_node_event(node_event_tx, event, max_send_wait).await
-                    }
-                    Message::AskPeerList => {
-                        let event = NodeEvent(node_id, NodeEventType::AskedPeerList);
-                        send_node_event(node_event_tx, event, max_send_wait).await
-                    }
-                    Message::Operations(operations) => {
-                        massa_trace!(
-                            "node_worker.run_loop. receive Message::Operations: ",
-                            {"node": node_id, "operations": operations}
-                        );
-                        //massa_trace!("node_worker.run_loop. receive Message::Operations", {"node": self.node_id, "operations": operations});
-                        let event =
-                            NodeEvent(node_id, NodeEventType::ReceivedOperations(operations));
-                        send_node_event(node_event_tx, event, max_send_wait).await
-                    }
-                    Message::AskForOperations(operation_prefix_ids) => {
-                        massa_trace!(
-                            "node_worker.run_loop. receive Message::AskForOperations: ",
-                            {"node": node_id, "operation_ids": operation_prefix_ids}
-                        );
-                        //massa_trace!("node_worker.run_loop. receive Message::AskForOperations", {"node": self.node_id, "operations": operation_ids});
-                        let event = NodeEvent(
-                            node_id,
-                            NodeEventType::ReceivedAskForOperations(operation_prefix_ids),
-                        );
-                        send_node_event(node_event_tx, event, max_send_wait).await
-                    }
-                    Message::OperationsAnnouncement(operation_prefix_ids) => {
-                        massa_trace!("node_worker.run_loop. receive Message::OperationsBatch", {"node": node_id, "operation_prefix_ids": operation_prefix_ids});
-                        let event = NodeEvent(
-                            node_id,
-                            NodeEventType::ReceivedOperationAnnouncements(operation_prefix_ids),
-                        );
-                        send_node_event(node_event_tx, event, max_send_wait).await
-                    }
-                    Message::Endorsements(endorsements) => {
-                        massa_trace!("node_worker.run_loop. receive Message::Endorsement", {"node": node_id, "endorsements": endorsements});
-                        let event =
-                            NodeEvent(node_id, NodeEventType::ReceivedEndorsements(endorsements));
-                        send_node_event(node_event_tx, event, max_send_wait).await
-                    }
-                    _ => {
-                        // TODO: Write a more user-friendly warning/logout after several consecutive fails? see #1082
-                        massa_trace!("node_worker.run_loop.self.socket_reader.next(). Unexpected message Warning", {});
-                    }
+                #[cfg(feature = "chaos")]
+                for msg in chaos_filter.apply(vec![msg]).await {
+                    forward_message(
+                        msg,
+                        node_event_tx,
+                        node_command_tx,
+                        pong_tx,
+                        node_id,
+                        max_send_wait,
+                    )
+                    .await;
                 }
+                #[cfg(not(feature = "chaos"))]
+                forward_message(
+                    msg,
+                    node_event_tx,
+                    node_command_tx,
+                    pong_tx,
+                    node_id,
+                    max_send_wait,
+                )
+                .await;
             }
             Ok(None) => {
                 massa_trace!(
@@ -422,6 +480,127 @@ async fn node_reader_handle(
     exit_reason
 }
 
+/// Convert a message received from a node into a node event and send it.
+/// Pings are answered through `node_command_tx` and the nonces of the pongs are sent to `pong_tx`,
+/// along with the time of the node for the timed ones.
+async fn forward_message(
+    msg: Message,
+    node_event_tx: &mut Sender<NodeEvent>,
+    node_command_tx: &Sender<NodeCommand>,
+    pong_tx: &Sender<(u64, Option<MassaTime>)>,
+    node_id: NodeId,
+    max_send_wait: MassaTime,
+) {
+    match msg {
+        Message::BlockHeader(header) => {
+            massa_trace!(
+                "node_worker.run_loop. receive Message::BlockHeader",
+                {"block_id": header.id.get_hash(), "header": header, "node": node_id}
+            );
+            let event = NodeEvent(node_id, NodeEventType::ReceivedBlockHeader(header));
+            send_node_event(node_event_tx, event, max_send_wait).await
+        }
+        Message::AskForBlocks(list) => {
+            massa_trace!("node_worker.run_loop. receive Message::AskForBlocks", {"hashlist": list, "node": node_id});
+            let event = NodeEvent(node_id, NodeEventType::ReceivedAskForBlocks(list));
+            send_node_event(node_event_tx, event, max_send_wait).await
+        }
+        Message::ReplyForBlocks(list) => {
+            massa_trace!("node_worker.run_loop. receive Message::AskForBlocks", {"hashlist": list, "node": node_id});
+            let event = NodeEvent(node_id, NodeEventType::ReceivedReplyForBlocks(list));
+            send_node_event(node_event_tx, event, max_send_wait).await
+        }
+        Message::PeerList(pl) => {
+            massa_trace!("node_worker.run_loop. receive Message::PeerList", {"peerlist": pl, "node": node_id});
+            let event = NodeEvent(node_id, NodeEventType::ReceivedPeerList(pl));
+            send_node_event(node_event_tx, event, max_send_wait).await
+        }
+        Message::AskPeerList => {
+            let event = NodeEvent(node_id, NodeEventType::AskedPeerList);
+            send_node_event(node_event_tx, event, max_send_wait).await
+        }
+        Message::Operations(operations) => {
+            massa_trace!(
+                "node_worker.run_loop. receive Message::Operations: ",
+                {"node": node_id, "operations": operations}
+            );
+            //massa_trace!("node_worker.run_loop. receive Message::Operations", {"node": self.node_id, "operations": operations});
+            let event = NodeEvent(node_id, NodeEventType::ReceivedOperations(operations));
+            send_node_event(node_event_tx, event, max_send_wait).await
+        }
+        Message::AskForOperations(operation_prefix_ids) => {
+            massa_trace!(
+                "node_worker.run_loop. receive Message::AskForOperations: ",
+                {"node": node_id, "operation_ids": operation_prefix_ids}
+            );
+            //massa_trace!("node_worker.run_loop. receive Message::AskForOperations", {"node": self.node_id, "operations": operation_ids});
+            let event = NodeEvent(
+                node_id,
+                NodeEventType::ReceivedAskForOperations(operation_prefix_ids),
+            );
+            send_node_event(node_event_tx, event, max_send_wait).await
+        }
+        Message::OperationsAnnouncement(operation_prefix_ids) => {
+            massa_trace!("node_worker.run_loop. receive Message::OperationsBatch", {"node": node_id, "operation_prefix_ids": operation_prefix_ids});
+            let event = NodeEvent(
+                node_id,
+                NodeEventType::ReceivedOperationAnnouncements(operation_prefix_ids),
+            );
+            send_node_event(node_event_tx, event, max_send_wait).await
+        }
+        Message::Endorsements(endorsements) => {
+            massa_trace!("node_worker.run_loop. receive Message::Endorsement", {"node": node_id, "endorsements": endorsements});
+            let event = NodeEvent(node_id, NodeEventType::ReceivedEndorsements(endorsements));
+            send_node_event(node_event_tx, event, max_send_wait).await
+        }
+        Message::NodeMetadata(metadata) => {
+            massa_trace!("node_worker.run_loop. receive Message::NodeMetadata", {
+                "node": node_id
+            });
+            let event = NodeEvent(node_id, NodeEventType::ReceivedNodeMetadata(metadata));
+            send_node_event(node_event_tx, event, max_send_wait).await
+        }
+        Message::Ping(nonce) => {
+            massa_trace!("node_worker.run_loop. receive Message::Ping", {"node": node_id, "nonce": nonce});
+            // never block the reading on a busy writer: a missing pong only counts as a lost probe
+            if node_command_tx
+                .try_send(NodeCommand::SendPong(nonce))
+                .is_err()
+            {
+                debug!("Node worker {}: unable to answer a ping", node_id);
+            }
+        }
+        Message::Pong(nonce) => {
+            massa_trace!("node_worker.run_loop. receive Message::Pong", {"node": node_id, "nonce": nonce});
+            let _ = pong_tx.try_send((nonce, None));
+        }
+        Message::TimedPong { nonce, time } => {
+            massa_trace!("node_worker.run_loop. receive Message::TimedPong", {"node": node_id, "nonce": nonce});
+            let _ = pong_tx.try_send((nonce, Some(time)));
+        }
+        Message::FinalSlot(slot) => {
+            massa_trace!("node_worker.run_loop. receive Message::FinalSlot", {"node": node_id, "slot": slot});
+            let event = NodeEvent(node_id, NodeEventType::ReceivedFinalSlot(slot));
+            send_node_event(node_event_tx, event, max_send_wait).await
+        }
+        Message::EndorsementSignatures(signatures) => {
+            massa_trace!("node_worker.run_loop. receive Message::EndorsementSignatures", {"node": node_id, "signatures": signatures});
+            let event = NodeEvent(
+                node_id,
+                NodeEventType::ReceivedEndorsementSignatures(signatures),
+            );
+            send_node_event(node_event_tx, event, max_send_wait).await
+        }
+        _ => {
+            // TODO: Write a more user-friendly warning/logout after several consecutive fails? see #1082
+            massa_trace!(
+                "node_worker.run_loop.self.socket_reader.next(). Unexpected message Warning",
+                {}
+            );
+        }
+    }
+}
+
 /// Send a node event
 // via node_event_tx queue - used by 'node_reader_handle'
 async fn send_node_event(
@@ -1,19 +1,22 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use crate::ban_list::BanList;
 use enum_map::EnumMap;
 use itertools::Itertools;
 use massa_logging::massa_trace;
 use massa_network_exports::settings::PeerTypeConnectionConfig;
 use massa_network_exports::ConnectionCount;
+use massa_network_exports::IpSubnet;
 use massa_network_exports::NetworkConfig;
 use massa_network_exports::NetworkConnectionErrorType;
 use massa_network_exports::NetworkError;
+use massa_network_exports::PeerDistribution;
 use massa_network_exports::PeerInfo;
 use massa_network_exports::PeerType;
 use massa_time::MassaTime;
 use serde_json::json;
 use std::cmp::Reverse;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::net::IpAddr;
 use std::path::Path;
 use tokio::sync::watch;
@@ -34,6 +37,12 @@ pub struct PeerInfoDatabase {
     pub(crate) peer_types_connection_count: EnumMap<PeerType, ConnectionCount>,
     /// Every `wakeup_interval` we try to establish a connection with known inactive peers
     pub(crate) wakeup_interval: MassaTime,
+    /// Bans of IP ranges set by the operator, kept across restarts
+    pub(crate) ban_list: BanList,
+    /// Regions advertised in the metadata of the peers we met, kept in memory only
+    pub(crate) peer_regions: HashMap<IpAddr, String>,
+    /// Peers whose outgoing connection stopped answering the probes
+    pub(crate) degraded_peers: HashSet<IpAddr>,
 }
 
 /// Saves advertised and non standard peers to a file.
@@ -95,7 +104,7 @@ pub(crate) fn cleanup_peers(
                     p.advertised = true;
                     return false;
                 }
-                if !ip.is_global() {
+                if !cfg.is_peer_ip(&ip) {
                     // avoid non-global IPs
                     return false;
                 }
@@ -122,7 +131,7 @@ pub(crate) fn cleanup_peers(
     let mut banned_peers: Vec<PeerInfo> = Vec::new();
     let mut idle_peers: Vec<PeerInfo> = Vec::new();
     for (ip, p) in peers.drain() {
-        if !ip.is_global() {
+        if !cfg.is_peer_ip(&ip) {
             // avoid non-global IPs
             continue;
         }
@@ -239,6 +248,9 @@ impl PeerInfoDatabase {
             saver_watch_tx,
             wakeup_interval,
             peer_types_connection_count: EnumMap::default(),
+            ban_list: BanList::load(&cfg.ban_list_file),
+            peer_regions: HashMap::new(),
+            degraded_peers: HashSet::new(),
         })
     }
 
@@ -294,6 +306,8 @@ impl PeerInfoDatabase {
             None,
             self.network_settings.ban_timeout,
         )?;
+        self.peer_regions
+            .retain(|ip, _| self.peers.contains_key(ip));
         Ok(())
     }
 
@@ -332,6 +346,8 @@ impl PeerInfoDatabase {
 
     /// Unban a list of ip
     pub fn unban(&mut self, ips: Vec<IpAddr>) -> Result<(), NetworkError> {
+        let subnets: Vec<IpSubnet> = ips.iter().map(|ip| IpSubnet::host(*ip)).collect();
+        self.ban_list.unban(&subnets);
         let mut update_happened = false;
         for ip in ips.into_iter() {
             let ip = ip.to_canonical();
@@ -425,7 +441,7 @@ impl PeerInfoDatabase {
     /// `ip`: `IpAddr` we are now connected to
     pub fn new_out_connection_attempt(&mut self, ip: &IpAddr) -> Result<(), NetworkError> {
         let ip = ip.to_canonical();
-        if !ip.is_global() {
+        if !self.network_settings.is_peer_ip(&ip) {
             return Err(NetworkError::InvalidIpError(ip));
         }
         let peer_type = if let Some(peer) = self.peers.get(&ip) {
@@ -546,6 +562,10 @@ impl PeerInfoDatabase {
             }
             peer_type
         };
+        if self.degraded_peers.remove(&ip) {
+            let count = &mut self.peer_types_connection_count[peer_type];
+            count.degraded_out_connections = count.degraded_out_connections.saturating_sub(1);
+        }
         self.decrease_global_active_out_connection_count(peer_type, &ip)?;
         Ok(())
     }
@@ -616,6 +636,7 @@ impl PeerInfoDatabase {
 
         self.decrease_global_active_out_connection_attempt_count(peer_type, &ip)?;
 
+        let subnet_banned = self.ban_list.is_banned(&ip);
         let peer_type = {
             let peer = self.peers.get(&ip).ok_or_else(|| {
                 NetworkError::PeerConnectionError(
@@ -635,7 +656,7 @@ impl PeerInfoDatabase {
             peer.active_out_connection_attempts -= 1;
             peer.advertised = true; // we just connected to it. Assume advertised.
 
-            if peer.banned {
+            if peer.banned || subnet_banned {
                 peer.last_failure = Some(MassaTime::now()?);
                 if !peer.is_active() && peer.peer_type == Default::default() {
                     self.update()?;
@@ -700,7 +721,9 @@ impl PeerInfoDatabase {
     pub fn try_new_in_connection(&mut self, ip: &IpAddr) -> Result<(), NetworkError> {
         let ip = ip.to_canonical();
         // try to create a new input connection, return false if no slots
-        if !ip.is_global() || self.network_settings.max_in_connections_per_ip == 0 {
+        if !self.network_settings.is_peer_ip(&ip)
+            || self.network_settings.max_in_connections_per_ip == 0
+        {
             return Err(NetworkError::PeerConnectionError(
                 NetworkConnectionErrorType::MaxPeersConnectionReached(ip),
             ));
@@ -728,6 +751,8 @@ impl PeerInfoDatabase {
             ));
         }
 
+        let subnet_banned = self.ban_list.is_banned(&ip);
+        let subnet_full = peer_type == PeerType::Standard && self.is_subnet_full(&ip);
         let peer_type = {
             let peer = self.peers.get_mut(&ip).ok_or_else(|| {
                 NetworkError::PeerConnectionError(
@@ -736,13 +761,19 @@ impl PeerInfoDatabase {
             })?; // peer was inserted just before
 
             // is there a attempt slot available
-            if peer.banned {
+            if peer.banned || subnet_banned {
                 massa_trace!("in_connection_refused_peer_banned", {"ip": peer.ip});
                 peer.last_failure = Some(MassaTime::now()?);
                 self.request_dump()?;
                 return Err(NetworkError::PeerConnectionError(
                     NetworkConnectionErrorType::BannedPeerTryingToConnect(ip),
                 ));
+            } else if subnet_full {
+                massa_trace!("in_connection_refused_subnet_full", {"ip": peer.ip});
+                self.request_dump()?;
+                return Err(NetworkError::PeerConnectionError(
+                    NetworkConnectionErrorType::MaxPeersPerSubnetReached(ip),
+                ));
             } else if peer.active_in_connections >= self.network_settings.max_in_connections_per_ip
             {
                 self.request_dump()?;
@@ -794,7 +825,7 @@ impl PeerInfoDatabase {
         let mut sorted_peers: Vec<PeerInfo> = self
             .peers
             .values()
-            .filter(|&p| (p.advertised && !p.banned))
+            .filter(|&p| p.advertised && !p.banned && !self.ban_list.is_banned(&p.ip))
             .copied()
             .collect();
         sorted_peers.sort_unstable_by_key(|&p| (std::cmp::Reverse(p.last_alive), p.last_failure));
@@ -821,7 +852,7 @@ impl PeerInfoDatabase {
     }
 
     fn is_target_out_connection_count_reached(&self, peer_type: PeerType) -> bool {
-        self.peer_types_connection_count[peer_type].active_out_connections
+        self.peer_types_connection_count[peer_type].get_healthy_out_connections()
             >= self.network_settings.peer_types_config[peer_type].target_out_connections
     }
 
@@ -847,19 +878,187 @@ impl PeerInfoDatabase {
         let available_slots = count.get_available_out_connection_attempts(cfg);
         let now = MassaTime::now()?;
         let f = move |p: &&PeerInfo| {
-            if p.peer_type != peer_type || !p.advertised || p.is_active() || p.banned {
+            if p.peer_type != peer_type
+                || !p.advertised
+                || p.is_active()
+                || p.banned
+                || self.ban_list.is_banned(&p.ip)
+            {
                 return false;
             }
             p.is_peer_ready(self.wakeup_interval, now)
         };
-        let mut res: Vec<_> = self
+        let diversity = &self.network_settings.peer_diversity;
+        if peer_type != PeerType::Standard
+            || (diversity.max_peers_per_subnet == 0 && !diversity.prefer_distinct_regions)
+        {
+            let mut res: Vec<_> = self
+                .peers
+                .values()
+                .filter(f)
+                .take(available_slots)
+                .collect();
+            res.sort_unstable_by_key(|&p| (p.last_failure, std::cmp::Reverse(p.last_alive)));
+            return Ok(res.into_iter().map(|p| p.ip).collect());
+        }
+
+        // prefer the least connected ranges, then the regions we are not connected to yet
+        let mut subnet_counts = self.get_standard_connection_counts_by_subnet();
+        let connected_regions: HashSet<&String> = self
+            .peers
+            .values()
+            .filter(|p| p.is_active())
+            .filter_map(|p| self.peer_regions.get(&p.ip))
+            .collect();
+        let region_rank = |ip: &IpAddr| match self.peer_regions.get(ip) {
+            _ if !diversity.prefer_distinct_regions => 0,
+            Some(region) if !connected_regions.contains(region) => 0,
+            None => 1,
+            Some(_) => 2,
+        };
+        let mut candidates: Vec<_> = self
             .peers
             .values()
             .filter(f)
-            .take(available_slots)
+            .map(|p| (self.get_diversity_subnet(&p.ip), p))
             .collect();
-        res.sort_unstable_by_key(|&p| (p.last_failure, std::cmp::Reverse(p.last_alive)));
-        Ok(res.into_iter().map(|p| p.ip).collect())
+        candidates.sort_unstable_by_key(|(subnet, p)| {
+            (
+                subnet_counts.get(subnet).copied().unwrap_or(0),
+                region_rank(&p.ip),
+                p.last_failure,
+                std::cmp::Reverse(p.last_alive),
+            )
+        });
+        let mut res = Vec::new();
+        for (subnet, p) in candidates {
+            if res.len() >= available_slots {
+                break;
+            }
+            let count = subnet_counts.entry(subnet).or_default();
+            if diversity.max_peers_per_subnet != 0 && *count >= diversity.max_peers_per_subnet {
+                continue;
+            }
+            *count += 1;
+            res.push(p.ip);
+        }
+        Ok(res)
+    }
+
+    ////////////////////
+    // peer diversity //
+    ////////////////////
+
+    /// IP range of `ip` considered for the diversity of the connections
+    fn get_diversity_subnet(&self, ip: &IpAddr) -> IpSubnet {
+        let diversity = &self.network_settings.peer_diversity;
+        let ip = ip.to_canonical();
+        let prefix_len = if ip.is_ipv4() {
+            diversity.ipv4_subnet_prefix_len
+        } else {
+            diversity.ipv6_subnet_prefix_len
+        };
+        IpSubnet::new(ip, prefix_len).unwrap_or_else(|_| IpSubnet::host(ip))
+    }
+
+    /// Number of connections, in, out and attempts, to the standard peers of each IP range
+    fn get_standard_connection_counts_by_subnet(&self) -> HashMap<IpSubnet, usize> {
+        let mut counts = HashMap::new();
+        for peer in self.peers.values() {
+            let count = peer.active_in_connections
+                + peer.active_out_connections
+                + peer.active_out_connection_attempts;
+            if peer.peer_type == PeerType::Standard && count > 0 {
+                *counts
+                    .entry(self.get_diversity_subnet(&peer.ip))
+                    .or_default() += count;
+            }
+        }
+        counts
+    }
+
+    /// Whether the IP range of `ip` already has `max_peers_per_subnet` connections
+    fn is_subnet_full(&self, ip: &IpAddr) -> bool {
+        let max_peers_per_subnet = self.network_settings.peer_diversity.max_peers_per_subnet;
+        if max_peers_per_subnet == 0 {
+            return false;
+        }
+        let subnet = self.get_diversity_subnet(ip);
+        let count: usize = self
+            .peers
+            .values()
+            .filter(|p| p.peer_type == PeerType::Standard && subnet.contains(&p.ip))
+            .map(|p| {
+                p.active_in_connections
+                    + p.active_out_connections
+                    + p.active_out_connection_attempts
+            })
+            .sum();
+        count >= max_peers_per_subnet
+    }
+
+    /// Marks the outgoing connection to `ip` as degraded, or healthy again.
+    /// The degraded connections are not counted in the target, so that replacements are attempted.
+    pub fn set_out_connection_degraded(&mut self, ip: &IpAddr, degraded: bool) {
+        let ip = ip.to_canonical();
+        let Some(peer) = self.peers.get(&ip) else {
+            return;
+        };
+        if degraded && peer.active_out_connections == 0 {
+            return;
+        }
+        let changed = if degraded {
+            self.degraded_peers.insert(ip)
+        } else {
+            self.degraded_peers.remove(&ip)
+        };
+        if changed {
+            let count = &mut self.peer_types_connection_count[peer.peer_type];
+            if degraded {
+                count.degraded_out_connections += 1;
+            } else {
+                count.degraded_out_connections = count.degraded_out_connections.saturating_sub(1);
+            }
+        }
+    }
+
+    /// Remembers the region advertised by the peer `ip` in its metadata
+    pub fn set_peer_region(&mut self, ip: IpAddr, region: Option<String>) {
+        let ip = ip.to_canonical();
+        match region {
+            Some(region) if self.peers.contains_key(&ip) => {
+                self.peer_regions.insert(ip, region);
+            }
+            _ => {
+                self.peer_regions.remove(&ip);
+            }
+        }
+    }
+
+    /// Distribution of the active connections, in and out, across IP ranges and regions
+    pub fn get_peer_distribution(&self) -> PeerDistribution {
+        let mut distribution = PeerDistribution::default();
+        let mut subnets: HashMap<IpSubnet, usize> = HashMap::new();
+        for peer in self.peers.values() {
+            let count = peer.active_in_connections + peer.active_out_connections;
+            if count == 0 {
+                continue;
+            }
+            *subnets
+                .entry(self.get_diversity_subnet(&peer.ip))
+                .or_default() += count;
+            match self.peer_regions.get(&peer.ip) {
+                Some(region) => *distribution.regions.entry(region.clone()).or_default() += count,
+                None => distribution.unknown_region += count,
+            }
+        }
+        distribution.subnets = subnets.into_iter().collect();
+        distribution
+            .subnets
+            .sort_unstable_by_key(|(subnet, count)| {
+                (std::cmp::Reverse(*count), subnet.to_string())
+            });
+        distribution
     }
 
     fn get_peer_type(&self, ip: &IpAddr) -> Option<PeerType> {
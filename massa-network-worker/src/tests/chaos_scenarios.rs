@@ -0,0 +1,142 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+// To start alone RUST_BACKTRACE=1 cargo test --features chaos chaos -- --nocapture --test-threads=1
+use super::scenarios::default_testing_peer_type_enum_map;
+use super::tools;
+use crate::chaos::{chaos_controller, ChaosDirection, FaultRules};
+use crate::messages::Message;
+use crate::{NetworkConfig, NetworkEvent};
+use massa_hash::Hash;
+use massa_models::{
+    block_id::BlockId,
+    endorsement::{Endorsement, EndorsementSerializer, SecureShareEndorsement},
+    secure_share::SecureShareContent,
+    slot::Slot,
+};
+use massa_network_exports::{ConnectionId, PeerInfo, PeerType};
+use massa_signature::KeyPair;
+use serial_test::serial;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+fn create_endorsement(period: u64) -> SecureShareEndorsement {
+    let content = Endorsement {
+        slot: Slot::new(period, 1),
+        index: 0,
+        endorsed_block: BlockId(Hash::compute_from(&[])),
+    };
+    Endorsement::new_verifiable(content, EndorsementSerializer::new(), &KeyPair::generate())
+        .unwrap()
+}
+
+#[tokio::test]
+#[serial]
+async fn test_chaos_drop_and_duplicate_incoming() {
+    let bind_port: u16 = 50_000;
+    let mock_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(169, 202, 0, 11)), bind_port);
+    let temp_peers_file = super::tools::generate_peers_file(&[PeerInfo {
+        ip: mock_addr.ip(),
+        peer_type: PeerType::Bootstrap,
+        last_alive: None,
+        last_failure: None,
+        advertised: true,
+        active_out_connection_attempts: 0,
+        active_out_connections: 0,
+        active_in_connections: 0,
+        banned: false,
+    }]);
+    let network_conf = NetworkConfig {
+        peer_types_config: default_testing_peer_type_enum_map(),
+        ..NetworkConfig::scenarios_default(bind_port, temp_peers_file.path())
+    };
+    chaos_controller().clear_all();
+    chaos_controller().reseed(42);
+
+    tools::network_test(
+        network_conf.clone(),
+        temp_peers_file,
+        async move |_network_command_sender,
+                    mut network_event_receiver,
+                    network_manager,
+                    mut mock_interface| {
+            let (conn1_id, conn1_r, mut conn1_w) = tools::full_connection_from_controller(
+                &mut network_event_receiver,
+                &mut mock_interface,
+                mock_addr,
+                1_000u64,
+                1_000u64,
+                1_000u64,
+                ConnectionId(0),
+            )
+            .await;
+
+            // all messages received from the peer are dropped
+            chaos_controller().set_rules(
+                conn1_id,
+                ChaosDirection::Incoming,
+                FaultRules {
+                    drop_rate: 1.0,
+                    ..Default::default()
+                },
+            );
+            conn1_w
+                .send(&Message::Endorsements(vec![create_endorsement(10)]))
+                .await
+                .unwrap();
+            assert!(
+                tools::wait_network_event(
+                    &mut network_event_receiver,
+                    500.into(),
+                    |msg| match msg {
+                        NetworkEvent::ReceivedEndorsements { .. } => Some(()),
+                        _ => None,
+                    }
+                )
+                .await
+                .is_none(),
+                "dropped message reached the network worker"
+            );
+            assert_eq!(chaos_controller().get_stats(&conn1_id).dropped, 1);
+
+            // all messages received from the peer are duplicated
+            chaos_controller().set_rules(
+                conn1_id,
+                ChaosDirection::Incoming,
+                FaultRules {
+                    duplicate_rate: 1.0,
+                    ..Default::default()
+                },
+            );
+            let endorsement = create_endorsement(11);
+            conn1_w
+                .send(&Message::Endorsements(vec![endorsement.clone()]))
+                .await
+                .unwrap();
+            for _ in 0..2 {
+                match tools::wait_network_event(&mut network_event_receiver, 1000.into(), |msg| {
+                    match msg {
+                        NetworkEvent::ReceivedEndorsements { endorsements, .. } => {
+                            Some(endorsements)
+                        }
+                        _ => None,
+                    }
+                })
+                .await
+                {
+                    Some(endorsements) => assert_eq!(endorsements, vec![endorsement.clone()]),
+                    None => panic!("Timeout while waiting for duplicated endorsement event."),
+                }
+            }
+            assert_eq!(chaos_controller().get_stats(&conn1_id).duplicated, 1);
+
+            chaos_controller().clear_all();
+            let conn1_drain = tools::incoming_message_drain_start(conn1_r).await;
+            (
+                network_event_receiver,
+                network_manager,
+                mock_interface,
+                vec![conn1_drain],
+            )
+        },
+    )
+    .await;
+}
@@ -1,5 +1,7 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+#[cfg(all(test, feature = "chaos"))]
+mod chaos_scenarios;
 #[cfg(test)]
 mod scenarios;
 #[cfg(test)]
@@ -2,6 +2,7 @@
 
 // To start alone RUST_BACKTRACE=1 cargo test -- --nocapture --test-threads=1
 use super::tools;
+use crate::handshake_worker::{HandshakeNonces, HandshakeWorker};
 use crate::messages::{Message, MessageDeserializer};
 use crate::node_worker::NodeWorker;
 use crate::tests::tools::{get_dummy_block_id, get_transaction};
@@ -19,7 +20,7 @@ use massa_models::config::{
     MAX_DATASTORE_VALUE_LENGTH, MAX_ENDORSEMENTS_PER_MESSAGE, MAX_FUNCTION_NAME_LENGTH,
     MAX_MESSAGE_SIZE, MAX_OPERATIONS_PER_BLOCK, MAX_OPERATIONS_PER_MESSAGE,
     MAX_OPERATION_DATASTORE_ENTRY_COUNT, MAX_OPERATION_DATASTORE_KEY_LENGTH,
-    MAX_OPERATION_DATASTORE_VALUE_LENGTH, MAX_PARAMETERS_SIZE, THREAD_COUNT,
+    MAX_OPERATION_DATASTORE_VALUE_LENGTH, MAX_PARAMETERS_SIZE, NODE_CAPABILITIES, THREAD_COUNT,
 };
 use massa_models::{
     block_id::BlockId,
@@ -27,6 +28,7 @@ use massa_models::{
     node::NodeId,
     secure_share::SecureShareContent,
     slot::Slot,
+    version::{Capabilities, Version},
 };
 use massa_network_exports::{settings::PeerTypeConnectionConfig, NodeCommand, NodeEvent};
 use massa_network_exports::{
@@ -37,6 +39,7 @@ use massa_signature::KeyPair;
 use massa_time::MassaTime;
 use serial_test::serial;
 use std::collections::HashMap;
+use std::str::FromStr;
 use std::{
     net::{IpAddr, Ipv4Addr, SocketAddr},
     time::{Duration, Instant},
@@ -45,7 +48,7 @@ use tokio::sync::mpsc;
 use tokio::time::sleep;
 use tracing::trace;
 
-fn default_testing_peer_type_enum_map() -> EnumMap<PeerType, PeerTypeConnectionConfig> {
+pub(super) fn default_testing_peer_type_enum_map() -> EnumMap<PeerType, PeerTypeConnectionConfig> {
     enum_map! {
         PeerType::Bootstrap => PeerTypeConnectionConfig {
             target_out_connections: 1,
@@ -109,6 +112,7 @@ async fn test_node_worker_shutdown() {
         NodeWorker::new(
             network_conf,
             mock_node_id,
+            NODE_CAPABILITIES,
             reader,
             writer,
             node_worker_command_tx,
@@ -186,6 +190,7 @@ async fn test_node_worker_operations_message() {
         NodeWorker::new(
             network_conf,
             mock_node_id,
+            NODE_CAPABILITIES,
             reader,
             writer,
             node_worker_command_tx,
@@ -1273,3 +1278,97 @@ async fn test_endorsements_messages() {
     )
     .await;
 }
+
+fn handshake_test_binders(duplex: tokio::io::DuplexStream) -> (ReadBinder, WriteBinder) {
+    let (read_half, write_half) = tokio::io::split(duplex);
+    let reader = ReadBinder::new(
+        read_half,
+        f64::INFINITY,
+        MAX_MESSAGE_SIZE,
+        MessageDeserializer::new(
+            THREAD_COUNT,
+            ENDORSEMENT_COUNT,
+            MAX_ADVERTISE_LENGTH,
+            MAX_ASK_BLOCKS_PER_MESSAGE,
+            MAX_OPERATIONS_PER_BLOCK,
+            MAX_OPERATIONS_PER_MESSAGE,
+            MAX_ENDORSEMENTS_PER_MESSAGE,
+            MAX_DATASTORE_VALUE_LENGTH,
+            MAX_FUNCTION_NAME_LENGTH,
+            MAX_PARAMETERS_SIZE,
+            MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+            MAX_OPERATION_DATASTORE_KEY_LENGTH,
+            MAX_OPERATION_DATASTORE_VALUE_LENGTH,
+        ),
+    );
+    let writer = WriteBinder::new(write_half, f64::INFINITY, MAX_MESSAGE_SIZE);
+    (reader, writer)
+}
+
+/// Test that the handshakes succeed between nodes signing the transcript,
+/// and that random bytes sent again in another handshake are refused.
+#[tokio::test]
+#[serial]
+async fn test_handshake_replay() {
+    let version = Version::from_str("TEST.1.10").unwrap();
+    let nonces = HandshakeNonces::default();
+    let spawn_handshake = |duplex: tokio::io::DuplexStream, nonces: HandshakeNonces| {
+        let (read_half, write_half) = tokio::io::split(duplex);
+        let keypair = KeyPair::generate();
+        HandshakeWorker::spawn(
+            read_half,
+            write_half,
+            NodeId::new(keypair.get_public_key()),
+            keypair,
+            MassaTime::from_millis(2000),
+            MassaTime::from_millis(1000),
+            nonces,
+            version,
+            NODE_CAPABILITIES,
+            ConnectionId::default(),
+            f64::INFINITY,
+            f64::INFINITY,
+        )
+    };
+
+    // honest handshake
+    let (duplex_a, duplex_b) = tokio::io::duplex(1_000_000);
+    let handshake_a = spawn_handshake(duplex_a, nonces.clone());
+    let handshake_b = spawn_handshake(duplex_b, HandshakeNonces::default());
+    let (res_a, res_b) = tokio::join!(handshake_a, handshake_b);
+    let (_, _, _, capabilities) = res_a.unwrap().1.expect("handshake failed");
+    assert!(capabilities.contains(Capabilities::HANDSHAKE_TRANSCRIPT));
+    res_b.unwrap().1.expect("handshake failed");
+
+    // the same random bytes in two handshakes
+    let mock_keypair = KeyPair::generate();
+    let replayed_init = Message::HandshakeInitiation {
+        public_key: mock_keypair.get_public_key(),
+        random_bytes: [7u8; 32],
+        version,
+        capabilities: NODE_CAPABILITIES,
+    };
+    let mut results = Vec::new();
+    for _ in 0..2 {
+        let (duplex_controller, duplex_mock) = tokio::io::duplex(1_000_000);
+        let handshake = spawn_handshake(duplex_controller, nonces.clone());
+        let (mut mock_reader, mut mock_writer) = handshake_test_binders(duplex_mock);
+        mock_writer.send(&replayed_init).await.unwrap();
+        mock_reader.next().await.unwrap();
+        // the mock never replies
+        let result = handshake.await.unwrap().1;
+        drop((mock_reader, mock_writer));
+        results.push(result);
+    }
+    match &results[1] {
+        Err(NetworkError::HandshakeError(HandshakeErrorType::HandshakeReplay)) => {}
+        res => panic!("replayed handshake not refused: {:?}", res.as_ref().err()),
+    }
+    match &results[0] {
+        Err(NetworkError::HandshakeError(HandshakeErrorType::HandshakeTimeout)) => {}
+        res => panic!(
+            "silent handshake did not time out: {:?}",
+            res.as_ref().err()
+        ),
+    }
+}
@@ -1,10 +1,12 @@
 use crate::{
+    ban_list::BanList,
     peer_info_database::{cleanup_peers, PeerInfoDatabase},
     NetworkConfig, NetworkError,
 };
 use enum_map::enum_map;
 use massa_network_exports::{
-    settings::PeerTypeConnectionConfig, NetworkConnectionErrorType, PeerInfo, PeerType,
+    settings::{PeerDiversityConfig, PeerTypeConnectionConfig},
+    BanEntry, IpSubnet, NetworkConnectionErrorType, PeerInfo, PeerType,
 };
 use massa_time::MassaTime;
 use serial_test::serial;
@@ -55,6 +57,9 @@ async fn test_try_new_in_connection_in_connection_closed() {
         saver_watch_tx,
         wakeup_interval,
         peer_types_connection_count: Default::default(),
+        ban_list: Default::default(),
+        peer_regions: Default::default(),
+        degraded_peers: Default::default(),
     };
 
     // test with no connection attempt before
@@ -156,6 +161,9 @@ async fn test_out_connection_attempt_failed() {
         saver_join_handle,
         saver_watch_tx,
         peer_types_connection_count: Default::default(),
+        ban_list: Default::default(),
+        peer_regions: Default::default(),
+        degraded_peers: Default::default(),
         wakeup_interval,
     };
 
@@ -266,6 +274,9 @@ async fn test_try_out_connection_attempt_success() {
         saver_join_handle,
         saver_watch_tx,
         peer_types_connection_count: Default::default(),
+        ban_list: Default::default(),
+        peer_regions: Default::default(),
+        degraded_peers: Default::default(),
         wakeup_interval,
     };
 
@@ -357,6 +368,9 @@ async fn test_new_out_connection_closed() {
         saver_join_handle,
         saver_watch_tx,
         peer_types_connection_count: Default::default(),
+        ban_list: Default::default(),
+        peer_regions: Default::default(),
+        degraded_peers: Default::default(),
         wakeup_interval,
     };
 
@@ -437,6 +451,9 @@ async fn test_new_out_connection_attempt() {
         saver_join_handle,
         saver_watch_tx,
         peer_types_connection_count: Default::default(),
+        ban_list: Default::default(),
+        peer_regions: Default::default(),
+        degraded_peers: Default::default(),
         wakeup_interval,
     };
 
@@ -523,6 +540,9 @@ async fn test_get_advertisable_peer_ips() {
         saver_join_handle,
         saver_watch_tx,
         peer_types_connection_count: Default::default(),
+        ban_list: Default::default(),
+        peer_regions: Default::default(),
+        degraded_peers: Default::default(),
         wakeup_interval,
     };
 
@@ -634,6 +654,9 @@ async fn test_get_out_connection_candidate_ips() {
         saver_join_handle,
         saver_watch_tx,
         peer_types_connection_count: Default::default(),
+        ban_list: Default::default(),
+        peer_regions: Default::default(),
+        degraded_peers: Default::default(),
         wakeup_interval,
     };
 
@@ -806,6 +829,220 @@ fn default_peer_info_connected(ip: IpAddr) -> PeerInfo {
     }
 }
 
+#[tokio::test]
+#[serial]
+async fn test_subnet_ban_list() {
+    // subnet parsing
+    let subnet: IpSubnet = "169.202.0.130/25".parse().unwrap();
+    assert_eq!(subnet.to_string(), "169.202.0.128/25");
+    assert!(subnet.contains(&"169.202.0.200".parse().unwrap()));
+    assert!(!subnet.contains(&"169.202.0.12".parse().unwrap()));
+    assert!(subnet.contains(&"::ffff:169.202.0.129".parse().unwrap()));
+    assert!("169.202.0.1".parse::<IpSubnet>().unwrap().is_host());
+    assert!("169.202.0.1/33".parse::<IpSubnet>().is_err());
+    let subnet_v6: IpSubnet = "2001:db8::/32".parse().unwrap();
+    assert!(subnet_v6.contains(&"2001:db8:1::1".parse().unwrap()));
+    assert!(!subnet_v6.contains(&"2001:db9::1".parse().unwrap()));
+
+    // bans are saved and expired bans are dropped at load
+    let dir = tempfile::tempdir().unwrap();
+    let ban_list_file = dir.path().join("ban_list.json");
+    let now = MassaTime::now().unwrap();
+    let mut ban_list = BanList::load(&ban_list_file);
+    ban_list.ban(BanEntry {
+        subnet,
+        reason: "spam".into(),
+        created: now,
+        expiry: None,
+    });
+    ban_list.ban(BanEntry {
+        subnet: subnet_v6,
+        reason: String::new(),
+        created: now,
+        expiry: Some(now.checked_sub(1.into()).unwrap()),
+    });
+    let mut ban_list = BanList::load(&ban_list_file);
+    assert_eq!(
+        ban_list.entries(),
+        vec![BanEntry {
+            subnet,
+            reason: "spam".into(),
+            created: now,
+            expiry: None,
+        }]
+    );
+
+    // connections from the banned range are refused
+    let network_settings = NetworkConfig {
+        ban_list_file,
+        ..Default::default()
+    };
+    let wakeup_interval = network_settings.wakeup_interval;
+    let (saver_watch_tx, _) = watch::channel(HashMap::new());
+    let saver_join_handle = tokio::spawn(async move {});
+    let mut db = PeerInfoDatabase {
+        network_settings,
+        peers: HashMap::new(),
+        saver_join_handle,
+        saver_watch_tx,
+        peer_types_connection_count: Default::default(),
+        ban_list,
+        peer_regions: Default::default(),
+        degraded_peers: Default::default(),
+        wakeup_interval,
+    };
+    let banned_ip: IpAddr = "169.202.0.200".parse().unwrap();
+    match db.try_new_in_connection(&banned_ip) {
+        Err(NetworkError::PeerConnectionError(
+            NetworkConnectionErrorType::BannedPeerTryingToConnect(ip),
+        )) => assert_eq!(ip, banned_ip),
+        res => panic!("connection from a banned subnet not refused: {:?}", res),
+    }
+    db.try_new_in_connection(&"169.202.0.12".parse().unwrap())
+        .expect("connection from outside of the banned subnet refused");
+
+    // lifting the ban
+    assert!(db.ban_list.unban(&[subnet]));
+    assert!(!db.ban_list.is_banned(&banned_ip));
+    assert!(BanList::load(&db.network_settings.ban_list_file)
+        .entries()
+        .is_empty());
+}
+
+#[tokio::test]
+#[serial]
+async fn test_peer_diversity() {
+    let network_settings = NetworkConfig {
+        peer_diversity: PeerDiversityConfig {
+            max_peers_per_subnet: 2,
+            ipv4_subnet_prefix_len: 16,
+            ipv6_subnet_prefix_len: 32,
+            prefer_distinct_regions: true,
+        },
+        ..Default::default()
+    };
+    let mut peers: HashMap<IpAddr, PeerInfo> = HashMap::new();
+    for ip in [
+        "169.203.0.1",
+        "169.203.0.2",
+        "169.203.0.3",
+        "171.0.0.1",
+        "172.0.0.1",
+    ] {
+        let peer = default_peer_info_not_connected(ip.parse().unwrap());
+        peers.insert(peer.ip, peer);
+    }
+    let wakeup_interval = network_settings.wakeup_interval;
+    let (saver_watch_tx, _) = watch::channel(peers.clone());
+    let saver_join_handle = tokio::spawn(async move {});
+    let mut db = PeerInfoDatabase {
+        network_settings,
+        peers,
+        saver_join_handle,
+        saver_watch_tx,
+        peer_types_connection_count: Default::default(),
+        ban_list: Default::default(),
+        peer_regions: Default::default(),
+        degraded_peers: Default::default(),
+        wakeup_interval,
+    };
+
+    // at most 2 connections per /16
+    db.try_new_in_connection(&"169.202.0.1".parse().unwrap())
+        .unwrap();
+    db.try_new_in_connection(&"169.202.0.2".parse().unwrap())
+        .unwrap();
+    match db.try_new_in_connection(&"169.202.0.3".parse().unwrap()) {
+        Err(NetworkError::PeerConnectionError(
+            NetworkConnectionErrorType::MaxPeersPerSubnetReached(_),
+        )) => {}
+        res => panic!(
+            "connection over the limit of the subnet not refused: {:?}",
+            res
+        ),
+    }
+
+    // regions
+    db.set_peer_region("169.202.0.1".parse().unwrap(), Some("eu".into()));
+    db.set_peer_region("171.0.0.1".parse().unwrap(), Some("eu".into()));
+    db.set_peer_region("172.0.0.1".parse().unwrap(), Some("us".into()));
+    let distribution = db.get_peer_distribution();
+    assert_eq!(
+        distribution.subnets,
+        vec![("169.202.0.0/16".parse().unwrap(), 2)]
+    );
+    assert_eq!(distribution.regions.get("eu"), Some(&1));
+    assert_eq!(distribution.unknown_region, 1);
+
+    // new region first, then unknown regions within the limit of the subnet, then known regions
+    let candidates = db.get_out_connection_candidate_ips().unwrap();
+    assert_eq!(candidates.len(), 4);
+    assert_eq!(candidates[0], "172.0.0.1".parse::<IpAddr>().unwrap());
+    assert_eq!(candidates[3], "171.0.0.1".parse::<IpAddr>().unwrap());
+    let subnet: IpSubnet = "169.203.0.0/16".parse().unwrap();
+    assert!(candidates[1..3].iter().all(|ip| subnet.contains(ip)));
+}
+
+#[tokio::test]
+#[serial]
+async fn test_degraded_out_connection() {
+    let mut network_settings = NetworkConfig::default();
+    network_settings.peer_types_config[PeerType::Standard] = PeerTypeConnectionConfig {
+        target_out_connections: 1,
+        max_out_attempts: 1,
+        max_in_connections: 5,
+    };
+    let mut peers: HashMap<IpAddr, PeerInfo> = HashMap::new();
+    let ip_a: IpAddr = "169.202.0.1".parse().unwrap();
+    let ip_b: IpAddr = "169.202.0.2".parse().unwrap();
+    for ip in [ip_a, ip_b] {
+        let peer = default_peer_info_not_connected(ip);
+        peers.insert(peer.ip, peer);
+    }
+    let wakeup_interval = network_settings.wakeup_interval;
+    let (saver_watch_tx, _) = watch::channel(peers.clone());
+    let saver_join_handle = tokio::spawn(async move {});
+    let mut db = PeerInfoDatabase {
+        network_settings,
+        peers,
+        saver_join_handle,
+        saver_watch_tx,
+        peer_types_connection_count: Default::default(),
+        ban_list: Default::default(),
+        peer_regions: Default::default(),
+        degraded_peers: Default::default(),
+        wakeup_interval,
+    };
+
+    db.new_out_connection_attempt(&ip_a).unwrap();
+    assert!(db.try_out_connection_attempt_success(&ip_a).unwrap());
+    assert!(db.get_out_connection_candidate_ips().unwrap().is_empty());
+
+    // a degraded connection does not count in the target: a replacement is attempted
+    db.set_out_connection_degraded(&ip_a, true);
+    db.set_out_connection_degraded(&ip_a, true);
+    let count = &db.peer_types_connection_count[PeerType::Standard];
+    assert_eq!(count.degraded_out_connections, 1);
+    assert_eq!(count.get_healthy_out_connections(), 0);
+    assert_eq!(db.get_out_connection_candidate_ips().unwrap(), vec![ip_b]);
+    db.new_out_connection_attempt(&ip_b).unwrap();
+    assert!(db.try_out_connection_attempt_success(&ip_b).unwrap());
+    assert!(db.get_out_connection_candidate_ips().unwrap().is_empty());
+
+    // closing the degraded connection forgets it
+    db.out_connection_closed(&ip_a).unwrap();
+    let count = &db.peer_types_connection_count[PeerType::Standard];
+    assert_eq!(count.active_out_connections, 1);
+    assert_eq!(count.degraded_out_connections, 0);
+    assert!(db.degraded_peers.is_empty());
+
+    // a connection answering again is healthy
+    db.set_out_connection_degraded(&ip_b, true);
+    db.set_out_connection_degraded(&ip_b, false);
+    let count = &db.peer_types_connection_count[PeerType::Standard];
+    assert_eq!(count.get_healthy_out_connections(), 1);
+}
+
 fn default_peer_info_not_connected(ip: IpAddr) -> PeerInfo {
     PeerInfo {
         ip,
@@ -860,6 +1097,9 @@ impl From<u32> for PeerInfoDatabase {
             saver_join_handle,
             saver_watch_tx,
             peer_types_connection_count: Default::default(),
+            ban_list: Default::default(),
+            peer_regions: Default::default(),
+            degraded_peers: Default::default(),
             wakeup_interval,
         }
     }
@@ -2,14 +2,16 @@
 
 use super::super::binders::{ReadBinder, WriteBinder};
 use super::tools;
-use crate::handshake_worker::HandshakeWorker;
+use crate::handshake_worker::{HandshakeNonces, HandshakeWorker};
 use crate::messages::Message;
 use crate::start_network_controller;
 use crate::NetworkConfig;
 use crate::NetworkError;
 use crate::NetworkEvent;
 
+use massa_channel::EventBus;
 use massa_hash::Hash;
+use massa_models::config::NODE_CAPABILITIES;
 use massa_models::node::NodeId;
 use massa_models::secure_share::SecureShareContent;
 use massa_models::{
@@ -17,6 +19,7 @@ use massa_models::{
     amount::Amount,
     block_id::BlockId,
     operation::{Operation, OperationSerializer, OperationType, SecureShareOperation},
+    slot::SyncLag,
     version::Version,
 };
 use massa_network_exports::test_exports::mock_establisher::{self, MockEstablisherInterface};
@@ -24,7 +27,7 @@ use massa_network_exports::{
     ConnectionId, NetworkCommandSender, NetworkEventReceiver, NetworkManager, PeerInfo,
 };
 use massa_signature::KeyPair;
-use massa_time::MassaTime;
+use massa_time::{ClockOffset, MassaTime};
 use std::str::FromStr;
 use std::{
     future::Future,
@@ -90,7 +93,10 @@ pub async fn full_connection_to_controller(
         mock_node_id,
         keypair,
         rw_timeout_ms.into(),
+        rw_timeout_ms.into(),
+        HandshakeNonces::default(),
         Version::from_str("TEST.1.10").unwrap(),
+        NODE_CAPABILITIES,
         connection_id,
         f64::INFINITY,
         f64::INFINITY,
@@ -149,7 +155,10 @@ pub async fn rejected_connection_to_controller(
         mock_node_id,
         keypair,
         rw_timeout_ms.into(),
+        rw_timeout_ms.into(),
+        HandshakeNonces::default(),
         Version::from_str("TEST.1.10").unwrap(),
+        NODE_CAPABILITIES,
         connection_id,
         f64::INFINITY,
         f64::INFINITY,
@@ -234,7 +243,10 @@ pub async fn full_connection_from_controller(
         mock_node_id,
         keypair,
         rw_timeout_ms.into(),
+        rw_timeout_ms.into(),
+        HandshakeNonces::default(),
         Version::from_str("TEST.1.10").unwrap(),
+        NODE_CAPABILITIES,
         connection_id,
         f64::INFINITY,
         f64::INFINITY,
@@ -332,11 +344,14 @@ pub fn get_transaction(expire_period: u64, fee: u64) -> SecureShareOperation {
     let op = OperationType::Transaction {
         recipient_address: Address::from_public_key(&recv_keypair.get_public_key()),
         amount: Amount::default(),
+        memo: None,
     };
     let content = Operation {
         fee: Amount::from_str(&fee.to_string()).unwrap(),
         op,
         expire_period,
+        sequence: None,
+        sponsor: None,
     };
 
     Operation::new_verifiable(content, OperationSerializer::new(), &sender_keypair).unwrap()
@@ -372,6 +387,9 @@ pub async fn network_test<F, V>(
             establisher,
             None,
             Version::from_str("TEST.1.10").unwrap(),
+            ClockOffset::new(),
+            SyncLag::new(),
+            EventBus::new(10),
         )
         .await
         .expect("could not start network controller");
@@ -0,0 +1,70 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Embeds the provenance of the node binary, reported by `get_status`.
+//!
+//! The build hash is the hash of the build inputs: commit, dirty flag, compiler version, target, profile,
+//! features and `Cargo.lock`. Nothing depending on the time or the machine of the build is hashed, so two builds
+//! from the same inputs report the same build hash, which operators can compare with the one published for a release.
+//! It is not a hash of the produced binary: the build itself is not reproducible (paths, linker, environment),
+//! and uncommitted changes only show in the dirty flag.
+//! The commit is read from git, or from `MASSA_GIT_COMMIT` when building from a source archive.
+
+use massa_hash::Hash;
+use std::path::Path;
+use std::process::Command;
+
+/// Output of a command, `None` if it could not run or failed
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=MASSA_GIT_COMMIT");
+    // a path that does not exist would make the script run at every build
+    for path in ["../.git/HEAD", "../.git/index", "../Cargo.lock"] {
+        if Path::new(path).exists() {
+            println!("cargo:rerun-if-changed={}", path);
+        }
+    }
+
+    let (git_commit, git_dirty) = match std::env::var("MASSA_GIT_COMMIT") {
+        Ok(commit) => (commit, false),
+        Err(_) => (
+            command_output("git", &["rev-parse", "HEAD"]).unwrap_or_else(|| "unknown".into()),
+            command_output("git", &["status", "--porcelain", "--untracked-files=no"])
+                .map_or(false, |status| !status.is_empty()),
+        ),
+    };
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".into());
+    let rustc_version = command_output(&rustc, &["--version"]).unwrap_or_else(|| "unknown".into());
+    let target = std::env::var("TARGET").unwrap_or_default();
+    let profile = std::env::var("PROFILE").unwrap_or_default();
+    let mut features: Vec<String> = std::env::vars()
+        .filter_map(|(key, _)| {
+            key.strip_prefix("CARGO_FEATURE_")
+                .map(|feature| feature.to_lowercase())
+        })
+        .collect();
+    features.sort_unstable();
+    let features = features.join(",");
+
+    let mut inputs = format!(
+        "{}\n{}\n{}\n{}\n{}\n{}\n",
+        git_commit, git_dirty, rustc_version, target, profile, features
+    )
+    .into_bytes();
+    inputs.extend(std::fs::read("../Cargo.lock").unwrap_or_default());
+    let build_hash = Hash::compute_from(&inputs);
+
+    println!("cargo:rustc-env=MASSA_GIT_COMMIT={}", git_commit);
+    println!("cargo:rustc-env=MASSA_GIT_DIRTY={}", git_dirty);
+    println!("cargo:rustc-env=MASSA_RUSTC_VERSION={}", rustc_version);
+    println!("cargo:rustc-env=MASSA_BUILD_TARGET={}", target);
+    println!("cargo:rustc-env=MASSA_BUILD_PROFILE={}", profile);
+    println!("cargo:rustc-env=MASSA_BUILD_FEATURES={}", features);
+    println!("cargo:rustc-env=MASSA_BUILD_HASH={}", build_hash);
+}
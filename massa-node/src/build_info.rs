@@ -0,0 +1,22 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Provenance of the node binary, embedded at compile time by `build.rs`.
+
+use massa_api_exports::node::BuildInfo;
+
+/// Build information of the running binary
+pub fn build_info() -> BuildInfo {
+    BuildInfo {
+        git_commit: env!("MASSA_GIT_COMMIT").to_string(),
+        git_dirty: env!("MASSA_GIT_DIRTY") == "true",
+        rustc_version: env!("MASSA_RUSTC_VERSION").to_string(),
+        target: env!("MASSA_BUILD_TARGET").to_string(),
+        profile: env!("MASSA_BUILD_PROFILE").to_string(),
+        features: env!("MASSA_BUILD_FEATURES")
+            .split(',')
+            .filter(|feature| !feature.is_empty())
+            .map(str::to_string)
+            .collect(),
+        build_hash: env!("MASSA_BUILD_HASH").to_string(),
+    }
+}
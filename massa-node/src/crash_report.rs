@@ -0,0 +1,185 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Opt-in crash reports.
+//!
+//! When enabled, every panic is written as a JSON report in `directory`, with its backtrace, the
+//! node version, the hash of the node settings and the latest log lines of the node. The reports
+//! are written synchronously by the panic hook as the process may exit right after it.
+//!
+//! If a submission endpoint is configured, the reports not sent yet are submitted at each launch
+//! of the node with the `submit_crash_report` JSON-RPC method, then renamed with a `.sent`
+//! extension.
+
+use jsonrpsee::core::client::ClientT;
+use jsonrpsee::http_client::HttpClientBuilder;
+use jsonrpsee::rpc_params;
+use massa_hash::Hash;
+use massa_logging::RecentLogs;
+use massa_models::version::Version;
+use massa_time::MassaTime;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tracing::{info, warn, Level};
+
+/// Crash report configuration
+#[derive(Debug, Clone)]
+pub struct CrashReportConfig {
+    /// directory in which the reports are written
+    pub directory: PathBuf,
+    /// JSON-RPC endpoint the reports are submitted to, `None` to keep them local
+    pub submit_endpoint: Option<String>,
+    /// timeout of a submission
+    pub submit_timeout: MassaTime,
+    /// number of recent log lines included in the reports
+    pub log_count: usize,
+}
+
+/// Report of a panic
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashReport {
+    /// version of the node
+    pub version: String,
+    /// hash of the node settings, to group the reports of a same configuration
+    pub config_hash: String,
+    /// time of the panic
+    pub time: MassaTime,
+    /// name of the thread that panicked
+    pub thread: String,
+    /// source location of the panic
+    pub location: Option<String>,
+    /// panic message
+    pub message: String,
+    /// backtrace of the panic
+    pub backtrace: String,
+    /// latest log lines before the panic, oldest first
+    pub recent_logs: Vec<String>,
+}
+
+/// Writes the crash reports of the panics
+pub struct CrashReporter {
+    config: CrashReportConfig,
+    version: Version,
+    config_hash: String,
+    logs: RecentLogs,
+}
+
+impl CrashReporter {
+    /// Creates the reporter of a node running with the settings described by `settings`
+    pub fn new(
+        config: CrashReportConfig,
+        version: Version,
+        settings: &str,
+        logs: RecentLogs,
+    ) -> Self {
+        CrashReporter {
+            config,
+            version,
+            config_hash: Hash::compute_from(settings.as_bytes()).to_string(),
+            logs,
+        }
+    }
+
+    /// Installs a panic hook writing a report before the previous hook runs
+    pub fn install(self) {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let thread = std::thread::current();
+            let message = if let Some(message) = info.payload().downcast_ref::<&str>() {
+                message.to_string()
+            } else if let Some(message) = info.payload().downcast_ref::<String>() {
+                message.clone()
+            } else {
+                "unknown panic payload".to_string()
+            };
+            let report = CrashReport {
+                version: self.version.to_string(),
+                config_hash: self.config_hash.clone(),
+                time: MassaTime::now().unwrap_or_else(|_| MassaTime::from_millis(0)),
+                thread: thread.name().unwrap_or_default().to_string(),
+                location: info.location().map(|location| location.to_string()),
+                message,
+                backtrace: std::backtrace::Backtrace::force_capture().to_string(),
+                recent_logs: self
+                    .logs
+                    .recent(Level::TRACE, self.config.log_count)
+                    .iter()
+                    .map(|line| line.to_string())
+                    .collect(),
+            };
+            if let Err(err) = self.write(&report) {
+                eprintln!("could not write the crash report: {}", err);
+            }
+            previous(info);
+        }));
+    }
+
+    fn write(&self, report: &CrashReport) -> anyhow::Result<()> {
+        std::fs::create_dir_all(&self.config.directory)?;
+        let path = self.config.directory.join(format!(
+            "crash_{}_{}.json",
+            report.time.to_millis(),
+            report
+                .thread
+                .replace(|c: char| !c.is_ascii_alphanumeric(), "_")
+        ));
+        std::fs::write(&path, serde_json::to_string_pretty(report)?)?;
+        eprintln!("crash report written in {}", path.display());
+        Ok(())
+    }
+}
+
+/// Submits the reports of `config.directory` not sent yet to the configured endpoint
+pub async fn submit_pending_reports(config: &CrashReportConfig) {
+    let Some(endpoint) = &config.submit_endpoint else {
+        return;
+    };
+    let Ok(entries) = std::fs::read_dir(&config.directory) else {
+        return;
+    };
+    let client = match HttpClientBuilder::default()
+        .request_timeout(config.submit_timeout.to_duration())
+        .build(endpoint)
+    {
+        Ok(client) => client,
+        Err(err) => {
+            warn!("invalid crash report endpoint {}: {}", endpoint, err);
+            return;
+        }
+    };
+    for path in entries.filter_map(|entry| Some(entry.ok()?.path())) {
+        if path
+            .extension()
+            .map_or(true, |extension| extension != "json")
+        {
+            continue;
+        }
+        let report: CrashReport = match std::fs::read(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|bytes| Ok(serde_json::from_slice(&bytes)?))
+        {
+            Ok(report) => report,
+            Err(err) => {
+                warn!("invalid crash report {}: {}", path.display(), err);
+                continue;
+            }
+        };
+        match client
+            .request::<(), _>("submit_crash_report", rpc_params![report])
+            .await
+        {
+            Ok(()) => {
+                info!("crash report {} submitted", path.display());
+                if let Err(err) = std::fs::rename(&path, path.with_extension("json.sent")) {
+                    warn!("could not mark {} as sent: {}", path.display(), err);
+                }
+            }
+            Err(err) => {
+                warn!(
+                    "could not submit the crash reports to {}: {}",
+                    endpoint, err
+                );
+                return;
+            }
+        }
+    }
+}
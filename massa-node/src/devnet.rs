@@ -0,0 +1,231 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Local devnet launcher.
+//!
+//! `massa-node --launch-devnet N` generates N staking keys, a genesis ledger funding their addresses and giving
+//! them rolls, and a directory per node in the devnet directory, then launches the N nodes and waits for them.
+//! Node `i` (from 1) runs in `node_i` with the base configuration overridden by `node_i/config/config.toml`:
+//! it listens on the loopback IP `127.0.0.i` with the default ports (so its public API is at `127.0.0.i:33035`),
+//! starts from genesis without bootstrap and has the other nodes as initial peers.
+//! Its output is written in `node_i/node.log`.
+//!
+//! The nodes share the genesis timestamp through the `GENESIS_TIMESTAMP` variable, read by the sandbox builds only:
+//! the launcher is only available with the `sandbox` feature.
+//! On systems where only `127.0.0.1` is a loopback IP by default (ex: macOS), the other ones must be aliased.
+
+use anyhow::{bail, Context};
+use massa_ledger_exports::LedgerEntry;
+use massa_models::{address::Address, amount::Amount};
+use massa_network_exports::{PeerInfo, PeerType};
+use massa_signature::KeyPair;
+use massa_time::MassaTime;
+use massa_wallet::Wallet;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::net::{IpAddr, Ipv4Addr};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::str::FromStr;
+
+/// Password of the staking wallets of the devnet nodes
+const DEVNET_WALLET_PASSWORD: &str = "devnet";
+
+/// Initial balance of each staking address
+const DEVNET_INITIAL_BALANCE: &str = "1000000000";
+
+/// Initial rolls of each staking address
+const DEVNET_INITIAL_ROLLS: u64 = 10;
+
+/// Delay between the launch of the nodes and the genesis, in milliseconds
+const DEVNET_GENESIS_DELAY: u64 = 10_000;
+
+/// Loopback IP of the node `index` (from 0)
+fn node_ip(index: usize) -> IpAddr {
+    IpAddr::V4(Ipv4Addr::new(127, 0, 0, (index + 1) as u8))
+}
+
+/// `path` as a TOML string
+fn toml_path(path: &Path) -> String {
+    format!("{:?}", path.display().to_string())
+}
+
+/// Writes `value` as JSON in `path`
+fn write_json<T: serde::Serialize>(path: &Path, value: &T) -> anyhow::Result<()> {
+    std::fs::write(path, serde_json::to_string_pretty(value)?)
+        .with_context(|| format!("could not write {}", path.display()))
+}
+
+/// Configuration overriding the base one for the node `index`
+fn node_config(index: usize, base_dir: &Path, devnet_dir: &Path) -> String {
+    let ip = node_ip(index);
+    format!(
+        r#"# generated by massa-node --launch-devnet
+[api]
+    bind_private = "{ip}:33034"
+    bind_public = "{ip}:33035"
+    bind_api = "{ip}:33036"
+    openrpc_spec_path = {openrpc}
+
+[execution]
+    abi_gas_costs_file = {abi_gas_costs}
+    wasm_gas_costs_file = {wasm_gas_costs}
+    initial_vesting_path = {vesting}
+
+[ledger]
+    initial_ledger_path = {ledger}
+
+[network]
+    bind = "{ip}:31244"
+    routable_ip = "{ip}"
+    allow_local_peers = true
+    initial_peers_file = {peers}
+
+[bootstrap]
+    bootstrap_list = []
+    bootstrap_whitelist_path = {whitelist}
+    bootstrap_blacklist_path = {blacklist}
+    bind = "{ip}:31245"
+
+[selector]
+    initial_rolls_path = {rolls}
+
+[versioning]
+    mip_list_path = {mip_list}
+"#,
+        ip = ip,
+        openrpc = toml_path(&base_dir.join("openrpc.json")),
+        abi_gas_costs = toml_path(&base_dir.join("gas_costs/abi_gas_costs.json")),
+        wasm_gas_costs = toml_path(&base_dir.join("gas_costs/wasm_gas_costs.json")),
+        vesting = toml_path(&devnet_dir.join("initial_vesting.json")),
+        ledger = toml_path(&devnet_dir.join("initial_ledger.json")),
+        peers = toml_path(&devnet_dir.join(format!("node_{}/initial_peers.json", index + 1))),
+        whitelist = toml_path(&base_dir.join("bootstrap_whitelist.json")),
+        blacklist = toml_path(&base_dir.join("bootstrap_blacklist.json")),
+        rolls = toml_path(&devnet_dir.join("initial_rolls.json")),
+        mip_list = toml_path(&base_dir.join("mip_list.json")),
+    )
+}
+
+/// Generates a devnet of `node_count` nodes in `devnet_dir`, which must not exist or be empty,
+/// then launches its nodes and waits for them
+pub fn launch_devnet(node_count: usize, devnet_dir: &Path) -> anyhow::Result<()> {
+    if node_count == 0 || node_count > 254 {
+        bail!("a devnet has between 1 and 254 nodes");
+    }
+    if devnet_dir.is_dir() && std::fs::read_dir(devnet_dir)?.next().is_some() {
+        bail!(
+            "the devnet directory {} is not empty: remove it to launch a new devnet",
+            devnet_dir.display()
+        );
+    }
+    std::fs::create_dir_all(devnet_dir)?;
+    let devnet_dir = devnet_dir.canonicalize()?;
+    let config_path = PathBuf::from(
+        std::env::var("MASSA_CONFIG_PATH").unwrap_or_else(|_| "base_config/config.toml".into()),
+    )
+    .canonicalize()
+    .context("could not find the base configuration")?;
+    let base_dir = config_path
+        .parent()
+        .context("invalid base configuration path")?
+        .to_path_buf();
+
+    // genesis state shared by the nodes
+    let staking_keys: Vec<KeyPair> = (0..node_count).map(|_| KeyPair::generate()).collect();
+    let addresses: Vec<Address> = staking_keys
+        .iter()
+        .map(|keypair| Address::from_public_key(&keypair.get_public_key()))
+        .collect();
+    let balance = Amount::from_str(DEVNET_INITIAL_BALANCE)?;
+    let ledger: HashMap<Address, LedgerEntry> = addresses
+        .iter()
+        .map(|address| {
+            (
+                *address,
+                LedgerEntry {
+                    balance,
+                    ..Default::default()
+                },
+            )
+        })
+        .collect();
+    write_json(&devnet_dir.join("initial_ledger.json"), &ledger)?;
+    let rolls: BTreeMap<Address, u64> = addresses
+        .iter()
+        .map(|address| (*address, DEVNET_INITIAL_ROLLS))
+        .collect();
+    write_json(&devnet_dir.join("initial_rolls.json"), &rolls)?;
+    write_json(
+        &devnet_dir.join("initial_vesting.json"),
+        &BTreeMap::<Address, ()>::new(),
+    )?;
+
+    // node directories
+    for (index, keypair) in staking_keys.iter().enumerate() {
+        let node_dir = devnet_dir.join(format!("node_{}", index + 1));
+        std::fs::create_dir_all(node_dir.join("config"))?;
+        let peers: Vec<PeerInfo> = (0..node_count)
+            .filter(|other| *other != index)
+            .map(|other| PeerInfo {
+                peer_type: PeerType::WhiteListed,
+                ..PeerInfo::new(node_ip(other), true)
+            })
+            .collect();
+        write_json(&node_dir.join("initial_peers.json"), &peers)?;
+        std::fs::write(
+            node_dir.join("config/config.toml"),
+            node_config(index, &base_dir, &devnet_dir),
+        )?;
+        let mut wallet = Wallet::new(
+            node_dir.join("config/staking_wallet.dat"),
+            DEVNET_WALLET_PASSWORD.to_string(),
+        )?;
+        wallet.add_keypairs(vec![keypair.clone()])?;
+    }
+
+    // launch the nodes
+    let genesis_timestamp =
+        MassaTime::now()?.saturating_add(MassaTime::from_millis(DEVNET_GENESIS_DELAY));
+    let node_binary = std::env::current_exe()?;
+    let mut nodes: Vec<Child> = Vec::with_capacity(node_count);
+    for (index, address) in addresses.iter().enumerate() {
+        let node_dir = devnet_dir.join(format!("node_{}", index + 1));
+        let log = File::create(node_dir.join("node.log"))?;
+        let child = Command::new(&node_binary)
+            .args(["-p", DEVNET_WALLET_PASSWORD])
+            .current_dir(&node_dir)
+            .env("MASSA_CONFIG_PATH", &config_path)
+            .env(
+                "MASSA_CONFIG_OVERRIDE_PATH",
+                node_dir.join("config/config.toml"),
+            )
+            .env(
+                "GENESIS_TIMESTAMP",
+                genesis_timestamp.to_millis().to_string(),
+            )
+            .stdin(Stdio::null())
+            .stdout(log.try_clone()?)
+            .stderr(log)
+            .spawn()
+            .with_context(|| format!("could not launch node {}", index + 1))?;
+        println!(
+            "node {}: staking address {}, public API at {}:33035, logs in {}",
+            index + 1,
+            address,
+            node_ip(index),
+            node_dir.join("node.log").display()
+        );
+        nodes.push(child);
+    }
+    println!(
+        "devnet of {} nodes launched, genesis at {}",
+        node_count,
+        genesis_timestamp.to_utc_string()
+    );
+
+    for (index, mut node) in nodes.into_iter().enumerate() {
+        let status = node.wait()?;
+        println!("node {} stopped: {}", index + 1, status);
+    }
+    Ok(())
+}
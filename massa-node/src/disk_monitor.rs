@@ -0,0 +1,209 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Disk usage monitoring.
+//!
+//! Periodically measures the monitored directories and the free space of their filesystems.
+//! When the free space of one of them falls below `read_only_threshold`, the node switches to
+//! read-only mode: block and endorsement production is paused while the node keeps validating.
+//! Production resumes once the free space of all of them is back above `resume_threshold`.
+
+use massa_models::stats::{DiskStatus, DiskUsage};
+use massa_time::MassaTime;
+use parking_lot::RwLock;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc};
+use std::thread::JoinHandle;
+use tracing::{info, warn};
+
+/// Disk monitor configuration
+#[derive(Debug, Clone)]
+pub struct DiskMonitorConfig {
+    /// monitored directories
+    pub paths: Vec<PathBuf>,
+    /// interval between two checks
+    pub check_interval: MassaTime,
+    /// free space in bytes below which the node switches to read-only mode
+    pub read_only_threshold: u64,
+    /// free space in bytes above which the node leaves read-only mode
+    pub resume_threshold: u64,
+}
+
+/// Background thread updating the disk status of the node. Stops when dropped.
+pub struct DiskMonitor {
+    stop_tx: Option<mpsc::Sender<()>>,
+    thread_handle: Option<JoinHandle<()>>,
+}
+
+impl DiskMonitor {
+    /// Checks the disks once, then keeps updating `status` every `check_interval`
+    pub fn start(config: DiskMonitorConfig, status: Arc<RwLock<DiskStatus>>) -> Self {
+        if cfg!(not(unix)) || config.paths.is_empty() {
+            warn!("disk monitoring is disabled: unsupported system or no monitored directory");
+            return DiskMonitor {
+                stop_tx: None,
+                thread_handle: None,
+            };
+        }
+        check_disks(&config, &status);
+        let (stop_tx, stop_rx) = mpsc::channel::<()>();
+        let thread_handle = std::thread::Builder::new()
+            .name("disk-monitor".into())
+            .spawn(move || {
+                while let Err(mpsc::RecvTimeoutError::Timeout) =
+                    stop_rx.recv_timeout(config.check_interval.to_duration())
+                {
+                    check_disks(&config, &status);
+                }
+            })
+            .expect("failed to spawn disk monitor thread");
+        DiskMonitor {
+            stop_tx: Some(stop_tx),
+            thread_handle: Some(thread_handle),
+        }
+    }
+}
+
+impl Drop for DiskMonitor {
+    fn drop(&mut self) {
+        // disconnecting the channel stops the thread
+        self.stop_tx = None;
+        if let Some(handle) = self.thread_handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Measures the monitored directories and switches the read-only mode if needed
+fn check_disks(config: &DiskMonitorConfig, status: &RwLock<DiskStatus>) {
+    let mut usage = Vec::with_capacity(config.paths.len());
+    for path in &config.paths {
+        match filesystem_space(path) {
+            Ok((available_bytes, total_bytes)) => usage.push(DiskUsage {
+                path: path.clone(),
+                used_bytes: directory_size(path),
+                available_bytes,
+                total_bytes,
+            }),
+            Err(err) => warn!(
+                "disk monitor could not get the free space of {}: {}",
+                path.display(),
+                err
+            ),
+        }
+    }
+    let min_available = usage
+        .iter()
+        .map(|usage| usage.available_bytes)
+        .min()
+        .unwrap_or(u64::MAX);
+
+    let mut status = status.write();
+    let read_only = next_read_only(config, status.read_only, min_available);
+    if read_only && !status.read_only {
+        warn!(
+            "low disk space ({} MB free): switching to read-only mode, block and endorsement production is paused",
+            min_available / 1_000_000
+        );
+    } else if !read_only && status.read_only {
+        info!(
+            "disk space is back to {} MB free: leaving read-only mode, production resumes",
+            min_available / 1_000_000
+        );
+    }
+    status.read_only = read_only;
+    status.usage = usage;
+}
+
+/// Whether the node is in read-only mode after a check finding `min_available` free bytes on the most filled disk.
+/// Between the thresholds, the node stays in its current mode so that it does not switch at every check.
+fn next_read_only(config: &DiskMonitorConfig, read_only: bool, min_available: u64) -> bool {
+    if read_only {
+        min_available < config.resume_threshold
+    } else {
+        min_available < config.read_only_threshold
+    }
+}
+
+/// Total size of the files in a directory, in bytes.
+/// Files that disappear while walking (ex: compacted database files) are ignored.
+fn directory_size(path: &Path) -> u64 {
+    let Ok(metadata) = std::fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| directory_size(&entry.path()))
+        .sum()
+}
+
+/// Free space available to the node and total size of the filesystem of a path, in bytes
+#[cfg(unix)]
+#[allow(clippy::unnecessary_cast)] // the types of the statvfs fields depend on the platform
+fn filesystem_space(path: &Path) -> std::io::Result<(u64, u64)> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = std::ffi::CString::new(path.as_os_str().as_bytes())?;
+    // SAFETY: `c_path` is a valid null-terminated string and `stat` is a plain struct filled by the call
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    let block_size = stat.f_frsize as u64;
+    Ok((
+        (stat.f_bavail as u64).saturating_mul(block_size),
+        (stat.f_blocks as u64).saturating_mul(block_size),
+    ))
+}
+
+/// Free space monitoring is only supported on unix systems
+#[cfg(not(unix))]
+fn filesystem_space(_path: &Path) -> std::io::Result<(u64, u64)> {
+    Err(std::io::ErrorKind::Unsupported.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> DiskMonitorConfig {
+        DiskMonitorConfig {
+            paths: Vec::new(),
+            check_interval: MassaTime::from_millis(1000),
+            read_only_threshold: 100,
+            resume_threshold: 200,
+        }
+    }
+
+    #[test]
+    fn test_read_only_enter() {
+        let config = config();
+        assert!(!next_read_only(&config, false, 100));
+        assert!(next_read_only(&config, false, 99));
+        assert!(next_read_only(&config, false, 0));
+    }
+
+    #[test]
+    fn test_read_only_leave() {
+        let config = config();
+        assert!(next_read_only(&config, true, 199));
+        assert!(!next_read_only(&config, true, 200));
+        // no measured disk
+        assert!(!next_read_only(&config, true, u64::MAX));
+    }
+
+    #[test]
+    fn test_read_only_between_thresholds() {
+        let config = config();
+        // the mode is kept between the thresholds
+        for min_available in [100, 150, 199] {
+            assert!(!next_read_only(&config, false, min_available));
+            assert!(next_read_only(&config, true, min_available));
+        }
+    }
+}
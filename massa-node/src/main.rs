@@ -4,26 +4,41 @@
 #![warn(missing_docs)]
 #![warn(unused_crate_dependencies)]
 extern crate massa_logging;
+use crate::build_info::build_info;
+use crate::crash_report::{submit_pending_reports, CrashReportConfig, CrashReporter};
+use crate::disk_monitor::{DiskMonitor, DiskMonitorConfig};
+use crate::memory_monitor::{MemoryMonitor, MemoryMonitorConfig};
+use crate::resync_policy::{ResyncPolicy, ResyncPolicyConfig};
 use crate::settings::SETTINGS;
+use crate::supervisor::{Decision, Supervisor, SupervisorConfig};
 
 use crossbeam_channel::{Receiver, TryRecvError};
 use dialoguer::Password;
 use massa_api::{ApiServer, ApiV2, Private, Public, RpcServer, StopHandle, API};
 use massa_api_exports::config::APIConfig;
 use massa_async_pool::AsyncPoolConfig;
-use massa_bootstrap::{get_state, start_bootstrap_server, BootstrapConfig, BootstrapManager};
+use massa_bootstrap::{
+    fetch_trusted_state, get_state, start_bootstrap_server, start_trusted_sync_server,
+    BootstrapConfig, BootstrapManager, TrustedSyncConfig, TrustedSyncServer,
+};
+use massa_channel::{channel_with_priority, EventBus, NodeEvent};
 use massa_consensus_exports::events::ConsensusEvent;
 use massa_consensus_exports::{ConsensusChannels, ConsensusConfig, ConsensusManager};
 use massa_consensus_worker::start_consensus_worker;
 use massa_executed_ops::ExecutedOpsConfig;
-use massa_execution_exports::{ExecutionConfig, ExecutionManager, GasCosts, StorageCostsConstants};
+use massa_execution_exports::{
+    ExecutionConfig, ExecutionManager, GasCosts, ReadOnlyExecutionLimits, StorageCostsConstants,
+    StorageRentConstants,
+};
 use massa_execution_worker::start_execution_worker;
 use massa_factory_exports::{FactoryChannels, FactoryConfig, FactoryManager};
 use massa_factory_worker::start_factory;
-use massa_final_state::{FinalState, FinalStateConfig};
+use massa_final_state::{
+    restore_journal_ledger, restore_ledger_backup, FinalState, FinalStateConfig,
+};
 use massa_ledger_exports::LedgerConfig;
 use massa_ledger_worker::FinalLedger;
-use massa_logging::massa_trace;
+use massa_logging::{massa_trace, RecentLogs};
 use massa_models::address::Address;
 use massa_models::config::constants::{
     ASYNC_POOL_BOOTSTRAP_PART_SIZE, BLOCK_REWARD, BOOTSTRAP_RANDOMNESS_SIZE_BYTES, CHANNEL_SIZE,
@@ -31,37 +46,47 @@ use massa_models::config::constants::{
     EXECUTED_OPS_BOOTSTRAP_PART_SIZE, GENESIS_KEY, GENESIS_TIMESTAMP, INITIAL_DRAW_SEED,
     LEDGER_COST_PER_BYTE, LEDGER_ENTRY_BASE_SIZE, LEDGER_ENTRY_DATASTORE_BASE_SIZE,
     LEDGER_PART_SIZE_MESSAGE_BYTES, MAX_ADVERTISE_LENGTH, MAX_ASK_BLOCKS_PER_MESSAGE,
-    MAX_ASYNC_GAS, MAX_ASYNC_MESSAGE_DATA, MAX_ASYNC_POOL_LENGTH, MAX_BLOCK_SIZE,
-    MAX_BOOTSTRAP_ASYNC_POOL_CHANGES, MAX_BOOTSTRAP_BLOCKS, MAX_BOOTSTRAP_ERROR_LENGTH,
-    MAX_BOOTSTRAP_FINAL_STATE_PARTS_SIZE, MAX_BOOTSTRAP_MESSAGE_SIZE, MAX_BYTECODE_LENGTH,
-    MAX_CONSENSUS_BLOCKS_IDS, MAX_DATASTORE_ENTRY_COUNT, MAX_DATASTORE_KEY_LENGTH,
-    MAX_DATASTORE_VALUE_LENGTH, MAX_DEFERRED_CREDITS_LENGTH, MAX_ENDORSEMENTS_PER_MESSAGE,
-    MAX_EXECUTED_OPS_CHANGES_LENGTH, MAX_EXECUTED_OPS_LENGTH, MAX_FUNCTION_NAME_LENGTH,
-    MAX_GAS_PER_BLOCK, MAX_LEDGER_CHANGES_COUNT, MAX_MESSAGE_SIZE, MAX_OPERATIONS_PER_BLOCK,
-    MAX_OPERATION_DATASTORE_ENTRY_COUNT, MAX_OPERATION_DATASTORE_KEY_LENGTH,
-    MAX_OPERATION_DATASTORE_VALUE_LENGTH, MAX_PARAMETERS_SIZE, MAX_PRODUCTION_STATS_LENGTH,
-    MAX_ROLLS_COUNT_LENGTH, NETWORK_CONTROLLER_CHANNEL_SIZE, NETWORK_EVENT_CHANNEL_SIZE,
-    NETWORK_NODE_COMMAND_CHANNEL_SIZE, NETWORK_NODE_EVENT_CHANNEL_SIZE, OPERATION_VALIDITY_PERIODS,
-    PERIODS_PER_CYCLE, POOL_CONTROLLER_CHANNEL_SIZE, POS_MISS_RATE_DEACTIVATION_THRESHOLD,
-    POS_SAVED_CYCLES, PROTOCOL_CONTROLLER_CHANNEL_SIZE, PROTOCOL_EVENT_CHANNEL_SIZE, ROLL_PRICE,
-    T0, THREAD_COUNT, VERSION,
+    MAX_ASYNC_GAS, MAX_ASYNC_MESSAGE_DATA, MAX_ASYNC_POOL_LENGTH, MAX_ASYNC_PRIORITY_FEE,
+    MAX_BLOCK_SIZE, MAX_BOOTSTRAP_ASYNC_POOL_CHANGES, MAX_BOOTSTRAP_BLOCKS,
+    MAX_BOOTSTRAP_ERROR_LENGTH, MAX_BOOTSTRAP_FINAL_STATE_PARTS_SIZE, MAX_BOOTSTRAP_MESSAGE_SIZE,
+    MAX_BYTECODE_LENGTH, MAX_CONSENSUS_BLOCKS_IDS, MAX_DATASTORE_ENTRY_COUNT,
+    MAX_DATASTORE_KEY_LENGTH, MAX_DATASTORE_VALUE_LENGTH, MAX_DEFERRED_CREDITS_LENGTH,
+    MAX_ENDORSEMENTS_PER_MESSAGE, MAX_EXECUTED_OPS_CHANGES_LENGTH, MAX_EXECUTED_OPS_LENGTH,
+    MAX_FUNCTION_NAME_LENGTH, MAX_GAS_PER_BLOCK, MAX_LEDGER_CHANGES_COUNT, MAX_MESSAGE_SIZE,
+    MAX_OPERATIONS_PER_BLOCK, MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+    MAX_OPERATION_DATASTORE_KEY_LENGTH, MAX_OPERATION_DATASTORE_VALUE_LENGTH, MAX_PARAMETERS_SIZE,
+    MAX_PRODUCTION_STATS_LENGTH, MAX_ROLLS_COUNT_LENGTH, MAX_SCHEDULED_TRANSFERS_PER_ADDRESS,
+    MAX_SCHEDULED_TRANSFER_DELAY, NETWORK_NODE_COMMAND_CHANNEL_SIZE,
+    NETWORK_NODE_EVENT_CHANNEL_SIZE, OPERATION_VALIDITY_PERIODS, PERIODS_PER_CYCLE,
+    POOL_CONTROLLER_CHANNEL_SIZE, POS_MISS_RATE_DEACTIVATION_THRESHOLD, POS_SAVED_CYCLES,
+    PROTOCOL_CONTROLLER_CHANNEL_SIZE, PROTOCOL_EVENT_CHANNEL_SIZE, ROLL_PRICE,
+    STORAGE_RENT_GRACE_CYCLES, STORAGE_RENT_PER_BYTE, T0, THREAD_COUNT,
+    TRANSACTION_MEMO_COST_PER_BYTE, VERSION,
 };
-use massa_models::config::CONSENSUS_BOOTSTRAP_PART_SIZE;
+use massa_models::config::{CapacityScale, PruningCoordinator, CONSENSUS_BOOTSTRAP_PART_SIZE};
+use massa_models::slot::SyncLag;
+use massa_models::stats::{BlockProductionStats, DiskStatus, MemoryStatus};
 use massa_network_exports::{Establisher, NetworkConfig, NetworkManager};
 use massa_network_worker::start_network_controller;
-use massa_pool_exports::{PoolChannels, PoolConfig, PoolManager};
+use massa_pool_exports::{
+    BytecodeAnalyzer, OperationAnalyzer, PoolChannels, PoolConfig, PoolManager,
+};
 use massa_pool_worker::start_pool_controller;
-use massa_pos_exports::{PoSConfig, SelectorConfig, SelectorManager};
+use massa_pos_exports::{
+    JobScheduler, JobSchedulerConfig, PeriodicJob, PoSConfig, SelectorConfig, SelectorManager,
+};
 use massa_pos_worker::start_selector_worker;
 use massa_protocol_exports::{
     ProtocolCommand, ProtocolCommandSender, ProtocolConfig, ProtocolManager, ProtocolReceivers,
     ProtocolSenders,
 };
 use massa_protocol_worker::start_protocol_controller;
-use massa_storage::Storage;
-use massa_time::MassaTime;
+use massa_storage::{LeakDetector, Storage};
+use massa_time::{ClockOffset, MassaTime};
+use massa_versioning::MipStoreConfig;
 use massa_wallet::Wallet;
 use parking_lot::RwLock;
+use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::thread::sleep;
@@ -73,11 +98,21 @@ use tokio::sync::{broadcast, mpsc};
 use tracing::{error, info, warn};
 use tracing_subscriber::filter::{filter_fn, LevelFilter};
 
+mod build_info;
+mod crash_report;
+#[cfg(feature = "sandbox")]
+mod devnet;
+mod disk_monitor;
+mod memory_monitor;
+mod resync_policy;
 mod settings;
+mod supervisor;
 
 async fn launch(
     _args: &Args,
     node_wallet: Arc<RwLock<Wallet>>,
+    restore_backup: Option<PathBuf>,
+    recent_logs: RecentLogs,
 ) -> (
     Receiver<ConsensusEvent>,
     Option<BootstrapManager>,
@@ -92,16 +127,54 @@ async fn launch(
     StopHandle,
     StopHandle,
     StopHandle,
+    Option<LeakDetector>,
+    DiskMonitor,
+    MemoryMonitor,
+    Option<PeriodicJob>,
+    Option<TrustedSyncServer>,
+    Arc<RwLock<FinalState>>,
+    SyncLag,
 ) {
     info!("Node version : {}", *VERSION);
+    let build_info = build_info();
+    info!(
+        "Node build : commit {}{}, {}, build hash {}",
+        build_info.git_commit,
+        if build_info.git_dirty { " (dirty)" } else { "" },
+        build_info.rustc_version,
+        build_info.build_hash
+    );
     if let Some(end) = *END_TIMESTAMP {
         if MassaTime::now().expect("could not get now time") > end {
             panic!("This episode has come to an end, please get the latest testnet node version to continue");
         }
     }
 
+    // Retention rules of the objects kept in RAM by consensus, pool and execution
+    let pruning_coordinator = PruningCoordinator::new(SETTINGS.retention, THREAD_COUNT);
+    if pruning_coordinator.is_constrained() {
+        warn!(
+            "retention limits scaled down to fit in the memory budget: {:?}",
+            pruning_coordinator.rules()
+        );
+    }
+    info!(
+        "estimated memory use of the retained objects: {} MB",
+        pruning_coordinator.estimated_memory_usage() / 1_000_000
+    );
+    let retention = *pruning_coordinator.rules();
+
     // Storage shared by multiple components.
-    let shared_storage: Storage = Storage::create_root();
+    let shared_storage: Storage = if SETTINGS.storage.diagnostics {
+        warn!("storage diagnostics are enabled, the node will be slower");
+        Storage::create_root_with_diagnostics()
+    } else {
+        Storage::create_root()
+    };
+    let storage_leak_detector = shared_storage.start_leak_detector(
+        SETTINGS.storage.leak_check_interval.to_duration(),
+        SETTINGS.storage.leak_check_window,
+    );
 
     // init final state
     let ledger_config = LedgerConfig {
@@ -122,6 +195,8 @@ async fn launch(
         periods_per_cycle: PERIODS_PER_CYCLE,
         thread_count: THREAD_COUNT,
         cycle_history_length: POS_SAVED_CYCLES,
+        cycle_info_retention: retention.pos_cycle_info_retention,
+        deferred_credits_retention: retention.pos_deferred_credits_retention,
         credits_bootstrap_part_size: DEFERRED_CREDITS_BOOTSTRAP_PART_SIZE,
     };
     let executed_ops_config = ExecutedOpsConfig {
@@ -138,11 +213,45 @@ async fn launch(
         periods_per_cycle: PERIODS_PER_CYCLE,
         initial_seed_string: INITIAL_DRAW_SEED.into(),
         initial_rolls_path: SETTINGS.selector.initial_rolls_path.clone(),
+        genesis_path: SETTINGS.ledger.genesis_path.clone(),
+        journal_path: SETTINGS.ledger.final_state_journal_path.clone(),
+        journal_checkpoint_interval: SETTINGS.ledger.final_state_journal_checkpoint_interval,
+        mip_store_config: MipStoreConfig {
+            mips: serde_json::from_str(
+                &std::fs::read_to_string(&SETTINGS.versioning.mip_list_path)
+                    .expect("could not read the MIP list file"),
+            )
+            .expect("could not parse the MIP list file"),
+            block_count_considered: SETTINGS.versioning.block_count_considered,
+            activation_threshold_percent: SETTINGS.versioning.activation_threshold,
+        },
     };
 
-    // Remove current disk ledger if there is one
+    // recover the final state from its journal if the previous run left one, instead of bootstrapping from scratch
+    let mut recover_journal = false;
+    if let (None, Some(journal_path)) = (&restore_backup, &SETTINGS.ledger.final_state_journal_path)
+    {
+        match restore_journal_ledger(journal_path, &SETTINGS.ledger.disk_ledger_path) {
+            Ok(restored) => recover_journal = restored,
+            Err(err) => warn!(
+                "could not restore the ledger of the final state journal: {}",
+                err
+            ),
+        }
+    }
+
+    // Remove current disk ledger if there is one, or replace it by the one of the backup to restore
     // NOTE: this is temporary, since we cannot currently handle bootstrap from remaining ledger
-    if SETTINGS.ledger.disk_ledger_path.exists() {
+    if recover_journal {
+        info!("recovering the final state from its journal");
+    } else if let Some(backup_path) = &restore_backup {
+        info!(
+            "restoring the final state backup of {}",
+            backup_path.display()
+        );
+        restore_ledger_backup(backup_path, &SETTINGS.ledger.disk_ledger_path)
+            .expect("could not restore the ledger backup");
+    } else if SETTINGS.ledger.disk_ledger_path.exists() {
         std::fs::remove_dir_all(SETTINGS.ledger.disk_ledger_path.clone())
             .expect("disk ledger delete failed");
     }
@@ -150,6 +259,26 @@ async fn launch(
     // Create final ledger
     let ledger = FinalLedger::new(ledger_config.clone());
 
+    // monitor disk usage, switching the node to read-only mode when free space is low
+    let disk_status: Arc<RwLock<DiskStatus>> = Default::default();
+    let mut monitored_paths = vec![SETTINGS.ledger.disk_ledger_path.clone()];
+    if SETTINGS.execution.event_store.enabled {
+        monitored_paths.push(SETTINGS.execution.event_store.path.clone());
+    }
+    if SETTINGS.execution.operation_index.enabled {
+        monitored_paths.push(SETTINGS.execution.operation_index.path.clone());
+    }
+    monitored_paths.extend(SETTINGS.disk_monitor.extra_paths.iter().cloned());
+    let disk_monitor = DiskMonitor::start(
+        DiskMonitorConfig {
+            paths: monitored_paths,
+            check_interval: SETTINGS.disk_monitor.check_interval,
+            read_only_threshold: SETTINGS.disk_monitor.read_only_threshold,
+            resume_threshold: SETTINGS.disk_monitor.resume_threshold,
+        },
+        disk_status.clone(),
+    );
+
     // launch selector worker
     let (selector_manager, selector_controller) = start_selector_worker(SelectorConfig {
         max_draw_cache: SETTINGS.selector.max_draw_cache,
@@ -158,6 +287,7 @@ async fn launch(
         endorsement_count: ENDORSEMENT_COUNT,
         periods_per_cycle: PERIODS_PER_CYCLE,
         genesis_address: Address::from_public_key(&GENESIS_KEY.get_public_key()),
+        permissioned_validators: SETTINGS.selector.permissioned_validators.clone(),
     })
     .expect("could not start selector worker");
 
@@ -170,6 +300,55 @@ async fn launch(
         )
         .expect("could not init final state"),
     ));
+    if let Some(backup_path) = &restore_backup {
+        let manifest = final_state
+            .write()
+            .restore_backup(backup_path)
+            .expect("could not restore the final state backup");
+        info!(
+            "final state restored at slot {} with hash {}",
+            manifest.slot, manifest.final_state_hash
+        );
+    }
+    if recover_journal {
+        let mut final_state_guard = final_state.write();
+        match final_state_guard.recover_from_journal() {
+            Ok(slot) => info!("final state recovered from its journal at slot {}", slot),
+            Err(err) => {
+                warn!(
+                    "could not recover the final state from its journal, bootstrapping from scratch: {}",
+                    err
+                );
+                final_state_guard.reset();
+                recover_journal = false;
+            }
+        }
+    }
+
+    // run the heavy background jobs away from the production and endorsement slots of the node
+    let staking_wallet = node_wallet.clone();
+    let job_scheduler = JobScheduler::new(
+        JobSchedulerConfig {
+            thread_count: THREAD_COUNT,
+            t0: T0,
+            genesis_timestamp: *GENESIS_TIMESTAMP,
+            margin_before: SETTINGS.scheduler.margin_before,
+            margin_after: SETTINGS.scheduler.margin_after,
+            max_delay: SETTINGS.scheduler.max_delay,
+        },
+        selector_controller.clone(),
+        Arc::new(move || staking_wallet.read().get_staking_address_list()),
+    );
+    final_state.write().set_job_scheduler(job_scheduler.clone());
+    let ledger_compaction = SETTINGS
+        .scheduler
+        .ledger_compaction_interval
+        .map(|interval| {
+            let final_state = final_state.clone();
+            job_scheduler.spawn_periodic("ledger-compaction", interval, move || {
+                final_state.read().ledger.compact()
+            })
+        });
 
     // interrupt signal listener
     let stop_signal = signal::ctrl_c();
@@ -177,6 +356,8 @@ async fn launch(
 
     let bootstrap_config: BootstrapConfig = BootstrapConfig {
         bootstrap_list: SETTINGS.bootstrap.bootstrap_list.clone(),
+        bootstrap_directories: SETTINGS.bootstrap.bootstrap_directories.clone(),
+        directory_timeout: SETTINGS.bootstrap.directory_timeout,
         bootstrap_protocol: SETTINGS.bootstrap.bootstrap_protocol,
         bootstrap_whitelist_path: SETTINGS.bootstrap.bootstrap_whitelist_path.clone(),
         bootstrap_blacklist_path: SETTINGS.bootstrap.bootstrap_blacklist_path.clone(),
@@ -195,6 +376,8 @@ async fn launch(
         per_ip_min_interval: SETTINGS.bootstrap.per_ip_min_interval,
         ip_list_max_size: SETTINGS.bootstrap.ip_list_max_size,
         max_bytes_read_write: SETTINGS.bootstrap.max_bytes_read_write,
+        apply_queue_length: SETTINGS.bootstrap.apply_queue_length,
+        trusted_checkpoint: SETTINGS.bootstrap.trusted_checkpoint,
         max_bootstrap_message_size: MAX_BOOTSTRAP_MESSAGE_SIZE,
         max_datastore_key_length: MAX_DATASTORE_KEY_LENGTH,
         randomness_size_bytes: BOOTSTRAP_RANDOMNESS_SIZE_BYTES,
@@ -227,6 +410,9 @@ async fn launch(
         max_consensus_block_ids: MAX_CONSENSUS_BLOCKS_IDS,
     };
 
+    // event bus shared by the workers and the subsystems reporting their events
+    let event_bus = EventBus::new(SETTINGS.channels.event_bus_capacity);
+
     // bootstrap
     let bootstrap_state = tokio::select! {
         _ = &mut stop_signal => {
@@ -240,22 +426,39 @@ async fn launch(
             *VERSION,
             *GENESIS_TIMESTAMP,
             *END_TIMESTAMP,
+            restore_backup.is_some() || recover_journal,
         ) => match res {
             Ok(vals) => vals,
             Err(err) => panic!("critical error detected in the bootstrap process: {}", err)
         }
     };
+    event_bus.publish(NodeEvent::BootstrapFinished {
+        slot: final_state.read().slot,
+        from_server: bootstrap_state.peers.is_some(),
+    });
+
+    // on a private network, the draws fail if no permissioned validator has rolls
+    if let Some(validators) = &SETTINGS.selector.permissioned_validators {
+        if !final_state.read().pos_state.has_rolls_among(validators) {
+            panic!("none of the permissioned validators has rolls, no slot can be drawn");
+        }
+    }
 
     let network_config: NetworkConfig = NetworkConfig {
         bind: SETTINGS.network.bind,
         routable_ip: SETTINGS.network.routable_ip,
+        allow_local_peers: SETTINGS.network.allow_local_peers,
         protocol_port: SETTINGS.network.protocol_port,
         connect_timeout: SETTINGS.network.connect_timeout,
+        handshake_phase_timeout: SETTINGS.network.handshake_phase_timeout,
+        max_in_handshakes: SETTINGS.network.max_in_handshakes,
         wakeup_interval: SETTINGS.network.wakeup_interval,
         initial_peers_file: SETTINGS.network.initial_peers_file.clone(),
         peers_file: SETTINGS.network.peers_file.clone(),
+        ban_list_file: SETTINGS.network.ban_list_file.clone(),
         keypair_file: SETTINGS.network.keypair_file.clone(),
         peer_types_config: SETTINGS.network.peer_types_config.clone(),
+        peer_diversity: SETTINGS.network.peer_diversity.clone(),
         max_in_connections_per_ip: SETTINGS.network.max_in_connections_per_ip,
         max_idle_peers: SETTINGS.network.max_idle_peers,
         max_banned_peers: SETTINGS.network.max_banned_peers,
@@ -270,6 +473,13 @@ async fn launch(
         max_operations_per_message: SETTINGS.network.max_operations_per_message,
         max_bytes_read: SETTINGS.network.max_bytes_read,
         max_bytes_write: SETTINGS.network.max_bytes_write,
+        publish_node_metadata: SETTINGS.network.publish_node_metadata,
+        operator_contact: SETTINGS.network.operator_contact.clone(),
+        region: SETTINGS.network.region.clone(),
+        node_capabilities: SETTINGS.network.node_capabilities.clone(),
+        probe_interval: SETTINGS.network.probe_interval,
+        probe_max_losses: SETTINGS.network.probe_max_losses,
+        final_slot_gossip_interval: SETTINGS.network.final_slot_gossip_interval,
         max_ask_blocks: MAX_ASK_BLOCKS_PER_MESSAGE,
         max_operations_per_block: MAX_OPERATIONS_PER_BLOCK,
         thread_count: THREAD_COUNT,
@@ -283,19 +493,24 @@ async fn launch(
         max_op_datastore_value_length: MAX_OPERATION_DATASTORE_VALUE_LENGTH,
         max_function_name_length: MAX_FUNCTION_NAME_LENGTH,
         max_parameters_size: MAX_PARAMETERS_SIZE,
-        controller_channel_size: NETWORK_CONTROLLER_CHANNEL_SIZE,
-        event_channel_size: NETWORK_EVENT_CHANNEL_SIZE,
+        command_channel: SETTINGS.channels.network_command,
+        event_channel: SETTINGS.channels.network_event,
         node_command_channel_size: NETWORK_NODE_COMMAND_CHANNEL_SIZE,
         node_event_channel_size: NETWORK_NODE_EVENT_CHANNEL_SIZE,
     };
 
-    // launch network controller
+    // launch network controller, estimating the network clock offset and the sync lag for the factory and the API
+    let clock_offset = ClockOffset::new();
+    let sync_lag = SyncLag::new();
     let (network_command_sender, network_event_receiver, network_manager, private_key, node_id) =
         start_network_controller(
             &network_config,
             Establisher::new(),
             bootstrap_state.peers,
             *VERSION,
+            clock_offset.clone(),
+            sync_lag.clone(),
+            event_bus.clone(),
         )
         .await
         .expect("could not start network controller");
@@ -318,10 +533,16 @@ async fn launch(
     };
     // launch execution module
     let execution_config = ExecutionConfig {
-        max_final_events: SETTINGS.execution.max_final_events,
+        max_final_events: retention.max_final_events,
+        max_staking_rewards_cycles: SETTINGS.execution.max_staking_rewards_cycles,
+        max_staking_rewards_addresses: SETTINGS.execution.max_staking_rewards_addresses,
         readonly_queue_length: SETTINGS.execution.readonly_queue_length,
         cursor_delay: SETTINGS.execution.cursor_delay,
         max_async_gas: MAX_ASYNC_GAS,
+        max_async_priority_fee: MAX_ASYNC_PRIORITY_FEE,
+        transaction_memo_cost_per_byte: TRANSACTION_MEMO_COST_PER_BYTE,
+        max_scheduled_transfers_per_address: MAX_SCHEDULED_TRANSFERS_PER_ADDRESS,
+        max_scheduled_transfer_delay: MAX_SCHEDULED_TRANSFER_DELAY,
         max_gas_per_block: MAX_GAS_PER_BLOCK,
         roll_price: ROLL_PRICE,
         thread_count: THREAD_COUNT,
@@ -338,8 +559,28 @@ async fn launch(
         max_datastore_value_size: MAX_DATASTORE_VALUE_LENGTH,
         max_module_cache_size: SETTINGS.execution.max_module_cache_size,
         storage_costs_constants,
+        storage_rent_constants: StorageRentConstants {
+            rent_per_byte: STORAGE_RENT_PER_BYTE,
+            grace_cycles: STORAGE_RENT_GRACE_CYCLES,
+        },
         max_read_only_gas: SETTINGS.execution.max_read_only_gas,
+        read_only_limits: ReadOnlyExecutionLimits {
+            max_call_depth: SETTINGS.execution.read_only_max_call_depth,
+            max_event_count: SETTINGS.execution.read_only_max_event_count,
+            max_datastore_value_size: SETTINGS
+                .execution
+                .read_only_max_datastore_value_size
+                .min(MAX_DATASTORE_VALUE_LENGTH),
+            max_cpu_time_us: SETTINGS.execution.read_only_max_cpu_time,
+            max_memory_bytes: SETTINGS.execution.read_only_max_memory,
+        },
+        max_execution_forks: SETTINGS.execution.max_execution_forks,
+        execution_fork_lifetime: SETTINGS.execution.execution_fork_lifetime,
         initial_vesting_path: SETTINGS.execution.initial_vesting_path.clone(),
+        state_sink: SETTINGS.execution.state_sink.clone(),
+        state_sink_queue_length: SETTINGS.execution.state_sink_queue_length,
+        event_store: SETTINGS.execution.event_store.clone(),
+        operation_index: SETTINGS.execution.operation_index.clone(),
         gas_costs: GasCosts::new(
             SETTINGS.execution.abi_gas_costs_file.clone(),
             SETTINGS.execution.wasm_gas_costs_file.clone(),
@@ -352,6 +593,9 @@ async fn launch(
         selector_controller.clone(),
     );
 
+    // capacities of the pool and protocol caches, lowered by the memory monitor under memory pressure
+    let capacity_scale = CapacityScale::new();
+
     // launch pool controller
     let pool_config = PoolConfig {
         thread_count: THREAD_COUNT,
@@ -359,28 +603,42 @@ async fn launch(
         max_block_gas: MAX_GAS_PER_BLOCK,
         roll_price: ROLL_PRICE,
         max_block_endorsement_count: ENDORSEMENT_COUNT,
+        periods_per_cycle: PERIODS_PER_CYCLE,
         operation_validity_periods: OPERATION_VALIDITY_PERIODS,
         max_operations_per_block: MAX_OPERATIONS_PER_BLOCK,
-        max_operation_pool_size_per_thread: SETTINGS.pool.max_pool_size_per_thread,
-        max_endorsements_pool_size_per_thread: SETTINGS.pool.max_pool_size_per_thread,
+        max_operation_pool_size_per_thread: retention.max_operations_per_thread,
+        max_endorsements_pool_size_per_thread: retention.max_endorsements_per_thread,
         channels_size: POOL_CONTROLLER_CHANNEL_SIZE,
         broadcast_enabled: SETTINGS.api.enable_ws,
-        broadcast_operations_capacity: SETTINGS.pool.broadcast_operations_capacity,
     };
 
     let pool_channels = PoolChannels {
-        operation_sender: broadcast::channel(pool_config.broadcast_operations_capacity).0,
+        event_bus: event_bus.clone(),
+        capacity_scale: capacity_scale.clone(),
+        selector: selector_controller.clone(),
     };
 
+    let bytecode_analyzer: Option<Box<dyn OperationAnalyzer>> =
+        if SETTINGS.pool.bytecode_analyzer.enabled {
+            Some(Box::new(BytecodeAnalyzer::new(
+                SETTINGS.pool.bytecode_analyzer.clone(),
+            )))
+        } else {
+            None
+        };
     let (pool_manager, pool_controller) = start_pool_controller(
         pool_config,
-        &shared_storage,
+        &shared_storage.clone_without_refs().with_owner("pool"),
         execution_controller.clone(),
         pool_channels.clone(),
+        bytecode_analyzer,
     );
 
-    let (protocol_command_sender, protocol_command_receiver) =
-        mpsc::channel::<ProtocolCommand>(PROTOCOL_CONTROLLER_CHANNEL_SIZE);
+    let (protocol_command_sender, protocol_command_receiver) = channel_with_priority(
+        "protocol_command",
+        SETTINGS.channels.protocol_command,
+        ProtocolCommand::priority,
+    );
 
     let consensus_config = ConsensusConfig {
         genesis_timestamp: *GENESIS_TIMESTAMP,
@@ -388,18 +646,19 @@ async fn launch(
         thread_count: THREAD_COUNT,
         t0: T0,
         genesis_key: GENESIS_KEY.clone(),
-        max_discarded_blocks: SETTINGS.consensus.max_discarded_blocks,
+        max_discarded_blocks: retention.max_discarded_blocks,
         future_block_processing_max_periods: SETTINGS.consensus.future_block_processing_max_periods,
-        max_future_processing_blocks: SETTINGS.consensus.max_future_processing_blocks,
-        max_dependency_blocks: SETTINGS.consensus.max_dependency_blocks,
+        max_future_processing_blocks: retention.max_future_processing_blocks,
+        max_dependency_blocks: retention.max_dependency_blocks,
+        max_orphans_per_node: SETTINGS.consensus.max_orphans_per_node,
         delta_f0: DELTA_F0,
         operation_validity_periods: OPERATION_VALIDITY_PERIODS,
         periods_per_cycle: PERIODS_PER_CYCLE,
         stats_timespan: SETTINGS.consensus.stats_timespan,
         max_send_wait: SETTINGS.consensus.max_send_wait,
-        force_keep_final_periods: SETTINGS.consensus.force_keep_final_periods,
+        force_keep_final_periods: retention.force_keep_final_periods,
         endorsement_count: ENDORSEMENT_COUNT,
-        block_db_prune_interval: SETTINGS.consensus.block_db_prune_interval,
+        block_db_prune_interval: retention.block_db_prune_interval,
         max_item_return_count: SETTINGS.consensus.max_item_return_count,
         max_gas_per_block: MAX_GAS_PER_BLOCK,
         channel_size: CHANNEL_SIZE,
@@ -408,6 +667,9 @@ async fn launch(
         broadcast_blocks_headers_capacity: SETTINGS.consensus.broadcast_blocks_headers_capacity,
         broadcast_blocks_capacity: SETTINGS.consensus.broadcast_blocks_capacity,
         broadcast_filled_blocks_capacity: SETTINGS.consensus.broadcast_filled_blocks_capacity,
+        broadcast_reorgs_capacity: SETTINGS.consensus.broadcast_reorgs_capacity,
+        trusted_checkpoint: SETTINGS.bootstrap.trusted_checkpoint,
+        permissioned_validators: SETTINGS.selector.permissioned_validators.clone(),
     };
 
     let (consensus_event_sender, consensus_event_receiver) =
@@ -423,13 +685,16 @@ async fn launch(
         block_sender: broadcast::channel(consensus_config.broadcast_blocks_capacity).0,
         filled_block_sender: broadcast::channel(consensus_config.broadcast_filled_blocks_capacity)
             .0,
+        reorg_sender: broadcast::channel(consensus_config.broadcast_reorgs_capacity).0,
+        mip_store: final_state.read().mip_store.clone(),
+        event_bus: event_bus.clone(),
     };
 
     let (consensus_controller, consensus_manager) = start_consensus_worker(
         consensus_config,
         consensus_channels.clone(),
         bootstrap_state.graph,
-        shared_storage.clone(),
+        shared_storage.clone().with_owner("consensus"),
     );
 
     // launch protocol controller
@@ -446,11 +711,16 @@ async fn launch(
         max_simultaneous_ask_blocks_per_node: SETTINGS
             .protocol
             .max_simultaneous_ask_blocks_per_node,
+        block_propagation_strategy: SETTINGS.protocol.block_propagation_strategy,
+        erasure_coding_min_operations: SETTINGS.protocol.erasure_coding_min_operations,
         max_send_wait: SETTINGS.protocol.max_send_wait,
         operation_batch_buffer_capacity: SETTINGS.protocol.operation_batch_buffer_capacity,
         operation_announcement_buffer_capacity: SETTINGS
             .protocol
             .operation_announcement_buffer_capacity,
+        operation_announcement_overflow_policy: SETTINGS
+            .protocol
+            .operation_announcement_overflow_policy,
         operation_batch_proc_period: SETTINGS.protocol.operation_batch_proc_period,
         asked_operations_pruning_period: SETTINGS.protocol.asked_operations_pruning_period,
         operation_announcement_interval: SETTINGS.protocol.operation_announcement_interval,
@@ -463,6 +733,10 @@ async fn launch(
         t0: T0,
         max_operations_propagation_time: SETTINGS.protocol.max_operations_propagation_time,
         max_endorsements_propagation_time: SETTINGS.protocol.max_endorsements_propagation_time,
+        max_signature_batch_size: SETTINGS.protocol.max_signature_batch_size,
+        max_pending_signature_verifications: SETTINGS.protocol.max_pending_signature_verifications,
+        signature_verification_shards: SETTINGS.protocol.signature_verification_shards,
+        max_invalid_messages_per_minute: SETTINGS.protocol.max_invalid_messages_per_minute,
     };
 
     let protocol_senders = ProtocolSenders {
@@ -480,12 +754,65 @@ async fn launch(
         protocol_senders.clone(),
         consensus_controller.clone(),
         pool_controller.clone(),
-        shared_storage.clone(),
+        shared_storage.clone().with_owner("protocol"),
+        capacity_scale.clone(),
     )
     .await
     .expect("could not start protocol controller");
 
+    // follow the resident memory, shrinking the pool and protocol caches when it approaches the cap
+    let memory_status: Arc<RwLock<MemoryStatus>> = Default::default();
+    let memory_monitor = MemoryMonitor::start(
+        MemoryMonitorConfig {
+            rss_cap: SETTINGS.memory_monitor.rss_cap,
+            check_interval: SETTINGS.memory_monitor.check_interval,
+            shrink_threshold: SETTINGS.memory_monitor.shrink_threshold,
+            grow_threshold: SETTINGS.memory_monitor.grow_threshold,
+            min_capacity: SETTINGS.memory_monitor.min_capacity,
+            limits: [
+                (
+                    "pool_operations_per_thread",
+                    pool_config.max_operation_pool_size_per_thread,
+                ),
+                (
+                    "pool_endorsements_per_thread",
+                    pool_config.max_endorsements_pool_size_per_thread,
+                ),
+                (
+                    "protocol_checked_operations",
+                    protocol_config.max_known_ops_size,
+                ),
+                (
+                    "protocol_checked_endorsements",
+                    protocol_config.max_known_endorsements_size,
+                ),
+                (
+                    "protocol_checked_headers",
+                    protocol_config.max_node_known_blocks_size,
+                ),
+                (
+                    "protocol_relayed_headers",
+                    protocol_config.max_known_blocks_size,
+                ),
+                (
+                    "protocol_node_known_operations",
+                    protocol_config.max_node_known_ops_size,
+                ),
+                (
+                    "protocol_node_known_endorsements",
+                    protocol_config.max_node_known_endorsements_size,
+                ),
+            ]
+            .into_iter()
+            .map(|(name, limit)| (name.to_string(), limit))
+            .collect(),
+        },
+        capacity_scale,
+        memory_status.clone(),
+    );
+
     // launch factory
+    let production_stats: Arc<RwLock<BlockProductionStats>> = Default::default();
     let factory_config = FactoryConfig {
         thread_count: THREAD_COUNT,
         genesis_timestamp: *GENESIS_TIMESTAMP,
@@ -494,13 +821,27 @@ async fn launch(
         max_block_size: MAX_BLOCK_SIZE as u64,
         max_block_gas: MAX_GAS_PER_BLOCK,
         max_operations_per_block: MAX_OPERATIONS_PER_BLOCK,
+        production_check_delay: SETTINGS.factory.production_check_delay,
+        late_production_threshold: SETTINGS.factory.late_production_threshold,
+        production_timings_history: SETTINGS.factory.production_timings_history,
+        miss_alert_webhook: SETTINGS.factory.miss_alert_webhook.clone(),
+        operation_denylist_path: SETTINGS.factory.operation_denylist_path.clone(),
+        production_guard_path: SETTINGS.factory.production_guard_path.clone(),
+        compensate_clock_offset: SETTINGS.factory.compensate_clock_offset,
+        max_clock_offset_compensation: SETTINGS.factory.max_clock_offset_compensation,
+        max_sync_lag: SETTINGS.factory.max_sync_lag,
     };
     let factory_channels = FactoryChannels {
         selector: selector_controller.clone(),
         consensus: consensus_controller.clone(),
         pool: pool_controller.clone(),
         protocol: ProtocolCommandSender(protocol_command_sender.clone()),
-        storage: shared_storage.clone(),
+        storage: shared_storage.clone_without_refs().with_owner("factory"),
+        disk_status: disk_status.clone(),
+        production_stats: production_stats.clone(),
+        mip_store: final_state.read().mip_store.clone(),
+        clock_offset: clock_offset.clone(),
+        sync_lag: sync_lag.clone(),
     };
     let factory_manager = start_factory(factory_config, node_wallet.clone(), factory_channels);
 
@@ -517,6 +858,23 @@ async fn launch(
     .await
     .unwrap();
 
+    // serve the final state to the trusted nodes of the operator
+    let trusted_sync_server = match SETTINGS.trusted_sync.bind {
+        Some(bind) if SETTINGS.trusted_sync.secret.is_empty() => {
+            warn!(
+                "trusted sync is not served on {}: the trusted sync secret is empty",
+                bind
+            );
+            None
+        }
+        Some(bind) => Some(
+            start_trusted_sync_server(trusted_sync_config(), bind, final_state.clone())
+                .await
+                .expect("could not start trusted sync server"),
+        ),
+        None => None,
+    };
+
     let api_config: APIConfig = APIConfig {
         bind_private: SETTINGS.api.bind_private,
         bind_public: SETTINGS.api.bind_public,
@@ -536,6 +894,12 @@ async fn launch(
         ping_interval: SETTINGS.api.ping_interval,
         enable_http: SETTINGS.api.enable_http,
         enable_ws: SETTINGS.api.enable_ws,
+        cache_max_entries: SETTINGS.api.cache_max_entries,
+        max_query_results: SETTINGS.api.max_query_results,
+        private_relay_delay: SETTINGS.api.private_relay_delay,
+        private_relay_expiry: SETTINGS.api.private_relay_expiry,
+        verified_contracts_path: SETTINGS.api.verified_contracts_path.clone(),
+        max_datastore_chunk_size: SETTINGS.api.max_datastore_chunk_size,
         max_datastore_value_length: MAX_DATASTORE_VALUE_LENGTH,
         max_op_datastore_entry_count: MAX_OPERATION_DATASTORE_ENTRY_COUNT,
         max_op_datastore_key_length: MAX_OPERATION_DATASTORE_KEY_LENGTH,
@@ -546,6 +910,7 @@ async fn launch(
         genesis_timestamp: *GENESIS_TIMESTAMP,
         t0: T0,
         periods_per_cycle: PERIODS_PER_CYCLE,
+        endorsement_count: ENDORSEMENT_COUNT,
     };
 
     // spawn Massa API
@@ -554,8 +919,11 @@ async fn launch(
         consensus_channels,
         execution_controller.clone(),
         pool_channels,
+        event_bus,
+        shared_storage.clone_without_refs(),
         api_config.clone(),
         *VERSION,
+        build_info.clone(),
     );
     let api_handle = api
         .serve(&SETTINGS.api.bind_api, &api_config)
@@ -569,9 +937,13 @@ async fn launch(
     // spawn private API
     let (api_private, api_private_stop_rx) = API::<Private>::new(
         network_command_sender.clone(),
+        ProtocolCommandSender(protocol_command_sender.clone()),
         execution_controller.clone(),
         api_config.clone(),
         node_wallet,
+        shared_storage.clone_without_refs(),
+        final_state.clone(),
+        recent_logs,
     );
     let api_private_handle = api_private
         .serve(&SETTINGS.api.bind_private, &api_config)
@@ -588,9 +960,14 @@ async fn launch(
         ProtocolCommandSender(protocol_command_sender.clone()),
         network_config,
         *VERSION,
+        build_info,
         network_command_sender.clone(),
         node_id,
-        shared_storage.clone(),
+        shared_storage.clone_without_refs().with_owner("api"),
+        disk_status,
+        memory_status,
+        production_stats,
+        final_state.clone(),
     );
     let api_public_handle = api_public
         .serve(&SETTINGS.api.bind_public, &api_config)
@@ -640,9 +1017,24 @@ async fn launch(
         api_private_handle,
         api_public_handle,
         api_handle,
+        storage_leak_detector,
+        disk_monitor,
+        memory_monitor,
+        ledger_compaction,
+        trusted_sync_server,
+        final_state,
+        sync_lag,
     )
 }
 
+fn trusted_sync_config() -> TrustedSyncConfig {
+    TrustedSyncConfig {
+        secret: SETTINGS.trusted_sync.secret.clone(),
+        work_dir: SETTINGS.trusted_sync.work_dir.clone(),
+        timeout: SETTINGS.trusted_sync.timeout,
+    }
+}
+
 struct Managers {
     bootstrap_manager: Option<BootstrapManager>,
     consensus_manager: Box<dyn ConsensusManager>,
@@ -672,10 +1064,9 @@ async fn stop(
 ) {
     // stop bootstrap
     if let Some(bootstrap_manager) = bootstrap_manager {
-        bootstrap_manager
-            .stop()
-            .await
-            .expect("bootstrap server shutdown failed")
+        if let Err(err) = bootstrap_manager.stop().await {
+            warn!("bootstrap server shutdown failed: {}", err);
+        }
     }
 
     // stop public API
@@ -690,11 +1081,14 @@ async fn stop(
     // stop factory
     factory_manager.stop();
 
-    // stop protocol controller
-    let network_event_receiver = protocol_manager
-        .stop()
-        .await
-        .expect("protocol shutdown failed");
+    // stop protocol controller, the network event receiver is lost if the protocol worker crashed
+    let network_event_receiver = match protocol_manager.stop().await {
+        Ok(network_event_receiver) => Some(network_event_receiver),
+        Err(err) => {
+            warn!("protocol shutdown failed: {}", err);
+            None
+        }
+    };
 
     // stop consensus
     consensus_manager.stop();
@@ -713,10 +1107,13 @@ async fn stop(
     //let protocol_pool_event_receiver = pool_manager.stop().await.expect("pool shutdown failed");
 
     // stop network controller
-    network_manager
-        .stop(network_event_receiver)
-        .await
-        .expect("network shutdown failed");
+    let network_stop = match network_event_receiver {
+        Some(network_event_receiver) => network_manager.stop(network_event_receiver).await,
+        None => network_manager.stop_without_events().await,
+    };
+    if let Err(err) = network_stop {
+        warn!("network shutdown failed: {}", err);
+    }
 
     // note that FinalLedger gets destroyed as soon as its Arc count goes to zero
 }
@@ -727,6 +1124,27 @@ struct Args {
     #[structopt(short = "p", long = "pwd")]
     password: Option<String>,
 
+    /// Restore the final state from a backup directory created with the `node_create_backup` command,
+    /// then only catch up with the changes that happened since the backup
+    #[structopt(long = "restore-backup", parse(from_os_str))]
+    restore_backup: Option<PathBuf>,
+
+    /// Copy the final state of another node of the operator, authenticated by the trusted sync secret,
+    /// then only catch up with the changes that happened since the copy
+    #[structopt(long = "sync-from")]
+    sync_from: Option<SocketAddr>,
+
+    /// Generate a local devnet of this number of nodes, with their keys, genesis ledger and configurations,
+    /// then launch its nodes
+    #[cfg(feature = "sandbox")]
+    #[structopt(long = "launch-devnet")]
+    launch_devnet: Option<usize>,
+
+    /// Directory in which the devnet is generated
+    #[cfg(feature = "sandbox")]
+    #[structopt(long = "devnet-dir", parse(from_os_str), default_value = "devnet")]
+    devnet_dir: PathBuf,
+
     #[cfg(feature = "deadlock_detection")]
     /// Deadlocks detector
     #[structopt(
@@ -765,6 +1183,11 @@ fn load_wallet(password: Option<String>, path: &Path) -> anyhow::Result<Arc<RwLo
 
 #[paw::main]
 fn main(args: Args) -> anyhow::Result<()> {
+    #[cfg(feature = "sandbox")]
+    if let Some(node_count) = args.launch_devnet {
+        return devnet::launch_devnet(node_count, &args.devnet_dir);
+    }
+
     let tokio_rt = tokio::runtime::Builder::new_multi_thread()
         .thread_name_fn(|| {
             static ATOMIC_ID: AtomicUsize = AtomicUsize::new(0);
@@ -781,37 +1204,85 @@ fn main(args: Args) -> anyhow::Result<()> {
 async fn run(args: Args) -> anyhow::Result<()> {
     use tracing_subscriber::prelude::*;
     // spawn the console server in the background, returning a `Layer`:
+    let level_filter = match SETTINGS.logging.level {
+        4 => LevelFilter::TRACE,
+        3 => LevelFilter::DEBUG,
+        2 => LevelFilter::INFO,
+        1 => LevelFilter::WARN,
+        _ => LevelFilter::ERROR,
+    };
     let tracing_layer = tracing_subscriber::fmt::layer()
-        .with_filter(match SETTINGS.logging.level {
-            4 => LevelFilter::TRACE,
-            3 => LevelFilter::DEBUG,
-            2 => LevelFilter::INFO,
-            1 => LevelFilter::WARN,
-            _ => LevelFilter::ERROR,
-        })
+        .with_filter(level_filter)
         .with_filter(filter_fn(|metadata| {
             metadata.target().starts_with("massa") // ignore non-massa logs
         }));
+    // recent logs kept in memory for the private API and the crash reports
+    let recent_logs = RecentLogs::new(SETTINGS.logging.recent_logs_per_level);
+    let recent_logs_layer = recent_logs
+        .clone()
+        .with_filter(level_filter)
+        .with_filter(filter_fn(|metadata| metadata.target().starts_with("massa")));
     // build a `Subscriber` by combining layers with a `tracing_subscriber::Registry`:
     tracing_subscriber::registry()
         // add the console layer to the subscriber or default layers...
         .with(tracing_layer)
+        .with(recent_logs_layer)
         .init();
 
     // Setup panic handlers,
     // and when a panic occurs,
     // run default handler,
-    // and then shutdown.
-    let default_panic = std::panic::take_hook();
-    std::panic::set_hook(Box::new(move |info| {
-        default_panic(info);
-        std::process::exit(1);
-    }));
+    // and then restart the node if a worker crashed, or shutdown.
+    let mut supervisor = Supervisor::install(SupervisorConfig {
+        max_restarts: SETTINGS.supervisor.max_restarts,
+        restart_window: SETTINGS.supervisor.restart_window,
+        diagnostics_path: SETTINGS.supervisor.diagnostics_path.clone(),
+        backup_path: SETTINGS.supervisor.backup_path.clone(),
+    });
+    // the crash reports are written before the supervisor handles the panic
+    let crash_report_config = CrashReportConfig {
+        directory: SETTINGS.crash_report.directory.clone(),
+        submit_endpoint: SETTINGS.crash_report.submit_endpoint.clone(),
+        submit_timeout: SETTINGS.crash_report.submit_timeout,
+        log_count: SETTINGS.crash_report.log_count,
+    };
+    if SETTINGS.crash_report.enabled {
+        CrashReporter::new(
+            crash_report_config.clone(),
+            *VERSION,
+            &format!("{:?}", *SETTINGS),
+            recent_logs.clone(),
+        )
+        .install();
+    }
 
     // load or create wallet, asking for password if necessary
     let node_wallet = load_wallet(args.password.clone(), &SETTINGS.factory.staking_wallet_path)?;
 
+    // the backup is only restored at the first launch, restarts bootstrap as usual
+    let mut restore_backup = args.restore_backup.clone();
+    if let Some(addr) = args.sync_from {
+        if restore_backup.is_some() {
+            anyhow::bail!("--sync-from and --restore-backup cannot be used together");
+        }
+        // the received copy is restored like a local backup
+        restore_backup = Some(fetch_trusted_state(&trusted_sync_config(), addr).await?);
+    }
+
+    // re-bootstraps the node when it stays behind the network
+    let mut resync_policy = ResyncPolicy::new(ResyncPolicyConfig {
+        max_lag_periods: SETTINGS.resync.max_lag_periods,
+        grace_period: SETTINGS.resync.grace_period,
+        min_interval: SETTINGS.resync.min_interval,
+        thread_count: THREAD_COUNT,
+    });
+
     loop {
+        // reports of the previous runs and of the crashes that restarted the node
+        if SETTINGS.crash_report.enabled {
+            submit_pending_reports(&crash_report_config).await;
+        }
+
         let (
             consensus_event_receiver,
             bootstrap_manager,
@@ -826,7 +1297,22 @@ async fn run(args: Args) -> anyhow::Result<()> {
             api_private_handle,
             api_public_handle,
             api_handle,
-        ) = launch(&args, node_wallet.clone()).await;
+            // stop when dropped
+            _storage_leak_detector,
+            _disk_monitor,
+            _memory_monitor,
+            ledger_compaction,
+            _trusted_sync_server,
+            final_state,
+            sync_lag,
+        ) = launch(
+            &args,
+            node_wallet.clone(),
+            restore_backup.take(),
+            recent_logs.clone(),
+        )
+        .await;
+        resync_policy.reset();
 
         // interrupt signal listener
         let (tx, rx) = crossbeam_channel::bounded(1);
@@ -836,8 +1322,25 @@ async fn run(args: Args) -> anyhow::Result<()> {
         });
 
         // loop over messages
+        let mut reattach_state = false;
+        let mut restarts_exhausted = false;
         let restart = loop {
             massa_trace!("massa-node.main.run.select", {});
+            if let Some(crash) = supervisor.try_recv_crash() {
+                match supervisor.on_crash(&crash) {
+                    Decision::Restart {
+                        reattach_state: reattach,
+                    } => {
+                        reattach_state = reattach;
+                        break true;
+                    }
+                    Decision::Shutdown => {
+                        restarts_exhausted = true;
+                        break false;
+                    }
+                }
+            }
+
             match consensus_event_receiver.try_recv() {
                 Ok(evt) => match evt {
                     ConsensusEvent::NeedSync => {
@@ -855,6 +1358,11 @@ async fn run(args: Args) -> anyhow::Result<()> {
                 _ => {}
             };
 
+            if resync_policy.check(&sync_lag) {
+                warn!("in response to a desynchronization, the node is going to bootstrap again");
+                break true;
+            }
+
             match api_private_stop_rx.try_recv() {
                 Ok(_) => {
                     info!("stop command received from private API");
@@ -896,7 +1404,34 @@ async fn run(args: Args) -> anyhow::Result<()> {
             api_handle,
         )
         .await;
+        supervisor.drain_crashes();
+        // waits for the end of a running compaction
+        drop(ledger_compaction);
+
+        if reattach_state {
+            // the workers are stopped, the final state does not change anymore
+            let backup_path = supervisor.backup_path();
+            if backup_path.exists() {
+                std::fs::remove_dir_all(backup_path)?;
+            }
+            match final_state.read().create_backup(backup_path) {
+                Ok(manifest) => {
+                    info!("final state re-attached at slot {}", manifest.slot);
+                    restore_backup = Some(backup_path.clone());
+                }
+                Err(err) => warn!(
+                    "could not back up the final state, the node will bootstrap again: {}",
+                    err
+                ),
+            }
+        }
+        // release the ledger database before launching again
+        drop(final_state);
 
+        if restarts_exhausted {
+            supervisor.write_diagnostics()?;
+            anyhow::bail!("the node workers keep crashing, see the crash diagnostics");
+        }
         if !restart {
             break;
         }
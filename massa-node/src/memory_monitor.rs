@@ -0,0 +1,172 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Resident memory monitoring.
+//!
+//! Periodically measures the resident memory of the node process. When it goes above `shrink_threshold` percent
+//! of the configured cap (ex: during a gossip storm filling the pool and the protocol caches), the capacities of
+//! the pool and protocol caches are lowered through the shared `CapacityScale`, a step at each check, down to
+//! `min_capacity` percent of the configured ones. They grow back a step at each check once the resident memory
+//! is below `grow_threshold` percent of the cap, the gap between both thresholds preventing oscillations.
+
+use massa_models::config::{CapacityScale, FULL_CAPACITY_PERMILLE};
+use massa_models::stats::MemoryStatus;
+use massa_time::MassaTime;
+use parking_lot::RwLock;
+use std::collections::BTreeMap;
+use std::sync::{mpsc, Arc};
+use std::thread::JoinHandle;
+use tracing::{info, warn};
+
+/// Factor applied to the capacity scale at each check above the shrink threshold, in percent
+const SHRINK_FACTOR: u32 = 75;
+/// Increase of the capacity scale at each check below the grow threshold, in thousandths
+const GROW_STEP: u32 = 100;
+
+/// Memory monitor configuration
+#[derive(Debug, Clone)]
+pub struct MemoryMonitorConfig {
+    /// resident memory cap in bytes, the capacities are never shrunk if none
+    pub rss_cap: Option<u64>,
+    /// interval between two checks
+    pub check_interval: MassaTime,
+    /// percentage of the cap above which the capacities shrink
+    pub shrink_threshold: u64,
+    /// percentage of the cap below which the capacities grow back
+    pub grow_threshold: u64,
+    /// percentage of the configured capacities below which they are never shrunk
+    pub min_capacity: u64,
+    /// configured capacities affected by the scale, by name
+    pub limits: BTreeMap<String, usize>,
+}
+
+/// Background thread following the memory pressure of the node. Stops when dropped.
+pub struct MemoryMonitor {
+    stop_tx: Option<mpsc::Sender<()>>,
+    thread_handle: Option<JoinHandle<()>>,
+}
+
+impl MemoryMonitor {
+    /// Checks the resident memory once, then keeps updating `scale` and `status` every `check_interval`
+    pub fn start(
+        config: MemoryMonitorConfig,
+        scale: CapacityScale,
+        status: Arc<RwLock<MemoryStatus>>,
+    ) -> Self {
+        check_memory(&config, &scale, &status);
+        if config.rss_cap.is_none() || resident_memory().is_err() {
+            if config.rss_cap.is_some() {
+                warn!("memory monitoring is disabled: unsupported system");
+            }
+            return MemoryMonitor {
+                stop_tx: None,
+                thread_handle: None,
+            };
+        }
+        let (stop_tx, stop_rx) = mpsc::channel::<()>();
+        let thread_handle = std::thread::Builder::new()
+            .name("memory-monitor".into())
+            .spawn(move || {
+                while let Err(mpsc::RecvTimeoutError::Timeout) =
+                    stop_rx.recv_timeout(config.check_interval.to_duration())
+                {
+                    check_memory(&config, &scale, &status);
+                }
+            })
+            .expect("failed to spawn memory monitor thread");
+        MemoryMonitor {
+            stop_tx: Some(stop_tx),
+            thread_handle: Some(thread_handle),
+        }
+    }
+}
+
+impl Drop for MemoryMonitor {
+    fn drop(&mut self) {
+        // disconnecting the channel stops the thread
+        self.stop_tx = None;
+        if let Some(handle) = self.thread_handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Measures the resident memory, moves the capacity scale if needed and reports the live limits
+fn check_memory(
+    config: &MemoryMonitorConfig,
+    scale: &CapacityScale,
+    status: &RwLock<MemoryStatus>,
+) {
+    let rss_bytes = match resident_memory() {
+        Ok(rss) => Some(rss),
+        Err(err) => {
+            if config.rss_cap.is_some() {
+                warn!(
+                    "memory monitor could not measure the resident memory: {}",
+                    err
+                );
+            }
+            None
+        }
+    };
+
+    if let (Some(rss), Some(cap)) = (rss_bytes, config.rss_cap) {
+        let permille = scale.get_permille();
+        let usage = rss as u128 * 100;
+        let min_permille = (config.min_capacity.min(100) * 10) as u32;
+        if usage >= cap as u128 * config.shrink_threshold as u128 && permille > min_permille {
+            let shrunk = (permille * SHRINK_FACTOR / 100).max(min_permille);
+            warn!(
+                "resident memory at {} MB out of a {} MB cap: shrinking the pool and protocol caches to {:.1}% of their capacity",
+                rss / 1_000_000,
+                cap / 1_000_000,
+                shrunk as f64 / 10.0
+            );
+            scale.set_permille(shrunk);
+        } else if usage <= cap as u128 * config.grow_threshold as u128
+            && permille < FULL_CAPACITY_PERMILLE
+        {
+            let grown = (permille + GROW_STEP).min(FULL_CAPACITY_PERMILLE);
+            info!(
+                "resident memory back to {} MB out of a {} MB cap: growing the pool and protocol caches to {:.1}% of their capacity",
+                rss / 1_000_000,
+                cap / 1_000_000,
+                grown as f64 / 10.0
+            );
+            scale.set_permille(grown);
+        }
+    }
+
+    let mut status = status.write();
+    status.rss_bytes = rss_bytes;
+    status.rss_cap = config.rss_cap;
+    status.capacity_permille = scale.get_permille();
+    status.live_limits = config
+        .limits
+        .iter()
+        .map(|(name, limit)| (name.clone(), scale.apply(*limit)))
+        .collect();
+}
+
+/// Resident memory of the node process, in bytes
+#[cfg(target_os = "linux")]
+fn resident_memory() -> std::io::Result<u64> {
+    // the second field of statm is the number of resident pages
+    let statm = std::fs::read_to_string("/proc/self/statm")?;
+    let pages: u64 = statm
+        .split_whitespace()
+        .nth(1)
+        .and_then(|field| field.parse().ok())
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidData, "invalid statm"))?;
+    // SAFETY: sysconf has no precondition
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    if page_size <= 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(pages.saturating_mul(page_size as u64))
+}
+
+/// Resident memory monitoring is only supported on linux
+#[cfg(not(target_os = "linux"))]
+fn resident_memory() -> std::io::Result<u64> {
+    Err(std::io::ErrorKind::Unsupported.into())
+}
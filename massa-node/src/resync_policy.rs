@@ -0,0 +1,201 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Automatic re-bootstrap of a desynchronized node.
+//!
+//! The network estimates how many slots the final slot of the node lags behind the median of the final slots
+//! gossiped by its peers. When that lag stays above `max_lag_periods` periods for a whole `grace_period`,
+//! the node is considered desynchronized: the main loop stops the workers, the factory first so that nothing
+//! more is produced on the stale state, then bootstraps again in-process and resumes, as it does when consensus
+//! detects a desynchronization. Two automatic re-bootstraps are at least `min_interval` apart, so that a network
+//! the bootstrap servers cannot catch up with does not make the node bootstrap in a loop.
+
+use massa_models::slot::SyncLag;
+use massa_time::MassaTime;
+use std::time::Instant;
+use tracing::{info, warn};
+
+/// Re-bootstrap policy configuration
+#[derive(Debug, Clone)]
+pub struct ResyncPolicyConfig {
+    /// lag in periods above which the node is desynchronized, 0 to disable the automatic re-bootstrap
+    pub max_lag_periods: u64,
+    /// time the lag must stay above the threshold before bootstrapping again
+    pub grace_period: MassaTime,
+    /// minimal time between two automatic re-bootstraps
+    pub min_interval: MassaTime,
+    /// number of threads, to convert the lag from slots to periods
+    pub thread_count: u8,
+}
+
+/// Decides when the node bootstraps again, kept across the launches of the node
+pub struct ResyncPolicy {
+    config: ResyncPolicyConfig,
+    /// since when the lag of the current launch is above the threshold
+    lagging_since: Option<Instant>,
+    /// latest automatic re-bootstrap
+    last_resync: Option<Instant>,
+}
+
+impl ResyncPolicy {
+    /// Creates the policy of the node
+    pub fn new(config: ResyncPolicyConfig) -> Self {
+        ResyncPolicy {
+            config,
+            lagging_since: None,
+            last_resync: None,
+        }
+    }
+
+    /// Forgets the lag of the previous launch, the `SyncLag` of a new launch being unknown until the peers gossip
+    pub fn reset(&mut self) {
+        self.lagging_since = None;
+    }
+
+    /// Whether the node lagged behind the network for the whole grace period and should bootstrap again.
+    /// A positive answer is counted as a re-bootstrap.
+    pub fn check(&mut self, sync_lag: &SyncLag) -> bool {
+        self.check_at(sync_lag, Instant::now())
+    }
+
+    /// `check` at the instant `now`
+    fn check_at(&mut self, sync_lag: &SyncLag, now: Instant) -> bool {
+        if self.config.max_lag_periods == 0 {
+            return false;
+        }
+        let max_lag = self
+            .config
+            .max_lag_periods
+            .saturating_mul(self.config.thread_count as u64);
+        let lag = match sync_lag.get() {
+            Some(lag) if lag > max_lag => lag,
+            _ => {
+                if self.lagging_since.take().is_some() {
+                    info!("the node caught up with the network");
+                }
+                return false;
+            }
+        };
+        let lagging_since = *self.lagging_since.get_or_insert_with(|| {
+            warn!(
+                "the final slot of the node lags {} periods behind the network",
+                lag / self.config.thread_count as u64
+            );
+            now
+        });
+        if now.saturating_duration_since(lagging_since) < self.config.grace_period.to_duration() {
+            return false;
+        }
+        if let Some(last_resync) = self.last_resync {
+            if now.saturating_duration_since(last_resync) < self.config.min_interval.to_duration() {
+                return false;
+            }
+        }
+        warn!(
+            "the final slot of the node lagged more than {} periods behind the network for {} ms",
+            self.config.max_lag_periods,
+            self.config.grace_period.to_millis()
+        );
+        self.last_resync = Some(now);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_models::slot::Slot;
+    use std::time::Duration;
+
+    const THREAD_COUNT: u8 = 2;
+
+    fn new_policy(max_lag_periods: u64) -> ResyncPolicy {
+        ResyncPolicy::new(ResyncPolicyConfig {
+            max_lag_periods,
+            grace_period: MassaTime::from_millis(10_000),
+            min_interval: MassaTime::from_millis(60_000),
+            thread_count: THREAD_COUNT,
+        })
+    }
+
+    /// Estimate of a lag of `slots` slots
+    fn lag(slots: u64) -> SyncLag {
+        let sync_lag = SyncLag::new();
+        let local = Slot::new(10, 0);
+        let network_index = 10 * THREAD_COUNT as u64 + slots;
+        let network = Slot::new(
+            network_index / THREAD_COUNT as u64,
+            (network_index % THREAD_COUNT as u64) as u8,
+        );
+        sync_lag.set_median(Some(local), vec![network], THREAD_COUNT);
+        assert_eq!(sync_lag.get(), Some(slots));
+        sync_lag
+    }
+
+    fn secs(start: Instant, secs: u64) -> Instant {
+        start + Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn test_resync_after_grace_period() {
+        let mut policy = new_policy(2);
+        let start = Instant::now();
+
+        // 2 periods are 4 slots: the node is desynchronized from 5 slots on
+        assert!(!policy.check_at(&lag(4), start));
+        assert!(policy.lagging_since.is_none());
+        assert!(!policy.check_at(&lag(5), start));
+        assert!(!policy.check_at(&lag(5), secs(start, 9)));
+        assert!(policy.check_at(&lag(5), secs(start, 10)));
+
+        // catching up restarts the grace period
+        let mut policy = new_policy(2);
+        assert!(!policy.check_at(&lag(5), start));
+        assert!(!policy.check_at(&lag(4), secs(start, 5)));
+        assert!(!policy.check_at(&lag(5), secs(start, 10)));
+        assert!(!policy.check_at(&lag(5), secs(start, 19)));
+        assert!(policy.check_at(&lag(5), secs(start, 20)));
+
+        // an unknown lag is not a desynchronization
+        let mut policy = new_policy(2);
+        assert!(!policy.check_at(&lag(5), start));
+        assert!(!policy.check_at(&SyncLag::new(), secs(start, 10)));
+        assert!(policy.lagging_since.is_none());
+    }
+
+    #[test]
+    fn test_resync_min_interval() {
+        let mut policy = new_policy(2);
+        let start = Instant::now();
+        assert!(!policy.check_at(&lag(5), start));
+        assert!(policy.check_at(&lag(5), secs(start, 10)));
+        assert!(!policy.check_at(&lag(5), secs(start, 11)));
+        assert!(!policy.check_at(&lag(5), secs(start, 69)));
+        assert!(policy.check_at(&lag(5), secs(start, 70)));
+    }
+
+    #[test]
+    fn test_resync_reset() {
+        let mut policy = new_policy(2);
+        let start = Instant::now();
+        assert!(!policy.check_at(&lag(5), start));
+        // the lag of the previous launch does not count in the grace period of the new one
+        policy.reset();
+        assert!(!policy.check_at(&lag(5), secs(start, 10)));
+        assert!(!policy.check_at(&lag(5), secs(start, 19)));
+        assert!(policy.check_at(&lag(5), secs(start, 20)));
+        // but the latest re-bootstrap is kept
+        policy.reset();
+        assert!(!policy.check_at(&lag(5), secs(start, 30)));
+        assert!(!policy.check_at(&lag(5), secs(start, 40)));
+        assert!(policy.check_at(&lag(5), secs(start, 80)));
+    }
+
+    #[test]
+    fn test_resync_disabled() {
+        let mut policy = new_policy(0);
+        let start = Instant::now();
+        assert!(!policy.check_at(&lag(1_000), start));
+        assert!(!policy.check_at(&lag(1_000), secs(start, 3_600)));
+        assert!(policy.lagging_since.is_none());
+    }
+}
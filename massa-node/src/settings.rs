@@ -1,16 +1,30 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 //! Build here the default node settings from the configuration file toml
+use std::collections::BTreeSet;
 use std::path::PathBuf;
 
 use enum_map::EnumMap;
-use massa_bootstrap::IpType;
-use massa_models::{config::build_massa_settings, node::NodeId};
+use massa_api_exports::config::PrivateRelayExpiry;
+use massa_bootstrap::{BootstrapDirectory, IpType};
+use massa_channel::ChannelConfig;
+use massa_execution_exports::{EventStoreConfig, OperationIndexConfig, StateSinkConfig};
+use massa_models::{
+    address::Address,
+    checkpoint::TrustedCheckpoint,
+    config::{build_massa_settings, RetentionRules},
+    node::NodeId,
+};
 use massa_time::MassaTime;
 use serde::Deserialize;
 use std::net::{IpAddr, SocketAddr};
 
-use massa_network_exports::{settings::PeerTypeConnectionConfig, PeerType};
+use massa_network_exports::{
+    settings::{PeerDiversityConfig, PeerTypeConnectionConfig},
+    PeerType,
+};
+use massa_pool_exports::BytecodeAnalyzerConfig;
+use massa_protocol_exports::{AnnouncementOverflowPolicy, BlockPropagationStrategy};
 
 lazy_static::lazy_static! {
     pub static ref SETTINGS: Settings = build_massa_settings("massa-node", "MASSA_NODE");
@@ -19,45 +33,67 @@ lazy_static::lazy_static! {
 #[derive(Debug, Deserialize, Clone)]
 pub struct LoggingSettings {
     pub level: usize,
+    pub recent_logs_per_level: usize,
 }
 
 #[derive(Clone, Debug, Deserialize)]
 pub struct ExecutionSettings {
-    pub max_final_events: usize,
+    pub max_staking_rewards_cycles: usize,
+    pub max_staking_rewards_addresses: usize,
     pub readonly_queue_length: usize,
     pub cursor_delay: MassaTime,
     pub stats_time_window_duration: MassaTime,
     pub max_read_only_gas: u64,
+    pub read_only_max_call_depth: u16,
+    pub read_only_max_event_count: u64,
+    pub read_only_max_datastore_value_size: u64,
+    pub read_only_max_cpu_time: u64,
+    pub read_only_max_memory: u64,
+    pub max_execution_forks: usize,
+    pub execution_fork_lifetime: MassaTime,
     pub abi_gas_costs_file: PathBuf,
     pub wasm_gas_costs_file: PathBuf,
     pub max_module_cache_size: u32,
     pub initial_vesting_path: PathBuf,
+    pub state_sink_queue_length: usize,
+    pub state_sink: StateSinkConfig,
+    pub event_store: EventStoreConfig,
+    pub operation_index: OperationIndexConfig,
 }
 
 #[derive(Clone, Debug, Deserialize)]
 pub struct SelectionSettings {
     pub max_draw_cache: usize,
     pub initial_rolls_path: PathBuf,
+    pub permissioned_validators: Option<BTreeSet<Address>>,
 }
 
 #[derive(Clone, Debug, Deserialize)]
 pub struct LedgerSettings {
     pub initial_ledger_path: PathBuf,
+    pub genesis_path: Option<PathBuf>,
     pub disk_ledger_path: PathBuf,
     pub final_history_length: usize,
+    pub final_state_journal_path: Option<PathBuf>,
+    pub final_state_journal_checkpoint_interval: u64,
 }
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct NetworkSettings {
     pub bind: SocketAddr,
     pub routable_ip: Option<IpAddr>,
+    pub allow_local_peers: bool,
     pub protocol_port: u16,
     pub connect_timeout: MassaTime,
+    pub handshake_phase_timeout: MassaTime,
+    pub max_in_handshakes: usize,
     pub wakeup_interval: MassaTime,
     pub initial_peers_file: PathBuf,
     pub peers_file: PathBuf,
+    pub ban_list_file: PathBuf,
     pub keypair_file: PathBuf,
     pub peer_types_config: EnumMap<PeerType, PeerTypeConnectionConfig>,
+    pub peer_diversity: PeerDiversityConfig,
     pub max_in_connections_per_ip: usize,
     pub max_idle_peers: usize,
     pub max_banned_peers: usize,
@@ -72,12 +108,21 @@ pub struct NetworkSettings {
     pub max_operations_per_message: u32,
     pub max_bytes_read: f64,
     pub max_bytes_write: f64,
+    pub publish_node_metadata: bool,
+    pub operator_contact: Option<String>,
+    pub region: Option<String>,
+    pub node_capabilities: Vec<String>,
+    pub probe_interval: MassaTime,
+    pub probe_max_losses: u32,
+    pub final_slot_gossip_interval: MassaTime,
 }
 
 /// Bootstrap configuration.
 #[derive(Debug, Deserialize, Clone)]
 pub struct BootstrapSettings {
     pub bootstrap_list: Vec<(SocketAddr, NodeId)>,
+    pub bootstrap_directories: Vec<BootstrapDirectory>,
+    pub directory_timeout: MassaTime,
     pub bootstrap_protocol: IpType,
     pub bootstrap_whitelist_path: PathBuf,
     pub bootstrap_blacklist_path: PathBuf,
@@ -95,8 +140,11 @@ pub struct BootstrapSettings {
     pub per_ip_min_interval: MassaTime,
     pub ip_list_max_size: usize,
     pub max_bytes_read_write: f64,
+    pub apply_queue_length: usize,
     /// Allocated time with which to manage the bootstrap process
     pub bootstrap_timeout: MassaTime,
+    /// Point of the final history the bootstrapped state and the synchronized blocks must agree with
+    pub trusted_checkpoint: Option<TrustedCheckpoint>,
 }
 
 /// Factory settings
@@ -106,17 +154,162 @@ pub struct FactorySettings {
     pub initial_delay: MassaTime,
     /// Staking wallet file
     pub staking_wallet_path: PathBuf,
+    /// Delay after a slot before checking that the local block made it to the blockclique
+    pub production_check_delay: MassaTime,
+    /// Production delay above which a miss is attributed to late production
+    pub late_production_threshold: MassaTime,
+    /// Number of produced blocks whose stage timings are kept
+    pub production_timings_history: usize,
+    /// Optional webhook receiving block production miss alerts
+    pub miss_alert_webhook: Option<String>,
+    /// Optional file listing the addresses whose operations are left out of the produced blocks
+    pub operation_denylist_path: Option<PathBuf>,
+    /// Optional file recording the productions of the node to refuse double productions
+    pub production_guard_path: Option<PathBuf>,
+    /// Whether the blocks are produced on the estimated network clock
+    pub compensate_clock_offset: bool,
+    /// Maximal shift of the production timing towards the network clock
+    pub max_clock_offset_compensation: MassaTime,
+    /// Lag in slots of the final slot behind the network above which no block is produced, 0 to disable
+    pub max_sync_lag: u64,
+}
+
+/// Storage settings
+#[derive(Debug, Deserialize, Clone)]
+pub struct StorageSettings {
+    /// Whether to attribute each storage reference to the module holding it
+    pub diagnostics: bool,
+    /// Interval of the reference leak checks, if diagnostics are enabled
+    pub leak_check_interval: MassaTime,
+    /// Number of consecutive growing checks after which a reference leak is reported
+    pub leak_check_window: usize,
+}
+
+/// Disk monitor settings
+#[derive(Debug, Deserialize, Clone)]
+pub struct DiskMonitorSettings {
+    /// Interval between two disk usage checks
+    pub check_interval: MassaTime,
+    /// Free space in bytes below which the node switches to read-only mode
+    pub read_only_threshold: u64,
+    /// Free space in bytes above which the node leaves read-only mode
+    pub resume_threshold: u64,
+    /// Directories monitored in addition to the ledger database
+    pub extra_paths: Vec<PathBuf>,
+}
+
+/// Memory monitor settings
+#[derive(Debug, Deserialize, Clone)]
+pub struct MemoryMonitorSettings {
+    /// Resident memory in bytes approached before the caches are shrunk, unmonitored if none
+    pub rss_cap: Option<u64>,
+    /// Interval between two resident memory checks
+    pub check_interval: MassaTime,
+    /// Percentage of the cap above which the capacities shrink
+    pub shrink_threshold: u64,
+    /// Percentage of the cap below which the capacities grow back
+    pub grow_threshold: u64,
+    /// Percentage of the configured capacities below which they are never shrunk
+    pub min_capacity: u64,
+}
+
+/// Background job scheduler settings
+#[derive(Debug, Deserialize, Clone)]
+pub struct SchedulerSettings {
+    /// Time kept free of heavy jobs before a production or endorsement slot of the node
+    pub margin_before: MassaTime,
+    /// Time kept free of heavy jobs after a production or endorsement slot of the node
+    pub margin_after: MassaTime,
+    /// Maximum time a job waits for a quiet window before running anyway
+    pub max_delay: MassaTime,
+    /// Interval between two compactions of the disk ledger, `None` to disable them
+    pub ledger_compaction_interval: Option<MassaTime>,
+}
+
+/// Supervisor settings
+#[derive(Debug, Deserialize, Clone)]
+pub struct SupervisorSettings {
+    /// Maximum number of restarts after a worker crash within `restart_window`
+    pub max_restarts: usize,
+    /// Window in which the restarts are counted
+    pub restart_window: MassaTime,
+    /// File in which the crash history is written when the restarts loop
+    pub diagnostics_path: PathBuf,
+    /// Directory of the final state backup re-attached after a restart
+    pub backup_path: PathBuf,
+}
+
+/// Automatic re-bootstrap settings
+#[derive(Debug, Deserialize, Clone)]
+pub struct ResyncSettings {
+    /// Lag in periods of the final slot behind the network above which the node bootstraps again, 0 to disable
+    pub max_lag_periods: u64,
+    /// Time the lag must stay above the threshold before bootstrapping again
+    pub grace_period: MassaTime,
+    /// Minimal time between two automatic re-bootstraps
+    pub min_interval: MassaTime,
+}
+
+/// Crash report settings
+#[derive(Debug, Deserialize, Clone)]
+pub struct CrashReportSettings {
+    /// Whether the panics are written as crash reports
+    pub enabled: bool,
+    /// Directory in which the reports are written
+    pub directory: PathBuf,
+    /// Number of recent log lines included in the reports
+    pub log_count: usize,
+    /// JSON-RPC endpoint the reports are submitted to, kept local if unset
+    pub submit_endpoint: Option<String>,
+    /// Timeout of a submission
+    pub submit_timeout: MassaTime,
+}
+
+/// Trusted sync settings
+#[derive(Debug, Deserialize, Clone)]
+pub struct TrustedSyncSettings {
+    /// Address on which the final state is served to the trusted nodes
+    pub bind: Option<SocketAddr>,
+    /// Secret shared by the trusted nodes
+    pub secret: String,
+    /// Directory in which the final state is written before being sent or after being received
+    pub work_dir: PathBuf,
+    /// Timeout of each network operation
+    pub timeout: MassaTime,
+}
+
+/// Configuration of the channels between the workers
+#[derive(Debug, Deserialize, Clone)]
+pub struct ChannelsSettings {
+    /// Maximum number of events kept on the event bus for its slowest subscriber
+    pub event_bus_capacity: usize,
+    /// Commands sent to the network worker
+    pub network_command: ChannelConfig,
+    /// Events sent by the network worker to the protocol worker
+    pub network_event: ChannelConfig,
+    /// Commands sent to the protocol worker
+    pub protocol_command: ChannelConfig,
+}
+
+/// Versioning settings
+#[derive(Debug, Deserialize, Clone)]
+pub struct VersioningSettings {
+    /// Path of the JSON list of the MIPs known by the node
+    pub mip_list_path: PathBuf,
+    /// Number of latest final blocks considered for the votes
+    pub block_count_considered: usize,
+    /// Percentage of the considered blocks that must announce the version of a MIP to lock it in
+    pub activation_threshold: u64,
 }
 
 /// Pool configuration, read from a file configuration
 #[derive(Debug, Deserialize, Clone)]
 pub struct PoolSettings {
-    pub max_pool_size_per_thread: usize,
     pub max_operation_future_validity_start_periods: u64,
     pub max_endorsement_count: u64,
     pub max_item_return_count: usize,
-    /// operations sender(channel) capacity
-    pub broadcast_operations_capacity: usize,
+    /// pre-admission analysis of the `ExecuteSC` bytecode
+    pub bytecode_analyzer: BytecodeAnalyzerConfig,
 }
 
 /// API and server configuration, read from a file configuration.
@@ -138,6 +331,12 @@ pub struct APISettings {
     pub ping_interval: MassaTime,
     pub enable_http: bool,
     pub enable_ws: bool,
+    pub cache_max_entries: usize,
+    pub max_query_results: usize,
+    pub private_relay_delay: MassaTime,
+    pub private_relay_expiry: PrivateRelayExpiry,
+    pub verified_contracts_path: PathBuf,
+    pub max_datastore_chunk_size: u64,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -153,28 +352,31 @@ pub struct Settings {
     pub ledger: LedgerSettings,
     pub selector: SelectionSettings,
     pub factory: FactorySettings,
+    pub storage: StorageSettings,
+    pub retention: RetentionRules,
+    pub disk_monitor: DiskMonitorSettings,
+    pub memory_monitor: MemoryMonitorSettings,
+    pub scheduler: SchedulerSettings,
+    pub supervisor: SupervisorSettings,
+    pub resync: ResyncSettings,
+    pub crash_report: CrashReportSettings,
+    pub trusted_sync: TrustedSyncSettings,
+    pub versioning: VersioningSettings,
+    pub channels: ChannelsSettings,
 }
 
 /// Consensus configuration
 /// Assumes `thread_count >= 1, t0_millis >= 1, t0_millis % thread_count == 0`
 #[derive(Debug, Deserialize, Clone)]
 pub struct ConsensusSettings {
-    /// Maximum number of blocks allowed in discarded blocks.
-    pub max_discarded_blocks: usize,
     /// If a block is `future_block_processing_max_periods` periods in the future, it is just discarded.
     pub future_block_processing_max_periods: u64,
-    /// Maximum number of blocks allowed in `FutureIncomingBlocks`.
-    pub max_future_processing_blocks: usize,
-    /// Maximum number of blocks allowed in `DependencyWaitingBlocks`.
-    pub max_dependency_blocks: usize,
+    /// max number of headers waiting for missing parents kept per source node
+    pub max_orphans_per_node: usize,
     /// stats time span
     pub stats_timespan: MassaTime,
     /// max event send wait
     pub max_send_wait: MassaTime,
-    /// force keep at least this number of final periods in RAM for each thread
-    pub force_keep_final_periods: u64,
-    /// old blocks are pruned every `block_db_prune_interval`
-    pub block_db_prune_interval: MassaTime,
     /// max number of items returned while querying
     pub max_item_return_count: usize,
     /// blocks headers sender(channel) capacity
@@ -183,6 +385,8 @@ pub struct ConsensusSettings {
     pub broadcast_blocks_capacity: usize,
     /// filled blocks sender(channel) capacity
     pub broadcast_filled_blocks_capacity: usize,
+    /// chain reorganizations sender(channel) capacity
+    pub broadcast_reorgs_capacity: usize,
 }
 
 /// Protocol Configuration, read from toml user configuration file
@@ -206,14 +410,20 @@ pub struct ProtocolSettings {
     pub max_node_known_endorsements_size: usize,
     /// we ask for the same block `max_simultaneous_ask_blocks_per_node` times at the same time
     pub max_simultaneous_ask_blocks_per_node: usize,
+    /// How the headers of new blocks are propagated
+    pub block_propagation_strategy: BlockPropagationStrategy,
+    /// minimum number of missing operations for the operations of a block to be asked as erasure-coded chunks
+    /// to several nodes at once, 0 to disable
+    pub erasure_coding_min_operations: usize,
     /// Max wait time for sending a Network or Node event.
     pub max_send_wait: MassaTime,
     /// Maximum number of batches in the memory buffer.
     /// Dismiss the new batches if overflow
     pub operation_batch_buffer_capacity: usize,
     /// Maximum number of operations in the announcement buffer.
-    /// Immediately announce if overflow.
     pub operation_announcement_buffer_capacity: usize,
+    /// What to do when the announcement buffer overflows
+    pub operation_announcement_overflow_policy: AnnouncementOverflowPolicy,
     /// Start processing batches in the buffer each `operation_batch_proc_period` in millisecond
     pub operation_batch_proc_period: MassaTime,
     /// All operations asked are prune each `operation_asked_pruning_period` millisecond
@@ -226,6 +436,14 @@ pub struct ProtocolSettings {
     pub max_operations_propagation_time: MassaTime,
     /// Time threshold after which operation are not propagated
     pub max_endorsements_propagation_time: MassaTime,
+    /// Maximum number of signatures verified in a single batch
+    pub max_signature_batch_size: usize,
+    /// Maximum number of gossiped batches of operations or endorsements waiting for signature verification
+    pub max_pending_signature_verifications: usize,
+    /// Number of signature verification workers, objects are dispatched to them by slot thread
+    pub signature_verification_shards: usize,
+    /// Maximum number of invalid messages of a type a peer can send per minute before being banned
+    pub max_invalid_messages_per_minute: u64,
 }
 
 #[cfg(test)]
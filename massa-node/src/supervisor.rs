@@ -0,0 +1,435 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Supervision of the node workers.
+//!
+//! The panic hook attributes each panic to the worker whose thread or task panicked, from the
+//! thread name or the crate of the panic location, and reports it to the supervisor instead of
+//! exiting. The workers share their channels and controllers, so a crash restarts all of them:
+//! the node stops and launches again. The final state is re-attached from a local backup unless
+//! the execution worker, which writes it, crashed: the node bootstraps again in that case.
+//!
+//! A restart does not reuse anything the crashed threads could have left half-written: all the
+//! workers, channels and in-memory states are dropped and built again. The states shared between
+//! the workers, like the final state, are behind `parking_lot` locks, released without poisoning
+//! when a panicking thread unwinds, so the stop of the other workers and the backup of the final
+//! state do not block on them.
+//!
+//! When more than `max_restarts` restarts happen within `restart_window`, the node shuts down and
+//! writes the crash history in `diagnostics_path`.
+//!
+//! The following panics still exit the process immediately instead of restarting the node:
+//! * panics of the main thread, which runs the supervision loop and stops the workers;
+//! * panics that cannot be attributed to a worker: threads not named by a worker whose panic
+//!   location is outside of the worker crates, for example in a shared crate called from a
+//!   tokio task;
+//! * panics reported after the supervisor is dropped, while the process exits.
+//!
+//! Panics while a thread is already unwinding, and builds with `panic = "abort"`, abort the
+//! process after the panic hook returns, whatever the decision.
+
+use massa_time::MassaTime;
+use serde::Serialize;
+use std::any::Any;
+use std::backtrace::Backtrace;
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::panic::{Location, PanicInfo};
+use std::path::PathBuf;
+use tracing::{error, info, warn};
+
+/// Supervisor configuration
+#[derive(Debug, Clone)]
+pub struct SupervisorConfig {
+    /// maximum number of restarts within `restart_window` before shutting down
+    pub max_restarts: usize,
+    /// window in which restarts are counted
+    pub restart_window: MassaTime,
+    /// file in which the crash history is written before shutting down
+    pub diagnostics_path: PathBuf,
+    /// directory of the final state backup re-attached after a restart
+    pub backup_path: PathBuf,
+}
+
+/// Supervised worker
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Worker {
+    Bootstrap,
+    Consensus,
+    Execution,
+    Pool,
+    Selector,
+    Factory,
+    Protocol,
+    Network,
+    Api,
+}
+
+impl Display for Worker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Worker::Bootstrap => "bootstrap server",
+            Worker::Consensus => "consensus",
+            Worker::Execution => "execution",
+            Worker::Pool => "pool",
+            Worker::Selector => "selector",
+            Worker::Factory => "factory",
+            Worker::Protocol => "protocol",
+            Worker::Network => "network",
+            Worker::Api => "API",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl Worker {
+    /// Worker owning a thread, from the thread names given by the workers
+    fn from_thread_name(name: &str) -> Option<Worker> {
+        const PREFIXES: &[(&str, Worker)] = &[
+            ("bootstrap", Worker::Bootstrap),
+            ("bs", Worker::Bootstrap),
+            ("wb_list_updater", Worker::Bootstrap),
+            ("consensus", Worker::Consensus),
+            ("execution", Worker::Execution),
+            ("operation-pool", Worker::Pool),
+            ("endorsement-pool", Worker::Pool),
+            ("selector", Worker::Selector),
+            ("protocol", Worker::Protocol),
+            ("block-factory", Worker::Factory),
+            ("endorsement-factory", Worker::Factory),
+            ("production-monitor", Worker::Factory),
+        ];
+        PREFIXES
+            .iter()
+            .find(|(prefix, _)| name.starts_with(prefix))
+            .map(|(_, worker)| *worker)
+    }
+
+    /// Worker implemented by the crate of a source file, for the tasks of the tokio runtime
+    fn from_source_file(file: &str) -> Option<Worker> {
+        const CRATES: &[(&str, Worker)] = &[
+            ("massa-bootstrap", Worker::Bootstrap),
+            ("massa-consensus-worker", Worker::Consensus),
+            ("massa-execution-worker", Worker::Execution),
+            ("massa-pool-worker", Worker::Pool),
+            ("massa-pos-worker", Worker::Selector),
+            ("massa-factory-worker", Worker::Factory),
+            ("massa-protocol-worker", Worker::Protocol),
+            ("massa-network-worker", Worker::Network),
+            ("massa-api", Worker::Api),
+        ];
+        CRATES
+            .iter()
+            .find(|(name, _)| file.split(['/', '\\']).any(|component| component == *name))
+            .map(|(_, worker)| *worker)
+    }
+}
+
+/// Panic of a worker
+#[derive(Debug, Clone, Serialize)]
+pub struct Crash {
+    /// crashed worker
+    pub worker: Worker,
+    /// name of the thread that panicked
+    pub thread: String,
+    /// source location of the panic
+    pub location: Option<String>,
+    /// panic message
+    pub message: String,
+    /// time of the panic
+    pub time: MassaTime,
+    /// backtrace of the panic
+    pub backtrace: String,
+}
+
+/// What to do after a crash
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    /// stop and launch the node again, re-attaching the final state if `reattach_state`
+    Restart { reattach_state: bool },
+    /// too many restarts, stop the node
+    Shutdown,
+}
+
+/// Crash history written before shutting down
+#[derive(Serialize)]
+struct Diagnostics<'a> {
+    max_restarts: usize,
+    restart_window: MassaTime,
+    restarts: &'a VecDeque<MassaTime>,
+    crashes: &'a [Crash],
+}
+
+/// Receives the crash reports of the panic hook and decides on the restarts
+pub struct Supervisor {
+    config: SupervisorConfig,
+    crash_rx: crossbeam_channel::Receiver<Crash>,
+    restarts: VecDeque<MassaTime>,
+    history: Vec<Crash>,
+}
+
+impl Supervisor {
+    /// Creates the supervisor and installs the panic hook reporting the crashes of the workers
+    pub fn install(config: SupervisorConfig) -> Self {
+        let (crash_tx, crash_rx) = crossbeam_channel::unbounded();
+        let supervisor = Supervisor::new(config, crash_rx);
+        let default_panic = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            default_panic(info);
+            match attribute(info) {
+                Some(crash) => {
+                    error!("the {} worker crashed: {}", crash.worker, crash.message);
+                    if crash_tx.send(crash).is_err() {
+                        std::process::exit(1);
+                    }
+                }
+                None => std::process::exit(1),
+            }
+        }));
+        supervisor
+    }
+
+    /// Creates the supervisor receiving the crash reports from `crash_rx`
+    fn new(config: SupervisorConfig, crash_rx: crossbeam_channel::Receiver<Crash>) -> Self {
+        Supervisor {
+            config,
+            crash_rx,
+            restarts: VecDeque::new(),
+            history: Vec::new(),
+        }
+    }
+
+    /// Next reported crash, if any
+    pub fn try_recv_crash(&mut self) -> Option<Crash> {
+        let crash = self.crash_rx.try_recv().ok()?;
+        self.history.push(crash.clone());
+        Some(crash)
+    }
+
+    /// Records the crashes reported while the node was stopping, usually caused by the first one
+    pub fn drain_crashes(&mut self) {
+        while let Some(crash) = self.try_recv_crash() {
+            warn!(
+                "the {} worker also crashed while stopping: {}",
+                crash.worker, crash.message
+            );
+        }
+    }
+
+    /// Decides between restarting the node and shutting it down after a crash
+    pub fn on_crash(&mut self, crash: &Crash) -> Decision {
+        let window_start = crash.time.saturating_sub(self.config.restart_window);
+        while matches!(self.restarts.front(), Some(time) if *time < window_start) {
+            self.restarts.pop_front();
+        }
+        if self.restarts.len() >= self.config.max_restarts {
+            error!(
+                "{} restarts within {}, shutting down",
+                self.restarts.len(),
+                self.config.restart_window
+            );
+            return Decision::Shutdown;
+        }
+        self.restarts.push_back(crash.time);
+        info!(
+            "restarting the node after the crash of the {} worker ({} of {} allowed restarts)",
+            crash.worker,
+            self.restarts.len(),
+            self.config.max_restarts
+        );
+        Decision::Restart {
+            reattach_state: crash.worker != Worker::Execution,
+        }
+    }
+
+    /// Directory of the final state backup re-attached after a restart
+    pub fn backup_path(&self) -> &PathBuf {
+        &self.config.backup_path
+    }
+
+    /// Writes the crash history in the diagnostics file
+    pub fn write_diagnostics(&self) -> anyhow::Result<()> {
+        let diagnostics = Diagnostics {
+            max_restarts: self.config.max_restarts,
+            restart_window: self.config.restart_window,
+            restarts: &self.restarts,
+            crashes: &self.history,
+        };
+        std::fs::write(
+            &self.config.diagnostics_path,
+            serde_json::to_string_pretty(&diagnostics)?,
+        )?;
+        error!(
+            "crash diagnostics written in {}",
+            self.config.diagnostics_path.display()
+        );
+        Ok(())
+    }
+}
+
+/// Crash of the worker that panicked, `None` if the panic cannot be attributed to a worker
+fn attribute(info: &PanicInfo) -> Option<Crash> {
+    let thread = std::thread::current();
+    attribute_panic(
+        thread.name().unwrap_or_default(),
+        info.location(),
+        info.payload(),
+    )
+}
+
+/// Crash of the worker owning the thread `thread_name`, or else implemented by the crate of the panic `location`
+fn attribute_panic(
+    thread_name: &str,
+    location: Option<&Location>,
+    payload: &(dyn Any + Send),
+) -> Option<Crash> {
+    if thread_name == "main" {
+        return None;
+    }
+    let worker = Worker::from_thread_name(thread_name)
+        .or_else(|| location.and_then(|l| Worker::from_source_file(l.file())))?;
+    let message = if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic payload".to_string()
+    };
+    Some(Crash {
+        worker,
+        thread: thread_name.to_string(),
+        location: location.map(|location| location.to_string()),
+        message,
+        time: MassaTime::now().unwrap_or_else(|_| MassaTime::from_millis(0)),
+        backtrace: Backtrace::force_capture().to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_supervisor(max_restarts: usize) -> Supervisor {
+        let (_, crash_rx) = crossbeam_channel::unbounded();
+        Supervisor::new(
+            SupervisorConfig {
+                max_restarts,
+                restart_window: MassaTime::from_millis(60_000),
+                diagnostics_path: PathBuf::from("crash_diagnostics.json"),
+                backup_path: PathBuf::from("supervisor_backup"),
+            },
+            crash_rx,
+        )
+    }
+
+    fn crash(worker: Worker, time: u64) -> Crash {
+        Crash {
+            worker,
+            thread: "test".to_string(),
+            location: None,
+            message: "test panic".to_string(),
+            time: MassaTime::from_millis(time),
+            backtrace: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_restart_window() {
+        let mut supervisor = new_supervisor(2);
+        let restart = Decision::Restart {
+            reattach_state: true,
+        };
+        assert_eq!(supervisor.on_crash(&crash(Worker::Pool, 0)), restart);
+        assert_eq!(supervisor.on_crash(&crash(Worker::Pool, 30_000)), restart);
+        // a third crash within the window shuts the node down
+        assert_eq!(
+            supervisor.on_crash(&crash(Worker::Pool, 59_000)),
+            Decision::Shutdown
+        );
+        // the restarts that left the window are not counted anymore
+        assert_eq!(supervisor.on_crash(&crash(Worker::Pool, 61_000)), restart);
+        assert_eq!(
+            supervisor.on_crash(&crash(Worker::Pool, 62_000)),
+            Decision::Shutdown
+        );
+        assert_eq!(supervisor.on_crash(&crash(Worker::Pool, 91_000)), restart);
+    }
+
+    #[test]
+    fn test_restart_reattach_state() {
+        let mut supervisor = new_supervisor(10);
+        assert_eq!(
+            supervisor.on_crash(&crash(Worker::Consensus, 0)),
+            Decision::Restart {
+                reattach_state: true
+            }
+        );
+        // the final state written by the crashed execution worker is bootstrapped again
+        assert_eq!(
+            supervisor.on_crash(&crash(Worker::Execution, 0)),
+            Decision::Restart {
+                reattach_state: false
+            }
+        );
+    }
+
+    #[test]
+    fn test_no_restart() {
+        let mut supervisor = new_supervisor(0);
+        assert_eq!(
+            supervisor.on_crash(&crash(Worker::Api, 0)),
+            Decision::Shutdown
+        );
+    }
+
+    #[test]
+    fn test_attribute_thread() {
+        let payload: &(dyn Any + Send) = &"thread panic";
+        let crash = attribute_panic("execution", Some(Location::caller()), payload).unwrap();
+        assert_eq!(crash.worker, Worker::Execution);
+        assert_eq!(crash.thread, "execution");
+        assert_eq!(crash.message, "thread panic");
+        assert!(crash.location.unwrap().contains("supervisor.rs"));
+        let payload: &(dyn Any + Send) = &"thread panic".to_string();
+        let crash = attribute_panic("bs-server-session", None, payload).unwrap();
+        assert_eq!(crash.worker, Worker::Bootstrap);
+        assert_eq!(crash.message, "thread panic");
+        let payload: &(dyn Any + Send) = &42;
+        let crash = attribute_panic("endorsement-factory", None, payload).unwrap();
+        assert_eq!(crash.worker, Worker::Factory);
+        assert_eq!(crash.message, "unknown panic payload");
+    }
+
+    #[test]
+    fn test_attribute_unknown_thread() {
+        let payload: &(dyn Any + Send) = &"panic";
+        // the main thread and the panics outside of the worker crates exit the process
+        assert!(attribute_panic("main", None, payload).is_none());
+        assert!(
+            attribute_panic("tokio-runtime-worker", Some(Location::caller()), payload).is_none()
+        );
+        assert!(attribute_panic("", None, payload).is_none());
+    }
+
+    #[test]
+    fn test_attribute_source_file() {
+        assert_eq!(
+            Worker::from_source_file("massa-pool-worker/src/operation_pool.rs"),
+            Some(Worker::Pool)
+        );
+        assert_eq!(
+            Worker::from_source_file("/home/massa/massa-api/src/public.rs"),
+            Some(Worker::Api)
+        );
+        assert_eq!(
+            Worker::from_source_file(r"C:\massa\massa-network-worker\src\lib.rs"),
+            Some(Worker::Network)
+        );
+        // the crate names are matched exactly, not as prefixes
+        assert_eq!(
+            Worker::from_source_file("massa-api-exports/src/node.rs"),
+            None
+        );
+        assert_eq!(Worker::from_source_file("massa-node/src/main.rs"), None);
+    }
+}
@@ -0,0 +1,200 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Analysis of the bytecode of `ExecuteSC` operations before they are admitted into the pool.
+//!
+//! An `OperationAnalyzer` given to the pool refuses the operations deploying junk bytecode,
+//! which are then neither kept in the pool nor included in the blocks produced by the node.
+//! The analysis only filters what the node relays and produces: the blocks of other nodes are not affected.
+//!
+//! `BytecodeAnalyzer` is the default analyzer. It is conservative and only refuses the bytecode that is too large,
+//! that is not a WebAssembly module, that imports functions out of the allowed modules or banned by the operator,
+//! and the operations whose gas cannot even pay for the processing of their bytecode.
+
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+/// Reason for refusing an `ExecuteSC` operation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AnalysisRejection {
+    /// the bytecode is larger than the analyzer limit
+    TooLarge(usize),
+    /// the bytecode is not a valid WebAssembly module
+    InvalidModule(String),
+    /// the bytecode imports a function that is not allowed, as `module.name`
+    BannedImport(String),
+    /// the maximal gas of the operation is too low for the size of its bytecode
+    InsufficientGas(u64),
+}
+
+impl Display for AnalysisRejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AnalysisRejection::TooLarge(size) => {
+                write!(f, "bytecode of {} bytes is too large", size)
+            }
+            AnalysisRejection::InvalidModule(reason) => {
+                write!(f, "invalid WebAssembly module: {}", reason)
+            }
+            AnalysisRejection::BannedImport(import) => write!(f, "banned import {}", import),
+            AnalysisRejection::InsufficientGas(max_gas) => {
+                write!(f, "maximal gas {} is too low for the bytecode", max_gas)
+            }
+        }
+    }
+}
+
+/// Pre-admission check of the `ExecuteSC` operations
+pub trait OperationAnalyzer: Send + Sync {
+    /// Checks the `bytecode` executed by an operation with `max_gas`
+    fn analyze(&self, bytecode: &[u8], max_gas: u64) -> Result<(), AnalysisRejection>;
+}
+
+/// Configuration of the default `BytecodeAnalyzer`
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BytecodeAnalyzerConfig {
+    /// whether the bytecode of the `ExecuteSC` operations is analyzed before pool admission
+    pub enabled: bool,
+    /// maximal size of the bytecode in bytes
+    pub max_bytecode_size: u64,
+    /// modules from which the bytecode can import functions
+    pub allowed_import_modules: Vec<String>,
+    /// imports refused even from an allowed module, as `module.name`
+    pub banned_imports: Vec<String>,
+    /// minimal gas an operation must provide per byte of its bytecode
+    pub min_gas_per_byte: u64,
+}
+
+/// Default conservative analyzer
+pub struct BytecodeAnalyzer {
+    config: BytecodeAnalyzerConfig,
+}
+
+impl BytecodeAnalyzer {
+    /// Creates an analyzer applying `config`
+    pub fn new(config: BytecodeAnalyzerConfig) -> Self {
+        BytecodeAnalyzer { config }
+    }
+}
+
+impl OperationAnalyzer for BytecodeAnalyzer {
+    fn analyze(&self, bytecode: &[u8], max_gas: u64) -> Result<(), AnalysisRejection> {
+        if bytecode.len() as u64 > self.config.max_bytecode_size {
+            return Err(AnalysisRejection::TooLarge(bytecode.len()));
+        }
+        if max_gas < (bytecode.len() as u64).saturating_mul(self.config.min_gas_per_byte) {
+            return Err(AnalysisRejection::InsufficientGas(max_gas));
+        }
+        for (module, name) in read_imports(bytecode).map_err(AnalysisRejection::InvalidModule)? {
+            let import = format!("{}.{}", module, name);
+            if !self.config.allowed_import_modules.contains(&module)
+                || self.config.banned_imports.contains(&import)
+            {
+                return Err(AnalysisRejection::BannedImport(import));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Prefix of a WebAssembly module: magic number then version 1
+const WASM_PREAMBLE: [u8; 8] = [0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00];
+
+/// Identifier of the import section of a WebAssembly module
+const IMPORT_SECTION_ID: u8 = 2;
+
+/// Reader of the WebAssembly binary format
+struct WasmReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> WasmReader<'a> {
+    fn byte(&mut self) -> Result<u8, String> {
+        let (first, rest) = self.bytes.split_first().ok_or("unexpected end")?;
+        self.bytes = rest;
+        Ok(*first)
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if len > self.bytes.len() {
+            return Err("unexpected end".to_string());
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    /// Unsigned LEB128 integer of at most 32 bits
+    fn u32(&mut self) -> Result<u32, String> {
+        let mut value: u32 = 0;
+        for shift in (0..35).step_by(7) {
+            let byte = self.byte()?;
+            value |= ((byte & 0x7f) as u32)
+                .checked_shl(shift)
+                .ok_or("integer overflow")?;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err("integer overflow".to_string())
+    }
+
+    fn name(&mut self) -> Result<String, String> {
+        let len = self.u32()? as usize;
+        String::from_utf8(self.take(len)?.to_vec()).map_err(|_| "invalid name".to_string())
+    }
+
+    fn limits(&mut self) -> Result<(), String> {
+        let flags = self.byte()?;
+        self.u32()?;
+        if flags & 0x01 != 0 {
+            self.u32()?;
+        }
+        Ok(())
+    }
+}
+
+/// Imports of a WebAssembly module, as `(module, name)`
+fn read_imports(bytecode: &[u8]) -> Result<Vec<(String, String)>, String> {
+    if !bytecode.starts_with(&WASM_PREAMBLE) {
+        return Err("missing WebAssembly preamble".to_string());
+    }
+    let mut reader = WasmReader {
+        bytes: &bytecode[WASM_PREAMBLE.len()..],
+    };
+    while !reader.bytes.is_empty() {
+        let id = reader.byte()?;
+        let size = reader.u32()? as usize;
+        let content = reader.take(size)?;
+        if id != IMPORT_SECTION_ID {
+            continue;
+        }
+        let mut section = WasmReader { bytes: content };
+        let count = section.u32()?;
+        let mut imports = Vec::new();
+        for _ in 0..count {
+            let module = section.name()?;
+            let name = section.name()?;
+            match section.byte()? {
+                // function: type index
+                0x00 => {
+                    section.u32()?;
+                }
+                // table: reference type and limits
+                0x01 => {
+                    section.byte()?;
+                    section.limits()?;
+                }
+                // memory: limits
+                0x02 => section.limits()?,
+                // global: value type and mutability
+                0x03 => {
+                    section.take(2)?;
+                }
+                kind => return Err(format!("unknown import kind {}", kind)),
+            }
+            imports.push((module, name));
+        }
+        return Ok(imports);
+    }
+    Ok(Vec::new())
+}
@@ -1,8 +1,14 @@
-use massa_models::operation::Operation;
+use massa_channel::EventBus;
+use massa_models::config::CapacityScale;
+use massa_pos_exports::SelectorController;
 
 /// channels used by the pool worker
 #[derive(Clone)]
 pub struct PoolChannels {
-    /// Broadcast sender(channel) for new operations
-    pub operation_sender: tokio::sync::broadcast::Sender<Operation>,
+    /// Event bus of the node, on which the operations added to the pool are published
+    pub event_bus: EventBus,
+    /// Scale of the pool capacities, lowered by the memory monitor under memory pressure
+    pub capacity_scale: CapacityScale,
+    /// Selector, to check the BLS signatures of endorsers against the endorsers drawn at their index
+    pub selector: Box<dyn SelectorController>,
 }
@@ -24,10 +24,10 @@ pub struct PoolConfig {
     pub max_endorsements_pool_size_per_thread: usize,
     /// max number of endorsements per block
     pub max_block_endorsement_count: u32,
+    /// periods per cycle, from which the registered BLS keys can sign
+    pub periods_per_cycle: u64,
     /// operations and endorsements communication channels size
     pub channels_size: usize,
     /// Whether WebSockets are enabled
     pub broadcast_enabled: bool,
-    /// operations sender(channel) capacity
-    pub broadcast_operations_capacity: usize,
 }
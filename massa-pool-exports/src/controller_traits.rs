@@ -1,7 +1,8 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 use massa_models::{
-    block_id::BlockId, endorsement::EndorsementId, operation::OperationId, slot::Slot,
+    block_id::BlockId, endorsement::EndorsementId, endorsement_signature::EndorsementSignature,
+    operation::OperationId, prehash::PreHashSet, slot::Slot, stats::BytecodeRejectStats,
 };
 use massa_storage::Storage;
 
@@ -10,9 +11,23 @@ pub trait PoolController: Send + Sync {
     /// Asynchronously add operations to pool. Simply print a warning on failure.
     fn add_operations(&mut self, ops: Storage);
 
+    /// Asynchronously add operations to pool without broadcasting them to the subscribers of new operations.
+    /// Simply print a warning on failure.
+    fn add_private_operations(&mut self, ops: Storage);
+
+    /// Asynchronously remove operations from pool. Simply print a warning on failure.
+    fn remove_operations(&mut self, ops: PreHashSet<OperationId>);
+
     /// Asynchronously add endorsements to pool. Simply print a warning on failure.
     fn add_endorsements(&mut self, endorsements: Storage);
 
+    /// Add BLS signatures of endorsers to the pool, after checking them against the final keys registered by
+    /// the endorsers drawn at their index. Returns the signatures that were not in the pool yet, to propagate.
+    fn add_endorsement_signatures(
+        &mut self,
+        signatures: Vec<EndorsementSignature>,
+    ) -> Vec<EndorsementSignature>;
+
     /// Asynchronously notify of new consensus final periods. Simply print a warning on failure.
     fn notify_final_cs_periods(&mut self, final_cs_periods: &[u64]);
 
@@ -26,12 +41,22 @@ pub trait PoolController: Send + Sync {
         slot: &Slot,
     ) -> (Vec<Option<EndorsementId>>, Storage);
 
+    /// Get the BLS signatures of endorsers to aggregate in a block header
+    fn get_block_endorsement_signatures(
+        &self,
+        target_block: &BlockId,
+        slot: &Slot,
+    ) -> Vec<EndorsementSignature>;
+
     /// Get the number of endorsements in the pool
     fn get_endorsement_count(&self) -> usize;
 
     /// Get the number of operations in the pool
     fn get_operation_count(&self) -> usize;
 
+    /// Get the number of operations refused by the bytecode analyzer since the start of the node
+    fn get_bytecode_reject_stats(&self) -> BytecodeRejectStats;
+
     /// Check if the pool contains a list of endorsements. Returns one boolean per item.
     fn contains_endorsements(&self, endorsements: &[EndorsementId]) -> Vec<bool>;
 
@@ -5,10 +5,14 @@
 #![warn(missing_docs)]
 #![warn(unused_crate_dependencies)]
 
+mod analyzer;
 mod channels;
 mod config;
 mod controller_traits;
 
+pub use analyzer::{
+    AnalysisRejection, BytecodeAnalyzer, BytecodeAnalyzerConfig, OperationAnalyzer,
+};
 pub use channels::PoolChannels;
 pub use config::PoolConfig;
 pub use controller_traits::{PoolController, PoolManager};
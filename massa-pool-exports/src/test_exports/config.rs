@@ -2,7 +2,7 @@
 
 use massa_models::config::{
     ENDORSEMENT_COUNT, MAX_BLOCK_SIZE, MAX_GAS_PER_BLOCK, MAX_OPERATIONS_PER_BLOCK,
-    OPERATION_VALIDITY_PERIODS, ROLL_PRICE, THREAD_COUNT,
+    OPERATION_VALIDITY_PERIODS, PERIODS_PER_CYCLE, ROLL_PRICE, THREAD_COUNT,
 };
 
 use crate::PoolConfig;
@@ -19,9 +19,9 @@ impl Default for PoolConfig {
             max_endorsements_pool_size_per_thread: 1000,
             max_operations_per_block: MAX_OPERATIONS_PER_BLOCK,
             max_block_endorsement_count: ENDORSEMENT_COUNT,
+            periods_per_cycle: PERIODS_PER_CYCLE,
             channels_size: 1024,
             broadcast_enabled: false,
-            broadcast_operations_capacity: 5000,
         }
     }
 }
@@ -6,7 +6,8 @@ use std::sync::{
 };
 
 use massa_models::{
-    block_id::BlockId, endorsement::EndorsementId, operation::OperationId, slot::Slot,
+    block_id::BlockId, endorsement::EndorsementId, endorsement_signature::EndorsementSignature,
+    operation::OperationId, prehash::PreHashSet, slot::Slot, stats::BytecodeRejectStats,
 };
 use massa_storage::Storage;
 use massa_time::MassaTime;
@@ -26,11 +27,28 @@ pub enum MockPoolControllerMessage {
         /// Storage that contains all endorsements
         endorsements: Storage,
     },
+    /// Add BLS signatures of endorsers to the pool
+    AddEndorsementSignatures {
+        /// signatures to add
+        signatures: Vec<EndorsementSignature>,
+        /// Response channel
+        response_tx: mpsc::Sender<Vec<EndorsementSignature>>,
+    },
     /// Add operations to the pool
     AddOperations {
         /// Storage that contains all operations
         operations: Storage,
     },
+    /// Add operations to the pool without broadcasting them
+    AddPrivateOperations {
+        /// Storage that contains all operations
+        operations: Storage,
+    },
+    /// Remove operations from the pool
+    RemoveOperations {
+        /// ids of the removed operations
+        ids: PreHashSet<OperationId>,
+    },
     /// Get block endorsements
     GetBlockEndorsements {
         /// Block id of the block endorsed
@@ -40,6 +58,15 @@ pub enum MockPoolControllerMessage {
         /// Response channel
         response_tx: mpsc::Sender<(Vec<Option<EndorsementId>>, Storage)>,
     },
+    /// Get the BLS signatures of endorsers to aggregate in a block
+    GetBlockEndorsementSignatures {
+        /// Block id of the block endorsed
+        block_id: BlockId,
+        /// Slot of the block
+        slot: Slot,
+        /// Response channel
+        response_tx: mpsc::Sender<Vec<EndorsementSignature>>,
+    },
     /// Get operations of a block
     GetBlockOperations {
         /// Slot of the block to search operations in
@@ -131,6 +158,22 @@ impl PoolController for MockPoolController {
             .unwrap();
     }
 
+    fn add_endorsement_signatures(
+        &mut self,
+        signatures: Vec<EndorsementSignature>,
+    ) -> Vec<EndorsementSignature> {
+        let (response_tx, response_rx) = mpsc::channel();
+        self.0
+            .lock()
+            .unwrap()
+            .send(MockPoolControllerMessage::AddEndorsementSignatures {
+                signatures,
+                response_tx,
+            })
+            .unwrap();
+        response_rx.recv().unwrap()
+    }
+
     fn add_operations(&mut self, operations: Storage) {
         self.0
             .lock()
@@ -139,6 +182,22 @@ impl PoolController for MockPoolController {
             .unwrap();
     }
 
+    fn add_private_operations(&mut self, operations: Storage) {
+        self.0
+            .lock()
+            .unwrap()
+            .send(MockPoolControllerMessage::AddPrivateOperations { operations })
+            .unwrap();
+    }
+
+    fn remove_operations(&mut self, ids: PreHashSet<OperationId>) {
+        self.0
+            .lock()
+            .unwrap()
+            .send(MockPoolControllerMessage::RemoveOperations { ids })
+            .unwrap();
+    }
+
     fn get_block_endorsements(
         &self,
         target_block: &BlockId,
@@ -157,6 +216,24 @@ impl PoolController for MockPoolController {
         response_rx.recv().unwrap()
     }
 
+    fn get_block_endorsement_signatures(
+        &self,
+        target_block: &BlockId,
+        target_slot: &Slot,
+    ) -> Vec<EndorsementSignature> {
+        let (response_tx, response_rx) = mpsc::channel();
+        self.0
+            .lock()
+            .unwrap()
+            .send(MockPoolControllerMessage::GetBlockEndorsementSignatures {
+                block_id: *target_block,
+                slot: *target_slot,
+                response_tx,
+            })
+            .unwrap();
+        response_rx.recv().unwrap()
+    }
+
     fn get_block_operations(&self, slot: &Slot) -> (Vec<OperationId>, Storage) {
         let (response_tx, response_rx) = mpsc::channel();
         self.0
@@ -190,6 +267,10 @@ impl PoolController for MockPoolController {
         response_rx.recv().unwrap()
     }
 
+    fn get_bytecode_reject_stats(&self) -> BytecodeRejectStats {
+        BytecodeRejectStats::default()
+    }
+
     fn contains_endorsements(&self, endorsements: &[EndorsementId]) -> Vec<bool> {
         let (response_tx, response_rx) = mpsc::channel();
         self.0
@@ -2,15 +2,18 @@
 
 //! Pool controller implementation
 
+use massa_execution_exports::ExecutionController;
 use massa_models::{
-    block_id::BlockId, endorsement::EndorsementId, operation::OperationId, slot::Slot,
+    block_id::BlockId, endorsement::EndorsementId, endorsement_signature::EndorsementSignature,
+    operation::OperationId, prehash::PreHashSet, slot::Slot, stats::BytecodeRejectStats,
 };
 use massa_pool_exports::{PoolConfig, PoolController, PoolManager};
+use massa_pos_exports::SelectorController;
 use massa_storage::Storage;
 use parking_lot::RwLock;
 use std::sync::mpsc::TrySendError;
 use std::sync::{mpsc::SyncSender, Arc};
-use tracing::{info, warn};
+use tracing::{debug, info, warn};
 
 use crate::{endorsement_pool::EndorsementPool, operation_pool::OperationPool};
 
@@ -18,6 +21,10 @@ use crate::{endorsement_pool::EndorsementPool, operation_pool::OperationPool};
 pub enum Command {
     /// Add items to the pool
     AddItems(Storage),
+    /// Add operations to the pool without broadcasting them
+    AddPrivateOperations(Storage),
+    /// Remove operations from the pool
+    RemoveOperations(PreHashSet<OperationId>),
     /// Notify of new final consensus periods
     NotifyFinalCsPeriods(Vec<u64>),
     /// Stop the worker
@@ -28,7 +35,7 @@ pub enum Command {
 #[derive(Clone)]
 pub struct PoolControllerImpl {
     /// Config
-    pub(crate) _config: PoolConfig,
+    pub(crate) config: PoolConfig,
     /// Shared reference to the operation pool
     pub(crate) operation_pool: Arc<RwLock<OperationPool>>,
     /// Shared reference to the endorsement pool
@@ -37,6 +44,39 @@ pub struct PoolControllerImpl {
     pub(crate) operations_input_sender: SyncSender<Command>,
     /// Endorsement write worker command sender
     pub(crate) endorsements_input_sender: SyncSender<Command>,
+    /// Selector, giving the endorsers drawn for the signed slots
+    pub(crate) selector: Box<dyn SelectorController>,
+    /// Execution controller, giving the BLS keys registered by the endorsers
+    pub(crate) execution_controller: Box<dyn ExecutionController>,
+}
+
+impl PoolControllerImpl {
+    /// Whether a BLS signature was made by the endorser drawn at its index,
+    /// with a key it registered early enough to sign at its slot
+    fn check_endorsement_signature(&self, signature: &EndorsementSignature) -> bool {
+        let endorser = match self.selector.get_selection(signature.slot) {
+            Ok(selection) => match selection.endorsements.get(signature.index as usize) {
+                Some(endorser) => *endorser,
+                None => return false,
+            },
+            Err(err) => {
+                debug!(
+                    "could not get the endorsers of slot {}: {}",
+                    signature.slot, err
+                );
+                return false;
+            }
+        };
+        match self
+            .execution_controller
+            .get_registered_bls_keys(&[endorser])[0]
+        {
+            Some(key) if key.is_usable_at(&signature.slot, self.config.periods_per_cycle) => {
+                signature.verify(&key.public_key).is_ok()
+            }
+            _ => false,
+        }
+    }
 }
 
 impl PoolController for PoolControllerImpl {
@@ -56,6 +96,38 @@ impl PoolController for PoolControllerImpl {
         }
     }
 
+    /// Asynchronously add operations to pool without broadcasting them. Simply print a warning on failure.
+    fn add_private_operations(&mut self, ops: Storage) {
+        match self
+            .operations_input_sender
+            .try_send(Command::AddPrivateOperations(ops))
+        {
+            Err(TrySendError::Disconnected(_)) => {
+                warn!("Could not add private operations to pool: worker is unreachable.");
+            }
+            Err(TrySendError::Full(_)) => {
+                warn!("Could not add private operations to pool: worker channel is full.");
+            }
+            Ok(_) => {}
+        }
+    }
+
+    /// Asynchronously remove operations from pool. Simply print a warning on failure.
+    fn remove_operations(&mut self, ops: PreHashSet<OperationId>) {
+        match self
+            .operations_input_sender
+            .try_send(Command::RemoveOperations(ops))
+        {
+            Err(TrySendError::Disconnected(_)) => {
+                warn!("Could not remove operations from pool: worker is unreachable.");
+            }
+            Err(TrySendError::Full(_)) => {
+                warn!("Could not remove operations from pool: worker channel is full.");
+            }
+            Ok(_) => {}
+        }
+    }
+
     /// Asynchronously add endorsements to pool. Simply print a warning on failure.
     fn add_endorsements(&mut self, endorsements: Storage) {
         match self
@@ -72,6 +144,31 @@ impl PoolController for PoolControllerImpl {
         }
     }
 
+    /// Add BLS signatures of endorsers to the pool, once checked. Returns the added ones.
+    /// The signatures are checked in the calling thread, outside of the pool lock.
+    fn add_endorsement_signatures(
+        &mut self,
+        signatures: Vec<EndorsementSignature>,
+    ) -> Vec<EndorsementSignature> {
+        let new_signatures: Vec<EndorsementSignature> = {
+            let lck = self.endorsement_pool.read();
+            signatures
+                .into_iter()
+                .filter(|signature| !lck.contains_signature(signature))
+                .collect()
+        };
+        let checked_signatures: Vec<EndorsementSignature> = new_signatures
+            .into_iter()
+            .filter(|signature| self.check_endorsement_signature(signature))
+            .collect();
+        if checked_signatures.is_empty() {
+            return checked_signatures;
+        }
+        self.endorsement_pool
+            .write()
+            .add_endorsement_signatures(checked_signatures)
+    }
+
     /// Asynchronously notify of new final consensus periods. Simply print a warning on failure.
     fn notify_final_cs_periods(&mut self, final_cs_periods: &[u64]) {
         match self
@@ -123,6 +220,17 @@ impl PoolController for PoolControllerImpl {
             .get_block_endorsements(target_slot, target_block)
     }
 
+    /// get BLS signatures of endorsers to aggregate in a block header
+    fn get_block_endorsement_signatures(
+        &self,
+        target_block: &BlockId,
+        target_slot: &Slot,
+    ) -> Vec<EndorsementSignature> {
+        self.endorsement_pool
+            .read()
+            .get_block_endorsement_signatures(target_slot, target_block)
+    }
+
     /// Returns a boxed clone of self.
     /// Allows cloning `Box<dyn PoolController>`,
     fn clone_box(&self) -> Box<dyn PoolController> {
@@ -139,6 +247,11 @@ impl PoolController for PoolControllerImpl {
         self.operation_pool.read().len()
     }
 
+    /// Get the number of operations refused by the bytecode analyzer
+    fn get_bytecode_reject_stats(&self) -> BytecodeRejectStats {
+        self.operation_pool.read().bytecode_reject_stats
+    }
+
     /// Check if the pool contains a list of endorsements. Returns one boolean per item.
     fn contains_endorsements(&self, endorsements: &[EndorsementId]) -> Vec<bool> {
         let lck = self.endorsement_pool.read();
@@ -173,14 +286,14 @@ impl PoolManager for PoolManagerImpl {
         let _ = self.operations_input_sender.send(Command::Stop);
         let _ = self.endorsements_input_sender.send(Command::Stop);
         if let Some(join_handle) = self.operations_thread_handle.take() {
-            join_handle
-                .join()
-                .expect("operations pool thread panicked on try to join");
+            if let Err(err) = join_handle.join() {
+                warn!("operations pool thread panicked: {:?}", err);
+            }
         }
         if let Some(join_handle) = self.endorsements_thread_handle.take() {
-            join_handle
-                .join()
-                .expect("endorsements pool thread panicked on try to join");
+            if let Err(err) = join_handle.join() {
+                warn!("endorsements pool thread panicked: {:?}", err);
+            }
         }
         info!("pool workers stopped");
     }
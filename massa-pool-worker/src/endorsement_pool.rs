@@ -1,8 +1,10 @@
 //! Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use massa_models::config::CapacityScale;
 use massa_models::{
     block_id::BlockId,
     endorsement::EndorsementId,
+    endorsement_signature::EndorsementSignature,
     prehash::{CapacityAllocator, PreHashSet},
     slot::Slot,
 };
@@ -21,21 +23,30 @@ pub struct EndorsementPool {
     /// indexed by thread, then `BTreeMap<(inclusion_slot, index, target_block), endorsement_id>`
     endorsements_sorted: Vec<BTreeMap<(Slot, u32, BlockId), EndorsementId>>,
 
+    /// checked BLS signatures of endorsers sorted by increasing inclusion slot for pruning
+    /// indexed by thread, then `BTreeMap<(inclusion_slot, index, target_block), signature>`
+    signatures_sorted: Vec<BTreeMap<(Slot, u32, BlockId), EndorsementSignature>>,
+
     /// storage
     storage: Storage,
 
     /// last consensus final periods, per thread
     last_cs_final_periods: Vec<u64>,
+
+    /// scale of the pool capacity, lowered under memory pressure
+    capacity_scale: CapacityScale,
 }
 
 impl EndorsementPool {
-    pub fn init(config: PoolConfig, storage: &Storage) -> Self {
+    pub fn init(config: PoolConfig, storage: &Storage, capacity_scale: CapacityScale) -> Self {
         EndorsementPool {
             last_cs_final_periods: vec![0u64; config.thread_count as usize],
             endorsements_indexed: Default::default(),
             endorsements_sorted: vec![Default::default(); config.thread_count as usize],
+            signatures_sorted: vec![Default::default(); config.thread_count as usize],
             config,
             storage: storage.clone_without_refs(),
+            capacity_scale,
         }
     }
 
@@ -49,6 +60,15 @@ impl EndorsementPool {
         self.storage.get_endorsement_refs().contains(id)
     }
 
+    /// Checks whether a signature of the same endorser for the same block is stored in the pool.
+    pub fn contains_signature(&self, signature: &EndorsementSignature) -> bool {
+        self.signatures_sorted[signature.slot.thread as usize].contains_key(&(
+            signature.slot,
+            signature.index,
+            signature.endorsed_block,
+        ))
+    }
+
     /// notify of new final CS periods
     pub(crate) fn notify_final_cs_periods(&mut self, final_cs_periods: &[u64]) {
         // update internal final CS period counter
@@ -72,6 +92,47 @@ impl EndorsementPool {
             }
         }
         self.storage.drop_endorsement_refs(&removed);
+
+        // remove all signatures whose periods <= last_cs_final_periods[signature.thread]
+        for (thread, signatures) in self.signatures_sorted.iter_mut().enumerate() {
+            let final_period = self.last_cs_final_periods[thread];
+            signatures.retain(|(inclusion_slot, _, _), _| inclusion_slot.period > final_period);
+        }
+    }
+
+    /// Add a list of checked BLS signatures of endorsers to the pool.
+    /// Returns the ones that were added, excluding the expired ones and the ones already stored.
+    pub(crate) fn add_endorsement_signatures(
+        &mut self,
+        signatures: Vec<EndorsementSignature>,
+    ) -> Vec<EndorsementSignature> {
+        let mut added = Vec::with_capacity(signatures.len());
+        for signature in signatures {
+            let thread = signature.slot.thread as usize;
+            if signature.slot.period < self.last_cs_final_periods[thread] {
+                // signature expired: ignore
+                continue;
+            }
+            let key = (signature.slot, signature.index, signature.endorsed_block);
+            if self.signatures_sorted[thread]
+                .try_insert(key, signature)
+                .is_ok()
+            {
+                added.push(signature);
+            }
+        }
+
+        // prune excess signatures
+        let max_signatures_per_thread = self
+            .capacity_scale
+            .apply(self.config.max_endorsements_pool_size_per_thread);
+        for signatures in self.signatures_sorted.iter_mut() {
+            while signatures.len() > max_signatures_per_thread {
+                signatures.pop_last();
+            }
+        }
+        added.retain(|signature| self.contains_signature(signature));
+        added
     }
 
     /// Add a list of endorsements to the pool
@@ -120,10 +181,11 @@ impl EndorsementPool {
         }
 
         // prune excess endorsements
+        let max_endorsements_per_thread = self
+            .capacity_scale
+            .apply(self.config.max_endorsements_pool_size_per_thread);
         for thread in 0..self.config.thread_count {
-            while self.endorsements_sorted[thread as usize].len()
-                > self.config.max_endorsements_pool_size_per_thread
-            {
+            while self.endorsements_sorted[thread as usize].len() > max_endorsements_per_thread {
                 // won't panic because len was checked above
                 let (_key, endo_id) = self.endorsements_sorted[thread as usize]
                     .pop_last()
@@ -174,4 +236,19 @@ impl EndorsementPool {
 
         (endo_ids, endo_storage)
     }
+
+    /// get the BLS signatures of endorsers to aggregate in a block header
+    pub fn get_block_endorsement_signatures(
+        &self,
+        slot: &Slot, // slot of the block in which the signatures are aggregated
+        target_block: &BlockId,
+    ) -> Vec<EndorsementSignature> {
+        (0..self.config.max_block_endorsement_count)
+            .filter_map(|index| {
+                self.signatures_sorted[slot.thread as usize]
+                    .get(&(*slot, index, *target_block))
+                    .copied()
+            })
+            .collect()
+    }
 }
@@ -1,20 +1,39 @@
 //! Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use massa_channel::{NodeEvent, Topic};
 use massa_execution_exports::ExecutionController;
 use massa_models::{
     address::Address,
     amount::Amount,
-    operation::OperationId,
+    operation::{
+        next_operation_sequence, OperationId, OperationType, SecureShareOperation,
+        OPERATION_SEQUENCE_KEY,
+    },
     prehash::{CapacityAllocator, PreHashMap, PreHashSet},
     slot::Slot,
+    stats::BytecodeRejectStats,
 };
-use massa_pool_exports::{PoolChannels, PoolConfig};
+use massa_pool_exports::{AnalysisRejection, OperationAnalyzer, PoolChannels, PoolConfig};
 use massa_storage::Storage;
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use tracing::debug;
 
 use crate::types::{OperationInfo, PoolOperationCursor};
 
+/// Operations selected for a block, and what remains of the block for the next ones
+struct BlockOperationsSelection {
+    /// selected operations, in their order of selection
+    op_ids: Vec<OperationId>,
+    /// remaining block space
+    remaining_space: usize,
+    /// remaining block gas
+    remaining_gas: u64,
+    /// remaining number of operations
+    remaining_ops: u32,
+    /// cache of balances
+    balance_cache: PreHashMap<Address, Amount>,
+}
+
 pub struct OperationPool {
     /// configuration
     config: PoolConfig,
@@ -38,7 +57,13 @@ pub struct OperationPool {
     last_cs_final_periods: Vec<u64>,
 
     /// channels used by the pool worker
-    channels: PoolChannels,
+    pub(crate) channels: PoolChannels,
+
+    /// pre-admission analyzer of the `ExecuteSC` operations, if any
+    pub(crate) analyzer: Option<Box<dyn OperationAnalyzer>>,
+
+    /// operations refused by the analyzer
+    pub(crate) bytecode_reject_stats: BytecodeRejectStats,
 }
 
 impl OperationPool {
@@ -47,6 +72,7 @@ impl OperationPool {
         storage: &Storage,
         execution_controller: Box<dyn ExecutionController>,
         channels: PoolChannels,
+        analyzer: Option<Box<dyn OperationAnalyzer>>,
     ) -> Self {
         OperationPool {
             operations: Default::default(),
@@ -57,6 +83,8 @@ impl OperationPool {
             storage: storage.clone_without_refs(),
             execution_controller,
             channels,
+            analyzer,
+            bytecode_reject_stats: Default::default(),
         }
     }
 
@@ -99,6 +127,30 @@ impl OperationPool {
         self.storage.drop_operation_refs(&removed_ops);
     }
 
+    /// Checks the bytecode of an `ExecuteSC` operation with the analyzer, counting the refused operations
+    fn is_bytecode_admitted(&mut self, op: &SecureShareOperation) -> bool {
+        let (Some(analyzer), OperationType::ExecuteSC { data, max_gas, .. }) =
+            (&self.analyzer, &op.content.op)
+        else {
+            return true;
+        };
+        let Err(rejection) = analyzer.analyze(data, *max_gas) else {
+            return true;
+        };
+        debug!(
+            "operation {} refused by the bytecode analyzer: {}",
+            op.id, rejection
+        );
+        let stats = &mut self.bytecode_reject_stats;
+        match rejection {
+            AnalysisRejection::TooLarge(_) => stats.too_large += 1,
+            AnalysisRejection::InvalidModule(_) => stats.invalid_module += 1,
+            AnalysisRejection::BannedImport(_) => stats.banned_import += 1,
+            AnalysisRejection::InsufficientGas(_) => stats.insufficient_gas += 1,
+        }
+        false
+    }
+
     /// Checks if an operation is relevant according to its thread and period validity range
     pub(crate) fn is_operation_relevant(&self, op_info: &OperationInfo) -> bool {
         // too old
@@ -107,7 +159,34 @@ impl OperationPool {
     }
 
     /// Add a list of operations to the pool
-    pub(crate) fn add_operations(&mut self, mut ops_storage: Storage) {
+    pub(crate) fn add_operations(&mut self, ops_storage: Storage) {
+        self.insert_operations(ops_storage, self.config.broadcast_enabled)
+    }
+
+    /// Add a list of operations to the pool without broadcasting them to the subscribers of new operations
+    pub(crate) fn add_private_operations(&mut self, ops_storage: Storage) {
+        self.insert_operations(ops_storage, false)
+    }
+
+    /// Remove a list of operations from the pool, ignoring the ones that are not in it
+    pub(crate) fn remove_operations(&mut self, op_ids: &PreHashSet<OperationId>) {
+        let mut removed = PreHashSet::with_capacity(op_ids.len());
+        for op_id in op_ids {
+            if let Some(op_info) = self.operations.remove(op_id) {
+                if !self.sorted_ops_per_thread[op_info.thread as usize].remove(&op_info.cursor) {
+                    panic!("expected op presence in sorted list")
+                }
+                let end_slot = Slot::new(*op_info.validity_period_range.end(), op_info.thread);
+                if !self.ops_per_expiration.remove(&(end_slot, op_info.id)) {
+                    panic!("expected op presence in expiration indexed ops")
+                }
+                removed.insert(op_info.id);
+            }
+        }
+        self.storage.drop_operation_refs(&removed);
+    }
+
+    fn insert_operations(&mut self, mut ops_storage: Storage, broadcast: bool) {
         let items = ops_storage
             .get_op_refs()
             .iter()
@@ -124,9 +203,17 @@ impl OperationPool {
                 let op = ops
                     .get(&op_id)
                     .expect("attempting to add operation to pool, but it is absent from storage");
-                // Broadcast operation to active sender(channel) subscribers.
-                if self.config.broadcast_enabled {
-                    let _ = self.channels.operation_sender.send(op.content.clone());
+                if !self.is_bytecode_admitted(op) {
+                    continue;
+                }
+                // publish the operation to the subscribers of the event bus
+                if broadcast {
+                    self.channels
+                        .event_bus
+                        .publish_with(Topic::OperationPooled, || NodeEvent::OperationPooled {
+                            operation_id: op_id,
+                            operation: op.content.clone(),
+                        });
                 }
                 let op_info = OperationInfo::from_op(
                     op,
@@ -137,6 +224,10 @@ impl OperationPool {
                 if !self.is_operation_relevant(&op_info) {
                     continue;
                 }
+                // an operation paying less than its minimal fee is never executed
+                if op.content.fee < op.get_min_fee() {
+                    continue;
+                }
                 if let Ok(op_info) = self.operations.try_insert(op_info.id, op_info) {
                     if !self.sorted_ops_per_thread[op_info.thread as usize].insert(op_info.cursor) {
                         panic!("sorted ops should not contain the op at this point");
@@ -153,8 +244,12 @@ impl OperationPool {
         }
 
         // prune excess operations
+        let max_ops_per_thread = self
+            .channels
+            .capacity_scale
+            .apply(self.config.max_operation_pool_size_per_thread);
         self.sorted_ops_per_thread.iter_mut().for_each(|ops| {
-            while ops.len() > self.config.max_operation_pool_size_per_thread {
+            while ops.len() > max_ops_per_thread {
                 // the unwrap below won't panic because the loop condition tests for non-emptines of self.operations
                 let cursor = ops.pop_last().unwrap();
                 let op_info = self
@@ -190,23 +285,31 @@ impl OperationPool {
     /// Searches the available operations, and selects the sub-set of operations that:
     /// - fit inside the block
     /// - is the most profitable for block producer
+    /// - follow the sequence of their sender, if they have one
+    ///
+    /// An op whose sequence is beyond the next one of its sender is held until the missing sequences are included:
+    /// the held ops of a sender are searched again each time its sequence advances, so that the ops of a sender
+    /// can be included in the same block whatever their order of profitability.
     pub fn get_block_operations(&self, slot: &Slot) -> (Vec<OperationId>, Storage) {
-        // init list of selected operation IDs
-        let mut op_ids = Vec::new();
-
-        // init remaining space
-        let mut remaining_space = self.config.max_block_size as usize;
-        // init remaining gas
-        let mut remaining_gas = self.config.max_block_gas;
-        // init remaining number of operations
-        let mut remaining_ops = self.config.max_operations_per_block;
-        // cache of balances
-        let mut balance_cache: PreHashMap<Address, Amount> = Default::default();
+        let mut selection = BlockOperationsSelection {
+            op_ids: Vec::new(),
+            remaining_space: self.config.max_block_size as usize,
+            remaining_gas: self.config.max_block_gas,
+            remaining_ops: self.config.max_operations_per_block,
+            balance_cache: Default::default(),
+        };
+        // cache of the next sequences expected from the senders
+        let mut sequence_cache: PreHashMap<Address, u64> = Default::default();
+        // ops beyond the next sequence of their sender, by sender and sequence, from best to worst
+        let mut held_ops: PreHashMap<Address, BTreeMap<u64, Vec<&OperationInfo>>> =
+            Default::default();
+        // senders whose sequence advanced, whose held ops are to be searched again
+        let mut advanced_senders = Vec::new();
 
         // iterate over pool operations in the right thread, from best to worst
         for cursor in self.sorted_ops_per_thread[slot.thread as usize].iter() {
             // if we have reached the maximum number of operations, stop
-            if remaining_ops == 0 {
+            if selection.remaining_ops == 0 {
                 break;
             }
             let op_info = self
@@ -214,70 +317,57 @@ impl OperationPool {
                 .get(&cursor.get_id())
                 .expect("the operation should be in self.operations at this point");
 
-            // exclude ops for which the block slot is outside of their validity range
-            if !op_info.validity_period_range.contains(&slot.period) {
-                continue;
+            // check sequence: an op whose sequence was already used can never be executed,
+            // and an op beyond the next sequence waits for the missing ones
+            if let Some(sequence) = op_info.sequence {
+                let next_sequence = *sequence_cache
+                    .entry(op_info.creator_address)
+                    .or_insert_with(|| self.get_next_operation_sequence(&op_info.creator_address));
+                if sequence < next_sequence {
+                    continue;
+                }
+                if sequence > next_sequence {
+                    held_ops
+                        .entry(op_info.creator_address)
+                        .or_default()
+                        .entry(sequence)
+                        .or_default()
+                        .push(op_info);
+                    continue;
+                }
             }
 
-            // exclude ops that are too large
-            if op_info.size > remaining_space {
-                continue;
+            if self.select_operation(&mut selection, op_info, slot) {
+                if let Some(sequence) = op_info.sequence {
+                    sequence_cache.insert(op_info.creator_address, sequence.saturating_add(1));
+                    advanced_senders.push(op_info.creator_address);
+                }
             }
+        }
 
-            // exclude ops that require too much gas
-            if op_info.max_gas > remaining_gas {
-                continue;
+        // search the held ops of the senders whose sequence advanced
+        while let Some(sender) = advanced_senders.pop() {
+            if selection.remaining_ops == 0 {
+                break;
             }
-
-            // check if the op was already executed
-            // TODO batch this
-            if self
-                .execution_controller
-                .unexecuted_ops_among(&vec![op_info.id].into_iter().collect(), slot.thread)
-                .is_empty()
-            {
+            let Some(sender_ops) = held_ops.get_mut(&sender) else {
                 continue;
-            }
-
-            // check balance
-            //TODO: It's a weird behaviour because if the address is created afterwards this operation will be executed
-            // and also it spams the pool maybe we should just try to put the operation if there is no balance and 0 gas price
-            // and the execution will throw an error
-            let creator_balance =
-                if let Some(amount) = balance_cache.get_mut(&op_info.creator_address) {
-                    amount
-                } else if let Some(balance) = self
-                    .execution_controller
-                    .get_final_and_candidate_balance(&[op_info.creator_address])
-                    .get(0)
-                    .map(|balances| balances.1.or(balances.0))
-                    && let Some(final_amount) = balance {
-                        balance_cache
-                        .entry(op_info.creator_address)
-                        .or_insert(final_amount)
-                } else {
-                    continue;
-                };
-
-            if *creator_balance < op_info.fee {
+            };
+            let next_sequence = sequence_cache[&sender];
+            // the held ops whose sequence was used meanwhile can never be executed
+            *sender_ops = sender_ops.split_off(&next_sequence);
+            let Some(candidates) = sender_ops.remove(&next_sequence) else {
                 continue;
+            };
+            for op_info in candidates {
+                if self.select_operation(&mut selection, op_info, slot) {
+                    sequence_cache.insert(sender, next_sequence.saturating_add(1));
+                    advanced_senders.push(sender);
+                    break;
+                }
             }
-
-            // here we consider the operation as accepted
-            op_ids.push(op_info.id);
-
-            // update remaining block space
-            remaining_space -= op_info.size;
-
-            // update remaining block gas
-            remaining_gas -= op_info.max_gas;
-
-            // update remaining number of operations
-            remaining_ops -= 1;
-
-            // update balance cache
-            *creator_balance = creator_balance.saturating_sub(op_info.max_spending);
         }
+        let op_ids = selection.op_ids;
 
         // generate storage
         let mut res_storage = self.storage.clone_without_refs();
@@ -289,4 +379,109 @@ impl OperationPool {
 
         (op_ids, res_storage)
     }
+
+    /// Adds `op_info` to `selection` if it can be included in a block at `slot`, whatever its sequence.
+    /// Returns whether it was added.
+    fn select_operation(
+        &self,
+        selection: &mut BlockOperationsSelection,
+        op_info: &OperationInfo,
+        slot: &Slot,
+    ) -> bool {
+        // exclude ops for which the block slot is outside of their validity range
+        if !op_info.validity_period_range.contains(&slot.period) {
+            return false;
+        }
+
+        // exclude ops that are too large
+        if op_info.size > selection.remaining_space {
+            return false;
+        }
+
+        // exclude ops that require too much gas
+        if op_info.max_gas > selection.remaining_gas {
+            return false;
+        }
+
+        // check if the op was already executed
+        // TODO batch this
+        if self
+            .execution_controller
+            .unexecuted_ops_among(&vec![op_info.id].into_iter().collect(), slot.thread)
+            .is_empty()
+        {
+            return false;
+        }
+
+        // check balance
+        //TODO: It's a weird behaviour because if the address is created afterwards this operation will be executed
+        // and also it spams the pool maybe we should just try to put the operation if there is no balance and 0 gas price
+        // and the execution will throw an error
+        let Some(fee_payer_balance) =
+            self.get_cached_balance(&mut selection.balance_cache, &op_info.fee_payer) else {
+            return false;
+        };
+        if fee_payer_balance < op_info.fee {
+            return false;
+        }
+        // the creator of a sponsored op must still afford the rest of its spending
+        let sponsored = op_info.fee_payer != op_info.creator_address;
+        if sponsored
+            && self
+                .get_cached_balance(&mut selection.balance_cache, &op_info.creator_address)
+                .unwrap_or_default()
+                < op_info.max_spending
+        {
+            return false;
+        }
+
+        // here we consider the operation as accepted
+        selection.op_ids.push(op_info.id);
+
+        // update remaining block space
+        selection.remaining_space -= op_info.size;
+
+        // update remaining block gas
+        selection.remaining_gas -= op_info.max_gas;
+
+        // update remaining number of operations
+        selection.remaining_ops -= 1;
+
+        // update balance cache
+        if sponsored && let Some(balance) = selection.balance_cache.get_mut(&op_info.fee_payer) {
+            *balance = balance.saturating_sub(op_info.fee);
+        }
+        if let Some(balance) = selection.balance_cache.get_mut(&op_info.creator_address) {
+            *balance = balance.saturating_sub(op_info.max_spending);
+        }
+        true
+    }
+
+    /// Candidate balance of `address`, read from `balance_cache` or from the ledger, `None` if it does not exist
+    fn get_cached_balance(
+        &self,
+        balance_cache: &mut PreHashMap<Address, Amount>,
+        address: &Address,
+    ) -> Option<Amount> {
+        if let Some(amount) = balance_cache.get(address) {
+            return Some(*amount);
+        }
+        let amount = self
+            .execution_controller
+            .get_final_and_candidate_balance(&[*address])
+            .get(0)
+            .and_then(|balances| balances.1.or(balances.0))?;
+        balance_cache.insert(*address, amount);
+        Some(amount)
+    }
+
+    /// Next sequence expected from the ops of `address`, according to the candidate ledger
+    fn get_next_operation_sequence(&self, address: &Address) -> u64 {
+        let recorded = self
+            .execution_controller
+            .get_final_and_active_data_entry(vec![(*address, OPERATION_SEQUENCE_KEY.to_vec())])
+            .pop()
+            .and_then(|(final_value, active_value)| active_value.or(final_value));
+        next_operation_sequence(recorded.as_deref())
+    }
 }
@@ -21,8 +21,12 @@ use crate::tests::tools::OpGenerator;
 
 use super::tools::{create_some_operations, operation_pool_test, pool_test};
 use massa_execution_exports::test_exports::MockExecutionControllerMessage;
-use massa_models::{amount::Amount, operation::OperationId, slot::Slot};
-use massa_pool_exports::PoolConfig;
+use massa_models::{
+    amount::Amount, operation::OperationId, prehash::PreHashSet, slot::Slot,
+    stats::BytecodeRejectStats,
+};
+use massa_pool_exports::{BytecodeAnalyzer, BytecodeAnalyzerConfig, PoolConfig};
+use massa_signature::KeyPair;
 use std::time::Duration;
 
 #[test]
@@ -54,6 +58,48 @@ fn test_add_irrelevant_operation() {
     });
 }
 
+/// Test that removed operations are no longer referenced by the pool, the others being kept.
+#[test]
+fn test_remove_operations() {
+    operation_pool_test(PoolConfig::default(), |mut operation_pool, mut storage| {
+        let op_gen = OpGenerator::default().expirery(2);
+        let operations = create_some_operations(10, &op_gen);
+        let removed: PreHashSet<OperationId> = operations.iter().take(4).map(|op| op.id).collect();
+        storage.store_operations(operations);
+        operation_pool.add_private_operations(storage);
+        operation_pool.remove_operations(&removed);
+        assert_eq!(operation_pool.len(), 6);
+        assert!(removed.iter().all(|op_id| !operation_pool.contains(op_id)));
+        assert_eq!(operation_pool.storage.get_op_refs().len(), 6);
+    });
+}
+
+/// Test that the pool is pruned to its capacity scaled down under memory pressure at the next addition.
+#[test]
+fn test_capacity_scale() {
+    let pool_config = PoolConfig {
+        max_operation_pool_size_per_thread: 10,
+        ..PoolConfig::default()
+    };
+    operation_pool_test(pool_config, |mut operation_pool, storage| {
+        // a single creator puts all the operations in the same thread
+        let op_gen = OpGenerator::default()
+            .expirery(2)
+            .creator(KeyPair::generate());
+        let mut ops_storage = storage.clone_without_refs();
+        ops_storage.store_operations(create_some_operations(10, &op_gen));
+        operation_pool.add_private_operations(ops_storage);
+        assert_eq!(operation_pool.len(), 10);
+
+        operation_pool.channels.capacity_scale.set_permille(500);
+        let mut ops_storage = storage.clone_without_refs();
+        ops_storage.store_operations(create_some_operations(1, &op_gen));
+        operation_pool.add_private_operations(ops_storage);
+        assert_eq!(operation_pool.len(), 5);
+        assert_eq!(operation_pool.storage.get_op_refs().len(), 5);
+    });
+}
+
 /// TODO refactor old tests
 #[test]
 fn test_pool() {
@@ -222,3 +268,139 @@ fn test_pool() {
         },
     );
 }
+
+/// Test that the ops with a sequence are selected in the order of the sequence of their sender:
+/// the used sequences are skipped and the ops after a gap are held.
+#[test]
+fn test_operation_sequence_gaps() {
+    let pool_config = PoolConfig::default();
+    pool_test(
+        pool_config,
+        |mut pool_manager, mut pool, execution_receiver, storage_base| {
+            let creator = KeyPair::generate();
+            let op_gen = OpGenerator::default().creator(creator).expirery(10);
+            // (sequence, fee): the most profitable ops come after the next sequence
+            let ops: Vec<_> = [(0, 40), (1, 10), (2, 30), (3, 20), (5, 50)]
+                .into_iter()
+                .map(|(sequence, fee)| {
+                    op_gen
+                        .clone()
+                        .sequence(sequence)
+                        .fee(Amount::from_raw(fee))
+                        .generate()
+                })
+                .collect();
+            let mut storage = storage_base.clone_without_refs();
+            storage.store_operations(ops.clone());
+            pool.add_operations(storage);
+            // Allow some time for the pool to add the operations
+            std::thread::sleep(Duration::from_millis(100));
+
+            std::thread::spawn(move || loop {
+                match execution_receiver.recv_timeout(Duration::from_millis(2000)) {
+                    Ok(MockExecutionControllerMessage::UnexecutedOpsAmong {
+                        ops,
+                        response_tx,
+                        ..
+                    }) => {
+                        response_tx.send(ops).unwrap();
+                    }
+                    Ok(MockExecutionControllerMessage::GetFinalAndCandidateBalance {
+                        response_tx,
+                        ..
+                    }) => response_tx
+                        .send(vec![(
+                            Some(Amount::from_raw(60 * 1_000_000_000)),
+                            Some(Amount::from_raw(60 * 1_000_000_000)),
+                        )])
+                        .unwrap(),
+                    // the sequence 0 was already used
+                    Ok(MockExecutionControllerMessage::GetFinalAndActiveDataEntry {
+                        response_tx,
+                        ..
+                    }) => response_tx
+                        .send(vec![(Some(1u64.to_be_bytes().to_vec()), None)])
+                        .unwrap(),
+                    Ok(_) => {}
+                    Err(_) => break,
+                }
+            });
+
+            let op_thread = ops[0]
+                .content_creator_address
+                .get_thread(pool_config.thread_count);
+            let (ids, _) = pool.get_block_operations(&Slot::new(10, op_thread));
+            assert_eq!(ids, vec![ops[1].id, ops[2].id, ops[3].id]);
+            pool_manager.stop();
+        },
+    );
+}
+
+/// WebAssembly module importing the function `name` of `module`
+fn module_importing(module: &str, name: &str) -> Vec<u8> {
+    let mut import_section = vec![1, module.len() as u8];
+    import_section.extend(module.as_bytes());
+    import_section.push(name.len() as u8);
+    import_section.extend(name.as_bytes());
+    import_section.extend([0x00, 0x00]);
+    let mut bytecode = vec![0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00];
+    bytecode.extend([0x02, import_section.len() as u8]);
+    bytecode.extend(import_section);
+    bytecode
+}
+
+/// Test that the `ExecuteSC` operations refused by the bytecode analyzer are not added and are counted by reason
+#[test]
+fn test_bytecode_analyzer() {
+    operation_pool_test(PoolConfig::default(), |mut operation_pool, mut storage| {
+        operation_pool.analyzer = Some(Box::new(BytecodeAnalyzer::new(BytecodeAnalyzerConfig {
+            enabled: true,
+            max_bytecode_size: 1_000,
+            allowed_import_modules: vec!["massa".to_string()],
+            banned_imports: vec!["massa.banned".to_string()],
+            min_gas_per_byte: 1,
+        })));
+        let op_gen = OpGenerator::default().expirery(2);
+        let accepted = op_gen
+            .clone()
+            .execute_sc(module_importing("massa", "print"), 1_000)
+            .generate();
+        storage.store_operations(vec![
+            accepted.clone(),
+            op_gen
+                .clone()
+                .execute_sc(vec![0; 2_000], 1_000_000)
+                .generate(),
+            op_gen
+                .clone()
+                .execute_sc(b"junk".to_vec(), 1_000)
+                .generate(),
+            op_gen
+                .clone()
+                .execute_sc(module_importing("wasi", "fd_write"), 1_000)
+                .generate(),
+            op_gen
+                .clone()
+                .execute_sc(module_importing("massa", "banned"), 1_000)
+                .generate(),
+            op_gen
+                .clone()
+                .execute_sc(module_importing("massa", "print"), 1)
+                .generate(),
+            op_gen.generate(),
+        ]);
+        operation_pool.add_operations(storage);
+
+        assert_eq!(operation_pool.storage.get_op_refs().len(), 2);
+        assert!(operation_pool.storage.get_op_refs().contains(&accepted.id));
+        assert_eq!(
+            operation_pool.bytecode_reject_stats,
+            BytecodeRejectStats {
+                too_large: 1,
+                invalid_module: 1,
+                banned_import: 2,
+                insufficient_gas: 1,
+            }
+        );
+    });
+}
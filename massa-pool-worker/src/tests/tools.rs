@@ -1,6 +1,7 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 use crate::{operation_pool::OperationPool, start_pool_controller};
+use massa_channel::EventBus;
 use massa_execution_exports::test_exports::{
     MockExecutionController, MockExecutionControllerMessage,
 };
@@ -15,18 +16,20 @@ use massa_models::{
     slot::Slot,
 };
 use massa_pool_exports::{PoolChannels, PoolConfig, PoolController, PoolManager};
+use massa_pos_exports::test_exports::MockSelectorController;
 use massa_signature::KeyPair;
 use massa_storage::Storage;
 use std::sync::mpsc::Receiver;
-use tokio::sync::broadcast;
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub(crate) struct OpGenerator {
     creator: Option<KeyPair>,
     receiver: Option<KeyPair>,
     fee: Option<Amount>,
     amount: Option<Amount>,
     expirery: Option<u64>,
+    sequence: Option<u64>,
+    execute_sc: Option<(Vec<u8>, u64)>,
 }
 
 impl OpGenerator {
@@ -35,12 +38,23 @@ impl OpGenerator {
         self
     }
 
+    pub(crate) fn sequence(mut self, sequence: u64) -> Self {
+        self.sequence = Some(sequence);
+        self
+    }
+
     #[allow(dead_code)]
     pub(crate) fn amount(mut self, amount: Amount) -> Self {
         self.amount = Some(amount);
         self
     }
 
+    /// Generates `ExecuteSC` operations running `bytecode` with `max_gas`
+    pub(crate) fn execute_sc(mut self, bytecode: Vec<u8>, max_gas: u64) -> Self {
+        self.execute_sc = Some((bytecode, max_gas));
+        self
+    }
+
     pub(crate) fn fee(mut self, fee: Amount) -> Self {
         self.fee = Some(fee);
         self
@@ -64,14 +78,24 @@ impl OpGenerator {
         let amount = self.amount.unwrap_or_default();
         let expirery = self.expirery.unwrap_or_default();
 
-        let op = OperationType::Transaction {
-            recipient_address: Address::from_public_key(&receiver.get_public_key()),
-            amount,
+        let op = match &self.execute_sc {
+            Some((data, max_gas)) => OperationType::ExecuteSC {
+                data: data.clone(),
+                max_gas: *max_gas,
+                datastore: Default::default(),
+            },
+            None => OperationType::Transaction {
+                recipient_address: Address::from_public_key(&receiver.get_public_key()),
+                amount,
+                memo: None,
+            },
         };
         let content = Operation {
             fee,
             op,
             expire_period: expirery,
+            sequence: self.sequence,
+            sponsor: None,
         };
         Operation::new_verifiable(content, OperationSerializer::new(), &creator).unwrap()
     }
@@ -93,13 +117,17 @@ where
     ),
 {
     let storage: Storage = Storage::create_root();
-    let operation_sender = broadcast::channel(5000).0;
     let (execution_controller, execution_receiver) = MockExecutionController::new_with_receiver();
     let (pool_manager, pool_controller) = start_pool_controller(
         cfg,
         &storage,
         execution_controller,
-        PoolChannels { operation_sender },
+        PoolChannels {
+            event_bus: EventBus::new(5000),
+            capacity_scale: Default::default(),
+            selector: MockSelectorController::new_with_receiver().0,
+        },
+        None,
     );
 
     test(pool_manager, pool_controller, execution_receiver, storage)
@@ -109,7 +137,6 @@ pub fn operation_pool_test<F>(cfg: PoolConfig, test: F)
 where
     F: FnOnce(OperationPool, Storage),
 {
-    let operation_sender = broadcast::channel(5000).0;
     let (execution_controller, _) = MockExecutionController::new_with_receiver();
     let storage = Storage::create_root();
     test(
@@ -117,7 +144,12 @@ where
             cfg,
             &storage.clone_without_refs(),
             execution_controller,
-            PoolChannels { operation_sender },
+            PoolChannels {
+                event_bus: EventBus::new(5000),
+                capacity_scale: Default::default(),
+                selector: MockSelectorController::new_with_receiver().0,
+            },
+            None,
         ),
         storage,
     )
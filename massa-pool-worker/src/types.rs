@@ -31,11 +31,15 @@ pub struct OperationInfo {
     pub size: usize,
     pub max_gas: u64,
     pub creator_address: Address,
+    /// address paying the fee: the sponsor of the op if any, otherwise its creator
+    pub fee_payer: Address,
     pub thread: u8,
     pub fee: Amount,
-    /// max amount that the op might spend from the sender's balance
+    /// max amount that the op might spend from the sender's balance, without the fee if it is sponsored
     pub max_spending: Amount,
     pub validity_period_range: RangeInclusive<u64>,
+    /// sequence of the op among those of its sender, if any
+    pub sequence: Option<u64>,
 }
 
 impl OperationInfo {
@@ -51,10 +55,12 @@ impl OperationInfo {
             size: op.serialized_size(),
             max_gas: op.get_gas_usage(),
             creator_address: op.content_creator_address,
+            fee_payer: op.get_fee_payer(),
             fee: op.content.fee,
             thread: op.content_creator_address.get_thread(thread_count),
             validity_period_range: op.get_validity_range(operation_validity_periods),
             max_spending: op.get_max_spending(roll_price),
+            sequence: op.content.sequence,
         }
     }
 }
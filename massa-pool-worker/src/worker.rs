@@ -7,7 +7,7 @@ use crate::operation_pool::OperationPool;
 use crate::{controller_impl::PoolControllerImpl, endorsement_pool::EndorsementPool};
 use massa_execution_exports::ExecutionController;
 use massa_pool_exports::PoolConfig;
-use massa_pool_exports::{PoolChannels, PoolController, PoolManager};
+use massa_pool_exports::{OperationAnalyzer, PoolChannels, PoolController, PoolManager};
 use massa_storage::Storage;
 use parking_lot::RwLock;
 use std::{
@@ -52,6 +52,8 @@ impl EndorsementPoolThread {
                 Ok(Command::AddItems(endorsements)) => {
                     self.endorsement_pool.write().add_endorsements(endorsements)
                 }
+                // operation pool commands
+                Ok(Command::AddPrivateOperations(_)) | Ok(Command::RemoveOperations(_)) => {}
                 Ok(Command::NotifyFinalCsPeriods(final_cs_periods)) => self
                     .endorsement_pool
                     .write()
@@ -96,6 +98,13 @@ impl OperationPoolThread {
                 Ok(Command::AddItems(operations)) => {
                     self.operation_pool.write().add_operations(operations)
                 }
+                Ok(Command::AddPrivateOperations(operations)) => self
+                    .operation_pool
+                    .write()
+                    .add_private_operations(operations),
+                Ok(Command::RemoveOperations(op_ids)) => {
+                    self.operation_pool.write().remove_operations(&op_ids)
+                }
                 Ok(Command::NotifyFinalCsPeriods(final_cs_periods)) => self
                     .operation_pool
                     .write()
@@ -112,23 +121,32 @@ pub fn start_pool_controller(
     storage: &Storage,
     execution_controller: Box<dyn ExecutionController>,
     channels: PoolChannels,
+    analyzer: Option<Box<dyn OperationAnalyzer>>,
 ) -> (Box<dyn PoolManager>, Box<dyn PoolController>) {
     let (operations_input_sender, operations_input_receiver) = sync_channel(config.channels_size);
     let (endorsements_input_sender, endorsements_input_receiver) =
         sync_channel(config.channels_size);
+    let endorsement_pool = Arc::new(RwLock::new(EndorsementPool::init(
+        config,
+        storage,
+        channels.capacity_scale.clone(),
+    )));
+    let selector = channels.selector.clone();
     let operation_pool = Arc::new(RwLock::new(OperationPool::init(
         config,
         storage,
-        execution_controller,
+        execution_controller.clone(),
         channels,
+        analyzer,
     )));
-    let endorsement_pool = Arc::new(RwLock::new(EndorsementPool::init(config, storage)));
     let controller = PoolControllerImpl {
-        _config: config,
+        config,
         operation_pool: operation_pool.clone(),
         endorsement_pool: endorsement_pool.clone(),
         operations_input_sender: operations_input_sender.clone(),
         endorsements_input_sender: endorsements_input_sender.clone(),
+        selector,
+        execution_controller,
     };
 
     let operations_thread_handle =
@@ -9,6 +9,10 @@ pub struct PoSConfig {
     pub thread_count: u8,
     /// number of saved cycle
     pub cycle_history_length: usize,
+    /// number of complete cycles kept for queries after leaving the cycle history
+    pub cycle_info_retention: usize,
+    /// number of periods during which the executed deferred credits are kept for queries
+    pub deferred_credits_retention: u64,
     /// maximum size of a deferred credits bootstrap part
     pub credits_bootstrap_part_size: u64,
 }
@@ -31,6 +31,10 @@ pub trait SelectorController: Send + Sync {
     /// Errors can occur if the thread stopped.
     fn wait_for_draws(&self, cycle: u64) -> PosResult<u64>;
 
+    /// Get the latest cycle for which draws are available, without waiting.
+    /// Returns `None` if no draws were computed yet.
+    fn get_last_drawn_cycle(&self) -> PosResult<Option<u64>>;
+
     /// Feed cycle to the selector
     ///
     /// # Arguments
@@ -20,6 +20,7 @@ use nom::{
     IResult, Parser,
 };
 use num::rational::Ratio;
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::ops::Bound::Included;
@@ -122,21 +123,61 @@ impl CycleInfo {
         production_stats: PreHashMap<Address, ProductionStats>,
     ) -> Self {
         let hash_computer = CycleInfoHashComputer::new();
-        let mut roll_counts_hash = Hash::from_bytes(CYCLE_INFO_HASH_INITIAL_BYTES);
-        let mut production_stats_hash = Hash::from_bytes(CYCLE_INFO_HASH_INITIAL_BYTES);
+        // the entry hashes are independent, compute them in parallel and XOR them together
+        let roll_counts_hash = roll_counts
+            .par_iter()
+            .map(|(addr, &count)| hash_computer.compute_roll_entry_hash(addr, count))
+            .reduce(
+                || Hash::from_bytes(CYCLE_INFO_HASH_INITIAL_BYTES),
+                |a, b| a ^ b,
+            );
+        Self::new_with_roll_counts_hash(
+            cycle,
+            complete,
+            roll_counts,
+            roll_counts_hash,
+            rng_seed,
+            production_stats,
+        )
+    }
+
+    /// Create the `CycleInfo` of the cycle following this one.
+    /// Its roll counts start as the ones of this cycle, whose hash is reused instead of being computed again.
+    pub(crate) fn new_next_cycle(&self, cycle: u64, rng_seed: BitVec<u8>) -> Self {
+        Self::new_with_roll_counts_hash(
+            cycle,
+            false,
+            self.roll_counts.clone(),
+            self.roll_counts_hash,
+            rng_seed,
+            PreHashMap::default(),
+        )
+    }
+
+    /// Create a new `CycleInfo` whose `roll_counts_hash` is already known and compute the other hashes
+    fn new_with_roll_counts_hash(
+        cycle: u64,
+        complete: bool,
+        roll_counts: BTreeMap<Address, u64>,
+        roll_counts_hash: Hash,
+        rng_seed: BitVec<u8>,
+        production_stats: PreHashMap<Address, ProductionStats>,
+    ) -> Self {
+        let hash_computer = CycleInfoHashComputer::new();
+        let production_stats_hash = production_stats
+            .par_iter()
+            .map(|(addr, prod_stats)| hash_computer.compute_prod_stats_entry_hash(addr, prod_stats))
+            .reduce(
+                || Hash::from_bytes(CYCLE_INFO_HASH_INITIAL_BYTES),
+                |a, b| a ^ b,
+            );
 
         // compute the cycle hash
         let mut hash_concat: Vec<u8> = Vec::new();
         hash_concat.extend(hash_computer.compute_cycle_hash(cycle).to_bytes());
         hash_concat.extend(hash_computer.compute_complete_hash(complete).to_bytes());
         hash_concat.extend(hash_computer.compute_seed_hash(&rng_seed).to_bytes());
-        for (addr, &count) in &roll_counts {
-            roll_counts_hash ^= hash_computer.compute_roll_entry_hash(addr, count);
-        }
         hash_concat.extend(roll_counts_hash.to_bytes());
-        for (addr, prod_stats) in &production_stats {
-            production_stats_hash ^= hash_computer.compute_prod_stats_entry_hash(addr, prod_stats);
-        }
         hash_concat.extend(production_stats_hash.to_bytes());
 
         // compute the global hash
@@ -1,4 +1,5 @@
 use displaydoc::Display;
+use massa_models::slot::Slot;
 use thiserror::Error;
 
 /// Proof-of-Stake result
@@ -18,8 +19,12 @@ pub enum PosError {
     CycleUnavailable(u64),
     /// `CycleUnfinished`: PoS cycle {0} is needed but is not complete yet
     CycleUnfinished(u64),
+    /// `SlotNotFinal`: slot {0} is not final yet
+    SlotNotFinal(Slot),
     /// Error while loading initial rolls file: {0}
     RollsFileLoadingError(String),
     /// Communication channel was down: {0}
     ChannelDown(String),
+    /// Invalid selector configuration: {0}
+    InvalidConfig(String),
 }
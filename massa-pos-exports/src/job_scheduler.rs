@@ -0,0 +1,201 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Slot-time-aware scheduling of the heavy background jobs.
+//!
+//! Jobs like ledger compaction or final state checkpoints load the disk and the CPU for seconds,
+//! which can make a modest node miss the blocks and endorsements it has to produce. The scheduler
+//! queries the selector for the upcoming slots of the local staking addresses and only lets the
+//! jobs run in the quiet windows between them. A job never waits for more than `max_delay`.
+
+use crate::SelectorController;
+use massa_models::address::Address;
+use massa_models::prehash::PreHashSet;
+use massa_models::slot::Slot;
+use massa_models::timeslots::{get_block_slot_timestamp, get_latest_block_slot_at_timestamp};
+use massa_time::MassaTime;
+use std::sync::{mpsc, Arc};
+use std::thread::JoinHandle;
+use tracing::{debug, info};
+
+/// Background job scheduler configuration
+#[derive(Debug, Clone)]
+pub struct JobSchedulerConfig {
+    /// thread count
+    pub thread_count: u8,
+    /// period duration
+    pub t0: MassaTime,
+    /// genesis timestamp
+    pub genesis_timestamp: MassaTime,
+    /// time kept free of heavy jobs before a production or endorsement slot of the node
+    pub margin_before: MassaTime,
+    /// time kept free of heavy jobs after a production or endorsement slot of the node
+    pub margin_after: MassaTime,
+    /// maximum time a job waits for a quiet window before running anyway
+    pub max_delay: MassaTime,
+}
+
+/// Provides the current staking addresses of the node
+pub type StakingAddresses = Arc<dyn Fn() -> PreHashSet<Address> + Send + Sync>;
+
+/// Schedules the heavy background jobs away from the production slots of the node, cheap to clone
+#[derive(Clone)]
+pub struct JobScheduler {
+    config: JobSchedulerConfig,
+    selector: Box<dyn SelectorController>,
+    staking_addresses: StakingAddresses,
+}
+
+impl std::fmt::Debug for JobScheduler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JobScheduler")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl JobScheduler {
+    /// Creates a scheduler avoiding the slots where `staking_addresses` produce or endorse
+    pub fn new(
+        config: JobSchedulerConfig,
+        selector: Box<dyn SelectorController>,
+        staking_addresses: StakingAddresses,
+    ) -> Self {
+        JobScheduler {
+            config,
+            selector,
+            staking_addresses,
+        }
+    }
+
+    /// Whether no production or endorsement slot of the node is within the margins of now
+    pub fn is_quiet(&self) -> bool {
+        let Ok(now) = MassaTime::now() else {
+            return true;
+        };
+        self.next_local_slot_time(
+            now.saturating_sub(self.config.margin_after),
+            now.saturating_add(self.config.margin_before),
+        )
+        .is_none()
+    }
+
+    /// Time of the first slot between `start` and `end` where the node produces or endorses.
+    /// Slots whose draws are not available yet are considered free.
+    fn next_local_slot_time(&self, start: MassaTime, end: MassaTime) -> Option<MassaTime> {
+        let addresses = (self.staking_addresses)();
+        if addresses.is_empty() {
+            return None;
+        }
+        let JobSchedulerConfig {
+            thread_count,
+            t0,
+            genesis_timestamp,
+            ..
+        } = self.config;
+        let mut slot =
+            match get_latest_block_slot_at_timestamp(thread_count, t0, genesis_timestamp, start) {
+                Ok(Some(slot)) => slot,
+                Ok(None) => Slot::new(0, 0),
+                Err(_) => return None,
+            };
+        loop {
+            let time = get_block_slot_timestamp(thread_count, t0, genesis_timestamp, slot).ok()?;
+            if time > end {
+                return None;
+            }
+            if time >= start {
+                if let Ok(selection) = self.selector.get_selection(slot) {
+                    if addresses.contains(&selection.producer)
+                        || selection
+                            .endorsements
+                            .iter()
+                            .any(|address| addresses.contains(address))
+                    {
+                        return Some(time);
+                    }
+                }
+            }
+            slot = slot.get_next_slot(thread_count).ok()?;
+        }
+    }
+
+    /// Waits for a quiet window, at most `max_delay`.
+    /// Returns `false` if `stop_rx` is signaled or disconnected while waiting.
+    pub fn wait_quiet(&self, stop_rx: &mpsc::Receiver<()>) -> bool {
+        let deadline = MassaTime::now()
+            .unwrap_or_else(|_| MassaTime::from_millis(0))
+            .saturating_add(self.config.max_delay);
+        // check again every slot
+        let step = self
+            .config
+            .t0
+            .checked_div_u64(self.config.thread_count as u64)
+            .unwrap_or(self.config.t0);
+        loop {
+            let now = MassaTime::now().unwrap_or(deadline);
+            if now >= deadline {
+                debug!(
+                    "no quiet window within {}, running anyway",
+                    self.config.max_delay
+                );
+                return true;
+            }
+            if self.is_quiet() {
+                return true;
+            }
+            match stop_rx.recv_timeout(step.min(deadline.saturating_sub(now)).to_duration()) {
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
+                _ => return false,
+            }
+        }
+    }
+
+    /// Runs `job` every `interval` in a thread named `name`, in the quiet windows of the node
+    pub fn spawn_periodic<F>(&self, name: &str, interval: MassaTime, mut job: F) -> PeriodicJob
+    where
+        F: FnMut() + Send + 'static,
+    {
+        let scheduler = self.clone();
+        let (stop_tx, stop_rx) = mpsc::channel::<()>();
+        let job_name = name.to_string();
+        let thread_handle = std::thread::Builder::new()
+            .name(name.into())
+            .spawn(move || {
+                while let Err(mpsc::RecvTimeoutError::Timeout) =
+                    stop_rx.recv_timeout(interval.to_duration())
+                {
+                    if !scheduler.wait_quiet(&stop_rx) {
+                        break;
+                    }
+                    let start = MassaTime::now().unwrap_or_else(|_| MassaTime::from_millis(0));
+                    job();
+                    info!(
+                        "background job {} done in {}",
+                        job_name,
+                        MassaTime::now().unwrap_or(start).saturating_sub(start)
+                    );
+                }
+            })
+            .expect("failed to spawn background job thread");
+        PeriodicJob {
+            stop_tx: Some(stop_tx),
+            thread_handle: Some(thread_handle),
+        }
+    }
+}
+
+/// Thread running a periodic background job. Stops when dropped, after the current run.
+pub struct PeriodicJob {
+    stop_tx: Option<mpsc::Sender<()>>,
+    thread_handle: Option<JoinHandle<()>>,
+}
+
+impl Drop for PeriodicJob {
+    fn drop(&mut self) {
+        // disconnecting the channel stops the thread
+        self.stop_tx = None;
+        if let Some(handle) = self.thread_handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
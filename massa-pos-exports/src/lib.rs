@@ -12,6 +12,7 @@ mod controller_traits;
 mod cycle_info;
 mod deferred_credits;
 mod error;
+mod job_scheduler;
 mod pos_changes;
 mod pos_final_state;
 mod settings;
@@ -21,6 +22,7 @@ pub use controller_traits::{Selection, SelectorController, SelectorManager};
 pub use cycle_info::*;
 pub use deferred_credits::*;
 pub use error::*;
+pub use job_scheduler::{JobScheduler, JobSchedulerConfig, PeriodicJob, StakingAddresses};
 pub use pos_changes::*;
 pub use pos_final_state::*;
 pub use settings::SelectorConfig;
@@ -6,14 +6,28 @@ use massa_models::error::ModelsError;
 use massa_models::streaming_step::StreamingStep;
 use massa_models::{address::Address, amount::Amount, prehash::PreHashMap, slot::Slot};
 use massa_serialization::{Serializer, U64VarIntSerializer};
+use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, BTreeSet},
     ops::Bound::{Excluded, Unbounded},
     path::PathBuf,
 };
 use tracing::debug;
 
+/// Ranges of the PoS data that can still be queried, bounds included
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PoSHistoryRanges {
+    /// cycles whose roll counts are known, archived cycles included
+    pub cycles: Option<(u64, u64)>,
+    /// cycles of the cycle history used by consensus
+    pub cycle_history: Option<(u64, u64)>,
+    /// slots of the pending deferred credits
+    pub deferred_credits: Option<(Slot, Slot)>,
+    /// slots of the executed deferred credits still kept
+    pub executed_credits: Option<(Slot, Slot)>,
+}
+
 /// Final state of PoS
 pub struct PoSFinalState {
     /// proof-of-stake configuration
@@ -22,6 +36,11 @@ pub struct PoSFinalState {
     pub cycle_history: VecDeque<CycleInfo>,
     /// coins to be credited at the end of the slot
     pub deferred_credits: DeferredCredits,
+    /// complete cycles that left `cycle_history`, back = newest.
+    /// Node-local: they are neither hashed nor bootstrapped.
+    pub archived_cycles: VecDeque<CycleInfo>,
+    /// deferred credits already credited, kept for queries. Node-local like `archived_cycles`.
+    pub executed_credits: BTreeMap<Slot, PreHashMap<Address, Amount>>,
     /// selector controller
     pub selector: Box<dyn SelectorController>,
     /// initial rolls, used for negative cycle look back
@@ -48,20 +67,38 @@ impl PoSFinalState {
             })?,
         )
         .map_err(|err| PosError::RollsFileLoadingError(format!("error opening file: {}", err)))?;
+        Ok(Self::with_initial_rolls(
+            config,
+            initial_seed_string,
+            initial_rolls,
+            selector,
+            initial_ledger_hash,
+        ))
+    }
 
+    /// create a new `PoSFinalState` with the given initial rolls
+    pub fn with_initial_rolls(
+        config: PoSConfig,
+        initial_seed_string: &str,
+        initial_rolls: BTreeMap<Address, u64>,
+        selector: Box<dyn SelectorController>,
+        initial_ledger_hash: Hash,
+    ) -> Self {
         // Seeds used as the initial seeds for negative cycles (-2 and -1 respectively)
         let init_seed = Hash::compute_from(initial_seed_string.as_bytes());
         let initial_seeds = vec![Hash::compute_from(init_seed.to_bytes()), init_seed];
 
-        Ok(Self {
+        Self {
             config,
             cycle_history: Default::default(),
             deferred_credits: DeferredCredits::default(),
+            archived_cycles: Default::default(),
+            executed_credits: Default::default(),
             selector,
             initial_rolls,
             initial_seeds,
             initial_ledger_hash,
-        })
+        }
     }
 
     /// Reset the state of the PoS final state
@@ -70,6 +107,8 @@ impl PoSFinalState {
     pub fn reset(&mut self) {
         self.cycle_history.clear();
         self.deferred_credits = DeferredCredits::default();
+        self.archived_cycles.clear();
+        self.executed_credits.clear();
     }
 
     /// Create the initial cycle based off the initial rolls.
@@ -146,17 +185,20 @@ impl PoSFinalState {
     /// if cycle C is absent from `self.cycle_history`:
     ///     `push` a new empty `CycleInfo` at the back of `self.cycle_history` and set its cycle = C
     ///     `pop_front` from `cycle_history` until front() represents cycle C-4 or later (not C-3 because we might need older endorsement draws on the limit between 2 cycles)
+    ///         the popped cycles are archived, at most `cycle_info_retention` of them being kept
     /// for the cycle C entry of `cycle_history`:
     ///     extend `seed_bits` with `changes.seed_bits`
     ///     extend `roll_counts` with `changes.roll_changes`
     ///         delete all entries from `roll_counts` for which the roll count is zero
     ///     add each element of `changes.production_stats` to the cycle's `production_stats`
     /// for each `changes.deferred_credits` targeting cycle Ct:
+    ///     keep the credits set to 0 by their execution in `executed_credits`
     ///     overwrite `self.deferred_credits` entries of cycle Ct in `cycle_history` with the ones from change
     ///         remove entries for which Amount = 0
     /// if slot S was the last of cycle C:
     ///     set complete=true for cycle C in the history
     ///     compute the seed hash and notifies the `PoSDrawer` for cycle `C+3`
+    /// drop the executed credits older than `deferred_credits_retention` periods
     ///
     pub fn apply_changes(
         &mut self,
@@ -183,15 +225,13 @@ impl PoSFinalState {
                 // extend the last incomplete cycle
             } else if info.cycle.checked_add(1) == Some(cycle) && info.complete {
                 // the previous cycle is complete, push a new incomplete/empty one to extend
-                self.cycle_history.push_back(CycleInfo::new_with_hash(
-                    cycle,
-                    false,
-                    info.roll_counts.clone(),
-                    BitVec::with_capacity(slots_per_cycle),
-                    PreHashMap::default(),
-                ));
+                // the rolls of the new cycle start as the final rolls of the previous one
+                self.cycle_history
+                    .push_back(info.new_next_cycle(cycle, BitVec::with_capacity(slots_per_cycle)));
                 while self.cycle_history.len() > self.config.cycle_history_length {
-                    self.cycle_history.pop_front();
+                    if let Some(archived) = self.cycle_history.pop_front() {
+                        self.archived_cycles.push_back(archived);
+                    }
                 }
             } else {
                 return Err(PosError::OverflowError(
@@ -218,12 +258,37 @@ impl PoSFinalState {
             self.config.thread_count,
         );
 
+        // keep the credits zeroed by their execution
+        if self.config.deferred_credits_retention > 0 {
+            for (credit_slot, credits) in &changes.deferred_credits.credits {
+                for (address, amount) in credits {
+                    if !amount.is_zero() {
+                        continue;
+                    }
+                    if let Some(executed) = self
+                        .deferred_credits
+                        .credits
+                        .get(credit_slot)
+                        .and_then(|current| current.get(address))
+                        .filter(|executed| !executed.is_zero())
+                    {
+                        self.executed_credits
+                            .entry(*credit_slot)
+                            .or_default()
+                            .insert(*address, *executed);
+                    }
+                }
+            }
+        }
+
         // extent deferred_credits with changes.deferred_credits
         // remove zero-valued credits
         self.deferred_credits
             .final_nested_extend(changes.deferred_credits);
         self.deferred_credits.remove_zeros();
 
+        self.prune_history(slot);
+
         // feed the cycle if it is complete
         // notify the PoSDrawer about the newly ready draw data
         // to draw cycle + 2, we use the rng data from cycle - 1 and the seed from cycle
@@ -244,10 +309,65 @@ impl PoSFinalState {
         }
     }
 
+    /// Drops the archived cycles and executed credits that exceed the retention settings
+    fn prune_history(&mut self, slot: Slot) {
+        while self.archived_cycles.len() > self.config.cycle_info_retention {
+            self.archived_cycles.pop_front();
+        }
+        if let Some(min_period) = slot
+            .period
+            .checked_sub(self.config.deferred_credits_retention)
+        {
+            self.executed_credits = self.executed_credits.split_off(&Slot::new(min_period, 0));
+        }
+    }
+
+    /// Gets the ranges of the cycles and deferred credits that can still be queried
+    pub fn get_history_ranges(&self) -> PoSHistoryRanges {
+        let first_cycle = self
+            .archived_cycles
+            .front()
+            .or_else(|| self.cycle_history.front())
+            .map(|info| info.cycle);
+        let last_cycle = self.cycle_history.back().map(|info| info.cycle);
+        PoSHistoryRanges {
+            cycles: first_cycle.zip(last_cycle),
+            cycle_history: self
+                .cycle_history
+                .front()
+                .zip(self.cycle_history.back())
+                .map(|(first, last)| (first.cycle, last.cycle)),
+            deferred_credits: self
+                .deferred_credits
+                .credits
+                .keys()
+                .next()
+                .zip(self.deferred_credits.credits.keys().next_back())
+                .map(|(first, last)| (*first, *last)),
+            executed_credits: self
+                .executed_credits
+                .keys()
+                .next()
+                .zip(self.executed_credits.keys().next_back())
+                .map(|(first, last)| (*first, *last)),
+        }
+    }
+
     /// Feeds the selector targeting a given draw cycle
     fn feed_selector(&self, draw_cycle: u64) -> PosResult<()> {
+        let (lookback_rolls, lookback_state_hash) = self.get_lookback_rolls(draw_cycle)?;
+        let lookback_seed = self.get_lookback_seed(draw_cycle, lookback_state_hash)?;
+
+        // feed selector
+        self.selector
+            .as_ref()
+            .feed_cycle(draw_cycle, lookback_rolls.clone(), lookback_seed)
+    }
+
+    /// Gets the roll counts and the final state hash snapshot looked back by a given draw cycle
+    fn get_lookback_rolls(&self, draw_cycle: u64) -> PosResult<(&BTreeMap<Address, u64>, Hash)> {
         // get roll lookback
-        let (lookback_rolls, lookback_state_hash) = match draw_cycle.checked_sub(3) {
+        match draw_cycle.checked_sub(3) {
             // looking back in history
             Some(c) => {
                 let index = self
@@ -263,14 +383,17 @@ impl PoSFinalState {
                 let state_hash = cycle_info
                     .final_state_hash_snapshot
                     .expect("critical: a complete cycle must contain a final state hash snapshot");
-                (cycle_info.roll_counts.clone(), state_hash)
+                Ok((&cycle_info.roll_counts, state_hash))
             }
             // looking back to negative cycles
-            None => (self.initial_rolls.clone(), self.initial_ledger_hash),
-        };
+            None => Ok((&self.initial_rolls, self.initial_ledger_hash)),
+        }
+    }
 
+    /// Computes the seed of the draws of a given cycle
+    fn get_lookback_seed(&self, draw_cycle: u64, lookback_state_hash: Hash) -> PosResult<Hash> {
         // get seed lookback
-        let lookback_seed = match draw_cycle.checked_sub(2) {
+        match draw_cycle.checked_sub(2) {
             // looking back in history
             Some(c) => {
                 let index = self
@@ -285,16 +408,40 @@ impl PoSFinalState {
                 u64_ser.serialize(&c, &mut seed).unwrap();
                 seed.extend(cycle_info.rng_seed.clone().into_vec());
                 seed.extend(lookback_state_hash.to_bytes());
-                Hash::compute_from(&seed)
+                Ok(Hash::compute_from(&seed))
             }
             // looking back to negative cycles
-            None => self.initial_seeds[draw_cycle as usize],
-        };
+            None => Ok(self.initial_seeds[draw_cycle as usize]),
+        }
+    }
 
-        // feed selector
-        self.selector
-            .as_ref()
-            .feed_cycle(draw_cycle, lookback_rolls, lookback_seed)
+    /// Computes the random beacon of a final slot.
+    ///
+    /// The beacon is the hash of the draw seed of the cycle of the slot and of the seed bits
+    /// produced by the slots of that cycle up to `slot` included:
+    /// it is only known once `slot` is final, and anyone can recompute it from the final state.
+    pub fn get_slot_beacon(&self, slot: Slot) -> PosResult<Hash> {
+        let cycle = slot.get_cycle(self.config.periods_per_cycle);
+        let index = self
+            .get_cycle_index(cycle)
+            .ok_or(PosError::CycleUnavailable(cycle))?;
+        let cycle_info = &self.cycle_history[index];
+
+        // one seed bit is pushed per final slot of the cycle
+        let bit_count = ((slot.period % self.config.periods_per_cycle)
+            * self.config.thread_count as u64
+            + slot.thread as u64
+            + 1) as usize;
+        if cycle_info.rng_seed.len() < bit_count {
+            return Err(PosError::SlotNotFinal(slot));
+        }
+
+        let (_, lookback_state_hash) = self.get_lookback_rolls(cycle)?;
+        let draw_seed = self.get_lookback_seed(cycle, lookback_state_hash)?;
+        let mut beacon = draw_seed.to_bytes().to_vec();
+        beacon.extend(slot.to_bytes_key());
+        beacon.extend(BitVec::from_bitslice(&cycle_info.rng_seed[..bit_count]).into_vec());
+        Ok(Hash::compute_from(&beacon))
     }
 
     /// Feeds the selector targeting a given draw cycle
@@ -315,6 +462,17 @@ impl PoSFinalState {
             .unwrap_or_default()
     }
 
+    /// Whether one of `addresses` has rolls at the latest cycle, or in the initial rolls before the first one
+    pub fn has_rolls_among(&self, addresses: &BTreeSet<Address>) -> bool {
+        let roll_counts = self
+            .cycle_history
+            .back()
+            .map_or(&self.initial_rolls, |info| &info.roll_counts);
+        addresses
+            .iter()
+            .any(|address| roll_counts.get(address).map_or(false, |rolls| *rolls > 0))
+    }
+
     /// Retrieves the amount of rolls a given address has at a given cycle
     pub fn get_address_active_rolls(&self, addr: &Address, cycle: u64) -> Option<u64> {
         // get lookback cycle index
@@ -334,6 +492,43 @@ impl PoSFinalState {
         }
     }
 
+    /// Retrieves a page of the stakers taken into account by the selector at a given cycle,
+    /// with at least `min_rolls` active rolls, sorted by decreasing roll count.
+    ///
+    /// # Returns
+    /// The `(address, active rolls)` pairs of the page, and the total number of matching stakers
+    pub fn get_cycle_stakers(
+        &self,
+        cycle: u64,
+        min_rolls: u64,
+        offset: usize,
+        limit: usize,
+    ) -> (Vec<(Address, u64)>, usize) {
+        let roll_counts = match cycle.checked_sub(3) {
+            Some(lookback_cycle) => match self.get_cycle_info(lookback_cycle) {
+                Some(info) => &info.roll_counts,
+                None => return (Vec::new(), 0),
+            },
+            None => &self.initial_rolls,
+        };
+        let mut stakers: Vec<(&Address, &u64)> = roll_counts
+            .iter()
+            .filter(|(_, rolls)| **rolls >= min_rolls)
+            .collect();
+        let total_count = stakers.len();
+        // the address breaks ties so that pages are stable
+        stakers.sort_unstable_by(|(addr_a, rolls_a), (addr_b, rolls_b)| {
+            rolls_b.cmp(rolls_a).then_with(|| addr_a.cmp(addr_b))
+        });
+        let page = stakers
+            .into_iter()
+            .skip(offset)
+            .take(limit)
+            .map(|(addr, rolls)| (*addr, *rolls))
+            .collect();
+        (page, total_count)
+    }
+
     /// Retrieves every deferred credit of the given slot
     pub fn get_deferred_credits_at(&self, slot: &Slot) -> PreHashMap<Address, Amount> {
         self.deferred_credits
@@ -343,6 +538,30 @@ impl PoSFinalState {
             .unwrap_or_default()
     }
 
+    /// Retrieves the non-zero deferred credits of an address, starting from `min_slot` (included)
+    pub fn get_address_deferred_credits(
+        &self,
+        address: &Address,
+        min_slot: Slot,
+    ) -> BTreeMap<Slot, Amount> {
+        self.deferred_credits
+            .credits
+            .range(min_slot..)
+            .filter_map(|(slot, credits)| match credits.get(address) {
+                Some(amount) if !amount.is_zero() => Some((*slot, *amount)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Retrieves the executed deferred credits of an address that are still kept
+    pub fn get_address_executed_credits(&self, address: &Address) -> BTreeMap<Slot, Amount> {
+        self.executed_credits
+            .iter()
+            .filter_map(|(slot, credits)| credits.get(address).map(|amount| (*slot, *amount)))
+            .collect()
+    }
+
     /// Retrieves the productions statistics for all addresses on a given cycle
     pub fn get_all_production_stats(
         &self,
@@ -371,6 +590,18 @@ impl PoSFinalState {
         Some(index)
     }
 
+    /// Gets a cycle from the history or, if it already left it, from the archived cycles
+    pub fn get_cycle_info(&self, cycle: u64) -> Option<&CycleInfo> {
+        match self.get_cycle_index(cycle) {
+            Some(index) => Some(&self.cycle_history[index]),
+            None => {
+                let first_cycle = self.archived_cycles.front()?.cycle;
+                let index: usize = cycle.checked_sub(first_cycle)?.try_into().ok()?;
+                self.archived_cycles.get(index)
+            }
+        }
+    }
+
     /// Gets a cycle of the Proof of Stake `cycle_history`. Used only in the bootstrap process.
     ///
     /// # Arguments:
@@ -1,5 +1,6 @@
 use massa_models::address::Address;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
 
 /// Configuration of selector thread
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -16,4 +17,6 @@ pub struct SelectorConfig {
     pub genesis_address: Address,
     /// communication channel length
     pub channel_size: usize,
+    /// staking addresses the draws are restricted to in a private network, `None` on a public network
+    pub permissioned_validators: Option<BTreeSet<Address>>,
 }
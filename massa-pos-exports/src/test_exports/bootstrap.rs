@@ -18,6 +18,7 @@ impl Default for SelectorConfig {
             periods_per_cycle: PERIODS_PER_CYCLE,
             genesis_address: Address::from_public_key(&KeyPair::generate().get_public_key()),
             channel_size: CHANNEL_SIZE,
+            permissioned_validators: None,
         }
     }
 }
@@ -60,6 +60,11 @@ pub enum MockSelectorControllerMessage {
         /// Receiver to send the result to
         response_tx: mpsc::Sender<PosResult<Selection>>,
     },
+    /// Get the latest drawn cycle
+    GetLastDrawnCycle {
+        /// Receiver to send the result to
+        response_tx: mpsc::Sender<PosResult<Option<u64>>>,
+    },
     /// Wait for draws
     WaitForDraws {
         /// Cycle to wait for
@@ -132,6 +137,15 @@ impl SelectorController for MockSelectorController {
         response_rx.recv().unwrap()
     }
 
+    fn get_last_drawn_cycle(&self) -> PosResult<Option<u64>> {
+        let (response_tx, response_rx) = mpsc::channel();
+        self.0
+            .lock()
+            .send(MockSelectorControllerMessage::GetLastDrawnCycle { response_tx })
+            .unwrap();
+        response_rx.recv().unwrap()
+    }
+
     fn get_address_selections(
         &self,
         address: &Address,
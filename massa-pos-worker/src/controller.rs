@@ -52,6 +52,15 @@ impl SelectorController for SelectorControllerImpl {
         }
     }
 
+    /// Get the latest cycle for which draws are available, without waiting.
+    /// Returns `None` if no draws were computed yet.
+    fn get_last_drawn_cycle(&self) -> PosResult<Option<u64>> {
+        let (_cache_cv, cache_lock) = &*self.cache;
+        let cache_guard = cache_lock.read();
+        let cache = cache_guard.as_ref().map_err(|err| err.clone())?;
+        Ok(cache.0.back().map(|cd| cd.cycle))
+    }
+
     /// Feed cycle to the selector
     ///
     /// # Arguments
@@ -188,11 +197,10 @@ impl SelectorManager for SelectorManagerImpl {
         let _ = self.input_mpsc.send(Command::Stop);
         // join the selector thread
         if let Some(join_handle) = self.thread_handle.take() {
-            if let Err(err) = join_handle
-                .join()
-                .expect("selector thread panicked on try to join")
-            {
-                warn!("{}", err);
+            match join_handle.join() {
+                Ok(Err(err)) => warn!("{}", err),
+                Err(err) => warn!("selector thread panicked: {:?}", err),
+                Ok(Ok(())) => {}
             }
         }
         info!("selector worker stopped");
@@ -17,18 +17,30 @@ use tracing::debug;
 /// * `lookback_rolls`: Roll counts at look back (`cycle-3`)
 /// * `lookback_seed`: RNG seed at look back (`cycle-2`)
 ///
+/// On a private network, only the rolls of the permissioned validators are drawn.
+///
 /// # Result
 /// - The draws can throw the errors of the function `get_params` and from the
 ///   creation of a `Xoshiro256PlusPlus`.
-/// - An inconsistency error is thrown if nobody has rolls
+/// - An inconsistency error is thrown if nobody (or no permissioned validator) has rolls
 ///
 /// Otherwise, the draws return an empty success.
 pub(crate) fn perform_draws(
     cfg: &SelectorConfig,
     cycle: u64,
-    lookback_rolls: BTreeMap<Address, u64>,
+    mut lookback_rolls: BTreeMap<Address, u64>,
     lookback_seed: Hash,
 ) -> PosResult<CycleDraws> {
+    if let Some(validators) = &cfg.permissioned_validators {
+        lookback_rolls.retain(|address, rolls| *rolls > 0 && validators.contains(address));
+        if lookback_rolls.is_empty() {
+            return Err(PosError::InvalidRollDistribution(format!(
+                "no permissioned validator has rolls at the lookback of cycle {}",
+                cycle
+            )));
+        }
+    }
+
     // get seeded RNG
     let mut rng = Xoshiro256PlusPlus::from_seed(*lookback_seed.to_bytes());
 
@@ -101,3 +113,50 @@ pub(crate) fn perform_draws(
 
     Ok(cycle_draws)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_signature::KeyPair;
+    use std::collections::BTreeSet;
+
+    fn random_address() -> Address {
+        Address::from_public_key(&KeyPair::generate().get_public_key())
+    }
+
+    #[test]
+    fn test_permissioned_draws() {
+        let validators: BTreeSet<Address> = (0..2).map(|_| random_address()).collect();
+        // the other stakers hold most of the rolls but must never be drawn
+        let mut rolls: BTreeMap<Address, u64> =
+            validators.iter().map(|address| (*address, 1)).collect();
+        for _ in 0..3 {
+            rolls.insert(random_address(), 1000);
+        }
+        let seed = Hash::compute_from("seed".as_bytes());
+
+        let cfg = SelectorConfig {
+            permissioned_validators: Some(validators.clone()),
+            ..Default::default()
+        };
+        let cycle_draws = perform_draws(&cfg, 1, rolls.clone(), seed).unwrap();
+        assert!(!cycle_draws.draws.is_empty());
+        for selection in cycle_draws.draws.values() {
+            assert!(validators.contains(&selection.producer));
+            assert!(selection
+                .endorsements
+                .iter()
+                .all(|address| validators.contains(address)));
+        }
+
+        // no permissioned validator with rolls: nothing can be drawn
+        let cfg = SelectorConfig {
+            permissioned_validators: Some(BTreeSet::from([random_address()])),
+            ..Default::default()
+        };
+        assert!(matches!(
+            perform_draws(&cfg, 1, rolls, seed),
+            Err(PosError::InvalidRollDistribution(_))
+        ));
+    }
+}
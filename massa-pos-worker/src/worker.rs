@@ -142,6 +142,12 @@ impl SelectorThread {
 pub fn start_selector_worker(
     selector_config: SelectorConfig,
 ) -> PosResult<(Box<dyn SelectorManager>, Box<dyn SelectorController>)> {
+    if matches!(&selector_config.permissioned_validators, Some(validators) if validators.is_empty())
+    {
+        return Err(PosError::InvalidConfig(
+            "the set of permissioned validators is empty, no slot could be drawn".into(),
+        ));
+    }
     let (input_sender, input_receiver) = sync_channel(selector_config.channel_size);
     let cache = Arc::new((
         RwLockCondvar::default(),
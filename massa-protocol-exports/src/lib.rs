@@ -7,16 +7,18 @@
 #![warn(unused_crate_dependencies)]
 mod channels;
 mod error;
+mod peer_stats;
 mod protocol_controller;
 mod settings;
 
 pub use channels::{ProtocolReceivers, ProtocolSenders};
 pub use error::ProtocolError;
+pub use peer_stats::{MessageCounters, PeerAnomaly, PeerMessageStats, PeerMessageType};
 pub use protocol_controller::{
     BlocksResults, ProtocolCommand, ProtocolCommandSender, ProtocolManagementCommand,
     ProtocolManager,
 };
-pub use settings::ProtocolConfig;
+pub use settings::{AnnouncementOverflowPolicy, BlockPropagationStrategy, ProtocolConfig};
 
 /// TODO: Add only if test. Removed the configuration test because don't work if running cargo test on an other sub-crate.
 pub mod tests;
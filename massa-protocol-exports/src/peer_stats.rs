@@ -0,0 +1,59 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Accounting of the messages received from each connected peer
+
+use massa_models::node::NodeId;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Type of a protocol message received from a peer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PeerMessageType {
+    /// block header propagated by the peer
+    BlockHeader,
+    /// reply to a block information request
+    BlockInfo,
+    /// block information request
+    AskForBlocks,
+    /// full operations
+    Operations,
+    /// endorsements
+    Endorsements,
+    /// operation id announcements
+    OperationAnnouncements,
+    /// operation request
+    AskForOperations,
+    /// BLS signatures of endorsers
+    EndorsementSignatures,
+}
+
+/// Counters of the messages of one type received from a peer
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct MessageCounters {
+    /// number of received messages
+    pub count: u64,
+    /// approximate size in bytes of the received messages: serialized contents and ids
+    pub bytes: u64,
+    /// number of received messages that were invalid or that we did not ask for
+    pub invalid_count: u64,
+}
+
+/// Anomaly detected in the messages of a peer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PeerAnomaly {
+    /// more invalid messages of a type than allowed in one minute
+    TooManyInvalidMessages(PeerMessageType),
+}
+
+/// Message statistics of a connected peer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeerMessageStats {
+    /// peer node id
+    pub node_id: NodeId,
+    /// message counters by message type
+    pub messages: BTreeMap<PeerMessageType, MessageCounters>,
+    /// anomalies detected so far
+    pub anomalies: Vec<PeerAnomaly>,
+}
@@ -1,17 +1,22 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 use crate::error::ProtocolError;
+use crate::PeerMessageStats;
+use massa_channel::{MassaSender, Priority};
 use massa_logging::massa_trace;
 
 use massa_models::prehash::{PreHashMap, PreHashSet};
 use massa_models::{
     block_header::SecuredHeader, block_id::BlockId, endorsement::EndorsementId,
-    operation::OperationId,
+    endorsement_signature::EndorsementSignature, operation::OperationId,
 };
 use massa_network_exports::NetworkEventReceiver;
 use massa_storage::Storage;
 use serde::Serialize;
-use tokio::{sync::mpsc, task::JoinHandle};
+use tokio::{
+    sync::{mpsc, oneshot},
+    task::JoinHandle,
+};
 use tracing::info;
 
 /// block result: map block id to
@@ -49,6 +54,26 @@ pub enum ProtocolCommand {
     PropagateOperations(Storage),
     /// Propagate endorsements
     PropagateEndorsements(Storage),
+    /// Propagate BLS signatures of endorsers to the nodes supporting them
+    PropagateEndorsementSignatures(Vec<EndorsementSignature>),
+    /// Get the message statistics of the connected peers
+    GetPeerMessageStats(oneshot::Sender<Vec<PeerMessageStats>>),
+}
+
+impl ProtocolCommand {
+    /// Priority of the command in the channel of the protocol worker:
+    /// the block related commands go before the propagation of operations and endorsements
+    pub fn priority(&self) -> Priority {
+        match self {
+            ProtocolCommand::IntegratedBlock { .. }
+            | ProtocolCommand::AttackBlockDetected(_)
+            | ProtocolCommand::WishlistDelta { .. } => Priority::High,
+            ProtocolCommand::PropagateOperations(_)
+            | ProtocolCommand::PropagateEndorsements(_)
+            | ProtocolCommand::PropagateEndorsementSignatures(_)
+            | ProtocolCommand::GetPeerMessageStats(_) => Priority::Normal,
+        }
+    }
 }
 
 /// protocol management commands
@@ -57,7 +82,7 @@ pub enum ProtocolManagementCommand {}
 
 /// protocol command sender
 #[derive(Clone)]
-pub struct ProtocolCommandSender(pub mpsc::Sender<ProtocolCommand>);
+pub struct ProtocolCommandSender(pub MassaSender<ProtocolCommand>);
 
 impl ProtocolCommandSender {
     /// Sends the order to propagate the header of a block
@@ -129,6 +154,39 @@ impl ProtocolCommandSender {
                 ProtocolError::ChannelError("propagate_endorsements command send error".into())
             })
     }
+
+    /// propagate BLS signatures of endorsers to connected nodes supporting them
+    pub fn propagate_endorsement_signatures(
+        &mut self,
+        signatures: Vec<EndorsementSignature>,
+    ) -> Result<(), ProtocolError> {
+        massa_trace!(
+            "protocol.command_sender.propagate_endorsement_signatures",
+            { "signatures": signatures }
+        );
+        self.0
+            .blocking_send(ProtocolCommand::PropagateEndorsementSignatures(signatures))
+            .map_err(|_| {
+                ProtocolError::ChannelError(
+                    "propagate_endorsement_signatures command send error".into(),
+                )
+            })
+    }
+
+    /// Get the message statistics of the connected peers
+    pub async fn get_peer_message_stats(&self) -> Result<Vec<PeerMessageStats>, ProtocolError> {
+        massa_trace!("protocol.command_sender.get_peer_message_stats", {});
+        let (response_tx, response_rx) = oneshot::channel();
+        self.0
+            .send(ProtocolCommand::GetPeerMessageStats(response_tx))
+            .await
+            .map_err(|_| {
+                ProtocolError::ChannelError("get_peer_message_stats command send error".into())
+            })?;
+        response_rx.await.map_err(|_| {
+            ProtocolError::ChannelError("get_peer_message_stats response read error".into())
+        })
+    }
 }
 
 /// protocol manager used to stop the protocol
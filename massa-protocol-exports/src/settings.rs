@@ -2,6 +2,30 @@
 
 use massa_time::MassaTime;
 use serde::Deserialize;
+/// Policy applied when the operation announcement buffer overflows
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AnnouncementOverflowPolicy {
+    /// announce the whole buffer immediately
+    ImmediateFlush,
+    /// keep the operations with the highest fees until the next interval, and do not announce the others
+    DropLowestFee,
+    /// keep the buffered operations until the next interval, do not announce the new ones,
+    /// and stop asking the peers for the operations they announce until the buffer is flushed
+    Backpressure,
+}
+
+/// How the headers of new blocks are propagated to the peers
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockPropagationStrategy {
+    /// announce the header once the full block has been integrated into the graph
+    AfterIntegration,
+    /// relay the header to all the peers as soon as it is received and checked,
+    /// the peers then fetch the full block on demand
+    HeadersFirst,
+}
+
 /// Dynamic protocol configuration mix in static settings and constants configurations.
 #[derive(Debug, Deserialize, Clone, Copy)]
 pub struct ProtocolConfig {
@@ -25,14 +49,20 @@ pub struct ProtocolConfig {
     pub max_node_known_endorsements_size: usize,
     /// we ask for the same block `max_simultaneous_ask_blocks_per_node` times at the same time
     pub max_simultaneous_ask_blocks_per_node: usize,
+    /// How the headers of new blocks are propagated
+    pub block_propagation_strategy: BlockPropagationStrategy,
+    /// minimum number of missing operations for the operations of a block to be asked as erasure-coded chunks
+    /// to several nodes at once, 0 to disable
+    pub erasure_coding_min_operations: usize,
     /// Max wait time for sending a Network or Node event.
     pub max_send_wait: MassaTime,
     /// Maximum number of batches in the memory buffer.
     /// Dismiss the new batches if overflow
     pub operation_batch_buffer_capacity: usize,
     /// Maximum number of operations in the announcement buffer.
-    /// Immediately announce if overflow.
     pub operation_announcement_buffer_capacity: usize,
+    /// What to do when the announcement buffer overflows
+    pub operation_announcement_overflow_policy: AnnouncementOverflowPolicy,
     /// Start processing batches in the buffer each `operation_batch_proc_period` in millisecond
     pub operation_batch_proc_period: MassaTime,
     /// All operations asked are prune each `operation_asked_pruning_period` millisecond
@@ -57,4 +87,12 @@ pub struct ProtocolConfig {
     pub max_operations_propagation_time: MassaTime,
     /// max time we propagate endorsements
     pub max_endorsements_propagation_time: MassaTime,
+    /// maximum number of signatures verified in a single batch
+    pub max_signature_batch_size: usize,
+    /// maximum number of gossiped batches of operations or endorsements waiting for signature verification
+    pub max_pending_signature_verifications: usize,
+    /// number of signature verification workers, objects are dispatched to them by slot thread
+    pub signature_verification_shards: usize,
+    /// maximum number of invalid messages of a type a peer can send per minute before being banned
+    pub max_invalid_messages_per_minute: u64,
 }
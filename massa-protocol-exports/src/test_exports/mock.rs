@@ -1,20 +1,22 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 use crate::{ProtocolCommand, ProtocolCommandSender};
+use massa_channel::{channel, ChannelConfig, MassaReceiver};
 use massa_models::block_id::BlockId;
 use massa_time::MassaTime;
-use tokio::{sync::mpsc, time::sleep};
+use tokio::time::sleep;
 
 /// Mock of the protocol
 /// TODO: Improve doc
 pub struct MockProtocolController {
-    protocol_command_rx: mpsc::Receiver<ProtocolCommand>,
+    protocol_command_rx: MassaReceiver<ProtocolCommand>,
 }
 
 impl MockProtocolController {
     /// Creates a new protocol mock
     pub fn new() -> (Self, ProtocolCommandSender) {
-        let (protocol_command_tx, protocol_command_rx) = mpsc::channel::<ProtocolCommand>(256);
+        let (protocol_command_tx, protocol_command_rx) =
+            channel::<ProtocolCommand>("protocol_command", ChannelConfig::new(256));
         (
             MockProtocolController {
                 protocol_command_rx,
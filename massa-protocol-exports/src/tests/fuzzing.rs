@@ -0,0 +1,378 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Generation of arbitrary-but-structured protocol messages.
+//!
+//! Messages are built from a seeded random generator so that a failing run can be replayed.
+//! Most generated objects are well-formed and correctly signed, a fraction of them is
+//! tampered with (stale signature, wrong creator, out of range values) to exercise the
+//! rejection paths of the protocol worker.
+
+use super::tools::NodeInfo;
+use massa_hash::Hash;
+use massa_models::{
+    address::Address,
+    amount::Amount,
+    block_header::{BlockHeader, BlockHeaderSerializer, SecuredHeader},
+    block_id::BlockId,
+    endorsement::{Endorsement, EndorsementSerializerLW, SecureShareEndorsement},
+    node::NodeId,
+    operation::{Operation, OperationId, OperationSerializer, OperationType, SecureShareOperation},
+    secure_share::SecureShareContent,
+    slot::Slot,
+};
+use massa_network_exports::{AskForBlocksInfo, BlockInfoReply, NetworkEvent};
+use massa_signature::{KeyPair, SECRET_KEY_BYTES_SIZE};
+use rand::{prelude::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+/// Probability for a generated object to be tampered with after signature
+const TAMPER_PROBABILITY: f64 = 0.2;
+
+/// Maximum number of items in a generated list
+const MAX_LIST_LENGTH: usize = 8;
+
+/// Generator of structured protocol messages
+pub struct ProtocolMessageGenerator {
+    rng: StdRng,
+    thread_count: u8,
+    endorsement_count: u32,
+    /// nodes used as message sources
+    nodes: Vec<NodeInfo>,
+    /// keys used to sign generated objects
+    keypairs: Vec<KeyPair>,
+    /// ids of the blocks generated so far, reused as parents and in block queries
+    known_block_ids: Vec<BlockId>,
+    /// ids of the operations generated so far, reused in announcements and queries
+    known_operation_ids: Vec<OperationId>,
+}
+
+impl ProtocolMessageGenerator {
+    /// Creates a generator from a seed: two generators created with the same arguments
+    /// produce the same sequence of messages
+    pub fn new(seed: u64, thread_count: u8, endorsement_count: u32, node_count: usize) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let nodes = (0..node_count.max(1))
+            .map(|_| {
+                let keypair = keypair_from_rng(&mut rng);
+                let id = NodeId::new(keypair.get_public_key());
+                NodeInfo { keypair, id }
+            })
+            .collect();
+        let keypairs = (0..4).map(|_| keypair_from_rng(&mut rng)).collect();
+        let known_block_ids = (0..thread_count)
+            .map(|thread| BlockId(Hash::compute_from(format!("Genesis {}", thread).as_bytes())))
+            .collect();
+        ProtocolMessageGenerator {
+            rng,
+            thread_count,
+            endorsement_count,
+            nodes,
+            keypairs,
+            known_block_ids,
+            known_operation_ids: Vec::new(),
+        }
+    }
+
+    /// Nodes used as sources of the generated events
+    pub fn nodes(&self) -> &[NodeInfo] {
+        &self.nodes
+    }
+
+    /// Generates a random network event, as it would be received by the protocol worker
+    pub fn generate_event(&mut self) -> NetworkEvent {
+        let node = self.pick_node();
+        match self.rng.gen_range(0..9) {
+            0 => NetworkEvent::NewConnection(node),
+            1 => NetworkEvent::ConnectionClosed(node),
+            2 => NetworkEvent::ReceivedBlockInfo {
+                node,
+                info: self.generate_block_info(),
+            },
+            3 => NetworkEvent::ReceivedBlockHeader {
+                source_node_id: node,
+                header: self.generate_header(),
+            },
+            4 => NetworkEvent::AskedForBlocks {
+                node,
+                list: self.generate_ask_for_blocks(),
+            },
+            5 => NetworkEvent::ReceivedOperations {
+                node,
+                operations: self.generate_operations(),
+            },
+            6 => NetworkEvent::ReceivedOperationAnnouncements {
+                node,
+                operation_prefix_ids: self
+                    .pick_operation_ids()
+                    .into_iter()
+                    .map(|id| id.into_prefix())
+                    .collect(),
+            },
+            7 => NetworkEvent::ReceiveAskForOperations {
+                node,
+                operation_prefix_ids: self
+                    .pick_operation_ids()
+                    .into_iter()
+                    .map(|id| id.into_prefix())
+                    .collect(),
+            },
+            _ => NetworkEvent::ReceivedEndorsements {
+                node,
+                endorsements: self.generate_endorsements(),
+            },
+        }
+    }
+
+    /// Generates a signed block header, possibly tampered with
+    pub fn generate_header(&mut self) -> SecuredHeader {
+        let slot = self.generate_slot();
+        let parents = if self.rng.gen_bool(0.9) {
+            (0..self.thread_count)
+                .map(|_| self.pick_block_id())
+                .collect()
+        } else {
+            (0..self.rng.gen_range(0..MAX_LIST_LENGTH))
+                .map(|_| self.pick_block_id())
+                .collect()
+        };
+        let endorsements = (0..self.rng.gen_range(0..=self.endorsement_count))
+            .map(|_| self.generate_endorsement())
+            .collect();
+        let keypair = self.pick_keypair();
+        let mut header = BlockHeader::new_verifiable(
+            BlockHeader {
+                slot,
+                parents,
+                operation_merkle_root: Hash::compute_from(&self.rng.gen::<[u8; 32]>()),
+                announced_version: 0,
+                endorsements,
+                aggregated_endorsements: None,
+            },
+            BlockHeaderSerializer::new(),
+            &keypair,
+        )
+        .expect("could not sign generated header");
+        if self.should_tamper() {
+            match self.rng.gen_range(0..2) {
+                0 => header.content.slot = self.generate_slot(),
+                _ => header.content_creator_pub_key = self.pick_keypair().get_public_key(),
+            }
+        }
+        self.known_block_ids.push(header.id);
+        header
+    }
+
+    /// Generates a list of signed endorsements, possibly tampered with
+    pub fn generate_endorsements(&mut self) -> Vec<SecureShareEndorsement> {
+        (0..self.rng.gen_range(0..MAX_LIST_LENGTH))
+            .map(|_| self.generate_endorsement())
+            .collect()
+    }
+
+    /// Generates a list of signed operations, possibly tampered with
+    pub fn generate_operations(&mut self) -> Vec<SecureShareOperation> {
+        (0..self.rng.gen_range(0..MAX_LIST_LENGTH))
+            .map(|_| self.generate_operation())
+            .collect()
+    }
+
+    /// Generates a list of block queries
+    pub fn generate_ask_for_blocks(&mut self) -> Vec<(BlockId, AskForBlocksInfo)> {
+        (0..self.rng.gen_range(0..MAX_LIST_LENGTH))
+            .map(|_| {
+                let info = match self.rng.gen_range(0..3) {
+                    0 => AskForBlocksInfo::Header,
+                    1 => AskForBlocksInfo::Info,
+                    _ => AskForBlocksInfo::Operations(self.pick_operation_ids()),
+                };
+                (self.pick_block_id(), info)
+            })
+            .collect()
+    }
+
+    /// Generates a list of answers to block queries
+    pub fn generate_block_info(&mut self) -> Vec<(BlockId, BlockInfoReply)> {
+        (0..self.rng.gen_range(0..MAX_LIST_LENGTH))
+            .map(|_| match self.rng.gen_range(0..4) {
+                0 => {
+                    let header = self.generate_header();
+                    (header.id, BlockInfoReply::Header(header))
+                }
+                1 => (
+                    self.pick_block_id(),
+                    BlockInfoReply::Info(self.pick_operation_ids()),
+                ),
+                2 => (
+                    self.pick_block_id(),
+                    BlockInfoReply::Operations(self.generate_operations()),
+                ),
+                _ => (self.pick_block_id(), BlockInfoReply::NotFound),
+            })
+            .collect()
+    }
+
+    /// Returns a mutated copy of serialized data: bit flips, truncation, byte insertion
+    /// or duplication of a chunk
+    pub fn mutate_bytes(&mut self, data: &[u8]) -> Vec<u8> {
+        let mut mutated = data.to_vec();
+        for _ in 0..self.rng.gen_range(1..4) {
+            if mutated.is_empty() {
+                mutated.push(self.rng.gen());
+                continue;
+            }
+            let idx = self.rng.gen_range(0..mutated.len());
+            match self.rng.gen_range(0..4) {
+                0 => mutated[idx] ^= 1 << self.rng.gen_range(0..8),
+                1 => mutated.truncate(idx),
+                2 => mutated.insert(idx, self.rng.gen()),
+                _ => {
+                    let end = self.rng.gen_range(idx..=mutated.len());
+                    let chunk = mutated[idx..end].to_vec();
+                    let tail = mutated.split_off(end);
+                    mutated.extend(chunk);
+                    mutated.extend(tail);
+                }
+            }
+        }
+        mutated
+    }
+
+    fn generate_endorsement(&mut self) -> SecureShareEndorsement {
+        let keypair = self.pick_keypair();
+        let mut endorsement = Endorsement::new_verifiable(
+            Endorsement {
+                slot: self.generate_slot(),
+                index: self
+                    .rng
+                    .gen_range(0..self.endorsement_count.saturating_add(2)),
+                endorsed_block: self.pick_block_id(),
+            },
+            EndorsementSerializerLW::new(),
+            &keypair,
+        )
+        .expect("could not sign generated endorsement");
+        if self.should_tamper() {
+            match self.rng.gen_range(0..2) {
+                0 => endorsement.content.index = self.rng.gen(),
+                _ => endorsement.content_creator_pub_key = self.pick_keypair().get_public_key(),
+            }
+        }
+        endorsement
+    }
+
+    fn generate_operation(&mut self) -> SecureShareOperation {
+        let op = match self.rng.gen_range(0..5) {
+            0 => OperationType::Transaction {
+                recipient_address: Address::from_public_key(&self.pick_keypair().get_public_key()),
+                amount: Amount::from_raw(self.rng.gen()),
+                memo: None,
+            },
+            1 => OperationType::RollBuy {
+                roll_count: self.rng.gen(),
+            },
+            2 => OperationType::RollSell {
+                roll_count: self.rng.gen(),
+            },
+            3 => OperationType::ExecuteSC {
+                data: self.generate_bytes(),
+                max_gas: self.rng.gen(),
+                datastore: (0..self.rng.gen_range(0..MAX_LIST_LENGTH))
+                    .map(|_| (self.generate_bytes(), self.generate_bytes()))
+                    .collect(),
+            },
+            _ => OperationType::CallSC {
+                target_addr: Address::from_public_key(&self.pick_keypair().get_public_key()),
+                target_func: String::from_utf8_lossy(&self.generate_bytes()).into_owned(),
+                param: self.generate_bytes(),
+                max_gas: self.rng.gen(),
+                coins: Amount::from_raw(self.rng.gen()),
+            },
+        };
+        let keypair = self.pick_keypair();
+        let mut operation = Operation::new_verifiable(
+            Operation {
+                fee: Amount::from_raw(self.rng.gen()),
+                op,
+                expire_period: self.rng.gen_range(0..1000),
+                sequence: None,
+                sponsor: None,
+            },
+            OperationSerializer::new(),
+            &keypair,
+        )
+        .expect("could not sign generated operation");
+        if self.should_tamper() {
+            match self.rng.gen_range(0..2) {
+                0 => operation.content.expire_period = self.rng.gen(),
+                _ => operation.content_creator_pub_key = self.pick_keypair().get_public_key(),
+            }
+        }
+        self.known_operation_ids.push(operation.id);
+        operation
+    }
+
+    fn generate_slot(&mut self) -> Slot {
+        // mostly valid slots close to genesis, sometimes an out of range thread
+        let thread = if self.rng.gen_bool(0.95) {
+            self.rng.gen_range(0..self.thread_count)
+        } else {
+            self.rng.gen()
+        };
+        Slot::new(self.rng.gen_range(0..100), thread)
+    }
+
+    fn generate_bytes(&mut self) -> Vec<u8> {
+        (0..self.rng.gen_range(0..64))
+            .map(|_| self.rng.gen())
+            .collect()
+    }
+
+    fn should_tamper(&mut self) -> bool {
+        self.rng.gen_bool(TAMPER_PROBABILITY)
+    }
+
+    fn pick_node(&mut self) -> NodeId {
+        self.nodes
+            .choose(&mut self.rng)
+            .expect("generator has no node")
+            .id
+    }
+
+    fn pick_keypair(&mut self) -> KeyPair {
+        self.keypairs
+            .choose(&mut self.rng)
+            .expect("generator has no keypair")
+            .clone()
+    }
+
+    fn pick_block_id(&mut self) -> BlockId {
+        if self.known_block_ids.is_empty() || self.rng.gen_bool(0.1) {
+            return BlockId(Hash::compute_from(&self.rng.gen::<[u8; 32]>()));
+        }
+        *self
+            .known_block_ids
+            .choose(&mut self.rng)
+            .expect("no known block id")
+    }
+
+    fn pick_operation_ids(&mut self) -> Vec<OperationId> {
+        (0..self.rng.gen_range(0..MAX_LIST_LENGTH))
+            .map(|_| {
+                if self.known_operation_ids.is_empty() || self.rng.gen_bool(0.1) {
+                    OperationId::from_bytes(&self.rng.gen())
+                } else {
+                    *self
+                        .known_operation_ids
+                        .choose(&mut self.rng)
+                        .expect("no known operation id")
+                }
+            })
+            .collect()
+    }
+}
+
+/// Derives a keypair from the generator randomness
+fn keypair_from_rng(rng: &mut StdRng) -> KeyPair {
+    let mut bytes = [0u8; SECRET_KEY_BYTES_SIZE];
+    rng.fill(&mut bytes);
+    KeyPair::from_bytes(&bytes).expect("could not create keypair from random bytes")
+}
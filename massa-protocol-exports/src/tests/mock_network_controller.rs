@@ -1,5 +1,6 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use massa_channel::{channel, ChannelConfig, MassaReceiver, MassaSender};
 use massa_models::{
     block_header::SecuredHeader, block_id::BlockId, endorsement::SecureShareEndorsement,
 };
@@ -13,20 +14,21 @@ use massa_network_exports::{
     NetworkEventReceiver,
 };
 use massa_time::MassaTime;
-use tokio::{sync::mpsc, time::sleep};
+use tokio::time::sleep;
 
 /// mock network controller
 pub struct MockNetworkController {
-    network_command_rx: mpsc::Receiver<NetworkCommand>,
-    network_event_tx: mpsc::Sender<NetworkEvent>,
+    network_command_rx: MassaReceiver<NetworkCommand>,
+    network_event_tx: MassaSender<NetworkEvent>,
 }
 
 impl MockNetworkController {
     /// new mock network controller
     pub fn new() -> (Self, NetworkCommandSender, NetworkEventReceiver) {
         let (network_command_tx, network_command_rx) =
-            mpsc::channel::<NetworkCommand>(CHANNEL_SIZE);
-        let (network_event_tx, network_event_rx) = mpsc::channel::<NetworkEvent>(CHANNEL_SIZE);
+            channel::<NetworkCommand>("network_command", ChannelConfig::new(CHANNEL_SIZE));
+        let (network_event_tx, network_event_rx) =
+            channel::<NetworkEvent>("network_event", ChannelConfig::new(CHANNEL_SIZE));
         (
             MockNetworkController {
                 network_event_tx,
@@ -55,6 +57,23 @@ impl MockNetworkController {
         }
     }
 
+    /// drain the commands sent by protocol so far, returns the number of drained commands
+    pub fn drain_commands(&mut self) -> usize {
+        let mut count = 0;
+        while self.network_command_rx.try_recv().is_ok() {
+            count += 1;
+        }
+        count
+    }
+
+    /// send an arbitrary network event
+    pub async fn send_event(&mut self, event: NetworkEvent) {
+        self.network_event_tx
+            .send(event)
+            .await
+            .expect("Couldn't send event to protocol.");
+    }
+
     /// new connection
     pub async fn new_connection(&mut self, new_node_id: NodeId) {
         self.network_event_tx
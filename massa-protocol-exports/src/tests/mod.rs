@@ -5,3 +5,6 @@ pub mod mock_network_controller;
 
 /// test utilities
 pub mod tools;
+
+/// structured message generation for fuzzing
+pub mod fuzzing;
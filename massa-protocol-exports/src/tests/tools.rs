@@ -1,7 +1,7 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 use super::mock_network_controller::MockNetworkController;
-use crate::ProtocolConfig;
+use crate::{AnnouncementOverflowPolicy, BlockPropagationStrategy, ProtocolConfig};
 use massa_hash::Hash;
 use massa_models::node::NodeId;
 use massa_models::operation::OperationSerializer;
@@ -66,7 +66,9 @@ pub fn create_block(keypair: &KeyPair) -> SecureShareBlock {
                 BlockId(Hash::compute_from("Genesis 1".as_bytes())),
             ],
             operation_merkle_root: Hash::compute_from(&Vec::new()),
+            announced_version: 0,
             endorsements: Vec::new(),
+            aggregated_endorsements: None,
         },
         BlockHeaderSerializer::new(),
         keypair,
@@ -107,7 +109,9 @@ pub fn create_block_with_operations(
                 BlockId(Hash::compute_from("Genesis 1".as_bytes())),
             ],
             operation_merkle_root,
+            announced_version: 0,
             endorsements: Vec::new(),
+            aggregated_endorsements: None,
         },
         BlockHeaderSerializer::new(),
         keypair,
@@ -144,7 +148,9 @@ pub fn create_block_with_endorsements(
                 BlockId(Hash::compute_from("Genesis 1".as_bytes())),
             ],
             operation_merkle_root: Hash::compute_from(&Vec::new()),
+            announced_version: 0,
             endorsements,
+            aggregated_endorsements: None,
         },
         BlockHeaderSerializer::new(),
         keypair,
@@ -185,11 +191,14 @@ pub fn create_operation_with_expire_period(
     let op = OperationType::Transaction {
         recipient_address: Address::from_public_key(&recv_keypair.get_public_key()),
         amount: Amount::default(),
+        memo: None,
     };
     let content = Operation {
         fee: Amount::default(),
         op,
         expire_period,
+        sequence: None,
+        sponsor: None,
     };
     Operation::new_verifiable(content, OperationSerializer::new(), keypair).unwrap()
 }
@@ -207,6 +216,8 @@ pub fn create_protocol_config() -> ProtocolConfig {
         max_node_known_blocks_size: 100,
         max_node_wanted_blocks_size: 100,
         max_simultaneous_ask_blocks_per_node: 10,
+        block_propagation_strategy: BlockPropagationStrategy::AfterIntegration,
+        erasure_coding_min_operations: 0,
         max_send_wait: MassaTime::from_millis(100),
         max_known_ops_size: 1000,
         max_node_known_ops_size: 1000,
@@ -214,6 +225,7 @@ pub fn create_protocol_config() -> ProtocolConfig {
         max_node_known_endorsements_size: 1000,
         operation_batch_buffer_capacity: 1000,
         operation_announcement_buffer_capacity: 1000,
+        operation_announcement_overflow_policy: AnnouncementOverflowPolicy::ImmediateFlush,
         operation_batch_proc_period: 200.into(),
         asked_operations_pruning_period: 500.into(),
         operation_announcement_interval: 150.into(),
@@ -227,6 +239,10 @@ pub fn create_protocol_config() -> ProtocolConfig {
         t0: MassaTime::from_millis(16000),
         max_operations_propagation_time: MassaTime::from_millis(30000),
         max_endorsements_propagation_time: MassaTime::from_millis(60000),
+        max_signature_batch_size: 256,
+        max_pending_signature_verifications: 1024,
+        signature_verification_shards: 2,
+        max_invalid_messages_per_minute: 10,
     }
 }
 
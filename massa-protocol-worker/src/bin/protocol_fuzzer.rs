@@ -0,0 +1,108 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Protocol fuzzing harness.
+//!
+//! Starts a protocol worker wired to mocked network, consensus and pool components,
+//! and feeds it with generated network events.
+//!
+//! Usage: `cargo run -p massa_protocol_worker --features testing --bin protocol_fuzzer -- [SEED] [ITERATIONS]`
+//!
+//! The seed is printed at startup so that a failing run can be replayed.
+
+use massa_channel::{channel_with_priority, ChannelConfig};
+use massa_consensus_exports::test_exports::MockConsensusController;
+use massa_models::config::{CapacityScale, ENDORSEMENT_COUNT};
+use massa_pool_exports::test_exports::MockPoolController;
+use massa_protocol_exports::{
+    tests::{
+        fuzzing::ProtocolMessageGenerator, mock_network_controller::MockNetworkController,
+        tools::create_protocol_config,
+    },
+    ProtocolCommand, ProtocolReceivers, ProtocolSenders,
+};
+use massa_protocol_worker::start_protocol_controller;
+use massa_storage::Storage;
+use massa_time::MassaTime;
+
+/// Number of generated events when not specified
+const DEFAULT_ITERATIONS: u64 = 10_000;
+
+/// Number of nodes used as message sources
+const NODE_COUNT: usize = 10;
+
+#[tokio::main]
+async fn main() {
+    let mut args = std::env::args().skip(1);
+    let seed = match args.next() {
+        Some(seed) => seed.parse::<u64>().expect("invalid seed"),
+        None => MassaTime::now()
+            .expect("could not get current time")
+            .to_millis(),
+    };
+    let iterations = match args.next() {
+        Some(iterations) => iterations.parse::<u64>().expect("invalid iteration count"),
+        None => DEFAULT_ITERATIONS,
+    };
+    println!("protocol fuzzer: seed {}, {} iterations", seed, iterations);
+
+    let protocol_config = create_protocol_config();
+    let (mut network_controller, network_command_sender, network_event_receiver) =
+        MockNetworkController::new();
+    let (pool_controller, pool_event_receiver) = MockPoolController::new_with_receiver();
+    let (consensus_controller, consensus_event_receiver) =
+        MockConsensusController::new_with_receiver();
+    let (_protocol_command_sender, protocol_command_receiver) = channel_with_priority(
+        "protocol_command",
+        ChannelConfig::new(protocol_config.controller_channel_size),
+        ProtocolCommand::priority,
+    );
+    let protocol_manager = start_protocol_controller(
+        protocol_config,
+        ProtocolReceivers {
+            network_event_receiver,
+            protocol_command_receiver,
+        },
+        ProtocolSenders {
+            network_command_sender,
+        },
+        consensus_controller,
+        pool_controller,
+        Storage::create_root(),
+        CapacityScale::default(),
+    )
+    .await
+    .expect("could not start protocol controller");
+
+    let mut generator = ProtocolMessageGenerator::new(
+        seed,
+        protocol_config.thread_count,
+        ENDORSEMENT_COUNT,
+        NODE_COUNT,
+    );
+    let node_ids: Vec<_> = generator.nodes().iter().map(|node| node.id).collect();
+    for node_id in node_ids {
+        network_controller.new_connection(node_id).await;
+    }
+
+    let (mut network_commands, mut consensus_events, mut pool_events) = (0, 0, 0);
+    for _ in 0..iterations {
+        network_controller
+            .send_event(generator.generate_event())
+            .await;
+        network_commands += network_controller.drain_commands();
+        consensus_events += consensus_event_receiver.0.try_iter().count();
+        pool_events += pool_event_receiver.0.try_iter().count();
+    }
+
+    if let Err(err) = protocol_manager.stop().await {
+        eprintln!(
+            "protocol fuzzer: protocol stopped with an error (seed {}): {}",
+            seed, err
+        );
+        std::process::exit(1);
+    }
+    println!(
+        "protocol fuzzer: done, protocol emitted {} network commands, {} consensus events and {} pool events",
+        network_commands, consensus_events, pool_events
+    );
+}
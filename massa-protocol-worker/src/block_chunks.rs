@@ -0,0 +1,282 @@
+//! Erasure-coded distribution of the block operations
+//!
+//! Copyright (c) 2022 MASSA LABS <info@massa.net>
+//!
+//! When many operations of a wanted block are missing, they are asked as erasure-coded chunks
+//! to several nodes at once instead of asking all of them to a single node.
+//! Any `BLOCK_ERASURE_DATA_CHUNKS` of the `BLOCK_ERASURE_TOTAL_CHUNKS` chunks are enough to rebuild
+//! the serialized operations, so a slow node does not delay the reception of the block.
+//! Only the nodes that negotiated `Capabilities::ERASURE_CODED_BLOCKS` are asked for chunks.
+
+use crate::protocol_worker::{BlockInfo, ProtocolWorker};
+use massa_logging::massa_trace;
+use massa_models::{
+    block_id::BlockId,
+    config::{
+        BLOCK_ERASURE_DATA_CHUNKS, BLOCK_ERASURE_TOTAL_CHUNKS, MAX_DATASTORE_VALUE_LENGTH,
+        MAX_FUNCTION_NAME_LENGTH, MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+        MAX_OPERATION_DATASTORE_KEY_LENGTH, MAX_OPERATION_DATASTORE_VALUE_LENGTH,
+        MAX_PARAMETERS_SIZE,
+    },
+    erasure::ErasureCoder,
+    node::NodeId,
+    operation::{OperationId, OperationsDeserializer, OperationsSerializer, SecureShareOperation},
+    prehash::{CapacityAllocator, PreHashSet},
+    version::Capabilities,
+};
+use massa_network_exports::{AskForBlocksInfo, BlockChunk};
+use massa_protocol_exports::{PeerMessageType, ProtocolError};
+use massa_serialization::{DeserializeError, Deserializer, Serializer};
+use massa_storage::Storage;
+use std::collections::BTreeMap;
+use std::pin::Pin;
+use tokio::time::{Instant, Sleep};
+use tracing::warn;
+
+impl ProtocolWorker {
+    /// Returns the operations of the wanted block that are not in its storage yet
+    pub(crate) fn missing_operation_ids(block_info: &BlockInfo) -> Vec<OperationId> {
+        let already_stored_operations = block_info.storage.get_op_refs();
+        block_info
+            .operation_ids
+            .iter()
+            .flatten()
+            .filter(|id| !already_stored_operations.contains(id))
+            .copied()
+            .collect()
+    }
+
+    /// Returns what to ask a node for the missing operations of a wanted block:
+    /// the chunks that were not received yet if the block is large enough to be worth chunking.
+    pub(crate) fn operations_ask_info(&self, block_info: &BlockInfo) -> AskForBlocksInfo {
+        let missing_operations = Self::missing_operation_ids(block_info);
+        if self.config.erasure_coding_min_operations == 0
+            || missing_operations.len() < self.config.erasure_coding_min_operations
+            || block_info.chunks_failed
+        {
+            return AskForBlocksInfo::Operations(missing_operations);
+        }
+        AskForBlocksInfo::Chunks(
+            (0..BLOCK_ERASURE_TOTAL_CHUNKS)
+                .filter(|index| !block_info.chunks.contains_key(index))
+                .collect(),
+        )
+    }
+
+    /// Spread the chunks of a block over the capable candidate nodes, sorted best first.
+    /// Falls back to asking all the missing operations to the best node if less than two nodes can serve chunks.
+    pub(crate) fn plan_chunk_asks(
+        &self,
+        block_id: &BlockId,
+        indices: &[u16],
+        candidates: &[(NodeId, bool)],
+    ) -> Vec<(NodeId, AskForBlocksInfo)> {
+        let capable_nodes: Vec<NodeId> = candidates
+            .iter()
+            .filter(|(node_id, may_have_block)| {
+                *may_have_block
+                    && self.active_nodes.get(node_id).map_or(false, |node_info| {
+                        node_info
+                            .capabilities
+                            .contains(Capabilities::ERASURE_CODED_BLOCKS)
+                    })
+            })
+            .map(|(node_id, _)| *node_id)
+            .take(indices.len())
+            .collect();
+        if capable_nodes.len() < 2 {
+            let missing_operations = self
+                .block_wishlist
+                .get(block_id)
+                .map(Self::missing_operation_ids)
+                .unwrap_or_default();
+            return candidates
+                .first()
+                .map(|(node_id, _)| {
+                    vec![(*node_id, AskForBlocksInfo::Operations(missing_operations))]
+                })
+                .unwrap_or_default();
+        }
+        let mut asks: Vec<(NodeId, Vec<u16>)> = capable_nodes
+            .into_iter()
+            .map(|node_id| (node_id, Vec::new()))
+            .collect();
+        let node_count = asks.len();
+        for (i, index) in indices.iter().enumerate() {
+            asks[i % node_count].1.push(*index);
+        }
+        asks.into_iter()
+            .map(|(node_id, indices)| (node_id, AskForBlocksInfo::Chunks(indices)))
+            .collect()
+    }
+
+    /// Computes the requested chunks of the operations of a block.
+    /// Returns `None` if some of the operations are not in `storage`.
+    pub(crate) fn get_block_chunks(
+        storage: &Storage,
+        operation_ids: &[OperationId],
+        indices: &[u16],
+    ) -> Result<Option<Vec<BlockChunk>>, ProtocolError> {
+        let operations: Vec<SecureShareOperation> = {
+            let stored_operations = storage.read_operations();
+            let operations: Vec<SecureShareOperation> = operation_ids
+                .iter()
+                .filter_map(|id| stored_operations.get(id))
+                .cloned()
+                .collect();
+            if operations.len() != operation_ids.len() {
+                return Ok(None);
+            }
+            operations
+        };
+        let mut data = Vec::new();
+        OperationsSerializer::new()
+            .serialize(&operations, &mut data)
+            .map_err(|err| ProtocolError::GeneralProtocolError(err.to_string()))?;
+        let total_size: u32 = data.len().try_into().map_err(|_| {
+            ProtocolError::GeneralProtocolError("block operations too large to chunk".into())
+        })?;
+        let mut chunks: Vec<Option<Vec<u8>>> = ErasureCoder::new(
+            BLOCK_ERASURE_DATA_CHUNKS as usize,
+            BLOCK_ERASURE_TOTAL_CHUNKS as usize,
+        )?
+        .encode(&data)
+        .into_iter()
+        .map(Some)
+        .collect();
+        Ok(Some(
+            indices
+                .iter()
+                .filter_map(|index| {
+                    chunks
+                        .get_mut(*index as usize)
+                        .and_then(Option::take)
+                        .map(|data| BlockChunk {
+                            index: *index,
+                            total_size,
+                            data,
+                        })
+                })
+                .collect(),
+        ))
+    }
+
+    /// On chunks of the operations of a block received from a node.
+    ///
+    /// Once enough consistent chunks are received, the operations are rebuilt
+    /// and processed as if they had been received at once.
+    /// If the rebuilt operations do not match the block, the chunks are dropped
+    /// and the operations are asked again without erasure coding.
+    pub(crate) async fn on_block_chunks_received(
+        &mut self,
+        from_node_id: NodeId,
+        block_id: BlockId,
+        chunks: Vec<BlockChunk>,
+        op_timer: &mut Pin<&mut Sleep>,
+    ) -> Result<(), ProtocolError> {
+        if let Some(node_info) = self.active_nodes.get_mut(&from_node_id) {
+            node_info.asked_blocks.remove(&block_id);
+            node_info.insert_known_blocks(
+                &[block_id],
+                true,
+                Instant::now(),
+                self.config.max_node_known_blocks_size,
+            );
+        }
+        let data_chunks = BLOCK_ERASURE_DATA_CHUNKS as usize;
+        // the serialized operations are prefixed by their count
+        let max_total_size = self
+            .config
+            .max_serialized_operations_size_per_block
+            .saturating_add(std::mem::size_of::<u64>());
+        let info = match self.block_wishlist.get_mut(&block_id) {
+            // the block may already have been rebuilt from the chunks of other nodes
+            Some(info) if info.operation_ids.is_some() && !info.chunks_failed => info,
+            _ => {
+                massa_trace!("protocol.protocol_worker.on_block_chunks_received.unexpected", { "node": from_node_id, "block_id": block_id });
+                return Ok(());
+            }
+        };
+        let mut malformed = false;
+        for chunk in chunks {
+            let expected_len = std::cmp::max(
+                1,
+                (chunk.total_size as usize + data_chunks - 1) / data_chunks,
+            );
+            if chunk.total_size as usize > max_total_size || chunk.data.len() != expected_len {
+                malformed = true;
+                continue;
+            }
+            // chunks computed from different data cannot be combined: keep the first ones
+            if let Some(first) = info.chunks.values().next() && first.total_size != chunk.total_size {
+                warn!(
+                    "Node {} sent us a chunk of block id {} inconsistent with the chunks of other nodes.",
+                    from_node_id, block_id
+                );
+                continue;
+            }
+            info.chunks.entry(chunk.index).or_insert(chunk);
+        }
+        if malformed {
+            self.note_invalid_message(&from_node_id, PeerMessageType::BlockInfo)
+                .await;
+        }
+
+        let info = match self.block_wishlist.get_mut(&block_id) {
+            Some(info) if info.chunks.len() >= data_chunks => info,
+            _ => return Ok(()),
+        };
+        let chunks = std::mem::take(&mut info.chunks);
+        let operations = Self::decode_block_chunks(&chunks, self.config.max_operations_per_block)
+            .unwrap_or_default();
+        let expected_ids: PreHashSet<OperationId> =
+            info.operation_ids.iter().flatten().copied().collect();
+        let mut received_ids = PreHashSet::<OperationId>::with_capacity(operations.len());
+        received_ids.extend(operations.iter().map(|op| op.id));
+        if operations.len() != expected_ids.len() || received_ids != expected_ids {
+            warn!(
+                "Chunks received for block id {} do not rebuild its operations, asking them without erasure coding.",
+                block_id
+            );
+            info.chunks_failed = true;
+            let mut set = PreHashSet::<BlockId>::with_capacity(1);
+            set.insert(block_id);
+            return self.remove_asked_blocks_of_node(&set);
+        }
+        self.on_block_full_operations_received(from_node_id, block_id, operations, op_timer)
+            .await
+    }
+
+    /// Rebuild the operations of a block from its chunks, `None` if they are not valid
+    fn decode_block_chunks(
+        chunks: &BTreeMap<u16, BlockChunk>,
+        max_operations_per_block: u32,
+    ) -> Option<Vec<SecureShareOperation>> {
+        let total_size = chunks.values().next()?.total_size as usize;
+        let received: Vec<(usize, &[u8])> = chunks
+            .iter()
+            .map(|(index, chunk)| (*index as usize, chunk.data.as_slice()))
+            .collect();
+        let data = ErasureCoder::new(
+            BLOCK_ERASURE_DATA_CHUNKS as usize,
+            BLOCK_ERASURE_TOTAL_CHUNKS as usize,
+        )
+        .and_then(|coder| coder.decode(&received, total_size))
+        .ok()?;
+        let (rest, operations) = OperationsDeserializer::new(
+            max_operations_per_block,
+            MAX_DATASTORE_VALUE_LENGTH,
+            MAX_FUNCTION_NAME_LENGTH,
+            MAX_PARAMETERS_SIZE,
+            MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+            MAX_OPERATION_DATASTORE_KEY_LENGTH,
+            MAX_OPERATION_DATASTORE_VALUE_LENGTH,
+        )
+        .deserialize::<DeserializeError>(&data)
+        .ok()?;
+        if !rest.is_empty() {
+            return None;
+        }
+        Some(operations)
+    }
+}
@@ -213,6 +213,15 @@ impl<K: PreHashed + std::hash::Hash + Clone + Copy + PartialEq + Eq> LinearHashC
             self.try_insert(k);
         });
     }
+
+    /// Change the capacity, removing the oldest elements if the cache is over the new one
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.container.len() > self.capacity {
+            self.container.remove(&self.queue.pop_front().unwrap());
+        }
+        self.queue.shrink_to(capacity.saturating_add(1));
+    }
 }
 
 /// Structure holding a finite capacity cache map that deletes the oldest item when full.
@@ -286,6 +295,15 @@ impl<K: PreHashed + std::hash::Hash + Clone + Copy + PartialEq + Eq, V> LinearHa
         }
     }
 
+    /// Change the capacity, removing the oldest items if the cache is over the new one
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.container.len() > self.capacity {
+            self.container.remove(&self.queue.pop_front().unwrap());
+        }
+        self.queue.shrink_to(capacity.saturating_add(1));
+    }
+
     /// Extend with new elements
     pub fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
         iter.into_iter().for_each(|(k, v)| {
@@ -41,6 +41,12 @@ impl CheckedOperations {
         self.op_prefixes.contains(prefix)
     }
 
+    /// Change the capacity, forgetting the oldest operations if the cache is over the new one
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.op_ids.set_capacity(capacity);
+        self.op_prefixes.set_capacity(capacity);
+    }
+
     /// Extend with new IDs
     pub fn extend<I: IntoIterator<Item = OperationId>>(&mut self, iter: I) {
         iter.into_iter().for_each(|id| {
@@ -14,10 +14,13 @@
 pub mod protocol_worker;
 pub mod worker_operations_impl;
 pub use protocol_worker::start_protocol_controller;
+mod block_chunks;
 mod cache;
 mod checked_operations;
+mod message_stats;
 mod node_info;
 mod protocol_network;
+mod sig_verification_stage;
 mod sig_verifier;
 
 #[cfg(test)]
@@ -0,0 +1,204 @@
+//! Per-peer accounting of the received messages, and detection of abnormal message patterns.
+//!
+//! Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use massa_hash::HASH_SIZE_BYTES;
+use massa_models::{node::NodeId, operation::OPERATION_ID_PREFIX_SIZE_BYTES};
+use massa_network_exports::{BlockInfoReply, NetworkEvent};
+use massa_protocol_exports::{MessageCounters, PeerAnomaly, PeerMessageStats, PeerMessageType};
+use massa_signature::BLS_SIGNATURE_SIZE_BYTES;
+use std::collections::{BTreeMap, VecDeque};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Window over which the invalid messages of a peer are counted
+const INVALID_MESSAGES_WINDOW: Duration = Duration::from_secs(60);
+
+/// Message statistics of a connected peer
+#[derive(Debug, Clone, Default)]
+pub(crate) struct MessageStats {
+    /// message counters by message type
+    counters: BTreeMap<PeerMessageType, MessageCounters>,
+    /// reception instants of the invalid messages of the last minute, by message type
+    recent_invalid: BTreeMap<PeerMessageType, VecDeque<Instant>>,
+    /// anomalies detected so far
+    anomalies: Vec<PeerAnomaly>,
+}
+
+impl MessageStats {
+    /// Accounts for a received message
+    pub fn record(&mut self, message_type: PeerMessageType, bytes: u64) {
+        let counters = self.counters.entry(message_type).or_default();
+        counters.count = counters.count.saturating_add(1);
+        counters.bytes = counters.bytes.saturating_add(bytes);
+    }
+
+    /// Accounts for a received message that was invalid or that we did not ask for.
+    ///
+    /// Returns the anomaly raised if the peer sent more than `max_per_minute` such messages of that type in the last minute.
+    pub fn record_invalid(
+        &mut self,
+        message_type: PeerMessageType,
+        now: Instant,
+        max_per_minute: u64,
+    ) -> Option<PeerAnomaly> {
+        let counters = self.counters.entry(message_type).or_default();
+        counters.invalid_count = counters.invalid_count.saturating_add(1);
+
+        let recent = self.recent_invalid.entry(message_type).or_default();
+        recent.push_back(now);
+        while let Some(oldest) = recent.front() && now.saturating_duration_since(*oldest) > INVALID_MESSAGES_WINDOW {
+            recent.pop_front();
+        }
+        if recent.len() as u64 <= max_per_minute {
+            return None;
+        }
+        let anomaly = PeerAnomaly::TooManyInvalidMessages(message_type);
+        if !self.anomalies.contains(&anomaly) {
+            self.anomalies.push(anomaly);
+        }
+        Some(anomaly)
+    }
+
+    /// Exports the statistics of the peer `node_id`
+    pub fn export(&self, node_id: NodeId) -> PeerMessageStats {
+        PeerMessageStats {
+            node_id,
+            messages: self.counters.clone(),
+            anomalies: self.anomalies.clone(),
+        }
+    }
+}
+
+/// Returns the peer that sent a network event, the type of the message and its approximate size in bytes
+pub(crate) fn get_message_info(evt: &NetworkEvent) -> Option<(NodeId, PeerMessageType, u64)> {
+    let (node_id, message_type, bytes) = match evt {
+        NetworkEvent::NewConnection(_)
+        | NetworkEvent::ConnectionClosed(_)
+        | NetworkEvent::NodeCapabilities { .. } => return None,
+        NetworkEvent::ReceivedBlockHeader {
+            source_node_id,
+            header,
+        } => (
+            *source_node_id,
+            PeerMessageType::BlockHeader,
+            header.serialized_data.len(),
+        ),
+        NetworkEvent::ReceivedBlockInfo { node, info } => (
+            *node,
+            PeerMessageType::BlockInfo,
+            info.iter()
+                .map(|(_, reply)| {
+                    HASH_SIZE_BYTES
+                        + match reply {
+                            BlockInfoReply::Header(header) => header.serialized_data.len(),
+                            BlockInfoReply::Info(operation_ids) => {
+                                operation_ids.len() * HASH_SIZE_BYTES
+                            }
+                            BlockInfoReply::Operations(operations) => {
+                                operations.iter().map(|op| op.serialized_data.len()).sum()
+                            }
+                            BlockInfoReply::Chunks(chunks) => {
+                                chunks.iter().map(|chunk| chunk.data.len()).sum()
+                            }
+                            BlockInfoReply::NotFound => 0,
+                        }
+                })
+                .sum(),
+        ),
+        NetworkEvent::AskedForBlocks { node, list } => (
+            *node,
+            PeerMessageType::AskForBlocks,
+            list.len() * HASH_SIZE_BYTES,
+        ),
+        NetworkEvent::ReceivedOperations { node, operations } => (
+            *node,
+            PeerMessageType::Operations,
+            operations.iter().map(|op| op.serialized_data.len()).sum(),
+        ),
+        NetworkEvent::ReceivedEndorsements { node, endorsements } => (
+            *node,
+            PeerMessageType::Endorsements,
+            endorsements
+                .iter()
+                .map(|endorsement| endorsement.serialized_data.len())
+                .sum(),
+        ),
+        NetworkEvent::ReceivedOperationAnnouncements {
+            node,
+            operation_prefix_ids,
+        } => (
+            *node,
+            PeerMessageType::OperationAnnouncements,
+            operation_prefix_ids.len() * OPERATION_ID_PREFIX_SIZE_BYTES,
+        ),
+        NetworkEvent::ReceiveAskForOperations {
+            node,
+            operation_prefix_ids,
+        } => (
+            *node,
+            PeerMessageType::AskForOperations,
+            operation_prefix_ids.len() * OPERATION_ID_PREFIX_SIZE_BYTES,
+        ),
+        NetworkEvent::ReceivedEndorsementSignatures { node, signatures } => (
+            *node,
+            PeerMessageType::EndorsementSignatures,
+            signatures.len() * (HASH_SIZE_BYTES + BLS_SIGNATURE_SIZE_BYTES),
+        ),
+    };
+    Some((node_id, message_type, bytes as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invalid_messages_anomaly() {
+        let mut stats = MessageStats::default();
+        let start = Instant::now();
+        stats.record(PeerMessageType::BlockInfo, 100);
+        for _ in 0..3 {
+            assert_eq!(
+                stats.record_invalid(PeerMessageType::BlockInfo, start, 3),
+                None
+            );
+        }
+        // the invalid messages of another type are counted separately
+        assert_eq!(
+            stats.record_invalid(PeerMessageType::Operations, start, 3),
+            None
+        );
+        // the invalid messages older than a minute are forgotten
+        let later = start + INVALID_MESSAGES_WINDOW + Duration::from_secs(1);
+        assert_eq!(
+            stats.record_invalid(PeerMessageType::BlockInfo, later, 3),
+            None
+        );
+        for _ in 0..2 {
+            assert_eq!(
+                stats.record_invalid(PeerMessageType::BlockInfo, later, 3),
+                None
+            );
+        }
+        assert_eq!(
+            stats.record_invalid(PeerMessageType::BlockInfo, later, 3),
+            Some(PeerAnomaly::TooManyInvalidMessages(
+                PeerMessageType::BlockInfo
+            ))
+        );
+
+        let node_id = NodeId::new(massa_signature::KeyPair::generate().get_public_key());
+        let exported = stats.export(node_id);
+        let counters = exported.messages[&PeerMessageType::BlockInfo];
+        assert_eq!(counters.count, 1);
+        assert_eq!(counters.bytes, 100);
+        assert_eq!(counters.invalid_count, 7);
+        assert_eq!(
+            exported.anomalies,
+            vec![PeerAnomaly::TooManyInvalidMessages(
+                PeerMessageType::BlockInfo
+            )]
+        );
+    }
+}
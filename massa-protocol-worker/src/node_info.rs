@@ -6,13 +6,16 @@
 //! Same as for wanted/known blocks, we remember here in cache which node asked
 //! for operations and which operations he seem to already know.
 
+use massa_models::config::CapacityScale;
 use massa_models::operation::OperationPrefixId;
 use massa_models::prehash::{CapacityAllocator, PreHashMap};
+use massa_models::version::Capabilities;
 use massa_models::{block_id::BlockId, endorsement::EndorsementId};
 use massa_protocol_exports::ProtocolConfig;
 use tokio::time::Instant;
 
 use crate::cache::LinearHashCacheSet;
+use crate::message_stats::MessageStats;
 
 /// Information about a node we are connected to,
 /// essentially our view of its state.
@@ -23,12 +26,18 @@ pub(crate) struct NodeInfo {
     pub(crate) known_blocks: PreHashMap<BlockId, (bool, Instant)>,
     /// Blocks we asked that node for
     pub asked_blocks: PreHashMap<BlockId, Instant>,
+    /// Blocks whose operations we recently sent to that node
+    served_blocks: PreHashMap<BlockId, Instant>,
     /// Instant when the node was added
     pub connection_instant: Instant,
     /// all known operations (prefix-based)
     known_operations: LinearHashCacheSet<OperationPrefixId>,
     /// all known endorsements
     known_endorsements: LinearHashCacheSet<EndorsementId>,
+    /// statistics of the messages received from the node
+    pub(crate) message_stats: MessageStats,
+    /// optional features supported by both the node and us
+    pub(crate) capabilities: Capabilities,
 }
 
 impl NodeInfo {
@@ -37,14 +46,25 @@ impl NodeInfo {
         NodeInfo {
             known_blocks: PreHashMap::with_capacity(pool_settings.max_node_known_blocks_size),
             asked_blocks: Default::default(),
+            served_blocks: Default::default(),
             connection_instant: Instant::now(),
             known_operations: LinearHashCacheSet::new(pool_settings.max_node_known_ops_size),
             known_endorsements: LinearHashCacheSet::new(
                 pool_settings.max_node_known_endorsements_size,
             ),
+            message_stats: Default::default(),
+            capabilities: Capabilities::empty(),
         }
     }
 
+    /// Applies `capacity_scale` to the capacities of the caches of what the node knows
+    pub fn scale_caches(&mut self, config: &ProtocolConfig, capacity_scale: &CapacityScale) {
+        self.known_operations
+            .set_capacity(capacity_scale.apply(config.max_node_known_ops_size));
+        self.known_endorsements
+            .set_capacity(capacity_scale.apply(config.max_node_known_endorsements_size));
+    }
+
     /// Get boolean if block knows about the block and when this information was got
     /// in a option if we don't know if that node knows that block or not
     pub fn get_known_block(&self, block_id: &BlockId) -> Option<&(bool, Instant)> {
@@ -87,6 +107,23 @@ impl NodeInfo {
         self.remove_excess_known_blocks(max_node_known_blocks_size);
     }
 
+    /// Record that the operations of a block are sent to that node.
+    /// Returns `false` if they were already sent less than `dedup_window` ago, in which case they must not be sent again.
+    pub fn try_serve_block(
+        &mut self,
+        block_id: BlockId,
+        now: Instant,
+        dedup_window: std::time::Duration,
+    ) -> bool {
+        self.served_blocks
+            .retain(|_, served_at| now.saturating_duration_since(*served_at) < dedup_window);
+        if self.served_blocks.contains_key(&block_id) {
+            return false;
+        }
+        self.served_blocks.insert(block_id, now);
+        true
+    }
+
     pub fn insert_known_endorsements<I: IntoIterator<Item = EndorsementId>>(
         &mut self,
         endorsements: I,
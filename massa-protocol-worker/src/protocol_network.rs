@@ -4,6 +4,7 @@
 
 use std::collections::hash_map::Entry;
 
+use crate::message_stats::get_message_info;
 use crate::node_info::NodeInfo;
 use crate::protocol_worker::ProtocolWorker;
 use massa_hash::{Hash, HASH_SIZE_BYTES};
@@ -18,7 +19,7 @@ use massa_models::{
     secure_share::{Id, SecureShare},
 };
 use massa_network_exports::{AskForBlocksInfo, BlockInfoReply, NetworkEvent};
-use massa_protocol_exports::ProtocolError;
+use massa_protocol_exports::{BlockPropagationStrategy, PeerMessageType, ProtocolError};
 use massa_serialization::Serializer;
 use massa_storage::Storage;
 use std::pin::Pin;
@@ -33,6 +34,8 @@ static BLOCK_HEADER: &str = "protocol.protocol_worker.on_network_event.received_
 static BLOCKS_INFO: &str = "protocol.protocol_worker.on_network_event.received_blocks_info";
 static OPS: &str = "protocol.protocol_worker.on_network_event.received_operations";
 static ENDORSEMENTS: &str = "protocol.protocol_worker.on_network_event.received_endorsements";
+static ENDORSEMENT_SIGNATURES: &str =
+    "protocol.protocol_worker.on_network_event.received_endorsement_signatures";
 static OPS_BATCH: &str =
     "protocol.protocol_worker.on_network_event.received_operation_announcements";
 static ASKED_OPS: &str = "protocol.protocol_worker.on_network_event.receive_ask_for_operations";
@@ -50,14 +53,23 @@ impl ProtocolWorker {
         block_ask_timer: &mut Pin<&mut Sleep>,
         op_timer: &mut Pin<&mut Sleep>,
     ) -> Result<(), ProtocolError> {
+        if let Some((node_id, message_type, bytes)) = get_message_info(&evt) && let Some(node_info) = self.active_nodes.get_mut(&node_id) {
+            node_info.message_stats.record(message_type, bytes);
+        }
         match evt {
             NetworkEvent::NewConnection(node_id) => {
                 info!("Connected to node {}", node_id);
                 massa_trace!(NEW_CONN, { "node": node_id });
-                self.active_nodes
-                    .insert(node_id, NodeInfo::new(&self.config));
+                let mut node_info = NodeInfo::new(&self.config);
+                node_info.scale_caches(&self.config, &self.capacity_scale);
+                self.active_nodes.insert(node_id, node_info);
                 self.update_ask_block(block_ask_timer).await?;
             }
+            NetworkEvent::NodeCapabilities { node, capabilities } => {
+                if let Some(node_info) = self.active_nodes.get_mut(&node) {
+                    node_info.capabilities = capabilities;
+                }
+            }
             NetworkEvent::ConnectionClosed(node_id) => {
                 massa_trace!(CONN_CLOSED, { "node": node_id });
                 if self.active_nodes.remove(&node_id).is_some() {
@@ -99,8 +111,17 @@ impl ProtocolWorker {
                     self.note_header_from_node(&header, &source_node_id).await?
                 {
                     if is_new {
-                        self.consensus_controller
-                            .register_block_header(block_id, header);
+                        if self.config.block_propagation_strategy
+                            == BlockPropagationStrategy::HeadersFirst
+                        {
+                            self.relayed_headers.try_insert(block_id);
+                            self.propagate_block_header(block_id, &header).await?;
+                        }
+                        self.consensus_controller.register_block_header(
+                            block_id,
+                            header,
+                            source_node_id,
+                        );
                     }
                     self.update_ask_block(block_ask_timer).await?;
                 } else {
@@ -115,23 +136,16 @@ impl ProtocolWorker {
             }
             NetworkEvent::ReceivedOperations { node, operations } => {
                 massa_trace!(OPS, { "node": node, "operations": operations});
-                self.on_operations_received(node, operations, op_timer)
-                    .await;
+                self.on_operations_received(node, operations).await;
             }
             NetworkEvent::ReceivedEndorsements { node, endorsements } => {
                 massa_trace!(ENDORSEMENTS, { "node": node, "endorsements": endorsements});
-                if let Err(err) = self
-                    .note_endorsements_from_node(endorsements, &node, true)
-                    .await
-                {
-                    warn!(
-                        "node {} sent us critically incorrect endorsements, \
-                        which may be an attack attempt by the remote node or a \
-                        loss of sync between us and the remote node. Err = {}",
-                        node, err
-                    );
-                    let _ = self.ban_node(&node).await;
-                }
+                self.on_endorsements_received(node, endorsements);
+            }
+            NetworkEvent::ReceivedEndorsementSignatures { node, signatures } => {
+                massa_trace!(ENDORSEMENT_SIGNATURES, { "node": node, "signatures": signatures});
+                self.on_endorsement_signatures_received(node, signatures)
+                    .await;
             }
             NetworkEvent::ReceivedOperationAnnouncements {
                 node,
@@ -157,7 +171,8 @@ impl ProtocolWorker {
     ///
     /// React on another node asking for blocks information. We can forward the operation ids if
     /// the foreign node asked for `AskForBlocksInfo::Info` or the full operations if he asked for
-    /// the missing operations in his storage with `AskForBlocksInfo::Operations`
+    /// the missing operations in his storage with `AskForBlocksInfo::Operations`,
+    /// or erasure-coded chunks of the operations with `AskForBlocksInfo::Chunks`
     ///
     /// Forward the reply to the network.
     async fn on_asked_for_blocks_received(
@@ -186,11 +201,19 @@ impl ProtocolWorker {
                 AskForBlocksInfo::Header => BlockInfoReply::Header(header),
                 AskForBlocksInfo::Info => BlockInfoReply::Info(operations_ids),
                 AskForBlocksInfo::Operations(op_ids) => {
+                    let now = Instant::now();
+                    // Do not send the same block body twice while the previous reply may still be on its way.
+                    if !node_info.try_serve_block(*hash, now, self.config.ask_block_timeout.into())
+                    {
+                        massa_trace!("protocol.protocol_worker.on_asked_for_blocks_received.duplicate", { "node": from_node_id, "block_id": hash });
+                        continue;
+                    }
+
                     // Mark the node as having the block.
                     node_info.insert_known_blocks(
                         &[*hash],
                         true,
-                        Instant::now(),
+                        now,
                         self.config.max_node_known_blocks_size,
                     );
 
@@ -206,6 +229,18 @@ impl ProtocolWorker {
                     };
                     BlockInfoReply::Operations(needed_ops)
                 }
+                AskForBlocksInfo::Chunks(indices) => {
+                    node_info.insert_known_blocks(
+                        &[*hash],
+                        true,
+                        Instant::now(),
+                        self.config.max_node_known_blocks_size,
+                    );
+                    match Self::get_block_chunks(&self.storage, &operations_ids, indices)? {
+                        Some(chunks) => BlockInfoReply::Chunks(chunks),
+                        None => BlockInfoReply::NotFound,
+                    }
+                }
             };
             all_blocks_info.push((*hash, block_info));
         }
@@ -252,6 +287,8 @@ impl ProtocolWorker {
                     node.insert_known_blocks(&[block_id], false, Instant::now(), self.config.max_node_known_blocks_size);
                 }
 
+                self.note_invalid_message(&from_node_id, PeerMessageType::BlockInfo)
+                    .await;
                 return Ok(());
             }
         }
@@ -318,6 +355,8 @@ impl ProtocolWorker {
                 node.asked_blocks.remove(&block_id);
                 node.insert_known_blocks(&[block_id], false, Instant::now(), self.config.max_node_known_blocks_size);
             }
+            self.note_invalid_message(&from_node_id, PeerMessageType::BlockInfo)
+                .await;
             return Ok(());
         };
 
@@ -329,6 +368,8 @@ impl ProtocolWorker {
                 node.asked_blocks.remove(&block_id);
                 node.insert_known_blocks(&[block_id], false, Instant::now(), self.config.max_node_known_blocks_size);
             }
+            self.note_invalid_message(&from_node_id, PeerMessageType::BlockInfo)
+                .await;
             return Ok(());
         };
 
@@ -341,6 +382,8 @@ impl ProtocolWorker {
                 node.asked_blocks.remove(&block_id);
                 node.insert_known_blocks(&[block_id], false, Instant::now(), self.config.max_node_known_blocks_size);
             }
+            self.note_invalid_message(&from_node_id, PeerMessageType::BlockInfo)
+                .await;
             return Ok(());
         }
 
@@ -443,6 +486,8 @@ impl ProtocolWorker {
                         node.asked_blocks.remove(&block_id);
                         node.insert_known_blocks(&[block_id], false, Instant::now(), self.config.max_node_known_blocks_size);
                     }
+                    self.note_invalid_message(&from_node_id, PeerMessageType::BlockInfo)
+                        .await;
                     return Ok(());
                 };
                 let block_operation_ids = if let Some(operations) = &info.operation_ids {
@@ -453,6 +498,8 @@ impl ProtocolWorker {
                         node.asked_blocks.remove(&block_id);
                         node.insert_known_blocks(&[block_id], false, Instant::now(), self.config.max_node_known_blocks_size);
                     }
+                    self.note_invalid_message(&from_node_id, PeerMessageType::BlockInfo)
+                        .await;
                     return Ok(());
                 };
                 operations.retain(|op| block_operation_ids.contains(&op.id));
@@ -487,6 +534,8 @@ impl ProtocolWorker {
                             node.asked_blocks.remove(&block_id);
                             node.insert_known_blocks(&[block_id], false, Instant::now(), self.config.max_node_known_blocks_size);
                         }
+                        self.note_invalid_message(&from_node_id, PeerMessageType::BlockInfo)
+                            .await;
                         return Ok(());
                     }
 
@@ -533,6 +582,8 @@ impl ProtocolWorker {
                     node.asked_blocks.remove(&block_id);
                     node.insert_known_blocks(&[block_id], false, Instant::now(), self.config.max_node_known_blocks_size);
                 }
+                self.note_invalid_message(&from_node_id, PeerMessageType::BlockInfo)
+                    .await;
                 return Ok(());
             }
         };
@@ -575,6 +626,11 @@ impl ProtocolWorker {
                 self.on_block_full_operations_received(from_node_id, block_id, operations, op_timer)
                     .await
             }
+            BlockInfoReply::Chunks(chunks) => {
+                // Rebuild the operations once enough chunks have been received from any nodes.
+                self.on_block_chunks_received(from_node_id, block_id, chunks, op_timer)
+                    .await
+            }
             BlockInfoReply::NotFound => {
                 if let Some(info) = self.active_nodes.get_mut(&from_node_id) {
                     info.insert_known_blocks(
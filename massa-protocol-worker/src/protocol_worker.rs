@@ -2,33 +2,40 @@
 
 use crate::cache::{LinearHashCacheMap, LinearHashCacheSet};
 use crate::checked_operations::CheckedOperations;
+use crate::sig_verification_stage::{SigVerificationStage, VerificationJob, VerificationResult};
 use crate::sig_verifier::verify_sigs_batch;
 use crate::{node_info::NodeInfo, worker_operations_impl::OperationBatchBuffer};
 
+use massa_channel::MassaReceiver;
 use massa_consensus_exports::ConsensusController;
 use massa_logging::massa_trace;
 
-use massa_models::secure_share::Id;
 use massa_models::slot::Slot;
 use massa_models::timeslots::get_block_slot_timestamp;
 use massa_models::{
+    amount::Amount,
     block_header::SecuredHeader,
     block_id::BlockId,
+    config::{CapacityScale, FULL_CAPACITY_PERMILLE},
     endorsement::{EndorsementId, SecureShareEndorsement},
+    endorsement_signature::EndorsementSignature,
     node::NodeId,
     operation::OperationPrefixId,
     operation::{OperationId, SecureShareOperation},
     prehash::{CapacityAllocator, PreHashMap, PreHashSet},
+    version::Capabilities,
+};
+use massa_network_exports::{
+    AskForBlocksInfo, BlockChunk, NetworkCommandSender, NetworkEventReceiver,
 };
-use massa_network_exports::{AskForBlocksInfo, NetworkCommandSender, NetworkEventReceiver};
 use massa_pool_exports::PoolController;
 use massa_protocol_exports::{
-    ProtocolCommand, ProtocolConfig, ProtocolError, ProtocolManagementCommand, ProtocolManager,
-    ProtocolReceivers, ProtocolSenders,
+    AnnouncementOverflowPolicy, PeerMessageType, ProtocolCommand, ProtocolConfig, ProtocolError,
+    ProtocolManagementCommand, ProtocolManager, ProtocolReceivers, ProtocolSenders,
 };
 use massa_storage::Storage;
 use massa_time::{MassaTime, TimeError};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::mem;
 use std::pin::Pin;
 use tokio::{
@@ -55,6 +62,7 @@ pub async fn start_protocol_controller(
     consensus_controller: Box<dyn ConsensusController>,
     pool_controller: Box<dyn PoolController>,
     storage: Storage,
+    capacity_scale: CapacityScale,
 ) -> Result<ProtocolManager, ProtocolError> {
     debug!("starting protocol controller");
 
@@ -73,6 +81,7 @@ pub async fn start_protocol_controller(
             consensus_controller,
             pool_controller,
             storage,
+            capacity_scale,
         )
         .run_loop()
         .await;
@@ -103,6 +112,11 @@ pub(crate) struct BlockInfo {
     pub(crate) storage: Storage,
     /// Full operations size in bytes
     pub(crate) operations_size: usize,
+    /// Erasure-coded chunks of the operations received so far, by index
+    pub(crate) chunks: BTreeMap<u16, BlockChunk>,
+    /// Whether the received chunks failed to rebuild the operations,
+    /// in which case they are asked without erasure coding
+    pub(crate) chunks_failed: bool,
 }
 
 impl BlockInfo {
@@ -112,6 +126,8 @@ impl BlockInfo {
             operation_ids: None,
             storage,
             operations_size: 0,
+            chunks: Default::default(),
+            chunks_failed: false,
         }
     }
 }
@@ -129,7 +145,7 @@ pub struct ProtocolWorker {
     /// Channel to send protocol pool events to the controller.
     pool_controller: Box<dyn PoolController>,
     /// Channel receiving commands from the controller.
-    controller_command_rx: mpsc::Receiver<ProtocolCommand>,
+    controller_command_rx: MassaReceiver<ProtocolCommand>,
     /// Channel to send management commands to the controller.
     controller_manager_rx: mpsc::Receiver<ProtocolManagementCommand>,
     /// Ids of active nodes mapped to node info.
@@ -143,14 +159,22 @@ pub struct ProtocolWorker {
     pub(crate) checked_operations: CheckedOperations,
     /// List of processed headers
     pub(crate) checked_headers: LinearHashCacheMap<BlockId, SecuredHeader>,
+    /// Headers already relayed to the peers on reception (headers-first propagation)
+    pub(crate) relayed_headers: LinearHashCacheSet<BlockId>,
     /// List of ids of operations that we asked to the nodes
     pub(crate) asked_operations: PreHashMap<OperationPrefixId, (Instant, Vec<NodeId>)>,
     /// Buffer for operations that we want later
     pub(crate) op_batch_buffer: OperationBatchBuffer,
     /// Shared storage.
     pub(crate) storage: Storage,
-    /// Operations to announce at the next interval.
-    operations_to_announce: Vec<OperationId>,
+    /// Operations to announce at the next interval, with their fees.
+    operations_to_announce: Vec<(OperationId, Amount)>,
+    /// Verification of the signatures of gossiped operations and endorsements.
+    pub(crate) sig_verification_stage: SigVerificationStage,
+    /// Scale of the cache capacities, lowered by the memory monitor under memory pressure.
+    pub(crate) capacity_scale: CapacityScale,
+    /// Scale last applied to the caches, in thousandths of the configured capacities.
+    applied_capacity_permille: u32,
 }
 
 /// channels used by the protocol worker
@@ -160,7 +184,7 @@ pub struct ProtocolWorkerChannels {
     /// network event receiver
     pub network_event_receiver: NetworkEventReceiver,
     /// protocol command receiver
-    pub controller_command_rx: mpsc::Receiver<ProtocolCommand>,
+    pub controller_command_rx: MassaReceiver<ProtocolCommand>,
     /// protocol management command receiver
     pub controller_manager_rx: mpsc::Receiver<ProtocolManagementCommand>,
 }
@@ -174,6 +198,7 @@ impl ProtocolWorker {
     /// * `controller_event_tx`: Channel to send protocol events.
     /// * `controller_command_rx`: Channel receiving commands.
     /// * `controller_manager_rx`: Channel receiving management commands.
+    /// * `capacity_scale`: scale of the cache capacities, lowered under memory pressure.
     pub fn new(
         config: ProtocolConfig,
         ProtocolWorkerChannels {
@@ -185,6 +210,7 @@ impl ProtocolWorker {
         consensus_controller: Box<dyn ConsensusController>,
         pool_controller: Box<dyn PoolController>,
         storage: Storage,
+        capacity_scale: CapacityScale,
     ) -> ProtocolWorker {
         ProtocolWorker {
             config,
@@ -199,6 +225,7 @@ impl ProtocolWorker {
             checked_endorsements: LinearHashCacheSet::new(config.max_known_endorsements_size),
             checked_operations: CheckedOperations::new(config.max_known_ops_size),
             checked_headers: LinearHashCacheMap::new(config.max_node_known_blocks_size),
+            relayed_headers: LinearHashCacheSet::new(config.max_known_blocks_size),
             asked_operations: Default::default(),
             op_batch_buffer: OperationBatchBuffer::with_capacity(
                 config.operation_batch_buffer_capacity,
@@ -207,6 +234,40 @@ impl ProtocolWorker {
             operations_to_announce: Vec::with_capacity(
                 config.operation_announcement_buffer_capacity,
             ),
+            sig_verification_stage: SigVerificationStage::start(
+                config.signature_verification_shards,
+                config.thread_count,
+                config.max_pending_signature_verifications,
+                config.max_signature_batch_size,
+            ),
+            capacity_scale,
+            applied_capacity_permille: FULL_CAPACITY_PERMILLE,
+        }
+    }
+
+    /// Resizes the caches if the capacity scale changed since it was last applied.
+    /// Shrinking a cache forgets its oldest items.
+    fn apply_capacity_scale(&mut self) {
+        let permille = self.capacity_scale.get_permille();
+        if permille == self.applied_capacity_permille {
+            return;
+        }
+        debug!(
+            "protocol cache capacities scaled from {}‰ to {}‰",
+            self.applied_capacity_permille, permille
+        );
+        self.applied_capacity_permille = permille;
+        let scale = &self.capacity_scale;
+        self.checked_endorsements
+            .set_capacity(scale.apply(self.config.max_known_endorsements_size));
+        self.checked_operations
+            .set_capacity(scale.apply(self.config.max_known_ops_size));
+        self.checked_headers
+            .set_capacity(scale.apply(self.config.max_node_known_blocks_size));
+        self.relayed_headers
+            .set_capacity(scale.apply(self.config.max_known_blocks_size));
+        for node_info in self.active_nodes.values_mut() {
+            node_info.scale_caches(&self.config, scale);
         }
     }
 
@@ -241,6 +302,7 @@ impl ProtocolWorker {
                     * manager commands: low freq, avoid having to wait to stop
                     * incoming commands (high frequency): process commands in priority (this is a high-level crate so we prioritize this side to avoid slowing down consensus)
                     * network events (high frequency): process incoming events
+                    * verified gossiped operations and endorsements
                     * ask for blocks (timing not important)
             */
             tokio::select! {
@@ -266,6 +328,12 @@ impl ProtocolWorker {
                     self.on_network_event(evt?, &mut block_ask_timer, &mut operation_announcement_interval).await?;
                 }
 
+                // listen to signature verification results
+                Some(result) = self.sig_verification_stage.result_rx.recv() => {
+                    massa_trace!("protocol.protocol_worker.run_loop.sig_verification_result", {});
+                    self.on_verification_result(result, &mut operation_announcement_interval).await;
+                }
+
                 // block ask timer
                 _ = &mut block_ask_timer => {
                     massa_trace!("protocol.protocol_worker.run_loop.block_ask_timer", { });
@@ -282,6 +350,9 @@ impl ProtocolWorker {
                 _ = &mut operation_batch_proc_period_timer => {
                     massa_trace!("protocol.protocol_worker.run_loop.operation_ask_and_announce_timer", { });
 
+                    // follow the memory pressure at the pace of the operation batches, which fill the caches
+                    self.apply_capacity_scale();
+
                     // Update operations to ask.
                     self.update_ask_operation(&mut operation_batch_proc_period_timer).await?;
                 }
@@ -312,7 +383,10 @@ impl ProtocolWorker {
             timer.set(sleep_until(next_tick));
             return;
         }
-        let operation_ids = mem::take(&mut self.operations_to_announce);
+        let operation_ids: Vec<OperationId> = mem::take(&mut self.operations_to_announce)
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
         massa_trace!("protocol.protocol_worker.announce_ops.begin", {
             "operation_ids": operation_ids
         });
@@ -343,26 +417,56 @@ impl ProtocolWorker {
         timer.set(sleep_until(next_tick));
     }
 
-    /// Add an list of operations to a buffer for announcement at the next interval,
-    /// or immediately if the buffer is full.
+    /// Add the operations of a storage to a buffer for announcement at the next interval.
+    /// If the buffer overflows, the `operation_announcement_overflow_policy` is applied.
     async fn note_operations_to_announce(
         &mut self,
-        operations: &[OperationId],
+        operations: &Storage,
         timer: &mut Pin<&mut Sleep>,
     ) {
         massa_trace!(
             "protocol.protocol_worker.note_operations_to_announce.begin",
-            { "operations": operations }
+            { "operations": operations.get_op_refs() }
         );
         // Add the operations to a list for announcement at the next interval.
-        self.operations_to_announce.extend_from_slice(operations);
+        {
+            let read_operations = operations.read_operations();
+            self.operations_to_announce.extend(
+                operations
+                    .get_op_refs()
+                    .iter()
+                    .filter_map(|id| read_operations.get(id).map(|op| (*id, op.content.fee))),
+            );
+        }
 
-        // If the buffer is full,
-        // announce operations immediately,
-        // clearing the data at the same time.
-        if self.operations_to_announce.len() > self.config.operation_announcement_buffer_capacity {
-            self.announce_ops(timer).await;
+        let capacity = self.config.operation_announcement_buffer_capacity;
+        if self.operations_to_announce.len() <= capacity {
+            return;
         }
+        match self.config.operation_announcement_overflow_policy {
+            AnnouncementOverflowPolicy::ImmediateFlush => {
+                // announce operations immediately, clearing the data at the same time
+                self.announce_ops(timer).await;
+            }
+            AnnouncementOverflowPolicy::DropLowestFee => {
+                self.operations_to_announce
+                    .sort_unstable_by(|(_, fee_a), (_, fee_b)| fee_b.cmp(fee_a));
+                self.operations_to_announce.truncate(capacity);
+            }
+            AnnouncementOverflowPolicy::Backpressure => {
+                // the new operations are the last ones
+                self.operations_to_announce.truncate(capacity);
+            }
+        }
+    }
+
+    /// Whether the announcements of the peers must not be fetched for now,
+    /// because the announcement buffer is full and the overflow policy is `Backpressure`
+    pub(crate) fn is_announcement_buffer_saturated(&self) -> bool {
+        self.config.operation_announcement_overflow_policy
+            == AnnouncementOverflowPolicy::Backpressure
+            && self.operations_to_announce.len()
+                >= self.config.operation_announcement_buffer_capacity
     }
 
     async fn propagate_endorsements(&mut self, storage: &Storage) {
@@ -413,35 +517,21 @@ impl ProtocolWorker {
                     "protocol.protocol_worker.process_command.integrated_block.begin",
                     { "block_id": block_id }
                 );
-                let header = {
-                    let blocks = storage.read_blocks();
-                    blocks
-                        .get(&block_id)
-                        .map(|block| block.content.header.clone())
-                        .ok_or_else(|| {
-                            ProtocolError::ContainerInconsistencyError(format!(
-                                "header of id {} not found.",
-                                block_id
-                            ))
-                        })?
-                };
-                for (node_id, node_info) in self.active_nodes.iter_mut() {
-                    // node that isn't asking for that block
-                    let cond = node_info.get_known_block(&block_id);
-                    // if we don't know if that node knows that hash or if we know it doesn't
-                    if !cond.map_or_else(|| false, |v| v.0) {
-                        massa_trace!("protocol.protocol_worker.process_command.integrated_block.send_header", { "node": node_id, "block_id": block_id});
-                        self.network_command_sender
-                            .send_block_header(*node_id, header.clone())
-                            .await
-                            .map_err(|_| {
-                                ProtocolError::ChannelError(
-                                    "send block header network command send failed".into(),
-                                )
-                            })?;
-                    } else {
-                        massa_trace!("protocol.protocol_worker.process_command.integrated_block.do_not_send", { "node": node_id, "block_id": block_id });
-                    }
+                // headers-first: the header was already relayed when it was received
+                if !self.relayed_headers.contains(&block_id) {
+                    let header = {
+                        let blocks = storage.read_blocks();
+                        blocks
+                            .get(&block_id)
+                            .map(|block| block.content.header.clone())
+                            .ok_or_else(|| {
+                                ProtocolError::ContainerInconsistencyError(format!(
+                                    "header of id {} not found.",
+                                    block_id
+                                ))
+                            })?
+                    };
+                    self.propagate_block_header(block_id, &header).await?;
                 }
                 massa_trace!(
                     "protocol.protocol_worker.process_command.integrated_block.end",
@@ -505,13 +595,25 @@ impl ProtocolWorker {
                     .extend(operation_ids.iter().copied());
 
                 // Announce operations to active nodes not knowing about it.
-                let to_announce: Vec<OperationId> = operation_ids.iter().copied().collect();
-                self.note_operations_to_announce(&to_announce, op_timer)
-                    .await;
+                self.note_operations_to_announce(&storage, op_timer).await;
             }
             ProtocolCommand::PropagateEndorsements(endorsements) => {
                 self.propagate_endorsements(&endorsements).await;
             }
+            ProtocolCommand::PropagateEndorsementSignatures(signatures) => {
+                self.propagate_endorsement_signatures(signatures, None)
+                    .await;
+            }
+            ProtocolCommand::GetPeerMessageStats(response_tx) => {
+                let stats = self
+                    .active_nodes
+                    .iter()
+                    .map(|(node_id, node_info)| node_info.message_stats.export(*node_id))
+                    .collect();
+                if response_tx.send(stats).is_err() {
+                    warn!("protocol: could not send get_peer_message_stats answer");
+                }
+            }
         }
         massa_trace!("protocol.protocol_worker.process_command.end", {});
         Ok(())
@@ -533,6 +635,33 @@ impl ProtocolWorker {
         Ok(())
     }
 
+    /// Send a block header to the nodes that are not known to have it
+    pub(crate) async fn propagate_block_header(
+        &mut self,
+        block_id: BlockId,
+        header: &SecuredHeader,
+    ) -> Result<(), ProtocolError> {
+        for (node_id, node_info) in self.active_nodes.iter_mut() {
+            // node that isn't asking for that block
+            let cond = node_info.get_known_block(&block_id);
+            // if we don't know if that node knows that hash or if we know it doesn't
+            if !cond.map_or_else(|| false, |v| v.0) {
+                massa_trace!("protocol.protocol_worker.propagate_block_header.send_header", { "node": node_id, "block_id": block_id});
+                self.network_command_sender
+                    .send_block_header(*node_id, header.clone())
+                    .await
+                    .map_err(|_| {
+                        ProtocolError::ChannelError(
+                            "send block header network command send failed".into(),
+                        )
+                    })?;
+            } else {
+                massa_trace!("protocol.protocol_worker.propagate_block_header.do_not_send", { "node": node_id, "block_id": block_id });
+            }
+        }
+        Ok(())
+    }
+
     pub(crate) async fn update_ask_block(
         &mut self,
         ask_block_timer: &mut Pin<&mut Sleep>,
@@ -557,18 +686,7 @@ impl ProtocolWorker {
             } else if block_info.operation_ids.is_none() {
                 AskForBlocksInfo::Info
             } else {
-                let already_stored_operations = block_info.storage.get_op_refs();
-                // Unwrap safety: Check if `operation_ids` is none just above
-                AskForBlocksInfo::Operations(
-                    block_info
-                        .operation_ids
-                        .as_ref()
-                        .unwrap()
-                        .iter()
-                        .filter(|id| !already_stored_operations.contains(id))
-                        .copied()
-                        .collect(),
-                )
+                self.operations_ask_info(block_info)
             };
             let mut needs_ask = true;
 
@@ -685,39 +803,55 @@ impl ProtocolWorker {
             .collect();
 
         for (hash, criteria) in candidate_nodes.into_iter() {
-            // find the best node
-            if let Some((_knowledge, best_node, required_info)) = criteria
+            // sort the nodes, best first
+            let mut criteria: Vec<_> = criteria
                 .into_iter()
                 .filter(|(_knowledge, node_id, _)| {
                     // filter out nodes with too many active block requests
                     *active_block_req_count.get(node_id).unwrap_or(&0)
                         <= self.config.max_simultaneous_ask_blocks_per_node
                 })
-                .min_by_key(|(knowledge, node_id, _)| {
-                    (
-                        *knowledge,                                                 // block knowledge
-                        *active_block_req_count.get(node_id).unwrap_or(&0), // active requests
-                        self.active_nodes.get(node_id).unwrap().connection_instant, // node age (will not panic, already checked)
-                        *node_id,                                                   // node ID
-                    )
-                })
-            {
-                let info = self.active_nodes.get_mut(&best_node).unwrap(); // will not panic, already checked
+                .collect();
+            criteria.sort_by_key(|(knowledge, node_id, _)| {
+                (
+                    *knowledge,                                                 // block knowledge
+                    *active_block_req_count.get(node_id).unwrap_or(&0),         // active requests
+                    self.active_nodes.get(node_id).unwrap().connection_instant, // node age (will not panic, already checked)
+                    *node_id,                                                   // node ID
+                )
+            });
+
+            // ask the best node, or spread the chunks of the operations over several nodes
+            let asks = match criteria.first() {
+                Some((_knowledge, _best_node, AskForBlocksInfo::Chunks(indices))) => {
+                    let candidates: Vec<(NodeId, bool)> = criteria
+                        .iter()
+                        .map(|((knowledge, _), node_id, _)| (*node_id, *knowledge < 2))
+                        .collect();
+                    self.plan_chunk_asks(&hash, indices, &candidates)
+                }
+                Some((_knowledge, best_node, required_info)) => {
+                    vec![(*best_node, required_info.clone())]
+                }
+                None => continue,
+            };
+            for (node_id, required_info) in asks {
+                let info = self.active_nodes.get_mut(&node_id).unwrap(); // will not panic, already checked
                 info.asked_blocks.insert(hash, now);
-                if let Some(cnt) = active_block_req_count.get_mut(&best_node) {
+                if let Some(cnt) = active_block_req_count.get_mut(&node_id) {
                     *cnt += 1; // increase the number of actively asked blocks
                 }
 
                 ask_block_list
-                    .entry(best_node)
+                    .entry(node_id)
                     .or_insert_with(Vec::new)
-                    .push((hash, required_info.clone()));
-
-                let timeout_at = now
-                    .checked_add(self.config.ask_block_timeout.into())
-                    .ok_or(TimeError::TimeOverflowError)?;
-                next_tick = std::cmp::min(next_tick, timeout_at);
+                    .push((hash, required_info));
             }
+
+            let timeout_at = now
+                .checked_add(self.config.ask_block_timeout.into())
+                .ok_or(TimeError::TimeOverflowError)?;
+            next_tick = std::cmp::min(next_tick, timeout_at);
         }
 
         // send AskBlockEvents
@@ -753,6 +887,29 @@ impl ProtocolWorker {
         Ok(())
     }
 
+    /// Accounts for an invalid or unsolicited message from a node,
+    /// and bans the node if it sent too many of them in the last minute.
+    pub(crate) async fn note_invalid_message(
+        &mut self,
+        node_id: &NodeId,
+        message_type: PeerMessageType,
+    ) {
+        let Some(node_info) = self.active_nodes.get_mut(node_id) else {
+            return;
+        };
+        if let Some(anomaly) = node_info.message_stats.record_invalid(
+            message_type,
+            Instant::now(),
+            self.config.max_invalid_messages_per_minute,
+        ) {
+            warn!(
+                "node {} raised the anomaly {:?}, banning it",
+                node_id, anomaly
+            );
+            let _ = self.ban_node(node_id).await;
+        }
+    }
+
     /// Perform checks on a header,
     /// and if valid update the node's view of the world.
     ///
@@ -892,6 +1049,38 @@ impl ProtocolWorker {
         op_timer: &mut Pin<&mut Sleep>,
     ) -> Result<(), ProtocolError> {
         massa_trace!("protocol.protocol_worker.note_operations_from_node", { "node": source_node_id, "operations": operations });
+        let (received_ids, new_operations) = self.filter_new_operations(operations)?;
+
+        // optimized signature verification
+        let signatures = new_operations
+            .iter()
+            .map(|(_op_id, op)| op.get_signatures_to_verify())
+            .collect::<Result<Vec<_>, _>>()?
+            .concat();
+        verify_sigs_batch(&signatures, self.config.max_signature_batch_size)?;
+
+        // add to known ops
+        if let Some(node_info) = self.active_nodes.get_mut(source_node_id) {
+            node_info.insert_known_ops(received_ids.iter().map(|id| id.prefix()));
+        }
+
+        self.note_verified_operations(new_operations, op_timer)
+            .await
+    }
+
+    /// Checks the size of received operations and keeps the ones whose signature was not checked yet.
+    ///
+    /// Returns the ids of all the received operations, and the operations to verify.
+    pub(crate) fn filter_new_operations(
+        &self,
+        operations: Vec<SecureShareOperation>,
+    ) -> Result<
+        (
+            PreHashSet<OperationId>,
+            PreHashMap<OperationId, SecureShareOperation>,
+        ),
+        ProtocolError,
+    > {
         let length = operations.len();
         let mut new_operations = PreHashMap::with_capacity(length);
         let mut received_ids = PreHashSet::with_capacity(length);
@@ -913,24 +1102,22 @@ impl ProtocolWorker {
                 new_operations.insert(operation_id, operation);
             };
         }
+        Ok((received_ids, new_operations))
+    }
 
-        // optimized signature verification
-        verify_sigs_batch(
-            &new_operations
-                .iter()
-                .map(|(op_id, op)| (*op_id.get_hash(), op.signature, op.content_creator_pub_key))
-                .collect::<Vec<_>>(),
-        )?;
+    /// Caches operations whose signature was verified, stores them, propagates them and adds them to pool.
+    /// Operations that were verified meanwhile through another path are ignored.
+    pub(crate) async fn note_verified_operations(
+        &mut self,
+        mut new_operations: PreHashMap<OperationId, SecureShareOperation>,
+        op_timer: &mut Pin<&mut Sleep>,
+    ) -> Result<(), ProtocolError> {
+        new_operations.retain(|op_id, _| !self.checked_operations.contains_id(op_id));
 
         // add to checked operations
         self.checked_operations
             .extend(new_operations.keys().copied());
 
-        // add to known ops
-        if let Some(node_info) = self.active_nodes.get_mut(source_node_id) {
-            node_info.insert_known_ops(received_ids.iter().map(|id| id.prefix()));
-        }
-
         if !new_operations.is_empty() {
             // Store operation, claim locally
             let mut ops = self.storage.clone_without_refs();
@@ -966,9 +1153,7 @@ impl ProtocolWorker {
                     .collect()
             };
             ops_to_propagate.drop_operation_refs(&operations_to_not_propagate);
-            let to_announce: Vec<OperationId> =
-                ops_to_propagate.get_op_refs().iter().copied().collect();
-            self.note_operations_to_announce(&to_announce, op_timer)
+            self.note_operations_to_announce(&ops_to_propagate, op_timer)
                 .await;
 
             // Add to pool
@@ -994,32 +1179,22 @@ impl ProtocolWorker {
         propagate: bool,
     ) -> Result<(), ProtocolError> {
         massa_trace!("protocol.protocol_worker.note_endorsements_from_node", { "node": source_node_id, "endorsements": endorsements});
-        let length = endorsements.len();
-        let mut new_endorsements = PreHashMap::with_capacity(length);
-        let mut endorsement_ids = PreHashSet::with_capacity(length);
-        for endorsement in endorsements.into_iter() {
-            let endorsement_id = endorsement.id;
-            endorsement_ids.insert(endorsement_id);
-
-            // check endorsement signature if not already checked
-            if !self.checked_endorsements.contains(&endorsement_id) {
-                new_endorsements.insert(endorsement_id, endorsement);
-            }
-        }
+        let (endorsement_ids, new_endorsements) = self.filter_new_endorsements(endorsements);
 
         // Batch signature verification
         // optimized signature verification
         verify_sigs_batch(
             &new_endorsements
                 .iter()
-                .map(|(endorsement_id, endorsement)| {
+                .map(|(_endorsement_id, endorsement)| {
                     (
-                        *endorsement_id.get_hash(),
+                        endorsement.signed_hash(),
                         endorsement.signature,
                         endorsement.content_creator_pub_key,
                     )
                 })
                 .collect::<Vec<_>>(),
+            self.config.max_signature_batch_size,
         )?;
 
         // add to verified signature cache
@@ -1031,6 +1206,41 @@ impl ProtocolWorker {
             node_info.insert_known_endorsements(endorsement_ids);
         }
 
+        self.note_verified_endorsements(new_endorsements, propagate)
+            .await
+    }
+
+    /// Keeps the received endorsements whose signature was not checked yet.
+    ///
+    /// Returns the ids of all the received endorsements, and the endorsements to verify.
+    pub(crate) fn filter_new_endorsements(
+        &self,
+        endorsements: Vec<SecureShareEndorsement>,
+    ) -> (
+        PreHashSet<EndorsementId>,
+        PreHashMap<EndorsementId, SecureShareEndorsement>,
+    ) {
+        let length = endorsements.len();
+        let mut new_endorsements = PreHashMap::with_capacity(length);
+        let mut endorsement_ids = PreHashSet::with_capacity(length);
+        for endorsement in endorsements.into_iter() {
+            let endorsement_id = endorsement.id;
+            endorsement_ids.insert(endorsement_id);
+
+            // check endorsement signature if not already checked
+            if !self.checked_endorsements.contains(&endorsement_id) {
+                new_endorsements.insert(endorsement_id, endorsement);
+            }
+        }
+        (endorsement_ids, new_endorsements)
+    }
+
+    /// Stores endorsements whose signature was verified, propagates them if asked and adds them to pool.
+    pub(crate) async fn note_verified_endorsements(
+        &mut self,
+        new_endorsements: PreHashMap<EndorsementId, SecureShareEndorsement>,
+        propagate: bool,
+    ) -> Result<(), ProtocolError> {
         if !new_endorsements.is_empty() {
             let mut endorsements = self.storage.clone_without_refs();
             endorsements.store_endorsements(new_endorsements.into_values().collect());
@@ -1081,6 +1291,116 @@ impl ProtocolWorker {
 
         Ok(())
     }
+
+    /// Sends BLS signatures of endorsers to the active nodes supporting them, except to the node that sent them
+    async fn propagate_endorsement_signatures(
+        &mut self,
+        signatures: Vec<EndorsementSignature>,
+        source_node_id: Option<NodeId>,
+    ) {
+        if signatures.is_empty() {
+            return;
+        }
+        for (node, node_info) in self.active_nodes.iter() {
+            if Some(*node) == source_node_id
+                || !node_info
+                    .capabilities
+                    .contains(Capabilities::ENDORSEMENT_SIGNATURES)
+            {
+                continue;
+            }
+            if let Err(err) = self
+                .network_command_sender
+                .send_endorsement_signatures(*node, signatures.clone())
+                .await
+            {
+                debug!(
+                    "could not send endorsement signatures to node {}: {}",
+                    node, err
+                );
+            }
+        }
+    }
+
+    /// Hands the BLS signatures of endorsers pushed by a node to the pool, which checks them against
+    /// the registered keys, and relays the ones that were new and valid.
+    pub(crate) async fn on_endorsement_signatures_received(
+        &mut self,
+        node_id: NodeId,
+        signatures: Vec<EndorsementSignature>,
+    ) {
+        massa_trace!("protocol.protocol_worker.on_endorsement_signatures_received", { "node": node_id, "signatures": signatures});
+        let added = self.pool_controller.add_endorsement_signatures(signatures);
+        self.propagate_endorsement_signatures(added, Some(node_id))
+            .await;
+    }
+
+    /// Queues the signature verification of endorsements pushed by a node.
+    /// The endorsements are processed in `on_verification_result` once verified.
+    pub(crate) fn on_endorsements_received(
+        &mut self,
+        node_id: NodeId,
+        endorsements: Vec<SecureShareEndorsement>,
+    ) {
+        massa_trace!("protocol.protocol_worker.on_endorsements_received", { "node": node_id, "endorsements": endorsements});
+        let (endorsement_ids, new_endorsements) = self.filter_new_endorsements(endorsements);
+        if let Some(node_info) = self.active_nodes.get_mut(&node_id) {
+            node_info.insert_known_endorsements(endorsement_ids);
+        }
+        if !new_endorsements.is_empty() {
+            self.sig_verification_stage
+                .submit(VerificationJob::Endorsements {
+                    node: node_id,
+                    endorsements: new_endorsements.into_values().collect(),
+                });
+        }
+    }
+
+    /// Processes a batch of gossiped operations or endorsements once its signatures were verified.
+    /// Bans the node that sent it if a signature is invalid.
+    pub(crate) async fn on_verification_result(
+        &mut self,
+        VerificationResult { job, result }: VerificationResult,
+        op_timer: &mut Pin<&mut Sleep>,
+    ) {
+        let (node_id, result) = match (job, result) {
+            (VerificationJob::Operations { node, operations }, Ok(())) => (
+                node,
+                self.note_verified_operations(
+                    operations.into_iter().map(|op| (op.id, op)).collect(),
+                    op_timer,
+                )
+                .await,
+            ),
+            (VerificationJob::Endorsements { node, endorsements }, Ok(())) => {
+                // endorsements verified meanwhile through another path are ignored
+                let new_endorsements: PreHashMap<EndorsementId, SecureShareEndorsement> =
+                    endorsements
+                        .into_iter()
+                        .filter(|endorsement| !self.checked_endorsements.contains(&endorsement.id))
+                        .map(|endorsement| (endorsement.id, endorsement))
+                        .collect();
+                self.checked_endorsements
+                    .try_extend(new_endorsements.keys().copied());
+                (
+                    node,
+                    self.note_verified_endorsements(new_endorsements, true)
+                        .await,
+                )
+            }
+            (VerificationJob::Operations { node, .. }, Err(err))
+            | (VerificationJob::Endorsements { node, .. }, Err(err)) => (node, Err(err)),
+        };
+        if let Err(err) = result {
+            warn!(
+                "node {} sent us critically incorrect operations or endorsements, \
+                which may be an attack attempt by the remote node or a \
+                loss of sync between us and the remote node. Err = {}",
+                node_id, err
+            );
+            let _ = self.ban_node(&node_id).await;
+        }
+    }
 }
 
 #[cfg(test)]
@@ -1153,4 +1473,30 @@ mod tests {
             assert!(nodeinfo.get_known_block(&hash).is_some());
         }
     }
+
+    #[test]
+    #[serial]
+    fn test_node_info_scale_caches() {
+        let config = &PROTOCOL_CONFIG;
+        let mut nodeinfo = NodeInfo::new(config);
+        let prefixes: Vec<OperationPrefixId> = (0..config.max_node_known_ops_size)
+            .map(|index| {
+                OperationId::from_bytes(Hash::compute_from(&index.to_be_bytes()).to_bytes())
+                    .prefix()
+            })
+            .collect();
+        nodeinfo.insert_known_ops(prefixes.iter().copied());
+
+        // under memory pressure, only the most recent half is kept
+        let capacity_scale = CapacityScale::new();
+        capacity_scale.set_permille(500);
+        nodeinfo.scale_caches(config, &capacity_scale);
+        let half = config.max_node_known_ops_size / 2;
+        assert!(prefixes[..half]
+            .iter()
+            .all(|prefix| !nodeinfo.knows_op(prefix)));
+        assert!(prefixes[half..]
+            .iter()
+            .all(|prefix| nodeinfo.knows_op(prefix)));
+    }
 }
@@ -0,0 +1,285 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Signature verification stage for gossiped operations and endorsements.
+//!
+//! Verifying the signatures of the operations and endorsements that peers push to us is done on
+//! dedicated threads so that a flood of them does not stall the handling of blocks by the protocol worker.
+//! The stage is sharded by slot thread: each object goes to the shard of the thread it belongs to
+//! (the thread of its creator for an operation, the thread of its slot for an endorsement), and a batch
+//! received from a node is split between the shards it spans. With many threads and heavy traffic the
+//! verifications are thus spread over several cores, each shard keeping its own queue.
+//!
+//! The queue of each shard is bounded: when it is full, new batches are dropped without
+//! being marked as known (so they can be received again later), and the worker stops asking peers
+//! for the operations they announce until the queue drains.
+
+use crate::sig_verifier::verify_sigs_batch;
+use massa_models::{
+    endorsement::SecureShareEndorsement, node::NodeId, operation::SecureShareOperation,
+};
+use massa_protocol_exports::ProtocolError;
+use std::thread::JoinHandle;
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tracing::debug;
+
+/// Batch of objects received from a node, to be verified
+pub(crate) enum VerificationJob {
+    /// operations pushed by a node
+    Operations {
+        node: NodeId,
+        operations: Vec<SecureShareOperation>,
+    },
+    /// endorsements pushed by a node
+    Endorsements {
+        node: NodeId,
+        endorsements: Vec<SecureShareEndorsement>,
+    },
+}
+
+impl VerificationJob {
+    /// Splits the batch by shard, the objects of a thread always going to the same shard
+    fn split(self, shard_count: usize, thread_count: u8) -> Vec<(usize, VerificationJob)> {
+        let mut jobs: Vec<(usize, VerificationJob)> = Vec::new();
+        match self {
+            VerificationJob::Operations { node, operations } => {
+                let mut shards: Vec<Vec<SecureShareOperation>> = vec![Vec::new(); shard_count];
+                for op in operations {
+                    let thread = op.content_creator_address.get_thread(thread_count);
+                    shards[thread as usize % shard_count].push(op);
+                }
+                for (shard, operations) in shards.into_iter().enumerate() {
+                    if !operations.is_empty() {
+                        jobs.push((shard, VerificationJob::Operations { node, operations }));
+                    }
+                }
+            }
+            VerificationJob::Endorsements { node, endorsements } => {
+                let mut shards: Vec<Vec<SecureShareEndorsement>> = vec![Vec::new(); shard_count];
+                for endorsement in endorsements {
+                    shards[endorsement.content.slot.thread as usize % shard_count]
+                        .push(endorsement);
+                }
+                for (shard, endorsements) in shards.into_iter().enumerate() {
+                    if !endorsements.is_empty() {
+                        jobs.push((shard, VerificationJob::Endorsements { node, endorsements }));
+                    }
+                }
+            }
+        }
+        jobs
+    }
+}
+
+/// Verified batch, sent back to the protocol worker
+pub(crate) struct VerificationResult {
+    /// the verified batch
+    pub job: VerificationJob,
+    /// `Err(ProtocolError::WrongSignature)` if at least one signature is invalid
+    pub result: Result<(), ProtocolError>,
+}
+
+/// Handle on the verification threads
+pub(crate) struct SigVerificationStage {
+    /// job queue of each shard
+    job_txs: Vec<mpsc::Sender<VerificationJob>>,
+    /// verified batches of all the shards
+    pub result_rx: mpsc::Receiver<VerificationResult>,
+    thread_count: u8,
+    dropped_jobs: u64,
+    thread_handles: Vec<JoinHandle<()>>,
+}
+
+/// Verifies the jobs of a shard until the stage is dropped
+fn run_shard(
+    mut job_rx: mpsc::Receiver<VerificationJob>,
+    result_tx: mpsc::Sender<VerificationResult>,
+    max_batch_size: usize,
+) {
+    while let Some(job) = job_rx.blocking_recv() {
+        let signatures: Result<Vec<_>, ProtocolError> = match &job {
+            VerificationJob::Operations { operations, .. } => operations
+                .iter()
+                .map(|op| op.get_signatures_to_verify())
+                .collect::<Result<Vec<_>, _>>()
+                .map(|signatures| signatures.concat())
+                .map_err(ProtocolError::from),
+            VerificationJob::Endorsements { endorsements, .. } => Ok(endorsements
+                .iter()
+                .map(|endorsement| {
+                    (
+                        endorsement.signed_hash(),
+                        endorsement.signature,
+                        endorsement.content_creator_pub_key,
+                    )
+                })
+                .collect()),
+        };
+        let result =
+            signatures.and_then(|signatures| verify_sigs_batch(&signatures, max_batch_size));
+        if result_tx
+            .blocking_send(VerificationResult { job, result })
+            .is_err()
+        {
+            break;
+        }
+    }
+}
+
+impl SigVerificationStage {
+    /// Starts the verification threads.
+    ///
+    /// # Arguments
+    /// * `shard_count`: number of verification threads
+    /// * `thread_count`: number of threads of the network, used to dispatch the objects to the shards
+    /// * `queue_size`: maximum number of batches waiting for verification, split evenly between the shards
+    /// * `max_batch_size`: maximum number of signatures verified in a single batch
+    pub fn start(
+        shard_count: usize,
+        thread_count: u8,
+        queue_size: usize,
+        max_batch_size: usize,
+    ) -> Self {
+        let shard_count = shard_count.clamp(1, thread_count.max(1) as usize);
+        let shard_queue_size = (queue_size / shard_count).max(1);
+        let (result_tx, result_rx) = mpsc::channel(shard_queue_size * shard_count);
+        let mut job_txs = Vec::with_capacity(shard_count);
+        let mut thread_handles = Vec::with_capacity(shard_count);
+        for shard in 0..shard_count {
+            let (job_tx, job_rx) = mpsc::channel::<VerificationJob>(shard_queue_size);
+            let result_tx = result_tx.clone();
+            let thread_handle = std::thread::Builder::new()
+                .name(format!("protocol-sig-verifier-{}", shard))
+                .spawn(move || run_shard(job_rx, result_tx, max_batch_size))
+                .expect("failed to spawn protocol signature verification thread");
+            job_txs.push(job_tx);
+            thread_handles.push(thread_handle);
+        }
+        SigVerificationStage {
+            job_txs,
+            result_rx,
+            thread_count,
+            dropped_jobs: 0,
+            thread_handles,
+        }
+    }
+
+    /// Queues a batch for verification, split between the shards of its objects.
+    /// Returns `false` if a part of the batch was dropped because the queue of its shard is full.
+    pub fn submit(&mut self, job: VerificationJob) -> bool {
+        if self.job_txs.is_empty() {
+            return false;
+        }
+        let mut queued = true;
+        for (shard, job) in job.split(self.job_txs.len(), self.thread_count) {
+            match self.job_txs[shard].try_send(job) {
+                Ok(()) => {}
+                Err(TrySendError::Full(_)) => {
+                    self.dropped_jobs += 1;
+                    debug!(
+                        "signature verification queue of shard {} full, dropped batch ({} dropped so far)",
+                        shard, self.dropped_jobs
+                    );
+                    queued = false;
+                }
+                Err(TrySendError::Closed(_)) => queued = false,
+            }
+        }
+        queued
+    }
+
+    /// Whether the queue of a shard is full, in which case we stop asking peers for more operations
+    pub fn is_saturated(&self) -> bool {
+        self.job_txs.iter().any(|job_tx| job_tx.capacity() == 0)
+    }
+}
+
+impl Drop for SigVerificationStage {
+    fn drop(&mut self) {
+        // closing the job channels and the result channel stops the threads
+        self.job_txs.clear();
+        self.result_rx.close();
+        for handle in self.thread_handles.drain(..) {
+            let _ = handle.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_protocol_exports::tests::tools::{
+        create_endorsement, create_operation_with_expire_period,
+    };
+    use massa_signature::KeyPair;
+
+    #[test]
+    fn test_sig_verification_stage() {
+        let mut stage = SigVerificationStage::start(1, 2, 4, 2);
+        let node = NodeId::new(KeyPair::generate().get_public_key());
+        let keypair = KeyPair::generate();
+        let operations: Vec<_> = (0..5)
+            .map(|expire_period| create_operation_with_expire_period(&keypair, expire_period))
+            .collect();
+        assert!(stage.submit(VerificationJob::Operations {
+            node,
+            operations: operations.clone(),
+        }));
+        let verified = stage.result_rx.blocking_recv().unwrap();
+        assert!(verified.result.is_ok());
+        match verified.job {
+            VerificationJob::Operations {
+                operations: ops, ..
+            } => {
+                assert_eq!(ops.len(), operations.len())
+            }
+            VerificationJob::Endorsements { .. } => panic!("unexpected job"),
+        }
+
+        // an endorsement with the signature of another one is rejected
+        let mut endorsements = vec![create_endorsement(), create_endorsement()];
+        endorsements[1].signature = endorsements[0].signature;
+        assert!(stage.submit(VerificationJob::Endorsements { node, endorsements }));
+        let verified = stage.result_rx.blocking_recv().unwrap();
+        assert!(matches!(
+            verified.result,
+            Err(ProtocolError::WrongSignature)
+        ));
+    }
+
+    #[test]
+    fn test_split_by_thread() {
+        let node = NodeId::new(KeyPair::generate().get_public_key());
+        // one operation created in each of the 2 threads
+        let mut operations = Vec::new();
+        let mut threads = Vec::new();
+        while threads.len() < 2 {
+            let keypair = KeyPair::generate();
+            let operation = create_operation_with_expire_period(&keypair, 10);
+            let thread = operation.content_creator_address.get_thread(2);
+            if !threads.contains(&thread) {
+                threads.push(thread);
+                operations.push(operation);
+            }
+        }
+        let jobs = VerificationJob::Operations { node, operations }.split(2, 2);
+        assert_eq!(jobs.len(), 2);
+        for (shard, job) in jobs {
+            match job {
+                VerificationJob::Operations { operations, .. } => {
+                    assert_eq!(operations.len(), 1);
+                    assert_eq!(
+                        operations[0].content_creator_address.get_thread(2) as usize,
+                        shard
+                    );
+                }
+                VerificationJob::Endorsements { .. } => panic!("unexpected job"),
+            }
+        }
+
+        // the endorsements of slots of thread 1 all go to shard 1
+        let endorsements = vec![create_endorsement(), create_endorsement()];
+        let jobs = VerificationJob::Endorsements { node, endorsements }.split(2, 2);
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].0, 1);
+    }
+}
@@ -10,9 +10,13 @@ use rayon::{prelude::ParallelIterator, slice::ParallelSlice};
 /// Limit for small batch optimization
 const SMALL_BATCH_LIMIT: usize = 2;
 
-/// Efficiently verifies a batch of signatures in parallel.
+/// Efficiently verifies a batch of signatures in parallel,
+/// in chunks of at most `max_batch_size` signatures.
 /// Returns an error if at least one of them fails to verify.
-pub fn verify_sigs_batch(ops: &[(Hash, Signature, PublicKey)]) -> Result<(), ProtocolError> {
+pub fn verify_sigs_batch(
+    ops: &[(Hash, Signature, PublicKey)],
+    max_batch_size: usize,
+) -> Result<(), ProtocolError> {
     // if it's a small batch, use single-core verification
     if ops.len() <= SMALL_BATCH_LIMIT {
         return verify_signature_batch(ops).map_err(|_err| ProtocolError::WrongSignature);
@@ -21,7 +25,7 @@ pub fn verify_sigs_batch(ops: &[(Hash, Signature, PublicKey)]) -> Result<(), Pro
     // otherwise, use parallel batch verif
 
     // compute chunk size for parallelization
-    let chunk_size = std::cmp::max(1, ops.len() / rayon::current_num_threads());
+    let chunk_size = (ops.len() / rayon::current_num_threads()).clamp(1, max_batch_size.max(1));
     // process chunks in parallel
     ops.par_chunks(chunk_size)
         .try_for_each(verify_signature_batch)
@@ -0,0 +1,74 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+// RUST_BACKTRACE=1 cargo test test_protocol_survives_generated_events -- --nocapture --test-threads=1
+
+use super::tools::protocol_test;
+use massa_models::config::ENDORSEMENT_COUNT;
+use massa_pool_exports::test_exports::MockPoolControllerMessage;
+use massa_protocol_exports::tests::{fuzzing::ProtocolMessageGenerator, tools};
+use serial_test::serial;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[serial]
+async fn test_protocol_survives_generated_events() {
+    let protocol_config = &tools::PROTOCOL_CONFIG;
+    protocol_test(
+        protocol_config,
+        async move |mut network_controller,
+                    protocol_command_sender,
+                    protocol_manager,
+                    protocol_consensus_event_receiver,
+                    mut pool_event_receiver| {
+            let mut generator = ProtocolMessageGenerator::new(
+                42,
+                protocol_config.thread_count,
+                ENDORSEMENT_COUNT,
+                5,
+            );
+            let node_ids: Vec<_> = generator.nodes().iter().map(|node| node.id).collect();
+            for node_id in node_ids {
+                network_controller.new_connection(node_id).await;
+            }
+
+            // 1. Feed generated events to protocol.
+            for _ in 0..500 {
+                network_controller
+                    .send_event(generator.generate_event())
+                    .await;
+                network_controller.drain_commands();
+            }
+
+            // 2. Check that protocol still handles valid messages.
+            let mut nodes = tools::create_and_connect_nodes(1, &mut network_controller).await;
+            let creator_node = nodes.pop().expect("Failed to get node info.");
+            let endorsement = tools::create_endorsement();
+            let expected_endorsement_id = endorsement.id;
+            network_controller
+                .send_endorsements(creator_node.id, vec![endorsement])
+                .await;
+            loop {
+                match pool_event_receiver.wait_command(1000.into(), |evt| match evt {
+                    MockPoolControllerMessage::AddEndorsements { endorsements, .. } => Some(
+                        endorsements
+                            .get_endorsement_refs()
+                            .contains(&expected_endorsement_id),
+                    ),
+                    _ => Some(false),
+                }) {
+                    Some(true) => break,
+                    Some(false) => continue,
+                    None => panic!("protocol stopped handling endorsements"),
+                }
+            }
+
+            (
+                network_controller,
+                protocol_command_sender,
+                protocol_manager,
+                protocol_consensus_event_receiver,
+                pool_event_receiver,
+            )
+        },
+    )
+    .await;
+}
@@ -224,7 +224,9 @@ async fn test_protocol_sends_blocks_with_operations_to_consensus() {
                             slot: Slot::new(1, op_thread),
                             parents: Vec::new(),
                             operation_merkle_root,
+                            announced_version: 0,
                             endorsements: Vec::new(),
+                            aggregated_endorsements: None,
                         },
                         BlockHeaderSerializer::new(),
                         &creator_node.keypair,
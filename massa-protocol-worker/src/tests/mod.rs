@@ -4,6 +4,7 @@ mod ask_block_scenarios;
 mod ban_nodes_scenarios;
 mod cache_scenarios;
 mod endorsements_scenarios;
+mod fuzzing_scenarios;
 mod in_block_operations_scenarios;
 mod operations_scenarios;
 mod scenarios;
@@ -5,13 +5,21 @@
 use super::tools::{protocol_test, protocol_test_with_storage};
 use massa_consensus_exports::test_exports::MockConsensusControllerMessage;
 use massa_models::block_id::BlockId;
+use massa_models::config::{BLOCK_ERASURE_DATA_CHUNKS, BLOCK_ERASURE_TOTAL_CHUNKS};
+use massa_models::erasure::ErasureCoder;
+use massa_models::operation::OperationsSerializer;
 use massa_models::prehash::{PreHashMap, PreHashSet};
-use massa_network_exports::{AskForBlocksInfo, NetworkCommand};
+use massa_models::slot::Slot;
+use massa_network_exports::{AskForBlocksInfo, BlockInfoReply, NetworkCommand};
 use massa_protocol_exports::tests::tools;
 use massa_protocol_exports::{
-    tests::tools::{create_and_connect_nodes, create_block},
-    BlocksResults,
+    tests::tools::{
+        create_and_connect_nodes, create_block, create_block_with_operations,
+        create_operation_with_expire_period,
+    },
+    BlockPropagationStrategy, BlocksResults,
 };
+use massa_serialization::Serializer;
 use massa_time::MassaTime;
 use serial_test::serial;
 use std::collections::HashSet;
@@ -330,6 +338,80 @@ async fn test_protocol_propagates_block_to_all_nodes_including_those_who_asked_f
     .await;
 }
 
+#[tokio::test]
+#[serial]
+async fn test_protocol_relays_header_on_reception_with_headers_first() {
+    let mut protocol_config = *tools::PROTOCOL_CONFIG;
+    protocol_config.block_propagation_strategy = BlockPropagationStrategy::HeadersFirst;
+    protocol_test_with_storage(
+        &protocol_config,
+        async move |mut network_controller,
+                    mut protocol_command_sender,
+                    protocol_manager,
+                    protocol_consensus_event_receiver,
+                    protocol_pool_event_receiver,
+                    mut storage| {
+            // Create 3 nodes.
+            let nodes = create_and_connect_nodes(3, &mut network_controller).await;
+            let (node_a, node_b, node_c) = (nodes[0].clone(), nodes[1].clone(), nodes[2].clone());
+
+            // 1. Send a header from node_a.
+            let ref_block = create_block(&node_a.keypair);
+            let ref_hash = ref_block.id;
+            network_controller
+                .send_header(node_a.id, ref_block.content.header.clone())
+                .await;
+
+            // 2. Check that the header is relayed to the other nodes before the block is integrated.
+            let mut expected_headers = HashSet::new();
+            expected_headers.insert(node_b.id);
+            expected_headers.insert(node_c.id);
+            while !expected_headers.is_empty() {
+                match network_controller
+                    .wait_command(1000.into(), |cmd| match cmd {
+                        cmd @ NetworkCommand::SendBlockHeader { .. } => Some(cmd),
+                        _ => None,
+                    })
+                    .await
+                {
+                    Some(NetworkCommand::SendBlockHeader { node, header }) => {
+                        assert!(expected_headers.remove(&node));
+                        assert_eq!(header.id, ref_hash);
+                    }
+                    _ => panic!("Unexpected or no network command."),
+                };
+            }
+
+            // 3. Integrate the block: the header must not be sent again.
+            storage.store_block(ref_block.clone());
+            protocol_command_sender = tokio::task::spawn_blocking(move || {
+                protocol_command_sender
+                    .integrated_block(ref_hash, storage.clone())
+                    .unwrap();
+                protocol_command_sender
+            })
+            .await
+            .unwrap();
+            assert!(network_controller
+                .wait_command(200.into(), |cmd| match cmd {
+                    cmd @ NetworkCommand::SendBlockHeader { .. } => Some(cmd),
+                    _ => None,
+                })
+                .await
+                .is_none());
+
+            (
+                network_controller,
+                protocol_command_sender,
+                protocol_manager,
+                protocol_consensus_event_receiver,
+                protocol_pool_event_receiver,
+            )
+        },
+    )
+    .await;
+}
+
 #[tokio::test]
 #[serial]
 async fn test_protocol_propagates_block_to_node_who_asked_for_operations_and_only_header_to_others()
@@ -566,3 +648,92 @@ async fn test_protocol_block_not_found() {
     )
     .await;
 }
+
+#[tokio::test]
+#[serial]
+async fn test_protocol_serves_erasure_coded_chunks_of_block_operations() {
+    let protocol_config = &tools::PROTOCOL_CONFIG;
+    protocol_test_with_storage(
+        protocol_config,
+        async move |mut network_controller,
+                    mut protocol_command_sender,
+                    protocol_manager,
+                    protocol_consensus_event_receiver,
+                    protocol_pool_event_receiver,
+                    mut storage| {
+            let nodes = create_and_connect_nodes(1, &mut network_controller).await;
+            let node_a = nodes[0].clone();
+
+            // 1. Integrate a block with operations.
+            let operations = vec![
+                create_operation_with_expire_period(&node_a.keypair, 5),
+                create_operation_with_expire_period(&node_a.keypair, 6),
+            ];
+            let block =
+                create_block_with_operations(&node_a.keypair, Slot::new(1, 0), operations.clone());
+            let block_id = block.id;
+            storage.store_operations(operations.clone());
+            storage.store_block(block);
+            protocol_command_sender = tokio::task::spawn_blocking(move || {
+                protocol_command_sender
+                    .integrated_block(block_id, storage.clone())
+                    .unwrap();
+                protocol_command_sender
+            })
+            .await
+            .unwrap();
+
+            // 2. Ask for some of the chunks of its operations.
+            let indices: Vec<u16> = (0..BLOCK_ERASURE_DATA_CHUNKS).map(|i| i + 2).collect();
+            network_controller
+                .send_ask_for_block(
+                    node_a.id,
+                    vec![(block_id, AskForBlocksInfo::Chunks(indices.clone()))],
+                )
+                .await;
+
+            // 3. Check that the chunks rebuild the operations.
+            let chunks = match network_controller
+                .wait_command(1000.into(), |cmd| match cmd {
+                    NetworkCommand::SendBlockInfo { node, mut info } if node == node_a.id => {
+                        info.pop()
+                    }
+                    _ => None,
+                })
+                .await
+            {
+                Some((id, BlockInfoReply::Chunks(chunks))) if id == block_id => chunks,
+                _ => panic!("chunks not served"),
+            };
+            assert_eq!(
+                chunks.iter().map(|chunk| chunk.index).collect::<Vec<_>>(),
+                indices
+            );
+            let received: Vec<(usize, &[u8])> = chunks
+                .iter()
+                .map(|chunk| (chunk.index as usize, chunk.data.as_slice()))
+                .collect();
+            let data = ErasureCoder::new(
+                BLOCK_ERASURE_DATA_CHUNKS as usize,
+                BLOCK_ERASURE_TOTAL_CHUNKS as usize,
+            )
+            .unwrap()
+            .decode(&received, chunks[0].total_size as usize)
+            .unwrap();
+            let mut expected = Vec::new();
+            OperationsSerializer::new()
+                .serialize(&operations, &mut expected)
+                .unwrap();
+            assert_eq!(data, expected);
+
+            (
+                network_controller,
+                protocol_command_sender,
+                protocol_manager,
+                protocol_consensus_event_receiver,
+                protocol_pool_event_receiver,
+            )
+        },
+    )
+    .await;
+}
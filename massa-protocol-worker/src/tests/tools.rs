@@ -1,18 +1,18 @@
 use crate::start_protocol_controller;
 use futures::Future;
+use massa_channel::{channel_with_priority, ChannelConfig};
 use massa_consensus_exports::test_exports::{ConsensusEventReceiver, MockConsensusController};
 use massa_models::{
-    block::SecureShareBlock, block_id::BlockId, node::NodeId, operation::SecureShareOperation,
-    prehash::PreHashSet,
+    block::SecureShareBlock, block_id::BlockId, config::CapacityScale, node::NodeId,
+    operation::SecureShareOperation, prehash::PreHashSet,
 };
 use massa_network_exports::BlockInfoReply;
 use massa_pool_exports::test_exports::{MockPoolController, PoolEventReceiver};
 use massa_protocol_exports::{
-    tests::mock_network_controller::MockNetworkController, ProtocolCommandSender, ProtocolConfig,
-    ProtocolManager, ProtocolReceivers, ProtocolSenders,
+    tests::mock_network_controller::MockNetworkController, ProtocolCommand, ProtocolCommandSender,
+    ProtocolConfig, ProtocolManager, ProtocolReceivers, ProtocolSenders,
 };
 use massa_storage::Storage;
-use tokio::sync::mpsc;
 
 pub async fn protocol_test<F, V>(protocol_config: &ProtocolConfig, test: F)
 where
@@ -40,8 +40,11 @@ where
     let (consensus_controller, consensus_event_receiver) =
         MockConsensusController::new_with_receiver();
     // start protocol controller
-    let (protocol_command_sender, protocol_command_receiver) =
-        mpsc::channel(protocol_config.controller_channel_size);
+    let (protocol_command_sender, protocol_command_receiver) = channel_with_priority(
+        "protocol_command",
+        ChannelConfig::new(protocol_config.controller_channel_size),
+        ProtocolCommand::priority,
+    );
     let protocol_receivers = ProtocolReceivers {
         network_event_receiver,
         protocol_command_receiver,
@@ -57,6 +60,7 @@ where
         consensus_controller,
         pool_controller,
         Storage::create_root(),
+        CapacityScale::default(),
     )
     .await
     .expect("could not start protocol controller");
@@ -110,8 +114,11 @@ where
         MockConsensusController::new_with_receiver();
     let storage = Storage::create_root();
     // start protocol controller
-    let (protocol_command_sender, protocol_command_receiver) =
-        mpsc::channel(protocol_config.controller_channel_size);
+    let (protocol_command_sender, protocol_command_receiver) = channel_with_priority(
+        "protocol_command",
+        ChannelConfig::new(protocol_config.controller_channel_size),
+        ProtocolCommand::priority,
+    );
 
     let protocol_senders = ProtocolSenders {
         network_command_sender: network_command_sender.clone(),
@@ -129,6 +136,7 @@ where
         consensus_controller,
         pool_controller,
         storage.clone(),
+        CapacityScale::default(),
     )
     .await
     .expect("could not start protocol controller");
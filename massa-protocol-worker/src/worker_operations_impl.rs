@@ -11,6 +11,7 @@
 use std::collections::VecDeque;
 
 use crate::protocol_worker::ProtocolWorker;
+use crate::sig_verification_stage::VerificationJob;
 use massa_logging::massa_trace;
 use massa_models::{
     node::NodeId,
@@ -19,7 +20,6 @@ use massa_models::{
 };
 use massa_protocol_exports::ProtocolError;
 use massa_time::TimeError;
-use std::pin::Pin;
 use tokio::time::{sleep_until, Instant, Sleep};
 use tracing::warn;
 
@@ -72,10 +72,28 @@ impl ProtocolWorker {
         // filter out the operations that we already know about
         op_batch.retain(|prefix| !self.checked_operations.contains_prefix(prefix));
 
-        let mut ask_set = OperationPrefixIds::with_capacity(op_batch.len());
-        let mut future_set = OperationPrefixIds::with_capacity(op_batch.len());
         // exactitude isn't important, we want to have a now for that function call
         let now = Instant::now();
+
+        // do not ask for more operations while the signature verification queue
+        // or the announcement buffer is full
+        if self.sig_verification_stage.is_saturated() || self.is_announcement_buffer_saturated() {
+            if self.op_batch_buffer.len() < self.config.operation_batch_buffer_capacity
+                && !op_batch.is_empty()
+            {
+                self.op_batch_buffer.push_back(OperationBatchItem {
+                    instant: now
+                        .checked_add(self.config.operation_batch_proc_period.into())
+                        .ok_or(TimeError::TimeOverflowError)?,
+                    node_id,
+                    operations_prefix_ids: op_batch,
+                });
+            }
+            return Ok(());
+        }
+
+        let mut ask_set = OperationPrefixIds::with_capacity(op_batch.len());
+        let mut future_set = OperationPrefixIds::with_capacity(op_batch.len());
         let mut count_reask = 0;
         for op_id in op_batch {
             let wish = match self.asked_operations.get_mut(&op_id) {
@@ -135,21 +153,31 @@ impl ProtocolWorker {
     }
 
     /// On full operations are received from the network,
-    /// - Update the cache `received_operations` ids and each
-    ///   `node_info.known_operations`
-    /// - Notify the operations to he local node, to be propagated
+    /// - Update each `node_info.known_operations`
+    /// - Queue the verification of the signatures of the new ones. Once verified,
+    ///   they are noted to the local node and propagated by `on_verification_result`
     pub(crate) async fn on_operations_received(
         &mut self,
         node_id: NodeId,
         operations: Vec<SecureShareOperation>,
-        op_timer: &mut Pin<&mut Sleep>,
     ) {
-        if let Err(err) = self
-            .note_operations_from_node(operations, &node_id, op_timer)
-            .await
-        {
-            warn!("node {} sent us critically incorrect operation, which may be an attack attempt by the remote node or a loss of sync between us and the remote node. Err = {}", node_id, err);
-            let _ = self.ban_node(&node_id).await;
+        let (received_ids, new_operations) = match self.filter_new_operations(operations) {
+            Ok(filtered) => filtered,
+            Err(err) => {
+                warn!("node {} sent us critically incorrect operation, which may be an attack attempt by the remote node or a loss of sync between us and the remote node. Err = {}", node_id, err);
+                let _ = self.ban_node(&node_id).await;
+                return;
+            }
+        };
+        if let Some(node_info) = self.active_nodes.get_mut(&node_id) {
+            node_info.insert_known_ops(received_ids.iter().map(|id| id.prefix()));
+        }
+        if !new_operations.is_empty() {
+            self.sig_verification_stage
+                .submit(VerificationJob::Operations {
+                    node: node_id,
+                    operations: new_operations.into_values().collect(),
+                });
         }
     }
 
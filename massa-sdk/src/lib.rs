@@ -14,17 +14,32 @@ use jsonrpsee::types::error::CallError;
 use jsonrpsee::types::ErrorObject;
 use jsonrpsee::ws_client::{HeaderMap, HeaderValue, WsClient, WsClientBuilder};
 use massa_api_exports::page::PagedVecV2;
-use massa_api_exports::ApiRequest;
 use massa_api_exports::{
-    address::AddressInfo,
-    block::{BlockInfo, BlockSummary},
-    datastore::{DatastoreEntryInput, DatastoreEntryOutput},
+    address::{
+        AddressDeferredCredits, AddressInfo, AddressStakingRewards, AddressStorageRent,
+        VerifiedContract,
+    },
+    async_pool::ScheduledTransferInfo,
+    block::{BlockFieldMask, BlockInfo, BlockSummary, GraphPage, GraphPageRequest},
+    datastore::{
+        DatastoreEntryChunkInput, DatastoreEntryChunkOutput, DatastoreEntryInput,
+        DatastoreEntryOutput,
+    },
     endorsement::EndorsementInfo,
-    execution::{ExecuteReadOnlyResponse, ReadOnlyBytecodeExecution, ReadOnlyCall},
-    node::NodeStatus,
+    execution::{
+        DryRunOperationResponse, ExecuteReadOnlyResponse, ReadOnlyBytecodeExecution, ReadOnlyCall,
+    },
+    node::{BuildInfo, NodeStatus},
     operation::{OperationInfo, OperationInput},
+    profiling::{CpuProfile, HeapStats},
+    selection::AddressSelectionForecast,
     TimeInterval,
 };
+use massa_api_exports::{ApiRequest, StakersFilter};
+use massa_channel::{ChannelStats, NodeEvent, Topic};
+use massa_final_state::BackupManifest;
+use massa_hash::Hash;
+use massa_logging::LogLine;
 use massa_models::{
     address::Address,
     block::FilledBlock,
@@ -38,11 +53,17 @@ use massa_models::{
     operation::{Operation, OperationId},
     output_event::SCOutputEvent,
     prehash::{PreHashMap, PreHashSet},
+    slot::Slot,
     version::Version,
 };
+use massa_network_exports::{BanEntry, IpSubnet};
+use massa_storage::StorageOwnersDump;
+use massa_time::MassaTime;
+use massa_versioning::MipStatus;
 
 use jsonrpsee::{core::Error as JsonRpseeError, core::RpcResult, http_client::HttpClientBuilder};
 use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
 use std::str::FromStr;
 
 mod config;
@@ -126,6 +147,29 @@ impl RpcClient {
             .await
     }
 
+    /// Resume the production of staking addresses disabled before.
+    /// No confirmation to expect.
+    pub async fn enable_staking_addresses(&self, addresses: Vec<Address>) -> RpcResult<()> {
+        self.http_client
+            .request("enable_staking_addresses", rpc_params![addresses])
+            .await
+    }
+
+    /// Pause the production of staking addresses, keeping their keys.
+    /// No confirmation to expect.
+    pub async fn disable_staking_addresses(&self, addresses: Vec<Address>) -> RpcResult<()> {
+        self.http_client
+            .request("disable_staking_addresses", rpc_params![addresses])
+            .await
+    }
+
+    /// Return hash-set of the staking addresses whose production is disabled.
+    pub async fn get_disabled_staking_addresses(&self) -> RpcResult<PreHashSet<Address>> {
+        self.http_client
+            .request("get_disabled_staking_addresses", rpc_params![])
+            .await
+    }
+
     /// Bans given ip address(es)
     /// No confirmation to expect.
     pub async fn node_ban_by_ip(&self, ips: Vec<IpAddr>) -> RpcResult<()> {
@@ -158,6 +202,111 @@ impl RpcClient {
             .await
     }
 
+    /// Bans a range of IP addresses for `duration`, permanently without duration
+    pub async fn node_ban_subnet(
+        &self,
+        subnet: IpSubnet,
+        duration: Option<MassaTime>,
+        reason: String,
+    ) -> RpcResult<()> {
+        self.http_client
+            .request("node_ban_subnet", rpc_params![subnet, duration, reason])
+            .await
+    }
+
+    /// Lifts the bans of given IP ranges
+    pub async fn node_unban_subnet(&self, subnets: Vec<IpSubnet>) -> RpcResult<()> {
+        self.http_client
+            .request("node_unban_subnet", rpc_params![subnets])
+            .await
+    }
+
+    /// Bans of IP ranges and addresses
+    pub async fn node_list_bans(&self) -> RpcResult<Vec<BanEntry>> {
+        self.http_client
+            .request("node_list_bans", rpc_params![])
+            .await
+    }
+
+    /// Objects in the node storage along with the modules referencing them
+    pub async fn get_storage_diagnostics(&self) -> RpcResult<StorageOwnersDump> {
+        self.http_client
+            .request("get_storage_diagnostics", rpc_params![])
+            .await
+    }
+
+    /// Capacity, load and lag of the channels between the workers of the node
+    pub async fn get_channel_stats(&self) -> RpcResult<Vec<ChannelStats>> {
+        self.http_client
+            .request("get_channel_stats", rpc_params![])
+            .await
+    }
+
+    /// Latest log lines of a level or of a more severe level
+    pub async fn get_recent_logs(&self, level: String, count: usize) -> RpcResult<Vec<LogLine>> {
+        self.http_client
+            .request("get_recent_logs", rpc_params![level, count])
+            .await
+    }
+
+    /// Start sampling the CPU stacks of the node
+    pub async fn node_start_profiling(&self, frequency: u32) -> RpcResult<()> {
+        self.http_client
+            .request("node_start_profiling", rpc_params![frequency])
+            .await
+    }
+
+    /// Stop the CPU profiling session of the node and get its profile
+    pub async fn node_stop_profiling(&self) -> RpcResult<CpuProfile> {
+        self.http_client
+            .request("node_stop_profiling", rpc_params![])
+            .await
+    }
+
+    /// Heap statistics of the node
+    pub async fn get_heap_stats(&self) -> RpcResult<HeapStats> {
+        self.http_client
+            .request("get_heap_stats", rpc_params![])
+            .await
+    }
+
+    /// Write a backup of the final state in a new directory of the node machine
+    pub async fn node_create_backup(&self, path: PathBuf) -> RpcResult<BackupManifest> {
+        self.http_client
+            .request("node_create_backup", rpc_params![path])
+            .await
+    }
+
+    /// Registers the source of contracts verified by the node operator
+    pub async fn node_add_verified_contracts(
+        &self,
+        contracts: Vec<VerifiedContract>,
+    ) -> RpcResult<()> {
+        self.http_client
+            .request("node_add_verified_contracts", rpc_params![contracts])
+            .await
+    }
+
+    /// Removes the verified contracts of given bytecode hashes
+    pub async fn node_remove_verified_contracts(
+        &self,
+        bytecode_hashes: Vec<Hash>,
+    ) -> RpcResult<()> {
+        self.http_client
+            .request(
+                "node_remove_verified_contracts",
+                rpc_params![bytecode_hashes],
+            )
+            .await
+    }
+
+    /// Contracts verified by the node operator
+    pub async fn node_verified_contracts(&self) -> RpcResult<Vec<VerifiedContract>> {
+        self.http_client
+            .request("node_verified_contracts", rpc_params![])
+            .await
+    }
+
     /// Returns node peers whitelist IP address(es).
     pub async fn node_peers_whitelist(&self) -> RpcResult<Vec<IpAddr>> {
         self.http_client
@@ -271,10 +420,15 @@ impl RpcClient {
             .await
     }
 
-    /// Returns block(s) information associated to a given list of block(s) ID(s)
-    pub async fn get_blocks(&self, block_ids: Vec<BlockId>) -> RpcResult<Vec<BlockInfo>> {
+    /// Returns block(s) information associated to a given list of block(s) ID(s),
+    /// optionally restricted to their headers or their statuses
+    pub async fn get_blocks(
+        &self,
+        block_ids: Vec<BlockId>,
+        fields: Option<BlockFieldMask>,
+    ) -> RpcResult<Vec<BlockInfo>> {
         self.http_client
-            .request("get_blocks", rpc_params![block_ids])
+            .request("get_blocks", rpc_params![block_ids, fields])
             .await
     }
 
@@ -290,7 +444,7 @@ impl RpcClient {
 
     /// Get the block graph within the specified time interval.
     /// Optional parameters: from `<time_start>` (included) and to `<time_end>` (excluded) millisecond timestamp
-    pub(crate) async fn _get_graph_interval(
+    pub async fn get_graph_interval(
         &self,
         time_interval: TimeInterval,
     ) -> RpcResult<Vec<BlockSummary>> {
@@ -299,6 +453,14 @@ impl RpcClient {
             .await
     }
 
+    /// Get a page of the block graph within the specified time interval, sorted by slot then id.
+    /// The following page is requested with the `next_page_token` of the response.
+    pub async fn get_graph_page(&self, request: GraphPageRequest) -> RpcResult<GraphPage> {
+        self.http_client
+            .request("get_graph_page", rpc_params![request])
+            .await
+    }
+
     /// Get info by addresses
     pub async fn get_addresses(&self, addresses: Vec<Address>) -> RpcResult<Vec<AddressInfo>> {
         self.http_client
@@ -306,6 +468,51 @@ impl RpcClient {
             .await
     }
 
+    /// Get the upcoming selections of addresses, with production estimates
+    /// for the cycles that are not drawn yet
+    pub async fn get_selection_forecast(
+        &self,
+        addresses: Vec<Address>,
+        cycle_count: u64,
+    ) -> RpcResult<Vec<AddressSelectionForecast>> {
+        self.http_client
+            .request(
+                "get_selection_forecast",
+                rpc_params![addresses, cycle_count],
+            )
+            .await
+    }
+
+    /// Get the staking rewards credited to addresses, per cycle
+    pub async fn get_staking_rewards(
+        &self,
+        addresses: Vec<Address>,
+    ) -> RpcResult<Vec<AddressStakingRewards>> {
+        self.http_client
+            .request("get_staking_rewards", rpc_params![addresses])
+            .await
+    }
+
+    /// Get the storage rent of addresses, projected from the latest candidate slot
+    pub async fn get_storage_rent(
+        &self,
+        addresses: Vec<Address>,
+    ) -> RpcResult<Vec<AddressStorageRent>> {
+        self.http_client
+            .request("get_storage_rent", rpc_params![addresses])
+            .await
+    }
+
+    /// Get the pending scheduled transfers sent by addresses
+    pub async fn get_scheduled_transfers(
+        &self,
+        addresses: Vec<Address>,
+    ) -> RpcResult<Vec<ScheduledTransferInfo>> {
+        self.http_client
+            .request("get_scheduled_transfers", rpc_params![addresses])
+            .await
+    }
+
     /// Get datastore entries
     pub async fn get_datastore_entries(
         &self,
@@ -316,6 +523,16 @@ impl RpcClient {
             .await
     }
 
+    /// Get chunks of datastore entry values
+    pub async fn get_datastore_entry_chunks(
+        &self,
+        input: Vec<DatastoreEntryChunkInput>,
+    ) -> RpcResult<Vec<DatastoreEntryChunkOutput>> {
+        self.http_client
+            .request("get_datastore_entry_chunks", rpc_params![input])
+            .await
+    }
+
     // User (interaction with the node)
 
     /// Adds operations to pool. Returns operations that were ok and sent to pool.
@@ -328,6 +545,59 @@ impl RpcClient {
             .await
     }
 
+    /// Adds operations to pool without gossiping them until the private relay delay of the node is over.
+    /// Returns operations that were ok and sent to pool.
+    pub async fn send_private_operations(
+        &self,
+        operations: Vec<OperationInput>,
+    ) -> RpcResult<Vec<OperationId>> {
+        self.http_client
+            .request("send_private_operations", rpc_params![operations])
+            .await
+    }
+
+    /// Executes an operation against the candidate state without pooling nor propagating it
+    pub async fn dry_run_operation(
+        &self,
+        operation: OperationInput,
+    ) -> RpcResult<DryRunOperationResponse> {
+        self.http_client
+            .request("dry_run_operation", rpc_params![operation])
+            .await
+    }
+
+    /// Creates a temporary copy of the candidate state to simulate operations in
+    pub async fn create_execution_fork(&self, name: String) -> RpcResult<Slot> {
+        self.http_client
+            .request("create_execution_fork", rpc_params![name])
+            .await
+    }
+
+    /// Executes operations one after the other in an execution fork, keeping their changes in it
+    pub async fn execute_operations_in_fork(
+        &self,
+        name: String,
+        operations: Vec<OperationInput>,
+    ) -> RpcResult<Vec<ExecuteReadOnlyResponse>> {
+        self.http_client
+            .request("execute_operations_in_fork", rpc_params![name, operations])
+            .await
+    }
+
+    /// Discards an execution fork
+    pub async fn discard_execution_fork(&self, name: String) -> RpcResult<()> {
+        self.http_client
+            .request("discard_execution_fork", rpc_params![name])
+            .await
+    }
+
+    /// get the status of the MIPs known by the node
+    pub async fn get_mip_status(&self) -> RpcResult<Vec<MipStatus>> {
+        self.http_client
+            .request("get_mip_status", rpc_params![])
+            .await
+    }
+
     /// execute read only bytecode
     pub async fn execute_read_only_bytecode(
         &self,
@@ -431,14 +701,16 @@ impl RpcClientV2 {
     //
     // Experimental APIs. They might disappear, and they will change //
 
-    /// Get the active stakers and their active roll counts for the current cycle sorted by largest roll counts.
+    /// Get the active stakers and their active roll counts for the current cycle sorted by largest roll counts,
+    /// optionally filtered by a minimum roll count.
     pub async fn get_largest_stakers(
         &self,
         request: Option<ApiRequest>,
+        filter: Option<StakersFilter>,
     ) -> RpcResult<PagedVecV2<(BlockId, u64)>> {
         if let Some(client) = self.http_client.as_ref() {
             client
-                .request("get_largest_stakers", rpc_params![request])
+                .request("get_largest_stakers", rpc_params![request, filter])
                 .await
         } else {
             Err(JsonRpseeError::Custom(
@@ -460,6 +732,22 @@ impl RpcClientV2 {
         }
     }
 
+    /// Get the schedule of upcoming deferred credits (roll sale reimbursements) of the given addresses
+    pub async fn get_deferred_credits_schedule(
+        &self,
+        addresses: Vec<Address>,
+    ) -> RpcResult<Vec<AddressDeferredCredits>> {
+        if let Some(client) = self.http_client.as_ref() {
+            client
+                .request("get_deferred_credits_schedule", rpc_params![addresses])
+                .await
+        } else {
+            Err(JsonRpseeError::Custom(
+                "error, no Http client instance found".to_owned(),
+            ))
+        }
+    }
+
     /// Get Massa node version
     pub async fn get_version(&self) -> RpcResult<Version> {
         if let Some(client) = self.http_client.as_ref() {
@@ -471,6 +759,17 @@ impl RpcClientV2 {
         }
     }
 
+    /// Get the provenance of the node binary
+    pub async fn get_build_info(&self) -> RpcResult<BuildInfo> {
+        if let Some(client) = self.http_client.as_ref() {
+            client.request("get_build_info", rpc_params![]).await
+        } else {
+            Err(JsonRpseeError::Custom(
+                "error, no Http client instance found".to_owned(),
+            ))
+        }
+    }
+
     /// New produced blocks
     pub async fn subscribe_new_blocks(
         &self,
@@ -558,6 +857,29 @@ impl RpcClientV2 {
             .into())
         }
     }
+
+    /// Events of the node on `topics`, on all of them if empty
+    pub async fn subscribe_node_events(
+        &self,
+        topics: Vec<Topic>,
+    ) -> Result<Subscription<NodeEvent>, jsonrpsee::core::Error> {
+        if let Some(client) = self.ws_client.as_ref() {
+            client
+                .subscribe(
+                    "subscribe_node_events",
+                    rpc_params![topics],
+                    "unsubscribe_node_events",
+                )
+                .await
+        } else {
+            Err(CallError::Custom(ErrorObject::owned(
+                -32080,
+                "error, no WebSocket client instance found".to_owned(),
+                None::<()>,
+            ))
+            .into())
+        }
+    }
 }
 
 async fn http_client_from_url(url: &str, http_config: &HttpConfig) -> HttpClient {
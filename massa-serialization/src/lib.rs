@@ -1,5 +1,5 @@
 use std::{
-    collections::VecDeque,
+    collections::{BTreeMap, VecDeque},
     fmt::{Debug, Display},
 };
 
@@ -390,3 +390,195 @@ impl Deserializer<bool> for BoolDeserializer {
         })(buffer)
     }
 }
+
+/// Serializer writing the wire-format version before the data.
+///
+/// Each registered version has its own serializer so that a new format can be introduced
+/// while the previous ones remain available. If the formats of a type need different
+/// serializer types, `ST` is typically an enum of them.
+pub struct VersionedSerializer<T, ST>
+where
+    ST: Serializer<T>,
+{
+    version_serializer: U32VarIntSerializer,
+    formats: BTreeMap<u32, ST>,
+    phantom_t: std::marker::PhantomData<T>,
+}
+
+impl<T, ST> VersionedSerializer<T, ST>
+where
+    ST: Serializer<T>,
+{
+    /// Creates a serializer without any registered version
+    pub fn new() -> Self {
+        VersionedSerializer {
+            version_serializer: U32VarIntSerializer::new(),
+            formats: BTreeMap::new(),
+            phantom_t: std::marker::PhantomData,
+        }
+    }
+
+    /// Registers the serializer of a version, replacing the previous one if any
+    pub fn register(&mut self, version: u32, serializer: ST) {
+        self.formats.insert(version, serializer);
+    }
+
+    /// Registered versions, in increasing order
+    pub fn versions(&self) -> impl Iterator<Item = u32> + '_ {
+        self.formats.keys().copied()
+    }
+
+    /// Serializes `value` with the format of `version`, prefixed by the version
+    pub fn serialize_with_version(
+        &self,
+        version: u32,
+        value: &T,
+        buffer: &mut Vec<u8>,
+    ) -> Result<(), SerializeError> {
+        let serializer = self.formats.get(&version).ok_or_else(|| {
+            SerializeError::GeneralError(format!("unknown wire-format version {}", version))
+        })?;
+        self.version_serializer.serialize(&version, buffer)?;
+        serializer.serialize(value, buffer)
+    }
+}
+
+impl<T, ST> Default for VersionedSerializer<T, ST>
+where
+    ST: Serializer<T>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Deserializer reading the wire-format version before the data,
+/// and parsing the data with the deserializer registered for that version.
+/// Returns the version along with the data.
+pub struct VersionedDeserializer<T, DT>
+where
+    DT: Deserializer<T>,
+{
+    version_deserializer: U32VarIntDeserializer,
+    formats: BTreeMap<u32, DT>,
+    phantom_t: std::marker::PhantomData<T>,
+}
+
+impl<T, DT> VersionedDeserializer<T, DT>
+where
+    DT: Deserializer<T>,
+{
+    /// Creates a deserializer without any registered version
+    pub fn new() -> Self {
+        VersionedDeserializer {
+            version_deserializer: U32VarIntDeserializer::new(
+                std::ops::Bound::Included(0),
+                std::ops::Bound::Included(u32::MAX),
+            ),
+            formats: BTreeMap::new(),
+            phantom_t: std::marker::PhantomData,
+        }
+    }
+
+    /// Registers the deserializer of a version, replacing the previous one if any
+    pub fn register(&mut self, version: u32, deserializer: DT) {
+        self.formats.insert(version, deserializer);
+    }
+
+    /// Registered versions, in increasing order
+    pub fn versions(&self) -> impl Iterator<Item = u32> + '_ {
+        self.formats.keys().copied()
+    }
+}
+
+impl<T, DT> Default for VersionedDeserializer<T, DT>
+where
+    DT: Deserializer<T>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, DT> Deserializer<(u32, T)> for VersionedDeserializer<T, DT>
+where
+    DT: Deserializer<T>,
+{
+    fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], (u32, T), E> {
+        context("Failed versioned deserialization", |input: &'a [u8]| {
+            let (rest, version) = context("Failed version deserialization", |input| {
+                self.version_deserializer.deserialize(input)
+            })
+            .parse(input)?;
+            let Some(deserializer) = self.formats.get(&version) else {
+                return Err(nom::Err::Error(ParseError::from_error_kind(
+                    input,
+                    nom::error::ErrorKind::Fail,
+                )));
+            };
+            let (rest, data) = deserializer.deserialize(rest)?;
+            Ok((rest, (version, data)))
+        })(buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_versioned_serialization() {
+        let mut serializer = VersionedSerializer::new();
+        serializer.register(0, U32VarIntSerializer::new());
+        serializer.register(1, U32VarIntSerializer::new());
+        let mut deserializer = VersionedDeserializer::new();
+        deserializer.register(
+            0,
+            U32VarIntDeserializer::new(std::ops::Bound::Included(0), std::ops::Bound::Included(10)),
+        );
+        deserializer.register(
+            1,
+            U32VarIntDeserializer::new(
+                std::ops::Bound::Included(0),
+                std::ops::Bound::Included(1000),
+            ),
+        );
+
+        let mut buffer = Vec::new();
+        serializer
+            .serialize_with_version(1, &500, &mut buffer)
+            .unwrap();
+        let (rest, (version, value)) = deserializer
+            .deserialize::<DeserializeError>(&buffer)
+            .unwrap();
+        assert!(rest.is_empty());
+        assert_eq!((version, value), (1, 500));
+
+        // the same value is out of the bounds of the older format
+        let mut buffer = Vec::new();
+        serializer
+            .serialize_with_version(0, &500, &mut buffer)
+            .unwrap();
+        assert!(deserializer
+            .deserialize::<DeserializeError>(&buffer)
+            .is_err());
+
+        // unknown versions are rejected on both sides
+        assert!(serializer
+            .serialize_with_version(2, &5, &mut Vec::new())
+            .is_err());
+        let mut buffer = Vec::new();
+        U32VarIntSerializer::new()
+            .serialize(&2, &mut buffer)
+            .unwrap();
+        U32VarIntSerializer::new()
+            .serialize(&5, &mut buffer)
+            .unwrap();
+        assert!(deserializer
+            .deserialize::<DeserializeError>(&buffer)
+            .is_err());
+    }
+}
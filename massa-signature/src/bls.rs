@@ -0,0 +1,503 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! BLS signatures on the BLS12-381 curve.
+//!
+//! Unlike ed25519 signatures, BLS signatures aggregate: the signatures of many keys can be combined into a single
+//! signature of the size of one, verified at once against their public keys. This is the groundwork for aggregated
+//! endorsements and compact finality proofs for light clients.
+//!
+//! Public keys are in G1 (48 bytes) and signatures in G2 (96 bytes), with the proof of possession ciphersuite:
+//! the aggregated verification of a same message is only safe for keys whose proof of possession was checked
+//! when they were registered, which prevents rogue key attacks.
+
+use crate::error::MassaSignatureError;
+use crate::KeyPair;
+use blst::min_pk;
+use blst::BLST_ERROR;
+use massa_hash::Hash;
+use nom::{
+    error::{ContextError, ParseError},
+    IResult,
+};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::convert::TryInto;
+use std::str::FromStr;
+
+/// Size of a BLS secret key
+pub const BLS_SECRET_KEY_SIZE_BYTES: usize = 32;
+/// Size of a BLS public key
+pub const BLS_PUBLIC_KEY_SIZE_BYTES: usize = 48;
+/// Size of a BLS signature
+pub const BLS_SIGNATURE_SIZE_BYTES: usize = 96;
+
+/// Domain separation tag of the signatures
+const SIGNATURE_DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+/// Domain separation tag of the proofs of possession
+const POSSESSION_DST: &[u8] = b"BLS_POP_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+/// Information mixed in the derivation of the BLS keypairs from the ed25519 ones
+const KEY_DERIVATION_INFO: &[u8] = b"massa staking BLS key";
+/// Number of random bits of the scalars of a batch verification
+const BATCH_RANDOM_BITS: usize = 64;
+
+/// Converts the result of a blst verification
+fn check(result: BLST_ERROR, what: &str) -> Result<(), MassaSignatureError> {
+    match result {
+        BLST_ERROR::BLST_SUCCESS => Ok(()),
+        err => Err(MassaSignatureError::SignatureError(format!(
+            "{} failed: {:?}",
+            what, err
+        ))),
+    }
+}
+
+/// BLS keypair
+#[derive(Clone)]
+pub struct BlsKeyPair(min_pk::SecretKey);
+
+impl std::fmt::Debug for BlsKeyPair {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "BlsKeyPair({})", self.get_public_key())
+    }
+}
+
+impl BlsKeyPair {
+    /// Generates a new BLS keypair
+    pub fn generate() -> Self {
+        let mut ikm = [0u8; 32];
+        OsRng.fill_bytes(&mut ikm);
+        // never fails with 32 bytes of key material
+        BlsKeyPair(min_pk::SecretKey::key_gen(&ikm, &[]).expect("BLS key generation failed"))
+    }
+
+    /// Derives the BLS keypair of an ed25519 `keypair`, so that a staker does not hold any other secret.
+    /// The derivation is deterministic and one-way: the BLS public key tells nothing about the ed25519 secret key.
+    ///
+    /// # Example
+    /// ```
+    /// # use massa_signature::{BlsKeyPair, KeyPair};
+    /// let keypair = KeyPair::generate();
+    /// assert_eq!(
+    ///     BlsKeyPair::derive_from(&keypair).get_public_key(),
+    ///     BlsKeyPair::derive_from(&keypair).get_public_key()
+    /// );
+    /// ```
+    pub fn derive_from(keypair: &KeyPair) -> Self {
+        // never fails with 32 bytes of key material
+        BlsKeyPair(
+            min_pk::SecretKey::key_gen(keypair.to_bytes(), KEY_DERIVATION_INFO)
+                .expect("BLS key derivation failed"),
+        )
+    }
+
+    /// Serializes the secret key as bytes
+    pub fn to_bytes(&self) -> [u8; BLS_SECRET_KEY_SIZE_BYTES] {
+        self.0.to_bytes()
+    }
+
+    /// Deserializes a secret key from bytes
+    pub fn from_bytes(
+        data: &[u8; BLS_SECRET_KEY_SIZE_BYTES],
+    ) -> Result<BlsKeyPair, MassaSignatureError> {
+        min_pk::SecretKey::from_bytes(data)
+            .map(BlsKeyPair)
+            .map_err(|err| {
+                MassaSignatureError::ParsingError(format!(
+                    "BLS secret key parsing error: {:?}",
+                    err
+                ))
+            })
+    }
+
+    /// Public key of the keypair
+    pub fn get_public_key(&self) -> BlsPublicKey {
+        BlsPublicKey(self.0.sk_to_pk())
+    }
+
+    /// Signs `hash`
+    ///
+    /// # Example
+    /// ```
+    /// # use massa_signature::BlsKeyPair;
+    /// # use massa_hash::Hash;
+    /// let keypair = BlsKeyPair::generate();
+    /// let hash = Hash::compute_from("Hello World!".as_bytes());
+    /// let signature = keypair.sign(&hash);
+    /// assert!(keypair.get_public_key().verify_signature(&hash, &signature).is_ok());
+    /// ```
+    pub fn sign(&self, hash: &Hash) -> BlsSignature {
+        BlsSignature(self.0.sign(hash.to_bytes(), SIGNATURE_DST, &[]))
+    }
+
+    /// Proves the possession of the secret key, to be checked before aggregating signatures of the public key
+    pub fn prove_possession(&self) -> BlsSignature {
+        BlsSignature(
+            self.0
+                .sign(&self.get_public_key().to_bytes(), POSSESSION_DST, &[]),
+        )
+    }
+}
+
+/// BLS public key
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BlsPublicKey(min_pk::PublicKey);
+
+impl std::fmt::Display for BlsPublicKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            bs58::encode(self.to_bytes()).with_check().into_string()
+        )
+    }
+}
+
+impl FromStr for BlsPublicKey {
+    type Err = MassaSignatureError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = bs58::decode(s).with_check(None).into_vec().map_err(|err| {
+            MassaSignatureError::ParsingError(format!("BLS public key bs58 error: {}", err))
+        })?;
+        BlsPublicKey::from_bytes(&bytes.try_into().map_err(|_| {
+            MassaSignatureError::ParsingError(format!("bad BLS public key length: {}", s))
+        })?)
+    }
+}
+
+impl BlsPublicKey {
+    /// Serializes the public key as compressed bytes
+    pub fn to_bytes(&self) -> [u8; BLS_PUBLIC_KEY_SIZE_BYTES] {
+        self.0.compress()
+    }
+
+    /// Deserializes a public key from compressed bytes, refusing the invalid and infinity points
+    pub fn from_bytes(
+        data: &[u8; BLS_PUBLIC_KEY_SIZE_BYTES],
+    ) -> Result<BlsPublicKey, MassaSignatureError> {
+        min_pk::PublicKey::key_validate(data)
+            .map(BlsPublicKey)
+            .map_err(|err| {
+                MassaSignatureError::ParsingError(format!(
+                    "BLS public key parsing error: {:?}",
+                    err
+                ))
+            })
+    }
+
+    /// Checks that `signature` is the signature of `hash` by this key
+    pub fn verify_signature(
+        &self,
+        hash: &Hash,
+        signature: &BlsSignature,
+    ) -> Result<(), MassaSignatureError> {
+        check(
+            signature
+                .0
+                .verify(true, hash.to_bytes(), SIGNATURE_DST, &[], &self.0, false),
+            "BLS signature verification",
+        )
+    }
+
+    /// Checks the proof of possession of the secret key of this public key
+    pub fn verify_possession(&self, proof: &BlsSignature) -> Result<(), MassaSignatureError> {
+        check(
+            proof
+                .0
+                .verify(true, &self.to_bytes(), POSSESSION_DST, &[], &self.0, false),
+            "BLS proof of possession verification",
+        )
+    }
+}
+
+/// BLS signature, possibly aggregating several signatures
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BlsSignature(min_pk::Signature);
+
+impl std::fmt::Display for BlsSignature {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            bs58::encode(self.to_bytes()).with_check().into_string()
+        )
+    }
+}
+
+impl FromStr for BlsSignature {
+    type Err = MassaSignatureError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = bs58::decode(s).with_check(None).into_vec().map_err(|err| {
+            MassaSignatureError::ParsingError(format!("BLS signature bs58 error: {}", err))
+        })?;
+        BlsSignature::from_bytes(&bytes.try_into().map_err(|_| {
+            MassaSignatureError::ParsingError(format!("bad BLS signature length: {}", s))
+        })?)
+    }
+}
+
+impl BlsSignature {
+    /// Serializes the signature as compressed bytes
+    pub fn to_bytes(&self) -> [u8; BLS_SIGNATURE_SIZE_BYTES] {
+        self.0.compress()
+    }
+
+    /// Deserializes a signature from compressed bytes, refusing the invalid and infinity points
+    pub fn from_bytes(
+        data: &[u8; BLS_SIGNATURE_SIZE_BYTES],
+    ) -> Result<BlsSignature, MassaSignatureError> {
+        min_pk::Signature::sig_validate(data, true)
+            .map(BlsSignature)
+            .map_err(|err| {
+                MassaSignatureError::ParsingError(format!("BLS signature parsing error: {:?}", err))
+            })
+    }
+
+    /// Aggregates `signatures` into a single one
+    ///
+    /// # Example
+    /// ```
+    /// # use massa_signature::{BlsKeyPair, BlsSignature};
+    /// # use massa_hash::Hash;
+    /// let keypairs: Vec<BlsKeyPair> = (0..3).map(|_| BlsKeyPair::generate()).collect();
+    /// let hash = Hash::compute_from("Hello World!".as_bytes());
+    /// let signatures: Vec<BlsSignature> = keypairs.iter().map(|keypair| keypair.sign(&hash)).collect();
+    /// let aggregate = BlsSignature::aggregate(&signatures).unwrap();
+    /// let public_keys: Vec<_> = keypairs.iter().map(BlsKeyPair::get_public_key).collect();
+    /// assert!(aggregate.verify_aggregate(&hash, &public_keys).is_ok());
+    /// assert!(aggregate.verify_aggregate(&hash, &public_keys[1..]).is_err());
+    /// ```
+    pub fn aggregate(signatures: &[BlsSignature]) -> Result<BlsSignature, MassaSignatureError> {
+        if signatures.is_empty() {
+            return Err(MassaSignatureError::SignatureError(
+                "no BLS signature to aggregate".into(),
+            ));
+        }
+        let signatures: Vec<&min_pk::Signature> = signatures.iter().map(|sig| &sig.0).collect();
+        min_pk::AggregateSignature::aggregate(&signatures, false)
+            .map(|aggregate| BlsSignature(aggregate.to_signature()))
+            .map_err(|err| {
+                MassaSignatureError::SignatureError(format!(
+                    "BLS signature aggregation failed: {:?}",
+                    err
+                ))
+            })
+    }
+
+    /// Checks that this signature aggregates the signatures of `hash` by all the `public_keys`.
+    /// The proofs of possession of the keys must have been checked beforehand.
+    pub fn verify_aggregate(
+        &self,
+        hash: &Hash,
+        public_keys: &[BlsPublicKey],
+    ) -> Result<(), MassaSignatureError> {
+        if public_keys.is_empty() {
+            return Err(MassaSignatureError::SignatureError(
+                "no BLS public key to verify the aggregate signature".into(),
+            ));
+        }
+        let public_keys: Vec<&min_pk::PublicKey> = public_keys.iter().map(|pk| &pk.0).collect();
+        check(
+            self.0
+                .fast_aggregate_verify(true, hash.to_bytes(), SIGNATURE_DST, &public_keys),
+            "BLS aggregate signature verification",
+        )
+    }
+}
+
+impl Serialize for BlsPublicKey {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for BlsPublicKey {
+    fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<BlsPublicKey, D::Error> {
+        let s = String::deserialize(d)?;
+        BlsPublicKey::from_str(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+impl Serialize for BlsSignature {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for BlsSignature {
+    fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<BlsSignature, D::Error> {
+        let s = String::deserialize(d)?;
+        BlsSignature::from_str(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Verify a batch of BLS signatures of distinct messages at once.
+/// Every provided triplet `(hash, signature, public_key)` is verified
+/// and an error is returned if at least one of them fails.
+///
+/// The signatures are combined with random scalars, so that invalid signatures cannot compensate each other.
+pub fn verify_bls_signature_batch(
+    batch: &[(Hash, BlsSignature, BlsPublicKey)],
+) -> Result<(), MassaSignatureError> {
+    // nothing to verify
+    if batch.is_empty() {
+        return Ok(());
+    }
+
+    // normal verif is fastest for size 1 batches
+    if batch.len() == 1 {
+        let (hash, signature, public_key) = &batch[0];
+        return public_key.verify_signature(hash, signature);
+    }
+
+    let mut messages = Vec::with_capacity(batch.len());
+    let mut signatures = Vec::with_capacity(batch.len());
+    let mut public_keys = Vec::with_capacity(batch.len());
+    let mut scalars = Vec::with_capacity(batch.len());
+    for (hash, signature, public_key) in batch {
+        messages.push(hash.to_bytes().as_slice());
+        signatures.push(&signature.0);
+        public_keys.push(&public_key.0);
+        let mut scalar = blst::blst_scalar::default();
+        OsRng.fill_bytes(&mut scalar.b[..BATCH_RANDOM_BITS / 8]);
+        scalars.push(scalar);
+    }
+    check(
+        min_pk::Signature::verify_multiple_aggregate_signatures(
+            &messages,
+            SIGNATURE_DST,
+            &public_keys,
+            false,
+            &signatures,
+            true,
+            &scalars,
+            BATCH_RANDOM_BITS,
+        ),
+        "BLS batch signature verification",
+    )
+}
+
+/// Deserializer for `BlsPublicKey`
+#[derive(Default, Clone)]
+pub struct BlsPublicKeyDeserializer;
+
+impl BlsPublicKeyDeserializer {
+    /// Creates a `BlsPublicKeyDeserializer`
+    pub const fn new() -> Self {
+        Self
+    }
+}
+
+impl massa_serialization::Deserializer<BlsPublicKey> for BlsPublicKeyDeserializer {
+    fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], BlsPublicKey, E> {
+        deserialize_fixed(buffer, BlsPublicKey::from_bytes)
+    }
+}
+
+/// Deserializer for `BlsSignature`
+#[derive(Default, Clone)]
+pub struct BlsSignatureDeserializer;
+
+impl BlsSignatureDeserializer {
+    /// Creates a `BlsSignatureDeserializer`
+    pub const fn new() -> Self {
+        Self
+    }
+}
+
+impl massa_serialization::Deserializer<BlsSignature> for BlsSignatureDeserializer {
+    /// ```
+    /// use massa_signature::{BlsKeyPair, BlsSignatureDeserializer};
+    /// use massa_serialization::{DeserializeError, Deserializer};
+    /// use massa_hash::Hash;
+    ///
+    /// let signature = BlsKeyPair::generate().sign(&Hash::compute_from("Hello World!".as_bytes()));
+    /// let serialized = signature.to_bytes();
+    /// let (rest, deser_signature) = BlsSignatureDeserializer::new().deserialize::<DeserializeError>(&serialized).unwrap();
+    /// assert!(rest.is_empty());
+    /// assert_eq!(signature, deser_signature);
+    /// ```
+    fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], BlsSignature, E> {
+        deserialize_fixed(buffer, BlsSignature::from_bytes)
+    }
+}
+
+/// Parses a value of `N` bytes at the start of `buffer`
+fn deserialize_fixed<'a, T, E: ParseError<&'a [u8]>, const N: usize>(
+    buffer: &'a [u8],
+    from_bytes: impl Fn(&[u8; N]) -> Result<T, MassaSignatureError>,
+) -> IResult<&'a [u8], T, E> {
+    // Can't use try into directly because it fails if there is more data in the buffer
+    let bytes: &[u8; N] = match buffer.get(..N) {
+        Some(bytes) => bytes.try_into().unwrap(),
+        None => {
+            return Err(nom::Err::Error(ParseError::from_error_kind(
+                buffer,
+                nom::error::ErrorKind::LengthValue,
+            )))
+        }
+    };
+    let value = from_bytes(bytes).map_err(|_| {
+        nom::Err::Error(ParseError::from_error_kind(
+            buffer,
+            nom::error::ErrorKind::Fail,
+        ))
+    })?;
+    Ok((&buffer[N..], value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bls_serialization() {
+        let keypair = BlsKeyPair::generate();
+        let restored = BlsKeyPair::from_bytes(&keypair.to_bytes()).unwrap();
+        assert_eq!(keypair.get_public_key(), restored.get_public_key());
+
+        let public_key = keypair.get_public_key();
+        let signature = keypair.sign(&Hash::compute_from(b"data"));
+        assert_eq!(
+            BlsPublicKey::from_str(&public_key.to_string()).unwrap(),
+            public_key
+        );
+        let serialized = serde_json::to_string(&signature).unwrap();
+        assert_eq!(
+            serde_json::from_str::<BlsSignature>(&serialized).unwrap(),
+            signature
+        );
+        assert!(BlsSignature::from_bytes(&[0u8; BLS_SIGNATURE_SIZE_BYTES]).is_err());
+    }
+
+    #[test]
+    fn test_bls_possession_and_batch() {
+        let keypairs: Vec<BlsKeyPair> = (0..4).map(|_| BlsKeyPair::generate()).collect();
+        for keypair in &keypairs {
+            let public_key = keypair.get_public_key();
+            assert!(public_key
+                .verify_possession(&keypair.prove_possession())
+                .is_ok());
+            // a signature of the key bytes is not a proof of possession
+            let hash = Hash::compute_from(&public_key.to_bytes());
+            assert!(public_key.verify_possession(&keypair.sign(&hash)).is_err());
+        }
+
+        let mut batch: Vec<(Hash, BlsSignature, BlsPublicKey)> = keypairs
+            .iter()
+            .enumerate()
+            .map(|(index, keypair)| {
+                let hash = Hash::compute_from(&index.to_be_bytes());
+                (hash, keypair.sign(&hash), keypair.get_public_key())
+            })
+            .collect();
+        assert!(verify_bls_signature_batch(&batch).is_ok());
+        batch[2].0 = Hash::compute_from(b"other");
+        assert!(verify_bls_signature_batch(&batch).is_err());
+    }
+}
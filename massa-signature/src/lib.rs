@@ -3,11 +3,21 @@
 
 #![warn(missing_docs)]
 #![warn(unused_crate_dependencies)]
+#[cfg(feature = "bls")]
+mod bls;
 mod error;
 mod signature_impl;
+mod stats;
 
+#[cfg(feature = "bls")]
+pub use bls::{
+    verify_bls_signature_batch, BlsKeyPair, BlsPublicKey, BlsPublicKeyDeserializer, BlsSignature,
+    BlsSignatureDeserializer, BLS_PUBLIC_KEY_SIZE_BYTES, BLS_SECRET_KEY_SIZE_BYTES,
+    BLS_SIGNATURE_SIZE_BYTES,
+};
 pub use error::MassaSignatureError;
 pub use signature_impl::{
     verify_signature_batch, KeyPair, PublicKey, PublicKeyDeserializer, Signature,
     SignatureDeserializer, PUBLIC_KEY_SIZE_BYTES, SECRET_KEY_BYTES_SIZE, SIGNATURE_SIZE_BYTES,
 };
+pub use stats::{get_verification_stats, SignatureVerificationStats};
@@ -1,6 +1,7 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 use crate::error::MassaSignatureError;
+use crate::stats;
 use ed25519_dalek::{verify_batch, Signer, Verifier};
 use massa_hash::Hash;
 use massa_serialization::{
@@ -17,7 +18,7 @@ use serde::{
     Deserialize,
 };
 use std::{borrow::Cow, cmp::Ordering, hash::Hasher, ops::Bound::Included};
-use std::{convert::TryInto, str::FromStr};
+use std::{convert::TryInto, str::FromStr, time::Instant};
 
 /// Size of a public key
 pub const PUBLIC_KEY_SIZE_BYTES: usize = ed25519_dalek::PUBLIC_KEY_LENGTH;
@@ -408,9 +409,12 @@ impl PublicKey {
         hash: &Hash,
         signature: &Signature,
     ) -> Result<(), MassaSignatureError> {
-        self.0.verify(hash.to_bytes(), &signature.0).map_err(|err| {
+        let start = Instant::now();
+        let result = self.0.verify(hash.to_bytes(), &signature.0).map_err(|err| {
             MassaSignatureError::SignatureError(format!("Signature verification failed: {}", err))
-        })
+        });
+        stats::record_single(start.elapsed());
+        result
     }
 
     /// Serialize a `PublicKey` as bytes.
@@ -896,9 +900,16 @@ pub fn verify_signature_batch(
         signatures.push(signature.0);
         public_keys.push(public_key.0);
     });
-    verify_batch(&hashes, signatures.as_slice(), public_keys.as_slice()).map_err(|err| {
-        MassaSignatureError::SignatureError(format!("Batch signature verification failed: {}", err))
-    })
+    let start = Instant::now();
+    let result =
+        verify_batch(&hashes, signatures.as_slice(), public_keys.as_slice()).map_err(|err| {
+            MassaSignatureError::SignatureError(format!(
+                "Batch signature verification failed: {}",
+                err
+            ))
+        });
+    stats::record_batch(batch.len(), start.elapsed());
+    result
 }
 
 #[cfg(test)]
@@ -955,4 +966,29 @@ mod tests {
             serde_json::from_str(&serialized).expect("could not deserialize signature key");
         assert_eq!(signature, deserialized);
     }
+
+    #[test]
+    #[serial]
+    fn test_verify_signature_batch_stats() {
+        let batch: Vec<(Hash, Signature, PublicKey)> = (0..4u8)
+            .map(|i| {
+                let keypair = KeyPair::generate();
+                let hash = Hash::compute_from(&[i]);
+                (hash, keypair.sign(&hash).unwrap(), keypair.get_public_key())
+            })
+            .collect();
+        let before = crate::get_verification_stats();
+        verify_signature_batch(&batch).unwrap();
+        let after = crate::get_verification_stats();
+        assert_eq!(after.batch_count, before.batch_count + 1);
+        assert_eq!(
+            after.batch_signature_count,
+            before.batch_signature_count + 4
+        );
+
+        // a single wrong signature makes the whole batch fail
+        let mut wrong_batch = batch.clone();
+        wrong_batch[2].1 = batch[1].1;
+        assert!(verify_signature_batch(&wrong_batch).is_err());
+    }
 }
@@ -0,0 +1,79 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Process-wide signature verification counters
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+static SINGLE_COUNT: AtomicU64 = AtomicU64::new(0);
+static SINGLE_TIME_NANOS: AtomicU64 = AtomicU64::new(0);
+static BATCH_COUNT: AtomicU64 = AtomicU64::new(0);
+static BATCH_SIGNATURE_COUNT: AtomicU64 = AtomicU64::new(0);
+static BATCH_TIME_NANOS: AtomicU64 = AtomicU64::new(0);
+
+/// Signature verification statistics since the start of the process
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SignatureVerificationStats {
+    /// number of signatures verified one by one
+    pub single_count: u64,
+    /// total time spent verifying signatures one by one, in nanoseconds
+    pub single_time_nanos: u64,
+    /// number of batches verified
+    pub batch_count: u64,
+    /// number of signatures verified in batches
+    pub batch_signature_count: u64,
+    /// total time spent verifying batches, in nanoseconds
+    pub batch_time_nanos: u64,
+}
+
+impl SignatureVerificationStats {
+    /// Ratio between the average time to verify a signature alone and in a batch.
+    /// `None` until both kinds of verification happened.
+    pub fn batch_speedup(&self) -> Option<f64> {
+        if self.single_count == 0 || self.batch_signature_count == 0 || self.batch_time_nanos == 0 {
+            return None;
+        }
+        let single_avg = self.single_time_nanos as f64 / self.single_count as f64;
+        let batch_avg = self.batch_time_nanos as f64 / self.batch_signature_count as f64;
+        Some(single_avg / batch_avg)
+    }
+}
+
+impl std::fmt::Display for SignatureVerificationStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Signature verification stats:")?;
+        writeln!(f, "\tSingle verifications: {}", self.single_count)?;
+        writeln!(
+            f,
+            "\tBatch verifications: {} ({} signatures)",
+            self.batch_count, self.batch_signature_count
+        )?;
+        if let Some(speedup) = self.batch_speedup() {
+            writeln!(f, "\tBatch speedup: {:.2}x", speedup)?;
+        }
+        Ok(())
+    }
+}
+
+/// Get the signature verification statistics since the start of the process
+pub fn get_verification_stats() -> SignatureVerificationStats {
+    SignatureVerificationStats {
+        single_count: SINGLE_COUNT.load(Ordering::Relaxed),
+        single_time_nanos: SINGLE_TIME_NANOS.load(Ordering::Relaxed),
+        batch_count: BATCH_COUNT.load(Ordering::Relaxed),
+        batch_signature_count: BATCH_SIGNATURE_COUNT.load(Ordering::Relaxed),
+        batch_time_nanos: BATCH_TIME_NANOS.load(Ordering::Relaxed),
+    }
+}
+
+pub(crate) fn record_single(duration: Duration) {
+    SINGLE_COUNT.fetch_add(1, Ordering::Relaxed);
+    SINGLE_TIME_NANOS.fetch_add(duration.as_nanos() as u64, Ordering::Relaxed);
+}
+
+pub(crate) fn record_batch(signature_count: usize, duration: Duration) {
+    BATCH_COUNT.fetch_add(1, Ordering::Relaxed);
+    BATCH_SIGNATURE_COUNT.fetch_add(signature_count as u64, Ordering::Relaxed);
+    BATCH_TIME_NANOS.fetch_add(duration.as_nanos() as u64, Ordering::Relaxed);
+}
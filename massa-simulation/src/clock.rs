@@ -0,0 +1,28 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use massa_time::MassaTime;
+use std::sync::{Arc, Mutex};
+
+/// Virtual clock shared by the components of a simulation.
+/// Time only moves forward when the simulation advances it.
+#[derive(Debug, Clone)]
+pub struct VirtualClock(Arc<Mutex<MassaTime>>);
+
+impl VirtualClock {
+    /// Creates a clock starting at `start`
+    pub fn new(start: MassaTime) -> Self {
+        VirtualClock(Arc::new(Mutex::new(start)))
+    }
+
+    /// Current virtual time
+    pub fn now(&self) -> MassaTime {
+        *self.0.lock().expect("virtual clock lock poisoned")
+    }
+
+    /// Moves the clock forward by `duration` and returns the new virtual time
+    pub fn advance(&self, duration: MassaTime) -> MassaTime {
+        let mut now = self.0.lock().expect("virtual clock lock poisoned");
+        *now = now.saturating_add(duration);
+        *now
+    }
+}
@@ -0,0 +1,36 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use crate::network::LinkConditions;
+use massa_protocol_exports::{tests::tools::create_protocol_config, ProtocolConfig};
+use massa_time::MassaTime;
+use std::time::Duration;
+
+/// Simulation configuration
+#[derive(Debug, Clone)]
+pub struct SimulationConfig {
+    /// number of simulated nodes
+    pub node_count: usize,
+    /// seed of the random generator deciding node keys, latencies and message losses
+    pub seed: u64,
+    /// virtual time elapsed at each simulation step
+    pub step_duration: MassaTime,
+    /// real time given to the workers to process their inputs at each step
+    pub step_real_delay: Duration,
+    /// conditions applied to links that were not configured explicitly
+    pub link_conditions: LinkConditions,
+    /// configuration of the protocol worker of each node
+    pub protocol_config: ProtocolConfig,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        SimulationConfig {
+            node_count: 4,
+            seed: 0,
+            step_duration: MassaTime::from_millis(10),
+            step_real_delay: Duration::from_millis(5),
+            link_conditions: LinkConditions::default(),
+            protocol_config: create_protocol_config(),
+        }
+    }
+}
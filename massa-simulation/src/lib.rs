@@ -0,0 +1,27 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+//! Deterministic multi-node simulation.
+//!
+//! Wires several in-process protocol workers through a virtual network driven by a virtual clock.
+//! Latency, message loss and partitions are decided by a seeded random generator,
+//! so that a scenario replays identically from the same seed.
+//!
+//! Each simulated node runs a real protocol worker. Consensus and pool are replaced by
+//! lightweight relays that record what the node received and propagate it further,
+//! as the real components would.
+#![warn(missing_docs)]
+#![warn(unused_crate_dependencies)]
+
+mod clock;
+mod config;
+mod network;
+mod node;
+mod simulation;
+
+pub use clock::VirtualClock;
+pub use config::SimulationConfig;
+pub use network::{LinkConditions, NodeIndex, VirtualNetwork, VirtualNetworkStats};
+pub use node::NodeObservations;
+pub use simulation::Simulation;
+
+#[cfg(test)]
+mod tests;
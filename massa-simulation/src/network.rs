@@ -0,0 +1,179 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Virtual network carrying the events exchanged by simulated nodes.
+//!
+//! Every decision (latency, jitter, loss) is drawn from a seeded random generator
+//! in the order messages are sent, so the same sequence of messages gives the same deliveries.
+
+use massa_network_exports::NetworkEvent;
+use massa_time::MassaTime;
+use rand::{prelude::StdRng, Rng, SeedableRng};
+use std::collections::{BTreeMap, HashMap};
+
+/// Index of a node in the simulation
+pub type NodeIndex = usize;
+
+/// Conditions applied to the messages sent on a link
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinkConditions {
+    /// minimum time taken by a message to go through the link
+    pub latency: MassaTime,
+    /// maximum random delay added to the latency
+    pub jitter: MassaTime,
+    /// probability for a message to be lost, between 0 and 1
+    pub loss_rate: f64,
+}
+
+impl Default for LinkConditions {
+    fn default() -> Self {
+        LinkConditions {
+            latency: MassaTime::from_millis(50),
+            jitter: MassaTime::from_millis(0),
+            loss_rate: 0.0,
+        }
+    }
+}
+
+/// Counters of the virtual network
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct VirtualNetworkStats {
+    /// messages sent
+    pub sent: u64,
+    /// messages delivered to their destination
+    pub delivered: u64,
+    /// messages lost because of the link loss rate
+    pub lost: u64,
+    /// messages dropped because of a partition
+    pub partitioned: u64,
+}
+
+/// Message travelling on the virtual network
+struct InFlightMessage {
+    from: NodeIndex,
+    to: NodeIndex,
+    event: NetworkEvent,
+}
+
+/// Virtual network
+pub struct VirtualNetwork {
+    rng: StdRng,
+    default_conditions: LinkConditions,
+    /// conditions of the links configured explicitly, indexed by `(min index, max index)`
+    link_conditions: HashMap<(NodeIndex, NodeIndex), LinkConditions>,
+    /// partition group of each node, no partition if empty
+    partition_groups: HashMap<NodeIndex, usize>,
+    /// messages in flight, indexed by delivery time then send order
+    in_flight: BTreeMap<(MassaTime, u64), InFlightMessage>,
+    /// number of messages sent so far, used to order messages delivered at the same time
+    sequence: u64,
+    stats: VirtualNetworkStats,
+}
+
+impl VirtualNetwork {
+    /// Creates a virtual network
+    pub fn new(seed: u64, default_conditions: LinkConditions) -> Self {
+        VirtualNetwork {
+            rng: StdRng::seed_from_u64(seed),
+            default_conditions,
+            link_conditions: Default::default(),
+            partition_groups: Default::default(),
+            in_flight: Default::default(),
+            sequence: 0,
+            stats: Default::default(),
+        }
+    }
+
+    /// Sets the conditions of the link between two nodes, in both directions
+    pub fn set_link_conditions(&mut self, a: NodeIndex, b: NodeIndex, conditions: LinkConditions) {
+        self.link_conditions
+            .insert((a.min(b), a.max(b)), conditions);
+    }
+
+    /// Gets the conditions of the link between two nodes
+    pub fn get_link_conditions(&self, a: NodeIndex, b: NodeIndex) -> LinkConditions {
+        self.link_conditions
+            .get(&(a.min(b), a.max(b)))
+            .copied()
+            .unwrap_or(self.default_conditions)
+    }
+
+    /// Splits the network: nodes of different groups cannot communicate anymore.
+    /// Nodes that are not listed form an additional group.
+    /// Messages already in flight between groups are dropped when they arrive.
+    pub fn partition(&mut self, groups: &[Vec<NodeIndex>]) {
+        self.partition_groups = groups
+            .iter()
+            .enumerate()
+            .flat_map(|(group, nodes)| nodes.iter().map(move |node| (*node, group)))
+            .collect();
+    }
+
+    /// Removes the partition
+    pub fn heal(&mut self) {
+        self.partition_groups.clear();
+    }
+
+    /// Checks whether two nodes are on the same side of the partition
+    pub fn can_communicate(&self, a: NodeIndex, b: NodeIndex) -> bool {
+        if self.partition_groups.is_empty() {
+            return true;
+        }
+        let unlisted = usize::MAX;
+        self.partition_groups.get(&a).unwrap_or(&unlisted)
+            == self.partition_groups.get(&b).unwrap_or(&unlisted)
+    }
+
+    /// Sends an event from a node to another at virtual time `now`
+    pub fn send(&mut self, now: MassaTime, from: NodeIndex, to: NodeIndex, event: NetworkEvent) {
+        self.stats.sent += 1;
+        self.sequence += 1;
+        let conditions = self.get_link_conditions(from, to);
+        // always draw both values so that the random sequence does not depend on the conditions
+        let lost = self.rng.gen::<f64>() < conditions.loss_rate;
+        let jitter = self.rng.gen_range(0..=conditions.jitter.to_millis());
+        if lost {
+            self.stats.lost += 1;
+            return;
+        }
+        if !self.can_communicate(from, to) {
+            self.stats.partitioned += 1;
+            return;
+        }
+        let delivery_time = now
+            .saturating_add(conditions.latency)
+            .saturating_add(MassaTime::from_millis(jitter));
+        self.in_flight.insert(
+            (delivery_time, self.sequence),
+            InFlightMessage { from, to, event },
+        );
+    }
+
+    /// Removes the messages that arrive at or before `now`, in delivery order.
+    /// Returns `(from, to, event)` tuples.
+    pub fn pop_due(&mut self, now: MassaTime) -> Vec<(NodeIndex, NodeIndex, NetworkEvent)> {
+        let not_due = self
+            .in_flight
+            .split_off(&(now.saturating_add(MassaTime::from_millis(1)), 0));
+        let due = std::mem::replace(&mut self.in_flight, not_due);
+        let mut delivered = Vec::with_capacity(due.len());
+        for (_, msg) in due {
+            if self.can_communicate(msg.from, msg.to) {
+                self.stats.delivered += 1;
+                delivered.push((msg.from, msg.to, msg.event));
+            } else {
+                self.stats.partitioned += 1;
+            }
+        }
+        delivered
+    }
+
+    /// Number of messages in flight
+    pub fn in_flight_count(&self) -> usize {
+        self.in_flight.len()
+    }
+
+    /// Counters of the network
+    pub fn stats(&self) -> VirtualNetworkStats {
+        self.stats
+    }
+}
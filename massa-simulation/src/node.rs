@@ -0,0 +1,401 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Simulated node: a real protocol worker plugged on relays replacing consensus and pool.
+
+use massa_channel::{channel_with_priority, ChannelConfig, MassaReceiver, MassaSender};
+use massa_consensus_exports::{
+    block_graph_export::{BlockGraphExport, BlockGraphQuery, ExportBlockSummary},
+    bootstrapable_graph::BootstrapableGraph,
+    error::ConsensusError,
+    ConsensusController,
+};
+use massa_hash::Hash;
+use massa_models::{
+    block::BlockGraphStatus,
+    block_header::BlockHeader,
+    block_id::BlockId,
+    clique::Clique,
+    config::CapacityScale,
+    endorsement::EndorsementId,
+    endorsement_signature::EndorsementSignature,
+    node::NodeId,
+    operation::OperationId,
+    prehash::{PreHashMap, PreHashSet},
+    secure_share::SecureShare,
+    slot::Slot,
+    stats::{BytecodeRejectStats, ConsensusStats},
+    streaming_step::StreamingStep,
+};
+use massa_network_exports::{
+    NetworkCommand, NetworkCommandSender, NetworkEvent, NetworkEventReceiver,
+};
+use massa_pool_exports::PoolController;
+use massa_protocol_exports::{
+    ProtocolCommand, ProtocolCommandSender, ProtocolConfig, ProtocolManager, ProtocolReceivers,
+    ProtocolSenders,
+};
+use massa_protocol_worker::start_protocol_controller;
+use massa_signature::KeyPair;
+use massa_storage::Storage;
+use massa_time::MassaTime;
+use std::collections::BTreeMap;
+use std::sync::{mpsc as std_mpsc, Arc, Mutex, RwLock};
+
+/// What the protocol worker of a node handed to consensus
+pub(crate) enum ConsensusEvent {
+    /// a new block header was received
+    BlockHeader {
+        block_id: BlockId,
+        header: SecureShare<BlockHeader, BlockId>,
+    },
+    /// a full block was received
+    Block { block_id: BlockId, storage: Storage },
+    /// a block was detected as invalid
+    InvalidBlock { block_id: BlockId },
+}
+
+/// What the protocol worker of a node handed to pool
+pub(crate) enum PoolEvent {
+    /// operations were received
+    Operations(Storage),
+    /// endorsements were received
+    Endorsements(Storage),
+}
+
+/// Consensus replacement: forwards what it receives to the simulation
+/// and answers queries from the blocks it registered
+#[derive(Clone)]
+pub(crate) struct SimulatedConsensus {
+    event_tx: Arc<Mutex<std_mpsc::Sender<ConsensusEvent>>>,
+    blocks: Arc<RwLock<BTreeMap<Slot, BlockId>>>,
+}
+
+impl SimulatedConsensus {
+    fn send(&self, event: ConsensusEvent) {
+        // the simulation may have been dropped before the protocol worker
+        let _ = self
+            .event_tx
+            .lock()
+            .expect("simulated consensus lock poisoned")
+            .send(event);
+    }
+}
+
+impl ConsensusController for SimulatedConsensus {
+    fn get_block_graph_status(
+        &self,
+        _start_slot: Option<Slot>,
+        _end_slot: Option<Slot>,
+    ) -> Result<BlockGraphExport, ConsensusError> {
+        Err(ConsensusError::ContainerInconsistency(
+            "block graph is not available in simulation".into(),
+        ))
+    }
+
+    fn get_block_graph_summaries(
+        &self,
+        _query: BlockGraphQuery,
+    ) -> Result<Vec<ExportBlockSummary>, ConsensusError> {
+        Err(ConsensusError::ContainerInconsistency(
+            "block graph is not available in simulation".into(),
+        ))
+    }
+
+    fn get_block_statuses(&self, ids: &[BlockId]) -> Vec<BlockGraphStatus> {
+        let blocks = self
+            .blocks
+            .read()
+            .expect("simulated consensus lock poisoned");
+        ids.iter()
+            .map(|id| {
+                if blocks.values().any(|block_id| block_id == id) {
+                    BlockGraphStatus::ActiveInBlockclique
+                } else {
+                    BlockGraphStatus::NotFound
+                }
+            })
+            .collect()
+    }
+
+    fn get_cliques(&self) -> Vec<Clique> {
+        Vec::new()
+    }
+
+    fn get_bootstrap_part(
+        &self,
+        _cursor: StreamingStep<PreHashSet<BlockId>>,
+        _execution_cursor: StreamingStep<Slot>,
+    ) -> Result<
+        (
+            BootstrapableGraph,
+            PreHashSet<BlockId>,
+            StreamingStep<PreHashSet<BlockId>>,
+        ),
+        ConsensusError,
+    > {
+        Err(ConsensusError::ContainerInconsistency(
+            "bootstrap is not available in simulation".into(),
+        ))
+    }
+
+    fn get_stats(&self) -> Result<ConsensusStats, ConsensusError> {
+        Err(ConsensusError::ContainerInconsistency(
+            "stats are not available in simulation".into(),
+        ))
+    }
+
+    fn get_best_parents(&self) -> Vec<(BlockId, u64)> {
+        Vec::new()
+    }
+
+    fn get_blockclique_block_at_slot(&self, slot: Slot) -> Option<BlockId> {
+        self.blocks
+            .read()
+            .expect("simulated consensus lock poisoned")
+            .get(&slot)
+            .copied()
+    }
+
+    fn get_latest_blockclique_block_at_slot(&self, slot: Slot) -> BlockId {
+        self.blocks
+            .read()
+            .expect("simulated consensus lock poisoned")
+            .range(..=slot)
+            .rev()
+            .find(|(block_slot, _)| block_slot.thread == slot.thread)
+            .map(|(_, block_id)| *block_id)
+            .unwrap_or_else(|| {
+                BlockId(Hash::compute_from(
+                    format!("Genesis {}", slot.thread).as_bytes(),
+                ))
+            })
+    }
+
+    fn register_block(
+        &self,
+        block_id: BlockId,
+        slot: Slot,
+        block_storage: Storage,
+        _created: bool,
+    ) {
+        self.blocks
+            .write()
+            .expect("simulated consensus lock poisoned")
+            .insert(slot, block_id);
+        self.send(ConsensusEvent::Block {
+            block_id,
+            storage: block_storage,
+        });
+    }
+
+    fn register_block_header(
+        &self,
+        block_id: BlockId,
+        header: SecureShare<BlockHeader, BlockId>,
+        _source_node_id: NodeId,
+    ) {
+        self.send(ConsensusEvent::BlockHeader { block_id, header });
+    }
+
+    fn mark_invalid_block(&self, block_id: BlockId, _header: SecureShare<BlockHeader, BlockId>) {
+        self.send(ConsensusEvent::InvalidBlock { block_id });
+    }
+
+    fn clone_box(&self) -> Box<dyn ConsensusController> {
+        Box::new(self.clone())
+    }
+}
+
+/// Pool replacement: forwards what it receives to the simulation
+#[derive(Clone)]
+pub(crate) struct SimulatedPool {
+    event_tx: Arc<Mutex<std_mpsc::Sender<PoolEvent>>>,
+}
+
+impl SimulatedPool {
+    fn send(&self, event: PoolEvent) {
+        // the simulation may have been dropped before the protocol worker
+        let _ = self
+            .event_tx
+            .lock()
+            .expect("simulated pool lock poisoned")
+            .send(event);
+    }
+}
+
+impl PoolController for SimulatedPool {
+    fn add_operations(&mut self, ops: Storage) {
+        self.send(PoolEvent::Operations(ops));
+    }
+
+    fn add_private_operations(&mut self, ops: Storage) {
+        self.send(PoolEvent::Operations(ops));
+    }
+
+    fn remove_operations(&mut self, _ops: PreHashSet<OperationId>) {}
+
+    fn add_endorsements(&mut self, endorsements: Storage) {
+        self.send(PoolEvent::Endorsements(endorsements));
+    }
+
+    // the simulated nodes have no registered keys to check the signatures against: none are relayed
+    fn add_endorsement_signatures(
+        &mut self,
+        _signatures: Vec<EndorsementSignature>,
+    ) -> Vec<EndorsementSignature> {
+        Vec::new()
+    }
+
+    fn notify_final_cs_periods(&mut self, _final_cs_periods: &[u64]) {}
+
+    fn get_block_operations(&self, _slot: &Slot) -> (Vec<OperationId>, Storage) {
+        (Vec::new(), Storage::create_root())
+    }
+
+    fn get_block_endorsements(
+        &self,
+        _target_block: &BlockId,
+        _slot: &Slot,
+    ) -> (Vec<Option<EndorsementId>>, Storage) {
+        (Vec::new(), Storage::create_root())
+    }
+
+    fn get_block_endorsement_signatures(
+        &self,
+        _target_block: &BlockId,
+        _slot: &Slot,
+    ) -> Vec<EndorsementSignature> {
+        Vec::new()
+    }
+
+    fn get_endorsement_count(&self) -> usize {
+        0
+    }
+
+    fn get_operation_count(&self) -> usize {
+        0
+    }
+
+    fn get_bytecode_reject_stats(&self) -> BytecodeRejectStats {
+        BytecodeRejectStats::default()
+    }
+
+    fn contains_endorsements(&self, endorsements: &[EndorsementId]) -> Vec<bool> {
+        vec![false; endorsements.len()]
+    }
+
+    fn contains_operations(&self, operations: &[OperationId]) -> Vec<bool> {
+        vec![false; operations.len()]
+    }
+
+    fn clone_box(&self) -> Box<dyn PoolController> {
+        Box::new(self.clone())
+    }
+}
+
+/// What a node received, with the virtual time of reception
+#[derive(Debug, Default, Clone)]
+pub struct NodeObservations {
+    /// block headers handed to consensus
+    pub headers: PreHashMap<BlockId, MassaTime>,
+    /// full blocks handed to consensus
+    pub blocks: PreHashMap<BlockId, MassaTime>,
+    /// blocks marked as invalid
+    pub invalid_blocks: PreHashMap<BlockId, MassaTime>,
+    /// operations handed to pool
+    pub operations: PreHashMap<OperationId, MassaTime>,
+    /// endorsements handed to pool
+    pub endorsements: PreHashMap<EndorsementId, MassaTime>,
+    /// nodes banned by this node
+    pub banned_nodes: Vec<NodeId>,
+}
+
+/// A node of the simulation
+pub(crate) struct SimulatedNode {
+    pub id: NodeId,
+    pub network_event_tx: MassaSender<NetworkEvent>,
+    pub network_command_rx: MassaReceiver<NetworkCommand>,
+    pub protocol_command_sender: ProtocolCommandSender,
+    pub protocol_manager: ProtocolManager,
+    pub consensus_event_rx: std_mpsc::Receiver<ConsensusEvent>,
+    pub pool_event_rx: std_mpsc::Receiver<PoolEvent>,
+    pub storage: Storage,
+    pub observations: NodeObservations,
+}
+
+impl SimulatedNode {
+    /// Starts the protocol worker of a node
+    pub async fn start(keypair: &KeyPair, protocol_config: ProtocolConfig) -> Self {
+        let (network_command_tx, network_command_rx) = channel_with_priority(
+            "network_command",
+            ChannelConfig::new(protocol_config.controller_channel_size),
+            NetworkCommand::priority,
+        );
+        let (network_event_tx, network_event_rx) = channel_with_priority(
+            "network_event",
+            ChannelConfig::new(protocol_config.event_channel_size),
+            NetworkEvent::priority,
+        );
+        let (protocol_command_tx, protocol_command_rx) = channel_with_priority(
+            "protocol_command",
+            ChannelConfig::new(protocol_config.controller_channel_size),
+            ProtocolCommand::priority,
+        );
+        let (consensus_event_tx, consensus_event_rx) = std_mpsc::channel();
+        let (pool_event_tx, pool_event_rx) = std_mpsc::channel();
+        let storage = Storage::create_root();
+
+        let consensus = SimulatedConsensus {
+            event_tx: Arc::new(Mutex::new(consensus_event_tx)),
+            blocks: Default::default(),
+        };
+        let pool = SimulatedPool {
+            event_tx: Arc::new(Mutex::new(pool_event_tx)),
+        };
+        let protocol_manager = start_protocol_controller(
+            protocol_config,
+            ProtocolReceivers {
+                network_event_receiver: NetworkEventReceiver(network_event_rx),
+                protocol_command_receiver: protocol_command_rx,
+            },
+            ProtocolSenders {
+                network_command_sender: NetworkCommandSender(network_command_tx),
+            },
+            Box::new(consensus),
+            Box::new(pool),
+            storage.clone(),
+            CapacityScale::default(),
+        )
+        .await
+        .expect("could not start protocol controller of simulated node");
+
+        SimulatedNode {
+            id: NodeId::new(keypair.get_public_key()),
+            network_event_tx,
+            network_command_rx,
+            protocol_command_sender: ProtocolCommandSender(protocol_command_tx),
+            protocol_manager,
+            consensus_event_rx,
+            pool_event_rx,
+            storage,
+            observations: Default::default(),
+        }
+    }
+
+    /// Hands a network event to the protocol worker
+    pub async fn receive_event(&mut self, event: NetworkEvent) {
+        self.network_event_tx
+            .send(event)
+            .await
+            .expect("protocol worker of simulated node stopped");
+    }
+
+    /// Sends a command to the protocol worker
+    pub async fn send_protocol_command(&mut self, command: ProtocolCommand) {
+        self.protocol_command_sender
+            .0
+            .send(command)
+            .await
+            .expect("protocol worker of simulated node stopped");
+    }
+}
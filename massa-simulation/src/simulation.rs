@@ -0,0 +1,441 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use crate::{
+    clock::VirtualClock,
+    config::SimulationConfig,
+    network::{NodeIndex, VirtualNetwork},
+    node::{ConsensusEvent, NodeObservations, PoolEvent, SimulatedNode},
+};
+use massa_models::{
+    block::SecureShareBlock,
+    block_id::BlockId,
+    endorsement::SecureShareEndorsement,
+    node::NodeId,
+    operation::{OperationId, SecureShareOperation},
+    prehash::{PreHashMap, PreHashSet},
+};
+use massa_network_exports::{NetworkCommand, NetworkEvent};
+use massa_protocol_exports::ProtocolCommand;
+use massa_signature::{KeyPair, SECRET_KEY_BYTES_SIZE};
+use massa_time::MassaTime;
+use rand::{prelude::StdRng, Rng, SeedableRng};
+use std::collections::{BTreeSet, HashMap};
+use tracing::debug;
+
+/// Multi-node simulation
+pub struct Simulation {
+    cfg: SimulationConfig,
+    clock: VirtualClock,
+    network: VirtualNetwork,
+    nodes: Vec<SimulatedNode>,
+    node_indexes: HashMap<NodeId, NodeIndex>,
+    /// connected pairs of nodes, as `(min index, max index)`
+    connections: BTreeSet<(NodeIndex, NodeIndex)>,
+}
+
+impl Simulation {
+    /// Starts the nodes of the simulation and connects all of them together
+    pub async fn start(cfg: SimulationConfig) -> Self {
+        let mut rng = StdRng::seed_from_u64(cfg.seed);
+        let mut nodes = Vec::with_capacity(cfg.node_count);
+        for _ in 0..cfg.node_count {
+            let mut bytes = [0u8; SECRET_KEY_BYTES_SIZE];
+            rng.fill(&mut bytes);
+            let keypair =
+                KeyPair::from_bytes(&bytes).expect("could not create keypair from random bytes");
+            nodes.push(SimulatedNode::start(&keypair, cfg.protocol_config).await);
+        }
+        let node_indexes = nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| (node.id, index))
+            .collect();
+        let mut simulation = Simulation {
+            clock: VirtualClock::new(MassaTime::from_millis(0)),
+            network: VirtualNetwork::new(rng.gen(), cfg.link_conditions),
+            cfg,
+            nodes,
+            node_indexes,
+            connections: Default::default(),
+        };
+        for a in 0..simulation.nodes.len() {
+            for b in (a + 1)..simulation.nodes.len() {
+                simulation.connect(a, b).await;
+            }
+        }
+        simulation
+    }
+
+    /// Virtual clock of the simulation
+    pub fn clock(&self) -> &VirtualClock {
+        &self.clock
+    }
+
+    /// Virtual network of the simulation, to set link conditions and partitions
+    pub fn network(&mut self) -> &mut VirtualNetwork {
+        &mut self.network
+    }
+
+    /// Number of nodes
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Id of a node
+    pub fn node_id(&self, node: NodeIndex) -> NodeId {
+        self.nodes[node].id
+    }
+
+    /// What a node received so far
+    pub fn observations(&self, node: NodeIndex) -> &NodeObservations {
+        &self.nodes[node].observations
+    }
+
+    /// Connects two nodes
+    pub async fn connect(&mut self, a: NodeIndex, b: NodeIndex) {
+        if a == b || !self.connections.insert((a.min(b), a.max(b))) {
+            return;
+        }
+        let (id_a, id_b) = (self.nodes[a].id, self.nodes[b].id);
+        self.nodes[a]
+            .receive_event(NetworkEvent::NewConnection(id_b))
+            .await;
+        self.nodes[b]
+            .receive_event(NetworkEvent::NewConnection(id_a))
+            .await;
+    }
+
+    /// Disconnects two nodes
+    pub async fn disconnect(&mut self, a: NodeIndex, b: NodeIndex) {
+        if !self.connections.remove(&(a.min(b), a.max(b))) {
+            return;
+        }
+        let (id_a, id_b) = (self.nodes[a].id, self.nodes[b].id);
+        self.nodes[a]
+            .receive_event(NetworkEvent::ConnectionClosed(id_b))
+            .await;
+        self.nodes[b]
+            .receive_event(NetworkEvent::ConnectionClosed(id_a))
+            .await;
+    }
+
+    /// Makes a node integrate a block it produced and propagate it
+    pub async fn produce_block(
+        &mut self,
+        node: NodeIndex,
+        block: SecureShareBlock,
+        operations: Vec<SecureShareOperation>,
+    ) {
+        let now = self.clock.now();
+        let simulated = &mut self.nodes[node];
+        let block_id = block.id;
+        let mut storage = simulated.storage.clone_without_refs();
+        storage.store_operations(operations);
+        storage.store_block(block);
+        simulated.observations.headers.insert(block_id, now);
+        simulated.observations.blocks.insert(block_id, now);
+        simulated
+            .send_protocol_command(ProtocolCommand::IntegratedBlock { block_id, storage })
+            .await;
+    }
+
+    /// Makes a node propagate operations, as if they were added to its pool
+    pub async fn propagate_operations(
+        &mut self,
+        node: NodeIndex,
+        operations: Vec<SecureShareOperation>,
+    ) {
+        let now = self.clock.now();
+        let simulated = &mut self.nodes[node];
+        for op in &operations {
+            simulated.observations.operations.insert(op.id, now);
+        }
+        let mut storage = simulated.storage.clone_without_refs();
+        storage.store_operations(operations);
+        simulated
+            .send_protocol_command(ProtocolCommand::PropagateOperations(storage))
+            .await;
+    }
+
+    /// Makes a node propagate endorsements, as if they were added to its pool
+    pub async fn propagate_endorsements(
+        &mut self,
+        node: NodeIndex,
+        endorsements: Vec<SecureShareEndorsement>,
+    ) {
+        let now = self.clock.now();
+        let simulated = &mut self.nodes[node];
+        for endorsement in &endorsements {
+            simulated
+                .observations
+                .endorsements
+                .insert(endorsement.id, now);
+        }
+        let mut storage = simulated.storage.clone_without_refs();
+        storage.store_endorsements(endorsements);
+        simulated
+            .send_protocol_command(ProtocolCommand::PropagateEndorsements(storage))
+            .await;
+    }
+
+    /// Runs one step of the simulation:
+    /// advances the clock, routes the messages sent by the nodes,
+    /// delivers the messages that arrived and lets the workers process them.
+    pub async fn step(&mut self) {
+        let now = self.clock.advance(self.cfg.step_duration);
+
+        // nodes are always visited in index order to keep the sends ordered
+        for index in 0..self.nodes.len() {
+            while let Ok(command) = self.nodes[index].network_command_rx.try_recv() {
+                self.route_command(now, index, command).await;
+            }
+            self.relay_consensus_events(now, index).await;
+            self.relay_pool_events(now, index).await;
+        }
+
+        for (from, to, event) in self.network.pop_due(now) {
+            if self.connections.contains(&(from.min(to), from.max(to))) {
+                self.nodes[to].receive_event(event).await;
+            }
+        }
+
+        tokio::time::sleep(self.cfg.step_real_delay).await;
+    }
+
+    /// Runs the simulation for a virtual duration
+    pub async fn run_for(&mut self, duration: MassaTime) {
+        let end = self.clock.now().saturating_add(duration);
+        while self.clock.now() < end {
+            self.step().await;
+        }
+    }
+
+    /// Runs the simulation until `condition` holds or the virtual `timeout` elapses.
+    /// Returns true if the condition was met.
+    pub async fn run_until<F>(&mut self, timeout: MassaTime, condition: F) -> bool
+    where
+        F: Fn(&Simulation) -> bool,
+    {
+        let end = self.clock.now().saturating_add(timeout);
+        loop {
+            if condition(self) {
+                return true;
+            }
+            if self.clock.now() >= end {
+                return false;
+            }
+            self.step().await;
+        }
+    }
+
+    /// Checks whether a node received a full block
+    pub fn has_block(&self, node: NodeIndex, block_id: &BlockId) -> bool {
+        self.nodes[node].observations.blocks.contains_key(block_id)
+    }
+
+    /// Checks whether all the nodes received a full block
+    pub fn all_have_block(&self, block_id: &BlockId) -> bool {
+        (0..self.nodes.len()).all(|node| self.has_block(node, block_id))
+    }
+
+    /// Checks whether a node received an operation
+    pub fn has_operation(&self, node: NodeIndex, operation_id: &OperationId) -> bool {
+        self.nodes[node]
+            .observations
+            .operations
+            .contains_key(operation_id)
+    }
+
+    /// Stops all the nodes
+    pub async fn stop(self) {
+        for node in self.nodes {
+            node.protocol_manager
+                .stop()
+                .await
+                .expect("could not stop protocol worker of simulated node");
+        }
+    }
+
+    /// Turns a command sent by the protocol worker of a node into events for its peers
+    async fn route_command(&mut self, now: MassaTime, from: NodeIndex, command: NetworkCommand) {
+        let from_id = self.nodes[from].id;
+        let mut sends = Vec::new();
+        match command {
+            NetworkCommand::AskForBlocks { list } => {
+                for (node, list) in list {
+                    sends.push((
+                        node,
+                        NetworkEvent::AskedForBlocks {
+                            node: from_id,
+                            list,
+                        },
+                    ));
+                }
+            }
+            NetworkCommand::SendBlockInfo { node, info } => sends.push((
+                node,
+                NetworkEvent::ReceivedBlockInfo {
+                    node: from_id,
+                    info,
+                },
+            )),
+            NetworkCommand::SendBlockHeader { node, header } => sends.push((
+                node,
+                NetworkEvent::ReceivedBlockHeader {
+                    source_node_id: from_id,
+                    header,
+                },
+            )),
+            NetworkCommand::SendEndorsements { node, endorsements } => sends.push((
+                node,
+                NetworkEvent::ReceivedEndorsements {
+                    node: from_id,
+                    endorsements,
+                },
+            )),
+            NetworkCommand::SendEndorsementSignatures { node, signatures } => sends.push((
+                node,
+                NetworkEvent::ReceivedEndorsementSignatures {
+                    node: from_id,
+                    signatures,
+                },
+            )),
+            NetworkCommand::SendOperations { node, operations } => sends.push((
+                node,
+                NetworkEvent::ReceivedOperations {
+                    node: from_id,
+                    operations,
+                },
+            )),
+            NetworkCommand::SendOperationAnnouncements { to_node, batch } => sends.push((
+                to_node,
+                NetworkEvent::ReceivedOperationAnnouncements {
+                    node: from_id,
+                    operation_prefix_ids: batch,
+                },
+            )),
+            NetworkCommand::AskForOperations { to_node, wishlist } => sends.push((
+                to_node,
+                NetworkEvent::ReceiveAskForOperations {
+                    node: from_id,
+                    operation_prefix_ids: wishlist,
+                },
+            )),
+            NetworkCommand::NodeBanByIds(ids) => {
+                for id in ids {
+                    self.nodes[from].observations.banned_nodes.push(id);
+                    if let Some(banned) = self.node_indexes.get(&id).copied() {
+                        self.disconnect(from, banned).await;
+                    }
+                }
+            }
+            cmd => debug!("simulation: ignoring network command {:?}", cmd),
+        }
+        for (to_id, event) in sends {
+            match self.node_indexes.get(&to_id).copied() {
+                Some(to) if self.connections.contains(&(from.min(to), from.max(to))) => {
+                    self.network.send(now, from, to, event)
+                }
+                _ => debug!(
+                    "simulation: dropping message from {} to unknown or disconnected node {}",
+                    from_id, to_id
+                ),
+            }
+        }
+    }
+
+    /// Records what the protocol worker of a node handed to consensus,
+    /// and asks for or relays blocks as consensus would
+    async fn relay_consensus_events(&mut self, now: MassaTime, index: NodeIndex) {
+        let events: Vec<_> = self.nodes[index].consensus_event_rx.try_iter().collect();
+        let node = &mut self.nodes[index];
+        for event in events {
+            match event {
+                ConsensusEvent::BlockHeader { block_id, header } => {
+                    node.observations.headers.entry(block_id).or_insert(now);
+                    if !node.observations.blocks.contains_key(&block_id) {
+                        let mut new = PreHashMap::default();
+                        new.insert(block_id, Some(header));
+                        node.send_protocol_command(ProtocolCommand::WishlistDelta {
+                            new,
+                            remove: Default::default(),
+                        })
+                        .await;
+                    }
+                }
+                ConsensusEvent::Block { block_id, storage } => {
+                    if node.observations.blocks.contains_key(&block_id) {
+                        continue;
+                    }
+                    node.observations.headers.entry(block_id).or_insert(now);
+                    node.observations.blocks.insert(block_id, now);
+                    let mut remove = PreHashSet::default();
+                    remove.insert(block_id);
+                    node.send_protocol_command(ProtocolCommand::WishlistDelta {
+                        new: Default::default(),
+                        remove,
+                    })
+                    .await;
+                    node.send_protocol_command(ProtocolCommand::IntegratedBlock {
+                        block_id,
+                        storage,
+                    })
+                    .await;
+                }
+                ConsensusEvent::InvalidBlock { block_id } => {
+                    node.observations
+                        .invalid_blocks
+                        .entry(block_id)
+                        .or_insert(now);
+                }
+            }
+        }
+    }
+
+    /// Records what the protocol worker of a node handed to pool,
+    /// and propagates new items as pool would
+    async fn relay_pool_events(&mut self, now: MassaTime, index: NodeIndex) {
+        let events: Vec<_> = self.nodes[index].pool_event_rx.try_iter().collect();
+        let node = &mut self.nodes[index];
+        for event in events {
+            match event {
+                PoolEvent::Operations(mut storage) => {
+                    let new_ids: PreHashSet<OperationId> = storage
+                        .get_op_refs()
+                        .iter()
+                        .filter(|id| !node.observations.operations.contains_key(*id))
+                        .copied()
+                        .collect();
+                    if new_ids.is_empty() {
+                        continue;
+                    }
+                    for id in &new_ids {
+                        node.observations.operations.insert(*id, now);
+                    }
+                    let propagated =
+                        storage.split_off(&Default::default(), &new_ids, &Default::default());
+                    node.send_protocol_command(ProtocolCommand::PropagateOperations(propagated))
+                        .await;
+                }
+                PoolEvent::Endorsements(mut storage) => {
+                    let new_ids: PreHashSet<_> = storage
+                        .get_endorsement_refs()
+                        .iter()
+                        .filter(|id| !node.observations.endorsements.contains_key(*id))
+                        .copied()
+                        .collect();
+                    if new_ids.is_empty() {
+                        continue;
+                    }
+                    for id in &new_ids {
+                        node.observations.endorsements.insert(*id, now);
+                    }
+                    let propagated =
+                        storage.split_off(&Default::default(), &Default::default(), &new_ids);
+                    node.send_protocol_command(ProtocolCommand::PropagateEndorsements(propagated))
+                        .await;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,129 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use crate::{LinkConditions, Simulation, SimulationConfig, VirtualNetwork};
+use massa_models::node::NodeId;
+use massa_network_exports::NetworkEvent;
+use massa_protocol_exports::tests::tools::{create_block, create_operation_with_expire_period};
+use massa_signature::KeyPair;
+use massa_time::MassaTime;
+use serial_test::serial;
+
+#[test]
+fn test_virtual_network_is_deterministic() {
+    let run = |seed: u64| {
+        let mut network = VirtualNetwork::new(
+            seed,
+            LinkConditions {
+                latency: MassaTime::from_millis(10),
+                jitter: MassaTime::from_millis(50),
+                loss_rate: 0.3,
+            },
+        );
+        let node_id = NodeId::new(KeyPair::generate().get_public_key());
+        for i in 0..100 {
+            network.send(
+                MassaTime::from_millis(i),
+                0,
+                1,
+                NetworkEvent::NewConnection(node_id),
+            );
+        }
+        let mut deliveries = Vec::new();
+        for t in 0..200 {
+            for (from, to, _) in network.pop_due(MassaTime::from_millis(t)) {
+                deliveries.push((t, from, to));
+            }
+        }
+        (deliveries, network.stats())
+    };
+    let (deliveries, stats) = run(7);
+    assert_eq!(stats.sent, 100);
+    assert_eq!(stats.sent, stats.delivered + stats.lost);
+    assert!(stats.lost > 0);
+    assert_eq!((deliveries, stats), run(7));
+}
+
+#[test]
+fn test_virtual_network_partition() {
+    let mut network = VirtualNetwork::new(0, LinkConditions::default());
+    network.partition(&[vec![0, 1], vec![2]]);
+    assert!(network.can_communicate(0, 1));
+    assert!(!network.can_communicate(0, 2));
+    // unlisted nodes form their own group
+    assert!(network.can_communicate(3, 4));
+    assert!(!network.can_communicate(1, 3));
+
+    let node_id = NodeId::new(KeyPair::generate().get_public_key());
+    network.send(
+        MassaTime::from_millis(0),
+        0,
+        1,
+        NetworkEvent::NewConnection(node_id),
+    );
+    network.send(
+        MassaTime::from_millis(0),
+        0,
+        2,
+        NetworkEvent::NewConnection(node_id),
+    );
+    assert_eq!(network.in_flight_count(), 1);
+    // in flight messages crossing the partition are dropped on arrival
+    network.partition(&[vec![0], vec![1, 2]]);
+    assert!(network.pop_due(MassaTime::from_millis(1000)).is_empty());
+    assert_eq!(network.stats().partitioned, 2);
+    network.heal();
+    assert!(network.can_communicate(0, 2));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[serial]
+async fn test_block_propagates_to_all_nodes() {
+    let mut simulation = Simulation::start(SimulationConfig::default()).await;
+    let block = create_block(&KeyPair::generate());
+    let block_id = block.id;
+    simulation.produce_block(0, block, Vec::new()).await;
+    assert!(
+        simulation
+            .run_until(MassaTime::from_millis(5000), |sim| sim
+                .all_have_block(&block_id))
+            .await,
+        "block did not reach all nodes"
+    );
+    simulation.stop().await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[serial]
+async fn test_partition_stops_propagation() {
+    let mut simulation = Simulation::start(SimulationConfig::default()).await;
+    simulation.network().partition(&[vec![0, 1], vec![2, 3]]);
+
+    let block = create_block(&KeyPair::generate());
+    let block_id = block.id;
+    simulation.produce_block(0, block, Vec::new()).await;
+    assert!(
+        simulation
+            .run_until(MassaTime::from_millis(5000), |sim| sim
+                .has_block(1, &block_id))
+            .await,
+        "block did not reach the node on the same side of the partition"
+    );
+    simulation.run_for(MassaTime::from_millis(1000)).await;
+    assert!(!simulation.has_block(2, &block_id));
+    assert!(!simulation.has_block(3, &block_id));
+
+    // once healed, new items propagate again
+    simulation.network().heal();
+    let operation = create_operation_with_expire_period(&KeyPair::generate(), 10);
+    let operation_id = operation.id;
+    simulation.propagate_operations(1, vec![operation]).await;
+    assert!(
+        simulation
+            .run_until(MassaTime::from_millis(5000), |sim| {
+                (0..sim.node_count()).all(|node| sim.has_operation(node, &operation_id))
+            })
+            .await,
+        "operation did not reach all nodes after healing"
+    );
+    simulation.stop().await;
+}
@@ -0,0 +1,244 @@
+//! Copyright (c) 2022 MASSA LABS <info@massa.net>
+//!
+//! Reference ownership diagnostics.
+//!
+//! When the root `Storage` is created with `Storage::create_root_with_diagnostics`, every reference
+//! claimed on an object is attributed to the owner tag of the `Storage` instance holding it
+//! (see `Storage::set_owner`). This allows listing which modules keep which objects alive,
+//! and detecting owners whose reference count keeps growing.
+
+use massa_models::{
+    block_id::BlockId, endorsement::EndorsementId, operation::OperationId, prehash::PreHashMap,
+    prehash::PreHashed,
+};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::hash::Hash;
+use std::sync::{mpsc, Arc};
+use std::thread::JoinHandle;
+use std::time::Duration;
+use tracing::warn;
+
+/// Owner tag of the `Storage` instances that were not given one
+pub const UNTAGGED_OWNER: &str = "untagged";
+
+/// Number of references held by each owner on each object of a kind
+pub(crate) struct OwnerRefs<IdT: PreHashed>(PreHashMap<IdT, BTreeMap<&'static str, usize>>);
+
+impl<IdT: PreHashed> Default for OwnerRefs<IdT> {
+    fn default() -> Self {
+        OwnerRefs(Default::default())
+    }
+}
+
+impl<IdT: PreHashed + Hash + Eq + Copy + ToString> OwnerRefs<IdT> {
+    /// Notes a reference claimed by `owner`
+    pub(crate) fn claim(&mut self, id: IdT, owner: &'static str) {
+        *self.0.entry(id).or_default().entry(owner).or_default() += 1;
+    }
+
+    /// Notes a reference released by `owner`
+    pub(crate) fn release(&mut self, id: &IdT, owner: &'static str) {
+        let Some(owners) = self.0.get_mut(id) else {
+            return;
+        };
+        if let Some(count) = owners.get_mut(owner) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                owners.remove(owner);
+            }
+        }
+        if owners.is_empty() {
+            self.0.remove(id);
+        }
+    }
+
+    /// Notes a reference moved from an owner to another
+    pub(crate) fn transfer(&mut self, id: IdT, from: &'static str, to: &'static str) {
+        if from != to {
+            self.release(&id, from);
+            self.claim(id, to);
+        }
+    }
+
+    /// Number of objects on which each owner holds a reference
+    fn totals(&self) -> BTreeMap<&'static str, usize> {
+        let mut totals = BTreeMap::new();
+        for owners in self.0.values() {
+            for owner in owners.keys() {
+                *totals.entry(*owner).or_default() += 1;
+            }
+        }
+        totals
+    }
+
+    fn dump(&self) -> BTreeMap<String, BTreeMap<String, usize>> {
+        self.0
+            .iter()
+            .map(|(id, owners)| {
+                (
+                    id.to_string(),
+                    owners
+                        .iter()
+                        .map(|(owner, count)| (owner.to_string(), *count))
+                        .collect(),
+                )
+            })
+            .collect()
+    }
+}
+
+/// References held by each owner, shared by all the `Storage` instances of a root
+#[derive(Default)]
+pub(crate) struct OwnerTracker {
+    pub(crate) blocks: OwnerRefs<BlockId>,
+    pub(crate) operations: OwnerRefs<OperationId>,
+    pub(crate) endorsements: OwnerRefs<EndorsementId>,
+}
+
+impl OwnerTracker {
+    fn owner_totals(&self) -> BTreeMap<String, OwnerTotals> {
+        let mut totals: BTreeMap<String, OwnerTotals> = BTreeMap::new();
+        for (owner, count) in self.blocks.totals() {
+            totals.entry(owner.to_string()).or_default().blocks = count;
+        }
+        for (owner, count) in self.operations.totals() {
+            totals.entry(owner.to_string()).or_default().operations = count;
+        }
+        for (owner, count) in self.endorsements.totals() {
+            totals.entry(owner.to_string()).or_default().endorsements = count;
+        }
+        totals
+    }
+
+    pub(crate) fn dump(&self) -> StorageOwnersDump {
+        StorageOwnersDump {
+            owner_totals: self.owner_totals(),
+            blocks: self.blocks.dump(),
+            operations: self.operations.dump(),
+            endorsements: self.endorsements.dump(),
+        }
+    }
+}
+
+/// Number of objects on which an owner holds a reference
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OwnerTotals {
+    /// referenced blocks
+    pub blocks: usize,
+    /// referenced operations
+    pub operations: usize,
+    /// referenced endorsements
+    pub endorsements: usize,
+}
+
+/// Objects in storage along with the owners referencing them
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct StorageOwnersDump {
+    /// number of objects referenced by each owner
+    pub owner_totals: BTreeMap<String, OwnerTotals>,
+    /// number of references held by each owner, for each block
+    pub blocks: BTreeMap<String, BTreeMap<String, usize>>,
+    /// number of references held by each owner, for each operation
+    pub operations: BTreeMap<String, BTreeMap<String, usize>>,
+    /// number of references held by each owner, for each endorsement
+    pub endorsements: BTreeMap<String, BTreeMap<String, usize>>,
+}
+
+impl std::fmt::Display for StorageOwnersDump {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Storage owners:")?;
+        for (owner, totals) in &self.owner_totals {
+            writeln!(
+                f,
+                "\t{}: {} blocks, {} operations, {} endorsements",
+                owner, totals.blocks, totals.operations, totals.endorsements
+            )?;
+        }
+        for (kind, objects) in [
+            ("Blocks", &self.blocks),
+            ("Operations", &self.operations),
+            ("Endorsements", &self.endorsements),
+        ] {
+            writeln!(f, "{}:", kind)?;
+            for (id, owners) in objects {
+                let owners: Vec<String> = owners
+                    .iter()
+                    .map(|(owner, count)| format!("{} x{}", owner, count))
+                    .collect();
+                writeln!(f, "\t{}: {}", id, owners.join(", "))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Background thread warning about owners whose number of referenced objects keeps growing.
+/// Stops when dropped.
+pub struct LeakDetector {
+    stop_tx: Option<mpsc::Sender<()>>,
+    thread_handle: Option<JoinHandle<()>>,
+}
+
+impl LeakDetector {
+    /// Every `interval`, samples the number of objects referenced by each owner,
+    /// and warns when it grew at each of the last `window` samples.
+    pub(crate) fn start(
+        tracker: Arc<Mutex<OwnerTracker>>,
+        interval: Duration,
+        window: usize,
+    ) -> Self {
+        let window = window.max(1);
+        let (stop_tx, stop_rx) = mpsc::channel::<()>();
+        let thread_handle = std::thread::Builder::new()
+            .name("storage-leak-detector".into())
+            .spawn(move || {
+                let mut history: HashMap<(String, &'static str), VecDeque<usize>> =
+                    HashMap::new();
+                while let Err(mpsc::RecvTimeoutError::Timeout) = stop_rx.recv_timeout(interval) {
+                    let totals = tracker.lock().owner_totals();
+                    // owners that do not reference anything anymore are not growing
+                    history.retain(|(owner, _), _| totals.contains_key(owner));
+                    for (owner, owner_totals) in totals {
+                        for (kind, count) in [
+                            ("blocks", owner_totals.blocks),
+                            ("operations", owner_totals.operations),
+                            ("endorsements", owner_totals.endorsements),
+                        ] {
+                            let samples = history.entry((owner.clone(), kind)).or_default();
+                            samples.push_back(count);
+                            if samples.len() > window + 1 {
+                                samples.pop_front();
+                            }
+                            if samples.len() == window + 1
+                                && samples.iter().zip(samples.iter().skip(1)).all(|(a, b)| a < b) {
+                                warn!(
+                                    "storage: the number of {} referenced by {} grew at each of the last {} checks, now {}: possible reference leak",
+                                    kind, owner, window, count
+                                );
+                                // warn again only if it keeps growing for another window
+                                samples.clear();
+                                samples.push_back(count);
+                            }
+                        }
+                    }
+                }
+            })
+            .expect("failed to spawn storage leak detector thread");
+        LeakDetector {
+            stop_tx: Some(stop_tx),
+            thread_handle: Some(thread_handle),
+        }
+    }
+}
+
+impl Drop for LeakDetector {
+    fn drop(&mut self) {
+        // disconnecting the channel stops the thread
+        self.stop_tx = None;
+        if let Some(handle) = self.thread_handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
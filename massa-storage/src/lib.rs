@@ -11,6 +11,7 @@
 #![feature(map_try_insert)]
 
 mod block_indexes;
+mod diagnostics;
 mod endorsement_indexes;
 mod operation_indexes;
 
@@ -18,6 +19,8 @@ mod operation_indexes;
 mod tests;
 
 use block_indexes::BlockIndexes;
+pub use diagnostics::{LeakDetector, OwnerTotals, StorageOwnersDump, UNTAGGED_OWNER};
+use diagnostics::{OwnerRefs, OwnerTracker};
 use endorsement_indexes::EndorsementIndexes;
 use massa_models::prehash::{CapacityAllocator, PreHashMap, PreHashSet, PreHashed};
 use massa_models::secure_share::Id;
@@ -28,9 +31,10 @@ use massa_models::{
     operation::{OperationId, SecureShareOperation},
 };
 use operation_indexes::OperationIndexes;
-use parking_lot::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use parking_lot::{ArcMutexGuard, Mutex, RawMutex, RwLock, RwLockReadGuard, RwLockWriteGuard};
 use std::fmt::Debug;
 use std::hash::Hash;
+use std::time::Duration;
 use std::{collections::hash_map, sync::Arc};
 
 /// A storage system for objects (blocks, operations...), shared by various components.
@@ -55,6 +59,11 @@ pub struct Storage {
     local_used_ops: PreHashSet<OperationId>,
     /// locally used endorsement references
     local_used_endorsements: PreHashSet<EndorsementId>,
+
+    /// owner tag of the references held by this instance
+    owner: &'static str,
+    /// reference ownership diagnostics shared by all instances, `None` if disabled
+    owner_tracker: Option<Arc<Mutex<OwnerTracker>>>,
 }
 
 impl Debug for Storage {
@@ -69,10 +78,12 @@ impl Clone for Storage {
         let mut res = Self::clone_without_refs(self);
 
         // claim one more user of the op refs
+        let mut tracker = res.lock_owner_tracker();
         Storage::internal_claim_refs(
             &self.local_used_ops.clone(),
             &mut res.operation_owners.write(),
             &mut res.local_used_ops,
+            tracker.as_mut().map(|t| (&mut t.operations, res.owner)),
         );
 
         // claim one more user of the block refs
@@ -80,6 +91,7 @@ impl Clone for Storage {
             &self.local_used_blocks.clone(),
             &mut res.block_owners.write(),
             &mut res.local_used_blocks,
+            tracker.as_mut().map(|t| (&mut t.blocks, res.owner)),
         );
 
         // claim one more user of the endorsement refs
@@ -87,6 +99,7 @@ impl Clone for Storage {
             &self.local_used_endorsements.clone(),
             &mut res.endorsement_owners.write(),
             &mut res.local_used_endorsements,
+            tracker.as_mut().map(|t| (&mut t.endorsements, res.owner)),
         );
 
         res
@@ -109,9 +122,20 @@ impl Storage {
             local_used_blocks: Default::default(),
             local_used_ops: Default::default(),
             local_used_endorsements: Default::default(),
+            owner: UNTAGGED_OWNER,
+            owner_tracker: None,
         }
     }
 
+    /// Creates a new root `Storage` instance like `create_root`,
+    /// with reference ownership diagnostics enabled (see `set_owner` and `get_owners_dump`).
+    /// Diagnostics slow down reference updates and should only be enabled for debugging.
+    pub fn create_root_with_diagnostics() -> Storage {
+        let mut storage = Storage::create_root();
+        storage.owner_tracker = Some(Default::default());
+        storage
+    }
+
     /// Clones the object to a new one that has no references
     pub fn clone_without_refs(&self) -> Self {
         Self {
@@ -126,33 +150,102 @@ impl Storage {
             local_used_ops: Default::default(),
             local_used_blocks: Default::default(),
             local_used_endorsements: Default::default(),
+
+            owner: self.owner,
+            owner_tracker: self.owner_tracker.clone(),
+        }
+    }
+
+    /// Sets the owner tag of the references held by this instance,
+    /// and of the instances that will be cloned from it.
+    /// Only used for diagnostics.
+    pub fn set_owner(&mut self, owner: &'static str) {
+        if let Some(mut tracker) = self.lock_owner_tracker() {
+            for id in &self.local_used_blocks {
+                tracker.blocks.transfer(*id, self.owner, owner);
+            }
+            for id in &self.local_used_ops {
+                tracker.operations.transfer(*id, self.owner, owner);
+            }
+            for id in &self.local_used_endorsements {
+                tracker.endorsements.transfer(*id, self.owner, owner);
+            }
         }
+        self.owner = owner;
+    }
+
+    /// Returns this instance with the given owner tag, see `set_owner`
+    pub fn with_owner(mut self, owner: &'static str) -> Self {
+        self.set_owner(owner);
+        self
+    }
+
+    /// Gets the owner tag of the references held by this instance
+    pub fn get_owner(&self) -> &'static str {
+        self.owner
+    }
+
+    /// Lists the stored objects along with the owners referencing them.
+    /// Returns `None` if diagnostics are disabled.
+    pub fn get_owners_dump(&self) -> Option<StorageOwnersDump> {
+        self.owner_tracker
+            .as_ref()
+            .map(|tracker| tracker.lock().dump())
+    }
+
+    /// Starts a background check warning when the number of objects referenced by an owner
+    /// grew at each of the last `window` checks, done every `interval`.
+    /// Returns `None` if diagnostics are disabled.
+    pub fn start_leak_detector(&self, interval: Duration, window: usize) -> Option<LeakDetector> {
+        self.owner_tracker
+            .clone()
+            .map(|tracker| LeakDetector::start(tracker, interval, window))
+    }
+
+    fn lock_owner_tracker(&self) -> Option<ArcMutexGuard<RawMutex, OwnerTracker>> {
+        self.owner_tracker
+            .as_ref()
+            .map(|tracker| tracker.lock_arc())
     }
 
     /// Efficiently extends the current Storage by consuming the refs of another storage.
     pub fn extend(&mut self, mut other: Storage) {
         // Take ownership ot `other`'s references.
         // Objects owned by both require a counter decrement and are handled when `other` is dropped.
-        self.local_used_ops.extend(
-            &other
-                .local_used_ops
-                .drain_filter(|id| !self.local_used_ops.contains(id))
-                .collect::<Vec<_>>(),
-        );
-
-        self.local_used_blocks.extend(
-            &other
-                .local_used_blocks
-                .drain_filter(|id| !self.local_used_blocks.contains(id))
-                .collect::<Vec<_>>(),
-        );
-
-        self.local_used_endorsements.extend(
-            &other
-                .local_used_endorsements
-                .drain_filter(|id| !self.local_used_endorsements.contains(id))
-                .collect::<Vec<_>>(),
-        );
+        let mut tracker = self.lock_owner_tracker();
+
+        let ops = other
+            .local_used_ops
+            .drain_filter(|id| !self.local_used_ops.contains(id))
+            .collect::<Vec<_>>();
+        if let Some(tracker) = tracker.as_mut() {
+            for id in &ops {
+                tracker.operations.transfer(*id, other.owner, self.owner);
+            }
+        }
+        self.local_used_ops.extend(&ops);
+
+        let blocks = other
+            .local_used_blocks
+            .drain_filter(|id| !self.local_used_blocks.contains(id))
+            .collect::<Vec<_>>();
+        if let Some(tracker) = tracker.as_mut() {
+            for id in &blocks {
+                tracker.blocks.transfer(*id, other.owner, self.owner);
+            }
+        }
+        self.local_used_blocks.extend(&blocks);
+
+        let endorsements = other
+            .local_used_endorsements
+            .drain_filter(|id| !self.local_used_endorsements.contains(id))
+            .collect::<Vec<_>>();
+        if let Some(tracker) = tracker.as_mut() {
+            for id in &endorsements {
+                tracker.endorsements.transfer(*id, other.owner, self.owner);
+            }
+        }
+        self.local_used_endorsements.extend(&endorsements);
     }
 
     /// Efficiently splits off a subset of the reference ownership into a new Storage object.
@@ -199,15 +292,20 @@ impl Storage {
         res
     }
 
-    /// internal helper to locally claim a reference to an object
-    fn internal_claim_refs<IdT: Id + PartialEq + Eq + Hash + PreHashed + Copy>(
+    /// internal helper to locally claim a reference to an object,
+    /// attributing it to an owner in `tracked` if diagnostics are enabled
+    fn internal_claim_refs<IdT: Id + PartialEq + Eq + Hash + PreHashed + Copy + ToString>(
         ids: &PreHashSet<IdT>,
         owners: &mut RwLockWriteGuard<PreHashMap<IdT, usize>>,
         local_used_ids: &mut PreHashSet<IdT>,
+        mut tracked: Option<(&mut OwnerRefs<IdT>, &'static str)>,
     ) {
         for &id in ids {
             if local_used_ids.insert(id) {
                 owners.entry(id).and_modify(|v| *v += 1).or_insert(1);
+                if let Some((owner_refs, owner)) = tracked.as_mut() {
+                    owner_refs.claim(id, owner);
+                }
             }
         }
     }
@@ -226,13 +324,19 @@ impl Storage {
             return claimed;
         }
 
+        let mut tracker = self.lock_owner_tracker();
         let owners = &mut self.block_owners.write();
 
         // check that all IDs are owned
         claimed.extend(ids.iter().filter(|id| owners.contains_key(id)));
 
         // effectively add local ownership on the refs
-        Storage::internal_claim_refs(&claimed, owners, &mut self.local_used_blocks);
+        Storage::internal_claim_refs(
+            &claimed,
+            owners,
+            &mut self.local_used_blocks,
+            tracker.as_mut().map(|t| (&mut t.blocks, self.owner)),
+        );
 
         claimed
     }
@@ -242,6 +346,7 @@ impl Storage {
         if ids.is_empty() {
             return;
         }
+        let mut tracker = self.lock_owner_tracker();
         let mut owners = self.block_owners.write();
         let mut orphaned_ids = Vec::new();
         for id in ids {
@@ -249,6 +354,9 @@ impl Storage {
                 // the object was already not referenced locally
                 continue;
             }
+            if let Some(tracker) = tracker.as_mut() {
+                tracker.blocks.release(id, self.owner);
+            }
             match owners.entry(*id) {
                 hash_map::Entry::Occupied(mut occ) => {
                     let res_count = {
@@ -281,6 +389,7 @@ impl Storage {
     /// Note that this also claims a local reference to the block
     pub fn store_block(&mut self, block: SecureShareBlock) {
         let id = block.id;
+        let mut tracker = self.lock_owner_tracker();
         let mut owners = self.block_owners.write();
         let mut blocks = self.blocks.write();
         blocks.insert(block);
@@ -289,6 +398,7 @@ impl Storage {
             &vec![id].into_iter().collect(),
             &mut owners,
             &mut self.local_used_blocks,
+            tracker.as_mut().map(|t| (&mut t.blocks, self.owner)),
         );
     }
 
@@ -304,13 +414,19 @@ impl Storage {
             return claimed;
         }
 
+        let mut tracker = self.lock_owner_tracker();
         let owners = &mut self.operation_owners.write();
 
         // check that all IDs are owned
         claimed.extend(ids.iter().filter(|id| owners.contains_key(id)));
 
         // effectively add local ownership on the refs
-        Storage::internal_claim_refs(&claimed, owners, &mut self.local_used_ops);
+        Storage::internal_claim_refs(
+            &claimed,
+            owners,
+            &mut self.local_used_ops,
+            tracker.as_mut().map(|t| (&mut t.operations, self.owner)),
+        );
 
         claimed
     }
@@ -326,6 +442,7 @@ impl Storage {
         if ids.is_empty() {
             return;
         }
+        let mut tracker = self.lock_owner_tracker();
         let mut owners = self.operation_owners.write();
         let mut orphaned_ids = Vec::new();
         for id in ids {
@@ -333,6 +450,9 @@ impl Storage {
                 // the object was already not referenced locally
                 continue;
             }
+            if let Some(tracker) = tracker.as_mut() {
+                tracker.operations.release(id, self.owner);
+            }
             match owners.entry(*id) {
                 hash_map::Entry::Occupied(mut occ) => {
                     let res_count = {
@@ -367,13 +487,19 @@ impl Storage {
         if operations.is_empty() {
             return;
         }
+        let mut tracker = self.lock_owner_tracker();
         let mut owners = self.operation_owners.write();
         let mut op_store = self.operations.write();
         let ids: PreHashSet<OperationId> = operations.iter().map(|op| op.id).collect();
         for op in operations {
             op_store.insert(op);
         }
-        Storage::internal_claim_refs(&ids, &mut owners, &mut self.local_used_ops);
+        Storage::internal_claim_refs(
+            &ids,
+            &mut owners,
+            &mut self.local_used_ops,
+            tracker.as_mut().map(|t| (&mut t.operations, self.owner)),
+        );
     }
 
     /// Gets a read reference to the operations index
@@ -403,13 +529,19 @@ impl Storage {
             return claimed;
         }
 
+        let mut tracker = self.lock_owner_tracker();
         let owners = &mut self.endorsement_owners.write();
 
         // check that all IDs are owned
         claimed.extend(ids.iter().filter(|id| owners.contains_key(id)));
 
         // effectively add local ownership on the refs
-        Storage::internal_claim_refs(&claimed, owners, &mut self.local_used_endorsements);
+        Storage::internal_claim_refs(
+            &claimed,
+            owners,
+            &mut self.local_used_endorsements,
+            tracker.as_mut().map(|t| (&mut t.endorsements, self.owner)),
+        );
         claimed
     }
 
@@ -424,6 +556,7 @@ impl Storage {
         if ids.is_empty() {
             return;
         }
+        let mut tracker = self.lock_owner_tracker();
         let mut owners = self.endorsement_owners.write();
         let mut orphaned_ids = Vec::new();
         for id in ids {
@@ -431,6 +564,9 @@ impl Storage {
                 // the object was already not referenced locally
                 continue;
             }
+            if let Some(tracker) = tracker.as_mut() {
+                tracker.endorsements.release(id, self.owner);
+            }
             match owners.entry(*id) {
                 hash_map::Entry::Occupied(mut occ) => {
                     let res_count = {
@@ -465,13 +601,19 @@ impl Storage {
         if endorsements.is_empty() {
             return;
         }
+        let mut tracker = self.lock_owner_tracker();
         let mut owners = self.endorsement_owners.write();
         let mut endo_store = self.endorsements.write();
         let ids: PreHashSet<EndorsementId> = endorsements.iter().map(|op| op.id).collect();
         for endorsement in endorsements {
             endo_store.insert(endorsement);
         }
-        Storage::internal_claim_refs(&ids, &mut owners, &mut self.local_used_endorsements);
+        Storage::internal_claim_refs(
+            &ids,
+            &mut owners,
+            &mut self.local_used_endorsements,
+            tracker.as_mut().map(|t| (&mut t.endorsements, self.owner)),
+        );
     }
 }
 
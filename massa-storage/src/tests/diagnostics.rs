@@ -0,0 +1,70 @@
+use crate::{Storage, UNTAGGED_OWNER};
+use massa_factory_exports::test_exports::create_empty_block;
+use massa_models::{prehash::PreHashSet, slot::Slot};
+use massa_signature::KeyPair;
+
+#[test]
+fn test_diagnostics_disabled() {
+    let mut storage = Storage::create_root();
+    let block = create_empty_block(&KeyPair::generate(), &Slot::new(0, 0));
+    storage.store_block(block);
+    assert!(storage.get_owners_dump().is_none());
+    assert!(storage
+        .start_leak_detector(std::time::Duration::from_millis(10), 2)
+        .is_none());
+}
+
+#[test]
+fn test_owner_attribution() {
+    let mut root = Storage::create_root_with_diagnostics();
+    let mut pool = root.clone_without_refs().with_owner("pool");
+    let block = create_empty_block(&KeyPair::generate(), &Slot::new(0, 0));
+    let block_id = block.id;
+
+    root.store_block(block);
+    pool.claim_block_refs(&vec![block_id].into_iter().collect());
+    let consensus = pool.clone().with_owner("consensus");
+
+    let dump = root.get_owners_dump().unwrap();
+    let owners = dump.blocks.get(&block_id.to_string()).unwrap();
+    assert_eq!(owners.get(UNTAGGED_OWNER), Some(&1));
+    assert_eq!(owners.get("pool"), Some(&1));
+    assert_eq!(owners.get("consensus"), Some(&1));
+    assert_eq!(dump.owner_totals.get("consensus").unwrap().blocks, 1);
+
+    // dropped references are not listed anymore
+    drop(consensus);
+    let mut ids = PreHashSet::default();
+    ids.insert(block_id);
+    root.drop_block_refs(&ids);
+    let dump = root.get_owners_dump().unwrap();
+    let owners = dump.blocks.get(&block_id.to_string()).unwrap();
+    assert_eq!(owners.len(), 1);
+    assert_eq!(owners.get("pool"), Some(&1));
+
+    drop(pool);
+    assert!(root.get_owners_dump().unwrap().blocks.is_empty());
+}
+
+#[test]
+fn test_owner_transfer_on_extend() {
+    let root = Storage::create_root_with_diagnostics();
+    let mut protocol = root.clone_without_refs().with_owner("protocol");
+    let mut pool = root.clone_without_refs().with_owner("pool");
+    let block = create_empty_block(&KeyPair::generate(), &Slot::new(0, 0));
+    let block_id = block.id;
+
+    protocol.store_block(block);
+    pool.extend(protocol);
+
+    let dump = root.get_owners_dump().unwrap();
+    let owners = dump.blocks.get(&block_id.to_string()).unwrap();
+    assert_eq!(owners.len(), 1);
+    assert_eq!(owners.get("pool"), Some(&1));
+
+    pool.set_owner("consensus");
+    let dump = root.get_owners_dump().unwrap();
+    let owners = dump.blocks.get(&block_id.to_string()).unwrap();
+    assert_eq!(owners.len(), 1);
+    assert_eq!(owners.get("consensus"), Some(&1));
+}
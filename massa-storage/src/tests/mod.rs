@@ -1,3 +1,4 @@
 mod basic;
+mod diagnostics;
 mod indexes;
 mod references;
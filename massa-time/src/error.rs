@@ -13,4 +13,6 @@ pub enum TimeError {
     TimeOverflowError,
     /// Checked operation error : {0}
     CheckedOperationError(String),
+    /// Parsing error : {0}
+    ParseError(String),
 }
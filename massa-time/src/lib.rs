@@ -9,22 +9,37 @@ pub use error::TimeError;
 use massa_serialization::{Deserializer, Serializer, U64VarIntDeserializer, U64VarIntSerializer};
 use nom::error::{context, ContextError, ParseError};
 use nom::IResult;
-use serde::{Deserialize, Serialize};
+use serde::{de, Deserialize, Serialize};
 use std::fmt;
 use std::ops::Bound;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use std::{
     convert::{TryFrom, TryInto},
     str::FromStr,
 };
 use time::format_description::well_known::Rfc3339;
-use time::OffsetDateTime;
+use time::{OffsetDateTime, UtcOffset};
 
 /// Time structure used everywhere.
 /// milliseconds since 01/01/1970.
-#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+///
+/// It is serialized as a number of milliseconds. Human readable formats deserialize it from either a number
+/// of milliseconds or a string accepted by `from_str`, so that configuration files can hold `"2h30m"` or
+/// `"2023-03-22T10:45:00Z"`, while the other formats, such as bincode, only read the number of milliseconds.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize)]
 pub struct MassaTime(u64);
 
+/// Units of the human-friendly durations, the longest first
+const DURATION_UNITS: [(&str, u64); 5] = [
+    ("d", 24 * 60 * 60 * 1000),
+    ("h", 60 * 60 * 1000),
+    ("m", 60 * 1000),
+    ("s", 1000),
+    ("ms", 1),
+];
+
 /// Serializer for `MassaTime`
 pub struct MassaTimeSerializer {
     u64_serializer: U64VarIntSerializer,
@@ -170,7 +185,8 @@ impl From<MassaTime> for Duration {
 impl FromStr for MassaTime {
     type Err = crate::TimeError;
 
-    /// Conversion from `&str`.
+    /// Conversion from `&str`: a number of milliseconds, a duration as parsed by `parse_duration`
+    /// or an RFC3339 timestamp as parsed by `from_rfc3339`.
     ///
     /// ```
     /// # use massa_time::*;
@@ -179,11 +195,59 @@ impl FromStr for MassaTime {
     /// let time : MassaTime = MassaTime::from(42);
     ///
     /// assert_eq!(time, MassaTime::from_str(duration).unwrap());
+    /// assert_eq!(MassaTime::from_str("1m30s").unwrap(), MassaTime::from(90_000));
+    /// assert_eq!(MassaTime::from_str("2022-01-01T00:00:00Z").unwrap(), MassaTime::from(1_640_995_200_000));
+    /// assert!(MassaTime::from_str("soon").is_err());
     /// ```
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(MassaTime(
-            u64::from_str(s).map_err(|_| Self::Err::ConversionError)?,
-        ))
+        let s = s.trim();
+        if s.contains(['T', 't']) {
+            MassaTime::from_rfc3339(s)
+        } else {
+            MassaTime::parse_duration(s)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for MassaTime {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct MassaTimeVisitor;
+
+        impl<'de> de::Visitor<'de> for MassaTimeVisitor {
+            type Value = MassaTime;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a number of milliseconds, a duration or an RFC3339 timestamp")
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+                Ok(MassaTime(value))
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<Self::Value, E> {
+                u64::try_from(value)
+                    .map(MassaTime)
+                    .map_err(|_| E::invalid_value(de::Unexpected::Signed(value), &self))
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+                MassaTime::from_str(value).map_err(E::custom)
+            }
+
+            fn visit_newtype_struct<D: de::Deserializer<'de>>(
+                self,
+                deserializer: D,
+            ) -> Result<Self::Value, D::Error> {
+                u64::deserialize(deserializer).map(MassaTime)
+            }
+        }
+
+        // the formats that are not self-describing cannot guess the type of the value
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(MassaTimeVisitor)
+        } else {
+            deserializer.deserialize_newtype_struct("MassaTime", MassaTimeVisitor)
+        }
     }
 }
 
@@ -293,6 +357,34 @@ impl MassaTime {
         MassaTime(self.0.saturating_add(t.0))
     }
 
+    /// ```
+    /// # use massa_time::*;
+    /// let time : MassaTime = MassaTime::from(42);
+    /// assert_eq!(time.saturating_mul(7), MassaTime::from(42*7));
+    /// assert_eq!(time.saturating_mul(u64::MAX), MassaTime::from(u64::MAX));
+    /// ```
+    #[must_use]
+    pub fn saturating_mul(self, n: u64) -> Self {
+        MassaTime(self.0.saturating_mul(n))
+    }
+
+    /// Shifts the time by a signed number of milliseconds
+    /// ```
+    /// # use massa_time::*;
+    /// let time : MassaTime = MassaTime::from(42);
+    /// assert_eq!(time.saturating_shift(7), MassaTime::from(42+7));
+    /// assert_eq!(time.saturating_shift(-7), MassaTime::from(42-7));
+    /// assert_eq!(time.saturating_shift(-50), MassaTime::from(0));
+    /// ```
+    #[must_use]
+    pub fn saturating_shift(self, millis: i64) -> Self {
+        if millis >= 0 {
+            MassaTime(self.0.saturating_add(millis as u64))
+        } else {
+            MassaTime(self.0.saturating_sub(millis.unsigned_abs()))
+        }
+    }
+
     /// ```
     /// # use massa_time::*;
     /// let time_1 : MassaTime = MassaTime::from(42);
@@ -421,6 +513,107 @@ impl MassaTime {
         naive.format(&Rfc3339).unwrap()
     }
 
+    /// Formats the timestamp in RFC3339 in the time zone `offset_minutes` away from UTC,
+    /// with the milliseconds when there are some
+    /// ```
+    /// # use massa_time::*;
+    /// let massa_time : MassaTime = MassaTime::from(1_640_995_200_000);
+    /// assert_eq!(massa_time.to_rfc3339(0).unwrap(), "2022-01-01T00:00:00Z");
+    /// assert_eq!(massa_time.to_rfc3339(120).unwrap(), "2022-01-01T02:00:00+02:00");
+    /// assert_eq!(MassaTime::from(1_640_995_200_500).to_rfc3339(-90).unwrap(), "2021-12-31T22:30:00.5-01:30");
+    /// ```
+    pub fn to_rfc3339(self, offset_minutes: i16) -> Result<String, TimeError> {
+        let offset = UtcOffset::from_whole_seconds(offset_minutes as i32 * 60)
+            .map_err(|err| TimeError::ParseError(err.to_string()))?;
+        OffsetDateTime::from_unix_timestamp_nanos(self.0 as i128 * 1_000_000)
+            .map_err(|_| TimeError::TimeOverflowError)?
+            .to_offset(offset)
+            .format(&Rfc3339)
+            .map_err(|_| TimeError::TimeOverflowError)
+    }
+
+    /// Parses an RFC3339 timestamp in any time zone, truncated to the millisecond
+    /// ```
+    /// # use massa_time::*;
+    /// let massa_time : MassaTime = MassaTime::from(1_640_995_200_000);
+    /// assert_eq!(MassaTime::from_rfc3339("2022-01-01T00:00:00Z").unwrap(), massa_time);
+    /// assert_eq!(MassaTime::from_rfc3339("2022-01-01T02:00:00+02:00").unwrap(), massa_time);
+    /// assert_eq!(MassaTime::from_rfc3339("2022-01-01T00:00:00.042Z").unwrap(), MassaTime::from(1_640_995_200_042));
+    /// assert!(MassaTime::from_rfc3339("1969-12-31T23:59:59Z").is_err());
+    /// ```
+    pub fn from_rfc3339(s: &str) -> Result<Self, TimeError> {
+        let date = OffsetDateTime::parse(s, &Rfc3339)
+            .map_err(|err| TimeError::ParseError(format!("invalid timestamp {}: {}", s, err)))?;
+        u64::try_from(date.unix_timestamp_nanos() / 1_000_000)
+            .map(MassaTime)
+            .map_err(|_| TimeError::ParseError(format!("timestamp {} is before 1970", s)))
+    }
+
+    /// Parses a duration as a number of milliseconds or as a sequence of amounts of
+    /// days (`d`), hours (`h`), minutes (`m`), seconds (`s`) and milliseconds (`ms`)
+    /// ```
+    /// # use massa_time::*;
+    /// assert_eq!(MassaTime::parse_duration("1500").unwrap(), MassaTime::from(1500));
+    /// assert_eq!(MassaTime::parse_duration("2h30m").unwrap(), MassaTime::from(9_000_000));
+    /// assert_eq!(MassaTime::parse_duration("1d 500ms").unwrap(), MassaTime::from(86_400_500));
+    /// assert!(MassaTime::parse_duration("").is_err());
+    /// assert!(MassaTime::parse_duration("2h30").is_err());
+    /// assert!(MassaTime::parse_duration("3w").is_err());
+    /// ```
+    pub fn parse_duration(s: &str) -> Result<Self, TimeError> {
+        let invalid = || TimeError::ParseError(format!("invalid duration {:?}", s));
+        if let Ok(millis) = u64::from_str(s) {
+            return Ok(MassaTime(millis));
+        }
+        let mut rest = s.trim();
+        if rest.is_empty() {
+            return Err(invalid());
+        }
+        let mut total: u64 = 0;
+        while !rest.is_empty() {
+            let digits = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .ok_or_else(invalid)?;
+            let amount = u64::from_str(&rest[..digits]).map_err(|_| invalid())?;
+            rest = &rest[digits..];
+            let unit_len = rest
+                .find(|c: char| !c.is_ascii_alphabetic())
+                .unwrap_or(rest.len());
+            let (_, unit_millis) = DURATION_UNITS
+                .iter()
+                .find(|(unit, _)| *unit == &rest[..unit_len])
+                .ok_or_else(invalid)?;
+            total = amount
+                .checked_mul(*unit_millis)
+                .and_then(|millis| total.checked_add(millis))
+                .ok_or(TimeError::TimeOverflowError)?;
+            rest = rest[unit_len..].trim_start();
+        }
+        Ok(MassaTime(total))
+    }
+
+    /// Formats the time as a duration accepted by `parse_duration`
+    /// ```
+    /// # use massa_time::*;
+    /// assert_eq!(MassaTime::from(9_000_000).to_duration_string(), "2h30m");
+    /// assert_eq!(MassaTime::from(86_400_500).to_duration_string(), "1d500ms");
+    /// assert_eq!(MassaTime::from(0).to_duration_string(), "0ms");
+    /// ```
+    pub fn to_duration_string(self) -> String {
+        if self.0 == 0 {
+            return "0ms".to_string();
+        }
+        let mut rest = self.0;
+        let mut res = String::new();
+        for (unit, unit_millis) in DURATION_UNITS {
+            if rest >= unit_millis {
+                res.push_str(&format!("{}{}", rest / unit_millis, unit));
+                rest %= unit_millis;
+            }
+        }
+        res
+    }
+
     /// ```
     /// # use massa_time::*;
     /// let massa_time = MassaTime::from(1000 * ( 8 * 24*60*60 + 1 * 60*60 + 3 * 60 + 6 ));
@@ -445,3 +638,160 @@ impl MassaTime {
         Ok((days, hours, mins, secs))
     }
 }
+
+/// Value of the shared atomic when there is no estimate
+const NO_CLOCK_OFFSET: i64 = i64::MIN;
+
+/// Estimate of the offset of the network clock relative to the local one, in milliseconds:
+/// positive when the clocks of the other nodes are ahead of ours.
+///
+/// The clones share the same estimate, so that it can be updated by the network and read by the modules
+/// timing their actions on the network clock.
+#[derive(Debug, Clone)]
+pub struct ClockOffset(Arc<AtomicI64>);
+
+impl Default for ClockOffset {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ClockOffset {
+    /// Creates a shared estimate, initially unknown
+    pub fn new() -> Self {
+        ClockOffset(Arc::new(AtomicI64::new(NO_CLOCK_OFFSET)))
+    }
+
+    /// Current estimate, `None` if unknown
+    pub fn get(&self) -> Option<i64> {
+        match self.0.load(Ordering::Relaxed) {
+            NO_CLOCK_OFFSET => None,
+            offset => Some(offset),
+        }
+    }
+
+    /// Sets the estimate to the median of the `offsets` measured with the other nodes, unknown if there is none
+    /// ```
+    /// # use massa_time::*;
+    /// let clock_offset = ClockOffset::new();
+    /// clock_offset.set_median(vec![-20, 300, 15, 10]);
+    /// assert_eq!(clock_offset.get(), Some(12));
+    /// clock_offset.set_median(vec![]);
+    /// assert_eq!(clock_offset.get(), None);
+    /// ```
+    pub fn set_median(&self, offsets: impl IntoIterator<Item = i64>) {
+        let mut offsets: Vec<i64> = offsets.into_iter().collect();
+        offsets.sort_unstable();
+        let median = match offsets.len() {
+            0 => NO_CLOCK_OFFSET,
+            len if len % 2 == 1 => offsets[len / 2].max(NO_CLOCK_OFFSET + 1),
+            len => (((offsets[len / 2 - 1] as i128 + offsets[len / 2] as i128) / 2) as i64)
+                .max(NO_CLOCK_OFFSET + 1),
+        };
+        self.0.store(median, Ordering::Relaxed);
+    }
+
+    /// Estimate bounded to `max` in absolute value, 0 if unknown
+    /// ```
+    /// # use massa_time::*;
+    /// let clock_offset = ClockOffset::new();
+    /// assert_eq!(clock_offset.get_bounded(MassaTime::from(100)), 0);
+    /// clock_offset.set_median(vec![-250]);
+    /// assert_eq!(clock_offset.get_bounded(MassaTime::from(100)), -100);
+    /// ```
+    pub fn get_bounded(&self, max: MassaTime) -> i64 {
+        let max = i64::try_from(max.to_millis()).unwrap_or(i64::MAX);
+        self.get().map_or(0, |offset| offset.clamp(-max, max))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::de::value::{Error, I64Deserializer, StrDeserializer, U64Deserializer};
+    use serde::de::IntoDeserializer;
+
+    /// Number of milliseconds in a format that is not self-describing, like bincode
+    struct CompactDeserializer(u64);
+
+    impl<'de> de::Deserializer<'de> for CompactDeserializer {
+        type Error = Error;
+
+        fn deserialize_any<V: de::Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Error> {
+            Err(de::Error::custom("the type of the value is unknown"))
+        }
+
+        fn deserialize_u64<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            visitor.visit_u64(self.0)
+        }
+
+        fn deserialize_newtype_struct<V: de::Visitor<'de>>(
+            self,
+            _name: &'static str,
+            visitor: V,
+        ) -> Result<V::Value, Error> {
+            visitor.visit_newtype_struct(self)
+        }
+
+        fn is_human_readable(&self) -> bool {
+            false
+        }
+
+        serde::forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u128 f32 f64 char str string bytes byte_buf
+            option unit unit_struct seq tuple tuple_struct map struct enum identifier ignored_any
+        }
+    }
+
+    fn from_str(value: &str) -> Result<MassaTime, Error> {
+        let deserializer: StrDeserializer<Error> = value.into_deserializer();
+        MassaTime::deserialize(deserializer)
+    }
+
+    #[test]
+    fn test_deserialize_integer() {
+        let deserializer: U64Deserializer<Error> = 42u64.into_deserializer();
+        assert_eq!(MassaTime::deserialize(deserializer).unwrap(), MassaTime(42));
+        let deserializer: I64Deserializer<Error> = 42i64.into_deserializer();
+        assert_eq!(MassaTime::deserialize(deserializer).unwrap(), MassaTime(42));
+        assert_eq!(from_str("42").unwrap(), MassaTime(42));
+    }
+
+    #[test]
+    fn test_deserialize_duration_string() {
+        assert_eq!(from_str("2h30m").unwrap(), MassaTime(9_000_000));
+        assert_eq!(from_str(" 1d 500ms ").unwrap(), MassaTime(86_400_500));
+    }
+
+    #[test]
+    fn test_deserialize_rfc3339() {
+        assert_eq!(
+            from_str("2022-01-01T00:00:00Z").unwrap(),
+            MassaTime(1_640_995_200_000)
+        );
+        assert_eq!(
+            from_str("2022-01-01T02:00:00.042+02:00").unwrap(),
+            MassaTime(1_640_995_200_042)
+        );
+    }
+
+    #[test]
+    fn test_deserialize_invalid() {
+        let deserializer: I64Deserializer<Error> = (-1i64).into_deserializer();
+        assert!(MassaTime::deserialize(deserializer).is_err());
+        assert!(from_str("-1").is_err());
+        assert!(from_str("").is_err());
+        assert!(from_str("2h30").is_err());
+        assert!(from_str("soon").is_err());
+        assert!(from_str("1969-12-31T23:59:59Z").is_err());
+        assert!(from_str("2022-13-01T00:00:00Z").is_err());
+    }
+
+    #[test]
+    fn test_deserialize_not_human_readable() {
+        assert_eq!(
+            MassaTime::deserialize(CompactDeserializer(42)).unwrap(),
+            MassaTime(42)
+        );
+    }
+}
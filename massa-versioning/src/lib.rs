@@ -0,0 +1,26 @@
+//! Copyright (c) 2022 MASSA LABS <info@massa.net>
+//!
+//! # General description
+//!
+//! Coordination of the protocol upgrades, following MIPs (Massa Improvement Proposals).
+//!
+//! A MIP gathers new behaviors of some components of the node under a network version.
+//! The blocks announce in their header the latest network version their producer is ready for.
+//! Each MIP goes through the following states, updated with every final block:
+//! * `Defined`: the MIP is known but its vote has not started yet
+//! * `Started`: the vote is open until the timeout of the MIP
+//! * `LockedIn`: enough of the latest final blocks announced the version before the timeout:
+//!   the MIP activates after its activation delay, giving time to the remaining nodes to upgrade
+//! * `Active`: the new behaviors are enabled from the activation time on
+//! * `Failed`: the timeout was reached without enough announcements
+//!
+//! The components gate their new behaviors on the version returned by `MipStore::component_version_at`
+//! for the timestamp of the slot they process.
+#![warn(missing_docs)]
+#![warn(unused_crate_dependencies)]
+
+mod mips;
+mod store;
+
+pub use mips::*;
+pub use store::*;
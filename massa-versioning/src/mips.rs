@@ -0,0 +1,228 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use massa_serialization::{
+    Deserializer, SerializeError, Serializer, U32VarIntDeserializer, U32VarIntSerializer,
+};
+use massa_time::{MassaTime, MassaTimeDeserializer, MassaTimeSerializer};
+use nom::error::{context, ContextError, ParseError};
+use nom::IResult;
+use serde::{Deserialize, Serialize};
+use std::ops::Bound::Included;
+
+/// Component of the node whose behavior can be changed by a MIP
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MipComponent {
+    /// block validity rules checked by the consensus
+    Consensus,
+    /// execution of the operations and smart contracts
+    Execution,
+}
+
+/// Description of a MIP, identical on all the nodes supporting it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MipInfo {
+    /// name of the MIP
+    pub name: String,
+    /// network version announced by the nodes ready for the MIP, above the versions of the previous MIPs
+    pub version: u32,
+    /// components whose behavior changes once the MIP is active
+    pub components: Vec<MipComponent>,
+    /// start of the vote
+    pub start: MassaTime,
+    /// end of the vote, the MIP fails if it is not locked in before
+    pub timeout: MassaTime,
+    /// delay between the lock in and the activation of the MIP
+    pub activation_delay: MassaTime,
+}
+
+/// State of a MIP
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MipState {
+    /// the vote has not started yet
+    Defined,
+    /// the vote is open
+    Started,
+    /// enough blocks announced the version, the MIP is active from `activation` on
+    LockedIn {
+        /// activation time of the MIP
+        activation: MassaTime,
+    },
+    /// the MIP is active since `activation`
+    Active {
+        /// activation time of the MIP
+        activation: MassaTime,
+    },
+    /// the timeout was reached before the lock in
+    Failed,
+}
+
+impl MipState {
+    /// Activation time of the MIP, if it is locked in
+    pub fn activation(&self) -> Option<MassaTime> {
+        match self {
+            MipState::LockedIn { activation } | MipState::Active { activation } => {
+                Some(*activation)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for MipState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MipState::Defined => write!(f, "defined"),
+            MipState::Started => write!(f, "started"),
+            MipState::LockedIn { activation } => {
+                write!(f, "locked in, active from {}", activation.to_utc_string())
+            }
+            MipState::Active { activation } => {
+                write!(f, "active since {}", activation.to_utc_string())
+            }
+            MipState::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+/// Status of a MIP, reported by the API
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MipStatus {
+    /// description of the MIP
+    pub info: MipInfo,
+    /// current state of the MIP
+    pub state: MipState,
+    /// number of the latest final blocks announcing the version of the MIP or a later one
+    pub announcement_count: u64,
+    /// number of final blocks considered for the vote
+    pub block_count_considered: u64,
+}
+
+impl std::fmt::Display for MipStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "MIP {} (version {})", self.info.name, self.info.version)?;
+        writeln!(
+            f,
+            "\tComponents: {}",
+            self.info
+                .components
+                .iter()
+                .map(|component| format!("{:?}", component))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )?;
+        writeln!(
+            f,
+            "\tVote: from {} to {}, activation delay {}",
+            self.info.start.to_utc_string(),
+            self.info.timeout.to_utc_string(),
+            self.info.activation_delay
+        )?;
+        writeln!(f, "\tState: {}", self.state)?;
+        writeln!(
+            f,
+            "\tAnnouncements: {} of the latest {} final blocks",
+            self.announcement_count, self.block_count_considered
+        )
+    }
+}
+
+const MIP_STATE_DEFINED: u32 = 0;
+const MIP_STATE_STARTED: u32 = 1;
+const MIP_STATE_LOCKED_IN: u32 = 2;
+const MIP_STATE_ACTIVE: u32 = 3;
+const MIP_STATE_FAILED: u32 = 4;
+
+/// Serializer for `MipState`
+pub struct MipStateSerializer {
+    u32_serializer: U32VarIntSerializer,
+    time_serializer: MassaTimeSerializer,
+}
+
+impl MipStateSerializer {
+    /// Creates a `MipStateSerializer`
+    pub fn new() -> Self {
+        Self {
+            u32_serializer: U32VarIntSerializer::new(),
+            time_serializer: MassaTimeSerializer::new(),
+        }
+    }
+}
+
+impl Default for MipStateSerializer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Serializer<MipState> for MipStateSerializer {
+    fn serialize(&self, value: &MipState, buffer: &mut Vec<u8>) -> Result<(), SerializeError> {
+        match value {
+            MipState::Defined => self.u32_serializer.serialize(&MIP_STATE_DEFINED, buffer),
+            MipState::Started => self.u32_serializer.serialize(&MIP_STATE_STARTED, buffer),
+            MipState::LockedIn { activation } => {
+                self.u32_serializer
+                    .serialize(&MIP_STATE_LOCKED_IN, buffer)?;
+                self.time_serializer.serialize(activation, buffer)
+            }
+            MipState::Active { activation } => {
+                self.u32_serializer.serialize(&MIP_STATE_ACTIVE, buffer)?;
+                self.time_serializer.serialize(activation, buffer)
+            }
+            MipState::Failed => self.u32_serializer.serialize(&MIP_STATE_FAILED, buffer),
+        }
+    }
+}
+
+/// Deserializer for `MipState`
+pub struct MipStateDeserializer {
+    u32_deserializer: U32VarIntDeserializer,
+    time_deserializer: MassaTimeDeserializer,
+}
+
+impl MipStateDeserializer {
+    /// Creates a `MipStateDeserializer`
+    pub fn new() -> Self {
+        Self {
+            u32_deserializer: U32VarIntDeserializer::new(
+                Included(MIP_STATE_DEFINED),
+                Included(MIP_STATE_FAILED),
+            ),
+            time_deserializer: MassaTimeDeserializer::new((
+                Included(MassaTime::from_millis(0)),
+                Included(MassaTime::from_millis(u64::MAX)),
+            )),
+        }
+    }
+}
+
+impl Default for MipStateDeserializer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deserializer<MipState> for MipStateDeserializer {
+    fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], MipState, E> {
+        context("Failed MipState deserialization", |input| {
+            let (rest, id) = self.u32_deserializer.deserialize(input)?;
+            match id {
+                MIP_STATE_DEFINED => Ok((rest, MipState::Defined)),
+                MIP_STATE_STARTED => Ok((rest, MipState::Started)),
+                MIP_STATE_LOCKED_IN => self
+                    .time_deserializer
+                    .deserialize(rest)
+                    .map(|(rest, activation)| (rest, MipState::LockedIn { activation })),
+                MIP_STATE_ACTIVE => self
+                    .time_deserializer
+                    .deserialize(rest)
+                    .map(|(rest, activation)| (rest, MipState::Active { activation })),
+                _ => Ok((rest, MipState::Failed)),
+            }
+        })(buffer)
+    }
+}
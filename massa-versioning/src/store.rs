@@ -0,0 +1,494 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use crate::mips::{
+    MipComponent, MipInfo, MipState, MipStateDeserializer, MipStateSerializer, MipStatus,
+};
+use massa_models::serialization::{StringDeserializer, StringSerializer};
+use massa_models::slot::{Slot, SlotDeserializer, SlotSerializer};
+use massa_serialization::{
+    Deserializer, OptionDeserializer, OptionSerializer, SerializeError, Serializer,
+    U32VarIntDeserializer, U32VarIntSerializer, U64VarIntDeserializer, U64VarIntSerializer,
+};
+use massa_time::MassaTime;
+use nom::error::{context, ContextError, ParseError};
+use nom::multi::length_count;
+use nom::sequence::tuple;
+use nom::{IResult, Parser};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::ops::Bound::{Excluded, Included};
+use std::sync::Arc;
+use tracing::info;
+
+/// Maximum number of MIPs in a snapshot of the store
+pub const MAX_MIP_COUNT: u64 = 1024;
+
+/// Maximum length of the name of a MIP, in bytes
+pub const MAX_MIP_NAME_LENGTH: u32 = 255;
+
+/// Maximum number of votes in a snapshot of the store
+pub const MAX_MIP_VOTE_COUNT: u64 = 1_000_000;
+
+/// Configuration of the MIP store
+#[derive(Debug, Clone)]
+pub struct MipStoreConfig {
+    /// MIPs known by the node
+    pub mips: Vec<MipInfo>,
+    /// number of latest final blocks considered for the votes
+    pub block_count_considered: usize,
+    /// percentage of the considered blocks that must announce the version of a MIP to lock it in
+    pub activation_threshold_percent: u64,
+}
+
+/// Versioning state, updated with the final blocks
+#[derive(Debug)]
+pub struct MipStoreRaw {
+    config: MipStoreConfig,
+    /// state of each MIP of the configuration, by name
+    states: BTreeMap<String, MipState>,
+    /// announced versions of the latest final blocks, `front = oldest`
+    votes: VecDeque<u32>,
+    /// latest final slot taken into account
+    last_slot: Option<Slot>,
+}
+
+/// Shared versioning state of the node
+#[derive(Debug, Clone)]
+pub struct MipStore(pub Arc<RwLock<MipStoreRaw>>);
+
+impl MipStore {
+    /// Creates a store where all the MIPs of `config` are `Defined`
+    pub fn new(config: MipStoreConfig) -> Self {
+        let states = config
+            .mips
+            .iter()
+            .map(|mip| (mip.name.clone(), MipState::Defined))
+            .collect();
+        MipStore(Arc::new(RwLock::new(MipStoreRaw {
+            config,
+            states,
+            votes: VecDeque::new(),
+            last_slot: None,
+        })))
+    }
+
+    /// Takes into account the final block at `slot`, whose header announces `announced_version`.
+    /// `timestamp` is the timestamp of `slot`.
+    ///
+    /// Slots not after the latest final slot taken into account are ignored.
+    pub fn update_with_block_header(
+        &self,
+        slot: Slot,
+        announced_version: u32,
+        timestamp: MassaTime,
+    ) {
+        self.0
+            .write()
+            .update_with_block_header(slot, announced_version, timestamp)
+    }
+
+    /// Version of `component` at `timestamp`: the latest version of the MIPs changing `component`
+    /// that are active at `timestamp`, 0 if there is none.
+    pub fn component_version_at(&self, component: MipComponent, timestamp: MassaTime) -> u32 {
+        self.0.read().component_version_at(component, timestamp)
+    }
+
+    /// Version announced in the headers of the blocks produced by the node:
+    /// the latest version of the MIPs it knows that did not fail, 0 if there is none.
+    pub fn version_to_announce(&self) -> u32 {
+        self.0.read().version_to_announce()
+    }
+
+    /// Status of all the known MIPs, by increasing version
+    pub fn get_status(&self) -> Vec<MipStatus> {
+        self.0.read().get_status()
+    }
+
+    /// Snapshot of the store, sent to bootstrapping nodes and written in backups
+    pub fn snapshot(&self) -> MipStoreSnapshot {
+        self.0.read().snapshot()
+    }
+
+    /// Replaces the state of the store by `snapshot`.
+    ///
+    /// MIPs of the snapshot that are unknown to the node are ignored.
+    pub fn restore(&self, snapshot: MipStoreSnapshot) {
+        self.0.write().restore(snapshot)
+    }
+}
+
+impl MipStoreRaw {
+    fn update_with_block_header(
+        &mut self,
+        slot: Slot,
+        announced_version: u32,
+        timestamp: MassaTime,
+    ) {
+        if self.last_slot.map_or(false, |last_slot| slot <= last_slot) {
+            return;
+        }
+        self.last_slot = Some(slot);
+        self.votes.push_back(announced_version);
+        while self.votes.len() > self.config.block_count_considered {
+            self.votes.pop_front();
+        }
+
+        for mip in &self.config.mips {
+            let announcement_count = self.announcement_count(mip.version);
+            let state = self
+                .states
+                .entry(mip.name.clone())
+                .or_insert(MipState::Defined);
+            let mut new_state = *state;
+            if new_state == MipState::Defined && timestamp >= mip.start {
+                new_state = MipState::Started;
+            }
+            if new_state == MipState::Started {
+                if timestamp >= mip.timeout {
+                    new_state = MipState::Failed;
+                } else if announcement_count.saturating_mul(100)
+                    >= self
+                        .config
+                        .activation_threshold_percent
+                        .saturating_mul(self.config.block_count_considered as u64)
+                {
+                    new_state = MipState::LockedIn {
+                        activation: timestamp.saturating_add(mip.activation_delay),
+                    };
+                }
+            }
+            if let MipState::LockedIn { activation } = new_state {
+                if timestamp >= activation {
+                    new_state = MipState::Active { activation };
+                }
+            }
+            if new_state != *state {
+                info!(
+                    "MIP {} (version {}) at slot {}: {}",
+                    mip.name, mip.version, slot, new_state
+                );
+                *state = new_state;
+            }
+        }
+    }
+
+    /// number of considered blocks announcing `version` or a later one
+    fn announcement_count(&self, version: u32) -> u64 {
+        self.votes.iter().filter(|vote| **vote >= version).count() as u64
+    }
+
+    fn component_version_at(&self, component: MipComponent, timestamp: MassaTime) -> u32 {
+        self.config
+            .mips
+            .iter()
+            .filter(|mip| mip.components.contains(&component))
+            .filter(|mip| {
+                self.states
+                    .get(&mip.name)
+                    .and_then(MipState::activation)
+                    .map_or(false, |activation| activation <= timestamp)
+            })
+            .map(|mip| mip.version)
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn version_to_announce(&self) -> u32 {
+        self.config
+            .mips
+            .iter()
+            .filter(|mip| self.states.get(&mip.name) != Some(&MipState::Failed))
+            .map(|mip| mip.version)
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn get_status(&self) -> Vec<MipStatus> {
+        let mut status: Vec<MipStatus> = self
+            .config
+            .mips
+            .iter()
+            .map(|mip| MipStatus {
+                info: mip.clone(),
+                state: self
+                    .states
+                    .get(&mip.name)
+                    .copied()
+                    .unwrap_or(MipState::Defined),
+                announcement_count: self.announcement_count(mip.version),
+                block_count_considered: self.config.block_count_considered as u64,
+            })
+            .collect();
+        status.sort_by_key(|status| status.info.version);
+        status
+    }
+
+    fn snapshot(&self) -> MipStoreSnapshot {
+        MipStoreSnapshot {
+            last_slot: self.last_slot,
+            states: self.states.clone(),
+            votes: self.votes.iter().copied().collect(),
+        }
+    }
+
+    fn restore(&mut self, snapshot: MipStoreSnapshot) {
+        let mut states: BTreeMap<String, MipState> = self
+            .config
+            .mips
+            .iter()
+            .map(|mip| (mip.name.clone(), MipState::Defined))
+            .collect();
+        for (name, state) in snapshot.states {
+            if let Some(known_state) = states.get_mut(&name) {
+                *known_state = state;
+            }
+        }
+        self.states = states;
+        let skipped = snapshot
+            .votes
+            .len()
+            .saturating_sub(self.config.block_count_considered);
+        self.votes = snapshot.votes.into_iter().skip(skipped).collect();
+        self.last_slot = snapshot.last_slot;
+    }
+}
+
+/// Snapshot of the versioning state
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct MipStoreSnapshot {
+    /// latest final slot taken into account
+    pub last_slot: Option<Slot>,
+    /// state of the MIPs, by name
+    pub states: BTreeMap<String, MipState>,
+    /// announced versions of the latest final blocks, `front = oldest`
+    pub votes: Vec<u32>,
+}
+
+/// Serializer for `MipStoreSnapshot`
+pub struct MipStoreSnapshotSerializer {
+    slot_serializer: OptionSerializer<Slot, SlotSerializer>,
+    u64_serializer: U64VarIntSerializer,
+    name_serializer: StringSerializer<U32VarIntSerializer, u32>,
+    state_serializer: MipStateSerializer,
+    vote_serializer: U32VarIntSerializer,
+}
+
+impl MipStoreSnapshotSerializer {
+    /// Creates a `MipStoreSnapshotSerializer`
+    pub fn new() -> Self {
+        Self {
+            slot_serializer: OptionSerializer::new(SlotSerializer::new()),
+            u64_serializer: U64VarIntSerializer::new(),
+            name_serializer: StringSerializer::new(U32VarIntSerializer::new()),
+            state_serializer: MipStateSerializer::new(),
+            vote_serializer: U32VarIntSerializer::new(),
+        }
+    }
+}
+
+impl Default for MipStoreSnapshotSerializer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Serializer<MipStoreSnapshot> for MipStoreSnapshotSerializer {
+    fn serialize(
+        &self,
+        value: &MipStoreSnapshot,
+        buffer: &mut Vec<u8>,
+    ) -> Result<(), SerializeError> {
+        self.slot_serializer.serialize(&value.last_slot, buffer)?;
+        self.u64_serializer
+            .serialize(&(value.states.len() as u64), buffer)?;
+        for (name, state) in &value.states {
+            self.name_serializer.serialize(name, buffer)?;
+            self.state_serializer.serialize(state, buffer)?;
+        }
+        self.u64_serializer
+            .serialize(&(value.votes.len() as u64), buffer)?;
+        for vote in &value.votes {
+            self.vote_serializer.serialize(vote, buffer)?;
+        }
+        Ok(())
+    }
+}
+
+/// Deserializer for `MipStoreSnapshot`
+pub struct MipStoreSnapshotDeserializer {
+    slot_deserializer: OptionDeserializer<Slot, SlotDeserializer>,
+    state_count_deserializer: U64VarIntDeserializer,
+    name_deserializer: StringDeserializer<U32VarIntDeserializer, u32>,
+    state_deserializer: MipStateDeserializer,
+    vote_count_deserializer: U64VarIntDeserializer,
+    vote_deserializer: U32VarIntDeserializer,
+}
+
+impl MipStoreSnapshotDeserializer {
+    /// Creates a `MipStoreSnapshotDeserializer`
+    pub fn new(thread_count: u8) -> Self {
+        Self {
+            slot_deserializer: OptionDeserializer::new(SlotDeserializer::new(
+                (Included(0), Included(u64::MAX)),
+                (Included(0), Excluded(thread_count)),
+            )),
+            state_count_deserializer: U64VarIntDeserializer::new(
+                Included(0),
+                Included(MAX_MIP_COUNT),
+            ),
+            name_deserializer: StringDeserializer::new(U32VarIntDeserializer::new(
+                Included(0),
+                Included(MAX_MIP_NAME_LENGTH),
+            )),
+            state_deserializer: MipStateDeserializer::new(),
+            vote_count_deserializer: U64VarIntDeserializer::new(
+                Included(0),
+                Included(MAX_MIP_VOTE_COUNT),
+            ),
+            vote_deserializer: U32VarIntDeserializer::new(Included(0), Included(u32::MAX)),
+        }
+    }
+}
+
+impl Deserializer<MipStoreSnapshot> for MipStoreSnapshotDeserializer {
+    fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], MipStoreSnapshot, E> {
+        context(
+            "Failed MipStoreSnapshot deserialization",
+            tuple((
+                context("Failed last_slot deserialization", |input| {
+                    self.slot_deserializer.deserialize(input)
+                }),
+                context(
+                    "Failed states deserialization",
+                    length_count(
+                        |input| self.state_count_deserializer.deserialize(input),
+                        tuple((
+                            |input| self.name_deserializer.deserialize(input),
+                            |input| self.state_deserializer.deserialize(input),
+                        )),
+                    ),
+                ),
+                context(
+                    "Failed votes deserialization",
+                    length_count(
+                        |input| self.vote_count_deserializer.deserialize(input),
+                        |input| self.vote_deserializer.deserialize(input),
+                    ),
+                ),
+            )),
+        )
+        .map(|(last_slot, states, votes)| MipStoreSnapshot {
+            last_slot,
+            states: states.into_iter().collect(),
+            votes,
+        })
+        .parse(buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_serialization::DeserializeError;
+
+    fn test_store(activation_threshold_percent: u64) -> MipStore {
+        MipStore::new(MipStoreConfig {
+            mips: vec![MipInfo {
+                name: "MIP-0001".to_string(),
+                version: 1,
+                components: vec![MipComponent::Consensus],
+                start: MassaTime::from_millis(100),
+                timeout: MassaTime::from_millis(1000),
+                activation_delay: MassaTime::from_millis(50),
+            }],
+            block_count_considered: 4,
+            activation_threshold_percent,
+        })
+    }
+
+    #[test]
+    fn test_mip_lock_in_and_activation() {
+        let store = test_store(75);
+        assert_eq!(store.version_to_announce(), 1);
+
+        // the vote opens at the start of the MIP
+        store.update_with_block_header(Slot::new(1, 0), 1, MassaTime::from_millis(10));
+        assert_eq!(store.get_status()[0].state, MipState::Defined);
+        store.update_with_block_header(Slot::new(2, 0), 0, MassaTime::from_millis(100));
+        assert_eq!(store.get_status()[0].state, MipState::Started);
+        store.update_with_block_header(Slot::new(3, 0), 1, MassaTime::from_millis(200));
+        assert_eq!(store.get_status()[0].state, MipState::Started);
+
+        // already considered slots are ignored
+        store.update_with_block_header(Slot::new(3, 0), 1, MassaTime::from_millis(200));
+        assert_eq!(store.get_status()[0].announcement_count, 2);
+
+        // 3 of the latest 4 blocks announce the version
+        store.update_with_block_header(Slot::new(4, 0), 1, MassaTime::from_millis(300));
+        let activation = MassaTime::from_millis(350);
+        assert_eq!(
+            store.get_status()[0].state,
+            MipState::LockedIn { activation }
+        );
+        assert_eq!(
+            store.component_version_at(MipComponent::Consensus, MassaTime::from_millis(349)),
+            0
+        );
+        assert_eq!(
+            store.component_version_at(MipComponent::Consensus, activation),
+            1
+        );
+        assert_eq!(
+            store.component_version_at(MipComponent::Execution, activation),
+            0
+        );
+
+        store.update_with_block_header(Slot::new(5, 0), 0, MassaTime::from_millis(400));
+        assert_eq!(store.get_status()[0].state, MipState::Active { activation });
+    }
+
+    #[test]
+    fn test_mip_timeout() {
+        let store = test_store(100);
+        store.update_with_block_header(Slot::new(1, 0), 1, MassaTime::from_millis(500));
+        assert_eq!(store.get_status()[0].state, MipState::Started);
+        store.update_with_block_header(Slot::new(2, 0), 1, MassaTime::from_millis(1000));
+        assert_eq!(store.get_status()[0].state, MipState::Failed);
+        assert_eq!(store.version_to_announce(), 0);
+        assert_eq!(
+            store.component_version_at(MipComponent::Consensus, MassaTime::from_millis(2000)),
+            0
+        );
+    }
+
+    #[test]
+    fn test_mip_store_snapshot() {
+        let store = test_store(75);
+        for period in 1..=4 {
+            store.update_with_block_header(Slot::new(period, 1), 1, MassaTime::from_millis(200));
+        }
+        let snapshot = store.snapshot();
+        assert!(matches!(
+            snapshot.states.get("MIP-0001"),
+            Some(MipState::LockedIn { .. })
+        ));
+
+        let mut serialized = Vec::new();
+        MipStoreSnapshotSerializer::new()
+            .serialize(&snapshot, &mut serialized)
+            .unwrap();
+        let (rest, deserialized) = MipStoreSnapshotDeserializer::new(2)
+            .deserialize::<DeserializeError>(&serialized)
+            .unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(deserialized, snapshot);
+
+        let restored = test_store(75);
+        restored.restore(deserialized);
+        assert_eq!(restored.get_status(), store.get_status());
+    }
+}
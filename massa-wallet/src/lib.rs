@@ -29,6 +29,10 @@ pub struct Wallet {
     pub wallet_path: PathBuf,
     /// Password
     pub password: String,
+    /// Addresses whose keys are kept but do not produce blocks or endorsements.
+    /// Not saved: all the keys are enabled when the wallet is loaded.
+    #[serde(skip)]
+    pub disabled_addresses: PreHashSet<Address>,
 }
 
 impl Wallet {
@@ -43,12 +47,14 @@ impl Wallet {
                 keys,
                 wallet_path: path,
                 password,
+                disabled_addresses: PreHashSet::default(),
             })
         } else {
             let wallet = Wallet {
                 keys: PreHashMap::default(),
                 wallet_path: path,
                 password,
+                disabled_addresses: PreHashSet::default(),
             };
             wallet.save()?;
             Ok(wallet)
@@ -99,6 +105,7 @@ impl Wallet {
             if self.keys.remove(address).is_some() {
                 changed = true;
             }
+            self.disabled_addresses.remove(address);
         }
         if changed {
             self.save()?;
@@ -123,6 +130,43 @@ impl Wallet {
         self.keys.keys().copied().collect()
     }
 
+    /// Enables or disables the staking of a list of addresses, without removing their keys.
+    /// Fails without changing anything if one of the addresses is not in the wallet.
+    pub fn set_staking_enabled(
+        &mut self,
+        addresses: &[Address],
+        enabled: bool,
+    ) -> Result<(), WalletError> {
+        if let Some(address) = addresses.iter().find(|addr| !self.keys.contains_key(addr)) {
+            return Err(WalletError::MissingKeyError(*address));
+        }
+        for address in addresses {
+            if enabled {
+                self.disabled_addresses.remove(address);
+            } else {
+                self.disabled_addresses.insert(*address);
+            }
+        }
+        Ok(())
+    }
+
+    /// Finds the keypair associated with given address if its staking is enabled
+    pub fn find_staking_keypair(&self, address: &Address) -> Option<&KeyPair> {
+        if self.disabled_addresses.contains(address) {
+            return None;
+        }
+        self.keys.get(address)
+    }
+
+    /// Get the addresses of the wallet whose staking is enabled
+    pub fn get_staking_address_list(&self) -> PreHashSet<Address> {
+        self.keys
+            .keys()
+            .filter(|addr| !self.disabled_addresses.contains(addr))
+            .copied()
+            .collect()
+    }
+
     /// Save the wallet in json format in a file
     /// Only the keypair is dumped
     fn save(&self) -> Result<(), WalletError> {